@@ -0,0 +1,261 @@
+//! Atari 2600 public interface and main loop
+
+use crate::audio::AudioResampler;
+use crate::bus::Bus;
+use crate::cartridge::Cartridge;
+use crate::input::Atari2600Inputs;
+use crate::riot::{ConsoleSwitches, Riot};
+use crate::tia;
+use crate::tia::Tia;
+use bincode::{Decode, Encode};
+use jgenesis_common::audio::ResamplerQuality;
+use jgenesis_common::frontend::{
+    AudioOutput, Color, EmulatorTrait, PixelAspectRatio, Renderer, SaveWriter, TickEffect,
+    TickResult, TimingMode,
+};
+use jgenesis_common::rng::{InitialRamState, Rng};
+use jgenesis_common::state::StateFormat;
+use jgenesis_proc_macros::{EnumDisplay, EnumFromStr, PartialClone};
+use mos6502_emu::{Mos6502, Variant};
+use std::fmt::{Debug, Display};
+use thiserror::Error;
+
+// The TIA runs at the NTSC color subcarrier frequency, and the CPU runs at 1/3 of that
+pub const NTSC_COLOR_CLOCK_FREQUENCY: f64 = 3_579_545.454_545_454_5;
+
+#[derive(Debug, Error)]
+pub enum Atari2600LoadError {
+    #[error("Unsupported ROM size: {0} bytes")]
+    UnsupportedRomSize(usize),
+}
+
+#[derive(Debug, Error)]
+pub enum Atari2600Error<RErr, AErr> {
+    #[error("Error rendering a frame: {0}")]
+    Rendering(RErr),
+    #[error("Error outputting audio samples: {0}")]
+    Audio(AErr),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode, EnumDisplay, EnumFromStr)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Atari2600AspectRatio {
+    #[default]
+    Ntsc,
+    SquarePixels,
+    Stretched,
+}
+
+impl Atari2600AspectRatio {
+    fn to_pixel_aspect_ratio(self) -> Option<PixelAspectRatio> {
+        match self {
+            // TIA pixels are 1 color clock (~3.58 MHz) wide
+            Self::Ntsc => Some(PixelAspectRatio::try_from(12.0 / 7.0).unwrap()),
+            Self::SquarePixels => Some(PixelAspectRatio::SQUARE),
+            Self::Stretched => None,
+        }
+    }
+}
+
+/// Position of a difficulty switch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode, EnumDisplay, EnumFromStr)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Atari2600Difficulty {
+    /// B position
+    #[default]
+    Novice,
+    /// A position
+    Pro,
+}
+
+impl Atari2600Difficulty {
+    fn is_a(self) -> bool {
+        self == Self::Pro
+    }
+}
+
+#[derive(Debug, Clone, Copy, Encode, Decode)]
+pub struct Atari2600EmulatorConfig {
+    pub aspect_ratio: Atari2600AspectRatio,
+    pub left_difficulty: Atari2600Difficulty,
+    pub right_difficulty: Atari2600Difficulty,
+    /// If true, set the TV Type switch to black and white instead of color
+    pub black_and_white: bool,
+    pub audio_resampler_quality: ResamplerQuality,
+    /// Initial contents of RIOT RAM; if None, RAM is zeroed
+    pub initial_ram_state: Option<InitialRamState>,
+    /// Seed for all in-core randomness, including random initial RAM contents; if None, a seed is
+    /// chosen randomly at power on
+    pub rng_seed: Option<u64>,
+}
+
+impl Atari2600EmulatorConfig {
+    fn console_switches(&self) -> ConsoleSwitches {
+        ConsoleSwitches {
+            color: !self.black_and_white,
+            left_difficulty_a: self.left_difficulty.is_a(),
+            right_difficulty_a: self.right_difficulty.is_a(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Encode, Decode, PartialClone)]
+pub struct Atari2600Emulator {
+    cpu: Mos6502,
+    #[partial_clone(partial)]
+    cartridge: Cartridge,
+    tia: Tia,
+    riot: Riot,
+    data_bus: u8,
+    audio_resampler: AudioResampler,
+    config: Atari2600EmulatorConfig,
+}
+
+macro_rules! new_bus {
+    ($self:expr) => {
+        Bus {
+            cartridge: &mut $self.cartridge,
+            tia: &mut $self.tia,
+            riot: &mut $self.riot,
+            data_bus: &mut $self.data_bus,
+        }
+    };
+}
+
+impl Atari2600Emulator {
+    /// # Errors
+    ///
+    /// This function will return an error if the ROM size is not supported by any known bank
+    /// switching scheme.
+    pub fn create(
+        rom: Vec<u8>,
+        config: Atari2600EmulatorConfig,
+    ) -> Result<Self, Atari2600LoadError> {
+        let mut cartridge = Cartridge::create(rom)?;
+
+        let mut rng = Rng::from_optional_seed(config.rng_seed);
+        let initial_ram_state = config.initial_ram_state.unwrap_or(InitialRamState::AllZeroes);
+        let mut riot = Riot::new(initial_ram_state, &mut rng);
+        let mut tia = Tia::new();
+        let mut data_bus = 0;
+
+        let cpu = Mos6502::new(
+            &mut Bus {
+                cartridge: &mut cartridge,
+                tia: &mut tia,
+                riot: &mut riot,
+                data_bus: &mut data_bus,
+            },
+            Variant::Nmos6502,
+        );
+
+        Ok(Self {
+            cpu,
+            cartridge,
+            tia,
+            riot,
+            data_bus,
+            audio_resampler: AudioResampler::new(config.audio_resampler_quality),
+            config,
+        })
+    }
+
+    /// Copy the fixed 128-color NTSC palette into `out`, indexed by the upper 7 bits of a color
+    /// register value.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if `out` has fewer than 128 elements.
+    pub fn copy_palette(&self, out: &mut [Color]) {
+        out[..tia::palette::NTSC.len()].copy_from_slice(&tia::palette::NTSC);
+    }
+
+    fn render_frame<R: Renderer>(&self, renderer: &mut R) -> Result<(), R::Err> {
+        renderer.render_frame(
+            self.tia.frame_buffer(),
+            tia::FRAME_SIZE,
+            self.config.aspect_ratio.to_pixel_aspect_ratio(),
+        )
+    }
+}
+
+impl EmulatorTrait for Atari2600Emulator {
+    type Inputs = Atari2600Inputs;
+    type Config = Atari2600EmulatorConfig;
+    const STATE_FORMAT: StateFormat = StateFormat { core: "atari2600", version: 1 };
+    type Err<
+        RErr: Debug + Display + Send + Sync + 'static,
+        AErr: Debug + Display + Send + Sync + 'static,
+    > = Atari2600Error<RErr, AErr>;
+
+    fn tick<R, A>(
+        &mut self,
+        renderer: &mut R,
+        audio_output: &mut A,
+        inputs: &Self::Inputs,
+    ) -> TickResult<Self::Err<R::Err, A::Err>>
+    where
+        R: Renderer,
+        R::Err: Debug + Display + Send + Sync + 'static,
+        A: AudioOutput,
+        A::Err: Debug + Display + Send + Sync + 'static,
+    {
+        self.riot.set_inputs(*inputs, self.config.console_switches());
+        self.tia.set_fire_buttons(inputs.p1.fire, inputs.p2.fire);
+
+        self.cpu.tick(&mut new_bus!(self));
+        self.riot.tick();
+        self.tia.tick_cpu_cycle(&mut self.audio_resampler);
+
+        if self.tia.frame_complete() {
+            self.tia.clear_frame_complete();
+
+            self.render_frame(renderer).map_err(Atari2600Error::Rendering)?;
+            self.audio_resampler.output_samples(audio_output).map_err(Atari2600Error::Audio)?;
+
+            Ok(TickEffect::FrameRendered)
+        } else {
+            Ok(TickEffect::None)
+        }
+    }
+
+    fn save_dirty(&self) -> bool {
+        false
+    }
+
+    fn persist_save<S: SaveWriter>(&mut self, _save_writer: &mut S) -> Result<(), S::Err> {
+        Ok(())
+    }
+
+    fn force_render<R>(&mut self, renderer: &mut R) -> Result<(), R::Err>
+    where
+        R: Renderer,
+    {
+        self.render_frame(renderer)
+    }
+
+    fn reload_config(&mut self, config: &Self::Config) {
+        self.config = *config;
+        self.audio_resampler.set_quality(config.audio_resampler_quality);
+    }
+
+    fn take_rom_from(&mut self, other: &mut Self) {
+        self.cartridge.take_rom_from(&mut other.cartridge);
+    }
+
+    fn soft_reset(&mut self) {
+        // The console has no CPU reset line; the Game Reset switch is an input that games poll
+        log::warn!("The Atari 2600 does not support soft reset; use the Game Reset switch instead");
+    }
+
+    fn hard_reset<S: SaveWriter>(&mut self, _save_writer: &mut S) {
+        let rom = self.cartridge.take_rom();
+
+        *self =
+            Self::create(rom, self.config).expect("Hard reset should never fail to load cartridge");
+    }
+
+    fn timing_mode(&self) -> TimingMode {
+        TimingMode::Ntsc
+    }
+}
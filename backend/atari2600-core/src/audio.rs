@@ -0,0 +1,73 @@
+//! Atari 2600 audio resampling code
+
+use bincode::{Decode, Encode};
+use jgenesis_common::audio::{ResamplerQuality, SignalResampler};
+use jgenesis_common::frontend::AudioOutput;
+
+// The TIA audio channels are clocked twice per scanline
+pub const TIA_SAMPLE_FREQUENCY: f64 = crate::api::NTSC_COLOR_CLOCK_FREQUENCY / 114.0;
+
+// The TIA sample rate is lower than the output rate, so samples are zero-padded to 4x before
+// filtering. This is the same filter as the SNES core, which has a similar source sample rate
+const LPF_COEFFICIENT_0: f64 = -0.001032167331725023;
+const LPF_COEFFICIENTS: [f64; 21] = [
+    -0.001032167331725023,
+    -0.00337362854293201,
+    -0.002300741105977643,
+    0.007438828683983638,
+    0.01718256624704002,
+    0.002040390827841266,
+    -0.04030652783842427,
+    -0.05506118523737572,
+    0.02814357569062969,
+    0.2004791993149999,
+    0.3467896892919401,
+    0.3467896892919402,
+    0.2004791993149999,
+    0.02814357569062969,
+    -0.05506118523737575,
+    -0.04030652783842429,
+    0.002040390827841267,
+    0.01718256624704001,
+    0.00743882868398364,
+    -0.002300741105977646,
+    -0.003373628542932013,
+];
+
+const HPF_CHARGE_FACTOR: f64 = 0.9946028448191855;
+
+type TiaResampler = SignalResampler<21, 3>;
+
+#[derive(Debug, Clone, Encode, Decode)]
+pub struct AudioResampler {
+    tia_resampler: TiaResampler,
+}
+
+impl AudioResampler {
+    pub fn new(quality: ResamplerQuality) -> Self {
+        let mut tia_resampler = TiaResampler::new(
+            TIA_SAMPLE_FREQUENCY,
+            LPF_COEFFICIENT_0,
+            LPF_COEFFICIENTS,
+            HPF_CHARGE_FACTOR,
+        );
+        tia_resampler.set_quality(quality);
+        Self { tia_resampler }
+    }
+
+    pub fn set_quality(&mut self, quality: ResamplerQuality) {
+        self.tia_resampler.set_quality(quality);
+    }
+
+    pub fn collect_sample(&mut self, sample: f64) {
+        self.tia_resampler.collect_sample(sample, sample);
+    }
+
+    pub fn output_samples<A: AudioOutput>(&mut self, audio_output: &mut A) -> Result<(), A::Err> {
+        while let Some((sample_l, sample_r)) = self.tia_resampler.output_buffer_pop_front() {
+            audio_output.push_sample(sample_l, sample_r)?;
+        }
+
+        Ok(())
+    }
+}
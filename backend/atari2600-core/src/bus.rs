@@ -0,0 +1,77 @@
+//! Atari 2600 CPU bus
+//!
+//! The 6507 only has 13 address lines, so the address space is 8KB and is mirrored 8 times across
+//! the 6502's 64KB address space. Address decoding only looks at a few lines:
+//! * A12 set: Cartridge
+//! * A12 clear, A7 clear: TIA
+//! * A12 clear, A7 set, A9 clear: RIOT RAM
+//! * A12 clear, A7 set, A9 set: RIOT I/O ports and timer
+
+use crate::cartridge::Cartridge;
+use crate::riot::Riot;
+use crate::tia::Tia;
+use jgenesis_common::num::GetBit;
+use mos6502_emu::bus::BusInterface;
+
+const ADDRESS_MASK: u16 = 0x1FFF;
+
+pub struct Bus<'a> {
+    pub cartridge: &'a mut Cartridge,
+    pub tia: &'a mut Tia,
+    pub riot: &'a mut Riot,
+    // Last value driven on the data bus, which is what the CPU sees in bits that the TIA does not
+    // drive
+    pub data_bus: &'a mut u8,
+}
+
+impl BusInterface for Bus<'_> {
+    fn read(&mut self, address: u16) -> u8 {
+        let address = address & ADDRESS_MASK;
+        let value = if address.bit(12) {
+            self.cartridge.read(address)
+        } else if !address.bit(7) {
+            (self.tia.read(address) & 0xC0) | (*self.data_bus & 0x3F)
+        } else if !address.bit(9) {
+            self.riot.read_ram(address)
+        } else {
+            self.riot.read_register(address)
+        };
+
+        *self.data_bus = value;
+        value
+    }
+
+    fn write(&mut self, address: u16, value: u8) {
+        let address = address & ADDRESS_MASK;
+        *self.data_bus = value;
+
+        self.cartridge.write(address, value);
+        if address.bit(12) {
+            return;
+        }
+
+        if !address.bit(7) {
+            self.tia.write(address, value);
+        } else if !address.bit(9) {
+            self.riot.write_ram(address, value);
+        } else {
+            self.riot.write_register(address, value);
+        }
+    }
+
+    // The 6507 has no NMI or IRQ pins
+    fn nmi(&self) -> bool {
+        false
+    }
+
+    fn acknowledge_nmi(&mut self) {}
+
+    fn irq(&self) -> bool {
+        false
+    }
+
+    // Writing to WSYNC pulls RDY low until the start of the next line
+    fn rdy(&self) -> bool {
+        !self.tia.wsync_pending()
+    }
+}
@@ -0,0 +1,282 @@
+//! Atari 2600 cartridge ROM mapping and bank switching
+//!
+//! Cartridges only have 4KB of address space ($1000-$1FFF), so anything larger uses bank switching.
+//! Most bank switching schemes are triggered by reading or writing "hotspot" addresses, and some
+//! cartridges also include 128 bytes of RAM (Superchip) which uses separate addresses for reads and
+//! writes because there is no R/W line on the cartridge port.
+
+use crate::api::Atari2600LoadError;
+use bincode::{Decode, Encode};
+use jgenesis_proc_macros::{FakeDecode, FakeEncode, PartialClone};
+use std::ops::Deref;
+
+const WINDOW_LEN: usize = 4 * 1024;
+
+const SUPERCHIP_RAM_LEN: usize = 128;
+
+// Instructions that access the Parker Brothers (E0) hotspots, e.g. STA $1FE0 and LDA $FFE9
+const E0_SIGNATURES: [[u8; 3]; 8] = [
+    [0x8D, 0xE0, 0x1F],
+    [0x8D, 0xE0, 0x5F],
+    [0x8D, 0xE9, 0xFF],
+    [0x0C, 0xE0, 0x1F],
+    [0xAD, 0xE0, 0x1F],
+    [0xAD, 0xE9, 0xFF],
+    [0xAD, 0xED, 0xFF],
+    [0xAD, 0xF3, 0xBF],
+];
+
+// STA $3F, which switches banks in Tigervision (3F) cartridges
+const TIGERVISION_SIGNATURE: [u8; 2] = [0x85, 0x3F];
+
+#[derive(Debug, Clone, Default, FakeEncode, FakeDecode)]
+struct Rom(Box<[u8]>);
+
+impl Deref for Rom {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+enum Mapper {
+    // 2KB or 4KB; 2KB ROMs are mirrored
+    Standard,
+    // F8 / F6 / F4: accessing one of a contiguous range of hotspots switches the entire 4KB window
+    Atari { bank: u8, first_hotspot: u16, bank_count: u8 },
+    // E0: three switchable 1KB slices at $1000-$1BFF, plus the last 1KB of ROM fixed at $1C00-$1FFF
+    ParkerBros { slices: [u8; 3] },
+    // 3F: one switchable 2KB bank at $1000-$17FF, plus the last 2KB of ROM fixed at $1800-$1FFF.
+    // Writing to $00-$3F (which is otherwise TIA address space) switches the bank
+    Tigervision { bank: u8 },
+}
+
+impl Mapper {
+    fn detect(rom: &[u8]) -> Result<Self, Atari2600LoadError> {
+        let is_tigervision = count_matches(rom, &TIGERVISION_SIGNATURE) >= 2;
+        let tigervision = Self::Tigervision { bank: 0 };
+
+        let mapper = match rom.len() {
+            2048 | 4096 => Self::Standard,
+            8192 => {
+                if E0_SIGNATURES.iter().any(|signature| count_matches(rom, signature) != 0) {
+                    Self::ParkerBros { slices: [4, 5, 6] }
+                } else if is_tigervision {
+                    tigervision
+                } else {
+                    new_atari_mapper(0x1FF8, 2)
+                }
+            }
+            16384 if !is_tigervision => new_atari_mapper(0x1FF6, 4),
+            32768 if !is_tigervision => new_atari_mapper(0x1FF4, 8),
+            len if len % 2048 == 0 && len <= 512 * 1024 && is_tigervision => tigervision,
+            len => return Err(Atari2600LoadError::UnsupportedRomSize(len)),
+        };
+
+        Ok(mapper)
+    }
+}
+
+fn new_atari_mapper(first_hotspot: u16, bank_count: u8) -> Mapper {
+    // Power-on bank is random on actual hardware; games generally include a reset vector in every
+    // bank, but the last bank is the safest choice
+    Mapper::Atari { bank: bank_count - 1, first_hotspot, bank_count }
+}
+
+fn count_matches(rom: &[u8], signature: &[u8]) -> usize {
+    rom.windows(signature.len()).filter(|&window| window == signature).count()
+}
+
+// Superchip cartridges have RAM at $1000-$10FF, so the ROM at those addresses is unused and
+// normally filled with a single repeated byte in every bank
+fn has_superchip(rom: &[u8]) -> bool {
+    rom.chunks_exact(WINDOW_LEN)
+        .all(|bank| bank[..2 * SUPERCHIP_RAM_LEN].iter().all(|&byte| byte == bank[0]))
+}
+
+#[derive(Debug, Clone, Encode, Decode, PartialClone)]
+pub struct Cartridge {
+    #[partial_clone(default)]
+    rom: Rom,
+    mapper: Mapper,
+    superchip_ram: Option<[u8; SUPERCHIP_RAM_LEN]>,
+}
+
+impl Cartridge {
+    pub fn create(rom: Vec<u8>) -> Result<Self, Atari2600LoadError> {
+        let mapper = Mapper::detect(&rom)?;
+
+        let superchip_ram = (matches!(mapper, Mapper::Atari { .. }) && has_superchip(&rom))
+            .then_some([0; SUPERCHIP_RAM_LEN]);
+
+        log::info!(
+            "Loaded cartridge ROM of size {} KB, using mapper {mapper:?} (Superchip RAM: {})",
+            rom.len() / 1024,
+            superchip_ram.is_some()
+        );
+
+        Ok(Self { rom: Rom(rom.into_boxed_slice()), mapper, superchip_ram })
+    }
+
+    fn map_address(&self, address: u16) -> usize {
+        let offset = usize::from(address & 0x0FFF);
+
+        match self.mapper {
+            Mapper::Standard => offset & (self.rom.len() - 1),
+            Mapper::Atari { bank, .. } => usize::from(bank) * WINDOW_LEN + offset,
+            Mapper::ParkerBros { slices } => {
+                let slice = slices.get(offset >> 10).copied().unwrap_or(7);
+                usize::from(slice) * 1024 + (offset & 0x3FF)
+            }
+            Mapper::Tigervision { bank } => {
+                if offset < 0x800 {
+                    (usize::from(bank) * 2048 + offset) % self.rom.len()
+                } else {
+                    self.rom.len() - 0x1000 + offset
+                }
+            }
+        }
+    }
+
+    // Bank switching hotspots respond to both reads and writes
+    fn check_hotspot(&mut self, address: u16) {
+        let address = address | 0x1000;
+
+        match &mut self.mapper {
+            Mapper::Standard | Mapper::Tigervision { .. } => {}
+            Mapper::Atari { bank, first_hotspot, bank_count } => {
+                if (*first_hotspot..*first_hotspot + u16::from(*bank_count)).contains(&address) {
+                    *bank = (address - *first_hotspot) as u8;
+                }
+            }
+            Mapper::ParkerBros { slices } => {
+                if (0x1FE0..0x1FF8).contains(&address) {
+                    let slice = ((address >> 3) & 3) as usize;
+                    slices[slice] = (address & 7) as u8;
+                }
+            }
+        }
+    }
+
+    // $1000-$1FFF
+    pub fn read(&mut self, address: u16) -> u8 {
+        let offset = usize::from(address & 0x0FFF);
+        let value = match &self.superchip_ram {
+            Some(ram) if (SUPERCHIP_RAM_LEN..2 * SUPERCHIP_RAM_LEN).contains(&offset) => {
+                ram[offset & (SUPERCHIP_RAM_LEN - 1)]
+            }
+            _ => self.rom[self.map_address(address)],
+        };
+
+        self.check_hotspot(address);
+
+        value
+    }
+
+    /// Called for every CPU write, not only writes to $1000-$1FFF, because Tigervision bank
+    /// switching is triggered by writes to TIA addresses.
+    pub fn write(&mut self, address: u16, value: u8) {
+        if address & 0x1000 == 0 {
+            if let Mapper::Tigervision { bank } = &mut self.mapper {
+                if address <= 0x003F {
+                    *bank = value;
+                }
+            }
+            return;
+        }
+
+        let offset = usize::from(address & 0x0FFF);
+        if let Some(ram) = &mut self.superchip_ram {
+            if offset < SUPERCHIP_RAM_LEN {
+                ram[offset] = value;
+            }
+        }
+
+        self.check_hotspot(address);
+    }
+
+    pub fn take_rom(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.rom.0).into_vec()
+    }
+
+    pub fn take_rom_from(&mut self, other: &mut Self) {
+        self.rom = std::mem::take(&mut other.rom);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numbered_banks(len: usize, bank_len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i / bank_len) as u8).collect()
+    }
+
+    #[test]
+    fn standard_mirroring() {
+        let mut cartridge = Cartridge::create(vec![0xEA; 2048]).unwrap();
+        assert_eq!(cartridge.mapper, Mapper::Standard);
+        assert_eq!(cartridge.read(0x1800), 0xEA);
+
+        assert!(Cartridge::create(vec![0xEA; 3000]).is_err());
+    }
+
+    #[test]
+    fn atari_f6_banking() {
+        let mut rom = numbered_banks(16 * 1024, WINDOW_LEN);
+        rom[0x10] = 0xFF;
+        let mut cartridge = Cartridge::create(rom).unwrap();
+        assert!(matches!(cartridge.mapper, Mapper::Atari { bank: 3, bank_count: 4, .. }));
+        assert!(cartridge.superchip_ram.is_none());
+
+        assert_eq!(cartridge.read(0x1000), 0x03);
+        cartridge.read(0x1FF7);
+        assert_eq!(cartridge.read(0x1000), 0x01);
+        cartridge.write(0x1FF6, 0);
+        assert_eq!(cartridge.read(0x1000), 0x00);
+
+        // Not a hotspot for F6
+        cartridge.read(0x1FFA);
+        assert_eq!(cartridge.read(0x1000), 0x00);
+    }
+
+    #[test]
+    fn superchip_ram() {
+        let mut cartridge = Cartridge::create(numbered_banks(8 * 1024, WINDOW_LEN)).unwrap();
+        assert!(cartridge.superchip_ram.is_some());
+
+        cartridge.write(0x1005, 0x42);
+        assert_eq!(cartridge.read(0x1085), 0x42);
+        assert_eq!(cartridge.read(0x1100), 0x01);
+    }
+
+    #[test]
+    fn parker_bros_banking() {
+        let mut rom = numbered_banks(8 * 1024, 1024);
+        rom[0x123..0x126].copy_from_slice(&[0x8D, 0xE0, 0x1F]);
+        let mut cartridge = Cartridge::create(rom).unwrap();
+        assert!(matches!(cartridge.mapper, Mapper::ParkerBros { .. }));
+
+        cartridge.read(0x1FE2);
+        cartridge.read(0x1FEB);
+        cartridge.write(0x1FF1, 0);
+        assert_eq!(cartridge.read(0x1000), 0x02);
+        assert_eq!(cartridge.read(0x1400), 0x03);
+        assert_eq!(cartridge.read(0x1800), 0x01);
+        assert_eq!(cartridge.read(0x1C00), 0x07);
+    }
+
+    #[test]
+    fn tigervision_banking() {
+        let mut rom = numbered_banks(8 * 1024, 2048);
+        rom[0x100..0x104].copy_from_slice(&[0x85, 0x3F, 0x85, 0x3F]);
+        let mut cartridge = Cartridge::create(rom).unwrap();
+        assert!(matches!(cartridge.mapper, Mapper::Tigervision { .. }));
+
+        cartridge.write(0x003F, 2);
+        assert_eq!(cartridge.read(0x1000), 0x02);
+        assert_eq!(cartridge.read(0x1800), 0x03);
+    }
+}
@@ -0,0 +1,39 @@
+//! Atari 2600 joystick and console switch input handling
+
+use bincode::{Decode, Encode};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode)]
+pub struct Atari2600JoystickState {
+    pub up: bool,
+    pub down: bool,
+    pub left: bool,
+    pub right: bool,
+    pub fire: bool,
+}
+
+impl Atari2600JoystickState {
+    // Active-low direction bits in the order that they appear in SWCHA: right, left, down, up
+    fn direction_bits(self) -> u8 {
+        (u8::from(!self.right) << 3)
+            | (u8::from(!self.left) << 2)
+            | (u8::from(!self.down) << 1)
+            | u8::from(!self.up)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode)]
+pub struct Atari2600Inputs {
+    pub p1: Atari2600JoystickState,
+    pub p2: Atari2600JoystickState,
+    /// Game Reset console switch
+    pub reset: bool,
+    /// Game Select console switch
+    pub select: bool,
+}
+
+impl Atari2600Inputs {
+    // SWCHA: player 1 directions in bits 4-7 and player 2 directions in bits 0-3
+    pub(crate) fn swcha(&self) -> u8 {
+        (self.p1.direction_bits() << 4) | self.p2.direction_bits()
+    }
+}
@@ -0,0 +1,17 @@
+//! Atari 2600 emulation core
+//!
+//! Only NTSC consoles and standard joysticks are emulated.
+
+pub mod api;
+mod audio;
+mod bus;
+mod cartridge;
+pub mod input;
+mod riot;
+mod tia;
+
+pub use api::{
+    Atari2600AspectRatio, Atari2600Difficulty, Atari2600Emulator, Atari2600EmulatorConfig,
+    Atari2600Error, Atari2600LoadError,
+};
+pub use input::{Atari2600Inputs, Atari2600JoystickState};
@@ -0,0 +1,219 @@
+//! MOS 6532 RIOT (RAM-I/O-Timer): 128 bytes of RAM, the interval timer, and the joystick direction
+//! and console switch ports
+
+use crate::input::Atari2600Inputs;
+use bincode::{Decode, Encode};
+use jgenesis_common::num::GetBit;
+use jgenesis_common::rng::{InitialRamState, Rng};
+
+const RAM_LEN: usize = 128;
+
+// TIM1T / TIM8T / TIM64T / TIM1024T
+const TIMER_INTERVALS: [u16; 4] = [1, 8, 64, 1024];
+
+#[derive(Debug, Clone, Copy, Default, Encode, Decode)]
+pub struct ConsoleSwitches {
+    pub color: bool,
+    // Difficulty switches; true is the A (pro) position and false is the B (novice) position
+    pub left_difficulty_a: bool,
+    pub right_difficulty_a: bool,
+}
+
+#[derive(Debug, Clone, Encode, Decode)]
+struct IntervalTimer {
+    counter: u8,
+    interval: u16,
+    prescaler: u16,
+    // Set when the counter underflows; while set, the counter decrements every cycle regardless of
+    // the programmed interval
+    underflow_flag: bool,
+}
+
+impl IntervalTimer {
+    fn new() -> Self {
+        // The timer starts running at power on with a random value; use the maximum interval
+        Self { counter: 0xFF, interval: 1024, prescaler: 1024, underflow_flag: false }
+    }
+
+    fn write(&mut self, interval: u16, value: u8) {
+        self.counter = value;
+        self.interval = interval;
+        // The first decrement happens on the cycle after the write
+        self.prescaler = 1;
+        self.underflow_flag = false;
+    }
+
+    fn read(&mut self) -> u8 {
+        self.underflow_flag = false;
+        self.counter
+    }
+
+    fn tick(&mut self) {
+        self.prescaler -= 1;
+        if self.prescaler != 0 {
+            return;
+        }
+
+        if self.counter == 0 {
+            self.underflow_flag = true;
+        }
+        self.counter = self.counter.wrapping_sub(1);
+        self.prescaler = if self.underflow_flag { 1 } else { self.interval };
+    }
+}
+
+#[derive(Debug, Clone, Encode, Decode)]
+pub struct Riot {
+    ram: [u8; RAM_LEN],
+    timer: IntervalTimer,
+    swcha_output: u8,
+    swcha_ddr: u8,
+    swchb_output: u8,
+    swchb_ddr: u8,
+    // PA7 edge detection interrupt flag; nothing is connected to PA7 except player 1's left
+    // direction, but the flag is still readable
+    pa7_flag: bool,
+    pa7_rising_edge: bool,
+    last_pa7: bool,
+    inputs: Atari2600Inputs,
+    switches: ConsoleSwitches,
+}
+
+impl Riot {
+    pub fn new(initial_ram_state: InitialRamState, rng: &mut Rng) -> Self {
+        let mut ram = [0; RAM_LEN];
+        initial_ram_state.fill(&mut ram, rng);
+
+        Self {
+            ram,
+            timer: IntervalTimer::new(),
+            swcha_output: 0,
+            swcha_ddr: 0,
+            swchb_output: 0,
+            swchb_ddr: 0,
+            pa7_flag: false,
+            pa7_rising_edge: false,
+            last_pa7: true,
+            inputs: Atari2600Inputs::default(),
+            switches: ConsoleSwitches::default(),
+        }
+    }
+
+    pub fn set_inputs(&mut self, inputs: Atari2600Inputs, switches: ConsoleSwitches) {
+        self.inputs = inputs;
+        self.switches = switches;
+    }
+
+    pub fn tick(&mut self) {
+        self.timer.tick();
+
+        let pa7 = self.read_swcha().bit(7);
+        if pa7 != self.last_pa7 && pa7 == self.pa7_rising_edge {
+            self.pa7_flag = true;
+        }
+        self.last_pa7 = pa7;
+    }
+
+    pub fn read_ram(&self, address: u16) -> u8 {
+        self.ram[(address as usize) & (RAM_LEN - 1)]
+    }
+
+    pub fn write_ram(&mut self, address: u16, value: u8) {
+        self.ram[(address as usize) & (RAM_LEN - 1)] = value;
+    }
+
+    fn read_swcha(&self) -> u8 {
+        (self.swcha_output & self.swcha_ddr) | (self.inputs.swcha() & !self.swcha_ddr)
+    }
+
+    // SWCHB: bit 0 is Game Reset (active low), bit 1 is Game Select (active low), bit 3 is the
+    // color / black-and-white switch, and bits 6-7 are the difficulty switches
+    fn read_swchb(&self) -> u8 {
+        let switches = u8::from(!self.inputs.reset)
+            | (u8::from(!self.inputs.select) << 1)
+            | (u8::from(self.switches.color) << 3)
+            | (u8::from(self.switches.left_difficulty_a) << 6)
+            | (u8::from(self.switches.right_difficulty_a) << 7);
+        (self.swchb_output & self.swchb_ddr) | (switches & !self.swchb_ddr)
+    }
+
+    // $0280-$029F: I/O ports and timer, mirrored throughout the address space wherever A7 and A9
+    // are both set
+    pub fn read_register(&mut self, address: u16) -> u8 {
+        if !address.bit(2) {
+            return match address & 3 {
+                0 => self.read_swcha(),
+                1 => self.swcha_ddr,
+                2 => self.read_swchb(),
+                3 => self.swchb_ddr,
+                _ => unreachable!("value & 3 is always <= 3"),
+            };
+        }
+
+        if !address.bit(0) {
+            // INTIM
+            self.timer.read()
+        } else {
+            // TIMINT; reading clears the PA7 flag
+            let value = (u8::from(self.timer.underflow_flag) << 7) | (u8::from(self.pa7_flag) << 6);
+            self.pa7_flag = false;
+            value
+        }
+    }
+
+    pub fn write_register(&mut self, address: u16, value: u8) {
+        if !address.bit(2) {
+            match address & 3 {
+                0 => self.swcha_output = value,
+                1 => self.swcha_ddr = value,
+                2 => self.swchb_output = value,
+                3 => self.swchb_ddr = value,
+                _ => unreachable!("value & 3 is always <= 3"),
+            }
+            return;
+        }
+
+        if address.bit(4) {
+            // TIM1T / TIM8T / TIM64T / TIM1024T; bit 3 is the timer interrupt enable, but the 6507
+            // has no IRQ line
+            self.timer.write(TIMER_INTERVALS[(address & 3) as usize], value);
+        } else {
+            // PA7 edge detect control; bit 0 selects rising or falling edge
+            self.pa7_rising_edge = address.bit(0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timer_intervals() {
+        let mut timer = IntervalTimer::new();
+        timer.write(8, 2);
+
+        timer.tick();
+        assert_eq!(timer.counter, 1);
+        for _ in 0..8 {
+            timer.tick();
+        }
+        assert_eq!(timer.counter, 0);
+        assert!(!timer.underflow_flag);
+
+        for _ in 0..8 {
+            timer.tick();
+        }
+        assert_eq!(timer.counter, 0xFF);
+        assert!(timer.underflow_flag);
+
+        // After underflow, the timer decrements every cycle until INTIM is read
+        timer.tick();
+        assert_eq!(timer.counter, 0xFE);
+        assert_eq!(timer.read(), 0xFE);
+        for _ in 0..9 {
+            timer.tick();
+        }
+        assert_eq!(timer.counter, 0xFC);
+    }
+}
@@ -0,0 +1,537 @@
+//! TIA (Television Interface Adaptor): video output, audio, and the joystick fire buttons
+//!
+//! The TIA has no frame buffer and no concept of vertical position; it outputs one pixel per color
+//! clock from its current register values, and the CPU is responsible for updating the registers
+//! in time with the beam and for generating VSYNC.
+
+mod audio;
+pub mod palette;
+
+use crate::audio::AudioResampler;
+use crate::tia::audio::AudioChannel;
+use bincode::{Decode, Encode};
+use jgenesis_common::frontend::{Color, FrameSize};
+use jgenesis_common::num::GetBit;
+use jgenesis_proc_macros::{FakeDecode, FakeEncode};
+use std::ops::{Deref, DerefMut};
+
+pub const COLOR_CLOCKS_PER_LINE: u16 = 228;
+const HBLANK_LEN: u16 = 68;
+
+// HMOVE extends HBLANK by 8 color clocks on the line where it is strobed, which hides the leftmost
+// 8 pixels (the "HMOVE bar")
+const HMOVE_BLANK_LEN: u16 = 8;
+
+// Audio channels are clocked twice per line
+const AUDIO_CLOCK_INTERVAL: u16 = COLOR_CLOCKS_PER_LINE / 2;
+
+const SCREEN_WIDTH: u16 = 160;
+
+// NTSC games usually output 3 lines of VSYNC, 37 lines of VBLANK, 192 visible lines, and 30 lines of
+// overscan, but not all games follow this exactly. Display a few lines before and after the
+// standard 192-line visible area
+const FIRST_VISIBLE_LINE: u16 = 37;
+const FRAME_HEIGHT: u16 = 210;
+
+pub const FRAME_SIZE: FrameSize =
+    FrameSize { width: SCREEN_WIDTH as u32, height: FRAME_HEIGHT as u32 };
+
+const FRAME_BUFFER_LEN: usize = SCREEN_WIDTH as usize * FRAME_HEIGHT as usize;
+
+// Start a new frame if a game goes this many lines without starting VSYNC, e.g. during startup
+const MAX_LINES_PER_FRAME: u16 = 320;
+
+// Collision latch bits, in the order that they appear in the CXM0P-CXPPMM registers (bit 7 then
+// bit 6 of each register). Bit 6 of CXBLPF does not exist
+const COLLISION_LATCH_LEN: usize = 16;
+
+#[derive(Debug, Clone, FakeEncode, FakeDecode)]
+struct FrameBuffer(Box<[Color]>);
+
+impl Default for FrameBuffer {
+    fn default() -> Self {
+        Self(vec![Color::BLACK; FRAME_BUFFER_LEN].into_boxed_slice())
+    }
+}
+
+impl Deref for FrameBuffer {
+    type Target = [Color];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for FrameBuffer {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+// Horizontal distance from an object's position to the given pixel, wrapping around the line
+fn distance(position: u8, x: u8) -> u8 {
+    ((u16::from(x) + SCREEN_WIDTH - u16::from(position)) % SCREEN_WIDTH) as u8
+}
+
+// NUSIZ bits 0-2 select the number and spacing of player and missile copies, or the player size
+fn copy_starts(nusiz: u8) -> &'static [u8] {
+    match nusiz & 7 {
+        0 | 5 | 7 => &[0],
+        1 => &[0, 16],
+        2 => &[0, 32],
+        3 => &[0, 16, 32],
+        4 => &[0, 64],
+        6 => &[0, 32, 64],
+        _ => unreachable!("value & 7 is always <= 7"),
+    }
+}
+
+// If the pixel is inside one of an object's copies, returns the offset into that copy
+fn copy_offset(nusiz: u8, distance: u8, width: u8) -> Option<u8> {
+    copy_starts(nusiz).iter().find_map(|&start| {
+        (distance >= start && distance - start < width).then(|| distance - start)
+    })
+}
+
+fn player_scale(nusiz: u8) -> u8 {
+    match nusiz & 7 {
+        5 => 2,
+        7 => 4,
+        _ => 1,
+    }
+}
+
+// HMxx registers hold a signed 4-bit motion value in the high nibble; positive values move left
+fn apply_motion(position: &mut u8, motion: i8) {
+    *position = (i16::from(*position) - i16::from(motion)).rem_euclid(SCREEN_WIDTH as i16) as u8;
+}
+
+#[derive(Debug, Clone, Copy, Default, Encode, Decode)]
+struct Player {
+    position: u8,
+    motion: i8,
+    nusiz: u8,
+    graphics: u8,
+    // Copy of the graphics register, updated when the other player's graphics are written;
+    // displayed instead of the current value when vertical delay is enabled
+    delayed_graphics: u8,
+    reflect: bool,
+    vertical_delay: bool,
+}
+
+impl Player {
+    fn pixel(self, x: u8) -> bool {
+        let graphics = if self.vertical_delay { self.delayed_graphics } else { self.graphics };
+        if graphics == 0 {
+            return false;
+        }
+
+        let scale = player_scale(self.nusiz);
+        let Some(offset) = copy_offset(self.nusiz, distance(self.position, x), 8 * scale) else {
+            return false;
+        };
+
+        let bit = offset / scale;
+        graphics.bit(if self.reflect { bit } else { 7 - bit })
+    }
+
+    // Position that missiles are moved to when locked to the player with RESMPx
+    fn center(self) -> u8 {
+        let offset = match player_scale(self.nusiz) {
+            1 => 3,
+            2 => 6,
+            _ => 10,
+        };
+        ((u16::from(self.position) + offset) % SCREEN_WIDTH) as u8
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Encode, Decode)]
+struct Missile {
+    position: u8,
+    motion: i8,
+    enabled: bool,
+    // RESMPx; while set, the missile is hidden and follows the player's position
+    locked_to_player: bool,
+}
+
+impl Missile {
+    fn pixel(self, x: u8, nusiz: u8) -> bool {
+        if !self.enabled || self.locked_to_player {
+            return false;
+        }
+
+        let width = 1 << ((nusiz >> 4) & 3);
+        copy_offset(nusiz, distance(self.position, x), width).is_some()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Encode, Decode)]
+struct Ball {
+    position: u8,
+    motion: i8,
+    width: u8,
+    enabled: bool,
+    // Copy of the enable bit, updated when player 1's graphics are written
+    delayed_enabled: bool,
+    vertical_delay: bool,
+}
+
+impl Ball {
+    fn pixel(self, x: u8) -> bool {
+        let enabled = if self.vertical_delay { self.delayed_enabled } else { self.enabled };
+        enabled && distance(self.position, x) < self.width
+    }
+}
+
+#[derive(Debug, Clone, Encode, Decode)]
+pub struct Tia {
+    frame_buffer: FrameBuffer,
+    color_clock: u16,
+    line: u16,
+    frame_complete: bool,
+    vsync: bool,
+    vblank: bool,
+    wsync: bool,
+    hmove_blank: bool,
+    // COLUP0, COLUP1, COLUPF, COLUBK
+    colors: [u8; 4],
+    playfield: [u8; 3],
+    playfield_reflect: bool,
+    score_mode: bool,
+    playfield_priority: bool,
+    players: [Player; 2],
+    missiles: [Missile; 2],
+    ball: Ball,
+    collisions: u16,
+    fire_buttons: [bool; 2],
+    input_latch_enabled: bool,
+    latched_fire_buttons: [bool; 2],
+    audio_channels: [AudioChannel; 2],
+}
+
+impl Tia {
+    pub fn new() -> Self {
+        Self {
+            frame_buffer: FrameBuffer::default(),
+            color_clock: 0,
+            line: 0,
+            frame_complete: false,
+            vsync: false,
+            vblank: false,
+            wsync: false,
+            hmove_blank: false,
+            colors: [0; 4],
+            playfield: [0; 3],
+            playfield_reflect: false,
+            score_mode: false,
+            playfield_priority: false,
+            players: [Player::default(); 2],
+            missiles: [Missile::default(); 2],
+            ball: Ball { width: 1, ..Ball::default() },
+            collisions: 0,
+            fire_buttons: [false; 2],
+            input_latch_enabled: false,
+            latched_fire_buttons: [false; 2],
+            audio_channels: [AudioChannel::new(), AudioChannel::new()],
+        }
+    }
+
+    pub fn frame_buffer(&self) -> &[Color] {
+        &self.frame_buffer
+    }
+
+    pub fn frame_complete(&self) -> bool {
+        self.frame_complete
+    }
+
+    pub fn clear_frame_complete(&mut self) {
+        self.frame_complete = false;
+    }
+
+    /// Whether the CPU is halted waiting for the start of the next line, i.e. whether RDY is low.
+    pub fn wsync_pending(&self) -> bool {
+        self.wsync
+    }
+
+    pub fn set_fire_buttons(&mut self, p1: bool, p2: bool) {
+        self.fire_buttons = [p1, p2];
+        if self.input_latch_enabled {
+            self.latched_fire_buttons[0] |= p1;
+            self.latched_fire_buttons[1] |= p2;
+        }
+    }
+
+    /// Advance the TIA by 1 CPU cycle (3 color clocks).
+    pub fn tick_cpu_cycle(&mut self, audio_resampler: &mut AudioResampler) {
+        for _ in 0..3 {
+            self.clock(audio_resampler);
+        }
+    }
+
+    fn clock(&mut self, audio_resampler: &mut AudioResampler) {
+        if self.color_clock == 0 || self.color_clock == AUDIO_CLOCK_INTERVAL {
+            let sample = self.audio_channels[0].clock() + self.audio_channels[1].clock();
+            audio_resampler.collect_sample(f64::from(sample) / 30.0);
+        }
+
+        if self.color_clock >= HBLANK_LEN {
+            self.output_pixel((self.color_clock - HBLANK_LEN) as u8);
+        }
+
+        self.color_clock += 1;
+        if self.color_clock == COLOR_CLOCKS_PER_LINE {
+            self.color_clock = 0;
+            self.wsync = false;
+            self.hmove_blank = false;
+
+            self.line += 1;
+            if self.line == MAX_LINES_PER_FRAME {
+                self.end_frame();
+            }
+        }
+    }
+
+    fn output_pixel(&mut self, x: u8) {
+        let blanked = self.vblank || (self.hmove_blank && u16::from(x) < HMOVE_BLANK_LEN);
+        let color = if blanked {
+            Color::BLACK
+        } else {
+            let color_register = self.render_pixel(x);
+            palette::NTSC[usize::from(color_register >> 1)]
+        };
+
+        if (FIRST_VISIBLE_LINE..FIRST_VISIBLE_LINE + FRAME_HEIGHT).contains(&self.line) {
+            let row = usize::from(self.line - FIRST_VISIBLE_LINE);
+            self.frame_buffer[row * usize::from(SCREEN_WIDTH) + usize::from(x)] = color;
+        }
+    }
+
+    // Returns the value of the color register for the highest priority object at this pixel, and
+    // updates the collision latches
+    fn render_pixel(&mut self, x: u8) -> u8 {
+        let p0 = self.players[0].pixel(x);
+        let p1 = self.players[1].pixel(x);
+        let m0 = self.missiles[0].pixel(x, self.players[0].nusiz);
+        let m1 = self.missiles[1].pixel(x, self.players[1].nusiz);
+        let bl = self.ball.pixel(x);
+        let pf = self.playfield_pixel(x);
+
+        let collisions: [bool; COLLISION_LATCH_LEN] = [
+            m0 && p1,
+            m0 && p0,
+            m1 && p0,
+            m1 && p1,
+            p0 && pf,
+            p0 && bl,
+            p1 && pf,
+            p1 && bl,
+            m0 && pf,
+            m0 && bl,
+            m1 && pf,
+            m1 && bl,
+            bl && pf,
+            false,
+            p0 && p1,
+            m0 && m1,
+        ];
+        for (i, collision) in collisions.into_iter().enumerate() {
+            self.collisions |= u16::from(collision) << i;
+        }
+
+        let [colup0, colup1, colupf, colubk] = self.colors;
+
+        // Score mode colors the left half of the playfield with player 0's color and the right half
+        // with player 1's color, but it has no effect if the playfield has priority over players
+        let pf_color = if self.score_mode && !self.playfield_priority {
+            if u16::from(x) < SCREEN_WIDTH / 2 { colup0 } else { colup1 }
+        } else {
+            colupf
+        };
+
+        if self.playfield_priority && (pf || bl) {
+            colupf
+        } else if p0 || m0 {
+            colup0
+        } else if p1 || m1 {
+            colup1
+        } else if pf {
+            pf_color
+        } else if bl {
+            colupf
+        } else {
+            colubk
+        }
+    }
+
+    // The playfield is 20 bits wide and each bit covers 4 pixels. PF0 bits 4-7 are displayed first,
+    // then PF1 bits 7-0, then PF2 bits 0-7. The right half of the screen repeats or reflects the
+    // left half
+    fn playfield_pixel(&self, x: u8) -> bool {
+        let mut i = x / 4;
+        if i >= 20 {
+            i -= 20;
+            if self.playfield_reflect {
+                i = 19 - i;
+            }
+        }
+
+        let [pf0, pf1, pf2] = self.playfield;
+        match i {
+            0..=3 => pf0.bit(4 + i),
+            4..=11 => pf1.bit(11 - i),
+            _ => pf2.bit(i - 12),
+        }
+    }
+
+    fn end_frame(&mut self) {
+        // Blank out any lines that the frame did not reach
+        let first_unrendered_line = self.line.max(FIRST_VISIBLE_LINE);
+        if first_unrendered_line < FIRST_VISIBLE_LINE + FRAME_HEIGHT {
+            let start =
+                usize::from(first_unrendered_line - FIRST_VISIBLE_LINE) * usize::from(SCREEN_WIDTH);
+            self.frame_buffer[start..].fill(Color::BLACK);
+        }
+
+        self.line = 0;
+        self.frame_complete = true;
+    }
+
+    // Position that RESP0 / RESP1 / RESM0 / RESM1 / RESBL move an object to. Objects reset during
+    // HBLANK appear at the left edge of the screen; otherwise there is a delay of several pixels
+    // between the write and the new position
+    fn reset_position(&self, delay: u16) -> u8 {
+        let hblank_end = HBLANK_LEN + if self.hmove_blank { HMOVE_BLANK_LEN } else { 0 };
+        if self.color_clock < hblank_end {
+            (delay - 2) as u8
+        } else {
+            ((self.color_clock - HBLANK_LEN + delay) % SCREEN_WIDTH) as u8
+        }
+    }
+
+    // $00-$0D: collision latches and input ports, mirrored throughout TIA address space. Only bits
+    // 6-7 are driven; the bus handles the undriven bits
+    pub fn read(&mut self, address: u16) -> u8 {
+        match address & 0x0F {
+            register @ 0x00..=0x07 => {
+                let latches = self.collisions >> (2 * register);
+                (u8::from(latches.bit(0)) << 7) | (u8::from(latches.bit(1)) << 6)
+            }
+            // INPT4 / INPT5: fire buttons, active low
+            0x0C | 0x0D => {
+                let i = (address & 1) as usize;
+                let pressed = self.fire_buttons[i] || self.latched_fire_buttons[i];
+                u8::from(!pressed) << 7
+            }
+            // INPT0-INPT3 are paddle inputs; paddles are not emulated, so the capacitors never
+            // charge
+            _ => 0x00,
+        }
+    }
+
+    // $00-$2C, mirrored throughout TIA address space
+    pub fn write(&mut self, address: u16, value: u8) {
+        let register = address & 0x3F;
+        match register {
+            0x00 => self.write_vsync(value),
+            0x01 => self.write_vblank(value),
+            0x02 => self.wsync = true,
+            0x03 => log::debug!("RSYNC write at color clock {}, ignoring", self.color_clock),
+            0x04 | 0x05 => self.players[(register & 1) as usize].nusiz = value,
+            0x06..=0x09 => self.colors[(register - 0x06) as usize] = value & 0xFE,
+            0x0A => {
+                self.playfield_reflect = value.bit(0);
+                self.score_mode = value.bit(1);
+                self.playfield_priority = value.bit(2);
+                self.ball.width = 1 << ((value >> 4) & 3);
+            }
+            0x0B | 0x0C => self.players[(register - 0x0B) as usize].reflect = value.bit(3),
+            0x0D..=0x0F => self.playfield[(register - 0x0D) as usize] = value,
+            0x10 | 0x11 => {
+                self.players[(register & 1) as usize].position = self.reset_position(5);
+            }
+            0x12 | 0x13 => {
+                self.missiles[(register - 0x12) as usize].position = self.reset_position(4);
+            }
+            0x14 => self.ball.position = self.reset_position(4),
+            0x15 | 0x16 => self.audio_channels[(register - 0x15) as usize].write_control(value),
+            0x17 | 0x18 => self.audio_channels[(register - 0x17) as usize].write_frequency(value),
+            0x19 | 0x1A => self.audio_channels[(register - 0x19) as usize].write_volume(value),
+            0x1B => {
+                self.players[0].graphics = value;
+                self.players[1].delayed_graphics = self.players[1].graphics;
+            }
+            0x1C => {
+                self.players[1].graphics = value;
+                self.players[0].delayed_graphics = self.players[0].graphics;
+                self.ball.delayed_enabled = self.ball.enabled;
+            }
+            0x1D | 0x1E => self.missiles[(register - 0x1D) as usize].enabled = value.bit(1),
+            0x1F => self.ball.enabled = value.bit(1),
+            0x20 | 0x21 => self.players[(register & 1) as usize].motion = (value as i8) >> 4,
+            0x22 | 0x23 => self.missiles[(register & 1) as usize].motion = (value as i8) >> 4,
+            0x24 => self.ball.motion = (value as i8) >> 4,
+            0x25 | 0x26 => {
+                self.players[(register - 0x25) as usize].vertical_delay = value.bit(0);
+            }
+            0x27 => self.ball.vertical_delay = value.bit(0),
+            0x28 | 0x29 => self.write_resmp((register - 0x28) as usize, value),
+            0x2A => self.hmove(),
+            0x2B => self.hmclr(),
+            0x2C => self.collisions = 0,
+            _ => {}
+        }
+    }
+
+    fn write_vsync(&mut self, value: u8) {
+        let vsync = value.bit(1);
+        if vsync && !self.vsync {
+            self.end_frame();
+        }
+        self.vsync = vsync;
+    }
+
+    fn write_vblank(&mut self, value: u8) {
+        self.vblank = value.bit(1);
+
+        // Bit 6 enables latching the fire buttons, so that a press is remembered until the latch is
+        // disabled
+        self.input_latch_enabled = value.bit(6);
+        if !self.input_latch_enabled {
+            self.latched_fire_buttons = [false; 2];
+        }
+    }
+
+    fn write_resmp(&mut self, i: usize, value: u8) {
+        let locked = value.bit(1);
+        let missile = &mut self.missiles[i];
+        if locked || missile.locked_to_player {
+            missile.position = self.players[i].center();
+        }
+        missile.locked_to_player = locked;
+    }
+
+    fn hmove(&mut self) {
+        for player in &mut self.players {
+            apply_motion(&mut player.position, player.motion);
+        }
+        for missile in &mut self.missiles {
+            apply_motion(&mut missile.position, missile.motion);
+        }
+        apply_motion(&mut self.ball.position, self.ball.motion);
+
+        if self.color_clock < HBLANK_LEN {
+            self.hmove_blank = true;
+        }
+    }
+
+    fn hmclr(&mut self) {
+        for player in &mut self.players {
+            player.motion = 0;
+        }
+        for missile in &mut self.missiles {
+            missile.motion = 0;
+        }
+        self.ball.motion = 0;
+    }
+}
@@ -0,0 +1,150 @@
+//! TIA audio channels
+//!
+//! Each channel divides the ~31.4 KHz audio clock by AUDF+1 and uses the result to clock a waveform
+//! generator selected by AUDC, which is either a pure tone (square wave) or a pattern generated by
+//! one or two linear feedback shift registers.
+
+use bincode::{Decode, Encode};
+use jgenesis_common::num::GetBit;
+
+#[derive(Debug, Clone, Encode, Decode)]
+pub struct AudioChannel {
+    control: u8,
+    frequency: u8,
+    volume: u8,
+    frequency_counter: u8,
+    divider_counter: u8,
+    poly4: u8,
+    poly5: u8,
+    poly9: u16,
+    output: bool,
+}
+
+impl AudioChannel {
+    pub fn new() -> Self {
+        Self {
+            control: 0,
+            frequency: 0,
+            volume: 0,
+            frequency_counter: 0,
+            divider_counter: 0,
+            poly4: 0x0F,
+            poly5: 0x1F,
+            poly9: 0x1FF,
+            output: false,
+        }
+    }
+
+    // AUDC0 / AUDC1
+    pub fn write_control(&mut self, value: u8) {
+        self.control = value & 0x0F;
+    }
+
+    // AUDF0 / AUDF1
+    pub fn write_frequency(&mut self, value: u8) {
+        self.frequency = value & 0x1F;
+    }
+
+    // AUDV0 / AUDV1
+    pub fn write_volume(&mut self, value: u8) {
+        self.volume = value & 0x0F;
+    }
+
+    // Returns the current output level, 0-15
+    pub fn clock(&mut self) -> u8 {
+        if self.frequency_counter == self.frequency {
+            self.frequency_counter = 0;
+            self.clock_waveform();
+        } else {
+            self.frequency_counter += 1;
+        }
+
+        if self.output { self.volume } else { 0 }
+    }
+
+    fn clock_waveform(&mut self) {
+        let poly5_bit = self.step_poly5();
+
+        match self.control {
+            // Constant output; only the volume register matters
+            0x0 | 0xB => self.output = true,
+            // 4-bit poly
+            0x1 => self.output = self.step_poly4(),
+            // 4-bit poly clocked every 15 cycles
+            0x2 => {
+                if self.step_divider(15) {
+                    self.output = self.step_poly4();
+                }
+            }
+            // 4-bit poly clocked by the 5-bit poly
+            0x3 => {
+                if poly5_bit {
+                    self.output = self.step_poly4();
+                }
+            }
+            // Divide by 2 pure tone
+            0x4 | 0x5 => self.output = !self.output,
+            // Divide by 31 pure tone
+            0x6 | 0xA => self.output = self.divider_waveform(31),
+            // Divide by 2 clocked by the 5-bit poly
+            0x7 => {
+                if poly5_bit {
+                    self.output = !self.output;
+                }
+            }
+            // 9-bit poly
+            0x8 => self.output = self.step_poly9(),
+            // 5-bit poly
+            0x9 => self.output = poly5_bit,
+            // Divide by 6 pure tone
+            0xC | 0xD => self.output = self.divider_waveform(6),
+            // Divide by 93 pure tone
+            0xE => self.output = self.divider_waveform(93),
+            // Divide by 6 clocked by the 5-bit poly
+            0xF => {
+                if poly5_bit {
+                    self.output = self.divider_waveform(6);
+                }
+            }
+            _ => unreachable!("AUDC is masked to 4 bits"),
+        }
+    }
+
+    // Returns true when the divider wraps around
+    fn step_divider(&mut self, divisor: u8) -> bool {
+        self.divider_counter += 1;
+        if self.divider_counter >= divisor {
+            self.divider_counter = 0;
+            true
+        } else {
+            false
+        }
+    }
+
+    // Square wave with a period of `divisor` clocks
+    fn divider_waveform(&mut self, divisor: u8) -> bool {
+        self.step_divider(divisor);
+        self.divider_counter < divisor / 2
+    }
+
+    // x^4 + x^3 + 1, period 15
+    fn step_poly4(&mut self) -> bool {
+        let feedback = self.poly4.bit(0) ^ self.poly4.bit(1);
+        self.poly4 = (self.poly4 >> 1) | (u8::from(feedback) << 3);
+        self.poly4.bit(0)
+    }
+
+    // x^5 + x^3 + 1, period 31
+    fn step_poly5(&mut self) -> bool {
+        let feedback = self.poly5.bit(0) ^ self.poly5.bit(2);
+        self.poly5 = (self.poly5 >> 1) | (u8::from(feedback) << 4);
+        self.poly5.bit(0)
+    }
+
+    // x^9 + x^5 + 1, period 511
+    fn step_poly9(&mut self) -> bool {
+        let feedback = self.poly9.bit(0) ^ self.poly9.bit(4);
+        self.poly9 = (self.poly9 >> 1) | (u16::from(feedback) << 8);
+        self.poly9.bit(0)
+    }
+}
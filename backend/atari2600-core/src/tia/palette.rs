@@ -0,0 +1,136 @@
+//! NTSC TIA color palette
+
+use jgenesis_common::frontend::Color;
+
+// Indexed by the upper 7 bits of a color register: bits 4-7 are the hue and bits 1-3 are the
+// luminance. Values are the same as Stella's default NTSC palette
+pub const NTSC: [Color; 128] = [
+    Color::rgb(0x00, 0x00, 0x00),
+    Color::rgb(0x4A, 0x4A, 0x4A),
+    Color::rgb(0x6F, 0x6F, 0x6F),
+    Color::rgb(0x8E, 0x8E, 0x8E),
+    Color::rgb(0xAA, 0xAA, 0xAA),
+    Color::rgb(0xC0, 0xC0, 0xC0),
+    Color::rgb(0xD6, 0xD6, 0xD6),
+    Color::rgb(0xEC, 0xEC, 0xEC),
+    Color::rgb(0x48, 0x48, 0x00),
+    Color::rgb(0x69, 0x69, 0x0F),
+    Color::rgb(0x86, 0x86, 0x1D),
+    Color::rgb(0xA2, 0xA2, 0x2A),
+    Color::rgb(0xBB, 0xBB, 0x35),
+    Color::rgb(0xD2, 0xD2, 0x40),
+    Color::rgb(0xE8, 0xE8, 0x4A),
+    Color::rgb(0xFC, 0xFC, 0x54),
+    Color::rgb(0x7C, 0x2C, 0x00),
+    Color::rgb(0x90, 0x48, 0x11),
+    Color::rgb(0xA2, 0x62, 0x21),
+    Color::rgb(0xB4, 0x7A, 0x30),
+    Color::rgb(0xC3, 0x90, 0x3D),
+    Color::rgb(0xD2, 0xA4, 0x4A),
+    Color::rgb(0xDF, 0xB7, 0x55),
+    Color::rgb(0xEC, 0xC8, 0x60),
+    Color::rgb(0x90, 0x1C, 0x00),
+    Color::rgb(0xA3, 0x39, 0x15),
+    Color::rgb(0xB5, 0x53, 0x28),
+    Color::rgb(0xC6, 0x6C, 0x3A),
+    Color::rgb(0xD5, 0x82, 0x4A),
+    Color::rgb(0xE3, 0x97, 0x59),
+    Color::rgb(0xF0, 0xAA, 0x67),
+    Color::rgb(0xFC, 0xBC, 0x74),
+    Color::rgb(0x94, 0x00, 0x00),
+    Color::rgb(0xA7, 0x1A, 0x1A),
+    Color::rgb(0xB8, 0x32, 0x32),
+    Color::rgb(0xC8, 0x48, 0x48),
+    Color::rgb(0xD6, 0x5C, 0x5C),
+    Color::rgb(0xE4, 0x6F, 0x6F),
+    Color::rgb(0xF0, 0x80, 0x80),
+    Color::rgb(0xFC, 0x90, 0x90),
+    Color::rgb(0x84, 0x00, 0x64),
+    Color::rgb(0x97, 0x19, 0x7A),
+    Color::rgb(0xA8, 0x30, 0x8F),
+    Color::rgb(0xB8, 0x46, 0xA2),
+    Color::rgb(0xC6, 0x59, 0xB3),
+    Color::rgb(0xD4, 0x6C, 0xC3),
+    Color::rgb(0xE0, 0x7C, 0xD2),
+    Color::rgb(0xEC, 0x8C, 0xE0),
+    Color::rgb(0x50, 0x00, 0x84),
+    Color::rgb(0x68, 0x19, 0x9A),
+    Color::rgb(0x7D, 0x30, 0xAD),
+    Color::rgb(0x92, 0x46, 0xC0),
+    Color::rgb(0xA4, 0x59, 0xD0),
+    Color::rgb(0xB5, 0x6C, 0xE0),
+    Color::rgb(0xC5, 0x7C, 0xEE),
+    Color::rgb(0xD4, 0x8C, 0xFC),
+    Color::rgb(0x14, 0x00, 0x90),
+    Color::rgb(0x33, 0x1A, 0xA3),
+    Color::rgb(0x4E, 0x32, 0xB5),
+    Color::rgb(0x68, 0x48, 0xC6),
+    Color::rgb(0x7F, 0x5C, 0xD5),
+    Color::rgb(0x95, 0x6F, 0xE3),
+    Color::rgb(0xA9, 0x80, 0xF0),
+    Color::rgb(0xBC, 0x90, 0xFC),
+    Color::rgb(0x00, 0x00, 0x94),
+    Color::rgb(0x18, 0x1A, 0xA7),
+    Color::rgb(0x2D, 0x32, 0xB8),
+    Color::rgb(0x42, 0x48, 0xC8),
+    Color::rgb(0x54, 0x5C, 0xD6),
+    Color::rgb(0x65, 0x6F, 0xE4),
+    Color::rgb(0x75, 0x80, 0xF0),
+    Color::rgb(0x84, 0x90, 0xFC),
+    Color::rgb(0x00, 0x1C, 0x88),
+    Color::rgb(0x18, 0x3B, 0x9D),
+    Color::rgb(0x2D, 0x57, 0xB0),
+    Color::rgb(0x42, 0x72, 0xC2),
+    Color::rgb(0x54, 0x8A, 0xD2),
+    Color::rgb(0x65, 0xA0, 0xE1),
+    Color::rgb(0x75, 0xB5, 0xEF),
+    Color::rgb(0x84, 0xC8, 0xFC),
+    Color::rgb(0x00, 0x30, 0x64),
+    Color::rgb(0x18, 0x50, 0x80),
+    Color::rgb(0x2D, 0x6D, 0x98),
+    Color::rgb(0x42, 0x88, 0xB0),
+    Color::rgb(0x54, 0xA0, 0xC5),
+    Color::rgb(0x65, 0xB7, 0xD9),
+    Color::rgb(0x75, 0xCC, 0xEB),
+    Color::rgb(0x84, 0xE0, 0xFC),
+    Color::rgb(0x00, 0x40, 0x30),
+    Color::rgb(0x18, 0x62, 0x4E),
+    Color::rgb(0x2D, 0x81, 0x69),
+    Color::rgb(0x42, 0x9E, 0x82),
+    Color::rgb(0x54, 0xB8, 0x99),
+    Color::rgb(0x65, 0xD1, 0xAE),
+    Color::rgb(0x75, 0xE7, 0xC2),
+    Color::rgb(0x84, 0xFC, 0xD4),
+    Color::rgb(0x00, 0x44, 0x00),
+    Color::rgb(0x1A, 0x66, 0x1A),
+    Color::rgb(0x32, 0x84, 0x32),
+    Color::rgb(0x48, 0xA0, 0x48),
+    Color::rgb(0x5C, 0xBA, 0x5C),
+    Color::rgb(0x6F, 0xD2, 0x6F),
+    Color::rgb(0x80, 0xE8, 0x80),
+    Color::rgb(0x90, 0xFC, 0x90),
+    Color::rgb(0x14, 0x3C, 0x00),
+    Color::rgb(0x35, 0x5F, 0x18),
+    Color::rgb(0x52, 0x7E, 0x2D),
+    Color::rgb(0x6E, 0x9C, 0x42),
+    Color::rgb(0x87, 0xB7, 0x54),
+    Color::rgb(0x9E, 0xD0, 0x65),
+    Color::rgb(0xB4, 0xE7, 0x75),
+    Color::rgb(0xC8, 0xFC, 0x84),
+    Color::rgb(0x30, 0x38, 0x00),
+    Color::rgb(0x50, 0x59, 0x16),
+    Color::rgb(0x6D, 0x76, 0x2B),
+    Color::rgb(0x88, 0x92, 0x3E),
+    Color::rgb(0xA0, 0xAB, 0x4F),
+    Color::rgb(0xB7, 0xC2, 0x5F),
+    Color::rgb(0xCC, 0xD8, 0x6E),
+    Color::rgb(0xE0, 0xEC, 0x7C),
+    Color::rgb(0x48, 0x2C, 0x00),
+    Color::rgb(0x69, 0x4D, 0x14),
+    Color::rgb(0x86, 0x6A, 0x26),
+    Color::rgb(0xA2, 0x86, 0x38),
+    Color::rgb(0xBB, 0x9F, 0x47),
+    Color::rgb(0xD2, 0xB6, 0x56),
+    Color::rgb(0xE8, 0xCC, 0x63),
+    Color::rgb(0xFC, 0xE0, 0x70),
+];
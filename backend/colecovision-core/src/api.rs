@@ -0,0 +1,267 @@
+//! `ColecoVision` public interface and main loop
+
+use crate::audio::AudioResampler;
+use crate::bus::Bus;
+use crate::input::{ColecoVisionInputs, InputState};
+use crate::memory::{Memory, BIOS_LEN};
+use bincode::{Decode, Encode};
+use jgenesis_common::audio::ResamplerQuality;
+use jgenesis_common::frontend::{
+    AudioOutput, Color, EmulatorTrait, FrameSize, PixelAspectRatio, Renderer, SaveWriter,
+    TickEffect, TickResult, TimingMode,
+};
+use jgenesis_common::rng::{InitialRamState, Rng};
+use jgenesis_common::state::StateFormat;
+use jgenesis_proc_macros::{EnumDisplay, EnumFromStr, FakeDecode, FakeEncode, PartialClone};
+use jgenesis_scheduler::ClockRatio;
+use smsgg_core::psg::{Psg, PsgTickEffect, PsgVersion};
+use smsgg_core::vdp::{Vdp, VdpTickEffect, TMS9918_COLOR_TO_SMS_COLOR};
+use smsgg_core::VdpVersion;
+use std::fmt::{Debug, Display};
+use thiserror::Error;
+use z80_emu::Z80;
+
+// The ColecoVision VDP has the same NTSC timings as the Master System VDP, and it never uses mode 4
+const VDP_VERSION: VdpVersion = VdpVersion::NtscMasterSystem2;
+
+#[derive(Debug, Error)]
+pub enum ColecoVisionLoadError {
+    #[error("ROM file is empty")]
+    EmptyRom,
+    #[error("Unsupported ROM size: {0} bytes")]
+    UnsupportedRomSize(usize),
+    #[error("BIOS ROM must be exactly {BIOS_LEN} bytes, was {0} bytes")]
+    InvalidBiosLength(usize),
+}
+
+#[derive(Debug, Error)]
+pub enum ColecoVisionError<RErr, AErr> {
+    #[error("Error rendering a frame: {0}")]
+    Rendering(RErr),
+    #[error("Error outputting audio samples: {0}")]
+    Audio(AErr),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode, EnumDisplay, EnumFromStr)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ColecoVisionAspectRatio {
+    #[default]
+    Ntsc,
+    SquarePixels,
+    Stretched,
+}
+
+impl ColecoVisionAspectRatio {
+    fn to_pixel_aspect_ratio(self) -> Option<PixelAspectRatio> {
+        match self {
+            // Same pixel clock as the NTSC Master System
+            Self::Ntsc => {
+                Some(PixelAspectRatio::try_from(smsgg_core::SMS_NTSC_ASPECT_RATIO).unwrap())
+            }
+            Self::SquarePixels => Some(PixelAspectRatio::SQUARE),
+            Self::Stretched => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Encode, Decode)]
+pub struct ColecoVisionEmulatorConfig {
+    pub aspect_ratio: ColecoVisionAspectRatio,
+    pub remove_sprite_limit: bool,
+    pub audio_resampler_quality: ResamplerQuality,
+    /// Initial contents of work RAM; if None, RAM is zeroed
+    pub initial_ram_state: Option<InitialRamState>,
+    /// Seed for all in-core randomness, including random initial RAM contents; if None, a seed is
+    /// chosen randomly at power on
+    pub rng_seed: Option<u64>,
+}
+
+#[derive(Debug, Clone, FakeEncode, FakeDecode)]
+struct FrameBuffer(Vec<Color>);
+
+impl Default for FrameBuffer {
+    fn default() -> Self {
+        Self(vec![Color::default(); smsgg_core::vdp::FRAME_BUFFER_LEN])
+    }
+}
+
+#[derive(Debug, Clone, Encode, Decode, PartialClone)]
+pub struct ColecoVisionEmulator {
+    z80: Z80,
+    #[partial_clone(partial)]
+    memory: Memory,
+    vdp: Vdp,
+    psg: Psg,
+    input: InputState,
+    audio_resampler: AudioResampler,
+    frame_buffer: FrameBuffer,
+    vdp_clock: ClockRatio,
+    config: ColecoVisionEmulatorConfig,
+}
+
+macro_rules! new_bus {
+    ($self:expr) => {
+        Bus {
+            memory: &mut $self.memory,
+            vdp: &mut $self.vdp,
+            psg: &mut $self.psg,
+            input: &mut $self.input,
+        }
+    };
+}
+
+impl ColecoVisionEmulator {
+    /// # Errors
+    ///
+    /// This function will return an error if the BIOS is not exactly 8KB or if the cartridge ROM
+    /// size is not supported.
+    pub fn create(
+        rom: Vec<u8>,
+        bios: Vec<u8>,
+        config: ColecoVisionEmulatorConfig,
+    ) -> Result<Self, ColecoVisionLoadError> {
+        let mut rng = Rng::from_optional_seed(config.rng_seed);
+        let initial_ram_state = config.initial_ram_state.unwrap_or(InitialRamState::AllZeroes);
+        let memory = Memory::new(rom, bios, initial_ram_state, &mut rng)?;
+
+        // The BIOS initializes the stack pointer and interrupt mode itself
+        let z80 = Z80::new();
+
+        Ok(Self {
+            z80,
+            memory,
+            vdp: Vdp::new(VDP_VERSION, config.remove_sprite_limit),
+            psg: Psg::new(PsgVersion::Standard),
+            input: InputState::new(),
+            audio_resampler: AudioResampler::new(config.audio_resampler_quality),
+            frame_buffer: FrameBuffer::default(),
+            vdp_clock: ClockRatio::divider(2),
+            config,
+        })
+    }
+
+    /// Copy the 16-color TMS9918 palette into `out`.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if `out` has fewer than 16 elements.
+    pub fn copy_palette(&self, out: &mut [Color]) {
+        for (out_color, &sms_color) in out[..16].iter_mut().zip(TMS9918_COLOR_TO_SMS_COLOR) {
+            *out_color = smsgg_core::sms_color_to_rgb(sms_color.into());
+        }
+    }
+
+    fn render_frame<R: Renderer>(&mut self, renderer: &mut R) -> Result<(), R::Err> {
+        let viewport = VDP_VERSION.viewport_size();
+        let screen_width = viewport.width as usize;
+
+        for (i, row) in self.vdp.frame_buffer().iter().enumerate() {
+            for (j, &color) in row.iter().enumerate() {
+                self.frame_buffer.0[i * screen_width + j] = smsgg_core::sms_color_to_rgb(color);
+            }
+        }
+
+        let frame_size = FrameSize { width: viewport.width.into(), height: viewport.height.into() };
+        renderer.render_frame(
+            &self.frame_buffer.0,
+            frame_size,
+            self.config.aspect_ratio.to_pixel_aspect_ratio(),
+        )
+    }
+}
+
+impl EmulatorTrait for ColecoVisionEmulator {
+    type Inputs = ColecoVisionInputs;
+    type Config = ColecoVisionEmulatorConfig;
+    const STATE_FORMAT: StateFormat = StateFormat { core: "colecovision", version: 1 };
+    type Err<
+        RErr: Debug + Display + Send + Sync + 'static,
+        AErr: Debug + Display + Send + Sync + 'static,
+    > = ColecoVisionError<RErr, AErr>;
+
+    /// Execute a single CPU instruction and run the rest of the components for the corresponding
+    /// number of cycles.
+    ///
+    /// # Errors
+    ///
+    /// This method will propagate any errors encountered while rendering frames or pushing audio
+    /// samples.
+    fn tick<R, A>(
+        &mut self,
+        renderer: &mut R,
+        audio_output: &mut A,
+        inputs: &Self::Inputs,
+    ) -> TickResult<Self::Err<R::Err, A::Err>>
+    where
+        R: Renderer,
+        R::Err: Debug + Display + Send + Sync + 'static,
+        A: AudioOutput,
+        A::Err: Debug + Display + Send + Sync + 'static,
+    {
+        self.input.set_inputs(*inputs);
+
+        let t_cycles = u64::from(self.z80.execute_instruction(&mut new_bus!(self)));
+
+        for _ in 0..t_cycles {
+            if self.psg.tick() == PsgTickEffect::Clocked {
+                let (sample_l, sample_r) = self.psg.sample();
+                self.audio_resampler.collect_sample(sample_l, sample_r);
+            }
+        }
+
+        // The VDP runs 3 cycles for every 2 CPU cycles
+        let mut frame_rendered = false;
+        let vdp_cycles = self.vdp_clock.tick(t_cycles) * 3;
+        if self.vdp.tick(vdp_cycles) == VdpTickEffect::FrameComplete {
+            self.render_frame(renderer).map_err(ColecoVisionError::Rendering)?;
+            self.audio_resampler.output_samples(audio_output).map_err(ColecoVisionError::Audio)?;
+            frame_rendered = true;
+        }
+
+        Ok(if frame_rendered { TickEffect::FrameRendered } else { TickEffect::None })
+    }
+
+    fn save_dirty(&self) -> bool {
+        false
+    }
+
+    fn persist_save<S: SaveWriter>(&mut self, _save_writer: &mut S) -> Result<(), S::Err> {
+        Ok(())
+    }
+
+    fn force_render<R>(&mut self, renderer: &mut R) -> Result<(), R::Err>
+    where
+        R: Renderer,
+    {
+        self.render_frame(renderer)
+    }
+
+    fn reload_config(&mut self, config: &Self::Config) {
+        self.config = *config;
+        self.vdp.set_remove_sprite_limit(config.remove_sprite_limit);
+        self.audio_resampler.set_quality(config.audio_resampler_quality);
+    }
+
+    fn take_rom_from(&mut self, other: &mut Self) {
+        self.memory.take_rom_from(&mut other.memory);
+    }
+
+    fn soft_reset(&mut self) {
+        // The reset button is wired to the cartridge port, not to the Z80, but every game responds
+        // to it by jumping back into the BIOS
+        log::info!("Soft resetting console");
+
+        self.z80 = Z80::new();
+    }
+
+    fn hard_reset<S: SaveWriter>(&mut self, _save_writer: &mut S) {
+        let (rom, bios) = self.memory.take_rom_and_bios();
+
+        *self = Self::create(rom, bios, self.config)
+            .expect("Hard reset should never fail to load cartridge");
+    }
+
+    fn timing_mode(&self) -> TimingMode {
+        TimingMode::Ntsc
+    }
+}
@@ -0,0 +1,39 @@
+//! `ColecoVision` audio resampling code
+
+use bincode::{Decode, Encode};
+use jgenesis_common::audio::ResamplerQuality;
+use jgenesis_common::frontend::AudioOutput;
+use smsgg_core::audio::PsgResampler;
+
+// The PSG is clocked by the same 3.58 MHz clock as the Z80, which is equivalent to the NTSC Master
+// System's master clock divided by 15
+const MCLK_FREQUENCY: f64 = 53_693_175.0;
+
+#[derive(Debug, Clone, Encode, Decode)]
+pub struct AudioResampler {
+    psg_resampler: PsgResampler,
+}
+
+impl AudioResampler {
+    pub fn new(quality: ResamplerQuality) -> Self {
+        let mut psg_resampler = smsgg_core::audio::new_psg_resampler(MCLK_FREQUENCY);
+        psg_resampler.set_quality(quality);
+        Self { psg_resampler }
+    }
+
+    pub fn set_quality(&mut self, quality: ResamplerQuality) {
+        self.psg_resampler.set_quality(quality);
+    }
+
+    pub fn collect_sample(&mut self, sample_l: f64, sample_r: f64) {
+        self.psg_resampler.collect_sample(sample_l, sample_r);
+    }
+
+    pub fn output_samples<A: AudioOutput>(&mut self, audio_output: &mut A) -> Result<(), A::Err> {
+        while let Some((sample_l, sample_r)) = self.psg_resampler.output_buffer_pop_front() {
+            audio_output.push_sample(sample_l, sample_r)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,84 @@
+//! Implementation of the Z80's bus interface, which connects it to all other components
+//!
+//! I/O ports are decoded using only address bits 7-5 (plus bit 0 or 1 for some devices):
+//! * $80-$9F (write): Select keypad mode for both controllers
+//! * $A0-$BF: VDP data port (A0 clear) and control port (A0 set)
+//! * $C0-$DF (write): Select joystick mode for both controllers
+//! * $E0-$FF (write): PSG
+//! * $E0-$FF (read): Controller 1 (A1 clear) and controller 2 (A1 set)
+
+use crate::input::InputState;
+use crate::memory::Memory;
+use jgenesis_common::num::GetBit;
+use smsgg_core::psg::Psg;
+use smsgg_core::vdp::Vdp;
+use z80_emu::traits::{BusInterface, InterruptLine};
+
+pub struct Bus<'a> {
+    pub memory: &'a mut Memory,
+    pub vdp: &'a mut Vdp,
+    pub psg: &'a mut Psg,
+    pub input: &'a mut InputState,
+}
+
+impl BusInterface for Bus<'_> {
+    fn read_memory(&mut self, address: u16) -> u8 {
+        self.memory.read(address)
+    }
+
+    fn write_memory(&mut self, address: u16, value: u8) {
+        self.memory.write(address, value);
+    }
+
+    fn read_io(&mut self, address: u16) -> u8 {
+        let address = address & 0xFF;
+        match address & 0xE0 {
+            0xA0 => {
+                if address.bit(0) {
+                    self.vdp.read_control()
+                } else {
+                    self.vdp.read_data()
+                }
+            }
+            0xE0 => self.input.read_port(address.bit(1)),
+            _ => 0xFF,
+        }
+    }
+
+    fn write_io(&mut self, address: u16, value: u8) {
+        let address = address & 0xFF;
+        match address & 0xE0 {
+            0x80 => self.input.select_keypad(),
+            0xA0 => {
+                if address.bit(0) {
+                    self.vdp.write_control(value);
+                } else {
+                    self.vdp.write_data(value);
+                }
+            }
+            0xC0 => self.input.select_joystick(),
+            0xE0 => self.psg.write(value),
+            _ => {
+                log::trace!("Unmapped I/O write: {address:02X} {value:02X}");
+            }
+        }
+    }
+
+    fn nmi(&self) -> InterruptLine {
+        // The VDP interrupt is wired to NMI; the Z80 only responds to its falling edge, so games
+        // must read the VDP status register to acknowledge the interrupt before the next frame
+        self.vdp.interrupt_line()
+    }
+
+    fn int(&self) -> InterruptLine {
+        InterruptLine::High
+    }
+
+    fn busreq(&self) -> bool {
+        false
+    }
+
+    fn reset(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,131 @@
+//! `ColecoVision` controller input handling
+//!
+//! Each controller is read through a single 8-bit port, but the port only exposes half of the
+//! controller at a time. Writing to port $80 selects the keypad and right fire button, and writing
+//! to port $C0 selects the joystick and left fire button.
+
+use bincode::{Decode, Encode};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode)]
+pub struct ColecoVisionControllerState {
+    pub up: bool,
+    pub left: bool,
+    pub right: bool,
+    pub down: bool,
+    pub left_fire: bool,
+    pub right_fire: bool,
+    /// Number pad keys 0-9
+    pub keypad_digits: [bool; 10],
+    pub keypad_star: bool,
+    pub keypad_pound: bool,
+}
+
+// Keypad codes for keys 0-9 as they appear in the low 4 bits of the controller port
+const KEYPAD_DIGIT_CODES: [u8; 10] = [0x0A, 0x0D, 0x07, 0x0C, 0x02, 0x03, 0x0E, 0x05, 0x01, 0x0B];
+const KEYPAD_STAR_CODE: u8 = 0x06;
+const KEYPAD_POUND_CODE: u8 = 0x09;
+const KEYPAD_NO_KEY_CODE: u8 = 0x0F;
+
+impl ColecoVisionControllerState {
+    // Bits 0-3: Up, Right, Down, Left (all active low)
+    // Bit 6: Left fire (active low)
+    fn joystick_byte(self) -> u8 {
+        0xB0 | (u8::from(!self.left_fire) << 6)
+            | (u8::from(!self.left) << 3)
+            | (u8::from(!self.down) << 2)
+            | (u8::from(!self.right) << 1)
+            | u8::from(!self.up)
+    }
+
+    // Bits 0-3: Keypad code
+    // Bit 6: Right fire (active low)
+    fn keypad_byte(self) -> u8 {
+        0xB0 | (u8::from(!self.right_fire) << 6) | self.keypad_code()
+    }
+
+    // The keypad is a matrix that can only report one key at a time; if multiple keys are held, the
+    // lowest-numbered key wins
+    fn keypad_code(self) -> u8 {
+        if let Some(digit) = self.keypad_digits.iter().position(|&pressed| pressed) {
+            KEYPAD_DIGIT_CODES[digit]
+        } else if self.keypad_star {
+            KEYPAD_STAR_CODE
+        } else if self.keypad_pound {
+            KEYPAD_POUND_CODE
+        } else {
+            KEYPAD_NO_KEY_CODE
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode)]
+pub struct ColecoVisionInputs {
+    pub p1: ColecoVisionControllerState,
+    pub p2: ColecoVisionControllerState,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode)]
+enum ControllerMode {
+    #[default]
+    Keypad,
+    Joystick,
+}
+
+#[derive(Debug, Clone, Default, Encode, Decode)]
+pub struct InputState {
+    inputs: ColecoVisionInputs,
+    mode: ControllerMode,
+}
+
+impl InputState {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_inputs(&mut self, inputs: ColecoVisionInputs) {
+        self.inputs = inputs;
+    }
+
+    pub fn select_keypad(&mut self) {
+        self.mode = ControllerMode::Keypad;
+    }
+
+    pub fn select_joystick(&mut self) {
+        self.mode = ControllerMode::Joystick;
+    }
+
+    #[must_use]
+    pub fn read_port(&self, player_2: bool) -> u8 {
+        let controller = if player_2 { self.inputs.p2 } else { self.inputs.p1 };
+        match self.mode {
+            ControllerMode::Keypad => controller.keypad_byte(),
+            ControllerMode::Joystick => controller.joystick_byte(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keypad_codes() {
+        let mut controller = ColecoVisionControllerState::default();
+        assert_eq!(controller.keypad_byte(), 0xFF);
+
+        controller.keypad_digits[1] = true;
+        controller.right_fire = true;
+        assert_eq!(controller.keypad_byte(), 0xBD);
+
+        controller.keypad_digits[0] = true;
+        assert_eq!(controller.keypad_byte() & 0x0F, 0x0A);
+    }
+
+    #[test]
+    fn joystick_byte() {
+        let controller =
+            ColecoVisionControllerState { up: true, left_fire: true, ..Default::default() };
+        assert_eq!(controller.joystick_byte(), 0xBE);
+    }
+}
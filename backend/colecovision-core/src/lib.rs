@@ -0,0 +1,15 @@
+//! `ColecoVision` emulation core
+//!
+//! The VDP and PSG are shared with the Master System core; this crate only implements the memory
+//! map, I/O ports, and controllers.
+
+pub mod api;
+mod audio;
+mod bus;
+pub mod input;
+mod memory;
+
+pub use api::{
+    ColecoVisionEmulator, ColecoVisionEmulatorConfig, ColecoVisionError, ColecoVisionLoadError,
+};
+pub use input::{ColecoVisionControllerState, ColecoVisionInputs};
@@ -0,0 +1,165 @@
+//! `ColecoVision` memory map
+//!
+//! * $0000-$1FFF: BIOS ROM
+//! * $2000-$5FFF: Expansion port (unused)
+//! * $6000-$7FFF: 1KB work RAM, mirrored
+//! * $8000-$FFFF: Cartridge ROM
+
+use crate::api::ColecoVisionLoadError;
+use bincode::{Decode, Encode};
+use jgenesis_common::rng::{InitialRamState, Rng};
+use jgenesis_proc_macros::{FakeDecode, FakeEncode, PartialClone};
+
+pub const BIOS_LEN: usize = 8 * 1024;
+const RAM_LEN: usize = 1024;
+
+// Cartridges up to this size are mapped directly; anything larger must be a MegaCart
+const MAX_STANDARD_ROM_LEN: usize = 32 * 1024;
+const MEGACART_BANK_LEN: usize = 16 * 1024;
+
+#[derive(Debug, Clone, Default, FakeEncode, FakeDecode)]
+struct Rom(Box<[u8]>);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+enum Mapper {
+    Standard,
+    // The last 16KB bank is fixed at $8000-$BFFF, and reading from $FFC0-$FFFF switches the bank
+    // mapped at $C000-$FFFF
+    MegaCart { bank: u8, bank_mask: u8 },
+}
+
+#[derive(Debug, Clone, Encode, Decode, PartialClone)]
+pub struct Memory {
+    #[partial_clone(default)]
+    bios: Rom,
+    #[partial_clone(default)]
+    cartridge: Rom,
+    mapper: Mapper,
+    ram: Box<[u8]>,
+}
+
+impl Memory {
+    pub fn new(
+        rom: Vec<u8>,
+        bios: Vec<u8>,
+        initial_ram_state: InitialRamState,
+        rng: &mut Rng,
+    ) -> Result<Self, ColecoVisionLoadError> {
+        if bios.len() != BIOS_LEN {
+            return Err(ColecoVisionLoadError::InvalidBiosLength(bios.len()));
+        }
+
+        if rom.is_empty() {
+            return Err(ColecoVisionLoadError::EmptyRom);
+        }
+
+        let mapper = if rom.len() <= MAX_STANDARD_ROM_LEN {
+            Mapper::Standard
+        } else {
+            if !rom.len().is_multiple_of(MEGACART_BANK_LEN) || rom.len() > 256 * MEGACART_BANK_LEN {
+                return Err(ColecoVisionLoadError::UnsupportedRomSize(rom.len()));
+            }
+
+            // Bank count is not necessarily a power of two, but the mask is only used to keep bank
+            // numbers in range
+            let bank_count = rom.len() / MEGACART_BANK_LEN;
+            let bank_mask = (bank_count.next_power_of_two() - 1) as u8;
+            Mapper::MegaCart { bank: 0, bank_mask }
+        };
+
+        log::info!("Loaded cartridge ROM of size {} KB, using mapper {mapper:?}", rom.len() / 1024);
+
+        let mut ram = vec![0; RAM_LEN].into_boxed_slice();
+        initial_ram_state.fill(&mut ram, rng);
+
+        Ok(Self {
+            bios: Rom(bios.into_boxed_slice()),
+            cartridge: Rom(rom.into_boxed_slice()),
+            mapper,
+            ram,
+        })
+    }
+
+    pub fn read(&mut self, address: u16) -> u8 {
+        match address {
+            0x0000..=0x1FFF => self.bios.0[address as usize],
+            0x2000..=0x5FFF => 0xFF,
+            0x6000..=0x7FFF => self.ram[(address as usize) & (RAM_LEN - 1)],
+            0x8000..=0xFFFF => self.read_cartridge(address),
+        }
+    }
+
+    fn read_cartridge(&mut self, address: u16) -> u8 {
+        let rom = &self.cartridge.0;
+        match &mut self.mapper {
+            Mapper::Standard => {
+                // Unpopulated cartridge addresses read as open bus, which is normally $FF
+                rom.get((address & 0x7FFF) as usize).copied().unwrap_or(0xFF)
+            }
+            Mapper::MegaCart { bank, bank_mask } => {
+                let bank_count = rom.len() / MEGACART_BANK_LEN;
+                let mapped_bank = if address < 0xC000 {
+                    bank_count - 1
+                } else {
+                    usize::from(*bank) % bank_count
+                };
+                let rom_addr =
+                    mapped_bank * MEGACART_BANK_LEN + (address as usize & (MEGACART_BANK_LEN - 1));
+                let value = rom[rom_addr];
+
+                if address >= 0xFFC0 {
+                    *bank = (address as u8) & *bank_mask;
+                }
+
+                value
+            }
+        }
+    }
+
+    pub fn write(&mut self, address: u16, value: u8) {
+        if (0x6000..=0x7FFF).contains(&address) {
+            self.ram[(address as usize) & (RAM_LEN - 1)] = value;
+        }
+    }
+
+    pub fn take_rom_and_bios(&mut self) -> (Vec<u8>, Vec<u8>) {
+        let rom = std::mem::take(&mut self.cartridge.0).into_vec();
+        let bios = std::mem::take(&mut self.bios.0).into_vec();
+        (rom, bios)
+    }
+
+    pub fn take_rom_from(&mut self, other: &mut Self) {
+        self.bios = std::mem::take(&mut other.bios);
+        self.cartridge = std::mem::take(&mut other.cartridge);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_memory(rom: Vec<u8>) -> Memory {
+        Memory::new(rom, vec![0; BIOS_LEN], InitialRamState::AllZeroes, &mut Rng::new(0)).unwrap()
+    }
+
+    #[test]
+    fn ram_mirroring() {
+        let mut memory = new_memory(vec![0; 16 * 1024]);
+        memory.write(0x6005, 0x12);
+        assert_eq!(memory.read(0x7C05), 0x12);
+    }
+
+    #[test]
+    fn megacart_bank_switching() {
+        let rom: Vec<u8> =
+            (0..8).flat_map(|bank| std::iter::repeat_n(bank, MEGACART_BANK_LEN)).collect();
+        let mut memory = new_memory(rom);
+
+        assert_eq!(memory.read(0x8000), 7);
+        assert_eq!(memory.read(0xC000), 0);
+
+        memory.read(0xFFC3);
+        assert_eq!(memory.read(0xC000), 3);
+        assert_eq!(memory.read(0xBFFF), 7);
+    }
+}
@@ -14,10 +14,13 @@ use crate::speed::SpeedRegister;
 use crate::timer::GbTimer;
 use crate::{ppu, HardwareMode};
 use bincode::{Decode, Encode};
+use jgenesis_common::audio::ResamplerQuality;
 use jgenesis_common::frontend::{
     AudioOutput, Color, EmulatorTrait, PixelAspectRatio, Renderer, SaveWriter, TickEffect,
     TickResult, TimingMode,
 };
+use jgenesis_common::rng::{InitialRamState, Rng};
+use jgenesis_common::state::StateFormat;
 use jgenesis_proc_macros::{EnumDisplay, EnumFromStr, PartialClone};
 use std::fmt::{Debug, Display};
 use thiserror::Error;
@@ -31,13 +34,11 @@ pub enum GameBoyLoadError {
 }
 
 #[derive(Debug, Error)]
-pub enum GameBoyError<RErr, AErr, SErr> {
+pub enum GameBoyError<RErr, AErr> {
     #[error("Error rendering a frame: {0}")]
     Rendering(RErr),
     #[error("Error outputting audio samples: {0}")]
     Audio(AErr),
-    #[error("Error writing save file: {0}")]
-    SaveWrite(SErr),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode, EnumDisplay, EnumFromStr)]
@@ -83,6 +84,12 @@ pub struct GameBoyEmulatorConfig {
     pub gb_palette: GbPalette,
     pub gbc_color_correction: GbcColorCorrection,
     pub audio_60hz_hack: bool,
+    pub audio_resampler_quality: ResamplerQuality,
+    /// Initial contents of work RAM and HRAM; if None, RAM is zeroed
+    pub initial_ram_state: Option<InitialRamState>,
+    /// Seed for all in-core randomness, including random initial RAM contents; if None, a seed is
+    /// chosen randomly at power on
+    pub rng_seed: Option<u64>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -108,7 +115,6 @@ pub struct GameBoyEmulator {
     input_state: InputState,
     rgba_buffer: RgbaFrameBuffer,
     config: GameBoyEmulatorConfig,
-    frame_count: u64,
 }
 
 impl GameBoyEmulator {
@@ -132,12 +138,15 @@ impl GameBoyEmulator {
 
         log::info!("Running with hardware mode {hardware_mode}");
 
+        let mut rng = Rng::from_optional_seed(config.rng_seed);
+        let initial_ram_state = config.initial_ram_state.unwrap_or(InitialRamState::AllZeroes);
+
         Ok(Self {
             hardware_mode,
             cpu: Sm83::new(hardware_mode, config.pretend_to_be_gba),
             ppu: Ppu::new(hardware_mode),
             apu: Apu::new(config),
-            memory: Memory::new(),
+            memory: Memory::new(initial_ram_state, &mut rng),
             interrupt_registers: InterruptRegisters::default(),
             speed_register: SpeedRegister::new(),
             cartridge,
@@ -146,7 +155,6 @@ impl GameBoyEmulator {
             input_state: InputState::new(),
             rgba_buffer: RgbaFrameBuffer::default(),
             config,
-            frame_count: 0,
         })
     }
 
@@ -178,26 +186,23 @@ impl GameBoyEmulator {
 impl EmulatorTrait for GameBoyEmulator {
     type Inputs = GameBoyInputs;
     type Config = GameBoyEmulatorConfig;
+    const STATE_FORMAT: StateFormat = StateFormat { core: "gb", version: 1 };
     type Err<
         RErr: Debug + Display + Send + Sync + 'static,
         AErr: Debug + Display + Send + Sync + 'static,
-        SErr: Debug + Display + Send + Sync + 'static,
-    > = GameBoyError<RErr, AErr, SErr>;
+    > = GameBoyError<RErr, AErr>;
 
-    fn tick<R, A, S>(
+    fn tick<R, A>(
         &mut self,
         renderer: &mut R,
         audio_output: &mut A,
         inputs: &Self::Inputs,
-        save_writer: &mut S,
-    ) -> TickResult<Self::Err<R::Err, A::Err, S::Err>>
+    ) -> TickResult<Self::Err<R::Err, A::Err>>
     where
         R: Renderer,
         R::Err: Debug + Display + Send + Sync + 'static,
         A: AudioOutput,
         A::Err: Debug + Display + Send + Sync + 'static,
-        S: SaveWriter,
-        S::Err: Debug + Display + Send + Sync + 'static,
     {
         self.input_state.set_inputs(*inputs);
 
@@ -234,18 +239,6 @@ impl EmulatorTrait for GameBoyEmulator {
 
             self.cartridge.update_rtc_time();
 
-            if self.cartridge.has_battery()
-                && self.frame_count % 60 == 30
-                && self.cartridge.get_and_clear_sram_dirty()
-            {
-                let sram = self.cartridge.sram();
-                save_writer.persist_bytes("sav", sram).map_err(GameBoyError::SaveWrite)?;
-
-                self.cartridge.save_rtc_state(save_writer).map_err(GameBoyError::SaveWrite)?;
-            }
-
-            self.frame_count += 1;
-
             Ok(TickEffect::FrameRendered)
         } else if self.apu.queued_sample_count() > 1200 {
             // A frame and a half's worth of samples are queued up; this can happen when the PPU is disabled
@@ -258,6 +251,20 @@ impl EmulatorTrait for GameBoyEmulator {
         }
     }
 
+    fn save_dirty(&self) -> bool {
+        self.cartridge.has_battery() && self.cartridge.sram_dirty()
+    }
+
+    fn persist_save<S: SaveWriter>(&mut self, save_writer: &mut S) -> Result<(), S::Err> {
+        if self.save_dirty() {
+            save_writer.persist_bytes("sav", self.cartridge.sram())?;
+            self.cartridge.save_rtc_state(save_writer)?;
+            self.cartridge.clear_sram_dirty();
+        }
+
+        Ok(())
+    }
+
     fn force_render<R>(&mut self, renderer: &mut R) -> Result<(), R::Err>
     where
         R: Renderer,
@@ -109,7 +109,7 @@ impl Apu {
             stereo_control: StereoControl::new(),
             frame_sequencer_step: 0,
             previous_div_bit: false,
-            resampler: GameBoyResampler::new(config.audio_60hz_hack),
+            resampler: GameBoyResampler::new(config.audio_60hz_hack, config.audio_resampler_quality),
         }
     }
 
@@ -304,6 +304,7 @@ impl Apu {
 
     pub fn reload_config(&mut self, config: GameBoyEmulatorConfig) {
         self.resampler.update_audio_60hz_hack(config.audio_60hz_hack);
+        self.resampler.set_quality(config.audio_resampler_quality);
     }
 }
 
@@ -1,5 +1,5 @@
 use bincode::{Decode, Encode};
-use jgenesis_common::audio::SignalResampler;
+use jgenesis_common::audio::{ResamplerQuality, SignalResampler};
 use jgenesis_common::frontend::AudioOutput;
 
 type GbApuResampler = SignalResampler<45, 0>;
@@ -16,8 +16,14 @@ pub struct GameBoyResampler {
 }
 
 impl GameBoyResampler {
-    pub fn new(audio_60hz_hack: bool) -> Self {
-        Self { resampler: new_gb_apu_resampler(gb_source_frequency(audio_60hz_hack)) }
+    pub fn new(audio_60hz_hack: bool, quality: ResamplerQuality) -> Self {
+        let mut resampler = new_gb_apu_resampler(gb_source_frequency(audio_60hz_hack));
+        resampler.set_quality(quality);
+        Self { resampler }
+    }
+
+    pub fn set_quality(&mut self, quality: ResamplerQuality) {
+        self.resampler.set_quality(quality);
     }
 
     pub fn collect_sample(&mut self, sample_l: f64, sample_r: f64) {
@@ -248,8 +248,12 @@ impl Cartridge {
         }
     }
 
-    pub fn get_and_clear_sram_dirty(&mut self) -> bool {
-        mem::take(&mut self.sram_dirty)
+    pub fn sram_dirty(&self) -> bool {
+        self.sram_dirty
+    }
+
+    pub fn clear_sram_dirty(&mut self) {
+        self.sram_dirty = false;
     }
 
     pub fn update_rtc_time(&mut self) {
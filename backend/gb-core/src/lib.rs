@@ -1,3 +1,8 @@
+//! Game Boy / Game Boy Color emulation core
+//!
+//! The types re-exported at the crate root are the stable API; [`GameBoyEmulator::create`] detects
+//! whether to run in Game Boy Color mode from the cartridge header.
+
 pub mod api;
 mod apu;
 mod audio;
@@ -13,6 +18,12 @@ mod sm83;
 mod speed;
 mod timer;
 
+pub use api::{
+    GameBoyEmulator, GameBoyEmulatorConfig, GameBoyError, GameBoyLoadError, GbAspectRatio,
+    GbPalette, GbcColorCorrection,
+};
+pub use inputs::GameBoyInputs;
+
 use bincode::{Decode, Encode};
 use std::fmt::{Display, Formatter};
 
@@ -1,6 +1,7 @@
 //! Game Boy internal memory
 
 use bincode::{Decode, Encode};
+use jgenesis_common::rng::{InitialRamState, Rng};
 
 const MAIN_RAM_LEN: usize = 32 * 1024;
 const HRAM_LEN: usize = 127;
@@ -16,12 +17,14 @@ pub struct Memory {
 }
 
 impl Memory {
-    pub fn new() -> Self {
-        Self {
-            main_ram: vec![0; MAIN_RAM_LEN].into_boxed_slice().try_into().unwrap(),
-            main_ram_bank: 0,
-            hram: vec![0; HRAM_LEN].into_boxed_slice().try_into().unwrap(),
-        }
+    pub fn new(initial_ram_state: InitialRamState, rng: &mut Rng) -> Self {
+        let mut main_ram: Box<MainRam> =
+            vec![0; MAIN_RAM_LEN].into_boxed_slice().try_into().unwrap();
+        let mut hram: Box<Hram> = vec![0; HRAM_LEN].into_boxed_slice().try_into().unwrap();
+        initial_ram_state.fill(main_ram.as_mut(), rng);
+        initial_ram_state.fill(hram.as_mut(), rng);
+
+        Self { main_ram, main_ram_bank: 0, hram }
     }
 
     pub fn read_main_ram(&self, address: u16) -> u8 {
@@ -0,0 +1,294 @@
+//! Game Boy Advance public interface and main loop
+
+use crate::apu::Apu;
+use crate::audio::AudioResampler;
+use crate::bus::Bus;
+use crate::cartridge::Cartridge;
+use crate::dma::DmaController;
+use crate::input::{GbaInputs, InputState};
+use crate::interrupts::{InterruptRegisters, InterruptType};
+use crate::memory::{Memory, BIOS_LEN};
+use crate::ppu;
+use crate::ppu::Ppu;
+use crate::timers::Timers;
+use arm7tdmi_emu::{Arm7Tdmi, CpuMode};
+use bincode::{Decode, Encode};
+use jgenesis_common::audio::ResamplerQuality;
+use jgenesis_common::frontend::{
+    AudioOutput, Color, EmulatorTrait, PixelAspectRatio, Renderer, SaveWriter, TickEffect,
+    TickResult, TimingMode,
+};
+use jgenesis_common::rng::{InitialRamState, Rng};
+use jgenesis_common::state::StateFormat;
+use jgenesis_proc_macros::{EnumDisplay, EnumFromStr, PartialClone};
+use std::fmt::{Debug, Display};
+use thiserror::Error;
+
+pub const GBA_CLOCK_FREQUENCY: f64 = 16_777_216.0;
+
+const CARTRIDGE_ROM_START: u32 = 0x0800_0000;
+
+// Stack pointers as initialized by the BIOS before jumping to the cartridge
+const SP_SVC: u32 = 0x0300_7FE0;
+const SP_IRQ: u32 = 0x0300_7FA0;
+const SP_USR: u32 = 0x0300_7F00;
+
+#[derive(Debug, Error)]
+pub enum GbaLoadError {
+    #[error("ROM file is empty")]
+    EmptyRom,
+    #[error("ROM file is too large: {0} bytes (max 32MB)")]
+    RomTooLarge(usize),
+    #[error("BIOS ROM must be exactly {BIOS_LEN} bytes, was {0} bytes")]
+    InvalidBiosLength(usize),
+}
+
+#[derive(Debug, Error)]
+pub enum GbaError<RErr, AErr> {
+    #[error("Error rendering a frame: {0}")]
+    Rendering(RErr),
+    #[error("Error outputting audio samples: {0}")]
+    Audio(AErr),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode, EnumDisplay, EnumFromStr)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GbaAspectRatio {
+    #[default]
+    SquarePixels,
+    Stretched,
+}
+
+impl GbaAspectRatio {
+    fn to_pixel_aspect_ratio(self) -> Option<PixelAspectRatio> {
+        match self {
+            Self::SquarePixels => Some(PixelAspectRatio::SQUARE),
+            Self::Stretched => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Encode, Decode)]
+pub struct GbaEmulatorConfig {
+    pub aspect_ratio: GbaAspectRatio,
+    /// If true, start executing at the cartridge entry point with registers initialized as the BIOS
+    /// would leave them, skipping the boot animation
+    pub skip_bios_intro: bool,
+    pub audio_resampler_quality: ResamplerQuality,
+    /// Initial contents of EWRAM and IWRAM; if None, RAM is zeroed
+    pub initial_ram_state: Option<InitialRamState>,
+    /// Seed for all in-core randomness, including random initial RAM contents; if None, a seed is
+    /// chosen randomly at power on
+    pub rng_seed: Option<u64>,
+}
+
+#[derive(Debug, Clone, Encode, Decode, PartialClone)]
+pub struct GbaEmulator {
+    cpu: Arm7Tdmi,
+    #[partial_clone(partial)]
+    memory: Memory,
+    #[partial_clone(partial)]
+    cartridge: Cartridge,
+    ppu: Ppu,
+    apu: Apu,
+    dma: DmaController,
+    timers: Timers,
+    interrupts: InterruptRegisters,
+    input_state: InputState,
+    audio_resampler: AudioResampler,
+    config: GbaEmulatorConfig,
+}
+
+macro_rules! new_bus {
+    ($self:expr) => {
+        Bus {
+            memory: &mut $self.memory,
+            ppu: &mut $self.ppu,
+            apu: &mut $self.apu,
+            dma: &mut $self.dma,
+            timers: &mut $self.timers,
+            cartridge: &mut $self.cartridge,
+            interrupts: &mut $self.interrupts,
+            input_state: &mut $self.input_state,
+            bios_readable: $self.cpu.pc() < BIOS_LEN as u32,
+            cycles: 0,
+        }
+    };
+}
+
+impl GbaEmulator {
+    /// # Errors
+    ///
+    /// This function will return an error if the ROM or BIOS is invalid.
+    pub fn create<S: SaveWriter>(
+        rom: Vec<u8>,
+        bios: Vec<u8>,
+        config: GbaEmulatorConfig,
+        save_writer: &mut S,
+    ) -> Result<Self, GbaLoadError> {
+        let initial_save = save_writer.load_bytes("sav").ok();
+        let cartridge = Cartridge::create(rom, initial_save)?;
+
+        let mut rng = Rng::from_optional_seed(config.rng_seed);
+        let initial_ram_state = config.initial_ram_state.unwrap_or(InitialRamState::AllZeroes);
+        let mut memory = Memory::new(bios, initial_ram_state, &mut rng)?;
+
+        let mut cpu = Arm7Tdmi::new();
+        if config.skip_bios_intro {
+            skip_bios_intro(&mut cpu, &mut memory);
+        }
+
+        Ok(Self {
+            cpu,
+            memory,
+            cartridge,
+            ppu: Ppu::new(),
+            apu: Apu::new(),
+            dma: DmaController::new(),
+            timers: Timers::new(),
+            interrupts: InterruptRegisters::new(),
+            input_state: InputState::new(),
+            audio_resampler: AudioResampler::new(config.audio_resampler_quality),
+            config,
+        })
+    }
+
+    pub fn copy_palettes(&self, out: &mut [Color]) {
+        self.ppu.copy_palettes(out);
+    }
+
+    fn render_frame<R: Renderer>(&self, renderer: &mut R) -> Result<(), R::Err> {
+        renderer.render_frame(
+            self.ppu.frame_buffer(),
+            ppu::FRAME_SIZE,
+            self.config.aspect_ratio.to_pixel_aspect_ratio(),
+        )
+    }
+
+    // While halted, nothing can happen until the PPU or a timer raises an interrupt
+    fn halt_cycles(&self) -> u32 {
+        let ppu_cycles = self.ppu.cycles_until_next_event();
+        self.timers.cycles_until_next_overflow().map_or(ppu_cycles, |cycles| cycles.min(ppu_cycles))
+    }
+}
+
+fn skip_bios_intro(cpu: &mut Arm7Tdmi, memory: &mut Memory) {
+    let mut registers = cpu.registers().clone();
+
+    registers.svc_r13_r14[0] = SP_SVC;
+    registers.irq_r13_r14[0] = SP_IRQ;
+    registers.usr_r13_r14[0] = SP_USR;
+    registers.change_mode(CpuMode::System);
+    registers.cpsr.irq_disabled = false;
+    registers.cpsr.fiq_disabled = false;
+    registers.r[15] = CARTRIDGE_ROM_START;
+
+    cpu.set_registers(registers);
+    memory.write_post_boot_flag(1);
+}
+
+impl EmulatorTrait for GbaEmulator {
+    type Inputs = GbaInputs;
+    type Config = GbaEmulatorConfig;
+    const STATE_FORMAT: StateFormat = StateFormat { core: "gba", version: 1 };
+    type Err<
+        RErr: Debug + Display + Send + Sync + 'static,
+        AErr: Debug + Display + Send + Sync + 'static,
+    > = GbaError<RErr, AErr>;
+
+    fn tick<R, A>(
+        &mut self,
+        renderer: &mut R,
+        audio_output: &mut A,
+        inputs: &Self::Inputs,
+    ) -> TickResult<Self::Err<R::Err, A::Err>>
+    where
+        R: Renderer,
+        R::Err: Debug + Display + Send + Sync + 'static,
+        A: AudioOutput,
+        A::Err: Debug + Display + Send + Sync + 'static,
+    {
+        self.input_state.set_inputs(*inputs);
+
+        let halt_cycles = self.halt_cycles();
+        let mut bus = new_bus!(self);
+        if bus.dma.any_pending() {
+            // DMA stalls the CPU until all pending transfers complete
+            bus.run_dma();
+        } else if bus.interrupts.halted() {
+            bus.cycles = halt_cycles;
+        } else {
+            self.cpu.execute_instruction(&mut bus);
+        }
+        let cycles = bus.cycles;
+
+        self.timers.tick(cycles, &mut self.interrupts, &mut self.apu);
+        self.apu.tick(cycles, &mut self.audio_resampler);
+        self.ppu.tick(cycles, &mut self.interrupts, &mut self.dma);
+        self.dma.check_fifo_requests(&mut self.apu);
+
+        if self.input_state.irq_condition_met() {
+            self.interrupts.set_flag(InterruptType::Keypad);
+        }
+        self.interrupts.update_halt();
+
+        if self.ppu.frame_complete() {
+            self.ppu.clear_frame_complete();
+
+            self.render_frame(renderer).map_err(GbaError::Rendering)?;
+            self.audio_resampler.output_samples(audio_output).map_err(GbaError::Audio)?;
+
+            Ok(TickEffect::FrameRendered)
+        } else {
+            Ok(TickEffect::None)
+        }
+    }
+
+    fn save_dirty(&self) -> bool {
+        self.cartridge.save_dirty()
+    }
+
+    fn persist_save<S: SaveWriter>(&mut self, save_writer: &mut S) -> Result<(), S::Err> {
+        if let Some(save_memory) = self.cartridge.save_memory() {
+            if self.cartridge.save_dirty() {
+                save_writer.persist_bytes("sav", save_memory)?;
+                self.cartridge.clear_save_dirty();
+            }
+        }
+
+        Ok(())
+    }
+
+    fn force_render<R>(&mut self, renderer: &mut R) -> Result<(), R::Err>
+    where
+        R: Renderer,
+    {
+        self.render_frame(renderer)
+    }
+
+    fn reload_config(&mut self, config: &Self::Config) {
+        self.config = *config;
+        self.audio_resampler.set_quality(config.audio_resampler_quality);
+    }
+
+    fn take_rom_from(&mut self, other: &mut Self) {
+        self.cartridge.take_rom_from(&mut other.cartridge);
+        self.memory.take_bios_from(&mut other.memory);
+    }
+
+    fn soft_reset(&mut self) {
+        log::warn!("The Game Boy Advance does not support soft reset except in software");
+    }
+
+    fn hard_reset<S: SaveWriter>(&mut self, save_writer: &mut S) {
+        let rom = self.cartridge.take_rom();
+        let bios = self.memory.take_bios();
+
+        *self = Self::create(rom, bios, self.config, save_writer)
+            .expect("Hard reset should never fail to load cartridge");
+    }
+
+    fn timing_mode(&self) -> TimingMode {
+        TimingMode::Ntsc
+    }
+}
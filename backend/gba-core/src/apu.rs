@@ -0,0 +1,357 @@
+//! GBA APU (audio processing unit)
+//!
+//! The GBA has two 8-bit Direct Sound channels fed by 32-byte FIFOs, which are refilled by DMA and
+//! drained on timer 0/1 overflows, plus the Game Boy's four PSG channels. Only the two pulse
+//! channels are emulated out of the PSG channels; the wavetable and noise channel registers are
+//! readable and writable but those channels are silent.
+
+mod pulse;
+
+use crate::apu::pulse::PulseChannel;
+use crate::audio::AudioResampler;
+use bincode::{Decode, Encode};
+use jgenesis_common::num::GetBit;
+
+// The APU mixes a sample every 64 cycles
+const SAMPLE_DIVIDER: u32 = 64;
+
+// PSG frequency timers are clocked at 1.048576 MHz
+const PSG_DIVIDER: u32 = 16;
+
+// The frame sequencer is clocked at 512 Hz
+const FRAME_SEQUENCER_DIVIDER: u32 = 32768;
+
+const FIFO_LEN: usize = 32;
+
+// A FIFO requests DMA when it is half empty or emptier
+const FIFO_DMA_THRESHOLD: u8 = 16;
+
+#[derive(Debug, Clone, Default, Encode, Decode)]
+struct DirectSoundChannel {
+    fifo: [i8; FIFO_LEN],
+    fifo_start: u8,
+    fifo_len: u8,
+    sample: i8,
+    full_volume: bool,
+    enabled_r: bool,
+    enabled_l: bool,
+    timer: usize,
+    dma_requested: bool,
+}
+
+impl DirectSoundChannel {
+    fn push(&mut self, value: u8) {
+        if usize::from(self.fifo_len) == FIFO_LEN {
+            log::trace!("Direct Sound FIFO overflow");
+            return;
+        }
+
+        let idx = (usize::from(self.fifo_start) + usize::from(self.fifo_len)) % FIFO_LEN;
+        self.fifo[idx] = value as i8;
+        self.fifo_len += 1;
+    }
+
+    fn pop(&mut self) {
+        if self.fifo_len != 0 {
+            self.sample = self.fifo[usize::from(self.fifo_start)];
+            self.fifo_start = ((usize::from(self.fifo_start) + 1) % FIFO_LEN) as u8;
+            self.fifo_len -= 1;
+        }
+
+        if self.fifo_len <= FIFO_DMA_THRESHOLD {
+            self.dma_requested = true;
+        }
+    }
+
+    fn reset_fifo(&mut self) {
+        self.fifo_start = 0;
+        self.fifo_len = 0;
+    }
+
+    fn output(&self) -> i32 {
+        let sample = i32::from(self.sample);
+        if self.full_volume { 2 * sample } else { sample }
+    }
+}
+
+#[derive(Debug, Clone, Encode, Decode)]
+pub struct Apu {
+    enabled: bool,
+    pulse_1: PulseChannel,
+    pulse_2: PulseChannel,
+    direct_sound: [DirectSoundChannel; 2],
+    psg_volume_r: u8,
+    psg_volume_l: u8,
+    psg_enabled_r: [bool; 4],
+    psg_enabled_l: [bool; 4],
+    // 0 = 25%, 1 = 50%, 2 = 100%
+    psg_volume_shift: u8,
+    bias: u16,
+    bias_raw: u16,
+    // Wavetable and noise registers, and wave RAM
+    unemulated_registers: [u8; 0x40],
+    sample_counter: u32,
+    psg_counter: u32,
+    frame_sequencer_counter: u32,
+    frame_sequencer_step: u8,
+}
+
+impl Apu {
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            pulse_1: PulseChannel::new(),
+            pulse_2: PulseChannel::new(),
+            direct_sound: [DirectSoundChannel::default(), DirectSoundChannel::default()],
+            psg_volume_r: 0,
+            psg_volume_l: 0,
+            psg_enabled_r: [false; 4],
+            psg_enabled_l: [false; 4],
+            psg_volume_shift: 0,
+            bias: 0x200,
+            bias_raw: 0x200,
+            unemulated_registers: [0; 0x40],
+            sample_counter: 0,
+            psg_counter: 0,
+            frame_sequencer_counter: 0,
+            frame_sequencer_step: 0,
+        }
+    }
+
+    pub fn tick(&mut self, cycles: u32, resampler: &mut AudioResampler) {
+        if self.enabled {
+            self.psg_counter += cycles;
+            let psg_cycles = self.psg_counter / PSG_DIVIDER;
+            self.psg_counter %= PSG_DIVIDER;
+            self.pulse_1.tick(psg_cycles);
+            self.pulse_2.tick(psg_cycles);
+
+            self.frame_sequencer_counter += cycles;
+            while self.frame_sequencer_counter >= FRAME_SEQUENCER_DIVIDER {
+                self.frame_sequencer_counter -= FRAME_SEQUENCER_DIVIDER;
+                self.clock_frame_sequencer();
+            }
+        }
+
+        self.sample_counter += cycles;
+        while self.sample_counter >= SAMPLE_DIVIDER {
+            self.sample_counter -= SAMPLE_DIVIDER;
+
+            let (sample_l, sample_r) = self.mix_sample();
+            resampler.collect_sample(sample_l, sample_r);
+        }
+    }
+
+    fn clock_frame_sequencer(&mut self) {
+        if !self.frame_sequencer_step.bit(0) {
+            self.pulse_1.clock_length_counter();
+            self.pulse_2.clock_length_counter();
+        }
+
+        if self.frame_sequencer_step == 2 || self.frame_sequencer_step == 6 {
+            self.pulse_1.clock_sweep();
+        }
+
+        if self.frame_sequencer_step == 7 {
+            self.pulse_1.clock_envelope();
+            self.pulse_2.clock_envelope();
+        }
+
+        self.frame_sequencer_step = (self.frame_sequencer_step + 1) & 7;
+    }
+
+    fn mix_sample(&self) -> (f64, f64) {
+        if !self.enabled {
+            return (0.0, 0.0);
+        }
+
+        let pulse_samples = [self.pulse_1.sample(), self.pulse_2.sample()];
+        let psg_output = |enabled: [bool; 4], volume: u8| -> i32 {
+            let sum: i32 = pulse_samples
+                .into_iter()
+                .zip(enabled)
+                .filter_map(|(sample, enabled)| enabled.then_some(i32::from(sample)))
+                .sum();
+            (sum * i32::from(volume + 1)) >> (2 - self.psg_volume_shift)
+        };
+
+        let mut sample_l = psg_output(self.psg_enabled_l, self.psg_volume_l);
+        let mut sample_r = psg_output(self.psg_enabled_r, self.psg_volume_r);
+
+        for channel in &self.direct_sound {
+            if channel.enabled_l {
+                sample_l += channel.output();
+            }
+            if channel.enabled_r {
+                sample_r += channel.output();
+            }
+        }
+
+        // Output is a 10-bit value centered at the bias level
+        let bias = i32::from(self.bias);
+        let to_f64 = |sample: i32| -> f64 {
+            let clamped = (sample + bias).clamp(0, 0x3FF);
+            f64::from(clamped - bias) / 512.0
+        };
+
+        (to_f64(sample_l), to_f64(sample_r))
+    }
+
+    pub fn timer_overflow(&mut self, timer: usize, overflows: u32) {
+        for channel in &mut self.direct_sound {
+            if channel.timer == timer {
+                for _ in 0..overflows.min(FIFO_LEN as u32) {
+                    channel.pop();
+                }
+            }
+        }
+    }
+
+    /// Returns true and clears the request if the given Direct Sound FIFO (0 = A, 1 = B) has
+    /// requested a DMA refill.
+    pub fn take_fifo_dma_request(&mut self, fifo: usize) -> bool {
+        std::mem::take(&mut self.direct_sound[fifo].dma_requested)
+    }
+
+    pub fn read_register(&self, address: u32) -> u8 {
+        match address & 0xFF {
+            0x60 => self.pulse_1.read_sweep(),
+            0x62 => self.pulse_1.read_duty(),
+            0x63 => self.pulse_1.read_envelope(),
+            0x65 => self.pulse_1.read_control(),
+            0x68 => self.pulse_2.read_duty(),
+            0x69 => self.pulse_2.read_envelope(),
+            0x6D => self.pulse_2.read_control(),
+            address @ (0x70..=0x7F | 0x90..=0x9F) => self.read_unemulated(address),
+            0x80 => self.psg_volume_r | (self.psg_volume_l << 4),
+            0x81 => {
+                let enabled_r = nibble_from_flags(self.psg_enabled_r);
+                let enabled_l = nibble_from_flags(self.psg_enabled_l);
+                enabled_r | (enabled_l << 4)
+            }
+            0x82 => {
+                self.psg_volume_shift
+                    | (u8::from(self.direct_sound[0].full_volume) << 2)
+                    | (u8::from(self.direct_sound[1].full_volume) << 3)
+            }
+            0x83 => {
+                let [a, b] = &self.direct_sound;
+                u8::from(a.enabled_r)
+                    | (u8::from(a.enabled_l) << 1)
+                    | ((a.timer as u8) << 2)
+                    | (u8::from(b.enabled_r) << 4)
+                    | (u8::from(b.enabled_l) << 5)
+                    | ((b.timer as u8) << 6)
+            }
+            0x84 => {
+                (u8::from(self.enabled) << 7)
+                    | (u8::from(self.pulse_2.enabled()) << 1)
+                    | u8::from(self.pulse_1.enabled())
+            }
+            0x88 => self.bias_raw as u8,
+            0x89 => (self.bias_raw >> 8) as u8,
+            _ => 0,
+        }
+    }
+
+    pub fn write_register(&mut self, address: u32, value: u8) {
+        let address = address & 0xFF;
+
+        // PSG registers cannot be written while the APU is disabled
+        if !self.enabled && (0x60..0x80).contains(&address) {
+            return;
+        }
+
+        match address {
+            0x60 => self.pulse_1.write_sweep(value),
+            0x62 => self.pulse_1.write_length_duty(value),
+            0x63 => self.pulse_1.write_envelope(value),
+            0x64 => self.pulse_1.write_frequency_low(value),
+            0x65 => self.pulse_1.write_control(value),
+            0x68 => self.pulse_2.write_length_duty(value),
+            0x69 => self.pulse_2.write_envelope(value),
+            0x6C => self.pulse_2.write_frequency_low(value),
+            0x6D => self.pulse_2.write_control(value),
+            0x70..=0x7F | 0x90..=0x9F => self.write_unemulated(address, value),
+            0x80 => {
+                self.psg_volume_r = value & 7;
+                self.psg_volume_l = (value >> 4) & 7;
+            }
+            0x81 => {
+                self.psg_enabled_r = flags_from_nibble(value);
+                self.psg_enabled_l = flags_from_nibble(value >> 4);
+            }
+            0x82 => {
+                // Volume 3 is prohibited; treat it as 100%
+                self.psg_volume_shift = (value & 3).min(2);
+                self.direct_sound[0].full_volume = value.bit(2);
+                self.direct_sound[1].full_volume = value.bit(3);
+            }
+            0x83 => {
+                for (i, channel) in self.direct_sound.iter_mut().enumerate() {
+                    let bits = value >> (4 * i);
+                    channel.enabled_r = bits.bit(0);
+                    channel.enabled_l = bits.bit(1);
+                    channel.timer = bits.bit(2).into();
+                    if bits.bit(3) {
+                        channel.reset_fifo();
+                    }
+                }
+            }
+            0x84 => self.write_master_enable(value.bit(7)),
+            0x88 => self.write_bias((self.bias_raw & 0xFF00) | u16::from(value)),
+            0x89 => self.write_bias((self.bias_raw & 0x00FF) | (u16::from(value) << 8)),
+            0xA0..=0xA3 => self.direct_sound[0].push(value),
+            0xA4..=0xA7 => self.direct_sound[1].push(value),
+            _ => {}
+        }
+
+        log::trace!("APU register write: {address:02X} {value:02X}");
+    }
+
+    fn write_master_enable(&mut self, enabled: bool) {
+        if self.enabled && !enabled {
+            // Disabling the APU resets all PSG registers
+            self.pulse_1 = PulseChannel::new();
+            self.pulse_2 = PulseChannel::new();
+            self.psg_volume_r = 0;
+            self.psg_volume_l = 0;
+            self.psg_enabled_r = [false; 4];
+            self.psg_enabled_l = [false; 4];
+            self.unemulated_registers[..0x20].fill(0);
+        } else if !self.enabled && enabled {
+            self.frame_sequencer_step = 0;
+            self.frame_sequencer_counter = 0;
+        }
+
+        self.enabled = enabled;
+    }
+
+    fn write_bias(&mut self, value: u16) {
+        self.bias_raw = value & 0xC3FE;
+        self.bias = value & 0x03FE;
+    }
+
+    fn unemulated_register_idx(address: u32) -> usize {
+        match address {
+            0x70..=0x7F => (address - 0x70) as usize,
+            _ => (address - 0x90 + 0x20) as usize,
+        }
+    }
+
+    fn read_unemulated(&self, address: u32) -> u8 {
+        self.unemulated_registers[Self::unemulated_register_idx(address)]
+    }
+
+    fn write_unemulated(&mut self, address: u32, value: u8) {
+        self.unemulated_registers[Self::unemulated_register_idx(address)] = value;
+    }
+}
+
+fn flags_from_nibble(value: u8) -> [bool; 4] {
+    [value.bit(0), value.bit(1), value.bit(2), value.bit(3)]
+}
+
+fn nibble_from_flags(flags: [bool; 4]) -> u8 {
+    flags.into_iter().enumerate().map(|(i, flag)| u8::from(flag) << i).sum()
+}
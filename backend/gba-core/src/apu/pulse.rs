@@ -0,0 +1,233 @@
+//! Legacy Game Boy pulse channels (channels 1 and 2)
+
+use bincode::{Decode, Encode};
+use jgenesis_common::num::GetBit;
+
+// 12.5%, 25%, 50%, 75%
+const DUTY_WAVEFORMS: [u8; 4] = [0b1000_0000, 0b1000_0001, 0b1110_0001, 0b0111_1110];
+
+#[derive(Debug, Clone, Default, Encode, Decode)]
+struct Sweep {
+    period: u8,
+    negate: bool,
+    shift: u8,
+    enabled: bool,
+    counter: u8,
+    shadow_frequency: u16,
+}
+
+impl Sweep {
+    fn reload_counter(&mut self) {
+        self.counter = if self.period == 0 { 8 } else { self.period };
+    }
+
+    fn next_frequency(&self) -> u16 {
+        let delta = self.shadow_frequency >> self.shift;
+        if self.negate {
+            self.shadow_frequency.wrapping_sub(delta)
+        } else {
+            self.shadow_frequency + delta
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Encode, Decode)]
+struct Envelope {
+    initial_volume: u8,
+    increasing: bool,
+    period: u8,
+    volume: u8,
+    counter: u8,
+}
+
+impl Envelope {
+    fn write(&mut self, value: u8) {
+        self.initial_volume = value >> 4;
+        self.increasing = value.bit(3);
+        self.period = value & 7;
+    }
+
+    fn read(&self) -> u8 {
+        (self.initial_volume << 4) | (u8::from(self.increasing) << 3) | self.period
+    }
+
+    fn trigger(&mut self) {
+        self.volume = self.initial_volume;
+        self.counter = self.period;
+    }
+
+    fn clock(&mut self) {
+        if self.period == 0 {
+            return;
+        }
+
+        // The period can change after the counter is loaded, so the counter may already be 0
+        self.counter = self.counter.saturating_sub(1);
+        if self.counter == 0 {
+            self.counter = self.period;
+            if self.increasing && self.volume < 15 {
+                self.volume += 1;
+            } else if !self.increasing && self.volume > 0 {
+                self.volume -= 1;
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Encode, Decode)]
+pub struct PulseChannel {
+    sweep: Sweep,
+    envelope: Envelope,
+    duty: u8,
+    length_counter: u8,
+    length_enabled: bool,
+    frequency: u16,
+    timer_counter: u32,
+    phase: u8,
+    channel_enabled: bool,
+    dac_enabled: bool,
+}
+
+impl PulseChannel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // NR10 / SOUND1CNT_L bits 0-7
+    pub fn read_sweep(&self) -> u8 {
+        (self.sweep.period << 4) | (u8::from(self.sweep.negate) << 3) | self.sweep.shift
+    }
+
+    pub fn write_sweep(&mut self, value: u8) {
+        self.sweep.period = (value >> 4) & 7;
+        self.sweep.negate = value.bit(3);
+        self.sweep.shift = value & 7;
+    }
+
+    // NR11 / NR21: length is write-only
+    pub fn read_duty(&self) -> u8 {
+        self.duty << 6
+    }
+
+    pub fn write_length_duty(&mut self, value: u8) {
+        self.duty = value >> 6;
+        self.length_counter = 64 - (value & 0x3F);
+    }
+
+    // NR12 / NR22
+    pub fn read_envelope(&self) -> u8 {
+        self.envelope.read()
+    }
+
+    pub fn write_envelope(&mut self, value: u8) {
+        self.envelope.write(value);
+        self.dac_enabled = value & 0xF8 != 0;
+        if !self.dac_enabled {
+            self.channel_enabled = false;
+        }
+    }
+
+    // NR13 / NR23: frequency is write-only
+    pub fn write_frequency_low(&mut self, value: u8) {
+        self.frequency = (self.frequency & 0x0700) | u16::from(value);
+    }
+
+    // NR14 / NR24
+    pub fn read_control(&self) -> u8 {
+        u8::from(self.length_enabled) << 6
+    }
+
+    pub fn write_control(&mut self, value: u8) {
+        self.frequency = (self.frequency & 0x00FF) | (u16::from(value & 7) << 8);
+        self.length_enabled = value.bit(6);
+
+        if value.bit(7) {
+            self.trigger();
+        }
+    }
+
+    fn trigger(&mut self) {
+        self.channel_enabled = self.dac_enabled;
+        if self.length_counter == 0 {
+            self.length_counter = 64;
+        }
+        self.timer_counter = self.timer_period();
+        self.envelope.trigger();
+
+        self.sweep.shadow_frequency = self.frequency;
+        self.sweep.reload_counter();
+        self.sweep.enabled = self.sweep.period != 0 || self.sweep.shift != 0;
+        if self.sweep.shift != 0 && self.sweep.next_frequency() > 2047 {
+            self.channel_enabled = false;
+        }
+    }
+
+    fn timer_period(&self) -> u32 {
+        2048 - u32::from(self.frequency)
+    }
+
+    /// Advance the frequency timer by the given number of 1.048576 MHz PSG cycles.
+    pub fn tick(&mut self, psg_cycles: u32) {
+        let mut remaining = psg_cycles;
+        while remaining >= self.timer_counter {
+            remaining -= self.timer_counter;
+            self.timer_counter = self.timer_period();
+            self.phase = (self.phase + 1) & 7;
+        }
+        self.timer_counter -= remaining;
+    }
+
+    pub fn clock_length_counter(&mut self) {
+        if self.length_enabled && self.length_counter != 0 {
+            self.length_counter -= 1;
+            if self.length_counter == 0 {
+                self.channel_enabled = false;
+            }
+        }
+    }
+
+    pub fn clock_envelope(&mut self) {
+        self.envelope.clock();
+    }
+
+    pub fn clock_sweep(&mut self) {
+        if !self.sweep.enabled {
+            return;
+        }
+
+        self.sweep.counter -= 1;
+        if self.sweep.counter != 0 {
+            return;
+        }
+        self.sweep.reload_counter();
+
+        if self.sweep.period == 0 {
+            return;
+        }
+
+        let next_frequency = self.sweep.next_frequency();
+        if next_frequency > 2047 {
+            self.channel_enabled = false;
+        } else if self.sweep.shift != 0 {
+            self.sweep.shadow_frequency = next_frequency;
+            self.frequency = next_frequency;
+
+            if self.sweep.next_frequency() > 2047 {
+                self.channel_enabled = false;
+            }
+        }
+    }
+
+    /// Current digital output, 0-15.
+    pub fn sample(&self) -> u8 {
+        if !self.channel_enabled {
+            return 0;
+        }
+
+        u8::from(DUTY_WAVEFORMS[usize::from(self.duty)].bit(self.phase)) * self.envelope.volume
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.channel_enabled
+    }
+}
@@ -0,0 +1,90 @@
+//! GBA audio resampling code
+
+#![allow(clippy::excessive_precision)]
+
+use bincode::{Decode, Encode};
+use jgenesis_common::audio::{ResamplerQuality, SignalResampler};
+use jgenesis_common::frontend::AudioOutput;
+
+// The APU mixes one output sample every 64 cycles of the 16.78 MHz system clock
+pub const APU_SAMPLE_FREQUENCY: f64 = crate::api::GBA_CLOCK_FREQUENCY / 64.0;
+
+// Hamming-windowed sinc low-pass filter, equivalent to Octave's
+// `fir1(35, 24000 / (262144 / 2), 'low')`
+const LPF_COEFFICIENT_0: f64 = -0.0008708227602389845;
+const LPF_COEFFICIENTS: [f64; 36] = [
+    -0.0008708227602389845,
+    -0.00011235452000344631,
+    0.001092576197807136,
+    0.0028136492970458867,
+    0.004544280829006551,
+    0.005077098289686246,
+    0.002904453696475182,
+    -0.0029020729177246106,
+    -0.011692134987481754,
+    -0.02065499525098664,
+    -0.025117314322203668,
+    -0.01984647443405359,
+    -0.001020710460553115,
+    0.03185608528154534,
+    0.07492328468752485,
+    0.12039010285860458,
+    0.15845644182492877,
+    0.18015890669062143,
+    0.18015890669062143,
+    0.15845644182492877,
+    0.12039010285860458,
+    0.07492328468752486,
+    0.03185608528154535,
+    -0.001020710460553115,
+    -0.019846474434053598,
+    -0.02511731432220367,
+    -0.020654995250986644,
+    -0.011692134987481756,
+    -0.0029020729177246115,
+    0.0029044536964751835,
+    0.005077098289686248,
+    0.004544280829006554,
+    0.002813649297045888,
+    0.0010925761978071366,
+    -0.00011235452000344631,
+    -0.0008708227602389845,
+];
+
+const HPF_CHARGE_FACTOR: f64 = 0.9993282115559676;
+
+type GbaResampler = SignalResampler<36, 0>;
+
+#[derive(Debug, Clone, Encode, Decode)]
+pub struct AudioResampler {
+    resampler: GbaResampler,
+}
+
+impl AudioResampler {
+    pub fn new(quality: ResamplerQuality) -> Self {
+        let mut resampler = GbaResampler::new(
+            APU_SAMPLE_FREQUENCY,
+            LPF_COEFFICIENT_0,
+            LPF_COEFFICIENTS,
+            HPF_CHARGE_FACTOR,
+        );
+        resampler.set_quality(quality);
+        Self { resampler }
+    }
+
+    pub fn set_quality(&mut self, quality: ResamplerQuality) {
+        self.resampler.set_quality(quality);
+    }
+
+    pub fn collect_sample(&mut self, sample_l: f64, sample_r: f64) {
+        self.resampler.collect_sample(sample_l, sample_r);
+    }
+
+    pub fn output_samples<A: AudioOutput>(&mut self, audio_output: &mut A) -> Result<(), A::Err> {
+        while let Some((sample_l, sample_r)) = self.resampler.output_buffer_pop_front() {
+            audio_output.push_sample(sample_l, sample_r)?;
+        }
+
+        Ok(())
+    }
+}
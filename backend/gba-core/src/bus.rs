@@ -0,0 +1,447 @@
+//! GBA bus / address mapping
+//!
+//! The ARM7TDMI has a 32-bit address space, of which the GBA decodes bits 24-27:
+//! * $00: 16KB BIOS ROM
+//! * $02: 256KB external work RAM (16-bit bus, 2 waitstates)
+//! * $03: 32KB internal work RAM
+//! * $04: I/O registers
+//! * $05: 1KB palette RAM (16-bit bus)
+//! * $06: 96KB VRAM (16-bit bus)
+//! * $07: 1KB OAM
+//! * $08-$0D: Cartridge ROM, mirrored 3 times with different waitstate settings; EEPROM is also
+//!   mapped into $0D if present
+//! * $0E-$0F: Cartridge SRAM / flash (8-bit bus)
+
+use crate::apu::Apu;
+use crate::cartridge::Cartridge;
+use crate::dma::DmaController;
+use crate::input::InputState;
+use crate::interrupts::InterruptRegisters;
+use crate::memory::{Memory, BIOS_LEN};
+use crate::ppu::Ppu;
+use crate::timers::Timers;
+use arm7tdmi_emu::{BusInterface, MemoryCycle};
+use jgenesis_common::num::GetBit;
+
+// EWRAM accesses take 3 cycles per 16 bits
+const EWRAM_ACCESS_CYCLES: u32 = 3;
+
+// DMA transfers take 2 internal cycles to start
+const DMA_START_CYCLES: u32 = 2;
+
+pub struct Bus<'a> {
+    pub memory: &'a mut Memory,
+    pub ppu: &'a mut Ppu,
+    pub apu: &'a mut Apu,
+    pub dma: &'a mut DmaController,
+    pub timers: &'a mut Timers,
+    pub cartridge: &'a mut Cartridge,
+    pub interrupts: &'a mut InterruptRegisters,
+    pub input_state: &'a mut InputState,
+    /// Whether the CPU is currently executing from the BIOS
+    pub bios_readable: bool,
+    /// Cycles elapsed since the bus was created
+    pub cycles: u32,
+}
+
+impl Bus<'_> {
+    // Cartridge ROM is split into 3 waitstate regions: $08-$09, $0A-$0B, and $0C-$0D
+    fn rom_region(address: u32) -> usize {
+        (((address >> 24) - 0x08) >> 1) as usize
+    }
+
+    // Approximates the cartridge prefetch buffer by assuming sequential opcode fetches from ROM
+    // have always been prefetched
+    fn prefetched(&self, address: u32, cycle: MemoryCycle) -> bool {
+        cycle == MemoryCycle::S
+            && self.memory.wait_control.prefetch_enabled()
+            && (0x08..=0x0D).contains(&(address >> 24))
+            && !self.cartridge.is_eeprom_address(address)
+    }
+
+    fn read_io_halfword(&mut self, address: u32) -> u16 {
+        let address = address & 0x3FE;
+        match address {
+            0x000..=0x05F => self.ppu.read_register(address).unwrap_or(0),
+            0x060..=0x0A7 => u16::from_le_bytes([
+                self.apu.read_register(address),
+                self.apu.read_register(address + 1),
+            ]),
+            0x0B0..=0x0DF => {
+                let i = ((address - 0x0B0) / 12) as usize;
+                if (address - 0x0B0) % 12 == 10 { self.dma.read_control(i) } else { 0 }
+            }
+            0x100..=0x10F => {
+                let i = ((address >> 2) & 3) as usize;
+                if address.bit(1) {
+                    self.timers.read_control(i)
+                } else {
+                    self.timers.read_counter(i)
+                }
+            }
+            0x130 => self.input_state.read_keyinput(),
+            0x132 => self.input_state.read_keycnt(),
+            0x200 => self.interrupts.read_enabled(),
+            0x202 => self.interrupts.read_flags(),
+            0x204 => self.memory.wait_control.read(),
+            0x208 => self.interrupts.read_master_enabled(),
+            0x300 => self.memory.post_boot_flag().into(),
+            _ => {
+                log::trace!("Unmapped I/O read: {address:03X}");
+                0
+            }
+        }
+    }
+
+    fn write_io_halfword(&mut self, address: u32, value: u16) {
+        let address = address & 0x3FE;
+        self.memory.set_io_shadow(address, value);
+
+        match address {
+            0x000..=0x05F => self.ppu.write_register(address, value),
+            0x060..=0x0A7 => {
+                let [lsb, msb] = value.to_le_bytes();
+                self.apu.write_register(address, lsb);
+                self.apu.write_register(address + 1, msb);
+            }
+            0x0B0..=0x0DF => {
+                let i = ((address - 0x0B0) / 12) as usize;
+                match (address - 0x0B0) % 12 {
+                    0 => self.dma.write_source(i, value, false),
+                    2 => self.dma.write_source(i, value, true),
+                    4 => self.dma.write_destination(i, value, false),
+                    6 => self.dma.write_destination(i, value, true),
+                    8 => self.dma.write_length(i, value),
+                    10 => self.dma.write_control(i, value),
+                    _ => unreachable!("DMA register offsets are always even"),
+                }
+            }
+            0x100..=0x10F => {
+                let i = ((address >> 2) & 3) as usize;
+                if address.bit(1) {
+                    self.timers.write_control(i, value);
+                } else {
+                    self.timers.write_reload(i, value);
+                }
+            }
+            0x132 => self.input_state.write_keycnt(value),
+            0x200 => self.interrupts.write_enabled(value),
+            0x202 => self.interrupts.acknowledge(value),
+            0x204 => self.memory.wait_control.write(value),
+            0x208 => self.interrupts.write_master_enabled(value),
+            0x300 => {
+                self.memory.write_post_boot_flag(value as u8);
+                self.write_haltcnt((value >> 8) as u8);
+            }
+            _ => log::trace!("Unmapped I/O write: {address:03X} {value:04X}"),
+        }
+    }
+
+    fn write_io_byte(&mut self, address: u32, value: u8) {
+        let address = address & 0x3FF;
+        match address {
+            0x060..=0x0A7 => self.apu.write_register(address, value),
+            // Writing 0 bits to IF has no effect, so there is no need to merge with the other byte
+            0x202 | 0x203 => self.interrupts.acknowledge(u16::from(value) << (8 * (address & 1))),
+            0x300 => self.memory.write_post_boot_flag(value),
+            0x301 => self.write_haltcnt(value),
+            _ => {
+                // Registers with hardware-modified bits merge with the current value; all other
+                // registers merge with the last written value because many are write-only
+                let aligned = address & !1;
+                let is_dma_control =
+                    (0x0B0..0x0E0).contains(&aligned) && (aligned - 0x0B0) % 12 == 10;
+                let current = if is_dma_control || aligned == 0x200 || aligned == 0x208 {
+                    self.read_io_halfword(aligned)
+                } else {
+                    self.memory.io_shadow(aligned)
+                };
+
+                let merged = if address.bit(0) {
+                    (current & 0x00FF) | (u16::from(value) << 8)
+                } else {
+                    (current & 0xFF00) | u16::from(value)
+                };
+                self.write_io_halfword(aligned, merged);
+            }
+        }
+    }
+
+    // $4000301: HALTCNT
+    fn write_haltcnt(&mut self, value: u8) {
+        if value.bit(7) {
+            log::warn!("Stop mode is not emulated; halting instead");
+        }
+        self.interrupts.halt();
+    }
+
+    fn read_halfword_inner(&mut self, address: u32, cycle: MemoryCycle) -> u16 {
+        match address >> 24 {
+            0x00 => {
+                self.cycles += 1;
+                if address < BIOS_LEN as u32 {
+                    let word = self.memory.read_bios_word(address, self.bios_readable);
+                    (word >> (8 * (address & 2))) as u16
+                } else {
+                    0
+                }
+            }
+            0x02 => {
+                self.cycles += EWRAM_ACCESS_CYCLES;
+                self.memory.read_ewram::<2>(address) as u16
+            }
+            0x03 => {
+                self.cycles += 1;
+                self.memory.read_iwram::<2>(address) as u16
+            }
+            0x04 => {
+                self.cycles += 1;
+                self.read_io_halfword(address)
+            }
+            0x05 => {
+                self.cycles += 1;
+                self.ppu.read_palette_ram::<2>(address) as u16
+            }
+            0x06 => {
+                self.cycles += 1;
+                self.ppu.read_vram::<2>(address) as u16
+            }
+            0x07 => {
+                self.cycles += 1;
+                self.ppu.read_oam::<2>(address) as u16
+            }
+            0x08..=0x0D => {
+                self.cycles +=
+                    self.memory.wait_control.rom_access_cycles(Self::rom_region(address), cycle);
+                if self.cartridge.is_eeprom_address(address) {
+                    self.cartridge.read_eeprom()
+                } else {
+                    self.cartridge.read_rom_halfword(address)
+                }
+            }
+            0x0E..=0x0F => {
+                // SRAM has an 8-bit bus; wider reads return the byte repeated
+                self.cycles += self.memory.wait_control.sram_access_cycles();
+                u16::from(self.cartridge.read_sram(address)) * 0x0101
+            }
+            _ => {
+                self.cycles += 1;
+                log::trace!("Unmapped read: {address:08X}");
+                0
+            }
+        }
+    }
+
+    fn write_halfword_inner(&mut self, address: u32, value: u16, cycle: MemoryCycle) {
+        match address >> 24 {
+            0x02 => {
+                self.cycles += EWRAM_ACCESS_CYCLES;
+                self.memory.write_ewram::<2>(address, value.into());
+            }
+            0x03 => {
+                self.cycles += 1;
+                self.memory.write_iwram::<2>(address, value.into());
+            }
+            0x04 => {
+                self.cycles += 1;
+                self.write_io_halfword(address, value);
+            }
+            0x05 => {
+                self.cycles += 1;
+                self.ppu.write_palette_ram::<2>(address, value.into());
+            }
+            0x06 => {
+                self.cycles += 1;
+                self.ppu.write_vram::<2>(address, value.into());
+            }
+            0x07 => {
+                self.cycles += 1;
+                self.ppu.write_oam::<2>(address, value.into());
+            }
+            0x08..=0x0D => {
+                self.cycles +=
+                    self.memory.wait_control.rom_access_cycles(Self::rom_region(address), cycle);
+                if self.cartridge.is_eeprom_address(address) {
+                    self.cartridge.write_eeprom(value);
+                }
+            }
+            0x0E..=0x0F => {
+                self.cycles += self.memory.wait_control.sram_access_cycles();
+                self.cartridge.write_sram(address, (value >> (8 * (address & 1))) as u8);
+            }
+            _ => {
+                self.cycles += 1;
+                log::trace!("Unmapped write: {address:08X} {value:04X}");
+            }
+        }
+    }
+
+    /// Run all pending DMA transfers to completion.
+    pub fn run_dma(&mut self) {
+        while let Some(transfer) = self.dma.next_transfer() {
+            if self.cartridge.is_eeprom_address(transfer.destination) {
+                self.cartridge.notify_eeprom_dma(transfer.length);
+            }
+
+            self.cycles += DMA_START_CYCLES;
+
+            let mut source = transfer.source;
+            let mut destination = transfer.destination;
+            for i in 0..transfer.length {
+                let cycle = if i == 0 { MemoryCycle::N } else { MemoryCycle::S };
+                if transfer.word_size {
+                    let value = self.read_word(source & !3, cycle);
+                    self.write_word(destination & !3, value, cycle);
+                } else {
+                    let value = self.read_halfword(source & !1, cycle);
+                    self.write_halfword(destination & !1, value, cycle);
+                }
+
+                source = source.wrapping_add(transfer.source_step);
+                destination = destination.wrapping_add(transfer.destination_step);
+            }
+
+            self.dma.finish_transfer(transfer, self.interrupts);
+        }
+    }
+}
+
+impl BusInterface for Bus<'_> {
+    fn read_byte(&mut self, address: u32, cycle: MemoryCycle) -> u8 {
+        match address >> 24 {
+            0x04 => {
+                self.cycles += 1;
+                let address = address & 0x3FF;
+                if (0x060..=0x0A7).contains(&address) {
+                    self.apu.read_register(address)
+                } else {
+                    (self.read_io_halfword(address) >> (8 * (address & 1))) as u8
+                }
+            }
+            0x0E..=0x0F => {
+                self.cycles += self.memory.wait_control.sram_access_cycles();
+                self.cartridge.read_sram(address)
+            }
+            _ => (self.read_halfword_inner(address & !1, cycle) >> (8 * (address & 1))) as u8,
+        }
+    }
+
+    fn read_halfword(&mut self, address: u32, cycle: MemoryCycle) -> u16 {
+        self.read_halfword_inner(address, cycle)
+    }
+
+    fn read_word(&mut self, address: u32, cycle: MemoryCycle) -> u32 {
+        match address >> 24 {
+            // 32-bit buses
+            0x00 if address < BIOS_LEN as u32 => {
+                self.cycles += 1;
+                self.memory.read_bios_word(address, self.bios_readable)
+            }
+            0x03 => {
+                self.cycles += 1;
+                self.memory.read_iwram::<4>(address)
+            }
+            0x07 => {
+                self.cycles += 1;
+                self.ppu.read_oam::<4>(address)
+            }
+            0x0E..=0x0F => {
+                self.cycles += self.memory.wait_control.sram_access_cycles();
+                u32::from(self.cartridge.read_sram(address)) * 0x0101_0101
+            }
+            // 16-bit buses; the second access is always sequential
+            _ => {
+                let low = self.read_halfword_inner(address, cycle);
+                let high = self.read_halfword_inner(address | 2, MemoryCycle::S);
+                u32::from(low) | (u32::from(high) << 16)
+            }
+        }
+    }
+
+    fn fetch_opcode_halfword(&mut self, address: u32, cycle: MemoryCycle) -> u16 {
+        self.bios_readable = address < BIOS_LEN as u32;
+        if self.prefetched(address, cycle) {
+            self.cycles += 1;
+            return self.cartridge.read_rom_halfword(address);
+        }
+
+        self.read_halfword(address, cycle)
+    }
+
+    fn fetch_opcode_word(&mut self, address: u32, cycle: MemoryCycle) -> u32 {
+        self.bios_readable = address < BIOS_LEN as u32;
+        if self.prefetched(address, cycle) {
+            self.cycles += 2;
+            let low = self.cartridge.read_rom_halfword(address);
+            let high = self.cartridge.read_rom_halfword(address | 2);
+            return u32::from(low) | (u32::from(high) << 16);
+        }
+
+        self.read_word(address, cycle)
+    }
+
+    fn write_byte(&mut self, address: u32, value: u8, cycle: MemoryCycle) {
+        match address >> 24 {
+            0x02 => {
+                self.cycles += EWRAM_ACCESS_CYCLES;
+                self.memory.write_ewram::<1>(address, value.into());
+            }
+            0x03 => {
+                self.cycles += 1;
+                self.memory.write_iwram::<1>(address, value.into());
+            }
+            0x04 => {
+                self.cycles += 1;
+                self.write_io_byte(address, value);
+            }
+            0x05 => {
+                self.cycles += 1;
+                self.ppu.write_palette_ram_byte(address, value);
+            }
+            0x06 => {
+                self.cycles += 1;
+                self.ppu.write_vram_byte(address, value);
+            }
+            // Byte writes to OAM are ignored
+            0x07 => self.cycles += 1,
+            0x0E..=0x0F => {
+                self.cycles += self.memory.wait_control.sram_access_cycles();
+                self.cartridge.write_sram(address, value);
+            }
+            _ => self.write_halfword_inner(address & !1, u16::from(value) * 0x0101, cycle),
+        }
+    }
+
+    fn write_halfword(&mut self, address: u32, value: u16, cycle: MemoryCycle) {
+        self.write_halfword_inner(address, value, cycle);
+    }
+
+    fn write_word(&mut self, address: u32, value: u32, cycle: MemoryCycle) {
+        match address >> 24 {
+            0x03 => {
+                self.cycles += 1;
+                self.memory.write_iwram::<4>(address, value);
+            }
+            0x07 => {
+                self.cycles += 1;
+                self.ppu.write_oam::<4>(address, value);
+            }
+            0x0E..=0x0F => {
+                self.cycles += self.memory.wait_control.sram_access_cycles();
+                self.cartridge.write_sram(address, (value >> (8 * (address & 3))) as u8);
+            }
+            _ => {
+                self.write_halfword_inner(address, value as u16, cycle);
+                self.write_halfword_inner(address | 2, (value >> 16) as u16, MemoryCycle::S);
+            }
+        }
+    }
+
+    fn internal_cycles(&mut self, cycles: u32) {
+        self.cycles += cycles;
+    }
+
+    fn irq(&self) -> bool {
+        self.interrupts.irq()
+    }
+}
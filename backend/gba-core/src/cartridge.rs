@@ -0,0 +1,245 @@
+//! GBA cartridge ROM and save memory
+//!
+//! Cartridges have no header field describing the save memory type. Nearly all commercial
+//! cartridges were built with Nintendo's SDK, which embeds a library ID string in the ROM such as
+//! `SRAM_V113` or `FLASH1M_V103`, so the save type is detected by searching the ROM for these
+//! strings.
+
+mod eeprom;
+mod flash;
+
+use crate::api::GbaLoadError;
+use crate::cartridge::eeprom::{Eeprom, EepromSize};
+use crate::cartridge::flash::{FlashMemory, FlashSize};
+use bincode::{Decode, Encode};
+use jgenesis_proc_macros::{FakeDecode, FakeEncode, PartialClone};
+use std::ops::Deref;
+
+const MAX_ROM_LEN: usize = 32 * 1024 * 1024;
+const SRAM_LEN: usize = 32 * 1024;
+
+// ROMs larger than 16MB only map EEPROM to the last 256 bytes of the $0D000000 region
+const LARGE_ROM_LEN: usize = 16 * 1024 * 1024;
+
+#[derive(Debug, Clone, Default, FakeEncode, FakeDecode)]
+struct Rom(Box<[u8]>);
+
+impl Deref for Rom {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SaveType {
+    None,
+    Sram,
+    Flash64K,
+    Flash128K,
+    Eeprom,
+}
+
+impl SaveType {
+    pub fn detect(rom: &[u8]) -> Self {
+        const PATTERNS: [(&[u8], SaveType); 6] = [
+            (b"EEPROM_V", SaveType::Eeprom),
+            (b"SRAM_V", SaveType::Sram),
+            (b"SRAM_F_V", SaveType::Sram),
+            (b"FLASH_V", SaveType::Flash64K),
+            (b"FLASH512_V", SaveType::Flash64K),
+            (b"FLASH1M_V", SaveType::Flash128K),
+        ];
+
+        PATTERNS
+            .into_iter()
+            .find_map(|(pattern, save_type)| {
+                rom.windows(pattern.len()).any(|window| window == pattern).then_some(save_type)
+            })
+            .unwrap_or(Self::None)
+    }
+}
+
+#[derive(Debug, Clone, Encode, Decode)]
+enum SaveMemory {
+    None,
+    Sram(Box<[u8]>),
+    Flash(FlashMemory),
+    Eeprom(Eeprom),
+}
+
+#[derive(Debug, Clone, Encode, Decode, PartialClone)]
+pub struct Cartridge {
+    #[partial_clone(default)]
+    rom: Rom,
+    save_memory: SaveMemory,
+    save_dirty: bool,
+}
+
+impl Cartridge {
+    pub fn create(rom: Vec<u8>, initial_save: Option<Vec<u8>>) -> Result<Self, GbaLoadError> {
+        if rom.is_empty() {
+            return Err(GbaLoadError::EmptyRom);
+        }
+
+        if rom.len() > MAX_ROM_LEN {
+            return Err(GbaLoadError::RomTooLarge(rom.len()));
+        }
+
+        let save_type = SaveType::detect(&rom);
+        log::info!("Detected save memory type {save_type:?}");
+
+        let save_memory = match save_type {
+            SaveType::None => SaveMemory::None,
+            SaveType::Sram => {
+                let mut sram = vec![0xFF; SRAM_LEN].into_boxed_slice();
+                if let Some(save) = initial_save.filter(|save| save.len() == SRAM_LEN) {
+                    sram.copy_from_slice(&save);
+                }
+                SaveMemory::Sram(sram)
+            }
+            SaveType::Flash64K => {
+                SaveMemory::Flash(FlashMemory::new(FlashSize::SixtyFourK, initial_save))
+            }
+            SaveType::Flash128K => {
+                SaveMemory::Flash(FlashMemory::new(FlashSize::OneTwentyEightK, initial_save))
+            }
+            SaveType::Eeprom => SaveMemory::Eeprom(Eeprom::new(initial_save)),
+        };
+
+        Ok(Self { rom: Rom(rom.into_boxed_slice()), save_memory, save_dirty: false })
+    }
+
+    pub fn take_rom(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.rom.0).into_vec()
+    }
+
+    pub fn take_rom_from(&mut self, other: &mut Self) {
+        self.rom = std::mem::take(&mut other.rom);
+    }
+
+    pub fn read_rom_halfword(&self, address: u32) -> u16 {
+        let offset = (address & 0x01FF_FFFE) as usize;
+        if offset + 1 < self.rom.len() {
+            u16::from_le_bytes([self.rom[offset], self.rom[offset + 1]])
+        } else {
+            // Reading past the end of ROM returns the low bits of the halfword address
+            (address >> 1) as u16
+        }
+    }
+
+    pub fn is_eeprom_address(&self, address: u32) -> bool {
+        matches!(self.save_memory, SaveMemory::Eeprom(_))
+            && (address >> 24) == 0x0D
+            && (self.rom.len() <= LARGE_ROM_LEN || address & 0x00FF_FF00 == 0x00FF_FF00)
+    }
+
+    pub fn read_eeprom(&mut self) -> u16 {
+        match &mut self.save_memory {
+            SaveMemory::Eeprom(eeprom) => eeprom.read(),
+            _ => 0xFFFF,
+        }
+    }
+
+    pub fn write_eeprom(&mut self, value: u16) {
+        if let SaveMemory::Eeprom(eeprom) = &mut self.save_memory {
+            self.save_dirty |= eeprom.write(value);
+        }
+    }
+
+    /// Called when a DMA transfer to EEPROM starts, to detect the EEPROM size.
+    pub fn notify_eeprom_dma(&mut self, length: u32) {
+        if let SaveMemory::Eeprom(eeprom) = &mut self.save_memory {
+            if !eeprom.size_known() {
+                if let Some(size) = EepromSize::from_dma_length(length) {
+                    eeprom.set_size(size);
+                }
+            }
+        }
+    }
+
+    // $0E000000-$0FFFFFFF
+    pub fn read_sram(&self, address: u32) -> u8 {
+        match &self.save_memory {
+            SaveMemory::Sram(sram) => sram[(address as usize) & (SRAM_LEN - 1)],
+            SaveMemory::Flash(flash) => flash.read(address),
+            SaveMemory::None | SaveMemory::Eeprom(_) => 0xFF,
+        }
+    }
+
+    pub fn write_sram(&mut self, address: u32, value: u8) {
+        match &mut self.save_memory {
+            SaveMemory::Sram(sram) => {
+                sram[(address as usize) & (SRAM_LEN - 1)] = value;
+                self.save_dirty = true;
+            }
+            SaveMemory::Flash(flash) => self.save_dirty |= flash.write(address, value),
+            SaveMemory::None | SaveMemory::Eeprom(_) => {}
+        }
+    }
+
+    pub fn save_memory(&self) -> Option<&[u8]> {
+        match &self.save_memory {
+            SaveMemory::None => None,
+            SaveMemory::Sram(sram) => Some(sram),
+            SaveMemory::Flash(flash) => Some(flash.memory()),
+            SaveMemory::Eeprom(eeprom) => Some(eeprom.memory()),
+        }
+    }
+
+    pub fn save_dirty(&self) -> bool {
+        self.save_dirty
+    }
+
+    pub fn clear_save_dirty(&mut self) {
+        self.save_dirty = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rom_with(id: &[u8]) -> Vec<u8> {
+        let mut rom = vec![0; 1024];
+        rom[512..512 + id.len()].copy_from_slice(id);
+        rom
+    }
+
+    #[test]
+    fn save_type_detection() {
+        assert_eq!(SaveType::detect(&rom_with(b"EEPROM_V124")), SaveType::Eeprom);
+        assert_eq!(SaveType::detect(&rom_with(b"SRAM_V113")), SaveType::Sram);
+        assert_eq!(SaveType::detect(&rom_with(b"SRAM_F_V102")), SaveType::Sram);
+        assert_eq!(SaveType::detect(&rom_with(b"FLASH_V126")), SaveType::Flash64K);
+        assert_eq!(SaveType::detect(&rom_with(b"FLASH512_V131")), SaveType::Flash64K);
+        assert_eq!(SaveType::detect(&rom_with(b"FLASH1M_V103")), SaveType::Flash128K);
+        assert_eq!(SaveType::detect(&rom_with(b"")), SaveType::None);
+    }
+
+    #[test]
+    fn eeprom_write_then_read() {
+        let mut cartridge = Cartridge::create(rom_with(b"EEPROM_V124"), None).unwrap();
+        cartridge.notify_eeprom_dma(73);
+
+        // Write request to block 3: 10, 6-bit address, 64 data bits, stop bit
+        let data = 0x0123_4567_89AB_CDEF_u64;
+        let mut bits = vec![1, 0, 0, 0, 0, 0, 1, 1];
+        bits.extend((0..64).rev().map(|i| ((data >> i) & 1) as u16));
+        bits.push(0);
+        for bit in bits {
+            cartridge.write_eeprom(bit);
+        }
+        assert!(cartridge.save_dirty());
+
+        // Read request for block 3: 11, 6-bit address, stop bit
+        for bit in [1, 1, 0, 0, 0, 0, 1, 1, 0] {
+            cartridge.write_eeprom(bit);
+        }
+        let read_bits: Vec<_> = (0..68).map(|_| cartridge.read_eeprom()).collect();
+        let read_data = read_bits[4..].iter().fold(0_u64, |acc, &bit| (acc << 1) | u64::from(bit));
+        assert_eq!(read_data, data);
+        assert_eq!(cartridge.save_memory().unwrap().len(), 512);
+    }
+}
@@ -0,0 +1,190 @@
+//! 512B / 8KB serial EEPROM save memory
+//!
+//! EEPROM is accessed one bit at a time, almost always through DMA 3. A request starts with 2
+//! command bits (11 = read, 10 = write) followed by a 6-bit or 14-bit address depending on the chip
+//! size. Writes are followed by 64 data bits; both requests end with a single 0 bit. After a read
+//! request, the next 68 reads return 4 junk bits followed by 64 data bits, MSB first.
+//!
+//! The chip size is not stored anywhere in the ROM, so it is inferred from the save file size or
+//! from the length of the first DMA transfer to EEPROM.
+
+use bincode::{Decode, Encode};
+
+const BLOCK_LEN: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+pub enum EepromSize {
+    // 6-bit addresses
+    FiveTwelve,
+    // 14-bit addresses, of which only the lowest 10 bits are used
+    EightK,
+}
+
+impl EepromSize {
+    pub fn len(self) -> usize {
+        match self {
+            Self::FiveTwelve => 512,
+            Self::EightK => 8 * 1024,
+        }
+    }
+
+    fn address_bits(self) -> u8 {
+        match self {
+            Self::FiveTwelve => 6,
+            Self::EightK => 14,
+        }
+    }
+
+    pub fn from_dma_length(length: u32) -> Option<Self> {
+        match length {
+            // Read request / write request with 6-bit address
+            9 | 73 => Some(Self::FiveTwelve),
+            // Read request / write request with 14-bit address
+            17 | 81 => Some(Self::EightK),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+enum EepromState {
+    ReceivingCommand { bits: u8, count: u8 },
+    ReceivingAddress { read: bool, address: u16, remaining: u8 },
+    ReceivingData { address: u16, data: u64, remaining: u8 },
+    ReceivingStopBit { read: bool, address: u16, data: u64 },
+    SendingData { address: u16, bit: u8 },
+}
+
+impl Default for EepromState {
+    fn default() -> Self {
+        Self::ReceivingCommand { bits: 0, count: 0 }
+    }
+}
+
+#[derive(Debug, Clone, Encode, Decode)]
+pub struct Eeprom {
+    memory: Box<[u8]>,
+    size: Option<EepromSize>,
+    state: EepromState,
+}
+
+impl Eeprom {
+    pub fn new(initial_save: Option<Vec<u8>>) -> Self {
+        let size = initial_save.as_ref().and_then(|save| match save.len() {
+            512 => Some(EepromSize::FiveTwelve),
+            8192 => Some(EepromSize::EightK),
+            _ => None,
+        });
+
+        let mut memory = vec![0xFF; EepromSize::EightK.len()].into_boxed_slice();
+        if let (Some(save), Some(size)) = (initial_save, size) {
+            memory[..size.len()].copy_from_slice(&save);
+        }
+
+        Self { memory, size, state: EepromState::default() }
+    }
+
+    pub fn memory(&self) -> &[u8] {
+        let len = self.size.unwrap_or(EepromSize::EightK).len();
+        &self.memory[..len]
+    }
+
+    pub fn size_known(&self) -> bool {
+        self.size.is_some()
+    }
+
+    pub fn set_size(&mut self, size: EepromSize) {
+        log::info!("Detected EEPROM size as {} bytes", size.len());
+        self.size = Some(size);
+    }
+
+    fn block_offset(&self, address: u16) -> usize {
+        let len = self.size.unwrap_or(EepromSize::EightK).len();
+        (usize::from(address) * BLOCK_LEN) & (len - 1)
+    }
+
+    pub fn read(&mut self) -> u16 {
+        match self.state {
+            EepromState::SendingData { address, bit } => {
+                let value = if bit < 4 {
+                    0
+                } else {
+                    let data_bit = bit - 4;
+                    let byte = self.memory[self.block_offset(address) + usize::from(data_bit / 8)];
+                    u16::from((byte >> (7 - (data_bit % 8))) & 1)
+                };
+
+                self.state = if bit == 67 {
+                    EepromState::default()
+                } else {
+                    EepromState::SendingData { address, bit: bit + 1 }
+                };
+
+                value
+            }
+            // Always ready
+            _ => 1,
+        }
+    }
+
+    // Returns true if the write modified save memory
+    pub fn write(&mut self, value: u16) -> bool {
+        let bit = value & 1;
+        let address_bits = self.size.unwrap_or(EepromSize::EightK).address_bits();
+
+        match self.state {
+            EepromState::ReceivingCommand { bits, count } => {
+                let bits = (bits << 1) | bit as u8;
+                self.state = if count == 1 {
+                    match bits {
+                        0b11 => EepromState::ReceivingAddress {
+                            read: true,
+                            address: 0,
+                            remaining: address_bits,
+                        },
+                        0b10 => EepromState::ReceivingAddress {
+                            read: false,
+                            address: 0,
+                            remaining: address_bits,
+                        },
+                        _ => EepromState::default(),
+                    }
+                } else {
+                    EepromState::ReceivingCommand { bits, count: count + 1 }
+                };
+            }
+            EepromState::ReceivingAddress { read, address, remaining } => {
+                let address = (address << 1) | bit;
+                self.state = match (remaining, read) {
+                    (1, true) => EepromState::ReceivingStopBit { read, address, data: 0 },
+                    (1, false) => EepromState::ReceivingData { address, data: 0, remaining: 64 },
+                    _ => EepromState::ReceivingAddress { read, address, remaining: remaining - 1 },
+                };
+            }
+            EepromState::ReceivingData { address, data, remaining } => {
+                let data = (data << 1) | u64::from(bit);
+                self.state = if remaining == 1 {
+                    EepromState::ReceivingStopBit { read: false, address, data }
+                } else {
+                    EepromState::ReceivingData { address, data, remaining: remaining - 1 }
+                };
+            }
+            EepromState::ReceivingStopBit { read, address, data } => {
+                if read {
+                    self.state = EepromState::SendingData { address, bit: 0 };
+                } else {
+                    let offset = self.block_offset(address);
+                    self.memory[offset..offset + BLOCK_LEN].copy_from_slice(&data.to_be_bytes());
+                    self.state = EepromState::default();
+                    return true;
+                }
+            }
+            EepromState::SendingData { .. } => {
+                // A write while sending aborts the read and starts a new command
+                self.state = EepromState::ReceivingCommand { bits: bit as u8, count: 1 };
+            }
+        }
+
+        false
+    }
+}
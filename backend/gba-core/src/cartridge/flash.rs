@@ -0,0 +1,144 @@
+//! 64KB / 128KB flash save memory
+//!
+//! Flash chips are controlled by writing command sequences: $AA to $5555, $55 to $2AAA, then the
+//! command byte to $5555. 128KB chips have two 64KB banks selected by a bank switch command.
+
+use bincode::{Decode, Encode};
+
+const BANK_LEN: usize = 64 * 1024;
+const SECTOR_LEN: usize = 4 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+pub enum FlashSize {
+    SixtyFourK,
+    OneTwentyEightK,
+}
+
+impl FlashSize {
+    pub fn len(self) -> usize {
+        match self {
+            Self::SixtyFourK => BANK_LEN,
+            Self::OneTwentyEightK => 2 * BANK_LEN,
+        }
+    }
+
+    // (manufacturer ID, device ID); Panasonic for 64KB and Sanyo for 128KB
+    fn chip_id(self) -> [u8; 2] {
+        match self {
+            Self::SixtyFourK => [0x32, 0x1B],
+            Self::OneTwentyEightK => [0x62, 0x13],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+enum FlashState {
+    Ready,
+    ReceivedAa,
+    ReceivedAa55,
+    ProgramByte,
+    SelectBank,
+}
+
+#[derive(Debug, Clone, Encode, Decode)]
+pub struct FlashMemory {
+    memory: Box<[u8]>,
+    size: FlashSize,
+    state: FlashState,
+    id_mode: bool,
+    erase_prefix: bool,
+    bank: usize,
+}
+
+impl FlashMemory {
+    pub fn new(size: FlashSize, initial_save: Option<Vec<u8>>) -> Self {
+        let memory = match initial_save {
+            Some(save) if save.len() == size.len() => save.into_boxed_slice(),
+            _ => vec![0xFF; size.len()].into_boxed_slice(),
+        };
+
+        Self {
+            memory,
+            size,
+            state: FlashState::Ready,
+            id_mode: false,
+            erase_prefix: false,
+            bank: 0,
+        }
+    }
+
+    pub fn memory(&self) -> &[u8] {
+        &self.memory
+    }
+
+    pub fn read(&self, address: u32) -> u8 {
+        let offset = (address & 0xFFFF) as usize;
+        if self.id_mode && offset < 2 {
+            return self.size.chip_id()[offset];
+        }
+
+        self.memory[self.bank * BANK_LEN + offset]
+    }
+
+    // Returns true if the write modified save memory
+    pub fn write(&mut self, address: u32, value: u8) -> bool {
+        let offset = (address & 0xFFFF) as usize;
+
+        match (self.state, offset, value) {
+            (FlashState::Ready, 0x5555, 0xAA) => self.state = FlashState::ReceivedAa,
+            (FlashState::ReceivedAa, 0x2AAA, 0x55) => self.state = FlashState::ReceivedAa55,
+            (FlashState::ReceivedAa55, 0x5555, _) => return self.execute_command(value),
+            (FlashState::ReceivedAa55, _, 0x30) if self.erase_prefix => {
+                // Sector erase
+                let sector_start = self.bank * BANK_LEN + (offset & !(SECTOR_LEN - 1));
+                self.memory[sector_start..sector_start + SECTOR_LEN].fill(0xFF);
+                self.erase_prefix = false;
+                self.state = FlashState::Ready;
+                return true;
+            }
+            (FlashState::ProgramByte, _, _) => {
+                // Programming can only clear bits; erasing is required to set them
+                self.memory[self.bank * BANK_LEN + offset] &= value;
+                self.state = FlashState::Ready;
+                return true;
+            }
+            (FlashState::SelectBank, 0x0000, _) => {
+                self.bank = usize::from(value & 1);
+                self.state = FlashState::Ready;
+            }
+            _ => {
+                log::debug!(
+                    "Unexpected flash write in state {:?}: {address:08X} {value:02X}",
+                    self.state
+                );
+                self.state = FlashState::Ready;
+            }
+        }
+
+        false
+    }
+
+    fn execute_command(&mut self, command: u8) -> bool {
+        self.state = FlashState::Ready;
+
+        match command {
+            0x90 => self.id_mode = true,
+            0xF0 => self.id_mode = false,
+            0x80 => {
+                self.erase_prefix = true;
+                return false;
+            }
+            0x10 if self.erase_prefix => {
+                self.memory.fill(0xFF);
+                self.erase_prefix = false;
+                return true;
+            }
+            0xA0 => self.state = FlashState::ProgramByte,
+            0xB0 if self.size == FlashSize::OneTwentyEightK => self.state = FlashState::SelectBank,
+            _ => log::warn!("Unexpected flash command {command:02X}"),
+        }
+
+        self.erase_prefix = false;
+        false
+    }
+}
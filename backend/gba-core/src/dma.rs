@@ -0,0 +1,290 @@
+//! GBA DMA controller
+//!
+//! There are 4 DMA channels, with lower-numbered channels taking priority. Each channel can start
+//! immediately, at the start of VBlank, at the start of each HBlank, or on a channel-specific
+//! "special" trigger: Direct Sound FIFO refill requests for channels 1 and 2, and video capture
+//! for channel 3 (not emulated). Transfers run to completion without being interrupted by
+//! higher-priority channels.
+
+use crate::apu::Apu;
+use crate::interrupts::{InterruptRegisters, InterruptType};
+use bincode::{Decode, Encode};
+use jgenesis_common::num::GetBit;
+use std::array;
+
+const FIFO_A_ADDRESS: u32 = 0x0400_00A0;
+const FIFO_B_ADDRESS: u32 = 0x0400_00A4;
+
+// Sound FIFO transfers always transfer 4 words
+const FIFO_TRANSFER_LEN: u32 = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode)]
+enum AddressControl {
+    #[default]
+    Increment,
+    Decrement,
+    Fixed,
+    // Destination only: increment during the transfer, then reload when repeating
+    IncrementReload,
+}
+
+impl AddressControl {
+    fn from_bits(bits: u16) -> Self {
+        match bits & 3 {
+            0 => Self::Increment,
+            1 => Self::Decrement,
+            2 => Self::Fixed,
+            3 => Self::IncrementReload,
+            _ => unreachable!("value & 3 is always <= 3"),
+        }
+    }
+
+    fn step(self, unit_len: u32) -> u32 {
+        match self {
+            Self::Increment | Self::IncrementReload => unit_len,
+            Self::Decrement => unit_len.wrapping_neg(),
+            Self::Fixed => 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode)]
+enum DmaTiming {
+    #[default]
+    Immediate,
+    VBlank,
+    HBlank,
+    Special,
+}
+
+impl DmaTiming {
+    fn from_bits(bits: u16) -> Self {
+        match bits & 3 {
+            0 => Self::Immediate,
+            1 => Self::VBlank,
+            2 => Self::HBlank,
+            3 => Self::Special,
+            _ => unreachable!("value & 3 is always <= 3"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Encode, Decode)]
+struct DmaChannel {
+    source: u32,
+    destination: u32,
+    length: u16,
+    control: u16,
+    enabled: bool,
+    repeat: bool,
+    word_size: bool,
+    irq_enabled: bool,
+    timing: DmaTiming,
+    source_control: AddressControl,
+    destination_control: AddressControl,
+    internal_source: u32,
+    internal_destination: u32,
+    internal_length: u32,
+    pending: bool,
+}
+
+/// A single DMA transfer to be executed by the bus.
+#[derive(Debug, Clone, Copy)]
+pub struct DmaTransfer {
+    pub channel: usize,
+    pub source: u32,
+    pub destination: u32,
+    pub length: u32,
+    pub word_size: bool,
+    pub source_step: u32,
+    pub destination_step: u32,
+}
+
+#[derive(Debug, Clone, Encode, Decode)]
+pub struct DmaController {
+    channels: [DmaChannel; 4],
+}
+
+impl DmaController {
+    pub fn new() -> Self {
+        Self { channels: array::from_fn(|_| DmaChannel::default()) }
+    }
+
+    // DMA0 can only access internal memory; source addresses are 27 bits for DMA0 and 28 bits for
+    // the other channels
+    fn source_mask(i: usize) -> u32 {
+        if i == 0 { 0x07FF_FFFF } else { 0x0FFF_FFFF }
+    }
+
+    // Destination addresses are 28 bits for DMA3 and 27 bits for the other channels
+    fn destination_mask(i: usize) -> u32 {
+        if i == 3 { 0x0FFF_FFFF } else { 0x07FF_FFFF }
+    }
+
+    fn max_length(i: usize) -> u32 {
+        if i == 3 { 0x10000 } else { 0x4000 }
+    }
+
+    // $40000B0 + 12*i: DMAxSAD, write-only
+    pub fn write_source(&mut self, i: usize, value: u16, high: bool) {
+        let channel = &mut self.channels[i];
+        channel.source = if high {
+            (channel.source & 0x0000_FFFF) | (u32::from(value) << 16)
+        } else {
+            (channel.source & 0xFFFF_0000) | u32::from(value)
+        };
+    }
+
+    // $40000B4 + 12*i: DMAxDAD, write-only
+    pub fn write_destination(&mut self, i: usize, value: u16, high: bool) {
+        let channel = &mut self.channels[i];
+        channel.destination = if high {
+            (channel.destination & 0x0000_FFFF) | (u32::from(value) << 16)
+        } else {
+            (channel.destination & 0xFFFF_0000) | u32::from(value)
+        };
+    }
+
+    // $40000B8 + 12*i: DMAxCNT_L, write-only
+    pub fn write_length(&mut self, i: usize, value: u16) {
+        self.channels[i].length = value;
+    }
+
+    // $40000BA + 12*i: DMAxCNT_H
+    pub fn read_control(&self, i: usize) -> u16 {
+        self.channels[i].control
+    }
+
+    pub fn write_control(&mut self, i: usize, value: u16) {
+        let channel = &mut self.channels[i];
+
+        let enabled = value.bit(15);
+        channel.destination_control = AddressControl::from_bits(value >> 5);
+        channel.source_control = match AddressControl::from_bits(value >> 7) {
+            // Prohibited
+            AddressControl::IncrementReload => AddressControl::Increment,
+            control => control,
+        };
+        channel.repeat = value.bit(9);
+        channel.word_size = value.bit(10);
+        channel.timing = DmaTiming::from_bits(value >> 12);
+        channel.irq_enabled = value.bit(14);
+        channel.control = value & if i == 3 { 0xFFE0 } else { 0xF7E0 };
+
+        if enabled && !channel.enabled {
+            channel.internal_source = channel.source & Self::source_mask(i);
+            channel.internal_destination = channel.destination & Self::destination_mask(i);
+            Self::reload_length(channel, i);
+            channel.pending = channel.timing == DmaTiming::Immediate;
+
+            if channel.timing == DmaTiming::Special && i == 3 {
+                log::warn!("DMA3 video capture mode is not supported");
+            }
+        }
+        channel.enabled = enabled;
+        if !enabled {
+            channel.pending = false;
+        }
+
+        log::trace!("DMA{i}CNT_H write: {value:04X}");
+    }
+
+    fn reload_length(channel: &mut DmaChannel, i: usize) {
+        channel.internal_length = match channel.length {
+            0 => Self::max_length(i),
+            length => u32::from(length) & (Self::max_length(i) - 1),
+        };
+    }
+
+    fn is_fifo_channel(&self, i: usize) -> bool {
+        (i == 1 || i == 2) && self.channels[i].timing == DmaTiming::Special
+    }
+
+    pub fn notify_vblank(&mut self) {
+        self.trigger(DmaTiming::VBlank);
+    }
+
+    pub fn notify_hblank(&mut self) {
+        self.trigger(DmaTiming::HBlank);
+    }
+
+    fn trigger(&mut self, timing: DmaTiming) {
+        for channel in &mut self.channels {
+            if channel.enabled && channel.timing == timing {
+                channel.pending = true;
+            }
+        }
+    }
+
+    pub fn check_fifo_requests(&mut self, apu: &mut Apu) {
+        for (fifo, address) in [(0, FIFO_A_ADDRESS), (1, FIFO_B_ADDRESS)] {
+            if !apu.take_fifo_dma_request(fifo) {
+                continue;
+            }
+
+            for i in 1..=2 {
+                let channel = &self.channels[i];
+                if channel.enabled && self.is_fifo_channel(i) && channel.destination == address {
+                    self.channels[i].pending = true;
+                }
+            }
+        }
+    }
+
+    pub fn any_pending(&self) -> bool {
+        self.channels.iter().any(|channel| channel.pending)
+    }
+
+    /// Take the highest priority pending transfer, if any.
+    pub fn next_transfer(&mut self) -> Option<DmaTransfer> {
+        let i = self.channels.iter().position(|channel| channel.pending)?;
+        self.channels[i].pending = false;
+
+        let fifo = self.is_fifo_channel(i);
+        let channel = &self.channels[i];
+
+        let word_size = channel.word_size || fifo;
+        let unit_len = if word_size { 4 } else { 2 };
+        let destination_step = if fifo { 0 } else { channel.destination_control.step(unit_len) };
+
+        Some(DmaTransfer {
+            channel: i,
+            source: channel.internal_source,
+            destination: channel.internal_destination,
+            length: if fifo { FIFO_TRANSFER_LEN } else { channel.internal_length },
+            word_size,
+            source_step: channel.source_control.step(unit_len),
+            destination_step,
+        })
+    }
+
+    pub fn finish_transfer(&mut self, transfer: DmaTransfer, interrupts: &mut InterruptRegisters) {
+        let i = transfer.channel;
+        let fifo = self.is_fifo_channel(i);
+        let channel = &mut self.channels[i];
+
+        channel.internal_source =
+            transfer.source.wrapping_add(transfer.source_step.wrapping_mul(transfer.length))
+                & Self::source_mask(i);
+        if !fifo {
+            channel.internal_destination = transfer
+                .destination
+                .wrapping_add(transfer.destination_step.wrapping_mul(transfer.length))
+                & Self::destination_mask(i);
+        }
+
+        if channel.irq_enabled {
+            interrupts.set_flag(InterruptType::DMAS[i]);
+        }
+
+        if channel.repeat && channel.timing != DmaTiming::Immediate {
+            Self::reload_length(channel, i);
+            if channel.destination_control == AddressControl::IncrementReload {
+                channel.internal_destination = channel.destination & Self::destination_mask(i);
+            }
+        } else {
+            channel.enabled = false;
+            channel.control &= !(1 << 15);
+        }
+    }
+}
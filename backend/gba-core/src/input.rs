@@ -0,0 +1,84 @@
+//! GBA keypad input handling
+
+use bincode::{Decode, Encode};
+use jgenesis_common::num::GetBit;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode)]
+pub struct GbaInputs {
+    pub up: bool,
+    pub left: bool,
+    pub right: bool,
+    pub down: bool,
+    pub a: bool,
+    pub b: bool,
+    pub l: bool,
+    pub r: bool,
+    pub start: bool,
+    pub select: bool,
+}
+
+impl GbaInputs {
+    // KEYINPUT bit order, active high
+    fn to_bits(self) -> u16 {
+        u16::from(self.a)
+            | (u16::from(self.b) << 1)
+            | (u16::from(self.select) << 2)
+            | (u16::from(self.start) << 3)
+            | (u16::from(self.right) << 4)
+            | (u16::from(self.left) << 5)
+            | (u16::from(self.up) << 6)
+            | (u16::from(self.down) << 7)
+            | (u16::from(self.r) << 8)
+            | (u16::from(self.l) << 9)
+    }
+}
+
+#[derive(Debug, Clone, Encode, Decode)]
+pub(crate) struct InputState {
+    inputs: GbaInputs,
+    irq_mask: u16,
+    irq_enabled: bool,
+    irq_all_pressed: bool,
+}
+
+impl InputState {
+    pub(crate) fn new() -> Self {
+        Self {
+            inputs: GbaInputs::default(),
+            irq_mask: 0,
+            irq_enabled: false,
+            irq_all_pressed: false,
+        }
+    }
+
+    pub(crate) fn set_inputs(&mut self, inputs: GbaInputs) {
+        self.inputs = inputs;
+    }
+
+    // $4000130: KEYINPUT, active low
+    pub(crate) fn read_keyinput(&self) -> u16 {
+        !self.inputs.to_bits() & 0x03FF
+    }
+
+    // $4000132: KEYCNT
+    pub(crate) fn read_keycnt(&self) -> u16 {
+        self.irq_mask
+            | (u16::from(self.irq_enabled) << 14)
+            | (u16::from(self.irq_all_pressed) << 15)
+    }
+
+    pub(crate) fn write_keycnt(&mut self, value: u16) {
+        self.irq_mask = value & 0x03FF;
+        self.irq_enabled = value.bit(14);
+        self.irq_all_pressed = value.bit(15);
+    }
+
+    pub(crate) fn irq_condition_met(&self) -> bool {
+        if !self.irq_enabled || self.irq_mask == 0 {
+            return false;
+        }
+
+        let pressed = self.inputs.to_bits() & self.irq_mask;
+        if self.irq_all_pressed { pressed == self.irq_mask } else { pressed != 0 }
+    }
+}
@@ -0,0 +1,98 @@
+//! GBA interrupt controller registers
+
+use bincode::{Decode, Encode};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InterruptType {
+    VBlank = 0,
+    HBlank = 1,
+    VCounter = 2,
+    Timer0 = 3,
+    Timer1 = 4,
+    Timer2 = 5,
+    Timer3 = 6,
+    Dma0 = 8,
+    Dma1 = 9,
+    Dma2 = 10,
+    Dma3 = 11,
+    Keypad = 12,
+}
+
+impl InterruptType {
+    pub const TIMERS: [Self; 4] = [Self::Timer0, Self::Timer1, Self::Timer2, Self::Timer3];
+    pub const DMAS: [Self; 4] = [Self::Dma0, Self::Dma1, Self::Dma2, Self::Dma3];
+
+    fn mask(self) -> u16 {
+        1 << (self as u8)
+    }
+}
+
+#[derive(Debug, Clone, Default, Encode, Decode)]
+pub struct InterruptRegisters {
+    enabled: u16,
+    flags: u16,
+    master_enabled: bool,
+    halted: bool,
+}
+
+impl InterruptRegisters {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_flag(&mut self, interrupt: InterruptType) {
+        self.flags |= interrupt.mask();
+    }
+
+    // $4000200: IE
+    pub fn read_enabled(&self) -> u16 {
+        self.enabled
+    }
+
+    pub fn write_enabled(&mut self, value: u16) {
+        self.enabled = value & 0x3FFF;
+    }
+
+    // $4000202: IF
+    pub fn read_flags(&self) -> u16 {
+        self.flags
+    }
+
+    // Writing 1 to a bit acknowledges that interrupt
+    pub fn acknowledge(&mut self, value: u16) {
+        self.flags &= !value;
+    }
+
+    // $4000208: IME
+    pub fn read_master_enabled(&self) -> u16 {
+        self.master_enabled.into()
+    }
+
+    pub fn write_master_enabled(&mut self, value: u16) {
+        self.master_enabled = value & 1 != 0;
+    }
+
+    // Any enabled interrupt wakes the CPU from halt, even if IME is clear
+    pub fn pending(&self) -> bool {
+        self.enabled & self.flags != 0
+    }
+
+    pub fn irq(&self) -> bool {
+        self.master_enabled && self.pending()
+    }
+
+    // $4000301: HALTCNT
+    pub fn halt(&mut self) {
+        self.halted = true;
+    }
+
+    pub fn halted(&self) -> bool {
+        self.halted
+    }
+
+    pub fn update_halt(&mut self) {
+        if self.halted && self.pending() {
+            self.halted = false;
+        }
+    }
+}
@@ -0,0 +1,19 @@
+//! Game Boy Advance emulation core
+//!
+//! A GBA BIOS ROM is required. Only the two Direct Sound channels and the two PSG pulse channels
+//! are emulated; the PSG wavetable and noise channels are silent.
+
+pub mod api;
+mod apu;
+mod audio;
+mod bus;
+mod cartridge;
+mod dma;
+pub mod input;
+mod interrupts;
+mod memory;
+mod ppu;
+mod timers;
+
+pub use api::{GbaAspectRatio, GbaEmulator, GbaEmulatorConfig, GbaError, GbaLoadError};
+pub use input::GbaInputs;
@@ -0,0 +1,199 @@
+//! GBA internal memory and memory control registers
+
+use crate::api::GbaLoadError;
+use arm7tdmi_emu::MemoryCycle;
+use bincode::{Decode, Encode};
+use jgenesis_common::num::GetBit;
+use jgenesis_common::rng::{InitialRamState, Rng};
+use jgenesis_proc_macros::{FakeDecode, FakeEncode, PartialClone};
+use std::ops::Deref;
+
+pub const BIOS_LEN: usize = 16 * 1024;
+const EWRAM_LEN: usize = 256 * 1024;
+const IWRAM_LEN: usize = 32 * 1024;
+
+// I/O registers occupy $4000000-$40003FF
+const IO_HALFWORDS: usize = 0x200;
+
+#[derive(Debug, Clone, Default, FakeEncode, FakeDecode)]
+pub struct Bios(Box<[u8]>);
+
+impl Deref for Bios {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+// Cartridge access times in cycles, excluding the first cycle of each access
+const ROM_N_WAIT_STATES: [u32; 4] = [4, 3, 2, 8];
+const WS0_S_WAIT_STATES: [u32; 2] = [2, 1];
+const WS1_S_WAIT_STATES: [u32; 2] = [4, 1];
+const WS2_S_WAIT_STATES: [u32; 2] = [8, 1];
+
+#[derive(Debug, Clone, Default, Encode, Decode)]
+pub struct WaitControl {
+    sram: u32,
+    rom_n: [u32; 3],
+    rom_s: [u32; 3],
+    prefetch_enabled: bool,
+    raw: u16,
+}
+
+impl WaitControl {
+    fn new() -> Self {
+        let mut wait_control = Self::default();
+        wait_control.write(0);
+        wait_control
+    }
+
+    // $4000204: WAITCNT
+    pub fn read(&self) -> u16 {
+        self.raw
+    }
+
+    pub fn write(&mut self, value: u16) {
+        self.raw = value & 0x5FFF;
+        self.sram = ROM_N_WAIT_STATES[usize::from(value & 3)];
+        self.rom_n = [
+            ROM_N_WAIT_STATES[usize::from((value >> 2) & 3)],
+            ROM_N_WAIT_STATES[usize::from((value >> 5) & 3)],
+            ROM_N_WAIT_STATES[usize::from((value >> 8) & 3)],
+        ];
+        self.rom_s = [
+            WS0_S_WAIT_STATES[usize::from(value.bit(4))],
+            WS1_S_WAIT_STATES[usize::from(value.bit(7))],
+            WS2_S_WAIT_STATES[usize::from(value.bit(10))],
+        ];
+        self.prefetch_enabled = value.bit(14);
+
+        log::trace!("WAITCNT write: {value:04X}");
+    }
+
+    // Cycles for a 16-bit or smaller cartridge ROM access; `region` is the waitstate region 0-2
+    pub fn rom_access_cycles(&self, region: usize, cycle: MemoryCycle) -> u32 {
+        1 + match cycle {
+            MemoryCycle::N => self.rom_n[region],
+            MemoryCycle::S => self.rom_s[region],
+        }
+    }
+
+    pub fn sram_access_cycles(&self) -> u32 {
+        1 + self.sram
+    }
+
+    pub fn prefetch_enabled(&self) -> bool {
+        self.prefetch_enabled
+    }
+}
+
+#[derive(Debug, Clone, Encode, Decode, PartialClone)]
+pub struct Memory {
+    #[partial_clone(default)]
+    bios: Bios,
+    ewram: Box<[u8]>,
+    iwram: Box<[u8]>,
+    pub wait_control: WaitControl,
+    post_boot_flag: u8,
+    // Last value written to each I/O halfword, used to merge byte writes into write-only registers
+    io_shadow: Box<[u16]>,
+    // Last word fetched from BIOS; BIOS reads return this while executing outside the BIOS
+    bios_latch: u32,
+}
+
+impl Memory {
+    pub fn new(
+        bios: Vec<u8>,
+        initial_ram_state: InitialRamState,
+        rng: &mut Rng,
+    ) -> Result<Self, GbaLoadError> {
+        if bios.len() != BIOS_LEN {
+            return Err(GbaLoadError::InvalidBiosLength(bios.len()));
+        }
+
+        let mut ewram = vec![0; EWRAM_LEN].into_boxed_slice();
+        let mut iwram = vec![0; IWRAM_LEN].into_boxed_slice();
+        initial_ram_state.fill(&mut ewram, rng);
+        initial_ram_state.fill(&mut iwram, rng);
+
+        Ok(Self {
+            bios: Bios(bios.into_boxed_slice()),
+            ewram,
+            iwram,
+            wait_control: WaitControl::new(),
+            post_boot_flag: 0,
+            io_shadow: vec![0; IO_HALFWORDS].into_boxed_slice(),
+            bios_latch: 0,
+        })
+    }
+
+    pub fn take_bios(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.bios.0).into_vec()
+    }
+
+    pub fn take_bios_from(&mut self, other: &mut Self) {
+        self.bios = std::mem::take(&mut other.bios);
+    }
+
+    /// Read a BIOS word. The BIOS is only readable while the CPU is executing inside it; otherwise
+    /// reads return the last word fetched from the BIOS.
+    pub fn read_bios_word(&mut self, address: u32, readable: bool) -> u32 {
+        if readable {
+            self.bios_latch = read_word(&self.bios, address as usize & (BIOS_LEN - 1) & !3);
+        }
+        self.bios_latch
+    }
+
+    pub fn read_ewram<const N: usize>(&self, address: u32) -> u32 {
+        read_le::<N>(&self.ewram, address as usize & (EWRAM_LEN - 1))
+    }
+
+    pub fn write_ewram<const N: usize>(&mut self, address: u32, value: u32) {
+        write_le::<N>(&mut self.ewram, address as usize & (EWRAM_LEN - 1), value);
+    }
+
+    pub fn read_iwram<const N: usize>(&self, address: u32) -> u32 {
+        read_le::<N>(&self.iwram, address as usize & (IWRAM_LEN - 1))
+    }
+
+    pub fn write_iwram<const N: usize>(&mut self, address: u32, value: u32) {
+        write_le::<N>(&mut self.iwram, address as usize & (IWRAM_LEN - 1), value);
+    }
+
+    // $4000300: POSTFLG, set by the BIOS after the first boot
+    pub fn post_boot_flag(&self) -> u8 {
+        self.post_boot_flag
+    }
+
+    pub fn write_post_boot_flag(&mut self, value: u8) {
+        self.post_boot_flag = value & 1;
+    }
+
+    pub fn io_shadow(&self, address: u32) -> u16 {
+        self.io_shadow[((address & 0x3FF) >> 1) as usize]
+    }
+
+    pub fn set_io_shadow(&mut self, address: u32, value: u16) {
+        self.io_shadow[((address & 0x3FF) >> 1) as usize] = value;
+    }
+}
+
+fn read_word(memory: &[u8], address: usize) -> u32 {
+    u32::from_le_bytes(memory[address..address + 4].try_into().unwrap())
+}
+
+/// Read an `N`-byte little-endian value. `address` must be aligned to `N`.
+pub fn read_le<const N: usize>(memory: &[u8], address: usize) -> u32 {
+    match N {
+        1 => memory[address].into(),
+        2 => u16::from_le_bytes([memory[address], memory[address + 1]]).into(),
+        4 => read_word(memory, address),
+        _ => panic!("invalid access size {N}"),
+    }
+}
+
+/// Write an `N`-byte little-endian value. `address` must be aligned to `N`.
+pub fn write_le<const N: usize>(memory: &mut [u8], address: usize, value: u32) {
+    memory[address..address + N].copy_from_slice(&value.to_le_bytes()[..N]);
+}
@@ -0,0 +1,831 @@
+//! GBA PPU (picture processing unit)
+//!
+//! The PPU renders 240x160 frames using up to 4 background layers and 128 sprites. Background
+//! layers are either tiled (scrollable tile maps), affine (rotated/scaled tile maps), or bitmaps
+//! depending on the BG mode. Two rectangular windows plus the OBJ window can restrict which layers
+//! are visible in each region, and the color special effects unit can alpha blend two layers or
+//! fade the top layer to white or black.
+//!
+//! Each line is rendered in full when HBlank begins, so mid-line register changes are not
+//! emulated.
+
+mod registers;
+
+use crate::dma::DmaController;
+use crate::interrupts::{InterruptRegisters, InterruptType};
+use crate::memory;
+use crate::ppu::registers::{BgMode, BlendMode, Registers};
+use bincode::{Decode, Encode};
+use jgenesis_common::frontend::{Color, FrameSize};
+use jgenesis_common::num::GetBit;
+use jgenesis_proc_macros::{FakeDecode, FakeEncode};
+use std::ops::{Deref, DerefMut};
+
+pub const SCREEN_WIDTH: u32 = 240;
+pub const SCREEN_HEIGHT: u32 = 160;
+pub const FRAME_SIZE: FrameSize = FrameSize { width: SCREEN_WIDTH, height: SCREEN_HEIGHT };
+
+const LINES_PER_FRAME: u32 = 228;
+const CYCLES_PER_LINE: u32 = 1232;
+const HBLANK_START_CYCLE: u32 = 1008;
+
+const VRAM_LEN: usize = 96 * 1024;
+const PALETTE_RAM_LEN: usize = 1024;
+const OAM_LEN: usize = 1024;
+
+const OBJ_VRAM_START: u32 = 0x10000;
+const BITMAP_OBJ_VRAM_START: u32 = 0x14000;
+const BITMAP_FRAME_1_ADDR: u32 = 0xA000;
+
+const FRAME_BUFFER_LEN: usize = (SCREEN_WIDTH * SCREEN_HEIGHT) as usize;
+
+// Colors are 15-bit; bit 15 marks a transparent pixel in layer buffers
+const TRANSPARENT: u16 = 0x8000;
+
+// Layer indices as used by WININ/WINOUT and BLDCNT bit masks
+const OBJ_LAYER: u8 = 4;
+const BACKDROP_LAYER: u8 = 5;
+const EFFECTS_BIT: u8 = 5;
+
+#[derive(Debug, Clone, FakeEncode, FakeDecode)]
+struct FrameBuffer(Box<[Color]>);
+
+impl Default for FrameBuffer {
+    fn default() -> Self {
+        Self(vec![Color::default(); FRAME_BUFFER_LEN].into_boxed_slice())
+    }
+}
+
+impl Deref for FrameBuffer {
+    type Target = [Color];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for FrameBuffer {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ObjPixel {
+    color: u16,
+    priority: u8,
+    semi_transparent: bool,
+}
+
+impl ObjPixel {
+    const NONE: Self = Self { color: TRANSPARENT, priority: u8::MAX, semi_transparent: false };
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ObjMode {
+    Normal,
+    SemiTransparent,
+    Window,
+}
+
+// Sprite sizes indexed by [shape][size] as (width, height)
+const OBJ_SIZES: [[(u32, u32); 4]; 3] = [
+    [(8, 8), (16, 16), (32, 32), (64, 64)],
+    [(16, 8), (32, 8), (32, 16), (64, 32)],
+    [(8, 16), (8, 32), (16, 32), (32, 64)],
+];
+
+#[derive(Debug, Clone, Encode, Decode)]
+pub struct Ppu {
+    registers: Registers,
+    vram: Box<[u8]>,
+    palette_ram: Box<[u8]>,
+    oam: Box<[u8]>,
+    frame_buffer: FrameBuffer,
+    line: u32,
+    line_cycles: u32,
+    vcount_match: bool,
+    frame_complete: bool,
+}
+
+impl Ppu {
+    pub fn new() -> Self {
+        Self {
+            registers: Registers::new(),
+            vram: vec![0; VRAM_LEN].into_boxed_slice(),
+            palette_ram: vec![0; PALETTE_RAM_LEN].into_boxed_slice(),
+            oam: vec![0; OAM_LEN].into_boxed_slice(),
+            frame_buffer: FrameBuffer::default(),
+            line: 0,
+            line_cycles: 0,
+            vcount_match: true,
+            frame_complete: false,
+        }
+    }
+
+    pub fn frame_buffer(&self) -> &[Color] {
+        &self.frame_buffer
+    }
+
+    pub fn frame_complete(&self) -> bool {
+        self.frame_complete
+    }
+
+    pub fn clear_frame_complete(&mut self) {
+        self.frame_complete = false;
+    }
+
+    pub fn tick(
+        &mut self,
+        mut cycles: u32,
+        interrupts: &mut InterruptRegisters,
+        dma: &mut DmaController,
+    ) {
+        while cycles != 0 {
+            let next_event = if self.line_cycles < HBLANK_START_CYCLE {
+                HBLANK_START_CYCLE
+            } else {
+                CYCLES_PER_LINE
+            };
+            let elapsed = cycles.min(next_event - self.line_cycles);
+            cycles -= elapsed;
+            self.line_cycles += elapsed;
+
+            if self.line_cycles == HBLANK_START_CYCLE {
+                self.start_hblank(interrupts, dma);
+            } else if self.line_cycles == CYCLES_PER_LINE {
+                self.line_cycles = 0;
+                self.start_line(interrupts, dma);
+            }
+        }
+    }
+
+    /// Cycles until the next HBlank or line start, used to skip ahead while the CPU is halted.
+    pub fn cycles_until_next_event(&self) -> u32 {
+        if self.line_cycles < HBLANK_START_CYCLE {
+            HBLANK_START_CYCLE - self.line_cycles
+        } else {
+            CYCLES_PER_LINE - self.line_cycles
+        }
+    }
+
+    fn start_hblank(&mut self, interrupts: &mut InterruptRegisters, dma: &mut DmaController) {
+        if self.line < SCREEN_HEIGHT {
+            self.render_line();
+            for affine in &mut self.registers.affine {
+                affine.increment_reference_point();
+            }
+
+            // HBlank DMA does not trigger during VBlank
+            dma.notify_hblank();
+        }
+
+        if self.registers.hblank_irq_enabled {
+            interrupts.set_flag(InterruptType::HBlank);
+        }
+    }
+
+    fn start_line(&mut self, interrupts: &mut InterruptRegisters, dma: &mut DmaController) {
+        self.line += 1;
+        if self.line == LINES_PER_FRAME {
+            self.line = 0;
+        }
+
+        if self.line == SCREEN_HEIGHT {
+            self.frame_complete = true;
+            for affine in &mut self.registers.affine {
+                affine.latch_reference_point();
+            }
+
+            dma.notify_vblank();
+            if self.registers.vblank_irq_enabled {
+                interrupts.set_flag(InterruptType::VBlank);
+            }
+        }
+
+        let vcount_match = self.line == u32::from(self.registers.vcount_target);
+        if vcount_match && !self.vcount_match && self.registers.vcounter_irq_enabled {
+            interrupts.set_flag(InterruptType::VCounter);
+        }
+        self.vcount_match = vcount_match;
+    }
+
+    // The VBlank flag is set on lines 160-226; line 227 is part of VBlank but the flag is clear
+    fn in_vblank(&self) -> bool {
+        (SCREEN_HEIGHT..LINES_PER_FRAME - 1).contains(&self.line)
+    }
+
+    pub fn read_register(&self, address: u32) -> Option<u16> {
+        let value = match address & 0xFF {
+            0x00 => self.registers.read_dispcnt(),
+            0x04 => {
+                u16::from(self.in_vblank())
+                    | (u16::from(self.line_cycles >= HBLANK_START_CYCLE) << 1)
+                    | (u16::from(self.vcount_match) << 2)
+                    | self.registers.read_dispstat_control()
+            }
+            0x06 => self.line as u16,
+            0x08 | 0x0A | 0x0C | 0x0E => self.registers.read_bgcnt(((address & 0x7) >> 1) as usize),
+            0x48 => self.registers.read_winin(),
+            0x4A => self.registers.read_winout(),
+            0x50 => self.registers.read_bldcnt(),
+            0x52 => self.registers.read_bldalpha(),
+            // Unused bits of mostly-readable registers read as 0
+            0x02 | 0x4E | 0x56 => 0,
+            _ => return None,
+        };
+
+        Some(value)
+    }
+
+    pub fn write_register(&mut self, address: u32, value: u16) {
+        match address & 0xFF {
+            0x00 => self.registers.write_dispcnt(value),
+            // Green swap; not emulated
+            0x02 => {}
+            0x04 => {
+                self.registers.write_dispstat(value);
+                self.vcount_match = self.line == u32::from(self.registers.vcount_target);
+            }
+            0x08 | 0x0A | 0x0C | 0x0E => {
+                self.registers.write_bgcnt(((address & 0x7) >> 1) as usize, value);
+            }
+            0x10..=0x1F => {
+                let bg = ((address >> 2) & 3) as usize;
+                if address.bit(1) {
+                    self.registers.bg_v_scroll[bg] = value & 0x1FF;
+                } else {
+                    self.registers.bg_h_scroll[bg] = value & 0x1FF;
+                }
+            }
+            0x20..=0x3F => self.registers.write_affine_register(address, value),
+            0x40 | 0x42 => self.registers.write_window_h(((address >> 1) & 1) as usize, value),
+            0x44 | 0x46 => self.registers.write_window_v(((address >> 1) & 1) as usize, value),
+            0x48 => self.registers.write_winin(value),
+            0x4A => self.registers.write_winout(value),
+            0x4C => self.registers.write_mosaic(value),
+            0x50 => self.registers.write_bldcnt(value),
+            0x52 => self.registers.write_bldalpha(value),
+            0x54 => self.registers.write_bldy(value),
+            _ => log::trace!("Unmapped PPU register write: {address:08X} {value:04X}"),
+        }
+    }
+
+    fn vram_address(address: u32) -> usize {
+        // 96KB VRAM is mirrored in 128KB blocks, with the last 32KB mirroring the OBJ area
+        let address = address & 0x1FFFF;
+        (if address >= 0x18000 { address - 0x8000 } else { address }) as usize
+    }
+
+    pub fn read_vram<const N: usize>(&self, address: u32) -> u32 {
+        memory::read_le::<N>(&self.vram, Self::vram_address(address))
+    }
+
+    pub fn write_vram<const N: usize>(&mut self, address: u32, value: u32) {
+        memory::write_le::<N>(&mut self.vram, Self::vram_address(address), value);
+    }
+
+    // Byte writes to BG VRAM write the byte to both halves of the halfword; byte writes to OBJ VRAM
+    // are ignored
+    pub fn write_vram_byte(&mut self, address: u32, value: u8) {
+        let vram_address = Self::vram_address(address);
+        let obj_start =
+            if self.registers.bg_mode.is_bitmap() { BITMAP_OBJ_VRAM_START } else { OBJ_VRAM_START };
+        if vram_address >= obj_start as usize {
+            return;
+        }
+
+        let halfword = u16::from_le_bytes([value, value]);
+        memory::write_le::<2>(&mut self.vram, vram_address & !1, halfword.into());
+    }
+
+    pub fn read_palette_ram<const N: usize>(&self, address: u32) -> u32 {
+        memory::read_le::<N>(&self.palette_ram, (address as usize) & (PALETTE_RAM_LEN - 1))
+    }
+
+    pub fn write_palette_ram<const N: usize>(&mut self, address: u32, value: u32) {
+        memory::write_le::<N>(
+            &mut self.palette_ram,
+            (address as usize) & (PALETTE_RAM_LEN - 1),
+            value,
+        );
+    }
+
+    // Byte writes to palette RAM write the byte to both halves of the halfword
+    pub fn write_palette_ram_byte(&mut self, address: u32, value: u8) {
+        let halfword = u16::from_le_bytes([value, value]);
+        self.write_palette_ram::<2>(address & !1, halfword.into());
+    }
+
+    pub fn read_oam<const N: usize>(&self, address: u32) -> u32 {
+        memory::read_le::<N>(&self.oam, (address as usize) & (OAM_LEN - 1))
+    }
+
+    // Byte writes to OAM are ignored
+    pub fn write_oam<const N: usize>(&mut self, address: u32, value: u32) {
+        memory::write_le::<N>(&mut self.oam, (address as usize) & (OAM_LEN - 1), value);
+    }
+
+    fn palette_color(&self, index: usize) -> u16 {
+        u16::from_le_bytes([self.palette_ram[2 * index], self.palette_ram[2 * index + 1]]) & 0x7FFF
+    }
+
+    fn vram_u16(&self, address: usize) -> u16 {
+        u16::from_le_bytes([self.vram[address], self.vram[address + 1]])
+    }
+
+    fn oam_u16(&self, address: usize) -> u16 {
+        u16::from_le_bytes([self.oam[address], self.oam[address + 1]])
+    }
+
+    pub fn copy_palettes(&self, out: &mut [Color]) {
+        for (i, color) in out.iter_mut().take(PALETTE_RAM_LEN / 2).enumerate() {
+            *color = bgr555_to_rgb888(self.palette_color(i));
+        }
+    }
+
+    fn render_line(&mut self) {
+        let line = self.line;
+        let row_start = (line * SCREEN_WIDTH) as usize;
+
+        if self.registers.forced_blanking {
+            self.frame_buffer[row_start..row_start + SCREEN_WIDTH as usize]
+                .fill(Color::rgb(255, 255, 255));
+            return;
+        }
+
+        let mut bg_buffers = [[TRANSPARENT; SCREEN_WIDTH as usize]; 4];
+        for (bg, buffer) in bg_buffers.iter_mut().enumerate() {
+            if !self.registers.bg_active(bg) {
+                continue;
+            }
+
+            match self.registers.bg_mode {
+                BgMode::Zero | BgMode::One | BgMode::Two => {
+                    if self.registers.bg_is_affine(bg) {
+                        self.render_affine_bg(bg, buffer);
+                    } else {
+                        self.render_text_bg(bg, buffer);
+                    }
+                }
+                BgMode::Three | BgMode::Four | BgMode::Five => self.render_bitmap_bg(buffer),
+            }
+        }
+
+        let mut obj_buffer = [ObjPixel::NONE; SCREEN_WIDTH as usize];
+        let mut obj_window = [false; SCREEN_WIDTH as usize];
+        if self.registers.obj_enabled {
+            self.render_sprites(&mut obj_buffer, &mut obj_window);
+        }
+
+        // Sort active BGs by priority, then by BG index
+        let mut bg_order: Vec<usize> = (0..4).filter(|&bg| self.registers.bg_active(bg)).collect();
+        bg_order.sort_by_key(|&bg| (self.registers.bg_control[bg].priority, bg));
+
+        let backdrop = self.palette_color(0);
+        for x in 0..SCREEN_WIDTH as usize {
+            let window_layers = self.window_layers(x as u32, obj_window[x]);
+
+            // Find the top two visible layers as (color, layer index)
+            let mut layers = [(backdrop, BACKDROP_LAYER); 2];
+            let mut layer_count = 0;
+            let obj = obj_buffer[x];
+            let obj_visible = obj.color != TRANSPARENT && window_layers.bit(OBJ_LAYER);
+            let mut obj_placed = !obj_visible;
+
+            for &bg in &bg_order {
+                if layer_count == 2 {
+                    break;
+                }
+
+                let color = bg_buffers[bg][x];
+                if color == TRANSPARENT || !window_layers.bit(bg as u8) {
+                    continue;
+                }
+
+                // Sprites display above BGs of the same priority
+                if !obj_placed && obj.priority <= self.registers.bg_control[bg].priority {
+                    layers[layer_count] = (obj.color, OBJ_LAYER);
+                    layer_count += 1;
+                    obj_placed = true;
+                    if layer_count == 2 {
+                        break;
+                    }
+                }
+
+                layers[layer_count] = (color, bg as u8);
+                layer_count += 1;
+            }
+
+            if !obj_placed && layer_count < 2 {
+                layers[layer_count] = (obj.color, OBJ_LAYER);
+            }
+
+            let [(top_color, top_layer), (second_color, second_layer)] = layers;
+            let effects_enabled = window_layers.bit(EFFECTS_BIT);
+            let color = self.apply_color_effects(
+                top_color,
+                top_layer,
+                second_color,
+                second_layer,
+                effects_enabled,
+                top_layer == OBJ_LAYER && obj.semi_transparent,
+            );
+
+            self.frame_buffer[row_start + x] = bgr555_to_rgb888(color);
+        }
+    }
+
+    fn window_layers(&self, x: u32, in_obj_window: bool) -> u8 {
+        let registers = &self.registers;
+        if !registers.any_window_enabled() {
+            return 0x3F;
+        }
+
+        for i in 0..2 {
+            let window = &registers.windows[i];
+            if registers.window_enabled[i]
+                && window.contains_line(self.line)
+                && window.contains_pixel(x)
+            {
+                return registers.window_in_layers[i];
+            }
+        }
+
+        if registers.obj_window_enabled && in_obj_window {
+            return registers.obj_window_layers;
+        }
+
+        registers.window_out_layers
+    }
+
+    fn apply_color_effects(
+        &self,
+        top_color: u16,
+        top_layer: u8,
+        second_color: u16,
+        second_layer: u8,
+        effects_enabled: bool,
+        semi_transparent_obj: bool,
+    ) -> u16 {
+        let registers = &self.registers;
+        let second_is_target = registers.blend_second_targets.bit(second_layer);
+
+        // Semi-transparent sprites are always alpha blended if the layer below is a second target
+        if semi_transparent_obj && second_is_target {
+            return alpha_blend(top_color, second_color, registers.alpha_a, registers.alpha_b);
+        }
+
+        if !effects_enabled || !registers.blend_first_targets.bit(top_layer) {
+            return top_color;
+        }
+
+        match registers.blend_mode {
+            BlendMode::None => top_color,
+            BlendMode::AlphaBlending => {
+                if second_is_target {
+                    alpha_blend(top_color, second_color, registers.alpha_a, registers.alpha_b)
+                } else {
+                    top_color
+                }
+            }
+            BlendMode::IncreaseBrightness => {
+                map_components(top_color, |c| c + (((31 - c) * registers.brightness.min(16)) >> 4))
+            }
+            BlendMode::DecreaseBrightness => {
+                map_components(top_color, |c| c - ((c * registers.brightness.min(16)) >> 4))
+            }
+        }
+    }
+
+    fn render_text_bg(&self, bg: usize, buffer: &mut [u16; SCREEN_WIDTH as usize]) {
+        let control = self.registers.bg_control[bg];
+        let (width_tiles, height_tiles) = match control.screen_size {
+            0 => (32, 32),
+            1 => (64, 32),
+            2 => (32, 64),
+            _ => (64, 64),
+        };
+
+        let mosaic_h = if control.mosaic { self.registers.bg_mosaic_h } else { 1 };
+        let line = if control.mosaic {
+            self.line - self.line % self.registers.bg_mosaic_v
+        } else {
+            self.line
+        };
+
+        let y = (line + u32::from(self.registers.bg_v_scroll[bg])) & (height_tiles * 8 - 1);
+        let tile_row = y / 8;
+
+        for screen_x in 0..SCREEN_WIDTH {
+            if screen_x % mosaic_h != 0 {
+                buffer[screen_x as usize] = buffer[(screen_x - 1) as usize];
+                continue;
+            }
+
+            let x = (screen_x + u32::from(self.registers.bg_h_scroll[bg])) & (width_tiles * 8 - 1);
+            let tile_col = x / 8;
+
+            // Each 2KB screen block contains a 32x32 tile map
+            let screen_block = (tile_col / 32) + (tile_row / 32) * (width_tiles / 32);
+            let map_address = control.screen_base_addr
+                + screen_block * 0x800
+                + 2 * ((tile_row % 32) * 32 + (tile_col % 32));
+            let map_entry = self.vram_u16((map_address & 0xFFFF) as usize);
+
+            let tile_number = u32::from(map_entry & 0x3FF);
+            let h_flip = map_entry.bit(10);
+            let v_flip = map_entry.bit(11);
+            let palette = (map_entry >> 12) as usize;
+
+            let mut pixel_x = x % 8;
+            let mut pixel_y = y % 8;
+            if h_flip {
+                pixel_x = 7 - pixel_x;
+            }
+            if v_flip {
+                pixel_y = 7 - pixel_y;
+            }
+
+            let color_index = if control.bpp8 {
+                let address = control.char_base_addr + tile_number * 64 + pixel_y * 8 + pixel_x;
+                self.read_bg_tile_byte(address)
+            } else {
+                let address = control.char_base_addr + tile_number * 32 + pixel_y * 4 + pixel_x / 2;
+                let byte = self.read_bg_tile_byte(address);
+                let index = if pixel_x.bit(0) { byte >> 4 } else { byte & 0xF };
+                if index == 0 { 0 } else { (palette << 4) as u8 | index }
+            };
+
+            buffer[screen_x as usize] =
+                if color_index == 0 { TRANSPARENT } else { self.palette_color(color_index.into()) };
+        }
+    }
+
+    // BG tile data cannot come from OBJ VRAM
+    fn read_bg_tile_byte(&self, address: u32) -> u8 {
+        if address >= OBJ_VRAM_START { 0 } else { self.vram[address as usize] }
+    }
+
+    fn render_affine_bg(&self, bg: usize, buffer: &mut [u16; SCREEN_WIDTH as usize]) {
+        let control = self.registers.bg_control[bg];
+        let size_pixels = 128 << control.screen_size;
+        let size_tiles = size_pixels / 8;
+
+        self.render_affine_line(bg, buffer, |x, y| {
+            let (x, y) = if control.affine_wraparound {
+                (x.rem_euclid(size_pixels), y.rem_euclid(size_pixels))
+            } else if !(0..size_pixels).contains(&x) || !(0..size_pixels).contains(&y) {
+                return TRANSPARENT;
+            } else {
+                (x, y)
+            };
+            let (x, y) = (x as u32, y as u32);
+
+            let map_address = control.screen_base_addr + (y / 8) * size_tiles as u32 + x / 8;
+            let tile_number = u32::from(self.vram[(map_address & 0xFFFF) as usize]);
+
+            let tile_address = control.char_base_addr + tile_number * 64 + (y % 8) * 8 + x % 8;
+            let color_index = self.read_bg_tile_byte(tile_address);
+            if color_index == 0 { TRANSPARENT } else { self.palette_color(color_index.into()) }
+        });
+    }
+
+    fn render_bitmap_bg(&self, buffer: &mut [u16; SCREEN_WIDTH as usize]) {
+        let frame_address = if self.registers.frame_select { BITMAP_FRAME_1_ADDR } else { 0 };
+
+        match self.registers.bg_mode {
+            BgMode::Three => self.render_affine_line(2, buffer, |x, y| {
+                if !(0..SCREEN_WIDTH as i32).contains(&x) || !(0..SCREEN_HEIGHT as i32).contains(&y)
+                {
+                    return TRANSPARENT;
+                }
+
+                let address = 2 * (y as usize * SCREEN_WIDTH as usize + x as usize);
+                self.vram_u16(address) & 0x7FFF
+            }),
+            BgMode::Four => self.render_affine_line(2, buffer, |x, y| {
+                if !(0..SCREEN_WIDTH as i32).contains(&x) || !(0..SCREEN_HEIGHT as i32).contains(&y)
+                {
+                    return TRANSPARENT;
+                }
+
+                let address =
+                    frame_address as usize + y as usize * SCREEN_WIDTH as usize + x as usize;
+                let color_index = self.vram[address];
+                if color_index == 0 { TRANSPARENT } else { self.palette_color(color_index.into()) }
+            }),
+            BgMode::Five => self.render_affine_line(2, buffer, |x, y| {
+                if !(0..160).contains(&x) || !(0..128).contains(&y) {
+                    return TRANSPARENT;
+                }
+
+                let address = frame_address as usize + 2 * (y as usize * 160 + x as usize);
+                self.vram_u16(address) & 0x7FFF
+            }),
+            BgMode::Zero | BgMode::One | BgMode::Two => {}
+        }
+    }
+
+    // Samples a line of an affine or bitmap BG; `sample` receives integer texture coordinates
+    fn render_affine_line(
+        &self,
+        bg: usize,
+        buffer: &mut [u16; SCREEN_WIDTH as usize],
+        sample: impl Fn(i32, i32) -> u16,
+    ) {
+        let control = self.registers.bg_control[bg];
+        let affine = &self.registers.affine[bg - 2];
+        let mosaic_h = if control.mosaic { self.registers.bg_mosaic_h } else { 1 };
+
+        for screen_x in 0..SCREEN_WIDTH {
+            if screen_x % mosaic_h != 0 {
+                buffer[screen_x as usize] = buffer[(screen_x - 1) as usize];
+                continue;
+            }
+
+            let x = affine.current_x.wrapping_add(affine.a.wrapping_mul(screen_x as i32)) >> 8;
+            let y = affine.current_y.wrapping_add(affine.c.wrapping_mul(screen_x as i32)) >> 8;
+            buffer[screen_x as usize] = sample(x, y);
+        }
+    }
+
+    fn render_sprites(
+        &self,
+        obj_buffer: &mut [ObjPixel; SCREEN_WIDTH as usize],
+        obj_window: &mut [bool; SCREEN_WIDTH as usize],
+    ) {
+        let tile_base =
+            if self.registers.bg_mode.is_bitmap() { BITMAP_OBJ_VRAM_START } else { OBJ_VRAM_START };
+
+        for oam_idx in 0..128 {
+            let oam_address = 8 * oam_idx;
+            let attr0 = self.oam_u16(oam_address);
+            let attr1 = self.oam_u16(oam_address + 2);
+            let attr2 = self.oam_u16(oam_address + 4);
+
+            let affine = attr0.bit(8);
+            // Bit 9 is the double size flag for affine sprites and the disable flag otherwise
+            let double_size = affine && attr0.bit(9);
+            if !affine && attr0.bit(9) {
+                continue;
+            }
+
+            let mode = match (attr0 >> 10) & 3 {
+                0 => ObjMode::Normal,
+                1 => ObjMode::SemiTransparent,
+                2 => ObjMode::Window,
+                _ => continue,
+            };
+
+            let shape = usize::from((attr0 >> 14) & 3);
+            if shape == 3 {
+                continue;
+            }
+            let (width, height) = OBJ_SIZES[shape][usize::from(attr1 >> 14)];
+            let (bounds_width, bounds_height) =
+                if double_size { (2 * width, 2 * height) } else { (width, height) };
+
+            let y = u32::from(attr0 & 0xFF);
+            let sprite_line = self.line.wrapping_sub(y) & 0xFF;
+            if sprite_line >= bounds_height {
+                continue;
+            }
+
+            let mosaic = attr0.bit(12);
+            let sprite_line = if mosaic {
+                sprite_line - sprite_line % self.registers.obj_mosaic_v
+            } else {
+                sprite_line
+            };
+
+            // X is a 9-bit signed value
+            let x = i32::from(((attr1 << 7) as i16) >> 7);
+            let bpp8 = attr0.bit(13);
+            let tile_number = u32::from(attr2 & 0x3FF);
+            let priority = ((attr2 >> 10) & 3) as u8;
+            let palette = u32::from(attr2 >> 12);
+
+            // In bitmap modes, the lower half of OBJ VRAM is used by the bitmap
+            if tile_base == BITMAP_OBJ_VRAM_START && tile_number < 512 {
+                continue;
+            }
+
+            // Affine matrix as (pa, pb, pc, pd); non-affine sprites use identity or flip matrices
+            let matrix = if affine {
+                let param_address = 32 * usize::from((attr1 >> 9) & 0x1F);
+                [6, 14, 22, 30].map(|offset| i32::from(self.oam_u16(param_address + offset) as i16))
+            } else {
+                let h_flip = attr1.bit(12);
+                let v_flip = attr1.bit(13);
+                [if h_flip { -0x100 } else { 0x100 }, 0, 0, if v_flip { -0x100 } else { 0x100 }]
+            };
+
+            let half_width = bounds_width as i32 / 2;
+            let half_height = bounds_height as i32 / 2;
+            let dy = sprite_line as i32 - half_height;
+
+            for sprite_x in 0..bounds_width as i32 {
+                let screen_x = x + sprite_x;
+                if !(0..SCREEN_WIDTH as i32).contains(&screen_x) {
+                    continue;
+                }
+
+                let sample_x = if mosaic {
+                    sprite_x - screen_x % self.registers.obj_mosaic_h as i32
+                } else {
+                    sprite_x
+                };
+                let dx = sample_x - half_width;
+
+                // Texture coordinates relative to the sprite's center, then converted to top-left
+                let (mut tex_x, mut tex_y) = (
+                    ((matrix[0] * dx + matrix[1] * dy) >> 8) + width as i32 / 2,
+                    ((matrix[2] * dx + matrix[3] * dy) >> 8) + height as i32 / 2,
+                );
+                if !affine {
+                    // Flipping around the center maps pixel N to pixel (width - 1 - N)
+                    if matrix[0] < 0 {
+                        tex_x -= 1;
+                    }
+                    if matrix[3] < 0 {
+                        tex_y -= 1;
+                    }
+                }
+
+                if !(0..width as i32).contains(&tex_x) || !(0..height as i32).contains(&tex_y) {
+                    continue;
+                }
+                let (tex_x, tex_y) = (tex_x as u32, tex_y as u32);
+
+                let tile_x = tex_x / 8;
+                let tile_y = tex_y / 8;
+                let tile_units = if bpp8 { 2 } else { 1 };
+                let tile = if self.registers.obj_1d_mapping {
+                    tile_number + (tile_y * (width / 8) + tile_x) * tile_units
+                } else {
+                    let base = if bpp8 { tile_number & !1 } else { tile_number };
+                    base + tile_y * 32 + tile_x * tile_units
+                };
+                let tile_address = OBJ_VRAM_START + 32 * (tile & 0x3FF);
+
+                let (pixel_x, pixel_y) = (tex_x % 8, tex_y % 8);
+                let color_index = if bpp8 {
+                    self.read_obj_tile_byte(tile_address + pixel_y * 8 + pixel_x)
+                } else {
+                    let byte = self.read_obj_tile_byte(tile_address + pixel_y * 4 + pixel_x / 2);
+                    let index = if pixel_x.bit(0) { byte >> 4 } else { byte & 0xF };
+                    if index == 0 { 0 } else { ((palette << 4) as u8) | index }
+                };
+
+                if color_index == 0 {
+                    continue;
+                }
+
+                let screen_x = screen_x as usize;
+                if mode == ObjMode::Window {
+                    obj_window[screen_x] = true;
+                    continue;
+                }
+
+                let existing = &mut obj_buffer[screen_x];
+                if existing.color == TRANSPARENT || priority < existing.priority {
+                    *existing = ObjPixel {
+                        color: self.palette_color(0x100 + usize::from(color_index)),
+                        priority,
+                        semi_transparent: mode == ObjMode::SemiTransparent,
+                    };
+                }
+            }
+        }
+    }
+
+    fn read_obj_tile_byte(&self, address: u32) -> u8 {
+        self.vram[(address as usize).min(VRAM_LEN - 1)]
+    }
+}
+
+fn map_components(color: u16, f: impl Fn(u16) -> u16) -> u16 {
+    let r = f(color & 0x1F);
+    let g = f((color >> 5) & 0x1F);
+    let b = f((color >> 10) & 0x1F);
+    r | (g << 5) | (b << 10)
+}
+
+fn alpha_blend(top: u16, second: u16, alpha_a: u16, alpha_b: u16) -> u16 {
+    let (alpha_a, alpha_b) = (alpha_a.min(16), alpha_b.min(16));
+    let blend = |shift: u16| -> u16 {
+        let a = (top >> shift) & 0x1F;
+        let b = (second >> shift) & 0x1F;
+        ((a * alpha_a + b * alpha_b) >> 4).min(31)
+    };
+    blend(0) | (blend(5) << 5) | (blend(10) << 10)
+}
+
+fn bgr555_to_rgb888(color: u16) -> Color {
+    let expand = |c: u16| -> u8 {
+        let c = (c & 0x1F) as u8;
+        (c << 3) | (c >> 2)
+    };
+    Color::rgb(expand(color), expand(color >> 5), expand(color >> 10))
+}
@@ -0,0 +1,411 @@
+//! GBA PPU registers
+
+use bincode::{Decode, Encode};
+use jgenesis_common::num::GetBit;
+use std::array;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+pub enum BgMode {
+    // BG0-3 tiled
+    Zero,
+    // BG0-1 tiled, BG2 affine
+    One,
+    // BG2-3 affine
+    Two,
+    // BG2 240x160 15bpp bitmap
+    Three,
+    // BG2 240x160 8bpp bitmap, double buffered
+    Four,
+    // BG2 160x128 15bpp bitmap, double buffered
+    Five,
+}
+
+impl BgMode {
+    fn from_bits(bits: u16) -> Self {
+        match bits & 7 {
+            0 => Self::Zero,
+            1 => Self::One,
+            2 => Self::Two,
+            3 => Self::Three,
+            4 => Self::Four,
+            5 => Self::Five,
+            _ => {
+                log::warn!("Invalid BG mode {}", bits & 7);
+                Self::Zero
+            }
+        }
+    }
+
+    pub fn is_bitmap(self) -> bool {
+        matches!(self, Self::Three | Self::Four | Self::Five)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+pub enum BlendMode {
+    None,
+    AlphaBlending,
+    IncreaseBrightness,
+    DecreaseBrightness,
+}
+
+impl BlendMode {
+    fn from_bits(bits: u16) -> Self {
+        match bits & 3 {
+            0 => Self::None,
+            1 => Self::AlphaBlending,
+            2 => Self::IncreaseBrightness,
+            3 => Self::DecreaseBrightness,
+            _ => unreachable!("value & 3 is always <= 3"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Encode, Decode)]
+pub struct BgControl {
+    pub priority: u8,
+    pub char_base_addr: u32,
+    pub mosaic: bool,
+    pub bpp8: bool,
+    pub screen_base_addr: u32,
+    pub affine_wraparound: bool,
+    pub screen_size: u8,
+    raw: u16,
+}
+
+impl BgControl {
+    fn new() -> Self {
+        let mut control = Self {
+            priority: 0,
+            char_base_addr: 0,
+            mosaic: false,
+            bpp8: false,
+            screen_base_addr: 0,
+            affine_wraparound: false,
+            screen_size: 0,
+            raw: 0,
+        };
+        control.write(0);
+        control
+    }
+
+    fn write(&mut self, value: u16) {
+        self.priority = (value & 3) as u8;
+        self.char_base_addr = u32::from((value >> 2) & 3) * 16 * 1024;
+        self.mosaic = value.bit(6);
+        self.bpp8 = value.bit(7);
+        self.screen_base_addr = u32::from((value >> 8) & 0x1F) * 2 * 1024;
+        self.affine_wraparound = value.bit(13);
+        self.screen_size = (value >> 14) as u8;
+        self.raw = value;
+    }
+}
+
+/// Affine transformation parameters and reference point for BG2 or BG3.
+#[derive(Debug, Clone, Copy, Default, Encode, Decode)]
+pub struct AffineParameters {
+    pub a: i32,
+    pub b: i32,
+    pub c: i32,
+    pub d: i32,
+    pub reference_x: i32,
+    pub reference_y: i32,
+    // Internal reference point registers, incremented by B/D after every line
+    pub current_x: i32,
+    pub current_y: i32,
+}
+
+impl AffineParameters {
+    fn new() -> Self {
+        // Identity transform
+        Self { a: 0x100, d: 0x100, ..Self::default() }
+    }
+
+    pub fn latch_reference_point(&mut self) {
+        self.current_x = self.reference_x;
+        self.current_y = self.reference_y;
+    }
+
+    pub fn increment_reference_point(&mut self) {
+        self.current_x = self.current_x.wrapping_add(self.b);
+        self.current_y = self.current_y.wrapping_add(self.d);
+    }
+}
+
+// Reference points are 28-bit signed 20.8 fixed point values
+fn write_reference_point_half(reference: &mut i32, value: u16, high: bool) {
+    let raw = *reference as u32;
+    let raw = if high {
+        (raw & 0x0000_FFFF) | (u32::from(value & 0x0FFF) << 16)
+    } else {
+        (raw & 0xFFFF_0000) | u32::from(value)
+    };
+    *reference = ((raw << 4) as i32) >> 4;
+}
+
+#[derive(Debug, Clone, Copy, Default, Encode, Decode)]
+pub struct Window {
+    pub x1: u8,
+    pub x2: u8,
+    pub y1: u8,
+    pub y2: u8,
+}
+
+impl Window {
+    pub fn contains_line(self, line: u32) -> bool {
+        range_contains(self.y1.into(), self.y2.into(), line)
+    }
+
+    pub fn contains_pixel(self, x: u32) -> bool {
+        range_contains(self.x1.into(), self.x2.into(), x)
+    }
+}
+
+// Windows wrap around if the start coordinate is greater than the end coordinate
+fn range_contains(start: u32, end: u32, value: u32) -> bool {
+    if start <= end { (start..end).contains(&value) } else { value >= start || value < end }
+}
+
+#[derive(Debug, Clone, Encode, Decode)]
+pub struct Registers {
+    // DISPCNT
+    pub bg_mode: BgMode,
+    pub frame_select: bool,
+    pub hblank_obj_processing: bool,
+    pub obj_1d_mapping: bool,
+    pub forced_blanking: bool,
+    pub bg_enabled: [bool; 4],
+    pub obj_enabled: bool,
+    pub window_enabled: [bool; 2],
+    pub obj_window_enabled: bool,
+    dispcnt_raw: u16,
+    // DISPSTAT
+    pub vblank_irq_enabled: bool,
+    pub hblank_irq_enabled: bool,
+    pub vcounter_irq_enabled: bool,
+    pub vcount_target: u8,
+    // BGxCNT
+    pub bg_control: [BgControl; 4],
+    // BGxHOFS / BGxVOFS
+    pub bg_h_scroll: [u16; 4],
+    pub bg_v_scroll: [u16; 4],
+    // BG2 and BG3 affine parameters
+    pub affine: [AffineParameters; 2],
+    // WIN0H / WIN1H / WIN0V / WIN1V
+    pub windows: [Window; 2],
+    // WININ / WINOUT, as 6-bit layer masks (BG0-3, OBJ, color effects)
+    pub window_in_layers: [u8; 2],
+    pub window_out_layers: u8,
+    pub obj_window_layers: u8,
+    // MOSAIC
+    pub bg_mosaic_h: u32,
+    pub bg_mosaic_v: u32,
+    pub obj_mosaic_h: u32,
+    pub obj_mosaic_v: u32,
+    // BLDCNT / BLDALPHA / BLDY
+    pub blend_first_targets: u8,
+    pub blend_second_targets: u8,
+    pub blend_mode: BlendMode,
+    blend_raw: u16,
+    pub alpha_a: u16,
+    pub alpha_b: u16,
+    pub brightness: u16,
+}
+
+impl Registers {
+    pub fn new() -> Self {
+        Self {
+            bg_mode: BgMode::Zero,
+            frame_select: false,
+            hblank_obj_processing: false,
+            obj_1d_mapping: false,
+            forced_blanking: false,
+            bg_enabled: [false; 4],
+            obj_enabled: false,
+            window_enabled: [false; 2],
+            obj_window_enabled: false,
+            dispcnt_raw: 0,
+            vblank_irq_enabled: false,
+            hblank_irq_enabled: false,
+            vcounter_irq_enabled: false,
+            vcount_target: 0,
+            bg_control: array::from_fn(|_| BgControl::new()),
+            bg_h_scroll: [0; 4],
+            bg_v_scroll: [0; 4],
+            affine: array::from_fn(|_| AffineParameters::new()),
+            windows: [Window::default(); 2],
+            window_in_layers: [0; 2],
+            window_out_layers: 0,
+            obj_window_layers: 0,
+            bg_mosaic_h: 1,
+            bg_mosaic_v: 1,
+            obj_mosaic_h: 1,
+            obj_mosaic_v: 1,
+            blend_first_targets: 0,
+            blend_second_targets: 0,
+            blend_mode: BlendMode::None,
+            blend_raw: 0,
+            alpha_a: 0,
+            alpha_b: 0,
+            brightness: 0,
+        }
+    }
+
+    // $4000000: DISPCNT
+    pub fn write_dispcnt(&mut self, value: u16) {
+        self.bg_mode = BgMode::from_bits(value);
+        self.frame_select = value.bit(4);
+        self.hblank_obj_processing = value.bit(5);
+        self.obj_1d_mapping = value.bit(6);
+        self.forced_blanking = value.bit(7);
+        self.bg_enabled = array::from_fn(|i| value.bit(8 + i as u8));
+        self.obj_enabled = value.bit(12);
+        self.window_enabled = [value.bit(13), value.bit(14)];
+        self.obj_window_enabled = value.bit(15);
+        self.dispcnt_raw = value;
+
+        log::trace!("DISPCNT write: {value:04X}");
+    }
+
+    pub fn read_dispcnt(&self) -> u16 {
+        self.dispcnt_raw
+    }
+
+    // $4000004: DISPSTAT; bits 0-2 are read-only status flags
+    pub fn write_dispstat(&mut self, value: u16) {
+        self.vblank_irq_enabled = value.bit(3);
+        self.hblank_irq_enabled = value.bit(4);
+        self.vcounter_irq_enabled = value.bit(5);
+        self.vcount_target = (value >> 8) as u8;
+    }
+
+    pub fn read_dispstat_control(&self) -> u16 {
+        (u16::from(self.vblank_irq_enabled) << 3)
+            | (u16::from(self.hblank_irq_enabled) << 4)
+            | (u16::from(self.vcounter_irq_enabled) << 5)
+            | (u16::from(self.vcount_target) << 8)
+    }
+
+    // $4000008 + 2*i: BGxCNT
+    pub fn write_bgcnt(&mut self, i: usize, value: u16) {
+        // Bit 13 is only writable for BG2 and BG3
+        let value = if i < 2 { value & !(1 << 13) } else { value };
+        self.bg_control[i].write(value);
+    }
+
+    pub fn read_bgcnt(&self, i: usize) -> u16 {
+        self.bg_control[i].raw
+    }
+
+    // $4000020-$400003F: BG2/BG3 affine parameters
+    pub fn write_affine_register(&mut self, address: u32, value: u16) {
+        let affine = &mut self.affine[((address >> 4) & 1) as usize];
+        match address & 0xF {
+            0x0 => affine.a = (value as i16).into(),
+            0x2 => affine.b = (value as i16).into(),
+            0x4 => affine.c = (value as i16).into(),
+            0x6 => affine.d = (value as i16).into(),
+            0x8 | 0xA => {
+                write_reference_point_half(&mut affine.reference_x, value, address & 0xF == 0xA);
+                affine.current_x = affine.reference_x;
+            }
+            0xC | 0xE => {
+                write_reference_point_half(&mut affine.reference_y, value, address & 0xF == 0xE);
+                affine.current_y = affine.reference_y;
+            }
+            _ => unreachable!("value & 0xF is always <= 0xF, and address is aligned"),
+        }
+    }
+
+    // $4000040 + 2*i: WINxH
+    pub fn write_window_h(&mut self, i: usize, value: u16) {
+        self.windows[i].x1 = (value >> 8) as u8;
+        self.windows[i].x2 = value as u8;
+    }
+
+    // $4000044 + 2*i: WINxV
+    pub fn write_window_v(&mut self, i: usize, value: u16) {
+        self.windows[i].y1 = (value >> 8) as u8;
+        self.windows[i].y2 = value as u8;
+    }
+
+    // $4000048: WININ
+    pub fn write_winin(&mut self, value: u16) {
+        self.window_in_layers = [(value & 0x3F) as u8, ((value >> 8) & 0x3F) as u8];
+    }
+
+    pub fn read_winin(&self) -> u16 {
+        u16::from(self.window_in_layers[0]) | (u16::from(self.window_in_layers[1]) << 8)
+    }
+
+    // $400004A: WINOUT
+    pub fn write_winout(&mut self, value: u16) {
+        self.window_out_layers = (value & 0x3F) as u8;
+        self.obj_window_layers = ((value >> 8) & 0x3F) as u8;
+    }
+
+    pub fn read_winout(&self) -> u16 {
+        u16::from(self.window_out_layers) | (u16::from(self.obj_window_layers) << 8)
+    }
+
+    // $400004C: MOSAIC
+    pub fn write_mosaic(&mut self, value: u16) {
+        self.bg_mosaic_h = u32::from(value & 0xF) + 1;
+        self.bg_mosaic_v = u32::from((value >> 4) & 0xF) + 1;
+        self.obj_mosaic_h = u32::from((value >> 8) & 0xF) + 1;
+        self.obj_mosaic_v = u32::from((value >> 12) & 0xF) + 1;
+    }
+
+    // $4000050: BLDCNT
+    pub fn write_bldcnt(&mut self, value: u16) {
+        self.blend_first_targets = (value & 0x3F) as u8;
+        self.blend_mode = BlendMode::from_bits(value >> 6);
+        self.blend_second_targets = ((value >> 8) & 0x3F) as u8;
+        self.blend_raw = value & 0x3FFF;
+    }
+
+    pub fn read_bldcnt(&self) -> u16 {
+        self.blend_raw
+    }
+
+    // $4000052: BLDALPHA
+    pub fn write_bldalpha(&mut self, value: u16) {
+        self.alpha_a = value & 0x1F;
+        self.alpha_b = (value >> 8) & 0x1F;
+    }
+
+    pub fn read_bldalpha(&self) -> u16 {
+        self.alpha_a | (self.alpha_b << 8)
+    }
+
+    // $4000054: BLDY
+    pub fn write_bldy(&mut self, value: u16) {
+        self.brightness = value & 0x1F;
+    }
+
+    pub fn any_window_enabled(&self) -> bool {
+        self.window_enabled[0] || self.window_enabled[1] || self.obj_window_enabled
+    }
+
+    /// Whether the given BG is displayed in the current mode.
+    pub fn bg_active(&self, bg: usize) -> bool {
+        if !self.bg_enabled[bg] {
+            return false;
+        }
+
+        match self.bg_mode {
+            BgMode::Zero => true,
+            BgMode::One => bg <= 2,
+            BgMode::Two => bg >= 2,
+            BgMode::Three | BgMode::Four | BgMode::Five => bg == 2,
+        }
+    }
+
+    pub fn bg_is_affine(&self, bg: usize) -> bool {
+        match self.bg_mode {
+            BgMode::Zero => false,
+            BgMode::One => bg == 2,
+            BgMode::Two | BgMode::Three | BgMode::Four | BgMode::Five => true,
+        }
+    }
+}
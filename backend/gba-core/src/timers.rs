@@ -0,0 +1,146 @@
+//! GBA timers
+//!
+//! There are 4 16-bit up-counting timers. Each timer either increments at the system clock divided
+//! by 1/64/256/1024, or increments when the previous timer overflows (count-up / cascade mode).
+//! Timers 0 and 1 also clock the Direct Sound FIFOs.
+
+use crate::apu::Apu;
+use crate::interrupts::{InterruptRegisters, InterruptType};
+use bincode::{Decode, Encode};
+use jgenesis_common::num::GetBit;
+use std::array;
+
+const PRESCALER_SHIFTS: [u32; 4] = [0, 6, 8, 10];
+
+#[derive(Debug, Clone, Default, Encode, Decode)]
+struct Timer {
+    reload: u16,
+    counter: u16,
+    prescaler_shift: u32,
+    prescaler_counter: u32,
+    cascade: bool,
+    irq_enabled: bool,
+    enabled: bool,
+    control: u16,
+}
+
+impl Timer {
+    // Returns the number of times the counter overflowed
+    fn increment(&mut self, increments: u32) -> u32 {
+        let until_overflow = 0x10000 - u32::from(self.counter);
+        if increments < until_overflow {
+            self.counter += increments as u16;
+            return 0;
+        }
+
+        // Overflows reload the counter; any remaining increments count up from the reload value
+        let remaining = increments - until_overflow;
+        let period = 0x10000 - u32::from(self.reload);
+        self.counter = self.reload + (remaining % period) as u16;
+        1 + remaining / period
+    }
+
+    fn cycles_until_overflow(&self) -> Option<u32> {
+        if !self.enabled || self.cascade {
+            return None;
+        }
+
+        let increments = 0x10000 - u32::from(self.counter);
+        Some((increments << self.prescaler_shift) - self.prescaler_counter)
+    }
+}
+
+#[derive(Debug, Clone, Encode, Decode)]
+pub struct Timers {
+    timers: [Timer; 4],
+}
+
+impl Timers {
+    pub fn new() -> Self {
+        Self { timers: array::from_fn(|_| Timer::default()) }
+    }
+
+    // $4000100 + 4*i: TMxCNT_L read
+    pub fn read_counter(&self, i: usize) -> u16 {
+        self.timers[i].counter
+    }
+
+    // $4000100 + 4*i: TMxCNT_L write
+    pub fn write_reload(&mut self, i: usize, value: u16) {
+        self.timers[i].reload = value;
+    }
+
+    // $4000102 + 4*i: TMxCNT_H
+    pub fn read_control(&self, i: usize) -> u16 {
+        self.timers[i].control
+    }
+
+    pub fn write_control(&mut self, i: usize, value: u16) {
+        let timer = &mut self.timers[i];
+
+        let enabled = value.bit(7);
+        if enabled && !timer.enabled {
+            timer.counter = timer.reload;
+            timer.prescaler_counter = 0;
+        }
+
+        timer.prescaler_shift = PRESCALER_SHIFTS[usize::from(value & 3)];
+        // Timer 0 cannot cascade because there is no previous timer
+        timer.cascade = i != 0 && value.bit(2);
+        timer.irq_enabled = value.bit(6);
+        timer.enabled = enabled;
+        timer.control = value & 0x00C7;
+
+        log::trace!("TM{i}CNT_H write: {value:04X}");
+    }
+
+    pub fn tick(&mut self, cycles: u32, interrupts: &mut InterruptRegisters, apu: &mut Apu) {
+        for i in 0..4 {
+            let timer = &mut self.timers[i];
+            if !timer.enabled || timer.cascade {
+                continue;
+            }
+
+            timer.prescaler_counter += cycles;
+            let increments = timer.prescaler_counter >> timer.prescaler_shift;
+            timer.prescaler_counter &= (1 << timer.prescaler_shift) - 1;
+
+            self.increment(i, increments, interrupts, apu);
+        }
+    }
+
+    fn increment(
+        &mut self,
+        i: usize,
+        increments: u32,
+        interrupts: &mut InterruptRegisters,
+        apu: &mut Apu,
+    ) {
+        if increments == 0 {
+            return;
+        }
+
+        let overflows = self.timers[i].increment(increments);
+        if overflows == 0 {
+            return;
+        }
+
+        if self.timers[i].irq_enabled {
+            interrupts.set_flag(InterruptType::TIMERS[i]);
+        }
+
+        if i < 2 {
+            apu.timer_overflow(i, overflows);
+        }
+
+        if i < 3 && self.timers[i + 1].enabled && self.timers[i + 1].cascade {
+            self.increment(i + 1, overflows, interrupts, apu);
+        }
+    }
+
+    /// Minimum number of cycles until any prescaler-driven timer overflows, used to skip ahead
+    /// while the CPU is halted.
+    pub fn cycles_until_next_overflow(&self) -> Option<u32> {
+        self.timers.iter().filter_map(Timer::cycles_until_overflow).min()
+    }
+}
@@ -1,40 +1,93 @@
 //! Genesis public interface and main loop
 
 use crate::audio::GenesisAudioResampler;
+use crate::input::serial::GenesisSerialPort;
 use crate::input::{GenesisInputs, InputState};
 use crate::memory::{Cartridge, MainBus, MainBusSignals, MainBusWrites, Memory};
-use crate::vdp::{Vdp, VdpConfig, VdpTickEffect};
+use crate::soundlog::SoundLog;
+use crate::vdp::textures::{DumpedTile, TileReplacements};
+use crate::vdp::{
+    Vdp, VdpConfig, VdpDebugState, VdpEventLog, VdpTickEffect, WIDESCREEN_EXTRA_PIXELS,
+};
 use crate::ym2612::{Ym2612, YmTickEffect};
-use crate::GenesisControllerType;
+use crate::{GenesisControllerType, GenesisMultitap};
 use bincode::{Decode, Encode};
+use jgenesis_common::audio::ResamplerQuality;
+use jgenesis_common::debug::{CpuArchitecture, Debuggable, MemoryAccess, MemoryAccessLog};
 use jgenesis_common::frontend::{
     AudioOutput, Color, EmulatorTrait, FrameSize, PartialClone, PixelAspectRatio, Renderer,
     SaveWriter, TickEffect, TimingMode,
 };
 use jgenesis_common::num::GetBit;
+use jgenesis_common::rng::{InitialRamState, Rng};
+use jgenesis_common::state::StateFormat;
+use jgenesis_link::{BoxedLink, LinkTransport};
 use jgenesis_proc_macros::{EnumDisplay, EnumFromStr};
+use jgenesis_scheduler::ClockRatio;
+use m68000_emu::traits::LoggingBus;
 use m68000_emu::M68000;
 use smsgg_core::psg::{Psg, PsgTickEffect, PsgVersion};
 use std::fmt::{Debug, Display};
 use std::mem;
+use std::ops::RangeInclusive;
 use thiserror::Error;
-use z80_emu::Z80;
+use z80_emu::{RegisterSnapshot, Z80};
 
-const M68K_MCLK_DIVIDER: u64 = 7;
+pub(crate) const M68K_MCLK_DIVIDER: u64 = 7;
 const Z80_MCLK_DIVIDER: u64 = 15;
-const PSG_MCLK_DIVIDER: u64 = 15;
+pub(crate) const PSG_MCLK_DIVIDER: u64 = 15;
+
+const H40_WIDESCREEN_WIDTH: u32 = 320 + 2 * WIDESCREEN_EXTRA_PIXELS as u32;
 
 #[derive(Debug, Error)]
-pub enum GenesisError<RErr, AErr, SErr> {
+pub enum GenesisError<RErr, AErr> {
     #[error("Rendering error: {0}")]
     Render(RErr),
     #[error("Audio output error: {0}")]
     Audio(AErr),
-    #[error("Save write error: {0}")]
-    Save(SErr),
 }
 
-pub type GenesisResult<RErr, AErr, SErr> = Result<TickEffect, GenesisError<RErr, AErr, SErr>>;
+pub type GenesisResult<RErr, AErr> = Result<TickEffect, GenesisError<RErr, AErr>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct M68kDebugState {
+    pub data_registers: [u32; 8],
+    pub address_registers: [u32; 7],
+    pub user_stack_pointer: u32,
+    pub supervisor_stack_pointer: u32,
+    pub status_register: u16,
+    pub pc: u32,
+}
+
+impl M68kDebugState {
+    fn from_cpu(m68k: &M68000) -> Self {
+        Self {
+            data_registers: m68k.data_registers(),
+            address_registers: m68k.address_registers(),
+            user_stack_pointer: m68k.user_stack_pointer(),
+            supervisor_stack_pointer: m68k.supervisor_stack_pointer(),
+            status_register: m68k.status_register(),
+            pc: m68k.pc(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Z80DebugState {
+    pub registers: RegisterSnapshot,
+    pub busreq: bool,
+    pub reset: bool,
+    pub bus_acknowledged: bool,
+}
+
+/// Read-only snapshot of Genesis CPU and VDP state, intended for external tools that want to
+/// inspect emulator state without going through a debugger UI.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenesisDebugState {
+    pub m68k: M68kDebugState,
+    pub z80: Z80DebugState,
+    pub vdp: VdpDebugState,
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode, EnumDisplay, EnumFromStr)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -56,9 +109,9 @@ impl GenesisAspectRatio {
             (Self::SquarePixels, _) => Some(1.0),
             (Self::Stretched, _) => None,
             (Self::Ntsc, 256..=284) => Some(8.0 / 7.0),
-            (Self::Ntsc, 320..=347) => Some(32.0 / 35.0),
+            (Self::Ntsc, 320..=347 | H40_WIDESCREEN_WIDTH) => Some(32.0 / 35.0),
             (Self::Pal, 256..=284) => Some(11.0 / 8.0),
-            (Self::Pal, 320..=347) => Some(11.0 / 10.0),
+            (Self::Pal, 320..=347 | H40_WIDESCREEN_WIDTH) => Some(11.0 / 10.0),
             (Self::Ntsc | Self::Pal, _) => {
                 panic!("unexpected Genesis frame width: {}", frame_size.width)
             }
@@ -83,39 +136,32 @@ pub enum GenesisRegion {
 impl GenesisRegion {
     #[must_use]
     pub fn from_rom(rom: &[u8]) -> Option<Self> {
-        let region_bytes = &rom[0x1F0..0x1F3];
-
-        // Prefer Americas if region code contains a 'U'
-        if region_bytes.contains(&b'U') {
-            return Some(GenesisRegion::Americas);
-        }
-
-        // Otherwise, prefer Japan if it contains a 'J'
-        if region_bytes.contains(&b'J') {
-            return Some(GenesisRegion::Japan);
-        }
+        Self::from_rom_for_timing_mode(rom, None)
+    }
 
-        // Finally, prefer Europe if it contains an 'E'
-        if region_bytes.contains(&b'E') {
-            return Some(GenesisRegion::Europe);
-        }
+    /// Determine region from the ROM header, preferring a region that matches the given timing
+    /// mode if the cartridge supports more than one region.
+    ///
+    /// Some multi-region games check that the region and PAL bits in the version register are
+    /// consistent with each other and with the header, so forcing PAL timing on a game that lists
+    /// Europe as a supported region should also select the European region (and vice versa).
+    #[must_use]
+    pub fn from_rom_for_timing_mode(rom: &[u8], timing_mode: Option<TimingMode>) -> Option<Self> {
+        let region_bytes = rom.get(0x1F0..0x1F3)?;
+
+        // Prefer Americas, then Japan, then Europe
+        let mut supported_regions = [Self::Americas, Self::Japan, Self::Europe]
+            .into_iter()
+            .filter(|&region| header_supports_region(region_bytes, region));
+
+        let first_supported = supported_regions.clone().next();
+        let matches_timing_mode = |region: &Self| match timing_mode {
+            Some(TimingMode::Ntsc) => *region != Self::Europe,
+            Some(TimingMode::Pal) => *region == Self::Europe,
+            None => true,
+        };
 
-        // If region code contains neither a 'U' nor a 'J', treat it as a hex char
-        let c = region_bytes[0] as char;
-        let value = u8::from_str_radix(&c.to_string(), 16).ok()?;
-        if value.bit(2) {
-            // Bit 2 = Americas
-            Some(GenesisRegion::Americas)
-        } else if value.bit(0) {
-            // Bit 0 = Asia
-            Some(GenesisRegion::Japan)
-        } else if value.bit(3) {
-            // Bit 3 = Europe
-            Some(GenesisRegion::Europe)
-        } else {
-            // Invalid
-            None
-        }
+        supported_regions.find(matches_timing_mode).or(first_supported)
     }
 
     #[must_use]
@@ -124,19 +170,99 @@ impl GenesisRegion {
     }
 }
 
+/// Region reported to the game through the version register at $A10001.
+///
+/// Normally the version register reports the hardware region and whether the console is running at
+/// PAL or NTSC timing. Region-locked games check both, so running an import game at a different
+/// speed than its home region fails the region check. Spoofing reports a region and a matching
+/// PAL flag regardless of the actual timing mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode, EnumDisplay, EnumFromStr)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GenesisRegionSpoof {
+    /// Report the hardware region and the actual timing mode
+    #[default]
+    Disabled,
+    /// Report the hardware region with a PAL flag that matches it
+    Auto,
+    Americas,
+    Japan,
+    Europe,
+}
+
+impl GenesisRegionSpoof {
+    /// The region and PAL flag to report in the version register, or None if not spoofing.
+    #[must_use]
+    pub fn reported_region(self, hardware_region: GenesisRegion) -> Option<(GenesisRegion, bool)> {
+        let region = match self {
+            Self::Disabled => return None,
+            Self::Auto => hardware_region,
+            Self::Americas => GenesisRegion::Americas,
+            Self::Japan => GenesisRegion::Japan,
+            Self::Europe => GenesisRegion::Europe,
+        };
+
+        Some((region, region == GenesisRegion::Europe))
+    }
+}
+
+fn header_supports_region(region_bytes: &[u8], region: GenesisRegion) -> bool {
+    if region_bytes.iter().any(|b| matches!(b, b'U' | b'J' | b'E')) {
+        let region_char = match region {
+            GenesisRegion::Americas => b'U',
+            GenesisRegion::Japan => b'J',
+            GenesisRegion::Europe => b'E',
+        };
+        return region_bytes.contains(&region_char);
+    }
+
+    // If region code contains none of 'U', 'J', or 'E', treat it as a hex char
+    let Some(value) = (region_bytes[0] as char).to_digit(16) else { return false };
+    let value = value as u8;
+    match region {
+        // Bit 2 = Americas
+        GenesisRegion::Americas => value.bit(2),
+        // Bit 0 = Asia
+        GenesisRegion::Japan => value.bit(0),
+        // Bit 3 = Europe
+        GenesisRegion::Europe => value.bit(3),
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct GenesisEmulatorConfig {
     pub p1_controller_type: GenesisControllerType,
     pub p2_controller_type: GenesisControllerType,
+    pub multitap: GenesisMultitap,
+    /// I/O port connected to the serial transport set with
+    /// [`GenesisEmulator::set_serial_transport`]
+    pub serial_port: GenesisSerialPort,
     pub forced_timing_mode: Option<TimingMode>,
     pub forced_region: Option<GenesisRegion>,
+    pub region_spoof: GenesisRegionSpoof,
+    /// Report a console with TMSS in the version register (hardware version 1 instead of 0)
+    pub report_tmss: bool,
     pub aspect_ratio: GenesisAspectRatio,
     pub adjust_aspect_ratio_in_2x_resolution: bool,
     pub remove_sprite_limits: bool,
     pub emulate_non_linear_vdp_dac: bool,
     pub render_vertical_border: bool,
     pub render_horizontal_border: bool,
+    /// Render beyond the left and right edges of the screen in H40 mode; see
+    /// [`widescreen`](crate::widescreen)
+    pub widescreen: bool,
+    /// Output frames at 2x resolution with H32 lines stretched to match H40 lines, so that the
+    /// frame size does not change when games switch display modes. Does not affect emulation
+    pub hi_res_output: bool,
+    /// Install 128KB of VRAM like development hardware and the Tera Drive, for homebrew and
+    /// prototypes that use 128KB mode
+    pub expanded_vram: bool,
     pub quantize_ym2612_output: bool,
+    pub audio_resampler_quality: ResamplerQuality,
+    /// Initial contents of work RAM; if None, RAM is zeroed
+    pub initial_ram_state: Option<InitialRamState>,
+    /// Seed for all in-core randomness, including random initial RAM contents; if None, a seed is
+    /// chosen randomly at power on
+    pub rng_seed: Option<u64>,
 }
 
 impl GenesisEmulatorConfig {
@@ -147,6 +273,9 @@ impl GenesisEmulatorConfig {
             emulate_non_linear_dac: self.emulate_non_linear_vdp_dac,
             render_vertical_border: self.render_vertical_border,
             render_horizontal_border: self.render_horizontal_border,
+            widescreen: self.widescreen,
+            hi_res_output: self.hi_res_output,
+            expanded_vram: self.expanded_vram,
         }
     }
 }
@@ -183,9 +312,17 @@ pub struct GenesisEmulator {
     aspect_ratio: GenesisAspectRatio,
     adjust_aspect_ratio_in_2x_resolution: bool,
     audio_resampler: GenesisAudioResampler,
-    z80_mclk_cycles: u64,
-    psg_mclk_cycles: u64,
+    z80_clock: ClockRatio,
+    psg_clock: ClockRatio,
     wait_states: WaitStates,
+    external_ram_dirty: bool,
+    initial_ram_state: Option<InitialRamState>,
+    rng_seed: Option<u64>,
+    #[partial_clone(default)]
+    memory_access_log: MemoryAccessLog,
+    sound_log: SoundLog,
+    #[partial_clone(default)]
+    serial_transport: BoxedLink,
 }
 
 // This is a macro instead of a function so that it only mutably borrows the needed fields
@@ -197,6 +334,7 @@ macro_rules! new_main_bus {
             &mut $self.psg,
             &mut $self.ym2612,
             &mut $self.input,
+            &mut $self.sound_log,
             $self.timing_mode,
             MainBusSignals { z80_busack: $self.z80.stalled(), m68k_reset: $m68k_reset },
             std::mem::take(&mut $self.main_bus_writes),
@@ -217,8 +355,15 @@ impl GenesisEmulator {
         save_writer: &mut S,
     ) -> Self {
         let initial_ram = save_writer.load_bytes("sav").ok();
-        let cartridge = Cartridge::from_rom(rom, initial_ram, config.forced_region);
-        let memory = Memory::new(cartridge);
+        let cartridge =
+            Cartridge::from_rom(rom, initial_ram, config.forced_region, config.forced_timing_mode);
+        let mut rng = Rng::from_optional_seed(config.rng_seed);
+        let mut memory = Memory::new(
+            cartridge,
+            config.initial_ram_state.unwrap_or(InitialRamState::AllZeroes),
+            &mut rng,
+        );
+        memory.set_version_register_config(config.region_spoof, config.report_tmss);
 
         let timing_mode =
             config.forced_timing_mode.unwrap_or_else(|| match memory.hardware_region() {
@@ -232,7 +377,8 @@ impl GenesisEmulator {
         let vdp = Vdp::new(timing_mode, config.to_vdp_config());
         let psg = Psg::new(PsgVersion::Standard);
         let ym2612 = Ym2612::new(config.quantize_ym2612_output);
-        let input = InputState::new();
+        let mut input = InputState::new();
+        input.reload_config(config);
 
         // The Genesis does not allow TAS to lock the bus, so don't allow TAS writes
         let m68k = M68000::builder().allow_tas_writes(false).build();
@@ -249,10 +395,16 @@ impl GenesisEmulator {
             main_bus_writes: MainBusWrites::new(),
             aspect_ratio: config.aspect_ratio,
             adjust_aspect_ratio_in_2x_resolution: config.adjust_aspect_ratio_in_2x_resolution,
-            audio_resampler: GenesisAudioResampler::new(timing_mode),
-            z80_mclk_cycles: 0,
-            psg_mclk_cycles: 0,
+            audio_resampler: GenesisAudioResampler::new(timing_mode, config.audio_resampler_quality),
+            z80_clock: ClockRatio::divider(Z80_MCLK_DIVIDER),
+            psg_clock: ClockRatio::divider(PSG_MCLK_DIVIDER),
             wait_states: WaitStates::default(),
+            external_ram_dirty: false,
+            initial_ram_state: config.initial_ram_state,
+            rng_seed: config.rng_seed,
+            memory_access_log: MemoryAccessLog::new(),
+            sound_log: SoundLog::new(),
+            serial_transport: BoxedLink::default(),
         };
 
         // Reset CPU so that execution will start from the right place
@@ -266,6 +418,12 @@ impl GenesisEmulator {
         self.memory.game_title()
     }
 
+    /// Connect a serial transport to the I/O port selected by
+    /// [`GenesisEmulatorConfig::serial_port`]. Until this is called, nothing is connected.
+    pub fn set_serial_transport(&mut self, transport: Box<dyn LinkTransport + Send>) {
+        self.serial_transport = BoxedLink::new(transport);
+    }
+
     #[inline]
     #[must_use]
     pub fn has_sram(&self) -> bool {
@@ -281,6 +439,21 @@ impl GenesisEmulator {
         )
     }
 
+    /// Take a snapshot of the current CPU and VDP state. This does not modify emulator state.
+    #[must_use]
+    pub fn debug_state(&self) -> GenesisDebugState {
+        GenesisDebugState {
+            m68k: M68kDebugState::from_cpu(&self.m68k),
+            z80: Z80DebugState {
+                registers: self.z80.register_snapshot(),
+                busreq: self.memory.z80_busreq(),
+                reset: self.memory.z80_reset(),
+                bus_acknowledged: self.z80.stalled(),
+            },
+            vdp: self.vdp.debug_state(),
+        }
+    }
+
     pub fn copy_cram(&self, out: &mut [Color]) {
         self.vdp.copy_cram(out);
     }
@@ -288,6 +461,94 @@ impl GenesisEmulator {
     pub fn copy_vram(&self, out: &mut [Color], palette: u8, row_len: usize) {
         self.vdp.copy_vram(out, palette, row_len);
     }
+
+    pub fn import_vram(&mut self, pixels: &[Color], palette: u8, row_len: usize) {
+        self.vdp.import_vram(pixels, palette, row_len);
+    }
+
+    pub fn set_vdp_event_logging(&mut self, enabled: bool) {
+        self.vdp.set_event_logging(enabled);
+    }
+
+    #[must_use]
+    pub fn vdp_event_log(&self) -> &VdpEventLog {
+        self.vdp.event_log()
+    }
+
+    #[must_use]
+    pub fn sound_log_mut(&mut self) -> &mut SoundLog {
+        &mut self.sound_log
+    }
+
+    /// Set tile texture replacements; see [`textures`](crate::vdp::textures).
+    pub fn set_tile_replacements(&mut self, replacements: Option<TileReplacements>) {
+        self.vdp.set_tile_replacements(replacements);
+    }
+
+    /// Enable or disable recording of each tile the first time it is drawn.
+    pub fn set_tile_dump_enabled(&mut self, dump_enabled: bool) {
+        self.vdp.set_tile_dump_enabled(dump_enabled);
+    }
+
+    /// Take all tiles that were drawn for the first time since the last call, if dumping is
+    /// enabled.
+    #[must_use]
+    pub fn take_dumped_tiles(&mut self) -> Vec<DumpedTile> {
+        self.vdp.take_dumped_tiles()
+    }
+}
+
+impl Debuggable for GenesisEmulator {
+    fn cpu_architecture(&self) -> CpuArchitecture {
+        CpuArchitecture::M68000
+    }
+
+    fn cpu_registers(&self) -> Vec<u32> {
+        let status_register = self.m68k.status_register();
+        let supervisor_mode = status_register.bit(13);
+        let stack_pointer = if supervisor_mode {
+            self.m68k.supervisor_stack_pointer()
+        } else {
+            self.m68k.user_stack_pointer()
+        };
+
+        let mut registers = Vec::with_capacity(18);
+        registers.extend(self.m68k.data_registers());
+        registers.extend(self.m68k.address_registers());
+        registers.push(stack_pointer);
+        registers.push(status_register.into());
+        registers.push(self.m68k.pc());
+        registers
+    }
+
+    fn cpu_pc(&self) -> u32 {
+        self.m68k.pc()
+    }
+
+    fn at_instruction_boundary(&self) -> bool {
+        // Every call to tick() executes exactly one 68000 instruction
+        true
+    }
+
+    fn peek_memory(&self, address: u32) -> u8 {
+        self.memory.peek_byte(address)
+    }
+
+    fn poke_memory(&mut self, address: u32, value: u8) {
+        self.memory.poke_byte(address, value);
+    }
+
+    fn set_memory_access_logging(&mut self, enabled: bool) {
+        self.memory_access_log.set_enabled(enabled);
+    }
+
+    fn memory_accesses(&self) -> &[MemoryAccess] {
+        self.memory_access_log.accesses()
+    }
+
+    fn work_ram_range(&self) -> RangeInclusive<u32> {
+        0xFF0000..=0xFFFFFF
+    }
 }
 
 /// Render the current VDP frame buffer.
@@ -301,6 +562,17 @@ pub fn render_frame<R: Renderer>(
     adjust_aspect_ratio_in_2x_resolution: bool,
     renderer: &mut R,
 ) -> Result<(), R::Err> {
+    if vdp.config().hi_res_output {
+        // Every native H40 pixel is a 2x2 block in the hi-res frame, so the pixel aspect ratio is the
+        // same as for a native H40 frame
+        let frame_size = vdp.hi_res_frame_size();
+        let native_frame_size =
+            FrameSize { width: frame_size.width / 2, height: frame_size.height / 2 };
+        let pixel_aspect_ratio = aspect_ratio.to_pixel_aspect_ratio(native_frame_size, false);
+
+        return renderer.render_frame(vdp.hi_res_frame_buffer(), frame_size, pixel_aspect_ratio);
+    }
+
     let frame_width = vdp.screen_width();
     let frame_height = vdp.screen_height();
 
@@ -314,12 +586,12 @@ pub fn render_frame<R: Renderer>(
 impl EmulatorTrait for GenesisEmulator {
     type Inputs = GenesisInputs;
     type Config = GenesisEmulatorConfig;
+    const STATE_FORMAT: StateFormat = StateFormat { core: "genesis", version: 1 };
 
     type Err<
         RErr: Debug + Display + Send + Sync + 'static,
         AErr: Debug + Display + Send + Sync + 'static,
-        SErr: Debug + Display + Send + Sync + 'static,
-    > = GenesisError<RErr, AErr, SErr>;
+    > = GenesisError<RErr, AErr>;
 
     /// Execute one 68000 CPU instruction and run the rest of the components for the appropriate
     /// number of cycles.
@@ -329,42 +601,37 @@ impl EmulatorTrait for GenesisEmulator {
     /// This method will propagate any errors encountered while rendering frames or pushing audio
     /// samples.
     #[inline]
-    fn tick<R, A, S>(
+    fn tick<R, A>(
         &mut self,
         renderer: &mut R,
         audio_output: &mut A,
         inputs: &Self::Inputs,
-        save_writer: &mut S,
-    ) -> GenesisResult<R::Err, A::Err, S::Err>
+    ) -> GenesisResult<R::Err, A::Err>
     where
         R: Renderer,
         R::Err: Debug + Display + Send + Sync + 'static,
         A: AudioOutput,
         A::Err: Debug + Display + Send + Sync + 'static,
-        S: SaveWriter,
-        S::Err: Debug + Display + Send + Sync + 'static,
     {
+        self.memory_access_log.clear();
+
         let mut bus = new_main_bus!(self, m68k_reset: false);
         let m68k_cycles = if self.wait_states.m68k_cpu_cycles != 0 {
             mem::take(&mut self.wait_states.m68k_cpu_cycles)
+        } else if self.memory_access_log.is_enabled() {
+            self.m68k
+                .execute_instruction(&mut LoggingBus::new(&mut bus, &mut self.memory_access_log))
         } else {
             self.m68k.execute_instruction(&mut bus)
         };
 
         let elapsed_mclk_cycles = u64::from(m68k_cycles) * M68K_MCLK_DIVIDER;
 
-        self.z80_mclk_cycles += elapsed_mclk_cycles;
-        if self.z80_mclk_cycles >= self.wait_states.z80_mclk_cycles {
-            self.z80_mclk_cycles -= self.wait_states.z80_mclk_cycles;
-            self.wait_states.z80_mclk_cycles = 0;
-        } else {
-            self.wait_states.z80_mclk_cycles -= self.z80_mclk_cycles;
-            self.z80_mclk_cycles = 0;
-        }
+        self.z80_clock.advance(elapsed_mclk_cycles);
+        self.wait_states.z80_mclk_cycles = self.z80_clock.stall(self.wait_states.z80_mclk_cycles);
 
-        while self.z80_mclk_cycles >= Z80_MCLK_DIVIDER {
+        for _ in 0..self.z80_clock.take_cycles() {
             self.z80.tick(&mut bus);
-            self.z80_mclk_cycles -= Z80_MCLK_DIVIDER;
         }
 
         if bus.z80_accessed_68k_bus() {
@@ -376,15 +643,15 @@ impl EmulatorTrait for GenesisEmulator {
         self.memory.medium_mut().tick(m68k_cycles);
 
         self.input.tick(m68k_cycles);
+        self.input.serial_mut().exchange(&mut self.serial_transport);
+
+        self.sound_log.tick(elapsed_mclk_cycles);
 
-        self.psg_mclk_cycles += elapsed_mclk_cycles;
-        while self.psg_mclk_cycles >= PSG_MCLK_DIVIDER {
+        for _ in 0..self.psg_clock.tick(elapsed_mclk_cycles) {
             if self.psg.tick() == PsgTickEffect::Clocked {
                 let (psg_sample_l, psg_sample_r) = self.psg.sample();
                 self.audio_resampler.collect_psg_sample(psg_sample_l, psg_sample_r);
             }
-
-            self.psg_mclk_cycles -= PSG_MCLK_DIVIDER;
         }
 
         // The YM2612 uses the same master clock divider as the 68000
@@ -404,11 +671,9 @@ impl EmulatorTrait for GenesisEmulator {
 
             if self.memory.is_external_ram_persistent()
                 && self.memory.get_and_clear_external_ram_dirty()
+                && !self.memory.external_ram().is_empty()
             {
-                let ram = self.memory.external_ram();
-                if !ram.is_empty() {
-                    save_writer.persist_bytes("sav", ram).map_err(GenesisError::Save)?;
-                }
+                self.external_ram_dirty = true;
             }
 
             return Ok(TickEffect::FrameRendered);
@@ -417,6 +682,19 @@ impl EmulatorTrait for GenesisEmulator {
         Ok(TickEffect::None)
     }
 
+    fn save_dirty(&self) -> bool {
+        self.external_ram_dirty
+    }
+
+    fn persist_save<S: SaveWriter>(&mut self, save_writer: &mut S) -> Result<(), S::Err> {
+        if self.external_ram_dirty {
+            save_writer.persist_bytes("sav", self.memory.external_ram())?;
+            self.external_ram_dirty = false;
+        }
+
+        Ok(())
+    }
+
     fn force_render<R>(&mut self, renderer: &mut R) -> Result<(), R::Err>
     where
         R: Renderer,
@@ -428,12 +706,18 @@ impl EmulatorTrait for GenesisEmulator {
         self.aspect_ratio = config.aspect_ratio;
         self.adjust_aspect_ratio_in_2x_resolution = config.adjust_aspect_ratio_in_2x_resolution;
         self.vdp.reload_config(config.to_vdp_config());
+        self.memory.set_version_register_config(config.region_spoof, config.report_tmss);
         self.ym2612.set_quantize_output(config.quantize_ym2612_output);
+        self.audio_resampler.set_quality(config.audio_resampler_quality);
         self.input.reload_config(*config);
+        self.initial_ram_state = config.initial_ram_state;
+        self.rng_seed = config.rng_seed;
     }
 
     fn take_rom_from(&mut self, other: &mut Self) {
         self.memory.take_rom_from(&mut other.memory);
+        self.vdp.take_tile_textures_from(&mut other.vdp);
+        mem::swap(&mut self.serial_transport, &mut other.serial_transport);
     }
 
     fn soft_reset(&mut self) {
@@ -454,18 +738,31 @@ impl EmulatorTrait for GenesisEmulator {
         let config = GenesisEmulatorConfig {
             forced_timing_mode: Some(self.timing_mode),
             forced_region: Some(self.memory.hardware_region()),
+            region_spoof: self.memory.region_spoof(),
+            report_tmss: self.memory.report_tmss(),
             aspect_ratio: self.aspect_ratio,
             adjust_aspect_ratio_in_2x_resolution: self.adjust_aspect_ratio_in_2x_resolution,
             remove_sprite_limits: !vdp_config.enforce_sprite_limits,
             emulate_non_linear_vdp_dac: vdp_config.emulate_non_linear_dac,
             render_vertical_border: vdp_config.render_vertical_border,
             render_horizontal_border: vdp_config.render_horizontal_border,
+            widescreen: vdp_config.widescreen,
+            hi_res_output: vdp_config.hi_res_output,
+            expanded_vram: vdp_config.expanded_vram,
             quantize_ym2612_output: self.ym2612.get_quantize_output(),
+            audio_resampler_quality: self.audio_resampler.quality(),
             p1_controller_type,
             p2_controller_type,
+            multitap: self.input.multitap(),
+            serial_port: self.input.serial().connected_port(),
+            initial_ram_state: self.initial_ram_state,
+            rng_seed: self.rng_seed,
         };
 
-        *self = GenesisEmulator::create(rom, config, save_writer);
+        let mut emulator = GenesisEmulator::create(rom, config, save_writer);
+        emulator.vdp.take_tile_textures_from(&mut self.vdp);
+        emulator.serial_transport = mem::take(&mut self.serial_transport);
+        *self = emulator;
     }
 
     fn timing_mode(&self) -> TimingMode {
@@ -3,7 +3,7 @@
 #![allow(clippy::excessive_precision)]
 
 use bincode::{Decode, Encode};
-use jgenesis_common::audio::SignalResampler;
+use jgenesis_common::audio::{ResamplerQuality, SignalResampler};
 use jgenesis_common::frontend::{AudioOutput, TimingMode};
 use smsgg_core::audio::PsgResampler;
 use std::cmp;
@@ -66,7 +66,7 @@ pub struct GenesisAudioResampler {
 
 impl GenesisAudioResampler {
     #[must_use]
-    pub fn new(timing_mode: TimingMode) -> Self {
+    pub fn new(timing_mode: TimingMode, quality: ResamplerQuality) -> Self {
         let genesis_mclk_frequency = match timing_mode {
             TimingMode::Ntsc => NTSC_GENESIS_MCLK_FREQUENCY,
             TimingMode::Pal => PAL_GENESIS_MCLK_FREQUENCY,
@@ -75,7 +75,19 @@ impl GenesisAudioResampler {
         let ym2612_resampler = new_ym2612_resampler(genesis_mclk_frequency);
         let psg_resampler = smsgg_core::audio::new_psg_resampler(genesis_mclk_frequency);
 
-        Self { ym2612_resampler, psg_resampler }
+        let mut resampler = Self { ym2612_resampler, psg_resampler };
+        resampler.set_quality(quality);
+        resampler
+    }
+
+    #[must_use]
+    pub fn quality(&self) -> ResamplerQuality {
+        self.ym2612_resampler.quality()
+    }
+
+    pub fn set_quality(&mut self, quality: ResamplerQuality) {
+        self.ym2612_resampler.set_quality(quality);
+        self.psg_resampler.set_quality(quality);
     }
 
     pub fn collect_ym2612_sample(&mut self, sample_l: f64, sample_r: f64) {
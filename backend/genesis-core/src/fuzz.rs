@@ -0,0 +1,51 @@
+//! Entry points for the cargo-fuzz targets in the repository's `fuzz` directory
+
+use crate::memory::{Cartridge, PhysicalMedium};
+
+const MAX_BUS_ACCESSES: usize = 256;
+
+// Only matters for Virtua Racing, where ticking the cartridge runs the SVP
+const M68K_CYCLES_PER_ACCESS: u32 = 100;
+
+/// Load the input as a cartridge ROM and access it the same way that the 68000 bus does.
+///
+/// After loading, 6-byte chunks from the end of the ROM are interpreted as bus accesses: a 24-bit
+/// address, an access type byte (byte/word read/write), and a 16-bit value. Accesses outside of the
+/// ranges that the bus routes to the cartridge are skipped. Neither loading nor any sequence of
+/// accesses may panic.
+pub fn cartridge(data: &[u8]) {
+    let mut cartridge = Cartridge::from_rom(data.to_vec(), None, None, None);
+    let _ = cartridge.program_title();
+
+    for chunk in data.rchunks_exact(6).take(MAX_BUS_ACCESSES) {
+        let address = u32::from_be_bytes([0, chunk[0], chunk[1], chunk[2]]);
+        if !matches!(address, 0x000000..=0x7FFFFF | 0xA12000..=0xA1500F) {
+            continue;
+        }
+
+        // The 68000 never makes word accesses to odd addresses
+        let value = u16::from_be_bytes([chunk[4], chunk[5]]);
+        match chunk[3] & 0x03 {
+            0 => {
+                cartridge.read_byte(address);
+            }
+            1 => {
+                cartridge.read_word(address & !1);
+            }
+            2 => cartridge.write_byte(address, value as u8),
+            _ => cartridge.write_word(address & !1, value),
+        }
+
+        cartridge.tick(M68K_CYCLES_PER_ACCESS);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cartridge_corpus() {
+        jgenesis_common::fuzz::replay_corpus("genesis_cartridge", cartridge);
+    }
+}
@@ -1,5 +1,8 @@
 //! Code for handling Genesis controller input I/O registers
 
+pub mod serial;
+
+use crate::input::serial::SerialPorts;
 use crate::GenesisEmulatorConfig;
 use bincode::{Decode, Encode};
 use jgenesis_common::num::GetBit;
@@ -29,10 +32,25 @@ pub enum GenesisControllerType {
     SixButton,
 }
 
+/// Multiplayer adapter connected to the console, if any
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode, EnumFromStr, EnumDisplay)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GenesisMultitap {
+    #[default]
+    None,
+    /// EA 4-Way Play, which plugs into both controller ports
+    EaFourWayPlay,
+    /// Codemasters J-Cart, which has two extra controller ports on the cartridge
+    JCart,
+}
+
+/// P3 and P4 are only read when a multiplayer adapter is in use
 #[derive(Debug, Clone, Default, Encode, Decode)]
 pub struct GenesisInputs {
     pub p1: GenesisJoypadState,
     pub p2: GenesisJoypadState,
+    pub p3: GenesisJoypadState,
+    pub p4: GenesisJoypadState,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode)]
@@ -170,6 +188,12 @@ pub struct InputState {
     p2_controller_type: GenesisControllerType,
     p1_pin_directions: PinDirections,
     p2_pin_directions: PinDirections,
+    multitap: GenesisMultitap,
+    // EA 4-Way Play player select, set by writing to port 2 bits 4-6
+    ea_player_select: u8,
+    // J-Cart TH line, shared by both cartridge controller ports
+    jcart_th: bool,
+    serial: SerialPorts,
 }
 
 impl InputState {
@@ -185,6 +209,8 @@ impl InputState {
     pub fn reload_config(&mut self, config: GenesisEmulatorConfig) {
         self.p1_controller_type = config.p1_controller_type;
         self.p2_controller_type = config.p2_controller_type;
+        self.multitap = config.multitap;
+        self.serial.set_connected_port(config.serial_port);
     }
 
     #[must_use]
@@ -192,22 +218,94 @@ impl InputState {
         (self.p1_controller_type, self.p2_controller_type)
     }
 
+    #[must_use]
+    pub fn multitap(&self) -> GenesisMultitap {
+        self.multitap
+    }
+
+    #[must_use]
+    pub fn serial(&self) -> &SerialPorts {
+        &self.serial
+    }
+
+    pub fn serial_mut(&mut self) -> &mut SerialPorts {
+        &mut self.serial
+    }
+
     #[must_use]
     pub fn read_p1_data(&self) -> u8 {
+        if self.multitap == GenesisMultitap::EaFourWayPlay {
+            return self.read_ea_four_way_play();
+        }
+
         self.p1_pin_directions.to_data_byte(self.inputs.p1)
     }
 
     #[must_use]
     pub fn read_p2_data(&self) -> u8 {
+        if self.multitap == GenesisMultitap::EaFourWayPlay {
+            // Port 2 is used only to select the player; reads always return all 1s
+            return 0x7F;
+        }
+
         self.p2_pin_directions.to_data_byte(self.inputs.p2)
     }
 
+    fn read_ea_four_way_play(&self) -> u8 {
+        // Selecting players 5-8 is how games detect the adapter
+        if self.ea_player_select.bit(2) {
+            return 0x7C;
+        }
+
+        let joypad_state = match self.ea_player_select & 0x03 {
+            0 => self.inputs.p1,
+            1 => self.inputs.p2,
+            2 => self.inputs.p3,
+            3 => self.inputs.p4,
+            _ => unreachable!("value & 0x03 is always <= 0x03"),
+        };
+
+        // Controllers behind the adapter always behave as 3-button controllers
+        PinDirections { th_flip_count: 0, ..self.p1_pin_directions }.to_data_byte(joypad_state)
+    }
+
     pub fn write_p1_data(&mut self, value: u8) {
-        self.p1_pin_directions.write_data(value, self.p1_controller_type);
+        let controller_type = match self.multitap {
+            GenesisMultitap::EaFourWayPlay => GenesisControllerType::ThreeButton,
+            _ => self.p1_controller_type,
+        };
+        self.p1_pin_directions.write_data(value, controller_type);
     }
 
     pub fn write_p2_data(&mut self, value: u8) {
         self.p2_pin_directions.write_data(value, self.p2_controller_type);
+
+        // The player select lines are only driven if TH, TR, and TL are all set to output
+        if self.multitap == GenesisMultitap::EaFourWayPlay
+            && self.p2_pin_directions.to_ctrl_byte() & 0x70 == 0x70
+        {
+            self.ea_player_select = (value >> 4) & 0x07;
+        }
+    }
+
+    /// Read from the J-Cart controller ports. P3 is in the low byte and P4 is in the high byte.
+    #[must_use]
+    pub fn read_jcart(&self) -> u16 {
+        // The cartridge drives TH for both controllers; other lines are inputs
+        let pin_directions = PinDirections {
+            last_data_write: u8::from(self.jcart_th) << 6,
+            th: InputPinDirection::Output,
+            ..PinDirections::default()
+        };
+
+        // P4 TH always reads 0 (Micro Machines 2 depends on this)
+        let p3 = pin_directions.to_data_byte(self.inputs.p3) & 0x7F;
+        let p4 = pin_directions.to_data_byte(self.inputs.p4) & 0x3F;
+        u16::from_be_bytes([p4, p3])
+    }
+
+    pub fn write_jcart(&mut self, value: u8) {
+        self.jcart_th = value.bit(0);
     }
 
     #[must_use]
@@ -231,5 +329,6 @@ impl InputState {
     pub fn tick(&mut self, m68k_cycles: u32) {
         self.p1_pin_directions.tick(m68k_cycles);
         self.p2_pin_directions.tick(m68k_cycles);
+        self.serial.tick(m68k_cycles);
     }
 }
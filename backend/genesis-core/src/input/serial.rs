@@ -0,0 +1,231 @@
+//! Controller port serial mode
+//!
+//! Each of the three I/O ports (both controller ports and the EXT port on early Japanese consoles)
+//! can switch its TR and TL pins over to a UART that sends and receives bytes at 300 to 4800 baud.
+//! One port is connected to a [`LinkTransport`] supplied by the frontend; the other ports behave as
+//! if nothing is plugged in.
+//!
+//! The Mega Modem plugs into the EXT port. The modem itself is not emulated, but transmissions
+//! always complete at the selected baud rate, so Meganet software times out waiting for a response
+//! and reports that the modem is not responding rather than waiting forever for the transmit buffer
+//! to empty.
+
+use bincode::{Decode, Encode};
+use jgenesis_link::uart::Uart;
+use jgenesis_link::{LinkTransport, NullLink};
+use jgenesis_proc_macros::{EnumDisplay, EnumFromStr};
+
+// 53.693175 MHz / 7; the baud rate generator runs slightly slower on PAL consoles, but not by
+// enough to matter to software
+const M68K_CLOCK_HZ: u32 = 7_670_454;
+
+/// I/O port that the serial transport is connected to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode, EnumDisplay, EnumFromStr)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GenesisSerialPort {
+    Port1,
+    Port2,
+    /// The EXT port on the back of early Japanese consoles, which the Mega Modem plugs into
+    #[default]
+    Extension,
+}
+
+impl GenesisSerialPort {
+    fn index(self) -> usize {
+        match self {
+            Self::Port1 => 0,
+            Self::Port2 => 1,
+            Self::Extension => 2,
+        }
+    }
+}
+
+/// Serial registers for all three I/O ports, mapped at $A1000E-$A1001F
+#[derive(Debug, Clone, Encode, Decode)]
+pub struct SerialPorts {
+    ports: [Uart; 3],
+    connected_port: GenesisSerialPort,
+}
+
+impl SerialPorts {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            ports: [Uart::new(M68K_CLOCK_HZ), Uart::new(M68K_CLOCK_HZ), Uart::new(M68K_CLOCK_HZ)],
+            connected_port: GenesisSerialPort::default(),
+        }
+    }
+
+    #[must_use]
+    pub fn connected_port(&self) -> GenesisSerialPort {
+        self.connected_port
+    }
+
+    pub fn set_connected_port(&mut self, port: GenesisSerialPort) {
+        self.connected_port = port;
+    }
+
+    // Registers are at odd addresses, three per port: TxData, RxData, S-Ctrl
+    fn decode_address(address: u32) -> (usize, u32) {
+        let register_idx = ((address & 0x1F) >> 1) - 7;
+        ((register_idx / 3) as usize, register_idx % 3)
+    }
+
+    /// Read a register. `address` must be in $A1000E-$A1001F.
+    pub fn read_register(&mut self, address: u32) -> u8 {
+        let (port_idx, register) = Self::decode_address(address);
+        let port = &mut self.ports[port_idx];
+        match register {
+            0 => port.read_tx_data(),
+            1 => port.read_rx_data(),
+            2 => port.read_ctrl(),
+            _ => unreachable!("value % 3 is always < 3"),
+        }
+    }
+
+    /// Write a register. `address` must be in $A1000E-$A1001F.
+    pub fn write_register(&mut self, address: u32, value: u8) {
+        let (port_idx, register) = Self::decode_address(address);
+        let port = &mut self.ports[port_idx];
+        match register {
+            0 => port.write_tx_data(value),
+            // RxData is read-only
+            1 => {}
+            2 => port.write_ctrl(value),
+            _ => unreachable!("value % 3 is always < 3"),
+        }
+    }
+
+    pub fn tick(&mut self, m68k_cycles: u32) {
+        for port in &mut self.ports {
+            port.tick(m68k_cycles);
+        }
+    }
+
+    /// Hand any transmitted bytes to the transport and poll it for received bytes. Bytes
+    /// transmitted from ports other than the connected port are discarded.
+    pub fn exchange<T: LinkTransport + ?Sized>(&mut self, transport: &mut T) {
+        let connected_idx = self.connected_port.index();
+        for (i, port) in self.ports.iter_mut().enumerate() {
+            if i == connected_idx {
+                port.exchange(transport);
+            } else {
+                port.exchange(&mut NullLink);
+            }
+        }
+    }
+
+    /// Whether any port with receive interrupts enabled has a byte ready. This drives the level 2
+    /// external interrupt, which must also be enabled in VDP register #11.
+    #[must_use]
+    pub fn interrupt_pending(&self) -> bool {
+        self.ports.iter().any(Uart::interrupt_pending)
+    }
+}
+
+impl Default for SerialPorts {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+
+    #[derive(Default)]
+    struct LoopbackTransport {
+        sent: Vec<u8>,
+        incoming: VecDeque<u8>,
+    }
+
+    impl LinkTransport for LoopbackTransport {
+        fn send(&mut self, byte: u8) {
+            self.sent.push(byte);
+        }
+
+        fn receive(&mut self) -> Option<u8> {
+            self.incoming.pop_front()
+        }
+    }
+
+    fn m68k_cycles_per_byte(baud_rate: u32) -> u32 {
+        M68K_CLOCK_HZ * 10 / baud_rate
+    }
+
+    const EXT_TX_DATA: u32 = 0xA1001B;
+    const EXT_RX_DATA: u32 = 0xA1001D;
+    const EXT_S_CTRL: u32 = 0xA1001F;
+    const P1_TX_DATA: u32 = 0xA1000F;
+    const P1_S_CTRL: u32 = 0xA10013;
+
+    #[test]
+    fn power_on_values() {
+        let mut serial = SerialPorts::new();
+        for tx_address in [P1_TX_DATA, 0xA10015, EXT_TX_DATA] {
+            assert_eq!(serial.read_register(tx_address), 0xFF);
+            assert_eq!(serial.read_register(tx_address + 2), 0x00);
+            assert_eq!(serial.read_register(tx_address + 4), 0x00);
+        }
+    }
+
+    #[test]
+    fn transmit_completes_at_baud_rate() {
+        let mut serial = SerialPorts::new();
+        let mut transport = LoopbackTransport::default();
+
+        // 1200 baud, serial out
+        serial.write_register(EXT_S_CTRL, 0x90);
+        serial.write_register(EXT_TX_DATA, 0x41);
+        assert_eq!(serial.read_register(EXT_S_CTRL), 0x91);
+
+        let cycles_per_byte = m68k_cycles_per_byte(1200);
+        serial.tick(cycles_per_byte - 1);
+        serial.exchange(&mut transport);
+        assert_eq!(serial.read_register(EXT_S_CTRL) & 0x01, 0x01);
+        assert!(transport.sent.is_empty());
+
+        serial.tick(1);
+        serial.exchange(&mut transport);
+        assert_eq!(serial.read_register(EXT_S_CTRL) & 0x01, 0x00);
+        assert_eq!(transport.sent, vec![0x41]);
+    }
+
+    #[test]
+    fn only_connected_port_uses_transport() {
+        let mut serial = SerialPorts::new();
+        let mut transport = LoopbackTransport::default();
+
+        serial.write_register(P1_S_CTRL, 0x10);
+        serial.write_register(P1_TX_DATA, 0x55);
+        serial.tick(m68k_cycles_per_byte(4800));
+        serial.exchange(&mut transport);
+
+        // Transmission still completes so that software polling TFUL does not hang
+        assert_eq!(serial.read_register(P1_S_CTRL) & 0x01, 0x00);
+        assert!(transport.sent.is_empty());
+    }
+
+    #[test]
+    fn receive_sets_ready_and_interrupt() {
+        let mut serial = SerialPorts::new();
+        let mut transport = LoopbackTransport::default();
+        transport.incoming.extend([0x12, 0x34]);
+
+        // Serial in with receive interrupts
+        serial.write_register(EXT_S_CTRL, 0x28);
+        serial.exchange(&mut transport);
+        assert_eq!(serial.read_register(EXT_S_CTRL), 0x2A);
+        assert!(serial.interrupt_pending());
+
+        // Second byte overruns the first because it was never read
+        serial.tick(m68k_cycles_per_byte(4800));
+        serial.exchange(&mut transport);
+        assert_eq!(serial.read_register(EXT_S_CTRL), 0x2E);
+
+        assert_eq!(serial.read_register(EXT_RX_DATA), 0x34);
+        assert_eq!(serial.read_register(EXT_S_CTRL), 0x28);
+        assert!(!serial.interrupt_pending());
+    }
+}
@@ -1,13 +1,31 @@
+//! Sega Genesis / Mega Drive emulation core
+//!
+//! Everything needed to run games is re-exported at the crate root: create a [`GenesisEmulator`]
+//! from a ROM and a [`GenesisEmulatorConfig`], then drive it through
+//! [`EmulatorTrait`](jgenesis_common::frontend::EmulatorTrait). Save states are handled by
+//! [`jgenesis_common::state`] and memory can be inspected through
+//! [`Debuggable`](jgenesis_common::debug::Debuggable). The public submodules exist for the Sega CD
+//! core and for debugging tools, and are not covered by the same stability guarantees.
+
 mod api;
 pub mod audio;
+#[cfg(any(test, feature = "fuzzing"))]
+pub mod fuzz;
 pub mod input;
 pub mod memory;
+pub mod pico;
+pub mod soundlog;
 mod svp;
 pub mod vdp;
+pub mod widescreen;
 pub mod ym2612;
 
 pub use api::{
-    render_frame, GenesisAspectRatio, GenesisEmulator, GenesisEmulatorConfig, GenesisError,
-    GenesisRegion, GenesisResult,
+    render_frame, GenesisAspectRatio, GenesisDebugState, GenesisEmulator, GenesisEmulatorConfig,
+    GenesisError, GenesisRegion, GenesisRegionSpoof, GenesisResult, M68kDebugState, Z80DebugState,
 };
-pub use input::{GenesisControllerType, GenesisInputs, GenesisJoypadState};
+pub use input::serial::GenesisSerialPort;
+pub use input::{GenesisControllerType, GenesisInputs, GenesisJoypadState, GenesisMultitap};
+pub use memory::lockon::{attach_lock_on_cartridge, is_sonic_and_knuckles, LockOnError};
+pub use vdp::textures::{DumpedTile, TileImage, TileReplacements};
+pub use widescreen::{WidescreenPatchError, WidescreenPatches};
@@ -2,17 +2,21 @@
 
 mod eeprom;
 mod external;
+pub mod lockon;
 
-use crate::api::GenesisRegion;
-use crate::input::InputState;
+use crate::api::{GenesisRegion, GenesisRegionSpoof};
+use crate::input::{GenesisMultitap, InputState};
 use crate::memory::external::ExternalMemory;
+use crate::soundlog::{SoundLog, SoundWriteSource};
 use crate::svp::Svp;
 use crate::vdp::Vdp;
 use crate::ym2612::Ym2612;
 use bincode::{Decode, Encode};
 use jgenesis_common::frontend::TimingMode;
 use jgenesis_common::num::{GetBit, U16Ext};
+use jgenesis_common::rng::{InitialRamState, Rng};
 use jgenesis_proc_macros::{FakeDecode, FakeEncode, PartialClone};
+use jgenesis_state::SaveState;
 use regex::Regex;
 use smsgg_core::psg::Psg;
 use std::ops::Index;
@@ -66,9 +70,10 @@ impl SegaMapper {
             return address;
         }
 
+        // $400000-$7FFFFF is past the end of the cartridge address space and is not banked
         let idx = (address - 0x080000) >> 19;
-        let bank_number: u32 = self.bank_numbers[idx as usize].into();
-        (bank_number << 19) | (address & 0x07FFFF)
+        let Some(&bank_number) = self.bank_numbers.get(idx as usize) else { return address };
+        (u32::from(bank_number) << 19) | (address & 0x07FFFF)
     }
 }
 
@@ -78,6 +83,7 @@ pub struct Cartridge {
     rom: Rom,
     external_memory: ExternalMemory,
     ram_mapped: bool,
+    ram_writable: bool,
     mapper: Option<SegaMapper>,
     svp: Option<Svp>,
     region: GenesisRegion,
@@ -88,27 +94,41 @@ impl Cartridge {
         rom_bytes: Vec<u8>,
         initial_ram_bytes: Option<Vec<u8>>,
         forced_region: Option<GenesisRegion>,
+        forced_timing_mode: Option<TimingMode>,
     ) -> Self {
         let region = forced_region.unwrap_or_else(|| {
-            GenesisRegion::from_rom(&rom_bytes).unwrap_or_else(|| {
+            let region = GenesisRegion::from_rom_for_timing_mode(&rom_bytes, forced_timing_mode);
+            region.unwrap_or_else(|| {
                 log::warn!("Unable to determine cartridge region from ROM header; using Americas");
                 GenesisRegion::Americas
             })
         });
         log::info!("Genesis hardware region: {region:?}");
 
-        let external_memory = ExternalMemory::from_rom(&rom_bytes, initial_ram_bytes);
+        // With a cartridge locked on to Sonic & Knuckles, cartridge RAM belongs to the locked-on
+        // cartridge and is described by its header
+        let ram_header_rom = if lockon::has_lock_on_cartridge(&rom_bytes) {
+            log::info!("Detected Sonic & Knuckles with a locked-on cartridge");
+            &rom_bytes[lockon::LOCK_ON_ADDRESS..]
+        } else {
+            &rom_bytes[..]
+        };
+        let external_memory = ExternalMemory::from_rom(ram_header_rom, initial_ram_bytes);
 
-        // Initialize ram_mapped to true if external memory is present
-        // Only one game ever unmaps RAM (Phantasy Star 4)
-        let ram_mapped = !matches!(external_memory, ExternalMemory::None);
+        // Initialize ram_mapped to true if external memory is present, unless cartridge RAM overlaps
+        // the ROM; in that case the game must map RAM in through $A130F1 before using it
+        let ram_mapped = match external_memory.ram_start_address() {
+            Some(ram_start_address) => rom_bytes.len() <= ram_start_address as usize,
+            None => !matches!(external_memory, ExternalMemory::None),
+        };
+        log::info!("Cartridge RAM initially mapped: {ram_mapped}");
 
         // Only one game uses the bank switching Sega mapper, Super Street Fighter 2
-        let serial_number = &rom_bytes[0x183..0x18B];
+        let serial_number = rom_bytes.get(0x183..0x18B).unwrap_or_default();
         let is_ssf2 = is_super_street_fighter_2(serial_number);
 
         // Additionally enable the bank switching mapper for any cartridge that declares its system type as "SEGA SSF"
-        let is_ssf_system = &rom_bytes[0x100..0x110] == b"SEGA SSF        ";
+        let is_ssf_system = rom_bytes.get(0x100..0x110) == Some(b"SEGA SSF        ");
 
         let mapper = (is_ssf2 || is_ssf_system).then(SegaMapper::new);
         log::info!("Using Sega banked mapper: {}", mapper.is_some());
@@ -116,7 +136,15 @@ impl Cartridge {
         // Only one game uses the SVP, Virtua Racing
         let svp = is_virtua_racing(serial_number).then(Svp::new);
 
-        Self { rom: Rom(rom_bytes), external_memory, ram_mapped, mapper, svp, region }
+        Self {
+            rom: Rom(rom_bytes),
+            external_memory,
+            ram_mapped,
+            ram_writable: true,
+            mapper,
+            svp,
+            region,
+        }
     }
 
     #[inline]
@@ -126,27 +154,44 @@ impl Cartridge {
         }
     }
 
+    fn peek_byte(&self, address: u32) -> u8 {
+        if self.ram_mapped {
+            if let Some(byte) = self.external_memory.read_byte(address) {
+                return byte;
+            }
+        }
+
+        let rom_addr = self.mapper.map_or(address, |mapper| mapper.map_address(address));
+        self.rom.get(rom_addr as usize).unwrap_or(0xFF)
+    }
+
     fn write_cartridge_register(&mut self, address: u32, value: u8) {
         match address {
             0xA130F1 => {
+                // Bit 0 maps RAM over the ROM, bit 1 write protects RAM
                 self.ram_mapped = value.bit(0);
+                self.ram_writable = !value.bit(1);
             }
             0xA130F3..=0xA130FF => {
                 if let Some(mapper) = &mut self.mapper {
                     mapper.write(address, value);
                 }
             }
-            _ => panic!(
-                "unexpected cartridge register write; address={address:06X}, value={value:02X}"
-            ),
+            _ => {
+                // Some unlicensed cartridges have registers in this range, but licensed games
+                // should never write anywhere other than $A130F1-$A130FF
+                log::debug!(
+                    "Unexpected cartridge register write; address={address:06X}, value={value:02X}"
+                );
+            }
         }
     }
 
-    fn take_rom(&mut self) -> Vec<u8> {
+    pub fn take_rom(&mut self) -> Vec<u8> {
         mem::take(&mut self.rom).0
     }
 
-    fn take_rom_from(&mut self, other: &mut Self) {
+    pub fn take_rom_from(&mut self, other: &mut Self) {
         self.rom = mem::take(&mut other.rom);
     }
 
@@ -162,14 +207,17 @@ impl Cartridge {
         self.external_memory.get_and_clear_dirty_bit()
     }
 
-    fn program_title(&self) -> String {
+    /// Returns an empty string if the ROM is too small to contain a header.
+    #[allow(clippy::missing_panics_doc)]
+    #[must_use]
+    pub fn program_title(&self) -> String {
         static RE: OnceLock<Regex> = OnceLock::new();
 
         let addr = match self.region {
             GenesisRegion::Americas | GenesisRegion::Europe => 0x0150,
             GenesisRegion::Japan => 0x0120,
         };
-        let bytes = &self.rom.0[addr..addr + 48];
+        let bytes = self.rom.0.get(addr..addr + 48).unwrap_or_default();
         let title = bytes.iter().copied().map(|b| b as char).collect::<String>();
 
         let re = RE.get_or_init(|| Regex::new(r" +").unwrap());
@@ -257,7 +305,7 @@ impl PhysicalMedium for Cartridge {
 
         match address {
             0x000000..=0x3FFFFF => {
-                if self.ram_mapped {
+                if self.ram_mapped && self.ram_writable {
                     self.external_memory.write_byte(address, value);
                 }
             }
@@ -277,7 +325,7 @@ impl PhysicalMedium for Cartridge {
 
         match address {
             0x000000..=0x3FFFFF => {
-                if self.ram_mapped {
+                if self.ram_mapped && self.ram_writable {
                     self.external_memory.write_word(address, value);
                 }
             }
@@ -294,7 +342,7 @@ impl PhysicalMedium for Cartridge {
     }
 }
 
-const MAIN_RAM_LEN: usize = 64 * 1024;
+pub(crate) const MAIN_RAM_LEN: usize = 64 * 1024;
 const AUDIO_RAM_LEN: usize = 8 * 1024;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode)]
@@ -327,29 +375,76 @@ impl Default for Signals {
     }
 }
 
-#[derive(Debug, Encode, Decode, PartialClone)]
+#[derive(Debug, SaveState, PartialClone)]
 pub struct Memory<Medium> {
     #[partial_clone(partial)]
     physical_medium: Medium,
+    #[save_state(big_array)]
     main_ram: Box<[u8; MAIN_RAM_LEN]>,
+    #[save_state(big_array)]
     audio_ram: Box<[u8; AUDIO_RAM_LEN]>,
     z80_bank_register: Z80BankRegister,
     signals: Signals,
+    region_spoof: GenesisRegionSpoof,
+    report_tmss: bool,
 }
 
 impl<Medium: PhysicalMedium> Memory<Medium> {
     #[allow(clippy::missing_panics_doc)]
     #[must_use]
-    pub fn new(physical_medium: Medium) -> Self {
+    pub fn new(physical_medium: Medium, initial_ram_state: InitialRamState, rng: &mut Rng) -> Self {
+        let mut main_ram: Box<[u8; MAIN_RAM_LEN]> =
+            vec![0; MAIN_RAM_LEN].into_boxed_slice().try_into().unwrap();
+        let mut audio_ram: Box<[u8; AUDIO_RAM_LEN]> =
+            vec![0; AUDIO_RAM_LEN].into_boxed_slice().try_into().unwrap();
+        initial_ram_state.fill(main_ram.as_mut(), rng);
+        initial_ram_state.fill(audio_ram.as_mut(), rng);
+
         Self {
             physical_medium,
-            main_ram: vec![0; MAIN_RAM_LEN].into_boxed_slice().try_into().unwrap(),
-            audio_ram: vec![0; AUDIO_RAM_LEN].into_boxed_slice().try_into().unwrap(),
+            main_ram,
+            audio_ram,
             z80_bank_register: Z80BankRegister::default(),
             signals: Signals::default(),
+            region_spoof: GenesisRegionSpoof::default(),
+            report_tmss: false,
         }
     }
 
+    pub fn set_version_register_config(
+        &mut self,
+        region_spoof: GenesisRegionSpoof,
+        report_tmss: bool,
+    ) {
+        self.region_spoof = region_spoof;
+        self.report_tmss = report_tmss;
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn region_spoof(&self) -> GenesisRegionSpoof {
+        self.region_spoof
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn report_tmss(&self) -> bool {
+        self.report_tmss
+    }
+
+    #[must_use]
+    pub fn version_register(&self, timing_mode: TimingMode) -> u8 {
+        let hardware_region = self.hardware_region();
+        let (region, pal) = self
+            .region_spoof
+            .reported_region(hardware_region)
+            .unwrap_or((hardware_region, timing_mode == TimingMode::Pal));
+
+        0x20 | (u8::from(region.version_bit()) << 7)
+            | (u8::from(pal) << 6)
+            | u8::from(self.report_tmss)
+    }
+
     #[must_use]
     pub(crate) fn read_word_for_dma(&mut self, address: u32) -> u16 {
         match address {
@@ -383,10 +478,32 @@ impl<Medium: PhysicalMedium> Memory<Medium> {
         &mut self.physical_medium
     }
 
+    #[inline]
+    #[must_use]
+    pub fn z80_busreq(&self) -> bool {
+        self.signals.z80_busreq
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn z80_reset(&self) -> bool {
+        self.signals.z80_reset
+    }
+
     #[inline]
     pub fn reset_z80_signals(&mut self) {
         self.signals = Signals::default();
     }
+
+    #[inline]
+    pub(crate) fn main_ram(&self) -> &[u8; MAIN_RAM_LEN] {
+        &self.main_ram
+    }
+
+    #[inline]
+    pub(crate) fn main_ram_mut(&mut self) -> &mut [u8; MAIN_RAM_LEN] {
+        &mut self.main_ram
+    }
 }
 
 impl Memory<Cartridge> {
@@ -421,6 +538,30 @@ impl Memory<Cartridge> {
     pub fn get_and_clear_external_ram_dirty(&mut self) -> bool {
         self.physical_medium.get_and_clear_ram_dirty()
     }
+
+    /// Read a byte from the 68000 address space without side effects, for debuggers. Addresses
+    /// that cannot be read without side effects (e.g. I/O registers) read as 0.
+    #[must_use]
+    pub fn peek_byte(&self, address: u32) -> u8 {
+        let address = address & ADDRESS_MASK;
+        match address {
+            0x000000..=0x3FFFFF => self.physical_medium.peek_byte(address),
+            0xA00000..=0xA0FFFF => self.audio_ram[(address & 0x1FFF) as usize],
+            0xE00000..=0xFFFFFF => self.main_ram[(address & 0xFFFF) as usize],
+            _ => 0,
+        }
+    }
+
+    /// Write a byte to 68000 or Z80 RAM without side effects, for debuggers. Writes to any other
+    /// address are ignored.
+    pub fn poke_byte(&mut self, address: u32, value: u8) {
+        let address = address & ADDRESS_MASK;
+        match address {
+            0xA00000..=0xA0FFFF => self.audio_ram[(address & 0x1FFF) as usize] = value,
+            0xE00000..=0xFFFFFF => self.main_ram[(address & 0xFFFF) as usize] = value,
+            _ => {}
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -454,6 +595,7 @@ pub struct MainBus<'a, Medium> {
     psg: &'a mut Psg,
     ym2612: &'a mut Ym2612,
     input: &'a mut InputState,
+    sound_log: &'a mut SoundLog,
     timing_mode: TimingMode,
     signals: MainBusSignals,
     pending_writes: MainBusWrites,
@@ -469,6 +611,7 @@ impl<'a, Medium: PhysicalMedium> MainBus<'a, Medium> {
         psg: &'a mut Psg,
         ym2612: &'a mut Ym2612,
         input: &'a mut InputState,
+        sound_log: &'a mut SoundLog,
         timing_mode: TimingMode,
         signals: MainBusSignals,
         pending_writes: MainBusWrites,
@@ -479,6 +622,7 @@ impl<'a, Medium: PhysicalMedium> MainBus<'a, Medium> {
             psg,
             ym2612,
             input,
+            sound_log,
             timing_mode,
             signals,
             pending_writes,
@@ -486,19 +630,15 @@ impl<'a, Medium: PhysicalMedium> MainBus<'a, Medium> {
         }
     }
 
-    fn read_io_register(&self, address: u32) -> u8 {
+    fn read_io_register(&mut self, address: u32) -> u8 {
         match address {
             // Version register
-            0xA10000 | 0xA10001 => {
-                0x20 | (u8::from(self.memory.hardware_region().version_bit()) << 7)
-                    | (u8::from(self.timing_mode == TimingMode::Pal) << 6)
-            }
+            0xA10000 | 0xA10001 => self.memory.version_register(self.timing_mode),
             0xA10002 | 0xA10003 => self.input.read_p1_data(),
             0xA10004 | 0xA10005 => self.input.read_p2_data(),
             0xA10008 | 0xA10009 => self.input.read_p1_ctrl(),
             0xA1000A | 0xA1000B => self.input.read_p2_ctrl(),
-            // TxData registers return 0xFF by default
-            0xA1000E | 0xA1000F | 0xA10014 | 0xA10015 | 0xA1001A | 0xA1001B => 0xFF,
+            0xA1000E..=0xA1001F => self.input.serial_mut().read_register(address),
             // Other I/O registers return 0x00 by default
             _ => 0x00,
         }
@@ -518,42 +658,19 @@ impl<'a, Medium: PhysicalMedium> MainBus<'a, Medium> {
             0xA1000A | 0xA1000B => {
                 self.input.write_p2_ctrl(value);
             }
+            0xA1000E..=0xA1001F => {
+                self.input.serial_mut().write_register(address, value);
+            }
             _ => {}
         }
     }
 
     fn read_vdp_byte(&mut self, address: u32) -> u8 {
-        match address & 0x1F {
-            0x00 | 0x02 => self.vdp.read_data().msb(),
-            0x01 | 0x03 => self.vdp.read_data().lsb(),
-            0x04 | 0x06 => self.vdp.read_status().msb(),
-            0x05 | 0x07 => self.vdp.read_status().lsb(),
-            0x08 | 0x0A => self.vdp.hv_counter().msb(),
-            0x09 | 0x0B => self.vdp.hv_counter().lsb(),
-            0x10..=0x1F => {
-                // PSG / unused space; PSG is not readable
-                0xFF
-            }
-            _ => unreachable!("address & 0x1F is always <= 0x1F"),
-        }
+        read_vdp_byte(self.vdp, address)
     }
 
     fn write_vdp_byte(&mut self, address: u32, value: u8) {
-        // Byte-size VDP writes duplicate the byte into a word
-        let vdp_word = u16::from_le_bytes([value, value]);
-        match address & 0x1F {
-            0x00..=0x03 => {
-                self.vdp.write_data(vdp_word);
-            }
-            0x04..=0x07 => {
-                self.vdp.write_control(vdp_word);
-            }
-            0x11 | 0x13 | 0x15 | 0x17 => {
-                self.psg.write(value);
-            }
-            0x10 | 0x12 | 0x14 | 0x16 | 0x18..=0x1F => {}
-            _ => unreachable!("address & 0x1F is always <= 0x1F"),
-        }
+        write_vdp_byte(self.vdp, self.psg, self.sound_log, address, value);
     }
 
     /// Take the pending writes Vecs without applying them
@@ -569,6 +686,8 @@ impl<'a, Medium: PhysicalMedium> MainBus<'a, Medium> {
     pub fn apply_writes(mut self) -> MainBusWrites {
         let mut pending_writes = mem::take(&mut self.pending_writes);
 
+        self.sound_log.set_write_source(SoundWriteSource::M68000);
+
         for &(address, value) in &pending_writes.byte {
             self.apply_byte_write(address, value);
         }
@@ -577,6 +696,8 @@ impl<'a, Medium: PhysicalMedium> MainBus<'a, Medium> {
             self.apply_word_write(address, value);
         }
 
+        self.sound_log.set_write_source(SoundWriteSource::Z80);
+
         pending_writes.clear();
         pending_writes
     }
@@ -585,6 +706,9 @@ impl<'a, Medium: PhysicalMedium> MainBus<'a, Medium> {
         let address = address & ADDRESS_MASK;
         log::trace!("Main bus byte write: address={address:06X}, value={value:02X}");
         match address {
+            0x380000..=0x38FFFF if self.input.multitap() == GenesisMultitap::JCart => {
+                self.input.write_jcart(value);
+            }
             0x000000..=0x7FFFFF | 0xA12000..=0xA1500F => {
                 self.memory.physical_medium.write_byte(address, value);
             }
@@ -622,6 +746,9 @@ impl<'a, Medium: PhysicalMedium> MainBus<'a, Medium> {
         let address = address & ADDRESS_MASK;
         log::trace!("Main bus word write: address={address:06X}, value={value:02X}");
         match address {
+            0x380000..=0x38FFFF if self.input.multitap() == GenesisMultitap::JCart => {
+                self.input.write_jcart(value.lsb());
+            }
             0x000000..=0x7FFFFF | 0xA12000..=0xA1500F => {
                 self.memory.physical_medium.write_word(address, value);
             }
@@ -668,12 +795,58 @@ impl<'a, Medium: PhysicalMedium> MainBus<'a, Medium> {
 // The Genesis has a 24-bit bus, not 32-bit
 const ADDRESS_MASK: u32 = 0xFFFFFF;
 
+// Byte-size access to the VDP ports at $C00000-$C0001F, which also contain the PSG
+pub(crate) fn read_vdp_byte(vdp: &mut Vdp, address: u32) -> u8 {
+    match address & 0x1F {
+        0x00 | 0x02 => vdp.read_data().msb(),
+        0x01 | 0x03 => vdp.read_data().lsb(),
+        0x04 | 0x06 => vdp.read_status().msb(),
+        0x05 | 0x07 => vdp.read_status().lsb(),
+        0x08 | 0x0A => vdp.hv_counter().msb(),
+        0x09 | 0x0B => vdp.hv_counter().lsb(),
+        0x10..=0x1F => {
+            // PSG / unused space; PSG is not readable
+            0xFF
+        }
+        _ => unreachable!("address & 0x1F is always <= 0x1F"),
+    }
+}
+
+pub(crate) fn write_vdp_byte(
+    vdp: &mut Vdp,
+    psg: &mut Psg,
+    sound_log: &mut SoundLog,
+    address: u32,
+    value: u8,
+) {
+    // Byte-size VDP writes duplicate the byte into a word
+    let vdp_word = u16::from_le_bytes([value, value]);
+    match address & 0x1F {
+        0x00..=0x03 => {
+            vdp.write_data(vdp_word);
+        }
+        0x04..=0x07 => {
+            vdp.write_control(vdp_word);
+        }
+        0x11 | 0x13 | 0x15 | 0x17 => {
+            psg.write(value);
+            sound_log.log_psg_write(value);
+        }
+        0x10 | 0x12 | 0x14 | 0x16 | 0x18..=0x1F => {}
+        _ => unreachable!("address & 0x1F is always <= 0x1F"),
+    }
+}
+
 impl<'a, Medium: PhysicalMedium> m68000_emu::BusInterface for MainBus<'a, Medium> {
     #[inline]
     fn read_byte(&mut self, address: u32) -> u8 {
         let address = address & ADDRESS_MASK;
         log::trace!("Main bus byte read, address={address:06X}");
         match address {
+            0x380000..=0x38FFFF if self.input.multitap() == GenesisMultitap::JCart => {
+                let value = self.input.read_jcart();
+                if address.bit(0) { value.lsb() } else { value.msb() }
+            }
             0x000000..=0x7FFFFF | 0xA12000..=0xA1500F => {
                 self.memory.physical_medium.read_byte(address)
             }
@@ -695,6 +868,9 @@ impl<'a, Medium: PhysicalMedium> m68000_emu::BusInterface for MainBus<'a, Medium
         let address = address & ADDRESS_MASK;
         log::trace!("Main bus word read, address={address:06X}");
         match address {
+            0x380000..=0x38FFFF if self.input.multitap() == GenesisMultitap::JCart => {
+                self.input.read_jcart()
+            }
             0x000000..=0x7FFFFF | 0xA12000..=0xA1500F => {
                 self.memory.physical_medium.read_word(address)
             }
@@ -735,12 +911,22 @@ impl<'a, Medium: PhysicalMedium> m68000_emu::BusInterface for MainBus<'a, Medium
 
     #[inline]
     fn interrupt_level(&self) -> u8 {
-        self.vdp.m68k_interrupt_level()
+        let vdp_interrupt_level = self.vdp.m68k_interrupt_level();
+        if vdp_interrupt_level == 0
+            && self.vdp.external_interrupt_enabled()
+            && self.input.serial().interrupt_pending()
+        {
+            // External interrupt from a serial port
+            2
+        } else {
+            vdp_interrupt_level
+        }
     }
 
     #[inline]
-    fn acknowledge_interrupt(&mut self) {
+    fn acknowledge_interrupt(&mut self, interrupt_level: u8) -> u8 {
         self.vdp.acknowledge_m68k_interrupt();
+        m68000_emu::traits::autovector(interrupt_level)
     }
 
     #[inline]
@@ -818,9 +1004,18 @@ impl<'a, Medium: PhysicalMedium> z80_emu::BusInterface for MainBus<'a, Medium> {
             0x4000..=0x5FFF => {
                 // YM2612 registers/ports (mirrored every 4 addresses)
                 match address & 0x03 {
-                    0x00 => self.ym2612.write_address_1(value),
-                    0x02 => self.ym2612.write_address_2(value),
-                    0x01 | 0x03 => self.ym2612.write_data(value),
+                    0x00 => {
+                        self.ym2612.write_address_1(value);
+                        self.sound_log.log_ym2612_address(0, value);
+                    }
+                    0x02 => {
+                        self.ym2612.write_address_2(value);
+                        self.sound_log.log_ym2612_address(1, value);
+                    }
+                    0x01 | 0x03 => {
+                        self.ym2612.write_data(value);
+                        self.sound_log.log_ym2612_data(value);
+                    }
                     _ => unreachable!("value & 0x03 is always <= 0x03"),
                 }
             }
@@ -1,6 +1,8 @@
 //! Implementation for external memory on the cartridge, which can be SRAM or EEPROM
 
 mod metadata;
+#[cfg(test)]
+mod tests;
 
 use crate::memory::eeprom::{X24C01Chip, X24C02Chip, X24C08Chip, X24C16Chip};
 use crate::memory::external::metadata::{EepromMetadata, EepromType};
@@ -14,10 +16,19 @@ pub(crate) enum RamType {
     EightBitEvenAddress,
 }
 
+// Cartridge address space ends at $3FFFFF
+const CARTRIDGE_END_ADDRESS: u32 = 0x3FFFFF;
+
+// No cartridge has more than 64KB of RAM; larger ranges in the header are assumed to be garbage
+const MAX_RAM_RANGE_LEN: u32 = 0x10000;
+
+// Used if the ROM header declares RAM but the address range is invalid
+const DEFAULT_RAM_START_ADDRESS: u32 = 0x200001;
+const DEFAULT_RAM_END_ADDRESS: u32 = 0x20FFFF;
+
 #[derive(Debug, Clone, Encode, Decode)]
 pub(crate) struct Ram {
     ram: Vec<u8>,
-    address_mask: u32,
     ram_type: RamType,
     persistent: bool,
     dirty: bool,
@@ -27,65 +38,92 @@ pub(crate) struct Ram {
 
 impl Ram {
     pub(crate) fn from_rom_header(rom: &[u8], initial_ram: &mut Option<Vec<u8>>) -> Option<Self> {
-        let ram_header_bytes = &rom[0x1B0..0x1BC];
+        let ram_header_bytes = rom.get(0x1B0..0x1BC)?;
 
-        // RAM header should always start with ASCII "RA" followed by $F8 and $20
-        if ram_header_bytes[..4] != [b'R', b'A', 0xF8, 0x20] {
+        // RAM header should always start with ASCII "RA". The next two bytes are usually $F8 and
+        // $20, but the third byte varies by RAM type and the fourth byte is not always $20
+        if ram_header_bytes[..2] != *b"RA" {
             return None;
         }
 
-        // Third byte indicates RAM type and whether or not it is persistent memory
-        let (ram_type, persistent) = match ram_header_bytes[2] {
-            0xA0 => (RamType::SixteenBit, false),
-            0xB0 => (RamType::EightBitEvenAddress, false),
-            0xB8 => (RamType::EightBitOddAddress, false),
-            0xE0 => (RamType::SixteenBit, true),
-            0xF0 => (RamType::EightBitEvenAddress, true),
-            0xF8 => (RamType::EightBitOddAddress, true),
-            _ => {
-                return None;
-            }
-        };
-
         // Next 8 bytes indicate start and end addresses
-        let start_address = u32::from_be_bytes([
+        let mut start_address = u32::from_be_bytes([
             ram_header_bytes[4],
             ram_header_bytes[5],
             ram_header_bytes[6],
             ram_header_bytes[7],
         ]);
-        let end_address = u32::from_be_bytes([
+        let mut end_address = u32::from_be_bytes([
             ram_header_bytes[8],
             ram_header_bytes[9],
             ram_header_bytes[10],
             ram_header_bytes[11],
         ]);
 
+        if start_address > end_address
+            || end_address > CARTRIDGE_END_ADDRESS
+            || end_address - start_address >= MAX_RAM_RANGE_LEN
+        {
+            log::warn!(
+                "Invalid RAM address range in ROM header: {start_address:06X}-{end_address:06X}; using {DEFAULT_RAM_START_ADDRESS:06X}-{DEFAULT_RAM_END_ADDRESS:06X}"
+            );
+            start_address = DEFAULT_RAM_START_ADDRESS;
+            end_address = DEFAULT_RAM_END_ADDRESS;
+        }
+
+        // Third byte indicates RAM type and whether or not it is persistent memory
+        let (ram_type, persistent) = match ram_header_bytes[2] {
+            0xA0 => (RamType::SixteenBit, false),
+            0xB0 => (RamType::EightBitEvenAddress, false),
+            0xB8 => (RamType::EightBitOddAddress, false),
+            0xE0 => (RamType::SixteenBit, true),
+            0xF0 => (RamType::EightBitEvenAddress, true),
+            0xF8 => (RamType::EightBitOddAddress, true),
+            type_byte => {
+                // Assume that RAM starting at an odd address is only connected to the low byte
+                let ram_type = if start_address.bit(0) {
+                    RamType::EightBitOddAddress
+                } else {
+                    RamType::SixteenBit
+                };
+                log::warn!(
+                    "Unrecognized RAM type byte in ROM header: {type_byte:02X}; assuming {ram_type:?}"
+                );
+                (ram_type, true)
+            }
+        };
+
+        if ram_type == RamType::SixteenBit {
+            start_address &= !1;
+            end_address |= 1;
+        }
+
         log::info!(
             "RAM header information: type={ram_type:?}, persistent={persistent}, start_address={start_address:06X}, end_address={end_address:06X}"
         );
 
-        let ram_len = if ram_type == RamType::SixteenBit {
-            end_address - start_address + 1
-        } else {
-            (end_address - start_address) / 2 + 1
-        };
+        let ram_len = match ram_type {
+            RamType::SixteenBit => end_address - start_address + 1,
+            RamType::EightBitOddAddress | RamType::EightBitEvenAddress => {
+                (end_address >> 1) - (start_address >> 1) + 1
+            }
+        } as usize;
 
         let ram = match initial_ram.take() {
-            Some(ram) if ram.len() as u32 == ram_len => ram,
-            _ => vec![0; ram_len as usize],
+            Some(mut ram) => {
+                if ram.len() != ram_len {
+                    log::warn!(
+                        "Save file is {} bytes but cartridge RAM is {ram_len} bytes; resizing",
+                        ram.len()
+                    );
+                    ram.resize(ram_len, 0);
+                }
+                ram
+            }
+            None => vec![0; ram_len],
         };
 
-        // TODO support RAM persistence
-        Some(Self {
-            ram,
-            address_mask: ram_len - 1,
-            ram_type,
-            persistent,
-            dirty: false,
-            start_address,
-            end_address,
-        })
+        Some(Self { ram, ram_type, persistent, dirty: false, start_address, end_address })
     }
 
     fn map_address(&self, address: u32) -> Option<u32> {
@@ -94,10 +132,10 @@ impl Ram {
         }
 
         match (self.ram_type, address.bit(0)) {
-            (RamType::SixteenBit, _) => Some(address & self.address_mask),
+            (RamType::SixteenBit, _) => Some(address - self.start_address),
             (RamType::EightBitOddAddress, false) | (RamType::EightBitEvenAddress, true) => None,
             (RamType::EightBitEvenAddress, false) | (RamType::EightBitOddAddress, true) => {
-                Some((address >> 1) & self.address_mask)
+                Some((address >> 1) - (self.start_address >> 1))
             }
         }
     }
@@ -214,6 +252,14 @@ impl ExternalMemory {
         Self::None
     }
 
+    /// Start address of cartridge RAM, if this cartridge has RAM (as opposed to EEPROM).
+    pub(crate) fn ram_start_address(&self) -> Option<u32> {
+        match self {
+            Self::Ram(ram) => Some(ram.start_address),
+            Self::None | Self::Eeprom { .. } => None,
+        }
+    }
+
     pub(crate) fn read_byte(&self, address: u32) -> Option<u8> {
         match self {
             Self::None => None,
@@ -100,7 +100,8 @@ const CODEMASTERS_24C16_METADATA: EepromMetadata = EepromMetadata {
 const CRC: Crc<u32> = Crc::<u32>::new(&crc::CRC_32_ISO_HDLC);
 
 pub fn eeprom(rom: &[u8]) -> Option<EepromMetadata> {
-    let serial_number: String = rom[0x183..0x18B].iter().map(|&b| b as char).collect();
+    let serial_number: String =
+        rom.get(0x183..0x18B).unwrap_or_default().iter().map(|&b| b as char).collect();
     match serial_number.as_str() {
         // NBA Jam (UE)
         // NBA Jam (J)
@@ -144,6 +145,5 @@ pub fn eeprom(rom: &[u8]) -> Option<EepromMetadata> {
 }
 
 pub fn is_micro_machines_2(rom: &[u8]) -> bool {
-    let extended_serial: String = rom[0x183..0x18E].iter().map(|&b| b as char).collect();
-    extended_serial.as_str() == "T-120096-50"
+    rom.get(0x183..0x18E) == Some(b"T-120096-50")
 }
@@ -0,0 +1,77 @@
+use super::*;
+use test_log::test;
+
+fn rom_with_ram_header(type_byte: u8, start_address: u32, end_address: u32) -> Vec<u8> {
+    let mut rom = vec![0; 0x200];
+    rom[0x1B0..0x1B4].copy_from_slice(&[b'R', b'A', type_byte, 0x20]);
+    rom[0x1B4..0x1B8].copy_from_slice(&start_address.to_be_bytes());
+    rom[0x1B8..0x1BC].copy_from_slice(&end_address.to_be_bytes());
+    rom
+}
+
+// Same header as Sonic the Hedgehog 3
+#[test]
+fn odd_address_ram() {
+    let rom = rom_with_ram_header(0xF8, 0x200001, 0x203FFF);
+    let mut ram = Ram::from_rom_header(&rom, &mut None).unwrap();
+    assert_eq!(ram.ram.len(), 0x2000);
+
+    ram.write_byte(0x200001, 0x12);
+    ram.write_byte(0x200003, 0x34);
+    ram.write_byte(0x203FFF, 0x56);
+    assert_eq!(&ram.ram[..2], &[0x12, 0x34]);
+    assert_eq!(ram.ram[0x1FFF], 0x56);
+
+    assert_eq!(ram.read_byte(0x200000), None);
+    assert_eq!(ram.read_byte(0x204001), None);
+    assert_eq!(ram.read_word(0x200002), Some(0x0034));
+}
+
+#[test]
+fn even_address_ram() {
+    let rom = rom_with_ram_header(0xF0, 0x200000, 0x203FFE);
+    let mut ram = Ram::from_rom_header(&rom, &mut None).unwrap();
+    assert_eq!(ram.ram.len(), 0x2000);
+
+    ram.write_word(0x200002, 0xABCD);
+    assert_eq!(ram.ram[1], 0xAB);
+    assert_eq!(ram.read_byte(0x200003), None);
+}
+
+#[test]
+fn sixteen_bit_ram_outside_default_range() {
+    let rom = rom_with_ram_header(0xE0, 0x300000, 0x3007FF);
+    let mut ram = Ram::from_rom_header(&rom, &mut None).unwrap();
+    assert_eq!(ram.ram.len(), 0x800);
+
+    ram.write_word(0x300010, 0x1234);
+    assert_eq!(&ram.ram[0x10..0x12], &[0x12, 0x34]);
+    assert_eq!(ram.read_word(0x300010), Some(0x1234));
+    assert_eq!(ram.read_byte(0x200010), None);
+}
+
+#[test]
+fn invalid_range_uses_default() {
+    let rom = rom_with_ram_header(0xF8, 0x20FFFF, 0x200001);
+    let ram = Ram::from_rom_header(&rom, &mut None).unwrap();
+    assert_eq!((ram.start_address, ram.end_address), (0x200001, 0x20FFFF));
+    assert_eq!(ram.ram.len(), 0x8000);
+}
+
+#[test]
+fn unknown_type_byte() {
+    let rom = rom_with_ram_header(0x00, 0x200001, 0x200FFF);
+    let ram = Ram::from_rom_header(&rom, &mut None).unwrap();
+    assert_eq!(ram.ram_type, RamType::EightBitOddAddress);
+    assert!(ram.persistent);
+}
+
+#[test]
+fn mismatched_save_is_resized() {
+    let rom = rom_with_ram_header(0xF8, 0x200001, 0x203FFF);
+    let mut initial_ram = Some(vec![0x77; 0x1000]);
+    let ram = Ram::from_rom_header(&rom, &mut initial_ram).unwrap();
+    assert_eq!(ram.ram.len(), 0x2000);
+    assert_eq!(ram.ram[0xFFF], 0x77);
+    assert_eq!(ram.ram[0x1000], 0x00);
+}
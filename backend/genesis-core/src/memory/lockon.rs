@@ -0,0 +1,147 @@
+//! Sonic & Knuckles lock-on cartridge support
+//!
+//! Sonic & Knuckles has a pass-through slot on top of the cartridge, and the cartridge in that slot
+//! appears at $200000-$3FFFFF. Sonic & Knuckles checks the locked-on cartridge's header at boot:
+//! Sonic 3 combines the two games, Sonic 2 runs with Knuckles playable using an extra 256KB patch
+//! ROM that is mapped at $300000, and most other games start Blue Sphere.
+//!
+//! Lock-on is emulated by combining the ROM images into a single ROM with the locked-on cartridge at
+//! $200000, which [`Cartridge`](super::Cartridge) recognizes so that the locked-on cartridge's RAM
+//! (e.g. Sonic 3's save RAM) works.
+
+use thiserror::Error;
+
+pub(crate) const LOCK_ON_ADDRESS: usize = 0x200000;
+const LOCK_ON_MAX_LEN: usize = 0x200000;
+
+const PATCH_ROM_ADDRESS: usize = 0x300000;
+const PATCH_ROM_LEN: usize = 0x40000;
+
+const SONIC_AND_KNUCKLES_SERIAL: &[u8] = b"MK-1563 ";
+
+#[derive(Debug, Error)]
+pub enum LockOnError {
+    #[error("Base cartridge is not Sonic & Knuckles; only Sonic & Knuckles supports lock-on")]
+    NotSonicAndKnuckles,
+    #[error("Lock-on patch ROM must be {PATCH_ROM_LEN} bytes, was {len} bytes")]
+    InvalidPatchRom { len: usize },
+}
+
+#[must_use]
+pub fn is_sonic_and_knuckles(rom: &[u8]) -> bool {
+    rom.get(0x183..0x18B) == Some(SONIC_AND_KNUCKLES_SERIAL)
+}
+
+/// Whether the ROM is Sonic & Knuckles combined with a locked-on cartridge by
+/// [`attach_lock_on_cartridge`]. Sonic & Knuckles dumps that include only the patch ROM do not
+/// count.
+pub(crate) fn has_lock_on_cartridge(rom: &[u8]) -> bool {
+    is_sonic_and_knuckles(rom) && rom.len() > LOCK_ON_ADDRESS + PATCH_ROM_LEN
+}
+
+/// Combine a Sonic & Knuckles ROM with the ROM of the cartridge locked on top of it.
+///
+/// The patch ROM is only needed for Sonic 2 and is mapped only if the locked-on ROM is 1MB or
+/// smaller. If it is not provided, it is taken from the end of the Sonic & Knuckles ROM if the
+/// dump includes it.
+///
+/// # Errors
+///
+/// Returns an error if the base ROM is not Sonic & Knuckles or the patch ROM is the wrong size.
+pub fn attach_lock_on_cartridge(
+    mut base_rom: Vec<u8>,
+    lock_on_rom: &[u8],
+    patch_rom: Option<&[u8]>,
+) -> Result<Vec<u8>, LockOnError> {
+    if !is_sonic_and_knuckles(&base_rom) {
+        return Err(LockOnError::NotSonicAndKnuckles);
+    }
+
+    let embedded_patch_rom = (base_rom.len() == LOCK_ON_ADDRESS + PATCH_ROM_LEN)
+        .then(|| base_rom.split_off(LOCK_ON_ADDRESS));
+    let patch_rom = patch_rom.or(embedded_patch_rom.as_deref());
+    if let Some(patch_rom) = patch_rom {
+        if patch_rom.len() != PATCH_ROM_LEN {
+            return Err(LockOnError::InvalidPatchRom { len: patch_rom.len() });
+        }
+    }
+
+    let lock_on_len = lock_on_rom.len().min(LOCK_ON_MAX_LEN);
+    if lock_on_rom.len() > LOCK_ON_MAX_LEN {
+        log::warn!("Lock-on ROM is larger than 2MB; only the first 2MB will be mapped");
+    }
+
+    let mut rom = base_rom;
+    rom.resize(LOCK_ON_ADDRESS, 0xFF);
+    rom.extend_from_slice(&lock_on_rom[..lock_on_len]);
+
+    match patch_rom {
+        Some(patch_rom) if lock_on_len <= PATCH_ROM_ADDRESS - LOCK_ON_ADDRESS => {
+            rom.resize(PATCH_ROM_ADDRESS, 0xFF);
+            rom.extend_from_slice(patch_rom);
+            log::info!("Mapped lock-on patch ROM at ${PATCH_ROM_ADDRESS:06X}");
+        }
+        Some(_) => {}
+        None => log::info!("No lock-on patch ROM available; Knuckles in Sonic 2 will not work"),
+    }
+
+    Ok(rom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sonic_and_knuckles_rom(len: usize) -> Vec<u8> {
+        let mut rom = vec![0; len];
+        rom[0x183..0x18B].copy_from_slice(SONIC_AND_KNUCKLES_SERIAL);
+        rom
+    }
+
+    #[test]
+    fn rejects_other_base_roms() {
+        let result = attach_lock_on_cartridge(vec![0; LOCK_ON_ADDRESS], &[1; 0x1000], None);
+        assert!(matches!(result, Err(LockOnError::NotSonicAndKnuckles)));
+    }
+
+    #[test]
+    fn maps_lock_on_rom_at_2mb() {
+        let rom = attach_lock_on_cartridge(
+            sonic_and_knuckles_rom(LOCK_ON_ADDRESS),
+            &vec![1; 0x80000],
+            None,
+        )
+        .unwrap();
+        assert_eq!(rom.len(), LOCK_ON_ADDRESS + 0x80000);
+        assert_eq!(rom[LOCK_ON_ADDRESS - 1], 0);
+        assert_eq!(rom[LOCK_ON_ADDRESS], 1);
+        assert!(has_lock_on_cartridge(&rom));
+    }
+
+    #[test]
+    fn maps_embedded_patch_rom_for_small_lock_on_roms() {
+        let mut base_rom = sonic_and_knuckles_rom(LOCK_ON_ADDRESS);
+        base_rom.resize(LOCK_ON_ADDRESS + PATCH_ROM_LEN, 2);
+        assert!(!has_lock_on_cartridge(&base_rom));
+
+        let rom = attach_lock_on_cartridge(base_rom.clone(), &vec![1; 0x100000], None).unwrap();
+        assert_eq!(rom.len(), PATCH_ROM_ADDRESS + PATCH_ROM_LEN);
+        assert_eq!(rom[PATCH_ROM_ADDRESS - 1], 1);
+        assert_eq!(rom[PATCH_ROM_ADDRESS], 2);
+
+        // Patch ROM should not be mapped over a larger locked-on ROM
+        let rom = attach_lock_on_cartridge(base_rom, &vec![1; 0x200000], None).unwrap();
+        assert_eq!(rom.len(), LOCK_ON_ADDRESS + 0x200000);
+        assert_eq!(rom[PATCH_ROM_ADDRESS], 1);
+    }
+
+    #[test]
+    fn rejects_invalid_patch_rom() {
+        let result = attach_lock_on_cartridge(
+            sonic_and_knuckles_rom(LOCK_ON_ADDRESS),
+            &vec![1; 0x100000],
+            Some(&[2; 0x1000]),
+        );
+        assert!(matches!(result, Err(LockOnError::InvalidPatchRom { len: 0x1000 })));
+    }
+}
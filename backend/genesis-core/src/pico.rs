@@ -0,0 +1,342 @@
+//! Sega Pico emulation
+//!
+//! The Pico is a Genesis-derived educational console: a 68000, the Genesis VDP, and an SN76489
+//! PSG, but no Z80 or YM2612. Input comes from a pen that can touch either the drawing pad or the
+//! storyware book, which has sensors for detecting which page is open. Voice playback uses a
+//! uPD7759 ADPCM chip fed by the 68000.
+
+mod adpcm;
+mod bus;
+mod io;
+
+use crate::api::{M68K_MCLK_DIVIDER, PSG_MCLK_DIVIDER};
+use crate::audio::GenesisAudioResampler;
+use crate::memory::{Cartridge, Memory};
+use crate::pico::bus::PicoBus;
+use crate::pico::io::PicoIo;
+use crate::soundlog::SoundLog;
+use crate::vdp::{Vdp, VdpEventLog, VdpTickEffect};
+use crate::{
+    GenesisAspectRatio, GenesisControllerType, GenesisEmulatorConfig, GenesisError,
+    GenesisMultitap, GenesisRegion, GenesisResult, GenesisSerialPort,
+};
+use bincode::{Decode, Encode};
+use jgenesis_common::frontend::{
+    AudioOutput, Color, EmulatorTrait, PartialClone, Renderer, SaveWriter, TickEffect, TimingMode,
+};
+use jgenesis_common::rng::{InitialRamState, Rng};
+use jgenesis_common::state::StateFormat;
+use jgenesis_scheduler::ClockRatio;
+use m68000_emu::M68000;
+use smsgg_core::psg::{Psg, PsgTickEffect, PsgVersion};
+use std::fmt::{Debug, Display};
+
+/// Highest storyware page number; page 0 is the closed book / cover
+pub const MAX_PAGE: u8 = 6;
+
+// The ADPCM chip's sample rate is not emulated; always run at roughly 16kHz
+const ADPCM_MCLK_DIVIDER: u64 = 3356;
+
+// The audio resampler expects samples at the YM2612 output rate
+const ADPCM_OUTPUT_MCLK_DIVIDER: u64 = 1008;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode)]
+pub enum PicoPenTarget {
+    #[default]
+    DrawingPad,
+    Storyware,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode)]
+pub struct PicoPenState {
+    // X/Y position in frame buffer pixels starting from the top-left corner, or None if the pen
+    // is not over the emulator window
+    pub position: Option<(u16, u16)>,
+    pub pressed: bool,
+    pub target: PicoPenTarget,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode)]
+pub struct PicoInputs {
+    pub up: bool,
+    pub down: bool,
+    pub left: bool,
+    pub right: bool,
+    pub red: bool,
+    pub pen: PicoPenState,
+    /// Currently open storyware page, from 0 to [`MAX_PAGE`]
+    pub page: u8,
+}
+
+/// Returns whether the given ROM appears to be a Pico ROM, based on the console name in the header.
+#[must_use]
+pub fn is_pico_rom(rom: &[u8]) -> bool {
+    rom.get(0x100..0x109) == Some(b"SEGA PICO")
+}
+
+#[derive(Debug, Encode, Decode, PartialClone)]
+pub struct PicoEmulator {
+    #[partial_clone(partial)]
+    memory: Memory<Cartridge>,
+    m68k: M68000,
+    vdp: Vdp,
+    psg: Psg,
+    io: PicoIo,
+    timing_mode: TimingMode,
+    aspect_ratio: GenesisAspectRatio,
+    adjust_aspect_ratio_in_2x_resolution: bool,
+    audio_resampler: GenesisAudioResampler,
+    psg_clock: ClockRatio,
+    adpcm_clock: ClockRatio,
+    adpcm_output_clock: ClockRatio,
+    initial_ram_state: Option<InitialRamState>,
+    rng_seed: Option<u64>,
+    sound_log: SoundLog,
+}
+
+// This is a macro instead of a function so that it only mutably borrows the needed fields
+macro_rules! new_pico_bus {
+    ($self:expr, m68k_reset: $m68k_reset:expr) => {
+        PicoBus {
+            memory: &mut $self.memory,
+            vdp: &mut $self.vdp,
+            psg: &mut $self.psg,
+            io: &mut $self.io,
+            sound_log: &mut $self.sound_log,
+            m68k_reset: $m68k_reset,
+        }
+    };
+}
+
+impl PicoEmulator {
+    /// Initialize the emulator from the given ROM. Controller and YM2612 settings in the config
+    /// are ignored.
+    #[must_use]
+    pub fn create(rom: Vec<u8>, config: GenesisEmulatorConfig) -> Self {
+        let cartridge =
+            Cartridge::from_rom(rom, None, config.forced_region, config.forced_timing_mode);
+        let mut rng = Rng::from_optional_seed(config.rng_seed);
+        let memory = Memory::new(
+            cartridge,
+            config.initial_ram_state.unwrap_or(InitialRamState::AllZeroes),
+            &mut rng,
+        );
+
+        let timing_mode =
+            config.forced_timing_mode.unwrap_or_else(|| match memory.hardware_region() {
+                GenesisRegion::Europe => TimingMode::Pal,
+                GenesisRegion::Americas | GenesisRegion::Japan => TimingMode::Ntsc,
+            });
+
+        log::info!("Using timing / display mode {timing_mode}");
+
+        let vdp = Vdp::new(timing_mode, config.to_vdp_config());
+        let psg = Psg::new(PsgVersion::Standard);
+        let io = PicoIo::new(memory.hardware_region(), timing_mode);
+
+        // Like the Genesis, the Pico does not allow TAS to lock the bus
+        let m68k = M68000::builder().allow_tas_writes(false).build();
+
+        let mut emulator = Self {
+            memory,
+            m68k,
+            vdp,
+            psg,
+            io,
+            timing_mode,
+            aspect_ratio: config.aspect_ratio,
+            adjust_aspect_ratio_in_2x_resolution: config.adjust_aspect_ratio_in_2x_resolution,
+            audio_resampler: GenesisAudioResampler::new(timing_mode, config.audio_resampler_quality),
+            psg_clock: ClockRatio::divider(PSG_MCLK_DIVIDER),
+            adpcm_clock: ClockRatio::divider(ADPCM_MCLK_DIVIDER),
+            adpcm_output_clock: ClockRatio::divider(ADPCM_OUTPUT_MCLK_DIVIDER),
+            initial_ram_state: config.initial_ram_state,
+            rng_seed: config.rng_seed,
+            sound_log: SoundLog::new(),
+        };
+
+        // Reset CPU so that execution will start from the right place
+        emulator.m68k.execute_instruction(&mut new_pico_bus!(emulator, m68k_reset: true));
+
+        emulator
+    }
+
+    #[must_use]
+    pub fn cartridge_title(&self) -> String {
+        self.memory.game_title()
+    }
+
+    fn render_frame<R: Renderer>(&mut self, renderer: &mut R) -> Result<(), R::Err> {
+        crate::render_frame(
+            &self.vdp,
+            self.aspect_ratio,
+            self.adjust_aspect_ratio_in_2x_resolution,
+            renderer,
+        )
+    }
+
+    pub fn copy_cram(&self, out: &mut [Color]) {
+        self.vdp.copy_cram(out);
+    }
+
+    pub fn copy_vram(&self, out: &mut [Color], palette: u8, row_len: usize) {
+        self.vdp.copy_vram(out, palette, row_len);
+    }
+
+    pub fn import_vram(&mut self, pixels: &[Color], palette: u8, row_len: usize) {
+        self.vdp.import_vram(pixels, palette, row_len);
+    }
+
+    pub fn set_vdp_event_logging(&mut self, enabled: bool) {
+        self.vdp.set_event_logging(enabled);
+    }
+
+    #[must_use]
+    pub fn vdp_event_log(&self) -> &VdpEventLog {
+        self.vdp.event_log()
+    }
+
+    #[must_use]
+    pub fn sound_log_mut(&mut self) -> &mut SoundLog {
+        &mut self.sound_log
+    }
+}
+
+impl EmulatorTrait for PicoEmulator {
+    type Inputs = PicoInputs;
+    type Config = GenesisEmulatorConfig;
+    const STATE_FORMAT: StateFormat = StateFormat { core: "pico", version: 1 };
+
+    type Err<
+        RErr: Debug + Display + Send + Sync + 'static,
+        AErr: Debug + Display + Send + Sync + 'static,
+    > = GenesisError<RErr, AErr>;
+
+    /// Execute one 68000 CPU instruction and run the rest of the components for the appropriate
+    /// number of cycles.
+    ///
+    /// # Errors
+    ///
+    /// This method will propagate any errors encountered while rendering frames or pushing audio
+    /// samples.
+    #[inline]
+    fn tick<R, A>(
+        &mut self,
+        renderer: &mut R,
+        audio_output: &mut A,
+        inputs: &Self::Inputs,
+    ) -> GenesisResult<R::Err, A::Err>
+    where
+        R: Renderer,
+        R::Err: Debug + Display + Send + Sync + 'static,
+        A: AudioOutput,
+        A::Err: Debug + Display + Send + Sync + 'static,
+    {
+        let m68k_cycles =
+            self.m68k.execute_instruction(&mut new_pico_bus!(self, m68k_reset: false));
+        let elapsed_mclk_cycles = u64::from(m68k_cycles) * M68K_MCLK_DIVIDER;
+
+        self.sound_log.tick(elapsed_mclk_cycles);
+
+        for _ in 0..self.psg_clock.tick(elapsed_mclk_cycles) {
+            if self.psg.tick() == PsgTickEffect::Clocked {
+                let (psg_sample_l, psg_sample_r) = self.psg.sample();
+                self.audio_resampler.collect_psg_sample(psg_sample_l, psg_sample_r);
+            }
+        }
+
+        for _ in 0..self.adpcm_clock.tick(elapsed_mclk_cycles) {
+            self.io.adpcm_mut().clock();
+        }
+
+        for _ in 0..self.adpcm_output_clock.tick(elapsed_mclk_cycles) {
+            let adpcm_sample = self.io.adpcm_mut().sample();
+            self.audio_resampler.collect_ym2612_sample(adpcm_sample, adpcm_sample);
+        }
+
+        if self.vdp.tick(elapsed_mclk_cycles, &mut self.memory) == VdpTickEffect::FrameComplete {
+            self.render_frame(renderer).map_err(GenesisError::Render)?;
+
+            self.audio_resampler.output_samples(audio_output).map_err(GenesisError::Audio)?;
+
+            self.io.set_inputs(*inputs, self.vdp.screen_width(), self.vdp.screen_height());
+
+            return Ok(TickEffect::FrameRendered);
+        }
+
+        Ok(TickEffect::None)
+    }
+
+    fn save_dirty(&self) -> bool {
+        // Pico cartridges do not have battery-backed save memory
+        false
+    }
+
+    fn persist_save<S: SaveWriter>(&mut self, _save_writer: &mut S) -> Result<(), S::Err> {
+        Ok(())
+    }
+
+    fn force_render<R>(&mut self, renderer: &mut R) -> Result<(), R::Err>
+    where
+        R: Renderer,
+    {
+        self.render_frame(renderer)
+    }
+
+    fn reload_config(&mut self, config: &Self::Config) {
+        self.aspect_ratio = config.aspect_ratio;
+        self.adjust_aspect_ratio_in_2x_resolution = config.adjust_aspect_ratio_in_2x_resolution;
+        self.vdp.reload_config(config.to_vdp_config());
+        self.audio_resampler.set_quality(config.audio_resampler_quality);
+        self.initial_ram_state = config.initial_ram_state;
+        self.rng_seed = config.rng_seed;
+    }
+
+    fn take_rom_from(&mut self, other: &mut Self) {
+        self.memory.take_rom_from(&mut other.memory);
+    }
+
+    fn soft_reset(&mut self) {
+        log::info!("Soft resetting console");
+
+        self.m68k.execute_instruction(&mut new_pico_bus!(self, m68k_reset: true));
+        self.io.adpcm_mut().write_control(0x8000);
+    }
+
+    fn hard_reset<S: SaveWriter>(&mut self, _save_writer: &mut S) {
+        log::info!("Hard resetting console");
+
+        let rom = self.memory.take_rom();
+        let vdp_config = self.vdp.config();
+
+        let config = GenesisEmulatorConfig {
+            forced_timing_mode: Some(self.timing_mode),
+            forced_region: Some(self.memory.hardware_region()),
+            region_spoof: self.memory.region_spoof(),
+            report_tmss: self.memory.report_tmss(),
+            aspect_ratio: self.aspect_ratio,
+            adjust_aspect_ratio_in_2x_resolution: self.adjust_aspect_ratio_in_2x_resolution,
+            remove_sprite_limits: !vdp_config.enforce_sprite_limits,
+            emulate_non_linear_vdp_dac: vdp_config.emulate_non_linear_dac,
+            render_vertical_border: vdp_config.render_vertical_border,
+            render_horizontal_border: vdp_config.render_horizontal_border,
+            widescreen: vdp_config.widescreen,
+            hi_res_output: vdp_config.hi_res_output,
+            expanded_vram: vdp_config.expanded_vram,
+            quantize_ym2612_output: false,
+            audio_resampler_quality: self.audio_resampler.quality(),
+            p1_controller_type: GenesisControllerType::default(),
+            p2_controller_type: GenesisControllerType::default(),
+            multitap: GenesisMultitap::default(),
+            serial_port: GenesisSerialPort::default(),
+            initial_ram_state: self.initial_ram_state,
+            rng_seed: self.rng_seed,
+        };
+
+        *self = PicoEmulator::create(rom, config);
+    }
+
+    fn timing_mode(&self) -> TimingMode {
+        self.timing_mode
+    }
+}
@@ -0,0 +1,159 @@
+//! Pico ADPCM voice playback, using a NEC uPD7759 in slave mode
+//!
+//! The 68000 streams ADPCM data into a small FIFO and the uPD7759 decodes it one nibble at a time,
+//! high nibble first. Sample rate selection and the FIFO-low interrupt are not emulated; playback
+//! always runs at roughly 16kHz.
+
+use bincode::{Decode, Encode};
+use jgenesis_common::num::GetBit;
+use std::collections::VecDeque;
+
+const FIFO_LEN: usize = 64;
+
+// Step deltas indexed by [step state][nibble]
+const STEP_TABLE: [[i16; 16]; 16] = [
+    [0, 0, 1, 2, 3, 5, 7, 10, 0, 0, -1, -2, -3, -5, -7, -10],
+    [0, 1, 2, 3, 4, 6, 8, 13, 0, -1, -2, -3, -4, -6, -8, -13],
+    [0, 1, 2, 4, 5, 7, 10, 15, 0, -1, -2, -4, -5, -7, -10, -15],
+    [0, 1, 3, 4, 6, 9, 13, 19, 0, -1, -3, -4, -6, -9, -13, -19],
+    [0, 2, 3, 5, 8, 11, 15, 23, 0, -2, -3, -5, -8, -11, -15, -23],
+    [0, 2, 4, 7, 10, 14, 19, 29, 0, -2, -4, -7, -10, -14, -19, -29],
+    [0, 3, 5, 8, 12, 16, 22, 33, 0, -3, -5, -8, -12, -16, -22, -33],
+    [1, 4, 7, 10, 15, 20, 29, 43, -1, -4, -7, -10, -15, -20, -29, -43],
+    [1, 4, 8, 13, 18, 25, 35, 53, -1, -4, -8, -13, -18, -25, -35, -53],
+    [1, 6, 10, 16, 22, 31, 43, 64, -1, -6, -10, -16, -22, -31, -43, -64],
+    [2, 7, 12, 19, 27, 37, 51, 76, -2, -7, -12, -19, -27, -37, -51, -76],
+    [2, 9, 16, 24, 34, 46, 64, 96, -2, -9, -16, -24, -34, -46, -64, -96],
+    [3, 11, 19, 29, 41, 57, 79, 117, -3, -11, -19, -29, -41, -57, -79, -117],
+    [4, 13, 24, 36, 50, 69, 96, 143, -4, -13, -24, -36, -50, -69, -96, -143],
+    [4, 16, 29, 44, 62, 85, 118, 175, -4, -16, -29, -44, -62, -85, -118, -175],
+    [6, 20, 36, 54, 76, 104, 144, 214, -6, -20, -36, -54, -76, -104, -144, -214],
+];
+
+const STATE_TABLE: [i8; 16] = [-1, -1, 0, 0, 1, 2, 2, 3, -1, -1, 0, 0, 1, 2, 2, 3];
+
+const MAX_STEP_STATE: i8 = 15;
+
+// Decoded samples are 9-bit signed
+const SAMPLE_MIN: i16 = -256;
+const SAMPLE_MAX: i16 = 255;
+
+#[derive(Debug, Clone, Encode, Decode)]
+pub struct Adpcm {
+    fifo: VecDeque<u8>,
+    pending_nibble: Option<u8>,
+    sample: i16,
+    step_state: i8,
+}
+
+impl Adpcm {
+    pub fn new() -> Self {
+        Self {
+            fifo: VecDeque::with_capacity(FIFO_LEN),
+            pending_nibble: None,
+            sample: 0,
+            step_state: 0,
+        }
+    }
+
+    pub fn push_byte(&mut self, value: u8) {
+        if self.fifo.len() < FIFO_LEN {
+            self.fifo.push_back(value);
+        } else {
+            log::debug!("Pico ADPCM FIFO overflow, dropping byte {value:02X}");
+        }
+    }
+
+    /// Number of bytes that can be written to the FIFO; games poll this before writing.
+    pub fn fifo_free(&self) -> u16 {
+        (FIFO_LEN - 1).saturating_sub(self.fifo.len()) as u16
+    }
+
+    /// Status register; bit 15 is set while the chip is playing.
+    pub fn read_status(&self) -> u16 {
+        u16::from(self.is_busy()) << 15
+    }
+
+    /// Control register; setting bit 15 resets the chip and clears the FIFO.
+    pub fn write_control(&mut self, value: u16) {
+        if value.bit(15) {
+            self.fifo.clear();
+            self.reset_decoder();
+        }
+    }
+
+    fn is_busy(&self) -> bool {
+        self.pending_nibble.is_some() || !self.fifo.is_empty()
+    }
+
+    fn reset_decoder(&mut self) {
+        self.pending_nibble = None;
+        self.sample = 0;
+        self.step_state = 0;
+    }
+
+    /// Decode the next nibble. Should be called at the ADPCM sample rate.
+    pub fn clock(&mut self) {
+        let nibble = match self.pending_nibble.take() {
+            Some(nibble) => nibble,
+            None => match self.fifo.pop_front() {
+                Some(byte) => {
+                    self.pending_nibble = Some(byte & 0x0F);
+                    byte >> 4
+                }
+                None => {
+                    // FIFO ran dry; the chip goes idle
+                    self.reset_decoder();
+                    return;
+                }
+            },
+        };
+
+        self.decode_nibble(nibble);
+    }
+
+    fn decode_nibble(&mut self, nibble: u8) {
+        let delta = STEP_TABLE[self.step_state as usize][nibble as usize];
+        self.sample = (self.sample + delta).clamp(SAMPLE_MIN, SAMPLE_MAX);
+        self.step_state = (self.step_state + STATE_TABLE[nibble as usize]).clamp(0, MAX_STEP_STATE);
+    }
+
+    pub fn sample(&self) -> f64 {
+        f64::from(self.sample) / f64::from(-SAMPLE_MIN)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_high_nibble_first() {
+        let mut adpcm = Adpcm::new();
+        adpcm.push_byte(0x77);
+        adpcm.push_byte(0xF0);
+
+        let mut samples = Vec::new();
+        for _ in 0..4 {
+            adpcm.clock();
+            samples.push(adpcm.sample);
+        }
+
+        // 0 + 10 (state 0 -> 3), + 19 (state 3 -> 6), - 33 (state 6 -> 9), + 1 (state 9 -> 8)
+        assert_eq!(samples, vec![10, 29, -4, -3]);
+    }
+
+    #[test]
+    fn reset_clears_fifo() {
+        let mut adpcm = Adpcm::new();
+        for _ in 0..10 {
+            adpcm.push_byte(0x12);
+        }
+        assert_eq!(adpcm.fifo_free(), 53);
+        assert_eq!(adpcm.read_status(), 0x8000);
+
+        adpcm.write_control(0x8000);
+        assert_eq!(adpcm.fifo_free(), 63);
+        assert_eq!(adpcm.read_status(), 0x0000);
+    }
+}
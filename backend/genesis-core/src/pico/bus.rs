@@ -0,0 +1,121 @@
+//! Pico 68000 bus interface
+//!
+//! The Pico has no Z80 or Genesis I/O area; the only hardware outside of ROM, RAM, and the VDP
+//! is the I/O block at $800000.
+
+use crate::memory::{self, Cartridge, Memory, PhysicalMedium};
+use crate::pico::io::PicoIo;
+use crate::soundlog::SoundLog;
+use crate::vdp::Vdp;
+use jgenesis_common::num::U16Ext;
+use smsgg_core::psg::Psg;
+
+// The Pico has a 24-bit bus, same as the Genesis
+const ADDRESS_MASK: u32 = 0xFFFFFF;
+
+pub struct PicoBus<'a> {
+    pub memory: &'a mut Memory<Cartridge>,
+    pub vdp: &'a mut Vdp,
+    pub psg: &'a mut Psg,
+    pub io: &'a mut PicoIo,
+    pub sound_log: &'a mut SoundLog,
+    pub m68k_reset: bool,
+}
+
+impl m68000_emu::BusInterface for PicoBus<'_> {
+    #[inline]
+    fn read_byte(&mut self, address: u32) -> u8 {
+        let address = address & ADDRESS_MASK;
+        match address {
+            0x000000..=0x3FFFFF => self.memory.medium_mut().read_byte(address),
+            0x800000..=0x80001F => self.io.read_register(address),
+            0xC00000..=0xC0001F => memory::read_vdp_byte(self.vdp, address),
+            0xE00000..=0xFFFFFF => self.memory.main_ram()[(address & 0xFFFF) as usize],
+            _ => 0xFF,
+        }
+    }
+
+    #[inline]
+    fn read_word(&mut self, address: u32) -> u16 {
+        let address = address & ADDRESS_MASK;
+        match address {
+            0x000000..=0x3FFFFF => self.memory.medium_mut().read_word(address),
+            0x800000..=0x80001F => self.io.read_register_word(address),
+            0xC00000..=0xC00003 => self.vdp.read_data(),
+            0xC00004..=0xC00007 => self.vdp.read_status(),
+            0xC00008..=0xC0000F => self.vdp.hv_counter(),
+            0xE00000..=0xFFFFFF => {
+                let main_ram = self.memory.main_ram();
+                let ram_addr = (address & 0xFFFF) as usize;
+                u16::from_be_bytes([main_ram[ram_addr], main_ram[(ram_addr + 1) & 0xFFFF]])
+            }
+            _ => 0xFFFF,
+        }
+    }
+
+    #[inline]
+    fn write_byte(&mut self, address: u32, value: u8) {
+        let address = address & ADDRESS_MASK;
+        match address {
+            0x000000..=0x3FFFFF => self.memory.medium_mut().write_byte(address, value),
+            0x800000..=0x80001F => self.io.write_register_byte(address, value),
+            0xC00000..=0xC0001F => {
+                memory::write_vdp_byte(self.vdp, self.psg, self.sound_log, address, value);
+            }
+            0xE00000..=0xFFFFFF => {
+                self.memory.main_ram_mut()[(address & 0xFFFF) as usize] = value;
+            }
+            _ => {}
+        }
+    }
+
+    #[inline]
+    fn write_word(&mut self, address: u32, value: u16) {
+        let address = address & ADDRESS_MASK;
+        match address {
+            0x000000..=0x3FFFFF => self.memory.medium_mut().write_word(address, value),
+            0x800000..=0x80001F => self.io.write_register_word(address, value),
+            0xC00000..=0xC00003 => self.vdp.write_data(value),
+            0xC00004..=0xC00007 => self.vdp.write_control(value),
+            0xC00010..=0xC00017 => {
+                // PSG is connected to the low byte of the data bus
+                memory::write_vdp_byte(
+                    self.vdp,
+                    self.psg,
+                    self.sound_log,
+                    address | 1,
+                    value.lsb(),
+                );
+            }
+            0xC0001C => self.vdp.write_debug_register(value),
+            0xE00000..=0xFFFFFF => {
+                let main_ram = self.memory.main_ram_mut();
+                let ram_addr = (address & 0xFFFF) as usize;
+                main_ram[ram_addr] = value.msb();
+                main_ram[(ram_addr + 1) & 0xFFFF] = value.lsb();
+            }
+            _ => {}
+        }
+    }
+
+    #[inline]
+    fn interrupt_level(&self) -> u8 {
+        self.vdp.m68k_interrupt_level()
+    }
+
+    #[inline]
+    fn acknowledge_interrupt(&mut self, interrupt_level: u8) -> u8 {
+        self.vdp.acknowledge_m68k_interrupt();
+        m68000_emu::traits::autovector(interrupt_level)
+    }
+
+    #[inline]
+    fn halt(&self) -> bool {
+        self.vdp.should_halt_cpu()
+    }
+
+    #[inline]
+    fn reset(&self) -> bool {
+        self.m68k_reset
+    }
+}
@@ -0,0 +1,132 @@
+//! Pico I/O registers at $800000-$80001F: buttons, pen position, storyware page, and ADPCM
+
+use crate::pico::adpcm::Adpcm;
+use crate::pico::{PicoInputs, PicoPenTarget, MAX_PAGE};
+use crate::GenesisRegion;
+use bincode::{Decode, Encode};
+use jgenesis_common::frontend::TimingMode;
+use jgenesis_common::num::U16Ext;
+
+// Range of pen X values reported by the hardware, from the left edge to the right edge
+const PEN_X_MIN: u16 = 0x03C;
+const PEN_X_MAX: u16 = 0x17C;
+
+// The drawing pad and the storyware share the Y axis; the storyware occupies the higher half
+const PAD_Y_MIN: u16 = 0x1FC;
+const PAD_Y_MAX: u16 = 0x2F7;
+const STORYWARE_Y_MIN: u16 = 0x2F8;
+const STORYWARE_Y_MAX: u16 = 0x3F3;
+
+#[derive(Debug, Clone, Encode, Decode)]
+pub struct PicoIo {
+    inputs: PicoInputs,
+    pen_x: u16,
+    pen_y: u16,
+    region: GenesisRegion,
+    timing_mode: TimingMode,
+    adpcm: Adpcm,
+}
+
+impl PicoIo {
+    pub fn new(region: GenesisRegion, timing_mode: TimingMode) -> Self {
+        Self {
+            inputs: PicoInputs::default(),
+            pen_x: 0,
+            pen_y: 0,
+            region,
+            timing_mode,
+            adpcm: Adpcm::new(),
+        }
+    }
+
+    pub fn adpcm_mut(&mut self) -> &mut Adpcm {
+        &mut self.adpcm
+    }
+
+    /// Latch new inputs, converting the pen position from frame buffer pixels to the pen
+    /// coordinates reported by the hardware.
+    pub fn set_inputs(&mut self, inputs: PicoInputs, frame_width: u32, frame_height: u32) {
+        self.inputs = inputs;
+
+        let Some((x, y)) = inputs.pen.position else {
+            self.pen_x = 0;
+            self.pen_y = 0;
+            return;
+        };
+
+        let (y_min, y_max) = match inputs.pen.target {
+            PicoPenTarget::DrawingPad => (PAD_Y_MIN, PAD_Y_MAX),
+            PicoPenTarget::Storyware => (STORYWARE_Y_MIN, STORYWARE_Y_MAX),
+        };
+
+        self.pen_x = scale_coordinate(x, frame_width, PEN_X_MIN, PEN_X_MAX);
+        self.pen_y = scale_coordinate(y, frame_height, y_min, y_max);
+    }
+
+    pub fn read_register(&self, address: u32) -> u8 {
+        match address & 0x1F {
+            0x01 => {
+                // Same layout as the Genesis version register, without the expansion bit
+                (u8::from(self.region != GenesisRegion::Japan) << 7)
+                    | (u8::from(self.timing_mode == TimingMode::Pal) << 6)
+            }
+            0x03 => {
+                // Buttons are active low
+                !(u8::from(self.inputs.up)
+                    | (u8::from(self.inputs.down) << 1)
+                    | (u8::from(self.inputs.left) << 2)
+                    | (u8::from(self.inputs.right) << 3)
+                    | (u8::from(self.inputs.red) << 4)
+                    | (u8::from(self.inputs.pen.pressed) << 7))
+            }
+            0x05 => self.pen_x.msb(),
+            0x07 => self.pen_x.lsb(),
+            0x09 => self.pen_y.msb(),
+            0x0B => self.pen_y.lsb(),
+            0x0D => {
+                // One sensor bit per page; every page up to the open one is covered
+                let page = self.inputs.page.min(MAX_PAGE);
+                ((1_u16 << page) - 1) as u8
+            }
+            0x10 => self.adpcm.fifo_free().msb(),
+            0x11 => self.adpcm.fifo_free().lsb(),
+            0x12 => self.adpcm.read_status().msb(),
+            0x13 => self.adpcm.read_status().lsb(),
+            _ => 0x00,
+        }
+    }
+
+    pub fn read_register_word(&self, address: u32) -> u16 {
+        let address = address & !1;
+        u16::from_be_bytes([self.read_register(address), self.read_register(address | 1)])
+    }
+
+    pub fn write_register_byte(&mut self, address: u32, value: u8) {
+        match address & 0x1F {
+            0x10 | 0x11 => self.adpcm.push_byte(value),
+            0x12 => self.adpcm.write_control(u16::from_be_bytes([value, 0])),
+            _ => {
+                log::trace!("Unhandled Pico I/O write: {address:06X} {value:02X}");
+            }
+        }
+    }
+
+    pub fn write_register_word(&mut self, address: u32, value: u16) {
+        match address & 0x1E {
+            0x10 => {
+                self.adpcm.push_byte(value.msb());
+                self.adpcm.push_byte(value.lsb());
+            }
+            0x12 => self.adpcm.write_control(value),
+            _ => {
+                log::trace!("Unhandled Pico I/O write: {address:06X} {value:04X}");
+            }
+        }
+    }
+}
+
+fn scale_coordinate(position: u16, frame_len: u32, min: u16, max: u16) -> u16 {
+    let last = frame_len.saturating_sub(1).max(1);
+    let position = u32::from(position).min(last);
+    min + (position * u32::from(max - min) / last) as u16
+}
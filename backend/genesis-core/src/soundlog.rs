@@ -0,0 +1,469 @@
+//! YM2612 and PSG register write logging, for exporting music to VGM and GYM files
+//!
+//! The emulator always tracks the last value written to every sound chip register so that a log
+//! started in the middle of a song can begin by restoring the current chip state. Writes are only
+//! recorded while logging is enabled, and the frontend periodically drains them into a
+//! [`SoundLogWriter`].
+//!
+//! Separately from VGM/GYM logging, writes can be recorded into a timestamped [`trace`] for
+//! debugging sound driver behavior.
+
+pub mod trace;
+
+use crate::soundlog::trace::SoundTraceEntry;
+use bincode::{Decode, Encode};
+use jgenesis_common::frontend::TimingMode;
+use jgenesis_proc_macros::{FakeDecode, FakeEncode};
+
+const NTSC_MCLK_FREQUENCY: u64 = 53_693_175;
+const PAL_MCLK_FREQUENCY: u64 = 53_203_424;
+
+const YM2612_MCLK_DIVIDER: u64 = 7;
+const PSG_MCLK_DIVIDER: u64 = 15;
+
+const VGM_SAMPLE_RATE: u64 = 44100;
+const VGM_VERSION: u32 = 0x0000_0150;
+const VGM_HEADER_LEN: usize = 0x40;
+
+// GYM files are always timed in 60Hz frames, regardless of the console's refresh rate
+const GYM_FRAME_RATE: u64 = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoundChipWrite {
+    /// Register write to YM2612 port 0 (system registers + channels 1-3) or port 1 (channels 4-6)
+    Ym2612 {
+        port: u8,
+        register: u8,
+        value: u8,
+    },
+    Psg(u8),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoundLogEvent {
+    Write(SoundChipWrite),
+    /// Wait for the given number of master clock cycles
+    Wait(u64),
+}
+
+/// The CPU that performed a sound chip write.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode)]
+pub enum SoundWriteSource {
+    M68000,
+    /// Writes default to the Z80 because the main CPU's bus writes are applied in a batch after
+    /// the Z80 has executed
+    #[default]
+    Z80,
+}
+
+#[derive(Debug, Clone, Default, FakeEncode, FakeDecode)]
+struct SoundLogRecording {
+    enabled: bool,
+    pending_mclk_cycles: u64,
+    events: Vec<SoundLogEvent>,
+}
+
+#[derive(Debug, Clone, Default, FakeEncode, FakeDecode)]
+struct SoundTraceRecording {
+    enabled: bool,
+    mclk_elapsed: u64,
+    entries: Vec<SoundTraceEntry>,
+}
+
+#[derive(Debug, Clone, Encode, Decode)]
+pub struct SoundLog {
+    ym2612_registers: [[u8; 256]; 2],
+    ym2612_key_on: [u8; 8],
+    ym2612_address: (u8, u8),
+    psg_latches: [u8; 8],
+    psg_tone_high_bits: [u8; 3],
+    psg_latched_register: u8,
+    write_source: SoundWriteSource,
+    recording: SoundLogRecording,
+    trace: SoundTraceRecording,
+}
+
+impl SoundLog {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            ym2612_registers: [[0; 256]; 2],
+            ym2612_key_on: [0; 8],
+            ym2612_address: (0, 0),
+            // All PSG channels are silent (attenuation $F) at power-on
+            psg_latches: [0x80, 0x9F, 0xA0, 0xBF, 0xC0, 0xDF, 0xE0, 0xFF],
+            psg_tone_high_bits: [0; 3],
+            psg_latched_register: 0,
+            write_source: SoundWriteSource::default(),
+            recording: SoundLogRecording::default(),
+            trace: SoundTraceRecording::default(),
+        }
+    }
+
+    #[must_use]
+    pub fn is_enabled(&self) -> bool {
+        self.recording.enabled
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        if enabled == self.recording.enabled {
+            return;
+        }
+
+        self.recording = SoundLogRecording { enabled, ..SoundLogRecording::default() };
+    }
+
+    /// Take all events recorded since the last call, including a trailing wait for any time that
+    /// has passed since the last write.
+    pub fn drain_events(&mut self) -> Vec<SoundLogEvent> {
+        self.flush_pending_wait();
+        std::mem::take(&mut self.recording.events)
+    }
+
+    #[must_use]
+    pub fn is_trace_enabled(&self) -> bool {
+        self.trace.enabled
+    }
+
+    /// Enable or disable write tracing. Trace timestamps count master clock cycles from the point
+    /// that tracing was enabled.
+    pub fn set_trace_enabled(&mut self, enabled: bool) {
+        if enabled == self.trace.enabled {
+            return;
+        }
+
+        self.trace = SoundTraceRecording { enabled, ..SoundTraceRecording::default() };
+    }
+
+    /// Take all trace entries recorded since the last call.
+    pub fn drain_trace_entries(&mut self) -> Vec<SoundTraceEntry> {
+        std::mem::take(&mut self.trace.entries)
+    }
+
+    /// Register writes that will restore both sound chips to their current state.
+    #[must_use]
+    pub fn state_writes(&self) -> Vec<SoundChipWrite> {
+        let mut writes = Vec::new();
+
+        let mut ym2612_write = |port: u8, register: u8, value: u8| {
+            writes.push(SoundChipWrite::Ym2612 { port, register, value });
+        };
+
+        // LFO, channel 3 mode (without the timer control bits), and DAC
+        ym2612_write(0, 0x22, self.ym2612_registers[0][0x22]);
+        ym2612_write(0, 0x27, self.ym2612_registers[0][0x27] & 0xC0);
+        ym2612_write(0, 0x2B, self.ym2612_registers[0][0x2B]);
+        ym2612_write(0, 0x2A, self.ym2612_registers[0][0x2A]);
+
+        for port in 0..2 {
+            let registers = &self.ym2612_registers[port as usize];
+
+            // Operator registers
+            for register in 0x30..=0x9F {
+                if register & 0x03 != 0x03 {
+                    ym2612_write(port, register, registers[register as usize]);
+                }
+            }
+
+            // The high frequency byte is latched until the low byte is written, so it must be
+            // written first
+            for (high, low) in [(0xA4..=0xA6, 0xA0..=0xA2), (0xAC..=0xAE, 0xA8..=0xAA)] {
+                for register in high.chain(low) {
+                    ym2612_write(port, register, registers[register as usize]);
+                }
+            }
+
+            for register in 0xB0..=0xB6 {
+                if register & 0x03 != 0x03 {
+                    ym2612_write(port, register, registers[register as usize]);
+                }
+            }
+        }
+
+        for channel in [0, 1, 2, 4, 5, 6] {
+            ym2612_write(0, 0x28, self.ym2612_key_on[channel]);
+        }
+
+        for (register, &latch) in self.psg_latches.iter().enumerate() {
+            writes.push(SoundChipWrite::Psg(latch));
+
+            // Tone registers have 6 more bits in a data byte
+            if is_psg_tone_register(register) {
+                writes.push(SoundChipWrite::Psg(self.psg_tone_high_bits[register / 2]));
+            }
+        }
+
+        writes
+    }
+
+    /// Advance the log clock by the given number of Genesis master clock cycles.
+    pub fn tick(&mut self, mclk_cycles: u64) {
+        if self.recording.enabled {
+            self.recording.pending_mclk_cycles += mclk_cycles;
+        }
+
+        if self.trace.enabled {
+            self.trace.mclk_elapsed += mclk_cycles;
+        }
+    }
+
+    pub(crate) fn set_write_source(&mut self, source: SoundWriteSource) {
+        self.write_source = source;
+    }
+
+    pub(crate) fn log_ym2612_address(&mut self, port: u8, value: u8) {
+        self.ym2612_address = (port, value);
+    }
+
+    pub(crate) fn log_ym2612_data(&mut self, value: u8) {
+        let (port, register) = self.ym2612_address;
+        self.ym2612_registers[port as usize][register as usize] = value;
+
+        if port == 0 && register == 0x28 {
+            self.ym2612_key_on[(value & 0x07) as usize] = value;
+        }
+
+        self.record(SoundChipWrite::Ym2612 { port, register, value });
+    }
+
+    pub(crate) fn log_psg_write(&mut self, value: u8) {
+        if value & 0x80 != 0 {
+            self.psg_latched_register = (value >> 4) & 0x07;
+            self.psg_latches[self.psg_latched_register as usize] = value;
+        } else {
+            let register = self.psg_latched_register as usize;
+            if is_psg_tone_register(register) {
+                self.psg_tone_high_bits[register / 2] = value;
+            } else {
+                // Data bytes written to volume and noise registers replace the low 4 bits
+                let latch = &mut self.psg_latches[register];
+                *latch = (*latch & 0xF0) | (value & 0x0F);
+            }
+        }
+
+        self.record(SoundChipWrite::Psg(value));
+    }
+
+    fn record(&mut self, write: SoundChipWrite) {
+        if self.trace.enabled {
+            self.trace.entries.push(SoundTraceEntry {
+                mclk: self.trace.mclk_elapsed,
+                source: Some(self.write_source),
+                write,
+            });
+        }
+
+        if !self.recording.enabled {
+            return;
+        }
+
+        self.flush_pending_wait();
+        self.recording.events.push(SoundLogEvent::Write(write));
+    }
+
+    fn flush_pending_wait(&mut self) {
+        let pending = std::mem::take(&mut self.recording.pending_mclk_cycles);
+        if pending != 0 {
+            self.recording.events.push(SoundLogEvent::Wait(pending));
+        }
+    }
+}
+
+/// Master clock frequency in Hz for the given timing mode.
+#[must_use]
+pub fn mclk_frequency(timing_mode: TimingMode) -> u64 {
+    match timing_mode {
+        TimingMode::Ntsc => NTSC_MCLK_FREQUENCY,
+        TimingMode::Pal => PAL_MCLK_FREQUENCY,
+    }
+}
+
+fn is_psg_tone_register(register: usize) -> bool {
+    matches!(register, 0 | 2 | 4)
+}
+
+impl Default for SoundLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Converts sound log events into VGM and GYM command streams.
+#[derive(Debug, Clone)]
+pub struct SoundLogWriter {
+    timing_mode: TimingMode,
+    mclk_frequency: u64,
+    mclk_elapsed: u64,
+    vgm_data: Vec<u8>,
+    vgm_samples: u64,
+    gym_data: Vec<u8>,
+    gym_frames: u64,
+}
+
+impl SoundLogWriter {
+    /// Create a writer that begins by restoring the given chip state.
+    #[must_use]
+    pub fn new(timing_mode: TimingMode, initial_state: &[SoundChipWrite]) -> Self {
+        let mclk_frequency = mclk_frequency(timing_mode);
+
+        let mut writer = Self {
+            timing_mode,
+            mclk_frequency,
+            mclk_elapsed: 0,
+            vgm_data: Vec::new(),
+            vgm_samples: 0,
+            gym_data: Vec::new(),
+            gym_frames: 0,
+        };
+
+        for &write in initial_state {
+            writer.push_write(write);
+        }
+
+        writer
+    }
+
+    pub fn push_events(&mut self, events: &[SoundLogEvent]) {
+        for &event in events {
+            match event {
+                SoundLogEvent::Write(write) => self.push_write(write),
+                SoundLogEvent::Wait(mclk_cycles) => self.push_wait(mclk_cycles),
+            }
+        }
+    }
+
+    fn push_write(&mut self, write: SoundChipWrite) {
+        match write {
+            SoundChipWrite::Ym2612 { port, register, value } => {
+                self.vgm_data.extend([0x52 + port, register, value]);
+                self.gym_data.extend([0x01 + port, register, value]);
+            }
+            SoundChipWrite::Psg(value) => {
+                self.vgm_data.extend([0x50, value]);
+                self.gym_data.extend([0x03, value]);
+            }
+        }
+    }
+
+    fn push_wait(&mut self, mclk_cycles: u64) {
+        self.mclk_elapsed += mclk_cycles;
+
+        let vgm_samples = self.mclk_elapsed * VGM_SAMPLE_RATE / self.mclk_frequency;
+        let mut vgm_wait = vgm_samples - self.vgm_samples;
+        self.vgm_samples = vgm_samples;
+        while vgm_wait != 0 {
+            let samples = vgm_wait.min(u16::MAX.into());
+            match samples {
+                1..=16 => self.vgm_data.push(0x70 + (samples - 1) as u8),
+                735 => self.vgm_data.push(0x62),
+                882 => self.vgm_data.push(0x63),
+                _ => {
+                    self.vgm_data.push(0x61);
+                    self.vgm_data.extend((samples as u16).to_le_bytes());
+                }
+            }
+            vgm_wait -= samples;
+        }
+
+        let gym_frames = self.mclk_elapsed * GYM_FRAME_RATE / self.mclk_frequency;
+        for _ in self.gym_frames..gym_frames {
+            self.gym_data.push(0x00);
+        }
+        self.gym_frames = gym_frames;
+    }
+
+    /// Number of VGM samples (44100Hz) logged so far.
+    #[must_use]
+    pub fn vgm_samples(&self) -> u64 {
+        self.vgm_samples
+    }
+
+    /// Build a complete VGM file from everything logged so far.
+    #[must_use]
+    pub fn to_vgm_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![0; VGM_HEADER_LEN];
+        bytes.extend(&self.vgm_data);
+        // End of sound data
+        bytes.push(0x66);
+
+        let mut write_u32 = |offset: usize, value: u32| {
+            bytes[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+        };
+
+        let file_len = (VGM_HEADER_LEN + self.vgm_data.len() + 1) as u32;
+        let rate = match self.timing_mode {
+            TimingMode::Ntsc => 60,
+            TimingMode::Pal => 50,
+        };
+
+        write_u32(0x04, file_len - 4);
+        write_u32(0x08, VGM_VERSION);
+        write_u32(0x0C, (self.mclk_frequency / PSG_MCLK_DIVIDER) as u32);
+        write_u32(0x18, self.vgm_samples as u32);
+        write_u32(0x24, rate);
+        write_u32(0x2C, (self.mclk_frequency / YM2612_MCLK_DIVIDER) as u32);
+        // Data starts immediately after the header; offset is relative to $34
+        write_u32(0x34, (VGM_HEADER_LEN - 0x34) as u32);
+
+        bytes[0x00..0x04].copy_from_slice(b"Vgm ");
+        // SN76489 noise feedback pattern and shift register width for the Sega VDP PSG
+        bytes[0x28..0x2A].copy_from_slice(&0x0009_u16.to_le_bytes());
+        bytes[0x2A] = 16;
+
+        bytes
+    }
+
+    /// Build a complete GYM file from everything logged so far. GYM files have no header.
+    #[must_use]
+    pub fn to_gym_bytes(&self) -> Vec<u8> {
+        self.gym_data.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn state_writes_restore_frequency_high_byte_first() {
+        let mut log = SoundLog::new();
+        log.log_ym2612_address(0, 0xA0);
+        log.log_ym2612_data(0x12);
+        log.log_ym2612_address(0, 0xA4);
+        log.log_ym2612_data(0x22);
+
+        let writes = log.state_writes();
+        let position = |register| {
+            writes
+                .iter()
+                .position(|&write| {
+                    matches!(write, SoundChipWrite::Ym2612 { port: 0, register: r, .. } if r == register)
+                })
+                .unwrap()
+        };
+        assert!(position(0xA4) < position(0xA0));
+        assert!(writes.contains(&SoundChipWrite::Ym2612 { port: 0, register: 0xA0, value: 0x12 }));
+    }
+
+    #[test]
+    fn waits_are_converted_to_vgm_and_gym_timing() {
+        let mut log = SoundLog::new();
+        log.set_enabled(true);
+
+        log.tick(NTSC_MCLK_FREQUENCY / 60);
+        log.log_psg_write(0x9F);
+        log.tick(NTSC_MCLK_FREQUENCY / 60);
+
+        let mut writer = SoundLogWriter::new(TimingMode::Ntsc, &[]);
+        writer.push_events(&log.drain_events());
+
+        // 1/60s = 735 samples at 44100Hz
+        assert_eq!(writer.vgm_samples(), 1469);
+        assert_eq!(writer.to_gym_bytes(), vec![0x03, 0x9F, 0x00]);
+
+        let vgm = writer.to_vgm_bytes();
+        assert_eq!(&vgm[..4], b"Vgm ");
+        assert_eq!(u32::from_le_bytes(vgm[4..8].try_into().unwrap()) as usize, vgm.len() - 4);
+        assert_eq!(vgm[VGM_HEADER_LEN..], [0x61, 0xDE, 0x02, 0x50, 0x9F, 0x62, 0x66]);
+    }
+}
@@ -0,0 +1,409 @@
+//! Timestamped sound chip write traces, for debugging sound driver playback
+//!
+//! A trace records every YM2612 and PSG register write along with the master clock cycle that it
+//! occurred on and the CPU that performed it. Traces can be saved to and parsed from a plain text
+//! format with one write per line, converted from VGM files (e.g. logged from real hardware), and
+//! compared against each other to find where two runs of a sound driver diverge.
+
+use crate::soundlog::{
+    SoundChipWrite, SoundWriteSource, NTSC_MCLK_FREQUENCY, PSG_MCLK_DIVIDER, VGM_SAMPLE_RATE,
+    YM2612_MCLK_DIVIDER,
+};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use thiserror::Error;
+
+// How far ahead to search for a matching write when two traces diverge
+const RESYNC_WINDOW: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SoundTraceEntry {
+    /// Master clock cycles since the start of the trace
+    pub mclk: u64,
+    /// None if the source is not known, e.g. for traces converted from VGM files
+    pub source: Option<SoundWriteSource>,
+    pub write: SoundChipWrite,
+}
+
+impl Display for SoundTraceEntry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let source = match self.source {
+            Some(SoundWriteSource::M68000) => "68K",
+            Some(SoundWriteSource::Z80) => "Z80",
+            None => "---",
+        };
+
+        match self.write {
+            SoundChipWrite::Ym2612 { port, register, value } => {
+                write!(f, "{} {source} YM{port} {register:02X} {value:02X}", self.mclk)
+            }
+            SoundChipWrite::Psg(value) => write!(f, "{} {source} PSG {value:02X}", self.mclk),
+        }
+    }
+}
+
+impl FromStr for SoundTraceEntry {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse_byte =
+            |token: Option<&str>| token.and_then(|token| u8::from_str_radix(token, 16).ok());
+
+        let mut tokens = s.split_whitespace();
+        let mclk = tokens.next().and_then(|token| token.parse().ok()).ok_or("invalid timestamp")?;
+        let source = match tokens.next() {
+            Some("68K") => Some(SoundWriteSource::M68000),
+            Some("Z80") => Some(SoundWriteSource::Z80),
+            Some("---") => None,
+            _ => return Err("invalid source".into()),
+        };
+        let write = match tokens.next() {
+            Some(chip @ ("YM0" | "YM1")) => {
+                let port = u8::from(chip == "YM1");
+                let register = parse_byte(tokens.next()).ok_or("invalid register")?;
+                let value = parse_byte(tokens.next()).ok_or("invalid value")?;
+                SoundChipWrite::Ym2612 { port, register, value }
+            }
+            Some("PSG") => SoundChipWrite::Psg(parse_byte(tokens.next()).ok_or("invalid value")?),
+            _ => return Err("invalid chip".into()),
+        };
+
+        if tokens.next().is_some() {
+            return Err("unexpected trailing characters".into());
+        }
+
+        Ok(Self { mclk, source, write })
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum SoundTraceError {
+    #[error("Invalid trace on line {line}: {message}")]
+    InvalidLine { line: usize, message: String },
+    #[error("File is not a VGM file")]
+    NotVgm,
+    #[error("VGM file ended unexpectedly at offset ${offset:X}")]
+    VgmTruncated { offset: usize },
+    #[error("Unsupported VGM command ${command:02X} at offset ${offset:X}")]
+    UnsupportedVgmCommand { command: u8, offset: usize },
+}
+
+/// Format trace entries as text, one entry per line.
+#[must_use]
+pub fn to_text(entries: &[SoundTraceEntry]) -> String {
+    let mut text = String::from("# mclk source chip [register] value\n");
+    for entry in entries {
+        text.push_str(&entry.to_string());
+        text.push('\n');
+    }
+    text
+}
+
+/// Parse trace entries from text in the format written by [`to_text`]. Blank lines and lines
+/// starting with `#` are ignored.
+///
+/// # Errors
+///
+/// This function will return an error if any line is not a valid trace entry.
+pub fn parse_text(text: &str) -> Result<Vec<SoundTraceEntry>, SoundTraceError> {
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(line, text)| {
+            text.parse().map_err(|message| SoundTraceError::InvalidLine { line, message })
+        })
+        .collect()
+}
+
+/// Convert the YM2612 and PSG writes in a VGM file to trace entries. Wait times are converted from
+/// VGM samples to master clock cycles based on the chip clocks in the VGM header.
+///
+/// # Errors
+///
+/// This function will return an error if the file is not a valid VGM file, or if it uses DAC
+/// stream commands (which cannot be converted to individual register writes).
+pub fn from_vgm(bytes: &[u8]) -> Result<Vec<SoundTraceEntry>, SoundTraceError> {
+    let read_u32 = |offset: usize| -> Result<u32, SoundTraceError> {
+        bytes
+            .get(offset..offset + 4)
+            .and_then(|slice| slice.try_into().ok())
+            .map(u32::from_le_bytes)
+            .ok_or(SoundTraceError::VgmTruncated { offset })
+    };
+
+    if bytes.get(..4) != Some(b"Vgm ") {
+        return Err(SoundTraceError::NotVgm);
+    }
+
+    let version = read_u32(0x08)?;
+    let data_offset = match read_u32(0x34)? {
+        offset if version >= 0x150 && offset != 0 => 0x34 + offset as usize,
+        _ => 0x40,
+    };
+
+    let ym2612_clock = u64::from(read_u32(0x2C)? & 0x3FFF_FFFF);
+    let psg_clock = u64::from(read_u32(0x0C)? & 0x3FFF_FFFF);
+    let mclk_frequency = if ym2612_clock != 0 {
+        ym2612_clock * YM2612_MCLK_DIVIDER
+    } else if psg_clock != 0 {
+        psg_clock * PSG_MCLK_DIVIDER
+    } else {
+        NTSC_MCLK_FREQUENCY
+    };
+
+    let mut entries = Vec::new();
+    let mut samples: u64 = 0;
+    let mut pcm_data = Vec::new();
+    let mut pcm_offset = 0;
+    let mut offset = data_offset;
+
+    let operand =
+        |offset: usize| bytes.get(offset).copied().ok_or(SoundTraceError::VgmTruncated { offset });
+
+    loop {
+        let command = operand(offset)?;
+        let mut write = |write: SoundChipWrite| {
+            entries.push(SoundTraceEntry {
+                mclk: samples * mclk_frequency / VGM_SAMPLE_RATE,
+                source: None,
+                write,
+            });
+        };
+
+        let len = match command {
+            0x50 => {
+                write(SoundChipWrite::Psg(operand(offset + 1)?));
+                2
+            }
+            0x52 | 0x53 => {
+                let register = operand(offset + 1)?;
+                let value = operand(offset + 2)?;
+                write(SoundChipWrite::Ym2612 { port: command - 0x52, register, value });
+                3
+            }
+            0x61 => {
+                samples +=
+                    u64::from(u16::from_le_bytes([operand(offset + 1)?, operand(offset + 2)?]));
+                3
+            }
+            0x62 => {
+                samples += 735;
+                1
+            }
+            0x63 => {
+                samples += 882;
+                1
+            }
+            0x66 => break,
+            0x67 => {
+                let data_type = operand(offset + 2)?;
+                let size = read_u32(offset + 3)? as usize;
+                let data = bytes
+                    .get(offset + 7..offset + 7 + size)
+                    .ok_or(SoundTraceError::VgmTruncated { offset: offset + 7 })?;
+                // Type $00 is YM2612 PCM data
+                if data_type == 0x00 {
+                    pcm_data.extend_from_slice(data);
+                }
+                7 + size
+            }
+            0x70..=0x7F => {
+                samples += u64::from(command & 0x0F) + 1;
+                1
+            }
+            0x80..=0x8F => {
+                let value = pcm_data.get(pcm_offset).copied().unwrap_or(0);
+                pcm_offset += 1;
+                write(SoundChipWrite::Ym2612 { port: 0, register: 0x2A, value });
+                samples += u64::from(command & 0x0F);
+                1
+            }
+            0xE0 => {
+                pcm_offset = read_u32(offset + 1)? as usize;
+                5
+            }
+            // Writes to chips other than the YM2612 and PSG (including the Game Gear stereo
+            // register) are skipped
+            0x30..=0x3F | 0x4F => 2,
+            0x40..=0x4E | 0x51 | 0x54..=0x5F | 0xA0..=0xBF => 3,
+            0xC0..=0xDF => 4,
+            0xE1..=0xFF => 5,
+            _ => return Err(SoundTraceError::UnsupportedVgmCommand { command, offset }),
+        };
+        offset += len;
+    }
+
+    Ok(entries)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoundTraceDifference {
+    /// The same write occurs in both traces, but the time since the previous matching write
+    /// differs by more than the tolerance
+    Timing { left: SoundTraceEntry, right: SoundTraceEntry },
+    /// Different writes at the same position in both traces
+    Changed { left: SoundTraceEntry, right: SoundTraceEntry },
+    /// A write that only occurs in the left trace
+    LeftOnly(SoundTraceEntry),
+    /// A write that only occurs in the right trace
+    RightOnly(SoundTraceEntry),
+}
+
+/// Compare two traces write-by-write.
+///
+/// Timing is compared using the interval since the previous matching write rather than absolute
+/// timestamps, so traces that start at different times can be compared and a single late write
+/// does not cause every following write to be reported. Write sources are ignored because traces
+/// converted from VGM files do not have them.
+#[must_use]
+pub fn diff(
+    left: &[SoundTraceEntry],
+    right: &[SoundTraceEntry],
+    timing_tolerance_mclk: u64,
+) -> Vec<SoundTraceDifference> {
+    let mut differences = Vec::new();
+    let mut previous: Option<(u64, u64)> = None;
+    let (mut left_idx, mut right_idx) = (0, 0);
+
+    while left_idx < left.len() && right_idx < right.len() {
+        let (left_entry, right_entry) = (left[left_idx], right[right_idx]);
+
+        if left_entry.write == right_entry.write {
+            if let Some((previous_left, previous_right)) = previous {
+                let left_interval = left_entry.mclk.saturating_sub(previous_left);
+                let right_interval = right_entry.mclk.saturating_sub(previous_right);
+                if left_interval.abs_diff(right_interval) > timing_tolerance_mclk {
+                    differences.push(SoundTraceDifference::Timing {
+                        left: left_entry,
+                        right: right_entry,
+                    });
+                }
+            }
+
+            previous = Some((left_entry.mclk, right_entry.mclk));
+            left_idx += 1;
+            right_idx += 1;
+            continue;
+        }
+
+        match find_resync(&left[left_idx..], &right[right_idx..]) {
+            Some(Resync::SkipLeft(len)) => {
+                let skipped = &left[left_idx..left_idx + len];
+                differences.extend(skipped.iter().copied().map(SoundTraceDifference::LeftOnly));
+                left_idx += len;
+            }
+            Some(Resync::SkipRight(len)) => {
+                let skipped = &right[right_idx..right_idx + len];
+                differences.extend(skipped.iter().copied().map(SoundTraceDifference::RightOnly));
+                right_idx += len;
+            }
+            None => {
+                differences
+                    .push(SoundTraceDifference::Changed { left: left_entry, right: right_entry });
+                previous = Some((left_entry.mclk, right_entry.mclk));
+                left_idx += 1;
+                right_idx += 1;
+            }
+        }
+    }
+
+    differences.extend(left[left_idx..].iter().copied().map(SoundTraceDifference::LeftOnly));
+    differences.extend(right[right_idx..].iter().copied().map(SoundTraceDifference::RightOnly));
+
+    differences
+}
+
+enum Resync {
+    SkipLeft(usize),
+    SkipRight(usize),
+}
+
+// Find the smallest number of writes to skip in one trace so that the next writes match again
+fn find_resync(left: &[SoundTraceEntry], right: &[SoundTraceEntry]) -> Option<Resync> {
+    (1..=RESYNC_WINDOW).find_map(|len| {
+        if left.get(len).is_some_and(|entry| entry.write == right[0].write) {
+            Some(Resync::SkipLeft(len))
+        } else if right.get(len).is_some_and(|entry| entry.write == left[0].write) {
+            Some(Resync::SkipRight(len))
+        } else {
+            None
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::soundlog::{SoundLog, SoundLogWriter};
+    use jgenesis_common::frontend::TimingMode;
+
+    fn ym(mclk: u64, register: u8, value: u8) -> SoundTraceEntry {
+        SoundTraceEntry {
+            mclk,
+            source: Some(SoundWriteSource::Z80),
+            write: SoundChipWrite::Ym2612 { port: 0, register, value },
+        }
+    }
+
+    #[test]
+    fn text_round_trip() {
+        let entries = vec![
+            ym(0, 0x28, 0xF0),
+            SoundTraceEntry {
+                mclk: 12345,
+                source: Some(SoundWriteSource::M68000),
+                write: SoundChipWrite::Psg(0x9F),
+            },
+            SoundTraceEntry {
+                mclk: 99999,
+                source: None,
+                write: SoundChipWrite::Ym2612 { port: 1, register: 0xA4, value: 0x22 },
+            },
+        ];
+
+        assert_eq!(parse_text(&to_text(&entries)).unwrap(), entries);
+        assert!(matches!(
+            parse_text("0 Z80 YM2 28 F0"),
+            Err(SoundTraceError::InvalidLine { line: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn vgm_writes_are_converted_to_trace() {
+        let mut log = SoundLog::new();
+        log.set_enabled(true);
+        log.log_ym2612_address(0, 0x28);
+        log.log_ym2612_data(0xF0);
+        log.tick(NTSC_MCLK_FREQUENCY / 60);
+        log.log_psg_write(0x9F);
+
+        let mut writer = SoundLogWriter::new(TimingMode::Ntsc, &[]);
+        writer.push_events(&log.drain_events());
+
+        let entries = from_vgm(&writer.to_vgm_bytes()).unwrap();
+        let writes: Vec<_> = entries.iter().map(|entry| entry.write).collect();
+        assert_eq!(writes, vec![
+            SoundChipWrite::Ym2612 { port: 0, register: 0x28, value: 0xF0 },
+            SoundChipWrite::Psg(0x9F)
+        ]);
+        // Timestamps are rounded to whole VGM samples
+        assert_eq!(entries[1].mclk, writer.vgm_samples() * NTSC_MCLK_FREQUENCY / VGM_SAMPLE_RATE);
+    }
+
+    #[test]
+    fn diff_reports_timing_and_missing_writes() {
+        let left = vec![ym(100, 0x28, 0x00), ym(200, 0x28, 0xF0), ym(300, 0xA0, 0x12)];
+        let right = vec![
+            ym(1100, 0x28, 0x00),
+            ym(1250, 0x28, 0xF0),
+            ym(1260, 0x2A, 0x80),
+            ym(1350, 0xA0, 0x12),
+        ];
+
+        assert_eq!(diff(&left, &right, 10), vec![
+            SoundTraceDifference::Timing { left: left[1], right: right[1] },
+            SoundTraceDifference::RightOnly(right[2]),
+        ]);
+    }
+}
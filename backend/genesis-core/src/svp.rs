@@ -349,9 +349,7 @@ impl Svp {
         match address {
             0x000000..=0x1FFFFF => {
                 // ROM
-                let msb = rom[address as usize];
-                let lsb = rom[(address + 1) as usize];
-                u16::from_be_bytes([msb, lsb])
+                read_rom_word(rom, address)
             }
             0x300000..=0x37FFFF => {
                 // DRAM, mirrored every 128KB / $1FFFF
@@ -433,10 +431,7 @@ impl Svp {
             }
             0x0400..=0xFFFF => {
                 // ROM (first 128KB); program memory address maps to the same address in ROM
-                let byte_addr = u32::from(address) << 1;
-                let msb = rom[byte_addr as usize];
-                let lsb = rom[(byte_addr + 1) as usize];
-                u16::from_be_bytes([msb, lsb])
+                read_rom_word(rom, u32::from(address) << 1)
             }
         }
     }
@@ -447,10 +442,7 @@ impl Svp {
         match address {
             0x000000..=0x0FFFFF => {
                 // ROM
-                let byte_addr = address << 1;
-                let msb = rom[byte_addr as usize];
-                let lsb = rom[(byte_addr + 1) as usize];
-                u16::from_be_bytes([msb, lsb])
+                read_rom_word(rom, address << 1)
             }
             0x180000..=0x18FFFF => {
                 // DRAM
@@ -485,3 +477,10 @@ impl Svp {
         }
     }
 }
+
+// Bytes past the end of the ROM read as $FF, same as unmapped cartridge addresses
+fn read_rom_word(rom: &[u8], byte_addr: u32) -> u16 {
+    let msb = rom.get(byte_addr as usize).copied().unwrap_or(0xFF);
+    let lsb = rom.get(byte_addr as usize + 1).copied().unwrap_or(0xFF);
+    u16::from_be_bytes([msb, lsb])
+}
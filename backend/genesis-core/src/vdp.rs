@@ -3,25 +3,35 @@
 mod colors;
 mod debug;
 mod dma;
+mod events;
 mod fifo;
+mod hires;
 mod registers;
 mod render;
 mod sprites;
+pub mod textures;
+
+pub use debug::{VdpDebugState, VdpDmaStatus};
+pub use events::{VdpEvent, VdpEventKind, VdpEventLog, VdpMemoryTarget};
+pub use registers::DmaMode;
 
 use crate::memory::{Memory, PhysicalMedium};
 use crate::vdp::colors::ColorModifier;
 use crate::vdp::dma::{DmaTracker, LineType};
 use crate::vdp::fifo::FifoTracker;
+use crate::vdp::hires::HiResFrameBuffer;
 use crate::vdp::registers::{
-    DebugRegister, DmaMode, HorizontalDisplaySize, InterlacingMode, Registers, VerticalDisplaySize,
-    VramSizeKb, H40_LEFT_BORDER, NTSC_BOTTOM_BORDER, NTSC_TOP_BORDER, PAL_V28_BOTTOM_BORDER,
+    DebugRegister, HorizontalDisplaySize, InterlacingMode, Registers, VerticalDisplaySize,
+    VramSizeKb, VramTable, H40_LEFT_BORDER, NTSC_BOTTOM_BORDER, NTSC_TOP_BORDER, PAL_V28_BOTTOM_BORDER,
     PAL_V28_TOP_BORDER, PAL_V30_BOTTOM_BORDER, PAL_V30_TOP_BORDER, RIGHT_BORDER,
 };
 use crate::vdp::sprites::{SpriteBuffers, SpriteState};
+use crate::vdp::textures::{DumpedTile, TileReplacements, TileTextures};
 use bincode::{Decode, Encode};
 use jgenesis_common::frontend::{Color, TimingMode};
 use jgenesis_common::num::GetBit;
 use jgenesis_proc_macros::{FakeDecode, FakeEncode};
+use std::mem;
 use std::ops::{Deref, DerefMut};
 use z80_emu::traits::InterruptLine;
 
@@ -222,7 +232,17 @@ impl DerefMut for FrameBuffer {
     }
 }
 
-const MAX_SCREEN_WIDTH: usize = 320 + H40_LEFT_BORDER as usize + RIGHT_BORDER as usize;
+/// Number of extra pixels rendered on each side of the screen in widescreen mode, which widens H40
+/// mode from 320 pixels to 424 pixels (roughly 16:9 with the standard pixel aspect ratio)
+pub const WIDESCREEN_EXTRA_PIXELS: u16 = 52;
+
+const BORDER_SCREEN_WIDTH: usize = 320 + H40_LEFT_BORDER as usize + RIGHT_BORDER as usize;
+const WIDESCREEN_SCREEN_WIDTH: usize = 320 + 2 * WIDESCREEN_EXTRA_PIXELS as usize;
+const MAX_SCREEN_WIDTH: usize = if WIDESCREEN_SCREEN_WIDTH > BORDER_SCREEN_WIDTH {
+    WIDESCREEN_SCREEN_WIDTH
+} else {
+    BORDER_SCREEN_WIDTH
+};
 const MAX_SCREEN_HEIGHT: usize = 240 + PAL_V30_TOP_BORDER as usize + PAL_V30_BOTTOM_BORDER as usize;
 
 // Double screen height to account for interlaced 2x mode
@@ -270,6 +290,15 @@ pub struct VdpConfig {
     pub emulate_non_linear_dac: bool,
     pub render_vertical_border: bool,
     pub render_horizontal_border: bool,
+    /// Render plane and sprite pixels beyond the left and right edges of the screen in H40 mode.
+    /// Has no effect if the horizontal border is rendered
+    pub widescreen: bool,
+    /// Output frames at 2x the resolution of an H40 frame, stretching H32 lines to match. This is an
+    /// enhancement that has no effect on emulation, only on the output frame
+    pub hi_res_output: bool,
+    /// Install 128KB of VRAM instead of 64KB, as on development hardware and the Tera Drive. Only
+    /// games that enable 128KB mode can access the extra 64KB
+    pub expanded_vram: bool,
 }
 
 type Vram = [u8; VRAM_LEN];
@@ -279,7 +308,11 @@ type Vsram = [u8; VSRAM_LEN];
 #[derive(Debug, Clone, Encode, Decode)]
 pub struct Vdp {
     frame_buffer: FrameBuffer,
+    hi_res_frame_buffer: HiResFrameBuffer,
+    tile_textures: TileTextures,
     vram: Box<Vram>,
+    // Upper 64KB of VRAM, only present if expanded VRAM is enabled
+    expanded_vram: Option<Box<Vram>>,
     cram: Box<Cram>,
     vsram: Box<Vsram>,
     timing_mode: TimingMode,
@@ -296,6 +329,7 @@ pub struct Vdp {
     config: VdpConfig,
     dma_tracker: DmaTracker,
     fifo_tracker: FifoTracker,
+    event_log: VdpEventLog,
 }
 
 impl Vdp {
@@ -304,7 +338,10 @@ impl Vdp {
     pub fn new(timing_mode: TimingMode, config: VdpConfig) -> Self {
         Self {
             frame_buffer: FrameBuffer::new(),
-            vram: vec![0; VRAM_LEN].into_boxed_slice().try_into().unwrap(),
+            hi_res_frame_buffer: HiResFrameBuffer::default(),
+            tile_textures: TileTextures::default(),
+            vram: new_vram(),
+            expanded_vram: config.expanded_vram.then(new_vram),
             cram: vec![0; CRAM_LEN_WORDS].into_boxed_slice().try_into().unwrap(),
             vsram: vec![0; VSRAM_LEN].into_boxed_slice().try_into().unwrap(),
             timing_mode,
@@ -327,6 +364,7 @@ impl Vdp {
             config,
             dma_tracker: DmaTracker::new(),
             fifo_tracker: FifoTracker::new(),
+            event_log: VdpEventLog::default(),
         }
     }
 
@@ -360,6 +398,10 @@ impl Vdp {
 
                     let register_number = ((value >> 8) & 0x1F) as u8;
                     self.registers.write_internal_register(register_number, value as u8);
+                    self.log_event(VdpEventKind::RegisterWrite {
+                        register: register_number,
+                        value: value as u8,
+                    });
 
                     if self.registers.hv_counter_stopped && self.state.latched_hv_counter.is_none()
                     {
@@ -503,6 +545,13 @@ impl Vdp {
         let data = match data_port_location {
             DataPortLocation::Vram => {
                 match self.registers.vram_size {
+                    VramSizeKb::OneTwentyEight if self.expanded_vram_active() => {
+                        // With 128KB of VRAM installed, reads work like 64KB mode except that A16
+                        // selects the bank
+                        let address = (self.state.data_address & 0xFFFE) as usize;
+                        let vram = self.vram_bank(self.state.data_address.bit(16));
+                        u16::from_be_bytes([vram[address], vram[address + 1]])
+                    }
                     VramSizeKb::SixtyFour => {
                         // VRAM reads/writes ignore A0
                         let address = (self.state.data_address & 0xFFFE) as usize;
@@ -690,7 +739,11 @@ impl Vdp {
         match self.registers.horizontal_display_size {
             HorizontalDisplaySize::ThirtyTwoCell => {
                 let h = (scanline_mclk / 20) as u8;
-                if h <= 0x93 { h } else { h + (0xE9 - 0x94) }
+                if h <= 0x93 {
+                    h
+                } else {
+                    h + (0xE9 - 0x94)
+                }
             }
             HorizontalDisplaySize::FortyCell => {
                 let pixel = scanline_mclk_to_pixel_h40(scanline_mclk);
@@ -766,6 +819,7 @@ impl Vdp {
 
         if let Some(active_dma) = self.state.pending_dma {
             // TODO accurate DMA timing
+            self.log_dma_start(active_dma);
             self.run_dma(memory, active_dma);
         }
 
@@ -812,6 +866,9 @@ impl Vdp {
 
                     log::trace!("Generating H interrupt (scanline {})", self.state.scanline);
                     self.state.h_interrupt_pending = true;
+                    self.log_event(VdpEventKind::HInterrupt {
+                        enabled: self.registers.h_interrupt_enabled,
+                    });
                 } else {
                     self.state.h_interrupt_counter -= 1;
                 }
@@ -845,6 +902,9 @@ impl Vdp {
         {
             log::trace!("Generating V interrupt");
             self.state.v_interrupt_pending = true;
+            self.log_event(VdpEventKind::VInterrupt {
+                enabled: self.registers.v_interrupt_enabled,
+            });
         }
 
         // Check if the VDP has advanced to a new scanline
@@ -863,6 +923,7 @@ impl Vdp {
             if self.state.scanline == scanlines_per_frame {
                 self.state.scanline = 0;
                 self.state.frame_count += 1;
+                self.event_log.end_frame(scanlines_per_frame);
                 self.state.v_border_forgotten = false;
 
                 // Top border length needs to be saved at start-of-frame in case there is a mid-frame swap between V28
@@ -878,6 +939,9 @@ impl Vdp {
             {
                 log::trace!("Generating V interrupt");
                 self.state.v_interrupt_pending = true;
+                self.log_event(VdpEventKind::VInterrupt {
+                    enabled: self.registers.v_interrupt_enabled,
+                });
             }
 
             let last_scanline_of_frame =
@@ -935,6 +999,10 @@ impl Vdp {
         let [msb, lsb] = value.to_be_bytes();
 
         match self.registers.vram_size {
+            VramSizeKb::OneTwentyEight if self.expanded_vram_active() => {
+                self.write_expanded_vram_byte(address, msb);
+                self.write_expanded_vram_byte(address ^ 0x1, lsb);
+            }
             VramSizeKb::SixtyFour => {
                 let vram_addr = address & 0xFFFF;
                 self.vram[vram_addr as usize] = msb;
@@ -958,6 +1026,39 @@ impl Vdp {
         }
     }
 
+    // True if 128KB of VRAM is installed and the VDP is in 128KB mode
+    fn expanded_vram_active(&self) -> bool {
+        self.expanded_vram.is_some() && self.registers.vram_size == VramSizeKb::OneTwentyEight
+    }
+
+    // Write a byte to VRAM when 128KB of VRAM is installed and the VDP is in 128KB mode; A16
+    // selects between the lower and upper 64KB
+    fn write_expanded_vram_byte(&mut self, address: u32, value: u8) {
+        let upper = address.bit(16);
+        let vram_addr = (address & 0xFFFF) as u16;
+        self.vram_bank_mut(upper)[vram_addr as usize] = value;
+
+        if upper == self.registers.is_in_upper_vram(VramTable::SpriteAttributeTable) {
+            self.maybe_update_sprite_cache(vram_addr, value);
+        }
+    }
+
+    fn vram_bank(&self, upper: bool) -> &Vram {
+        vram_bank(&self.vram, self.expanded_vram.as_deref(), upper)
+    }
+
+    fn vram_bank_mut(&mut self, upper: bool) -> &mut Vram {
+        match &mut self.expanded_vram {
+            Some(expanded_vram) if upper => expanded_vram,
+            _ => &mut self.vram,
+        }
+    }
+
+    // VRAM bank that the given table is read from during rendering
+    fn table_vram(&self, registers: &Registers, table: VramTable) -> &Vram {
+        self.vram_bank(registers.is_in_upper_vram(table))
+    }
+
     fn write_cram_word(&mut self, address: u32, value: u16) {
         if !address.bit(0) {
             self.cram[((address & 0x7F) >> 1) as usize] = value;
@@ -1003,9 +1104,14 @@ impl Vdp {
             && self.state.scanline < self.timing_mode.scanlines_per_frame() - 1
     }
 
+    /// Level 2 external interrupts are raised by the I/O ports; the VDP only gates them.
+    #[must_use]
+    pub fn external_interrupt_enabled(&self) -> bool {
+        self.registers.external_interrupt_enabled
+    }
+
     #[must_use]
     pub fn m68k_interrupt_level(&self) -> u8 {
-        // TODO external interrupts at level 2
         if self.state.v_interrupt_pending && self.registers.v_interrupt_enabled {
             6
         } else if self.state.h_interrupt_pending && self.registers.h_interrupt_enabled {
@@ -1066,7 +1172,10 @@ impl Vdp {
 
     #[must_use]
     pub fn screen_width(&self) -> u32 {
-        let h_display_size = self.registers.horizontal_display_size;
+        self.screen_width_for(self.registers.horizontal_display_size)
+    }
+
+    fn screen_width_for(&self, h_display_size: HorizontalDisplaySize) -> u32 {
         let active_display_pixels: u32 = h_display_size.active_display_pixels().into();
 
         if self.config.render_horizontal_border {
@@ -1074,32 +1183,88 @@ impl Vdp {
                 + active_display_pixels
                 + u32::from(RIGHT_BORDER)
         } else {
-            active_display_pixels
+            active_display_pixels + 2 * u32::from(self.widescreen_pixels(h_display_size))
         }
     }
 
-    #[must_use]
-    pub fn screen_height(&self) -> u32 {
-        let screen_height: u32 = if self.config.render_vertical_border {
-            self.timing_mode.rendered_lines_per_frame().into()
+    // Number of pixels rendered on each side of active display in widescreen mode
+    fn widescreen_pixels(&self, h_display_size: HorizontalDisplaySize) -> u16 {
+        if self.config.widescreen
+            && !self.config.render_horizontal_border
+            && h_display_size == HorizontalDisplaySize::FortyCell
+        {
+            WIDESCREEN_EXTRA_PIXELS
         } else {
-            self.registers.vertical_display_size.active_scanlines().into()
-        };
+            0
+        }
+    }
 
+    #[must_use]
+    pub fn screen_height(&self) -> u32 {
+        let screen_height = self.progressive_screen_height();
         match self.registers.interlacing_mode {
             InterlacingMode::Progressive | InterlacingMode::Interlaced => screen_height,
             InterlacingMode::InterlacedDouble => 2 * screen_height,
         }
     }
 
+    fn progressive_screen_height(&self) -> u32 {
+        if self.config.render_vertical_border {
+            self.timing_mode.rendered_lines_per_frame().into()
+        } else {
+            self.registers.vertical_display_size.visible_scanlines(self.timing_mode).into()
+        }
+    }
+
     #[must_use]
     pub fn config(&self) -> VdpConfig {
         self.config
     }
 
     pub fn reload_config(&mut self, config: VdpConfig) {
+        if config.expanded_vram != self.expanded_vram.is_some() {
+            self.expanded_vram = config.expanded_vram.then(new_vram);
+        }
+
         self.config = config;
     }
+
+    /// Set the tile replacements to substitute at draw time; see [`textures`].
+    pub fn set_tile_replacements(&mut self, replacements: Option<TileReplacements>) {
+        self.tile_textures.set_replacements(replacements);
+    }
+
+    /// Enable or disable recording tiles the first time they are drawn; see [`textures`].
+    pub fn set_tile_dump_enabled(&mut self, dump_enabled: bool) {
+        self.tile_textures.set_dump_enabled(dump_enabled);
+    }
+
+    /// Take all tiles that have been drawn for the first time since the last call.
+    #[must_use]
+    pub fn take_dumped_tiles(&mut self) -> Vec<DumpedTile> {
+        self.tile_textures.take_dumped_tiles()
+    }
+
+    /// Carry over tile texture state, which is not persisted in save states.
+    pub fn take_tile_textures_from(&mut self, other: &mut Self) {
+        self.tile_textures = mem::take(&mut other.tile_textures);
+    }
+}
+
+// Takes the VRAM fields instead of &Vdp so that callers can hold borrows of other fields
+fn vram_bank<'vram>(
+    vram: &'vram Vram,
+    expanded_vram: Option<&'vram Vram>,
+    upper: bool,
+) -> &'vram Vram {
+    match expanded_vram {
+        Some(expanded_vram) if upper => expanded_vram,
+        _ => vram,
+    }
+}
+
+fn new_vram() -> Box<Vram> {
+    vec![0; VRAM_LEN].into_boxed_slice().try_into().unwrap()
 }
 
 fn convert_128kb_vram_address(address: u32) -> u32 {
@@ -1163,6 +1328,9 @@ mod tests {
                 emulate_non_linear_dac: false,
                 render_vertical_border: false,
                 render_horizontal_border: false,
+                widescreen: false,
+                hi_res_output: false,
+                expanded_vram: false,
             },
         )
     }
@@ -1212,4 +1380,82 @@ mod tests {
         assert_eq!(vdp.h_counter(MCLK_CYCLES_PER_SCANLINE - 16), 0xFF);
         assert_eq!(vdp.h_counter(MCLK_CYCLES_PER_SCANLINE - 1), 0xFF);
     }
+
+    #[test]
+    fn event_log() {
+        let mut vdp = new_vdp();
+
+        // Disabled by default
+        vdp.write_control(0x8ADF);
+        vdp.event_log.end_frame(NTSC_SCANLINES_PER_FRAME);
+        assert!(vdp.event_log().last_frame().is_empty());
+
+        vdp.set_event_logging(true);
+        vdp.state.scanline = 100;
+        vdp.state.scanline_mclk_cycles = 800;
+        vdp.write_control(0x8ADF);
+        assert!(vdp.event_log().last_frame().is_empty());
+
+        vdp.event_log.end_frame(NTSC_SCANLINES_PER_FRAME);
+        assert_eq!(
+            vdp.event_log().last_frame(),
+            &[VdpEvent {
+                scanline: 100,
+                pixel: 80,
+                kind: VdpEventKind::RegisterWrite { register: 10, value: 0xDF },
+            }]
+        );
+        assert_eq!(vdp.event_log().last_frame_scanlines(), NTSC_SCANLINES_PER_FRAME);
+
+        vdp.event_log.end_frame(NTSC_SCANLINES_PER_FRAME);
+        assert!(vdp.event_log().last_frame().is_empty());
+    }
+
+    #[test]
+    fn vram_export_import_round_trip() {
+        let mut vdp = new_vdp();
+
+        // Palette 1 with 16 distinct colors
+        for color_id in 0..16_u16 {
+            vdp.cram[16 + color_id as usize] = ((color_id & 0x07) << 1) | ((color_id >> 3) << 9);
+        }
+        for (i, byte) in vdp.vram.iter_mut().enumerate() {
+            *byte = (i as u8).wrapping_mul(37);
+        }
+        let expected_vram = vdp.vram.clone();
+
+        let mut pixels = vec![Color::default(); 2 * VRAM_LEN];
+        vdp.copy_vram(&mut pixels, 1, 64);
+
+        vdp.vram.fill(0);
+        vdp.import_vram(&pixels, 1, 64);
+        assert_eq!(vdp.vram, expected_vram);
+    }
+
+    #[test]
+    fn expanded_vram_bank_select() {
+        let config = VdpConfig { expanded_vram: true, ..new_vdp().config() };
+        let mut vdp = Vdp::new(TimingMode::Ntsc, config);
+
+        // Enable 128KB mode (and Genesis mode)
+        vdp.write_control(0x8184);
+
+        // VRAM write to $10004
+        vdp.write_control(0x4004);
+        vdp.write_control(0x0004);
+        vdp.write_data(0x1234);
+
+        assert_eq!(&vdp.expanded_vram.as_ref().unwrap()[4..6], &[0x12, 0x34]);
+        assert_eq!(&vdp.vram[4..6], &[0x00, 0x00]);
+
+        // VRAM read from $10004
+        vdp.write_control(0x0004);
+        vdp.write_control(0x0004);
+        assert_eq!(vdp.read_data(), 0x1234);
+
+        // Reg #14 bit 0 moves scroll A patterns to the upper bank
+        vdp.write_control(0x8E01);
+        assert!(vdp.registers.is_in_upper_vram(VramTable::ScrollAPatterns));
+        assert!(!vdp.registers.is_in_upper_vram(VramTable::ScrollBPatterns));
+    }
 }
@@ -67,6 +67,16 @@ pub fn gen_to_rgb(
     Color::rgb(colors[r as usize], colors[g as usize], colors[b as usize])
 }
 
+// Approximates the shadow/highlight tables above for colors that did not come from CRAM
+pub fn apply_modifier(color: Color, modifier: ColorModifier) -> Color {
+    let apply = |c: u8| match modifier {
+        ColorModifier::None => c,
+        ColorModifier::Shadow => c / 2,
+        ColorModifier::Highlight => 128 + c / 2,
+    };
+    Color::rgb(apply(color.r), apply(color.g), apply(color.b))
+}
+
 pub fn resolve_color(cram: &Cram, palette: u8, color_id: u8) -> u16 {
     cram[((palette << 4) | color_id) as usize]
 }
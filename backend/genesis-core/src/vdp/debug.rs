@@ -1,10 +1,56 @@
 use crate::vdp;
 use crate::vdp::{colors, render, ColorModifier, Vdp};
 
+use crate::vdp::registers::DmaMode;
 use crate::vdp::render::PatternGeneratorArgs;
+use jgenesis_common::debug::graphics;
 use jgenesis_common::frontend::Color;
+use std::array;
+
+/// Read-only snapshot of VDP state, for use by debuggers and other external tools.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VdpDebugState {
+    /// Last value written to each of the 24 VDP registers.
+    pub registers: [u8; 24],
+    pub scanline: u16,
+    pub hv_counter: u16,
+    pub frame_count: u64,
+    pub data_address: u32,
+    pub code: u8,
+    pub v_interrupt_pending: bool,
+    pub h_interrupt_pending: bool,
+    pub dma: VdpDmaStatus,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VdpDmaStatus {
+    pub in_progress: bool,
+    pub mode: DmaMode,
+    pub source_address: u32,
+    pub length: u32,
+}
 
 impl Vdp {
+    #[must_use]
+    pub fn debug_state(&self) -> VdpDebugState {
+        VdpDebugState {
+            registers: self.registers.raw_values,
+            scanline: self.state.scanline,
+            hv_counter: self.hv_counter(),
+            frame_count: self.state.frame_count,
+            data_address: self.state.data_address,
+            code: self.state.code,
+            v_interrupt_pending: self.state.v_interrupt_pending,
+            h_interrupt_pending: self.state.h_interrupt_pending,
+            dma: VdpDmaStatus {
+                in_progress: self.state.pending_dma.is_some() || self.dma_tracker.is_in_progress(),
+                mode: self.registers.dma_mode,
+                source_address: self.registers.dma_source_address,
+                length: self.registers.dma_length(),
+            },
+        }
+    }
+
     pub fn copy_cram(&self, out: &mut [Color]) {
         for (out_color, &cram_color) in out.iter_mut().zip(self.cram.as_ref()) {
             *out_color = parse_gen_color(cram_color);
@@ -36,6 +82,43 @@ impl Vdp {
             }
         }
     }
+
+    /// Inverse of [`Self::copy_vram`]: convert an image of VRAM patterns back into pattern data,
+    /// mapping each pixel to the closest color in the given palette.
+    ///
+    /// Pixels that match the color currently displayed at their position keep their current color
+    /// ID, so that patterns using duplicate palette colors are unchanged by an export/import round
+    /// trip. Pixels past the end of `pixels` are left unchanged.
+    pub fn import_vram(&mut self, pixels: &[Color], palette: u8, row_len: usize) {
+        let palette_colors: [Color; 16] = array::from_fn(|color_id| {
+            parse_gen_color(colors::resolve_color(&self.cram, palette, color_id as u8))
+        });
+
+        for pattern in 0..vdp::VRAM_LEN / 32 {
+            let base_idx = pattern / row_len * row_len * 64 + (pattern % row_len) * 8;
+
+            for row in 0..8 {
+                for col in 0..8 {
+                    let Some(&pixel) = pixels.get(base_idx + row * row_len * 8 + col) else {
+                        return;
+                    };
+
+                    // Same layout as render::read_pattern_generator() with no flipping
+                    let addr = 32 * pattern + 4 * row + col / 2;
+                    let shift = 4 - 4 * (col & 1);
+                    let current_color_id = (self.vram[addr] >> shift) & 0x0F;
+                    if palette_colors[current_color_id as usize] == pixel {
+                        continue;
+                    }
+
+                    let color_id = graphics::closest_color_index(&palette_colors, pixel) as u8;
+                    let value = (self.vram[addr] & !(0x0F << shift)) | (color_id << shift);
+                    self.vram[addr] = value;
+                    self.maybe_update_sprite_cache(addr as u16, value);
+                }
+            }
+        }
+    }
 }
 
 fn parse_gen_color(gen_color: u16) -> Color {
@@ -4,7 +4,7 @@ use crate::vdp::{
     ActiveDma, DataPortLocation, PendingWrite, Vdp, MCLK_CYCLES_PER_SCANLINE, VSRAM_LEN,
 };
 use bincode::{Decode, Encode};
-use jgenesis_common::num::U16Ext;
+use jgenesis_common::num::{GetBit, U16Ext};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LineType {
@@ -178,9 +178,13 @@ impl Vdp {
 
                 let [msb, _] = fill_data.to_be_bytes();
                 for _ in 0..self.registers.dma_length() {
-                    let vram_addr = (self.state.data_address ^ 0x1) & 0xFFFF;
-                    self.vram[vram_addr as usize] = msb;
-                    self.maybe_update_sprite_cache(vram_addr as u16, msb);
+                    if self.expanded_vram_active() {
+                        self.write_expanded_vram_byte(self.state.data_address ^ 0x1, msb);
+                    } else {
+                        let vram_addr = (self.state.data_address ^ 0x1) & 0xFFFF;
+                        self.vram[vram_addr as usize] = msb;
+                        self.maybe_update_sprite_cache(vram_addr as u16, msb);
+                    }
 
                     self.increment_data_address();
                 }
@@ -203,10 +207,17 @@ impl Vdp {
                 // VRAM copy DMA treats the source address as A15-A0 instead of A23-A1
                 let mut source_addr = (self.registers.dma_source_address >> 1) as u16;
                 for _ in 0..self.registers.dma_length() {
-                    let dest_addr = self.state.data_address & 0xFFFF;
-                    let byte = self.vram[source_addr as usize];
-                    self.vram[dest_addr as usize] = byte;
-                    self.maybe_update_sprite_cache(dest_addr as u16, byte);
+                    if self.expanded_vram_active() {
+                        // The source address has no A16, so copies stay within the destination bank
+                        let upper = self.state.data_address.bit(16);
+                        let byte = self.vram_bank(upper)[source_addr as usize];
+                        self.write_expanded_vram_byte(self.state.data_address, byte);
+                    } else {
+                        let dest_addr = self.state.data_address & 0xFFFF;
+                        let byte = self.vram[source_addr as usize];
+                        self.vram[dest_addr as usize] = byte;
+                        self.maybe_update_sprite_cache(dest_addr as u16, byte);
+                    }
 
                     source_addr = source_addr.wrapping_add(1);
                     self.increment_data_address();
@@ -0,0 +1,160 @@
+//! Per-frame log of VDP events, for diagnosing raster effects
+//!
+//! Each event is tagged with the scanline and pixel at which it occurred. The log for a frame is
+//! finalized when the VDP wraps around from the last scanline back to scanline 0.
+
+use crate::vdp;
+use crate::vdp::registers::DmaMode;
+use crate::vdp::{ActiveDma, DataPortLocation, Vdp};
+use jgenesis_proc_macros::{FakeDecode, FakeEncode};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VdpMemoryTarget {
+    Vram,
+    Cram,
+    Vsram,
+}
+
+impl From<DataPortLocation> for VdpMemoryTarget {
+    fn from(value: DataPortLocation) -> Self {
+        match value {
+            DataPortLocation::Vram => Self::Vram,
+            DataPortLocation::Cram => Self::Cram,
+            DataPortLocation::Vsram => Self::Vsram,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VdpEventKind {
+    RegisterWrite {
+        register: u8,
+        value: u8,
+    },
+    DmaStart {
+        mode: DmaMode,
+        target: VdpMemoryTarget,
+        source_address: u32,
+        destination_address: u32,
+        length: u32,
+    },
+    /// `enabled` is false if the interrupt was generated while masked in the VDP registers
+    HInterrupt {
+        enabled: bool,
+    },
+    VInterrupt {
+        enabled: bool,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VdpEvent {
+    pub scanline: u16,
+    pub pixel: u16,
+    pub kind: VdpEventKind,
+}
+
+/// Logging is disabled by default so that there is no overhead unless the log is in use. This is
+/// never persisted in save states.
+#[derive(Debug, Clone, Default, FakeEncode, FakeDecode)]
+pub struct VdpEventLog {
+    enabled: bool,
+    current_frame: Vec<VdpEvent>,
+    last_frame: Vec<VdpEvent>,
+    last_frame_scanlines: u16,
+}
+
+impl VdpEventLog {
+    #[inline]
+    #[must_use]
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub(super) fn set_enabled(&mut self, enabled: bool) {
+        if !enabled {
+            self.current_frame = Vec::new();
+            self.last_frame = Vec::new();
+        }
+
+        self.enabled = enabled;
+    }
+
+    #[inline]
+    pub(super) fn push(&mut self, scanline: u16, pixel: u16, kind: VdpEventKind) {
+        if self.enabled {
+            self.current_frame.push(VdpEvent { scanline, pixel, kind });
+        }
+    }
+
+    pub(super) fn end_frame(&mut self, scanlines: u16) {
+        if !self.enabled {
+            return;
+        }
+
+        std::mem::swap(&mut self.current_frame, &mut self.last_frame);
+        self.current_frame.clear();
+        self.last_frame_scanlines = scanlines;
+    }
+
+    /// Events from the most recently completed frame, in the order they occurred.
+    #[must_use]
+    pub fn last_frame(&self) -> &[VdpEvent] {
+        &self.last_frame
+    }
+
+    /// Total number of scanlines in the most recently completed frame, including VBlank.
+    #[must_use]
+    pub fn last_frame_scanlines(&self) -> u16 {
+        self.last_frame_scanlines
+    }
+}
+
+impl Vdp {
+    pub fn set_event_logging(&mut self, enabled: bool) {
+        if enabled != self.event_log.is_enabled() {
+            self.event_log.set_enabled(enabled);
+        }
+    }
+
+    #[must_use]
+    pub fn event_log(&self) -> &VdpEventLog {
+        &self.event_log
+    }
+
+    #[inline]
+    pub(super) fn log_event(&mut self, kind: VdpEventKind) {
+        if !self.event_log.is_enabled() {
+            return;
+        }
+
+        let pixel = vdp::scanline_mclk_to_pixel(
+            self.state.scanline_mclk_cycles,
+            self.registers.horizontal_display_size,
+        );
+        self.event_log.push(self.state.scanline, pixel, kind);
+    }
+
+    pub(super) fn log_dma_start(&mut self, active_dma: ActiveDma) {
+        let (mode, target, source_address) = match active_dma {
+            ActiveDma::MemoryToVram => (
+                DmaMode::MemoryToVram,
+                self.state.data_port_location.into(),
+                self.registers.dma_source_address,
+            ),
+            ActiveDma::VramFill(_) => (DmaMode::VramFill, VdpMemoryTarget::Vram, 0),
+            // VRAM copy DMA treats the source address as A15-A0 instead of A23-A1
+            ActiveDma::VramCopy => {
+                (DmaMode::VramCopy, VdpMemoryTarget::Vram, self.registers.dma_source_address >> 1)
+            }
+        };
+
+        self.log_event(VdpEventKind::DmaStart {
+            mode,
+            target,
+            source_address,
+            destination_address: self.state.data_address,
+            length: self.registers.dma_length(),
+        });
+    }
+}
@@ -0,0 +1,150 @@
+//! Optional 2x resolution output, an enhancement with no equivalent in actual hardware
+//!
+//! The accurate renderer is unchanged: it writes each line to the normal frame buffer at that
+//! line's own width. When hi-res output is enabled, every line is additionally copied into a
+//! separate frame buffer that is always double the width and height of an H40 frame. H40 pixels
+//! become 2x2 blocks, H32 pixels are stretched by 2.5x to cover the same area of the screen that
+//! they would on a CRT, and progressive lines are doubled vertically while interlaced double
+//! resolution lines map 1:1.
+//!
+//! Since the output size never changes, games that switch between H32 and H40 (including mid-frame)
+//! or between progressive and interlaced double resolution display without the frame size changing.
+
+use crate::vdp::registers::{HorizontalDisplaySize, InterlacingMode};
+use crate::vdp::{Vdp, FRAME_BUFFER_LEN};
+use jgenesis_common::frontend::{Color, FrameSize};
+use jgenesis_proc_macros::{FakeDecode, FakeEncode};
+
+// The normal frame buffer already has room for double height lines, so only the width needs doubling
+const HI_RES_FRAME_BUFFER_LEN: usize = 2 * FRAME_BUFFER_LEN;
+
+// Empty until hi-res output is enabled, so that the extra memory is only allocated when used
+#[derive(Debug, Clone, Default, FakeEncode, FakeDecode)]
+pub(super) struct HiResFrameBuffer(Vec<Color>);
+
+impl Vdp {
+    // Copy a line that was just rendered to the hi-res frame buffer. Must be called while the
+    // registers that determined the line's width are still in effect
+    pub(super) fn update_hi_res_row(&mut self, row: u32) {
+        if !self.config.hi_res_output {
+            return;
+        }
+
+        if self.hi_res_frame_buffer.0.len() < HI_RES_FRAME_BUFFER_LEN {
+            self.hi_res_frame_buffer.0.resize(HI_RES_FRAME_BUFFER_LEN, Color::BLACK);
+        }
+
+        let h_display_size = self.registers.horizontal_display_size;
+        let src_width = self.screen_width() as usize;
+        let out_width = self.hi_res_screen_width() as usize;
+
+        let (out_row, line_doubled) = match self.latched_registers.interlacing_mode {
+            InterlacingMode::Progressive | InterlacingMode::Interlaced => (2 * row as usize, true),
+            InterlacingMode::InterlacedDouble => (row as usize, false),
+        };
+
+        let h32_offset =
+            i32::from(self.active_display_offset(HorizontalDisplaySize::ThirtyTwoCell));
+        let h40_offset = i32::from(self.active_display_offset(HorizontalDisplaySize::FortyCell));
+
+        let src_start = row as usize * src_width;
+        let src = &self.frame_buffer[src_start..src_start + src_width];
+
+        let out_start = out_row * out_width;
+        let out = &mut self.hi_res_frame_buffer.0[out_start..out_start + out_width];
+
+        match h_display_size {
+            HorizontalDisplaySize::FortyCell => {
+                for (out_pair, &color) in out.chunks_exact_mut(2).zip(src) {
+                    out_pair.fill(color);
+                }
+            }
+            HorizontalDisplaySize::ThirtyTwoCell => {
+                // H32 pixels are 5/4 the width of H40 pixels, so each H32 pixel covers 2.5 output
+                // columns. Active display starts at the same output column as in H40 lines
+                for (out_col, out_color) in out.iter_mut().enumerate() {
+                    let src_col =
+                        (2 * (out_col as i32 - 2 * h40_offset)).div_euclid(5) + h32_offset;
+                    *out_color = usize::try_from(src_col)
+                        .ok()
+                        .and_then(|src_col| src.get(src_col).copied())
+                        .unwrap_or(Color::BLACK);
+                }
+            }
+        }
+
+        if line_doubled {
+            self.hi_res_frame_buffer
+                .0
+                .copy_within(out_start..out_start + out_width, out_start + out_width);
+        }
+    }
+
+    // Offset of the first active display pixel within a frame buffer row
+    fn active_display_offset(&self, h_display_size: HorizontalDisplaySize) -> u16 {
+        if self.config.render_horizontal_border {
+            h_display_size.left_border()
+        } else {
+            self.widescreen_pixels(h_display_size)
+        }
+    }
+
+    fn hi_res_screen_width(&self) -> u32 {
+        2 * self.screen_width_for(HorizontalDisplaySize::FortyCell)
+    }
+
+    /// The hi-res frame buffer; only populated while hi-res output is enabled.
+    #[must_use]
+    pub fn hi_res_frame_buffer(&self) -> &[Color] {
+        &self.hi_res_frame_buffer.0
+    }
+
+    /// Size of the hi-res frame buffer, which is the same regardless of horizontal display size and
+    /// interlacing mode.
+    #[must_use]
+    pub fn hi_res_frame_size(&self) -> FrameSize {
+        FrameSize {
+            width: self.hi_res_screen_width(),
+            height: 2 * self.progressive_screen_height(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vdp::VdpConfig;
+    use jgenesis_common::frontend::TimingMode;
+
+    #[test]
+    fn h32_lines_stretch_to_h40_width() {
+        let mut vdp = Vdp::new(
+            TimingMode::Ntsc,
+            VdpConfig {
+                enforce_sprite_limits: true,
+                emulate_non_linear_dac: false,
+                render_vertical_border: false,
+                render_horizontal_border: false,
+                widescreen: false,
+                hi_res_output: true,
+                expanded_vram: false,
+            },
+        );
+        vdp.registers.horizontal_display_size = HorizontalDisplaySize::ThirtyTwoCell;
+
+        for col in 0..256 {
+            vdp.frame_buffer[col] = Color::rgb(col as u8, 0, 0);
+        }
+        vdp.update_hi_res_row(0);
+
+        let frame_size = vdp.hi_res_frame_size();
+        assert_eq!(frame_size, FrameSize { width: 640, height: 448 });
+
+        let reds: Vec<_> = vdp.hi_res_frame_buffer()[..8].iter().map(|color| color.r).collect();
+        assert_eq!(reds, vec![0, 0, 0, 1, 1, 2, 2, 2]);
+        assert_eq!(vdp.hi_res_frame_buffer()[639].r, 255);
+
+        // Progressive lines are doubled vertically
+        assert_eq!(vdp.hi_res_frame_buffer()[640..1280], vdp.hi_res_frame_buffer()[..640]);
+    }
+}
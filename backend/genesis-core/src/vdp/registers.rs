@@ -119,6 +119,15 @@ impl VerticalDisplaySize {
         }
     }
 
+    // V30 mode is not a valid display mode on NTSC hardware (the picture rolls), so only the
+    // first 224 lines are ever displayed
+    pub const fn visible_scanlines(self, timing_mode: TimingMode) -> u16 {
+        match timing_mode {
+            TimingMode::Ntsc => Self::TwentyEightCell.active_scanlines(),
+            TimingMode::Pal => self.active_scanlines(),
+        }
+    }
+
     pub const fn top_border(self, timing_mode: TimingMode) -> u16 {
         match (self, timing_mode) {
             (_, TimingMode::Ntsc) => NTSC_TOP_BORDER,
@@ -271,6 +280,29 @@ impl Display for VramSizeKb {
     }
 }
 
+/// VRAM tables that can be relocated to the upper 64KB of VRAM in 128KB mode.
+///
+/// Each table's base address register has one extra bit for VRAM address bit 16. The bit has no
+/// effect on consoles with 64KB of VRAM, but development hardware and the Tera Drive have 128KB of
+/// VRAM installed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+pub enum VramTable {
+    ScrollANameTable,
+    WindowNameTable,
+    ScrollBNameTable,
+    SpriteAttributeTable,
+    SpritePatterns,
+    HScrollTable,
+    ScrollAPatterns,
+    ScrollBPatterns,
+}
+
+impl VramTable {
+    fn mask(self) -> u8 {
+        1 << (self as u8)
+    }
+}
+
 #[derive(Debug, Clone, Encode, Decode)]
 pub struct Registers {
     // Register #0
@@ -297,7 +329,7 @@ pub struct Registers {
     // Register #10
     pub h_interrupt_interval: u16,
     // Register #11
-    // TODO external interrupts enabled
+    pub external_interrupt_enabled: bool,
     pub vertical_scroll_mode: VerticalScrollMode,
     pub horizontal_scroll_mode: HorizontalScrollMode,
     // Register #12
@@ -306,6 +338,8 @@ pub struct Registers {
     pub interlacing_mode: InterlacingMode,
     // Register #13
     pub h_scroll_table_base_addr: u16,
+    // Registers #2-#6, #13, & #14: Bit 16 of each table's base address, one bit per VramTable
+    pub upper_vram_tables: u8,
     // Register #15
     pub data_port_auto_increment: u16,
     // Register #16
@@ -322,6 +356,8 @@ pub struct Registers {
     // Registers #21, #22, & #23
     pub dma_source_address: u32,
     pub dma_mode: DmaMode,
+    // Last value written to each register, only used for debugging
+    pub raw_values: [u8; 24],
 }
 
 impl Registers {
@@ -342,12 +378,14 @@ impl Registers {
             background_palette: 0,
             background_color_id: 0,
             h_interrupt_interval: 0,
+            external_interrupt_enabled: false,
             vertical_scroll_mode: VerticalScrollMode::default(),
             horizontal_scroll_mode: HorizontalScrollMode::default(),
             horizontal_display_size: HorizontalDisplaySize::default(),
             shadow_highlight_flag: false,
             interlacing_mode: InterlacingMode::default(),
             h_scroll_table_base_addr: 0,
+            upper_vram_tables: 0,
             data_port_auto_increment: 0,
             vertical_scroll_size: ScrollSize::default(),
             horizontal_scroll_size: ScrollSize::default(),
@@ -358,6 +396,7 @@ impl Registers {
             dma_length: 0,
             dma_source_address: 0,
             dma_mode: DmaMode::default(),
+            raw_values: [0; 24],
         }
     }
 
@@ -371,6 +410,10 @@ impl Registers {
             return;
         }
 
+        if let Some(raw_value) = self.raw_values.get_mut(register as usize) {
+            *raw_value = value;
+        }
+
         match register {
             0 => {
                 // Register #0: Mode set register 1
@@ -411,6 +454,7 @@ impl Registers {
             2 => {
                 // Register #2: Scroll A name table base address (bits 15-13)
                 self.scroll_a_base_nt_addr = u16::from(value & 0x38) << 10;
+                self.set_upper_vram_table(VramTable::ScrollANameTable, value.bit(6));
 
                 log::trace!(
                     "  Scroll A base nametable address: {:04X}",
@@ -420,12 +464,14 @@ impl Registers {
             3 => {
                 // Register #3: Window name table base address (bits 15-11)
                 self.window_base_nt_addr = u16::from(value & 0x3E) << 10;
+                self.set_upper_vram_table(VramTable::WindowNameTable, value.bit(6));
 
                 log::trace!("  Window base nametable address: {:04X}", self.window_base_nt_addr);
             }
             4 => {
                 // Register #4: Scroll B name table base address (bits 15-13)
                 self.scroll_b_base_nt_addr = u16::from(value & 0x07) << 13;
+                self.set_upper_vram_table(VramTable::ScrollBNameTable, value.bit(3));
 
                 log::trace!(
                     "  Scroll B base nametable address: {:04X}",
@@ -435,12 +481,19 @@ impl Registers {
             5 => {
                 // Register #5: Sprite attribute table base address (bits 15-9)
                 self.sprite_attribute_table_base_addr = u16::from(value & 0x7F) << 9;
+                self.set_upper_vram_table(VramTable::SpriteAttributeTable, value.bit(7));
 
                 log::trace!(
                     "  Sprite attribute table base address: {:04X}",
                     self.sprite_attribute_table_base_addr
                 );
             }
+            6 => {
+                // Register #6: Sprite pattern generator base address (bit 16)
+                self.set_upper_vram_table(VramTable::SpritePatterns, value.bit(5));
+
+                log::trace!("  Sprite patterns in upper VRAM: {}", value.bit(5));
+            }
             7 => {
                 // Register #7: Background color
                 self.background_palette = (value >> 4) & 0x03;
@@ -457,7 +510,7 @@ impl Registers {
             }
             11 => {
                 // Register #11: Mode set register 3
-                // TODO external interrupt enable
+                self.external_interrupt_enabled = value.bit(3);
                 self.vertical_scroll_mode = if value.bit(2) {
                     VerticalScrollMode::TwoCell
                 } else {
@@ -471,6 +524,7 @@ impl Registers {
                     _ => unreachable!("value & 0x03 is always <= 0x03"),
                 };
 
+                log::trace!("  External interrupt enabled: {}", self.external_interrupt_enabled);
                 log::trace!("  Vertical scroll mode: {:?}", self.vertical_scroll_mode);
                 log::trace!("  Horizontal scroll mode: {:?}", self.horizontal_scroll_mode);
             }
@@ -496,9 +550,19 @@ impl Registers {
             13 => {
                 // Register #13: Horizontal scroll table base address (bits 15-10)
                 self.h_scroll_table_base_addr = u16::from(value & 0x3F) << 10;
+                self.set_upper_vram_table(VramTable::HScrollTable, value.bit(6));
 
                 log::trace!("  H scroll table base address: {:04X}", self.h_scroll_table_base_addr);
             }
+            14 => {
+                // Register #14: Scroll A/B pattern generator base address (bit 16)
+                // The window plane uses the scroll A bit
+                self.set_upper_vram_table(VramTable::ScrollAPatterns, value.bit(0));
+                self.set_upper_vram_table(VramTable::ScrollBPatterns, value.bit(4));
+
+                log::trace!("  Scroll A patterns in upper VRAM: {}", value.bit(0));
+                log::trace!("  Scroll B patterns in upper VRAM: {}", value.bit(4));
+            }
             15 => {
                 // Register #15: VRAM address auto increment
                 self.data_port_auto_increment = value.into();
@@ -601,6 +665,21 @@ impl Registers {
         }
     }
 
+    fn set_upper_vram_table(&mut self, table: VramTable, upper: bool) {
+        if upper {
+            self.upper_vram_tables |= table.mask();
+        } else {
+            self.upper_vram_tables &= !table.mask();
+        }
+    }
+
+    /// Whether the given table is read from the upper 64KB of VRAM. Bit 16 of the base address is
+    /// ignored unless the VDP is in 128KB mode.
+    #[must_use]
+    pub fn is_in_upper_vram(&self, table: VramTable) -> bool {
+        self.vram_size == VramSizeKb::OneTwentyEight && self.upper_vram_tables & table.mask() != 0
+    }
+
     pub fn masked_sprite_attribute_table_addr(&self) -> u16 {
         self.sprite_attribute_table_base_addr
             & self.horizontal_display_size.sprite_attribute_table_mask()
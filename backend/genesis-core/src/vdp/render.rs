@@ -1,11 +1,12 @@
 use crate::vdp::colors::ColorModifier;
 use crate::vdp::registers::{
     DebugRegister, HorizontalDisplaySize, HorizontalScrollMode, InterlacingMode, Plane, Registers,
-    ScrollSize, VerticalDisplaySize, VerticalScrollMode, RIGHT_BORDER,
+    ScrollSize, VerticalDisplaySize, VerticalScrollMode, VramTable, RIGHT_BORDER,
 };
 use crate::vdp::sprites::SpritePixel;
+use crate::vdp::textures::{self, TileImage, TileTextureCache};
 use crate::vdp::{colors, Cram, FrameBuffer, TimingModeExt, Vdp, Vram, Vsram};
-use jgenesis_common::frontend::TimingMode;
+use jgenesis_common::frontend::{Color, TimingMode};
 use jgenesis_common::num::GetBit;
 use std::cmp;
 
@@ -103,6 +104,9 @@ impl Vdp {
                     frame_buffer_row,
                     false,
                 );
+                if let Some(row) = frame_buffer_row {
+                    self.update_hi_res_row(row);
+                }
             }
             InterlacingMode::InterlacedDouble => {
                 self.do_render_scanline(
@@ -119,6 +123,10 @@ impl Vdp {
                     frame_buffer_row.map(|row| 2 * row + 1),
                     true,
                 );
+                if let Some(row) = frame_buffer_row {
+                    self.update_hi_res_row(2 * row);
+                    self.update_hi_res_row(2 * row + 1);
+                }
             }
         }
     }
@@ -183,6 +191,7 @@ impl Vdp {
                             self.state.last_h_scroll_a,
                             self.state.last_h_scroll_b,
                         );
+                        self.update_hi_res_row(right_border_row);
                     }
                 }
             }
@@ -249,6 +258,11 @@ impl Vdp {
 
         let cell_height = self.latched_registers.interlacing_mode.cell_height();
         let v_scroll_size = self.latched_registers.vertical_scroll_size;
+
+        let textures_active = self.tile_textures.is_active() && cell_height == 8;
+        let mut scroll_a_texture_cache = TileTextureCache::default();
+        let mut scroll_b_texture_cache = TileTextureCache::default();
+        let mut window_texture_cache = TileTextureCache::default();
         let h_scroll_size = self.latched_registers.horizontal_scroll_size;
 
         let (h_scroll_size_pixels, v_scroll_size_pixels) = match (h_scroll_size, v_scroll_size) {
@@ -269,7 +283,7 @@ impl Vdp {
             InterlacingMode::InterlacedDouble => raster_line.line / 2,
         };
         let (h_scroll_a, h_scroll_b) = read_h_scroll(
-            &self.vram,
+            self.table_vram(&self.latched_registers, VramTable::HScrollTable),
             self.latched_registers.h_scroll_table_base_addr,
             self.latched_registers.horizontal_scroll_mode,
             // Only the lowest 8 bits of raster line are used for H scroll lookups
@@ -289,6 +303,8 @@ impl Vdp {
         let active_display_pixels =
             self.latched_registers.horizontal_display_size.active_display_pixels();
         let active_display_cells = active_display_pixels / 8;
+        let widescreen_pixels =
+            self.widescreen_pixels(self.latched_registers.horizontal_display_size);
 
         let (start_col, end_col, pixel_offset) = if self.config.render_horizontal_border {
             let left_border: u32 =
@@ -299,8 +315,14 @@ impl Vdp {
 
             (start_col, end_col, left_border as i16)
         } else {
-            (starting_pixel.into(), active_display_pixels.into(), 0)
+            let start_col =
+                if starting_pixel == 0 { 0 } else { u32::from(starting_pixel + widescreen_pixels) };
+            let end_col = u32::from(active_display_pixels + 2 * widescreen_pixels);
+
+            (start_col, end_col, widescreen_pixels as i16)
         };
+        let display_area =
+            -(widescreen_pixels as i16)..(active_display_pixels + widescreen_pixels) as i16;
 
         for frame_buffer_col in start_col..end_col {
             let pixel = frame_buffer_col as i16 - pixel_offset;
@@ -337,7 +359,7 @@ impl Vdp {
 
             if scroll_a_v_cell != scroll_a_nt_row || scroll_a_h_cell != scroll_a_nt_col {
                 scroll_a_nt_word = read_name_table_word(
-                    &self.vram,
+                    self.table_vram(&self.latched_registers, VramTable::ScrollANameTable),
                     self.latched_registers.scroll_a_base_nt_addr,
                     h_scroll_size.into(),
                     scroll_a_v_cell,
@@ -349,7 +371,7 @@ impl Vdp {
 
             if scroll_b_v_cell != scroll_b_nt_row || scroll_b_h_cell != scroll_b_nt_col {
                 scroll_b_nt_word = read_name_table_word(
-                    &self.vram,
+                    self.table_vram(&self.latched_registers, VramTable::ScrollBNameTable),
                     self.latched_registers.scroll_b_base_nt_addr,
                     h_scroll_size.into(),
                     scroll_b_v_cell,
@@ -365,7 +387,7 @@ impl Vdp {
             }
 
             let scroll_a_color_id = read_pattern_generator(
-                &self.vram,
+                self.table_vram(&self.latched_registers, VramTable::ScrollAPatterns),
                 PatternGeneratorArgs {
                     vertical_flip: scroll_a_nt_word.vertical_flip,
                     horizontal_flip: scroll_a_nt_word.horizontal_flip,
@@ -376,7 +398,7 @@ impl Vdp {
                 },
             );
             let scroll_b_color_id = read_pattern_generator(
-                &self.vram,
+                self.table_vram(&self.latched_registers, VramTable::ScrollBPatterns),
                 PatternGeneratorArgs {
                     vertical_flip: scroll_b_nt_word.vertical_flip,
                     horizontal_flip: scroll_b_nt_word.horizontal_flip,
@@ -387,84 +409,167 @@ impl Vdp {
                 },
             );
 
-            let in_window = self.latched_registers.is_in_window(raster_line.line, pixel as u16);
-            let (window_priority, window_palette, window_color_id) = if in_window {
-                let window_v_cell = raster_line.line / cell_height;
-
-                let window_width_cells =
-                    self.latched_registers.horizontal_display_size.window_width_cells();
-                let window_pixel = (pixel as u16) & (window_width_cells * 8 - 1);
-                let window_h_cell = window_pixel / 8;
-
-                let window_nt_word = read_name_table_word(
-                    &self.vram,
-                    self.latched_registers.window_base_nt_addr,
-                    window_width_cells,
-                    window_v_cell,
-                    window_h_cell,
+            let (scroll_a_replacement, scroll_b_replacement) = if textures_active {
+                let scroll_a_texture = scroll_a_texture_cache.get(
+                    &self.tile_textures,
+                    self.table_vram(&self.latched_registers, VramTable::ScrollAPatterns),
+                    &self.cram,
+                    scroll_a_nt_word.pattern_generator,
+                    scroll_a_nt_word.palette,
                 );
-                let window_color_id = read_pattern_generator(
-                    &self.vram,
-                    PatternGeneratorArgs {
-                        vertical_flip: window_nt_word.vertical_flip,
-                        horizontal_flip: window_nt_word.horizontal_flip,
-                        pattern_generator: window_nt_word.pattern_generator,
-                        row: raster_line.line,
-                        col: window_pixel,
-                        cell_height,
-                    },
+                let scroll_b_texture = scroll_b_texture_cache.get(
+                    &self.tile_textures,
+                    self.table_vram(&self.latched_registers, VramTable::ScrollBPatterns),
+                    &self.cram,
+                    scroll_b_nt_word.pattern_generator,
+                    scroll_b_nt_word.palette,
                 );
-                (window_nt_word.priority, window_nt_word.palette, window_color_id)
+                (
+                    replacement_color(
+                        scroll_a_texture,
+                        scroll_a_nt_word,
+                        scroll_a_color_id,
+                        scrolled_scanline_a,
+                        scrolled_pixel_a,
+                    ),
+                    replacement_color(
+                        scroll_b_texture,
+                        scroll_b_nt_word,
+                        scroll_b_color_id,
+                        scrolled_scanline_b,
+                        scrolled_pixel_b,
+                    ),
+                )
             } else {
-                (false, 0, 0)
+                (None, None)
             };
 
+            // The window plane does not scroll, so it is never drawn outside of the normal display
+            // area in widescreen mode
+            let in_widescreen_area =
+                widescreen_pixels != 0 && !(0..active_display_pixels as i16).contains(&pixel);
+            let in_window = !in_widescreen_area
+                && self.latched_registers.is_in_window(raster_line.line, pixel as u16);
+            let (window_priority, window_palette, window_color_id, window_replacement) =
+                if in_window {
+                    let window_v_cell = raster_line.line / cell_height;
+
+                    let window_width_cells =
+                        self.latched_registers.horizontal_display_size.window_width_cells();
+                    let window_pixel = (pixel as u16) & (window_width_cells * 8 - 1);
+                    let window_h_cell = window_pixel / 8;
+
+                    let window_nt_word = read_name_table_word(
+                        self.table_vram(&self.latched_registers, VramTable::WindowNameTable),
+                        self.latched_registers.window_base_nt_addr,
+                        window_width_cells,
+                        window_v_cell,
+                        window_h_cell,
+                    );
+                    let window_color_id = read_pattern_generator(
+                        self.table_vram(&self.latched_registers, VramTable::ScrollAPatterns),
+                        PatternGeneratorArgs {
+                            vertical_flip: window_nt_word.vertical_flip,
+                            horizontal_flip: window_nt_word.horizontal_flip,
+                            pattern_generator: window_nt_word.pattern_generator,
+                            row: raster_line.line,
+                            col: window_pixel,
+                            cell_height,
+                        },
+                    );
+                    let window_replacement = if textures_active {
+                        let window_texture = window_texture_cache.get(
+                            &self.tile_textures,
+                            self.table_vram(&self.latched_registers, VramTable::ScrollAPatterns),
+                            &self.cram,
+                            window_nt_word.pattern_generator,
+                            window_nt_word.palette,
+                        );
+                        replacement_color(
+                            window_texture,
+                            window_nt_word,
+                            window_color_id,
+                            raster_line.line,
+                            window_pixel,
+                        )
+                    } else {
+                        None
+                    };
+
+                    (
+                        window_nt_word.priority,
+                        window_nt_word.palette,
+                        window_color_id,
+                        window_replacement,
+                    )
+                } else {
+                    (false, 0, 0, None)
+                };
+
             let SpritePixel {
                 palette: sprite_palette,
                 color_id: sprite_color_id,
                 priority: sprite_priority,
+                replacement: sprite_replacement,
             } = sprite_buffers
                 .pixels
-                .get(pixel as usize)
+                .get((pixel + widescreen_pixels as i16) as usize)
                 .copied()
                 .unwrap_or(SpritePixel::default());
 
-            let (scroll_a_priority, scroll_a_palette, scroll_a_color_id) = if in_window {
-                // Window replaces scroll A if this pixel is inside the window
-                (window_priority, window_palette, window_color_id)
-            } else {
-                (scroll_a_nt_word.priority, scroll_a_nt_word.palette, scroll_a_color_id)
-            };
+            let (scroll_a_priority, scroll_a_palette, scroll_a_color_id, scroll_a_replacement) =
+                if in_window {
+                    // Window replaces scroll A if this pixel is inside the window
+                    (window_priority, window_palette, window_color_id, window_replacement)
+                } else {
+                    (
+                        scroll_a_nt_word.priority,
+                        scroll_a_nt_word.palette,
+                        scroll_a_color_id,
+                        scroll_a_replacement,
+                    )
+                };
 
-            let (pixel_color, color_modifier) = determine_pixel_color(
+            let (pixel_color, replacement, color_modifier) = determine_pixel_color(
                 &self.cram,
                 self.debug_register,
                 PixelColorArgs {
                     sprite_priority,
                     sprite_palette,
                     sprite_color_id,
+                    sprite_replacement,
                     scroll_a_priority,
                     scroll_a_palette,
                     scroll_a_color_id,
+                    scroll_a_replacement,
                     scroll_b_priority: scroll_b_nt_word.priority,
                     scroll_b_palette: scroll_b_nt_word.palette,
                     scroll_b_color_id,
+                    scroll_b_replacement,
                     bg_color,
                     shadow_highlight_flag: self.latched_registers.shadow_highlight_flag,
-                    in_h_border: !(0..active_display_pixels as i16).contains(&pixel),
+                    in_h_border: !display_area.contains(&pixel),
                     in_v_border: raster_line.in_v_border && !self.state.v_border_forgotten,
                 },
             );
 
-            set_in_frame_buffer(
-                &mut self.frame_buffer,
-                frame_buffer_row,
-                frame_buffer_col,
-                pixel_color,
-                color_modifier,
-                screen_width,
-                self.config.emulate_non_linear_dac,
-            );
+            match replacement {
+                Some(replacement) => {
+                    let replacement = colors::apply_modifier(replacement, color_modifier);
+                    self.frame_buffer
+                        [(frame_buffer_row * screen_width + frame_buffer_col) as usize] =
+                        replacement;
+                }
+                None => set_in_frame_buffer(
+                    &mut self.frame_buffer,
+                    frame_buffer_row,
+                    frame_buffer_col,
+                    pixel_color,
+                    color_modifier,
+                    screen_width,
+                    self.config.emulate_non_linear_dac,
+                ),
+            }
         }
 
         if self.config.render_horizontal_border {
@@ -512,6 +617,12 @@ impl Vdp {
                     (left_border, left_border + active_display_pixels)
                 };
 
+                // Widescreen columns have no equivalent in actual hardware; fill them with color 0
+                let widescreen_pixels = self.widescreen_pixels(h_display_size);
+                if widescreen_pixels != 0 {
+                    self.fill_frame_buffer_row(frame_buffer_row, 0, self.cram[0]);
+                }
+
                 // +2 here is needed to properly align with the horizontal borders in Overdrive 2
                 // The number of 4-byte groups is equal to half the number of pixel clocks per line, 171 in H32 mode
                 // and 210 in H40 mode
@@ -529,8 +640,10 @@ impl Vdp {
                     // +3 here is needed to properly align with the horizontal borders in Overdrive 2
                     let tile_col = (pixel + 3) % 8;
                     if pixel == 0 || tile_col == 0 {
+                        let vram =
+                            self.table_vram(&self.latched_registers, VramTable::SpritePatterns);
                         current_group.copy_from_slice(
-                            &self.vram[current_addr as usize..(current_addr + 4) as usize],
+                            &vram[current_addr as usize..(current_addr + 4) as usize],
                         );
                         if odd_group {
                             current_addr = current_addr.wrapping_add(1024);
@@ -549,7 +662,7 @@ impl Vdp {
                     let color_id = (current_byte >> (4 - ((tile_col & 1) << 2))) & 0x0F;
                     let color = colors::resolve_color(&self.cram, palette, color_id);
 
-                    let frame_buffer_col = pixel - start_pixel;
+                    let frame_buffer_col = pixel - start_pixel + widescreen_pixels;
                     set_in_frame_buffer(
                         &mut self.frame_buffer,
                         frame_buffer_row,
@@ -730,7 +843,8 @@ impl Vdp {
         let sprite_col = pixel % 8;
         let vram_addr =
             self.sprite_buffers.last_tile_addresses[sprite_tile as usize] + (sprite_col >> 1);
-        let color_id = (self.vram[vram_addr as usize] >> (4 - ((sprite_col & 1) << 2))) & 0x0F;
+        let vram = self.table_vram(&self.latched_registers, VramTable::SpritePatterns);
+        let color_id = (vram[vram_addr as usize] >> (4 - ((sprite_col & 1) << 2))) & 0x0F;
         let palette = self.state.last_scroll_b_palettes[(pixel / 8) as usize];
         let color = colors::resolve_color(&self.cram, palette, color_id);
 
@@ -888,6 +1002,21 @@ pub struct PatternGeneratorArgs {
     pub cell_height: u16,
 }
 
+// Position of a pixel within its cell's pattern data, after applying flips
+#[inline]
+fn cell_position(
+    vertical_flip: bool,
+    horizontal_flip: bool,
+    row: u16,
+    col: u16,
+    cell_height: u16,
+) -> (u16, u16) {
+    let cell_row =
+        if vertical_flip { cell_height - 1 - (row % cell_height) } else { row % cell_height };
+    let cell_col = if horizontal_flip { 7 - (col % 8) } else { col % 8 };
+    (cell_row, cell_col)
+}
+
 #[inline]
 pub fn read_pattern_generator(
     vram: &Vram,
@@ -900,32 +1029,49 @@ pub fn read_pattern_generator(
         cell_height,
     }: PatternGeneratorArgs,
 ) -> u8 {
-    let cell_row =
-        if vertical_flip { cell_height - 1 - (row % cell_height) } else { row % cell_height };
-    let cell_col = if horizontal_flip { 7 - (col % 8) } else { col % 8 };
+    let (cell_row, cell_col) = cell_position(vertical_flip, horizontal_flip, row, col, cell_height);
 
     let cell_addr = (4 * cell_height).wrapping_mul(pattern_generator);
     let addr = (cell_addr + 4 * cell_row + (cell_col >> 1)) as usize;
     (vram[addr] >> (4 - ((cell_col & 0x01) << 2))) & 0x0F
 }
 
+// Replacements only apply to non-transparent pixels, so that they never change which layer is
+// visible
+fn replacement_color(
+    texture: Option<&TileImage>,
+    nt_word: NameTableWord,
+    color_id: u8,
+    row: u16,
+    col: u16,
+) -> Option<Color> {
+    let texture = texture.filter(|_| color_id != 0)?;
+    let (cell_row, cell_col) =
+        cell_position(nt_word.vertical_flip, nt_word.horizontal_flip, row, col, 8);
+    textures::replacement_pixel(texture, cell_row, cell_col)
+}
+
 #[derive(Debug, Clone, Copy)]
 struct UnresolvedColor {
     palette: u8,
     color_id: u8,
     is_sprite: bool,
+    replacement: Option<Color>,
 }
 
 struct PixelColorArgs {
     sprite_priority: bool,
     sprite_palette: u8,
     sprite_color_id: u8,
+    sprite_replacement: Option<Color>,
     scroll_a_priority: bool,
     scroll_a_palette: u8,
     scroll_a_color_id: u8,
+    scroll_a_replacement: Option<Color>,
     scroll_b_priority: bool,
     scroll_b_palette: u8,
     scroll_b_color_id: u8,
+    scroll_b_replacement: Option<Color>,
     bg_color: u16,
     shadow_highlight_flag: bool,
     in_h_border: bool,
@@ -941,18 +1087,21 @@ fn determine_pixel_color(
         sprite_priority,
         sprite_palette,
         sprite_color_id,
+        sprite_replacement,
         scroll_a_priority,
         scroll_a_palette,
         scroll_a_color_id,
+        scroll_a_replacement,
         scroll_b_priority,
         scroll_b_palette,
         scroll_b_color_id,
+        scroll_b_replacement,
         bg_color,
         shadow_highlight_flag,
         in_h_border,
         in_v_border,
     }: PixelColorArgs,
-) -> (u16, ColorModifier) {
+) -> (u16, Option<Color>, ColorModifier) {
     let sprite_cram_idx = (sprite_palette << 4) | sprite_color_id;
     let scroll_a_cram_idx = (scroll_a_palette << 4) | scroll_a_color_id;
     let scroll_b_cram_idx = (scroll_b_palette << 4) | scroll_b_color_id;
@@ -964,7 +1113,7 @@ fn determine_pixel_color(
             Plane::ScrollA => cram[scroll_a_cram_idx as usize],
             Plane::ScrollB => cram[scroll_b_cram_idx as usize],
         };
-        return (color, ColorModifier::None);
+        return (color, None, ColorModifier::None);
     }
 
     if debug_register.display_disabled || in_v_border {
@@ -974,7 +1123,7 @@ fn determine_pixel_color(
             Plane::ScrollA => cram[scroll_a_cram_idx as usize],
             Plane::ScrollB => cram[scroll_b_cram_idx as usize],
         };
-        return (color, ColorModifier::None);
+        return (color, None, ColorModifier::None);
     };
 
     let mut modifier = if shadow_highlight_flag && !scroll_a_priority && !scroll_b_priority {
@@ -984,17 +1133,23 @@ fn determine_pixel_color(
         ColorModifier::None
     };
 
-    let sprite =
-        UnresolvedColor { palette: sprite_palette, color_id: sprite_color_id, is_sprite: true };
+    let sprite = UnresolvedColor {
+        palette: sprite_palette,
+        color_id: sprite_color_id,
+        is_sprite: true,
+        replacement: sprite_replacement,
+    };
     let scroll_a = UnresolvedColor {
         palette: scroll_a_palette,
         color_id: scroll_a_color_id,
         is_sprite: false,
+        replacement: scroll_a_replacement,
     };
     let scroll_b = UnresolvedColor {
         palette: scroll_b_palette,
         color_id: scroll_b_color_id,
         is_sprite: false,
+        replacement: scroll_b_replacement,
     };
     let colors = match (sprite_priority, scroll_a_priority, scroll_b_priority) {
         (false, false, false) | (true, false, false) | (true, true, false) | (true, true, true) => {
@@ -1006,7 +1161,7 @@ fn determine_pixel_color(
         (false, true, true) => [scroll_a, scroll_b, sprite],
     };
 
-    for UnresolvedColor { palette, color_id, is_sprite } in colors {
+    for UnresolvedColor { palette, color_id, is_sprite, replacement } in colors {
         if color_id == 0 {
             // Pixel is transparent
             continue;
@@ -1040,7 +1195,7 @@ fn determine_pixel_color(
         } else {
             modifier
         };
-        return (color, modifier);
+        return (color, replacement, modifier);
     }
 
     let fallback_color = match debug_register.forced_plane {
@@ -1050,7 +1205,7 @@ fn determine_pixel_color(
         Plane::ScrollB => cram[scroll_b_cram_idx as usize],
     };
 
-    (fallback_color, modifier)
+    (fallback_color, None, modifier)
 }
 
 #[cfg(test)]
@@ -1,11 +1,16 @@
-use crate::vdp::registers::{HorizontalDisplaySize, InterlacingMode};
+use crate::vdp::registers::{HorizontalDisplaySize, InterlacingMode, VramTable};
 use crate::vdp::render::{PatternGeneratorArgs, RasterLine};
-use crate::vdp::{render, CachedSpriteData, SpriteData, Vdp};
+use crate::vdp::textures::{self, TileTextureCache};
+use crate::vdp::{render, vram_bank, CachedSpriteData, SpriteData, Vdp, WIDESCREEN_EXTRA_PIXELS};
 use bincode::{Decode, Encode};
+use jgenesis_common::frontend::Color;
 
 // Sprites with X = $080 display at the left edge of the screen
 const SPRITE_H_DISPLAY_START: u16 = 0x080;
 
+// Sprite pixels are buffered for the widest possible display area, including widescreen columns
+const SPRITE_BUFFER_LEN: usize = 320 + 2 * WIDESCREEN_EXTRA_PIXELS as usize;
+
 #[derive(Debug, Clone, Default, Encode, Decode)]
 pub struct SpriteState {
     overflow: bool,
@@ -68,6 +73,8 @@ pub struct SpritePixel {
     pub palette: u8,
     pub color_id: u8,
     pub priority: bool,
+    // From a tile texture replacement, if any
+    pub replacement: Option<Color>,
 }
 
 #[derive(Debug, Clone, Encode, Decode)]
@@ -75,7 +82,7 @@ pub struct SpriteBuffers {
     pub scanned_ids: Vec<u8>,
     pub sprites: Vec<SpriteData>,
     pub last_tile_addresses: Box<[u16; 40]>,
-    pub pixels: Box<[SpritePixel; 320]>,
+    pub pixels: Box<[SpritePixel; SPRITE_BUFFER_LEN]>,
 }
 
 impl SpriteBuffers {
@@ -84,7 +91,10 @@ impl SpriteBuffers {
             scanned_ids: Vec::with_capacity(20),
             sprites: Vec::with_capacity(20),
             last_tile_addresses: vec![0; 40].into_boxed_slice().try_into().unwrap(),
-            pixels: vec![SpritePixel::default(); 320].into_boxed_slice().try_into().unwrap(),
+            pixels: vec![SpritePixel::default(); SPRITE_BUFFER_LEN]
+                .into_boxed_slice()
+                .try_into()
+                .unwrap(),
         }
     }
 }
@@ -200,6 +210,11 @@ impl Vdp {
     }
 
     fn do_sprite_attribute_fetch(&mut self, use_interlaced_buffers: bool) {
+        let vram = vram_bank(
+            &self.vram,
+            self.expanded_vram.as_deref(),
+            self.registers.is_in_upper_vram(VramTable::SpriteAttributeTable),
+        );
         let buffers = if use_interlaced_buffers {
             &mut self.interlaced_sprite_buffers
         } else {
@@ -214,7 +229,7 @@ impl Vdp {
             let sprite_addr = sprite_table_addr.wrapping_add(8 * u16::from(sprite_idx)) as usize;
             let sprite = SpriteData::create(
                 self.cached_sprite_attributes[sprite_idx as usize],
-                &self.vram[sprite_addr + 4..sprite_addr + 8],
+                &vram[sprite_addr + 4..sprite_addr + 8],
             );
             buffers.sprites.push(sprite);
         }
@@ -230,6 +245,14 @@ impl Vdp {
         raster_line: RasterLine,
         use_interlaced_buffers: bool,
     ) {
+        let widescreen_pixels =
+            self.widescreen_pixels(self.latched_registers.horizontal_display_size);
+
+        let pattern_vram = vram_bank(
+            &self.vram,
+            self.expanded_vram.as_deref(),
+            self.latched_registers.is_in_upper_vram(VramTable::SpritePatterns),
+        );
         let buffers = if use_interlaced_buffers {
             &mut self.interlaced_sprite_buffers
         } else {
@@ -242,6 +265,11 @@ impl Vdp {
         let sprite_display_area =
             SPRITE_H_DISPLAY_START..SPRITE_H_DISPLAY_START + h_size.active_display_pixels();
 
+        // Sprite pixels in widescreen columns are buffered but never collide, since they would not
+        // be rendered by actual hardware
+        let buffer_start = SPRITE_H_DISPLAY_START - widescreen_pixels;
+        let buffer_area = buffer_start..sprite_display_area.end + widescreen_pixels;
+
         let half_tiles_not_fetched = if self.sprite_state.pixels_disabled_during_hblank != 0 {
             self.sprite_state.pixels_disabled_during_hblank + 8
         } else {
@@ -252,6 +280,9 @@ impl Vdp {
         let sprite_scanline = interlacing_mode.sprite_display_top() + raster_line.line;
         let cell_height = interlacing_mode.cell_height();
 
+        let textures_active = self.tile_textures.is_active() && cell_height == 8;
+        let mut texture_cache = TileTextureCache::default();
+
         // Apply max sprite pixel per scanline limit.
         //
         // If display was disabled during HBlank on the previous scanline, the number of sprite pixels
@@ -321,7 +352,7 @@ impl Vdp {
                     break;
                 }
 
-                if !sprite_display_area.contains(&h_position) {
+                if !buffer_area.contains(&h_position) {
                     continue;
                 }
 
@@ -333,27 +364,45 @@ impl Vdp {
                 };
 
                 let pattern_offset = (sprite_col / 8) * v_size_cells + sprite_row / cell_height;
+                let pattern_generator = sprite.pattern_generator.wrapping_add(pattern_offset);
                 let color_id = render::read_pattern_generator(
-                    &self.vram,
+                    pattern_vram,
                     PatternGeneratorArgs {
                         vertical_flip: false,
                         horizontal_flip: false,
-                        pattern_generator: sprite.pattern_generator.wrapping_add(pattern_offset),
+                        pattern_generator,
                         row: sprite_row % cell_height,
                         col: sprite_col % 8,
                         cell_height,
                     },
                 );
 
-                let pixel = h_position - SPRITE_H_DISPLAY_START;
+                let replacement = if textures_active && color_id != 0 {
+                    texture_cache
+                        .get(
+                            &self.tile_textures,
+                            pattern_vram,
+                            &self.cram,
+                            pattern_generator,
+                            sprite.palette,
+                        )
+                        .and_then(|image| {
+                            textures::replacement_pixel(image, sprite_row % 8, sprite_col % 8)
+                        })
+                } else {
+                    None
+                };
+
+                let pixel = h_position - buffer_start;
                 if buffers.pixels[pixel as usize].color_id == 0 {
                     // Transparent pixels are always overwritten, even if the current pixel is also transparent
                     buffers.pixels[pixel as usize] = SpritePixel {
                         palette: sprite.palette,
                         color_id,
                         priority: sprite.priority,
+                        replacement,
                     };
-                } else {
+                } else if sprite_display_area.contains(&h_position) {
                     // Sprite collision; two non-transparent sprite pixels in the same position
                     self.sprite_state.collision = true;
                 }
@@ -0,0 +1,223 @@
+//! Experimental tile texture dumping and replacement
+//!
+//! Every 8x8 tile that the VDP draws is identified by a hash of its pattern data combined with the
+//! palette it is drawn with, so the same graphics drawn with a different palette count as a
+//! different tile. When dumping is enabled, the VDP records each tile the first time it is drawn so
+//! that the frontend can save it as an image. When replacements are loaded, the VDP substitutes the
+//! replacement's colors for the tile's colors at draw time.
+//!
+//! Replacements only change colors; transparency, priority, and shadow/highlight are still
+//! determined by the original tile, and replacements must be the same 8x8 size as the tile they
+//! replace. Tiles drawn in interlaced double resolution mode (8x16 cells) are neither dumped nor
+//! replaced.
+
+use crate::vdp::colors::{self, ColorModifier};
+use crate::vdp::{Cram, Vram};
+use crc::Crc;
+use jgenesis_common::frontend::Color;
+use jgenesis_proc_macros::{FakeDecode, FakeEncode};
+use std::collections::{HashMap, HashSet};
+use std::mem;
+use std::sync::{Arc, Mutex};
+
+const CRC: Crc<u64> = Crc::<u64>::new(&crc::CRC_64_XZ);
+
+pub const TILE_SIZE: usize = 8;
+pub const TILE_PIXELS: usize = TILE_SIZE * TILE_SIZE;
+
+const TILE_BYTES: usize = TILE_PIXELS / 2;
+
+pub type TileImage = [Color; TILE_PIXELS];
+
+/// Replacement images for tiles, keyed by tile hash.
+#[derive(Debug, Clone, Default)]
+pub struct TileReplacements(HashMap<u64, Box<TileImage>>);
+
+impl TileReplacements {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, hash: u64, image: Box<TileImage>) {
+        self.0.insert(hash, image);
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// A tile that was drawn for the first time while dumping was enabled. Pixels that use color 0 are
+/// transparent.
+#[derive(Debug, Clone)]
+pub struct DumpedTile {
+    pub hash: u64,
+    pub image: Box<TileImage>,
+}
+
+#[derive(Debug, Default)]
+struct TileDumper {
+    seen: HashSet<u64>,
+    pending: Vec<DumpedTile>,
+}
+
+// Shared between clones of the VDP (e.g. rewind snapshots) and not persisted in save states
+#[derive(Debug, Clone, Default, FakeEncode, FakeDecode)]
+pub(crate) struct TileTextures {
+    replacements: Option<Arc<TileReplacements>>,
+    dumper: Option<Arc<Mutex<TileDumper>>>,
+}
+
+impl TileTextures {
+    pub(crate) fn is_active(&self) -> bool {
+        self.replacements.is_some() || self.dumper.is_some()
+    }
+
+    pub(crate) fn set_replacements(&mut self, replacements: Option<TileReplacements>) {
+        self.replacements = replacements.filter(|r| !r.is_empty()).map(Arc::new);
+    }
+
+    pub(crate) fn set_dump_enabled(&mut self, dump_enabled: bool) {
+        if dump_enabled != self.dumper.is_some() {
+            self.dumper = dump_enabled.then(Arc::default);
+        }
+    }
+
+    pub(crate) fn take_dumped_tiles(&mut self) -> Vec<DumpedTile> {
+        self.dumper
+            .as_ref()
+            .map(|dumper| mem::take(&mut dumper.lock().unwrap().pending))
+            .unwrap_or_default()
+    }
+
+    // Look up the replacement for a tile drawn with the given palette, recording the tile if it has
+    // not been seen yet while dumping
+    pub(crate) fn lookup(
+        &self,
+        vram: &Vram,
+        cram: &Cram,
+        pattern_generator: u16,
+        palette: u8,
+    ) -> Option<&TileImage> {
+        let tile_addr = (TILE_BYTES as u16).wrapping_mul(pattern_generator) as usize;
+        let pattern = &vram[tile_addr..tile_addr + TILE_BYTES];
+        // Color 0 is always transparent, so it does not contribute to the hash
+        let palette_colors = &cram[16 * palette as usize + 1..16 * (palette as usize + 1)];
+
+        let mut digest = CRC.digest();
+        digest.update(pattern);
+        for &color in palette_colors {
+            digest.update(&color.to_be_bytes());
+        }
+        let hash = digest.finalize();
+
+        if let Some(dumper) = &self.dumper {
+            let mut dumper = dumper.lock().unwrap();
+            if dumper.seen.insert(hash) {
+                let image = tile_image(pattern, palette_colors);
+                dumper.pending.push(DumpedTile { hash, image });
+            }
+        }
+
+        self.replacements
+            .as_ref()
+            .and_then(|replacements| replacements.0.get(&hash))
+            .map(|image| &**image)
+    }
+}
+
+fn tile_image(pattern: &[u8], palette_colors: &[u16]) -> Box<TileImage> {
+    let mut image = Box::new([Color::TRANSPARENT; TILE_PIXELS]);
+    for (i, pixel) in image.iter_mut().enumerate() {
+        let color_id = (pattern[i / 2] >> (4 - ((i & 1) << 2))) & 0x0F;
+        if color_id == 0 {
+            continue;
+        }
+
+        let color = palette_colors[color_id as usize - 1];
+        let r = ((color >> 1) & 0x07) as u8;
+        let g = ((color >> 5) & 0x07) as u8;
+        let b = ((color >> 9) & 0x07) as u8;
+        *pixel = colors::gen_to_rgb(r, g, b, ColorModifier::None, false);
+    }
+    image
+}
+
+// Replacement color for the pixel at the given position within a tile (after applying flips), or
+// None if the replacement pixel is transparent
+pub(crate) fn replacement_pixel(image: &TileImage, row: u16, col: u16) -> Option<Color> {
+    let color = image[usize::from(row) * TILE_SIZE + usize::from(col)];
+    (color.a != 0).then_some(color)
+}
+
+// Avoids re-hashing the same tile for every pixel while drawing a line
+#[derive(Default)]
+pub(crate) struct TileTextureCache<'a> {
+    key: Option<(u16, u8)>,
+    image: Option<&'a TileImage>,
+}
+
+impl<'a> TileTextureCache<'a> {
+    pub(crate) fn get(
+        &mut self,
+        textures: &'a TileTextures,
+        vram: &Vram,
+        cram: &Cram,
+        pattern_generator: u16,
+        palette: u8,
+    ) -> Option<&'a TileImage> {
+        let key = (pattern_generator, palette);
+        if self.key != Some(key) {
+            self.key = Some(key);
+            self.image = textures.lookup(vram, cram, pattern_generator, palette);
+        }
+        self.image
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dumps_each_tile_and_palette_once() {
+        let mut vram = vec![0; 64 * 1024].into_boxed_slice();
+        vram[32..64].fill(0x12);
+        let vram: Box<Vram> = vram.try_into().unwrap();
+
+        let mut cram: Box<Cram> = Box::new([0; 64]);
+        cram[1] = 0x000E;
+        cram[2] = 0x00E0;
+        cram[16 + 2] = 0x0E00;
+
+        let mut textures = TileTextures::default();
+        textures.set_dump_enabled(true);
+
+        assert!(textures.lookup(&vram, &cram, 1, 0).is_none());
+        textures.lookup(&vram, &cram, 1, 0);
+        textures.lookup(&vram, &cram, 1, 1);
+
+        let dumped = textures.take_dumped_tiles();
+        assert_eq!(dumped.len(), 2);
+        assert_ne!(dumped[0].hash, dumped[1].hash);
+        assert_eq!(dumped[0].image[0], Color::rgb(255, 0, 0));
+        assert_eq!(dumped[0].image[1], Color::rgb(0, 255, 0));
+        assert_eq!(dumped[1].image[1], Color::rgb(0, 0, 255));
+
+        let mut replacements = TileReplacements::new();
+        replacements.insert(dumped[0].hash, Box::new([Color::rgb(1, 2, 3); TILE_PIXELS]));
+        textures.set_replacements(Some(replacements));
+
+        let replacement = textures.lookup(&vram, &cram, 1, 0).unwrap();
+        assert_eq!(replacement_pixel(replacement, 7, 7), Some(Color::rgb(1, 2, 3)));
+        assert!(textures.lookup(&vram, &cram, 1, 1).is_none());
+        assert!(textures.take_dumped_tiles().is_empty());
+    }
+}
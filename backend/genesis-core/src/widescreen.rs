@@ -0,0 +1,200 @@
+//! Per-game patches for widescreen rendering
+//!
+//! In widescreen mode the VDP renders the scroll planes and sprites beyond the left and right edges
+//! of the normal 320-pixel display. Most games only keep the plane columns that are about to scroll
+//! onscreen up to date and despawn objects as soon as they leave the screen, so the extra columns
+//! often show stale tiles or missing sprites. Community-made patches fix this for specific games,
+//! e.g. by widening the area that a game updates and spawns objects in.
+//!
+//! Patch definitions are plain text. Each game starts with a header containing the CRC32 of the ROM
+//! in brackets (logged when a ROM is loaded), followed by one patch per line: a hex ROM address, a
+//! colon, and hex bytes to write at that address. Lines starting with `#` are comments.
+//!
+//! ```text
+//! # Example Game (USA)
+//! [1A2B3C4D]
+//! 00A2C4: 4E71 4E71
+//! 012000: 0160
+//! ```
+
+use crc::Crc;
+use std::collections::HashMap;
+use thiserror::Error;
+
+const CRC: Crc<u32> = Crc::<u32>::new(&crc::CRC_32_ISO_HDLC);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RomPatch {
+    pub address: u32,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug, Error)]
+pub enum WidescreenPatchError {
+    #[error("Line {line}: invalid game header '{text}', expected a ROM CRC32 in brackets")]
+    InvalidHeader { line: usize, text: String },
+    #[error(
+        "Line {line}: invalid patch '{text}', expected a hex address followed by ':' and hex bytes"
+    )]
+    InvalidPatch { line: usize, text: String },
+    #[error("Line {line}: patch is not preceded by a game header")]
+    PatchWithoutGame { line: usize },
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct WidescreenPatches {
+    games: HashMap<u32, Vec<RomPatch>>,
+}
+
+impl WidescreenPatches {
+    /// Parse patch definitions in the format described in the [module docs](self).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any non-comment line is not a valid game header or patch.
+    pub fn parse(contents: &str) -> Result<Self, WidescreenPatchError> {
+        let mut games: HashMap<u32, Vec<RomPatch>> = HashMap::new();
+        let mut current_game = None;
+
+        for (i, line) in contents.lines().enumerate() {
+            let line_number = i + 1;
+            let line = line.trim_start_matches('\u{feff}').trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(header) = line.strip_prefix('[') {
+                let crc32 = header
+                    .strip_suffix(']')
+                    .and_then(|crc32| u32::from_str_radix(crc32.trim(), 16).ok())
+                    .ok_or_else(|| WidescreenPatchError::InvalidHeader {
+                        line: line_number,
+                        text: line.into(),
+                    })?;
+                games.entry(crc32).or_default();
+                current_game = Some(crc32);
+                continue;
+            }
+
+            let Some(crc32) = current_game else {
+                return Err(WidescreenPatchError::PatchWithoutGame { line: line_number });
+            };
+
+            let patch = parse_patch(line).ok_or_else(|| WidescreenPatchError::InvalidPatch {
+                line: line_number,
+                text: line.into(),
+            })?;
+            games.entry(crc32).or_default().push(patch);
+        }
+
+        Ok(Self { games })
+    }
+
+    /// Look up the patches for the given ROM, if any.
+    #[must_use]
+    pub fn rom_patches(&self, rom: &[u8]) -> Option<&[RomPatch]> {
+        let checksum = CRC.checksum(rom);
+        log::info!("ROM CRC32: {checksum:08X}");
+
+        self.games.get(&checksum).map(Vec::as_slice)
+    }
+
+    /// Apply the patches for the given ROM, if any. Returns whether any patches were found.
+    ///
+    /// Patches that extend past the end of the ROM are skipped.
+    pub fn apply(&self, rom: &mut [u8]) -> bool {
+        let Some(patches) = self.rom_patches(rom) else { return false };
+
+        for patch in patches {
+            let start = patch.address as usize;
+            match rom.get_mut(start..start + patch.data.len()) {
+                Some(dest) => dest.copy_from_slice(&patch.data),
+                None => log::warn!(
+                    "Skipping widescreen patch at ${:06X}; ROM is only {} bytes",
+                    patch.address,
+                    rom.len()
+                ),
+            }
+        }
+
+        log::info!("Applied {} widescreen patches", patches.len());
+
+        true
+    }
+}
+
+fn parse_patch(line: &str) -> Option<RomPatch> {
+    let (address, data) = line.split_once(':')?;
+    let address = u32::from_str_radix(address.trim(), 16).ok()?;
+
+    let hex: Vec<u8> = data.bytes().filter(|b| !b.is_ascii_whitespace()).collect();
+    if hex.is_empty() || hex.len() % 2 != 0 {
+        return None;
+    }
+
+    let data = hex
+        .chunks_exact(2)
+        .map(|byte| u8::from_str_radix(std::str::from_utf8(byte).ok()?, 16).ok())
+        .collect::<Option<Vec<_>>>()?;
+
+    Some(RomPatch { address, data })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_games_and_patches() {
+        let contents = "\
+# Comment
+[1A2B3C4D]
+00A2C4: 4E71 4E71
+
+[0000ABCD]
+# Another comment
+12000:0160
+";
+        let patches = WidescreenPatches::parse(contents).unwrap();
+        assert_eq!(
+            patches.games[&0x1A2B3C4D],
+            vec![RomPatch { address: 0xA2C4, data: vec![0x4E, 0x71, 0x4E, 0x71] }]
+        );
+        assert_eq!(
+            patches.games[&0xABCD],
+            vec![RomPatch { address: 0x12000, data: vec![0x01, 0x60] }]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_lines() {
+        assert!(matches!(
+            WidescreenPatches::parse("00A2C4: 4E71"),
+            Err(WidescreenPatchError::PatchWithoutGame { line: 1 })
+        ));
+        assert!(matches!(
+            WidescreenPatches::parse("[1A2B3C4D\n00A2C4: 4E71"),
+            Err(WidescreenPatchError::InvalidHeader { line: 1, .. })
+        ));
+        assert!(matches!(
+            WidescreenPatches::parse("[1A2B3C4D]\n00A2C4: 4E7"),
+            Err(WidescreenPatchError::InvalidPatch { line: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn applies_patches_for_matching_rom() {
+        let mut rom = vec![0; 16];
+        let checksum = CRC.checksum(&rom);
+
+        let contents = format!("[{checksum:08X}]\n000004: 1234\n00000F: 5678");
+        let patches = WidescreenPatches::parse(&contents).unwrap();
+        assert!(patches.apply(&mut rom));
+        assert_eq!(rom[4..6], [0x12, 0x34]);
+        // Out of bounds patch should be skipped
+        assert_eq!(rom[15], 0);
+
+        let mut other_rom = vec![1; 16];
+        assert!(!patches.apply(&mut other_rom));
+    }
+}
@@ -1,22 +1,26 @@
 use crate::apu::ApuState;
 use crate::audio::AudioResampler;
-use crate::bus::cartridge::CartridgeFileError;
+use crate::bus::cartridge::{CartridgeFileError, Mapper};
 use crate::bus::{cartridge, Bus};
+use crate::cdl::CodeDataLog;
 use crate::cpu::CpuState;
-use crate::graphics::TimingModeGraphicsExt;
-use crate::input::NesInputs;
+use crate::input::{NesExpansionDevice, NesInputs};
 use crate::ppu::PpuState;
 use crate::{apu, cpu, graphics, ppu};
 use bincode::{Decode, Encode};
+use jgenesis_common::audio::ResamplerQuality;
 use jgenesis_common::frontend::{
     AudioOutput, Color, EmulatorTrait, FrameSize, PixelAspectRatio, Renderer, SaveWriter,
     TickEffect, TickResult, TimingMode,
 };
+use jgenesis_common::rng::{InitialRamState, Rng};
+use jgenesis_common::state::StateFormat;
 use jgenesis_proc_macros::{EnumDisplay, EnumFromStr, PartialClone};
 use std::fmt::{Debug, Display, Formatter};
 use std::mem;
 use thiserror::Error;
 
+pub use crate::bus::cartridge::BarcodeError;
 pub use graphics::PatternTable;
 use mos6502_emu::bus::BusInterface;
 
@@ -26,6 +30,35 @@ const PAL_MASTER_CLOCK_TICKS: u32 = 80;
 const PAL_CPU_DIVIDER: u32 = 16;
 const PAL_PPU_DIVIDER: u32 = 5;
 
+/// NES timing mode. Dendy is a hybrid used by many PAL-region Famicom clones: it runs at PAL speed
+/// with a 50Hz display, but with the CPU/PPU clock ratio and vertical blanking length of NTSC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode, EnumDisplay, EnumFromStr)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NesTimingMode {
+    #[default]
+    Ntsc,
+    Pal,
+    Dendy,
+}
+
+impl From<TimingMode> for NesTimingMode {
+    fn from(value: TimingMode) -> Self {
+        match value {
+            TimingMode::Ntsc => Self::Ntsc,
+            TimingMode::Pal => Self::Pal,
+        }
+    }
+}
+
+impl From<NesTimingMode> for TimingMode {
+    fn from(value: NesTimingMode) -> Self {
+        match value {
+            NesTimingMode::Ntsc => Self::Ntsc,
+            NesTimingMode::Pal | NesTimingMode::Dendy => Self::Pal,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode, EnumDisplay, EnumFromStr)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum NesAspectRatio {
@@ -58,6 +91,16 @@ pub struct Overscan {
 
 impl Overscan {
     pub const NONE: Self = Self { top: 0, bottom: 0, left: 0, right: 0 };
+
+    #[must_use]
+    pub fn saturating_add(self, other: Self) -> Self {
+        Self {
+            top: self.top.saturating_add(other.top),
+            bottom: self.bottom.saturating_add(other.bottom),
+            left: self.left.saturating_add(other.left),
+            right: self.right.saturating_add(other.right),
+        }
+    }
 }
 
 impl Display for Overscan {
@@ -72,13 +115,17 @@ impl Display for Overscan {
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
 pub struct NesEmulatorConfig {
-    /// Force timing mode to NTSC/PAL if set
+    /// Force timing mode to NTSC/PAL/Dendy if set
     /// If None, timing mode will default based on iNES ROM header
-    pub forced_timing_mode: Option<TimingMode>,
+    pub forced_timing_mode: Option<NesTimingMode>,
     /// Aspect ratio
     pub aspect_ratio: NesAspectRatio,
     /// Overscan in pixels
     pub overscan: Overscan,
+    /// If true, hide the scanlines that most NTSC TVs cut off when in NTSC mode, in addition to
+    /// the configured overscan. This is the top and bottom 8 scanlines unless the game has an entry
+    /// in the overscan database
+    pub emulate_ntsc_overscan: bool,
     /// If true, do not emulate the 8 sprite per scanline limit; this eliminates sprite flickering
     /// but can cause bugs in some games
     pub remove_sprite_limit: bool,
@@ -92,16 +139,23 @@ pub struct NesEmulatorConfig {
     /// Some games exhibit severe glitches when opposing joypad directions are pressed
     /// simultaneously, e.g. Zelda 2 and Battletoads
     pub allow_opposing_joypad_inputs: bool,
+    /// Force the connected expansion port device if set
+    /// If None, the device will default based on the NES 2.0 ROM header
+    pub forced_expansion_device: Option<NesExpansionDevice>,
+    pub audio_resampler_quality: ResamplerQuality,
+    /// Initial contents of CPU internal RAM; if None, RAM is randomized
+    pub initial_ram_state: Option<InitialRamState>,
+    /// Seed for all in-core randomness, including random initial RAM contents; if None, a seed is
+    /// chosen randomly at power on
+    pub rng_seed: Option<u64>,
 }
 
 #[derive(Debug, Error)]
-pub enum NesError<RErr, AErr, SErr> {
+pub enum NesError<RErr, AErr> {
     #[error("Error rendering frame: {0}")]
     Render(RErr),
     #[error("Error outputting audio samples: {0}")]
     Audio(AErr),
-    #[error("Error persisting save file: {0}")]
-    SaveWrite(SErr),
 }
 
 #[derive(Debug, Error)]
@@ -120,6 +174,8 @@ pub struct NesEmulator {
     config: NesEmulatorConfig,
     rgba_frame_buffer: Vec<Color>,
     audio_resampler: AudioResampler,
+    ntsc_overscan: Overscan,
+    sram_dirty: bool,
     // Kept around to enable hard reset
     #[partial_clone(default)]
     raw_rom_bytes: Vec<u8>,
@@ -140,8 +196,12 @@ impl NesEmulator {
         let sav_bytes = save_writer.load_bytes("sav").ok();
         let mapper = cartridge::from_ines_file(&rom_bytes, sav_bytes, config.forced_timing_mode)?;
         let timing_mode = mapper.timing_mode();
+        let ntsc_overscan = graphics::ntsc_overscan(&rom_bytes);
 
-        let mut bus = Bus::from_cartridge(mapper);
+        let mut rng = Rng::from_optional_seed(config.rng_seed);
+        let initial_ram_state = config.initial_ram_state.unwrap_or(InitialRamState::Random);
+        let expansion_device = effective_expansion_device(&mapper, config);
+        let mut bus = Bus::from_cartridge(mapper, expansion_device, initial_ram_state, &mut rng);
 
         let cpu_state = CpuState::new(&mut bus.cpu());
         let ppu_state = PpuState::new(timing_mode);
@@ -156,7 +216,13 @@ impl NesEmulator {
             apu_state,
             config,
             rgba_frame_buffer: new_rgba_frame_buffer(),
-            audio_resampler: AudioResampler::new(timing_mode, config.audio_refresh_rate_adjustment),
+            audio_resampler: AudioResampler::new(
+                timing_mode,
+                config.audio_refresh_rate_adjustment,
+                config.audio_resampler_quality,
+            ),
+            ntsc_overscan,
+            sram_dirty: false,
             raw_rom_bytes: rom_bytes,
         })
     }
@@ -213,22 +279,19 @@ impl NesEmulator {
     }
 
     fn render_frame<R: Renderer>(&mut self, renderer: &mut R) -> Result<(), R::Err> {
-        let overscan = self.config.overscan;
-        let timing_mode = self.bus.mapper().timing_mode();
+        let overscan = self.effective_overscan();
         graphics::ppu_frame_buffer_to_rgba(
             self.ppu_state.frame_buffer(),
             &mut self.rgba_frame_buffer,
             overscan,
-            timing_mode,
         );
 
-        let visible_screen_height = timing_mode.visible_screen_height();
         let frame_size = FrameSize {
             width: ppu::SCREEN_WIDTH
                 .saturating_sub(overscan.left)
                 .saturating_sub(overscan.right)
                 .into(),
-            height: visible_screen_height
+            height: ppu::MAX_SCREEN_HEIGHT
                 .saturating_sub(overscan.top)
                 .saturating_sub(overscan.bottom)
                 .into(),
@@ -244,6 +307,15 @@ impl NesEmulator {
         renderer.render_frame(&self.rgba_frame_buffer, frame_size, pixel_aspect_ratio)
     }
 
+    fn effective_overscan(&self) -> Overscan {
+        let timing_mode = self.bus.mapper().timing_mode();
+        if timing_mode == NesTimingMode::Ntsc && self.config.emulate_ntsc_overscan {
+            self.config.overscan.saturating_add(self.ntsc_overscan)
+        } else {
+            self.config.overscan
+        }
+    }
+
     fn push_audio_sample(&mut self) {
         let audio_sample = {
             let sample = self.apu_state.sample();
@@ -269,6 +341,46 @@ impl NesEmulator {
     pub fn using_double_height_sprites(&mut self) -> bool {
         self.bus.ppu().get_ppu_registers().double_height_sprites()
     }
+
+    /// Enable or disable the code/data logger. Enabling it does not clear previously logged data.
+    pub fn set_code_data_logging(&mut self, enabled: bool) {
+        self.bus.set_code_data_logging(enabled);
+    }
+
+    #[must_use]
+    pub fn code_data_log(&self) -> &CodeDataLog {
+        self.bus.code_data_log()
+    }
+
+    pub fn code_data_log_mut(&mut self) -> &mut CodeDataLog {
+        self.bus.code_data_log_mut()
+    }
+
+    /// The device currently connected to the expansion port, either from the config or the ROM
+    /// header.
+    #[must_use]
+    pub fn expansion_device(&self) -> NesExpansionDevice {
+        self.bus.expansion_device()
+    }
+
+    /// Set whether the Famicom's built-in microphone on the second controller is picking up sound.
+    pub fn set_famicom_microphone(&mut self, microphone: bool) {
+        self.bus.set_famicom_microphone(microphone);
+    }
+
+    /// Scan a barcode using the Datach barcode reader. Barcodes must be EAN-13 or EAN-8.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cartridge does not have a barcode reader or if the barcode is
+    /// invalid.
+    pub fn scan_barcode(&mut self, barcode: &str) -> Result<(), BarcodeError> {
+        self.bus.mapper_mut().scan_barcode(barcode)
+    }
+}
+
+fn effective_expansion_device(mapper: &Mapper, config: NesEmulatorConfig) -> NesExpansionDevice {
+    config.forced_expansion_device.or(mapper.default_expansion_device()).unwrap_or_default()
 }
 
 fn new_rgba_frame_buffer() -> Vec<Color> {
@@ -278,43 +390,43 @@ fn new_rgba_frame_buffer() -> Vec<Color> {
 impl EmulatorTrait for NesEmulator {
     type Inputs = NesInputs;
     type Config = NesEmulatorConfig;
+    const STATE_FORMAT: StateFormat = StateFormat { core: "nes", version: 1 };
     type Err<
         RErr: Debug + Display + Send + Sync + 'static,
         AErr: Debug + Display + Send + Sync + 'static,
-        SErr: Debug + Display + Send + Sync + 'static,
-    > = NesError<RErr, AErr, SErr>;
+    > = NesError<RErr, AErr>;
 
-    /// Run the emulator for 1 CPU cycle / 3 PPU cycles (NTSC) or 5 CPU cycles / 16 PPU cycles (PAL).
+    /// Run the emulator for 1 CPU cycle / 3 PPU cycles (NTSC/Dendy) or 5 CPU cycles / 16 PPU cycles
+    /// (PAL).
     ///
     /// # Errors
     ///
-    /// This method will propagate any errors encountered while rendering a frame, pushing
-    /// audio samples, or persisting SRAM.
-    fn tick<R, A, S>(
+    /// This method will propagate any errors encountered while rendering a frame or pushing
+    /// audio samples.
+    fn tick<R, A>(
         &mut self,
         renderer: &mut R,
         audio_output: &mut A,
         inputs: &Self::Inputs,
-        save_writer: &mut S,
-    ) -> TickResult<Self::Err<R::Err, A::Err, S::Err>>
+    ) -> TickResult<Self::Err<R::Err, A::Err>>
     where
         R: Renderer,
         R::Err: Debug + Display + Send + Sync + 'static,
         A: AudioOutput,
         A::Err: Debug + Display + Send + Sync + 'static,
-        S: SaveWriter,
-        S::Err: Debug + Display + Send + Sync + 'static,
     {
         let prev_in_vblank = self.ppu_state.in_vblank();
 
         self.bus.update_p1_joypad_state(inputs.p1, self.config.allow_opposing_joypad_inputs);
         self.bus.update_p2_joypad_state(inputs.p2, self.config.allow_opposing_joypad_inputs);
+        self.bus.update_expansion_inputs(inputs.expansion);
 
         let timing_mode = self.bus.mapper().timing_mode();
 
         match timing_mode {
-            TimingMode::Ntsc => self.ntsc_tick(),
-            TimingMode::Pal => self.pal_tick(),
+            // Dendy has the same CPU/PPU clock ratio as NTSC
+            NesTimingMode::Ntsc | NesTimingMode::Dendy => self.ntsc_tick(),
+            NesTimingMode::Pal => self.pal_tick(),
         }
 
         if !prev_in_vblank && self.ppu_state.in_vblank() {
@@ -327,8 +439,7 @@ impl EmulatorTrait for NesEmulator {
             self.audio_resampler.output_samples(audio_output).map_err(NesError::Audio)?;
 
             if self.bus.mapper_mut().get_and_clear_ram_dirty_bit() {
-                let sram = self.bus.mapper().get_prg_ram();
-                save_writer.persist_bytes("sav", sram).map_err(NesError::SaveWrite)?;
+                self.sram_dirty = true;
             }
 
             return Ok(TickEffect::FrameRendered);
@@ -337,6 +448,19 @@ impl EmulatorTrait for NesEmulator {
         Ok(TickEffect::None)
     }
 
+    fn save_dirty(&self) -> bool {
+        self.sram_dirty
+    }
+
+    fn persist_save<S: SaveWriter>(&mut self, save_writer: &mut S) -> Result<(), S::Err> {
+        if self.sram_dirty {
+            save_writer.persist_bytes("sav", self.bus.mapper().get_prg_ram())?;
+            self.sram_dirty = false;
+        }
+
+        Ok(())
+    }
+
     fn force_render<R>(&mut self, renderer: &mut R) -> Result<(), R::Err>
     where
         R: Renderer,
@@ -347,8 +471,12 @@ impl EmulatorTrait for NesEmulator {
     fn reload_config(&mut self, config: &Self::Config) {
         self.config = *config;
 
+        let expansion_device = effective_expansion_device(self.bus.mapper(), *config);
+        self.bus.set_expansion_device(expansion_device);
+
         self.audio_resampler
             .set_apply_refresh_rate_adjustment(config.audio_refresh_rate_adjustment);
+        self.audio_resampler.set_quality(config.audio_resampler_quality);
     }
 
     fn take_rom_from(&mut self, other: &mut Self) {
@@ -369,13 +497,15 @@ impl EmulatorTrait for NesEmulator {
 
     fn hard_reset<S: SaveWriter>(&mut self, save_writer: &mut S) {
         let rom_bytes = mem::take(&mut self.raw_rom_bytes);
+        let code_data_log = mem::take(self.bus.code_data_log_mut());
 
         *self = Self::create(rom_bytes, self.config, save_writer)
             .expect("Creation during hard reset should never fail");
+        *self.bus.code_data_log_mut() = code_data_log;
     }
 
     fn timing_mode(&self) -> TimingMode {
-        self.bus.mapper().timing_mode()
+        self.bus.mapper().timing_mode().into()
     }
 }
 
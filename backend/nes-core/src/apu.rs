@@ -18,14 +18,13 @@ pub mod pulse;
 mod triangle;
 pub mod units;
 
-use crate::api::NesEmulatorConfig;
+use crate::api::{NesEmulatorConfig, NesTimingMode};
 use crate::apu::dmc::DeltaModulationChannel;
 use crate::apu::noise::NoiseChannel;
 use crate::apu::pulse::{PulseChannel, SweepStatus};
 use crate::apu::triangle::TriangleChannel;
 use crate::bus::{CpuBus, IoRegister, IrqSource};
 use bincode::{Decode, Encode};
-use jgenesis_common::frontend::TimingMode;
 use jgenesis_common::num::GetBit;
 use std::ops::Range;
 use std::sync::OnceLock;
@@ -60,10 +59,11 @@ impl FrameCounter {
     const NTSC_STEPS: [u16; 5] = [7456, 14912, 22370, 29828, 37280];
     const PAL_STEPS: [u16; 5] = [8312, 16626, 24938, 33252, 41564];
 
-    pub fn new(timing_mode: TimingMode) -> Self {
+    pub fn new(timing_mode: NesTimingMode) -> Self {
+        // Dendy clones use the NTSC frame counter timings
         let steps = match timing_mode {
-            TimingMode::Ntsc => Self::NTSC_STEPS,
-            TimingMode::Pal => Self::PAL_STEPS,
+            NesTimingMode::Ntsc | NesTimingMode::Dendy => Self::NTSC_STEPS,
+            NesTimingMode::Pal => Self::PAL_STEPS,
         };
 
         let four_step_reset = steps[3] + 2;
@@ -154,7 +154,7 @@ pub struct ApuState {
 }
 
 impl ApuState {
-    pub fn new(timing_mode: TimingMode) -> Self {
+    pub fn new(timing_mode: NesTimingMode) -> Self {
         Self {
             pulse_channel_1: PulseChannel::new_channel_1(SweepStatus::Enabled),
             pulse_channel_2: PulseChannel::new_channel_2(SweepStatus::Enabled),
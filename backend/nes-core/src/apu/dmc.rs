@@ -9,7 +9,6 @@
 use crate::bus::CpuBus;
 use bincode::{Decode, Encode};
 use jgenesis_common::num::GetBit;
-use mos6502_emu::bus::BusInterface;
 
 const DMC_PERIOD_LOOKUP_TABLE: [u16; 16] =
     [428, 380, 340, 320, 286, 254, 226, 214, 190, 160, 142, 128, 106, 84, 72, 54];
@@ -123,10 +122,11 @@ impl DeltaModulationChannel {
         self.enabled = snd_chn_value.bit(4);
         if self.enabled && self.sample_bytes_remaining == 0 {
             self.restart();
-            self.fill_sample_buffer(bus);
+            self.request_sample(bus);
         } else if !self.enabled {
             self.sample_bytes_remaining = 0;
             self.sample_buffer = None;
+            bus.cancel_dmc_dma();
         }
     }
 
@@ -135,12 +135,18 @@ impl DeltaModulationChannel {
         self.sample_bytes_remaining = self.sample_length;
     }
 
-    fn fill_sample_buffer(&mut self, bus: &mut CpuBus<'_>) {
+    // Sample bytes are fetched by the DMA unit, which halts the CPU for a few cycles before
+    // performing the read; see the cpu module
+    fn request_sample(&self, bus: &mut CpuBus<'_>) {
         if self.sample_buffer.is_some() || self.sample_bytes_remaining == 0 {
             return;
         }
 
-        self.sample_buffer = Some(bus.read(self.current_sample_address));
+        bus.request_dmc_dma(self.current_sample_address);
+    }
+
+    fn receive_sample(&mut self, sample: u8) {
+        self.sample_buffer = Some(sample);
         self.current_sample_address = if self.current_sample_address == 0xFFFF {
             0x8000
         } else {
@@ -158,6 +164,10 @@ impl DeltaModulationChannel {
     }
 
     pub fn tick_cpu(&mut self, bus: &mut CpuBus<'_>) {
+        if let Some(sample) = bus.take_dmc_dma_sample() {
+            self.receive_sample(sample);
+        }
+
         if self.timer_counter == 0 {
             self.clock(bus);
             self.timer_counter = self.timer_period - 1;
@@ -168,7 +178,7 @@ impl DeltaModulationChannel {
 
     fn clock(&mut self, bus: &mut CpuBus<'_>) {
         self.output_unit.clock(&mut self.sample_buffer);
-        self.fill_sample_buffer(bus);
+        self.request_sample(bus);
     }
 
     pub fn sample(&self) -> u8 {
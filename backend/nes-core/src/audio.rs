@@ -1,8 +1,9 @@
 #![allow(clippy::excessive_precision)]
 
+use crate::api::NesTimingMode;
 use bincode::{Decode, Encode};
-use jgenesis_common::audio::SignalResampler;
-use jgenesis_common::frontend::{AudioOutput, TimingMode};
+use jgenesis_common::audio::{ResamplerQuality, SignalResampler};
+use jgenesis_common::frontend::AudioOutput;
 
 // 236.25MHz / 11 / 12
 const NTSC_NES_AUDIO_FREQUENCY: f64 = 1789772.7272727272727273;
@@ -12,6 +13,10 @@ const NTSC_NES_NATIVE_DISPLAY_RATE: f64 = 60.0988;
 const PAL_NES_AUDIO_FREQUENCY: f64 = 1662607.03125;
 const PAL_NES_NATIVE_DISPLAY_RATE: f64 = 50.0070;
 
+// 26.6017125MHz / 15
+const DENDY_NES_AUDIO_FREQUENCY: f64 = 1773447.5;
+const DENDY_NES_NATIVE_DISPLAY_RATE: f64 = 50.0070;
+
 trait TimingModeAudioExt {
     fn nes_audio_frequency(self) -> f64;
 
@@ -20,11 +25,12 @@ trait TimingModeAudioExt {
     fn refresh_rate_multiplier(self) -> f64;
 }
 
-impl TimingModeAudioExt for TimingMode {
+impl TimingModeAudioExt for NesTimingMode {
     fn nes_audio_frequency(self) -> f64 {
         match self {
             Self::Ntsc => NTSC_NES_AUDIO_FREQUENCY,
             Self::Pal => PAL_NES_AUDIO_FREQUENCY,
+            Self::Dendy => DENDY_NES_AUDIO_FREQUENCY,
         }
     }
 
@@ -32,25 +38,32 @@ impl TimingModeAudioExt for TimingMode {
         match self {
             Self::Ntsc => NTSC_NES_NATIVE_DISPLAY_RATE,
             Self::Pal => PAL_NES_NATIVE_DISPLAY_RATE,
+            Self::Dendy => DENDY_NES_NATIVE_DISPLAY_RATE,
         }
     }
 
     fn refresh_rate_multiplier(self) -> f64 {
         match self {
             Self::Ntsc => 1.0,
-            Self::Pal => 50.0 / 60.0,
+            Self::Pal | Self::Dendy => 50.0 / 60.0,
         }
     }
 }
 
 type NesResampler = SignalResampler<93, 0>;
 
-fn new_nes_resampler(timing_mode: TimingMode, apply_refresh_rate_adjustment: bool) -> NesResampler {
+fn new_nes_resampler(
+    timing_mode: NesTimingMode,
+    apply_refresh_rate_adjustment: bool,
+) -> NesResampler {
     let source_frequency = compute_source_frequency(timing_mode, apply_refresh_rate_adjustment);
     NesResampler::new(source_frequency, LPF_COEFFICIENT_0, LPF_COEFFICIENTS, HPF_CHARGE_FACTOR)
 }
 
-fn compute_source_frequency(timing_mode: TimingMode, apply_refresh_rate_adjustment: bool) -> f64 {
+fn compute_source_frequency(
+    timing_mode: NesTimingMode,
+    apply_refresh_rate_adjustment: bool,
+) -> f64 {
     let refresh_rate_multiplier = if apply_refresh_rate_adjustment {
         timing_mode.refresh_rate_multiplier() * 60.0 / timing_mode.nes_native_display_rate()
     } else {
@@ -62,16 +75,23 @@ fn compute_source_frequency(timing_mode: TimingMode, apply_refresh_rate_adjustme
 
 #[derive(Debug, Clone, Encode, Decode)]
 pub struct AudioResampler {
-    timing_mode: TimingMode,
+    timing_mode: NesTimingMode,
     resampler: NesResampler,
 }
 
 impl AudioResampler {
-    pub fn new(timing_mode: TimingMode, apply_refresh_rate_adjustment: bool) -> Self {
-        Self {
-            timing_mode,
-            resampler: new_nes_resampler(timing_mode, apply_refresh_rate_adjustment),
-        }
+    pub fn new(
+        timing_mode: NesTimingMode,
+        apply_refresh_rate_adjustment: bool,
+        quality: ResamplerQuality,
+    ) -> Self {
+        let mut resampler = new_nes_resampler(timing_mode, apply_refresh_rate_adjustment);
+        resampler.set_quality(quality);
+        Self { timing_mode, resampler }
+    }
+
+    pub fn set_quality(&mut self, quality: ResamplerQuality) {
+        self.resampler.set_quality(quality);
     }
 
     pub fn collect_sample(&mut self, sample: f64) {
@@ -35,14 +35,18 @@
 
 pub mod cartridge;
 
+use crate::api::NesTimingMode;
 use crate::bus::cartridge::Mapper;
-use crate::input::{LatchedJoypadState, NesJoypadState};
+use crate::cdl::{ChrRomAccess, CodeDataLog, PrgRomAccess};
+use crate::input::{
+    ExpansionPort, LatchedJoypadState, NesExpansionDevice, NesExpansionInputs, NesJoypadState,
+};
 use bincode::{Decode, Encode};
-use jgenesis_common::frontend::TimingMode;
 use jgenesis_common::num::GetBit;
+use jgenesis_common::rng::{InitialRamState, Rng};
 use jgenesis_proc_macros::PartialClone;
 use mos6502_emu::bus::BusInterface;
-use std::array;
+use std::mem;
 
 pub const CPU_RAM_START: u16 = 0x0000;
 pub const CPU_RAM_END: u16 = 0x1FFF;
@@ -191,17 +195,17 @@ impl PpuRegisters {
         self.ppu_mask.bit(7)
     }
 
-    pub fn emphasize_green(&self, timing_mode: TimingMode) -> bool {
+    pub fn emphasize_green(&self, timing_mode: NesTimingMode) -> bool {
         match timing_mode {
-            TimingMode::Ntsc => self.ppu_mask.bit(6),
-            TimingMode::Pal => self.ppu_mask.bit(5),
+            NesTimingMode::Ntsc => self.ppu_mask.bit(6),
+            NesTimingMode::Pal | NesTimingMode::Dendy => self.ppu_mask.bit(5),
         }
     }
 
-    pub fn emphasize_red(&self, timing_mode: TimingMode) -> bool {
+    pub fn emphasize_red(&self, timing_mode: NesTimingMode) -> bool {
         match timing_mode {
-            TimingMode::Ntsc => self.ppu_mask.bit(5),
-            TimingMode::Pal => self.ppu_mask.bit(6),
+            NesTimingMode::Ntsc => self.ppu_mask.bit(5),
+            NesTimingMode::Pal | NesTimingMode::Dendy => self.ppu_mask.bit(6),
         }
     }
 
@@ -382,26 +386,32 @@ impl IoRegister {
 pub struct IoRegisters {
     data: [u8; 0x18],
     dma_dirty: bool,
+    dmc_dma_request: Option<u16>,
+    dmc_dma_sample: Option<u8>,
     dirty_register: Option<IoRegister>,
     snd_chn_read: bool,
     p1_joypad_state: NesJoypadState,
     p2_joypad_state: NesJoypadState,
     latched_joypad_state: Option<(LatchedJoypadState, LatchedJoypadState)>,
+    expansion_port: ExpansionPort,
 }
 
 impl IoRegisters {
     // All I/O registers are at $40xx, and JOY1/JOY2 leave the highest 3 bits unused
     const IO_OPEN_BUS_BITS: u8 = 0x40;
 
-    fn new() -> Self {
+    fn new(expansion_device: NesExpansionDevice) -> Self {
         Self {
             data: [0; 0x18],
             dma_dirty: false,
+            dmc_dma_request: None,
+            dmc_dma_sample: None,
             dirty_register: None,
             snd_chn_read: false,
             p1_joypad_state: NesJoypadState::new(),
             p2_joypad_state: NesJoypadState::new(),
             latched_joypad_state: None,
+            expansion_port: ExpansionPort::new(expansion_device),
         }
     }
 
@@ -421,20 +431,28 @@ impl IoRegisters {
                 self.data[register.to_relative_address()]
             }
             IoRegister::JOY1 => {
-                if let Some((p1_joypad_state, p2_joypad_state)) = self.latched_joypad_state {
+                let joypad_bit = if let Some((p1_joypad_state, p2_joypad_state)) =
+                    self.latched_joypad_state
+                {
                     self.latched_joypad_state = Some((p1_joypad_state.shift(), p2_joypad_state));
-                    p1_joypad_state.next_bit() | Self::IO_OPEN_BUS_BITS
+                    p1_joypad_state.next_bit()
                 } else {
-                    u8::from(self.p1_joypad_state.a) | Self::IO_OPEN_BUS_BITS
-                }
+                    u8::from(self.p1_joypad_state.a)
+                };
+
+                joypad_bit | self.expansion_port.read_port_1() | Self::IO_OPEN_BUS_BITS
             }
             IoRegister::JOY2 => {
-                if let Some((p1_joypad_state, p2_joypad_state)) = self.latched_joypad_state {
+                let joypad_bit = if let Some((p1_joypad_state, p2_joypad_state)) =
+                    self.latched_joypad_state
+                {
                     self.latched_joypad_state = Some((p1_joypad_state, p2_joypad_state.shift()));
-                    p2_joypad_state.next_bit() | Self::IO_OPEN_BUS_BITS
+                    p2_joypad_state.next_bit()
                 } else {
-                    u8::from(self.p2_joypad_state.a) | Self::IO_OPEN_BUS_BITS
-                }
+                    u8::from(self.p2_joypad_state.a)
+                };
+
+                joypad_bit | self.expansion_port.read_port_2() | Self::IO_OPEN_BUS_BITS
             }
             _ => Self::IO_OPEN_BUS_BITS,
         }
@@ -465,6 +483,8 @@ impl IoRegisters {
                     self.latched_joypad_state =
                         Some((self.p1_joypad_state.latch(), self.p2_joypad_state.latch()));
                 }
+
+                self.expansion_port.write_output(value);
             }
             IoRegister::OAMDMA => {
                 self.dma_dirty = true;
@@ -602,22 +622,32 @@ pub struct Bus {
     ppu_bus_address: u16,
     interrupt_lines: InterruptLines,
     pending_write: Option<PendingCpuWrite>,
+    #[partial_clone(default)]
+    code_data_log: CodeDataLog,
 }
 
 impl Bus {
-    pub(crate) fn from_cartridge(mapper: Mapper) -> Self {
+    pub(crate) fn from_cartridge(
+        mapper: Mapper,
+        expansion_device: NesExpansionDevice,
+        initial_ram_state: InitialRamState,
+        rng: &mut Rng,
+    ) -> Self {
+        let mut cpu_internal_ram = [0; 2048];
+        initial_ram_state.fill(&mut cpu_internal_ram, rng);
+
         Self {
             mapper,
-            // (Somewhat) randomize initial RAM contents
-            cpu_internal_ram: array::from_fn(|_| if rand::random() { 0x00 } else { 0xFF }),
+            cpu_internal_ram,
             ppu_registers: PpuRegisters::new(),
-            io_registers: IoRegisters::new(),
+            io_registers: IoRegisters::new(expansion_device),
             ppu_vram: [0; 2048],
             ppu_palette_ram: [0; 32],
             ppu_oam: [0; 256],
             ppu_bus_address: 0,
             interrupt_lines: InterruptLines::new(),
             pending_write: None,
+            code_data_log: CodeDataLog::new(),
         }
     }
 
@@ -653,6 +683,22 @@ impl Bus {
         };
     }
 
+    pub fn update_expansion_inputs(&mut self, inputs: NesExpansionInputs) {
+        self.io_registers.expansion_port.update_inputs(inputs);
+    }
+
+    pub fn expansion_device(&self) -> NesExpansionDevice {
+        self.io_registers.expansion_port.device()
+    }
+
+    pub fn set_expansion_device(&mut self, device: NesExpansionDevice) {
+        self.io_registers.expansion_port.set_device(device);
+    }
+
+    pub fn set_famicom_microphone(&mut self, microphone: bool) {
+        self.io_registers.expansion_port.set_microphone(microphone);
+    }
+
     pub fn tick(&mut self) {
         self.ppu_registers.tick(&mut self.interrupt_lines);
         self.mapper.tick(self.ppu_bus_address);
@@ -684,6 +730,25 @@ impl Bus {
 
     pub(crate) fn move_rom_from(&mut self, other: &mut Self) {
         self.mapper.move_rom_from(&mut other.mapper);
+        self.code_data_log = mem::take(&mut other.code_data_log);
+    }
+
+    pub(crate) fn code_data_log(&self) -> &CodeDataLog {
+        &self.code_data_log
+    }
+
+    pub(crate) fn code_data_log_mut(&mut self) -> &mut CodeDataLog {
+        &mut self.code_data_log
+    }
+
+    pub(crate) fn set_code_data_logging(&mut self, enabled: bool) {
+        if enabled {
+            let (prg_rom_len, chr_rom_len) = self.mapper.rom_lengths();
+            self.code_data_log.enable(prg_rom_len, chr_rom_len);
+            self.mapper.clear_last_rom_addresses();
+        } else {
+            self.code_data_log.disable();
+        }
     }
 }
 
@@ -693,24 +758,12 @@ pub struct CpuBus<'a>(&'a mut Bus);
 impl<'a> BusInterface for CpuBus<'a> {
     #[inline]
     fn read(&mut self, address: u16) -> u8 {
-        match address {
-            address @ CPU_RAM_START..=CPU_RAM_END => {
-                let ram_address = address & CPU_RAM_MASK;
-                self.0.cpu_internal_ram[ram_address as usize]
-            }
-            address @ CPU_PPU_REGISTERS_START..=CPU_PPU_REGISTERS_END => {
-                let ppu_register_relative_addr =
-                    (address - CPU_PPU_REGISTERS_START) & CPU_PPU_REGISTERS_MASK;
-                self.read_ppu_register_address(ppu_register_relative_addr as usize)
-            }
-            address @ CPU_IO_REGISTERS_START..=CPU_IO_REGISTERS_END => {
-                self.0.io_registers.read_address(address)
-            }
-            _address @ CPU_IO_TEST_MODE_START..=CPU_IO_TEST_MODE_END => cpu_open_bus(address),
-            address @ CPU_CARTRIDGE_START..=CPU_CARTRIDGE_END => {
-                self.0.mapper.read_cpu_address(address)
-            }
-        }
+        self.read_logged(address, PrgRomAccess::Data)
+    }
+
+    #[inline]
+    fn read_code(&mut self, address: u16) -> u8 {
+        self.read_logged(address, PrgRomAccess::Code)
     }
 
     #[inline]
@@ -738,6 +791,42 @@ impl<'a> BusInterface for CpuBus<'a> {
 }
 
 impl<'a> CpuBus<'a> {
+    #[inline]
+    fn read_logged(&mut self, address: u16, access: PrgRomAccess) -> u8 {
+        match address {
+            address @ CPU_RAM_START..=CPU_RAM_END => {
+                let ram_address = address & CPU_RAM_MASK;
+                self.0.cpu_internal_ram[ram_address as usize]
+            }
+            address @ CPU_PPU_REGISTERS_START..=CPU_PPU_REGISTERS_END => {
+                let ppu_register_relative_addr =
+                    (address - CPU_PPU_REGISTERS_START) & CPU_PPU_REGISTERS_MASK;
+                self.read_ppu_register_address(ppu_register_relative_addr as usize)
+            }
+            address @ CPU_IO_REGISTERS_START..=CPU_IO_REGISTERS_END => {
+                self.0.io_registers.read_address(address)
+            }
+            _address @ CPU_IO_TEST_MODE_START..=CPU_IO_TEST_MODE_END => cpu_open_bus(address),
+            address @ CPU_CARTRIDGE_START..=CPU_CARTRIDGE_END => {
+                if !self.0.code_data_log.is_enabled() {
+                    return self.0.mapper.read_cpu_address(address);
+                }
+
+                self.0.mapper.clear_last_rom_addresses();
+                let value = self.0.mapper.read_cpu_address(address);
+                if let Some(rom_address) = self.0.mapper.take_last_prg_rom_address() {
+                    self.0.code_data_log.log_prg_rom(rom_address, address, access);
+                }
+                value
+            }
+        }
+    }
+
+    /// Read a DMC sample byte. This is identical to a normal read except for code/data logging.
+    pub fn read_dmc_sample(&mut self, address: u16) -> u8 {
+        self.read_logged(address, PrgRomAccess::PcmSample)
+    }
+
     fn apply_write(&mut self, address: u16, value: u8) {
         match address {
             address @ CPU_RAM_START..=CPU_RAM_END => {
@@ -810,7 +899,7 @@ impl<'a> CpuBus<'a> {
                 self.0.mapper.about_to_access_ppu_data();
 
                 self.0.ppu_registers.ppu_data_buffer =
-                    self.0.ppu().read_address(buffer_read_address);
+                    self.0.ppu().read_address_logged(buffer_read_address, Some(ChrRomAccess::Read));
                 // Reset the bus address in case the buffer read address was different from the
                 // actual address
                 self.0.ppu_bus_address = address;
@@ -884,6 +973,30 @@ impl<'a> CpuBus<'a> {
         self.0.io_registers.data[IoRegister::OAMDMA.to_relative_address()]
     }
 
+    /// Request a DMC DMA read from the given address. The DMA unit will halt the CPU and deliver
+    /// the sample byte through [`Self::take_dmc_dma_sample`] a few cycles later.
+    pub fn request_dmc_dma(&mut self, address: u16) {
+        self.0.io_registers.dmc_dma_request = Some(address);
+    }
+
+    pub fn cancel_dmc_dma(&mut self) {
+        self.0.io_registers.dmc_dma_request = None;
+        self.0.io_registers.dmc_dma_sample = None;
+    }
+
+    pub fn dmc_dma_request(&self) -> Option<u16> {
+        self.0.io_registers.dmc_dma_request
+    }
+
+    pub fn complete_dmc_dma(&mut self, sample: u8) {
+        self.0.io_registers.dmc_dma_request = None;
+        self.0.io_registers.dmc_dma_sample = Some(sample);
+    }
+
+    pub fn take_dmc_dma_sample(&mut self) -> Option<u8> {
+        self.0.io_registers.dmc_dma_sample.take()
+    }
+
     pub fn get_io_registers_mut(&mut self) -> &mut IoRegisters {
         &mut self.0.io_registers
     }
@@ -898,13 +1011,34 @@ pub struct PpuBus<'a>(&'a mut Bus);
 
 impl<'a> PpuBus<'a> {
     pub fn read_address(&mut self, address: u16) -> u8 {
+        self.read_address_logged(address, Some(ChrRomAccess::Drawn))
+    }
+
+    /// Read an address without recording the access in the code/data log. Intended for debug
+    /// views.
+    pub fn debug_read_address(&mut self, address: u16) -> u8 {
+        self.read_address_logged(address, None)
+    }
+
+    fn read_address_logged(&mut self, address: u16, access: Option<ChrRomAccess>) -> u8 {
         // PPU bus only has 14-bit addressing
         let address = address & 0x3FFF;
 
         self.0.ppu_bus_address = address;
 
         match address {
-            0x0000..=0x3EFF => self.0.mapper.read_ppu_address(address, &self.0.ppu_vram),
+            0x0000..=0x3EFF => {
+                let Some(access) = access.filter(|_| self.0.code_data_log.is_enabled()) else {
+                    return self.0.mapper.read_ppu_address(address, &self.0.ppu_vram);
+                };
+
+                self.0.mapper.clear_last_rom_addresses();
+                let value = self.0.mapper.read_ppu_address(address, &self.0.ppu_vram);
+                if let Some(rom_address) = self.0.mapper.take_last_chr_rom_address() {
+                    self.0.code_data_log.log_chr_rom(rom_address, access);
+                }
+                value
+            }
             0x3F00..=0x3FFF => {
                 let palette_relative_addr = map_palette_address(address);
 
@@ -975,14 +1109,33 @@ fn map_palette_address(address: u16) -> usize {
 #[cfg(test)]
 mod tests {
     use crate::bus::{cartridge, Bus};
+    use crate::input::NesExpansionDevice;
+    use jgenesis_common::rng::{InitialRamState, Rng};
 
     #[test]
     fn randomized_ram_on_startup() {
         let mapper = cartridge::new_mmc1(vec![0; 32768]);
-        let bus1 = Bus::from_cartridge(mapper.clone());
-        let bus2 = Bus::from_cartridge(mapper);
+        let bus1 = Bus::from_cartridge(
+            mapper.clone(),
+            NesExpansionDevice::None,
+            InitialRamState::Random,
+            &mut Rng::new(1),
+        );
+        let bus2 = Bus::from_cartridge(
+            mapper.clone(),
+            NesExpansionDevice::None,
+            InitialRamState::Random,
+            &mut Rng::new(2),
+        );
+        let bus3 = Bus::from_cartridge(
+            mapper,
+            NesExpansionDevice::None,
+            InitialRamState::Random,
+            &mut Rng::new(1),
+        );
 
         assert_ne!(bus1.cpu_internal_ram, bus2.cpu_internal_ram);
+        assert_eq!(bus1.cpu_internal_ram, bus3.cpu_internal_ram);
     }
 }
 
@@ -1,93 +1,53 @@
 mod mappers;
 
+use crate::api::NesTimingMode;
 use crate::bus::cartridge::mappers::{
     Action52, Axrom, BandaiFcg, Bnrom, ChrType, Cnrom, Gxrom, Mmc1, Mmc2, Mmc3, Mmc5, Namco163,
     Namco175, NametableMirroring, Nrom, PpuMapResult, Sunsoft, Uxrom, Vrc4, Vrc6, Vrc7,
 };
-use bincode::de::{BorrowDecoder, Decoder};
-use bincode::enc::Encoder;
-use bincode::error::{DecodeError, EncodeError};
-use bincode::{BorrowDecode, Decode, Encode};
-use jgenesis_common::frontend::{PartialClone, TimingMode};
+use crate::input::NesExpansionDevice;
+use bincode::{Decode, Encode};
+use jgenesis_common::frontend::PartialClone;
 use jgenesis_common::num::GetBit;
 use jgenesis_proc_macros::MatchEachVariantMacro;
+use jgenesis_state::SaveState;
+use std::cell::Cell;
 use std::fmt::{Display, Formatter};
 use std::{io, mem};
 use thiserror::Error;
 
+pub use mappers::BarcodeError;
+
 #[cfg(test)]
 pub(crate) use mappers::new_mmc1;
 
-#[derive(Debug, Clone, PartialClone)]
+// ROM bytes are not serialized as part of save states
+#[derive(Debug, Clone, PartialClone, SaveState)]
 struct Cartridge {
-    timing_mode: TimingMode,
+    timing_mode: NesTimingMode,
+    default_expansion_device: Option<NesExpansionDevice>,
     #[partial_clone(default)]
+    #[save_state(skip)]
     prg_rom: Vec<u8>,
     prg_ram: Vec<u8>,
     has_ram_battery: bool,
     prg_ram_dirty_bit: bool,
     #[partial_clone(default)]
+    #[save_state(skip)]
     chr_rom: Vec<u8>,
     chr_ram: Vec<u8>,
-}
-
-// Encode and Decode are implemented explicitly instead of using derive in order to avoid
-// serializing ROM bytes as part of save states
-impl Encode for Cartridge {
-    fn encode<E: Encoder>(&self, encoder: &mut E) -> Result<(), EncodeError> {
-        self.timing_mode.encode(encoder)?;
-        self.prg_ram.encode(encoder)?;
-        self.has_ram_battery.encode(encoder)?;
-        self.prg_ram_dirty_bit.encode(encoder)?;
-        self.chr_ram.encode(encoder)?;
-
-        Ok(())
-    }
-}
-
-impl Decode for Cartridge {
-    fn decode<D: Decoder>(decoder: &mut D) -> Result<Self, DecodeError> {
-        let timing_mode = Decode::decode(decoder)?;
-        let prg_ram = Decode::decode(decoder)?;
-        let has_ram_battery = Decode::decode(decoder)?;
-        let prg_ram_dirty_bit = Decode::decode(decoder)?;
-        let chr_ram = Decode::decode(decoder)?;
-
-        Ok(Self {
-            timing_mode,
-            prg_rom: vec![],
-            prg_ram,
-            has_ram_battery,
-            prg_ram_dirty_bit,
-            chr_rom: vec![],
-            chr_ram,
-        })
-    }
-}
-
-impl<'de> BorrowDecode<'de> for Cartridge {
-    fn borrow_decode<D: BorrowDecoder<'de>>(decoder: &mut D) -> Result<Self, DecodeError> {
-        let timing_mode = BorrowDecode::borrow_decode(decoder)?;
-        let prg_ram = BorrowDecode::borrow_decode(decoder)?;
-        let has_ram_battery = BorrowDecode::borrow_decode(decoder)?;
-        let prg_ram_dirty_bit = BorrowDecode::borrow_decode(decoder)?;
-        let chr_ram = BorrowDecode::borrow_decode(decoder)?;
-
-        Ok(Self {
-            timing_mode,
-            prg_rom: vec![],
-            prg_ram,
-            has_ram_battery,
-            prg_ram_dirty_bit,
-            chr_rom: vec![],
-            chr_ram,
-        })
-    }
+    // ROM addresses of the most recent PRG/CHR ROM reads, used for code/data logging
+    #[save_state(skip)]
+    last_prg_rom_address: Cell<Option<u32>>,
+    #[save_state(skip)]
+    last_chr_rom_address: Cell<Option<u32>>,
 }
 
 impl Cartridge {
     fn get_prg_rom(&self, address: u32) -> u8 {
-        self.prg_rom[(address as usize) & (self.prg_rom.len() - 1)]
+        let rom_address = (address as usize) & (self.prg_rom.len() - 1);
+        self.last_prg_rom_address.set(Some(rom_address as u32));
+        self.prg_rom[rom_address]
     }
 
     fn get_prg_ram(&self, address: u32) -> u8 {
@@ -108,17 +68,32 @@ impl Cartridge {
         }
     }
 
+    // CHR ROM and CHR RAM can both be empty with a malformed header, e.g. an NES 2.0 header that
+    // specifies no CHR ROM and no CHR RAM; treat missing CHR memory the same as missing PRG RAM
+
     fn get_chr_rom(&self, address: u32) -> u8 {
-        self.chr_rom[(address as usize) & (self.chr_rom.len() - 1)]
+        if self.chr_rom.is_empty() {
+            return 0xFF;
+        }
+
+        let rom_address = (address as usize) & (self.chr_rom.len() - 1);
+        self.last_chr_rom_address.set(Some(rom_address as u32));
+        self.chr_rom[rom_address]
     }
 
     fn get_chr_ram(&self, address: u32) -> u8 {
+        if self.chr_ram.is_empty() {
+            return 0xFF;
+        }
+
         self.chr_ram[(address as usize) & (self.chr_ram.len() - 1)]
     }
 
     fn set_chr_ram(&mut self, address: u32, value: u8) {
-        let chr_ram_len = self.chr_ram.len();
-        self.chr_ram[(address as usize) & (chr_ram_len - 1)] = value;
+        if !self.chr_ram.is_empty() {
+            let chr_ram_len = self.chr_ram.len();
+            self.chr_ram[(address as usize) & (chr_ram_len - 1)] = value;
+        }
     }
 
     fn move_rom_from(&mut self, other: &mut Self) {
@@ -344,11 +319,48 @@ impl Mapper {
         match_each_variant!(self, mapper => &mapper.cartridge.prg_ram)
     }
 
-    /// Retrieve the timing mode of the cartridge (NTSC/PAL).
-    pub(crate) fn timing_mode(&self) -> TimingMode {
+    /// Retrieve the lengths of PRG ROM and CHR ROM, in bytes. CHR ROM length is 0 for boards with
+    /// CHR RAM.
+    pub(crate) fn rom_lengths(&self) -> (usize, usize) {
+        match_each_variant!(self, mapper => (mapper.cartridge.prg_rom.len(), mapper.cartridge.chr_rom.len()))
+    }
+
+    /// Forget the addresses of the most recent PRG/CHR ROM reads.
+    pub(crate) fn clear_last_rom_addresses(&self) {
+        match_each_variant!(self, mapper => {
+            mapper.cartridge.last_prg_rom_address.set(None);
+            mapper.cartridge.last_chr_rom_address.set(None);
+        });
+    }
+
+    /// Take the PRG ROM address of the most recent PRG ROM read, if any.
+    pub(crate) fn take_last_prg_rom_address(&self) -> Option<u32> {
+        match_each_variant!(self, mapper => mapper.cartridge.last_prg_rom_address.take())
+    }
+
+    /// Take the CHR ROM address of the most recent CHR ROM read, if any.
+    pub(crate) fn take_last_chr_rom_address(&self) -> Option<u32> {
+        match_each_variant!(self, mapper => mapper.cartridge.last_chr_rom_address.take())
+    }
+
+    /// Retrieve the timing mode of the cartridge (NTSC/PAL/Dendy).
+    pub(crate) fn timing_mode(&self) -> NesTimingMode {
         match_each_variant!(self, mapper => mapper.cartridge.timing_mode)
     }
 
+    /// Retrieve the expansion port device specified in the cartridge header, if any.
+    pub(crate) fn default_expansion_device(&self) -> Option<NesExpansionDevice> {
+        match_each_variant!(self, mapper => mapper.cartridge.default_expansion_device)
+    }
+
+    /// Scan a barcode using the board's barcode reader. Only the Datach board has a barcode reader.
+    pub(crate) fn scan_barcode(&mut self, barcode: &str) -> Result<(), BarcodeError> {
+        match self {
+            Self::BandaiFcg(bandai_fcg) => bandai_fcg.scan_barcode(barcode),
+            _ => Err(BarcodeError::NoBarcodeReader),
+        }
+    }
+
     /// If the board has expansion audio, generate an audio sample and mix it with the mixed APU
     /// sample.
     ///
@@ -387,12 +399,12 @@ pub enum CartridgeFileError {
     },
     #[error("invalid or unsupported file format")]
     Format,
+    #[error("file is {actual_len} bytes, but header specifies at least {expected_len} bytes")]
+    Truncated { expected_len: usize, actual_len: usize },
     #[error("unsupported mapper: {mapper_number}")]
     UnsupportedMapper { mapper_number: u16 },
     #[error("cartridge header specifies both volatile and non-volatile PRG RAM")]
     MultiplePrgRamTypes,
-    #[error("unsupported timing mode byte: {byte}")]
-    UnsupportedTimingMode { byte: u8 },
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -414,7 +426,8 @@ impl Display for FileFormat {
 struct INesHeader {
     mapper_number: u16,
     sub_mapper_number: u8,
-    timing_mode: TimingMode,
+    timing_mode: NesTimingMode,
+    default_expansion_device: Option<NesExpansionDevice>,
     prg_rom_size: u32,
     prg_ram_size: u32,
     chr_rom_size: u32,
@@ -428,7 +441,7 @@ struct INesHeader {
 
 impl INesHeader {
     fn parse_from_file(file_bytes: &[u8]) -> Result<INesHeader, CartridgeFileError> {
-        let header = &file_bytes[..16];
+        let header = file_bytes.get(..16).ok_or(CartridgeFileError::Format)?;
 
         // All iNES headers should begin with this 4-byte sequence, which is "NES" followed by the
         // character that MS-DOS used for EOF
@@ -469,25 +482,27 @@ impl INesHeader {
             FileFormat::Nes2Point0 => {
                 let timing_mode_byte = header[12] & 0x03;
                 match timing_mode_byte {
-                    0x00 | 0x02 => TimingMode::Ntsc,
-                    0x01 => TimingMode::Pal,
-                    0x03 => {
-                        return Err(CartridgeFileError::UnsupportedTimingMode {
-                            byte: timing_mode_byte,
-                        });
-                    }
+                    // 0x02 indicates a multi-region cartridge; default to NTSC
+                    0x00 | 0x02 => NesTimingMode::Ntsc,
+                    0x01 => NesTimingMode::Pal,
+                    0x03 => NesTimingMode::Dendy,
                     _ => unreachable!("value & 0x03 should always be 0x00/0x01/0x02/0x03"),
                 }
             }
             FileFormat::INes => {
                 if header[0].bit(0) {
-                    TimingMode::Pal
+                    NesTimingMode::Pal
                 } else {
-                    TimingMode::Ntsc
+                    NesTimingMode::Ntsc
                 }
             }
         };
 
+        let default_expansion_device = match format {
+            FileFormat::Nes2Point0 => NesExpansionDevice::from_nes2_header_byte(header[15]),
+            FileFormat::INes => None,
+        };
+
         let prg_ram_size = determine_prg_ram_size(header, mapper_number, format);
 
         let chr_ram_size = match (chr_type, format) {
@@ -503,6 +518,7 @@ impl INesHeader {
             mapper_number,
             sub_mapper_number,
             timing_mode,
+            default_expansion_device,
             prg_rom_size,
             prg_ram_size,
             chr_rom_size,
@@ -557,7 +573,7 @@ fn determine_prg_ram_size(header: &[u8], mapper_number: u16, format: FileFormat)
 pub(crate) fn from_ines_file(
     file_bytes: &[u8],
     sav_bytes: Option<Vec<u8>>,
-    forced_timing_mode: Option<TimingMode>,
+    forced_timing_mode: Option<NesTimingMode>,
 ) -> Result<Mapper, CartridgeFileError> {
     let header = INesHeader::parse_from_file(file_bytes)?;
 
@@ -566,6 +582,17 @@ pub(crate) fn from_ines_file(
     let prg_rom_end_address = prg_rom_start_address + header.prg_rom_size as usize;
     let chr_rom_end_address = prg_rom_end_address + header.chr_rom_size as usize;
 
+    if header.prg_rom_size == 0 {
+        return Err(CartridgeFileError::Format);
+    }
+
+    if file_bytes.len() < chr_rom_end_address {
+        return Err(CartridgeFileError::Truncated {
+            expected_len: chr_rom_end_address,
+            actual_len: file_bytes.len(),
+        });
+    }
+
     let prg_rom = Vec::from(&file_bytes[prg_rom_start_address..prg_rom_end_address]);
     let chr_rom = Vec::from(&file_bytes[prg_rom_end_address..chr_rom_end_address]);
 
@@ -589,12 +616,15 @@ pub(crate) fn from_ines_file(
 
     let cartridge = Cartridge {
         timing_mode,
+        default_expansion_device: header.default_expansion_device,
         prg_rom,
         prg_ram,
         has_ram_battery: header.has_battery,
         prg_ram_dirty_bit: header.has_battery,
         chr_rom,
         chr_ram: vec![0; header.chr_ram_size as usize],
+        last_prg_rom_address: Cell::new(None),
+        last_chr_rom_address: Cell::new(None),
     };
 
     let chr_size = match header.chr_type {
@@ -641,7 +671,7 @@ pub(crate) fn from_ines_file(
             cartridge,
             data: Gxrom::new(header.mapper_number, header.nametable_mirroring),
         }),
-        16 | 153 | 159 => Mapper::BandaiFcg(MapperImpl {
+        16 | 153 | 157 | 159 => Mapper::BandaiFcg(MapperImpl {
             cartridge,
             data: BandaiFcg::new(
                 header.mapper_number,
@@ -16,6 +16,7 @@ use bincode::{Decode, Encode};
 use crate::bus;
 pub(crate) use action52::Action52;
 pub(crate) use bandai::BandaiFcg;
+pub use bandai::BarcodeError;
 pub(crate) use konami::{Vrc4, Vrc6, Vrc7};
 pub(crate) use mmc1::Mmc1;
 pub(crate) use mmc2::Mmc2;
@@ -171,17 +172,21 @@ impl BankSizeKb {
 
 #[cfg(test)]
 pub(crate) fn new_mmc1(prg_rom: Vec<u8>) -> super::Mapper {
-    use super::{Mapper, MapperImpl, TimingMode};
+    use super::{Mapper, MapperImpl, NesTimingMode};
+    use std::cell::Cell;
 
     Mapper::Mmc1(MapperImpl {
         cartridge: Cartridge {
-            timing_mode: TimingMode::Ntsc,
+            timing_mode: NesTimingMode::Ntsc,
+            default_expansion_device: None,
             prg_rom,
             prg_ram: vec![0; 8192],
             has_ram_battery: false,
             prg_ram_dirty_bit: false,
             chr_rom: vec![0; 8192],
             chr_ram: Vec::new(),
+            last_prg_rom_address: Cell::default(),
+            last_chr_rom_address: Cell::default(),
         },
         data: Mmc1::new(ChrType::ROM),
     })
@@ -1,8 +1,12 @@
-//! Code for Bandai's FCG boards (iNES mappers 16 + 153 + 159).
+//! Code for Bandai's FCG boards (iNES mappers 16 + 153 + 157 + 159).
 
+mod datach;
 mod eeprom;
 
+pub use datach::BarcodeError;
+
 use crate::bus;
+use crate::bus::cartridge::mappers::bandai::datach::BarcodeReader;
 use crate::bus::cartridge::mappers::bandai::eeprom::{X24C01Chip, X24C02Chip};
 use crate::bus::cartridge::mappers::{BankSizeKb, ChrType, NametableMirroring, PpuMapResult};
 use crate::bus::cartridge::{HasBasicPpuMapping, MapperImpl};
@@ -22,6 +26,9 @@ enum MemoryVariant {
 enum Variant {
     Fcg,
     Lz93D50(MemoryVariant),
+    // LZ93D50 with CHR RAM, an internal 24C02 EEPROM, an optional 24C01 EEPROM in the game
+    // cartridge, and a barcode reader
+    Datach,
     Unknown,
 }
 
@@ -47,7 +54,7 @@ impl IrqCounter {
     fn handle_control_write(&mut self, value: u8) {
         self.enabled = value.bit(0);
 
-        if matches!(self.variant, Variant::Lz93D50(_) | Variant::Unknown) {
+        if matches!(self.variant, Variant::Lz93D50(_) | Variant::Datach | Variant::Unknown) {
             self.counter = self.latch;
         }
     }
@@ -55,7 +62,7 @@ impl IrqCounter {
     fn update_counter(&mut self, update: IrqCounterUpdate, value: u8) {
         let field_to_update = match self.variant {
             Variant::Fcg => &mut self.counter,
-            Variant::Lz93D50(_) | Variant::Unknown => &mut self.latch,
+            Variant::Lz93D50(_) | Variant::Datach | Variant::Unknown => &mut self.latch,
         };
 
         *field_to_update = match update {
@@ -81,6 +88,24 @@ enum Eeprom {
     X24C02(X24C02Chip),
 }
 
+#[derive(Debug, Clone, Encode, Decode)]
+struct Datach {
+    external_eeprom: X24C01Chip,
+    barcode_reader: BarcodeReader,
+    // Internal EEPROM contents followed by external EEPROM contents
+    save_buffer: Vec<u8>,
+}
+
+impl Datach {
+    const INTERNAL_EEPROM_LEN: usize = 256;
+
+    fn update_save_buffer(&mut self, internal_eeprom: &X24C02Chip) {
+        self.save_buffer.clear();
+        self.save_buffer.extend_from_slice(internal_eeprom.get_memory());
+        self.save_buffer.extend_from_slice(self.external_eeprom.get_memory());
+    }
+}
+
 #[derive(Debug, Clone, Encode, Decode)]
 pub(crate) struct BandaiFcg {
     variant: Variant,
@@ -92,6 +117,7 @@ pub(crate) struct BandaiFcg {
     ram_enabled: bool,
     irq: IrqCounter,
     eeprom: Option<Eeprom>,
+    datach: Option<Datach>,
 }
 
 impl BandaiFcg {
@@ -111,10 +137,15 @@ impl BandaiFcg {
             }
             (16, _) => Variant::Unknown,
             (153, _) => Variant::Lz93D50(MemoryVariant::RAM),
+            (157, _) => Variant::Datach,
             (159, _) => Variant::Lz93D50(MemoryVariant::X24C01),
             _ => panic!("unsupported Bandai mapper number: {mapper_number}"),
         };
 
+        if variant == Variant::Datach {
+            return Self::new_datach(chr_type, sav_bytes);
+        }
+
         let eeprom = match variant {
             Variant::Lz93D50(MemoryVariant::X24C01) => {
                 Some(Eeprom::X24C01(X24C01Chip::new(sav_bytes)))
@@ -137,6 +168,41 @@ impl BandaiFcg {
             ram_enabled: false,
             irq: IrqCounter::new(variant),
             eeprom,
+            datach: None,
+        }
+    }
+
+    fn new_datach(chr_type: ChrType, sav_bytes: Option<&Vec<u8>>) -> Self {
+        // Saves contain both EEPROMs back-to-back
+        let (internal_sav, external_sav) = match sav_bytes {
+            Some(sav_bytes) if sav_bytes.len() > Datach::INTERNAL_EEPROM_LEN => {
+                let (internal, external) = sav_bytes.split_at(Datach::INTERNAL_EEPROM_LEN);
+                (Some(internal.to_vec()), Some(external.to_vec()))
+            }
+            _ => (None, None),
+        };
+
+        let internal_eeprom = X24C02Chip::new(internal_sav.as_ref());
+        let mut datach = Datach {
+            external_eeprom: X24C01Chip::new(external_sav.as_ref()),
+            barcode_reader: BarcodeReader::new(),
+            save_buffer: Vec::new(),
+        };
+        datach.update_save_buffer(&internal_eeprom);
+
+        log::info!("Bandai FCG variant: {:?}", Variant::Datach);
+
+        Self {
+            variant: Variant::Datach,
+            chr_type,
+            prg_bank: 0,
+            prg_256kb_bank: 0,
+            chr_banks: [0; 8],
+            nametable_mirroring: NametableMirroring::Vertical,
+            ram_enabled: false,
+            irq: IrqCounter::new(Variant::Datach),
+            eeprom: Some(Eeprom::X24C02(internal_eeprom)),
+            datach: Some(datach),
         }
     }
 }
@@ -165,6 +231,18 @@ impl MapperImpl<BandaiFcg> {
                     Some(Eeprom::X24C02(chip)) => eeprom_read(address, chip.handle_read()),
                     None => bus::cpu_open_bus(address),
                 },
+                Variant::Datach => match (&self.data.eeprom, &self.data.datach) {
+                    (Some(Eeprom::X24C02(internal_eeprom)), Some(datach)) => {
+                        // EEPROM data lines are open drain, so either chip can pull the line low
+                        let eeprom_data =
+                            internal_eeprom.handle_read() && datach.external_eeprom.handle_read();
+                        let barcode = datach.barcode_reader.output();
+                        (bus::cpu_open_bus(address) & 0xE7)
+                            | (u8::from(eeprom_data) << 4)
+                            | (u8::from(barcode) << 3)
+                    }
+                    _ => bus::cpu_open_bus(address),
+                },
             },
             0x8000..=0xBFFF => {
                 let prg_rom_addr =
@@ -194,7 +272,7 @@ impl MapperImpl<BandaiFcg> {
         match (self.data.variant, address) {
             (_, 0x0000..=0x401F) => panic!("invalid CPU map address: {address:04X}"),
             (Variant::Fcg | Variant::Unknown, 0x6000..=0x7FFF)
-            | (Variant::Lz93D50(_) | Variant::Unknown, 0x8000..=0xFFFF) => {
+            | (Variant::Lz93D50(_) | Variant::Datach | Variant::Unknown, 0x8000..=0xFFFF) => {
                 match (self.data.variant, address & 0x000F) {
                     (Variant::Lz93D50(MemoryVariant::RAM), 0x0000..=0x0003) => {
                         self.data.prg_256kb_bank = value & 0x01;
@@ -210,6 +288,11 @@ impl MapperImpl<BandaiFcg> {
                         let chr_bank_index = address & 0x0007;
                         self.data.chr_banks[chr_bank_index as usize] = value;
                     }
+                    (Variant::Datach, 0x0000..=0x0003) => {
+                        if let Some(datach) = &mut self.data.datach {
+                            datach.external_eeprom.write_clock(value.bit(3));
+                        }
+                    }
                     (_, 0x0008) => {
                         self.data.prg_bank = value & 0x0F;
                     }
@@ -231,6 +314,15 @@ impl MapperImpl<BandaiFcg> {
                     (_, 0x000C) => {
                         self.data.irq.update_counter(IrqCounterUpdate::HighByte, value);
                     }
+                    (Variant::Datach, 0x000D) => {
+                        if let Some(Eeprom::X24C02(internal_eeprom)) = &mut self.data.eeprom {
+                            internal_eeprom.handle_write(value);
+                        }
+
+                        if let Some(datach) = &mut self.data.datach {
+                            datach.external_eeprom.write_data(value.bit(6));
+                        }
+                    }
                     (
                         Variant::Lz93D50(MemoryVariant::X24C01 | MemoryVariant::X24C02)
                         | Variant::Unknown,
@@ -261,6 +353,10 @@ impl MapperImpl<BandaiFcg> {
 
     pub(crate) fn tick_cpu(&mut self) {
         self.data.irq.tick_cpu();
+
+        if let Some(datach) = &mut self.data.datach {
+            datach.barcode_reader.tick_cpu();
+        }
     }
 
     pub(crate) fn interrupt_flag(&self) -> bool {
@@ -268,23 +364,48 @@ impl MapperImpl<BandaiFcg> {
     }
 
     pub(crate) fn get_and_clear_eeprom_dirty_bit(&mut self) -> bool {
-        self.data.eeprom.as_mut().is_some_and(|eeprom| match eeprom {
+        let dirty = self.data.eeprom.as_mut().is_some_and(|eeprom| match eeprom {
             Eeprom::X24C01(chip) => chip.get_and_clear_dirty_bit(),
             Eeprom::X24C02(chip) => chip.get_and_clear_dirty_bit(),
-        })
+        });
+
+        let (Some(datach), Some(Eeprom::X24C02(internal_eeprom))) =
+            (&mut self.data.datach, &self.data.eeprom)
+        else {
+            return dirty;
+        };
+
+        let external_dirty = datach.external_eeprom.get_and_clear_dirty_bit();
+        if dirty || external_dirty {
+            datach.update_save_buffer(internal_eeprom);
+        }
+
+        dirty || external_dirty
     }
 
     pub(crate) fn eeprom(&self) -> Option<&[u8]> {
+        if let Some(datach) = &self.data.datach {
+            return Some(&datach.save_buffer);
+        }
+
         self.data.eeprom.as_ref().map(|eeprom| match eeprom {
             Eeprom::X24C01(chip) => chip.get_memory(),
             Eeprom::X24C02(chip) => chip.get_memory(),
         })
     }
 
+    pub(crate) fn scan_barcode(&mut self, barcode: &str) -> Result<(), BarcodeError> {
+        match &mut self.data.datach {
+            Some(datach) => datach.barcode_reader.scan(barcode),
+            None => Err(BarcodeError::NoBarcodeReader),
+        }
+    }
+
     pub(crate) fn name(&self) -> &'static str {
         match self.data.variant {
             Variant::Fcg => "Bandai FCG-1 / FCG-2",
             Variant::Lz93D50(_) => "Bandai LZ93D50",
+            Variant::Datach => "Bandai Datach",
             Variant::Unknown => "Bandai FCG",
         }
     }
@@ -294,7 +415,9 @@ impl HasBasicPpuMapping for MapperImpl<BandaiFcg> {
     fn map_ppu_address(&self, address: u16) -> PpuMapResult {
         match address {
             0x0000..=0x1FFF => match self.data.variant {
-                Variant::Lz93D50(MemoryVariant::RAM) => PpuMapResult::ChrRAM(address.into()),
+                Variant::Lz93D50(MemoryVariant::RAM) | Variant::Datach => {
+                    PpuMapResult::ChrRAM(address.into())
+                }
                 _ => {
                     let chr_bank_index = address / 0x0400;
                     let chr_bank_number = self.data.chr_banks[chr_bank_index as usize];
@@ -0,0 +1,179 @@
+//! Code for the barcode reader built into the Datach Joint ROM System (iNES mapper 157).
+//!
+//! Scanned barcodes are streamed to the CPU one module (bar or space) at a time through bit 3 of
+//! $6000-$7FFF reads. Both EAN-13 and EAN-8 barcodes are supported.
+
+use bincode::{Decode, Encode};
+use thiserror::Error;
+
+// Approximately how long the reader outputs each module, in CPU cycles
+const MODULE_CPU_CYCLES: u32 = 1000;
+
+// Number of blank modules sent before and after the barcode
+const LEADING_QUIET_ZONE: usize = 33;
+const TRAILING_QUIET_ZONE: usize = 32;
+
+// EAN L-codes (odd parity) for digits 0-9, with 1 bits representing bars. R-codes are the bitwise
+// complement of L-codes, and G-codes (even parity) are R-codes in reverse order
+const L_CODES: [u8; 10] = [
+    0b0001101, 0b0011001, 0b0010011, 0b0111101, 0b0100011, 0b0110001, 0b0101111, 0b0111011,
+    0b0110111, 0b0001011,
+];
+
+// EAN-13 left half parity patterns, indexed by the first digit; 1 bits indicate G-codes
+const EAN13_PARITY: [u8; 10] = [
+    0b000000, 0b001011, 0b001101, 0b001110, 0b010011, 0b011001, 0b011100, 0b010101, 0b010110,
+    0b011010,
+];
+
+#[derive(Debug, Error)]
+pub enum BarcodeError {
+    #[error("Cartridge does not have a barcode reader")]
+    NoBarcodeReader,
+    #[error("Barcode must be 8 or 13 digits; was {0} characters")]
+    InvalidLength(usize),
+    #[error("Barcode contains non-digit character '{0}'")]
+    InvalidCharacter(char),
+}
+
+fn r_code(digit: u8) -> u8 {
+    !L_CODES[digit as usize] & 0x7F
+}
+
+fn g_code(digit: u8) -> u8 {
+    r_code(digit).reverse_bits() >> 1
+}
+
+fn push_code(modules: &mut Vec<bool>, code: u8) {
+    modules.extend((0..7).rev().map(|i| code & (1 << i) != 0));
+}
+
+fn push_guard(modules: &mut Vec<bool>, guard: &[bool]) {
+    modules.extend_from_slice(guard);
+}
+
+const END_GUARD: [bool; 3] = [true, false, true];
+const MIDDLE_GUARD: [bool; 5] = [false, true, false, true, false];
+
+fn encode_barcode(barcode: &str) -> Result<Vec<bool>, BarcodeError> {
+    let digits: Vec<u8> = barcode
+        .chars()
+        .map(|c| c.to_digit(10).map(|digit| digit as u8).ok_or(BarcodeError::InvalidCharacter(c)))
+        .collect::<Result<_, _>>()?;
+
+    let mut modules = vec![false; LEADING_QUIET_ZONE];
+    push_guard(&mut modules, &END_GUARD);
+
+    match digits.len() {
+        13 => {
+            let parity = EAN13_PARITY[digits[0] as usize];
+            for (i, &digit) in digits[1..7].iter().enumerate() {
+                let code = if parity & (1 << (5 - i)) != 0 {
+                    g_code(digit)
+                } else {
+                    L_CODES[digit as usize]
+                };
+                push_code(&mut modules, code);
+            }
+
+            push_guard(&mut modules, &MIDDLE_GUARD);
+
+            for &digit in &digits[7..13] {
+                push_code(&mut modules, r_code(digit));
+            }
+        }
+        8 => {
+            for &digit in &digits[..4] {
+                push_code(&mut modules, L_CODES[digit as usize]);
+            }
+
+            push_guard(&mut modules, &MIDDLE_GUARD);
+
+            for &digit in &digits[4..8] {
+                push_code(&mut modules, r_code(digit));
+            }
+        }
+        len => return Err(BarcodeError::InvalidLength(len)),
+    }
+
+    push_guard(&mut modules, &END_GUARD);
+    modules.extend([false; TRAILING_QUIET_ZONE]);
+
+    Ok(modules)
+}
+
+#[derive(Debug, Clone, Encode, Decode)]
+pub(crate) struct BarcodeReader {
+    modules: Vec<bool>,
+    position: usize,
+    cycles: u32,
+}
+
+impl BarcodeReader {
+    pub(crate) fn new() -> Self {
+        Self { modules: Vec::new(), position: 0, cycles: 0 }
+    }
+
+    pub(crate) fn scan(&mut self, barcode: &str) -> Result<(), BarcodeError> {
+        self.modules = encode_barcode(barcode)?;
+        self.position = 0;
+        self.cycles = 0;
+
+        log::info!("Scanning barcode {barcode}");
+
+        Ok(())
+    }
+
+    pub(crate) fn tick_cpu(&mut self) {
+        if self.position >= self.modules.len() {
+            return;
+        }
+
+        self.cycles += 1;
+        if self.cycles == MODULE_CPU_CYCLES {
+            self.cycles = 0;
+            self.position += 1;
+        }
+    }
+
+    // Spaces read as 1 and bars read as 0; reads 0 when no barcode is being scanned
+    pub(crate) fn output(&self) -> bool {
+        self.modules.get(self.position).is_some_and(|&bar| !bar)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn modules_to_string(modules: &[bool]) -> String {
+        modules.iter().map(|&bar| if bar { '1' } else { '0' }).collect()
+    }
+
+    #[test]
+    fn ean13_encoding() {
+        let modules = encode_barcode("4901234567894").unwrap();
+        assert_eq!(
+            modules_to_string(&modules[LEADING_QUIET_ZONE..modules.len() - TRAILING_QUIET_ZONE]),
+            "101 0001011 0100111 0011001 0010011 0100001 0011101 01010 \
+             1001110 1010000 1000100 1001000 1110100 1011100 101"
+                .replace(' ', "")
+        );
+    }
+
+    #[test]
+    fn ean8_encoding() {
+        let modules = encode_barcode("96385074").unwrap();
+        assert_eq!(
+            modules_to_string(&modules[LEADING_QUIET_ZONE..modules.len() - TRAILING_QUIET_ZONE]),
+            "101 0001011 0101111 0111101 0110111 01010 1001110 1110010 1000100 1011100 101"
+                .replace(' ', "")
+        );
+    }
+
+    #[test]
+    fn invalid_barcodes() {
+        assert!(matches!(encode_barcode("1234"), Err(BarcodeError::InvalidLength(4))));
+        assert!(matches!(encode_barcode("1234567a"), Err(BarcodeError::InvalidCharacter('a'))));
+    }
+}
@@ -262,10 +262,20 @@ impl<State: EepromState + Debug, const N: usize> EepromChip<State, N> {
     }
 
     pub fn handle_write(&mut self, value: u8) {
-        let data = value.bit(6);
-        let clock = value.bit(5);
-
         log::trace!("EEPROM write: {value:02X}");
+        self.write_lines(value.bit(5), value.bit(6));
+    }
+
+    // Used for chips whose clock and data lines are connected to different registers
+    pub fn write_clock(&mut self, clock: bool) {
+        self.write_lines(clock, self.last_data);
+    }
+
+    pub fn write_data(&mut self, data: bool) {
+        self.write_lines(self.last_clock, data);
+    }
+
+    fn write_lines(&mut self, clock: bool, data: bool) {
         if self.last_clock && clock && data != self.last_data {
             if data {
                 // Low to high
@@ -796,8 +796,7 @@ impl Vrc7 {
         let variant = match sub_mapper_number {
             1 => Variant::Vrc7b,
             2 => Variant::Vrc7a,
-            0 => Variant::Unknown,
-            _ => panic!("invalid VRC7 sub mapper: {sub_mapper_number}"),
+            _ => Variant::Unknown,
         };
 
         log::info!("VRC7 variant: {variant:?}");
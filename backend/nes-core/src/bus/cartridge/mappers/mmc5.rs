@@ -1,12 +1,12 @@
 //! Code for the MMC5 board (iNES mapper 5).
 
+use crate::api::NesTimingMode;
 use crate::apu::pulse::{PulseChannel, SweepStatus};
 use crate::apu::FrameCounter;
 use crate::bus::cartridge::mappers::{BankSizeKb, CpuMapResult};
 use crate::bus::cartridge::{Cartridge, MapperImpl};
 use crate::{apu, bus};
 use bincode::{Decode, Encode};
-use jgenesis_common::frontend::TimingMode;
 use jgenesis_common::num::GetBit;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
@@ -567,7 +567,7 @@ impl Mmc5 {
             pulse_channel_1: PulseChannel::new_channel_1(SweepStatus::Disabled),
             pulse_channel_2: PulseChannel::new_channel_2(SweepStatus::Disabled),
             pcm_channel: PcmChannel::new(),
-            frame_counter: FrameCounter::new(TimingMode::Ntsc),
+            frame_counter: FrameCounter::new(NesTimingMode::Ntsc),
             ram_writes_enabled_1: false,
             ram_writes_enabled_2: false,
         }
@@ -90,8 +90,8 @@ impl Uxrom {
     ) -> Self {
         let variant = match (mapper_number, sub_mapper_number) {
             (2, _) => UxromVariant::Uxrom,
-            (71, 0) => UxromVariant::Codemasters,
             (71, 1) => UxromVariant::FireHawk,
+            (71, _) => UxromVariant::Codemasters,
             _ => panic!(
                 "invalid UxROM mapper/submapper: mapper={mapper_number}, submapper={sub_mapper_number}"
             ),
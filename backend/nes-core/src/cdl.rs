@@ -0,0 +1,194 @@
+//! Code/data logger, which records how each byte of cartridge ROM has been used
+//!
+//! The log is stored and exported in FCEUX's `.cdl` format: one flag byte per PRG ROM byte,
+//! followed by one flag byte per CHR ROM byte (omitted for boards with CHR RAM).
+
+use jgenesis_proc_macros::{FakeDecode, FakeEncode};
+
+// PRG ROM flags
+const PRG_CODE: u8 = 1 << 0;
+const PRG_DATA: u8 = 1 << 1;
+// Bits 2-3 record which 8KB CPU window ($8000/$A000/$C000/$E000) the byte was last accessed through
+const PRG_WINDOW_SHIFT: u8 = 2;
+const PRG_WINDOW_MASK: u8 = 0x03 << PRG_WINDOW_SHIFT;
+const PRG_PCM_SAMPLE: u8 = 1 << 6;
+
+// CHR ROM flags
+const CHR_DRAWN: u8 = 1 << 0;
+const CHR_READ: u8 = 1 << 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PrgRomAccess {
+    Code,
+    Data,
+    PcmSample,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ChrRomAccess {
+    /// Fetched by the PPU during rendering
+    Drawn,
+    /// Read by the CPU through PPUDATA
+    Read,
+}
+
+/// Summary of how much of the cartridge ROM has been logged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CdlCoverage {
+    pub prg_rom_len: usize,
+    pub code_bytes: usize,
+    pub data_bytes: usize,
+    pub pcm_sample_bytes: usize,
+    pub unused_prg_bytes: usize,
+    pub chr_rom_len: usize,
+    pub drawn_bytes: usize,
+    pub read_bytes: usize,
+}
+
+/// Logging is disabled by default so that there is no overhead unless the log is in use. This is
+/// never persisted in save states; the frontend is expected to carry it over when loading a state.
+#[derive(Debug, Clone, Default, FakeEncode, FakeDecode)]
+pub struct CodeDataLog {
+    enabled: bool,
+    prg_rom: Vec<u8>,
+    chr_rom: Vec<u8>,
+}
+
+impl CodeDataLog {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub(crate) fn enable(&mut self, prg_rom_len: usize, chr_rom_len: usize) {
+        if self.prg_rom.len() != prg_rom_len || self.chr_rom.len() != chr_rom_len {
+            self.prg_rom = vec![0; prg_rom_len];
+            self.chr_rom = vec![0; chr_rom_len];
+        }
+
+        self.enabled = true;
+    }
+
+    pub(crate) fn disable(&mut self) {
+        self.enabled = false;
+    }
+
+    /// Reset all logged flags without changing whether logging is enabled.
+    pub fn clear(&mut self) {
+        self.prg_rom.fill(0);
+        self.chr_rom.fill(0);
+    }
+
+    #[inline]
+    pub(crate) fn log_prg_rom(&mut self, rom_address: u32, cpu_address: u16, access: PrgRomAccess) {
+        let Some(flags) = self.prg_rom.get_mut(rom_address as usize) else { return };
+
+        *flags |= match access {
+            PrgRomAccess::Code => PRG_CODE,
+            PrgRomAccess::Data => PRG_DATA,
+            PrgRomAccess::PcmSample => PRG_DATA | PRG_PCM_SAMPLE,
+        };
+
+        if cpu_address >= 0x8000 {
+            let window = ((cpu_address >> 13) & 0x03) as u8;
+            *flags = (*flags & !PRG_WINDOW_MASK) | (window << PRG_WINDOW_SHIFT);
+        }
+    }
+
+    #[inline]
+    pub(crate) fn log_chr_rom(&mut self, rom_address: u32, access: ChrRomAccess) {
+        let Some(flags) = self.chr_rom.get_mut(rom_address as usize) else { return };
+
+        *flags |= match access {
+            ChrRomAccess::Drawn => CHR_DRAWN,
+            ChrRomAccess::Read => CHR_READ,
+        };
+    }
+
+    /// Merge flags from an existing FCEUX `.cdl` file into this log, so that logging can continue
+    /// across sessions. Returns false without modifying the log if the file size does not match
+    /// the cartridge ROM size.
+    pub fn merge_fceux_bytes(&mut self, bytes: &[u8]) -> bool {
+        if bytes.len() != self.prg_rom.len() + self.chr_rom.len() {
+            return false;
+        }
+
+        let (prg_bytes, chr_bytes) = bytes.split_at(self.prg_rom.len());
+        for (flags, &loaded) in self.prg_rom.iter_mut().zip(prg_bytes) {
+            // Keep the current window bits if this byte has already been logged
+            let window_mask = if *flags != 0 { !PRG_WINDOW_MASK } else { 0xFF };
+            *flags |= loaded & window_mask;
+        }
+        for (flags, &loaded) in self.chr_rom.iter_mut().zip(chr_bytes) {
+            *flags |= loaded;
+        }
+
+        true
+    }
+
+    /// Export the log in FCEUX's `.cdl` format.
+    #[must_use]
+    pub fn to_fceux_bytes(&self) -> Vec<u8> {
+        [self.prg_rom.as_slice(), self.chr_rom.as_slice()].concat()
+    }
+
+    #[must_use]
+    pub fn coverage(&self) -> CdlCoverage {
+        let count_prg = |mask: u8| self.prg_rom.iter().filter(|&&flags| flags & mask != 0).count();
+        let count_chr = |mask: u8| self.chr_rom.iter().filter(|&&flags| flags & mask != 0).count();
+
+        CdlCoverage {
+            prg_rom_len: self.prg_rom.len(),
+            code_bytes: count_prg(PRG_CODE),
+            data_bytes: count_prg(PRG_DATA),
+            pcm_sample_bytes: count_prg(PRG_PCM_SAMPLE),
+            unused_prg_bytes: self.prg_rom.len() - count_prg(0xFF),
+            chr_rom_len: self.chr_rom.len(),
+            drawn_bytes: count_chr(CHR_DRAWN),
+            read_bytes: count_chr(CHR_READ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fceux_format() {
+        let mut log = CodeDataLog::new();
+        log.enable(0x8000, 0x2000);
+
+        log.log_prg_rom(0x0000, 0x8000, PrgRomAccess::Code);
+        log.log_prg_rom(0x4001, 0xC001, PrgRomAccess::Data);
+        log.log_prg_rom(0x4001, 0xE001, PrgRomAccess::Code);
+        log.log_prg_rom(0x7000, 0xF000, PrgRomAccess::PcmSample);
+        log.log_chr_rom(0x0010, ChrRomAccess::Drawn);
+        log.log_chr_rom(0x1000, ChrRomAccess::Read);
+
+        let bytes = log.to_fceux_bytes();
+        assert_eq!(bytes.len(), 0xA000);
+        assert_eq!(bytes[0x0000], 0x01);
+        assert_eq!(bytes[0x4001], 0x0F);
+        assert_eq!(bytes[0x7000], 0x4E);
+        assert_eq!(bytes[0x8010], 0x01);
+        assert_eq!(bytes[0x9000], 0x02);
+
+        let mut merged = CodeDataLog::new();
+        merged.enable(0x8000, 0x2000);
+        assert!(merged.merge_fceux_bytes(&bytes));
+        assert_eq!(merged.to_fceux_bytes(), bytes);
+        assert!(!merged.merge_fceux_bytes(&bytes[..0x8000]));
+
+        let coverage = merged.coverage();
+        assert_eq!(coverage.code_bytes, 2);
+        assert_eq!(coverage.data_bytes, 2);
+        assert_eq!(coverage.unused_prg_bytes, 0x8000 - 3);
+        assert_eq!(coverage.drawn_bytes, 1);
+    }
+}
@@ -7,35 +7,72 @@ use mos6502_emu::Mos6502;
 
 #[derive(Debug, Clone, Encode, Decode)]
 struct OamDmaState {
-    cycles_remaining: u16,
     source_high_byte: u8,
-    last_read_value: u8,
+    bytes_copied: u16,
+    read_value: Option<u8>,
 }
 
-#[derive(Debug, Clone, Encode, Decode)]
-enum State {
-    CpuExecuting,
-    OamDmaDelay(OamDmaState),
-    OamDma(OamDmaState),
-}
-
-impl Default for State {
-    fn default() -> Self {
-        Self::CpuExecuting
-    }
+#[derive(Debug, Clone, Default, Encode, Decode)]
+struct DmaState {
+    // Set once the DMA unit has performed its halt cycle, cleared when no DMA is pending
+    halted: bool,
+    oam: Option<OamDmaState>,
+    // Number of DMA cycles that have elapsed since a pending DMC DMA request was first seen
+    dmc_wait_cycles: u8,
 }
 
 #[derive(Debug, Clone, Encode, Decode)]
 pub struct CpuState {
     mos6502: Mos6502,
-    state: State,
+    dma: DmaState,
 }
 
 impl CpuState {
     pub fn new(bus: &mut CpuBus<'_>) -> Self {
         let mos6502 = Mos6502::new_nes(bus);
 
-        Self { mos6502, state: State::default() }
+        Self { mos6502, dma: DmaState::default() }
+    }
+}
+
+// Bus wrapper that holds the CPU's RDY line low while a DMA is pending. The CPU finishes any write
+// cycles in progress and stops on its next read, at which point the DMA unit takes over the bus.
+struct DmaHaltBus<'a, 'b>(&'a mut CpuBus<'b>);
+
+impl BusInterface for DmaHaltBus<'_, '_> {
+    #[inline]
+    fn read(&mut self, address: u16) -> u8 {
+        self.0.read(address)
+    }
+
+    #[inline]
+    fn read_code(&mut self, address: u16) -> u8 {
+        self.0.read_code(address)
+    }
+
+    #[inline]
+    fn write(&mut self, address: u16, value: u8) {
+        self.0.write(address, value);
+    }
+
+    #[inline]
+    fn nmi(&self) -> bool {
+        self.0.nmi()
+    }
+
+    #[inline]
+    fn acknowledge_nmi(&mut self) {
+        self.0.acknowledge_nmi();
+    }
+
+    #[inline]
+    fn irq(&self) -> bool {
+        self.0.irq()
+    }
+
+    #[inline]
+    fn rdy(&self) -> bool {
+        false
     }
 }
 
@@ -45,51 +82,83 @@ pub fn tick(state: &mut CpuState, bus: &mut CpuBus<'_>, is_apu_active_cycle: boo
         return;
     }
 
-    state.state = match std::mem::take(&mut state.state) {
-        State::CpuExecuting => {
-            if bus.is_oamdma_dirty() {
-                // Dummy opcode read
-                bus.read(state.mos6502.pc());
+    if bus.is_oamdma_dirty() {
+        bus.clear_oamdma_dirty();
 
-                bus.clear_oamdma_dirty();
+        let source_high_byte = bus.read_oamdma_for_transfer();
+        log::trace!("OAM: Initiating OAM DMA transfer from {source_high_byte:02X}");
 
-                let source_high_byte = bus.read_oamdma_for_transfer();
-                log::trace!("OAM: Initiating OAM DMA transfer from {source_high_byte:02X}");
+        state.dma.oam = Some(OamDmaState { source_high_byte, bytes_copied: 0, read_value: None });
+    }
 
-                let oam_dma_state =
-                    OamDmaState { cycles_remaining: 512, source_high_byte, last_read_value: 0 };
-                if is_apu_active_cycle {
-                    State::OamDmaDelay(oam_dma_state)
-                } else {
-                    State::OamDma(oam_dma_state)
-                }
-            } else {
-                state.mos6502.tick(bus);
-                State::CpuExecuting
-            }
+    let dmc_request = bus.dmc_dma_request();
+    if state.dma.oam.is_none() && dmc_request.is_none() {
+        state.mos6502.tick(bus);
+        return;
+    }
+
+    state.mos6502.tick(&mut DmaHaltBus(bus));
+    let Some(halt_address) = state.mos6502.rdy_halt_address() else {
+        // CPU is still finishing a write; DMA cannot start until the next read cycle
+        return;
+    };
+
+    // DMA get (read) cycles align with APU active cycles, and put (write) cycles with the others
+    dma_cycle(&mut state.dma, bus, halt_address, is_apu_active_cycle, dmc_request);
+
+    if bus.dmc_dma_request().is_some() {
+        state.dma.dmc_wait_cycles = state.dma.dmc_wait_cycles.saturating_add(1);
+    } else {
+        state.dma.dmc_wait_cycles = 0;
+    }
+
+    if state.dma.oam.is_none() && bus.dmc_dma_request().is_none() {
+        state.dma.halted = false;
+    }
+}
+
+fn dma_cycle(
+    dma: &mut DmaState,
+    bus: &mut CpuBus<'_>,
+    halt_address: u16,
+    is_get_cycle: bool,
+    dmc_request: Option<u16>,
+) {
+    if !dma.halted {
+        // Halt cycle; the halted CPU read is repeated as a dummy read
+        dma.halted = true;
+        bus.read(halt_address);
+        return;
+    }
+
+    if is_get_cycle {
+        // DMC DMA needs a dummy cycle after the halt cycle and takes priority over OAM DMA reads
+        if let Some(address) = dmc_request.filter(|_| dma.dmc_wait_cycles >= 2) {
+            let sample = bus.read_dmc_sample(address);
+            bus.complete_dmc_dma(sample);
+            return;
         }
-        State::OamDmaDelay(state) => State::OamDma(state),
-        State::OamDma(OamDmaState {
-            mut cycles_remaining,
-            source_high_byte,
-            mut last_read_value,
-        }) => {
-            cycles_remaining -= 1;
-
-            if cycles_remaining % 2 == 1 {
-                let source_low_byte = (0xFF - cycles_remaining / 2) as u8;
-                last_read_value = bus.read(u16::from_le_bytes([source_low_byte, source_high_byte]));
-            } else {
-                bus.write(PpuRegister::OAMDATA.to_address(), last_read_value);
-            }
 
-            if cycles_remaining > 0 {
-                State::OamDma(OamDmaState { cycles_remaining, source_high_byte, last_read_value })
-            } else {
-                State::CpuExecuting
+        if let Some(oam) = dma.oam.as_mut().filter(|oam| oam.read_value.is_none()) {
+            let source_low_byte = oam.bytes_copied as u8;
+            oam.read_value =
+                Some(bus.read(u16::from_le_bytes([source_low_byte, oam.source_high_byte])));
+            return;
+        }
+    } else if let Some(oam) = &mut dma.oam {
+        if let Some(value) = oam.read_value.take() {
+            bus.write(PpuRegister::OAMDATA.to_address(), value);
+
+            oam.bytes_copied += 1;
+            if oam.bytes_copied == 256 {
+                dma.oam = None;
             }
+            return;
         }
-    };
+    }
+
+    // Dummy or alignment cycle; repeat the halted CPU read
+    bus.read(halt_address);
 }
 
 /// Reset the CPU, as if the console's reset button was pressed.
@@ -101,3 +170,154 @@ pub fn tick(state: &mut CpuState, bus: &mut CpuBus<'_>, is_apu_active_cycle: boo
 pub fn reset<B: BusInterface>(cpu_state: &mut CpuState, bus: &mut B) {
     cpu_state.mos6502.reset(bus);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bus::{cartridge, Bus};
+    use crate::input::NesExpansionDevice;
+    use jgenesis_common::rng::{InitialRamState, Rng};
+
+    const DMC_SAMPLE_ADDRESS: u16 = 0xC000;
+    const DMC_SAMPLE: u8 = 0x5A;
+
+    struct Harness {
+        bus: Bus,
+        state: CpuState,
+        get_cycle: bool,
+    }
+
+    impl Harness {
+        // PRG ROM is all NOPs, which read on every cycle, so DMA can always halt the CPU immediately
+        fn new() -> Self {
+            let mut prg_rom = vec![0xEA; 32 * 1024];
+            prg_rom[0x4000] = DMC_SAMPLE;
+            prg_rom[0x7FFC..0x7FFE].copy_from_slice(&[0x00, 0x80]);
+
+            let mut bus = Bus::from_cartridge(
+                cartridge::new_mmc1(prg_rom),
+                NesExpansionDevice::None,
+                InitialRamState::AllZeroes,
+                &mut Rng::new(0),
+            );
+            let state = CpuState::new(&mut bus.cpu());
+
+            Self { bus, state, get_cycle: false }
+        }
+
+        fn tick(&mut self) {
+            tick(&mut self.state, &mut self.bus.cpu(), self.get_cycle);
+            self.bus.tick_cpu();
+            self.get_cycle = !self.get_cycle;
+        }
+
+        fn start_oam_dma(&mut self, source_high_byte: u8) {
+            for i in 0..=255 {
+                self.bus.cpu().write(u16::from_le_bytes([i, source_high_byte]), !i);
+                self.bus.tick_cpu();
+            }
+
+            self.bus.cpu().write(0x4014, source_high_byte);
+            self.bus.tick_cpu();
+        }
+
+        fn dma_active(&self) -> bool {
+            self.state.dma.oam.is_some() || self.state.dma.halted
+        }
+
+        fn assert_oam_copied(&mut self) {
+            for i in 0..=255 {
+                self.bus.cpu().write(0x2003, i);
+                self.bus.tick_cpu();
+                assert_eq!(self.bus.cpu().read(0x2004), !i, "OAM byte {i}");
+            }
+        }
+    }
+
+    fn oam_dma_cycles(halt_on_get_cycle: bool) -> u32 {
+        let mut harness = Harness::new();
+        harness.get_cycle = halt_on_get_cycle;
+        harness.start_oam_dma(0x02);
+
+        let mut cycles = 0;
+        loop {
+            harness.tick();
+            if harness.state.mos6502.rdy_halt_address().is_none() {
+                break;
+            }
+            cycles += 1;
+        }
+
+        assert!(!harness.dma_active());
+        harness.assert_oam_copied();
+
+        cycles
+    }
+
+    fn dmc_dma_cycles(halt_on_get_cycle: bool) -> u32 {
+        let mut harness = Harness::new();
+        harness.get_cycle = halt_on_get_cycle;
+        harness.bus.cpu().request_dmc_dma(DMC_SAMPLE_ADDRESS);
+
+        let mut cycles = 0;
+        while harness.bus.cpu().dmc_dma_request().is_some() {
+            harness.tick();
+            assert!(harness.state.mos6502.rdy_halt_address().is_some());
+            cycles += 1;
+        }
+
+        assert!(!harness.dma_active());
+        assert_eq!(harness.bus.cpu().take_dmc_dma_sample(), Some(DMC_SAMPLE));
+
+        cycles
+    }
+
+    #[test]
+    fn oam_dma_cycle_counts() {
+        // Halt cycle followed by 256 get/put pairs, with an alignment cycle if the halt cycle was a
+        // get cycle
+        assert_eq!(oam_dma_cycles(false), 513);
+        assert_eq!(oam_dma_cycles(true), 514);
+    }
+
+    #[test]
+    fn dmc_dma_cycle_counts() {
+        // Halt cycle, dummy cycle, and get cycle, with an alignment cycle if the get cycle would
+        // otherwise land on a put cycle
+        assert_eq!(dmc_dma_cycles(true), 3);
+        assert_eq!(dmc_dma_cycles(false), 4);
+    }
+
+    #[test]
+    fn dmc_dma_takes_priority_over_oam_dma() {
+        let mut harness = Harness::new();
+        harness.start_oam_dma(0x02);
+
+        for _ in 0..100 {
+            harness.tick();
+        }
+
+        harness.bus.cpu().request_dmc_dma(DMC_SAMPLE_ADDRESS);
+        let mut cycles = 0;
+        while harness.bus.cpu().dmc_dma_request().is_some() {
+            harness.tick();
+            cycles += 1;
+        }
+
+        // The DMC read replaces an OAM read rather than waiting for the OAM transfer to finish
+        assert!(cycles <= 4, "DMC DMA took {cycles} cycles");
+        assert_eq!(harness.bus.cpu().take_dmc_dma_sample(), Some(DMC_SAMPLE));
+        assert!(harness.state.dma.oam.is_some());
+
+        let mut total_cycles = 100 + cycles;
+        while harness.state.mos6502.rdy_halt_address().is_some() {
+            harness.tick();
+            total_cycles += 1;
+        }
+
+        // OAM DMA is delayed by one get/put pair; the final tick is the CPU resuming
+        assert_eq!(total_cycles - 1, 513 + 2);
+        assert!(!harness.dma_active());
+        harness.assert_oam_copied();
+    }
+}
@@ -0,0 +1,47 @@
+//! Entry points for the cargo-fuzz targets in the repository's `fuzz` directory
+
+use crate::bus::cartridge;
+
+const MAX_REGISTER_WRITES: usize = 256;
+
+/// Parse an iNES / NES 2.0 file and exercise the resulting mapper.
+///
+/// After loading, bytes from the end of the file are written to the mapper's CPU address space in
+/// (address low, address high, value) triples, reading back from the PRG and CHR address ranges
+/// after every write. Malformed headers must return an error rather than panicking, and no
+/// sequence of register writes may panic a mapper.
+pub fn ines_cartridge(data: &[u8]) {
+    let Ok(mut mapper) = cartridge::from_ines_file(data, None, None) else { return };
+
+    let mut vram = [0; 2048];
+    let writes = data.rchunks_exact(3).take(MAX_REGISTER_WRITES);
+    for chunk in writes {
+        // The bus only routes $4020-$FFFF to the cartridge
+        let address = u16::from_le_bytes([chunk[0], chunk[1]]);
+        if address < 0x4020 {
+            continue;
+        }
+        mapper.write_cpu_address(address, chunk[2]);
+
+        for address in (0x4020..=0xFFFF).step_by(0x101) {
+            mapper.read_cpu_address(address);
+        }
+
+        for address in (0x0000..0x3F00).step_by(0x41) {
+            mapper.read_ppu_address(address, &vram);
+            mapper.write_ppu_address(address, chunk[2], &mut vram);
+        }
+
+        mapper.tick_cpu();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ines_cartridge_corpus() {
+        jgenesis_common::fuzz::replay_corpus("ines_cartridge", ines_cartridge);
+    }
+}
@@ -20,7 +20,7 @@ pub fn copy_nametables(pattern_table: PatternTable, bus: &mut PpuBus<'_>, out: &
 
     let mut nametables = vec![0; 0x1000];
     for (i, value) in nametables.iter_mut().enumerate() {
-        *value = bus.read_address(0x2000 | (i as u16));
+        *value = bus.debug_read_address(0x2000 | (i as u16));
     }
 
     for nametable in 0..4 {
@@ -73,7 +73,7 @@ fn dump_pattern_table_into(pattern_table: PatternTable, bus: &mut PpuBus<'_>, ou
     };
 
     for (i, value) in out.iter_mut().enumerate() {
-        *value = bus.read_address(pattern_table_addr | (i as u16));
+        *value = bus.debug_read_address(pattern_table_addr | (i as u16));
     }
 }
 
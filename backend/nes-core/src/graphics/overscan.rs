@@ -0,0 +1,34 @@
+//! Per-game NTSC overscan database
+//!
+//! Most NTSC TVs cut off roughly the top and bottom 8 scanlines, and many games leave garbage in
+//! that region (or at the horizontal edges, e.g. attribute glitches from scrolling) because it
+//! would never have been visible. Games that need a different region hidden than the default can
+//! be listed here.
+//!
+//! Games are identified by the CRC32 of the ROM contents following the 16-byte iNES header, which
+//! is logged when a ROM is loaded.
+
+use crate::api::Overscan;
+use crc::Crc;
+
+const CRC: Crc<u32> = Crc::<u32>::new(&crc::CRC_32_ISO_HDLC);
+
+const INES_HEADER_LEN: usize = 16;
+
+const DEFAULT_NTSC_OVERSCAN: Overscan = Overscan { top: 8, bottom: 8, left: 0, right: 0 };
+
+// (ROM CRC32, overscan) pairs
+const NTSC_OVERSCAN_OVERRIDES: &[(u32, Overscan)] = &[];
+
+/// Look up the region of the frame that should be hidden when emulating NTSC overscan for the
+/// given iNES file.
+pub fn ntsc_overscan(rom_file: &[u8]) -> Overscan {
+    let checksum = CRC.checksum(rom_file.get(INES_HEADER_LEN..).unwrap_or_default());
+    log::info!("ROM CRC32: {checksum:08X}");
+
+    NTSC_OVERSCAN_OVERRIDES
+        .iter()
+        .find_map(|&(crc32, overscan)| (crc32 == checksum).then_some(overscan))
+        .inspect(|overscan| log::info!("Using NTSC overscan from game database: {overscan}"))
+        .unwrap_or(DEFAULT_NTSC_OVERSCAN)
+}
@@ -1,4 +1,6 @@
 use bincode::{Decode, Encode};
+use jgenesis_common::num::GetBit;
+use jgenesis_proc_macros::{EnumDisplay, EnumFromStr};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode)]
 pub struct NesJoypadState {
@@ -56,6 +58,7 @@ impl NesJoypadState {
 pub struct NesInputs {
     pub p1: NesJoypadState,
     pub p2: NesJoypadState,
+    pub expansion: NesExpansionInputs,
 }
 
 #[derive(Debug, Clone, Copy, Encode, Decode)]
@@ -71,3 +74,317 @@ impl LatchedJoypadState {
         Self((self.0 >> 1) | 0x80)
     }
 }
+
+/// Peripherals that connect to the Famicom expansion port, plus the NES variant of the Arkanoid
+/// controller which connects to the second controller port.
+///
+/// Values match the NES 2.0 header's default expansion device byte where one exists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode, EnumDisplay, EnumFromStr)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NesExpansionDevice {
+    #[default]
+    None,
+    FamilyBasicKeyboard,
+    HyperShot,
+    VausNes,
+    VausFamicom,
+}
+
+impl NesExpansionDevice {
+    pub(crate) fn from_nes2_header_byte(byte: u8) -> Option<Self> {
+        match byte & 0x3F {
+            0x01 => Some(Self::None),
+            0x0F => Some(Self::VausNes),
+            0x10 => Some(Self::VausFamicom),
+            0x12 => Some(Self::HyperShot),
+            0x23 => Some(Self::FamilyBasicKeyboard),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode)]
+pub struct HyperShotState {
+    pub p1_run: bool,
+    pub p1_jump: bool,
+    pub p2_run: bool,
+    pub p2_jump: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+pub struct VausState {
+    /// Knob position, from 0 (fully left) to 255 (fully right)
+    pub position: u8,
+    pub fire: bool,
+}
+
+impl Default for VausState {
+    fn default() -> Self {
+        Self { position: 128, fire: false }
+    }
+}
+
+/// Keys on the Family BASIC keyboard, in keyboard matrix order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FamilyBasicKey {
+    F8,
+    Return,
+    LeftBracket,
+    RightBracket,
+    Kana,
+    RightShift,
+    Yen,
+    Stop,
+    F7,
+    At,
+    Colon,
+    Semicolon,
+    Underscore,
+    Slash,
+    Minus,
+    Caret,
+    F6,
+    O,
+    L,
+    K,
+    Period,
+    Comma,
+    P,
+    Zero,
+    F5,
+    I,
+    U,
+    J,
+    M,
+    N,
+    Nine,
+    Eight,
+    F4,
+    Y,
+    G,
+    H,
+    B,
+    V,
+    Seven,
+    Six,
+    F3,
+    T,
+    R,
+    D,
+    F,
+    C,
+    Five,
+    Four,
+    F2,
+    W,
+    S,
+    A,
+    X,
+    Z,
+    E,
+    Three,
+    F1,
+    Escape,
+    Q,
+    Control,
+    LeftShift,
+    Graph,
+    One,
+    Two,
+    ClearHome,
+    Up,
+    Right,
+    Left,
+    Down,
+    Space,
+    Delete,
+    Insert,
+}
+
+const KEYBOARD_ROWS: usize = 9;
+
+/// State of all 72 Family BASIC keys. Each row of the key matrix is stored as a byte, with the
+/// first column in the low nibble and the second column in the high nibble.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode)]
+pub struct FamilyBasicKeyboardState([u8; KEYBOARD_ROWS]);
+
+impl FamilyBasicKeyboardState {
+    pub fn set_pressed(&mut self, key: FamilyBasicKey, pressed: bool) {
+        let idx = key as usize;
+        let mask = 1 << (idx % 8);
+        if pressed {
+            self.0[idx / 8] |= mask;
+        } else {
+            self.0[idx / 8] &= !mask;
+        }
+    }
+
+    #[must_use]
+    pub fn is_pressed(&self, key: FamilyBasicKey) -> bool {
+        let idx = key as usize;
+        self.0[idx / 8] & (1 << (idx % 8)) != 0
+    }
+
+    fn matrix_bits(self, row: u8, column: u8) -> u8 {
+        (self.0[row as usize] >> (4 * column)) & 0x0F
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode)]
+pub struct NesExpansionInputs {
+    pub hyper_shot: HyperShotState,
+    pub vaus: VausState,
+    pub keyboard: FamilyBasicKeyboardState,
+}
+
+// Range of values reported by the Vaus controller's potentiometer
+const VAUS_MIN: u16 = 0x62;
+const VAUS_MAX: u16 = 0xF2;
+
+#[derive(Debug, Clone, Encode, Decode)]
+pub(crate) struct ExpansionPort {
+    device: NesExpansionDevice,
+    inputs: NesExpansionInputs,
+    microphone: bool,
+    strobe: bool,
+    vaus_shift_register: u8,
+    hyper_shot_p1_enabled: bool,
+    hyper_shot_p2_enabled: bool,
+    keyboard_enabled: bool,
+    keyboard_row: u8,
+    keyboard_column: u8,
+}
+
+impl ExpansionPort {
+    pub fn new(device: NesExpansionDevice) -> Self {
+        Self {
+            device,
+            inputs: NesExpansionInputs::default(),
+            microphone: false,
+            strobe: false,
+            vaus_shift_register: 0,
+            hyper_shot_p1_enabled: true,
+            hyper_shot_p2_enabled: true,
+            keyboard_enabled: false,
+            keyboard_row: 0,
+            keyboard_column: 0,
+        }
+    }
+
+    pub fn device(&self) -> NesExpansionDevice {
+        self.device
+    }
+
+    pub fn set_device(&mut self, device: NesExpansionDevice) {
+        if device != self.device {
+            *self = Self { microphone: self.microphone, ..Self::new(device) };
+        }
+    }
+
+    pub fn update_inputs(&mut self, inputs: NesExpansionInputs) {
+        self.inputs = inputs;
+    }
+
+    pub fn set_microphone(&mut self, microphone: bool) {
+        self.microphone = microphone;
+    }
+
+    fn vaus_value(&self) -> u8 {
+        let position = u16::from(self.inputs.vaus.position);
+        (VAUS_MIN + position * (VAUS_MAX - VAUS_MIN) / 255) as u8
+    }
+
+    // $4016 writes
+    pub fn write_output(&mut self, value: u8) {
+        let prev_strobe = self.strobe;
+        self.strobe = value.bit(0);
+
+        match self.device {
+            NesExpansionDevice::None => {}
+            NesExpansionDevice::VausNes | NesExpansionDevice::VausFamicom => {
+                if self.strobe || prev_strobe {
+                    // Potentiometer data is serialized inverted
+                    self.vaus_shift_register = !self.vaus_value();
+                }
+            }
+            NesExpansionDevice::HyperShot => {
+                self.hyper_shot_p1_enabled = !value.bit(1);
+                self.hyper_shot_p2_enabled = !value.bit(2);
+            }
+            NesExpansionDevice::FamilyBasicKeyboard => {
+                let prev_column = self.keyboard_column;
+                self.keyboard_column = u8::from(value.bit(1));
+                self.keyboard_enabled = value.bit(2);
+
+                if self.keyboard_enabled {
+                    // Row advances on each 1-to-0 transition of the column select bit
+                    if prev_column == 1 && self.keyboard_column == 0 {
+                        self.keyboard_row = (self.keyboard_row + 1) % 10;
+                    }
+
+                    if value.bit(0) {
+                        self.keyboard_row = 0;
+                    }
+                }
+            }
+        }
+    }
+
+    fn shift_vaus(&mut self) -> bool {
+        let bit = self.vaus_shift_register.bit(7);
+        if !self.strobe {
+            self.vaus_shift_register <<= 1;
+        }
+        bit
+    }
+
+    // Bits 1-4 of $4016 reads
+    pub fn read_port_1(&mut self) -> u8 {
+        let microphone = u8::from(self.microphone) << 2;
+
+        let device_bits = match self.device {
+            NesExpansionDevice::VausFamicom => u8::from(self.inputs.vaus.fire) << 1,
+            _ => 0,
+        };
+
+        microphone | device_bits
+    }
+
+    // Bits 1-4 of $4017 reads
+    pub fn read_port_2(&mut self) -> u8 {
+        match self.device {
+            NesExpansionDevice::None => 0,
+            NesExpansionDevice::VausNes => {
+                let fire = u8::from(self.inputs.vaus.fire) << 3;
+                let data = u8::from(self.shift_vaus()) << 4;
+                fire | data
+            }
+            NesExpansionDevice::VausFamicom => u8::from(self.shift_vaus()) << 1,
+            NesExpansionDevice::HyperShot => {
+                let state = self.inputs.hyper_shot;
+                let mut value = 0;
+                if self.hyper_shot_p1_enabled {
+                    value |= (u8::from(state.p1_jump) << 1) | (u8::from(state.p1_run) << 2);
+                }
+                if self.hyper_shot_p2_enabled {
+                    value |= (u8::from(state.p2_jump) << 3) | (u8::from(state.p2_run) << 4);
+                }
+                value
+            }
+            NesExpansionDevice::FamilyBasicKeyboard => {
+                if !self.keyboard_enabled {
+                    return 0;
+                }
+
+                if (self.keyboard_row as usize) < KEYBOARD_ROWS {
+                    // Pressed keys read as 0
+                    let pressed =
+                        self.inputs.keyboard.matrix_bits(self.keyboard_row, self.keyboard_column);
+                    (!pressed << 1) & 0x1E
+                } else {
+                    0x1E
+                }
+            }
+        }
+    }
+}
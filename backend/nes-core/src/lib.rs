@@ -1,8 +1,24 @@
+//! NES / Famicom emulation core
+//!
+//! The types re-exported at the crate root are the stable API for frontends. Create an emulator with
+//! [`NesEmulator::create`] and run it through [`EmulatorTrait`](jgenesis_common::frontend::EmulatorTrait);
+//! save states are handled by [`jgenesis_common::state`]. The `api` and `input` modules remain public
+//! for existing frontends but their layout may change between releases.
+
 pub mod api;
 mod apu;
 mod audio;
 mod bus;
+pub mod cdl;
 mod cpu;
+#[cfg(any(test, feature = "fuzzing"))]
+pub mod fuzz;
 mod graphics;
 pub mod input;
 mod ppu;
+
+pub use api::{
+    NesAspectRatio, NesEmulator, NesEmulatorConfig, NesError, NesInitializationError,
+    NesTimingMode, Overscan,
+};
+pub use input::{NesExpansionDevice, NesExpansionInputs, NesInputs, NesJoypadState};
@@ -6,11 +6,13 @@
 //!
 //! PAL is (mostly) the same except the vertical blanking period lasts for 70 scanlines instead of 20,
 //! for a total of 312 scanlines.
+//!
+//! Dendy clones also have 312 scanlines, but they insert 50 idle post-render scanlines before the
+//! vertical blanking period so that VBlank still lasts for 20 scanlines like NTSC.
 
-use crate::api::NesEmulatorConfig;
+use crate::api::{NesEmulatorConfig, NesTimingMode};
 use crate::bus::{PpuBus, PpuRegisters, PpuTrackedRegister, PpuWriteToggle};
 use bincode::{Decode, Encode};
-use jgenesis_common::frontend::TimingMode;
 use jgenesis_common::num::GetBit;
 use std::fmt::{Display, Formatter};
 use std::ops::RangeInclusive;
@@ -31,13 +33,15 @@ const RESET_HORIZONTAL_POS_DOT: u16 = 257;
 const FIRST_SPRITE_TILE_FETCH_DOT: u16 = 257;
 
 const VISIBLE_SCANLINES: RangeInclusive<u16> = 0..=239;
-const FIRST_VBLANK_SCANLINE: u16 = 241;
 const NTSC_VBLANK_SCANLINES: RangeInclusive<u16> = 241..=260;
 const NTSC_ALL_IDLE_SCANLINES: RangeInclusive<u16> = 240..=260;
 const NTSC_PRE_RENDER_SCANLINE: u16 = 261;
 const PAL_VBLANK_SCANLINES: RangeInclusive<u16> = 241..=310;
 const PAL_ALL_IDLE_SCANLINES: RangeInclusive<u16> = 240..=310;
 const PAL_PRE_RENDER_SCANLINE: u16 = 311;
+const DENDY_VBLANK_SCANLINES: RangeInclusive<u16> = 291..=310;
+const DENDY_ALL_IDLE_SCANLINES: RangeInclusive<u16> = 240..=310;
+const DENDY_PRE_RENDER_SCANLINE: u16 = 311;
 
 const BLACK_NES_COLOR: u8 = 0x0F;
 
@@ -52,7 +56,7 @@ impl ColorEmphasis {
         Self(emphasis_bits)
     }
 
-    pub fn get_current(bus: &PpuBus<'_>, timing_mode: TimingMode) -> Self {
+    pub fn get_current(bus: &PpuBus<'_>, timing_mode: NesTimingMode) -> Self {
         let ppu_registers = bus.get_ppu_registers();
         Self::new(
             ppu_registers.emphasize_red(timing_mode),
@@ -90,11 +94,12 @@ trait TimingModePpuExt {
     fn pre_render_scanline(self) -> u16;
 }
 
-impl TimingModePpuExt for TimingMode {
+impl TimingModePpuExt for NesTimingMode {
     fn vblank_scanlines(self) -> RangeInclusive<u16> {
         match self {
             Self::Ntsc => NTSC_VBLANK_SCANLINES,
             Self::Pal => PAL_VBLANK_SCANLINES,
+            Self::Dendy => DENDY_VBLANK_SCANLINES,
         }
     }
 
@@ -102,6 +107,7 @@ impl TimingModePpuExt for TimingMode {
         match self {
             Self::Ntsc => NTSC_ALL_IDLE_SCANLINES,
             Self::Pal => PAL_ALL_IDLE_SCANLINES,
+            Self::Dendy => DENDY_ALL_IDLE_SCANLINES,
         }
     }
 
@@ -109,6 +115,7 @@ impl TimingModePpuExt for TimingMode {
         match self {
             Self::Ntsc => NTSC_PRE_RENDER_SCANLINE,
             Self::Pal => PAL_PRE_RENDER_SCANLINE,
+            Self::Dendy => DENDY_PRE_RENDER_SCANLINE,
         }
     }
 }
@@ -314,7 +321,7 @@ struct SpriteData {
 
 #[derive(Debug, Clone, Encode, Decode)]
 pub struct PpuState {
-    timing_mode: TimingMode,
+    timing_mode: NesTimingMode,
     frame_buffer: FrameBuffer,
     registers: InternalRegisters,
     bg_buffers: BgBuffers,
@@ -328,7 +335,7 @@ pub struct PpuState {
 }
 
 impl PpuState {
-    pub fn new(timing_mode: TimingMode) -> Self {
+    pub fn new(timing_mode: NesTimingMode) -> Self {
         Self {
             timing_mode,
             frame_buffer: [[(0, ColorEmphasis::default()); SCREEN_WIDTH as usize];
@@ -348,7 +355,8 @@ impl PpuState {
     /// Return whether the PPU is currently in the vertical blanking period.
     ///
     /// While the PPU's first idle scanline is scanline 240, this method will not return true
-    /// until scanline 241 in order to align with when the PPU sets the VBlank flag in PPUSTATUS.
+    /// until scanline 241 (291 on Dendy) in order to align with when the PPU sets the VBlank flag
+    /// in PPUSTATUS.
     pub fn in_vblank(&self) -> bool {
         self.timing_mode.vblank_scanlines().contains(&self.scanline)
     }
@@ -392,7 +400,9 @@ pub fn tick(state: &mut PpuState, bus: &mut PpuBus<'_>, config: NesEmulatorConfi
         ppu_registers.set_vblank_flag(false);
         ppu_registers.set_sprite_0_hit(false);
         ppu_registers.set_sprite_overflow(false);
-    } else if state.scanline == FIRST_VBLANK_SCANLINE && state.dot == VBLANK_FLAG_SET_DOT {
+    } else if state.scanline == *state.timing_mode.vblank_scanlines().start()
+        && state.dot == VBLANK_FLAG_SET_DOT
+    {
         bus.get_ppu_registers_mut().set_vblank_flag(true);
     }
 
@@ -443,7 +453,7 @@ pub fn tick(state: &mut PpuState, bus: &mut PpuBus<'_>, config: NesEmulatorConfi
         if state.scanline == state.timing_mode.pre_render_scanline() + 1 {
             state.scanline = 0;
 
-            if state.timing_mode == TimingMode::Ntsc && state.odd_frame && rendering_enabled {
+            if state.timing_mode == NesTimingMode::Ntsc && state.odd_frame && rendering_enabled {
                 // In NTSC, skip the idle cycle in the first visible scanline on odd frames
                 state.dot = 1;
             }
@@ -483,7 +493,9 @@ fn process_scanline(state: &mut PpuState, bus: &mut PpuBus<'_>, remove_sprite_li
     }
 
     match (timing_mode, scanline) {
-        (_, 0..=239) | (TimingMode::Ntsc, 261) | (TimingMode::Pal, 311) => {
+        (_, 0..=239)
+        | (NesTimingMode::Ntsc, 261)
+        | (NesTimingMode::Pal | NesTimingMode::Dendy, 311) => {
             let is_pre_render_scanline = scanline == timing_mode.pre_render_scanline();
 
             if is_pre_render_scanline && RESET_VERTICAL_POS_DOTS.contains(&dot) {
@@ -593,7 +605,8 @@ fn process_scanline(state: &mut PpuState, bus: &mut PpuBus<'_>, remove_sprite_li
                 _ => panic!("invalid dot: {dot}"),
             }
         }
-        (TimingMode::Ntsc, 240..=260) | (TimingMode::Pal, 240..=310) => {
+        (NesTimingMode::Ntsc, 240..=260)
+        | (NesTimingMode::Pal | NesTimingMode::Dendy, 240..=310) => {
             // PPU idle scanlines
 
             bus.get_ppu_registers_mut().set_oam_open_bus(None);
@@ -0,0 +1,354 @@
+//! PC Engine public interface and main loop
+
+use crate::audio::AudioResampler;
+use crate::bus::Bus;
+use crate::cartridge::Cartridge;
+use crate::input::{InputState, PceInputs};
+use crate::interrupts::InterruptRegisters;
+use crate::memory::Memory;
+use crate::psg::Psg;
+use crate::timer::Timer;
+use crate::vce::{DotClock, Vce};
+use crate::vdc;
+use crate::vdc::Vdc;
+use bincode::{Decode, Encode};
+use huc6280_emu::{ClockSpeed, HuC6280};
+use jgenesis_common::audio::ResamplerQuality;
+use jgenesis_common::frontend::{
+    AudioOutput, Color, EmulatorTrait, FrameSize, PixelAspectRatio, Renderer, SaveWriter,
+    TickEffect, TickResult, TimingMode,
+};
+use jgenesis_common::rng::{InitialRamState, Rng};
+use jgenesis_common::state::StateFormat;
+use jgenesis_proc_macros::{EnumDisplay, EnumFromStr, FakeDecode, FakeEncode, PartialClone};
+use std::fmt::{Debug, Display};
+use std::ops::{Deref, DerefMut};
+use thiserror::Error;
+
+pub const MASTER_CLOCK_FREQUENCY: f64 = 21_477_272.727_272_727;
+
+const MCLK_CYCLES_PER_LINE: u32 = 1365;
+
+// CPU cycles take 3 master clock cycles in high-speed mode (7.16 MHz) and 12 in low-speed mode
+// (1.79 MHz)
+const HIGH_SPEED_CPU_DIVIDER: u32 = 3;
+const LOW_SPEED_CPU_DIVIDER: u32 = 12;
+
+// Only lines 14-255 of each frame are visible on a typical TV
+const FIRST_VISIBLE_LINE: u16 = 14;
+const FRAME_HEIGHT: u16 = 242;
+
+const FRAME_BUFFER_LEN: usize = vdc::MAX_SCREEN_WIDTH * FRAME_HEIGHT as usize;
+
+#[derive(Debug, Error)]
+pub enum PceLoadError {
+    #[error("ROM file is empty")]
+    EmptyRom,
+}
+
+#[derive(Debug, Error)]
+pub enum PceError<RErr, AErr> {
+    #[error("Error rendering a frame: {0}")]
+    Rendering(RErr),
+    #[error("Error outputting audio samples: {0}")]
+    Audio(AErr),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode, EnumDisplay, EnumFromStr)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PceRegion {
+    /// PC Engine
+    #[default]
+    Japan,
+    /// TurboGrafx-16
+    Americas,
+}
+
+impl PceRegion {
+    fn region_bit(self) -> bool {
+        self == Self::Japan
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode, EnumDisplay, EnumFromStr)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PceAspectRatio {
+    #[default]
+    Ntsc,
+    SquarePixels,
+    Stretched,
+}
+
+impl PceAspectRatio {
+    fn to_pixel_aspect_ratio(self, dot_clock: DotClock) -> Option<PixelAspectRatio> {
+        let pixel_aspect_ratio = match (self, dot_clock) {
+            (Self::Ntsc, DotClock::Low) => 8.0 / 7.0,
+            (Self::Ntsc, DotClock::Medium) => 6.0 / 7.0,
+            (Self::Ntsc, DotClock::High) => 4.0 / 7.0,
+            (Self::SquarePixels, _) => 1.0,
+            (Self::Stretched, _) => return None,
+        };
+
+        Some(PixelAspectRatio::try_from(pixel_aspect_ratio).unwrap())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Encode, Decode)]
+pub struct PceEmulatorConfig {
+    pub region: PceRegion,
+    pub aspect_ratio: PceAspectRatio,
+    pub audio_resampler_quality: ResamplerQuality,
+    /// Initial contents of working RAM; if None, RAM is zeroed
+    pub initial_ram_state: Option<InitialRamState>,
+    /// Seed for all in-core randomness, including random initial RAM contents; if None, a seed is
+    /// chosen randomly at power on
+    pub rng_seed: Option<u64>,
+}
+
+#[derive(Debug, Clone, FakeEncode, FakeDecode)]
+struct FrameBuffer(Vec<Color>);
+
+impl Default for FrameBuffer {
+    fn default() -> Self {
+        Self(vec![Color::default(); FRAME_BUFFER_LEN])
+    }
+}
+
+impl Deref for FrameBuffer {
+    type Target = Vec<Color>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for FrameBuffer {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+#[derive(Debug, Clone, Encode, Decode, PartialClone)]
+pub struct PceEmulator {
+    cpu: HuC6280,
+    #[partial_clone(partial)]
+    cartridge: Cartridge,
+    memory: Memory,
+    vdc: Vdc,
+    vce: Vce,
+    psg: Psg,
+    timer: Timer,
+    input_state: InputState,
+    interrupt_registers: InterruptRegisters,
+    audio_resampler: AudioResampler,
+    frame_buffer: FrameBuffer,
+    frame_width: u32,
+    line: u16,
+    line_mclk_counter: u32,
+    config: PceEmulatorConfig,
+}
+
+macro_rules! new_bus {
+    ($self:expr) => {
+        Bus {
+            region_bit: $self.config.region.region_bit(),
+            cartridge: &mut $self.cartridge,
+            memory: &mut $self.memory,
+            vdc: &mut $self.vdc,
+            vce: &mut $self.vce,
+            psg: &mut $self.psg,
+            timer: &mut $self.timer,
+            input_state: &mut $self.input_state,
+            interrupt_registers: &mut $self.interrupt_registers,
+        }
+    };
+}
+
+impl PceEmulator {
+    /// # Errors
+    ///
+    /// This function will return an error if the ROM is invalid.
+    pub fn create(rom: Vec<u8>, config: PceEmulatorConfig) -> Result<Self, PceLoadError> {
+        let mut cartridge = Cartridge::create(rom)?;
+
+        let mut rng = Rng::from_optional_seed(config.rng_seed);
+        let initial_ram_state = config.initial_ram_state.unwrap_or(InitialRamState::AllZeroes);
+        let mut memory = Memory::new(initial_ram_state, &mut rng);
+
+        let mut vdc = Vdc::new();
+        let mut vce = Vce::new();
+        let mut psg = Psg::new();
+        let mut timer = Timer::new();
+        let mut input_state = InputState::new();
+        let mut interrupt_registers = InterruptRegisters::default();
+
+        let cpu = HuC6280::new(&mut Bus {
+            region_bit: config.region.region_bit(),
+            cartridge: &mut cartridge,
+            memory: &mut memory,
+            vdc: &mut vdc,
+            vce: &mut vce,
+            psg: &mut psg,
+            timer: &mut timer,
+            input_state: &mut input_state,
+            interrupt_registers: &mut interrupt_registers,
+        });
+
+        Ok(Self {
+            cpu,
+            cartridge,
+            memory,
+            vdc,
+            vce,
+            psg,
+            timer,
+            input_state,
+            interrupt_registers,
+            audio_resampler: AudioResampler::new(config.audio_resampler_quality),
+            frame_buffer: FrameBuffer::default(),
+            frame_width: 256,
+            line: 0,
+            line_mclk_counter: 0,
+            config,
+        })
+    }
+
+    pub fn copy_color_table(&self, out: &mut [Color]) {
+        self.vce.copy_color_table(out);
+    }
+
+    fn frame_size(&self) -> FrameSize {
+        FrameSize { width: self.frame_width, height: FRAME_HEIGHT.into() }
+    }
+
+    fn render_frame<R: Renderer>(&self, renderer: &mut R) -> Result<(), R::Err> {
+        let frame_size = self.frame_size();
+        let pixel_aspect_ratio =
+            self.config.aspect_ratio.to_pixel_aspect_ratio(self.vce.dot_clock());
+        renderer.render_frame(
+            &self.frame_buffer[..(frame_size.width * frame_size.height) as usize],
+            frame_size,
+            pixel_aspect_ratio,
+        )
+    }
+
+    // Returns true when a new frame has started
+    fn advance_line(&mut self) -> bool {
+        self.line += 1;
+        let new_frame = self.line >= self.vce.lines_per_frame();
+        if new_frame {
+            self.line = 0;
+        }
+
+        let active = self.vdc.begin_line(self.line, self.vce.lines_per_frame());
+        if self.line == 0 {
+            self.frame_width = self.vdc.screen_width() as u32;
+        }
+
+        if (FIRST_VISIBLE_LINE..FIRST_VISIBLE_LINE + FRAME_HEIGHT).contains(&self.line) {
+            let width = self.frame_width as usize;
+            let row_start = usize::from(self.line - FIRST_VISIBLE_LINE) * width;
+            let row = &mut self.frame_buffer[row_start..row_start + width];
+
+            if active {
+                self.vce.render_line(self.vdc.line_buffer(), row);
+            } else {
+                self.vce.fill_overscan(row);
+            }
+        }
+
+        new_frame
+    }
+}
+
+impl EmulatorTrait for PceEmulator {
+    type Inputs = PceInputs;
+    type Config = PceEmulatorConfig;
+    const STATE_FORMAT: StateFormat = StateFormat { core: "pce", version: 1 };
+    type Err<
+        RErr: Debug + Display + Send + Sync + 'static,
+        AErr: Debug + Display + Send + Sync + 'static,
+    > = PceError<RErr, AErr>;
+
+    fn tick<R, A>(
+        &mut self,
+        renderer: &mut R,
+        audio_output: &mut A,
+        inputs: &Self::Inputs,
+    ) -> TickResult<Self::Err<R::Err, A::Err>>
+    where
+        R: Renderer,
+        R::Err: Debug + Display + Send + Sync + 'static,
+        A: AudioOutput,
+        A::Err: Debug + Display + Send + Sync + 'static,
+    {
+        self.input_state.set_inputs(*inputs);
+
+        let cpu_cycles = self.cpu.execute_instruction(&mut new_bus!(self));
+        let mclk_cycles = cpu_cycles
+            * match self.cpu.clock_speed() {
+                ClockSpeed::High => HIGH_SPEED_CPU_DIVIDER,
+                ClockSpeed::Low => LOW_SPEED_CPU_DIVIDER,
+            };
+
+        self.timer.tick(mclk_cycles);
+        self.psg.tick(mclk_cycles, &mut self.audio_resampler);
+
+        let mut frame_complete = false;
+        self.line_mclk_counter += mclk_cycles;
+        while self.line_mclk_counter >= MCLK_CYCLES_PER_LINE {
+            self.line_mclk_counter -= MCLK_CYCLES_PER_LINE;
+            frame_complete |= self.advance_line();
+        }
+
+        if frame_complete {
+            self.render_frame(renderer).map_err(PceError::Rendering)?;
+            self.audio_resampler.output_samples(audio_output).map_err(PceError::Audio)?;
+
+            Ok(TickEffect::FrameRendered)
+        } else {
+            Ok(TickEffect::None)
+        }
+    }
+
+    fn save_dirty(&self) -> bool {
+        false
+    }
+
+    fn persist_save<S: SaveWriter>(&mut self, _save_writer: &mut S) -> Result<(), S::Err> {
+        Ok(())
+    }
+
+    fn force_render<R>(&mut self, renderer: &mut R) -> Result<(), R::Err>
+    where
+        R: Renderer,
+    {
+        self.render_frame(renderer)
+    }
+
+    fn reload_config(&mut self, config: &Self::Config) {
+        self.config = *config;
+        self.audio_resampler.set_quality(config.audio_resampler_quality);
+    }
+
+    fn take_rom_from(&mut self, other: &mut Self) {
+        self.cartridge.take_rom_from(&mut other.cartridge);
+    }
+
+    fn soft_reset(&mut self) {
+        // The PC Engine has no reset button; games implement soft reset by jumping to the reset
+        // vector when Run and Select are pressed simultaneously. This resets the CPU only.
+        self.cpu.reset(&mut new_bus!(self));
+    }
+
+    fn hard_reset<S: SaveWriter>(&mut self, _save_writer: &mut S) {
+        let rom = self.cartridge.take_rom();
+
+        *self =
+            Self::create(rom, self.config).expect("Hard reset should never fail to load cartridge");
+    }
+
+    fn timing_mode(&self) -> TimingMode {
+        TimingMode::Ntsc
+    }
+}
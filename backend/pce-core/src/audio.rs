@@ -0,0 +1,90 @@
+//! PC Engine audio resampling code
+
+#![allow(clippy::excessive_precision)]
+
+use bincode::{Decode, Encode};
+use jgenesis_common::audio::{ResamplerQuality, SignalResampler};
+use jgenesis_common::frontend::AudioOutput;
+
+// The PSG is clocked at 3.58 MHz, and the emulator samples it once every 16 PSG cycles
+pub const PSG_SAMPLE_FREQUENCY: f64 = crate::api::MASTER_CLOCK_FREQUENCY / 6.0 / 16.0;
+
+// Hamming-windowed sinc low-pass filter, equivalent to Octave's
+// `fir1(35, 24000 / (223721.59 / 2), 'low')`
+const PSG_LPF_COEFFICIENT_0: f64 = -0.0010165377125193208;
+const PSG_LPF_COEFFICIENTS: [f64; 36] = [
+    -0.0010165377125193208,
+    -0.0016771802652183095,
+    -0.0019217549584516446,
+    -0.0010915875704804452,
+    0.0014627645335739724,
+    0.005438053316591202,
+    0.008890888113320063,
+    0.008626849251923467,
+    0.0019261515444451652,
+    -0.01107358339489801,
+    -0.0257465320793466,
+    -0.033472091051556824,
+    -0.024676902472780593,
+    0.006585122178623175,
+    0.05864796383254519,
+    0.12106435708998083,
+    0.17732966493683944,
+    0.2107043547074093,
+    0.2107043547074093,
+    0.17732966493683944,
+    0.12106435708998083,
+    0.058647963832545204,
+    0.006585122178623177,
+    -0.024676902472780596,
+    -0.03347209105155683,
+    -0.025746532079346605,
+    -0.011073583394898012,
+    0.0019261515444451657,
+    0.008626849251923469,
+    0.008890888113320065,
+    0.005438053316591205,
+    0.0014627645335739733,
+    -0.0010915875704804456,
+    -0.0019217549584516457,
+    -0.0016771802652183095,
+    -0.0010165377125193208,
+];
+
+const PSG_HPF_CHARGE_FACTOR: f64 = 0.999212882632514;
+
+type PsgResampler = SignalResampler<36, 0>;
+
+#[derive(Debug, Clone, Encode, Decode)]
+pub struct AudioResampler {
+    psg_resampler: PsgResampler,
+}
+
+impl AudioResampler {
+    pub fn new(quality: ResamplerQuality) -> Self {
+        let mut psg_resampler = PsgResampler::new(
+            PSG_SAMPLE_FREQUENCY,
+            PSG_LPF_COEFFICIENT_0,
+            PSG_LPF_COEFFICIENTS,
+            PSG_HPF_CHARGE_FACTOR,
+        );
+        psg_resampler.set_quality(quality);
+        Self { psg_resampler }
+    }
+
+    pub fn set_quality(&mut self, quality: ResamplerQuality) {
+        self.psg_resampler.set_quality(quality);
+    }
+
+    pub fn collect_sample(&mut self, sample_l: f64, sample_r: f64) {
+        self.psg_resampler.collect_sample(sample_l, sample_r);
+    }
+
+    pub fn output_samples<A: AudioOutput>(&mut self, audio_output: &mut A) -> Result<(), A::Err> {
+        while let Some((sample_l, sample_r)) = self.psg_resampler.output_buffer_pop_front() {
+            audio_output.push_sample(sample_l, sample_r)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,137 @@
+//! PC Engine bus / address mapping
+//!
+//! The `HuC6280` has a 21-bit physical address space split into 256 banks of 8KB:
+//! * $00-$7F: `HuCard` ROM
+//! * $F8-$FB: 8KB working RAM (mirrored 4 times)
+//! * $FF: I/O (VDC, VCE, PSG, timer, joypad, interrupt controller)
+
+use crate::cartridge::Cartridge;
+use crate::input::InputState;
+use crate::interrupts;
+use crate::interrupts::InterruptRegisters;
+use crate::memory::Memory;
+use crate::psg::Psg;
+use crate::timer::Timer;
+use crate::vce::Vce;
+use crate::vdc::Vdc;
+use huc6280_emu::traits::BusInterface;
+
+pub struct Bus<'a> {
+    pub region_bit: bool,
+    pub cartridge: &'a mut Cartridge,
+    pub memory: &'a mut Memory,
+    pub vdc: &'a mut Vdc,
+    pub vce: &'a mut Vce,
+    pub psg: &'a mut Psg,
+    pub timer: &'a mut Timer,
+    pub input_state: &'a mut InputState,
+    pub interrupt_registers: &'a mut InterruptRegisters,
+}
+
+impl Bus<'_> {
+    fn read_io(&mut self, address: u32) -> u8 {
+        let io_buffer = self.memory.io_buffer();
+
+        match address & 0x1FFF {
+            0x0000..=0x03FF => self.vdc.read(address),
+            0x0400..=0x07FF => self.vce.read(address),
+            // PSG registers are write-only
+            0x0800..=0x0BFF => io_buffer,
+            0x0C00..=0x0FFF => {
+                let value = self.timer.read_counter() | (io_buffer & 0x80);
+                self.memory.set_io_buffer(value);
+                value
+            }
+            0x1000..=0x13FF => {
+                let value = self.input_state.read_port(self.region_bit);
+                self.memory.set_io_buffer(value);
+                value
+            }
+            0x1400..=0x17FF => {
+                let value = match address & 3 {
+                    2 => self.interrupt_registers.read_disable(),
+                    3 => interrupts::read_status(
+                        false,
+                        self.vdc.irq(),
+                        self.timer.interrupt_pending(),
+                    ),
+                    _ => return io_buffer,
+                };
+                let value = value | (io_buffer & 0xF8);
+                self.memory.set_io_buffer(value);
+                value
+            }
+            _ => 0xFF,
+        }
+    }
+
+    fn write_io(&mut self, address: u32, value: u8) {
+        match address & 0x1FFF {
+            0x0000..=0x03FF => self.vdc.write(address, value),
+            0x0400..=0x07FF => self.vce.write(address, value),
+            0x0800..=0x0BFF => {
+                self.memory.set_io_buffer(value);
+                self.psg.write(address, value);
+            }
+            0x0C00..=0x0FFF => {
+                self.memory.set_io_buffer(value);
+                match address & 1 {
+                    0 => self.timer.write_reload(value),
+                    1 => self.timer.write_control(value),
+                    _ => unreachable!("value & 1 is always <= 1"),
+                }
+            }
+            0x1000..=0x13FF => {
+                self.memory.set_io_buffer(value);
+                self.input_state.write_port(value);
+            }
+            0x1400..=0x17FF => {
+                self.memory.set_io_buffer(value);
+                match address & 3 {
+                    2 => self.interrupt_registers.write_disable(value),
+                    // Any write to $1403 acknowledges the timer interrupt
+                    3 => self.timer.acknowledge_interrupt(),
+                    _ => {}
+                }
+            }
+            _ => {
+                log::trace!("Unmapped I/O write: {address:06X} {value:02X}");
+            }
+        }
+    }
+}
+
+impl BusInterface for Bus<'_> {
+    fn read(&mut self, address: u32) -> u8 {
+        match address >> 13 {
+            0x00..=0x7F => self.cartridge.read(address),
+            0xF8..=0xFB => self.memory.read_working_ram(address),
+            0xFF => self.read_io(address),
+            _ => 0xFF,
+        }
+    }
+
+    fn write(&mut self, address: u32, value: u8) {
+        match address >> 13 {
+            0x00..=0x7F => self.cartridge.write(address, value),
+            0xF8..=0xFB => self.memory.write_working_ram(address, value),
+            0xFF => self.write_io(address, value),
+            _ => {
+                log::trace!("Unmapped write: {address:06X} {value:02X}");
+            }
+        }
+    }
+
+    fn irq1(&self) -> bool {
+        self.vdc.irq() && self.interrupt_registers.irq1_enabled()
+    }
+
+    fn irq2(&self) -> bool {
+        // IRQ2 is only used by the CD-ROM unit, which is not emulated
+        false
+    }
+
+    fn timer_irq(&self) -> bool {
+        self.timer.interrupt_pending() && self.interrupt_registers.timer_enabled()
+    }
+}
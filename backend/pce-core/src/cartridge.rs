@@ -0,0 +1,156 @@
+//! `HuCard` ROM mapping
+//!
+//! `HuCard`s occupy physical banks $00-$7F (1MB). Nearly all `HuCard`s are simple ROM chips that are
+//! mirrored to fill the address space, with two exceptions:
+//! * 384KB `HuCard`s are wired as a 256KB chip at $00-$3F and a 128KB chip at $40-$7F
+//! * Street Fighter II' (2.5MB) has a mapper that switches which 512KB bank is visible at $40-$7F
+
+use crate::api::PceLoadError;
+use bincode::{Decode, Encode};
+use jgenesis_proc_macros::{FakeDecode, FakeEncode, PartialClone};
+use std::ops::Deref;
+
+const BANK_LEN: usize = 8 * 1024;
+
+// Some ROM dumps include a 512-byte header that is not part of the ROM
+const COPIER_HEADER_LEN: usize = 512;
+
+const ROM_384K_LEN: usize = 384 * 1024;
+const SF2_MIN_LEN: usize = 1024 * 1024 + 1;
+
+#[derive(Debug, Clone, Default, FakeEncode, FakeDecode)]
+struct Rom(Box<[u8]>);
+
+impl Deref for Rom {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+enum Mapper {
+    Standard,
+    Split384K,
+    StreetFighterII { bank: u8 },
+}
+
+#[derive(Debug, Clone, Encode, Decode, PartialClone)]
+pub struct Cartridge {
+    #[partial_clone(default)]
+    rom: Rom,
+    mapper: Mapper,
+}
+
+impl Cartridge {
+    pub fn create(mut rom: Vec<u8>) -> Result<Self, PceLoadError> {
+        if rom.len() % BANK_LEN == COPIER_HEADER_LEN {
+            log::info!("Stripping 512-byte copier header from ROM");
+            rom.drain(..COPIER_HEADER_LEN);
+        }
+
+        if rom.is_empty() {
+            return Err(PceLoadError::EmptyRom);
+        }
+
+        let mapper = match rom.len() {
+            ROM_384K_LEN => Mapper::Split384K,
+            len if len >= SF2_MIN_LEN => Mapper::StreetFighterII { bank: 0 },
+            _ => Mapper::Standard,
+        };
+
+        log::info!("Loaded HuCard ROM of size {} KB, using mapper {mapper:?}", rom.len() / 1024);
+
+        Ok(Self { rom: Rom(rom.into_boxed_slice()), mapper })
+    }
+
+    fn map_address(&self, address: u32) -> usize {
+        let bank = ((address >> 13) & 0x7F) as usize;
+        let offset = (address & 0x1FFF) as usize;
+
+        let rom_bank = match self.mapper {
+            Mapper::Standard => bank,
+            Mapper::Split384K => {
+                if bank < 0x40 { bank & 0x1F } else { 0x20 | (bank & 0x0F) }
+            }
+            Mapper::StreetFighterII { bank: sf2_bank } => {
+                if bank < 0x40 { bank } else { 0x40 * (1 + usize::from(sf2_bank)) + (bank & 0x3F) }
+            }
+        };
+
+        (rom_bank * BANK_LEN + offset) % self.rom.len()
+    }
+
+    pub fn read(&self, address: u32) -> u8 {
+        self.rom[self.map_address(address)]
+    }
+
+    pub fn write(&mut self, address: u32, _value: u8) {
+        if let Mapper::StreetFighterII { bank } = &mut self.mapper {
+            // The SF2 mapper responds to writes to $1FF0-$1FF3 within any ROM bank
+            if address & 0x1FFC == 0x1FF0 {
+                *bank = (address & 0x03) as u8;
+            }
+        }
+    }
+
+    pub fn take_rom(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.rom.0).into_vec()
+    }
+
+    pub fn take_rom_from(&mut self, other: &mut Self) {
+        self.rom = std::mem::take(&mut other.rom);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numbered_banks(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i / BANK_LEN) as u8).collect()
+    }
+
+    #[test]
+    fn standard_mirroring() {
+        let cartridge = Cartridge::create(numbered_banks(256 * 1024)).unwrap();
+
+        assert_eq!(cartridge.read(0x00_0000), 0x00);
+        assert_eq!(cartridge.read(0x03_E000), 0x1F);
+        assert_eq!(cartridge.read(0x04_0000), 0x00);
+        assert_eq!(cartridge.read(0x0F_E000), 0x1F);
+    }
+
+    #[test]
+    fn split_384k() {
+        let cartridge = Cartridge::create(numbered_banks(ROM_384K_LEN)).unwrap();
+
+        assert_eq!(cartridge.read(0x03_E000), 0x1F);
+        assert_eq!(cartridge.read(0x04_0000), 0x00);
+        assert_eq!(cartridge.read(0x08_0000), 0x20);
+        assert_eq!(cartridge.read(0x0A_0000), 0x20);
+        assert_eq!(cartridge.read(0x0F_E000), 0x2F);
+    }
+
+    #[test]
+    fn copier_header() {
+        let mut rom = vec![0xFF; COPIER_HEADER_LEN];
+        rom.extend(numbered_banks(128 * 1024));
+        let cartridge = Cartridge::create(rom).unwrap();
+
+        assert_eq!(cartridge.read(0x00_0000), 0x00);
+        assert_eq!(cartridge.read(0x00_2000), 0x01);
+    }
+
+    #[test]
+    fn street_fighter_ii_banking() {
+        let mut cartridge = Cartridge::create(numbered_banks(2560 * 1024)).unwrap();
+
+        assert_eq!(cartridge.read(0x08_0000), 0x40);
+
+        cartridge.write(0x00_1FF2, 0);
+        assert_eq!(cartridge.read(0x08_0000), 0xC0);
+        assert_eq!(cartridge.read(0x00_0000), 0x00);
+    }
+}
@@ -0,0 +1,66 @@
+//! PC Engine controller input handling
+
+use bincode::{Decode, Encode};
+use jgenesis_common::num::GetBit;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode)]
+pub struct PceJoypadState {
+    pub up: bool,
+    pub left: bool,
+    pub right: bool,
+    pub down: bool,
+    pub i: bool,
+    pub ii: bool,
+    pub select: bool,
+    pub run: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode)]
+pub struct PceInputs {
+    pub p1: PceJoypadState,
+}
+
+#[derive(Debug, Clone, Encode, Decode)]
+pub(crate) struct InputState {
+    inputs: PceInputs,
+    select_line: bool,
+    clear_line: bool,
+}
+
+impl InputState {
+    pub(crate) fn new() -> Self {
+        Self { inputs: PceInputs::default(), select_line: false, clear_line: false }
+    }
+
+    pub(crate) fn set_inputs(&mut self, inputs: PceInputs) {
+        self.inputs = inputs;
+    }
+
+    // $1000 write: bit 0 is SEL and bit 1 is CLR
+    pub(crate) fn write_port(&mut self, value: u8) {
+        self.select_line = value.bit(0);
+        self.clear_line = value.bit(1);
+    }
+
+    // $1000 read: bits 0-3 are the active-low controller data, bit 6 is the region bit (set on
+    // PC Engine, clear on TurboGrafx-16), and bit 7 is clear if a CD-ROM unit is attached
+    pub(crate) fn read_port(&self, region_bit: bool) -> u8 {
+        let joypad = self.inputs.p1;
+
+        let data = if self.clear_line {
+            0x0
+        } else if self.select_line {
+            (u8::from(!joypad.left) << 3)
+                | (u8::from(!joypad.down) << 2)
+                | (u8::from(!joypad.right) << 1)
+                | u8::from(!joypad.up)
+        } else {
+            (u8::from(!joypad.run) << 3)
+                | (u8::from(!joypad.select) << 2)
+                | (u8::from(!joypad.ii) << 1)
+                | u8::from(!joypad.i)
+        };
+
+        0x80 | (u8::from(region_bit) << 6) | 0x30 | data
+    }
+}
@@ -0,0 +1,40 @@
+//! `HuC6280` interrupt controller registers
+
+use bincode::{Decode, Encode};
+use jgenesis_common::num::GetBit;
+
+#[derive(Debug, Clone, Default, Encode, Decode)]
+pub struct InterruptRegisters {
+    irq2_disabled: bool,
+    irq1_disabled: bool,
+    timer_disabled: bool,
+}
+
+impl InterruptRegisters {
+    // $1402 write
+    pub fn write_disable(&mut self, value: u8) {
+        self.irq2_disabled = value.bit(0);
+        self.irq1_disabled = value.bit(1);
+        self.timer_disabled = value.bit(2);
+    }
+
+    // $1402 read
+    pub fn read_disable(&self) -> u8 {
+        (u8::from(self.timer_disabled) << 2)
+            | (u8::from(self.irq1_disabled) << 1)
+            | u8::from(self.irq2_disabled)
+    }
+
+    pub fn irq1_enabled(&self) -> bool {
+        !self.irq1_disabled
+    }
+
+    pub fn timer_enabled(&self) -> bool {
+        !self.timer_disabled
+    }
+}
+
+// $1403 read
+pub fn read_status(irq2: bool, irq1: bool, timer: bool) -> u8 {
+    (u8::from(timer) << 2) | (u8::from(irq1) << 1) | u8::from(irq2)
+}
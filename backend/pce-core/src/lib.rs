@@ -0,0 +1,18 @@
+//! PC Engine / TurboGrafx-16 emulation core
+//!
+//! Only `HuCard` software is supported; the CD-ROM² add-on and `SuperGrafx` are not emulated.
+
+pub mod api;
+mod audio;
+mod bus;
+mod cartridge;
+pub mod input;
+mod interrupts;
+mod memory;
+mod psg;
+mod timer;
+mod vce;
+mod vdc;
+
+pub use api::{PceAspectRatio, PceEmulator, PceEmulatorConfig, PceError, PceLoadError, PceRegion};
+pub use input::{PceInputs, PceJoypadState};
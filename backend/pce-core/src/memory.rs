@@ -0,0 +1,42 @@
+//! PC Engine internal memory
+
+use bincode::{Decode, Encode};
+use jgenesis_common::rng::{InitialRamState, Rng};
+
+const WORKING_RAM_LEN: usize = 8 * 1024;
+
+type WorkingRam = [u8; WORKING_RAM_LEN];
+
+#[derive(Debug, Clone, Encode, Decode)]
+pub struct Memory {
+    working_ram: Box<WorkingRam>,
+    // Last value written to or read from the I/O area; reads from write-only or partially
+    // unmapped I/O registers return bits from this buffer
+    io_buffer: u8,
+}
+
+impl Memory {
+    pub fn new(initial_ram_state: InitialRamState, rng: &mut Rng) -> Self {
+        let mut working_ram: Box<WorkingRam> =
+            vec![0; WORKING_RAM_LEN].into_boxed_slice().try_into().unwrap();
+        initial_ram_state.fill(working_ram.as_mut(), rng);
+
+        Self { working_ram, io_buffer: 0xFF }
+    }
+
+    pub fn read_working_ram(&self, address: u32) -> u8 {
+        self.working_ram[(address as usize) & (WORKING_RAM_LEN - 1)]
+    }
+
+    pub fn write_working_ram(&mut self, address: u32, value: u8) {
+        self.working_ram[(address as usize) & (WORKING_RAM_LEN - 1)] = value;
+    }
+
+    pub fn io_buffer(&self) -> u8 {
+        self.io_buffer
+    }
+
+    pub fn set_io_buffer(&mut self, value: u8) {
+        self.io_buffer = value;
+    }
+}
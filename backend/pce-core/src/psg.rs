@@ -0,0 +1,288 @@
+//! `HuC6280` PSG (programmable sound generator)
+//!
+//! The PSG has 6 wavetable channels, each playing a 32-sample 5-bit waveform. Channels 4 and 5 can
+//! alternatively output noise, and channel 1 can be used as an LFO to modulate channel 0's
+//! frequency. Any channel can also be switched to DDA mode where the CPU writes samples directly.
+
+use crate::audio::AudioResampler;
+use bincode::{Decode, Encode};
+use jgenesis_common::num::GetBit;
+use std::array;
+
+// The PSG is clocked at master clock / 6 (3.58 MHz)
+const PSG_DIVIDER: u32 = 6;
+
+// One output sample every 16 PSG clocks
+const SAMPLE_DIVIDER: u32 = 16;
+
+const WAVEFORM_LEN: usize = 32;
+
+const NUM_CHANNELS: usize = 6;
+
+// Each step of attenuation is 1.5dB; volume steps are 1 step each and balance steps are 2 steps
+// each. The output is silent at 31 or more steps of attenuation.
+const MAX_ATTENUATION_STEPS: u8 = 31;
+
+#[derive(Debug, Clone, Encode, Decode)]
+struct Channel {
+    frequency: u16,
+    enabled: bool,
+    dda: bool,
+    volume: u8,
+    balance_l: u8,
+    balance_r: u8,
+    waveform: [u8; WAVEFORM_LEN],
+    waveform_index: u8,
+    dda_sample: u8,
+    counter: u32,
+    noise_enabled: bool,
+    noise_frequency: u8,
+    noise_counter: u32,
+    noise_lfsr: u32,
+}
+
+impl Channel {
+    fn new() -> Self {
+        Self {
+            frequency: 0,
+            enabled: false,
+            dda: false,
+            volume: 0,
+            balance_l: 0,
+            balance_r: 0,
+            waveform: [0; WAVEFORM_LEN],
+            waveform_index: 0,
+            dda_sample: 0,
+            counter: 0x1000,
+            noise_enabled: false,
+            noise_frequency: 0,
+            noise_counter: 0,
+            noise_lfsr: 1,
+        }
+    }
+
+    fn write_control(&mut self, value: u8) {
+        self.enabled = value.bit(7);
+        self.dda = value.bit(6);
+        self.volume = value & 0x1F;
+
+        // Setting DDA while the channel is disabled resets the waveform index
+        if self.dda && !self.enabled {
+            self.waveform_index = 0;
+        }
+    }
+
+    fn write_waveform(&mut self, value: u8) {
+        let value = value & 0x1F;
+
+        if self.dda {
+            self.dda_sample = value;
+        } else if !self.enabled {
+            self.waveform[self.waveform_index as usize] = value;
+            self.waveform_index = (self.waveform_index + 1) & 0x1F;
+        }
+    }
+
+    fn period(frequency: u16) -> u32 {
+        // A frequency of 0 behaves as $1000
+        if frequency == 0 { 0x1000 } else { frequency.into() }
+    }
+
+    fn noise_period(&self) -> u32 {
+        64 * u32::from(0x1F - self.noise_frequency).max(1)
+    }
+
+    fn clock(&mut self, psg_cycles: u32, period: u32) {
+        if self.noise_enabled {
+            self.noise_counter += psg_cycles;
+            let noise_period = self.noise_period();
+            while self.noise_counter >= noise_period {
+                self.noise_counter -= noise_period;
+                self.clock_lfsr();
+            }
+        }
+
+        if !self.enabled || self.dda {
+            return;
+        }
+
+        let mut remaining = psg_cycles;
+        while remaining >= self.counter {
+            remaining -= self.counter;
+            self.counter = period;
+            self.waveform_index = (self.waveform_index + 1) & 0x1F;
+        }
+        self.counter -= remaining;
+    }
+
+    fn clock_lfsr(&mut self) {
+        let lfsr = self.noise_lfsr;
+        let feedback = (lfsr ^ (lfsr >> 1) ^ (lfsr >> 11) ^ (lfsr >> 12) ^ (lfsr >> 17)) & 1;
+        self.noise_lfsr = (lfsr >> 1) | (feedback << 17);
+    }
+
+    fn current_sample(&self) -> u8 {
+        if self.dda {
+            self.dda_sample
+        } else if self.noise_enabled {
+            if self.noise_lfsr.bit(0) { 0x1F } else { 0 }
+        } else {
+            self.waveform[self.waveform_index as usize]
+        }
+    }
+
+    fn amplitude(&self, global_balance: u8, channel_balance: u8) -> f64 {
+        let steps = (MAX_ATTENUATION_STEPS - self.volume)
+            + 2 * (0xF - global_balance)
+            + 2 * (0xF - channel_balance);
+        if steps >= MAX_ATTENUATION_STEPS {
+            return 0.0;
+        }
+
+        10.0_f64.powf(-1.5 * f64::from(steps) / 20.0)
+    }
+
+    fn sample(&self, global_balance_l: u8, global_balance_r: u8) -> (f64, f64) {
+        if !self.enabled {
+            return (0.0, 0.0);
+        }
+
+        let sample = (f64::from(self.current_sample()) - 15.5) / 15.5;
+        (
+            sample * self.amplitude(global_balance_l, self.balance_l),
+            sample * self.amplitude(global_balance_r, self.balance_r),
+        )
+    }
+}
+
+#[derive(Debug, Clone, Encode, Decode)]
+pub struct Psg {
+    channels: [Channel; NUM_CHANNELS],
+    selected_channel: u8,
+    global_balance_l: u8,
+    global_balance_r: u8,
+    lfo_frequency: u8,
+    lfo_control: u8,
+    mclk_counter: u32,
+    psg_cycle_counter: u32,
+}
+
+impl Psg {
+    pub fn new() -> Self {
+        Self {
+            channels: array::from_fn(|_| Channel::new()),
+            selected_channel: 0,
+            global_balance_l: 0,
+            global_balance_r: 0,
+            lfo_frequency: 0,
+            lfo_control: 0,
+            mclk_counter: 0,
+            psg_cycle_counter: 0,
+        }
+    }
+
+    pub fn write(&mut self, address: u32, value: u8) {
+        log::trace!("PSG write: {:X} {value:02X}", address & 0xF);
+
+        let channel = self.channels.get_mut(self.selected_channel as usize);
+
+        match (address & 0xF, channel) {
+            (0x0, _) => self.selected_channel = value & 7,
+            (0x1, _) => {
+                self.global_balance_l = value >> 4;
+                self.global_balance_r = value & 0xF;
+            }
+            (0x2, Some(channel)) => {
+                channel.frequency = (channel.frequency & 0xF00) | u16::from(value);
+            }
+            (0x3, Some(channel)) => {
+                channel.frequency = (channel.frequency & 0x0FF) | (u16::from(value & 0xF) << 8);
+            }
+            (0x4, Some(channel)) => channel.write_control(value),
+            (0x5, Some(channel)) => {
+                channel.balance_l = value >> 4;
+                channel.balance_r = value & 0xF;
+            }
+            (0x6, Some(channel)) => channel.write_waveform(value),
+            // Only channels 4 and 5 support noise
+            (0x7, Some(channel)) if self.selected_channel >= 4 => {
+                channel.noise_enabled = value.bit(7);
+                channel.noise_frequency = value & 0x1F;
+            }
+            (0x8, _) => self.lfo_frequency = value,
+            (0x9, _) => {
+                self.lfo_control = value;
+                if value.bit(7) {
+                    self.channels[1].waveform_index = 0;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn lfo_active(&self) -> bool {
+        self.lfo_control & 3 != 0
+    }
+
+    pub fn tick(&mut self, mclk_cycles: u32, audio_resampler: &mut AudioResampler) {
+        self.mclk_counter += mclk_cycles;
+        let psg_cycles = self.mclk_counter / PSG_DIVIDER;
+        self.mclk_counter %= PSG_DIVIDER;
+
+        self.psg_cycle_counter += psg_cycles;
+        while self.psg_cycle_counter >= SAMPLE_DIVIDER {
+            self.psg_cycle_counter -= SAMPLE_DIVIDER;
+
+            self.clock_channels(SAMPLE_DIVIDER);
+
+            let (sample_l, sample_r) = self.sample();
+            audio_resampler.collect_sample(sample_l, sample_r);
+        }
+    }
+
+    fn clock_channels(&mut self, psg_cycles: u32) {
+        if self.lfo_active() {
+            // Channel 1 modulates channel 0's frequency, and channel 1 runs slower by a factor of
+            // the LFO frequency
+            let lfo_shift = 4 * ((self.lfo_control & 3) - 1);
+            let modulation = (i32::from(self.channels[1].current_sample()) - 16) << lfo_shift;
+            let frequency =
+                (i32::from(self.channels[0].frequency) + modulation).rem_euclid(0x1000) as u16;
+            self.channels[0].clock(psg_cycles, Channel::period(frequency));
+
+            let lfo_multiplier =
+                if self.lfo_frequency == 0 { 0x100 } else { u32::from(self.lfo_frequency) };
+            let lfo_period = Channel::period(self.channels[1].frequency) * lfo_multiplier;
+            if !self.lfo_control.bit(7) {
+                self.channels[1].clock(psg_cycles, lfo_period);
+            }
+        } else {
+            for channel in &mut self.channels[..2] {
+                channel.clock(psg_cycles, Channel::period(channel.frequency));
+            }
+        }
+
+        for channel in &mut self.channels[2..] {
+            channel.clock(psg_cycles, Channel::period(channel.frequency));
+        }
+    }
+
+    fn sample(&self) -> (f64, f64) {
+        let mut sample_l = 0.0;
+        let mut sample_r = 0.0;
+
+        for (i, channel) in self.channels.iter().enumerate() {
+            // Channel 1 does not produce audio output while it is used as an LFO
+            if i == 1 && self.lfo_active() {
+                continue;
+            }
+
+            let (channel_l, channel_r) =
+                channel.sample(self.global_balance_l, self.global_balance_r);
+            sample_l += channel_l;
+            sample_r += channel_r;
+        }
+
+        (sample_l / NUM_CHANNELS as f64, sample_r / NUM_CHANNELS as f64)
+    }
+}
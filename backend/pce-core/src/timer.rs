@@ -0,0 +1,78 @@
+//! `HuC6280` internal timer
+//!
+//! The timer is a 7-bit down counter that decrements once every 1024 cycles of the 7.16 MHz CPU
+//! clock regardless of the CPU's current speed. When it underflows it reloads and raises the timer
+//! interrupt.
+
+use bincode::{Decode, Encode};
+use jgenesis_common::num::GetBit;
+
+// 1024 CPU cycles at 7.16 MHz, 3 master clock cycles each
+const TIMER_DIVIDER_MCLK: u32 = 1024 * 3;
+
+#[derive(Debug, Clone, Encode, Decode)]
+pub struct Timer {
+    enabled: bool,
+    reload_value: u8,
+    counter: u8,
+    mclk_counter: u32,
+    interrupt_pending: bool,
+}
+
+impl Timer {
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            reload_value: 0,
+            counter: 0,
+            mclk_counter: 0,
+            interrupt_pending: false,
+        }
+    }
+
+    // $0C00 write
+    pub fn write_reload(&mut self, value: u8) {
+        self.reload_value = value & 0x7F;
+    }
+
+    // $0C01 write
+    pub fn write_control(&mut self, value: u8) {
+        let enabled = value.bit(0);
+        if enabled && !self.enabled {
+            self.counter = self.reload_value;
+            self.mclk_counter = 0;
+        }
+        self.enabled = enabled;
+    }
+
+    // $0C00/$0C01 read
+    pub fn read_counter(&self) -> u8 {
+        self.counter
+    }
+
+    pub fn tick(&mut self, mclk_cycles: u32) {
+        if !self.enabled {
+            return;
+        }
+
+        self.mclk_counter += mclk_cycles;
+        while self.mclk_counter >= TIMER_DIVIDER_MCLK {
+            self.mclk_counter -= TIMER_DIVIDER_MCLK;
+
+            if self.counter == 0 {
+                self.counter = self.reload_value;
+                self.interrupt_pending = true;
+            } else {
+                self.counter -= 1;
+            }
+        }
+    }
+
+    pub fn interrupt_pending(&self) -> bool {
+        self.interrupt_pending
+    }
+
+    pub fn acknowledge_interrupt(&mut self) {
+        self.interrupt_pending = false;
+    }
+}
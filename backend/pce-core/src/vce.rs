@@ -0,0 +1,154 @@
+//! `HuC6260` VCE (video color encoder)
+//!
+//! The VCE holds the 512-entry color table, generates the dot clock that drives the VDC, and
+//! converts the VDC's pixel output to RGB. The first 256 color table entries are used by the
+//! background and the last 256 by sprites.
+
+use bincode::{Decode, Encode};
+use jgenesis_common::frontend::Color;
+use jgenesis_common::num::GetBit;
+use jgenesis_proc_macros::{FakeDecode, FakeEncode};
+use std::ops::{Deref, DerefMut};
+
+const COLOR_TABLE_LEN: usize = 512;
+
+// Color table entry used outside of the VDC's active display area
+pub const OVERSCAN_COLOR_INDEX: u16 = 0x100;
+
+// [round(255 * i / 7) for i in range(8)]
+const RGB_3_TO_8: [u8; 8] = [0, 36, 73, 109, 146, 182, 219, 255];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+pub enum DotClock {
+    // 5.37 MHz, master clock / 4
+    Low,
+    // 7.16 MHz, master clock / 3
+    Medium,
+    // 10.74 MHz, master clock / 2
+    High,
+}
+
+#[derive(Debug, Clone, FakeEncode, FakeDecode)]
+struct ColorTable(Box<[u16; COLOR_TABLE_LEN]>);
+
+impl Default for ColorTable {
+    fn default() -> Self {
+        Self(vec![0; COLOR_TABLE_LEN].into_boxed_slice().try_into().unwrap())
+    }
+}
+
+impl Deref for ColorTable {
+    type Target = [u16; COLOR_TABLE_LEN];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for ColorTable {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+#[derive(Debug, Clone, Encode, Decode)]
+pub struct Vce {
+    control: u8,
+    color_address: u16,
+    color_table: ColorTable,
+}
+
+impl Vce {
+    pub fn new() -> Self {
+        Self { control: 0, color_address: 0, color_table: ColorTable::default() }
+    }
+
+    pub fn write(&mut self, address: u32, value: u8) {
+        match address & 7 {
+            0 => {
+                self.control = value;
+                log::trace!("VCE control write: {value:02X}");
+            }
+            2 => self.color_address = (self.color_address & 0x100) | u16::from(value),
+            3 => self.color_address = (self.color_address & 0xFF) | (u16::from(value & 1) << 8),
+            4 => {
+                let entry = &mut self.color_table[self.color_address as usize];
+                *entry = (*entry & 0x100) | u16::from(value);
+            }
+            5 => {
+                let entry = &mut self.color_table[self.color_address as usize];
+                *entry = (*entry & 0xFF) | (u16::from(value & 1) << 8);
+                self.increment_color_address();
+            }
+            _ => {}
+        }
+    }
+
+    pub fn read(&mut self, address: u32) -> u8 {
+        match address & 7 {
+            4 => self.color_table[self.color_address as usize] as u8,
+            5 => {
+                let value = 0xFE | (self.color_table[self.color_address as usize] >> 8) as u8;
+                self.increment_color_address();
+                value
+            }
+            _ => 0xFF,
+        }
+    }
+
+    fn increment_color_address(&mut self) {
+        self.color_address = (self.color_address + 1) & 0x1FF;
+    }
+
+    pub fn dot_clock(&self) -> DotClock {
+        match self.control & 3 {
+            0 => DotClock::Low,
+            1 => DotClock::Medium,
+            _ => DotClock::High,
+        }
+    }
+
+    pub fn lines_per_frame(&self) -> u16 {
+        if self.control.bit(2) { 263 } else { 262 }
+    }
+
+    fn grayscale(&self) -> bool {
+        self.control.bit(7)
+    }
+
+    /// Convert a line of color table indices to RGB.
+    pub fn render_line(&self, indices: &[u16], out: &mut [Color]) {
+        let grayscale = self.grayscale();
+        for (&index, color) in indices.iter().zip(out) {
+            let entry = self.color_table[(index & 0x1FF) as usize];
+            *color = color_table_entry_to_rgb(entry, grayscale);
+        }
+    }
+
+    pub fn copy_color_table(&self, out: &mut [Color]) {
+        let grayscale = self.grayscale();
+        for (&entry, color) in self.color_table.iter().zip(out) {
+            *color = color_table_entry_to_rgb(entry, grayscale);
+        }
+    }
+
+    pub fn fill_overscan(&self, out: &mut [Color]) {
+        let entry = self.color_table[OVERSCAN_COLOR_INDEX as usize];
+        out.fill(color_table_entry_to_rgb(entry, self.grayscale()));
+    }
+}
+
+// Color table entries are 9-bit GRB: bits 0-2 are blue, bits 3-5 are red, and bits 6-8 are green
+fn color_table_entry_to_rgb(entry: u16, grayscale: bool) -> Color {
+    let b = RGB_3_TO_8[(entry & 7) as usize];
+    let r = RGB_3_TO_8[((entry >> 3) & 7) as usize];
+    let g = RGB_3_TO_8[((entry >> 6) & 7) as usize];
+
+    if grayscale {
+        let luma = (0.299 * f64::from(r) + 0.587 * f64::from(g) + 0.114 * f64::from(b)).round();
+        let luma = luma as u8;
+        Color::rgb(luma, luma, luma)
+    } else {
+        Color::rgb(r, g, b)
+    }
+}
@@ -0,0 +1,643 @@
+//! `HuC6270` VDC (video display controller)
+//!
+//! The VDC renders a single tile-based background layer and up to 64 sprites (16 per line) from
+//! 64KB of VRAM. It outputs 9-bit color table indices which the VCE converts to RGB.
+//!
+//! This implementation renders one full line at a time. Vertical timing is derived from the VPR,
+//! VDW, and VCR registers, while horizontal timing is simplified to rendering HDR+1 tiles worth of
+//! pixels starting from the left edge of the frame.
+
+use bincode::{Decode, Encode};
+use jgenesis_common::num::GetBit;
+use jgenesis_proc_macros::{FakeDecode, FakeEncode};
+use std::ops::{Deref, DerefMut};
+
+const VRAM_LEN_WORDS: usize = 32 * 1024;
+const SAT_LEN_WORDS: usize = 256;
+
+pub const MAX_SCREEN_WIDTH: usize = 512;
+
+const MAX_SPRITES_PER_LINE: u8 = 16;
+
+// Number of lines between the start of the VCE frame and the first line of active display with
+// the standard vertical timing settings (VSW=2, VDS=15), chosen so that a 240-line display is
+// roughly centered in the visible area
+const ACTIVE_DISPLAY_OFFSET: u16 = 5;
+
+#[derive(Debug, Clone, FakeEncode, FakeDecode)]
+struct Vram(Box<[u16; VRAM_LEN_WORDS]>);
+
+impl Default for Vram {
+    fn default() -> Self {
+        Self(vec![0; VRAM_LEN_WORDS].into_boxed_slice().try_into().unwrap())
+    }
+}
+
+impl Deref for Vram {
+    type Target = [u16; VRAM_LEN_WORDS];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for Vram {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+enum Register {
+    // MAWR: Memory address write register
+    MemoryWriteAddress,
+    // MARR: Memory address read register
+    MemoryReadAddress,
+    // VWR/VRR: VRAM data write/read register
+    VramData,
+    // CR: Control register
+    Control,
+    // RCR: Raster counter register
+    RasterCompare,
+    // BXR: Background X scroll
+    BackgroundX,
+    // BYR: Background Y scroll
+    BackgroundY,
+    // MWR: Memory width register
+    MemoryWidth,
+    // HSR: Horizontal sync register
+    HorizontalSync,
+    // HDR: Horizontal display register
+    HorizontalDisplay,
+    // VPR: Vertical sync register
+    VerticalSync,
+    // VDW: Vertical display register
+    VerticalDisplay,
+    // VCR: Vertical display end position register
+    VerticalDisplayEnd,
+    // DCR: DMA control register
+    DmaControl,
+    // SOUR: DMA source address
+    DmaSource,
+    // DESR: DMA destination address
+    DmaDestination,
+    // LENR: DMA length
+    DmaLength,
+    // DVSSR: VRAM-SATB DMA source address
+    SatbSource,
+    Unused,
+}
+
+impl Register {
+    fn from_index(index: u8) -> Self {
+        match index {
+            0x00 => Self::MemoryWriteAddress,
+            0x01 => Self::MemoryReadAddress,
+            0x02 => Self::VramData,
+            0x05 => Self::Control,
+            0x06 => Self::RasterCompare,
+            0x07 => Self::BackgroundX,
+            0x08 => Self::BackgroundY,
+            0x09 => Self::MemoryWidth,
+            0x0A => Self::HorizontalSync,
+            0x0B => Self::HorizontalDisplay,
+            0x0C => Self::VerticalSync,
+            0x0D => Self::VerticalDisplay,
+            0x0E => Self::VerticalDisplayEnd,
+            0x0F => Self::DmaControl,
+            0x10 => Self::DmaSource,
+            0x11 => Self::DmaDestination,
+            0x12 => Self::DmaLength,
+            0x13 => Self::SatbSource,
+            _ => Self::Unused,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Encode, Decode)]
+struct Registers {
+    selected: u8,
+    memory_write_address: u16,
+    memory_read_address: u16,
+    write_latch: u8,
+    read_buffer: u16,
+    control: u16,
+    raster_compare: u16,
+    bg_x_scroll: u16,
+    bg_y_scroll: u16,
+    memory_width: u16,
+    horizontal_sync: u16,
+    horizontal_display: u16,
+    vertical_sync: u16,
+    vertical_display: u16,
+    vertical_display_end: u16,
+    dma_control: u16,
+    dma_source: u16,
+    dma_destination: u16,
+    dma_length: u16,
+    satb_source: u16,
+}
+
+impl Registers {
+    fn collision_irq_enabled(&self) -> bool {
+        self.control.bit(0)
+    }
+
+    fn overflow_irq_enabled(&self) -> bool {
+        self.control.bit(1)
+    }
+
+    fn raster_irq_enabled(&self) -> bool {
+        self.control.bit(2)
+    }
+
+    fn vblank_irq_enabled(&self) -> bool {
+        self.control.bit(3)
+    }
+
+    fn sprites_enabled(&self) -> bool {
+        self.control.bit(6)
+    }
+
+    fn bg_enabled(&self) -> bool {
+        self.control.bit(7)
+    }
+
+    fn address_increment(&self) -> u16 {
+        match (self.control >> 11) & 3 {
+            0 => 1,
+            1 => 32,
+            2 => 64,
+            3 => 128,
+            _ => unreachable!("value & 3 is always <= 3"),
+        }
+    }
+
+    fn bg_width_tiles(&self) -> u16 {
+        match (self.memory_width >> 4) & 3 {
+            0 => 32,
+            1 => 64,
+            _ => 128,
+        }
+    }
+
+    fn bg_height_tiles(&self) -> u16 {
+        if self.memory_width.bit(6) { 64 } else { 32 }
+    }
+
+    fn screen_width(&self) -> usize {
+        (((self.horizontal_display & 0x7F) as usize + 1) * 8).min(MAX_SCREEN_WIDTH)
+    }
+}
+
+#[derive(Debug, Clone, Default, Encode, Decode)]
+struct Status {
+    collision: bool,
+    sprite_overflow: bool,
+    raster: bool,
+    satb_dma_complete: bool,
+    vram_dma_complete: bool,
+    vblank: bool,
+}
+
+impl Status {
+    fn to_byte(&self) -> u8 {
+        (u8::from(self.vblank) << 5)
+            | (u8::from(self.vram_dma_complete) << 4)
+            | (u8::from(self.satb_dma_complete) << 3)
+            | (u8::from(self.raster) << 2)
+            | (u8::from(self.sprite_overflow) << 1)
+            | u8::from(self.collision)
+    }
+}
+
+// Vertical timing, latched at the start of each frame
+#[derive(Debug, Clone, Copy, Default, Encode, Decode)]
+struct FrameTiming {
+    active_start: u16,
+    active_end: u16,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct SpritePixel {
+    color: u16,
+    priority: bool,
+    sprite_0: bool,
+}
+
+#[derive(Debug, Clone, Encode, Decode)]
+pub struct Vdc {
+    vram: Vram,
+    sat: Vec<u16>,
+    registers: Registers,
+    status: Status,
+    satb_dma_pending: bool,
+    frame_timing: FrameTiming,
+    bg_y_counter: u16,
+    screen_width: usize,
+    line_buffer: Vec<u16>,
+}
+
+impl Vdc {
+    pub fn new() -> Self {
+        Self {
+            vram: Vram::default(),
+            sat: vec![0; SAT_LEN_WORDS],
+            registers: Registers::default(),
+            status: Status::default(),
+            satb_dma_pending: false,
+            frame_timing: FrameTiming::default(),
+            bg_y_counter: 0,
+            screen_width: 256,
+            line_buffer: vec![0; MAX_SCREEN_WIDTH],
+        }
+    }
+
+    pub fn read(&mut self, address: u32) -> u8 {
+        match address & 3 {
+            0 => {
+                let status = self.status.to_byte();
+                self.status = Status::default();
+                status
+            }
+            2 => self.registers.read_buffer as u8,
+            3 => {
+                let value = (self.registers.read_buffer >> 8) as u8;
+                if Register::from_index(self.registers.selected) == Register::VramData {
+                    self.registers.memory_read_address = self
+                        .registers
+                        .memory_read_address
+                        .wrapping_add(self.registers.address_increment());
+                    self.prefetch_vram();
+                }
+                value
+            }
+            _ => 0xFF,
+        }
+    }
+
+    pub fn write(&mut self, address: u32, value: u8) {
+        match address & 3 {
+            0 => self.registers.selected = value & 0x1F,
+            2 => self.write_register(false, value),
+            3 => self.write_register(true, value),
+            _ => {}
+        }
+    }
+
+    fn write_register(&mut self, high: bool, value: u8) {
+        fn set_byte(word: &mut u16, high: bool, value: u8) {
+            *word = if high {
+                (*word & 0x00FF) | (u16::from(value) << 8)
+            } else {
+                (*word & 0xFF00) | u16::from(value)
+            };
+        }
+
+        let register = Register::from_index(self.registers.selected);
+        log::trace!("VDC register write: {register:?} high={high} value={value:02X}");
+
+        match register {
+            Register::MemoryWriteAddress => {
+                set_byte(&mut self.registers.memory_write_address, high, value);
+            }
+            Register::MemoryReadAddress => {
+                set_byte(&mut self.registers.memory_read_address, high, value);
+                if high {
+                    self.prefetch_vram();
+                }
+            }
+            Register::VramData => {
+                if high {
+                    let word = u16::from_le_bytes([self.registers.write_latch, value]);
+                    self.write_vram(self.registers.memory_write_address, word);
+                    self.registers.memory_write_address = self
+                        .registers
+                        .memory_write_address
+                        .wrapping_add(self.registers.address_increment());
+                } else {
+                    self.registers.write_latch = value;
+                }
+            }
+            Register::Control => set_byte(&mut self.registers.control, high, value),
+            Register::RasterCompare => set_byte(&mut self.registers.raster_compare, high, value),
+            Register::BackgroundX => set_byte(&mut self.registers.bg_x_scroll, high, value),
+            Register::BackgroundY => {
+                set_byte(&mut self.registers.bg_y_scroll, high, value);
+                // Writing BYR resets the internal Y counter; the next line displays BYR+1
+                self.bg_y_counter = self.registers.bg_y_scroll;
+            }
+            Register::MemoryWidth => set_byte(&mut self.registers.memory_width, high, value),
+            Register::HorizontalSync => set_byte(&mut self.registers.horizontal_sync, high, value),
+            Register::HorizontalDisplay => {
+                set_byte(&mut self.registers.horizontal_display, high, value);
+            }
+            Register::VerticalSync => set_byte(&mut self.registers.vertical_sync, high, value),
+            Register::VerticalDisplay => {
+                set_byte(&mut self.registers.vertical_display, high, value);
+            }
+            Register::VerticalDisplayEnd => {
+                set_byte(&mut self.registers.vertical_display_end, high, value);
+            }
+            Register::DmaControl => set_byte(&mut self.registers.dma_control, high, value),
+            Register::DmaSource => set_byte(&mut self.registers.dma_source, high, value),
+            Register::DmaDestination => set_byte(&mut self.registers.dma_destination, high, value),
+            Register::DmaLength => {
+                set_byte(&mut self.registers.dma_length, high, value);
+                if high {
+                    self.run_vram_dma();
+                }
+            }
+            Register::SatbSource => {
+                set_byte(&mut self.registers.satb_source, high, value);
+                if high {
+                    self.satb_dma_pending = true;
+                }
+            }
+            Register::Unused => {}
+        }
+    }
+
+    fn prefetch_vram(&mut self) {
+        self.registers.read_buffer = self.read_vram(self.registers.memory_read_address);
+    }
+
+    fn read_vram(&self, address: u16) -> u16 {
+        self.vram[(address as usize) & (VRAM_LEN_WORDS - 1)]
+    }
+
+    fn write_vram(&mut self, address: u16, value: u16) {
+        // Only the first 32K words of the 64K word address space exist
+        if (address as usize) < VRAM_LEN_WORDS {
+            self.vram[address as usize] = value;
+        }
+    }
+
+    // VRAM-to-VRAM DMA; this completes instantly rather than taking time during VBlank
+    fn run_vram_dma(&mut self) {
+        let decrement_source = self.registers.dma_control.bit(2);
+        let decrement_destination = self.registers.dma_control.bit(3);
+
+        let mut source = self.registers.dma_source;
+        let mut destination = self.registers.dma_destination;
+        for _ in 0..=u32::from(self.registers.dma_length) {
+            self.write_vram(destination, self.read_vram(source));
+
+            source = if decrement_source { source.wrapping_sub(1) } else { source.wrapping_add(1) };
+            destination = if decrement_destination {
+                destination.wrapping_sub(1)
+            } else {
+                destination.wrapping_add(1)
+            };
+        }
+
+        self.registers.dma_source = source;
+        self.registers.dma_destination = destination;
+        self.registers.dma_length = 0xFFFF;
+
+        if self.registers.dma_control.bit(1) {
+            self.status.vram_dma_complete = true;
+        }
+    }
+
+    fn run_satb_dma(&mut self) {
+        for i in 0..SAT_LEN_WORDS {
+            self.sat[i] = self.read_vram(self.registers.satb_source.wrapping_add(i as u16));
+        }
+
+        if self.registers.dma_control.bit(0) {
+            self.status.satb_dma_complete = true;
+        }
+    }
+
+    pub fn irq(&self) -> bool {
+        self.status.to_byte() != 0
+    }
+
+    /// Process the start of the given line, rendering it into the line buffer if it is in the
+    /// active display area. Returns whether the line is in the active display area.
+    pub fn begin_line(&mut self, line: u16, lines_per_frame: u16) -> bool {
+        if line == 0 {
+            self.latch_frame_timing();
+        }
+
+        let FrameTiming { active_start, active_end } = self.frame_timing;
+
+        let vblank_line = active_end.min(lines_per_frame - 1);
+        if line == vblank_line {
+            self.begin_vblank();
+        }
+
+        if !(active_start..active_end).contains(&line) || line >= lines_per_frame {
+            return false;
+        }
+
+        let display_line = line - active_start;
+        let raster_counter = 0x40 + display_line;
+        if raster_counter == self.registers.raster_compare & 0x3FF
+            && self.registers.raster_irq_enabled()
+        {
+            self.status.raster = true;
+        }
+
+        if display_line == 0 {
+            self.bg_y_counter = self.registers.bg_y_scroll;
+        } else {
+            self.bg_y_counter = self.bg_y_counter.wrapping_add(1);
+        }
+
+        self.render_line(raster_counter);
+
+        true
+    }
+
+    fn latch_frame_timing(&mut self) {
+        let vsw = self.registers.vertical_sync & 0x1F;
+        let vds = self.registers.vertical_sync >> 8;
+        let vdw = self.registers.vertical_display & 0x1FF;
+
+        let active_start = (vsw + vds + 3).saturating_sub(ACTIVE_DISPLAY_OFFSET);
+        let active_end = active_start + vdw + 1;
+        self.frame_timing = FrameTiming { active_start, active_end };
+
+        self.screen_width = self.registers.screen_width();
+    }
+
+    fn begin_vblank(&mut self) {
+        if self.registers.vblank_irq_enabled() {
+            self.status.vblank = true;
+        }
+
+        // DCR bit 4 enables automatic SATB DMA every frame
+        if self.satb_dma_pending || self.registers.dma_control.bit(4) {
+            self.satb_dma_pending = false;
+            self.run_satb_dma();
+        }
+    }
+
+    pub fn screen_width(&self) -> usize {
+        self.screen_width
+    }
+
+    pub fn line_buffer(&self) -> &[u16] {
+        &self.line_buffer[..self.screen_width]
+    }
+
+    fn render_line(&mut self, raster_counter: u16) {
+        let screen_width = self.screen_width;
+
+        self.render_bg_line(screen_width);
+
+        if !self.registers.sprites_enabled() {
+            return;
+        }
+
+        let sprite_line = self.render_sprite_line(raster_counter, screen_width);
+        for (pixel, sprite_pixel) in self.line_buffer[..screen_width].iter_mut().zip(sprite_line) {
+            let Some(sprite_pixel) = sprite_pixel else { continue };
+
+            if sprite_pixel.priority || *pixel & 0xF == 0 {
+                *pixel = sprite_pixel.color;
+            }
+        }
+    }
+
+    fn render_bg_line(&mut self, screen_width: usize) {
+        if !self.registers.bg_enabled() {
+            self.line_buffer[..screen_width].fill(0);
+            return;
+        }
+
+        let bg_width_pixels = self.registers.bg_width_tiles() * 8;
+        let bg_height_pixels = self.registers.bg_height_tiles() * 8;
+
+        let y = self.bg_y_counter % bg_height_pixels;
+        let tile_row = y / 8;
+        let fine_y = y % 8;
+
+        for (screen_x, pixel) in self.line_buffer[..screen_width].iter_mut().enumerate() {
+            let x = (self.registers.bg_x_scroll.wrapping_add(screen_x as u16)) % bg_width_pixels;
+            let tile_col = x / 8;
+            let fine_x = x % 8;
+
+            let bat_entry = self.vram[(tile_row * self.registers.bg_width_tiles() + tile_col)
+                as usize
+                & (VRAM_LEN_WORDS - 1)];
+            let tile_number = bat_entry & 0xFFF;
+            let palette = bat_entry >> 12;
+
+            let tile_address = tile_number * 16 + fine_y;
+            let planes_01 = self.vram[(tile_address as usize) & (VRAM_LEN_WORDS - 1)];
+            let planes_23 = self.vram[(tile_address as usize + 8) & (VRAM_LEN_WORDS - 1)];
+
+            let bit = 7 - fine_x;
+            let color = ((planes_01 >> bit) & 1)
+                | (((planes_01 >> (8 + bit)) & 1) << 1)
+                | (((planes_23 >> bit) & 1) << 2)
+                | (((planes_23 >> (8 + bit)) & 1) << 3);
+
+            *pixel = if color != 0 { (palette << 4) | color } else { 0 };
+        }
+    }
+
+    fn render_sprite_line(
+        &mut self,
+        raster_counter: u16,
+        screen_width: usize,
+    ) -> Vec<Option<SpritePixel>> {
+        let mut sprite_line = vec![None::<SpritePixel>; screen_width];
+        let mut sprites_on_line = 0;
+
+        for sprite in 0..SAT_LEN_WORDS / 4 {
+            let y = self.sat[4 * sprite] & 0x3FF;
+            let x = self.sat[4 * sprite + 1] & 0x3FF;
+            let mut pattern = (self.sat[4 * sprite + 2] >> 1) & 0x3FF;
+            let attributes = self.sat[4 * sprite + 3];
+
+            let width: u16 = if attributes.bit(8) { 32 } else { 16 };
+            let height: u16 = match (attributes >> 12) & 3 {
+                0 => 16,
+                1 => 32,
+                _ => 64,
+            };
+
+            let Some(sprite_row) = raster_counter.checked_sub(y).filter(|&row| row < height) else {
+                continue;
+            };
+
+            sprites_on_line += 1;
+            if sprites_on_line > MAX_SPRITES_PER_LINE {
+                if self.registers.overflow_irq_enabled() {
+                    self.status.sprite_overflow = true;
+                }
+                break;
+            }
+
+            let x_flip = attributes.bit(11);
+            let y_flip = attributes.bit(15);
+            let priority = attributes.bit(7);
+            let palette = attributes & 0xF;
+
+            if width == 32 {
+                pattern &= !1;
+            }
+            match height {
+                32 => pattern &= !2,
+                64 => pattern &= !6,
+                _ => {}
+            }
+
+            let sprite_row = if y_flip { height - 1 - sprite_row } else { sprite_row };
+            let cell_y = sprite_row / 16;
+            let pattern_row = sprite_row % 16;
+
+            let cells_wide = width / 16;
+            for cell_x in 0..cells_wide {
+                let pattern_cell_x = if x_flip { cells_wide - 1 - cell_x } else { cell_x };
+                let cell = pattern + pattern_cell_x + 2 * cell_y;
+                let address = (cell * 64 + pattern_row) & 0x7FFF;
+
+                let planes = [
+                    self.read_vram(address),
+                    self.read_vram(address + 16),
+                    self.read_vram(address + 32),
+                    self.read_vram(address + 48),
+                ];
+
+                for pixel in 0..16 {
+                    let screen_x = i32::from(x) - 32 + i32::from(cell_x * 16) + pixel;
+                    if screen_x < 0 || screen_x >= screen_width as i32 {
+                        continue;
+                    }
+
+                    let bit = if x_flip { pixel } else { 15 - pixel };
+                    let color = planes
+                        .iter()
+                        .enumerate()
+                        .map(|(plane, &word)| ((word >> bit) & 1) << plane)
+                        .fold(0, |acc, bit| acc | bit);
+                    if color == 0 {
+                        continue;
+                    }
+
+                    match &sprite_line[screen_x as usize] {
+                        None => {
+                            sprite_line[screen_x as usize] = Some(SpritePixel {
+                                color: 0x100 | (palette << 4) | color,
+                                priority,
+                                sprite_0: sprite == 0,
+                            });
+                        }
+                        Some(existing) => {
+                            if existing.sprite_0 && self.registers.collision_irq_enabled() {
+                                self.status.collision = true;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        sprite_line
+    }
+}
@@ -10,18 +10,23 @@ use bincode::{Decode, Encode};
 use cdrom::reader::{CdRom, CdRomFileFormat};
 use cdrom::CdRomError;
 use genesis_core::input::InputState;
-use genesis_core::memory::{MainBus, MainBusSignals, MainBusWrites, Memory};
-use genesis_core::vdp::{Vdp, VdpTickEffect};
+use genesis_core::memory::{Cartridge, MainBus, MainBusSignals, MainBusWrites, Memory};
+use genesis_core::soundlog::SoundLog;
+use genesis_core::vdp::{Vdp, VdpEventLog, VdpTickEffect};
 use genesis_core::ym2612::{Ym2612, YmTickEffect};
 use genesis_core::{GenesisAspectRatio, GenesisEmulatorConfig, GenesisInputs, GenesisRegion};
 use jgenesis_common::frontend::{
-    AudioOutput, Color, EmulatorTrait, PartialClone, Renderer, SaveWriter, TickEffect, TimingMode,
+    AudioOutput, Color, EmulatorTrait, FrameSize, PartialClone, PixelAspectRatio, Renderer,
+    SaveWriter, TickEffect, TimingMode,
 };
+use jgenesis_common::rng::{InitialRamState, Rng};
+use jgenesis_common::state::StateFormat;
 use jgenesis_proc_macros::{FakeDecode, FakeEncode};
 use m68000_emu::M68000;
 use smsgg_core::psg::{Psg, PsgTickEffect, PsgVersion};
+use std::convert::Infallible;
 use std::fmt::{Debug, Display};
-use std::ops::{Deref, DerefMut};
+use std::ops::{Deref, DerefMut, Range};
 use std::path::Path;
 use thiserror::Error;
 use z80_emu::Z80;
@@ -36,6 +41,14 @@ pub(crate) const SEGA_CD_MASTER_CLOCK_RATE: u64 = 50_000_000;
 
 const BIOS_LEN: usize = memory::BIOS_LEN;
 
+// The BIOS loads the disc's initial program (IP) into the start of main CPU work RAM and jumps to it
+// once the boot animation and disc checks are done
+const IP_ADDRESS_RANGE: Range<u32> = 0xFF0000..0xFF8000;
+
+// Give up on fast boot if the BIOS has not started the IP after this many frames, e.g. because the
+// disc is not bootable and the BIOS went to its menu instead
+const FAST_BOOT_MAX_FRAMES: u32 = 30 * 60;
+
 #[derive(Debug, Error)]
 pub enum SegaCdLoadError {
     #[error("BIOS is required for Sega CD emulation")]
@@ -49,23 +62,48 @@ pub enum SegaCdLoadError {
 pub type SegaCdLoadResult<T> = Result<T, SegaCdLoadError>;
 
 #[derive(Debug, Error)]
-pub enum SegaCdError<RErr, AErr, SErr> {
+pub enum SegaCdError<RErr, AErr> {
     #[error("Disc-related error: {0}")]
     Disc(#[from] SegaCdLoadError),
     #[error("Rendering error: {0}")]
     Render(RErr),
     #[error("Audio output error: {0}")]
     Audio(AErr),
-    #[error("Save write error: {0}")]
-    SaveWrite(SErr),
 }
 
-pub type SegaCdResult<T, RErr, AErr, SErr> = Result<T, SegaCdError<RErr, AErr, SErr>>;
+pub type SegaCdResult<T, RErr, AErr> = Result<T, SegaCdError<RErr, AErr>>;
 
 #[derive(Debug, Clone, Copy)]
 pub struct SegaCdEmulatorConfig {
     pub genesis: GenesisEmulatorConfig,
     pub enable_ram_cartridge: bool,
+    /// Run the BIOS boot sequence at startup without outputting video or audio, so that emulation
+    /// begins at the start of the game instead of the BIOS intro
+    pub fast_boot: bool,
+}
+
+// Discards output while fast booting
+struct NullOutput;
+
+impl Renderer for NullOutput {
+    type Err = Infallible;
+
+    fn render_frame(
+        &mut self,
+        _frame_buffer: &[Color],
+        _frame_size: FrameSize,
+        _pixel_aspect_ratio: Option<PixelAspectRatio>,
+    ) -> Result<(), Self::Err> {
+        Ok(())
+    }
+}
+
+impl AudioOutput for NullOutput {
+    type Err = Infallible;
+
+    fn push_sample(&mut self, _sample_l: f64, _sample_r: f64) -> Result<(), Self::Err> {
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, FakeEncode, FakeDecode)]
@@ -106,6 +144,7 @@ pub struct SegaCdEmulator {
     input: InputState,
     audio_resampler: AudioResampler,
     save_serialization_buffer: SaveSerializationBuffer,
+    backup_ram_dirty: bool,
     timing_mode: TimingMode,
     main_bus_writes: MainBusWrites,
     aspect_ratio: GenesisAspectRatio,
@@ -115,6 +154,10 @@ pub struct SegaCdEmulator {
     sega_cd_mclk_cycles: u64,
     sega_cd_mclk_cycle_product: u64,
     sub_cpu_wait_cycles: u64,
+    initial_ram_state: Option<InitialRamState>,
+    rng_seed: Option<u64>,
+    fast_boot: bool,
+    sound_log: SoundLog,
 }
 
 // This is a macro instead of a function so that it only mutably borrows the needed fields
@@ -126,6 +169,7 @@ macro_rules! new_main_bus {
             &mut $self.psg,
             &mut $self.ym2612,
             &mut $self.input,
+            &mut $self.sound_log,
             $self.timing_mode,
             MainBusSignals { z80_busack: $self.z80.stalled(), m68k_reset: $m68k_reset },
             std::mem::take(&mut $self.main_bus_writes),
@@ -154,7 +198,27 @@ impl SegaCdEmulator {
     ) -> SegaCdLoadResult<Self> {
         let disc = if !run_without_disc { Some(CdRom::open(rom_path, format)?) } else { None };
 
-        Self::create_from_disc(bios, disc, emulator_config, save_writer)
+        Self::create_from_disc(bios, disc, None, emulator_config, save_writer)
+    }
+
+    /// Create a Sega CD emulator that boots from a cartridge in Mode 1, with the Sega CD hardware
+    /// mapped in as an expansion at $400000-$7FFFFF. A disc is optional.
+    ///
+    /// Cartridge SRAM is not persisted in Mode 1; only Sega CD backup RAM is saved.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the BIOS is invalid or if a disc is provided and cannot be read.
+    pub fn create_mode_1<P: AsRef<Path>, S: SaveWriter>(
+        bios: Vec<u8>,
+        cartridge_rom: Vec<u8>,
+        disc: Option<(P, CdRomFileFormat)>,
+        emulator_config: SegaCdEmulatorConfig,
+        save_writer: &mut S,
+    ) -> SegaCdLoadResult<Self> {
+        let disc = disc.map(|(rom_path, format)| CdRom::open(rom_path, format)).transpose()?;
+
+        Self::create_from_disc(bios, disc, Some(cartridge_rom), emulator_config, save_writer)
     }
 
     /// Create a Sega CD emulator that reads a CD-ROM image from an in-memory CHD image.
@@ -171,12 +235,13 @@ impl SegaCdEmulator {
     ) -> SegaCdLoadResult<Self> {
         let disc = CdRom::open_chd_in_memory(chd_bytes)?;
 
-        Self::create_from_disc(bios, Some(disc), emulator_config, save_writer)
+        Self::create_from_disc(bios, Some(disc), None, emulator_config, save_writer)
     }
 
     fn create_from_disc<S: SaveWriter>(
         bios: Vec<u8>,
         disc: Option<CdRom>,
+        mode_1_cartridge_rom: Option<Vec<u8>>,
         emulator_config: SegaCdEmulatorConfig,
         save_writer: &mut S,
     ) -> SegaCdLoadResult<Self> {
@@ -184,6 +249,19 @@ impl SegaCdEmulator {
             return Err(SegaCdLoadError::InvalidBios { bios_len: bios.len() });
         }
 
+        // Mode 1 boots from the cartridge, so there is no BIOS intro to skip
+        let should_fast_boot =
+            emulator_config.fast_boot && disc.is_some() && mode_1_cartridge_rom.is_none();
+
+        let mode_1_cartridge = mode_1_cartridge_rom.map(|rom| {
+            Cartridge::from_rom(
+                rom,
+                None,
+                emulator_config.genesis.forced_region,
+                emulator_config.genesis.forced_timing_mode,
+            )
+        });
+
         let initial_backup_ram = save_writer.load_bytes("sav").ok();
         let mut sega_cd = SegaCd::new(
             bios,
@@ -191,10 +269,23 @@ impl SegaCdEmulator {
             initial_backup_ram,
             emulator_config.enable_ram_cartridge,
             emulator_config.genesis.forced_region,
+            mode_1_cartridge,
         )?;
-        let disc_title = sega_cd.disc_title()?.unwrap_or("(no disc)".into());
+        let disc_title = match sega_cd.disc_title()? {
+            Some(disc_title) => disc_title,
+            None => sega_cd.mode_1_cartridge_title().unwrap_or_else(|| "(no disc)".into()),
+        };
 
-        let memory = Memory::new(sega_cd);
+        let mut rng = Rng::from_optional_seed(emulator_config.genesis.rng_seed);
+        let mut memory = Memory::new(
+            sega_cd,
+            emulator_config.genesis.initial_ram_state.unwrap_or(InitialRamState::AllZeroes),
+            &mut rng,
+        );
+        memory.set_version_register_config(
+            emulator_config.genesis.region_spoof,
+            emulator_config.genesis.report_tmss,
+        );
         let timing_mode =
             emulator_config.genesis.forced_timing_mode.unwrap_or_else(|| {
                 match memory.hardware_region() {
@@ -215,7 +306,8 @@ impl SegaCdEmulator {
         let pcm = Rf5c164::new();
         let input = InputState::new();
 
-        let audio_resampler = AudioResampler::new(timing_mode);
+        let audio_resampler =
+            AudioResampler::new(timing_mode, emulator_config.genesis.audio_resampler_quality);
         let mut emulator = Self {
             memory,
             main_cpu,
@@ -229,6 +321,7 @@ impl SegaCdEmulator {
             input,
             audio_resampler,
             save_serialization_buffer: SaveSerializationBuffer::default(),
+            backup_ram_dirty: false,
             timing_mode,
             main_bus_writes: MainBusWrites::new(),
             aspect_ratio: emulator_config.genesis.aspect_ratio,
@@ -240,14 +333,50 @@ impl SegaCdEmulator {
             sega_cd_mclk_cycles: 0,
             sega_cd_mclk_cycle_product: 0,
             sub_cpu_wait_cycles: 0,
+            initial_ram_state: emulator_config.genesis.initial_ram_state,
+            rng_seed: emulator_config.genesis.rng_seed,
+            fast_boot: emulator_config.fast_boot,
+            sound_log: SoundLog::new(),
         };
 
         // Reset main CPU so that execution starts from the right place
         emulator.main_cpu.execute_instruction(&mut new_main_bus!(emulator, m68k_reset: true));
 
+        if should_fast_boot {
+            emulator.run_until_initial_program()?;
+        }
+
         Ok(emulator)
     }
 
+    // Rather than HLEing the BIOS, this runs the real boot sequence as fast as possible. The machine
+    // state afterwards is exactly what it would be after a normal boot, so save states and games
+    // that call into the BIOS are unaffected
+    fn run_until_initial_program(&mut self) -> SegaCdLoadResult<()> {
+        let inputs = GenesisInputs::default();
+
+        let mut frames = 0;
+        while frames < FAST_BOOT_MAX_FRAMES {
+            if IP_ADDRESS_RANGE.contains(&(self.main_cpu.pc() & 0xFFFFFF)) {
+                log::info!("Fast boot reached the initial program after {frames} frames");
+                return Ok(());
+            }
+
+            match self.tick(&mut NullOutput, &mut NullOutput, &inputs) {
+                Ok(TickEffect::FrameRendered) => frames += 1,
+                Ok(TickEffect::None) => {}
+                Err(SegaCdError::Disc(err)) => return Err(err),
+                Err(SegaCdError::Render(err) | SegaCdError::Audio(err)) => match err {},
+            }
+        }
+
+        log::warn!(
+            "Fast boot did not reach the initial program after {FAST_BOOT_MAX_FRAMES} frames; the disc may not be bootable"
+        );
+
+        Ok(())
+    }
+
     #[inline]
     fn tick_sub_cpu(&mut self, mut sub_cpu_cycles: u64) {
         while sub_cpu_cycles >= self.sub_cpu_wait_cycles {
@@ -275,6 +404,12 @@ impl SegaCdEmulator {
         &self.disc_title
     }
 
+    /// Log of YM2612 and PSG writes. CD audio and RF5C164 PCM are not included.
+    #[must_use]
+    pub fn sound_log_mut(&mut self) -> &mut SoundLog {
+        &mut self.sound_log
+    }
+
     pub fn remove_disc(&mut self) {
         self.memory.medium_mut().remove_disc();
         self.disc_title = "(no disc)".into();
@@ -302,32 +437,42 @@ impl SegaCdEmulator {
     pub fn copy_vram(&self, out: &mut [Color], palette: u8, row_len: usize) {
         self.vdp.copy_vram(out, palette, row_len);
     }
+
+    pub fn import_vram(&mut self, pixels: &[Color], palette: u8, row_len: usize) {
+        self.vdp.import_vram(pixels, palette, row_len);
+    }
+
+    pub fn set_vdp_event_logging(&mut self, enabled: bool) {
+        self.vdp.set_event_logging(enabled);
+    }
+
+    #[must_use]
+    pub fn vdp_event_log(&self) -> &VdpEventLog {
+        self.vdp.event_log()
+    }
 }
 
 impl EmulatorTrait for SegaCdEmulator {
     type Inputs = GenesisInputs;
     type Config = SegaCdEmulatorConfig;
+    const STATE_FORMAT: StateFormat = StateFormat { core: "segacd", version: 1 };
 
     type Err<
         RErr: Debug + Display + Send + Sync + 'static,
         AErr: Debug + Display + Send + Sync + 'static,
-        SErr: Debug + Display + Send + Sync + 'static,
-    > = SegaCdError<RErr, AErr, SErr>;
+    > = SegaCdError<RErr, AErr>;
 
-    fn tick<R, A, S>(
+    fn tick<R, A>(
         &mut self,
         renderer: &mut R,
         audio_output: &mut A,
         inputs: &Self::Inputs,
-        save_writer: &mut S,
-    ) -> Result<TickEffect, Self::Err<R::Err, A::Err, S::Err>>
+    ) -> Result<TickEffect, Self::Err<R::Err, A::Err>>
     where
         R: Renderer,
         R::Err: Debug + Display + Send + Sync + 'static,
         A: AudioOutput,
         A::Err: Debug + Display + Send + Sync + 'static,
-        S: SaveWriter,
-        S::Err: Debug + Display + Send + Sync + 'static,
     {
         let mut main_bus = new_main_bus!(self, m68k_reset: false);
 
@@ -392,6 +537,8 @@ impl EmulatorTrait for SegaCdEmulator {
         // Input state (for 6-button controller reset)
         self.input.tick(main_cpu_cycles);
 
+        self.sound_log.tick(genesis_mclk_elapsed);
+
         // PSG
         for _ in 0..z80_cycles {
             if self.psg.tick() == PsgTickEffect::Clocked {
@@ -424,15 +571,7 @@ impl EmulatorTrait for SegaCdEmulator {
             self.input.set_inputs(inputs);
 
             if self.memory.medium_mut().get_and_clear_backup_ram_dirty_bit() {
-                let sega_cd = self.memory.medium();
-
-                self.save_serialization_buffer.clear();
-                self.save_serialization_buffer.extend(sega_cd.backup_ram());
-                self.save_serialization_buffer.extend(sega_cd.ram_cartridge());
-
-                save_writer
-                    .persist_bytes("sav", &self.save_serialization_buffer)
-                    .map_err(SegaCdError::SaveWrite)?;
+                self.backup_ram_dirty = true;
             }
 
             return Ok(TickEffect::FrameRendered);
@@ -441,6 +580,27 @@ impl EmulatorTrait for SegaCdEmulator {
         Ok(TickEffect::None)
     }
 
+    fn save_dirty(&self) -> bool {
+        self.backup_ram_dirty
+    }
+
+    fn persist_save<S: SaveWriter>(&mut self, save_writer: &mut S) -> Result<(), S::Err> {
+        if !self.backup_ram_dirty {
+            return Ok(());
+        }
+
+        let sega_cd = self.memory.medium();
+
+        self.save_serialization_buffer.clear();
+        self.save_serialization_buffer.extend(sega_cd.backup_ram());
+        self.save_serialization_buffer.extend(sega_cd.ram_cartridge());
+
+        save_writer.persist_bytes("sav", &self.save_serialization_buffer)?;
+        self.backup_ram_dirty = false;
+
+        Ok(())
+    }
+
     fn force_render<R>(&mut self, renderer: &mut R) -> Result<(), R::Err>
     where
         R: Renderer,
@@ -453,8 +613,14 @@ impl EmulatorTrait for SegaCdEmulator {
         self.adjust_aspect_ratio_in_2x_resolution =
             config.genesis.adjust_aspect_ratio_in_2x_resolution;
         self.vdp.reload_config(config.genesis.to_vdp_config());
+        self.memory
+            .set_version_register_config(config.genesis.region_spoof, config.genesis.report_tmss);
         self.ym2612.set_quantize_output(config.genesis.quantize_ym2612_output);
+        self.audio_resampler.set_quality(config.genesis.audio_resampler_quality);
         self.input.reload_config(config.genesis);
+        self.initial_ram_state = config.genesis.initial_ram_state;
+        self.rng_seed = config.genesis.rng_seed;
+        self.fast_boot = config.fast_boot;
 
         let sega_cd = self.memory.medium_mut();
         sega_cd.set_forced_region(config.genesis.forced_region);
@@ -480,6 +646,7 @@ impl EmulatorTrait for SegaCdEmulator {
         let sega_cd = self.memory.medium_mut();
         let bios = Vec::from(sega_cd.bios());
         let disc = sega_cd.take_cdrom();
+        let mode_1_cartridge_rom = sega_cd.take_mode_1_cartridge_rom();
         let forced_region = sega_cd.forced_region();
         let enable_ram_cartridge = sega_cd.get_enable_ram_cartridge();
         let vdp_config = self.vdp.config();
@@ -488,21 +655,33 @@ impl EmulatorTrait for SegaCdEmulator {
         *self = Self::create_from_disc(
             bios,
             disc,
+            mode_1_cartridge_rom,
             SegaCdEmulatorConfig {
                 genesis: GenesisEmulatorConfig {
                     forced_timing_mode: Some(self.timing_mode),
                     forced_region,
+                    region_spoof: self.memory.region_spoof(),
+                    report_tmss: self.memory.report_tmss(),
                     aspect_ratio: self.aspect_ratio,
                     adjust_aspect_ratio_in_2x_resolution: self.adjust_aspect_ratio_in_2x_resolution,
                     remove_sprite_limits: !vdp_config.enforce_sprite_limits,
                     emulate_non_linear_vdp_dac: vdp_config.emulate_non_linear_dac,
                     render_vertical_border: vdp_config.render_vertical_border,
                     render_horizontal_border: vdp_config.render_horizontal_border,
+                    widescreen: vdp_config.widescreen,
+                    hi_res_output: vdp_config.hi_res_output,
+                    expanded_vram: vdp_config.expanded_vram,
                     quantize_ym2612_output: self.ym2612.get_quantize_output(),
+                    audio_resampler_quality: self.audio_resampler.quality(),
                     p1_controller_type,
                     p2_controller_type,
+                    multitap: self.input.multitap(),
+                    serial_port: self.input.serial().connected_port(),
+                    initial_ram_state: self.initial_ram_state,
+                    rng_seed: self.rng_seed,
                 },
                 enable_ram_cartridge,
+                fast_boot: self.fast_boot,
             },
             save_writer,
         )
@@ -6,7 +6,7 @@
 
 use bincode::{Decode, Encode};
 use genesis_core::audio::Ym2612Resampler;
-use jgenesis_common::audio::SignalResampler;
+use jgenesis_common::audio::{ResamplerQuality, SignalResampler};
 use jgenesis_common::frontend::{AudioOutput, TimingMode};
 use smsgg_core::audio::PsgResampler;
 use std::cmp;
@@ -112,7 +112,7 @@ pub struct AudioResampler {
 }
 
 impl AudioResampler {
-    pub fn new(timing_mode: TimingMode) -> Self {
+    pub fn new(timing_mode: TimingMode, quality: ResamplerQuality) -> Self {
         let genesis_mclk_frequency = match timing_mode {
             TimingMode::Ntsc => NTSC_GENESIS_MCLK_FREQUENCY,
             TimingMode::Pal => PAL_GENESIS_MCLK_FREQUENCY,
@@ -123,7 +123,20 @@ impl AudioResampler {
         let pcm_resampler = new_pcm_resampler();
         let cd_resampler = new_cd_resampler();
 
-        Self { ym2612_resampler, psg_resampler, pcm_resampler, cd_resampler }
+        let mut resampler = Self { ym2612_resampler, psg_resampler, pcm_resampler, cd_resampler };
+        resampler.set_quality(quality);
+        resampler
+    }
+
+    pub fn quality(&self) -> ResamplerQuality {
+        self.ym2612_resampler.quality()
+    }
+
+    pub fn set_quality(&mut self, quality: ResamplerQuality) {
+        self.ym2612_resampler.set_quality(quality);
+        self.psg_resampler.set_quality(quality);
+        self.pcm_resampler.set_quality(quality);
+        self.cd_resampler.set_quality(quality);
     }
 
     pub fn collect_ym2612_sample(&mut self, sample_l: f64, sample_r: f64) {
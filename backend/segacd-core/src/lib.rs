@@ -1,3 +1,8 @@
+//! Sega CD / Mega CD emulation core, built on top of `genesis-core`
+//!
+//! The types re-exported at the crate root are the stable API. Inputs and most config types are
+//! shared with the Genesis core and are re-exported from there.
+
 pub mod api;
 mod audio;
 mod cddrive;
@@ -5,4 +10,12 @@ mod graphics;
 mod memory;
 mod rf5c164;
 
+pub use api::{
+    SegaCdEmulator, SegaCdEmulatorConfig, SegaCdError, SegaCdLoadError, SegaCdLoadResult,
+    SegaCdResult,
+};
+pub use cdrom::m3u;
 pub use cdrom::reader::CdRomFileFormat;
+pub use genesis_core::{
+    GenesisControllerType, GenesisEmulatorConfig, GenesisInputs, GenesisRegion,
+};
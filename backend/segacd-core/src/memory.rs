@@ -14,7 +14,7 @@ use crate::rf5c164::Rf5c164;
 use bincode::{Decode, Encode};
 use cdrom::cdtime::CdTime;
 use cdrom::reader::{CdRom, CdRomFileFormat};
-use genesis_core::memory::{Memory, PhysicalMedium};
+use genesis_core::memory::{Cartridge, Memory, PhysicalMedium};
 use genesis_core::GenesisRegion;
 use jgenesis_common::num::{GetBit, U16Ext};
 use jgenesis_proc_macros::{FakeDecode, FakeEncode, PartialClone};
@@ -144,6 +144,24 @@ pub struct SegaCd {
     disc_region: GenesisRegion,
     forced_region: Option<GenesisRegion>,
     timer_divider: u64,
+    #[partial_clone(partial)]
+    mode_1_cartridge: Option<Cartridge>,
+}
+
+// In Mode 1, a cartridge is mapped to $000000-$3FFFFF and the Sega CD hardware is moved up to
+// $400000-$7FFFFF (BIOS/PRG RAM at $400000 and word RAM at $600000). The RAM cartridge slot is not
+// accessible because the cartridge occupies it
+enum Mode1Address {
+    Cartridge,
+    SegaCd(u32),
+}
+
+fn map_mode_1_address(address: u32) -> Mode1Address {
+    match address {
+        0x000000..=0x3FFFFF | 0xA13000..=0xA130FF => Mode1Address::Cartridge,
+        0x400000..=0x7FFFFF => Mode1Address::SegaCd(address - 0x400000),
+        _ => Mode1Address::SegaCd(address),
+    }
 }
 
 impl SegaCd {
@@ -153,13 +171,16 @@ impl SegaCd {
         initial_backup_ram: Option<Vec<u8>>,
         enable_ram_cartridge: bool,
         forced_region: Option<GenesisRegion>,
+        mode_1_cartridge: Option<Cartridge>,
     ) -> SegaCdLoadResult<Self> {
         let (backup_ram, ram_cartridge) =
             backupram::load_initial_backup_ram(initial_backup_ram.as_ref());
 
-        let disc_region = match &mut disc {
-            Some(disc) => parse_disc_region(disc)?,
-            None => {
+        let disc_region = match (&mode_1_cartridge, &mut disc) {
+            // In Mode 1 the cartridge boots the system, so its header determines the region
+            (Some(cartridge), _) => cartridge.region(),
+            (None, Some(disc)) => parse_disc_region(disc)?,
+            (None, None) => {
                 // Default to US if no disc provided
                 GenesisRegion::Americas
             }
@@ -182,6 +203,7 @@ impl SegaCd {
             disc_region,
             forced_region,
             timer_divider: TIMER_DIVIDER,
+            mode_1_cartridge,
         })
     }
 
@@ -540,6 +562,20 @@ impl SegaCd {
     pub fn take_rom_from(&mut self, other: &mut Self) {
         self.bios = mem::take(&mut other.bios);
         self.disc_drive.take_disc_from(&mut other.disc_drive);
+
+        if let (Some(cartridge), Some(other_cartridge)) =
+            (&mut self.mode_1_cartridge, &mut other.mode_1_cartridge)
+        {
+            cartridge.take_rom_from(other_cartridge);
+        }
+    }
+
+    pub fn mode_1_cartridge_title(&self) -> Option<String> {
+        self.mode_1_cartridge.as_ref().map(Cartridge::program_title)
+    }
+
+    pub fn take_mode_1_cartridge_rom(&mut self) -> Option<Vec<u8>> {
+        self.mode_1_cartridge.as_mut().map(Cartridge::take_rom)
     }
 
     pub fn forced_region(&self) -> Option<GenesisRegion> {
@@ -605,6 +641,14 @@ fn parse_disc_region(disc: &mut CdRom) -> SegaCdLoadResult<GenesisRegion> {
 impl PhysicalMedium for SegaCd {
     #[inline]
     fn read_byte(&mut self, address: u32) -> u8 {
+        let address = match &mut self.mode_1_cartridge {
+            Some(cartridge) => match map_mode_1_address(address) {
+                Mode1Address::Cartridge => return cartridge.read_byte(address),
+                Mode1Address::SegaCd(address) => address,
+            },
+            None => address,
+        };
+
         match address {
             0x000000..=0x1FFFFF => {
                 // Mirrors of BIOS at $000000-$01FFFF and PRG RAM at $020000-$03FFFF
@@ -637,6 +681,14 @@ impl PhysicalMedium for SegaCd {
 
     #[inline]
     fn read_word(&mut self, address: u32) -> u16 {
+        let address = match &mut self.mode_1_cartridge {
+            Some(cartridge) => match map_mode_1_address(address) {
+                Mode1Address::Cartridge => return cartridge.read_word(address),
+                Mode1Address::SegaCd(address) => address,
+            },
+            None => address,
+        };
+
         match address {
             0x000000..=0x1FFFFF => {
                 // Mirrors of BIOS at $000000-$01FFFF and PRG RAM at $020000-$03FFFF
@@ -684,15 +736,27 @@ impl PhysicalMedium for SegaCd {
     fn read_word_for_dma(&mut self, address: u32) -> u16 {
         // VDP DMA reads from word RAM are delayed by a cycle, effectively meaning the read should
         // be from (address - 2)
+        let word_ram_start = if self.mode_1_cartridge.is_some() { 0x600000 } else { 0x200000 };
+
+        // End range one word past the last word address in word RAM
         match address & ADDRESS_MASK {
-            // End range at $240000, one word past the last word address in word RAM
-            address @ 0x200000..=0x240000 => self.read_word(address.wrapping_sub(2)),
+            address if (word_ram_start..=word_ram_start + 0x40000).contains(&address) => {
+                self.read_word(address.wrapping_sub(2))
+            }
             address => self.read_word(address),
         }
     }
 
     #[inline]
     fn write_byte(&mut self, address: u32, value: u8) {
+        let address = match &mut self.mode_1_cartridge {
+            Some(cartridge) => match map_mode_1_address(address) {
+                Mode1Address::Cartridge => return cartridge.write_byte(address, value),
+                Mode1Address::SegaCd(address) => address,
+            },
+            None => address,
+        };
+
         match address {
             0x000000..=0x1FFFFF => {
                 // Mirrors of BIOS at $000000-$01FFFF and PRG RAM at $020000-$03FFFF
@@ -719,6 +783,14 @@ impl PhysicalMedium for SegaCd {
 
     #[inline]
     fn write_word(&mut self, address: u32, value: u16) {
+        let address = match &mut self.mode_1_cartridge {
+            Some(cartridge) => match map_mode_1_address(address) {
+                Mode1Address::Cartridge => return cartridge.write_word(address, value),
+                Mode1Address::SegaCd(address) => address,
+            },
+            None => address,
+        };
+
         match address {
             0x000000..=0x1FFFFF => {
                 // Mirrors of BIOS at $000000-$01FFFF and PRG RAM at $020000-$03FFFF
@@ -1403,8 +1475,8 @@ impl<'a> BusInterface for SubBus<'a> {
     }
 
     #[inline]
-    fn acknowledge_interrupt(&mut self) {
-        match self.interrupt_level() {
+    fn acknowledge_interrupt(&mut self, interrupt_level: u8) -> u8 {
+        match interrupt_level {
             1 => {
                 self.graphics_coprocessor.acknowledge_interrupt();
             }
@@ -1422,6 +1494,9 @@ impl<'a> BusInterface for SubBus<'a> {
             }
             _ => {}
         }
+
+        // All sub CPU interrupts are auto-vectored
+        m68000_emu::traits::autovector(interrupt_level)
     }
 
     #[inline]
@@ -2,34 +2,38 @@
 
 use crate::audio::AudioResampler;
 use crate::bus::Bus;
-use crate::input::InputState;
-use crate::memory::Memory;
+use crate::input::{InputState, SmsControllerType};
+use crate::memory::{self, Memory};
 use crate::psg::{Psg, PsgTickEffect, PsgVersion};
 use crate::vdp::{Vdp, VdpBuffer, VdpTickEffect};
 use crate::ym2413::Ym2413;
 use crate::{vdp, SmsGgInputs, VdpVersion};
 use bincode::{Decode, Encode};
+use jgenesis_common::audio::ResamplerQuality;
 use jgenesis_common::frontend::{
     AudioOutput, Color, EmulatorTrait, FrameSize, PartialClone, PixelAspectRatio, Renderer,
     SaveWriter, TickEffect, TimingMode,
 };
+use jgenesis_common::rng::{InitialRamState, Rng};
+use jgenesis_common::state::StateFormat;
+use jgenesis_link::{BoxedLink, LinkTransport};
 use jgenesis_proc_macros::{EnumDisplay, EnumFromStr, FakeDecode, FakeEncode};
+use jgenesis_scheduler::ClockRatio;
 use std::fmt::{Debug, Display};
+use std::mem;
 use std::ops::{Deref, DerefMut};
 use thiserror::Error;
 use z80_emu::{InterruptMode, Z80};
 
 #[derive(Debug, Error)]
-pub enum SmsGgError<RErr, AErr, SErr> {
+pub enum SmsGgError<RErr, AErr> {
     #[error("Rendering error: {0}")]
     Render(RErr),
     #[error("Audio output error: {0}")]
     Audio(AErr),
-    #[error("Save write error: {0}")]
-    SaveWrite(SErr),
 }
 
-pub type SmsGgResult<RErr, AErr, SErr> = Result<TickEffect, SmsGgError<RErr, AErr, SErr>>;
+pub type SmsGgResult<RErr, AErr> = Result<TickEffect, SmsGgError<RErr, AErr>>;
 
 #[derive(Debug, Clone, FakeEncode, FakeDecode)]
 struct FrameBuffer(Vec<Color>);
@@ -75,19 +79,28 @@ pub struct SmsGgEmulatorConfig {
     pub pixel_aspect_ratio: Option<PixelAspectRatio>,
     pub remove_sprite_limit: bool,
     pub sms_region: SmsRegion,
+    pub p1_controller_type: SmsControllerType,
     pub sms_crop_vertical_border: bool,
     pub sms_crop_left_border: bool,
     pub fm_sound_unit_enabled: bool,
     pub overclock_z80: bool,
+    pub audio_resampler_quality: ResamplerQuality,
+    /// Initial contents of system RAM; if None, RAM is zeroed
+    pub initial_ram_state: Option<InitialRamState>,
+    /// Seed for all in-core randomness, including random initial RAM contents; if None, a seed is
+    /// chosen randomly at power on
+    pub rng_seed: Option<u64>,
 }
 
-#[derive(Debug, Clone, Encode, Decode, PartialClone)]
+#[derive(Debug, Encode, Decode, PartialClone)]
 pub struct SmsGgEmulator {
     #[partial_clone(partial)]
     memory: Memory,
     z80: Z80,
     vdp: Vdp,
     vdp_version: VdpVersion,
+    // Whether the ROM header marks this as an SMS game; if so, a Game Gear runs it in SMS mode
+    master_system_rom: bool,
     pixel_aspect_ratio: Option<PixelAspectRatio>,
     psg: Psg,
     ym2413: Option<Ym2413>,
@@ -97,10 +110,13 @@ pub struct SmsGgEmulator {
     sms_crop_vertical_border: bool,
     sms_crop_left_border: bool,
     overclock_z80: bool,
-    z80_cycles_remainder: u32,
-    vdp_cycles_remainder: u32,
-    frame_count: u64,
+    z80_overclock: ClockRatio,
+    vdp_clock: ClockRatio,
     reset_frames_remaining: u32,
+    initial_ram_state: Option<InitialRamState>,
+    rng_seed: Option<u64>,
+    #[partial_clone(default)]
+    link_transport: BoxedLink,
 }
 
 impl SmsGgEmulator {
@@ -112,10 +128,22 @@ impl SmsGgEmulator {
     ) -> Self {
         let cartridge_ram = save_writer.load_bytes("sav").ok();
 
-        let memory = Memory::new(rom, cartridge_ram);
-        let vdp = Vdp::new(config.vdp_version, config.remove_sprite_limit);
+        let master_system_rom = memory::rom_header_is_master_system(&rom);
+        let core_vdp_version = core_vdp_version(config.vdp_version, master_system_rom);
+        if core_vdp_version != config.vdp_version {
+            log::info!("Running Master System game in Game Gear SMS compatibility mode");
+        }
+
+        let mut rng = Rng::from_optional_seed(config.rng_seed);
+        let memory = Memory::new(
+            rom,
+            cartridge_ram,
+            config.initial_ram_state.unwrap_or(InitialRamState::AllZeroes),
+            &mut rng,
+        );
+        let vdp = Vdp::new(core_vdp_version, config.remove_sprite_limit);
         let psg = Psg::new(config.psg_version);
-        let input = InputState::new(config.sms_region);
+        let input = InputState::new(config.sms_region, config.p1_controller_type);
 
         let mut z80 = Z80::new();
         init_z80(&mut z80);
@@ -128,22 +156,31 @@ impl SmsGgEmulator {
             z80,
             vdp,
             vdp_version: config.vdp_version,
+            master_system_rom,
             pixel_aspect_ratio: config.pixel_aspect_ratio,
             psg,
             ym2413,
             input,
-            audio_resampler: AudioResampler::new(timing_mode),
+            audio_resampler: AudioResampler::new(timing_mode, config.audio_resampler_quality),
             frame_buffer: FrameBuffer::new(),
             sms_crop_vertical_border: config.sms_crop_vertical_border,
             sms_crop_left_border: config.sms_crop_left_border,
             overclock_z80: config.overclock_z80,
-            z80_cycles_remainder: 0,
-            vdp_cycles_remainder: 0,
-            frame_count: 0,
+            z80_overclock: ClockRatio::divider(2),
+            vdp_clock: ClockRatio::divider(2),
             reset_frames_remaining: 0,
+            initial_ram_state: config.initial_ram_state,
+            rng_seed: config.rng_seed,
+            link_transport: BoxedLink::default(),
         }
     }
 
+    /// Connect a transport to the Game Gear's Gear-to-Gear port. Until this is called, nothing is
+    /// connected. Has no effect on Master System games.
+    pub fn set_link_transport(&mut self, transport: Box<dyn LinkTransport + Send>) {
+        self.link_transport = BoxedLink::new(transport);
+    }
+
     #[must_use]
     pub fn vdp_version(&self) -> VdpVersion {
         self.vdp_version
@@ -155,7 +192,22 @@ impl SmsGgEmulator {
         self.memory.cartridge_has_battery()
     }
 
+    /// Returns true if this is a Game Gear running a Master System game in SMS compatibility mode.
+    #[must_use]
+    pub fn is_gg_sms_mode(&self) -> bool {
+        core_vdp_version(self.vdp_version, self.master_system_rom) != self.vdp_version
+    }
+
     fn render_frame<R: Renderer>(&mut self, renderer: &mut R) -> Result<(), R::Err> {
+        if self.is_gg_sms_mode() {
+            populate_gg_sms_mode_frame_buffer(self.vdp.frame_buffer(), &mut self.frame_buffer);
+
+            let viewport = VdpVersion::GameGear.viewport_size();
+            let frame_size =
+                FrameSize { width: viewport.width.into(), height: viewport.height.into() };
+            return renderer.render_frame(&self.frame_buffer, frame_size, self.pixel_aspect_ratio);
+        }
+
         let crop_vertical_border =
             self.vdp_version.is_master_system() && self.sms_crop_vertical_border;
         let crop_left_border = self.vdp_version.is_master_system() && self.sms_crop_left_border;
@@ -190,6 +242,19 @@ impl SmsGgEmulator {
     pub fn copy_vram(&self, out: &mut [Color], palette: u8, row_len: usize) {
         self.vdp.copy_vram(out, palette, row_len);
     }
+
+    pub fn import_vram(&mut self, pixels: &[Color], palette: u8, row_len: usize) {
+        self.vdp.import_vram(pixels, palette, row_len);
+    }
+}
+
+// In SMS compatibility mode, the Game Gear VDP, I/O ports, and PSG all behave like a Master System
+fn core_vdp_version(vdp_version: VdpVersion, master_system_rom: bool) -> VdpVersion {
+    if vdp_version == VdpVersion::GameGear && master_system_rom {
+        VdpVersion::NtscMasterSystem2
+    } else {
+        vdp_version
+    }
 }
 
 fn init_z80(z80: &mut Z80) {
@@ -201,50 +266,51 @@ fn init_z80(z80: &mut Z80) {
 impl EmulatorTrait for SmsGgEmulator {
     type Inputs = SmsGgInputs;
     type Config = SmsGgEmulatorConfig;
+    const STATE_FORMAT: StateFormat = StateFormat { core: "smsgg", version: 1 };
 
     type Err<
         RErr: Debug + Display + Send + Sync + 'static,
         AErr: Debug + Display + Send + Sync + 'static,
-        SErr: Debug + Display + Send + Sync + 'static,
-    > = SmsGgError<RErr, AErr, SErr>;
+    > = SmsGgError<RErr, AErr>;
 
     /// Execute a single CPU instruction and run the rest of the components for the corresponding
     /// number of cycles.
     ///
     /// # Errors
     ///
-    /// This method will propagate any errors encountered while rendering frames, pushing audio
-    /// samples, or persisting cartridge SRAM.
+    /// This method will propagate any errors encountered while rendering frames or pushing audio
+    /// samples.
     #[inline]
-    fn tick<R, A, S>(
+    fn tick<R, A>(
         &mut self,
         renderer: &mut R,
         audio_output: &mut A,
         inputs: &Self::Inputs,
-        save_writer: &mut S,
-    ) -> SmsGgResult<R::Err, A::Err, S::Err>
+    ) -> SmsGgResult<R::Err, A::Err>
     where
         R: Renderer,
         A: AudioOutput,
-        S: SaveWriter,
     {
-        let t_cycles = self.z80.execute_instruction(&mut Bus::new(
-            self.vdp_version,
+        let t_cycles = u64::from(self.z80.execute_instruction(&mut Bus::new(
+            core_vdp_version(self.vdp_version, self.master_system_rom),
             &mut self.memory,
             &mut self.vdp,
             &mut self.psg,
             self.ym2413.as_mut(),
             &mut self.input,
-        ));
-        let (t_cycles, remainder) = if self.overclock_z80 {
+        )));
+        let t_cycles = if self.overclock_z80 {
             // Emulate a Z80 running at 2x speed by only ticking the rest of the components for
             // half as many cycles
-            let t_cycles = t_cycles + self.z80_cycles_remainder;
-            (t_cycles / 2, t_cycles % 2)
+            self.z80_overclock.tick(t_cycles)
         } else {
-            (t_cycles, 0)
+            self.z80_overclock.reset();
+            t_cycles
         };
-        self.z80_cycles_remainder = remainder;
+
+        let gg_link = self.input.gg_link_mut();
+        gg_link.tick(t_cycles as u32);
+        gg_link.exchange(&mut self.link_transport);
 
         for _ in 0..t_cycles {
             if let Some(ym2413) = &mut self.ym2413 {
@@ -265,38 +331,36 @@ impl EmulatorTrait for SmsGgEmulator {
             }
         }
 
-        let t_cycles_plus_leftover = t_cycles + self.vdp_cycles_remainder;
-        self.vdp_cycles_remainder = t_cycles_plus_leftover % 2;
-
+        // The VDP runs 3 cycles for every 2 CPU cycles
         let mut frame_rendered = false;
-        let vdp_cycles = t_cycles_plus_leftover / 2 * 3;
-        for _ in 0..vdp_cycles {
-            if self.vdp.tick() == VdpTickEffect::FrameComplete {
-                self.render_frame(renderer).map_err(SmsGgError::Render)?;
-                frame_rendered = true;
-
-                self.audio_resampler.output_samples(audio_output).map_err(SmsGgError::Audio)?;
-
-                self.input.set_inputs(inputs);
-                self.input.set_reset(self.reset_frames_remaining != 0);
-                self.reset_frames_remaining = self.reset_frames_remaining.saturating_sub(1);
-
-                self.frame_count += 1;
-                if self.frame_count % 60 == 0
-                    && self.memory.cartridge_has_battery()
-                    && self.memory.cartridge_ram_dirty()
-                {
-                    self.memory.clear_cartridge_ram_dirty();
-                    save_writer
-                        .persist_bytes("sav", self.memory.cartridge_ram())
-                        .map_err(SmsGgError::SaveWrite)?;
-                }
-            }
+        let vdp_cycles = self.vdp_clock.tick(t_cycles) * 3;
+        if self.vdp.tick(vdp_cycles) == VdpTickEffect::FrameComplete {
+            self.render_frame(renderer).map_err(SmsGgError::Render)?;
+            frame_rendered = true;
+
+            self.audio_resampler.output_samples(audio_output).map_err(SmsGgError::Audio)?;
+
+            self.input.set_inputs(inputs);
+            self.input.set_reset(self.reset_frames_remaining != 0);
+            self.reset_frames_remaining = self.reset_frames_remaining.saturating_sub(1);
         }
 
         Ok(if frame_rendered { TickEffect::FrameRendered } else { TickEffect::None })
     }
 
+    fn save_dirty(&self) -> bool {
+        self.memory.cartridge_has_battery() && self.memory.cartridge_ram_dirty()
+    }
+
+    fn persist_save<S: SaveWriter>(&mut self, save_writer: &mut S) -> Result<(), S::Err> {
+        if self.save_dirty() {
+            save_writer.persist_bytes("sav", self.memory.cartridge_ram())?;
+            self.memory.clear_cartridge_ram_dirty();
+        }
+
+        Ok(())
+    }
+
     fn force_render<R>(&mut self, renderer: &mut R) -> Result<(), R::Err>
     where
         R: Renderer,
@@ -306,19 +370,24 @@ impl EmulatorTrait for SmsGgEmulator {
 
     fn reload_config(&mut self, config: &Self::Config) {
         self.vdp_version = config.vdp_version;
-        self.vdp.set_version(config.vdp_version);
+        self.vdp.set_version(core_vdp_version(config.vdp_version, self.master_system_rom));
         self.psg.set_version(config.psg_version);
         self.pixel_aspect_ratio = config.pixel_aspect_ratio;
         self.vdp.set_remove_sprite_limit(config.remove_sprite_limit);
         self.input.set_region(config.sms_region);
+        self.input.set_p1_controller_type(config.p1_controller_type);
         self.sms_crop_vertical_border = config.sms_crop_vertical_border;
         self.sms_crop_left_border = config.sms_crop_left_border;
         self.overclock_z80 = config.overclock_z80;
+        self.initial_ram_state = config.initial_ram_state;
+        self.rng_seed = config.rng_seed;
         self.audio_resampler.update_timing_mode(self.vdp.timing_mode());
+        self.audio_resampler.set_quality(config.audio_resampler_quality);
     }
 
     fn take_rom_from(&mut self, other: &mut Self) {
         self.memory.take_rom_from(&mut other.memory);
+        mem::swap(&mut self.link_transport, &mut other.link_transport);
     }
 
     fn soft_reset(&mut self) {
@@ -333,17 +402,25 @@ impl EmulatorTrait for SmsGgEmulator {
         log::info!("Hard resetting console");
 
         let (rom, ram) = self.memory.take_cartridge_rom_and_ram();
-        self.memory = Memory::new(rom, Some(ram));
+        let mut rng = Rng::from_optional_seed(self.rng_seed);
+        self.memory = Memory::new(
+            rom,
+            Some(ram),
+            self.initial_ram_state.unwrap_or(InitialRamState::AllZeroes),
+            &mut rng,
+        );
 
         self.z80 = Z80::new();
         init_z80(&mut self.z80);
 
-        self.vdp = Vdp::new(self.vdp_version, self.vdp.get_remove_sprite_limit());
+        self.vdp = Vdp::new(
+            core_vdp_version(self.vdp_version, self.master_system_rom),
+            self.vdp.get_remove_sprite_limit(),
+        );
         self.psg = Psg::new(self.psg.version());
-        self.input = InputState::new(self.input.region());
+        self.input = InputState::new(self.input.region(), self.input.p1_controller_type());
 
-        self.vdp_cycles_remainder = 0;
-        self.frame_count = 0;
+        self.vdp_clock.reset();
     }
 
     fn timing_mode(&self) -> TimingMode {
@@ -391,3 +468,54 @@ fn populate_frame_buffer(
         }
     }
 }
+
+// The Game Gear LCD scales the 256x192 SMS display down to 160x144 by blending adjacent pixels,
+// and it expands each 2-bit SMS color component to 4 bits by repeating the bits
+fn populate_gg_sms_mode_frame_buffer(vdp_buffer: &VdpBuffer, frame_buffer: &mut [Color]) {
+    let sms_viewport = VdpVersion::NtscMasterSystem2.viewport_size();
+    let gg_viewport = VdpVersion::GameGear.viewport_size();
+
+    let sms_height = sms_viewport.height_without_border();
+    let sms_width = sms_viewport.width;
+    let row_offset = sms_viewport.top_border_height;
+
+    let col_taps: Vec<_> = (0..gg_viewport.width)
+        .map(|gg_col| lcd_scale_taps(gg_col, sms_width, gg_viewport.width))
+        .collect();
+
+    for gg_row in 0..gg_viewport.height {
+        let row_taps = lcd_scale_taps(gg_row, sms_height, gg_viewport.height);
+        for (gg_col, col_taps) in col_taps.iter().enumerate() {
+            let mut rgb = [0.0; 3];
+            for &(sms_row, row_weight) in &row_taps {
+                for &(sms_col, col_weight) in col_taps {
+                    let color = vdp_buffer.get(row_offset + sms_row, sms_col);
+                    for (i, component) in rgb.iter_mut().enumerate() {
+                        let sms_component = (color >> (2 * i)) & 0x03;
+                        let gg_component = vdp::convert_gg_color(sms_component * 5);
+                        *component += f64::from(gg_component) * row_weight * col_weight;
+                    }
+                }
+            }
+
+            let [r, g, b] = rgb.map(|component| component.round() as u8);
+            frame_buffer[gg_row as usize * gg_viewport.width as usize + gg_col] =
+                Color::rgb(r, g, b);
+        }
+    }
+}
+
+// Returns the source pixels that overlap the given destination pixel, along with the fraction of
+// the destination pixel that each one covers
+fn lcd_scale_taps(dest: u16, src_len: u16, dest_len: u16) -> Vec<(u16, f64)> {
+    let scale = f64::from(src_len) / f64::from(dest_len);
+    let start = f64::from(dest) * scale;
+    let end = start + scale;
+
+    (start.floor() as u16..(end.ceil() as u16).min(src_len))
+        .map(|src| {
+            let overlap = end.min(f64::from(src + 1)) - start.max(f64::from(src));
+            (src, overlap / scale)
+        })
+        .collect()
+}
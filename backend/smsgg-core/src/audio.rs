@@ -3,7 +3,7 @@
 #![allow(clippy::excessive_precision)]
 
 use bincode::{Decode, Encode};
-use jgenesis_common::audio::SignalResampler;
+use jgenesis_common::audio::{ResamplerQuality, SignalResampler};
 use jgenesis_common::frontend::{AudioOutput, TimingMode};
 
 const NTSC_MCLK_FREQUENCY: f64 = 53_693_175.0;
@@ -86,8 +86,9 @@ pub(crate) struct AudioResampler {
 }
 
 impl AudioResampler {
-    pub fn new(timing_mode: TimingMode) -> Self {
-        let psg_resampler = new_psg_resampler(timing_mode.mclk_frequency());
+    pub fn new(timing_mode: TimingMode, quality: ResamplerQuality) -> Self {
+        let mut psg_resampler = new_psg_resampler(timing_mode.mclk_frequency());
+        psg_resampler.set_quality(quality);
         Self { psg_resampler }
     }
 
@@ -96,6 +97,10 @@ impl AudioResampler {
         self.psg_resampler.update_source_frequency(psg_frequency);
     }
 
+    pub fn set_quality(&mut self, quality: ResamplerQuality) {
+        self.psg_resampler.set_quality(quality);
+    }
+
     pub fn collect_sample(&mut self, sample_l: f64, sample_r: f64) {
         self.psg_resampler.collect_sample(sample_l, sample_r);
     }
@@ -43,12 +43,16 @@ impl<'a> BusInterface for Bus<'a> {
     fn read_io(&mut self, address: u16) -> u8 {
         let address = address & 0xFF;
         if self.version == VdpVersion::GameGear && address <= 0x06 {
-            // TODO Game Gear registers
+            // Only the serial mode of the Gear-to-Gear port ($03-$05) is linked. The parallel data
+            // port ($01) and its direction register ($02) always read as if nothing were plugged
+            // in, since link transports carry raw serial bytes with no room for pin states
             return match address {
                 0x00 => (u8::from(!self.input.pause_pressed()) << 7) | 0x40,
                 0x01 => 0x7F,
-                0x02 | 0x04 | 0x06 => 0xFF,
-                0x03 | 0x05 => 0x00,
+                0x02 | 0x06 => 0xFF,
+                0x03 => self.input.gg_link().read_tx_data(),
+                0x04 => self.input.gg_link_mut().read_rx_data(),
+                0x05 => self.input.gg_link().read_ctrl(),
                 _ => unreachable!("value is <= 0x06"),
             };
         }
@@ -93,8 +97,11 @@ impl<'a> BusInterface for Bus<'a> {
     fn write_io(&mut self, address: u16, value: u8) {
         let address = address & 0xFF;
         if self.version == VdpVersion::GameGear && address <= 0x06 {
-            if address == 0x06 {
-                self.psg.write_stereo_control(value);
+            match address {
+                0x03 => self.input.gg_link_mut().write_tx_data(value),
+                0x05 => self.input.gg_link_mut().write_ctrl(value),
+                0x06 => self.psg.write_stereo_control(value),
+                _ => {}
             }
             return;
         }
@@ -143,7 +150,10 @@ impl<'a> BusInterface for Bus<'a> {
     }
 
     fn nmi(&self) -> InterruptLine {
-        if self.version.is_master_system() && self.input.pause_pressed() {
+        // On Game Gear, NMI is used for Gear-to-Gear receive interrupts instead of the pause button
+        let gg_link_interrupt =
+            self.version == VdpVersion::GameGear && self.input.gg_link().interrupt_pending();
+        if (self.version.is_master_system() && self.input.pause_pressed()) || gg_link_interrupt {
             InterruptLine::Low
         } else {
             InterruptLine::High
@@ -3,6 +3,8 @@
 use crate::api::SmsRegion;
 use bincode::{Decode, Encode};
 use jgenesis_common::num::GetBit;
+use jgenesis_link::uart::Uart;
+use jgenesis_proc_macros::{EnumDisplay, EnumFromStr};
 
 #[derive(Debug, Clone, Copy, Default, Encode, Decode)]
 pub struct SmsGgJoypadState {
@@ -14,11 +16,40 @@ pub struct SmsGgJoypadState {
     pub button_2: bool,
 }
 
+/// Device connected to the Master System's first controller port
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode, EnumDisplay, EnumFromStr)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SmsControllerType {
+    #[default]
+    Joypad,
+    Paddle,
+    SportsPad,
+    GraphicBoard,
+}
+
+/// Analog state for the P1 peripherals. Peripheral buttons are read from the P1 joypad state:
+/// - Paddle: button 1
+/// - Sports Pad: buttons 1 and 2
+/// - Graphic Board: button 1 is Menu and button 2 is Do
+#[derive(Debug, Clone, Copy, Default, Encode, Decode)]
+pub struct SmsPeripheralState {
+    /// Paddle knob position, from 0 (fully left) to 255 (fully right)
+    pub paddle_position: u8,
+    /// Sports Pad trackball movement since the previous frame
+    pub sports_pad_x: i8,
+    pub sports_pad_y: i8,
+    /// Graphic Board pen position
+    pub graphic_board_x: u8,
+    pub graphic_board_y: u8,
+    pub graphic_board_pen_down: bool,
+}
+
 #[derive(Debug, Clone, Default, Encode, Decode)]
 pub struct SmsGgInputs {
     pub p1: SmsGgJoypadState,
     pub p2: SmsGgJoypadState,
     pub pause: bool,
+    pub p1_peripheral: SmsPeripheralState,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
@@ -34,6 +65,11 @@ impl PinDirection {
             Self::Output(output_value) => output_value,
         }
     }
+
+    // Input pins are pulled high
+    fn level(self) -> bool {
+        self.bit(true)
+    }
 }
 
 #[derive(Debug, Clone, Encode, Decode)]
@@ -45,10 +81,19 @@ pub struct InputState {
     port_b_th: PinDirection,
     region: SmsRegion,
     reset: bool,
+    p1_controller_type: SmsControllerType,
+    // Which part of the peripheral's data is currently being read; the meaning depends on the
+    // peripheral type
+    peripheral_phase: u8,
+    // Serial mode of the Game Gear's Gear-to-Gear port
+    gg_link: Uart,
 }
 
+// Game Gear Z80 clock; the Game Gear only exists as an NTSC system
+const GG_Z80_CLOCK_HZ: u32 = 3_579_545;
+
 impl InputState {
-    pub fn new(region: SmsRegion) -> Self {
+    pub fn new(region: SmsRegion, p1_controller_type: SmsControllerType) -> Self {
         Self {
             inputs: SmsGgInputs::default(),
             port_a_tr: PinDirection::Input,
@@ -57,9 +102,20 @@ impl InputState {
             port_b_th: PinDirection::Input,
             region,
             reset: false,
+            p1_controller_type,
+            peripheral_phase: 0,
+            gg_link: Uart::new(GG_Z80_CLOCK_HZ),
         }
     }
 
+    pub fn gg_link(&self) -> &Uart {
+        &self.gg_link
+    }
+
+    pub fn gg_link_mut(&mut self) -> &mut Uart {
+        &mut self.gg_link
+    }
+
     pub fn pause_pressed(&self) -> bool {
         self.inputs.pause
     }
@@ -80,7 +136,21 @@ impl InputState {
         self.reset = reset;
     }
 
+    pub fn p1_controller_type(&self) -> SmsControllerType {
+        self.p1_controller_type
+    }
+
+    pub fn set_p1_controller_type(&mut self, controller_type: SmsControllerType) {
+        if controller_type != self.p1_controller_type {
+            self.p1_controller_type = controller_type;
+            self.peripheral_phase = 0;
+        }
+    }
+
     pub fn write_control(&mut self, value: u8) {
+        let prev_port_a_th = self.port_a_th.level();
+        let prev_port_a_tr = self.port_a_tr.level();
+
         self.port_b_th =
             if value.bit(3) { PinDirection::Input } else { PinDirection::Output(value.bit(7)) };
         self.port_b_tr =
@@ -89,14 +159,44 @@ impl InputState {
             if value.bit(1) { PinDirection::Input } else { PinDirection::Output(value.bit(5)) };
         self.port_a_tr =
             if value.bit(0) { PinDirection::Input } else { PinDirection::Output(value.bit(4)) };
+
+        let port_a_th = self.port_a_th.level();
+        let port_a_tr = self.port_a_tr.level();
+        match self.p1_controller_type {
+            SmsControllerType::Joypad | SmsControllerType::Paddle => {}
+            SmsControllerType::SportsPad => {
+                // Every TH transition advances to the next nibble
+                if port_a_th != prev_port_a_th {
+                    self.peripheral_phase = (self.peripheral_phase + 1) & 0x03;
+                }
+            }
+            SmsControllerType::GraphicBoard => {
+                // Setting TH high resets the read sequence, and every TR transition while TH is low
+                // advances to the next nibble
+                if port_a_th {
+                    self.peripheral_phase = 0;
+                } else if port_a_tr != prev_port_a_tr {
+                    self.peripheral_phase = (self.peripheral_phase + 1).min(7);
+                }
+            }
+        }
+    }
+
+    pub fn port_dc(&mut self) -> u8 {
+        let port_a = match self.p1_controller_type {
+            SmsControllerType::Joypad => self.read_joypad_port_a(),
+            SmsControllerType::Paddle => self.read_paddle(),
+            SmsControllerType::SportsPad => self.read_sports_pad(),
+            SmsControllerType::GraphicBoard => self.read_graphic_board(),
+        };
+
+        (u8::from(!self.inputs.p2.down) << 7) | (u8::from(!self.inputs.p2.up) << 6) | port_a
     }
 
-    pub fn port_dc(&self) -> u8 {
+    fn read_joypad_port_a(&self) -> u8 {
         let port_a_tr_bit = u8::from(self.port_a_tr.bit(!self.inputs.p1.button_2)) << 5;
 
-        (u8::from(!self.inputs.p2.down) << 7)
-            | (u8::from(!self.inputs.p2.up) << 6)
-            | port_a_tr_bit
+        port_a_tr_bit
             | (u8::from(!self.inputs.p1.button_1) << 4)
             | (u8::from(!self.inputs.p1.right) << 3)
             | (u8::from(!self.inputs.p1.left) << 2)
@@ -104,6 +204,65 @@ impl InputState {
             | u8::from(!self.inputs.p1.up)
     }
 
+    fn read_paddle(&mut self) -> u8 {
+        // The Japanese paddle flips between the low and high nibble on its own, which is
+        // approximated by flipping on every read. The export paddle uses TH to select the nibble.
+        let high_nibble = match self.region {
+            SmsRegion::Domestic => {
+                self.peripheral_phase ^= 1;
+                self.peripheral_phase.bit(0)
+            }
+            SmsRegion::International => self.port_a_th.level(),
+        };
+
+        let position = self.inputs.p1_peripheral.paddle_position;
+        let nibble = if high_nibble { position >> 4 } else { position & 0x0F };
+
+        // TR reads 0 while the high nibble is presented
+        let port_a_tr_bit = u8::from(self.port_a_tr.bit(!high_nibble)) << 5;
+
+        port_a_tr_bit | (u8::from(!self.inputs.p1.button_1) << 4) | nibble
+    }
+
+    fn read_sports_pad(&self) -> u8 {
+        let peripheral = self.inputs.p1_peripheral;
+        let nibble = match self.peripheral_phase {
+            1 => (peripheral.sports_pad_x as u8) >> 4,
+            2 => (peripheral.sports_pad_x as u8) & 0x0F,
+            3 => (peripheral.sports_pad_y as u8) >> 4,
+            0 => (peripheral.sports_pad_y as u8) & 0x0F,
+            _ => unreachable!("phase & 0x03 is always <= 0x03"),
+        };
+
+        let port_a_tr_bit = u8::from(self.port_a_tr.bit(!self.inputs.p1.button_2)) << 5;
+
+        port_a_tr_bit | (u8::from(!self.inputs.p1.button_1) << 4) | nibble
+    }
+
+    fn read_graphic_board(&self) -> u8 {
+        let peripheral = self.inputs.p1_peripheral;
+        let nibble = match self.peripheral_phase {
+            // Buttons, active low
+            1 => {
+                (u8::from(!peripheral.graphic_board_pen_down) << 2)
+                    | (u8::from(!self.inputs.p1.button_2) << 1)
+                    | u8::from(!self.inputs.p1.button_1)
+                    | 0x08
+            }
+            // Pen pressure; games ignore the position unless pressure is at or near max
+            2 => 0x0F * u8::from(peripheral.graphic_board_pen_down),
+            3 => peripheral.graphic_board_x >> 4,
+            4 => peripheral.graphic_board_x & 0x0F,
+            5 => peripheral.graphic_board_y >> 4,
+            6 => peripheral.graphic_board_y & 0x0F,
+            _ => 0x00,
+        };
+
+        // TL always reads 1; TR is driven by the console
+        let port_a_tr_bit = u8::from(self.port_a_tr.level()) << 5;
+        port_a_tr_bit | 0x10 | nibble
+    }
+
     pub fn port_dd(&self) -> u8 {
         let port_b_th_bit =
             u8::from(self.region == SmsRegion::International && self.port_b_th.bit(true)) << 7;
@@ -1,14 +1,19 @@
+//! Sega Master System / Game Gear emulation core
+//!
+//! The crate root re-exports the emulator, its config, and its input types; these are the stable API
+//! for frontends.
+
 mod api;
 pub mod audio;
 mod bus;
 mod input;
 mod memory;
 pub mod psg;
-mod vdp;
+pub mod vdp;
 mod ym2413;
 
 pub use api::{SmsGgEmulator, SmsGgEmulatorConfig, SmsGgError, SmsGgResult, SmsRegion};
-pub use input::{SmsGgInputs, SmsGgJoypadState};
+pub use input::{SmsControllerType, SmsGgInputs, SmsGgJoypadState, SmsPeripheralState};
 pub use vdp::{gg_color_to_rgb, sms_color_to_rgb, VdpVersion};
 
 // 8:7
@@ -5,6 +5,7 @@ mod metadata;
 use bincode::{Decode, Encode};
 use crc::Crc;
 use jgenesis_common::num::GetBit;
+use jgenesis_common::rng::{InitialRamState, Rng};
 use jgenesis_proc_macros::{FakeDecode, FakeEncode, PartialClone};
 use std::mem;
 use std::ops::{Index, RangeInclusive};
@@ -52,6 +53,26 @@ impl Mapper {
     }
 }
 
+// The BIOS checks for the "TMR SEGA" header at each of these addresses, in this order
+const TMR_SEGA_HEADER_ADDRS: [usize; 3] = [0x7FF0, 0x3FF0, 0x1FF0];
+
+/// Returns whether the ROM header's region code marks this as a Master System game (codes 3 and 4)
+/// as opposed to a Game Gear game (codes 5-7). A Game Gear runs Master System games in its SMS
+/// compatibility mode. ROMs without a header are assumed not to be Master System games.
+pub fn rom_header_is_master_system(rom: &[u8]) -> bool {
+    let Some(header_addr) = TMR_SEGA_HEADER_ADDRS
+        .into_iter()
+        .find(|&addr| rom.len() > addr + 0xF && &rom[addr..addr + 8] == b"TMR SEGA")
+    else {
+        return false;
+    };
+
+    let region_code = rom[header_addr + 0xF] >> 4;
+    log::info!("ROM header at {header_addr:04X} has region code {region_code:X}");
+
+    matches!(region_code, 3 | 4)
+}
+
 #[derive(Debug, Clone, Default, FakeEncode, FakeDecode)]
 struct Rom(Vec<u8>);
 
@@ -201,10 +222,18 @@ pub struct Memory {
 }
 
 impl Memory {
-    pub fn new(rom: Vec<u8>, initial_cartridge_ram: Option<Vec<u8>>) -> Self {
+    pub fn new(
+        rom: Vec<u8>,
+        initial_cartridge_ram: Option<Vec<u8>>,
+        initial_ram_state: InitialRamState,
+        rng: &mut Rng,
+    ) -> Self {
+        let mut ram = [0; SYSTEM_RAM_SIZE];
+        initial_ram_state.fill(&mut ram, rng);
+
         Self {
             cartridge: Cartridge::new(rom, initial_cartridge_ram),
-            ram: [0; SYSTEM_RAM_SIZE],
+            ram,
             audio_control: AudioControl::default(),
         }
     }
@@ -7,13 +7,14 @@
 mod debug;
 mod tms9918;
 
-use bincode::de::{BorrowDecoder, Decoder};
-use bincode::enc::Encoder;
-use bincode::error::{DecodeError, EncodeError};
-use bincode::{BorrowDecode, Decode, Encode};
+pub use tms9918::TMS9918_COLOR_TO_SMS_COLOR;
+
+use bincode::{Decode, Encode};
 use jgenesis_common::frontend::{Color, TimingMode};
 use jgenesis_common::num::{GetBit, U16Ext};
 use jgenesis_proc_macros::{EnumDisplay, EnumFromStr};
+use jgenesis_scheduler::EventQueue;
+use jgenesis_state::SaveState;
 use z80_emu::traits::InterruptLine;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
@@ -58,10 +59,12 @@ impl ViewportSize {
         left_border_width: 0,
     };
 
+    #[must_use]
     pub fn height_without_border(self) -> u16 {
         self.height - self.top_border_height - self.bottom_border_height
     }
 
+    #[must_use]
     pub fn width_without_border(self) -> u16 {
         self.width - self.left_border_width
     }
@@ -148,6 +151,8 @@ enum Mode {
     #[default]
     Four,
     Four224Line,
+    // TMS9918 mode 0
+    GraphicsI,
     // TMS9918 mode 2
     GraphicsII,
 }
@@ -159,6 +164,7 @@ impl Mode {
                 Self::Four
             }
             [true, true, false, true] => Self::Four224Line,
+            [false, false, false, false] => Self::GraphicsI,
             [false, true, false, false] => Self::GraphicsII,
             _ => {
                 log::warn!("Unsupported mode, defaulting to mode 4: {mode_bits:?}");
@@ -169,14 +175,14 @@ impl Mode {
 
     const fn name_table_rows(self) -> u16 {
         match self {
-            Self::Four | Self::GraphicsII => 28,
+            Self::Four | Self::GraphicsI | Self::GraphicsII => 28,
             Self::Four224Line => 32,
         }
     }
 
     const fn active_scanlines(self) -> u16 {
         match self {
-            Self::Four | Self::GraphicsII => 192,
+            Self::Four | Self::GraphicsI | Self::GraphicsII => 192,
             Self::Four224Line => 224,
         }
     }
@@ -184,7 +190,7 @@ impl Mode {
     // The number of scanlines to remove from each of the top and bottom borders when in this mode
     const fn vertical_border_offset(self) -> u16 {
         match self {
-            Self::Four | Self::GraphicsII => 0,
+            Self::Four | Self::GraphicsI | Self::GraphicsII => 0,
             Self::Four224Line => 16,
         }
     }
@@ -494,6 +500,8 @@ struct SpriteData {
     tile_index: u16,
 }
 
+const MAX_SPRITES_PER_LINE: usize = 8;
+
 #[derive(Debug, Clone, Encode, Decode)]
 struct SpriteBuffer {
     sprites: [SpriteData; 64],
@@ -571,7 +579,7 @@ fn find_sprites_on_scanline(
         let sprite_top = y.saturating_add(1);
         let sprite_bottom = sprite_top.saturating_add(sprite_height);
         if (sprite_top..sprite_bottom).contains(&scanline) {
-            if sprite_buffer.len == 8 {
+            if sprite_buffer.len == MAX_SPRITES_PER_LINE {
                 sprite_buffer.overflow = true;
                 if !remove_sprite_limit {
                     return;
@@ -592,15 +600,16 @@ pub const SCREEN_WIDTH: u16 = 256;
 pub const SCREEN_HEIGHT: u16 = 240;
 pub const FRAME_BUFFER_LEN: usize = SCREEN_WIDTH as usize * SCREEN_HEIGHT as usize;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, SaveState)]
 pub struct VdpBuffer {
+    #[save_state(skip, default = "new_frame_buffer")]
     buffer: Vec<u16>,
     viewport: ViewportSize,
 }
 
 impl VdpBuffer {
     fn new(version: VdpVersion) -> Self {
-        Self { buffer: vec![0; FRAME_BUFFER_LEN], viewport: version.viewport_size() }
+        Self { buffer: new_frame_buffer(), viewport: version.viewport_size() }
     }
 
     #[inline]
@@ -611,6 +620,7 @@ impl VdpBuffer {
     }
 
     #[inline]
+    #[must_use]
     pub fn get(&self, row: u16, col: u16) -> u16 {
         self.buffer[self.idx(row, col)]
     }
@@ -620,30 +630,14 @@ impl VdpBuffer {
         self.buffer[row as usize * SCREEN_WIDTH as usize + col as usize] = value;
     }
 
+    #[must_use]
     pub fn iter(&self) -> FrameBufferRowIter<'_> {
         FrameBufferRowIter { buffer: self, row: 0 }
     }
 }
 
-impl Encode for VdpBuffer {
-    fn encode<E: Encoder>(&self, encoder: &mut E) -> Result<(), EncodeError> {
-        self.viewport.encode(encoder)?;
-        Ok(())
-    }
-}
-
-impl Decode for VdpBuffer {
-    fn decode<D: Decoder>(decoder: &mut D) -> Result<Self, DecodeError> {
-        let viewport = ViewportSize::decode(decoder)?;
-        Ok(Self { buffer: vec![0; FRAME_BUFFER_LEN], viewport })
-    }
-}
-
-impl<'de> BorrowDecode<'de> for VdpBuffer {
-    fn borrow_decode<D: BorrowDecoder<'de>>(decoder: &mut D) -> Result<Self, DecodeError> {
-        let viewport = ViewportSize::borrow_decode(decoder)?;
-        Ok(Self { buffer: vec![0; FRAME_BUFFER_LEN], viewport })
-    }
+fn new_frame_buffer() -> Vec<u16> {
+    vec![0; FRAME_BUFFER_LEN]
 }
 
 #[derive(Debug, Clone)]
@@ -686,13 +680,28 @@ pub struct Vdp {
     vram: [u8; VRAM_SIZE],
     color_ram: [u8; COLOR_RAM_SIZE],
     scanline: u16,
-    dot: u16,
+    // Total dots elapsed since power on; event timestamps are in dots
+    cycles: u64,
+    events: EventQueue<VdpEvent>,
     sprite_buffer: SpriteBuffer,
     remove_sprite_limit: bool,
     line_counter: u8,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+enum VdpEvent {
+    // Decrement or reload the line counter during HBlank
+    LineCounter,
+    // Move to the next scanline, then render it or start VBlank
+    LineEnd,
+}
+
 const DOTS_PER_SCANLINE: u16 = 342;
+
+// Line interrupts fire near the end of the line during HBlank (H counter $F4) rather than at the
+// start of the line, which means that registers written by the interrupt handler take effect
+// starting 2 lines after the line that triggered the interrupt
+const LINE_INTERRUPT_DOT: u16 = 318;
 const NTSC_SCANLINES_PER_FRAME: u16 = 262;
 const PAL_SCANLINES_PER_FRAME: u16 = 313;
 
@@ -702,21 +711,32 @@ pub enum VdpTickEffect {
     FrameComplete,
 }
 
+fn schedule_line_events(events: &mut EventQueue<VdpEvent>, line_start_cycles: u64) {
+    events.schedule(line_start_cycles + u64::from(LINE_INTERRUPT_DOT), VdpEvent::LineCounter);
+    events.schedule(line_start_cycles + u64::from(DOTS_PER_SCANLINE), VdpEvent::LineEnd);
+}
+
 impl Vdp {
+    #[must_use]
     pub fn new(version: VdpVersion, remove_sprite_limit: bool) -> Self {
+        let mut events = EventQueue::new();
+        schedule_line_events(&mut events, 0);
+
         Self {
             frame_buffer: VdpBuffer::new(version),
             registers: Registers::new(version),
             vram: [0; VRAM_SIZE],
             color_ram: [0; COLOR_RAM_SIZE],
             scanline: 0,
-            dot: 0,
+            cycles: 0,
+            events,
             sprite_buffer: SpriteBuffer::new(),
             remove_sprite_limit,
             line_counter: 0xFF,
         }
     }
 
+    #[must_use]
     pub fn get_remove_sprite_limit(&self) -> bool {
         self.remove_sprite_limit
     }
@@ -740,7 +760,9 @@ impl Vdp {
     fn read_name_table_word(&self, row: u16, col: u16) -> BgTileData {
         let base_name_table_addr = match self.registers.mode {
             // Mask out bit 10 (only used by legacy modes)
-            Mode::Four | Mode::GraphicsII => self.registers.base_name_table_address & 0xF800,
+            Mode::Four | Mode::GraphicsI | Mode::GraphicsII => {
+                self.registers.base_name_table_address & 0xF800
+            }
             // Mask out bit 11 and offset by $0700
             Mode::Four224Line => (self.registers.base_name_table_address & 0xF000) | 0x0700,
         };
@@ -759,8 +781,8 @@ impl Vdp {
     }
 
     fn render_scanline(&mut self) {
-        if self.registers.mode == Mode::GraphicsII {
-            self.render_graphics_2_scanline();
+        if matches!(self.registers.mode, Mode::GraphicsI | Mode::GraphicsII) {
+            self.render_tms9918_scanline();
             return;
         }
 
@@ -840,7 +862,7 @@ impl Vdp {
 
                 let sprite_dot = if self.registers.shift_sprites_left { dot + 8 } else { dot };
                 let mut found_sprite_color_id = None;
-                for sprite in &self.sprite_buffer {
+                for (i, sprite) in self.sprite_buffer.iter().enumerate() {
                     let sprite_left: u16 = sprite.x.into();
                     let sprite_right = sprite_left + sprite_width;
                     if !(sprite_left..sprite_right).contains(&sprite_dot) {
@@ -869,7 +891,12 @@ impl Vdp {
                                 found_sprite_color_id = Some(sprite_color_id);
                             }
                             Some(_) => {
-                                self.registers.sprite_collision = true;
+                                // Actual hardware only fetches the first 8 sprites on a line, so
+                                // sprites past the limit must not set the collision flag when the
+                                // sprite limit is removed
+                                if i < MAX_SPRITES_PER_LINE {
+                                    self.registers.sprite_collision = true;
+                                }
                                 break;
                             }
                         }
@@ -923,21 +950,38 @@ impl Vdp {
         }
     }
 
+    /// Run the VDP for the given number of dots, processing every scanline event that falls within
+    /// that time.
     #[must_use]
-    pub fn tick(&mut self) -> VdpTickEffect {
-        if log::log_enabled!(log::Level::Trace) && self.scanline == 0 && self.dot == 0 {
-            self.debug_log();
+    pub fn tick(&mut self, dots: u64) -> VdpTickEffect {
+        if dots == 0 {
+            return VdpTickEffect::None;
         }
 
-        let active_scanlines = self.registers.mode.active_scanlines();
-        if self.registers.display_enabled && self.scanline < active_scanlines && self.dot == 0 {
-            self.render_scanline();
+        // Events are processed if they fall on any of the dots in [cycles, cycles + dots)
+        let end = self.cycles + dots;
+        let mut tick_effect = VdpTickEffect::None;
+        while let Some((timestamp, event)) = self.events.pop_due(end - 1) {
+            match event {
+                VdpEvent::LineCounter => self.update_line_counter(),
+                VdpEvent::LineEnd => {
+                    if self.end_line(timestamp) == VdpTickEffect::FrameComplete {
+                        tick_effect = VdpTickEffect::FrameComplete;
+                    }
+                }
+            }
         }
+        self.cycles = end;
+
+        tick_effect
+    }
 
+    fn update_line_counter(&mut self) {
         // The apparent off-by-one in this comparison is intentional. The line counter is
         // decremented on every active scanline *and* on the scanline immediately following the
         // active period.
-        if self.scanline <= active_scanlines && self.dot == 0 {
+        let active_scanlines = self.registers.mode.active_scanlines();
+        if self.scanline <= active_scanlines {
             let (new_counter, overflowed) = self.line_counter.overflowing_sub(1);
             if overflowed {
                 self.line_counter = self.registers.line_counter_reload_value;
@@ -945,36 +989,49 @@ impl Vdp {
             } else {
                 self.line_counter = new_counter;
             }
-        } else if self.scanline > active_scanlines {
-            // Line counter is constantly reloaded outside of the active display period
+        } else {
+            self.reload_line_counter();
+        }
+    }
+
+    fn reload_line_counter(&mut self) {
+        // Line counter is constantly reloaded outside of the active display period, so only the
+        // value at the end of each line matters
+        if self.scanline > self.registers.mode.active_scanlines() {
             self.line_counter = self.registers.line_counter_reload_value;
         }
+    }
 
-        let vblank_start = self.scanline == active_scanlines + 1 && self.dot == 0;
-        if vblank_start {
-            self.registers.frame_interrupt_pending = true;
+    fn end_line(&mut self, line_end_cycles: u64) -> VdpTickEffect {
+        self.reload_line_counter();
 
-            self.fill_vertical_border();
+        let scanlines_per_frame = match self.registers.version.timing_mode() {
+            TimingMode::Ntsc => NTSC_SCANLINES_PER_FRAME,
+            TimingMode::Pal => PAL_SCANLINES_PER_FRAME,
+        };
+        self.scanline += 1;
+        if self.scanline == scanlines_per_frame {
+            self.scanline = 0;
         }
 
-        let tick_effect =
-            if vblank_start { VdpTickEffect::FrameComplete } else { VdpTickEffect::None };
+        schedule_line_events(&mut self.events, line_end_cycles);
 
-        self.dot += 1;
-        if self.dot == DOTS_PER_SCANLINE {
-            self.scanline += 1;
-            self.dot = 0;
+        if log::log_enabled!(log::Level::Trace) && self.scanline == 0 {
+            self.debug_log();
+        }
 
-            let scanlines_per_frame = match self.registers.version.timing_mode() {
-                TimingMode::Ntsc => NTSC_SCANLINES_PER_FRAME,
-                TimingMode::Pal => PAL_SCANLINES_PER_FRAME,
-            };
-            if self.scanline == scanlines_per_frame {
-                self.scanline = 0;
-            }
+        let active_scanlines = self.registers.mode.active_scanlines();
+        if self.registers.display_enabled && self.scanline < active_scanlines {
+            self.render_scanline();
         }
 
-        tick_effect
+        if self.scanline == active_scanlines + 1 {
+            self.registers.frame_interrupt_pending = true;
+            self.fill_vertical_border();
+            return VdpTickEffect::FrameComplete;
+        }
+
+        VdpTickEffect::None
     }
 
     fn fill_vertical_border(&mut self) {
@@ -982,7 +1039,7 @@ impl Vdp {
             Mode::Four | Mode::Four224Line => {
                 self.read_color_ram_word(0x10 | self.registers.backdrop_color)
             }
-            Mode::GraphicsII => {
+            Mode::GraphicsI | Mode::GraphicsII => {
                 tms9918::TMS9918_COLOR_TO_SMS_COLOR[self.registers.backdrop_color as usize].into()
             }
         };
@@ -1007,6 +1064,7 @@ impl Vdp {
         }
     }
 
+    #[must_use]
     pub fn frame_buffer(&self) -> &VdpBuffer {
         &self.frame_buffer
     }
@@ -1027,16 +1085,17 @@ impl Vdp {
         self.registers.write_data(value, &mut self.vram, &mut self.color_ram);
     }
 
+    #[must_use]
     pub fn v_counter(&self) -> u8 {
         match (self.registers.version.timing_mode(), self.registers.mode) {
-            (TimingMode::Ntsc, Mode::Four | Mode::GraphicsII) => {
+            (TimingMode::Ntsc, Mode::Four | Mode::GraphicsI | Mode::GraphicsII) => {
                 if self.scanline <= 0xDA {
                     self.scanline as u8
                 } else {
                     (self.scanline - 6) as u8
                 }
             }
-            (TimingMode::Pal, Mode::Four | Mode::GraphicsII) => {
+            (TimingMode::Pal, Mode::Four | Mode::GraphicsI | Mode::GraphicsII) => {
                 if self.scanline <= 0xF2 {
                     self.scanline as u8
                 } else {
@@ -1062,6 +1121,7 @@ impl Vdp {
         }
     }
 
+    #[must_use]
     pub fn interrupt_line(&self) -> InterruptLine {
         if (self.registers.frame_interrupt_enabled && self.registers.frame_interrupt_pending)
             || (self.registers.line_interrupt_enabled && self.registers.line_interrupt_pending)
@@ -1072,6 +1132,7 @@ impl Vdp {
         }
     }
 
+    #[must_use]
     pub fn timing_mode(&self) -> TimingMode {
         self.registers.version.timing_mode()
     }
@@ -1091,6 +1152,7 @@ fn get_color_id(tile: &[u8], tile_row: u16, tile_col: u16, horizontal_flip: bool
         | (((tile[(4 * tile_row + 3) as usize] & mask) >> shift) << 3)
 }
 
+#[must_use]
 pub fn convert_sms_color(color: u16) -> u8 {
     [0, 85, 170, 255][color as usize]
 }
@@ -1103,6 +1165,7 @@ pub fn sms_color_to_rgb(color: u16) -> Color {
     Color::rgb(r, g, b)
 }
 
+#[must_use]
 pub fn convert_gg_color(color: u16) -> u8 {
     [0, 17, 34, 51, 68, 85, 102, 119, 136, 153, 170, 187, 204, 221, 238, 255][color as usize]
 }
@@ -1114,3 +1177,68 @@ pub fn gg_color_to_rgb(color: u16) -> Color {
     let b = convert_gg_color((color >> 8) & 0x0F);
     Color::rgb(r, g, b)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOTS_PER_FRAME: u64 = DOTS_PER_SCANLINE as u64 * NTSC_SCANLINES_PER_FRAME as u64;
+
+    fn line_interrupt_vdp() -> Vdp {
+        let mut vdp = Vdp::new(VdpVersion::NtscMasterSystem2, false);
+
+        // Mode 4 with line interrupts enabled, and a line interrupt every 3 lines
+        for (register, value) in [(0, 0x14), (10, 2)] {
+            vdp.write_control(value);
+            vdp.write_control(0x80 | register);
+        }
+
+        vdp
+    }
+
+    #[test]
+    fn line_interrupt_fires_during_hblank() {
+        let mut vdp = line_interrupt_vdp();
+
+        // Run through one full frame so that the line counter is reloaded during VBlank
+        assert_eq!(vdp.tick(DOTS_PER_FRAME), VdpTickEffect::FrameComplete);
+        vdp.read_control();
+
+        // Counter goes 2 -> 1 on line 0, 1 -> 0 on line 1, and underflows on line 2
+        assert_eq!(vdp.tick(2 * u64::from(DOTS_PER_SCANLINE) + 318), VdpTickEffect::None);
+        assert_eq!(vdp.interrupt_line(), InterruptLine::High);
+        assert_eq!(vdp.v_counter(), 2);
+
+        assert_eq!(vdp.tick(1), VdpTickEffect::None);
+        assert_eq!(vdp.interrupt_line(), InterruptLine::Low);
+    }
+
+    #[test]
+    fn catch_up_matches_single_dot_ticks() {
+        let mut single = line_interrupt_vdp();
+        let mut batched = line_interrupt_vdp();
+
+        let mut single_frames = 0;
+        let mut batched_frames = 0;
+        for _ in 0..2 * DOTS_PER_FRAME / 23 {
+            for _ in 0..23 {
+                if single.tick(1) == VdpTickEffect::FrameComplete {
+                    single_frames += 1;
+                }
+            }
+            if batched.tick(23) == VdpTickEffect::FrameComplete {
+                batched_frames += 1;
+            }
+
+            assert_eq!(single.v_counter(), batched.v_counter());
+            assert_eq!(single.interrupt_line(), batched.interrupt_line());
+            if single.interrupt_line() == InterruptLine::Low {
+                single.read_control();
+                batched.read_control();
+            }
+        }
+
+        assert_eq!(single_frames, 2);
+        assert_eq!(batched_frames, 2);
+    }
+}
@@ -1,6 +1,8 @@
 use crate::vdp::{convert_gg_color, convert_sms_color, get_color_id, Vdp, VRAM_SIZE};
 
+use jgenesis_common::debug::graphics;
 use jgenesis_common::frontend::Color;
+use std::array;
 
 impl Vdp {
     pub fn copy_cram(&self, out: &mut [Color]) {
@@ -43,6 +45,53 @@ impl Vdp {
             }
         }
     }
+
+    /// Inverse of [`Self::copy_vram`]: convert an image of VRAM tiles back into tile data, mapping
+    /// each pixel to the closest color in the given palette.
+    ///
+    /// Pixels that match the color currently displayed at their position keep their current color
+    /// ID, so that tiles using duplicate palette colors are unchanged by an export/import round
+    /// trip. Pixels past the end of `pixels` are left unchanged.
+    pub fn import_vram(&mut self, pixels: &[Color], palette: u8, row_len: usize) {
+        let palette_colors: [Color; 16] = array::from_fn(|color_id| {
+            let color = self.read_color_ram_word((palette << 4) | color_id as u8);
+            if self.registers.version.is_master_system() {
+                sms_color_to_rgb(color as u8)
+            } else {
+                gg_color_to_rgb(color)
+            }
+        });
+
+        for pattern in 0..VRAM_SIZE / 32 {
+            let base_idx = pattern / row_len * row_len * 64 + (pattern % row_len) * 8;
+
+            for row in 0..8 {
+                for col in 0..8 {
+                    let Some(&pixel) = pixels.get(base_idx + row * row_len * 8 + col) else {
+                        return;
+                    };
+
+                    let tile = &mut self.vram[32 * pattern..32 * (pattern + 1)];
+                    let current_color_id = get_color_id(tile, row as u16, col as u16, false);
+                    if palette_colors[current_color_id as usize] == pixel {
+                        continue;
+                    }
+
+                    // Tiles are stored as 4 interleaved bitplanes per row, leftmost pixel in bit 7
+                    let color_id = graphics::closest_color_index(&palette_colors, pixel);
+                    let mask = 1 << (7 - col);
+                    for plane in 0..4 {
+                        let byte = &mut tile[4 * row + plane];
+                        if color_id & (1 << plane) != 0 {
+                            *byte |= mask;
+                        } else {
+                            *byte &= !mask;
+                        }
+                    }
+                }
+            }
+        }
+    }
 }
 
 fn sms_color_to_rgb(cram_byte: u8) -> Color {
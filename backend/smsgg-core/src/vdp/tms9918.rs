@@ -1,9 +1,10 @@
 use crate::vdp;
-use crate::vdp::Vdp;
+use crate::vdp::{Mode, Vdp};
 use arrayvec::ArrayVec;
 use jgenesis_common::num::GetBit;
 
 const MAX_SPRITES_PER_LINE: usize = 4;
+const SPRITE_TABLE_LEN: usize = 32;
 
 // From https://www.smspower.org/forums/8224-TMS9918ColorsForSMSVDP
 pub const TMS9918_COLOR_TO_SMS_COLOR: &[u8; 16] = &[
@@ -35,28 +36,16 @@ struct Graphics2SpriteData {
 }
 
 impl Vdp {
-    pub(super) fn render_graphics_2_scanline(&mut self) {
+    pub(super) fn render_tms9918_scanline(&mut self) {
         let scanline = self.scanline;
         let frame_buffer_row = self.frame_buffer_row();
         let backdrop_color = TMS9918_COLOR_TO_SMS_COLOR[self.registers.backdrop_color as usize];
 
         let base_name_table_addr = self.registers.base_name_table_address;
-        let base_color_table_addr = self.registers.color_table_address & 0x2000;
-        let base_pattern_generator = self.registers.pattern_generator_address & 0x2000;
 
         let nametable_row = scanline / 8;
         let line_name_table_addr = base_name_table_addr | (nametable_row * 32);
 
-        // Pattern generator and color table are split into 3 blocks of 2048 bytes each: one for the
-        // first 8 rows, one for the middle 8 rows, and one for the last 8 rows
-        let table_offset = if nametable_row >= 16 {
-            4096
-        } else if nametable_row >= 8 {
-            2048
-        } else {
-            0
-        };
-
         let tile_row = scanline % 8;
 
         let large_sprites = self.registers.double_sprite_height;
@@ -69,12 +58,9 @@ impl Vdp {
         for nametable_col in 0..vdp::SCREEN_WIDTH / 8 {
             let name_table_entry = self.vram[(line_name_table_addr | nametable_col) as usize];
 
-            let pattern_generator_addr =
-                base_pattern_generator + table_offset + 8 * u16::from(name_table_entry) + tile_row;
+            let (pattern_generator_addr, color_table_addr) =
+                self.tms9918_pattern_addresses(nametable_row, name_table_entry, tile_row);
             let pattern_generator_entry = self.vram[pattern_generator_addr as usize];
-
-            let color_table_addr =
-                base_color_table_addr + table_offset + 8 * u16::from(name_table_entry) + tile_row;
             let color_table_entry = self.vram[color_table_addr as usize];
             let bg_color_0 = color_table_entry & 0x0F;
             let bg_color_1 = color_table_entry >> 4;
@@ -106,15 +92,55 @@ impl Vdp {
         }
     }
 
+    // Returns the pattern generator and color table addresses for the given tile row
+    fn tms9918_pattern_addresses(
+        &self,
+        nametable_row: u16,
+        name_table_entry: u8,
+        tile_row: u16,
+    ) -> (u16, u16) {
+        let pattern_offset = 8 * u16::from(name_table_entry) + tile_row;
+
+        match self.registers.mode {
+            Mode::GraphicsII => {
+                let base_color_table_addr = self.registers.color_table_address & 0x2000;
+                let base_pattern_generator = self.registers.pattern_generator_address & 0x2000;
+
+                // Pattern generator and color table are split into 3 blocks of 2048 bytes each: one
+                // for the first 8 rows, one for the middle 8 rows, and one for the last 8 rows
+                let table_offset = if nametable_row >= 16 {
+                    4096
+                } else if nametable_row >= 8 {
+                    2048
+                } else {
+                    0
+                };
+
+                (
+                    base_pattern_generator + table_offset + pattern_offset,
+                    base_color_table_addr + table_offset + pattern_offset,
+                )
+            }
+            _ => {
+                // Graphics I: a single 256-pattern table, with one color table byte shared by each
+                // group of 8 consecutive patterns
+                (
+                    self.registers.pattern_generator_address + pattern_offset,
+                    self.registers.color_table_address + u16::from(name_table_entry >> 3),
+                )
+            }
+        }
+    }
+
     fn find_sprites_on_line(
         &mut self,
         sprite_size: u8,
-    ) -> ArrayVec<Graphics2SpriteData, MAX_SPRITES_PER_LINE> {
+    ) -> ArrayVec<Graphics2SpriteData, SPRITE_TABLE_LEN> {
         let scanline = self.scanline as u8;
         let base_sprite_table_addr = self.registers.base_sprite_table_address;
 
-        let mut sprite_buffer = ArrayVec::<Graphics2SpriteData, 4>::new();
-        for sprite_idx in 0..32 {
+        let mut sprite_buffer = ArrayVec::<Graphics2SpriteData, SPRITE_TABLE_LEN>::new();
+        for sprite_idx in 0..SPRITE_TABLE_LEN as u16 {
             // Add 1 because sprites with Y=0 should display starting on line 1
             let sprite_table_addr = base_sprite_table_addr + 4 * sprite_idx;
             let y = self.vram[sprite_table_addr as usize].wrapping_add(1);
@@ -132,9 +158,11 @@ impl Vdp {
                 continue;
             }
 
-            if sprite_buffer.len() == sprite_buffer.capacity() {
+            if sprite_buffer.len() == MAX_SPRITES_PER_LINE {
                 self.registers.sprite_overflow = true;
-                break;
+                if !self.remove_sprite_limit {
+                    break;
+                }
             }
 
             let x = self.vram[(sprite_table_addr + 1) as usize];
@@ -12,6 +12,7 @@ use crate::common;
 use bincode::{Decode, Encode};
 use jgenesis_common::frontend::TimingMode;
 use jgenesis_common::num::{GetBit, U16Ext};
+use jgenesis_scheduler::ClockRatio;
 
 pub const ST01X_RAM_LEN_BYTES: usize = Upd77c25Variant::St011.ram_len_words() << 1;
 
@@ -249,9 +250,7 @@ pub struct Upd77c25 {
     dp_mask: u16,
     rp_mask: u16,
     variant: Upd77c25Variant,
-    clock_speed: u64,
-    snes_mclk_speed: u64,
-    master_cycles_product: u64,
+    clock: ClockRatio,
 }
 
 impl Upd77c25 {
@@ -281,9 +280,7 @@ impl Upd77c25 {
             dp_mask: (variant.ram_len_words() - 1) as u16,
             rp_mask: (variant.data_rom_len_words() - 1) as u16,
             variant,
-            clock_speed: variant.clock_speed(),
-            snes_mclk_speed,
-            master_cycles_product: 0,
+            clock: ClockRatio::new(variant.clock_speed(), snes_mclk_speed),
         }
     }
 
@@ -344,10 +341,8 @@ impl Upd77c25 {
             return;
         }
 
-        self.master_cycles_product += master_cycles_elapsed * self.clock_speed;
-        while self.master_cycles_product >= self.snes_mclk_speed {
+        for _ in 0..self.clock.tick(master_cycles_elapsed) {
             instructions::execute(self);
-            self.master_cycles_product -= self.snes_mclk_speed;
         }
     }
 
@@ -7,19 +7,26 @@ use crate::input::SnesInputs;
 use crate::memory::dma::{DmaStatus, DmaUnit};
 use crate::memory::{CpuInternalRegisters, Memory};
 use crate::ppu::{Ppu, PpuTickEffect};
+use crate::spc::SpcFile;
 use bincode::error::EncodeError;
 use bincode::{Decode, Encode};
 use crc::Crc;
+use jgenesis_common::audio::ResamplerQuality;
+use jgenesis_common::debug::{CpuArchitecture, Debuggable, MemoryAccess, MemoryAccessLog};
 use jgenesis_common::frontend::{
     AudioOutput, Color, EmulatorTrait, FrameSize, PartialClone, PixelAspectRatio, Renderer,
     SaveWriter, TickEffect, TimingMode,
 };
-use jgenesis_proc_macros::{EnumDisplay, EnumFromStr, FakeDecode, FakeEncode};
+use jgenesis_common::rng::{InitialRamState, Rng};
+use jgenesis_common::state::StateFormat;
+use jgenesis_proc_macros::{ConfigDisplay, EnumDisplay, EnumFromStr, FakeDecode, FakeEncode};
 use std::fmt::{Debug, Display};
 use std::num::NonZeroU64;
+use std::ops::RangeInclusive;
 use std::{io, mem};
 use thiserror::Error;
 use wdc65816_emu::core::Wdc65816;
+use wdc65816_emu::traits::LoggingBus;
 
 const MEMORY_REFRESH_MCLK: u64 = 536;
 const MEMORY_REFRESH_CYCLES: u64 = 40;
@@ -37,7 +44,7 @@ pub enum SnesAspectRatio {
 }
 
 impl SnesAspectRatio {
-    fn to_pixel_aspect_ratio(self, frame_size: FrameSize) -> Option<PixelAspectRatio> {
+    pub(crate) fn to_pixel_aspect_ratio(self, frame_size: FrameSize) -> Option<PixelAspectRatio> {
         let mut pixel_aspect_ratio = match self {
             Self::Ntsc => 8.0 / 7.0,
             Self::Pal => 11.0 / 8.0,
@@ -56,12 +63,51 @@ impl SnesAspectRatio {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode, EnumDisplay, EnumFromStr)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Mode7Scale {
+    #[default]
+    Native,
+    X2,
+    X4,
+}
+
+impl Mode7Scale {
+    pub(crate) fn factor(self) -> u16 {
+        match self {
+            Self::Native => 1,
+            Self::X2 => 2,
+            Self::X4 => 4,
+        }
+    }
+}
+
+/// Options that improve image quality by departing from how the hardware renders. All are
+/// disabled by default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Encode, Decode, ConfigDisplay)]
+pub struct SnesEnhancements {
+    /// Render Mode 7 layers at 2x or 4x resolution ("HD Mode 7"). While enabled, every frame is
+    /// output at the higher resolution and all other layers are scaled up without filtering.
+    pub mode_7_scale: Mode7Scale,
+    /// When rendering Mode 7 at a higher resolution, interpolate the transformation between
+    /// consecutive lines so that perspective effects (which games implement by changing the matrix
+    /// every line) are smooth instead of stepping once per original line
+    pub mode_7_perspective_correction: bool,
+}
+
 #[derive(Debug, Clone, Copy, Encode, Decode)]
 pub struct SnesEmulatorConfig {
     pub forced_timing_mode: Option<TimingMode>,
     pub aspect_ratio: SnesAspectRatio,
     pub audio_60hz_hack: bool,
     pub gsu_overclock_factor: NonZeroU64,
+    pub audio_resampler_quality: ResamplerQuality,
+    /// Initial contents of work RAM; if None, RAM is zeroed
+    pub initial_ram_state: Option<InitialRamState>,
+    /// Seed for all in-core randomness, including random initial RAM contents; if None, a seed is
+    /// chosen randomly at power on
+    pub rng_seed: Option<u64>,
+    pub enhancements: SnesEnhancements,
 }
 
 pub type CoprocessorRomFn = dyn Fn() -> Result<Vec<u8>, (io::Error, String)>;
@@ -84,13 +130,11 @@ impl CoprocessorRoms {
 }
 
 #[derive(Debug, Error)]
-pub enum SnesError<RErr, AErr, SErr> {
+pub enum SnesError<RErr, AErr> {
     #[error("Error rendering frame: {0}")]
     Render(RErr),
     #[error("Error outputting audio samples: {0}")]
     AudioOutput(AErr),
-    #[error("Error persisting save file: {0}")]
-    SaveWrite(SErr),
     #[error("Error encoding save file bytes: {0}")]
     SaveEncode(#[from] EncodeError),
 }
@@ -147,10 +191,13 @@ pub struct SnesEmulator {
     aspect_ratio: SnesAspectRatio,
     frame_count: u64,
     last_sram_checksum: u32,
+    sram_dirty: bool,
     // Following fields only stored here to enable hard reset
     #[partial_clone(default)]
     coprocessor_roms: CoprocessorRoms,
     emulator_config: SnesEmulatorConfig,
+    #[partial_clone(default)]
+    memory_access_log: MemoryAccessLog,
 }
 
 impl SnesEmulator {
@@ -178,9 +225,15 @@ impl SnesEmulator {
             save_writer,
         )?;
 
+        let mut rng = Rng::from_optional_seed(config.rng_seed);
+        memory.initialize_main_ram(
+            config.initial_ram_state.unwrap_or(InitialRamState::AllZeroes),
+            &mut rng,
+        );
+
         let timing_mode =
             config.forced_timing_mode.unwrap_or_else(|| memory.cartridge_timing_mode());
-        let ppu = Ppu::new(timing_mode);
+        let ppu = Ppu::new(timing_mode, config.enhancements);
         let apu = Apu::new(timing_mode, config.audio_60hz_hack);
 
         log::info!("Running with timing/display mode {timing_mode}");
@@ -192,15 +245,17 @@ impl SnesEmulator {
             memory,
             ppu,
             apu,
-            audio_downsampler: AudioResampler::new(),
+            audio_downsampler: AudioResampler::new(config.audio_resampler_quality),
             total_master_cycles: 0,
             memory_refresh_pending: false,
             timing_mode,
             aspect_ratio: config.aspect_ratio,
             frame_count: 0,
             last_sram_checksum: sram_checksum,
+            sram_dirty: false,
             coprocessor_roms,
             emulator_config: config,
+            memory_access_log: MemoryAccessLog::new(),
         };
 
         // Reset CPU so that execution starts from the right place
@@ -214,6 +269,14 @@ impl SnesEmulator {
         self.memory.cartridge_title()
     }
 
+    /// Capture the current state of the APU as an SPC file, e.g. to rip the music that is
+    /// currently playing.
+    #[must_use]
+    pub fn save_spc(&mut self) -> SpcFile {
+        let game_title = self.cartridge_title();
+        self.apu.save_spc().with_game_title(game_title)
+    }
+
     #[inline]
     #[must_use]
     pub fn has_sram(&self) -> bool {
@@ -241,31 +304,96 @@ impl SnesEmulator {
     }
 }
 
+impl Debuggable for SnesEmulator {
+    fn cpu_architecture(&self) -> CpuArchitecture {
+        CpuArchitecture::Wdc65816
+    }
+
+    fn cpu_registers(&self) -> Vec<u32> {
+        let registers = self.main_cpu.registers();
+        vec![
+            registers.a.into(),
+            registers.x.into(),
+            registers.y.into(),
+            registers.s.into(),
+            registers.d.into(),
+            registers.dbr.into(),
+            u8::from(registers.p).into(),
+            self.cpu_pc(),
+        ]
+    }
+
+    fn cpu_pc(&self) -> u32 {
+        let registers = self.main_cpu.registers();
+        (u32::from(registers.pbr) << 16) | u32::from(registers.pc)
+    }
+
+    fn at_instruction_boundary(&self) -> bool {
+        !self.main_cpu.is_mid_instruction()
+    }
+
+    fn peek_memory(&self, address: u32) -> u8 {
+        let bank = (address >> 16) & 0xFF;
+        let offset = address & 0xFFFF;
+        match (bank, offset) {
+            (0x00..=0x3F | 0x80..=0xBF, 0x0000..=0x1FFF) => self.memory.read_wram(offset),
+            (0x00..=0x3F | 0x80..=0xBF, 0x2000..=0x5FFF) => 0,
+            (0x7E..=0x7F, _) => self.memory.read_wram(address & 0x1FFFF),
+            _ => self.memory.peek_cartridge(address).unwrap_or(0),
+        }
+    }
+
+    fn poke_memory(&mut self, address: u32, value: u8) {
+        let bank = (address >> 16) & 0xFF;
+        let offset = address & 0xFFFF;
+        match (bank, offset) {
+            (0x00..=0x3F | 0x80..=0xBF, 0x0000..=0x1FFF) => {
+                self.memory.write_wram(offset, value);
+            }
+            (0x7E..=0x7F, _) => {
+                self.memory.write_wram(address & 0x1FFFF, value);
+            }
+            _ => {}
+        }
+    }
+
+    fn set_memory_access_logging(&mut self, enabled: bool) {
+        self.memory_access_log.set_enabled(enabled);
+    }
+
+    fn memory_accesses(&self) -> &[MemoryAccess] {
+        self.memory_access_log.accesses()
+    }
+
+    fn work_ram_range(&self) -> RangeInclusive<u32> {
+        0x7E0000..=0x7FFFFF
+    }
+}
+
 impl EmulatorTrait for SnesEmulator {
     type Inputs = SnesInputs;
     type Config = SnesEmulatorConfig;
+    const STATE_FORMAT: StateFormat = StateFormat { core: "snes", version: 1 };
 
     type Err<
         RErr: Debug + Display + Send + Sync + 'static,
         AErr: Debug + Display + Send + Sync + 'static,
-        SErr: Debug + Display + Send + Sync + 'static,
-    > = SnesError<RErr, AErr, SErr>;
+    > = SnesError<RErr, AErr>;
 
-    fn tick<R, A, S>(
+    fn tick<R, A>(
         &mut self,
         renderer: &mut R,
         audio_output: &mut A,
         inputs: &Self::Inputs,
-        save_writer: &mut S,
-    ) -> Result<TickEffect, Self::Err<R::Err, A::Err, S::Err>>
+    ) -> Result<TickEffect, Self::Err<R::Err, A::Err>>
     where
         R: Renderer,
         R::Err: Debug + Display + Send + Sync + 'static,
         A: AudioOutput,
         A::Err: Debug + Display + Send + Sync + 'static,
-        S: SaveWriter,
-        S::Err: Debug + Display + Send + Sync + 'static,
     {
+        self.memory_access_log.clear();
+
         let master_cycles_elapsed = if self.memory_refresh_pending {
             // The CPU (including DMA) halts for 40 cycles partway through every scanline so that
             // the system can refresh DRAM (used for work RAM)
@@ -277,7 +405,12 @@ impl EmulatorTrait for SnesEmulator {
             match self.dma_unit.tick(&mut bus, self.total_master_cycles) {
                 DmaStatus::None => {
                     // DMA not in progress, tick CPU
-                    self.main_cpu.tick(&mut bus);
+                    if self.memory_access_log.is_enabled() {
+                        self.main_cpu
+                            .tick(&mut LoggingBus::new(&mut bus, &mut self.memory_access_log));
+                    } else {
+                        self.main_cpu.tick(&mut bus);
+                    }
                     bus.access_master_cycles
                 }
                 DmaStatus::InProgress { master_cycles_elapsed } => master_cycles_elapsed,
@@ -305,18 +438,14 @@ impl EmulatorTrait for SnesEmulator {
 
             self.audio_downsampler.output_samples(audio_output).map_err(SnesError::AudioOutput)?;
 
-            // Only persist SRAM if it's changed since the last write, and only check ~twice per
-            // second because of the checksum calculation
+            // SRAM writes are not tracked, so detect changes by checksumming SRAM; only check
+            // ~twice per second because of the checksum calculation
             if self.memory.has_battery_backed_sram() {
                 if let Some(sram) = self.memory.sram() {
                     if self.frame_count % 30 == 0 {
                         let checksum = CRC.checksum(sram);
                         if checksum != self.last_sram_checksum {
-                            save_writer.persist_bytes("sav", sram).map_err(SnesError::SaveWrite)?;
-                            self.memory
-                                .write_auxiliary_save_files(save_writer)
-                                .map_err(SnesError::SaveWrite)?;
-
+                            self.sram_dirty = true;
                             self.last_sram_checksum = checksum;
                         }
                     }
@@ -347,6 +476,24 @@ impl EmulatorTrait for SnesEmulator {
         Ok(tick_effect)
     }
 
+    fn save_dirty(&self) -> bool {
+        self.sram_dirty
+    }
+
+    fn persist_save<S: SaveWriter>(&mut self, save_writer: &mut S) -> Result<(), S::Err> {
+        if !self.sram_dirty {
+            return Ok(());
+        }
+
+        if let Some(sram) = self.memory.sram() {
+            save_writer.persist_bytes("sav", sram)?;
+        }
+        self.memory.write_auxiliary_save_files(save_writer)?;
+        self.sram_dirty = false;
+
+        Ok(())
+    }
+
     fn force_render<R>(&mut self, renderer: &mut R) -> Result<(), R::Err>
     where
         R: Renderer,
@@ -358,8 +505,10 @@ impl EmulatorTrait for SnesEmulator {
 
     fn reload_config(&mut self, config: &Self::Config) {
         self.aspect_ratio = config.aspect_ratio;
+        self.ppu.set_enhancements(config.enhancements);
         self.apu.set_audio_60hz_hack(config.audio_60hz_hack);
         self.memory.update_gsu_overclock_factor(config.gsu_overclock_factor);
+        self.audio_downsampler.set_quality(config.audio_resampler_quality);
 
         self.emulator_config = *config;
     }
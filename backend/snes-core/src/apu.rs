@@ -9,11 +9,13 @@ mod timer;
 use crate::apu::dsp::AudioDsp;
 use crate::apu::timer::{FastTimer, SlowTimer};
 use crate::constants;
+use crate::spc::{SpcCpuRegisters, SpcFile};
 use bincode::{Decode, Encode};
 use jgenesis_common::frontend::TimingMode;
 use jgenesis_common::num::GetBit;
+use jgenesis_scheduler::ClockRatio;
 use spc700_emu::traits::BusInterface;
-use spc700_emu::Spc700;
+use spc700_emu::{Registers as Spc700Registers, Spc700};
 
 const AUDIO_RAM_LEN: usize = 64 * 1024;
 
@@ -27,6 +29,14 @@ const SAMPLE_DIVIDER: u8 = 32;
 
 type AudioRam = [u8; AUDIO_RAM_LEN];
 
+fn apu_master_clock_frequency(audio_60hz_hack: bool) -> u64 {
+    if audio_60hz_hack {
+        ADJUSTED_APU_MASTER_CLOCK_FREQUENCY
+    } else {
+        ACTUAL_APU_MASTER_CLOCK_FREQUENCY
+    }
+}
+
 #[derive(Debug, Clone, Encode, Decode)]
 struct ApuRegisters {
     boot_rom_mapped: bool,
@@ -53,6 +63,14 @@ impl ApuRegisters {
         }
     }
 
+    // Control register, without the write-only port clear bits
+    fn control(&self) -> u8 {
+        u8::from(self.timer_0.enabled())
+            | (u8::from(self.timer_1.enabled()) << 1)
+            | (u8::from(self.timer_2.enabled()) << 2)
+            | (u8::from(self.boot_rom_mapped) << 7)
+    }
+
     fn read(&mut self, register: u16, dsp: &AudioDsp) -> u8 {
         log::trace!("SPC700 register read: {register}");
 
@@ -61,13 +79,7 @@ impl ApuRegisters {
                 log::warn!("Unimplemented APU test register was read");
                 0x00
             }
-            1 => {
-                // Control register
-                u8::from(self.timer_0.enabled())
-                    | (u8::from(self.timer_1.enabled()) << 1)
-                    | (u8::from(self.timer_2.enabled()) << 2)
-                    | (u8::from(self.boot_rom_mapped) << 7)
-            }
+            1 => self.control(),
             2 => dsp.read_address(),
             3 => dsp.read_register(),
             4 => self.main_cpu_communication[0],
@@ -210,10 +222,9 @@ pub struct Apu {
     dsp: AudioDsp,
     audio_ram: Box<AudioRam>,
     registers: ApuRegisters,
-    main_master_clock_frequency: u64,
-    master_cycles_product: u64,
+    // The SPC700 is clocked once every 24 APU master clock cycles
+    spc700_clock: ClockRatio,
     sample_divider: u8,
-    enable_audio_60hz_hack: bool,
 }
 
 macro_rules! new_spc700_bus {
@@ -238,10 +249,11 @@ impl Apu {
             dsp: AudioDsp::new(),
             audio_ram: vec![0; AUDIO_RAM_LEN].into_boxed_slice().try_into().unwrap(),
             registers: ApuRegisters::new(),
-            main_master_clock_frequency,
-            master_cycles_product: 0,
+            spc700_clock: ClockRatio::new(
+                apu_master_clock_frequency(enable_audio_60hz_hack),
+                24 * main_master_clock_frequency,
+            ),
             sample_divider: SAMPLE_DIVIDER,
-            enable_audio_60hz_hack,
         };
 
         apu.spc700.reset(&mut new_spc700_bus!(apu));
@@ -251,15 +263,9 @@ impl Apu {
 
     #[must_use]
     pub fn tick(&mut self, main_master_cycles: u64) -> ApuTickEffect {
-        let apu_master_clock_frequency = if self.enable_audio_60hz_hack {
-            ADJUSTED_APU_MASTER_CLOCK_FREQUENCY
-        } else {
-            ACTUAL_APU_MASTER_CLOCK_FREQUENCY
-        };
-        self.master_cycles_product += main_master_cycles * apu_master_clock_frequency;
+        self.spc700_clock.advance(main_master_cycles);
 
-        while self.master_cycles_product >= 24 * self.main_master_clock_frequency {
-            self.master_cycles_product -= 24 * self.main_master_clock_frequency;
+        while self.spc700_clock.next_cycle() {
             self.clock();
 
             self.sample_divider -= 1;
@@ -298,7 +304,79 @@ impl Apu {
         self.dsp.reset();
     }
 
+    /// Restore SPC700, DSP, and audio RAM state from an SPC file.
+    pub fn load_spc(&mut self, spc: &SpcFile) {
+        self.audio_ram.copy_from_slice(spc.audio_ram());
+
+        // Apply the I/O registers from the $F0-$FF region of audio RAM. Writing the control register
+        // directly would clear the CPU ports, so the relevant bits are applied individually
+        let control = self.audio_ram[0xF1];
+        self.registers.timer_0.set_enabled(control.bit(0));
+        self.registers.timer_1.set_enabled(control.bit(1));
+        self.registers.timer_2.set_enabled(control.bit(2));
+        self.registers.boot_rom_mapped = control.bit(7);
+        self.registers.main_cpu_communication.copy_from_slice(&self.audio_ram[0xF4..0xF8]);
+        self.registers.auxio4 = self.audio_ram[0xF8];
+        self.registers.auxio5 = self.audio_ram[0xF9];
+        self.registers.timer_0.set_divider(self.audio_ram[0xFA]);
+        self.registers.timer_1.set_divider(self.audio_ram[0xFB]);
+        self.registers.timer_2.set_divider(self.audio_ram[0xFC]);
+
+        // Write KON last so that voices key on using the restored voice registers, and skip ENDX
+        // because any write to it clears all of the end flags
+        for (address, &value) in spc.dsp_registers().iter().enumerate() {
+            if address != 0x4C && address != 0x7C {
+                self.dsp.write_address(address as u8);
+                self.dsp.write_register(value);
+            }
+        }
+        self.dsp.write_address(0x4C);
+        self.dsp.write_register(spc.dsp_registers()[0x4C]);
+        self.dsp.write_address(self.audio_ram[0xF2]);
+
+        let registers = spc.spc700_registers();
+        self.spc700.set_registers(Spc700Registers {
+            a: registers.a,
+            x: registers.x,
+            y: registers.y,
+            sp: registers.sp,
+            pc: registers.pc,
+            psw: registers.psw.into(),
+        });
+    }
+
+    /// Capture the current APU state as an SPC file. This is the inverse of [`Self::load_spc`].
+    pub fn save_spc(&self) -> SpcFile {
+        let mut audio_ram = self.audio_ram.to_vec();
+
+        // Registers in the $F0-$FF region are stored in audio RAM; $F4-$F7 hold the values written
+        // by the main CPU, since those are what the SPC700 reads
+        audio_ram[0xF1] = self.registers.control();
+        audio_ram[0xF2] = self.dsp.read_address();
+        audio_ram[0xF3] = self.dsp.read_register();
+        audio_ram[0xF4..0xF8].copy_from_slice(&self.registers.main_cpu_communication);
+        audio_ram[0xF8] = self.registers.auxio4;
+        audio_ram[0xF9] = self.registers.auxio5;
+        audio_ram[0xFA] = self.registers.timer_0.divider();
+        audio_ram[0xFB] = self.registers.timer_1.divider();
+        audio_ram[0xFC] = self.registers.timer_2.divider();
+
+        let dsp_registers = (0..0x80).map(|address| self.dsp.read_register_at(address)).collect();
+
+        let registers = self.spc700.registers();
+        let spc700_registers = SpcCpuRegisters {
+            pc: registers.pc,
+            a: registers.a,
+            x: registers.x,
+            y: registers.y,
+            psw: registers.psw.into(),
+            sp: registers.sp,
+        };
+
+        SpcFile::new(spc700_registers, audio_ram, dsp_registers)
+    }
+
     pub fn set_audio_60hz_hack(&mut self, audio_60hz_hack: bool) {
-        self.enable_audio_60hz_hack = audio_60hz_hack;
+        self.spc700_clock.set_numerator(apu_master_clock_frequency(audio_60hz_hack));
     }
 }
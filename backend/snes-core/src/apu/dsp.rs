@@ -893,8 +893,12 @@ impl AudioDsp {
     pub fn read_register(&self) -> u8 {
         log::trace!("DSP register read: {:02X}", self.register_address);
 
+        self.read_register_at(self.register_address)
+    }
+
+    pub fn read_register_at(&self, address: u8) -> u8 {
         // Addresses $80-$FF mirror $00-$7F
-        let address = self.register_address & 0x7F;
+        let address = address & 0x7F;
 
         // High nibble of register address encodes the voice
         let voice = (address >> 4) as usize;
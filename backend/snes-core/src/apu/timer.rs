@@ -29,6 +29,11 @@ impl<const MCLK_DIVIDER: u8> Timer<MCLK_DIVIDER> {
     }
 
     fn clock(&mut self) {
+        // The shared prescaler always runs, but the counter only advances while the timer is enabled
+        if !self.enabled {
+            return;
+        }
+
         self.counter += 1;
         if self.counter >= self.timer_divider {
             self.counter = 0;
@@ -41,11 +46,13 @@ impl<const MCLK_DIVIDER: u8> Timer<MCLK_DIVIDER> {
     }
 
     pub fn set_enabled(&mut self, enabled: bool) {
-        self.enabled = enabled;
-        if !enabled {
+        // Counter and output reset only when the timer goes from disabled to enabled; rewriting the
+        // enable bit while the timer is running (e.g. to clear the CPU ports) has no effect
+        if enabled && !self.enabled {
             self.counter = 0;
             self.output = 0;
         }
+        self.enabled = enabled;
     }
 
     pub fn divider(&self) -> u8 {
@@ -65,3 +72,31 @@ impl<const MCLK_DIVIDER: u8> Timer<MCLK_DIVIDER> {
 
 pub type SlowTimer = Timer<128>;
 pub type FastTimer = Timer<16>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timer_only_counts_while_enabled() {
+        let mut timer = FastTimer::new();
+        timer.set_divider(1);
+
+        for _ in 0..16 * 4 {
+            timer.tick();
+        }
+        assert_eq!(timer.read_output(), 0);
+
+        timer.set_enabled(true);
+        for _ in 0..16 * 3 {
+            timer.tick();
+        }
+
+        // Re-enabling a running timer should not reset it
+        timer.set_enabled(true);
+        for _ in 0..16 * 2 {
+            timer.tick();
+        }
+        assert_eq!(timer.read_output(), 5);
+    }
+}
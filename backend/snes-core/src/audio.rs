@@ -1,7 +1,7 @@
 //! SNES audio resampling code
 
 use bincode::{Decode, Encode};
-use jgenesis_common::audio::SignalResampler;
+use jgenesis_common::audio::{ResamplerQuality, SignalResampler};
 use jgenesis_common::frontend::AudioOutput;
 
 const SNES_AUDIO_FREQUENCY: f64 = 32000.0;
@@ -45,8 +45,14 @@ fn new_snes_resampler() -> SnesResampler {
 }
 
 impl AudioResampler {
-    pub fn new() -> Self {
-        Self { resampler: new_snes_resampler() }
+    pub fn new(quality: ResamplerQuality) -> Self {
+        let mut resampler = new_snes_resampler();
+        resampler.set_quality(quality);
+        Self { resampler }
+    }
+
+    pub fn set_quality(&mut self, quality: ResamplerQuality) {
+        self.resampler.set_quality(quality);
     }
 
     pub fn collect_sample(&mut self, sample_l: f64, sample_r: f64) {
@@ -1,3 +1,11 @@
+//! SNES / Super Famicom emulation core
+//!
+//! Frontends should use the types re-exported at the crate root rather than the `api` and `input`
+//! modules, which may be reorganized. [`SnesEmulator::create`] requires [`CoprocessorRoms`] for
+//! cartridges that use the DSP-1..4 or ST010/ST011 coprocessors; pass [`CoprocessorRoms::none`] if they are not
+//! available. [`SnesEmulator`] implements [`Debuggable`](jgenesis_common::debug::Debuggable) for
+//! reading and writing memory.
+
 pub mod api;
 mod apu;
 mod audio;
@@ -6,3 +14,10 @@ pub(crate) mod constants;
 pub mod input;
 mod memory;
 mod ppu;
+pub mod spc;
+
+pub use api::{
+    CoprocessorRomFn, CoprocessorRoms, Mode7Scale, SnesAspectRatio, SnesEmulator,
+    SnesEmulatorConfig, SnesEnhancements, SnesError, SnesLoadError, SnesLoadResult,
+};
+pub use input::{SnesInputDevice, SnesInputs, SnesJoypadState, SuperScopeState};
@@ -12,6 +12,7 @@ use crate::ppu::Ppu;
 use bincode::{Decode, Encode};
 use jgenesis_common::frontend::{SaveWriter, TimingMode};
 use jgenesis_common::num::{GetBit, U16Ext, U24Ext};
+use jgenesis_common::rng::{InitialRamState, Rng};
 use jgenesis_proc_macros::PartialClone;
 use std::array;
 use std::num::NonZeroU64;
@@ -76,6 +77,10 @@ impl Memory {
         })
     }
 
+    pub fn initialize_main_ram(&mut self, initial_ram_state: InitialRamState, rng: &mut Rng) {
+        initial_ram_state.fill(self.main_ram.as_mut(), rng);
+    }
+
     pub fn read_cartridge(&mut self, address: u32) -> Option<u8> {
         match self.cartridge.read(address) {
             Some(value) => {
@@ -90,6 +95,10 @@ impl Memory {
         self.cartridge.write(address, value);
     }
 
+    pub fn peek_cartridge(&self, address: u32) -> Option<u8> {
+        self.cartridge.peek(address)
+    }
+
     pub fn cartridge_irq(&self) -> bool {
         self.cartridge.irq()
     }
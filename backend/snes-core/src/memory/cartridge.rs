@@ -402,6 +402,51 @@ impl Cartridge {
         }
     }
 
+    /// Read a byte without side effects, for debuggers. Returns `None` for addresses that are
+    /// unmapped or that map to coprocessor registers.
+    pub fn peek(&self, address: u32) -> Option<u8> {
+        let bank = (address >> 16) & 0xFF;
+        let offset = address & 0xFFFF;
+        let (mapped_address, rom, sram) = match self {
+            Self::LoRom { rom, sram, mask } => {
+                (lorom_map_address(address, *mask, sram.len() as u32), rom, sram)
+            }
+            Self::DspLoRom { rom, sram, mask, .. } => match (bank, offset) {
+                (0x30..=0x3F | 0xC0..=0xCF, 0x8000..=0xFFFF) => return None,
+                _ => (lorom_map_address(address, *mask, sram.len() as u32), rom, sram),
+            },
+            Self::HiRom { rom, sram, mask } => {
+                (hirom_map_address(address, *mask, sram.len() as u32), rom, sram)
+            }
+            Self::DspHiRom { rom, sram, mask, .. } => match (bank, offset) {
+                (0x00..=0x0F | 0x80..=0x8F, 0x6000..=0x7FFF) => return None,
+                _ => (hirom_map_address(address, *mask, sram.len() as u32), rom, sram),
+            },
+            Self::ExHiRom { rom, sram, srtc, .. } => match (bank, offset, srtc) {
+                (0x00..=0x3F | 0x80..=0xBF, 0x2800, Some(_)) => return None,
+                _ => (exhirom_map_address(address, rom.len() as u32, sram.len() as u32), rom, sram),
+            },
+            Self::St01x { rom, mask, .. } => match lorom_map_address(address, *mask, 0) {
+                CartridgeAddress::Rom(rom_addr) if !(0x60..=0x6F).contains(&bank) => {
+                    return Some(rom[rom_addr as usize]);
+                }
+                _ => return None,
+            },
+            Self::Cx4(..)
+            | Self::Obc1(..)
+            | Self::Sa1(..)
+            | Self::Sdd1(..)
+            | Self::Spc7110(..)
+            | Self::SuperFx(..) => return None,
+        };
+
+        match mapped_address {
+            CartridgeAddress::None => None,
+            CartridgeAddress::Rom(rom_addr) => Some(rom[rom_addr as usize]),
+            CartridgeAddress::Sram(sram_addr) => Some(sram[sram_addr as usize]),
+        }
+    }
+
     pub fn write(&mut self, address: u32, value: u8) {
         let bank = (address >> 16) & 0xFF;
         let offset = address & 0xFFFF;
@@ -4,6 +4,7 @@ mod colortable;
 mod debug;
 mod registers;
 
+use crate::api::SnesEnhancements;
 use crate::ppu::registers::{
     AccessFlipflop, BgMode, BgScreenSize, BitsPerPixel, Mode7OobBehavior, ObjPriorityMode,
     Registers, TileSize, VramIncrementMode,
@@ -22,6 +23,11 @@ const HIRES_SCREEN_WIDTH: usize = 512;
 const MAX_SCREEN_HEIGHT: usize = 478;
 const FRAME_BUFFER_LEN: usize = HIRES_SCREEN_WIDTH * MAX_SCREEN_HEIGHT;
 
+// HD Mode 7 frames are the normal 256px width and non-interlaced height times the scale factor
+const MAX_MODE_7_SCALE: usize = 4;
+const HD_FRAME_BUFFER_LEN: usize =
+    NORMAL_SCREEN_WIDTH * MAX_MODE_7_SCALE * MAX_SCREEN_HEIGHT / 2 * MAX_MODE_7_SCALE;
+
 const VRAM_LEN_WORDS: usize = 64 * 1024 / 2;
 const OAM_LEN: usize = 512 + 32;
 const CGRAM_LEN_WORDS: usize = 256;
@@ -48,10 +54,13 @@ struct State {
     last_rendered_scanline: Option<u16>,
     // Tracks if Mode 5/6 or pseudo-hi-res was enabled at any point during active display
     hi_res_frame: bool,
+    // HD Mode 7 scale factor, latched at the start of each frame so that the frame size cannot
+    // change partway through a frame; 1 if HD Mode 7 is disabled
+    mode_7_scale: u16,
 }
 
 impl State {
-    fn new() -> Self {
+    fn new(enhancements: SnesEnhancements) -> Self {
         Self {
             scanline: 0,
             scanline_master_cycles: 0,
@@ -61,11 +70,24 @@ impl State {
             ppu2_open_bus: 0,
             last_rendered_scanline: None,
             hi_res_frame: false,
+            mode_7_scale: enhancements.mode_7_scale.factor(),
         }
     }
 
+    fn is_hd_frame(&self) -> bool {
+        self.mode_7_scale > 1
+    }
+
     fn frame_screen_width(&self) -> u32 {
-        if self.hi_res_frame { HIRES_SCREEN_WIDTH as u32 } else { NORMAL_SCREEN_WIDTH as u32 }
+        if self.is_hd_frame() {
+            return NORMAL_SCREEN_WIDTH as u32 * u32::from(self.mode_7_scale);
+        }
+
+        if self.hi_res_frame {
+            HIRES_SCREEN_WIDTH as u32
+        } else {
+            NORMAL_SCREEN_WIDTH as u32
+        }
     }
 }
 
@@ -122,6 +144,40 @@ impl Default for RenderedPixel {
     }
 }
 
+// Mode 7 transformation as computed for a line: map coordinates (in 1/256 pixel units) of the
+// leftmost pixel, and the change per pixel
+#[derive(Debug, Clone, Copy, Encode, Decode)]
+struct Mode7Line {
+    scanline: u16,
+    origin_x: i32,
+    origin_y: i32,
+    step_x: i32,
+    step_y: i32,
+}
+
+#[derive(Debug, Clone, Copy, Default, Encode, Decode)]
+struct Mode7LineHistory {
+    previous: Option<Mode7Line>,
+    current: Option<Mode7Line>,
+}
+
+impl Mode7LineHistory {
+    fn record(&mut self, line: Mode7Line) {
+        if self.current.is_some_and(|current| current.scanline != line.scanline) {
+            self.previous = self.current;
+        }
+        self.current = Some(line);
+    }
+}
+
+// Position within the block of frame buffer pixels that each pixel covers in an HD Mode 7 frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Mode7Subsample {
+    row: u16,
+    col: u16,
+    from_pixel: u16,
+}
+
 #[derive(Debug, Clone, Encode, Decode)]
 struct Buffers {
     bg_pixels: [[Pixel; HIRES_SCREEN_WIDTH]; 4],
@@ -132,6 +188,7 @@ struct Buffers {
     main_screen_rendered_pixels: [RenderedPixel; NORMAL_SCREEN_WIDTH],
     sub_screen_pixels: [PriorityResolver; NORMAL_SCREEN_WIDTH],
     sub_screen_rendered_pixels: [RenderedPixel; NORMAL_SCREEN_WIDTH],
+    mode_7_lines: [Mode7LineHistory; 2],
 }
 
 impl Buffers {
@@ -145,27 +202,36 @@ impl Buffers {
             main_screen_rendered_pixels: array::from_fn(|_| RenderedPixel::default()),
             sub_screen_pixels: array::from_fn(|_| PriorityResolver::new()),
             sub_screen_rendered_pixels: array::from_fn(|_| RenderedPixel::default()),
+            mode_7_lines: [Mode7LineHistory::default(); 2],
         }
     }
 }
 
 #[derive(Debug, Clone, FakeEncode, FakeDecode)]
-struct FrameBuffer(Box<[Color; FRAME_BUFFER_LEN]>);
+struct FrameBuffer(Vec<Color>);
 
 impl FrameBuffer {
     fn new() -> Self {
         Self::default()
     }
+
+    // HD Mode 7 frames are much larger than native frames; only allocate the space (which is
+    // copied in every rewind snapshot) while HD Mode 7 is in use
+    fn ensure_hd_len(&mut self) {
+        if self.0.len() < HD_FRAME_BUFFER_LEN {
+            self.0.resize(HD_FRAME_BUFFER_LEN, Color::default());
+        }
+    }
 }
 
 impl Default for FrameBuffer {
     fn default() -> Self {
-        Self(vec![Color::default(); FRAME_BUFFER_LEN].into_boxed_slice().try_into().unwrap())
+        Self(vec![Color::default(); FRAME_BUFFER_LEN])
     }
 }
 
 impl Deref for FrameBuffer {
-    type Target = Box<[Color; FRAME_BUFFER_LEN]>;
+    type Target = Vec<Color>;
 
     fn deref(&self) -> &Self::Target {
         &self.0
@@ -396,6 +462,7 @@ pub struct Ppu {
     frame_buffer: FrameBuffer,
     sprite_buffer: Vec<SpriteData>,
     sprite_bit_set: SpriteBitSet,
+    enhancements: SnesEnhancements,
 }
 
 // PPU starts rendering pixels at H=22
@@ -405,11 +472,11 @@ const RENDER_LINE_MCLK: u64 = 88;
 const END_RENDER_LINE_MCLK: u64 = RENDER_LINE_MCLK + 256 * 4;
 
 impl Ppu {
-    pub fn new(timing_mode: TimingMode) -> Self {
+    pub fn new(timing_mode: TimingMode, enhancements: SnesEnhancements) -> Self {
         Self {
             timing_mode,
             registers: Registers::new(),
-            state: State::new(),
+            state: State::new(enhancements),
             buffers: Box::new(Buffers::new()),
             vram: vec![0; VRAM_LEN_WORDS].into_boxed_slice().try_into().unwrap(),
             oam: vec![0; OAM_LEN].into_boxed_slice().try_into().unwrap(),
@@ -417,9 +484,15 @@ impl Ppu {
             frame_buffer: FrameBuffer::new(),
             sprite_buffer: Vec::with_capacity(32),
             sprite_bit_set: SpriteBitSet::new(),
+            enhancements,
         }
     }
 
+    /// Takes effect at the start of the next frame.
+    pub fn set_enhancements(&mut self, enhancements: SnesEnhancements) {
+        self.enhancements = enhancements;
+    }
+
     #[must_use]
     pub fn tick(&mut self, master_cycles: u64) -> PpuTickEffect {
         let prev_scanline_mclks = self.state.scanline_master_cycles;
@@ -452,6 +525,8 @@ impl Ppu {
                 self.state.odd_frame = !self.state.odd_frame;
                 self.state.last_rendered_scanline = None;
                 self.state.hi_res_frame = self.registers.in_hi_res_mode();
+                self.state.mode_7_scale = self.enhancements.mode_7_scale.factor();
+                self.buffers.mode_7_lines = [Mode7LineHistory::default(); 2];
 
                 if !self.registers.forced_blanking {
                     self.registers.sprite_overflow = false;
@@ -497,11 +572,27 @@ impl Ppu {
         let scanline = self.state.scanline;
         self.state.last_rendered_scanline = Some(scanline);
 
+        if self.state.is_hd_frame() {
+            self.frame_buffer.ensure_hd_len();
+        }
+
         if self.registers.forced_blanking {
             // Forced blanking always draws black
-            let screen_width = self.state.frame_screen_width();
-            for pixel in 0..screen_width as u16 {
-                self.set_in_frame_buffer(scanline, pixel, Color::BLACK);
+            if self.state.is_hd_frame() {
+                for pixel in 0..NORMAL_SCREEN_WIDTH as u16 {
+                    self.set_in_hd_frame_buffer(
+                        scanline,
+                        pixel,
+                        HiResMode::None,
+                        None,
+                        Color::BLACK,
+                    );
+                }
+            } else {
+                let screen_width = self.state.frame_screen_width();
+                for pixel in 0..screen_width as u16 {
+                    self.set_in_frame_buffer(scanline, pixel, Color::BLACK);
+                }
             }
             return;
         }
@@ -519,17 +610,51 @@ impl Ppu {
             self.render_obj_layer_to_buffer(scanline);
         }
 
+        if self.state.is_hd_frame()
+            && self.registers.bg_mode == BgMode::Seven
+            && hi_res_mode == HiResMode::None
+        {
+            self.render_hd_mode_7_line(scanline, from_pixel);
+            return;
+        }
+
         let bg_from_pixel =
             if hi_res_mode == HiResMode::True { 2 * from_pixel } else { from_pixel };
         if hi_res_mode == HiResMode::True && self.registers.interlaced {
             self.render_bg_layers_to_buffer(2 * scanline, hi_res_mode, bg_from_pixel);
-            self.render_scanline(2 * scanline, hi_res_mode);
+            self.render_scanline(2 * scanline, hi_res_mode, None);
 
             self.render_bg_layers_to_buffer(2 * scanline + 1, hi_res_mode, bg_from_pixel);
-            self.render_scanline(2 * scanline + 1, hi_res_mode);
+            self.render_scanline(2 * scanline + 1, hi_res_mode, None);
         } else {
             self.render_bg_layers_to_buffer(scanline, hi_res_mode, bg_from_pixel);
-            self.render_scanline(scanline, hi_res_mode);
+            self.render_scanline(scanline, hi_res_mode, None);
+        }
+    }
+
+    // Render a Mode 7 line once for each pixel of the block that every pixel covers in the HD
+    // frame buffer, sampling the Mode 7 layers at the corresponding subpixel positions each time.
+    // The other layers are the same in every pass
+    fn render_hd_mode_7_line(&mut self, scanline: u16, from_pixel: u16) {
+        let bg1_enabled = self.registers.main_bg_enabled[0] || self.registers.sub_bg_enabled[0];
+        let bg2_enabled = self.bg2_enabled()
+            && (self.registers.main_bg_enabled[1] || self.registers.sub_bg_enabled[1]);
+
+        let scale = self.state.mode_7_scale;
+        for row in 0..scale {
+            for col in 0..scale {
+                let subsample = Mode7Subsample { row, col, from_pixel };
+
+                if bg1_enabled {
+                    self.render_mode_7_to_buffer(0, scanline, from_pixel, Some(subsample));
+                }
+
+                if bg2_enabled {
+                    self.render_mode_7_to_buffer(1, scanline, from_pixel, Some(subsample));
+                }
+
+                self.render_scanline(scanline, HiResMode::None, Some(subsample));
+            }
         }
     }
 
@@ -542,7 +667,7 @@ impl Ppu {
         let mode = self.registers.bg_mode;
 
         let bg1_enabled = self.registers.main_bg_enabled[0] || self.registers.sub_bg_enabled[0];
-        let bg2_enabled = mode.bg2_enabled()
+        let bg2_enabled = self.bg2_enabled()
             && (self.registers.main_bg_enabled[1] || self.registers.sub_bg_enabled[1]);
         let bg3_enabled = mode.bg3_enabled()
             && (self.registers.main_bg_enabled[2] || self.registers.sub_bg_enabled[2]);
@@ -556,13 +681,16 @@ impl Ppu {
 
         if bg1_enabled {
             match mode {
-                BgMode::Seven => self.render_mode_7_to_buffer(scanline, from_pixel),
+                BgMode::Seven => self.render_mode_7_to_buffer(0, scanline, from_pixel, None),
                 _ => self.render_bg_to_buffer(0, scanline, hi_res_mode, from_pixel),
             }
         }
 
         if bg2_enabled {
-            self.render_bg_to_buffer(1, scanline, hi_res_mode, from_pixel);
+            match mode {
+                BgMode::Seven => self.render_mode_7_to_buffer(1, scanline, from_pixel, None),
+                _ => self.render_bg_to_buffer(1, scanline, hi_res_mode, from_pixel),
+            }
         }
 
         if bg3_enabled {
@@ -679,10 +807,13 @@ impl Ppu {
         }
     }
 
-    fn render_mode_7_to_buffer(&mut self, scanline: u16, from_pixel: u16) {
-        // Mode 7 tile map is always 128x128
-        const TILE_MAP_SIZE_PIXELS: i32 = 128 * 8;
-
+    fn render_mode_7_to_buffer(
+        &mut self,
+        bg: usize,
+        scanline: u16,
+        from_pixel: u16,
+        subsample: Option<Mode7Subsample>,
+    ) {
         // Affine transformation parameters (fixed point, 1/256 pixel units)
         let m7a: i32 = (self.registers.mode_7_parameter_a as i16).into();
         let m7b: i32 = (self.registers.mode_7_parameter_b as i16).into();
@@ -701,72 +832,122 @@ impl Ppu {
 
         let oob_behavior = self.registers.mode_7_oob_behavior;
 
+        // BG1 and the EXTBG BG2 layer have separate mosaic enable bits, so each layer applies its
+        // own vertical mosaic to the line used for the transformation
+        let (base_y, _) = self.apply_mosaic(bg, scanline, 0, HiResMode::None);
+        let screen_y: i32 = (if v_flip { 255 - base_y } else { base_y }).into();
+
+        // Perform the following matrix transformation:
+        //   [ vram_x ] = [ m7a  m7b ] * [ screen_x + m7hofs - m7x ] + [ m7x ]
+        //   [ vram_y ]   [ m7c  m7d ]   [ screen_y + m7vofs - m7y ]   [ m7y ]
+        // m7a/m7b/m7c/m7d are in 1/256 pixel units, so the multiplication result is also in
+        // 1/256 pixel units, and m7x/m7y need to be converted for the addition.
+        //
+        // The hardware computes the origin once per line and then steps it by m7a/m7c per pixel.
+        // Scroll minus center is clipped to a signed 10-bit value, and the lowest 6 bits of each
+        // origin product are truncated; games that depend on these quirks (e.g. F-Zero's horizon,
+        // Terranigma's world map) show seams or jitter if the transformation is done at full
+        // precision
+        let scrolled_x = clip_mode_7_offset(h_scroll - m7x);
+        let scrolled_y = clip_mode_7_offset(v_scroll - m7y);
+
+        let origin_x = ((m7a * scrolled_x) & !63)
+            + ((m7b * scrolled_y) & !63)
+            + ((m7b * screen_y) & !63)
+            + (m7x << 8);
+        let origin_y = ((m7c * scrolled_x) & !63)
+            + ((m7d * scrolled_y) & !63)
+            + ((m7d * screen_y) & !63)
+            + (m7y << 8);
+
+        let history = &mut self.buffers.mode_7_lines[bg];
+        let previous_line = history.previous;
+        history.record(Mode7Line { scanline, origin_x, origin_y, step_x: m7a, step_y: m7c });
+
+        // In HD Mode 7, the math below is done in 1/(256 * scale) pixel units. Each pixel's block
+        // of subsamples spans from just below the previous line to this line so that perspective
+        // correction can interpolate between the two lines' transformations without rendering
+        // ahead. Mosaic layers are always sampled at the original resolution
+        let mosaic = self.registers.bg_mosaic_enabled[bg];
+        let (scale, row, col) = match subsample {
+            Some(subsample) if !mosaic => (
+                i32::from(self.state.mode_7_scale),
+                i32::from(subsample.row),
+                i32::from(subsample.col),
+            ),
+            _ => (1, 0, 0),
+        };
+        let scale_shift = scale.trailing_zeros();
+
+        let (origin_x, origin_y, step_x, step_y) = match previous_line {
+            Some(previous)
+                if scale > 1
+                    && self.enhancements.mode_7_perspective_correction
+                    && previous.scanline + 1 == scanline =>
+            {
+                let t = row + 1;
+                (
+                    previous.origin_x * scale + (origin_x - previous.origin_x) * t,
+                    previous.origin_y * scale + (origin_y - previous.origin_y) * t,
+                    previous.step_x * scale + (m7a - previous.step_x) * t,
+                    previous.step_y * scale + (m7c - previous.step_y) * t,
+                )
+            }
+            _ => {
+                let dy = if v_flip { scale - 1 - row } else { row + 1 - scale };
+                (origin_x * scale + m7b * dy, origin_y * scale + m7d * dy, m7a * scale, m7c * scale)
+            }
+        };
+
+        let dx = if h_flip { -col } else { col };
+        let subpixel_x = (step_x * dx) >> scale_shift;
+        let subpixel_y = (step_y * dx) >> scale_shift;
+
         for pixel in from_pixel..NORMAL_SCREEN_WIDTH as u16 {
-            let (base_y, mosaic_x) = self.apply_mosaic(0, scanline, pixel, HiResMode::None);
+            let (_, mosaic_x) = self.apply_mosaic(bg, scanline, pixel, HiResMode::None);
             if mosaic_x != pixel {
                 // Copy last pixel and move on
-                self.buffers.bg_pixels[0][pixel as usize] =
-                    self.buffers.bg_pixels[0][(pixel - 1) as usize];
+                self.buffers.bg_pixels[bg][pixel as usize] =
+                    self.buffers.bg_pixels[bg][(pixel - 1) as usize];
                 continue;
             }
 
             let screen_x: i32 = (if h_flip { 255 - pixel } else { pixel }).into();
-            let screen_y: i32 = (if v_flip { 255 - base_y } else { base_y }).into();
 
-            // Perform the following matrix transformation:
-            //   [ vram_x ] = [ m7a  m7b ] * [ screen_x + m7hofs - m7x ] + [ m7x ]
-            //   [ vram_y ]   [ m7c  m7d ]   [ screen_y + m7vofs - m7y ]   [ m7y ]
-            // m7a/m7b/m7c/m7d are in 1/256 pixel units, so the multiplication result is also in
-            // 1/256 pixel units, and m7x/m7y need to be converted for the addition
-            let scrolled_x = screen_x + h_scroll - m7x;
-            let scrolled_y = screen_y + v_scroll - m7y;
+            // Convert back from 1/256 pixel units to pixel units
+            let tile_map_x = (origin_x + step_x * screen_x + subpixel_x) >> (8 + scale_shift);
+            let tile_map_y = (origin_y + step_y * screen_x + subpixel_y) >> (8 + scale_shift);
 
-            let mut tile_map_x = m7a * scrolled_x + m7b * scrolled_y + (m7x << 8);
-            let mut tile_map_y = m7c * scrolled_x + m7d * scrolled_y + (m7y << 8);
+            // Mode 7 tile map is always 128x128 tiles (1024x1024 pixels)
+            let out_of_bounds = (tile_map_x | tile_map_y) & !0x3FF != 0;
 
-            // Convert back from 1/256 pixel units to pixel units
-            tile_map_x >>= 8;
-            tile_map_y >>= 8;
-
-            let mut force_tile_0 = false;
-            if tile_map_x < 0
-                || tile_map_y < 0
-                || tile_map_x >= TILE_MAP_SIZE_PIXELS
-                || tile_map_y >= TILE_MAP_SIZE_PIXELS
-            {
-                match oob_behavior {
-                    Mode7OobBehavior::Wrap => {
-                        tile_map_x &= TILE_MAP_SIZE_PIXELS - 1;
-                        tile_map_y &= TILE_MAP_SIZE_PIXELS - 1;
-                    }
-                    Mode7OobBehavior::Transparent => {
-                        self.buffers.bg_pixels[0][pixel as usize] = Pixel::TRANSPARENT;
-                        continue;
-                    }
-                    Mode7OobBehavior::Tile0 => {
-                        tile_map_x &= 0x07;
-                        tile_map_y &= 0x07;
-                        force_tile_0 = true;
-                    }
+            let tile_number = match oob_behavior {
+                Mode7OobBehavior::Transparent if out_of_bounds => {
+                    self.buffers.bg_pixels[bg][pixel as usize] = Pixel::TRANSPARENT;
+                    continue;
+                }
+                Mode7OobBehavior::Tile0 if out_of_bounds => 0,
+                _ => {
+                    // Mode 7 tile map is always located at $0000
+                    let tile_map_row = (tile_map_y >> 3) & 0x7F;
+                    let tile_map_col = (tile_map_x >> 3) & 0x7F;
+                    let tile_map_addr = tile_map_row * 128 + tile_map_col;
+                    self.vram[tile_map_addr as usize] & 0x00FF
                 }
-            }
-
-            let tile_number = if force_tile_0 {
-                0
-            } else {
-                // Mode 7 tile map is always located at $0000
-                let tile_map_row = tile_map_y / 8;
-                let tile_map_col = tile_map_x / 8;
-                let tile_map_addr = tile_map_row * TILE_MAP_SIZE_PIXELS / 8 + tile_map_col;
-                self.vram[tile_map_addr as usize] & 0x00FF
             };
 
-            let tile_row = (tile_map_y % 8) as u16;
-            let tile_col = (tile_map_x % 8) as u16;
+            let tile_row = (tile_map_y & 0x07) as u16;
+            let tile_col = (tile_map_x & 0x07) as u16;
             let pixel_addr = 64 * tile_number + 8 * tile_row + tile_col;
             let color = self.vram[pixel_addr as usize].msb();
 
-            self.buffers.bg_pixels[0][pixel as usize] = Pixel { palette: 0, color, priority: 0 };
+            self.buffers.bg_pixels[bg][pixel as usize] = if bg == 0 {
+                Pixel { palette: 0, color, priority: 0 }
+            } else {
+                // EXTBG: BG2 uses the same pixel data as BG1, but with the highest bit as a
+                // per-pixel priority bit instead of part of the color
+                Pixel { palette: 0, color: color & 0x7F, priority: color >> 7 }
+            };
         }
     }
 
@@ -785,53 +966,38 @@ impl Ppu {
         let screen_width =
             if hi_res_mode == HiResMode::True { HIRES_SCREEN_WIDTH } else { NORMAL_SCREEN_WIDTH };
 
-        let mode = self.registers.bg_mode;
-        let bg3_h_scroll = self.registers.bg_h_scroll[2];
-        let bg3_v_scroll = self.registers.bg_v_scroll[2];
+        // Offset-per-tile can only apply to BG1 and BG2
+        for bg in 0..2 {
+            let bg_h_scroll = self.registers.bg_h_scroll[bg];
+            let bg_v_scroll = self.registers.bg_v_scroll[bg];
 
-        for pixel in 0..screen_width as u16 {
-            // Lowest 3 bits of BG3 H scroll do not apply in offset-per-tile
-            let bg3_x = (pixel.wrapping_sub(8) & !0x7).wrapping_add(bg3_h_scroll & !0x7);
-
-            let (h_offset_entry, v_offset_entry) = match mode {
-                BgMode::Four => {
-                    // In Mode 4, instead of loading both map entries, the PPU uses the highest bit
-                    // of the first entry to determine whether to apply offset to H or V
-                    let bg3_entry =
-                        get_bg_map_entry(&self.vram, &self.registers, 2, bg3_x, bg3_v_scroll);
-                    if bg3_entry.bit(15) {
-                        // Apply to V scroll
-                        (0, bg3_entry)
-                    } else {
-                        // Apply to H scroll
-                        (bg3_entry, 0)
-                    }
-                }
-                _ => {
-                    let h_offset_entry =
-                        get_bg_map_entry(&self.vram, &self.registers, 2, bg3_x, bg3_v_scroll);
-                    let v_offset_entry =
-                        get_bg_map_entry(&self.vram, &self.registers, 2, bg3_x, bg3_v_scroll + 8);
-                    (h_offset_entry, v_offset_entry)
-                }
+            // BG1 uses bit 13 to determine whether to apply offset-per-tile, while BG2 uses bit 14
+            let bg_offset_bit = if bg == 0 { 13 } else { 14 };
+
+            // Offsets are fetched once per BG tile, so which BG3 column applies to a given pixel
+            // depends on the fine H scroll of the layer being offset
+            let fine_h_scroll = if hi_res_mode == HiResMode::True {
+                (2 * bg_h_scroll) & 0x07
+            } else {
+                bg_h_scroll & 0x07
             };
 
-            // Offset-per-tile can only apply to BG1 and BG2
-            for bg in 0..2 {
-                let bg_h_scroll = self.registers.bg_h_scroll[bg];
-                let bg_v_scroll = self.registers.bg_v_scroll[bg];
-                if pixel + (bg_h_scroll & 0x07) < 8 {
+            for pixel in 0..screen_width as u16 {
+                let offset_x = pixel + fine_h_scroll;
+                if offset_x < 8 {
                     // Offset-per-tile only applies to the 2nd visible tile and onwards
                     self.buffers.offset_per_tile_h_scroll[bg][pixel as usize] = bg_h_scroll;
                     self.buffers.offset_per_tile_v_scroll[bg][pixel as usize] = bg_v_scroll;
                     continue;
                 }
 
-                // BG1 uses bit 13 to determine whether to apply offset-per-tile, while BG2 uses bit 14
-                let bg_offset_bit = if bg == 0 { 13 } else { 14 };
+                let (h_offset_entry, v_offset_entry) = self.read_offset_per_tile_entries(offset_x);
+
+                // Offset entries replace only the coarse H scroll; the lowest 3 bits still come from
+                // the layer's own H scroll register
                 self.buffers.offset_per_tile_h_scroll[bg][pixel as usize] =
                     if h_offset_entry.bit(bg_offset_bit) {
-                        h_offset_entry & 0x03FF
+                        (h_offset_entry & 0x03F8) | (bg_h_scroll & 0x07)
                     } else {
                         bg_h_scroll
                     };
@@ -845,7 +1011,43 @@ impl Ppu {
         }
     }
 
-    fn render_scanline(&mut self, scanline: u16, hi_res_mode: HiResMode) {
+    fn read_offset_per_tile_entries(&self, offset_x: u16) -> (u16, u16) {
+        let bg3_h_scroll = self.registers.bg_h_scroll[2];
+        let bg3_v_scroll = self.registers.bg_v_scroll[2];
+
+        // Lowest 3 bits of BG3 H scroll do not apply in offset-per-tile
+        let bg3_x = ((offset_x - 8) & !0x7).wrapping_add(bg3_h_scroll & !0x7);
+
+        match self.registers.bg_mode {
+            BgMode::Four => {
+                // In Mode 4, instead of loading both map entries, the PPU uses the highest bit
+                // of the first entry to determine whether to apply offset to H or V
+                let bg3_entry =
+                    get_bg_map_entry(&self.vram, &self.registers, 2, bg3_x, bg3_v_scroll);
+                if bg3_entry.bit(15) {
+                    // Apply to V scroll
+                    (0, bg3_entry)
+                } else {
+                    // Apply to H scroll
+                    (bg3_entry, 0)
+                }
+            }
+            _ => {
+                let h_offset_entry =
+                    get_bg_map_entry(&self.vram, &self.registers, 2, bg3_x, bg3_v_scroll);
+                let v_offset_entry =
+                    get_bg_map_entry(&self.vram, &self.registers, 2, bg3_x, bg3_v_scroll + 8);
+                (h_offset_entry, v_offset_entry)
+            }
+        }
+    }
+
+    fn render_scanline(
+        &mut self,
+        scanline: u16,
+        hi_res_mode: HiResMode,
+        subsample: Option<Mode7Subsample>,
+    ) {
         // Main screen is always rendered
         self.render_screen_pixels(Screen::Main, hi_res_mode);
 
@@ -864,7 +1066,11 @@ impl Ppu {
         let brightness = self.registers.brightness;
         let sub_backdrop_color = self.registers.sub_backdrop_color;
 
-        for pixel in 0..screen_width as u16 {
+        // HD Mode 7 passes only hold the current subsample in the layer buffers, so pixels left of
+        // a mid-line render must not be redrawn
+        let from_pixel = subsample.map_or(0, |subsample| subsample.from_pixel);
+
+        for pixel in from_pixel..screen_width as u16 {
             // In hi-res modes, window coordinates are effectively doubled and then shifted to
             // the right by 1 pixel
             let (screen_x, window_x) = match hi_res_mode {
@@ -874,12 +1080,17 @@ impl Ppu {
                 }
             };
 
-            let mut main_screen_pixel = if hi_res_mode.is_hi_res() && !pixel.bit(0) {
-                // Even pixels draw the sub screen in hi-res mode
-                self.buffers.sub_screen_rendered_pixels[screen_x as usize]
-            } else {
-                self.buffers.main_screen_rendered_pixels[screen_x as usize]
-            };
+            let main_pixel = self.buffers.main_screen_rendered_pixels[screen_x as usize];
+            let sub_pixel = self.buffers.sub_screen_rendered_pixels[screen_x as usize];
+            let (mut main_screen_pixel, color_math_pixel, color_math_transparent) =
+                if hi_res_mode.is_hi_res() && !pixel.bit(0) {
+                    // Even pixels draw the sub screen in hi-res mode, and the two screens swap
+                    // roles for color math: the sub screen pixel is blended with the main screen.
+                    // The main screen backdrop is never treated as transparent
+                    (sub_pixel, main_pixel, false)
+                } else {
+                    (main_pixel, sub_pixel, sub_pixel.layer == Layer::Backdrop)
+                };
 
             // Check if inside the color window (used for clipping and color math)
             let in_color_window = self.registers.in_math_window(window_x);
@@ -907,10 +1118,9 @@ impl Ppu {
             };
 
             let snes_color = if color_math_enabled_global && color_math_enabled_layer {
-                // Find the frontmost sub screen pixel
+                // Find the frontmost pixel on the other screen
                 let (sub_screen_color, sub_transparent) = if self.registers.sub_bg_obj_enabled {
-                    let pixel = self.buffers.sub_screen_rendered_pixels[screen_x as usize];
-                    (pixel.color, pixel.layer == Layer::Backdrop)
+                    (color_math_pixel.color, color_math_transparent)
                 } else {
                     (sub_backdrop_color, false)
                 };
@@ -932,7 +1142,9 @@ impl Ppu {
 
             let final_color = convert_snes_color(snes_color, brightness);
 
-            if self.state.hi_res_frame && !hi_res_mode.is_hi_res() {
+            if self.state.is_hd_frame() {
+                self.set_in_hd_frame_buffer(scanline, pixel, hi_res_mode, subsample, final_color);
+            } else if self.state.hi_res_frame && !hi_res_mode.is_hi_res() {
                 // Hi-res mode is not currently enabled, but it was enabled earlier in the frame;
                 // draw in 512px
                 self.set_in_frame_buffer(scanline, 2 * pixel, final_color);
@@ -944,6 +1156,8 @@ impl Ppu {
     }
 
     fn render_screen_pixels(&mut self, screen: Screen, hi_res_mode: HiResMode) {
+        let bg2_enabled = self.bg2_enabled();
+
         let (
             screen_pixels,
             screen_rendered_pixels,
@@ -1013,29 +1227,12 @@ impl Ppu {
                 let bg1_pixel = self.buffers.bg_pixels[0][screen_x as usize];
                 if !bg1_pixel.is_transparent() {
                     priority_resolver.add_bg1(bg1_pixel, is_mode_0_or_1);
-
-                    if mode == BgMode::Seven && self.registers.extbg_enabled {
-                        // When EXTBG is enabled in Mode 7, BG1 pixels are duplicated into BG2
-                        // but use the highest color bit as priority
-                        let bg2_pixel_color = bg1_pixel.color & 0x7F;
-                        if bg2_pixel_color != 0 {
-                            let bg2_priority = bg1_pixel.color >> 7;
-                            priority_resolver.add_bg2(
-                                Pixel {
-                                    priority: bg2_priority,
-                                    color: bg2_pixel_color,
-                                    palette: bg1_pixel.palette,
-                                },
-                                is_mode_0_or_1,
-                            );
-                        }
-                    }
                 }
             }
         }
 
-        // BG2 layer (enabled in all modes except 6 and 7)
-        if mode.bg2_enabled() && bg_enabled[1] {
+        // BG2 layer (enabled in all modes except 6, and in mode 7 only with EXTBG)
+        if bg2_enabled && bg_enabled[1] {
             for (x, priority_resolver) in screen_pixels.iter_mut().enumerate() {
                 if bg_disabled_in_window[1] {
                     let window_x = x_modifiers.window_x(x as u16);
@@ -1112,6 +1309,11 @@ impl Ppu {
         }
     }
 
+    fn bg2_enabled(&self) -> bool {
+        let mode = self.registers.bg_mode;
+        mode.bg2_enabled() || (mode == BgMode::Seven && self.registers.extbg_enabled)
+    }
+
     fn apply_mosaic(
         &self,
         bg: usize,
@@ -1322,7 +1524,8 @@ impl Ppu {
     }
 
     fn enter_hi_res_mode(&mut self) {
-        if !self.vblank_flag() && !self.state.hi_res_frame {
+        // HD Mode 7 frames are always wide enough for hi-res lines
+        if !self.vblank_flag() && !self.state.hi_res_frame && !self.state.is_hd_frame() {
             // Hi-res mode enabled mid-frame; redraw previously rendered scanlines to 512x224 in-place
             if let Some(last_rendered_scanline) = self.state.last_rendered_scanline {
                 for scanline in (1..=last_rendered_scanline).rev() {
@@ -1346,6 +1549,41 @@ impl Ppu {
         self.frame_buffer[index as usize] = color;
     }
 
+    // In HD Mode 7 frames, each pixel covers a block of the frame buffer: scale x scale for normal
+    // lines, half as wide for hi-res lines, and half as tall for interlaced Mode 5/6 lines. Mode 7
+    // subsamples are written to a single pixel of their block
+    fn set_in_hd_frame_buffer(
+        &mut self,
+        scanline: u16,
+        pixel: u16,
+        hi_res_mode: HiResMode,
+        subsample: Option<Mode7Subsample>,
+        color: Color,
+    ) {
+        let scale = u32::from(self.state.mode_7_scale);
+        let frame_width = NORMAL_SCREEN_WIDTH as u32 * scale;
+
+        let block_width = if hi_res_mode.is_hi_res() { scale / 2 } else { scale };
+        let (block_height, y) = if hi_res_mode == HiResMode::True && self.registers.interlaced {
+            // Interlaced lines are numbered from 2
+            (scale / 2, u32::from(scanline - 2) * scale / 2)
+        } else {
+            (scale, u32::from(scanline - 1) * scale)
+        };
+        let x = u32::from(pixel) * block_width;
+
+        if let Some(subsample) = subsample {
+            let index = (y + u32::from(subsample.row)) * frame_width + x + u32::from(subsample.col);
+            self.frame_buffer[index as usize] = color;
+            return;
+        }
+
+        for row in y..y + block_height {
+            let start = (row * frame_width + x) as usize;
+            self.frame_buffer[start..start + block_width as usize].fill(color);
+        }
+    }
+
     fn scanlines_per_frame(&self) -> u16 {
         match self.timing_mode {
             TimingMode::Ntsc => 262,
@@ -1398,14 +1636,16 @@ impl Ppu {
     }
 
     pub fn frame_buffer(&self) -> &[Color] {
-        self.frame_buffer.as_ref()
+        &self.frame_buffer
     }
 
     pub fn frame_size(&self) -> FrameSize {
         let screen_width = self.state.frame_screen_width();
 
         let mut screen_height = self.registers.v_display_size.to_lines();
-        if self.is_v_hi_res() {
+        if self.state.is_hd_frame() {
+            screen_height *= self.state.mode_7_scale;
+        } else if self.is_v_hi_res() {
             screen_height *= 2;
         }
 
@@ -1761,6 +2001,15 @@ fn sign_extend_13_bit(value: u16) -> i32 {
     (((value as i16) << 3) >> 3).into()
 }
 
+// Clip a 13-bit signed Mode 7 offset to a sign-extended 10-bit value
+fn clip_mode_7_offset(value: i32) -> i32 {
+    if value & 0x2000 != 0 {
+        value | !0x3FF
+    } else {
+        value & 0x3FF
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 fn get_bg_tile<'vram>(
     vram: &'vram Vram,
@@ -1903,7 +2152,8 @@ fn resolve_pixel_color(
         BitsPerPixel::Two => cgram[(two_bpp_offset | (palette << 2) | color) as usize],
         BitsPerPixel::Four => cgram[(four_bpp_offset | (palette << 4) | color) as usize],
         BitsPerPixel::Eight => {
-            if direct_color_mode {
+            // Direct color only applies to BG1; the mode 7 EXTBG layer always uses CGRAM
+            if direct_color_mode && layer == Layer::Bg1 {
                 resolve_direct_color(palette, color)
             } else {
                 cgram[color as usize]
@@ -1937,6 +2187,7 @@ fn convert_snes_color(snes_color: u16, brightness: u8) -> Color {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::api::Mode7Scale;
 
     #[test]
     fn direct_color() {
@@ -1951,4 +2202,250 @@ mod tests {
 
         assert_eq!(0b11100_11110_11110, resolve_direct_color(0b111, 0b11_111_111));
     }
+
+    #[test]
+    fn offset_per_tile() {
+        let mut ppu = Ppu::new(TimingMode::Ntsc, SnesEnhancements::default());
+        ppu.registers.bg_mode = BgMode::Two;
+        ppu.registers.bg_base_address[2] = 0x1000;
+        ppu.registers.bg_h_scroll[0] = 0x0003;
+        ppu.registers.bg_h_scroll[1] = 0x0005;
+        ppu.registers.bg_v_scroll[1] = 0x0020;
+
+        // Column 0: H offset for BG1 only, V offset for BG1 and BG2
+        ppu.vram[0x1000] = 0x2000 | 0x0105;
+        ppu.vram[0x1000 + 32] = 0x6000 | 0x0040;
+        // Column 1: H offset for BG2 only
+        ppu.vram[0x1001] = 0x4000 | 0x0088;
+
+        ppu.populate_offset_per_tile_buffers(HiResMode::None);
+
+        let h_scroll = &ppu.buffers.offset_per_tile_h_scroll;
+        let v_scroll = &ppu.buffers.offset_per_tile_v_scroll;
+
+        // First visible tile is never offset
+        assert_eq!(h_scroll[0][4], 0x0003);
+        assert_eq!(v_scroll[0][4], 0x0000);
+        assert_eq!(h_scroll[1][2], 0x0005);
+
+        // Lookup column depends on each layer's fine H scroll, and fine H scroll is retained
+        assert_eq!(h_scroll[0][5], 0x0103);
+        assert_eq!(v_scroll[0][5], 0x0040);
+        assert_eq!(h_scroll[0][13], 0x0003);
+        assert_eq!(h_scroll[1][3], 0x0005);
+        assert_eq!(v_scroll[1][3], 0x0040);
+        assert_eq!(h_scroll[1][11], 0x008D);
+        assert_eq!(v_scroll[1][11], 0x0020);
+
+        // Mode 4 uses bit 15 of a single entry to select H or V
+        ppu.registers.bg_mode = BgMode::Four;
+        ppu.vram[0x1000] = 0x8000 | 0x2000 | 0x0105;
+        ppu.populate_offset_per_tile_buffers(HiResMode::None);
+
+        let h_scroll = &ppu.buffers.offset_per_tile_h_scroll;
+        let v_scroll = &ppu.buffers.offset_per_tile_v_scroll;
+        assert_eq!(h_scroll[0][5], 0x0003);
+        assert_eq!(v_scroll[0][5], 0x0105);
+    }
+
+    fn mode_7_test_ppu(enhancements: SnesEnhancements, m7a: u16) -> Ppu {
+        let mut ppu = Ppu::new(TimingMode::Ntsc, enhancements);
+        ppu.registers.forced_blanking = false;
+        ppu.registers.brightness = 15;
+        ppu.registers.bg_mode = BgMode::Seven;
+        ppu.registers.main_bg_enabled[0] = true;
+        ppu.registers.mode_7_parameter_a = m7a;
+        ppu.registers.mode_7_parameter_b = 0;
+        ppu.registers.mode_7_parameter_c = 0;
+        ppu.registers.mode_7_parameter_d = 0x0100;
+
+        // Every map entry is tile 1, which has a different color in each column
+        for addr in 0..128 * 128 {
+            ppu.vram[addr] = 0x0001;
+        }
+        for row in 0..8 {
+            for col in 0..8 {
+                ppu.vram[64 + 8 * row + col] = ((col as u16) + 1) << 8;
+            }
+        }
+        for color in 1..=8 {
+            ppu.cgram[color] = (color as u16) << 2;
+        }
+
+        ppu.state.scanline = 1;
+        ppu
+    }
+
+    #[test]
+    fn hd_mode_7_samples_between_pixels() {
+        let mut native = mode_7_test_ppu(SnesEnhancements::default(), 0x0100);
+        native.render_current_line(0);
+
+        // Zooming out by 2x at 2x scale should sample the same map pixels as native at 1x zoom
+        let enhancements =
+            SnesEnhancements { mode_7_scale: Mode7Scale::X2, ..SnesEnhancements::default() };
+        let mut hd = mode_7_test_ppu(enhancements, 0x0200);
+        hd.render_current_line(0);
+
+        assert_eq!(hd.frame_size(), FrameSize { width: 512, height: 448 });
+        for row in 0..2 {
+            for x in 0..512 {
+                assert_eq!(
+                    hd.frame_buffer[row * 512 + x],
+                    native.frame_buffer[x % 256],
+                    "{row} {x}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn mode_7_offset_clipping() {
+        assert_eq!(0x155, clip_mode_7_offset(0x155));
+        assert_eq!(0x155, clip_mode_7_offset(0x1555));
+        assert_eq!(-1, clip_mode_7_offset(sign_extend_13_bit(0x1FFF)));
+        assert_eq!(-0x400, clip_mode_7_offset(sign_extend_13_bit(0x1000)));
+        assert_eq!(-0x400, clip_mode_7_offset(-0x1000));
+    }
+
+    fn gray(intensity: u16) -> u16 {
+        intensity | (intensity << 5) | (intensity << 10)
+    }
+
+    // BG1 is opaque at every pixel on the main screen, and nothing else is enabled
+    fn window_test_ppu() -> Ppu {
+        let mut ppu = mode_7_test_ppu(SnesEnhancements::default(), 0x0100);
+        ppu.cgram[0] = gray(8);
+        for color in 1..=8 {
+            ppu.cgram[color] = gray(16);
+        }
+        ppu
+    }
+
+    fn rendered_line(ppu: &Ppu, scanline: u16) -> &[Color] {
+        let start = usize::from(scanline - 1) * NORMAL_SCREEN_WIDTH;
+        &ppu.frame_buffer[start..start + NORMAL_SCREEN_WIDTH]
+    }
+
+    #[test]
+    fn bg_window_mask_logic() {
+        // Star Fox HUD: BG1 hidden inside two overlapping windows combined with XOR
+        let mut ppu = window_test_ppu();
+        ppu.write_port(0x2126, 0x20); // WH0
+        ppu.write_port(0x2127, 0x5F); // WH1
+        ppu.write_port(0x2128, 0x40); // WH2
+        ppu.write_port(0x2129, 0x9F); // WH3
+        ppu.write_port(0x2123, 0x0A); // W12SEL: BG1 inside window 1 and window 2
+        ppu.write_port(0x212A, 0x02); // WBGLOG: BG1 XOR
+        ppu.write_port(0x212E, 0x01); // TMW: BG1 disabled in window
+        ppu.render_current_line(0);
+
+        let bg1 = convert_snes_color(gray(16), 15);
+        let backdrop = convert_snes_color(gray(8), 15);
+        for (x, &color) in rendered_line(&ppu, 1).iter().enumerate() {
+            let expected = match x {
+                0x20..=0x3F | 0x60..=0x9F => backdrop,
+                _ => bg1,
+            };
+            assert_eq!(color, expected, "{x}");
+        }
+
+        // Same windows with AND: BG1 hidden only where they overlap
+        ppu.write_port(0x212A, 0x01);
+        ppu.render_current_line(0);
+
+        for (x, &color) in rendered_line(&ppu, 1).iter().enumerate() {
+            let expected = if (0x40..=0x5F).contains(&x) { backdrop } else { bg1 };
+            assert_eq!(color, expected, "{x}");
+        }
+    }
+
+    #[test]
+    fn force_main_screen_black_outside_color_window() {
+        // Star Fox HUD: everything outside the viewport window is clipped to black, and inverting
+        // window 2 shrinks the viewport from the right
+        let mut ppu = window_test_ppu();
+        ppu.write_port(0x2126, 0x10); // WH0
+        ppu.write_port(0x2127, 0xEF); // WH1
+        ppu.write_port(0x2128, 0xC0); // WH2
+        ppu.write_port(0x2129, 0xFF); // WH3
+        ppu.write_port(0x2125, 0xE0); // WOBJSEL: color window inside window 1, outside window 2
+        ppu.write_port(0x212B, 0x04); // WOBJLOG: color window AND
+        ppu.write_port(0x2130, 0x70); // CGWSEL: force black outside color window, math never
+        ppu.render_current_line(0);
+
+        let bg1 = convert_snes_color(gray(16), 15);
+        let black = convert_snes_color(0, 15);
+        for (x, &color) in rendered_line(&ppu, 1).iter().enumerate() {
+            let expected = if (0x10..0xC0).contains(&x) { bg1 } else { black };
+            assert_eq!(color, expected, "{x}");
+        }
+    }
+
+    #[test]
+    fn fixed_color_gradient() {
+        // Chrono Trigger: COLDATA is rewritten every line to subtract a gradient from BG1 and the
+        // backdrop
+        let mut ppu = window_test_ppu();
+        ppu.write_port(0x2126, 0x80); // WH0
+        ppu.write_port(0x2127, 0xFF); // WH1
+        ppu.write_port(0x2125, 0x20); // WOBJSEL: color window inside window 1
+        ppu.write_port(0x2130, 0x10); // CGWSEL: math inside color window, sub screen fixed color
+        ppu.write_port(0x2131, 0xA1); // CGADSUB: subtract for BG1 and backdrop
+
+        for scanline in 1..=12 {
+            ppu.write_port(0x2132, 0xE0 | scanline as u8); // COLDATA: R/G/B
+            ppu.state.scanline = scanline;
+            ppu.render_current_line(0);
+        }
+
+        for scanline in 1..=12 {
+            let line = rendered_line(&ppu, scanline);
+            assert_eq!(line[0x7F], convert_snes_color(gray(16), 15), "{scanline}");
+            assert_eq!(line[0x80], convert_snes_color(gray(16 - scanline), 15), "{scanline}");
+        }
+
+        // Widening window 1 to the full line and hiding BG1 inside it leaves only the backdrop,
+        // and subtraction clamps to 0
+        ppu.write_port(0x2126, 0x00); // WH0
+        ppu.write_port(0x2123, 0x02); // W12SEL: BG1 inside window 1
+        ppu.write_port(0x212E, 0x01); // TMW: BG1 disabled in window
+        ppu.render_current_line(0);
+
+        let line = rendered_line(&ppu, 12);
+        assert_eq!(line[0x00], convert_snes_color(0, 15));
+        assert_eq!(line[0xFF], convert_snes_color(0, 15));
+    }
+
+    #[test]
+    fn color_math_divide_requires_opaque_sub_screen() {
+        // Chrono Trigger: half-add a sub screen layer that is windowed out on part of the line;
+        // where the sub screen falls through to the fixed color backdrop, the sum is not halved
+        let mut ppu = window_test_ppu();
+        ppu.write_port(0x212D, 0x01); // TS: BG1
+        ppu.write_port(0x2126, 0x00); // WH0
+        ppu.write_port(0x2127, 0x7F); // WH1
+        ppu.write_port(0x2123, 0x02); // W12SEL: BG1 inside window 1
+        ppu.write_port(0x212F, 0x01); // TSW: BG1 disabled in window
+        ppu.write_port(0x2130, 0x02); // CGWSEL: math always, sub screen BG/OBJ
+        ppu.write_port(0x2131, 0x41); // CGADSUB: half-add for BG1
+        ppu.write_port(0x2132, 0xE2); // COLDATA: R/G/B = 2
+        ppu.render_current_line(0);
+
+        let line = rendered_line(&ppu, 1);
+        assert_eq!(line[0x00], convert_snes_color(gray(18), 15));
+        assert_eq!(line[0x7F], convert_snes_color(gray(18), 15));
+        assert_eq!(line[0x80], convert_snes_color(gray(16), 15));
+        assert_eq!(line[0xFF], convert_snes_color(gray(16), 15));
+
+        // Clipped main screen pixels are never halved either
+        ppu.write_port(0x2125, 0x20); // WOBJSEL: color window inside window 1
+        ppu.write_port(0x2130, 0x82); // CGWSEL: force black inside color window
+        ppu.write_port(0x212F, 0x00); // TSW: sub screen BG1 no longer windowed
+        ppu.render_current_line(0);
+
+        let line = rendered_line(&ppu, 1);
+        assert_eq!(line[0x00], convert_snes_color(gray(16), 15));
+        assert_eq!(line[0x80], convert_snes_color(gray(16), 15));
+    }
 }
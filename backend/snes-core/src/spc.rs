@@ -0,0 +1,409 @@
+//! SPC file loading and playback
+//!
+//! SPC files are snapshots of the SNES APU state (SPC700 registers, audio RAM, and DSP registers)
+//! taken while a game's sound driver is playing music. They can be played back by running only the
+//! APU, without the rest of the system.
+
+use crate::api::{SnesAspectRatio, SnesEmulatorConfig, SnesError};
+use crate::apu::{Apu, ApuTickEffect};
+use crate::audio::AudioResampler;
+use crate::constants;
+use crate::input::SnesInputs;
+use bincode::{Decode, Encode};
+use jgenesis_common::frontend::{
+    AudioOutput, Color, EmulatorTrait, FrameSize, PartialClone, Renderer, SaveWriter, TickEffect,
+    TimingMode,
+};
+use jgenesis_common::state::StateFormat;
+use jgenesis_proc_macros::{FakeDecode, FakeEncode};
+use std::fmt::{Debug, Display};
+use std::mem;
+use thiserror::Error;
+
+const SPC_SIGNATURE: &[u8] = b"SNES-SPC700 Sound File Data";
+const SPC_FULL_SIGNATURE: &[u8] = b"SNES-SPC700 Sound File Data v0.30";
+const ID666_PRESENT: u8 = 26;
+const SPC_MINOR_VERSION: u8 = 30;
+
+const AUDIO_RAM_OFFSET: usize = 0x100;
+const AUDIO_RAM_LEN: usize = 0x10000;
+const DSP_REGISTERS_OFFSET: usize = AUDIO_RAM_OFFSET + AUDIO_RAM_LEN;
+const DSP_REGISTERS_LEN: usize = 128;
+const MIN_FILE_LEN: usize = DSP_REGISTERS_OFFSET + DSP_REGISTERS_LEN;
+const EXTRA_RAM_OFFSET: usize = 0x101C0;
+const FULL_FILE_LEN: usize = 0x10200;
+
+// The player does not emulate the PPU, so frames are rendered at a fixed 60Hz (NTSC) or 50Hz (PAL)
+// based on the master clock
+const NTSC_MCLK_PER_FRAME: u64 = constants::NTSC_MASTER_CLOCK_FREQUENCY / 60;
+const PAL_MCLK_PER_FRAME: u64 = constants::PAL_MASTER_CLOCK_FREQUENCY / 50;
+
+// Roughly the length of one scanline
+const MCLK_PER_TICK: u64 = 1364;
+
+const FRAME_WIDTH: u32 = 256;
+const FRAME_HEIGHT: u32 = 224;
+
+#[derive(Debug, Error)]
+pub enum SpcLoadError {
+    #[error("File is not an SPC file (missing 'SNES-SPC700 Sound File Data' signature)")]
+    InvalidSignature,
+    #[error("SPC file is too short; expected at least {MIN_FILE_LEN} bytes, was {0} bytes")]
+    TooShort(usize),
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SpcCpuRegisters {
+    pub pc: u16,
+    pub a: u8,
+    pub x: u8,
+    pub y: u8,
+    pub psw: u8,
+    pub sp: u8,
+}
+
+#[derive(Debug, Clone, Default, FakeEncode, FakeDecode)]
+pub struct SpcFile {
+    spc700_registers: SpcCpuRegisters,
+    audio_ram: Vec<u8>,
+    dsp_registers: Vec<u8>,
+    song_title: Option<String>,
+    game_title: Option<String>,
+}
+
+impl SpcFile {
+    pub(crate) fn new(
+        spc700_registers: SpcCpuRegisters,
+        audio_ram: Vec<u8>,
+        dsp_registers: Vec<u8>,
+    ) -> Self {
+        Self { spc700_registers, audio_ram, dsp_registers, song_title: None, game_title: None }
+    }
+
+    /// # Errors
+    ///
+    /// This function will return an error if the file does not have a valid SPC header or if it is
+    /// too short to contain the full APU state.
+    pub fn parse(bytes: &[u8]) -> Result<Self, SpcLoadError> {
+        if !bytes.starts_with(SPC_SIGNATURE) {
+            return Err(SpcLoadError::InvalidSignature);
+        }
+
+        if bytes.len() < MIN_FILE_LEN {
+            return Err(SpcLoadError::TooShort(bytes.len()));
+        }
+
+        let spc700_registers = SpcCpuRegisters {
+            pc: u16::from_le_bytes([bytes[0x25], bytes[0x26]]),
+            a: bytes[0x27],
+            x: bytes[0x28],
+            y: bytes[0x29],
+            psw: bytes[0x2A],
+            sp: bytes[0x2B],
+        };
+
+        // Only the text format of the ID666 tag is supported; titles are the first two fields in
+        // both the text and binary formats
+        let (song_title, game_title) = if bytes[0x23] == ID666_PRESENT {
+            (parse_tag_string(&bytes[0x2E..0x4E]), parse_tag_string(&bytes[0x4E..0x6E]))
+        } else {
+            (None, None)
+        };
+
+        Ok(Self {
+            spc700_registers,
+            audio_ram: bytes[AUDIO_RAM_OFFSET..AUDIO_RAM_OFFSET + AUDIO_RAM_LEN].to_vec(),
+            dsp_registers: bytes[DSP_REGISTERS_OFFSET..DSP_REGISTERS_OFFSET + DSP_REGISTERS_LEN]
+                .to_vec(),
+            song_title,
+            game_title,
+        })
+    }
+
+    /// Serialize to the SPC file format, with a text-format ID666 tag.
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![0; FULL_FILE_LEN];
+
+        bytes[..SPC_FULL_SIGNATURE.len()].copy_from_slice(SPC_FULL_SIGNATURE);
+        bytes[0x21] = 26;
+        bytes[0x22] = 26;
+        bytes[0x23] = ID666_PRESENT;
+        bytes[0x24] = SPC_MINOR_VERSION;
+
+        let registers = self.spc700_registers;
+        bytes[0x25..0x27].copy_from_slice(&registers.pc.to_le_bytes());
+        bytes[0x27] = registers.a;
+        bytes[0x28] = registers.x;
+        bytes[0x29] = registers.y;
+        bytes[0x2A] = registers.psw;
+        bytes[0x2B] = registers.sp;
+
+        write_tag_string(&mut bytes[0x2E..0x4E], self.song_title());
+        write_tag_string(&mut bytes[0x4E..0x6E], self.game_title());
+        write_tag_string(&mut bytes[0x6E..0x7E], Some("jgenesis"));
+
+        bytes[AUDIO_RAM_OFFSET..AUDIO_RAM_OFFSET + AUDIO_RAM_LEN].copy_from_slice(&self.audio_ram);
+        bytes[DSP_REGISTERS_OFFSET..DSP_REGISTERS_OFFSET + DSP_REGISTERS_LEN]
+            .copy_from_slice(&self.dsp_registers);
+
+        // RAM underneath the IPL ROM region
+        bytes[EXTRA_RAM_OFFSET..].copy_from_slice(&self.audio_ram[0xFFC0..]);
+
+        bytes
+    }
+
+    #[must_use]
+    pub fn with_game_title(mut self, game_title: String) -> Self {
+        self.game_title = (!game_title.is_empty()).then_some(game_title);
+        self
+    }
+
+    #[must_use]
+    pub fn spc700_registers(&self) -> SpcCpuRegisters {
+        self.spc700_registers
+    }
+
+    #[must_use]
+    pub fn audio_ram(&self) -> &[u8] {
+        &self.audio_ram
+    }
+
+    #[must_use]
+    pub fn dsp_registers(&self) -> &[u8] {
+        &self.dsp_registers
+    }
+
+    #[must_use]
+    pub fn song_title(&self) -> Option<&str> {
+        self.song_title.as_deref()
+    }
+
+    #[must_use]
+    pub fn game_title(&self) -> Option<&str> {
+        self.game_title.as_deref()
+    }
+}
+
+fn parse_tag_string(bytes: &[u8]) -> Option<String> {
+    let len = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    let s: String = String::from_utf8_lossy(&bytes[..len])
+        .chars()
+        .filter(|c| !c.is_control())
+        .collect::<String>()
+        .trim()
+        .into();
+
+    (!s.is_empty()).then_some(s)
+}
+
+fn write_tag_string(field: &mut [u8], s: Option<&str>) {
+    let Some(s) = s else { return };
+
+    // Truncate to leave room for a null terminator
+    let len = s.len().min(field.len() - 1);
+    field[..len].copy_from_slice(&s.as_bytes()[..len]);
+}
+
+/// Plays back an SPC file by running only the APU. Renders a blank frame at the console's refresh
+/// rate so that the frontend's frame pacing and audio sync work the same as for a game.
+#[derive(Encode, Decode, PartialClone)]
+pub struct SpcPlayer {
+    apu: Apu,
+    audio_resampler: AudioResampler,
+    timing_mode: TimingMode,
+    frame_mclk_counter: u64,
+    frame_buffer: Vec<Color>,
+    emulator_config: SnesEmulatorConfig,
+    // Only stored here to enable hard reset
+    #[partial_clone(default)]
+    spc: SpcFile,
+}
+
+impl SpcPlayer {
+    #[must_use]
+    pub fn create(spc: SpcFile, config: SnesEmulatorConfig) -> Self {
+        let timing_mode = config.forced_timing_mode.unwrap_or(TimingMode::Ntsc);
+
+        let mut apu = Apu::new(timing_mode, config.audio_60hz_hack);
+        apu.load_spc(&spc);
+
+        if let Some(song_title) = spc.song_title() {
+            log::info!("Playing SPC '{song_title}' ({})", spc.game_title().unwrap_or("?"));
+        }
+
+        Self {
+            apu,
+            audio_resampler: AudioResampler::new(config.audio_resampler_quality),
+            timing_mode,
+            frame_mclk_counter: 0,
+            frame_buffer: vec![Color::BLACK; (FRAME_WIDTH * FRAME_HEIGHT) as usize],
+            emulator_config: config,
+            spc,
+        }
+    }
+
+    #[must_use]
+    pub fn title(&self) -> String {
+        self.spc.song_title().unwrap_or("(untitled)").into()
+    }
+
+    fn frame_size() -> FrameSize {
+        FrameSize { width: FRAME_WIDTH, height: FRAME_HEIGHT }
+    }
+
+    fn render<R: Renderer>(&self, renderer: &mut R) -> Result<(), R::Err> {
+        renderer.render_frame(
+            &self.frame_buffer,
+            Self::frame_size(),
+            SnesAspectRatio::Ntsc.to_pixel_aspect_ratio(Self::frame_size()),
+        )
+    }
+}
+
+impl EmulatorTrait for SpcPlayer {
+    type Inputs = SnesInputs;
+    type Config = SnesEmulatorConfig;
+    const STATE_FORMAT: StateFormat = StateFormat { core: "snes-spc", version: 1 };
+
+    type Err<
+        RErr: Debug + Display + Send + Sync + 'static,
+        AErr: Debug + Display + Send + Sync + 'static,
+    > = SnesError<RErr, AErr>;
+
+    fn tick<R, A>(
+        &mut self,
+        renderer: &mut R,
+        audio_output: &mut A,
+        _inputs: &Self::Inputs,
+    ) -> Result<TickEffect, Self::Err<R::Err, A::Err>>
+    where
+        R: Renderer,
+        R::Err: Debug + Display + Send + Sync + 'static,
+        A: AudioOutput,
+        A::Err: Debug + Display + Send + Sync + 'static,
+    {
+        // Small steps so that at most one sample is produced per APU tick call
+        for _ in 0..MCLK_PER_TICK / 8 {
+            if let ApuTickEffect::OutputSample(sample_l, sample_r) = self.apu.tick(8) {
+                self.audio_resampler.collect_sample(sample_l, sample_r);
+            }
+        }
+
+        let mclk_per_frame = match self.timing_mode {
+            TimingMode::Ntsc => NTSC_MCLK_PER_FRAME,
+            TimingMode::Pal => PAL_MCLK_PER_FRAME,
+        };
+
+        self.frame_mclk_counter += MCLK_PER_TICK;
+        if self.frame_mclk_counter < mclk_per_frame {
+            return Ok(TickEffect::None);
+        }
+        self.frame_mclk_counter -= mclk_per_frame;
+
+        self.render(renderer).map_err(SnesError::Render)?;
+        self.audio_resampler.output_samples(audio_output).map_err(SnesError::AudioOutput)?;
+
+        Ok(TickEffect::FrameRendered)
+    }
+
+    fn save_dirty(&self) -> bool {
+        // SPC files do not have battery-backed save memory
+        false
+    }
+
+    fn persist_save<S: SaveWriter>(&mut self, _save_writer: &mut S) -> Result<(), S::Err> {
+        Ok(())
+    }
+
+    fn force_render<R>(&mut self, renderer: &mut R) -> Result<(), R::Err>
+    where
+        R: Renderer,
+    {
+        self.render(renderer)
+    }
+
+    fn reload_config(&mut self, config: &Self::Config) {
+        self.apu.set_audio_60hz_hack(config.audio_60hz_hack);
+        self.audio_resampler.set_quality(config.audio_resampler_quality);
+        self.emulator_config = *config;
+    }
+
+    fn take_rom_from(&mut self, other: &mut Self) {
+        self.spc = mem::take(&mut other.spc);
+    }
+
+    fn soft_reset(&mut self) {
+        // There is no game to reset, so restart the song instead
+        self.apu.load_spc(&self.spc);
+    }
+
+    fn hard_reset<S: SaveWriter>(&mut self, _save_writer: &mut S) {
+        let spc = mem::take(&mut self.spc);
+        *self = Self::create(spc, self.emulator_config);
+    }
+
+    fn timing_mode(&self) -> TimingMode {
+        self.timing_mode
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_spc_header() {
+        let mut bytes = vec![0; MIN_FILE_LEN];
+        bytes[..SPC_SIGNATURE.len()].copy_from_slice(SPC_SIGNATURE);
+        bytes[0x23] = ID666_PRESENT;
+        bytes[0x25..0x2C].copy_from_slice(&[0x34, 0x12, 0x01, 0x02, 0x03, 0x04, 0xEF]);
+        bytes[0x2E..0x33].copy_from_slice(b"Title");
+        bytes[AUDIO_RAM_OFFSET + 0x200] = 0xAB;
+        bytes[DSP_REGISTERS_OFFSET + 0x6C] = 0x20;
+
+        let spc = SpcFile::parse(&bytes).unwrap();
+        let registers = spc.spc700_registers();
+        assert_eq!(registers.pc, 0x1234);
+        assert_eq!((registers.a, registers.x, registers.y), (0x01, 0x02, 0x03));
+        assert_eq!((registers.psw, registers.sp), (0x04, 0xEF));
+        assert_eq!(spc.song_title(), Some("Title"));
+        assert_eq!(spc.game_title(), None);
+        assert_eq!(spc.audio_ram()[0x200], 0xAB);
+        assert_eq!(spc.dsp_registers()[0x6C], 0x20);
+
+        assert!(matches!(
+            SpcFile::parse(&bytes[..MIN_FILE_LEN - 1]),
+            Err(SpcLoadError::TooShort(_))
+        ));
+        assert!(matches!(
+            SpcFile::parse(&vec![0; MIN_FILE_LEN]),
+            Err(SpcLoadError::InvalidSignature)
+        ));
+    }
+
+    #[test]
+    fn spc_round_trip() {
+        let registers =
+            SpcCpuRegisters { pc: 0x0456, a: 0x10, x: 0x20, y: 0x30, psw: 0x02, sp: 0xCF };
+        let mut audio_ram = vec![0; AUDIO_RAM_LEN];
+        audio_ram[0x1000] = 0x55;
+        audio_ram[0xFFFF] = 0xAA;
+        let mut dsp_registers = vec![0; DSP_REGISTERS_LEN];
+        dsp_registers[0x5D] = 0x12;
+
+        let spc =
+            SpcFile::new(registers, audio_ram, dsp_registers).with_game_title("Some Game".into());
+        let bytes = spc.to_bytes();
+        assert_eq!(bytes.len(), FULL_FILE_LEN);
+        assert_eq!(bytes[EXTRA_RAM_OFFSET + 0x3F], 0xAA);
+
+        let parsed = SpcFile::parse(&bytes).unwrap();
+        assert_eq!(parsed.spc700_registers().pc, 0x0456);
+        assert_eq!(parsed.spc700_registers().sp, 0xCF);
+        assert_eq!(parsed.audio_ram(), spc.audio_ram());
+        assert_eq!(parsed.dsp_registers(), spc.dsp_registers());
+        assert_eq!(parsed.song_title(), None);
+        assert_eq!(parsed.game_title(), Some("Some Game"));
+    }
+}
@@ -51,11 +51,50 @@ impl FromStr for TrackMode {
     }
 }
 
+/// Subchannel Q control flags, specified using the FLAGS command in CUE files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode)]
+pub struct TrackFlags {
+    pub digital_copy_permitted: bool,
+    pub four_channel: bool,
+    pub pre_emphasis: bool,
+    pub serial_copy_management: bool,
+}
+
+impl TrackFlags {
+    /// The 4-bit control field for this track, as reported in subchannel Q.
+    #[must_use]
+    pub fn to_control_bits(self, track_type: TrackType) -> u8 {
+        (u8::from(self.four_channel) << 3)
+            | (u8::from(track_type == TrackType::Data) << 2)
+            | (u8::from(self.digital_copy_permitted) << 1)
+            | u8::from(self.pre_emphasis)
+    }
+}
+
+impl FromStr for TrackFlags {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut flags = Self::default();
+        for flag in s.split_whitespace() {
+            match flag {
+                "DCP" => flags.digital_copy_permitted = true,
+                "4CH" => flags.four_channel = true,
+                "PRE" => flags.pre_emphasis = true,
+                "SCMS" => flags.serial_copy_management = true,
+                _ => return Err(format!("unsupported track flag: {flag}")),
+            }
+        }
+        Ok(flags)
+    }
+}
+
 #[derive(Debug, Clone, Encode, Decode)]
 pub struct Track {
     pub number: u8,
     pub mode: TrackMode,
     pub track_type: TrackType,
+    pub flags: TrackFlags,
     pub start_time: CdTime,
     pub end_time: CdTime,
     pub pregap_len: CdTime,
@@ -96,6 +135,10 @@ impl CueSheet {
         &self.tracks[(track_number - 1) as usize]
     }
 
+    pub fn tracks(&self) -> impl Iterator<Item = &Track> {
+        self.tracks.iter()
+    }
+
     #[must_use]
     #[allow(clippy::missing_panics_doc)]
     pub fn last_track(&self) -> &Track {
@@ -0,0 +1,24 @@
+//! Entry points for the cargo-fuzz targets in the repository's `fuzz` directory
+//!
+//! These must never panic regardless of input; errors are expected and ignored.
+
+use crate::reader::cuebin;
+use crate::BYTES_PER_SECTOR;
+
+/// Parse a CUE file, pretending that every referenced file is 10 minutes long.
+pub fn cue_sheet(data: &[u8]) {
+    let Ok(cue) = std::str::from_utf8(data) else { return };
+
+    let file_len_bytes = 10 * 60 * 75 * BYTES_PER_SECTOR;
+    let _ = cuebin::parse_cue_str(cue, file_len_bytes);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cue_sheet_corpus() {
+        jgenesis_common::fuzz::replay_corpus("cue_sheet", cue_sheet);
+    }
+}
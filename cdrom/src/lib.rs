@@ -1,5 +1,8 @@
 pub mod cdtime;
 pub mod cue;
+#[cfg(any(test, feature = "fuzzing"))]
+pub mod fuzz;
+pub mod m3u;
 pub mod reader;
 
 use std::io;
@@ -23,6 +26,18 @@ pub enum CdRomError {
     CueInvalidIndexLine(String),
     #[error("Invalid/unsupported PREGAP line in CUE file: {0}")]
     CueInvalidPregapLine(String),
+    #[error("Invalid/unsupported POSTGAP line in CUE file: {0}")]
+    CueInvalidPostgapLine(String),
+    #[error("Invalid/unsupported FLAGS line in CUE file: {0}")]
+    CueInvalidFlagsLine(String),
+    #[error("Invalid/unsupported WAVE file '{path}': {message}")]
+    WaveInvalid { path: String, message: String },
+    #[error("Error reading WAVE file '{path}': {source}")]
+    WaveRead {
+        path: String,
+        #[source]
+        source: io::Error,
+    },
     #[error("Unable to get file metadata for file '{path}': {source}")]
     FsMetadata {
         path: String,
@@ -41,6 +56,14 @@ pub enum CdRomError {
         #[source]
         source: io::Error,
     },
+    #[error("Error opening M3U playlist '{path}': {source}")]
+    M3uOpen {
+        path: String,
+        #[source]
+        source: io::Error,
+    },
+    #[error("M3U playlist '{0}' does not list any discs")]
+    M3uEmpty(String),
     #[error("CHD-related error: {0}")]
     ChdError(#[from] chd::Error),
     #[error("Error opening CHD file '{path}': {source}")]
@@ -53,6 +76,8 @@ pub enum CdRomError {
     ChdHeaderParseError { metadata_value: String },
     #[error("CHD header contains an invalid CD-ROM track list: {track_numbers:?}")]
     ChdInvalidTrackList { track_numbers: Vec<u8> },
+    #[error("Unable to spawn disc read thread: {0}")]
+    ReadThreadSpawn(#[source] io::Error),
     #[error("I/O error reading from disc: {0}")]
     DiscReadIo(#[source] io::Error),
     #[error(
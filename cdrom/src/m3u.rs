@@ -0,0 +1,67 @@
+//! Code for reading M3U playlists, which group the discs of a multi-disc game into a single file
+
+use crate::{CdRomError, CdRomResult};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Read the disc paths listed in an M3U playlist, in order.
+///
+/// Relative paths are resolved against the playlist's directory.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or if it does not list any discs.
+pub fn parse<P: AsRef<Path>>(m3u_path: P) -> CdRomResult<Vec<PathBuf>> {
+    let m3u_path = m3u_path.as_ref();
+
+    let contents = fs::read_to_string(m3u_path)
+        .map_err(|source| CdRomError::M3uOpen { path: m3u_path.display().to_string(), source })?;
+    let m3u_directory = m3u_path.parent().unwrap_or(Path::new(""));
+
+    let discs = parse_contents(&contents, m3u_directory);
+    if discs.is_empty() {
+        return Err(CdRomError::M3uEmpty(m3u_path.display().to_string()));
+    }
+
+    Ok(discs)
+}
+
+/// Parse M3U playlist contents, resolving relative paths against the given directory.
+///
+/// Blank lines and lines starting with `#` (comments and extended M3U directives) are skipped.
+#[must_use]
+pub fn parse_contents(contents: &str, m3u_directory: &Path) -> Vec<PathBuf> {
+    contents
+        .lines()
+        .map(|line| line.trim_start_matches('\u{feff}').trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| m3u_directory.join(line))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relative_and_absolute_paths() {
+        let contents = "Game (Disc 1).cue\r\nsubdir/Game (Disc 2).chd\n/abs/Game (Disc 3).cue\n";
+        let discs = parse_contents(contents, Path::new("/roms"));
+        assert_eq!(
+            discs,
+            vec![
+                PathBuf::from("/roms/Game (Disc 1).cue"),
+                PathBuf::from("/roms/subdir/Game (Disc 2).chd"),
+                PathBuf::from("/abs/Game (Disc 3).cue"),
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_comments_and_blank_lines() {
+        let contents =
+            "\u{feff}#EXTM3U\n\n#EXTINF:0,Disc 1\n  Disc 1.cue  \n\n# comment\nDisc 2.cue";
+        let discs = parse_contents(contents, Path::new("dir"));
+        assert_eq!(discs, vec![PathBuf::from("dir/Disc 1.cue"), PathBuf::from("dir/Disc 2.cue")]);
+    }
+}
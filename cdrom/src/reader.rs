@@ -1,13 +1,17 @@
 //! Code for reading CD-ROM files
 
 mod chd;
-mod cuebin;
+pub(crate) mod cuebin;
+mod mmap;
+mod readahead;
 mod seekvec;
 
 use crate::cdtime::CdTime;
-use crate::cue::{CueSheet, TrackMode, TrackType};
+use crate::cue::{CueSheet, Track, TrackMode, TrackType};
 use crate::reader::chd::ChdFile;
 use crate::reader::cuebin::CdBinFiles;
+use crate::reader::mmap::Mmap;
+use crate::reader::readahead::{ReadAheadReader, SectorSource};
 use crate::reader::seekvec::SeekableVec;
 use crate::{CdRomError, CdRomResult};
 use bincode::{Decode, Encode};
@@ -15,7 +19,7 @@ use crc::Crc;
 use jgenesis_proc_macros::{FakeDecode, FakeEncode};
 use std::ffi::OsStr;
 use std::fs::File;
-use std::io::BufReader;
+use std::io::{BufReader, Cursor};
 use std::ops::Range;
 use std::path::Path;
 
@@ -35,13 +39,16 @@ const MODE_2_FORM_2_DIGEST_RANGE: Range<usize> = 16..2348;
 const MODE_2_FORM_2_CHECKSUM_LOCATION: Range<usize> = 2348..2352;
 
 type ChdFsFile = ChdFile<BufReader<File>>;
+type ChdMappedFile = ChdFile<Cursor<Mmap>>;
 type ChdMemoryFile = ChdFile<SeekableVec>;
 
 #[derive(Debug, FakeEncode, FakeDecode)]
 enum CdRomReader {
     CueBin(CdBinFiles),
     ChdFs(ChdFsFile),
+    ChdMapped(ChdMappedFile),
     ChdMemory(ChdMemoryFile),
+    ReadAhead(ReadAheadReader),
 }
 
 impl Default for CdRomReader {
@@ -50,7 +57,7 @@ impl Default for CdRomReader {
     }
 }
 
-impl CdRomReader {
+impl SectorSource for CdRomReader {
     fn read_sector(
         &mut self,
         track_number: u8,
@@ -64,13 +71,43 @@ impl CdRomReader {
             Self::ChdFs(chd_file) => {
                 chd_file.read_sector(track_number, relative_sector_number, out)
             }
+            Self::ChdMapped(chd_file) => {
+                chd_file.read_sector(track_number, relative_sector_number, out)
+            }
             Self::ChdMemory(chd_file) => {
                 chd_file.read_sector(track_number, relative_sector_number, out)
             }
+            Self::ReadAhead(reader) => reader.read_sector(track_number, relative_sector_number, out),
         }
     }
 }
 
+impl CdRomReader {
+    // Move reads from the filesystem onto a separate I/O thread so that slow storage does not
+    // stall emulation
+    #[cfg(not(target_arch = "wasm32"))]
+    fn into_read_ahead(self, cue_sheet: &CueSheet) -> CdRomResult<Self> {
+        let track_sectors = cue_sheet.tracks().map(file_sector_count).collect();
+        let reader =
+            ReadAheadReader::spawn(self, track_sectors).map_err(CdRomError::ReadThreadSpawn)?;
+        Ok(Self::ReadAhead(reader))
+    }
+
+    // Threads are not available in WASM
+    #[cfg(target_arch = "wasm32")]
+    #[allow(clippy::unnecessary_wraps)]
+    fn into_read_ahead(self, _cue_sheet: &CueSheet) -> CdRomResult<Self> {
+        Ok(self)
+    }
+}
+
+// Number of sectors in the disc image file for the given track, excluding any pregap or postgap
+// that is not stored in the file
+#[cfg(not(target_arch = "wasm32"))]
+fn file_sector_count(track: &Track) -> u32 {
+    (track.end_time - track.postgap_len - track.start_time - track.pregap_len).to_sector_number()
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CdRomFileFormat {
     // CUE file + BIN files
@@ -113,6 +150,10 @@ pub struct CdRom {
 impl CdRom {
     /// Open a CD-ROM reader that will read from the filesystem as needed.
     ///
+    /// Disc image files are memory mapped if possible, falling back to buffered reads if not.
+    /// Sectors are read on a background I/O thread that reads ahead of the most recently requested
+    /// sector.
+    ///
     /// # Errors
     ///
     /// Will propagate any I/O errors, and will return an error if the CD-ROM metadata appears
@@ -126,8 +167,9 @@ impl CdRom {
 
     fn open_cue_bin<P: AsRef<Path>>(cue_path: P) -> CdRomResult<Self> {
         let (bin_files, cue_sheet) = CdBinFiles::create(cue_path)?;
+        let reader = CdRomReader::CueBin(bin_files).into_read_ahead(&cue_sheet)?;
 
-        Ok(Self { cue_sheet, reader: CdRomReader::CueBin(bin_files) })
+        Ok(Self { cue_sheet, reader })
     }
 
     fn open_chd<P: AsRef<Path>>(chd_path: P) -> CdRomResult<Self> {
@@ -137,9 +179,16 @@ impl CdRom {
             path: chd_path.display().to_string(),
             source,
         })?;
+        if let Some(mmap) = mmap::map_file(&file, chd_path) {
+            let (chd_file, cue_sheet) = ChdFile::open(Cursor::new(mmap))?;
+            let reader = CdRomReader::ChdMapped(chd_file).into_read_ahead(&cue_sheet)?;
+            return Ok(Self { cue_sheet, reader });
+        }
+
         let (chd_file, cue_sheet) = ChdFile::open(BufReader::new(file))?;
+        let reader = CdRomReader::ChdFs(chd_file).into_read_ahead(&cue_sheet)?;
 
-        Ok(Self { cue_sheet, reader: CdRomReader::ChdFs(chd_file) })
+        Ok(Self { cue_sheet, reader })
     }
 
     /// Open a CD-ROM reader that will read from a CHD file that has been read into memory.
@@ -1,7 +1,7 @@
 //! Code for loading and reading CD-ROM images in CHD format
 
 use crate::cdtime::CdTime;
-use crate::cue::{CueSheet, Track, TrackMode, TrackType};
+use crate::cue::{CueSheet, Track, TrackFlags, TrackMode, TrackType};
 use crate::{cue, CdRomError, CdRomResult};
 use chd::iter::LendingIterator;
 use chd::Chd;
@@ -106,6 +106,8 @@ impl<F: Read + Seek> ChdFile<F> {
                 number: cd_metadata.track_number,
                 mode: cd_metadata.mode,
                 track_type,
+                // CHD track metadata does not include subchannel Q flags
+                flags: TrackFlags::default(),
                 start_time: current_start_time,
                 end_time: current_start_time + padded_track_len,
                 pregap_len,
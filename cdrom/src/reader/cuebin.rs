@@ -1,32 +1,94 @@
 //! Code for loading and reading CD-ROM images in CUE/BIN format
 
+mod wave;
+
 use crate::cdtime::CdTime;
-use crate::cue::{CueSheet, Track, TrackMode, TrackType};
+use crate::cue::{CueSheet, Track, TrackFlags, TrackMode, TrackType};
+use crate::reader::mmap::{self, Mmap};
 use crate::{cue, CdRomError, CdRomResult};
 use bincode::{Decode, Encode};
 use regex::Regex;
 use std::collections::{HashMap, HashSet};
 use std::fs::File;
+use std::io;
 use std::io::{BufReader, Read, Seek, SeekFrom};
 use std::path::Path;
+use std::str::FromStr;
 use std::sync::OnceLock;
 use std::{fs, mem};
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+pub enum BinFileFormat {
+    /// Raw 2352-byte sectors
+    Binary,
+    /// Raw 2352-byte sectors with every 16-bit word byte-swapped (big-endian audio samples)
+    Motorola,
+    /// 16-bit stereo 44100Hz PCM audio in a RIFF WAVE container
+    Wave,
+}
+
+impl FromStr for BinFileFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "BINARY" => Ok(Self::Binary),
+            "MOTOROLA" => Ok(Self::Motorola),
+            "WAVE" => Ok(Self::Wave),
+            _ => Err(format!("unsupported file type: {s}")),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Encode, Decode)]
 pub struct TrackMetadata {
     pub file_name: String,
+    pub file_format: BinFileFormat,
+    /// Byte offset of the first sector in the file; non-zero only for WAVE files
+    pub file_offset: u64,
     pub time_in_file: CdTime,
 }
 
 #[derive(Debug)]
-struct CdRomFile {
-    file: BufReader<File>,
-    position: u64,
+enum CdRomFile {
+    Mapped(Mmap),
+    Buffered { file: BufReader<File>, position: u64 },
 }
 
 impl CdRomFile {
-    fn new(file: File) -> Self {
-        Self { file: BufReader::new(file), position: 0 }
+    fn new(file: File, path: &Path) -> Self {
+        match mmap::map_file(&file, path) {
+            Some(mmap) => Self::Mapped(mmap),
+            None => Self::Buffered { file: BufReader::new(file), position: 0 },
+        }
+    }
+
+    fn read_exact_at(&mut self, address: u64, out: &mut [u8]) -> io::Result<()> {
+        match self {
+            Self::Mapped(mmap) => {
+                let bytes = usize::try_from(address)
+                    .ok()
+                    .and_then(|start| mmap.as_ref().get(start..start + out.len()))
+                    .ok_or_else(|| {
+                        io::Error::new(
+                            io::ErrorKind::UnexpectedEof,
+                            format!("Read past end of file at address {address}"),
+                        )
+                    })?;
+                out.copy_from_slice(bytes);
+            }
+            Self::Buffered { file, position } => {
+                // Only seek if the file descriptor is not already at the desired position
+                if *position != address {
+                    file.seek(SeekFrom::Start(address))?;
+                }
+
+                file.read_exact(out)?;
+                *position = address + out.len() as u64;
+            }
+        }
+
+        Ok(())
     }
 }
 
@@ -56,7 +118,7 @@ impl CdBinFiles {
                 path: file_path.display().to_string(),
                 source,
             })?;
-            files.insert(file_name, CdRomFile::new(file));
+            files.insert(file_name, CdRomFile::new(file, &file_path));
         }
 
         let bin_files = Self { files, track_metadata };
@@ -70,23 +132,23 @@ impl CdBinFiles {
         out: &mut [u8],
     ) -> CdRomResult<()> {
         let metadata = &self.track_metadata[(track_number - 1) as usize];
-        let CdRomFile { file: track_file, position } = self
+        let track_file = self
             .files
             .get_mut(&metadata.file_name)
             .expect("Track file was not opened on load; this is a bug");
 
         let sector_number = metadata.time_in_file.to_sector_number() + relative_sector_number;
-        let sector_addr = u64::from(sector_number) * crate::BYTES_PER_SECTOR;
-
-        // Only seek if the file descriptor is not already at the desired position
-        if *position != sector_addr {
-            track_file.seek(SeekFrom::Start(sector_addr)).map_err(CdRomError::DiscReadIo)?;
-        }
+        let sector_addr = metadata.file_offset + u64::from(sector_number) * crate::BYTES_PER_SECTOR;
 
         track_file
-            .read_exact(&mut out[..crate::BYTES_PER_SECTOR as usize])
+            .read_exact_at(sector_addr, &mut out[..crate::BYTES_PER_SECTOR as usize])
             .map_err(CdRomError::DiscReadIo)?;
-        *position = sector_addr + crate::BYTES_PER_SECTOR;
+
+        if metadata.file_format == BinFileFormat::Motorola {
+            for word in out[..crate::BYTES_PER_SECTOR as usize].chunks_exact_mut(2) {
+                word.swap(0, 1);
+            }
+        }
 
         Ok(())
     }
@@ -96,26 +158,36 @@ impl CdBinFiles {
 struct ParsedTrack {
     number: u8,
     mode: TrackMode,
+    flags: TrackFlags,
     pregap_len: Option<CdTime>,
+    postgap_len: Option<CdTime>,
     pause_start: Option<CdTime>,
+    // Set if the track's INDEX 00 is in the previous file and its INDEX 01 is in this file
+    pause_in_previous_file: bool,
     track_start: CdTime,
 }
 
 #[derive(Debug, Clone)]
 struct ParsedFile {
     file_name: String,
+    format: BinFileFormat,
     tracks: Vec<ParsedTrack>,
+    // Start of the next track's pause if the file ends with the next track's INDEX 00
+    trailing_pause_start: Option<CdTime>,
 }
 
 #[derive(Debug, Clone)]
 struct CueParser {
     files: Vec<ParsedFile>,
     tracks: Vec<ParsedTrack>,
-    current_file: Option<String>,
+    current_file: Option<(String, BinFileFormat)>,
     current_track: Option<(u8, TrackMode)>,
     last_track_number: Option<u8>,
+    flags: TrackFlags,
     pregap_len: Option<CdTime>,
+    postgap_len: Option<CdTime>,
     pause_start: Option<CdTime>,
+    pause_in_previous_file: bool,
     track_start: Option<CdTime>,
 }
 
@@ -127,26 +199,35 @@ impl CueParser {
             current_file: None,
             current_track: None,
             last_track_number: None,
+            flags: TrackFlags::default(),
             pregap_len: None,
+            postgap_len: None,
             pause_start: None,
+            pause_in_previous_file: false,
             track_start: None,
         }
     }
 
     fn parse(mut self, file: &str) -> CdRomResult<Vec<ParsedFile>> {
         for line in file.lines() {
-            if line.starts_with("FILE ") {
-                self.parse_file_line(line)?;
-            } else if line.starts_with("  TRACK ") {
-                self.parse_track_line(line)?;
-            } else if line.starts_with("    INDEX ") {
-                self.parse_index_line(line)?;
-            } else if line.starts_with("    PREGAP ") {
-                self.parse_pregap_line(line)?;
+            // Indentation varies between CUE files, and some use tabs instead of spaces
+            let line = line.trim();
+
+            // Other commands (REM, CATALOG, TITLE, PERFORMER, ISRC, etc.) do not affect the
+            // disc layout and are ignored
+            match line.split_whitespace().next() {
+                Some("FILE") => self.parse_file_line(line)?,
+                Some("TRACK") => self.parse_track_line(line)?,
+                Some("INDEX") => self.parse_index_line(line)?,
+                Some("PREGAP") => self.parse_pregap_line(line)?,
+                Some("POSTGAP") => self.parse_postgap_line(line)?,
+                Some("FLAGS") => self.parse_flags_line(line)?,
+                _ => {}
             }
         }
 
-        self.push_file()?;
+        self.push_track()?;
+        self.push_file(None)?;
 
         if self.files.is_empty() {
             return Err(CdRomError::CueParse("CUE file has no tracks".into()));
@@ -158,13 +239,29 @@ impl CueParser {
     fn parse_file_line(&mut self, line: &str) -> CdRomResult<()> {
         static RE: OnceLock<Regex> = OnceLock::new();
 
-        self.push_file()?;
+        if self.current_track.is_some() && self.track_start.is_none() && self.pause_start.is_some()
+        {
+            // The current track's pause is at the end of the previous file, and the track itself
+            // starts in the new file
+            let trailing_pause_start = self.pause_start.take();
+            self.push_file(trailing_pause_start)?;
+            self.pause_in_previous_file = true;
+        } else {
+            self.push_track()?;
+            self.push_file(None)?;
+        }
 
-        let re = RE.get_or_init(|| Regex::new(r#"FILE "(.*)" BINARY"#).unwrap());
+        let re = RE.get_or_init(|| Regex::new(r#"^FILE (?:"(.*)"|(\S+)) (\S+)$"#).unwrap());
         let captures =
             re.captures(line).ok_or_else(|| CdRomError::CueInvalidFileLine(line.into()))?;
-        let file_name = captures.get(1).unwrap();
-        self.current_file = Some(file_name.as_str().into());
+        let file_name = captures.get(1).or_else(|| captures.get(2)).unwrap();
+        let format = captures
+            .get(3)
+            .unwrap()
+            .as_str()
+            .parse::<BinFileFormat>()
+            .map_err(|_| CdRomError::CueInvalidFileLine(line.into()))?;
+        self.current_file = Some((file_name.as_str().into(), format));
 
         Ok(())
     }
@@ -201,7 +298,12 @@ impl CueParser {
         let re = RE.get_or_init(|| Regex::new(r"INDEX ([^ ]*) ([^ ]*)").unwrap());
         let captures =
             re.captures(line).ok_or_else(|| CdRomError::CueInvalidIndexLine(line.into()))?;
-        let index_number = captures.get(1).unwrap();
+        let index_number = captures
+            .get(1)
+            .unwrap()
+            .as_str()
+            .parse::<u8>()
+            .map_err(|_| CdRomError::CueInvalidIndexLine(line.into()))?;
         let start_time = captures
             .get(2)
             .unwrap()
@@ -209,13 +311,16 @@ impl CueParser {
             .parse::<CdTime>()
             .map_err(|_| CdRomError::CueInvalidIndexLine(line.into()))?;
 
-        match index_number.as_str() {
-            "00" => {
+        match index_number {
+            0 => {
                 self.pause_start = Some(start_time);
             }
-            "01" => {
+            1 => {
                 self.track_start = Some(start_time);
             }
+            2..=99 => {
+                // Indexes past 01 only mark subdivisions within a track
+            }
             _ => {
                 return Err(CdRomError::CueInvalidIndexLine(line.into()));
             }
@@ -225,36 +330,43 @@ impl CueParser {
     }
 
     fn parse_pregap_line(&mut self, line: &str) -> CdRomResult<()> {
-        static RE: OnceLock<Regex> = OnceLock::new();
+        self.pregap_len = Some(
+            parse_gap_line(line, "PREGAP")
+                .ok_or_else(|| CdRomError::CueInvalidPregapLine(line.into()))?,
+        );
 
-        let re = RE.get_or_init(|| Regex::new(r"PREGAP ([^ ]*)").unwrap());
-        let captures =
-            re.captures(line).ok_or_else(|| CdRomError::CueInvalidPregapLine(line.into()))?;
-        let pregap_len = captures
-            .get(1)
-            .unwrap()
-            .as_str()
-            .parse::<CdTime>()
-            .map_err(|_| CdRomError::CueInvalidPregapLine(line.into()))?;
+        Ok(())
+    }
 
-        self.pregap_len = Some(pregap_len);
+    fn parse_postgap_line(&mut self, line: &str) -> CdRomResult<()> {
+        self.postgap_len = Some(
+            parse_gap_line(line, "POSTGAP")
+                .ok_or_else(|| CdRomError::CueInvalidPostgapLine(line.into()))?,
+        );
 
         Ok(())
     }
 
-    fn push_file(&mut self) -> CdRomResult<()> {
-        self.push_track()?;
+    fn parse_flags_line(&mut self, line: &str) -> CdRomResult<()> {
+        let flags = line.strip_prefix("FLAGS").unwrap_or_default();
+        self.flags = flags.parse().map_err(|_| CdRomError::CueInvalidFlagsLine(line.into()))?;
+
+        Ok(())
+    }
 
-        let Some(current_file) = self.current_file.take() else { return Ok(()) };
+    fn push_file(&mut self, trailing_pause_start: Option<CdTime>) -> CdRomResult<()> {
+        let Some((file_name, format)) = self.current_file.take() else { return Ok(()) };
 
         if self.tracks.is_empty() {
-            return Err(CdRomError::CueParse(format!(
-                "No tracks listed for file '{current_file}'"
-            )));
+            return Err(CdRomError::CueParse(format!("No tracks listed for file '{file_name}'")));
         }
 
-        self.files
-            .push(ParsedFile { file_name: current_file, tracks: mem::take(&mut self.tracks) });
+        self.files.push(ParsedFile {
+            file_name,
+            format,
+            tracks: mem::take(&mut self.tracks),
+            trailing_pause_start,
+        });
 
         Ok(())
     }
@@ -291,8 +403,11 @@ impl CueParser {
         self.tracks.push(ParsedTrack {
             number: track_number,
             mode: track_mode,
+            flags: mem::take(&mut self.flags),
             pregap_len: self.pregap_len.take(),
+            postgap_len: self.postgap_len.take(),
             pause_start: self.pause_start.take(),
+            pause_in_previous_file: mem::take(&mut self.pause_in_previous_file),
             track_start,
         });
 
@@ -300,6 +415,10 @@ impl CueParser {
     }
 }
 
+fn parse_gap_line(line: &str, command: &str) -> Option<CdTime> {
+    line.strip_prefix(command)?.trim().parse().ok()
+}
+
 fn parse_cue<P: AsRef<Path>>(cue_path: P) -> CdRomResult<(CueSheet, Vec<TrackMetadata>)> {
     let cue_path = cue_path.as_ref();
 
@@ -310,6 +429,15 @@ fn parse_cue<P: AsRef<Path>>(cue_path: P) -> CdRomResult<(CueSheet, Vec<TrackMet
     to_cue_sheet(parsed_files, cue_path)
 }
 
+/// Parse CUE file contents as if every file referenced by the CUE file contained `file_len_bytes`
+/// bytes of sector data.
+#[cfg(any(test, feature = "fuzzing"))]
+pub(crate) fn parse_cue_str(cue: &str, file_len_bytes: u64) -> CdRomResult<CueSheet> {
+    let parsed_files = CueParser::new().parse(cue)?;
+    let (cue_sheet, _) = build_cue_sheet(parsed_files, |_, _| Ok((0, file_len_bytes)))?;
+    Ok(cue_sheet)
+}
+
 fn to_cue_sheet(
     parsed_files: Vec<ParsedFile>,
     cue_path: &Path,
@@ -318,44 +446,74 @@ fn to_cue_sheet(
         .parent()
         .ok_or_else(|| CdRomError::CueParentDir(cue_path.display().to_string()))?;
 
+    build_cue_sheet(parsed_files, |file_name, format| {
+        let bin_path = cue_parent_dir.join(file_name);
+        match format {
+            BinFileFormat::Binary | BinFileFormat::Motorola => {
+                let file_metadata = fs::metadata(&bin_path).map_err(|source| {
+                    CdRomError::FsMetadata { path: bin_path.display().to_string(), source }
+                })?;
+                Ok((0, file_metadata.len()))
+            }
+            BinFileFormat::Wave => wave::find_data_chunk(&bin_path),
+        }
+    })
+}
+
+// Build the cue sheet from the parsed CUE file; `file_data_range` returns the offset and length of
+// the sector data in the given file
+fn build_cue_sheet(
+    parsed_files: Vec<ParsedFile>,
+    mut file_data_range: impl FnMut(&str, BinFileFormat) -> CdRomResult<(u64, u64)>,
+) -> CdRomResult<(CueSheet, Vec<TrackMetadata>)> {
     let mut absolute_start_time = CdTime::ZERO;
     let mut tracks = Vec::new();
     let mut track_metadata = Vec::new();
+    let mut carried_pause_len = CdTime::ZERO;
 
-    for ParsedFile { file_name, tracks: parsed_tracks } in parsed_files {
-        let bin_path = cue_parent_dir.join(&file_name);
-
-        let file_metadata = fs::metadata(&bin_path).map_err(|source| CdRomError::FsMetadata {
-            path: bin_path.display().to_string(),
-            source,
-        })?;
-        let file_len_bytes = file_metadata.len();
+    for ParsedFile { file_name, format, tracks: parsed_tracks, trailing_pause_start } in
+        parsed_files
+    {
+        let (file_offset, file_len_bytes) = file_data_range(&file_name, format)?;
         let file_len_sectors = (file_len_bytes / crate::BYTES_PER_SECTOR) as u32;
+        let file_end_time = CdTime::from_sector_number(file_len_sectors);
 
         for i in 0..parsed_tracks.len() {
             let track = &parsed_tracks[i];
 
             let track_type = track.mode.to_type();
-            let pregap_len = match track_type {
+            let mut pregap_len = match track_type {
                 TrackType::Data => {
                     // Data tracks always have a 2-second pregap
                     CdTime::new(0, 2, 0)
                 }
                 TrackType::Audio => track.pregap_len.unwrap_or(CdTime::ZERO),
             };
+            if track.pause_in_previous_file {
+                // Pause sectors at the end of the previous file are not read; they are treated as
+                // part of the pregap, which is always silent
+                pregap_len += mem::replace(&mut carried_pause_len, CdTime::ZERO);
+            }
             let pause_len = track
                 .pause_start
                 .map_or(CdTime::ZERO, |pause_start| track.track_start - pause_start);
 
             let is_last_track_in_file = i == parsed_tracks.len() - 1;
             let data_end_time = if is_last_track_in_file {
-                CdTime::from_sector_number(file_len_sectors)
+                trailing_pause_start.unwrap_or(file_end_time)
             } else {
                 let next_track = &parsed_tracks[i + 1];
                 next_track.pause_start.unwrap_or(next_track.track_start)
             };
 
-            let postgap_len = track_type.default_postgap_len();
+            if data_end_time < track.track_start {
+                return Err(CdRomError::CueParse(format!(
+                    "Track {} starts at {} but ends at {data_end_time} in file '{file_name}'",
+                    track.number, track.track_start
+                )));
+            }
+
+            let postgap_len = track.postgap_len.unwrap_or_else(|| track_type.default_postgap_len());
 
             let padded_track_len =
                 pregap_len + pause_len + (data_end_time - track.track_start) + postgap_len;
@@ -363,6 +521,7 @@ fn to_cue_sheet(
                 number: track.number,
                 mode: track.mode,
                 track_type,
+                flags: track.flags,
                 start_time: absolute_start_time,
                 end_time: absolute_start_time + padded_track_len,
                 pregap_len,
@@ -371,11 +530,22 @@ fn to_cue_sheet(
             });
             track_metadata.push(TrackMetadata {
                 file_name: file_name.clone(),
+                file_format: format,
+                file_offset,
                 time_in_file: track.pause_start.unwrap_or(track.track_start),
             });
 
             absolute_start_time += padded_track_len;
         }
+
+        if let Some(pause_start) = trailing_pause_start {
+            if pause_start > file_end_time {
+                return Err(CdRomError::CueParse(format!(
+                    "INDEX 00 at {pause_start} is past the end of file '{file_name}'"
+                )));
+            }
+            carried_pause_len = file_end_time - pause_start;
+        }
     }
 
     cue::finalize_track_list(&mut tracks);
@@ -389,3 +559,82 @@ fn to_cue_sheet(
 
     Ok((CueSheet::new(tracks), track_metadata))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_ignores_indentation_and_extra_indexes() {
+        let cue = "FILE \"Game.bin\" BINARY\n\
+                   \tTRACK 01 MODE1/2352\n\
+                   \t\tINDEX 01 00:00:00\n\
+                   TRACK 02 AUDIO\n\
+                   FLAGS DCP PRE\n\
+                   INDEX 00 10:00:00\n\
+                   INDEX 01 10:02:00\n\
+                   INDEX 02 11:00:00\n\
+                   POSTGAP 00:01:00\n";
+        let files = CueParser::new().parse(cue).unwrap();
+
+        assert_eq!(files.len(), 1);
+        let tracks = &files[0].tracks;
+        assert_eq!(tracks.len(), 2);
+        assert_eq!(tracks[1].pause_start, Some(CdTime::new(10, 0, 0)));
+        assert_eq!(tracks[1].track_start, CdTime::new(10, 2, 0));
+        assert_eq!(tracks[1].postgap_len, Some(CdTime::new(0, 1, 0)));
+        assert!(tracks[1].flags.digital_copy_permitted && tracks[1].flags.pre_emphasis);
+        assert_eq!(tracks[0].flags, TrackFlags::default());
+    }
+
+    #[test]
+    fn parse_pause_in_previous_file() {
+        let cue = "FILE \"Track 01.bin\" BINARY\n\
+                   \x20 TRACK 01 MODE1/2352\n\
+                   \x20   INDEX 01 00:00:00\n\
+                   \x20 TRACK 02 AUDIO\n\
+                   \x20   INDEX 00 05:00:00\n\
+                   FILE Track02.wav WAVE\n\
+                   \x20   INDEX 01 00:00:00\n";
+        let files = CueParser::new().parse(cue).unwrap();
+
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].trailing_pause_start, Some(CdTime::new(5, 0, 0)));
+        assert_eq!(files[1].file_name, "Track02.wav");
+        assert_eq!(files[1].format, BinFileFormat::Wave);
+
+        let track = &files[1].tracks[0];
+        assert_eq!(track.number, 2);
+        assert!(track.pause_in_previous_file);
+        assert_eq!(track.pause_start, None);
+    }
+
+    #[test]
+    fn mapped_and_buffered_reads_match() {
+        let path = std::env::temp_dir().join(format!("cdrom-read-test-{}.bin", std::process::id()));
+        let contents: Vec<u8> = (0..3 * crate::BYTES_PER_SECTOR).map(|i| i as u8).collect();
+        fs::write(&path, &contents).unwrap();
+
+        let mut mapped = CdRomFile::new(File::open(&path).unwrap(), &path);
+        assert!(matches!(mapped, CdRomFile::Mapped(_)));
+        let mut buffered =
+            CdRomFile::Buffered { file: BufReader::new(File::open(&path).unwrap()), position: 0 };
+
+        for address in [2352, 0, 4704] {
+            let mut mapped_out = [0; crate::BYTES_PER_SECTOR as usize];
+            let mut buffered_out = [0; crate::BYTES_PER_SECTOR as usize];
+            mapped.read_exact_at(address, &mut mapped_out).unwrap();
+            buffered.read_exact_at(address, &mut buffered_out).unwrap();
+
+            let start = address as usize;
+            assert_eq!(mapped_out.as_slice(), &contents[start..start + mapped_out.len()]);
+            assert_eq!(mapped_out, buffered_out);
+        }
+
+        let mut out = [0; crate::BYTES_PER_SECTOR as usize];
+        assert!(mapped.read_exact_at(4705, &mut out).is_err());
+        assert!(buffered.read_exact_at(4705, &mut out).is_err());
+
+        fs::remove_file(&path).unwrap();
+    }
+}
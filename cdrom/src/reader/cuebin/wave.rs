@@ -0,0 +1,70 @@
+//! Minimal RIFF WAVE parsing for audio tracks stored as WAVE files
+
+use crate::{CdRomError, CdRomResult};
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+const PCM_FORMAT: u16 = 1;
+
+/// Find the PCM sample data in a WAVE file, returning its byte offset and length.
+///
+/// Only 16-bit stereo 44100Hz PCM is supported since that is the format of CD audio; the samples
+/// can then be read as if they were raw audio sectors.
+pub fn find_data_chunk(path: &Path) -> CdRomResult<(u64, u64)> {
+    let file = File::open(path)
+        .map_err(|source| CdRomError::BinOpen { path: path.display().to_string(), source })?;
+    let mut reader = BufReader::new(file);
+
+    let invalid = |message: &str| CdRomError::WaveInvalid {
+        path: path.display().to_string(),
+        message: message.into(),
+    };
+    let read_err = |source| CdRomError::WaveRead { path: path.display().to_string(), source };
+
+    let mut riff_header = [0; 12];
+    reader.read_exact(&mut riff_header).map_err(read_err)?;
+    if &riff_header[0..4] != b"RIFF" || &riff_header[8..12] != b"WAVE" {
+        return Err(invalid("missing RIFF/WAVE header"));
+    }
+
+    let mut format_valid = false;
+    let mut position: u64 = 12;
+    loop {
+        let mut chunk_header = [0; 8];
+        reader.read_exact(&mut chunk_header).map_err(read_err)?;
+        let chunk_id = &chunk_header[0..4];
+        let chunk_len = u32::from_le_bytes(chunk_header[4..8].try_into().unwrap());
+        position += 8;
+
+        match chunk_id {
+            b"fmt " => {
+                let mut fmt = [0; 16];
+                reader.read_exact(&mut fmt).map_err(read_err)?;
+                let format = u16::from_le_bytes([fmt[0], fmt[1]]);
+                let channels = u16::from_le_bytes([fmt[2], fmt[3]]);
+                let sample_rate = u32::from_le_bytes(fmt[4..8].try_into().unwrap());
+                let bits_per_sample = u16::from_le_bytes([fmt[14], fmt[15]]);
+
+                if (format, channels, sample_rate, bits_per_sample) != (PCM_FORMAT, 2, 44100, 16) {
+                    return Err(invalid("audio must be 16-bit stereo 44100Hz PCM"));
+                }
+                format_valid = true;
+
+                reader.seek(SeekFrom::Start(position)).map_err(read_err)?;
+            }
+            b"data" => {
+                if !format_valid {
+                    return Err(invalid("data chunk appears before fmt chunk"));
+                }
+
+                return Ok((position, chunk_len.into()));
+            }
+            _ => {}
+        }
+
+        // Chunks are padded to an even length
+        position += u64::from(chunk_len) + u64::from(chunk_len & 1);
+        reader.seek(SeekFrom::Start(position)).map_err(read_err)?;
+    }
+}
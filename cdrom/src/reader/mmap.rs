@@ -0,0 +1,52 @@
+//! Memory-mapped disc image files
+//!
+//! Mapping a disc image lets the OS page sectors in on demand, so opening a 700MB image neither
+//! reads the whole file into RAM nor makes a read syscall for every sector. Mapping can fail, e.g.
+//! on 32-bit targets without enough free address space or on filesystems that do not support it, in
+//! which case readers fall back to buffered file reads.
+
+use std::fs::File;
+use std::path::Path;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use memmap2::Mmap;
+
+/// Memory mapping is not available in WASM, where disc images are always read into memory.
+#[cfg(target_arch = "wasm32")]
+#[derive(Debug)]
+pub enum Mmap {}
+
+#[cfg(target_arch = "wasm32")]
+impl AsRef<[u8]> for Mmap {
+    fn as_ref(&self) -> &[u8] {
+        match *self {}
+    }
+}
+
+/// Map the given file into memory, or return None if the file cannot be mapped.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn map_file(file: &File, path: &Path) -> Option<Mmap> {
+    // SAFETY: The file is only ever read through the map, but the map is only sound as long as no
+    // other process truncates or modifies the file while it is mapped. If that happens, reads
+    // through the map are undefined behavior, and reading a page past the new end of a truncated
+    // file raises SIGBUS and kills the process; the buffered reader would only get an I/O error.
+    // This risk is accepted because disc images are not expected to change while a game is running
+    match unsafe { Mmap::map(file) } {
+        Ok(mmap) => {
+            log::debug!("Memory mapped '{}' ({} bytes)", path.display(), mmap.len());
+            Some(mmap)
+        }
+        Err(err) => {
+            log::warn!(
+                "Unable to memory map '{}', falling back to buffered reads: {err}",
+                path.display()
+            );
+            None
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn map_file(_file: &File, _path: &Path) -> Option<Mmap> {
+    None
+}
@@ -0,0 +1,356 @@
+//! Disc reads on a background I/O thread with a read-ahead sector cache
+//!
+//! A slow disk or network share can take longer than a frame to return a sector, which would stall
+//! the emulation thread. Sectors are instead read on a dedicated I/O thread that keeps reading ahead
+//! of the last requested sector, since discs are almost always read sequentially (e.g. while
+//! streaming FMV data). The emulation thread only blocks when it requests a sector that the I/O
+//! thread has not read yet, which normally only happens right after a seek.
+
+use crate::{CdRomError, CdRomResult, BYTES_PER_SECTOR};
+use std::collections::HashMap;
+use std::io;
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::thread;
+
+// 75 sectors is 1 second of data at 1x speed
+const READ_AHEAD_SECTORS: u32 = 75;
+
+// Request more sectors once the I/O thread is fewer than this many sectors ahead
+const READ_AHEAD_LOW_WATER: u32 = READ_AHEAD_SECTORS / 2;
+
+type Sector = Box<[u8; BYTES_PER_SECTOR as usize]>;
+
+pub trait SectorSource {
+    /// Read a 2352-byte sector from the given track.
+    ///
+    /// # Errors
+    ///
+    /// Propagates any I/O errors.
+    fn read_sector(
+        &mut self,
+        track_number: u8,
+        relative_sector_number: u32,
+        out: &mut [u8],
+    ) -> CdRomResult<()>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct SectorAddress {
+    track_number: u8,
+    sector_number: u32,
+}
+
+#[derive(Debug)]
+enum Request {
+    // Start reading from a new position, discarding any remaining read-ahead
+    Restart(SectorAddress),
+    // Continue reading sequentially up to the given sector (exclusive)
+    Extend(u32),
+}
+
+#[derive(Debug)]
+struct SectorData {
+    generation: u32,
+    address: SectorAddress,
+    result: CdRomResult<Sector>,
+}
+
+// Sectors that the I/O thread will send, in order, without any further requests
+#[derive(Debug, Clone, Copy)]
+struct Window {
+    track_number: u8,
+    next_sector: u32,
+    end_sector: u32,
+}
+
+impl Window {
+    fn contains(&self, address: SectorAddress) -> bool {
+        address.track_number == self.track_number
+            && (self.next_sector..self.end_sector).contains(&address.sector_number)
+    }
+}
+
+#[derive(Debug)]
+pub struct ReadAheadReader {
+    request_tx: Sender<Request>,
+    sector_rx: Receiver<SectorData>,
+    // Number of sectors stored in the image file for each track, indexed by track number - 1
+    track_sectors: Vec<u32>,
+    cache: HashMap<SectorAddress, CdRomResult<Sector>>,
+    window: Option<Window>,
+    // Incremented on every restart so that sectors from a discarded read-ahead can be ignored
+    generation: u32,
+}
+
+impl ReadAheadReader {
+    /// Move `source` to a new I/O thread and return a reader that requests sectors from it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I/O thread cannot be spawned.
+    pub fn spawn<S: SectorSource + Send + 'static>(
+        source: S,
+        track_sectors: Vec<u32>,
+    ) -> io::Result<Self> {
+        let (request_tx, request_rx) = mpsc::channel();
+        let (sector_tx, sector_rx) = mpsc::channel();
+
+        let thread_track_sectors = track_sectors.clone();
+        thread::Builder::new().name("cd-read-ahead".into()).spawn(move || {
+            run_io_thread(source, &thread_track_sectors, &request_rx, &sector_tx);
+        })?;
+
+        Ok(Self {
+            request_tx,
+            sector_rx,
+            track_sectors,
+            cache: HashMap::new(),
+            window: None,
+            generation: 0,
+        })
+    }
+
+    pub fn read_sector(
+        &mut self,
+        track_number: u8,
+        relative_sector_number: u32,
+        out: &mut [u8],
+    ) -> CdRomResult<()> {
+        let address = SectorAddress { track_number, sector_number: relative_sector_number };
+
+        self.receive_ready_sectors();
+
+        if !self.cache.contains_key(&address)
+            && !self.window.is_some_and(|window| window.contains(address))
+        {
+            self.restart(address)?;
+        }
+
+        let sector = self.wait_for_sector(address)?;
+        out[..BYTES_PER_SECTOR as usize].copy_from_slice(sector.as_slice());
+
+        // Sectors before this one are unlikely to be read again soon
+        self.cache.retain(|cached, _| {
+            cached.track_number == track_number && cached.sector_number > relative_sector_number
+        });
+
+        self.maybe_extend_window(address)?;
+
+        Ok(())
+    }
+
+    fn receive_ready_sectors(&mut self) {
+        while let Ok(data) = self.sector_rx.try_recv() {
+            self.insert_sector(data);
+        }
+    }
+
+    fn wait_for_sector(&mut self, address: SectorAddress) -> CdRomResult<Sector> {
+        loop {
+            if let Some(result) = self.cache.remove(&address) {
+                return result;
+            }
+
+            let data = self.sector_rx.recv().map_err(|_| io_thread_exited())?;
+            self.insert_sector(data);
+        }
+    }
+
+    fn insert_sector(&mut self, data: SectorData) {
+        if data.generation != self.generation {
+            return;
+        }
+
+        if let Some(window) = &mut self.window {
+            window.next_sector = data.address.sector_number + 1;
+        }
+        self.cache.insert(data.address, data.result);
+    }
+
+    fn restart(&mut self, address: SectorAddress) -> CdRomResult<()> {
+        self.generation = self.generation.wrapping_add(1);
+        self.window = Some(Window {
+            track_number: address.track_number,
+            next_sector: address.sector_number,
+            end_sector: self.read_ahead_end(address),
+        });
+        self.cache.clear();
+
+        self.request_tx.send(Request::Restart(address)).map_err(|_| io_thread_exited())
+    }
+
+    fn maybe_extend_window(&mut self, address: SectorAddress) -> CdRomResult<()> {
+        let end_sector = self.read_ahead_end(address);
+        let Some(window) = &mut self.window else { return Ok(()) };
+
+        // No need to extend if the window is still far enough ahead or if it already ends at the
+        // end of the track
+        if window.track_number != address.track_number
+            || window.end_sector - address.sector_number >= READ_AHEAD_LOW_WATER
+            || end_sector <= window.end_sector
+        {
+            return Ok(());
+        }
+
+        window.end_sector = end_sector;
+        self.request_tx.send(Request::Extend(end_sector)).map_err(|_| io_thread_exited())
+    }
+
+    fn read_ahead_end(&self, address: SectorAddress) -> u32 {
+        read_ahead_end(&self.track_sectors, address)
+    }
+}
+
+fn read_ahead_end(track_sectors: &[u32], address: SectorAddress) -> u32 {
+    let track_len = track_sectors.get((address.track_number - 1) as usize).copied().unwrap_or(0);
+    // Always include the requested sector so that out-of-range reads return the source's error
+    address
+        .sector_number
+        .saturating_add(READ_AHEAD_SECTORS)
+        .min(track_len)
+        .max(address.sector_number + 1)
+}
+
+fn io_thread_exited() -> CdRomError {
+    CdRomError::DiscReadIo(io::Error::new(
+        io::ErrorKind::BrokenPipe,
+        "Disc read-ahead thread exited unexpectedly",
+    ))
+}
+
+fn run_io_thread<S: SectorSource>(
+    mut source: S,
+    track_sectors: &[u32],
+    request_rx: &Receiver<Request>,
+    sector_tx: &Sender<SectorData>,
+) {
+    let mut generation = 0_u32;
+    let mut window: Option<Window> = None;
+
+    loop {
+        let has_pending_reads = window.is_some_and(|window| window.next_sector < window.end_sector);
+
+        // Only block waiting for a request if there is nothing left to read ahead
+        let request = if has_pending_reads {
+            match request_rx.try_recv() {
+                Ok(request) => Some(request),
+                Err(TryRecvError::Empty) => None,
+                Err(TryRecvError::Disconnected) => return,
+            }
+        } else {
+            match request_rx.recv() {
+                Ok(request) => Some(request),
+                Err(_) => return,
+            }
+        };
+
+        match request {
+            Some(Request::Restart(address)) => {
+                generation = generation.wrapping_add(1);
+                window = Some(Window {
+                    track_number: address.track_number,
+                    next_sector: address.sector_number,
+                    end_sector: read_ahead_end(track_sectors, address),
+                });
+            }
+            Some(Request::Extend(end_sector)) => {
+                if let Some(window) = &mut window {
+                    window.end_sector = window.end_sector.max(end_sector);
+                }
+            }
+            None => {}
+        }
+
+        let Some(window) = &mut window else { continue };
+        if window.next_sector >= window.end_sector {
+            continue;
+        }
+
+        let address =
+            SectorAddress { track_number: window.track_number, sector_number: window.next_sector };
+        let mut sector: Sector = Box::new([0; BYTES_PER_SECTOR as usize]);
+        let result = source
+            .read_sector(address.track_number, address.sector_number, sector.as_mut_slice())
+            .map(|()| sector);
+        window.next_sector += 1;
+
+        if sector_tx.send(SectorData { generation, address, result }).is_err() {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    // Fills each sector with its sector number and counts reads
+    struct TestSource {
+        reads: Arc<AtomicU32>,
+    }
+
+    impl SectorSource for TestSource {
+        fn read_sector(
+            &mut self,
+            track_number: u8,
+            relative_sector_number: u32,
+            out: &mut [u8],
+        ) -> CdRomResult<()> {
+            self.reads.fetch_add(1, Ordering::Relaxed);
+            if relative_sector_number >= 200 {
+                return Err(CdRomError::DiscReadIo(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "past end of track",
+                )));
+            }
+
+            out.fill(track_number.wrapping_add(relative_sector_number as u8));
+            Ok(())
+        }
+    }
+
+    fn new_reader() -> (ReadAheadReader, Arc<AtomicU32>) {
+        let reads = Arc::new(AtomicU32::new(0));
+        let source = TestSource { reads: Arc::clone(&reads) };
+        (ReadAheadReader::spawn(source, vec![200, 200]).unwrap(), reads)
+    }
+
+    fn read(reader: &mut ReadAheadReader, track_number: u8, sector_number: u32) -> CdRomResult<u8> {
+        let mut out = [0; BYTES_PER_SECTOR as usize];
+        reader.read_sector(track_number, sector_number, &mut out)?;
+        Ok(out[0])
+    }
+
+    #[test]
+    fn sequential_reads() {
+        let (mut reader, reads) = new_reader();
+
+        for sector_number in 0..200 {
+            assert_eq!(read(&mut reader, 1, sector_number).unwrap(), 1 + sector_number as u8);
+        }
+
+        // Read-ahead should never go past the end of the track
+        assert_eq!(reads.load(Ordering::Relaxed), 200);
+    }
+
+    #[test]
+    fn seeks() {
+        let (mut reader, _) = new_reader();
+
+        assert_eq!(read(&mut reader, 1, 10).unwrap(), 11);
+        assert_eq!(read(&mut reader, 2, 150).unwrap(), 152);
+        assert_eq!(read(&mut reader, 2, 5).unwrap(), 7);
+        assert_eq!(read(&mut reader, 2, 5).unwrap(), 7);
+        assert_eq!(read(&mut reader, 1, 11).unwrap(), 12);
+    }
+
+    #[test]
+    fn errors_are_returned() {
+        let (mut reader, _) = new_reader();
+
+        assert!(read(&mut reader, 1, 200).is_err());
+        assert_eq!(read(&mut reader, 1, 199).unwrap(), 1 + 199_u32 as u8);
+    }
+}
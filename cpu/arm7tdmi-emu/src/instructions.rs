@@ -0,0 +1,382 @@
+//! ARM7TDMI instruction decoding and execution
+//!
+//! Each call executes one whole instruction. Opcodes are decoded on every execution rather than
+//! through lookup tables, and the pipeline is modeled only through the value read from R15 and
+//! the extra code fetches charged after a branch.
+
+mod arm;
+mod thumb;
+
+use crate::traits::{BusInterface, MemoryCycle};
+use crate::{Arm7Tdmi, CpuMode, CpuState, SWI_VECTOR, UNDEFINED_VECTOR};
+use jgenesis_common::num::{GetBit, SignBit};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ShiftType {
+    Lsl,
+    Lsr,
+    Asr,
+    Ror,
+}
+
+impl ShiftType {
+    fn from_bits(bits: u32) -> Self {
+        match bits & 3 {
+            0 => Self::Lsl,
+            1 => Self::Lsr,
+            2 => Self::Asr,
+            3 => Self::Ror,
+            _ => unreachable!("value & 3 is always <= 3"),
+        }
+    }
+}
+
+pub(crate) fn execute<B: BusInterface>(cpu: &mut Arm7Tdmi, bus: &mut B) {
+    let pc = cpu.registers.r[15];
+    let cycle = if cpu.sequential_fetch { MemoryCycle::S } else { MemoryCycle::N };
+    cpu.sequential_fetch = true;
+    cpu.branched = false;
+
+    match cpu.registers.cpsr.state {
+        CpuState::Arm => {
+            let opcode = bus.fetch_opcode_word(pc, cycle);
+            cpu.registers.r[15] = pc.wrapping_add(8);
+            arm::execute(cpu, bus, opcode);
+
+            if !cpu.branched {
+                cpu.registers.r[15] = pc.wrapping_add(4);
+            }
+        }
+        CpuState::Thumb => {
+            let opcode = bus.fetch_opcode_halfword(pc, cycle);
+            cpu.registers.r[15] = pc.wrapping_add(4);
+            thumb::execute(cpu, bus, opcode);
+
+            if !cpu.branched {
+                cpu.registers.r[15] = pc.wrapping_add(2);
+            }
+        }
+    }
+
+    if cpu.branched {
+        cpu.refill_pipeline(bus);
+    }
+}
+
+impl Arm7Tdmi {
+    fn check_condition(&self, condition: u32) -> bool {
+        let cpsr = self.registers.cpsr;
+        match condition & 0xF {
+            0x0 => cpsr.zero,
+            0x1 => !cpsr.zero,
+            0x2 => cpsr.carry,
+            0x3 => !cpsr.carry,
+            0x4 => cpsr.sign,
+            0x5 => !cpsr.sign,
+            0x6 => cpsr.overflow,
+            0x7 => !cpsr.overflow,
+            0x8 => cpsr.carry && !cpsr.zero,
+            0x9 => !cpsr.carry || cpsr.zero,
+            0xA => cpsr.sign == cpsr.overflow,
+            0xB => cpsr.sign != cpsr.overflow,
+            0xC => !cpsr.zero && cpsr.sign == cpsr.overflow,
+            0xD => cpsr.zero || cpsr.sign != cpsr.overflow,
+            0xE => true,
+            // The NV condition is reserved on ARMv4
+            _ => false,
+        }
+    }
+
+    fn write_register(&mut self, r: u32, value: u32) {
+        if r == 15 {
+            self.write_pc(value);
+        } else {
+            self.registers.r[r as usize] = value;
+        }
+    }
+
+    fn write_pc(&mut self, address: u32) {
+        let mask = match self.registers.cpsr.state {
+            CpuState::Arm => !3,
+            CpuState::Thumb => !1,
+        };
+        self.registers.r[15] = address & mask;
+        self.branched = true;
+    }
+
+    // BX: bit 0 of the target address selects the new state
+    fn branch_exchange(&mut self, address: u32) {
+        self.registers.cpsr.state = if address.bit(0) { CpuState::Thumb } else { CpuState::Arm };
+        self.write_pc(address);
+    }
+
+    fn set_nz(&mut self, value: u32) {
+        self.registers.cpsr.sign = value.sign_bit();
+        self.registers.cpsr.zero = value == 0;
+    }
+
+    // Returns (result, carry, overflow)
+    fn add_with_carry(a: u32, b: u32, carry_in: bool) -> (u32, bool, bool) {
+        let (partial, carry1) = a.overflowing_add(b);
+        let (result, carry2) = partial.overflowing_add(carry_in.into());
+        let overflow = !(a ^ b).sign_bit() && (a ^ result).sign_bit();
+        (result, carry1 || carry2, overflow)
+    }
+
+    fn add_set_flags(&mut self, a: u32, b: u32, carry_in: bool) -> u32 {
+        let (result, carry, overflow) = Self::add_with_carry(a, b, carry_in);
+        self.set_nz(result);
+        self.registers.cpsr.carry = carry;
+        self.registers.cpsr.overflow = overflow;
+        result
+    }
+
+    // ARM subtraction sets carry when no borrow occurs, which is equivalent to adding the
+    // complement
+    fn sub_set_flags(&mut self, a: u32, b: u32, carry_in: bool) -> u32 {
+        self.add_set_flags(a, !b, carry_in)
+    }
+
+    // Returns (result, carry out). `immediate` selects the special encodings used by
+    // immediate-amount shifts, where an amount of 0 means LSR #32, ASR #32, or RRX
+    fn shift(
+        &self,
+        shift_type: ShiftType,
+        value: u32,
+        amount: u32,
+        immediate: bool,
+    ) -> (u32, bool) {
+        let carry = self.registers.cpsr.carry;
+
+        match shift_type {
+            ShiftType::Lsl => match amount {
+                0 => (value, carry),
+                1..=31 => (value << amount, value.bit((32 - amount) as u8)),
+                32 => (0, value.bit(0)),
+                _ => (0, false),
+            },
+            ShiftType::Lsr => match amount {
+                0 if immediate => (0, value.sign_bit()),
+                0 => (value, carry),
+                1..=31 => (value >> amount, value.bit((amount - 1) as u8)),
+                32 => (0, value.sign_bit()),
+                _ => (0, false),
+            },
+            ShiftType::Asr => match amount {
+                0 if immediate => {
+                    let result = ((value as i32) >> 31) as u32;
+                    (result, value.sign_bit())
+                }
+                0 => (value, carry),
+                1..=31 => (((value as i32) >> amount) as u32, value.bit((amount - 1) as u8)),
+                _ => (((value as i32) >> 31) as u32, value.sign_bit()),
+            },
+            ShiftType::Ror => match amount {
+                // RRX
+                0 if immediate => ((u32::from(carry) << 31) | (value >> 1), value.bit(0)),
+                0 => (value, carry),
+                _ => {
+                    let amount = amount & 31;
+                    if amount == 0 {
+                        (value, value.sign_bit())
+                    } else {
+                        (value.rotate_right(amount), value.bit((amount - 1) as u8))
+                    }
+                }
+            },
+        }
+    }
+
+    fn restore_cpsr_from_spsr(&mut self) {
+        let Some(spsr) = self.registers.spsr() else {
+            log::warn!(
+                "Attempted to restore CPSR from SPSR in mode {:?}",
+                self.registers.cpsr.mode
+            );
+            return;
+        };
+
+        self.registers.change_mode(spsr.mode);
+        self.registers.cpsr = spsr;
+    }
+
+    fn write_psr(&mut self, spsr: bool, value: u32, write_flags: bool, write_control: bool) {
+        let privileged = self.registers.cpsr.mode.is_privileged();
+        let mut mask = 0;
+        if write_flags {
+            mask |= 0xF000_0000;
+        }
+        if write_control && privileged {
+            mask |= 0x0000_00FF;
+        }
+
+        if spsr {
+            let Some(spsr) = self.registers.spsr_mut() else { return };
+            *spsr = ((u32::from(*spsr) & !mask) | (value & mask)).into();
+        } else {
+            let new_cpsr: crate::StatusRegister =
+                ((u32::from(self.registers.cpsr) & !mask) | (value & mask)).into();
+            if new_cpsr.state != self.registers.cpsr.state {
+                log::warn!("MSR attempted to change CPU state; ignoring");
+            }
+            self.registers.change_mode(new_cpsr.mode);
+            self.registers.cpsr =
+                crate::StatusRegister { state: self.registers.cpsr.state, ..new_cpsr };
+        }
+    }
+
+    fn software_interrupt(&mut self) {
+        // R15 is 2 instructions ahead; return to the instruction after the SWI
+        let return_address = self.next_instruction_address();
+        self.enter_exception(CpuMode::Supervisor, SWI_VECTOR, return_address);
+    }
+
+    fn undefined_instruction(&mut self, opcode: u32) {
+        log::warn!(
+            "Undefined instruction {opcode:08X} at {:08X}",
+            self.registers.r[15].wrapping_sub(self.instruction_len() * 2)
+        );
+
+        let return_address = self.next_instruction_address();
+        self.enter_exception(CpuMode::Undefined, UNDEFINED_VECTOR, return_address);
+    }
+
+    fn instruction_len(&self) -> u32 {
+        match self.registers.cpsr.state {
+            CpuState::Arm => 4,
+            CpuState::Thumb => 2,
+        }
+    }
+
+    fn next_instruction_address(&self) -> u32 {
+        self.registers.r[15].wrapping_sub(self.instruction_len())
+    }
+
+    // Data accesses make the next opcode fetch non-sequential
+    fn read_word<B: BusInterface>(&mut self, bus: &mut B, address: u32, cycle: MemoryCycle) -> u32 {
+        self.sequential_fetch = false;
+        bus.read_word(address & !3, cycle)
+    }
+
+    // Misaligned word loads rotate the aligned word so that the addressed byte is in the LSB
+    fn read_word_rotated<B: BusInterface>(
+        &mut self,
+        bus: &mut B,
+        address: u32,
+        cycle: MemoryCycle,
+    ) -> u32 {
+        self.read_word(bus, address, cycle).rotate_right(8 * (address & 3))
+    }
+
+    // Misaligned halfword loads rotate the aligned halfword by 8 bits
+    fn read_halfword_rotated<B: BusInterface>(
+        &mut self,
+        bus: &mut B,
+        address: u32,
+        cycle: MemoryCycle,
+    ) -> u32 {
+        self.sequential_fetch = false;
+        let value = u32::from(bus.read_halfword(address & !1, cycle));
+        value.rotate_right(8 * (address & 1))
+    }
+
+    // Misaligned signed halfword loads behave like signed byte loads
+    fn read_signed_halfword<B: BusInterface>(
+        &mut self,
+        bus: &mut B,
+        address: u32,
+        cycle: MemoryCycle,
+    ) -> u32 {
+        if address.bit(0) {
+            self.read_signed_byte(bus, address, cycle)
+        } else {
+            self.sequential_fetch = false;
+            bus.read_halfword(address, cycle) as i16 as u32
+        }
+    }
+
+    fn read_byte<B: BusInterface>(&mut self, bus: &mut B, address: u32, cycle: MemoryCycle) -> u32 {
+        self.sequential_fetch = false;
+        bus.read_byte(address, cycle).into()
+    }
+
+    fn read_signed_byte<B: BusInterface>(
+        &mut self,
+        bus: &mut B,
+        address: u32,
+        cycle: MemoryCycle,
+    ) -> u32 {
+        self.sequential_fetch = false;
+        bus.read_byte(address, cycle) as i8 as u32
+    }
+
+    fn write_word<B: BusInterface>(
+        &mut self,
+        bus: &mut B,
+        address: u32,
+        value: u32,
+        cycle: MemoryCycle,
+    ) {
+        self.sequential_fetch = false;
+        bus.write_word(address & !3, value, cycle);
+    }
+
+    fn write_halfword<B: BusInterface>(
+        &mut self,
+        bus: &mut B,
+        address: u32,
+        value: u32,
+        cycle: MemoryCycle,
+    ) {
+        self.sequential_fetch = false;
+        bus.write_halfword(address & !1, value as u16, cycle);
+    }
+
+    fn write_byte<B: BusInterface>(
+        &mut self,
+        bus: &mut B,
+        address: u32,
+        value: u32,
+        cycle: MemoryCycle,
+    ) {
+        self.sequential_fetch = false;
+        bus.write_byte(address, value as u8, cycle);
+    }
+}
+
+// Multiplies take 1-4 internal cycles depending on how many of the multiplier's upper bytes are
+// significant. Signed multiplies terminate early on all-ones bytes as well as all-zeroes bytes
+fn multiply_internal_cycles(multiplier: u32, signed: bool) -> u32 {
+    for (cycles, shift) in [(1, 8), (2, 16), (3, 24)] {
+        let upper = multiplier >> shift;
+        if upper == 0 || (signed && upper == u32::MAX >> shift) {
+            return cycles;
+        }
+    }
+
+    4
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn immediate_shift_encodings() {
+        let cpu = Arm7Tdmi::new();
+
+        assert_eq!(cpu.shift(ShiftType::Lsr, 0x8000_0000, 0, true), (0, true));
+        assert_eq!(cpu.shift(ShiftType::Asr, 0x8000_0000, 0, true), (0xFFFF_FFFF, true));
+        assert_eq!(cpu.shift(ShiftType::Ror, 0x0000_0003, 0, true), (0x0000_0001, true));
+        assert_eq!(cpu.shift(ShiftType::Lsl, 0x0000_0001, 32, false), (0, true));
+        assert_eq!(cpu.shift(ShiftType::Ror, 0x8000_0001, 32, false), (0x8000_0001, true));
+    }
+
+    #[test]
+    fn multiply_cycles() {
+        assert_eq!(multiply_internal_cycles(0x0000_00FF, false), 1);
+        assert_eq!(multiply_internal_cycles(0xFFFF_FF00, true), 1);
+        assert_eq!(multiply_internal_cycles(0xFFFF_FF00, false), 4);
+        assert_eq!(multiply_internal_cycles(0x00FF_0000, false), 3);
+    }
+}
@@ -0,0 +1,499 @@
+//! 32-bit ARM instruction set
+
+use crate::instructions::{multiply_internal_cycles, ShiftType};
+use crate::traits::{BusInterface, MemoryCycle};
+use crate::Arm7Tdmi;
+use jgenesis_common::num::GetBit;
+
+pub(super) fn execute<B: BusInterface>(cpu: &mut Arm7Tdmi, bus: &mut B, opcode: u32) {
+    if !cpu.check_condition(opcode >> 28) {
+        return;
+    }
+
+    match (opcode >> 25) & 7 {
+        0b000 => {
+            if opcode & 0x0FFF_FFF0 == 0x012F_FF10 {
+                branch_exchange(cpu, opcode);
+            } else if opcode & 0x0FC0_00F0 == 0x0000_0090 {
+                multiply(cpu, bus, opcode);
+            } else if opcode & 0x0F80_00F0 == 0x0080_0090 {
+                multiply_long(cpu, bus, opcode);
+            } else if opcode & 0x0FB0_0FF0 == 0x0100_0090 {
+                swap(cpu, bus, opcode);
+            } else if opcode & 0x0E00_0090 == 0x0000_0090 {
+                halfword_transfer(cpu, bus, opcode);
+            } else if opcode & 0x0FBF_0FFF == 0x010F_0000 {
+                mrs(cpu, opcode);
+            } else if opcode & 0x0DB0_F000 == 0x0120_F000 {
+                msr(cpu, opcode);
+            } else {
+                data_processing(cpu, bus, opcode);
+            }
+        }
+        0b001 => {
+            if opcode & 0x0DB0_F000 == 0x0120_F000 {
+                msr(cpu, opcode);
+            } else {
+                data_processing(cpu, bus, opcode);
+            }
+        }
+        0b010 => single_data_transfer(cpu, bus, opcode),
+        0b011 => {
+            if opcode.bit(4) {
+                cpu.undefined_instruction(opcode);
+            } else {
+                single_data_transfer(cpu, bus, opcode);
+            }
+        }
+        0b100 => block_data_transfer(cpu, bus, opcode),
+        0b101 => branch(cpu, opcode),
+        0b110 => cpu.undefined_instruction(opcode),
+        0b111 => {
+            if opcode.bit(24) {
+                cpu.software_interrupt();
+            } else {
+                cpu.undefined_instruction(opcode);
+            }
+        }
+        _ => unreachable!("value & 7 is always <= 7"),
+    }
+}
+
+fn register_field(opcode: u32, lowest_bit: u8) -> u32 {
+    (opcode >> lowest_bit) & 0xF
+}
+
+// B / BL
+fn branch(cpu: &mut Arm7Tdmi, opcode: u32) {
+    let offset = (((opcode & 0x00FF_FFFF) << 8) as i32) >> 6;
+
+    if opcode.bit(24) {
+        cpu.registers.r[14] = cpu.next_instruction_address();
+    }
+
+    let pc = cpu.registers.r[15];
+    cpu.write_pc(pc.wrapping_add(offset as u32));
+}
+
+// BX
+fn branch_exchange(cpu: &mut Arm7Tdmi, opcode: u32) {
+    let rm = register_field(opcode, 0);
+    cpu.branch_exchange(cpu.registers.r[rm as usize]);
+}
+
+// Returns (operand, shifter carry out)
+fn shifted_register_operand<B: BusInterface>(
+    cpu: &mut Arm7Tdmi,
+    bus: &mut B,
+    opcode: u32,
+) -> (u32, bool) {
+    let rm = register_field(opcode, 0);
+    let shift_type = ShiftType::from_bits(opcode >> 5);
+
+    if opcode.bit(4) {
+        // Shift by register; this takes an extra internal cycle, during which the PC advances
+        // another 4 bytes
+        bus.internal_cycles(1);
+        cpu.sequential_fetch = false;
+
+        let rs = register_field(opcode, 8);
+        let amount = cpu.registers.r[rs as usize] & 0xFF;
+        let value = if rm == 15 {
+            cpu.registers.r[15].wrapping_add(4)
+        } else {
+            cpu.registers.r[rm as usize]
+        };
+        cpu.shift(shift_type, value, amount, false)
+    } else {
+        let amount = (opcode >> 7) & 0x1F;
+        cpu.shift(shift_type, cpu.registers.r[rm as usize], amount, true)
+    }
+}
+
+fn rotated_immediate_operand(cpu: &Arm7Tdmi, opcode: u32) -> (u32, bool) {
+    let rotation = 2 * ((opcode >> 8) & 0xF);
+    let value = (opcode & 0xFF).rotate_right(rotation);
+    let carry = if rotation == 0 { cpu.registers.cpsr.carry } else { value.bit(31) };
+    (value, carry)
+}
+
+// AND, EOR, SUB, RSB, ADD, ADC, SBC, RSC, TST, TEQ, CMP, CMN, ORR, MOV, BIC, MVN
+fn data_processing<B: BusInterface>(cpu: &mut Arm7Tdmi, bus: &mut B, opcode: u32) {
+    let set_flags = opcode.bit(20);
+    let rn = register_field(opcode, 16);
+    let rd = register_field(opcode, 12);
+
+    let (operand2, shifter_carry) = if opcode.bit(25) {
+        rotated_immediate_operand(cpu, opcode)
+    } else {
+        shifted_register_operand(cpu, bus, opcode)
+    };
+
+    // With a register-specified shift, R15 reads 12 bytes ahead instead of 8
+    let operand1 = if rn == 15 && !opcode.bit(25) && opcode.bit(4) {
+        cpu.registers.r[15].wrapping_add(4)
+    } else {
+        cpu.registers.r[rn as usize]
+    };
+
+    // Writing R15 with S set restores CPSR from SPSR, so flags are not updated normally
+    let update_flags = set_flags && rd != 15;
+    let carry = cpu.registers.cpsr.carry;
+
+    let operation = (opcode >> 21) & 0xF;
+    let result = match operation {
+        // AND / TST
+        0x0 | 0x8 => logical(cpu, operand1 & operand2, shifter_carry, update_flags),
+        // EOR / TEQ
+        0x1 | 0x9 => logical(cpu, operand1 ^ operand2, shifter_carry, update_flags),
+        // SUB / CMP
+        0x2 | 0xA => arithmetic(cpu, operand1, !operand2, true, update_flags),
+        // RSB
+        0x3 => arithmetic(cpu, operand2, !operand1, true, update_flags),
+        // ADD / CMN
+        0x4 | 0xB => arithmetic(cpu, operand1, operand2, false, update_flags),
+        // ADC
+        0x5 => arithmetic(cpu, operand1, operand2, carry, update_flags),
+        // SBC
+        0x6 => arithmetic(cpu, operand1, !operand2, carry, update_flags),
+        // RSC
+        0x7 => arithmetic(cpu, operand2, !operand1, carry, update_flags),
+        // ORR
+        0xC => logical(cpu, operand1 | operand2, shifter_carry, update_flags),
+        // MOV
+        0xD => logical(cpu, operand2, shifter_carry, update_flags),
+        // BIC
+        0xE => logical(cpu, operand1 & !operand2, shifter_carry, update_flags),
+        // MVN
+        0xF => logical(cpu, !operand2, shifter_carry, update_flags),
+        _ => unreachable!("value & 0xF is always <= 0xF"),
+    };
+
+    // TST, TEQ, CMP, and CMN do not write a result
+    if (0x8..0xC).contains(&operation) {
+        if rd == 15 && set_flags {
+            // Legacy 26-bit TSTP/TEQP/CMPP/CMNP; restore CPSR without branching
+            cpu.restore_cpsr_from_spsr();
+        }
+        return;
+    }
+
+    if rd == 15 {
+        if set_flags {
+            cpu.restore_cpsr_from_spsr();
+        }
+        cpu.write_pc(result);
+    } else {
+        cpu.registers.r[rd as usize] = result;
+    }
+}
+
+fn logical(cpu: &mut Arm7Tdmi, result: u32, shifter_carry: bool, set_flags: bool) -> u32 {
+    if set_flags {
+        cpu.set_nz(result);
+        cpu.registers.cpsr.carry = shifter_carry;
+    }
+    result
+}
+
+fn arithmetic(cpu: &mut Arm7Tdmi, a: u32, b: u32, carry_in: bool, set_flags: bool) -> u32 {
+    if set_flags {
+        cpu.add_set_flags(a, b, carry_in)
+    } else {
+        Arm7Tdmi::add_with_carry(a, b, carry_in).0
+    }
+}
+
+// MRS
+fn mrs(cpu: &mut Arm7Tdmi, opcode: u32) {
+    let rd = register_field(opcode, 12);
+    let psr = if opcode.bit(22) {
+        cpu.registers.spsr().unwrap_or(cpu.registers.cpsr)
+    } else {
+        cpu.registers.cpsr
+    };
+    cpu.registers.r[rd as usize] = psr.into();
+}
+
+// MSR
+fn msr(cpu: &mut Arm7Tdmi, opcode: u32) {
+    let value = if opcode.bit(25) {
+        rotated_immediate_operand(cpu, opcode).0
+    } else {
+        cpu.registers.r[register_field(opcode, 0) as usize]
+    };
+
+    cpu.write_psr(opcode.bit(22), value, opcode.bit(19), opcode.bit(16));
+}
+
+// MUL / MLA
+fn multiply<B: BusInterface>(cpu: &mut Arm7Tdmi, bus: &mut B, opcode: u32) {
+    let rd = register_field(opcode, 16);
+    let rn = register_field(opcode, 12);
+    let rs = register_field(opcode, 8);
+    let rm = register_field(opcode, 0);
+    let accumulate = opcode.bit(21);
+
+    let multiplier = cpu.registers.r[rs as usize];
+    let mut result = cpu.registers.r[rm as usize].wrapping_mul(multiplier);
+    let mut internal_cycles = multiply_internal_cycles(multiplier, true);
+    if accumulate {
+        result = result.wrapping_add(cpu.registers.r[rn as usize]);
+        internal_cycles += 1;
+    }
+
+    bus.internal_cycles(internal_cycles);
+    cpu.sequential_fetch = false;
+
+    if opcode.bit(20) {
+        cpu.set_nz(result);
+    }
+    cpu.write_register(rd, result);
+}
+
+// UMULL / UMLAL / SMULL / SMLAL
+fn multiply_long<B: BusInterface>(cpu: &mut Arm7Tdmi, bus: &mut B, opcode: u32) {
+    let rd_hi = register_field(opcode, 16);
+    let rd_lo = register_field(opcode, 12);
+    let rs = register_field(opcode, 8);
+    let rm = register_field(opcode, 0);
+    let signed = opcode.bit(22);
+    let accumulate = opcode.bit(21);
+
+    let multiplier = cpu.registers.r[rs as usize];
+    let multiplicand = cpu.registers.r[rm as usize];
+    let mut result = if signed {
+        (i64::from(multiplicand as i32) * i64::from(multiplier as i32)) as u64
+    } else {
+        u64::from(multiplicand) * u64::from(multiplier)
+    };
+
+    let mut internal_cycles = multiply_internal_cycles(multiplier, signed) + 1;
+    if accumulate {
+        let accumulator = (u64::from(cpu.registers.r[rd_hi as usize]) << 32)
+            | u64::from(cpu.registers.r[rd_lo as usize]);
+        result = result.wrapping_add(accumulator);
+        internal_cycles += 1;
+    }
+
+    bus.internal_cycles(internal_cycles);
+    cpu.sequential_fetch = false;
+
+    if opcode.bit(20) {
+        cpu.registers.cpsr.sign = result.bit(63);
+        cpu.registers.cpsr.zero = result == 0;
+    }
+    cpu.write_register(rd_lo, result as u32);
+    cpu.write_register(rd_hi, (result >> 32) as u32);
+}
+
+// SWP / SWPB
+fn swap<B: BusInterface>(cpu: &mut Arm7Tdmi, bus: &mut B, opcode: u32) {
+    let rn = register_field(opcode, 16);
+    let rd = register_field(opcode, 12);
+    let rm = register_field(opcode, 0);
+    let address = cpu.registers.r[rn as usize];
+    let source = cpu.registers.r[rm as usize];
+
+    let value = if opcode.bit(22) {
+        let value = cpu.read_byte(bus, address, MemoryCycle::N);
+        cpu.write_byte(bus, address, source, MemoryCycle::N);
+        value
+    } else {
+        let value = cpu.read_word_rotated(bus, address, MemoryCycle::N);
+        cpu.write_word(bus, address, source, MemoryCycle::N);
+        value
+    };
+
+    bus.internal_cycles(1);
+    cpu.write_register(rd, value);
+}
+
+// Computes (transfer address, written back address) for single data transfers
+fn transfer_addresses(opcode: u32, base: u32, offset: u32) -> (u32, u32) {
+    let offset_address =
+        if opcode.bit(23) { base.wrapping_add(offset) } else { base.wrapping_sub(offset) };
+    let pre_indexed = opcode.bit(24);
+    if pre_indexed { (offset_address, offset_address) } else { (base, offset_address) }
+}
+
+fn writes_back(opcode: u32) -> bool {
+    // Post-indexed transfers always write back
+    !opcode.bit(24) || opcode.bit(21)
+}
+
+// LDR / STR / LDRB / STRB
+fn single_data_transfer<B: BusInterface>(cpu: &mut Arm7Tdmi, bus: &mut B, opcode: u32) {
+    let rn = register_field(opcode, 16);
+    let rd = register_field(opcode, 12);
+    let load = opcode.bit(20);
+    let byte = opcode.bit(22);
+
+    let offset = if opcode.bit(25) {
+        // Register offsets can only be shifted by an immediate amount
+        let rm = register_field(opcode, 0);
+        let shift_type = ShiftType::from_bits(opcode >> 5);
+        let amount = (opcode >> 7) & 0x1F;
+        cpu.shift(shift_type, cpu.registers.r[rm as usize], amount, true).0
+    } else {
+        opcode & 0xFFF
+    };
+
+    let base = cpu.registers.r[rn as usize];
+    let (address, written_back) = transfer_addresses(opcode, base, offset);
+
+    if load {
+        let value = if byte {
+            cpu.read_byte(bus, address, MemoryCycle::N)
+        } else {
+            cpu.read_word_rotated(bus, address, MemoryCycle::N)
+        };
+        bus.internal_cycles(1);
+
+        if writes_back(opcode) {
+            cpu.write_register(rn, written_back);
+        }
+        // The loaded value takes priority over the written back base if Rd == Rn
+        cpu.write_register(rd, value);
+    } else {
+        // Stores of R15 store the instruction address plus 12
+        let value = if rd == 15 {
+            cpu.registers.r[15].wrapping_add(4)
+        } else {
+            cpu.registers.r[rd as usize]
+        };
+
+        if byte {
+            cpu.write_byte(bus, address, value, MemoryCycle::N);
+        } else {
+            cpu.write_word(bus, address, value, MemoryCycle::N);
+        }
+
+        if writes_back(opcode) {
+            cpu.write_register(rn, written_back);
+        }
+    }
+}
+
+// LDRH / STRH / LDRSB / LDRSH
+fn halfword_transfer<B: BusInterface>(cpu: &mut Arm7Tdmi, bus: &mut B, opcode: u32) {
+    let rn = register_field(opcode, 16);
+    let rd = register_field(opcode, 12);
+    let load = opcode.bit(20);
+
+    let offset = if opcode.bit(22) {
+        ((opcode >> 4) & 0xF0) | (opcode & 0xF)
+    } else {
+        cpu.registers.r[register_field(opcode, 0) as usize]
+    };
+
+    let base = cpu.registers.r[rn as usize];
+    let (address, written_back) = transfer_addresses(opcode, base, offset);
+
+    if load {
+        let value = match (opcode >> 5) & 3 {
+            1 => cpu.read_halfword_rotated(bus, address, MemoryCycle::N),
+            2 => cpu.read_signed_byte(bus, address, MemoryCycle::N),
+            3 => cpu.read_signed_halfword(bus, address, MemoryCycle::N),
+            _ => unreachable!("SWP and multiplies are decoded before halfword transfers"),
+        };
+        bus.internal_cycles(1);
+
+        if writes_back(opcode) {
+            cpu.write_register(rn, written_back);
+        }
+        cpu.write_register(rd, value);
+    } else {
+        if (opcode >> 5) & 3 != 1 {
+            // LDRD/STRD encodings from ARMv5TE; these do nothing on the ARM7TDMI
+            log::warn!("Unsupported halfword transfer opcode {opcode:08X}");
+            return;
+        }
+
+        let value = if rd == 15 {
+            cpu.registers.r[15].wrapping_add(4)
+        } else {
+            cpu.registers.r[rd as usize]
+        };
+        cpu.write_halfword(bus, address, value, MemoryCycle::N);
+
+        if writes_back(opcode) {
+            cpu.write_register(rn, written_back);
+        }
+    }
+}
+
+// LDM / STM
+fn block_data_transfer<B: BusInterface>(cpu: &mut Arm7Tdmi, bus: &mut B, opcode: u32) {
+    let rn = register_field(opcode, 16);
+    let load = opcode.bit(20);
+    let write_back = opcode.bit(21);
+    let psr_or_user = opcode.bit(22);
+    let increment = opcode.bit(23);
+    let pre_index = opcode.bit(24);
+
+    let mut register_list = opcode & 0xFFFF;
+    // An empty list transfers R15 and adjusts the base as if all 16 registers were transferred
+    let transfer_len = if register_list == 0 {
+        register_list = 1 << 15;
+        0x40
+    } else {
+        4 * register_list.count_ones()
+    };
+
+    let base = cpu.registers.r[rn as usize];
+    let written_back =
+        if increment { base.wrapping_add(transfer_len) } else { base.wrapping_sub(transfer_len) };
+
+    // Registers are always transferred lowest first to the lowest address
+    let mut address = match (increment, pre_index) {
+        (true, false) => base,
+        (true, true) => base.wrapping_add(4),
+        (false, false) => base.wrapping_sub(transfer_len).wrapping_add(4),
+        (false, true) => base.wrapping_sub(transfer_len),
+    };
+
+    // With S set, LDM with R15 in the list restores CPSR; otherwise the User bank is transferred
+    let user_bank = psr_or_user && !(load && register_list.bit(15));
+
+    let mut cycle = MemoryCycle::N;
+    if load {
+        if write_back {
+            cpu.write_register(rn, written_back);
+        }
+
+        for r in (0..16).filter(|&r| register_list.bit(r)) {
+            let value = cpu.read_word(bus, address, cycle);
+            cycle = MemoryCycle::S;
+            address = address.wrapping_add(4);
+
+            if user_bank {
+                cpu.registers.write_user_register(r.into(), value);
+            } else {
+                cpu.write_register(r.into(), value);
+            }
+        }
+        bus.internal_cycles(1);
+
+        if psr_or_user && register_list.bit(15) {
+            cpu.restore_cpsr_from_spsr();
+        }
+    } else {
+        for (i, r) in (0..16).filter(|&r| register_list.bit(r)).enumerate() {
+            let value = match r {
+                15 => cpu.registers.r[15].wrapping_add(4),
+                _ if user_bank => cpu.registers.read_user_register(r.into()),
+                _ => cpu.registers.r[usize::from(r)],
+            };
+            cpu.write_word(bus, address, value, cycle);
+            cycle = MemoryCycle::S;
+            address = address.wrapping_add(4);
+
+            // The base is written back after the first store, so storing the base register
+            // stores the original value only if it is the first register in the list
+            if i == 0 && write_back {
+                cpu.write_register(rn, written_back);
+            }
+        }
+    }
+}
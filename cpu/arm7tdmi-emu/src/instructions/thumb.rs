@@ -0,0 +1,454 @@
+//! 16-bit Thumb instruction set
+
+use crate::instructions::{multiply_internal_cycles, ShiftType};
+use crate::traits::{BusInterface, MemoryCycle};
+use crate::Arm7Tdmi;
+use jgenesis_common::num::GetBit;
+
+pub(super) fn execute<B: BusInterface>(cpu: &mut Arm7Tdmi, bus: &mut B, opcode: u16) {
+    match opcode >> 12 {
+        0x0 | 0x1 => {
+            if (opcode >> 11) & 3 == 3 {
+                add_subtract(cpu, opcode);
+            } else {
+                shift_immediate(cpu, opcode);
+            }
+        }
+        0x2 | 0x3 => immediate_operation(cpu, opcode),
+        0x4 => match (opcode >> 10) & 3 {
+            0 => alu_operation(cpu, bus, opcode),
+            1 => high_register_operation(cpu, opcode),
+            _ => pc_relative_load(cpu, bus, opcode),
+        },
+        0x5 => {
+            if opcode.bit(9) {
+                load_store_sign_extended(cpu, bus, opcode);
+            } else {
+                load_store_register_offset(cpu, bus, opcode);
+            }
+        }
+        0x6 | 0x7 => load_store_immediate_offset(cpu, bus, opcode),
+        0x8 => load_store_halfword(cpu, bus, opcode),
+        0x9 => sp_relative_load_store(cpu, bus, opcode),
+        0xA => load_address(cpu, opcode),
+        0xB => match (opcode >> 8) & 0xF {
+            0x0 => adjust_stack_pointer(cpu, opcode),
+            0x4 | 0x5 | 0xC | 0xD => push_pop(cpu, bus, opcode),
+            _ => cpu.undefined_instruction(opcode.into()),
+        },
+        0xC => multiple_load_store(cpu, bus, opcode),
+        0xD => match (opcode >> 8) & 0xF {
+            0xE => cpu.undefined_instruction(opcode.into()),
+            0xF => cpu.software_interrupt(),
+            _ => conditional_branch(cpu, opcode),
+        },
+        0xE => {
+            if opcode.bit(11) {
+                cpu.undefined_instruction(opcode.into());
+            } else {
+                unconditional_branch(cpu, opcode);
+            }
+        }
+        0xF => long_branch_with_link(cpu, opcode),
+        _ => unreachable!("u16 >> 12 is always <= 0xF"),
+    }
+}
+
+fn low_register(opcode: u16, lowest_bit: u8) -> usize {
+    usize::from((opcode >> lowest_bit) & 7)
+}
+
+// LSL / LSR / ASR Rd, Rs, #imm5
+fn shift_immediate(cpu: &mut Arm7Tdmi, opcode: u16) {
+    let shift_type = ShiftType::from_bits(((opcode >> 11) & 3).into());
+    let amount = u32::from((opcode >> 6) & 0x1F);
+    let rs = low_register(opcode, 3);
+    let rd = low_register(opcode, 0);
+
+    let (result, carry) = cpu.shift(shift_type, cpu.registers.r[rs], amount, true);
+    cpu.registers.r[rd] = result;
+    cpu.set_nz(result);
+    cpu.registers.cpsr.carry = carry;
+}
+
+// ADD / SUB Rd, Rs, Rn/#imm3
+fn add_subtract(cpu: &mut Arm7Tdmi, opcode: u16) {
+    let rs = low_register(opcode, 3);
+    let rd = low_register(opcode, 0);
+    let operand = if opcode.bit(10) {
+        u32::from((opcode >> 6) & 7)
+    } else {
+        cpu.registers.r[low_register(opcode, 6)]
+    };
+
+    let source = cpu.registers.r[rs];
+    cpu.registers.r[rd] = if opcode.bit(9) {
+        cpu.sub_set_flags(source, operand, true)
+    } else {
+        cpu.add_set_flags(source, operand, false)
+    };
+}
+
+// MOV / CMP / ADD / SUB Rd, #imm8
+fn immediate_operation(cpu: &mut Arm7Tdmi, opcode: u16) {
+    let rd = low_register(opcode, 8);
+    let immediate = u32::from(opcode & 0xFF);
+    let value = cpu.registers.r[rd];
+
+    match (opcode >> 11) & 3 {
+        0 => {
+            cpu.registers.r[rd] = immediate;
+            cpu.set_nz(immediate);
+        }
+        1 => {
+            cpu.sub_set_flags(value, immediate, true);
+        }
+        2 => cpu.registers.r[rd] = cpu.add_set_flags(value, immediate, false),
+        3 => cpu.registers.r[rd] = cpu.sub_set_flags(value, immediate, true),
+        _ => unreachable!("value & 3 is always <= 3"),
+    }
+}
+
+fn alu_operation<B: BusInterface>(cpu: &mut Arm7Tdmi, bus: &mut B, opcode: u16) {
+    let rs = low_register(opcode, 3);
+    let rd = low_register(opcode, 0);
+    let source = cpu.registers.r[rs];
+    let dest = cpu.registers.r[rd];
+    let carry = cpu.registers.cpsr.carry;
+
+    let result = match (opcode >> 6) & 0xF {
+        // AND
+        0x0 => Some(dest & source),
+        // EOR
+        0x1 => Some(dest ^ source),
+        // LSL
+        0x2 => Some(register_shift(cpu, bus, ShiftType::Lsl, dest, source)),
+        // LSR
+        0x3 => Some(register_shift(cpu, bus, ShiftType::Lsr, dest, source)),
+        // ASR
+        0x4 => Some(register_shift(cpu, bus, ShiftType::Asr, dest, source)),
+        // ADC
+        0x5 => Some(cpu.add_set_flags(dest, source, carry)),
+        // SBC
+        0x6 => Some(cpu.sub_set_flags(dest, source, carry)),
+        // ROR
+        0x7 => Some(register_shift(cpu, bus, ShiftType::Ror, dest, source)),
+        // TST
+        0x8 => {
+            cpu.set_nz(dest & source);
+            None
+        }
+        // NEG
+        0x9 => Some(cpu.sub_set_flags(0, source, true)),
+        // CMP
+        0xA => {
+            cpu.sub_set_flags(dest, source, true);
+            None
+        }
+        // CMN
+        0xB => {
+            cpu.add_set_flags(dest, source, false);
+            None
+        }
+        // ORR
+        0xC => Some(dest | source),
+        // MUL
+        0xD => {
+            bus.internal_cycles(multiply_internal_cycles(dest, true));
+            cpu.sequential_fetch = false;
+            Some(dest.wrapping_mul(source))
+        }
+        // BIC
+        0xE => Some(dest & !source),
+        // MVN
+        0xF => Some(!source),
+        _ => unreachable!("value & 0xF is always <= 0xF"),
+    };
+
+    if let Some(result) = result {
+        // Logical operations and MUL set only N and Z
+        cpu.set_nz(result);
+        cpu.registers.r[rd] = result;
+    }
+}
+
+// Shifts by register take an extra internal cycle
+fn register_shift<B: BusInterface>(
+    cpu: &mut Arm7Tdmi,
+    bus: &mut B,
+    shift_type: ShiftType,
+    value: u32,
+    amount: u32,
+) -> u32 {
+    bus.internal_cycles(1);
+    cpu.sequential_fetch = false;
+
+    let (result, carry) = cpu.shift(shift_type, value, amount & 0xFF, false);
+    cpu.registers.cpsr.carry = carry;
+    result
+}
+
+// ADD / CMP / MOV with high registers, and BX
+fn high_register_operation(cpu: &mut Arm7Tdmi, opcode: u16) {
+    let rs = usize::from((opcode >> 3) & 0xF);
+    let rd = usize::from(((opcode >> 4) & 8) | (opcode & 7));
+    let source = cpu.registers.r[rs];
+
+    match (opcode >> 8) & 3 {
+        0 => {
+            let result = cpu.registers.r[rd].wrapping_add(source);
+            cpu.write_register(rd as u32, result);
+        }
+        1 => {
+            cpu.sub_set_flags(cpu.registers.r[rd], source, true);
+        }
+        2 => cpu.write_register(rd as u32, source),
+        3 => cpu.branch_exchange(source),
+        _ => unreachable!("value & 3 is always <= 3"),
+    }
+}
+
+// LDR Rd, [PC, #imm8]
+fn pc_relative_load<B: BusInterface>(cpu: &mut Arm7Tdmi, bus: &mut B, opcode: u16) {
+    let rd = low_register(opcode, 8);
+    let offset = u32::from(opcode & 0xFF) << 2;
+    let address = (cpu.registers.r[15] & !2).wrapping_add(offset);
+
+    cpu.registers.r[rd] = cpu.read_word(bus, address, MemoryCycle::N);
+    bus.internal_cycles(1);
+}
+
+// STR / STRB / LDR / LDRB Rd, [Rb, Ro]
+fn load_store_register_offset<B: BusInterface>(cpu: &mut Arm7Tdmi, bus: &mut B, opcode: u16) {
+    let ro = low_register(opcode, 6);
+    let rb = low_register(opcode, 3);
+    let rd = low_register(opcode, 0);
+    let address = cpu.registers.r[rb].wrapping_add(cpu.registers.r[ro]);
+
+    match (opcode >> 10) & 3 {
+        0 => cpu.write_word(bus, address, cpu.registers.r[rd], MemoryCycle::N),
+        1 => cpu.write_byte(bus, address, cpu.registers.r[rd], MemoryCycle::N),
+        2 => {
+            cpu.registers.r[rd] = cpu.read_word_rotated(bus, address, MemoryCycle::N);
+            bus.internal_cycles(1);
+        }
+        3 => {
+            cpu.registers.r[rd] = cpu.read_byte(bus, address, MemoryCycle::N);
+            bus.internal_cycles(1);
+        }
+        _ => unreachable!("value & 3 is always <= 3"),
+    }
+}
+
+// STRH / LDRSB / LDRH / LDRSH Rd, [Rb, Ro]
+fn load_store_sign_extended<B: BusInterface>(cpu: &mut Arm7Tdmi, bus: &mut B, opcode: u16) {
+    let ro = low_register(opcode, 6);
+    let rb = low_register(opcode, 3);
+    let rd = low_register(opcode, 0);
+    let address = cpu.registers.r[rb].wrapping_add(cpu.registers.r[ro]);
+
+    let value = match (opcode >> 10) & 3 {
+        0 => {
+            cpu.write_halfword(bus, address, cpu.registers.r[rd], MemoryCycle::N);
+            return;
+        }
+        1 => cpu.read_signed_byte(bus, address, MemoryCycle::N),
+        2 => cpu.read_halfword_rotated(bus, address, MemoryCycle::N),
+        3 => cpu.read_signed_halfword(bus, address, MemoryCycle::N),
+        _ => unreachable!("value & 3 is always <= 3"),
+    };
+    bus.internal_cycles(1);
+    cpu.registers.r[rd] = value;
+}
+
+// STR / LDR / STRB / LDRB Rd, [Rb, #imm5]
+fn load_store_immediate_offset<B: BusInterface>(cpu: &mut Arm7Tdmi, bus: &mut B, opcode: u16) {
+    let byte = opcode.bit(12);
+    let load = opcode.bit(11);
+    let rb = low_register(opcode, 3);
+    let rd = low_register(opcode, 0);
+
+    let offset = u32::from((opcode >> 6) & 0x1F);
+    let offset = if byte { offset } else { offset << 2 };
+    let address = cpu.registers.r[rb].wrapping_add(offset);
+
+    match (load, byte) {
+        (false, false) => cpu.write_word(bus, address, cpu.registers.r[rd], MemoryCycle::N),
+        (false, true) => cpu.write_byte(bus, address, cpu.registers.r[rd], MemoryCycle::N),
+        (true, false) => {
+            cpu.registers.r[rd] = cpu.read_word_rotated(bus, address, MemoryCycle::N);
+            bus.internal_cycles(1);
+        }
+        (true, true) => {
+            cpu.registers.r[rd] = cpu.read_byte(bus, address, MemoryCycle::N);
+            bus.internal_cycles(1);
+        }
+    }
+}
+
+// STRH / LDRH Rd, [Rb, #imm5]
+fn load_store_halfword<B: BusInterface>(cpu: &mut Arm7Tdmi, bus: &mut B, opcode: u16) {
+    let rb = low_register(opcode, 3);
+    let rd = low_register(opcode, 0);
+    let offset = u32::from((opcode >> 6) & 0x1F) << 1;
+    let address = cpu.registers.r[rb].wrapping_add(offset);
+
+    if opcode.bit(11) {
+        cpu.registers.r[rd] = cpu.read_halfword_rotated(bus, address, MemoryCycle::N);
+        bus.internal_cycles(1);
+    } else {
+        cpu.write_halfword(bus, address, cpu.registers.r[rd], MemoryCycle::N);
+    }
+}
+
+// STR / LDR Rd, [SP, #imm8]
+fn sp_relative_load_store<B: BusInterface>(cpu: &mut Arm7Tdmi, bus: &mut B, opcode: u16) {
+    let rd = low_register(opcode, 8);
+    let offset = u32::from(opcode & 0xFF) << 2;
+    let address = cpu.registers.r[13].wrapping_add(offset);
+
+    if opcode.bit(11) {
+        cpu.registers.r[rd] = cpu.read_word_rotated(bus, address, MemoryCycle::N);
+        bus.internal_cycles(1);
+    } else {
+        cpu.write_word(bus, address, cpu.registers.r[rd], MemoryCycle::N);
+    }
+}
+
+// ADD Rd, PC/SP, #imm8
+fn load_address(cpu: &mut Arm7Tdmi, opcode: u16) {
+    let rd = low_register(opcode, 8);
+    let offset = u32::from(opcode & 0xFF) << 2;
+    let base = if opcode.bit(11) { cpu.registers.r[13] } else { cpu.registers.r[15] & !2 };
+
+    cpu.registers.r[rd] = base.wrapping_add(offset);
+}
+
+// ADD SP, #±imm7
+fn adjust_stack_pointer(cpu: &mut Arm7Tdmi, opcode: u16) {
+    let offset = u32::from(opcode & 0x7F) << 2;
+    let sp = cpu.registers.r[13];
+    cpu.registers.r[13] =
+        if opcode.bit(7) { sp.wrapping_sub(offset) } else { sp.wrapping_add(offset) };
+}
+
+// PUSH {Rlist, LR} / POP {Rlist, PC}
+fn push_pop<B: BusInterface>(cpu: &mut Arm7Tdmi, bus: &mut B, opcode: u16) {
+    let load = opcode.bit(11);
+    let pc_lr = opcode.bit(8);
+    let register_list = opcode & 0xFF;
+    let count = register_list.count_ones() + u32::from(pc_lr);
+
+    let mut cycle = MemoryCycle::N;
+    if load {
+        let mut address = cpu.registers.r[13];
+        for r in (0..8).filter(|&r| register_list.bit(r)) {
+            cpu.registers.r[usize::from(r)] = cpu.read_word(bus, address, cycle);
+            cycle = MemoryCycle::S;
+            address = address.wrapping_add(4);
+        }
+
+        if pc_lr {
+            let pc = cpu.read_word(bus, address, cycle);
+            cpu.write_pc(pc);
+            address = address.wrapping_add(4);
+        }
+
+        bus.internal_cycles(1);
+        cpu.registers.r[13] = address;
+    } else {
+        let start = cpu.registers.r[13].wrapping_sub(4 * count);
+        let mut address = start;
+        for r in (0..8).filter(|&r| register_list.bit(r)) {
+            cpu.write_word(bus, address, cpu.registers.r[usize::from(r)], cycle);
+            cycle = MemoryCycle::S;
+            address = address.wrapping_add(4);
+        }
+
+        if pc_lr {
+            cpu.write_word(bus, address, cpu.registers.r[14], cycle);
+        }
+
+        cpu.registers.r[13] = start;
+    }
+}
+
+// STMIA / LDMIA Rb!, {Rlist}
+fn multiple_load_store<B: BusInterface>(cpu: &mut Arm7Tdmi, bus: &mut B, opcode: u16) {
+    let load = opcode.bit(11);
+    let rb = low_register(opcode, 8);
+    let register_list = opcode & 0xFF;
+
+    let base = cpu.registers.r[rb];
+    if register_list == 0 {
+        // An empty list transfers R15 and adds $40 to the base
+        if load {
+            let pc = cpu.read_word(bus, base, MemoryCycle::N);
+            cpu.write_pc(pc);
+        } else {
+            let pc = cpu.registers.r[15].wrapping_add(2);
+            cpu.write_word(bus, base, pc, MemoryCycle::N);
+        }
+        cpu.registers.r[rb] = base.wrapping_add(0x40);
+        return;
+    }
+
+    let written_back = base.wrapping_add(4 * register_list.count_ones());
+    let mut address = base;
+    let mut cycle = MemoryCycle::N;
+
+    if load {
+        cpu.registers.r[rb] = written_back;
+        for r in (0..8).filter(|&r| register_list.bit(r)) {
+            cpu.registers.r[usize::from(r)] = cpu.read_word(bus, address, cycle);
+            cycle = MemoryCycle::S;
+            address = address.wrapping_add(4);
+        }
+        bus.internal_cycles(1);
+    } else {
+        for (i, r) in (0..8).filter(|&r| register_list.bit(r)).enumerate() {
+            cpu.write_word(bus, address, cpu.registers.r[usize::from(r)], cycle);
+            cycle = MemoryCycle::S;
+            address = address.wrapping_add(4);
+
+            // The base is written back after the first store
+            if i == 0 {
+                cpu.registers.r[rb] = written_back;
+            }
+        }
+    }
+}
+
+// B<cond> label
+fn conditional_branch(cpu: &mut Arm7Tdmi, opcode: u16) {
+    if !cpu.check_condition(((opcode >> 8) & 0xF).into()) {
+        return;
+    }
+
+    let offset = i32::from(opcode as u8 as i8) << 1;
+    let pc = cpu.registers.r[15];
+    cpu.write_pc(pc.wrapping_add(offset as u32));
+}
+
+// B label
+fn unconditional_branch(cpu: &mut Arm7Tdmi, opcode: u16) {
+    let offset = i32::from((opcode << 5) as i16) >> 4;
+    let pc = cpu.registers.r[15];
+    cpu.write_pc(pc.wrapping_add(offset as u32));
+}
+
+// BL label, split across two instructions
+fn long_branch_with_link(cpu: &mut Arm7Tdmi, opcode: u16) {
+    let offset = u32::from(opcode & 0x7FF);
+
+    if opcode.bit(11) {
+        // Second half: branch to LR + (offset << 1), and set LR to the next instruction
+        let target = cpu.registers.r[14].wrapping_add(offset << 1);
+        cpu.registers.r[14] = cpu.next_instruction_address() | 1;
+        cpu.write_pc(target);
+    } else {
+        // First half: LR = PC + (sign-extended offset << 12)
+        let upper_offset = (((offset << 21) as i32) >> 9) as u32;
+        cpu.registers.r[14] = cpu.registers.r[15].wrapping_add(upper_offset);
+    }
+}
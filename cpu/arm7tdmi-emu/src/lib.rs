@@ -0,0 +1,312 @@
+mod instructions;
+pub mod traits;
+
+pub use traits::{BusInterface, MemoryCycle};
+
+use bincode::{Decode, Encode};
+use jgenesis_common::num::GetBit;
+
+pub const RESET_VECTOR: u32 = 0x00;
+pub const UNDEFINED_VECTOR: u32 = 0x04;
+pub const SWI_VECTOR: u32 = 0x08;
+pub const IRQ_VECTOR: u32 = 0x18;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode)]
+pub enum CpuState {
+    #[default]
+    Arm,
+    Thumb,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode)]
+pub enum CpuMode {
+    User,
+    Fiq,
+    Irq,
+    #[default]
+    Supervisor,
+    Abort,
+    Undefined,
+    System,
+}
+
+impl CpuMode {
+    #[must_use]
+    pub fn from_bits(bits: u32) -> Option<Self> {
+        match bits & 0x1F {
+            0x10 => Some(Self::User),
+            0x11 => Some(Self::Fiq),
+            0x12 => Some(Self::Irq),
+            0x13 => Some(Self::Supervisor),
+            0x17 => Some(Self::Abort),
+            0x1B => Some(Self::Undefined),
+            0x1F => Some(Self::System),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn to_bits(self) -> u32 {
+        match self {
+            Self::User => 0x10,
+            Self::Fiq => 0x11,
+            Self::Irq => 0x12,
+            Self::Supervisor => 0x13,
+            Self::Abort => 0x17,
+            Self::Undefined => 0x1B,
+            Self::System => 0x1F,
+        }
+    }
+
+    fn is_privileged(self) -> bool {
+        self != Self::User
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode)]
+pub struct StatusRegister {
+    pub sign: bool,
+    pub zero: bool,
+    pub carry: bool,
+    pub overflow: bool,
+    pub irq_disabled: bool,
+    pub fiq_disabled: bool,
+    pub state: CpuState,
+    pub mode: CpuMode,
+}
+
+impl From<StatusRegister> for u32 {
+    fn from(value: StatusRegister) -> Self {
+        (u32::from(value.sign) << 31)
+            | (u32::from(value.zero) << 30)
+            | (u32::from(value.carry) << 29)
+            | (u32::from(value.overflow) << 28)
+            | (u32::from(value.irq_disabled) << 7)
+            | (u32::from(value.fiq_disabled) << 6)
+            | (u32::from(value.state == CpuState::Thumb) << 5)
+            | value.mode.to_bits()
+    }
+}
+
+impl From<u32> for StatusRegister {
+    fn from(value: u32) -> Self {
+        let mode = CpuMode::from_bits(value).unwrap_or_else(|| {
+            log::warn!("Invalid CPU mode bits in PSR write: {:02X}", value & 0x1F);
+            CpuMode::User
+        });
+
+        Self {
+            sign: value.bit(31),
+            zero: value.bit(30),
+            carry: value.bit(29),
+            overflow: value.bit(28),
+            irq_disabled: value.bit(7),
+            fiq_disabled: value.bit(6),
+            state: if value.bit(5) { CpuState::Thumb } else { CpuState::Arm },
+            mode,
+        }
+    }
+}
+
+/// ARM7TDMI registers.
+///
+/// `r` contains the registers visible in the current mode; the banked fields hold the values for
+/// every other mode and are swapped in and out on mode changes. R15 holds the address of the next
+/// instruction to execute, except while an instruction is executing when it holds the instruction
+/// address plus 8 (ARM) or plus 4 (Thumb) to match the prefetch behavior of the real pipeline.
+#[derive(Debug, Clone, Default, Encode, Decode)]
+pub struct Registers {
+    pub r: [u32; 16],
+    pub cpsr: StatusRegister,
+    pub usr_r8_r12: [u32; 5],
+    pub fiq_r8_r12: [u32; 5],
+    pub usr_r13_r14: [u32; 2],
+    pub fiq_r13_r14: [u32; 2],
+    pub irq_r13_r14: [u32; 2],
+    pub svc_r13_r14: [u32; 2],
+    pub abt_r13_r14: [u32; 2],
+    pub und_r13_r14: [u32; 2],
+    pub spsr_fiq: StatusRegister,
+    pub spsr_irq: StatusRegister,
+    pub spsr_svc: StatusRegister,
+    pub spsr_abt: StatusRegister,
+    pub spsr_und: StatusRegister,
+}
+
+impl Registers {
+    fn r13_r14_bank(&mut self, mode: CpuMode) -> &mut [u32; 2] {
+        match mode {
+            CpuMode::User | CpuMode::System => &mut self.usr_r13_r14,
+            CpuMode::Fiq => &mut self.fiq_r13_r14,
+            CpuMode::Irq => &mut self.irq_r13_r14,
+            CpuMode::Supervisor => &mut self.svc_r13_r14,
+            CpuMode::Abort => &mut self.abt_r13_r14,
+            CpuMode::Undefined => &mut self.und_r13_r14,
+        }
+    }
+
+    /// The current mode's SPSR, or None in User and System modes which have no SPSR.
+    #[must_use]
+    pub fn spsr(&self) -> Option<StatusRegister> {
+        match self.cpsr.mode {
+            CpuMode::User | CpuMode::System => None,
+            CpuMode::Fiq => Some(self.spsr_fiq),
+            CpuMode::Irq => Some(self.spsr_irq),
+            CpuMode::Supervisor => Some(self.spsr_svc),
+            CpuMode::Abort => Some(self.spsr_abt),
+            CpuMode::Undefined => Some(self.spsr_und),
+        }
+    }
+
+    fn spsr_mut(&mut self) -> Option<&mut StatusRegister> {
+        match self.cpsr.mode {
+            CpuMode::User | CpuMode::System => None,
+            CpuMode::Fiq => Some(&mut self.spsr_fiq),
+            CpuMode::Irq => Some(&mut self.spsr_irq),
+            CpuMode::Supervisor => Some(&mut self.spsr_svc),
+            CpuMode::Abort => Some(&mut self.spsr_abt),
+            CpuMode::Undefined => Some(&mut self.spsr_und),
+        }
+    }
+
+    /// Switch to a new mode, swapping banked registers in and out.
+    pub fn change_mode(&mut self, new_mode: CpuMode) {
+        let old_mode = self.cpsr.mode;
+        if old_mode == new_mode {
+            return;
+        }
+
+        let [r13, r14] = [self.r[13], self.r[14]];
+        *self.r13_r14_bank(old_mode) = [r13, r14];
+        [self.r[13], self.r[14]] = *self.r13_r14_bank(new_mode);
+
+        if old_mode == CpuMode::Fiq || new_mode == CpuMode::Fiq {
+            let (save_bank, load_bank) = if new_mode == CpuMode::Fiq {
+                (&mut self.usr_r8_r12, self.fiq_r8_r12)
+            } else {
+                (&mut self.fiq_r8_r12, self.usr_r8_r12)
+            };
+            save_bank.copy_from_slice(&self.r[8..13]);
+            self.r[8..13].copy_from_slice(&load_bank);
+        }
+
+        self.cpsr.mode = new_mode;
+    }
+
+    /// Read a register from the User mode bank regardless of the current mode, as used by LDM/STM
+    /// with the S bit set.
+    fn read_user_register(&self, i: usize) -> u32 {
+        match (i, self.cpsr.mode) {
+            (8..=12, CpuMode::Fiq) => self.usr_r8_r12[i - 8],
+            (13..=14, CpuMode::User | CpuMode::System) => self.r[i],
+            (13..=14, _) => self.usr_r13_r14[i - 13],
+            _ => self.r[i],
+        }
+    }
+
+    fn write_user_register(&mut self, i: usize, value: u32) {
+        match (i, self.cpsr.mode) {
+            (8..=12, CpuMode::Fiq) => self.usr_r8_r12[i - 8] = value,
+            (13..=14, CpuMode::User | CpuMode::System) => self.r[i] = value,
+            (13..=14, _) => self.usr_r13_r14[i - 13] = value,
+            _ => self.r[i] = value,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Encode, Decode)]
+pub struct Arm7Tdmi {
+    registers: Registers,
+    // Set when an instruction writes to R15
+    branched: bool,
+    // Whether the next opcode fetch is sequential to the previous bus access
+    sequential_fetch: bool,
+}
+
+impl Arm7Tdmi {
+    #[must_use]
+    pub fn new() -> Self {
+        let mut cpu = Self::default();
+        cpu.reset();
+        cpu
+    }
+
+    /// Reset the CPU: enter Supervisor mode in ARM state with interrupts disabled, and jump to the
+    /// reset vector.
+    pub fn reset(&mut self) {
+        self.registers.change_mode(CpuMode::Supervisor);
+        self.registers.cpsr.state = CpuState::Arm;
+        self.registers.cpsr.irq_disabled = true;
+        self.registers.cpsr.fiq_disabled = true;
+        self.registers.r[15] = RESET_VECTOR;
+        self.sequential_fetch = false;
+    }
+
+    /// Execute a single instruction or service an IRQ. The bus is responsible for tracking how
+    /// many cycles elapse.
+    pub fn execute_instruction<B: BusInterface>(&mut self, bus: &mut B) {
+        if bus.irq() && !self.registers.cpsr.irq_disabled {
+            self.handle_irq(bus);
+            return;
+        }
+
+        instructions::execute(self, bus);
+    }
+
+    fn handle_irq<B: BusInterface>(&mut self, bus: &mut B) {
+        log::trace!("Handling IRQ at PC={:08X}", self.registers.r[15]);
+
+        // Return with SUBS PC, R14, #4 in both states
+        let return_address = self.registers.r[15].wrapping_add(4);
+        self.enter_exception(CpuMode::Irq, IRQ_VECTOR, return_address);
+        self.refill_pipeline(bus);
+    }
+
+    fn enter_exception(&mut self, mode: CpuMode, vector: u32, return_address: u32) {
+        let cpsr = self.registers.cpsr;
+        self.registers.change_mode(mode);
+        if let Some(spsr) = self.registers.spsr_mut() {
+            *spsr = cpsr;
+        }
+
+        self.registers.r[14] = return_address;
+        self.registers.cpsr.state = CpuState::Arm;
+        self.registers.cpsr.irq_disabled = true;
+        self.registers.r[15] = vector;
+        self.branched = true;
+    }
+
+    // Charge the two code fetches that refill the pipeline after a branch
+    fn refill_pipeline<B: BusInterface>(&mut self, bus: &mut B) {
+        let pc = self.registers.r[15];
+        match self.registers.cpsr.state {
+            CpuState::Arm => {
+                bus.fetch_opcode_word(pc, MemoryCycle::N);
+                bus.fetch_opcode_word(pc.wrapping_add(4), MemoryCycle::S);
+            }
+            CpuState::Thumb => {
+                bus.fetch_opcode_halfword(pc, MemoryCycle::N);
+                bus.fetch_opcode_halfword(pc.wrapping_add(2), MemoryCycle::S);
+            }
+        }
+        self.sequential_fetch = true;
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn registers(&self) -> &Registers {
+        &self.registers
+    }
+
+    pub fn set_registers(&mut self, registers: Registers) {
+        self.registers = registers;
+        self.sequential_fetch = false;
+    }
+
+    /// Address of the next instruction to execute.
+    #[inline]
+    #[must_use]
+    pub fn pc(&self) -> u32 {
+        self.registers.r[15]
+    }
+}
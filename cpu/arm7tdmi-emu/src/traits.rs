@@ -0,0 +1,41 @@
+/// Whether a memory access is sequential to the previous access. Sequential accesses are faster
+/// than non-sequential accesses on many buses, particularly cartridge ROM.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryCycle {
+    /// Non-sequential access
+    N,
+    /// Sequential access
+    S,
+}
+
+/// The bus is responsible for tracking how many cycles each access takes. Addresses passed to the
+/// halfword and word methods are always aligned.
+pub trait BusInterface {
+    fn read_byte(&mut self, address: u32, cycle: MemoryCycle) -> u8;
+
+    fn read_halfword(&mut self, address: u32, cycle: MemoryCycle) -> u16;
+
+    fn read_word(&mut self, address: u32, cycle: MemoryCycle) -> u32;
+
+    /// Fetch a Thumb opcode. Defaults to a halfword read.
+    fn fetch_opcode_halfword(&mut self, address: u32, cycle: MemoryCycle) -> u16 {
+        self.read_halfword(address, cycle)
+    }
+
+    /// Fetch an ARM opcode. Defaults to a word read.
+    fn fetch_opcode_word(&mut self, address: u32, cycle: MemoryCycle) -> u32 {
+        self.read_word(address, cycle)
+    }
+
+    fn write_byte(&mut self, address: u32, value: u8, cycle: MemoryCycle);
+
+    fn write_halfword(&mut self, address: u32, value: u16, cycle: MemoryCycle);
+
+    fn write_word(&mut self, address: u32, value: u32, cycle: MemoryCycle);
+
+    /// Record internal cycles where the CPU does not access the bus.
+    fn internal_cycles(&mut self, cycles: u32);
+
+    /// Poll the IRQ line.
+    fn irq(&self) -> bool;
+}
@@ -0,0 +1,428 @@
+//! `HuC6280` instruction decoding and execution
+//!
+//! The `HuC6280` is a 65C02 derivative with the Rockwell bit instructions, a memory management unit,
+//! block transfer instructions, and a handful of other additions. Cycle counts are taken as a
+//! whole per instruction rather than emulating individual bus cycles.
+
+mod alu;
+mod flow;
+mod special;
+
+use crate::traits::BusInterface;
+use crate::{HuC6280, IRQ1_VECTOR, IRQ2_VECTOR, TIMER_IRQ_VECTOR};
+
+// Logical addresses of the zero page and the stack; these are usually mapped to work RAM
+const ZERO_PAGE: u16 = 0x2000;
+const STACK: u16 = 0x2100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AddressingMode {
+    Immediate,
+    ZeroPage,
+    ZeroPageX,
+    ZeroPageY,
+    Absolute,
+    AbsoluteX,
+    AbsoluteY,
+    ZeroPageIndirect,
+    ZeroPageIndexedIndirect,
+    ZeroPageIndirectIndexed,
+}
+
+impl AddressingMode {
+    // Cycles for a read or store instruction using this addressing mode; unlike the 6502, page
+    // crossings never cost an extra cycle
+    fn cycles(self) -> u32 {
+        match self {
+            Self::Immediate => 2,
+            Self::ZeroPage | Self::ZeroPageX | Self::ZeroPageY => 4,
+            Self::Absolute | Self::AbsoluteX | Self::AbsoluteY => 5,
+            Self::ZeroPageIndirect
+            | Self::ZeroPageIndexedIndirect
+            | Self::ZeroPageIndirectIndexed => 7,
+        }
+    }
+
+    // Cycles for a read-modify-write instruction using this addressing mode
+    fn rmw_cycles(self) -> u32 {
+        match self {
+            Self::ZeroPage | Self::ZeroPageX => 6,
+            _ => 7,
+        }
+    }
+}
+
+impl HuC6280 {
+    #[inline]
+    fn read<B: BusInterface>(&self, bus: &mut B, address: u16) -> u8 {
+        bus.read(self.registers.map_address(address))
+    }
+
+    #[inline]
+    fn write<B: BusInterface>(&self, bus: &mut B, address: u16, value: u8) {
+        bus.write(self.registers.map_address(address), value);
+    }
+
+    fn fetch_operand<B: BusInterface>(&mut self, bus: &mut B) -> u8 {
+        let value = self.read(bus, self.registers.pc);
+        self.registers.pc = self.registers.pc.wrapping_add(1);
+        value
+    }
+
+    fn fetch_operand_u16<B: BusInterface>(&mut self, bus: &mut B) -> u16 {
+        let lsb = self.fetch_operand(bus);
+        let msb = self.fetch_operand(bus);
+        u16::from_le_bytes([lsb, msb])
+    }
+
+    fn read_u16<B: BusInterface>(&self, bus: &mut B, address: u16) -> u16 {
+        let lsb = self.read(bus, address);
+        let msb = self.read(bus, address.wrapping_add(1));
+        u16::from_le_bytes([lsb, msb])
+    }
+
+    fn read_zero_page_u16<B: BusInterface>(&self, bus: &mut B, address: u8) -> u16 {
+        let lsb = self.read(bus, ZERO_PAGE | u16::from(address));
+        let msb = self.read(bus, ZERO_PAGE | u16::from(address.wrapping_add(1)));
+        u16::from_le_bytes([lsb, msb])
+    }
+
+    fn push<B: BusInterface>(&mut self, bus: &mut B, value: u8) {
+        self.write(bus, STACK | u16::from(self.registers.s), value);
+        self.registers.s = self.registers.s.wrapping_sub(1);
+    }
+
+    fn pull<B: BusInterface>(&mut self, bus: &mut B) -> u8 {
+        self.registers.s = self.registers.s.wrapping_add(1);
+        self.read(bus, STACK | u16::from(self.registers.s))
+    }
+
+    fn push_u16<B: BusInterface>(&mut self, bus: &mut B, value: u16) {
+        let [lsb, msb] = value.to_le_bytes();
+        self.push(bus, msb);
+        self.push(bus, lsb);
+    }
+
+    fn pull_u16<B: BusInterface>(&mut self, bus: &mut B) -> u16 {
+        let lsb = self.pull(bus);
+        let msb = self.pull(bus);
+        u16::from_le_bytes([lsb, msb])
+    }
+
+    fn resolve_address<B: BusInterface>(&mut self, bus: &mut B, mode: AddressingMode) -> u16 {
+        match mode {
+            AddressingMode::Immediate => {
+                panic!("immediate addressing mode does not resolve to an address")
+            }
+            AddressingMode::ZeroPage => ZERO_PAGE | u16::from(self.fetch_operand(bus)),
+            AddressingMode::ZeroPageX => {
+                ZERO_PAGE | u16::from(self.fetch_operand(bus).wrapping_add(self.registers.x))
+            }
+            AddressingMode::ZeroPageY => {
+                ZERO_PAGE | u16::from(self.fetch_operand(bus).wrapping_add(self.registers.y))
+            }
+            AddressingMode::Absolute => self.fetch_operand_u16(bus),
+            AddressingMode::AbsoluteX => {
+                self.fetch_operand_u16(bus).wrapping_add(self.registers.x.into())
+            }
+            AddressingMode::AbsoluteY => {
+                self.fetch_operand_u16(bus).wrapping_add(self.registers.y.into())
+            }
+            AddressingMode::ZeroPageIndirect => {
+                let pointer = self.fetch_operand(bus);
+                self.read_zero_page_u16(bus, pointer)
+            }
+            AddressingMode::ZeroPageIndexedIndirect => {
+                let pointer = self.fetch_operand(bus).wrapping_add(self.registers.x);
+                self.read_zero_page_u16(bus, pointer)
+            }
+            AddressingMode::ZeroPageIndirectIndexed => {
+                let pointer = self.fetch_operand(bus);
+                self.read_zero_page_u16(bus, pointer).wrapping_add(self.registers.y.into())
+            }
+        }
+    }
+
+    fn read_operand<B: BusInterface>(&mut self, bus: &mut B, mode: AddressingMode) -> u8 {
+        match mode {
+            AddressingMode::Immediate => self.fetch_operand(bus),
+            _ => {
+                let address = self.resolve_address(bus, mode);
+                self.read(bus, address)
+            }
+        }
+    }
+
+    fn pending_interrupt_vector<B: BusInterface>(&self, bus: &B) -> Option<u16> {
+        if self.registers.p.interrupt_disable {
+            return None;
+        }
+
+        if bus.timer_irq() {
+            Some(TIMER_IRQ_VECTOR)
+        } else if bus.irq1() {
+            Some(IRQ1_VECTOR)
+        } else if bus.irq2() {
+            Some(IRQ2_VECTOR)
+        } else {
+            None
+        }
+    }
+}
+
+pub fn execute<B: BusInterface>(cpu: &mut HuC6280, bus: &mut B) -> u32 {
+    use AddressingMode::*;
+
+    if cpu.block_transfer.is_some() {
+        return cpu.continue_block_transfer(bus);
+    }
+
+    if let Some(vector) = cpu.pending_interrupt_vector(bus) {
+        return cpu.handle_interrupt(bus, vector);
+    }
+
+    // The T flag only applies to the instruction immediately following SET
+    let t = std::mem::take(&mut cpu.registers.p.memory_operation);
+
+    let opcode = cpu.fetch_operand(bus);
+    match opcode {
+        0x00 => cpu.brk(bus),
+        0x01 => cpu.ora(bus, ZeroPageIndexedIndirect, t),
+        0x02 => cpu.sxy(),
+        0x03 => cpu.st(bus, 0),
+        0x04 => cpu.tsb(bus, ZeroPage),
+        0x05 => cpu.ora(bus, ZeroPage, t),
+        0x06 => cpu.rmw(bus, ZeroPage, alu::asl),
+        0x07 | 0x17 | 0x27 | 0x37 | 0x47 | 0x57 | 0x67 | 0x77 => cpu.rmb(bus, opcode >> 4),
+        0x08 => cpu.php(bus),
+        0x09 => cpu.ora(bus, Immediate, t),
+        0x0A => cpu.rmw_accumulator(alu::asl),
+        0x0C => cpu.tsb(bus, Absolute),
+        0x0D => cpu.ora(bus, Absolute, t),
+        0x0E => cpu.rmw(bus, Absolute, alu::asl),
+        0x0F | 0x1F | 0x2F | 0x3F | 0x4F | 0x5F | 0x6F | 0x7F => cpu.bbr(bus, opcode >> 4),
+        0x10 => cpu.branch(bus, !cpu.registers.p.negative),
+        0x11 => cpu.ora(bus, ZeroPageIndirectIndexed, t),
+        0x12 => cpu.ora(bus, ZeroPageIndirect, t),
+        0x13 => cpu.st(bus, 2),
+        0x14 => cpu.trb(bus, ZeroPage),
+        0x15 => cpu.ora(bus, ZeroPageX, t),
+        0x16 => cpu.rmw(bus, ZeroPageX, alu::asl),
+        0x18 => cpu.set_flag(|p| p.carry = false),
+        0x19 => cpu.ora(bus, AbsoluteY, t),
+        0x1A => cpu.rmw_accumulator(alu::inc),
+        0x1C => cpu.trb(bus, Absolute),
+        0x1D => cpu.ora(bus, AbsoluteX, t),
+        0x1E => cpu.rmw(bus, AbsoluteX, alu::asl),
+        0x20 => cpu.jsr(bus),
+        0x21 => cpu.and(bus, ZeroPageIndexedIndirect, t),
+        0x22 => cpu.sax(),
+        0x23 => cpu.st(bus, 3),
+        0x24 => cpu.bit(bus, ZeroPage),
+        0x25 => cpu.and(bus, ZeroPage, t),
+        0x26 => cpu.rmw(bus, ZeroPage, alu::rol),
+        0x28 => cpu.plp(bus),
+        0x29 => cpu.and(bus, Immediate, t),
+        0x2A => cpu.rmw_accumulator(alu::rol),
+        0x2C => cpu.bit(bus, Absolute),
+        0x2D => cpu.and(bus, Absolute, t),
+        0x2E => cpu.rmw(bus, Absolute, alu::rol),
+        0x30 => cpu.branch(bus, cpu.registers.p.negative),
+        0x31 => cpu.and(bus, ZeroPageIndirectIndexed, t),
+        0x32 => cpu.and(bus, ZeroPageIndirect, t),
+        0x34 => cpu.bit(bus, ZeroPageX),
+        0x35 => cpu.and(bus, ZeroPageX, t),
+        0x36 => cpu.rmw(bus, ZeroPageX, alu::rol),
+        0x38 => cpu.set_flag(|p| p.carry = true),
+        0x39 => cpu.and(bus, AbsoluteY, t),
+        0x3A => cpu.rmw_accumulator(alu::dec),
+        0x3C => cpu.bit(bus, AbsoluteX),
+        0x3D => cpu.and(bus, AbsoluteX, t),
+        0x3E => cpu.rmw(bus, AbsoluteX, alu::rol),
+        0x40 => cpu.rti(bus),
+        0x41 => cpu.eor(bus, ZeroPageIndexedIndirect, t),
+        0x42 => cpu.say(),
+        0x43 => cpu.tma(bus),
+        0x44 => cpu.bsr(bus),
+        0x45 => cpu.eor(bus, ZeroPage, t),
+        0x46 => cpu.rmw(bus, ZeroPage, alu::lsr),
+        0x48 => cpu.push_register(bus, cpu.registers.a),
+        0x49 => cpu.eor(bus, Immediate, t),
+        0x4A => cpu.rmw_accumulator(alu::lsr),
+        0x4C => cpu.jmp_absolute(bus),
+        0x4D => cpu.eor(bus, Absolute, t),
+        0x4E => cpu.rmw(bus, Absolute, alu::lsr),
+        0x50 => cpu.branch(bus, !cpu.registers.p.overflow),
+        0x51 => cpu.eor(bus, ZeroPageIndirectIndexed, t),
+        0x52 => cpu.eor(bus, ZeroPageIndirect, t),
+        0x53 => cpu.tam(bus),
+        0x54 => cpu.csl(),
+        0x55 => cpu.eor(bus, ZeroPageX, t),
+        0x56 => cpu.rmw(bus, ZeroPageX, alu::lsr),
+        0x58 => cpu.set_flag(|p| p.interrupt_disable = false),
+        0x59 => cpu.eor(bus, AbsoluteY, t),
+        0x5A => cpu.push_register(bus, cpu.registers.y),
+        0x5D => cpu.eor(bus, AbsoluteX, t),
+        0x5E => cpu.rmw(bus, AbsoluteX, alu::lsr),
+        0x60 => cpu.rts(bus),
+        0x61 => cpu.adc(bus, ZeroPageIndexedIndirect, t),
+        0x62 => cpu.clear_register(|registers| registers.a = 0),
+        0x64 => cpu.stz(bus, ZeroPage),
+        0x65 => cpu.adc(bus, ZeroPage, t),
+        0x66 => cpu.rmw(bus, ZeroPage, alu::ror),
+        0x68 => cpu.pull_register(bus, |registers, value| registers.a = value),
+        0x69 => cpu.adc(bus, Immediate, t),
+        0x6A => cpu.rmw_accumulator(alu::ror),
+        0x6C => cpu.jmp_indirect(bus),
+        0x6D => cpu.adc(bus, Absolute, t),
+        0x6E => cpu.rmw(bus, Absolute, alu::ror),
+        0x70 => cpu.branch(bus, cpu.registers.p.overflow),
+        0x71 => cpu.adc(bus, ZeroPageIndirectIndexed, t),
+        0x72 => cpu.adc(bus, ZeroPageIndirect, t),
+        0x73 => cpu.start_block_transfer(bus, crate::BlockTransferKind::IncrementIncrement),
+        0x74 => cpu.stz(bus, ZeroPageX),
+        0x75 => cpu.adc(bus, ZeroPageX, t),
+        0x76 => cpu.rmw(bus, ZeroPageX, alu::ror),
+        0x78 => cpu.set_flag(|p| p.interrupt_disable = true),
+        0x79 => cpu.adc(bus, AbsoluteY, t),
+        0x7A => cpu.pull_register(bus, |registers, value| registers.y = value),
+        0x7C => cpu.jmp_indexed_indirect(bus),
+        0x7D => cpu.adc(bus, AbsoluteX, t),
+        0x7E => cpu.rmw(bus, AbsoluteX, alu::ror),
+        0x80 => cpu.branch(bus, true),
+        0x81 => cpu.store(bus, ZeroPageIndexedIndirect, cpu.registers.a),
+        0x82 => cpu.clear_register(|registers| registers.x = 0),
+        0x83 => cpu.tst(bus, ZeroPage),
+        0x84 => cpu.store(bus, ZeroPage, cpu.registers.y),
+        0x85 => cpu.store(bus, ZeroPage, cpu.registers.a),
+        0x86 => cpu.store(bus, ZeroPage, cpu.registers.x),
+        0x87 | 0x97 | 0xA7 | 0xB7 | 0xC7 | 0xD7 | 0xE7 | 0xF7 => cpu.smb(bus, (opcode >> 4) & 7),
+        0x88 => cpu.transfer(|registers| {
+            registers.y = registers.y.wrapping_sub(1);
+            registers.y
+        }),
+        0x89 => cpu.bit(bus, Immediate),
+        0x8A => cpu.transfer(|registers| {
+            registers.a = registers.x;
+            registers.a
+        }),
+        0x8C => cpu.store(bus, Absolute, cpu.registers.y),
+        0x8D => cpu.store(bus, Absolute, cpu.registers.a),
+        0x8E => cpu.store(bus, Absolute, cpu.registers.x),
+        0x8F | 0x9F | 0xAF | 0xBF | 0xCF | 0xDF | 0xEF | 0xFF => cpu.bbs(bus, (opcode >> 4) & 7),
+        0x90 => cpu.branch(bus, !cpu.registers.p.carry),
+        0x91 => cpu.store(bus, ZeroPageIndirectIndexed, cpu.registers.a),
+        0x92 => cpu.store(bus, ZeroPageIndirect, cpu.registers.a),
+        0x93 => cpu.tst(bus, Absolute),
+        0x94 => cpu.store(bus, ZeroPageX, cpu.registers.y),
+        0x95 => cpu.store(bus, ZeroPageX, cpu.registers.a),
+        0x96 => cpu.store(bus, ZeroPageY, cpu.registers.x),
+        0x98 => cpu.transfer(|registers| {
+            registers.a = registers.y;
+            registers.a
+        }),
+        0x99 => cpu.store(bus, AbsoluteY, cpu.registers.a),
+        0x9A => {
+            // TXS does not modify flags
+            cpu.registers.s = cpu.registers.x;
+            2
+        }
+        0x9C => cpu.stz(bus, Absolute),
+        0x9D => cpu.store(bus, AbsoluteX, cpu.registers.a),
+        0x9E => cpu.stz(bus, AbsoluteX),
+        0xA0 => cpu.load(bus, Immediate, |registers, value| registers.y = value),
+        0xA1 => cpu.load(bus, ZeroPageIndexedIndirect, |registers, value| registers.a = value),
+        0xA2 => cpu.load(bus, Immediate, |registers, value| registers.x = value),
+        0xA3 => cpu.tst(bus, ZeroPageX),
+        0xA4 => cpu.load(bus, ZeroPage, |registers, value| registers.y = value),
+        0xA5 => cpu.load(bus, ZeroPage, |registers, value| registers.a = value),
+        0xA6 => cpu.load(bus, ZeroPage, |registers, value| registers.x = value),
+        0xA8 => cpu.transfer(|registers| {
+            registers.y = registers.a;
+            registers.y
+        }),
+        0xA9 => cpu.load(bus, Immediate, |registers, value| registers.a = value),
+        0xAA => cpu.transfer(|registers| {
+            registers.x = registers.a;
+            registers.x
+        }),
+        0xAC => cpu.load(bus, Absolute, |registers, value| registers.y = value),
+        0xAD => cpu.load(bus, Absolute, |registers, value| registers.a = value),
+        0xAE => cpu.load(bus, Absolute, |registers, value| registers.x = value),
+        0xB0 => cpu.branch(bus, cpu.registers.p.carry),
+        0xB1 => cpu.load(bus, ZeroPageIndirectIndexed, |registers, value| registers.a = value),
+        0xB2 => cpu.load(bus, ZeroPageIndirect, |registers, value| registers.a = value),
+        0xB3 => cpu.tst(bus, AbsoluteX),
+        0xB4 => cpu.load(bus, ZeroPageX, |registers, value| registers.y = value),
+        0xB5 => cpu.load(bus, ZeroPageX, |registers, value| registers.a = value),
+        0xB6 => cpu.load(bus, ZeroPageY, |registers, value| registers.x = value),
+        0xB8 => cpu.set_flag(|p| p.overflow = false),
+        0xB9 => cpu.load(bus, AbsoluteY, |registers, value| registers.a = value),
+        0xBA => cpu.transfer(|registers| {
+            registers.x = registers.s;
+            registers.x
+        }),
+        0xBC => cpu.load(bus, AbsoluteX, |registers, value| registers.y = value),
+        0xBD => cpu.load(bus, AbsoluteX, |registers, value| registers.a = value),
+        0xBE => cpu.load(bus, AbsoluteY, |registers, value| registers.x = value),
+        0xC0 => cpu.compare(bus, Immediate, cpu.registers.y),
+        0xC1 => cpu.compare(bus, ZeroPageIndexedIndirect, cpu.registers.a),
+        0xC2 => cpu.clear_register(|registers| registers.y = 0),
+        0xC3 => cpu.start_block_transfer(bus, crate::BlockTransferKind::DecrementDecrement),
+        0xC4 => cpu.compare(bus, ZeroPage, cpu.registers.y),
+        0xC5 => cpu.compare(bus, ZeroPage, cpu.registers.a),
+        0xC6 => cpu.rmw(bus, ZeroPage, alu::dec),
+        0xC8 => cpu.transfer(|registers| {
+            registers.y = registers.y.wrapping_add(1);
+            registers.y
+        }),
+        0xC9 => cpu.compare(bus, Immediate, cpu.registers.a),
+        0xCA => cpu.transfer(|registers| {
+            registers.x = registers.x.wrapping_sub(1);
+            registers.x
+        }),
+        0xCC => cpu.compare(bus, Absolute, cpu.registers.y),
+        0xCD => cpu.compare(bus, Absolute, cpu.registers.a),
+        0xCE => cpu.rmw(bus, Absolute, alu::dec),
+        0xD0 => cpu.branch(bus, !cpu.registers.p.zero),
+        0xD1 => cpu.compare(bus, ZeroPageIndirectIndexed, cpu.registers.a),
+        0xD2 => cpu.compare(bus, ZeroPageIndirect, cpu.registers.a),
+        0xD3 => cpu.start_block_transfer(bus, crate::BlockTransferKind::IncrementNone),
+        0xD4 => cpu.csh(),
+        0xD5 => cpu.compare(bus, ZeroPageX, cpu.registers.a),
+        0xD6 => cpu.rmw(bus, ZeroPageX, alu::dec),
+        0xD8 => cpu.set_flag(|p| p.decimal = false),
+        0xD9 => cpu.compare(bus, AbsoluteY, cpu.registers.a),
+        0xDA => cpu.push_register(bus, cpu.registers.x),
+        0xDD => cpu.compare(bus, AbsoluteX, cpu.registers.a),
+        0xDE => cpu.rmw(bus, AbsoluteX, alu::dec),
+        0xE0 => cpu.compare(bus, Immediate, cpu.registers.x),
+        0xE1 => cpu.sbc(bus, ZeroPageIndexedIndirect),
+        0xE3 => cpu.start_block_transfer(bus, crate::BlockTransferKind::IncrementAlternate),
+        0xE4 => cpu.compare(bus, ZeroPage, cpu.registers.x),
+        0xE5 => cpu.sbc(bus, ZeroPage),
+        0xE6 => cpu.rmw(bus, ZeroPage, alu::inc),
+        0xE8 => cpu.transfer(|registers| {
+            registers.x = registers.x.wrapping_add(1);
+            registers.x
+        }),
+        0xE9 => cpu.sbc(bus, Immediate),
+        0xEC => cpu.compare(bus, Absolute, cpu.registers.x),
+        0xED => cpu.sbc(bus, Absolute),
+        0xEE => cpu.rmw(bus, Absolute, alu::inc),
+        0xF0 => cpu.branch(bus, cpu.registers.p.zero),
+        0xF1 => cpu.sbc(bus, ZeroPageIndirectIndexed),
+        0xF2 => cpu.sbc(bus, ZeroPageIndirect),
+        0xF3 => cpu.start_block_transfer(bus, crate::BlockTransferKind::AlternateIncrement),
+        0xF4 => cpu.set(),
+        0xF5 => cpu.sbc(bus, ZeroPageX),
+        0xF6 => cpu.rmw(bus, ZeroPageX, alu::inc),
+        0xF8 => cpu.set_flag(|p| p.decimal = true),
+        0xF9 => cpu.sbc(bus, AbsoluteY),
+        0xFA => cpu.pull_register(bus, |registers, value| registers.x = value),
+        0xFD => cpu.sbc(bus, AbsoluteX),
+        0xFE => cpu.rmw(bus, AbsoluteX, alu::inc),
+        // All other opcodes, including the 65C02 WAI and STP opcodes, are 2-cycle NOPs
+        _ => 2,
+    }
+}
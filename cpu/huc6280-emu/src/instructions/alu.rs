@@ -0,0 +1,413 @@
+use crate::instructions::{AddressingMode, ZERO_PAGE};
+use crate::traits::BusInterface;
+use crate::{HuC6280, Registers, StatusFlags};
+
+// Extra cycles taken by ADC/AND/EOR/ORA when the T flag is set
+const MEMORY_OPERATION_CYCLES: u32 = 3;
+
+fn set_nz(flags: &mut StatusFlags, value: u8) {
+    flags.negative = value & 0x80 != 0;
+    flags.zero = value == 0;
+}
+
+fn or(accumulator: u8, value: u8, flags: &mut StatusFlags) -> u8 {
+    let result = accumulator | value;
+    set_nz(flags, result);
+    result
+}
+
+fn and(accumulator: u8, value: u8, flags: &mut StatusFlags) -> u8 {
+    let result = accumulator & value;
+    set_nz(flags, result);
+    result
+}
+
+fn xor(accumulator: u8, value: u8, flags: &mut StatusFlags) -> u8 {
+    let result = accumulator ^ value;
+    set_nz(flags, result);
+    result
+}
+
+fn add(accumulator: u8, value: u8, flags: &mut StatusFlags) -> u8 {
+    if flags.decimal {
+        return add_decimal(accumulator, value, flags);
+    }
+
+    let sum = u16::from(accumulator) + u16::from(value) + u16::from(flags.carry);
+    let result = sum as u8;
+
+    flags.carry = sum > 0xFF;
+    flags.overflow = (accumulator ^ result) & (value ^ result) & 0x80 != 0;
+    set_nz(flags, result);
+
+    result
+}
+
+// Decimal mode behaves as on the 65C02: N and Z are valid and reflect the BCD result
+fn add_decimal(accumulator: u8, value: u8, flags: &mut StatusFlags) -> u8 {
+    let mut low = u16::from(accumulator & 0x0F) + u16::from(value & 0x0F) + u16::from(flags.carry);
+    if low >= 0x0A {
+        low = ((low + 0x06) & 0x0F) + 0x10;
+    }
+
+    let mut sum = u16::from(accumulator & 0xF0) + u16::from(value & 0xF0) + low;
+    flags.overflow = !(accumulator ^ value) & (accumulator ^ sum as u8) & 0x80 != 0;
+    if sum >= 0xA0 {
+        sum += 0x60;
+    }
+
+    let result = sum as u8;
+    flags.carry = sum > 0xFF;
+    set_nz(flags, result);
+
+    result
+}
+
+fn subtract(accumulator: u8, value: u8, flags: &mut StatusFlags) -> u8 {
+    let borrow = i16::from(!flags.carry);
+    let difference = i16::from(accumulator) - i16::from(value) - borrow;
+    let binary_result = difference as u8;
+
+    flags.carry = difference >= 0;
+    flags.overflow = (accumulator ^ value) & (accumulator ^ binary_result) & 0x80 != 0;
+
+    let result = if flags.decimal {
+        let low = i16::from(accumulator & 0x0F) - i16::from(value & 0x0F) - borrow;
+        let mut decimal_result = difference;
+        if difference < 0 {
+            decimal_result -= 0x60;
+        }
+        if low < 0 {
+            decimal_result -= 0x06;
+        }
+        decimal_result as u8
+    } else {
+        binary_result
+    };
+    set_nz(flags, result);
+
+    result
+}
+
+pub(super) fn asl(value: u8, flags: &mut StatusFlags) -> u8 {
+    let result = value << 1;
+    flags.carry = value & 0x80 != 0;
+    set_nz(flags, result);
+    result
+}
+
+pub(super) fn lsr(value: u8, flags: &mut StatusFlags) -> u8 {
+    let result = value >> 1;
+    flags.carry = value & 0x01 != 0;
+    set_nz(flags, result);
+    result
+}
+
+pub(super) fn rol(value: u8, flags: &mut StatusFlags) -> u8 {
+    let result = (value << 1) | u8::from(flags.carry);
+    flags.carry = value & 0x80 != 0;
+    set_nz(flags, result);
+    result
+}
+
+pub(super) fn ror(value: u8, flags: &mut StatusFlags) -> u8 {
+    let result = (value >> 1) | (u8::from(flags.carry) << 7);
+    flags.carry = value & 0x01 != 0;
+    set_nz(flags, result);
+    result
+}
+
+pub(super) fn inc(value: u8, flags: &mut StatusFlags) -> u8 {
+    let result = value.wrapping_add(1);
+    set_nz(flags, result);
+    result
+}
+
+pub(super) fn dec(value: u8, flags: &mut StatusFlags) -> u8 {
+    let result = value.wrapping_sub(1);
+    set_nz(flags, result);
+    result
+}
+
+impl HuC6280 {
+    // ADC/AND/EOR/ORA; if the T flag was set, these operate on the zero page byte at X instead of
+    // the accumulator
+    fn accumulator_op<B: BusInterface>(
+        &mut self,
+        bus: &mut B,
+        mode: AddressingMode,
+        memory_operation: bool,
+        op: fn(u8, u8, &mut StatusFlags) -> u8,
+    ) -> u32 {
+        let value = self.read_operand(bus, mode);
+
+        if memory_operation {
+            let address = ZERO_PAGE | u16::from(self.registers.x);
+            let operand = self.read(bus, address);
+            let result = op(operand, value, &mut self.registers.p);
+            self.write(bus, address, result);
+
+            mode.cycles() + MEMORY_OPERATION_CYCLES
+        } else {
+            self.registers.a = op(self.registers.a, value, &mut self.registers.p);
+
+            mode.cycles()
+        }
+    }
+
+    pub(super) fn ora<B: BusInterface>(
+        &mut self,
+        bus: &mut B,
+        mode: AddressingMode,
+        memory_operation: bool,
+    ) -> u32 {
+        self.accumulator_op(bus, mode, memory_operation, or)
+    }
+
+    pub(super) fn and<B: BusInterface>(
+        &mut self,
+        bus: &mut B,
+        mode: AddressingMode,
+        memory_operation: bool,
+    ) -> u32 {
+        self.accumulator_op(bus, mode, memory_operation, and)
+    }
+
+    pub(super) fn eor<B: BusInterface>(
+        &mut self,
+        bus: &mut B,
+        mode: AddressingMode,
+        memory_operation: bool,
+    ) -> u32 {
+        self.accumulator_op(bus, mode, memory_operation, xor)
+    }
+
+    pub(super) fn adc<B: BusInterface>(
+        &mut self,
+        bus: &mut B,
+        mode: AddressingMode,
+        memory_operation: bool,
+    ) -> u32 {
+        // Decimal mode takes an extra cycle
+        let decimal_cycles = u32::from(self.registers.p.decimal);
+        self.accumulator_op(bus, mode, memory_operation, add) + decimal_cycles
+    }
+
+    pub(super) fn sbc<B: BusInterface>(&mut self, bus: &mut B, mode: AddressingMode) -> u32 {
+        let value = self.read_operand(bus, mode);
+        self.registers.a = subtract(self.registers.a, value, &mut self.registers.p);
+
+        mode.cycles() + u32::from(self.registers.p.decimal)
+    }
+
+    pub(super) fn compare<B: BusInterface>(
+        &mut self,
+        bus: &mut B,
+        mode: AddressingMode,
+        register: u8,
+    ) -> u32 {
+        let value = self.read_operand(bus, mode);
+        self.registers.p.carry = register >= value;
+        set_nz(&mut self.registers.p, register.wrapping_sub(value));
+
+        mode.cycles()
+    }
+
+    pub(super) fn rmw<B: BusInterface>(
+        &mut self,
+        bus: &mut B,
+        mode: AddressingMode,
+        op: fn(u8, &mut StatusFlags) -> u8,
+    ) -> u32 {
+        let address = self.resolve_address(bus, mode);
+        let value = self.read(bus, address);
+        let result = op(value, &mut self.registers.p);
+        self.write(bus, address, result);
+
+        mode.rmw_cycles()
+    }
+
+    pub(super) fn rmw_accumulator(&mut self, op: fn(u8, &mut StatusFlags) -> u8) -> u32 {
+        self.registers.a = op(self.registers.a, &mut self.registers.p);
+
+        2
+    }
+
+    // Unlike the 65C02, BIT sets N and V from the operand even in immediate mode
+    pub(super) fn bit<B: BusInterface>(&mut self, bus: &mut B, mode: AddressingMode) -> u32 {
+        let value = self.read_operand(bus, mode);
+        self.registers.p.negative = value & 0x80 != 0;
+        self.registers.p.overflow = value & 0x40 != 0;
+        self.registers.p.zero = self.registers.a & value == 0;
+
+        mode.cycles()
+    }
+
+    fn test_and_modify<B: BusInterface>(
+        &mut self,
+        bus: &mut B,
+        mode: AddressingMode,
+        op: fn(u8, u8) -> u8,
+    ) -> u32 {
+        let address = self.resolve_address(bus, mode);
+        let value = self.read(bus, address);
+
+        self.registers.p.negative = value & 0x80 != 0;
+        self.registers.p.overflow = value & 0x40 != 0;
+        self.registers.p.zero = self.registers.a & value == 0;
+
+        self.write(bus, address, op(value, self.registers.a));
+
+        mode.rmw_cycles()
+    }
+
+    pub(super) fn tsb<B: BusInterface>(&mut self, bus: &mut B, mode: AddressingMode) -> u32 {
+        self.test_and_modify(bus, mode, |value, a| value | a)
+    }
+
+    pub(super) fn trb<B: BusInterface>(&mut self, bus: &mut B, mode: AddressingMode) -> u32 {
+        self.test_and_modify(bus, mode, |value, a| value & !a)
+    }
+
+    // TST #imm, <ea>
+    pub(super) fn tst<B: BusInterface>(&mut self, bus: &mut B, mode: AddressingMode) -> u32 {
+        let mask = self.fetch_operand(bus);
+        let address = self.resolve_address(bus, mode);
+        let value = self.read(bus, address);
+
+        self.registers.p.negative = value & 0x80 != 0;
+        self.registers.p.overflow = value & 0x40 != 0;
+        self.registers.p.zero = mask & value == 0;
+
+        match mode {
+            AddressingMode::ZeroPage | AddressingMode::ZeroPageX => 7,
+            _ => 8,
+        }
+    }
+
+    pub(super) fn rmb<B: BusInterface>(&mut self, bus: &mut B, bit: u8) -> u32 {
+        let address = self.resolve_address(bus, AddressingMode::ZeroPage);
+        let value = self.read(bus, address);
+        self.write(bus, address, value & !(1 << bit));
+
+        7
+    }
+
+    pub(super) fn smb<B: BusInterface>(&mut self, bus: &mut B, bit: u8) -> u32 {
+        let address = self.resolve_address(bus, AddressingMode::ZeroPage);
+        let value = self.read(bus, address);
+        self.write(bus, address, value | (1 << bit));
+
+        7
+    }
+
+    pub(super) fn load<B: BusInterface>(
+        &mut self,
+        bus: &mut B,
+        mode: AddressingMode,
+        set_register: fn(&mut Registers, u8),
+    ) -> u32 {
+        let value = self.read_operand(bus, mode);
+        set_register(&mut self.registers, value);
+        set_nz(&mut self.registers.p, value);
+
+        mode.cycles()
+    }
+
+    pub(super) fn store<B: BusInterface>(
+        &mut self,
+        bus: &mut B,
+        mode: AddressingMode,
+        value: u8,
+    ) -> u32 {
+        let address = self.resolve_address(bus, mode);
+        self.write(bus, address, value);
+
+        mode.cycles()
+    }
+
+    pub(super) fn stz<B: BusInterface>(&mut self, bus: &mut B, mode: AddressingMode) -> u32 {
+        self.store(bus, mode, 0)
+    }
+
+    // Register transfers, increments, and decrements; the closure returns the value used to set N/Z
+    pub(super) fn transfer(&mut self, op: fn(&mut Registers) -> u8) -> u32 {
+        let value = op(&mut self.registers);
+        set_nz(&mut self.registers.p, value);
+
+        2
+    }
+
+    // CLA/CLX/CLY do not modify flags
+    pub(super) fn clear_register(&mut self, op: fn(&mut Registers)) -> u32 {
+        op(&mut self.registers);
+
+        2
+    }
+
+    pub(super) fn set_flag(&mut self, op: fn(&mut StatusFlags)) -> u32 {
+        op(&mut self.registers.p);
+
+        2
+    }
+
+    pub(super) fn pull_register<B: BusInterface>(
+        &mut self,
+        bus: &mut B,
+        set_register: fn(&mut Registers, u8),
+    ) -> u32 {
+        let value = self.pull(bus);
+        set_register(&mut self.registers, value);
+        set_nz(&mut self.registers.p, value);
+
+        4
+    }
+
+    pub(super) fn push_register<B: BusInterface>(&mut self, bus: &mut B, value: u8) -> u32 {
+        self.push(bus, value);
+
+        3
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flags(carry: bool, decimal: bool) -> StatusFlags {
+        StatusFlags { decimal, carry, ..StatusFlags::default() }
+    }
+
+    #[test]
+    fn binary_add() {
+        let mut p = flags(false, false);
+        assert_eq!(add(0x50, 0x50, &mut p), 0xA0);
+        assert!(p.overflow && p.negative && !p.carry);
+
+        let mut p = flags(true, false);
+        assert_eq!(add(0xFF, 0x00, &mut p), 0x00);
+        assert!(p.carry && p.zero && !p.overflow);
+    }
+
+    #[test]
+    fn decimal_add() {
+        let mut p = flags(false, true);
+        assert_eq!(add(0x19, 0x28, &mut p), 0x47);
+        assert!(!p.carry);
+
+        let mut p = flags(true, true);
+        assert_eq!(add(0x99, 0x00, &mut p), 0x00);
+        assert!(p.carry && p.zero);
+    }
+
+    #[test]
+    fn decimal_subtract() {
+        let mut p = flags(true, true);
+        assert_eq!(subtract(0x42, 0x13, &mut p), 0x29);
+        assert!(p.carry);
+
+        let mut p = flags(true, true);
+        assert_eq!(subtract(0x00, 0x01, &mut p), 0x99);
+        assert!(!p.carry && p.negative);
+    }
+}
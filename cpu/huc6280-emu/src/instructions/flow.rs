@@ -0,0 +1,138 @@
+use crate::instructions::AddressingMode;
+use crate::traits::BusInterface;
+use crate::{HuC6280, StatusFlags, IRQ2_VECTOR};
+use jgenesis_common::num::GetBit;
+
+impl HuC6280 {
+    fn take_branch(&mut self, offset: u8) {
+        self.registers.pc = self.registers.pc.wrapping_add(offset as i8 as u16);
+    }
+
+    pub(super) fn branch<B: BusInterface>(&mut self, bus: &mut B, condition: bool) -> u32 {
+        let offset = self.fetch_operand(bus);
+
+        if condition {
+            self.take_branch(offset);
+            4
+        } else {
+            2
+        }
+    }
+
+    fn branch_on_bit<B: BusInterface>(&mut self, bus: &mut B, bit: u8, set: bool) -> u32 {
+        let address = self.resolve_address(bus, AddressingMode::ZeroPage);
+        let value = self.read(bus, address);
+        let offset = self.fetch_operand(bus);
+
+        if value.bit(bit) == set {
+            self.take_branch(offset);
+            8
+        } else {
+            6
+        }
+    }
+
+    pub(super) fn bbr<B: BusInterface>(&mut self, bus: &mut B, bit: u8) -> u32 {
+        self.branch_on_bit(bus, bit, false)
+    }
+
+    pub(super) fn bbs<B: BusInterface>(&mut self, bus: &mut B, bit: u8) -> u32 {
+        self.branch_on_bit(bus, bit, true)
+    }
+
+    pub(super) fn jmp_absolute<B: BusInterface>(&mut self, bus: &mut B) -> u32 {
+        self.registers.pc = self.fetch_operand_u16(bus);
+
+        4
+    }
+
+    // JMP (abs); the HuC6280 does not have the NMOS 6502's page wrapping bug
+    pub(super) fn jmp_indirect<B: BusInterface>(&mut self, bus: &mut B) -> u32 {
+        let pointer = self.fetch_operand_u16(bus);
+        self.registers.pc = self.read_u16(bus, pointer);
+
+        7
+    }
+
+    // JMP (abs,X)
+    pub(super) fn jmp_indexed_indirect<B: BusInterface>(&mut self, bus: &mut B) -> u32 {
+        let pointer = self.fetch_operand_u16(bus).wrapping_add(self.registers.x.into());
+        self.registers.pc = self.read_u16(bus, pointer);
+
+        7
+    }
+
+    pub(super) fn jsr<B: BusInterface>(&mut self, bus: &mut B) -> u32 {
+        let address = self.fetch_operand_u16(bus);
+        self.push_u16(bus, self.registers.pc.wrapping_sub(1));
+        self.registers.pc = address;
+
+        7
+    }
+
+    // BSR: branch to subroutine, pushing the return address the same way as JSR
+    pub(super) fn bsr<B: BusInterface>(&mut self, bus: &mut B) -> u32 {
+        let offset = self.fetch_operand(bus);
+        self.push_u16(bus, self.registers.pc.wrapping_sub(1));
+        self.take_branch(offset);
+
+        8
+    }
+
+    pub(super) fn rts<B: BusInterface>(&mut self, bus: &mut B) -> u32 {
+        self.registers.pc = self.pull_u16(bus).wrapping_add(1);
+
+        7
+    }
+
+    pub(super) fn rti<B: BusInterface>(&mut self, bus: &mut B) -> u32 {
+        self.registers.p = self.pull(bus).into();
+        self.registers.pc = self.pull_u16(bus);
+
+        7
+    }
+
+    pub(super) fn php<B: BusInterface>(&mut self, bus: &mut B) -> u32 {
+        self.push(bus, self.registers.p.to_byte(true));
+
+        3
+    }
+
+    pub(super) fn plp<B: BusInterface>(&mut self, bus: &mut B) -> u32 {
+        self.registers.p = self.pull(bus).into();
+
+        4
+    }
+
+    fn enter_interrupt_handler<B: BusInterface>(
+        &mut self,
+        bus: &mut B,
+        vector: u16,
+        break_flag: bool,
+    ) {
+        self.push_u16(bus, self.registers.pc);
+        self.push(bus, self.registers.p.to_byte(break_flag));
+
+        self.registers.p = StatusFlags {
+            interrupt_disable: true,
+            decimal: false,
+            memory_operation: false,
+            ..self.registers.p
+        };
+        self.registers.pc = self.read_u16(bus, vector);
+    }
+
+    pub(super) fn brk<B: BusInterface>(&mut self, bus: &mut B) -> u32 {
+        // BRK skips over the byte following the opcode
+        self.registers.pc = self.registers.pc.wrapping_add(1);
+        self.enter_interrupt_handler(bus, IRQ2_VECTOR, true);
+
+        8
+    }
+
+    pub(super) fn handle_interrupt<B: BusInterface>(&mut self, bus: &mut B, vector: u16) -> u32 {
+        self.enter_interrupt_handler(bus, vector, false);
+
+        8
+    }
+}
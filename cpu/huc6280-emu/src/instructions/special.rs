@@ -0,0 +1,151 @@
+//! Instructions that are unique to the `HuC6280`
+
+use crate::traits::BusInterface;
+use crate::{BlockTransfer, BlockTransferKind, ClockSpeed, HuC6280};
+use jgenesis_common::num::GetBit;
+
+// Physical address of the VDC, which ST0/ST1/ST2 write to regardless of the MPR contents
+const VDC_PHYSICAL_ADDRESS: u32 = 0x1FE000;
+
+// Block transfers take 17 cycles of overhead plus 6 cycles per byte
+const BLOCK_TRANSFER_SETUP_CYCLES: u32 = 17;
+const BLOCK_TRANSFER_BYTE_CYCLES: u32 = 6;
+
+impl HuC6280 {
+    pub(super) fn sxy(&mut self) -> u32 {
+        std::mem::swap(&mut self.registers.x, &mut self.registers.y);
+
+        3
+    }
+
+    pub(super) fn sax(&mut self) -> u32 {
+        std::mem::swap(&mut self.registers.a, &mut self.registers.x);
+
+        3
+    }
+
+    pub(super) fn say(&mut self) -> u32 {
+        std::mem::swap(&mut self.registers.a, &mut self.registers.y);
+
+        3
+    }
+
+    // ST0/ST1/ST2: Store an immediate value to the given VDC port
+    pub(super) fn st<B: BusInterface>(&mut self, bus: &mut B, port: u32) -> u32 {
+        let value = self.fetch_operand(bus);
+        bus.write(VDC_PHYSICAL_ADDRESS | port, value);
+
+        5
+    }
+
+    // TAM: Copy A to every MPR whose bit is set in the operand
+    pub(super) fn tam<B: BusInterface>(&mut self, bus: &mut B) -> u32 {
+        let mask = self.fetch_operand(bus);
+        for i in 0..8 {
+            if mask.bit(i) {
+                self.registers.mpr[i as usize] = self.registers.a;
+            }
+        }
+
+        5
+    }
+
+    // TMA: Copy an MPR to A; the operand should have exactly one bit set
+    pub(super) fn tma<B: BusInterface>(&mut self, bus: &mut B) -> u32 {
+        let mask = self.fetch_operand(bus);
+        if mask != 0 {
+            self.registers.a = self.registers.mpr[mask.trailing_zeros() as usize];
+        }
+
+        4
+    }
+
+    pub(super) fn csl(&mut self) -> u32 {
+        self.registers.clock_speed = ClockSpeed::Low;
+
+        3
+    }
+
+    pub(super) fn csh(&mut self) -> u32 {
+        self.registers.clock_speed = ClockSpeed::High;
+
+        3
+    }
+
+    pub(super) fn set(&mut self) -> u32 {
+        self.registers.p.memory_operation = true;
+
+        2
+    }
+
+    pub(super) fn start_block_transfer<B: BusInterface>(
+        &mut self,
+        bus: &mut B,
+        kind: BlockTransferKind,
+    ) -> u32 {
+        let source = self.fetch_operand_u16(bus);
+        let destination = self.fetch_operand_u16(bus);
+        let length = self.fetch_operand_u16(bus);
+
+        // Block transfers save Y, A, and X on the stack and restore them once finished
+        self.push(bus, self.registers.y);
+        self.push(bus, self.registers.a);
+        self.push(bus, self.registers.x);
+
+        // A length of 0 transfers 64KB
+        let remaining = if length == 0 { 0x10000 } else { length.into() };
+        self.block_transfer =
+            Some(BlockTransfer { kind, source, destination, remaining, alternate: false });
+
+        BLOCK_TRANSFER_SETUP_CYCLES
+    }
+
+    pub(super) fn continue_block_transfer<B: BusInterface>(&mut self, bus: &mut B) -> u32 {
+        let Some(mut transfer) = self.block_transfer.take() else {
+            return 0;
+        };
+
+        let alternate_offset = u16::from(transfer.alternate);
+        let (source, destination) = match transfer.kind {
+            BlockTransferKind::AlternateIncrement => {
+                (transfer.source.wrapping_add(alternate_offset), transfer.destination)
+            }
+            BlockTransferKind::IncrementAlternate => {
+                (transfer.source, transfer.destination.wrapping_add(alternate_offset))
+            }
+            _ => (transfer.source, transfer.destination),
+        };
+
+        let value = self.read(bus, source);
+        self.write(bus, destination, value);
+
+        match transfer.kind {
+            BlockTransferKind::IncrementIncrement => {
+                transfer.source = transfer.source.wrapping_add(1);
+                transfer.destination = transfer.destination.wrapping_add(1);
+            }
+            BlockTransferKind::DecrementDecrement => {
+                transfer.source = transfer.source.wrapping_sub(1);
+                transfer.destination = transfer.destination.wrapping_sub(1);
+            }
+            BlockTransferKind::IncrementNone | BlockTransferKind::IncrementAlternate => {
+                transfer.source = transfer.source.wrapping_add(1);
+            }
+            BlockTransferKind::AlternateIncrement => {
+                transfer.destination = transfer.destination.wrapping_add(1);
+            }
+        }
+        transfer.alternate = !transfer.alternate;
+        transfer.remaining -= 1;
+
+        if transfer.remaining == 0 {
+            self.registers.x = self.pull(bus);
+            self.registers.a = self.pull(bus);
+            self.registers.y = self.pull(bus);
+        } else {
+            self.block_transfer = Some(transfer);
+        }
+
+        BLOCK_TRANSFER_BYTE_CYCLES
+    }
+}
@@ -0,0 +1,181 @@
+mod instructions;
+pub mod traits;
+
+pub use traits::BusInterface;
+
+use bincode::{Decode, Encode};
+use jgenesis_common::num::GetBit;
+
+pub const RESET_VECTOR: u16 = 0xFFFE;
+pub const TIMER_IRQ_VECTOR: u16 = 0xFFFA;
+pub const IRQ1_VECTOR: u16 = 0xFFF8;
+pub const IRQ2_VECTOR: u16 = 0xFFF6;
+
+/// The `HuC6280` can run at either 7.16 MHz or 1.79 MHz, switched in software using the CSH and CSL
+/// instructions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode)]
+pub enum ClockSpeed {
+    /// 1.79 MHz
+    #[default]
+    Low,
+    /// 7.16 MHz
+    High,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Encode, Decode)]
+pub struct StatusFlags {
+    pub negative: bool,
+    pub overflow: bool,
+    /// T flag; when set, the next ADC/AND/EOR/ORA instruction operates on the zero page byte
+    /// at X instead of the accumulator
+    pub memory_operation: bool,
+    pub decimal: bool,
+    pub interrupt_disable: bool,
+    pub zero: bool,
+    pub carry: bool,
+}
+
+impl StatusFlags {
+    fn to_byte(self, break_flag: bool) -> u8 {
+        (u8::from(self.negative) << 7)
+            | (u8::from(self.overflow) << 6)
+            | (u8::from(self.memory_operation) << 5)
+            | (u8::from(break_flag) << 4)
+            | (u8::from(self.decimal) << 3)
+            | (u8::from(self.interrupt_disable) << 2)
+            | (u8::from(self.zero) << 1)
+            | u8::from(self.carry)
+    }
+}
+
+impl From<StatusFlags> for u8 {
+    fn from(value: StatusFlags) -> Self {
+        value.to_byte(false)
+    }
+}
+
+impl From<u8> for StatusFlags {
+    fn from(value: u8) -> Self {
+        Self {
+            negative: value.bit(7),
+            overflow: value.bit(6),
+            memory_operation: value.bit(5),
+            decimal: value.bit(3),
+            interrupt_disable: value.bit(2),
+            zero: value.bit(1),
+            carry: value.bit(0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Encode, Decode)]
+pub struct Registers {
+    pub a: u8,
+    pub x: u8,
+    pub y: u8,
+    pub s: u8,
+    pub p: StatusFlags,
+    pub pc: u16,
+    /// Memory mapping registers; MPR N maps logical addresses N*$2000 through N*$2000+$1FFF to the
+    /// 8KB physical bank MPR[N]
+    pub mpr: [u8; 8],
+    pub clock_speed: ClockSpeed,
+}
+
+impl Registers {
+    /// Translate a 16-bit logical address to a 21-bit physical address using the MPRs.
+    #[inline]
+    #[must_use]
+    pub fn map_address(&self, logical_address: u16) -> u32 {
+        let bank = self.mpr[(logical_address >> 13) as usize];
+        (u32::from(bank) << 13) | u32::from(logical_address & 0x1FFF)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+enum BlockTransferKind {
+    // TII: Increment source and destination
+    IncrementIncrement,
+    // TDD: Decrement source and destination
+    DecrementDecrement,
+    // TIN: Increment source, fixed destination
+    IncrementNone,
+    // TIA: Increment source, alternate destination
+    IncrementAlternate,
+    // TAI: Alternate source, increment destination
+    AlternateIncrement,
+}
+
+#[derive(Debug, Clone, Encode, Decode)]
+struct BlockTransfer {
+    kind: BlockTransferKind,
+    source: u16,
+    destination: u16,
+    remaining: u32,
+    alternate: bool,
+}
+
+#[derive(Debug, Clone, Default, Encode, Decode)]
+pub struct HuC6280 {
+    registers: Registers,
+    // Block transfer instructions copy one byte per call to execute_instruction so that a
+    // transfer of up to 64KB does not stall the rest of the system
+    block_transfer: Option<BlockTransfer>,
+}
+
+impl HuC6280 {
+    /// Create a new `HuC6280` and immediately reset it.
+    #[must_use]
+    pub fn new<B: BusInterface>(bus: &mut B) -> Self {
+        let mut cpu = Self::default();
+        cpu.reset(bus);
+        cpu
+    }
+
+    /// Reset the CPU. This maps physical bank $00 to logical $E000-$FFFF so that the reset vector
+    /// is read from the start of the `HuCard`, switches to low clock speed, and disables interrupts.
+    pub fn reset<B: BusInterface>(&mut self, bus: &mut B) {
+        self.registers.mpr[7] = 0x00;
+        self.registers.clock_speed = ClockSpeed::Low;
+        self.registers.p.interrupt_disable = true;
+        self.registers.p.decimal = false;
+        self.registers.p.memory_operation = false;
+        self.block_transfer = None;
+
+        let pc_lsb = bus.read(self.registers.map_address(RESET_VECTOR));
+        let pc_msb = bus.read(self.registers.map_address(RESET_VECTOR + 1));
+        self.registers.pc = u16::from_le_bytes([pc_lsb, pc_msb]);
+    }
+
+    /// Execute a single instruction or service an interrupt, returning the number of CPU cycles
+    /// taken. Block transfer instructions execute one byte per call.
+    ///
+    /// The length of a cycle depends on [`Self::clock_speed`].
+    pub fn execute_instruction<B: BusInterface>(&mut self, bus: &mut B) -> u32 {
+        instructions::execute(self, bus)
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn clock_speed(&self) -> ClockSpeed {
+        self.registers.clock_speed
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn registers(&self) -> &Registers {
+        &self.registers
+    }
+
+    pub fn set_registers(&mut self, registers: Registers) {
+        self.registers = registers;
+    }
+
+    /// Whether the CPU is in the middle of a block transfer instruction, during which it does not
+    /// respond to interrupts.
+    #[inline]
+    #[must_use]
+    pub fn is_mid_block_transfer(&self) -> bool {
+        self.block_transfer.is_some()
+    }
+}
@@ -0,0 +1,58 @@
+use jgenesis_common::debug::{MemoryAccessKind, MemoryAccessLog};
+
+pub trait BusInterface {
+    /// Read a byte from the given 21-bit physical address.
+    fn read(&mut self, address: u32) -> u8;
+
+    /// Write a byte to the given 21-bit physical address.
+    fn write(&mut self, address: u32, value: u8);
+
+    /// Poll the IRQ1 line (VDC interrupts on the PC Engine), after applying the interrupt disable
+    /// register.
+    fn irq1(&self) -> bool;
+
+    /// Poll the IRQ2 line (external/CD-ROM interrupts on the PC Engine), after applying the
+    /// interrupt disable register.
+    fn irq2(&self) -> bool;
+
+    /// Poll the internal timer interrupt line, after applying the interrupt disable register.
+    fn timer_irq(&self) -> bool;
+}
+
+/// Bus wrapper that records every memory access to a [`MemoryAccessLog`], for use by debugger
+/// watchpoints.
+pub struct LoggingBus<'a, B> {
+    bus: &'a mut B,
+    log: &'a mut MemoryAccessLog,
+}
+
+impl<'a, B: BusInterface> LoggingBus<'a, B> {
+    pub fn new(bus: &'a mut B, log: &'a mut MemoryAccessLog) -> Self {
+        Self { bus, log }
+    }
+}
+
+impl<B: BusInterface> BusInterface for LoggingBus<'_, B> {
+    fn read(&mut self, address: u32) -> u8 {
+        let value = self.bus.read(address);
+        self.log.record(address, value, MemoryAccessKind::Read);
+        value
+    }
+
+    fn write(&mut self, address: u32, value: u8) {
+        self.bus.write(address, value);
+        self.log.record(address, value, MemoryAccessKind::Write);
+    }
+
+    fn irq1(&self) -> bool {
+        self.bus.irq1()
+    }
+
+    fn irq2(&self) -> bool {
+        self.bus.irq2()
+    }
+
+    fn timer_irq(&self) -> bool {
+        self.bus.timer_irq()
+    }
+}
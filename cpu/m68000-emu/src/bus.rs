@@ -1,3 +1,4 @@
+use crate::traits;
 use crate::traits::BusInterface;
 
 pub struct InMemoryBus {
@@ -17,8 +18,7 @@ impl BusInterface for InMemoryBus {
     }
 
     fn read_word(&mut self, address: u32) -> u16 {
-        let address = (address & Self::ADDRESS_MASK) as usize;
-        u16::from_be_bytes([self.memory[address], self.memory[address.wrapping_add(1)]])
+        u16::from_be_bytes([self.read_byte(address), self.read_byte(address.wrapping_add(1))])
     }
 
     fn write_byte(&mut self, address: u32, value: u8) {
@@ -27,17 +27,17 @@ impl BusInterface for InMemoryBus {
 
     fn write_word(&mut self, address: u32, value: u16) {
         let [msb, lsb] = value.to_be_bytes();
-        let address = (address & Self::ADDRESS_MASK) as usize;
-
-        self.memory[address] = msb;
-        self.memory[address.wrapping_add(1)] = lsb;
+        self.write_byte(address, msb);
+        self.write_byte(address.wrapping_add(1), lsb);
     }
 
     fn interrupt_level(&self) -> u8 {
         0
     }
 
-    fn acknowledge_interrupt(&mut self) {}
+    fn acknowledge_interrupt(&mut self, interrupt_level: u8) -> u8 {
+        traits::autovector(interrupt_level)
+    }
 
     fn halt(&self) -> bool {
         false
@@ -53,3 +53,22 @@ impl Default for InMemoryBus {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn word_access_wraps_at_end_of_address_space() {
+        let mut bus = InMemoryBus::new();
+
+        bus.write_word(0xFFFFFF, 0x1234);
+        assert_eq!(bus.read_byte(0xFFFFFF), 0x12);
+        assert_eq!(bus.read_byte(0x000000), 0x34);
+        assert_eq!(bus.read_word(0xFFFFFF), 0x1234);
+
+        bus.write_long_word(0xFFFFFE, 0x5678_9ABC);
+        assert_eq!(bus.read_long_word(0xFFFFFE), 0x5678_9ABC);
+        assert_eq!(bus.read_word(0x000000), 0x9ABC);
+    }
+}
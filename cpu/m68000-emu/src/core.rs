@@ -1,7 +1,9 @@
 mod instructions;
+#[cfg(test)]
+mod tests;
 
 use crate::core::instructions::Instruction;
-use crate::traits::BusInterface;
+use crate::traits::{BusInterface, SPURIOUS_INTERRUPT_VECTOR, UNINITIALIZED_INTERRUPT_VECTOR};
 use jgenesis_common::num::GetBit;
 use jgenesis_proc_macros::EnumAll;
 use std::fmt::{Display, Formatter};
@@ -52,6 +54,7 @@ struct Registers {
     trace_enabled: bool,
     address_error: bool,
     stopped: bool,
+    halted: bool,
 }
 
 const DEFAULT_INTERRUPT_MASK: u8 = 7;
@@ -70,6 +73,7 @@ impl Registers {
             trace_enabled: false,
             address_error: false,
             stopped: false,
+            halted: false,
         }
     }
 
@@ -412,7 +416,7 @@ const ADDRESS_ERROR_VECTOR: u32 = 3;
 const ILLEGAL_OPCODE_VECTOR: u32 = 4;
 const DIVIDE_BY_ZERO_VECTOR: u32 = 5;
 const CHECK_REGISTER_VECTOR: u32 = 6;
-const AUTO_VECTORED_INTERRUPT_BASE_ADDRESS: u32 = 0x60;
+const PRIVILEGE_VIOLATION_VECTOR: u32 = 8;
 
 impl<'registers, 'bus, B: BusInterface> InstructionExecutor<'registers, 'bus, B> {
     fn new(
@@ -775,6 +779,23 @@ impl<'registers, 'bus, B: BusInterface> InstructionExecutor<'registers, 'bus, B>
         Ok(value)
     }
 
+    // Group 1 and 2 exceptions (traps, interrupts, etc.) push a 6-byte frame with the PC at the
+    // higher address and the SR at the lower address
+    fn push_exception_frame(&mut self, pc: u32, sr: u16) -> ExecuteResult<()> {
+        self.push_stack_u32(pc)?;
+        self.push_stack_u16(sr)?;
+
+        Ok(())
+    }
+
+    // Returns (SR, PC)
+    fn pop_exception_frame(&mut self) -> ExecuteResult<(u16, u32)> {
+        let sr = self.pop_stack_u16()?;
+        let pc = self.pop_stack_u32()?;
+
+        Ok((sr, pc))
+    }
+
     fn handle_address_error(&mut self, address: u32, op_type: BusOpType) -> ExecuteResult<()> {
         let sr = self.registers.status_register();
         let supervisor_mode = self.registers.supervisor_mode;
@@ -808,9 +829,8 @@ impl<'registers, 'bus, B: BusInterface> InstructionExecutor<'registers, 'bus, B>
         };
 
         log::trace!("Address error PC: {pc:08X}");
-        self.push_stack_u32(pc)?;
         log::trace!("Address error SR: {sr:08X}");
-        self.push_stack_u16(sr)?;
+        self.push_exception_frame(pc, sr)?;
         log::trace!("Address error opcode: {:08X}", self.opcode);
         self.push_stack_u16(self.opcode)?;
         self.push_stack_u32(address)?;
@@ -842,41 +862,79 @@ impl<'registers, 'bus, B: BusInterface> InstructionExecutor<'registers, 'bus, B>
         self.registers.trace_enabled = false;
         self.registers.supervisor_mode = true;
 
-        self.push_stack_u32(pc)?;
-        self.push_stack_u16(sr)?;
+        self.push_exception_frame(pc, sr)?;
 
         self.registers.pc = self.bus.read_long_word(vector * 4);
 
         Ok(())
     }
 
-    fn handle_auto_vectored_interrupt(&mut self, interrupt_level: u8) -> ExecuteResult<u32> {
+    fn handle_interrupt(&mut self, interrupt_level: u8, vector: u8) -> ExecuteResult<u32> {
         let sr = self.registers.status_register();
         self.registers.trace_enabled = false;
         self.registers.supervisor_mode = true;
         self.registers.interrupt_priority_mask = interrupt_level;
 
-        self.push_stack_u32(self.registers.pc)?;
-        self.push_stack_u16(sr)?;
+        self.push_exception_frame(self.registers.pc, sr)?;
 
-        let vector_addr = AUTO_VECTORED_INTERRUPT_BASE_ADDRESS + 4 * u32::from(interrupt_level);
-        self.registers.pc = self.bus.read_long_word(vector_addr);
+        self.registers.pc = self.bus.read_long_word(4 * u32::from(vector));
 
         Ok(44)
     }
 
+    // Exception processing can only fail due to an address error while pushing the exception frame,
+    // e.g. because SSP is odd
+    fn handle_exception_processing_error(&mut self, err: Exception) {
+        let Exception::AddressError(address, op_type) = err else {
+            panic!("unexpected exception during exception processing: {err:?}");
+        };
+
+        self.registers.address_error = true;
+        if self.handle_address_error(address, op_type).is_err() {
+            self.double_fault();
+        }
+    }
+
+    // An address error while processing an address error halts the CPU until it is reset
+    fn double_fault(&mut self) {
+        log::error!("[{}] 68000 double fault; halting CPU until reset", self.name);
+
+        self.registers.halted = true;
+    }
+
     fn execute(mut self) -> u32 {
         self.registers.address_error = false;
 
         // TODO properly handle non-maskable level 7 interrupts?
         let interrupt_level = self.bus.interrupt_level() & 0x07;
         if interrupt_level > self.registers.interrupt_priority_mask {
-            log::trace!("[{}] Handling interrupt of level {interrupt_level}", self.name);
-            self.bus.acknowledge_interrupt();
+            let vector = self.bus.acknowledge_interrupt(interrupt_level);
+            log::trace!(
+                "[{}] Handling interrupt of level {interrupt_level} with vector {vector}",
+                self.name
+            );
+
+            match vector {
+                SPURIOUS_INTERRUPT_VECTOR => {
+                    log::warn!("[{}] Spurious level {interrupt_level} interrupt", self.name);
+                }
+                UNINITIALIZED_INTERRUPT_VECTOR => {
+                    log::warn!(
+                        "[{}] Level {interrupt_level} interrupt from device with uninitialized vector",
+                        self.name
+                    );
+                }
+                _ => {}
+            }
+
             self.registers.stopped = false;
-            return self
-                .handle_auto_vectored_interrupt(interrupt_level)
-                .unwrap_or_else(|_err| todo!("address error during interrupt service routine"));
+            return match self.handle_interrupt(interrupt_level, vector) {
+                Ok(cycles) => cycles,
+                Err(err) => {
+                    self.handle_exception_processing_error(err);
+                    50
+                }
+            };
         }
 
         if self.registers.stopped {
@@ -893,24 +951,34 @@ impl<'registers, 'bus, B: BusInterface> InstructionExecutor<'registers, 'bus, B>
 
                 self.registers.address_error = true;
                 if self.handle_address_error(address, op_type).is_err() {
-                    todo!("halt CPU")
+                    self.double_fault();
                 }
 
                 // Not completely accurate but close enough; this shouldn't occur in real software
                 50
             }
-            Err(Exception::PrivilegeViolation) => todo!("privilege violation"),
+            Err(Exception::PrivilegeViolation) => {
+                log::error!("[{}] Encountered 68000 privilege violation", self.name);
+
+                // All privileged instructions fail before fetching any extension words
+                if let Err(err) =
+                    self.handle_trap(PRIVILEGE_VIOLATION_VECTOR, self.registers.pc.wrapping_sub(2))
+                {
+                    self.handle_exception_processing_error(err);
+                }
+
+                34
+            }
             Err(Exception::IllegalInstruction(opcode)) => {
                 log::error!(
                     "[{}] Illegal opcode executed: {opcode:04X} / {opcode:016b}",
                     self.name
                 );
 
-                if self
-                    .handle_trap(ILLEGAL_OPCODE_VECTOR, self.registers.pc.wrapping_sub(2))
-                    .is_err()
+                if let Err(err) =
+                    self.handle_trap(ILLEGAL_OPCODE_VECTOR, self.registers.pc.wrapping_sub(2))
                 {
-                    todo!("???")
+                    self.handle_exception_processing_error(err);
                 }
 
                 // TODO this shouldn't happen in real software
@@ -919,25 +987,24 @@ impl<'registers, 'bus, B: BusInterface> InstructionExecutor<'registers, 'bus, B>
             Err(Exception::DivisionByZero { cycles }) => {
                 log::error!("[{}] Encountered 68000 divide by zero error", self.name);
 
-                if self
-                    .handle_trap(DIVIDE_BY_ZERO_VECTOR, self.registers.pc.wrapping_sub(4))
-                    .is_err()
+                if let Err(err) =
+                    self.handle_trap(DIVIDE_BY_ZERO_VECTOR, self.registers.pc.wrapping_sub(4))
                 {
-                    todo!("???")
+                    self.handle_exception_processing_error(err);
                 }
 
                 38 + cycles
             }
             Err(Exception::Trap(vector)) => {
-                if self.handle_trap(vector, self.registers.pc).is_err() {
-                    todo!("???")
+                if let Err(err) = self.handle_trap(vector, self.registers.pc) {
+                    self.handle_exception_processing_error(err);
                 }
 
                 34
             }
             Err(Exception::CheckRegister { cycles }) => {
-                if self.handle_trap(CHECK_REGISTER_VECTOR, self.registers.pc).is_err() {
-                    todo!("???")
+                if let Err(err) = self.handle_trap(CHECK_REGISTER_VECTOR, self.registers.pc) {
+                    self.handle_exception_processing_error(err);
                 }
 
                 30 + cycles
@@ -980,7 +1047,6 @@ impl M68000Builder {
     pub fn build(self) -> M68000 {
         M68000 {
             registers: Registers::new(),
-            halted: false,
             allow_tas_writes: self.allow_tas_writes,
             name: self.name.unwrap_or_default(),
         }
@@ -993,7 +1059,6 @@ const RESET_CYCLES: u32 = 132;
 #[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
 pub struct M68000 {
     registers: Registers,
-    halted: bool,
     allow_tas_writes: bool,
     // Used only for trace logging
     name: String,
@@ -1018,6 +1083,7 @@ impl M68000 {
         self.registers.interrupt_priority_mask = DEFAULT_INTERRUPT_MASK;
 
         self.registers.stopped = false;
+        self.registers.halted = false;
 
         // Read SSP from $000000 and PC from $000004
         self.registers.ssp = bus.read_long_word(0);
@@ -1081,6 +1147,21 @@ impl M68000 {
         self.registers.address_error
     }
 
+    /// Whether the CPU is stopped after executing a STOP instruction. A stopped CPU does not execute
+    /// any instructions until it receives an interrupt above its interrupt priority mask, or until it
+    /// is reset.
+    #[must_use]
+    pub fn is_stopped(&self) -> bool {
+        self.registers.stopped
+    }
+
+    /// Whether the CPU is halted due to a double fault, i.e. an address error that occurred while
+    /// processing an exception. A halted CPU does nothing until it is reset.
+    #[must_use]
+    pub fn is_halted(&self) -> bool {
+        self.registers.halted
+    }
+
     #[inline]
     pub fn execute_instruction<B: BusInterface>(&mut self, bus: &mut B) -> u32 {
         if bus.reset() {
@@ -1088,6 +1169,10 @@ impl M68000 {
             return RESET_CYCLES;
         }
 
+        if self.registers.halted {
+            return 4;
+        }
+
         if bus.halt() {
             return 1;
         }
@@ -106,9 +106,7 @@ impl<'registers, 'bus, B: BusInterface> InstructionExecutor<'registers, 'bus, B>
             return Err(Exception::PrivilegeViolation);
         }
 
-        let sr = self.pop_stack_u16()?;
-
-        let pc = self.pop_stack_u32()?;
+        let (sr, pc) = self.pop_exception_frame()?;
         self.registers.set_status_register(sr);
         self.registers.pc = self.check_jump_address(pc)?;
 
@@ -238,6 +236,18 @@ impl<'registers, 'bus, B: BusInterface> InstructionExecutor<'registers, 'bus, B>
 
         Ok(4)
     }
+
+    pub(super) fn reset(&mut self) -> ExecuteResult<u32> {
+        if !self.registers.supervisor_mode {
+            return Err(Exception::PrivilegeViolation);
+        }
+
+        // RESET asserts the bus reset line for 124 cycles; this resets external devices but does
+        // not affect the CPU itself
+        self.bus.reset_external_devices();
+
+        Ok(132)
+    }
 }
 
 fn jump_cycles(addressing_mode: AddressingMode) -> u32 {
@@ -263,12 +273,6 @@ pub(super) const fn nop() -> u32 {
     4
 }
 
-pub(super) const fn reset() -> u32 {
-    // The RESET instruction is intended to reset external devices, but this functionality isn't
-    // used in the Genesis, so treat it as an extremely long NOP
-    132
-}
-
 pub(super) fn trap(vector: u32) -> ExecuteResult<u32> {
     Err(Exception::Trap(TRAP_VECTOR_OFFSET + vector))
 }
@@ -317,6 +317,11 @@ fn populate_addq(table: &mut InstructionTable) {
     for q_value in 0..8 {
         for dest in dest_addressing_modes() {
             for size in OpSize::ALL {
+                // Byte-size ADDQ to an address register is not a valid instruction
+                if size == OpSize::Byte && dest.is_address_direct() {
+                    continue;
+                }
+
                 let opcode = 0x5000 | size.to_bits() | dest.to_bits() | (q_value << 9);
                 let source = if q_value == 0 {
                     AddressingMode::Quick(8)
@@ -1133,6 +1138,11 @@ fn populate_subq(table: &mut InstructionTable) {
     // SUBQ #<d>, <ea>
     for dest in dest_addressing_modes() {
         for size in OpSize::ALL {
+            // Byte-size SUBQ to an address register is not a valid instruction
+            if size == OpSize::Byte && dest.is_address_direct() {
+                continue;
+            }
+
             for q_value in 0..8_u16 {
                 let opcode = 0x5100 | size.to_bits() | dest.to_bits() | (q_value << 9);
                 let source = if q_value == 0 {
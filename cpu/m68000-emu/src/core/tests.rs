@@ -0,0 +1,683 @@
+//! Instruction tests that compare against reference traces: the CPU state before executing a single
+//! instruction, and the expected CPU state and cycle count after executing it.
+//!
+//! Traces use the same format as the 68000 tests from <https://github.com/TomHarte/ProcessorTests>.
+//! The traces in this file cover stack-heavy instructions with easy-to-miss edge cases; to also run
+//! a directory of trace files from that repository (`.json` or `.json.gz`), set the
+//! `M68000_REFERENCE_TRACES` environment variable to the directory path.
+
+use crate::bus::InMemoryBus;
+use crate::traits;
+use crate::traits::{BusInterface, SPURIOUS_INTERRUPT_VECTOR, UNINITIALIZED_INTERRUPT_VECTOR};
+use crate::M68000;
+use flate2::read::GzDecoder;
+use serde::Deserialize;
+use std::ffi::OsStr;
+use std::fmt::Write;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+use std::{env, fs};
+
+const REFERENCE_TRACES_VAR: &str = "M68000_REFERENCE_TRACES";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+struct CpuState {
+    d0: u32,
+    d1: u32,
+    d2: u32,
+    d3: u32,
+    d4: u32,
+    d5: u32,
+    d6: u32,
+    d7: u32,
+    a0: u32,
+    a1: u32,
+    a2: u32,
+    a3: u32,
+    a4: u32,
+    a5: u32,
+    a6: u32,
+    usp: u32,
+    ssp: u32,
+    sr: u16,
+    pc: u32,
+    // The first two words of the instruction; ignored in expected states
+    prefetch: [u16; 2],
+    ram: Vec<(u32, u8)>,
+}
+
+impl CpuState {
+    fn data_registers(&self) -> [u32; 8] {
+        [self.d0, self.d1, self.d2, self.d3, self.d4, self.d5, self.d6, self.d7]
+    }
+
+    fn address_registers(&self) -> [u32; 7] {
+        [self.a0, self.a1, self.a2, self.a3, self.a4, self.a5, self.a6]
+    }
+
+    // Read back the current values of every address in the expected state's RAM
+    fn capture(m68000: &M68000, bus: &mut InMemoryBus, expected: &Self) -> Self {
+        let [d0, d1, d2, d3, d4, d5, d6, d7] = m68000.data_registers();
+        let [a0, a1, a2, a3, a4, a5, a6] = m68000.address_registers();
+
+        Self {
+            d0,
+            d1,
+            d2,
+            d3,
+            d4,
+            d5,
+            d6,
+            d7,
+            a0,
+            a1,
+            a2,
+            a3,
+            a4,
+            a5,
+            a6,
+            usp: m68000.user_stack_pointer(),
+            ssp: m68000.supervisor_stack_pointer(),
+            sr: m68000.status_register(),
+            pc: m68000.pc(),
+            prefetch: expected.prefetch,
+            ram: expected
+                .ram
+                .iter()
+                .map(|&(address, _)| (address, bus.read_byte(address)))
+                .collect(),
+        }
+    }
+
+    fn diff(&self, expected: &Self) -> String {
+        let mut diff = String::new();
+
+        let registers = [
+            ("d0", self.d0, expected.d0),
+            ("d1", self.d1, expected.d1),
+            ("d2", self.d2, expected.d2),
+            ("d3", self.d3, expected.d3),
+            ("d4", self.d4, expected.d4),
+            ("d5", self.d5, expected.d5),
+            ("d6", self.d6, expected.d6),
+            ("d7", self.d7, expected.d7),
+            ("a0", self.a0, expected.a0),
+            ("a1", self.a1, expected.a1),
+            ("a2", self.a2, expected.a2),
+            ("a3", self.a3, expected.a3),
+            ("a4", self.a4, expected.a4),
+            ("a5", self.a5, expected.a5),
+            ("a6", self.a6, expected.a6),
+            ("usp", self.usp, expected.usp),
+            ("ssp", self.ssp, expected.ssp),
+            ("sr", self.sr.into(), expected.sr.into()),
+            ("pc", self.pc, expected.pc),
+        ];
+        for (name, actual, expected) in registers {
+            if actual != expected {
+                writeln!(diff, "  {name}: actual={actual:08X}, expected={expected:08X}").unwrap();
+            }
+        }
+
+        for (&(address, actual), &(_, expected)) in self.ram.iter().zip(&expected.ram) {
+            if actual != expected {
+                writeln!(diff, "  {address:08X}: actual={actual:02X}, expected={expected:02X}")
+                    .unwrap();
+            }
+        }
+
+        diff
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct TraceCase {
+    name: String,
+    initial: CpuState,
+    #[serde(rename = "final")]
+    expected: CpuState,
+    #[serde(rename = "length")]
+    cycles: u32,
+}
+
+// Returns a description of every mismatch, or None if the instruction matched the trace
+fn run_case(case: &TraceCase) -> Option<String> {
+    let mut bus = InMemoryBus::new();
+    let initial = &case.initial;
+
+    let mut m68000 = M68000::default();
+    m68000.set_data_registers(initial.data_registers());
+    m68000.set_address_registers(initial.address_registers(), initial.usp, initial.ssp);
+    m68000.set_status_register(initial.sr);
+    m68000.set_pc(initial.pc);
+
+    bus.write_word(initial.pc, initial.prefetch[0]);
+    bus.write_word(initial.pc.wrapping_add(2), initial.prefetch[1]);
+    for &(address, value) in &initial.ram {
+        bus.write_byte(address, value);
+    }
+
+    let cycles = m68000.execute_instruction(&mut bus);
+    let actual = CpuState::capture(&m68000, &mut bus, &case.expected);
+
+    let mut diff = actual.diff(&case.expected);
+    // Timings after address errors are not emulated accurately
+    if cycles != case.cycles && !m68000.address_error() {
+        writeln!(diff, "  cycles: actual={cycles}, expected={}", case.cycles).unwrap();
+    }
+
+    (!diff.is_empty()).then(|| format!("'{}':\n{diff}", case.name))
+}
+
+fn assert_traces_match(cases: &[TraceCase]) {
+    let failures: Vec<_> = cases.iter().filter_map(run_case).collect();
+    assert!(
+        failures.is_empty(),
+        "{} of {} traces did not match:\n{}",
+        failures.len(),
+        cases.len(),
+        failures.join("\n")
+    );
+}
+
+fn load_trace_file(path: &Path) -> Vec<TraceCase> {
+    let file = BufReader::new(File::open(path).unwrap());
+    let reader: Box<dyn Read> = match path.extension().and_then(OsStr::to_str) {
+        Some("gz") => Box::new(GzDecoder::new(file)),
+        _ => Box::new(file),
+    };
+
+    serde_json::from_reader(reader)
+        .unwrap_or_else(|err| panic!("invalid trace file '{}': {err}", path.display()))
+}
+
+// Supervisor mode with interrupts masked; SSP=$2000, USP=$3000
+fn supervisor_state(prefetch: [u16; 2]) -> CpuState {
+    CpuState { usp: 0x3000, ssp: 0x2000, sr: 0x2700, pc: 0x1000, prefetch, ..CpuState::default() }
+}
+
+fn user_state(prefetch: [u16; 2]) -> CpuState {
+    CpuState { sr: 0x0000, ..supervisor_state(prefetch) }
+}
+
+fn long_word_bytes(address: u32, value: u32) -> [(u32, u8); 4] {
+    let [b0, b1, b2, b3] = value.to_be_bytes();
+    [(address, b0), (address + 1, b1), (address + 2, b2), (address + 3, b3)]
+}
+
+fn word_bytes(address: u32, value: u16) -> [(u32, u8); 2] {
+    let [msb, lsb] = value.to_be_bytes();
+    [(address, msb), (address + 1, lsb)]
+}
+
+#[test]
+fn movem_predecrement() {
+    let cases = [
+        // Registers are stored from A7 down to D0, so D0 ends up at the lowest address
+        TraceCase {
+            name: "MOVEM.L D0-D1/A0,-(A7)".into(),
+            initial: CpuState {
+                d0: 0x0011_2233,
+                d1: 0x4455_6677,
+                a0: 0x8899_AABB,
+                ..supervisor_state([0x48E7, 0xC080])
+            },
+            expected: CpuState {
+                d0: 0x0011_2233,
+                d1: 0x4455_6677,
+                a0: 0x8899_AABB,
+                ssp: 0x1FF4,
+                pc: 0x1004,
+                ram: [
+                    long_word_bytes(0x1FF4, 0x0011_2233),
+                    long_word_bytes(0x1FF8, 0x4455_6677),
+                    long_word_bytes(0x1FFC, 0x8899_AABB),
+                ]
+                .concat(),
+                ..supervisor_state([0, 0])
+            },
+            cycles: 32,
+        },
+        // The 68000 stores the initial value of the address register, not the decremented value
+        TraceCase {
+            name: "MOVEM.W D0/A1,-(A1)".into(),
+            initial: CpuState { d0: 0xFFFF_1234, a1: 0x3000, ..supervisor_state([0x48A1, 0x8040]) },
+            expected: CpuState {
+                d0: 0xFFFF_1234,
+                a1: 0x2FFC,
+                pc: 0x1004,
+                ram: [word_bytes(0x2FFC, 0x1234), word_bytes(0x2FFE, 0x3000)].concat(),
+                ..supervisor_state([0, 0])
+            },
+            cycles: 16,
+        },
+    ];
+
+    assert_traces_match(&cases);
+}
+
+#[test]
+fn movem_memory_to_registers() {
+    let cases = [
+        // Word transfers are sign extended to 32 bits, including for data registers
+        TraceCase {
+            name: "MOVEM.W (A0)+,D0/A1".into(),
+            initial: CpuState {
+                d0: 0x1234_5678,
+                a0: 0x3000,
+                ram: [word_bytes(0x3000, 0x8001), word_bytes(0x3002, 0x7FFF)].concat(),
+                ..supervisor_state([0x4C98, 0x0201])
+            },
+            expected: CpuState {
+                d0: 0xFFFF_8001,
+                a0: 0x3004,
+                a1: 0x0000_7FFF,
+                pc: 0x1004,
+                ..supervisor_state([0, 0])
+            },
+            cycles: 20,
+        },
+        // With postincrement, the value loaded into the address register is overwritten by the
+        // incremented address
+        TraceCase {
+            name: "MOVEM.L (A0)+,D0/A0".into(),
+            initial: CpuState {
+                a0: 0x3000,
+                ram: [long_word_bytes(0x3000, 0xDEAD_BEEF), long_word_bytes(0x3004, 0x1234_5678)]
+                    .concat(),
+                ..supervisor_state([0x4CD8, 0x0101])
+            },
+            expected: CpuState {
+                d0: 0xDEAD_BEEF,
+                a0: 0x3008,
+                pc: 0x1004,
+                ..supervisor_state([0, 0])
+            },
+            cycles: 28,
+        },
+        // Control addressing modes transfer in ascending order, D0 first
+        TraceCase {
+            name: "MOVEM.L D0-D1,(A2)".into(),
+            initial: CpuState {
+                d0: 0x0102_0304,
+                d1: 0x0506_0708,
+                a2: 0x3000,
+                ..supervisor_state([0x48D2, 0x0003])
+            },
+            expected: CpuState {
+                d0: 0x0102_0304,
+                d1: 0x0506_0708,
+                a2: 0x3000,
+                pc: 0x1004,
+                ram: [long_word_bytes(0x3000, 0x0102_0304), long_word_bytes(0x3004, 0x0506_0708)]
+                    .concat(),
+                ..supervisor_state([0, 0])
+            },
+            cycles: 24,
+        },
+    ];
+
+    assert_traces_match(&cases);
+}
+
+#[test]
+fn link_unlk() {
+    let cases = [
+        TraceCase {
+            name: "LINK A6,#-8".into(),
+            initial: CpuState { a6: 0x1234_5678, ..supervisor_state([0x4E56, 0xFFF8]) },
+            expected: CpuState {
+                a6: 0x1FFC,
+                ssp: 0x1FF4,
+                pc: 0x1004,
+                ram: long_word_bytes(0x1FFC, 0x1234_5678).to_vec(),
+                ..supervisor_state([0, 0])
+            },
+            cycles: 16,
+        },
+        // LINK A7 pushes the already-decremented stack pointer
+        TraceCase {
+            name: "LINK A7,#4".into(),
+            initial: supervisor_state([0x4E57, 0x0004]),
+            expected: CpuState {
+                ssp: 0x2000,
+                pc: 0x1004,
+                ram: long_word_bytes(0x1FFC, 0x1FFC).to_vec(),
+                ..supervisor_state([0, 0])
+            },
+            cycles: 16,
+        },
+        // User mode uses USP
+        TraceCase {
+            name: "LINK A5,#0 (user mode)".into(),
+            initial: CpuState { a5: 0x0000_4000, ..user_state([0x4E55, 0x0000]) },
+            expected: CpuState {
+                a5: 0x2FFC,
+                usp: 0x2FFC,
+                pc: 0x1004,
+                ram: long_word_bytes(0x2FFC, 0x4000).to_vec(),
+                ..user_state([0, 0])
+            },
+            cycles: 16,
+        },
+        TraceCase {
+            name: "UNLK A6".into(),
+            initial: CpuState {
+                a6: 0x1FFC,
+                ssp: 0x1000,
+                ram: long_word_bytes(0x1FFC, 0x1234_5678).to_vec(),
+                ..supervisor_state([0x4E5E, 0x0000])
+            },
+            expected: CpuState {
+                a6: 0x1234_5678,
+                ssp: 0x2000,
+                pc: 0x1002,
+                ..supervisor_state([0, 0])
+            },
+            cycles: 12,
+        },
+        // UNLK A7 loads the popped value into the stack pointer, discarding the increment
+        TraceCase {
+            name: "UNLK A7".into(),
+            initial: CpuState {
+                ram: long_word_bytes(0x2000, 0x4000).to_vec(),
+                ..supervisor_state([0x4E5F, 0x0000])
+            },
+            expected: CpuState { ssp: 0x4000, pc: 0x1002, ..supervisor_state([0, 0]) },
+            cycles: 12,
+        },
+    ];
+
+    assert_traces_match(&cases);
+}
+
+#[test]
+fn exception_frames() {
+    let cases = [
+        // Frame is SR at the lower address followed by the PC of the next instruction
+        TraceCase {
+            name: "TRAP #0".into(),
+            initial: CpuState {
+                sr: 0x2715,
+                ram: long_word_bytes(0x80, 0x5000).to_vec(),
+                ..supervisor_state([0x4E40, 0x0000])
+            },
+            expected: CpuState {
+                sr: 0x2715,
+                ssp: 0x1FFA,
+                pc: 0x5000,
+                ram: [word_bytes(0x1FFA, 0x2715).as_slice(), &long_word_bytes(0x1FFC, 0x1002)]
+                    .concat(),
+                ..supervisor_state([0, 0])
+            },
+            cycles: 34,
+        },
+        // Returning to user mode switches the active stack pointer to USP
+        TraceCase {
+            name: "RTE (to user mode)".into(),
+            initial: CpuState {
+                ram: [word_bytes(0x2000, 0x0015).as_slice(), &long_word_bytes(0x2002, 0x5000)]
+                    .concat(),
+                ..supervisor_state([0x4E73, 0x0000])
+            },
+            expected: CpuState { ssp: 0x2006, sr: 0x0015, pc: 0x5000, ..supervisor_state([0, 0]) },
+            cycles: 20,
+        },
+        TraceCase {
+            name: "RTE (to supervisor mode)".into(),
+            initial: CpuState {
+                ram: [word_bytes(0x2000, 0x2404).as_slice(), &long_word_bytes(0x2002, 0x5000)]
+                    .concat(),
+                ..supervisor_state([0x4E73, 0x0000])
+            },
+            expected: CpuState { ssp: 0x2006, sr: 0x2404, pc: 0x5000, ..supervisor_state([0, 0]) },
+            cycles: 20,
+        },
+        // RTE is privileged; the frame holds the address of the RTE itself
+        TraceCase {
+            name: "RTE (privilege violation)".into(),
+            initial: CpuState {
+                ram: long_word_bytes(0x20, 0x6000).to_vec(),
+                ..user_state([0x4E73, 0x0000])
+            },
+            expected: CpuState {
+                ssp: 0x1FFA,
+                sr: 0x2000,
+                pc: 0x6000,
+                ram: [word_bytes(0x1FFA, 0x0000).as_slice(), &long_word_bytes(0x1FFC, 0x1000)]
+                    .concat(),
+                ..supervisor_state([0, 0])
+            },
+            cycles: 34,
+        },
+    ];
+
+    assert_traces_match(&cases);
+}
+
+// Raises a single interrupt and responds to the interrupt acknowledge cycle with a fixed vector
+struct InterruptBus {
+    memory: InMemoryBus,
+    interrupt_level: u8,
+    vector: u8,
+    acknowledged_level: Option<u8>,
+    reset_line: bool,
+    external_device_resets: u32,
+}
+
+impl InterruptBus {
+    fn new(interrupt_level: u8, vector: u8) -> Self {
+        Self {
+            memory: InMemoryBus::new(),
+            interrupt_level,
+            vector,
+            acknowledged_level: None,
+            reset_line: false,
+            external_device_resets: 0,
+        }
+    }
+}
+
+impl BusInterface for InterruptBus {
+    fn read_byte(&mut self, address: u32) -> u8 {
+        self.memory.read_byte(address)
+    }
+
+    fn read_word(&mut self, address: u32) -> u16 {
+        self.memory.read_word(address)
+    }
+
+    fn write_byte(&mut self, address: u32, value: u8) {
+        self.memory.write_byte(address, value);
+    }
+
+    fn write_word(&mut self, address: u32, value: u16) {
+        self.memory.write_word(address, value);
+    }
+
+    fn interrupt_level(&self) -> u8 {
+        self.interrupt_level
+    }
+
+    fn acknowledge_interrupt(&mut self, interrupt_level: u8) -> u8 {
+        self.acknowledged_level = Some(interrupt_level);
+        self.interrupt_level = 0;
+        self.vector
+    }
+
+    fn halt(&self) -> bool {
+        false
+    }
+
+    fn reset(&self) -> bool {
+        self.reset_line
+    }
+
+    fn reset_external_devices(&mut self) {
+        self.external_device_resets += 1;
+    }
+}
+
+fn interrupt_test_cpu() -> M68000 {
+    let mut m68000 = M68000::default();
+    m68000.set_address_registers([0; 7], 0x3000, 0x2000);
+    m68000.set_status_register(0x2300);
+    m68000.set_pc(0x1000);
+    m68000
+}
+
+#[test]
+fn vectored_interrupts() {
+    // Vector number returned from interrupt acknowledge, and the address of that vector
+    let vectors = [
+        (traits::autovector(4), 0x70),
+        (0x40, 0x100),
+        (UNINITIALIZED_INTERRUPT_VECTOR, 0x3C),
+        (SPURIOUS_INTERRUPT_VECTOR, 0x60),
+    ];
+
+    for (vector, vector_address) in vectors {
+        let mut bus = InterruptBus::new(4, vector);
+        bus.write_long_word(vector_address, 0x5000);
+
+        let mut m68000 = interrupt_test_cpu();
+        let cycles = m68000.execute_instruction(&mut bus);
+
+        assert_eq!(bus.acknowledged_level, Some(4), "vector {vector}");
+        assert_eq!(m68000.pc(), 0x5000, "vector {vector}");
+        // Interrupt mask is raised to the level of the interrupt
+        assert_eq!(m68000.status_register(), 0x2400, "vector {vector}");
+        assert_eq!(m68000.supervisor_stack_pointer(), 0x1FFA, "vector {vector}");
+        assert_eq!(bus.read_word(0x1FFA), 0x2300, "vector {vector}");
+        assert_eq!(bus.read_long_word(0x1FFC), 0x1000, "vector {vector}");
+        assert_eq!(cycles, 44, "vector {vector}");
+    }
+}
+
+#[test]
+fn masked_interrupt_not_acknowledged() {
+    let mut bus = InterruptBus::new(3, traits::autovector(3));
+    // NOP
+    bus.write_word(0x1000, 0x4E71);
+
+    let mut m68000 = interrupt_test_cpu();
+    m68000.execute_instruction(&mut bus);
+
+    assert_eq!(bus.acknowledged_level, None);
+    assert_eq!(m68000.pc(), 0x1002);
+}
+
+#[test]
+fn stop_until_interrupt() {
+    let mut bus = InterruptBus::new(0, traits::autovector(4));
+    // STOP #$2000
+    bus.write_word(0x1000, 0x4E72);
+    bus.write_word(0x1002, 0x2000);
+    bus.write_long_word(0x70, 0x5000);
+
+    let mut m68000 = interrupt_test_cpu();
+    m68000.execute_instruction(&mut bus);
+
+    assert!(m68000.is_stopped());
+    assert_eq!(m68000.status_register(), 0x2000);
+    assert_eq!(m68000.pc(), 0x1004);
+
+    // Nothing executes while stopped
+    assert_eq!(m68000.execute_instruction(&mut bus), 4);
+    assert!(m68000.is_stopped());
+    assert_eq!(m68000.pc(), 0x1004);
+
+    bus.interrupt_level = 4;
+    m68000.execute_instruction(&mut bus);
+
+    assert!(!m68000.is_stopped());
+    assert_eq!(m68000.pc(), 0x5000);
+    // The stacked PC is the instruction after STOP
+    assert_eq!(bus.read_long_word(0x1FFC), 0x1004);
+}
+
+#[test]
+fn reset_instruction() {
+    let mut bus = InterruptBus::new(0, 0);
+    // RESET
+    bus.write_word(0x1000, 0x4E70);
+
+    let mut m68000 = interrupt_test_cpu();
+    let cycles = m68000.execute_instruction(&mut bus);
+
+    assert_eq!(cycles, 132);
+    assert_eq!(bus.external_device_resets, 1);
+    assert_eq!(m68000.pc(), 0x1002);
+    assert_eq!(m68000.supervisor_stack_pointer(), 0x2000);
+
+    // RESET is privileged
+    bus.write_word(0x1002, 0x4E70);
+    bus.write_long_word(0x20, 0x6000);
+    m68000.set_status_register(0x0000);
+    let cycles = m68000.execute_instruction(&mut bus);
+
+    assert_eq!(cycles, 34);
+    assert_eq!(bus.external_device_resets, 1);
+    assert_eq!(m68000.pc(), 0x6000);
+    assert_eq!(bus.read_long_word(0x1FFC), 0x1002);
+}
+
+#[test]
+fn double_fault_halts_until_reset() {
+    let mut bus = InterruptBus::new(0, 0);
+    // ILLEGAL
+    bus.write_word(0x1000, 0x4AFC);
+    // Reset vectors
+    bus.write_long_word(0, 0x2000);
+    bus.write_long_word(4, 0x8000);
+
+    // Stacking the illegal instruction exception frame causes an address error, and stacking the
+    // address error exception frame causes another
+    let mut m68000 = interrupt_test_cpu();
+    m68000.set_supervisor_stack_pointer(0x2001);
+    m68000.execute_instruction(&mut bus);
+
+    assert!(m68000.is_halted());
+
+    let pc = m68000.pc();
+    bus.write_word(pc, 0x4E71);
+    assert_eq!(m68000.execute_instruction(&mut bus), 4);
+    assert_eq!(m68000.pc(), pc);
+
+    bus.reset_line = true;
+    m68000.execute_instruction(&mut bus);
+
+    assert!(!m68000.is_halted());
+    assert_eq!(m68000.pc(), 0x8000);
+    assert_eq!(m68000.supervisor_stack_pointer(), 0x2000);
+}
+
+#[test]
+fn reference_trace_directory() {
+    let Some(dir) = env::var_os(REFERENCE_TRACES_VAR) else { return };
+
+    let mut paths: Vec<_> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| matches!(path.extension().and_then(OsStr::to_str), Some("json" | "gz")))
+        .collect();
+    paths.sort();
+
+    // Run every file before failing, reporting the first mismatch in each file
+    let mut failures = Vec::new();
+    for path in paths {
+        let cases = load_trace_file(&path);
+        let mismatches: Vec<_> = cases.iter().filter_map(run_case).collect();
+        if let Some(first_mismatch) = mismatches.first() {
+            failures.push(format!(
+                "{}: {} of {} traces did not match; first mismatch was {first_mismatch}",
+                path.display(),
+                mismatches.len(),
+                cases.len()
+            ));
+        }
+    }
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
@@ -0,0 +1,41 @@
+//! Entry points for the cargo-fuzz targets in the repository's `fuzz` directory
+
+use crate::bus::InMemoryBus;
+use crate::traits::BusInterface;
+use crate::M68000;
+
+const MAX_INSTRUCTIONS: u32 = 1000;
+
+/// Run the CPU from reset with the input loaded into memory starting at $000000, which means that
+/// the first 8 bytes are the initial stack pointer and PC.
+///
+/// Arbitrary code must never panic the CPU core. It can raise any exception, including address
+/// errors and double faults that halt the CPU.
+pub fn execute(data: &[u8]) {
+    let mut bus = InMemoryBus::new();
+    for (address, &byte) in (0..=InMemoryBus::ADDRESS_MASK).zip(data) {
+        bus.write_byte(address, byte);
+    }
+
+    let mut m68000 = M68000::default();
+    m68000.set_supervisor_stack_pointer(bus.read_long_word(0));
+    m68000.set_pc(bus.read_long_word(4));
+
+    for _ in 0..MAX_INSTRUCTIONS {
+        if m68000.is_halted() {
+            break;
+        }
+
+        m68000.execute_instruction(&mut bus);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn execute_corpus() {
+        jgenesis_common::fuzz::replay_corpus("m68000_execute", execute);
+    }
+}
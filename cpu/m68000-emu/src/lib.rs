@@ -1,6 +1,8 @@
 #[cfg(any(test, feature = "memorybus"))]
 pub mod bus;
 mod core;
+#[cfg(any(test, feature = "fuzzing"))]
+pub mod fuzz;
 pub mod traits;
 
 pub use crate::core::M68000;
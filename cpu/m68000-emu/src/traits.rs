@@ -1,3 +1,20 @@
+use jgenesis_common::debug::{MemoryAccessKind, MemoryAccessLog};
+
+/// Exception vector number for a spurious interrupt, i.e. an interrupt acknowledge cycle that was
+/// terminated with a bus error because no device responded.
+pub const SPURIOUS_INTERRUPT_VECTOR: u8 = 24;
+
+/// Exception vector number that 68000 peripherals return during interrupt acknowledge if their
+/// interrupt vector register has not been initialized since reset.
+pub const UNINITIALIZED_INTERRUPT_VECTOR: u8 = 15;
+
+/// Exception vector number for an auto-vectored interrupt of the given level (1-7).
+#[inline]
+#[must_use]
+pub const fn autovector(interrupt_level: u8) -> u8 {
+    SPURIOUS_INTERRUPT_VECTOR + interrupt_level
+}
+
 pub trait BusInterface {
     // Addresses are 32-bit internally but the 68000 only has a 24-bit address bus
     const ADDRESS_MASK: u32 = 0x00FF_FFFF;
@@ -25,12 +42,99 @@ pub trait BusInterface {
         self.write_word(address.wrapping_add(2), low_word);
     }
 
-    // Auto-vectored interrupt level; should be between 0 and 7, with 0 indicating no interrupt
+    // Interrupt level; should be between 0 and 7, with 0 indicating no interrupt
     fn interrupt_level(&self) -> u8;
 
-    fn acknowledge_interrupt(&mut self);
+    /// Perform an interrupt acknowledge cycle for an interrupt of the given level and return the
+    /// exception vector number to jump to.
+    ///
+    /// Buses where the interrupting device does not supply its own vector should return
+    /// [`autovector(interrupt_level)`](autovector). This can also return
+    /// [`SPURIOUS_INTERRUPT_VECTOR`] if no device responded, or [`UNINITIALIZED_INTERRUPT_VECTOR`].
+    fn acknowledge_interrupt(&mut self, interrupt_level: u8) -> u8;
 
     fn halt(&self) -> bool;
 
     fn reset(&self) -> bool;
+
+    /// Called when the CPU executes a RESET instruction, which asserts the reset line for external
+    /// devices without resetting the CPU. The default implementation does nothing.
+    #[inline]
+    fn reset_external_devices(&mut self) {}
+}
+
+/// Bus wrapper that records every memory access to a [`MemoryAccessLog`], for use by debugger
+/// watchpoints.
+pub struct LoggingBus<'a, B> {
+    bus: &'a mut B,
+    log: &'a mut MemoryAccessLog,
+}
+
+impl<'a, B: BusInterface> LoggingBus<'a, B> {
+    pub fn new(bus: &'a mut B, log: &'a mut MemoryAccessLog) -> Self {
+        Self { bus, log }
+    }
+
+    fn record(&mut self, address: u32, bytes: &[u8], kind: MemoryAccessKind) {
+        for (i, &byte) in (0..).zip(bytes) {
+            self.log.record(address.wrapping_add(i) & B::ADDRESS_MASK, byte, kind);
+        }
+    }
+}
+
+impl<B: BusInterface> BusInterface for LoggingBus<'_, B> {
+    const ADDRESS_MASK: u32 = B::ADDRESS_MASK;
+
+    fn read_byte(&mut self, address: u32) -> u8 {
+        let value = self.bus.read_byte(address);
+        self.record(address, &[value], MemoryAccessKind::Read);
+        value
+    }
+
+    fn read_word(&mut self, address: u32) -> u16 {
+        let value = self.bus.read_word(address);
+        self.record(address, &value.to_be_bytes(), MemoryAccessKind::Read);
+        value
+    }
+
+    fn write_byte(&mut self, address: u32, value: u8) {
+        self.bus.write_byte(address, value);
+        self.record(address, &[value], MemoryAccessKind::Write);
+    }
+
+    fn write_word(&mut self, address: u32, value: u16) {
+        self.bus.write_word(address, value);
+        self.record(address, &value.to_be_bytes(), MemoryAccessKind::Write);
+    }
+
+    fn read_long_word(&mut self, address: u32) -> u32 {
+        let value = self.bus.read_long_word(address);
+        self.record(address, &value.to_be_bytes(), MemoryAccessKind::Read);
+        value
+    }
+
+    fn write_long_word(&mut self, address: u32, value: u32) {
+        self.bus.write_long_word(address, value);
+        self.record(address, &value.to_be_bytes(), MemoryAccessKind::Write);
+    }
+
+    fn interrupt_level(&self) -> u8 {
+        self.bus.interrupt_level()
+    }
+
+    fn acknowledge_interrupt(&mut self, interrupt_level: u8) -> u8 {
+        self.bus.acknowledge_interrupt(interrupt_level)
+    }
+
+    fn halt(&self) -> bool {
+        self.bus.halt()
+    }
+
+    fn reset(&self) -> bool {
+        self.bus.reset()
+    }
+
+    fn reset_external_devices(&mut self) {
+        self.bus.reset_external_devices();
+    }
 }
@@ -1,6 +1,15 @@
+use jgenesis_common::debug::{MemoryAccessKind, MemoryAccessLog};
+
 pub trait BusInterface {
     fn read(&mut self, address: u16) -> u8;
 
+    /// Read an opcode or instruction operand. Buses can override this to distinguish instruction
+    /// fetches from data reads, e.g. for code/data logging.
+    #[inline]
+    fn read_code(&mut self, address: u16) -> u8 {
+        self.read(address)
+    }
+
     fn write(&mut self, address: u16, value: u8);
 
     fn nmi(&self) -> bool;
@@ -8,4 +17,60 @@ pub trait BusInterface {
     fn acknowledge_nmi(&mut self);
 
     fn irq(&self) -> bool;
+
+    /// State of the RDY line. While RDY is low, the CPU halts on its next read cycle and stays
+    /// halted until RDY goes high again, but write cycles complete as normal. External DMA can pull
+    /// RDY low to steal bus cycles from the CPU; see [`Mos6502::rdy_halt_address`](crate::Mos6502::rdy_halt_address).
+    #[inline]
+    fn rdy(&self) -> bool {
+        true
+    }
+}
+
+/// Bus wrapper that records every memory access to a [`MemoryAccessLog`], for use by debugger
+/// watchpoints.
+pub struct LoggingBus<'a, B> {
+    bus: &'a mut B,
+    log: &'a mut MemoryAccessLog,
+}
+
+impl<'a, B: BusInterface> LoggingBus<'a, B> {
+    pub fn new(bus: &'a mut B, log: &'a mut MemoryAccessLog) -> Self {
+        Self { bus, log }
+    }
+}
+
+impl<B: BusInterface> BusInterface for LoggingBus<'_, B> {
+    fn read(&mut self, address: u16) -> u8 {
+        let value = self.bus.read(address);
+        self.log.record(address.into(), value, MemoryAccessKind::Read);
+        value
+    }
+
+    fn read_code(&mut self, address: u16) -> u8 {
+        let value = self.bus.read_code(address);
+        self.log.record(address.into(), value, MemoryAccessKind::Read);
+        value
+    }
+
+    fn write(&mut self, address: u16, value: u8) {
+        self.bus.write(address, value);
+        self.log.record(address.into(), value, MemoryAccessKind::Write);
+    }
+
+    fn nmi(&self) -> bool {
+        self.bus.nmi()
+    }
+
+    fn acknowledge_nmi(&mut self) {
+        self.bus.acknowledge_nmi();
+    }
+
+    fn irq(&self) -> bool {
+        self.bus.irq()
+    }
+
+    fn rdy(&self) -> bool {
+        self.bus.rdy()
+    }
 }
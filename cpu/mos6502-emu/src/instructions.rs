@@ -3,7 +3,9 @@ use bincode::{Decode, Encode};
 use jgenesis_common::num::{GetBit, SignBit};
 
 use crate::bus::BusInterface;
-use crate::{CpuRegisters, Mos6502, StatusFlags, StatusReadContext, IRQ_VECTOR, NMI_VECTOR};
+use crate::{
+    CpuRegisters, Mos6502, StatusFlags, StatusReadContext, Variant, IRQ_VECTOR, NMI_VECTOR,
+};
 
 #[derive(Debug, Clone, Encode, Decode)]
 pub struct InstructionState {
@@ -18,6 +20,7 @@ pub struct InstructionState {
     pub interrupt_vector: u16,
     pub pending_interrupt: bool,
     pub instruction_complete: bool,
+    pub decimal_adjust_cycle: bool,
 }
 
 impl Default for InstructionState {
@@ -34,12 +37,13 @@ impl Default for InstructionState {
             interrupt_vector: 0,
             pending_interrupt: false,
             instruction_complete: true,
+            decimal_adjust_cycle: false,
         }
     }
 }
 
 #[inline]
-fn poll_interrupt_lines<B: BusInterface>(cpu: &mut Mos6502, bus: &mut B) {
+pub fn poll_interrupt_lines<B: BusInterface>(cpu: &mut Mos6502, bus: &mut B) {
     cpu.state.pending_interrupt |=
         bus.nmi() || (!cpu.registers.status.interrupt_disable && bus.irq());
 }
@@ -52,7 +56,7 @@ fn final_cycle<B: BusInterface>(cpu: &mut Mos6502, bus: &mut B) {
 
 #[inline]
 fn fetch_operand<B: BusInterface>(cpu: &mut Mos6502, bus: &mut B) -> u8 {
-    let operand = bus.read(cpu.registers.pc);
+    let operand = bus.read_code(cpu.registers.pc);
     cpu.registers.pc = cpu.registers.pc.wrapping_add(1);
     operand
 }
@@ -250,6 +254,35 @@ macro_rules! impl_read_indirect_y {
     }
 }
 
+macro_rules! impl_read_zero_page_indirect {
+    ($cpu:expr, $bus:expr, |$operand:ident, $registers_param:ident| $body:block) => {
+        match $cpu.state.cycle {
+            0 => {
+                $cpu.state.operand_first_byte = fetch_operand($cpu, $bus);
+            }
+            1 => {
+                $cpu.state.target_first_byte = $bus.read($cpu.state.operand_first_byte.into());
+            }
+            2 => {
+                let address = $cpu.state.operand_first_byte.wrapping_add(1);
+                $cpu.state.target_second_byte = $bus.read(address.into());
+            }
+            3 => {
+                final_cycle($cpu, $bus);
+
+                let address = u16::from_le_bytes([
+                    $cpu.state.target_first_byte,
+                    $cpu.state.target_second_byte,
+                ]);
+                let $operand = $bus.read(address);
+                let $registers_param = &mut $cpu.registers;
+                $body
+            }
+            _ => invalid_cycle!($cpu),
+        }
+    };
+}
+
 macro_rules! impl_read_instruction {
     (immediate, $($rest:tt)*) => {
         impl_read_immediate!($($rest)*)
@@ -278,6 +311,9 @@ macro_rules! impl_read_instruction {
     (indirect_y, $($rest:tt)*) => {
         impl_read_indirect_y!($($rest)*)
     };
+    (zero_page_indirect, $($rest:tt)*) => {
+        impl_read_zero_page_indirect!($($rest)*)
+    };
 }
 
 macro_rules! impl_read_fn {
@@ -443,6 +479,33 @@ macro_rules! impl_store_indirect_y {
     };
 }
 
+macro_rules! impl_store_zero_page_indirect {
+    ($cpu:expr, $bus:expr, $register:expr) => {
+        match $cpu.state.cycle {
+            0 => {
+                $cpu.state.operand_first_byte = fetch_operand($cpu, $bus);
+            }
+            1 => {
+                $cpu.state.target_first_byte = $bus.read($cpu.state.operand_first_byte.into());
+            }
+            2 => {
+                let address = $cpu.state.operand_first_byte.wrapping_add(1);
+                $cpu.state.target_second_byte = $bus.read(address.into());
+            }
+            3 => {
+                final_cycle($cpu, $bus);
+
+                let address = u16::from_le_bytes([
+                    $cpu.state.target_first_byte,
+                    $cpu.state.target_second_byte,
+                ]);
+                $bus.write(address, $register);
+            }
+            _ => invalid_cycle!($cpu),
+        }
+    };
+}
+
 macro_rules! impl_store {
     (zero_page, $($rest:tt)*) => {
         impl_store_zero_page!($($rest)*)
@@ -468,15 +531,23 @@ macro_rules! impl_store {
     (indirect_y, $($rest:tt)*) => {
         impl_store_indirect_y!($($rest)*)
     };
+    (zero_page_indirect, $($rest:tt)*) => {
+        impl_store_zero_page_indirect!($($rest)*)
+    };
 }
 
-// STA, STX, STY, unofficial SAX
+// STA, STX, STY, STZ (65C02), unofficial SAX
 macro_rules! impl_store_fn {
     ($name:ident, ax, $addressing_mode:tt) => {
         fn $name<B: BusInterface>(cpu: &mut Mos6502, bus: &mut B) {
             impl_store!($addressing_mode, cpu, bus, cpu.registers.accumulator & cpu.registers.x);
         }
     };
+    ($name:ident, zero, $addressing_mode:tt) => {
+        fn $name<B: BusInterface>(cpu: &mut Mos6502, bus: &mut B) {
+            impl_store!($addressing_mode, cpu, bus, 0);
+        }
+    };
     ($name:ident, $register:ident, $addressing_mode:tt) => {
         fn $name<B: BusInterface>(cpu: &mut Mos6502, bus: &mut B) {
             impl_store!($addressing_mode, cpu, bus, cpu.registers.$register);
@@ -491,6 +562,7 @@ impl_store_fn!(sta_absolute_x, accumulator, absolute_x);
 impl_store_fn!(sta_absolute_y, accumulator, absolute_y);
 impl_store_fn!(sta_indirect_x, accumulator, indirect_x);
 impl_store_fn!(sta_indirect_y, accumulator, indirect_y);
+impl_store_fn!(sta_zero_page_indirect, accumulator, zero_page_indirect);
 
 impl_store_fn!(stx_zero_page, x, zero_page);
 impl_store_fn!(stx_zero_page_y, x, zero_page_y);
@@ -500,6 +572,11 @@ impl_store_fn!(sty_zero_page, y, zero_page);
 impl_store_fn!(sty_zero_page_x, y, zero_page_x);
 impl_store_fn!(sty_absolute, y, absolute);
 
+impl_store_fn!(stz_zero_page, zero, zero_page);
+impl_store_fn!(stz_zero_page_x, zero, zero_page_x);
+impl_store_fn!(stz_absolute, zero, absolute);
+impl_store_fn!(stz_absolute_x, zero, absolute_x);
+
 impl_store_fn!(sax_zero_page, ax, zero_page);
 impl_store_fn!(sax_zero_page_y, ax, zero_page_y);
 impl_store_fn!(sax_absolute, ax, absolute);
@@ -837,6 +914,7 @@ impl_load!(lda_absolute_x, accumulator, absolute_x);
 impl_load!(lda_absolute_y, accumulator, absolute_y);
 impl_load!(lda_indirect_x, accumulator, indirect_x);
 impl_load!(lda_indirect_y, accumulator, indirect_y);
+impl_load!(lda_zero_page_indirect, accumulator, zero_page_indirect);
 
 impl_load!(ldx_immediate, x, immediate);
 impl_load!(ldx_zero_page, x, zero_page);
@@ -869,7 +947,7 @@ fn add(accumulator: u8, value: u8, flags: &mut StatusFlags) -> u8 {
     result
 }
 
-fn add_bcd(accumulator: u8, value: u8, flags: &mut StatusFlags) -> u8 {
+fn add_bcd(accumulator: u8, value: u8, cmos: bool, flags: &mut StatusFlags) -> u8 {
     // Formulas from http://www.6502.org/tutorials/decimal_mode.html#A which correctly handle
     // invalid values and undocumented behaviors
 
@@ -896,6 +974,11 @@ fn add_bcd(accumulator: u8, value: u8, flags: &mut StatusFlags) -> u8 {
     flags.negative = s.bit(7);
     flags.overflow = overflow;
 
+    if cmos {
+        // The 65C02 sets N and Z based on the decimal result
+        flags.set_negative(result.bit(7)).set_zero(result == 0);
+    }
+
     result
 }
 
@@ -904,7 +987,12 @@ macro_rules! impl_add_with_carry {
     ($name:ident, $addressing_mode:tt) => {
         impl_read_fn!($name, $addressing_mode, |operand, registers| {
             registers.accumulator = if registers.in_decimal_mode() {
-                add_bcd(registers.accumulator, operand, &mut registers.status)
+                add_bcd(
+                    registers.accumulator,
+                    operand,
+                    registers.variant.is_cmos(),
+                    &mut registers.status,
+                )
             } else {
                 add(registers.accumulator, operand, &mut registers.status)
             };
@@ -920,6 +1008,7 @@ impl_add_with_carry!(adc_absolute_x, absolute_x);
 impl_add_with_carry!(adc_absolute_y, absolute_y);
 impl_add_with_carry!(adc_indirect_x, indirect_x);
 impl_add_with_carry!(adc_indirect_y, indirect_y);
+impl_add_with_carry!(adc_zero_page_indirect, zero_page_indirect);
 
 fn and(accumulator: u8, value: u8, flags: &mut StatusFlags) -> u8 {
     let result = accumulator & value;
@@ -944,6 +1033,7 @@ impl_and!(and_absolute_x, absolute_x);
 impl_and!(and_absolute_y, absolute_y);
 impl_and!(and_indirect_x, indirect_x);
 impl_and!(and_indirect_y, indirect_y);
+impl_and!(and_zero_page_indirect, zero_page_indirect);
 
 fn bit_test(accumulator: u8, value: u8, flags: &mut StatusFlags) {
     flags.set_negative(value.bit(7)).set_overflow(value.bit(6)).set_zero(accumulator & value == 0);
@@ -959,7 +1049,14 @@ macro_rules! impl_bit_test {
 }
 
 impl_bit_test!(bit_zero_page, zero_page);
+impl_bit_test!(bit_zero_page_x, zero_page_x);
 impl_bit_test!(bit_absolute, absolute);
+impl_bit_test!(bit_absolute_x, absolute_x);
+
+// BIT #imm (65C02); unlike other BIT addressing modes, only sets the Z flag
+impl_read_fn!(bit_immediate, immediate, |operand, registers| {
+    registers.status.zero = registers.accumulator & operand == 0;
+});
 
 fn compare(register: u8, value: u8, flags: &mut StatusFlags) {
     flags
@@ -985,6 +1082,7 @@ impl_compare!(cmp_absolute_x, accumulator, absolute_x);
 impl_compare!(cmp_absolute_y, accumulator, absolute_y);
 impl_compare!(cmp_indirect_x, accumulator, indirect_x);
 impl_compare!(cmp_indirect_y, accumulator, indirect_y);
+impl_compare!(cmp_zero_page_indirect, accumulator, zero_page_indirect);
 
 impl_compare!(cpx_immediate, x, immediate);
 impl_compare!(cpx_zero_page, x, zero_page);
@@ -1017,6 +1115,7 @@ impl_xor!(eor_absolute_x, absolute_x);
 impl_xor!(eor_absolute_y, absolute_y);
 impl_xor!(eor_indirect_x, indirect_x);
 impl_xor!(eor_indirect_y, indirect_y);
+impl_xor!(eor_zero_page_indirect, zero_page_indirect);
 
 fn or(accumulator: u8, value: u8, flags: &mut StatusFlags) -> u8 {
     let result = accumulator | value;
@@ -1041,6 +1140,7 @@ impl_or!(ora_absolute_x, absolute_x);
 impl_or!(ora_absolute_y, absolute_y);
 impl_or!(ora_indirect_x, indirect_x);
 impl_or!(ora_indirect_y, indirect_y);
+impl_or!(ora_zero_page_indirect, zero_page_indirect);
 
 fn subtract(accumulator: u8, value: u8, flags: &mut StatusFlags) -> u8 {
     // Carry flag is inverted in subtraction
@@ -1062,12 +1162,16 @@ fn subtract(accumulator: u8, value: u8, flags: &mut StatusFlags) -> u8 {
     result
 }
 
-fn subtract_bcd(accumulator: u8, value: u8, flags: &mut StatusFlags) -> u8 {
+fn subtract_bcd(accumulator: u8, value: u8, cmos: bool, flags: &mut StatusFlags) -> u8 {
     // Formulas from http://www.6502.org/tutorials/decimal_mode.html#A which correctly handle
     // invalid values and undocumented behaviors
 
     let existing_borrow: u8 = (!flags.carry).into();
 
+    if cmos {
+        return subtract_bcd_cmos(accumulator, value, existing_borrow, flags);
+    }
+
     let mut al = u16::from(accumulator & 0x0F)
         .wrapping_sub(u16::from(value & 0x0F))
         .wrapping_sub(u16::from(existing_borrow));
@@ -1099,12 +1203,43 @@ fn subtract_bcd(accumulator: u8, value: u8, flags: &mut StatusFlags) -> u8 {
     result
 }
 
+fn subtract_bcd_cmos(
+    accumulator: u8,
+    value: u8,
+    existing_borrow: u8,
+    flags: &mut StatusFlags,
+) -> u8 {
+    // The 65C02 adjusts the binary difference instead of subtracting digit by digit, which gives
+    // different results for invalid BCD values
+    let al = i16::from(accumulator & 0x0F) - i16::from(value & 0x0F) - i16::from(existing_borrow);
+    let mut a = i16::from(accumulator) - i16::from(value) - i16::from(existing_borrow);
+    if a < 0 {
+        a -= 0x60;
+    }
+    if al < 0 {
+        a -= 0x06;
+    }
+
+    let result = a as u8;
+
+    // C and V are set based on binary arithmetic, while N and Z are set based on the decimal result
+    subtract(accumulator, value, flags);
+    flags.set_negative(result.bit(7)).set_zero(result == 0);
+
+    result
+}
+
 // SBC
 macro_rules! impl_subtract_with_carry {
     ($name:ident, $addressing_mode:tt) => {
         impl_read_fn!($name, $addressing_mode, |operand, registers| {
             registers.accumulator = if registers.in_decimal_mode() {
-                subtract_bcd(registers.accumulator, operand, &mut registers.status)
+                subtract_bcd(
+                    registers.accumulator,
+                    operand,
+                    registers.variant.is_cmos(),
+                    &mut registers.status,
+                )
             } else {
                 subtract(registers.accumulator, operand, &mut registers.status)
             };
@@ -1120,6 +1255,7 @@ impl_subtract_with_carry!(sbc_absolute_x, absolute_x);
 impl_subtract_with_carry!(sbc_absolute_y, absolute_y);
 impl_subtract_with_carry!(sbc_indirect_x, indirect_x);
 impl_subtract_with_carry!(sbc_indirect_y, indirect_y);
+impl_subtract_with_carry!(sbc_zero_page_indirect, zero_page_indirect);
 
 fn shift_left(value: u8, flags: &mut StatusFlags) -> u8 {
     let shifted = value << 1;
@@ -1157,6 +1293,7 @@ macro_rules! impl_decrement {
     };
 }
 
+impl_decrement!(dec_accumulator, accumulator);
 impl_decrement!(dec_zero_page, zero_page);
 impl_decrement!(dec_zero_page_x, zero_page_x);
 impl_decrement!(dec_absolute, absolute);
@@ -1177,6 +1314,7 @@ macro_rules! impl_increment {
     };
 }
 
+impl_increment!(inc_accumulator, accumulator);
 impl_increment!(inc_zero_page, zero_page);
 impl_increment!(inc_zero_page_x, zero_page_x);
 impl_increment!(inc_absolute, absolute);
@@ -1245,6 +1383,59 @@ impl_rotate_right!(ror_zero_page_x, zero_page_x);
 impl_rotate_right!(ror_absolute, absolute);
 impl_rotate_right!(ror_absolute_x, absolute_x);
 
+fn test_and_set_bits(accumulator: u8, value: u8, flags: &mut StatusFlags) -> u8 {
+    flags.zero = accumulator & value == 0;
+    value | accumulator
+}
+
+fn test_and_reset_bits(accumulator: u8, value: u8, flags: &mut StatusFlags) -> u8 {
+    flags.zero = accumulator & value == 0;
+    value & !accumulator
+}
+
+// TSB, TRB (65C02)
+macro_rules! impl_test_and_modify_bits {
+    ($name:ident, $f:ident, $addressing_mode:tt) => {
+        impl_modify_fn!($name, $addressing_mode, |operand, registers| {
+            $f(registers.accumulator, operand, &mut registers.status)
+        });
+    };
+}
+
+impl_test_and_modify_bits!(tsb_zero_page, test_and_set_bits, zero_page);
+impl_test_and_modify_bits!(tsb_absolute, test_and_set_bits, absolute);
+impl_test_and_modify_bits!(trb_zero_page, test_and_reset_bits, zero_page);
+impl_test_and_modify_bits!(trb_absolute, test_and_reset_bits, absolute);
+
+// RMB0-7, SMB0-7 (Rockwell and WDC 65C02); the bit number is in bits 4-6 of the opcode, and bit 7
+// of the opcode is set for SMB
+fn rmb_smb<B: BusInterface>(cpu: &mut Mos6502, bus: &mut B) {
+    match cpu.state.cycle {
+        0 => {
+            cpu.state.operand_first_byte = fetch_operand(cpu, bus);
+        }
+        1 => {
+            cpu.state.target_first_byte = bus.read(cpu.state.operand_first_byte.into());
+        }
+        2 => {
+            // The 65C02 performs a spurious read instead of a spurious write
+            bus.read(cpu.state.operand_first_byte.into());
+        }
+        3 => {
+            final_cycle(cpu, bus);
+
+            let bit = (cpu.state.opcode >> 4) & 0x07;
+            let value = if cpu.state.opcode.bit(7) {
+                cpu.state.target_first_byte | (1 << bit)
+            } else {
+                cpu.state.target_first_byte & !(1 << bit)
+            };
+            bus.write(cpu.state.operand_first_byte.into(), value);
+        }
+        _ => invalid_cycle!(cpu),
+    }
+}
+
 // SLO (unofficial; combination of ASL and ORA)
 macro_rules! impl_shift_left_or {
     ($name:ident, $addressing_mode:tt) => {
@@ -1308,7 +1499,12 @@ macro_rules! impl_rotate_right_add {
         impl_modify_fn!($name, $addressing_mode, |operand, registers| {
             let rotated = rotate_right(operand, &mut registers.status);
             registers.accumulator = if registers.in_decimal_mode() {
-                add_bcd(registers.accumulator, rotated, &mut registers.status)
+                add_bcd(
+                    registers.accumulator,
+                    rotated,
+                    registers.variant.is_cmos(),
+                    &mut registers.status,
+                )
             } else {
                 add(registers.accumulator, rotated, &mut registers.status)
             };
@@ -1350,7 +1546,12 @@ macro_rules! impl_increment_subtract {
         impl_modify_fn!($name, $addressing_mode, |operand, registers| {
             let incremented = increment(operand, &mut registers.status);
             registers.accumulator = if registers.in_decimal_mode() {
-                subtract_bcd(registers.accumulator, incremented, &mut registers.status)
+                subtract_bcd(
+                    registers.accumulator,
+                    incremented,
+                    registers.variant.is_cmos(),
+                    &mut registers.status,
+                )
             } else {
                 subtract(registers.accumulator, incremented, &mut registers.status)
             };
@@ -1440,9 +1641,15 @@ impl_register_transfer!(tya, y -> accumulator);
 // NOP
 impl_registers_only_fn!(nop, |_registers| {});
 
-// BCC, BCS, BEQ, BMI, BNE, BPL, BVC, BVS
+// BCC, BCS, BEQ, BMI, BNE, BPL, BVC, BVS, BRA (65C02)
 macro_rules! impl_branch {
+    ($name:ident, always) => {
+        impl_branch!(@impl $name, |_status| true);
+    };
     ($name:ident, $flag:ident == $flag_value:expr) => {
+        impl_branch!(@impl $name, |status| status.$flag == $flag_value);
+    };
+    (@impl $name:ident, |$status:ident| $taken:expr) => {
         fn $name<B: BusInterface>(cpu: &mut Mos6502, bus: &mut B) {
             match cpu.state.cycle {
                 0 => {
@@ -1450,7 +1657,8 @@ macro_rules! impl_branch {
 
                     cpu.state.operand_first_byte = fetch_operand(cpu, bus);
 
-                    if cpu.registers.status.$flag != $flag_value {
+                    let $status = &cpu.registers.status;
+                    if !$taken {
                         cpu.state.instruction_complete = true;
                     }
                 }
@@ -1489,6 +1697,56 @@ impl_branch!(bne, zero == false);
 impl_branch!(bpl, negative == false);
 impl_branch!(bvc, overflow == false);
 impl_branch!(bvs, overflow == true);
+impl_branch!(bra, always);
+
+// BBR0-7, BBS0-7 (Rockwell and WDC 65C02); the bit number is in bits 4-6 of the opcode, and bit 7
+// of the opcode is set for BBS
+fn bbr_bbs<B: BusInterface>(cpu: &mut Mos6502, bus: &mut B) {
+    match cpu.state.cycle {
+        0 => {
+            cpu.state.operand_first_byte = fetch_operand(cpu, bus);
+        }
+        1 => {
+            cpu.state.target_first_byte = bus.read(cpu.state.operand_first_byte.into());
+        }
+        2 => {
+            // Spurious zero page read
+            bus.read(cpu.state.operand_first_byte.into());
+        }
+        3 => {
+            poll_interrupt_lines(cpu, bus);
+
+            cpu.state.operand_second_byte = fetch_operand(cpu, bus);
+
+            let bit = (cpu.state.opcode >> 4) & 0x07;
+            if cpu.state.target_first_byte.bit(bit) != cpu.state.opcode.bit(7) {
+                cpu.state.instruction_complete = true;
+            }
+        }
+        4 => {
+            bus.read(cpu.registers.pc);
+
+            let offset = cpu.state.operand_second_byte as i8;
+            let pc = cpu.registers.pc.wrapping_add_signed(offset.into());
+
+            if cpu.registers.pc & 0xFF00 == pc & 0xFF00 {
+                cpu.registers.pc = pc;
+                cpu.state.instruction_complete = true;
+            }
+        }
+        5 => {
+            final_cycle(cpu, bus);
+
+            let offset = cpu.state.operand_second_byte as i8;
+            let pc = cpu.registers.pc.wrapping_add_signed(offset.into());
+
+            bus.read((cpu.registers.pc & 0xFF00) | (pc & 0x00FF));
+
+            cpu.registers.pc = pc;
+        }
+        _ => invalid_cycle!(cpu),
+    }
+}
 
 // JMP
 fn jmp_absolute<B: BusInterface>(cpu: &mut Mos6502, bus: &mut B) {
@@ -1499,7 +1757,7 @@ fn jmp_absolute<B: BusInterface>(cpu: &mut Mos6502, bus: &mut B) {
         1 => {
             final_cycle(cpu, bus);
 
-            let address_msb = bus.read(cpu.registers.pc);
+            let address_msb = bus.read_code(cpu.registers.pc);
             cpu.registers.pc = u16::from_le_bytes([cpu.state.operand_first_byte, address_msb]);
         }
         _ => invalid_cycle!(cpu),
@@ -1533,16 +1791,81 @@ fn jmp_indirect<B: BusInterface>(cpu: &mut Mos6502, bus: &mut B) {
     }
 }
 
+// JMP (65C02); fixes the NMOS bug where the pointer's high byte is read from the start of the same
+// page if the pointer is at the end of a page, at the cost of an extra cycle
+fn jmp_indirect_cmos<B: BusInterface>(cpu: &mut Mos6502, bus: &mut B) {
+    match cpu.state.cycle {
+        0 => {
+            cpu.state.operand_first_byte = fetch_operand(cpu, bus);
+        }
+        1 => {
+            cpu.state.operand_second_byte = fetch_operand(cpu, bus);
+        }
+        2 => {
+            // Spurious operand read
+            bus.read(cpu.registers.pc.wrapping_sub(1));
+        }
+        3 => {
+            let address =
+                u16::from_le_bytes([cpu.state.operand_first_byte, cpu.state.operand_second_byte]);
+            cpu.state.target_first_byte = bus.read(address);
+        }
+        4 => {
+            final_cycle(cpu, bus);
+
+            let address =
+                u16::from_le_bytes([cpu.state.operand_first_byte, cpu.state.operand_second_byte]);
+            let pc_msb = bus.read(address.wrapping_add(1));
+
+            cpu.registers.pc = u16::from_le_bytes([cpu.state.target_first_byte, pc_msb]);
+        }
+        _ => invalid_cycle!(cpu),
+    }
+}
+
+// JMP (65C02)
+fn jmp_absolute_x_indirect<B: BusInterface>(cpu: &mut Mos6502, bus: &mut B) {
+    match cpu.state.cycle {
+        0 => {
+            cpu.state.operand_first_byte = fetch_operand(cpu, bus);
+        }
+        1 => {
+            cpu.state.operand_second_byte = fetch_operand(cpu, bus);
+        }
+        2 => {
+            // Spurious operand read
+            bus.read(cpu.registers.pc.wrapping_sub(1));
+        }
+        3 => {
+            let address =
+                u16::from_le_bytes([cpu.state.operand_first_byte, cpu.state.operand_second_byte])
+                    .wrapping_add(cpu.registers.x.into());
+            cpu.state.target_first_byte = bus.read(address);
+        }
+        4 => {
+            final_cycle(cpu, bus);
+
+            let address =
+                u16::from_le_bytes([cpu.state.operand_first_byte, cpu.state.operand_second_byte])
+                    .wrapping_add(cpu.registers.x.into());
+            let pc_msb = bus.read(address.wrapping_add(1));
+
+            cpu.registers.pc = u16::from_le_bytes([cpu.state.target_first_byte, pc_msb]);
+        }
+        _ => invalid_cycle!(cpu),
+    }
+}
+
 macro_rules! read_register_for_push {
-    (accumulator, $registers:expr) => {
-        $registers.accumulator
-    };
     (p, $registers:expr) => {
         $registers.status.to_byte(StatusReadContext::PushStack)
     };
+    ($register:ident, $registers:expr) => {
+        $registers.$register
+    };
 }
 
-// PHA, PHP
+// PHA, PHP, PHX (65C02), PHY (65C02)
 macro_rules! impl_push_stack {
     ($name:ident, $register:tt) => {
         fn $name<B: BusInterface>(cpu: &mut Mos6502, bus: &mut B) {
@@ -1566,19 +1889,21 @@ macro_rules! impl_push_stack {
 
 impl_push_stack!(pha, accumulator);
 impl_push_stack!(php, p);
+impl_push_stack!(phx, x);
+impl_push_stack!(phy, y);
 
 macro_rules! write_register_for_pull {
-    (accumulator, $registers:expr, $value:expr) => {{
-        let value = $value;
-        $registers.accumulator = value;
-        $registers.status.set_negative(value.bit(7)).set_zero(value == 0);
-    }};
     (p, $registers:expr, $value:expr) => {
         $registers.status = StatusFlags::from_byte($value);
     };
+    ($register:ident, $registers:expr, $value:expr) => {{
+        let value = $value;
+        $registers.$register = value;
+        $registers.status.set_negative(value.bit(7)).set_zero(value == 0);
+    }};
 }
 
-// PLA, PLP
+// PLA, PLP, PLX (65C02), PLY (65C02)
 macro_rules! impl_pull_stack {
     ($name:ident, $register:tt) => {
         fn $name<B: BusInterface>(cpu: &mut Mos6502, bus: &mut B) {
@@ -1604,6 +1929,8 @@ macro_rules! impl_pull_stack {
 
 impl_pull_stack!(pla, accumulator);
 impl_pull_stack!(plp, p);
+impl_pull_stack!(plx, x);
+impl_pull_stack!(ply, y);
 
 #[inline]
 fn push_pc_msb<B: BusInterface>(cpu: &mut Mos6502, bus: &mut B) {
@@ -1651,7 +1978,7 @@ fn jsr<B: BusInterface>(cpu: &mut Mos6502, bus: &mut B) {
         4 => {
             final_cycle(cpu, bus);
 
-            let address_msb = bus.read(cpu.registers.pc);
+            let address_msb = bus.read_code(cpu.registers.pc);
             cpu.registers.pc = u16::from_le_bytes([cpu.state.operand_first_byte, address_msb]);
         }
         _ => invalid_cycle!(cpu),
@@ -1735,6 +2062,11 @@ fn interrupt_push_status<B: BusInterface>(
 fn interrupt_pull_pc_lsb<B: BusInterface>(cpu: &mut Mos6502, bus: &mut B) {
     cpu.registers.pc = bus.read(cpu.state.interrupt_vector).into();
     cpu.registers.status.interrupt_disable = true;
+
+    // The 65C02 also clears the decimal flag when handling any interrupt, including BRK
+    if cpu.registers.variant.is_cmos() {
+        cpu.registers.status.decimal = false;
+    }
 }
 
 #[inline]
@@ -1799,6 +2131,29 @@ fn interrupt_service_routine<B: BusInterface>(cpu: &mut Mos6502, bus: &mut B) {
     }
 }
 
+// WAI (WDC 65C02)
+fn wai<B: BusInterface>(cpu: &mut Mos6502, bus: &mut B) {
+    match cpu.state.cycle {
+        0 => {
+            // Spurious operand read
+            bus.read(cpu.registers.pc);
+        }
+        1 => {
+            final_cycle(cpu, bus);
+
+            bus.read(cpu.registers.pc);
+            cpu.waiting = true;
+        }
+        _ => invalid_cycle!(cpu),
+    }
+}
+
+// STP (WDC 65C02); halts the CPU until a reset
+fn stp<B: BusInterface>(cpu: &mut Mos6502, bus: &mut B) {
+    bus.read(cpu.registers.pc);
+    cpu.frozen = true;
+}
+
 fn execute_unofficial_store<B: BusInterface>(
     cpu: &mut Mos6502,
     bus: &mut B,
@@ -2016,6 +2371,60 @@ impl_multi_byte_noop!(nop_zero_page_x, zero_page_x);
 impl_multi_byte_noop!(nop_absolute, absolute);
 impl_multi_byte_noop!(nop_absolute_x, absolute_x);
 
+// 65C02 NOP $5C, which reads its operands and then takes 5 more cycles
+fn nop_5c<B: BusInterface>(cpu: &mut Mos6502, bus: &mut B) {
+    match cpu.state.cycle {
+        0 => {
+            cpu.state.operand_first_byte = fetch_operand(cpu, bus);
+        }
+        1 => {
+            cpu.state.operand_second_byte = fetch_operand(cpu, bus);
+        }
+        2..=5 => {
+            bus.read(u16::from_le_bytes([cpu.state.operand_first_byte, 0xFF]));
+        }
+        6 => {
+            final_cycle(cpu, bus);
+
+            bus.read(u16::from_le_bytes([cpu.state.operand_first_byte, 0xFF]));
+        }
+        _ => invalid_cycle!(cpu),
+    }
+}
+
+/// Returns whether the given opcode is a NOP that completes in the same cycle as the opcode fetch.
+/// On the 65C02, this is every opcode in columns $x3 and $xB except for WAI and STP.
+pub fn is_single_cycle_nop(variant: Variant, opcode: u8) -> bool {
+    match variant {
+        Variant::Nmos6502 | Variant::Ricoh2A03 => false,
+        Variant::Wdc65C02 => opcode & 0x07 == 0x03 && opcode != 0xCB && opcode != 0xDB,
+    }
+}
+
+// ADC and SBC opcodes that take an extra cycle in decimal mode on the 65C02
+fn is_cmos_decimal_opcode(opcode: u8) -> bool {
+    matches!(
+        opcode,
+        0x61 | 0x65
+            | 0x69
+            | 0x6D
+            | 0x71
+            | 0x72
+            | 0x75
+            | 0x79
+            | 0x7D
+            | 0xE1
+            | 0xE5
+            | 0xE9
+            | 0xED
+            | 0xF1
+            | 0xF2
+            | 0xF5
+            | 0xF9
+            | 0xFD
+    )
+}
+
 pub fn execute_cycle<B: BusInterface>(cpu: &mut Mos6502, bus: &mut B) {
     if cpu.state.executing_interrupt {
         interrupt_service_routine(cpu, bus);
@@ -2023,6 +2432,83 @@ pub fn execute_cycle<B: BusInterface>(cpu: &mut Mos6502, bus: &mut B) {
         return;
     }
 
+    if cpu.state.decimal_adjust_cycle {
+        // Extra cycle for 65C02 ADC/SBC in decimal mode
+        final_cycle(cpu, bus);
+        cpu.state.decimal_adjust_cycle = false;
+        bus.read(cpu.registers.pc);
+        return;
+    }
+
+    if cpu.registers.variant.is_cmos() {
+        execute_cmos_opcode(cpu, bus);
+
+        if cpu.state.instruction_complete
+            && cpu.registers.status.decimal
+            && is_cmos_decimal_opcode(cpu.state.opcode)
+        {
+            cpu.state.instruction_complete = false;
+            cpu.state.decimal_adjust_cycle = true;
+        }
+    } else {
+        execute_nmos_opcode(cpu, bus);
+    }
+
+    cpu.state.cycle += 1;
+}
+
+// Opcodes that differ between the NMOS 6502 and the 65C02; all others fall through to the NMOS
+// implementation
+fn execute_cmos_opcode<B: BusInterface>(cpu: &mut Mos6502, bus: &mut B) {
+    match cpu.state.opcode {
+        0x02 | 0x22 | 0x42 | 0x62 | 0x82 | 0xC2 | 0xE2 => nop_immediate(cpu, bus),
+        0x04 => tsb_zero_page(cpu, bus),
+        0x07 | 0x17 | 0x27 | 0x37 | 0x47 | 0x57 | 0x67 | 0x77 | 0x87 | 0x97 | 0xA7 | 0xB7
+        | 0xC7 | 0xD7 | 0xE7 | 0xF7 => rmb_smb(cpu, bus),
+        0x0C => tsb_absolute(cpu, bus),
+        0x0F | 0x1F | 0x2F | 0x3F | 0x4F | 0x5F | 0x6F | 0x7F | 0x8F | 0x9F | 0xAF | 0xBF
+        | 0xCF | 0xDF | 0xEF | 0xFF => bbr_bbs(cpu, bus),
+        0x12 => ora_zero_page_indirect(cpu, bus),
+        0x14 => trb_zero_page(cpu, bus),
+        0x1A => inc_accumulator(cpu, bus),
+        0x1C => trb_absolute(cpu, bus),
+        0x32 => and_zero_page_indirect(cpu, bus),
+        0x34 => bit_zero_page_x(cpu, bus),
+        0x3A => dec_accumulator(cpu, bus),
+        0x3C => bit_absolute_x(cpu, bus),
+        0x44 => nop_zero_page(cpu, bus),
+        0x52 => eor_zero_page_indirect(cpu, bus),
+        0x54 | 0xD4 | 0xF4 => nop_zero_page_x(cpu, bus),
+        0x5A => phy(cpu, bus),
+        0x5C => nop_5c(cpu, bus),
+        0x64 => stz_zero_page(cpu, bus),
+        0x6C => jmp_indirect_cmos(cpu, bus),
+        0x72 => adc_zero_page_indirect(cpu, bus),
+        0x74 => stz_zero_page_x(cpu, bus),
+        0x7A => ply(cpu, bus),
+        0x7C => jmp_absolute_x_indirect(cpu, bus),
+        0x80 => bra(cpu, bus),
+        0x89 => bit_immediate(cpu, bus),
+        0x92 => sta_zero_page_indirect(cpu, bus),
+        0x9C => stz_absolute(cpu, bus),
+        0x9E => stz_absolute_x(cpu, bus),
+        0xB2 => lda_zero_page_indirect(cpu, bus),
+        0xCB => wai(cpu, bus),
+        0xD2 => cmp_zero_page_indirect(cpu, bus),
+        0xDA => phx(cpu, bus),
+        0xDB => stp(cpu, bus),
+        0xDC | 0xFC => nop_absolute(cpu, bus),
+        0xF2 => sbc_zero_page_indirect(cpu, bus),
+        0xFA => plx(cpu, bus),
+        opcode if opcode & 0x07 == 0x03 => {
+            // Single-cycle NOPs are handled during the opcode fetch
+            panic!("Single-cycle 65C02 NOP executed: {opcode:02X}");
+        }
+        _ => execute_nmos_opcode(cpu, bus),
+    }
+}
+
+fn execute_nmos_opcode<B: BusInterface>(cpu: &mut Mos6502, bus: &mut B) {
     match cpu.state.opcode {
         0x00 => brk(cpu, bus),
         0x01 => ora_indirect_x(cpu, bus),
@@ -2248,6 +2734,4 @@ pub fn execute_cycle<B: BusInterface>(cpu: &mut Mos6502, bus: &mut B) {
             cpu.frozen = true;
         }
     }
-
-    cpu.state.cycle += 1;
 }
@@ -6,6 +6,36 @@ use crate::instructions::InstructionState;
 use bincode::{Decode, Encode};
 use jgenesis_common::num::GetBit;
 
+/// 6502 variant to emulate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode)]
+pub enum Variant {
+    /// Stock NMOS 6502, including its unofficial opcodes. The decimal mode flag toggles BCD
+    /// arithmetic.
+    #[default]
+    Nmos6502,
+    /// Ricoh 2A03 / 2A07, the CPU cores in the NTSC and PAL NES. Identical to the NMOS 6502 except
+    /// that the decimal mode flag does nothing. The APU frame counter and DMC IRQs are not part of
+    /// the CPU core; buses should report them through [`BusInterface::irq`].
+    Ricoh2A03,
+    /// WDC 65C02, with the CMOS instruction set extensions (including the Rockwell bit instructions
+    /// and WAI/STP), valid N and Z flags in decimal mode, and all unused opcodes acting as NOPs.
+    Wdc65C02,
+}
+
+impl Variant {
+    #[inline]
+    #[must_use]
+    pub const fn decimal_mode_enabled(self) -> bool {
+        !matches!(self, Self::Ricoh2A03)
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn is_cmos(self) -> bool {
+        matches!(self, Self::Wdc65C02)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
 pub enum StatusReadContext {
     HardwareInterruptHandler,
@@ -103,11 +133,11 @@ pub struct CpuRegisters {
     pub status: StatusFlags,
     pub pc: u16,
     pub sp: u8,
-    pub enable_decimal_mode: bool,
+    pub variant: Variant,
 }
 
 impl CpuRegisters {
-    fn new(reset_vector: u16, enable_decimal_mode: bool) -> Self {
+    fn new(reset_vector: u16, variant: Variant) -> Self {
         Self {
             accumulator: 0,
             x: 0,
@@ -115,12 +145,12 @@ impl CpuRegisters {
             status: StatusFlags::new(),
             pc: reset_vector,
             sp: 0xFD,
-            enable_decimal_mode,
+            variant,
         }
     }
 
     fn in_decimal_mode(&self) -> bool {
-        self.enable_decimal_mode && self.status.decimal
+        self.variant.decimal_mode_enabled() && self.status.decimal
     }
 }
 
@@ -129,6 +159,8 @@ pub struct Mos6502 {
     registers: CpuRegisters,
     state: InstructionState,
     frozen: bool,
+    waiting: bool,
+    rdy_halt_address: Option<u16>,
 }
 
 const NMI_VECTOR: u16 = 0xFFFA;
@@ -140,25 +172,29 @@ impl Mos6502 {
     ///
     /// In the standard 6502, the decimal mode flag works as intended and toggles BCD arithmetic.
     pub fn new_standard<B: BusInterface>(bus: &mut B) -> Self {
-        Self::new(bus, true)
+        Self::new(bus, Variant::Nmos6502)
     }
 
     /// Create a new NES 6502 with the PC pointing to the RESET vector, read from $FFFC.
     ///
     /// In the NES 6502, the decimal mode flag does nothing.
     pub fn new_nes<B: BusInterface>(bus: &mut B) -> Self {
-        Self::new(bus, false)
+        Self::new(bus, Variant::Ricoh2A03)
     }
 
-    fn new<B: BusInterface>(bus: &mut B, enable_decimal_mode: bool) -> Self {
+    /// Create a new 6502 of the given variant with the PC pointing to the RESET vector, read from
+    /// $FFFC.
+    pub fn new<B: BusInterface>(bus: &mut B, variant: Variant) -> Self {
         let reset_vector_lsb = bus.read(RESET_VECTOR);
         let reset_vector_msb = bus.read(RESET_VECTOR + 1);
         let reset_vector = u16::from_le_bytes([reset_vector_lsb, reset_vector_msb]);
 
         Self {
-            registers: CpuRegisters::new(reset_vector, enable_decimal_mode),
+            registers: CpuRegisters::new(reset_vector, variant),
             state: InstructionState::default(),
             frozen: false,
+            waiting: false,
+            rdy_halt_address: None,
         }
     }
 
@@ -166,7 +202,8 @@ impl Mos6502 {
     /// * Immediately update PC to point to the RESET vector, abandoning any in-progress instruction
     /// * Subtract 3 from the stack pointer
     /// * Disable IRQs
-    /// * If the CPU was frozen by an illegal KIL opcode, unfreeze it
+    /// * If the CPU was frozen by an illegal KIL opcode or a 65C02 STP instruction, unfreeze it
+    /// * If the CPU was waiting for an interrupt after a 65C02 WAI instruction, stop waiting
     pub fn reset<B: BusInterface>(&mut self, bus: &mut B) {
         let reset_vector_lsb = bus.read(RESET_VECTOR);
         let reset_vector_msb = bus.read(RESET_VECTOR + 1);
@@ -178,19 +215,73 @@ impl Mos6502 {
         self.registers.status.interrupt_disable = true;
 
         self.frozen = false;
+        self.waiting = false;
+        self.rdy_halt_address = None;
     }
 
     /// Run the CPU for 1 cycle.
+    ///
+    /// If the bus's RDY line is low and this cycle would be a read, the CPU does nothing and does not
+    /// access the bus; [`rdy_halt_address`](Self::rdy_halt_address) will return the address that
+    /// the CPU is waiting to read.
     #[inline]
     pub fn tick<B: BusInterface>(&mut self, bus: &mut B) {
+        if !bus.rdy() {
+            self.rdy_halt_address = self.next_read_address();
+            if self.rdy_halt_address.is_some() {
+                return;
+            }
+        } else {
+            self.rdy_halt_address = None;
+        }
+
+        self.execute_cycle(bus);
+    }
+
+    // Returns the address that the next cycle will read from, or None if the next cycle is a write.
+    // This runs the cycle on a copy of the CPU so that the instruction implementations do not need
+    // to know about RDY; it's only called while RDY is low, which should be rare
+    fn next_read_address(&self) -> Option<u16> {
+        let mut probe = self.clone();
+        let mut probe_bus = ProbeBus { first_access: None };
+        probe.execute_cycle(&mut probe_bus);
+
+        match probe_bus.first_access {
+            Some(BusAccess::Read(address)) => Some(address),
+            Some(BusAccess::Write) => None,
+            // Frozen or waiting for an interrupt; the address bus holds the PC
+            None => Some(self.registers.pc),
+        }
+    }
+
+    fn execute_cycle<B: BusInterface>(&mut self, bus: &mut B) {
         if self.frozen {
-            // CPU was frozen by an illegal KIL opcode; do nothing
+            // CPU was frozen by an illegal KIL opcode or STP; do nothing
+            return;
+        }
+
+        if self.waiting {
+            // WAI resumes when an interrupt line is asserted, even if IRQs are disabled; the
+            // interrupt is only serviced if it would have been otherwise
+            let nmi = bus.nmi();
+            let irq = bus.irq();
+            if !nmi && !irq {
+                return;
+            }
+
+            self.waiting = false;
+            self.state.pending_interrupt |=
+                nmi || (!self.registers.status.interrupt_disable && irq);
             return;
         }
 
         if self.state.instruction_complete {
             // Opcode is always read, even if handling an interrupt
-            let opcode = bus.read(self.registers.pc);
+            let opcode = if self.state.pending_interrupt {
+                bus.read(self.registers.pc)
+            } else {
+                bus.read_code(self.registers.pc)
+            };
 
             if self.state.pending_interrupt {
                 self.state.pending_interrupt = false;
@@ -198,6 +289,11 @@ impl Mos6502 {
             } else {
                 self.registers.pc = self.registers.pc.wrapping_add(1);
                 self.state.opcode = opcode;
+
+                if instructions::is_single_cycle_nop(self.registers.variant, opcode) {
+                    instructions::poll_interrupt_lines(self, bus);
+                    return;
+                }
             }
 
             self.state.instruction_complete = false;
@@ -219,6 +315,12 @@ impl Mos6502 {
         !self.state.instruction_complete
     }
 
+    #[inline]
+    #[must_use]
+    pub fn variant(&self) -> Variant {
+        self.registers.variant
+    }
+
     #[must_use]
     pub fn registers(&self) -> &CpuRegisters {
         &self.registers
@@ -228,10 +330,190 @@ impl Mos6502 {
         self.registers = registers;
     }
 
-    /// Return whether the CPU has frozen from a KIL instruction.
+    /// Return whether the CPU has frozen from a KIL instruction or a 65C02 STP instruction.
     #[inline]
     #[must_use]
     pub fn frozen(&self) -> bool {
         self.frozen
     }
+
+    /// If the CPU was halted by the RDY line during the last call to [`tick`](Self::tick), returns
+    /// the address of the read that it is stalled on.
+    ///
+    /// External DMA can use the bus during these cycles. Real hardware leaves the halted read's
+    /// address on the address bus, so DMA cycles that do not access memory themselves should
+    /// perform a spurious read from this address.
+    #[inline]
+    #[must_use]
+    pub fn rdy_halt_address(&self) -> Option<u16> {
+        self.rdy_halt_address
+    }
+
+    /// Return whether the CPU is waiting for an interrupt after a 65C02 WAI instruction.
+    #[inline]
+    #[must_use]
+    pub fn waiting_for_interrupt(&self) -> bool {
+        self.waiting
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum BusAccess {
+    Read(u16),
+    Write,
+}
+
+// Bus that records the first access without side effects, used to check whether the next cycle is a
+// read or a write
+struct ProbeBus {
+    first_access: Option<BusAccess>,
+}
+
+impl BusInterface for ProbeBus {
+    fn read(&mut self, address: u16) -> u8 {
+        self.first_access.get_or_insert(BusAccess::Read(address));
+        0
+    }
+
+    fn write(&mut self, _address: u16, _value: u8) {
+        self.first_access.get_or_insert(BusAccess::Write);
+    }
+
+    fn nmi(&self) -> bool {
+        false
+    }
+
+    fn acknowledge_nmi(&mut self) {}
+
+    fn irq(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Access {
+        Read(u16),
+        Write(u16, u8),
+    }
+
+    struct TestBus {
+        memory: Vec<u8>,
+        rdy: bool,
+        accesses: Vec<Access>,
+    }
+
+    impl TestBus {
+        // Loads the program at $0200 and points the RESET vector to it
+        fn new(program: &[u8]) -> Self {
+            let mut memory = vec![0xEA; 0x10000];
+            memory[0x0200..0x0200 + program.len()].copy_from_slice(program);
+            memory[usize::from(RESET_VECTOR)..usize::from(RESET_VECTOR) + 2]
+                .copy_from_slice(&[0x00, 0x02]);
+            Self { memory, rdy: true, accesses: Vec::new() }
+        }
+    }
+
+    impl BusInterface for TestBus {
+        fn read(&mut self, address: u16) -> u8 {
+            self.accesses.push(Access::Read(address));
+            self.memory[usize::from(address)]
+        }
+
+        fn write(&mut self, address: u16, value: u8) {
+            self.accesses.push(Access::Write(address, value));
+            self.memory[usize::from(address)] = value;
+        }
+
+        fn nmi(&self) -> bool {
+            false
+        }
+
+        fn acknowledge_nmi(&mut self) {}
+
+        fn irq(&self) -> bool {
+            false
+        }
+
+        fn rdy(&self) -> bool {
+            self.rdy
+        }
+    }
+
+    fn new_cpu(program: &[u8]) -> (Mos6502, TestBus) {
+        let mut bus = TestBus::new(program);
+        let cpu = Mos6502::new_nes(&mut bus);
+        bus.accesses.clear();
+        (cpu, bus)
+    }
+
+    fn tick_n(cpu: &mut Mos6502, bus: &mut TestBus, cycles: u32) {
+        for _ in 0..cycles {
+            cpu.tick(bus);
+        }
+    }
+
+    #[test]
+    fn rdy_halts_on_read() {
+        // LDA #$42; NOP
+        let (mut cpu, mut bus) = new_cpu(&[0xA9, 0x42, 0xEA]);
+
+        bus.rdy = false;
+        tick_n(&mut cpu, &mut bus, 3);
+        assert_eq!(cpu.rdy_halt_address(), Some(0x0200));
+        assert_eq!(cpu.pc(), 0x0200);
+        assert_eq!(bus.accesses, []);
+
+        bus.rdy = true;
+        tick_n(&mut cpu, &mut bus, 2);
+        assert_eq!(cpu.rdy_halt_address(), None);
+        assert_eq!(cpu.registers().accumulator, 0x42);
+        assert_eq!(bus.accesses, [Access::Read(0x0200), Access::Read(0x0201)]);
+
+        // Halting on an operand fetch mid-instruction
+        bus.accesses.clear();
+        cpu.tick(&mut bus);
+        bus.rdy = false;
+        tick_n(&mut cpu, &mut bus, 2);
+        assert_eq!(cpu.rdy_halt_address(), Some(0x0203));
+        assert_eq!(bus.accesses, [Access::Read(0x0202)]);
+    }
+
+    #[test]
+    fn rdy_completes_writes() {
+        // LDA #$42; STA $0010; NOP
+        let (mut cpu, mut bus) = new_cpu(&[0xA9, 0x42, 0x8D, 0x10, 0x00, 0xEA]);
+
+        // LDA, then STA up through the address fetch
+        tick_n(&mut cpu, &mut bus, 5);
+        bus.accesses.clear();
+
+        bus.rdy = false;
+        cpu.tick(&mut bus);
+        assert_eq!(cpu.rdy_halt_address(), None);
+        assert_eq!(bus.accesses, [Access::Write(0x0010, 0x42)]);
+
+        tick_n(&mut cpu, &mut bus, 2);
+        assert_eq!(cpu.rdy_halt_address(), Some(0x0205));
+        assert_eq!(bus.accesses, [Access::Write(0x0010, 0x42)]);
+    }
+
+    #[test]
+    fn rdy_completes_consecutive_writes() {
+        // INC $10, which writes the unmodified value and then the incremented value
+        let (mut cpu, mut bus) = new_cpu(&[0xE6, 0x10, 0xEA]);
+        bus.memory[0x10] = 0x7F;
+
+        // Opcode, address, and value fetches
+        tick_n(&mut cpu, &mut bus, 3);
+        bus.accesses.clear();
+
+        bus.rdy = false;
+        tick_n(&mut cpu, &mut bus, 3);
+        assert_eq!(bus.accesses, [Access::Write(0x0010, 0x7F), Access::Write(0x0010, 0x80)]);
+        assert_eq!(cpu.rdy_halt_address(), Some(0x0202));
+    }
 }
@@ -1,7 +1,7 @@
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use env_logger::Env;
 use mos6502_emu::bus::BusInterface;
-use mos6502_emu::{CpuRegisters, Mos6502, StatusFlags, StatusReadContext};
+use mos6502_emu::{CpuRegisters, Mos6502, StatusFlags, StatusReadContext, Variant};
 use serde::Deserialize;
 use std::fs::File;
 use std::io::BufReader;
@@ -95,21 +95,46 @@ struct TestDescription {
     cycles: Vec<Cycle>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum VariantArg {
+    #[value(name = "nmos")]
+    Nmos6502,
+    #[value(name = "2a03")]
+    Ricoh2A03,
+    #[value(name = "wdc65c02")]
+    Wdc65C02,
+}
+
+impl From<VariantArg> for Variant {
+    fn from(value: VariantArg) -> Self {
+        match value {
+            VariantArg::Nmos6502 => Self::Nmos6502,
+            VariantArg::Ricoh2A03 => Self::Ricoh2A03,
+            VariantArg::Wdc65C02 => Self::Wdc65C02,
+        }
+    }
+}
+
 #[derive(Debug, Parser)]
 struct Args {
     /// Directory containing JSON tests
     #[arg(long, short = 'd')]
     dir_path: String,
 
-    /// Emulate the NES 6502 instead of the standard 6502
-    #[arg(long, short = 'n', default_value_t)]
+    /// Emulate the NES 6502 instead of the standard 6502; equivalent to --variant 2a03
+    #[arg(long, short = 'n', default_value_t, conflicts_with = "variant")]
     nes: bool,
+
+    /// 6502 variant to emulate
+    #[arg(long, short = 'v', value_enum, default_value_t = VariantArg::Nmos6502)]
+    variant: VariantArg,
 }
 
 fn main() -> anyhow::Result<()> {
     env_logger::Builder::from_env(Env::default().default_filter_or("info")).init();
 
     let args = Args::parse();
+    let variant = if args.nes { Variant::Ricoh2A03 } else { args.variant.into() };
     let mut bus = Bus::new();
 
     for opcode in 0x00..=0xFF {
@@ -125,8 +150,7 @@ fn main() -> anyhow::Result<()> {
                 bus.write(address, value);
             }
 
-            let mut cpu =
-                if args.nes { Mos6502::new_nes(&mut bus) } else { Mos6502::new_standard(&mut bus) };
+            let mut cpu = Mos6502::new(&mut bus, variant);
 
             cpu.set_registers(CpuRegisters {
                 accumulator: test.initial.a,
@@ -145,7 +169,7 @@ fn main() -> anyhow::Result<()> {
             }
 
             if cpu.frozen() {
-                // Don't bother testing KIL opcodes
+                // Don't bother testing KIL or STP opcodes
                 continue;
             }
 
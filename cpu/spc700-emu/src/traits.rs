@@ -1,3 +1,5 @@
+use jgenesis_common::debug::{MemoryAccessKind, MemoryAccessLog};
+
 pub trait BusInterface {
     fn read(&mut self, address: u16) -> u8;
 
@@ -5,3 +7,33 @@ pub trait BusInterface {
 
     fn idle(&mut self);
 }
+
+/// Bus wrapper that records every memory access to a [`MemoryAccessLog`], for use by debugger
+/// watchpoints.
+pub struct LoggingBus<'a, B> {
+    bus: &'a mut B,
+    log: &'a mut MemoryAccessLog,
+}
+
+impl<'a, B: BusInterface> LoggingBus<'a, B> {
+    pub fn new(bus: &'a mut B, log: &'a mut MemoryAccessLog) -> Self {
+        Self { bus, log }
+    }
+}
+
+impl<B: BusInterface> BusInterface for LoggingBus<'_, B> {
+    fn read(&mut self, address: u16) -> u8 {
+        let value = self.bus.read(address);
+        self.log.record(address.into(), value, MemoryAccessKind::Read);
+        value
+    }
+
+    fn write(&mut self, address: u16, value: u8) {
+        self.bus.write(address, value);
+        self.log.record(address.into(), value, MemoryAccessKind::Write);
+    }
+
+    fn idle(&mut self) {
+        self.bus.idle();
+    }
+}
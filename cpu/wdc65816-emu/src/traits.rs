@@ -1,3 +1,5 @@
+use jgenesis_common::debug::{MemoryAccessKind, MemoryAccessLog};
+
 pub trait BusInterface {
     const ADDRESS_MASK: u32 = 0xFFFFFF;
 
@@ -17,3 +19,55 @@ pub trait BusInterface {
 
     fn reset(&self) -> bool;
 }
+
+/// Bus wrapper that records every memory access to a [`MemoryAccessLog`], for use by debugger
+/// watchpoints.
+pub struct LoggingBus<'a, B> {
+    bus: &'a mut B,
+    log: &'a mut MemoryAccessLog,
+}
+
+impl<'a, B: BusInterface> LoggingBus<'a, B> {
+    pub fn new(bus: &'a mut B, log: &'a mut MemoryAccessLog) -> Self {
+        Self { bus, log }
+    }
+}
+
+impl<B: BusInterface> BusInterface for LoggingBus<'_, B> {
+    const ADDRESS_MASK: u32 = B::ADDRESS_MASK;
+
+    fn read(&mut self, address: u32) -> u8 {
+        let value = self.bus.read(address);
+        self.log.record(address & B::ADDRESS_MASK, value, MemoryAccessKind::Read);
+        value
+    }
+
+    fn write(&mut self, address: u32, value: u8) {
+        self.bus.write(address, value);
+        self.log.record(address & B::ADDRESS_MASK, value, MemoryAccessKind::Write);
+    }
+
+    fn idle(&mut self) {
+        self.bus.idle();
+    }
+
+    fn nmi(&self) -> bool {
+        self.bus.nmi()
+    }
+
+    fn acknowledge_nmi(&mut self) {
+        self.bus.acknowledge_nmi();
+    }
+
+    fn irq(&self) -> bool {
+        self.bus.irq()
+    }
+
+    fn halt(&self) -> bool {
+        self.bus.halt()
+    }
+
+    fn reset(&self) -> bool {
+        self.bus.reset()
+    }
+}
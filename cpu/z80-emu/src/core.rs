@@ -52,6 +52,29 @@ pub enum InterruptMode {
     Mode2,
 }
 
+/// Read-only snapshot of the Z80 register file, for use by debuggers and other external tools.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegisterSnapshot {
+    pub af: u16,
+    pub bc: u16,
+    pub de: u16,
+    pub hl: u16,
+    pub af_shadow: u16,
+    pub bc_shadow: u16,
+    pub de_shadow: u16,
+    pub hl_shadow: u16,
+    pub ix: u16,
+    pub iy: u16,
+    pub sp: u16,
+    pub pc: u16,
+    pub i: u8,
+    pub r: u8,
+    pub iff1: bool,
+    pub iff2: bool,
+    pub interrupt_mode: InterruptMode,
+    pub halted: bool,
+}
+
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
 pub struct Registers {
@@ -323,6 +346,31 @@ impl Z80 {
         self.registers.pc = pc;
     }
 
+    #[must_use]
+    pub fn register_snapshot(&self) -> RegisterSnapshot {
+        let r = &self.registers;
+        RegisterSnapshot {
+            af: u16::from_be_bytes([r.a, r.f.into()]),
+            bc: u16::from_be_bytes([r.b, r.c]),
+            de: u16::from_be_bytes([r.d, r.e]),
+            hl: u16::from_be_bytes([r.h, r.l]),
+            af_shadow: u16::from_be_bytes([r.ap, r.fp.into()]),
+            bc_shadow: u16::from_be_bytes([r.bp, r.cp]),
+            de_shadow: u16::from_be_bytes([r.dp, r.ep]),
+            hl_shadow: u16::from_be_bytes([r.hp, r.lp]),
+            ix: r.ix,
+            iy: r.iy,
+            sp: r.sp,
+            pc: r.pc,
+            i: r.i,
+            r: r.r,
+            iff1: r.iff1,
+            iff2: r.iff2,
+            interrupt_mode: r.interrupt_mode,
+            halted: r.halted,
+        }
+    }
+
     pub fn set_sp(&mut self, sp: u16) {
         self.registers.sp = sp;
     }
@@ -204,7 +204,6 @@ impl<'registers, 'bus, B: BusInterface> InstructionExecutor<'registers, 'bus, B>
                 self.registers.iff1 = false;
                 self.registers.iff2 = false;
 
-                #[allow(unreachable_code)]
                 match self.registers.interrupt_mode {
                     // Modes 0 and 1 don't actually work the same way in actual hardware, but for
                     // the purposes of emulating these consoles they do.
@@ -220,7 +219,13 @@ impl<'registers, 'bus, B: BusInterface> InstructionExecutor<'registers, 'bus, B>
                         13
                     }
                     InterruptMode::Mode2 => {
-                        todo!("interrupt mode 2");
+                        // Mode 2 reads the low byte of the vector table address from the data bus,
+                        // which similarly always reads $FF on these consoles
+                        let vector_address = u16::from_be_bytes([self.registers.i, 0xFF]);
+                        let isr_address = self.read_memory_u16(vector_address);
+
+                        self.push_stack(self.registers.pc);
+                        self.registers.pc = isr_address;
 
                         19
                     }
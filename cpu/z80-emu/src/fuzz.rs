@@ -0,0 +1,39 @@
+//! Entry points for the cargo-fuzz targets in the repository's `fuzz` directory
+
+use crate::traits::{InMemoryBus, InterruptLine};
+use crate::Z80;
+
+const MAX_INSTRUCTIONS: u32 = 1000;
+
+// Assert INT periodically so that all three interrupt modes get exercised
+const INTERRUPT_INTERVAL: u32 = 100;
+
+/// Run the CPU from address $0000 with the input loaded into memory starting at $0000.
+///
+/// Arbitrary code must never panic the CPU core.
+pub fn execute(data: &[u8]) {
+    let mut bus = InMemoryBus::new();
+    let len = data.len().min(bus.memory.len());
+    bus.memory[..len].copy_from_slice(&data[..len]);
+
+    let mut z80 = Z80::new();
+    for i in 0..MAX_INSTRUCTIONS {
+        bus.int = if i % INTERRUPT_INTERVAL == INTERRUPT_INTERVAL - 1 {
+            InterruptLine::Low
+        } else {
+            InterruptLine::High
+        };
+
+        z80.execute_instruction(&mut bus);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn execute_corpus() {
+        jgenesis_common::fuzz::replay_corpus("z80_execute", execute);
+    }
+}
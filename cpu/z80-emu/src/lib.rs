@@ -1,5 +1,7 @@
 mod core;
+#[cfg(any(test, feature = "fuzzing"))]
+pub mod fuzz;
 pub mod traits;
 
-pub use crate::core::{InterruptMode, Z80};
+pub use crate::core::{InterruptMode, RegisterSnapshot, Z80};
 pub use traits::BusInterface;
@@ -1,3 +1,5 @@
+use jgenesis_common::debug::{MemoryAccessKind, MemoryAccessLog};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
 pub enum InterruptLine {
@@ -31,7 +33,57 @@ pub trait BusInterface {
     fn reset(&self) -> bool;
 }
 
-#[cfg(test)]
+/// Bus wrapper that records every memory access to a [`MemoryAccessLog`], for use by debugger
+/// watchpoints.
+pub struct LoggingBus<'a, B> {
+    bus: &'a mut B,
+    log: &'a mut MemoryAccessLog,
+}
+
+impl<'a, B: BusInterface> LoggingBus<'a, B> {
+    pub fn new(bus: &'a mut B, log: &'a mut MemoryAccessLog) -> Self {
+        Self { bus, log }
+    }
+}
+
+impl<B: BusInterface> BusInterface for LoggingBus<'_, B> {
+    fn read_memory(&mut self, address: u16) -> u8 {
+        let value = self.bus.read_memory(address);
+        self.log.record(address.into(), value, MemoryAccessKind::Read);
+        value
+    }
+
+    fn write_memory(&mut self, address: u16, value: u8) {
+        self.bus.write_memory(address, value);
+        self.log.record(address.into(), value, MemoryAccessKind::Write);
+    }
+
+    fn read_io(&mut self, address: u16) -> u8 {
+        self.bus.read_io(address)
+    }
+
+    fn write_io(&mut self, address: u16, value: u8) {
+        self.bus.write_io(address, value);
+    }
+
+    fn nmi(&self) -> InterruptLine {
+        self.bus.nmi()
+    }
+
+    fn int(&self) -> InterruptLine {
+        self.bus.int()
+    }
+
+    fn busreq(&self) -> bool {
+        self.bus.busreq()
+    }
+
+    fn reset(&self) -> bool {
+        self.bus.reset()
+    }
+}
+
+#[cfg(any(test, feature = "fuzzing"))]
 #[derive(Debug, Clone)]
 pub(crate) struct InMemoryBus {
     pub(crate) memory: [u8; 0x10000],
@@ -41,7 +93,7 @@ pub(crate) struct InMemoryBus {
     pub(crate) reset: bool,
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "fuzzing"))]
 impl InMemoryBus {
     pub(crate) fn new() -> Self {
         Self {
@@ -54,7 +106,7 @@ impl InMemoryBus {
     }
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "fuzzing"))]
 impl BusInterface for InMemoryBus {
     fn read_memory(&mut self, address: u16) -> u8 {
         self.memory[address as usize]
@@ -0,0 +1,78 @@
+package io.github.jsgroth.jgenesis;
+
+import android.app.NativeActivity;
+import android.content.Intent;
+import android.database.Cursor;
+import android.net.Uri;
+import android.os.ParcelFileDescriptor;
+import android.provider.OpenableColumns;
+import android.util.Log;
+import android.widget.Toast;
+
+import java.io.FileNotFoundException;
+
+/**
+ * NativeActivity cannot receive activity results, so this subclass handles Storage Access Framework
+ * file picking on behalf of the Rust code. Everything else runs in libjgenesis_android.so.
+ */
+public class MainActivity extends NativeActivity {
+    private static final String TAG = "jgenesis";
+
+    static {
+        System.loadLibrary("jgenesis_android");
+    }
+
+    /** Takes ownership of fd. Called on a background thread. */
+    private static native void nativeOnFilePicked(int requestCode, String fileName, int fd);
+
+    // Called from Rust over JNI
+    public void openFilePicker(int requestCode) {
+        Intent intent = new Intent(Intent.ACTION_OPEN_DOCUMENT);
+        intent.addCategory(Intent.CATEGORY_OPENABLE);
+        // ROM file extensions do not have registered MIME types
+        intent.setType("*/*");
+        runOnUiThread(() -> startActivityForResult(intent, requestCode));
+    }
+
+    // Called from Rust over JNI
+    public void showMessage(String message) {
+        runOnUiThread(() -> Toast.makeText(this, message, Toast.LENGTH_LONG).show());
+    }
+
+    @Override
+    protected void onActivityResult(int requestCode, int resultCode, Intent data) {
+        super.onActivityResult(requestCode, resultCode, data);
+
+        if (resultCode != RESULT_OK || data == null || data.getData() == null) {
+            return;
+        }
+
+        Uri uri = data.getData();
+        String fileName = queryDisplayName(uri);
+
+        ParcelFileDescriptor pfd;
+        try {
+            pfd = getContentResolver().openFileDescriptor(uri, "r");
+        } catch (FileNotFoundException e) {
+            Log.e(TAG, "Unable to open picked file " + uri, e);
+            showMessage("Unable to open " + fileName);
+            return;
+        }
+        if (pfd == null) {
+            return;
+        }
+
+        int fd = pfd.detachFd();
+        new Thread(() -> nativeOnFilePicked(requestCode, fileName, fd)).start();
+    }
+
+    private String queryDisplayName(Uri uri) {
+        try (Cursor cursor = getContentResolver().query(uri, new String[]{OpenableColumns.DISPLAY_NAME}, null, null, null)) {
+            if (cursor != null && cursor.moveToFirst()) {
+                return cursor.getString(0);
+            }
+        }
+        String lastSegment = uri.getLastPathSegment();
+        return lastSegment != null ? lastSegment : "";
+    }
+}
@@ -0,0 +1,100 @@
+use ndk::audio::{
+    AudioCallbackResult, AudioDirection, AudioError as NdkAudioError, AudioFormat,
+    AudioPerformanceMode, AudioSharingMode, AudioStream, AudioStreamBuilder,
+};
+use std::collections::VecDeque;
+use std::ffi::c_void;
+use std::slice;
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+
+const AUDIO_FREQUENCY: i32 = jgenesis_capi::emulator::AUDIO_SAMPLE_RATE as i32;
+
+// Emulation runs ahead until the queue holds this many sample frames (~64ms), and samples past
+// twice this are dropped
+const SYNC_THRESHOLD: usize = 3072;
+const MAX_QUEUE_LEN: usize = 2 * SYNC_THRESHOLD;
+
+#[derive(Debug, Error)]
+pub enum AudioError {
+    #[error("Error opening AAudio output stream: {0}")]
+    OpenStream(NdkAudioError),
+    #[error("Error starting or pausing AAudio output stream: {0}")]
+    StreamState(NdkAudioError),
+}
+
+type SampleQueue = Arc<Mutex<VecDeque<f32>>>;
+
+/// Stereo f32 output through AAudio. The stream's data callback pulls interleaved samples from a
+/// shared queue, outputting silence if the queue runs dry.
+pub struct AAudioOutput {
+    stream: AudioStream,
+    queue: SampleQueue,
+}
+
+impl AAudioOutput {
+    pub fn open() -> Result<Self, AudioError> {
+        let queue: SampleQueue = Arc::new(Mutex::new(VecDeque::with_capacity(2 * MAX_QUEUE_LEN)));
+
+        let callback_queue = Arc::clone(&queue);
+        let stream = AudioStreamBuilder::new()
+            .map_err(AudioError::OpenStream)?
+            .direction(AudioDirection::Output)
+            .format(AudioFormat::PCM_Float)
+            .channel_count(2)
+            .sample_rate(AUDIO_FREQUENCY)
+            .performance_mode(AudioPerformanceMode::LowLatency)
+            .sharing_mode(AudioSharingMode::Shared)
+            .data_callback(Box::new(move |_stream, data: *mut c_void, num_frames: i32| {
+                // SAFETY: AAudio passes a buffer of num_frames frames in the requested format,
+                // which is 2x f32 per frame
+                let output = unsafe {
+                    slice::from_raw_parts_mut(data.cast::<f32>(), 2 * num_frames as usize)
+                };
+                fill_output(&callback_queue, output);
+                AudioCallbackResult::Continue
+            }))
+            .error_callback(Box::new(|_stream, err| {
+                log::error!("AAudio stream error: {err}");
+            }))
+            .open_stream()
+            .map_err(AudioError::OpenStream)?;
+
+        Ok(Self { stream, queue })
+    }
+
+    pub fn start(&mut self) -> Result<(), AudioError> {
+        self.stream.request_start().map_err(AudioError::StreamState)
+    }
+
+    /// Pause the stream and discard any queued samples so that stale audio doesn't play on resume.
+    pub fn pause(&mut self) -> Result<(), AudioError> {
+        self.stream.request_pause().map_err(AudioError::StreamState)?;
+        self.queue.lock().unwrap().clear();
+        Ok(())
+    }
+
+    pub fn push_samples(&self, samples: &[f32]) {
+        let mut queue = self.queue.lock().unwrap();
+        if queue.len() + samples.len() > 2 * MAX_QUEUE_LEN {
+            log::debug!("Audio queue full, dropping {} sample frames", samples.len() / 2);
+            return;
+        }
+        queue.extend(samples);
+    }
+
+    /// Whether the queue has enough samples buffered that emulation should wait before running
+    /// another frame.
+    pub fn should_wait(&self) -> bool {
+        self.queue.lock().unwrap().len() >= 2 * SYNC_THRESHOLD
+    }
+}
+
+fn fill_output(queue: &Mutex<VecDeque<f32>>, output: &mut [f32]) {
+    let mut queue = queue.lock().unwrap();
+    let available = queue.len().min(output.len());
+    for (out, sample) in output.iter_mut().zip(queue.drain(..available)) {
+        *out = sample;
+    }
+    output[available..].fill(0.0);
+}
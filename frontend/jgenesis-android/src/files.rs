@@ -0,0 +1,141 @@
+//! File picking through the Storage Access Framework
+//!
+//! NativeActivity has no way to receive activity results, so the picker is handled by the
+//! `MainActivity` subclass in `app/`. Rust calls `MainActivity.openFilePicker()` over JNI, and once
+//! the user picks a file the activity opens it and passes the file descriptor back through
+//! `MainActivity.nativeOnFilePicked()`, which forwards it to the event loop as a user event.
+
+use jni::objects::{JClass, JObject, JString, JValue};
+use jni::sys::jint;
+use jni::{JNIEnv, JavaVM};
+use std::fs::File;
+use std::os::fd::{FromRawFd, OwnedFd};
+use std::sync::{Mutex, OnceLock};
+use winit::event_loop::EventLoopProxy;
+use winit::platform::android::activity::AndroidApp;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileRequest {
+    Rom,
+    SegaCdBios,
+}
+
+impl FileRequest {
+    fn request_code(self) -> jint {
+        match self {
+            Self::Rom => 1,
+            Self::SegaCdBios => 2,
+        }
+    }
+
+    fn from_request_code(request_code: jint) -> Option<Self> {
+        match request_code {
+            1 => Some(Self::Rom),
+            2 => Some(Self::SegaCdBios),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum AndroidUserEvent {
+    FilePicked { request: FileRequest, file_name: String, file: File },
+}
+
+static EVENT_LOOP_PROXY: OnceLock<Mutex<EventLoopProxy<AndroidUserEvent>>> = OnceLock::new();
+
+pub fn set_event_loop_proxy(proxy: EventLoopProxy<AndroidUserEvent>) {
+    if EVENT_LOOP_PROXY.set(Mutex::new(proxy)).is_err() {
+        log::error!("Event loop proxy was already set");
+    }
+}
+
+fn with_activity<T>(
+    app: &AndroidApp,
+    f: impl FnOnce(&mut JNIEnv<'_>, &JObject<'_>) -> jni::errors::Result<T>,
+) -> jni::errors::Result<T> {
+    // SAFETY: android-activity guarantees that these pointers are valid for as long as the app is
+    // running
+    let vm = unsafe { JavaVM::from_raw(app.vm_as_ptr().cast())? };
+    let activity = unsafe { JObject::from_raw(app.activity_as_ptr().cast()) };
+
+    let mut env = vm.attach_current_thread()?;
+    f(&mut env, &activity)
+}
+
+/// Launch the system file picker. The result arrives later as an [`AndroidUserEvent::FilePicked`]
+/// event, or not at all if the user cancels.
+pub fn open_file_picker(app: &AndroidApp, request: FileRequest) {
+    let result = with_activity(app, |env, activity| {
+        env.call_method(
+            activity,
+            "openFilePicker",
+            "(I)V",
+            &[JValue::Int(request.request_code())],
+        )?;
+        Ok(())
+    });
+    if let Err(err) = result {
+        log::error!("Error opening file picker: {err}");
+    }
+}
+
+/// Show a short message to the user as a toast.
+pub fn show_message(app: &AndroidApp, message: &str) {
+    log::info!("{message}");
+
+    let result = with_activity(app, |env, activity| {
+        let message = env.new_string(message)?;
+        env.call_method(
+            activity,
+            "showMessage",
+            "(Ljava/lang/String;)V",
+            &[JValue::Object(&message)],
+        )?;
+        Ok(())
+    });
+    if let Err(err) = result {
+        log::error!("Error showing message '{message}': {err}");
+    }
+}
+
+/// Called by `MainActivity` on a background thread after the user picks a file. Takes ownership of
+/// `fd`.
+#[no_mangle]
+pub extern "system" fn Java_io_github_jsgroth_jgenesis_MainActivity_nativeOnFilePicked(
+    mut env: JNIEnv<'_>,
+    _class: JClass<'_>,
+    request_code: jint,
+    file_name: JString<'_>,
+    fd: jint,
+) {
+    // SAFETY: MainActivity detaches the descriptor from its ParcelFileDescriptor before passing it
+    // here, so this is the only owner
+    let file = File::from(unsafe { OwnedFd::from_raw_fd(fd) });
+
+    let Some(request) = FileRequest::from_request_code(request_code) else {
+        log::error!("Unexpected file picker request code: {request_code}");
+        return;
+    };
+
+    let file_name: String = match env.get_string(&file_name) {
+        Ok(file_name) => file_name.into(),
+        Err(err) => {
+            log::error!("Error reading picked file name: {err}");
+            return;
+        }
+    };
+
+    let Some(proxy) = EVENT_LOOP_PROXY.get() else {
+        log::error!("File picked before the event loop was initialized");
+        return;
+    };
+    if proxy
+        .lock()
+        .unwrap()
+        .send_event(AndroidUserEvent::FilePicked { request, file_name, file })
+        .is_err()
+    {
+        log::error!("Event loop closed before picked file could be sent");
+    }
+}
@@ -0,0 +1,450 @@
+//! Android frontend
+//!
+//! Runs on top of `jgenesis-capi`'s headless [`Emulator`] and renders through `jgenesis-renderer`
+//! using winit's NativeActivity backend. The Java side is limited to a small NativeActivity subclass
+//! in `app/` that handles Storage Access Framework file picking.
+
+#![cfg(target_os = "android")]
+
+mod audio;
+mod files;
+mod overlay;
+
+use crate::audio::AAudioOutput;
+use crate::files::{AndroidUserEvent, FileRequest};
+use crate::overlay::{Orientation, OverlayAction, TouchOverlay};
+use jgenesis_capi::emulator::{Emulator, EmulatorError, System};
+use jgenesis_common::frontend::{Color, FrameSize, PixelAspectRatio, Renderer};
+use jgenesis_renderer::config::{
+    ColorBlindFilter, FilterMode, MagnifierCorner, MagnifierMode, PreprocessShader, PrescaleFactor,
+    RendererConfig, Scanlines, VSyncMode, WgpuBackend,
+};
+use jgenesis_renderer::renderer::WgpuRenderer;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use std::{fs, io, thread};
+use thiserror::Error;
+use winit::event::{Event, WindowEvent};
+use winit::event_loop::{ControlFlow, EventLoopBuilder, EventLoopWindowTarget};
+use winit::platform::android::activity::AndroidApp;
+use winit::platform::android::EventLoopBuilderExtAndroid;
+use winit::window::{Window, WindowBuilder};
+
+// Only used to pace emulation if the audio stream could not be opened
+const FALLBACK_FRAME_DURATION: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+const SEGA_CD_BIOS_FILE_NAME: &str = "segacd_bios.bin";
+const SEGA_CD_DISC_FILE_NAME: &str = "disc.chd";
+
+#[derive(Debug, Error)]
+enum LoadError {
+    #[error("Unsupported file extension: '{0}'")]
+    UnsupportedExtension(String),
+    #[error("CUE/BIN discs are not supported on Android; convert the disc to CHD")]
+    CueNotSupported,
+    #[error("I/O error reading '{path}': {source}")]
+    Io {
+        path: String,
+        #[source]
+        source: io::Error,
+    },
+    #[error(transparent)]
+    Emulator(#[from] EmulatorError),
+}
+
+fn system_from_file_name(file_name: &str) -> Result<System, LoadError> {
+    let extension = Path::new(file_name)
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default();
+
+    match extension.as_str() {
+        "sms" => Ok(System::MasterSystem),
+        "gg" => Ok(System::GameGear),
+        "md" | "bin" => Ok(System::Genesis),
+        "chd" => Ok(System::SegaCd),
+        "cue" => Err(LoadError::CueNotSupported),
+        "nes" => Ok(System::Nes),
+        "sfc" | "smc" => Ok(System::Snes),
+        "gb" | "gbc" => Ok(System::GameBoy),
+        _ => Err(LoadError::UnsupportedExtension(extension)),
+    }
+}
+
+fn renderer_config() -> RendererConfig {
+    RendererConfig {
+        wgpu_backend: WgpuBackend::Auto,
+        vsync_mode: VSyncMode::Enabled,
+        prescale_factor: PrescaleFactor::try_from(3).unwrap(),
+        scanlines: Scanlines::default(),
+        force_integer_height_scaling: false,
+        filter_mode: FilterMode::default(),
+        preprocess_shader: PreprocessShader::default(),
+        color_blind_filter: ColorBlindFilter::default(),
+        flash_reduction: false,
+        magnifier_mode: MagnifierMode::default(),
+        magnifier_corner: MagnifierCorner::default(),
+        use_webgl2_limits: false,
+        pal_50hz_fullscreen: false,
+        show_border: false,
+    }
+}
+
+fn window_size_fn(window: &Window) -> (u32, u32) {
+    let size = window.inner_size();
+    (size.width, size.height)
+}
+
+struct RunningGame {
+    emulator: Emulator,
+    system: System,
+    save_path: PathBuf,
+}
+
+impl RunningGame {
+    fn persist_save(&self) {
+        let Some(save_file) = self.emulator.save_file("sav") else { return };
+        if let Err(err) = fs::write(&self.save_path, save_file) {
+            log::error!("Error writing save file to '{}': {err}", self.save_path.display());
+        }
+    }
+}
+
+struct App {
+    android_app: AndroidApp,
+    data_dir: PathBuf,
+    renderer: Option<WgpuRenderer<Window>>,
+    audio: Option<AAudioOutput>,
+    overlay: TouchOverlay,
+    game: Option<RunningGame>,
+    // Set while waiting for the user to pick a Sega CD BIOS before a disc can be loaded
+    pending_disc_name: Option<String>,
+    focused: bool,
+    prompted_for_rom: bool,
+    next_frame_time: Instant,
+}
+
+impl App {
+    fn new(android_app: AndroidApp) -> Self {
+        let data_dir = android_app.internal_data_path().unwrap_or_else(|| {
+            log::warn!("Unable to determine internal data path; saves will not persist");
+            std::env::temp_dir()
+        });
+
+        let audio = match AAudioOutput::open() {
+            Ok(audio) => Some(audio),
+            Err(err) => {
+                log::error!("Unable to open audio stream, running without audio: {err}");
+                None
+            }
+        };
+
+        Self {
+            android_app,
+            data_dir,
+            renderer: None,
+            audio,
+            overlay: TouchOverlay::new(),
+            game: None,
+            pending_disc_name: None,
+            focused: true,
+            prompted_for_rom: false,
+            next_frame_time: Instant::now(),
+        }
+    }
+
+    fn handle_event(
+        &mut self,
+        event: Event<'_, AndroidUserEvent>,
+        target: &EventLoopWindowTarget<AndroidUserEvent>,
+        control_flow: &mut ControlFlow,
+    ) {
+        match event {
+            Event::Resumed => self.resume(target),
+            Event::Suspended => self.suspend(),
+            Event::UserEvent(AndroidUserEvent::FilePicked { request, file_name, file }) => {
+                self.handle_file_picked(request, &file_name, file);
+            }
+            Event::WindowEvent { event: window_event, .. } => {
+                self.handle_window_event(&window_event);
+            }
+            Event::MainEventsCleared => self.run_frame(control_flow),
+            Event::LoopDestroyed => {
+                if let Some(game) = &self.game {
+                    game.persist_save();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // The native window only exists between Resumed and Suspended events, so the renderer (which
+    // owns the window surface) is created here and dropped on suspend
+    fn resume(&mut self, target: &EventLoopWindowTarget<AndroidUserEvent>) {
+        let window = match WindowBuilder::new().build(target) {
+            Ok(window) => window,
+            Err(err) => {
+                log::error!("Error creating window: {err}");
+                return;
+            }
+        };
+
+        match pollster::block_on(WgpuRenderer::new(window, window_size_fn, renderer_config())) {
+            Ok(renderer) => self.renderer = Some(renderer),
+            Err(err) => {
+                log::error!("Error creating wgpu renderer: {err}");
+                return;
+            }
+        }
+
+        if let Some(audio) = &mut self.audio {
+            if let Err(err) = audio.start() {
+                log::error!("{err}");
+            }
+        }
+
+        self.next_frame_time = Instant::now();
+
+        if !self.prompted_for_rom {
+            self.prompted_for_rom = true;
+            files::open_file_picker(&self.android_app, FileRequest::Rom);
+        }
+    }
+
+    fn suspend(&mut self) {
+        self.renderer = None;
+        self.overlay.clear_touches();
+
+        if let Some(audio) = &mut self.audio {
+            if let Err(err) = audio.pause() {
+                log::error!("{err}");
+            }
+        }
+
+        // The app may be killed at any point after being suspended
+        if let Some(game) = &self.game {
+            game.persist_save();
+        }
+    }
+
+    fn is_paused(&self) -> bool {
+        self.renderer.is_none() || !self.focused
+    }
+
+    fn handle_window_event(&mut self, event: &WindowEvent<'_>) {
+        match event {
+            WindowEvent::Resized(_) => {
+                if let Some(renderer) = &mut self.renderer {
+                    renderer.handle_resize();
+                }
+            }
+            WindowEvent::Focused(focused) => {
+                self.focused = *focused;
+                if !focused {
+                    self.overlay.clear_touches();
+                }
+                self.next_frame_time = Instant::now();
+            }
+            WindowEvent::Touch(touch) => {
+                let display_info =
+                    self.renderer.as_ref().and_then(WgpuRenderer::current_display_info);
+                if let Some(OverlayAction::OpenFile) =
+                    self.overlay.handle_touch(touch, display_info)
+                {
+                    files::open_file_picker(&self.android_app, FileRequest::Rom);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_file_picked(&mut self, request: FileRequest, file_name: &str, file: File) {
+        let result = match request {
+            FileRequest::Rom => self.open_rom(file_name, file),
+            FileRequest::SegaCdBios => self.store_sega_cd_bios(file),
+        };
+
+        if let Err(err) = result {
+            files::show_message(&self.android_app, &format!("Error opening '{file_name}': {err}"));
+        }
+    }
+
+    fn open_rom(&mut self, file_name: &str, mut file: File) -> Result<(), LoadError> {
+        let system = system_from_file_name(file_name)?;
+
+        if system == System::SegaCd {
+            // The running disc must be closed before its file is overwritten
+            if let Some(game) = self.game.take_if(|game| game.system == System::SegaCd) {
+                game.persist_save();
+            }
+
+            // Disc images are too large to read into memory and the Sega CD core reads them by
+            // path, so copy the picked file into app storage
+            let disc_path = self.data_dir.join(SEGA_CD_DISC_FILE_NAME);
+            File::create(&disc_path)
+                .and_then(|mut disc_file| io::copy(&mut file, &mut disc_file))
+                .map_err(|source| LoadError::Io { path: file_name.into(), source })?;
+
+            if !self.data_dir.join(SEGA_CD_BIOS_FILE_NAME).exists() {
+                self.pending_disc_name = Some(file_name.into());
+                files::show_message(&self.android_app, "Select a Sega CD BIOS ROM");
+                files::open_file_picker(&self.android_app, FileRequest::SegaCdBios);
+                return Ok(());
+            }
+
+            return self.start_sega_cd(file_name);
+        }
+
+        let mut rom = Vec::new();
+        file.read_to_end(&mut rom)
+            .map_err(|source| LoadError::Io { path: file_name.into(), source })?;
+
+        let save_path = self.save_path(file_name);
+        let sram = fs::read(&save_path).ok();
+        let emulator = Emulator::create(system, rom, sram)?;
+
+        self.start_game(RunningGame { emulator, system, save_path });
+        Ok(())
+    }
+
+    fn store_sega_cd_bios(&mut self, mut file: File) -> Result<(), LoadError> {
+        let bios_path = self.data_dir.join(SEGA_CD_BIOS_FILE_NAME);
+        File::create(&bios_path)
+            .and_then(|mut bios_file| io::copy(&mut file, &mut bios_file))
+            .map_err(|source| LoadError::Io { path: bios_path.display().to_string(), source })?;
+
+        match self.pending_disc_name.take() {
+            Some(disc_name) => self.start_sega_cd(&disc_name),
+            None => Ok(()),
+        }
+    }
+
+    fn start_sega_cd(&mut self, disc_name: &str) -> Result<(), LoadError> {
+        let bios_path = self.data_dir.join(SEGA_CD_BIOS_FILE_NAME);
+        let bios = fs::read(&bios_path)
+            .map_err(|source| LoadError::Io { path: bios_path.display().to_string(), source })?;
+
+        let save_path = self.save_path(disc_name);
+        let backup_ram = fs::read(&save_path).ok();
+        let emulator = Emulator::create_sega_cd(
+            bios,
+            &self.data_dir.join(SEGA_CD_DISC_FILE_NAME),
+            backup_ram,
+        )?;
+
+        self.start_game(RunningGame { emulator, system: System::SegaCd, save_path });
+        Ok(())
+    }
+
+    fn save_path(&self, file_name: &str) -> PathBuf {
+        let saves_dir = self.data_dir.join("saves");
+        if let Err(err) = fs::create_dir_all(&saves_dir) {
+            log::error!("Error creating saves directory '{}': {err}", saves_dir.display());
+        }
+
+        let stem = Path::new(file_name)
+            .file_stem()
+            .map_or(file_name.into(), |stem| stem.to_string_lossy());
+        saves_dir.join(format!("{stem}.sav"))
+    }
+
+    fn start_game(&mut self, game: RunningGame) {
+        if let Some(previous) = self.game.replace(game) {
+            previous.persist_save();
+        }
+
+        self.overlay.clear_touches();
+        self.next_frame_time = Instant::now();
+    }
+
+    fn run_frame(&mut self, control_flow: &mut ControlFlow) {
+        if self.is_paused() {
+            *control_flow = ControlFlow::Wait;
+            return;
+        }
+
+        let Some(game) = &mut self.game else {
+            // Nothing is changing while no game is running, so only redraw in response to events
+            self.render();
+            *control_flow = ControlFlow::Wait;
+            return;
+        };
+
+        *control_flow = ControlFlow::Poll;
+
+        // Emulation is paced by audio sync when audio is available, and otherwise by time
+        match &self.audio {
+            Some(audio) => {
+                if audio.should_wait() {
+                    thread::sleep(Duration::from_millis(1));
+                    return;
+                }
+            }
+            None => {
+                let now = Instant::now();
+                if now < self.next_frame_time {
+                    thread::sleep(self.next_frame_time - now);
+                    return;
+                }
+                self.next_frame_time = (self.next_frame_time + FALLBACK_FRAME_DURATION).max(now);
+            }
+        }
+
+        game.emulator.set_buttons(0, self.overlay.pressed_buttons());
+        if let Err(err) = game.emulator.run_frame() {
+            let message = format!("Emulator error: {err}");
+            self.game = None;
+            files::show_message(&self.android_app, &message);
+            return;
+        }
+
+        if let Some(audio) = &self.audio {
+            audio.push_samples(game.emulator.audio_samples());
+        }
+
+        self.render();
+    }
+
+    fn render(&mut self) {
+        let Some(renderer) = &mut self.renderer else { return };
+
+        let (game_frame, game_size, system) = match &self.game {
+            Some(game) if game.emulator.frame().1.width != 0 => {
+                let (frame, frame_size) = game.emulator.frame();
+                (bytemuck::cast_slice::<u8, Color>(frame), frame_size, Some(game.system))
+            }
+            _ => (&[][..], FrameSize { width: 256, height: 224 }, None),
+        };
+
+        let (window_width, window_height) = window_size_fn(renderer.window());
+        let orientation = Orientation::from_window_size(window_width, window_height);
+
+        // The capi configs use square pixels, and the overlay is drawn with square pixels
+        let (canvas, canvas_size) =
+            self.overlay.compose(system, game_frame, game_size, orientation);
+        if let Err(err) = renderer.render_frame(canvas, canvas_size, Some(PixelAspectRatio::SQUARE))
+        {
+            log::error!("Error rendering frame: {err}");
+        }
+    }
+}
+
+#[no_mangle]
+fn android_main(android_app: AndroidApp) {
+    android_logger::init_once(
+        android_logger::Config::default()
+            .with_max_level(log::LevelFilter::Info)
+            .with_tag("jgenesis"),
+    );
+
+    let event_loop =
+        EventLoopBuilder::with_user_event().with_android_app(android_app.clone()).build();
+    files::set_event_loop_proxy(event_loop.create_proxy());
+
+    let mut app = App::new(android_app);
+    event_loop.run(move |event, target, control_flow| {
+        app.handle_event(event, target, control_flow);
+    });
+}
@@ -0,0 +1,476 @@
+//! On-screen touch controls
+//!
+//! The overlay is drawn in software into a composite frame that contains the game frame plus a
+//! control panel (below the game in portrait, on either side in landscape), and the composite frame
+//! is what gets passed to the renderer. This keeps the renderer unchanged, and it means that touch
+//! positions can be mapped back to composite frame coordinates using the renderer's display area.
+
+use jgenesis_capi::emulator::{
+    System, BUTTON_A, BUTTON_B, BUTTON_C, BUTTON_DOWN, BUTTON_L, BUTTON_LEFT, BUTTON_R,
+    BUTTON_RIGHT, BUTTON_SELECT, BUTTON_START, BUTTON_UP, BUTTON_X, BUTTON_Y, BUTTON_Z,
+};
+use jgenesis_common::frontend::{Color, FrameSize};
+use jgenesis_renderer::renderer::DisplayArea;
+use std::collections::HashMap;
+use winit::event::{Touch, TouchPhase};
+
+const BACKGROUND_COLOR: Color = Color::rgb(24, 24, 24);
+const BUTTON_COLOR: Color = Color::rgb(72, 72, 72);
+const PRESSED_COLOR: Color = Color::rgb(150, 150, 150);
+const LABEL_COLOR: Color = Color::rgb(235, 235, 235);
+
+// Touches are accepted slightly outside of a button's drawn area
+const HIT_RADIUS_MULTIPLIER: f64 = 1.25;
+
+// sin(22.5 degrees); D-pad touches within 22.5 degrees of a diagonal press both directions
+const DPAD_AXIS_THRESHOLD: f64 = 0.383;
+const DPAD_DEAD_ZONE: f64 = 0.2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    Portrait,
+    Landscape,
+}
+
+impl Orientation {
+    pub fn from_window_size(width: u32, height: u32) -> Self {
+        if height > width {
+            Self::Portrait
+        } else {
+            Self::Landscape
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverlayAction {
+    OpenFile,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ButtonKind {
+    Emulated(u32),
+    Action(OverlayAction),
+}
+
+#[derive(Debug, Clone, Copy)]
+struct OverlayButton {
+    label: &'static str,
+    x: f64,
+    y: f64,
+    radius: f64,
+    kind: ButtonKind,
+}
+
+impl OverlayButton {
+    fn contains(&self, x: f64, y: f64) -> bool {
+        (x - self.x).hypot(y - self.y) <= self.radius * HIT_RADIUS_MULTIPLIER
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Dpad {
+    x: f64,
+    y: f64,
+    size: f64,
+}
+
+impl Dpad {
+    fn buttons_at(&self, x: f64, y: f64) -> u32 {
+        let (dx, dy) = ((x - self.x) / self.size, (y - self.y) / self.size);
+        let distance = dx.hypot(dy);
+        if !(DPAD_DEAD_ZONE..=HIT_RADIUS_MULTIPLIER).contains(&distance) {
+            return 0;
+        }
+
+        let (nx, ny) = (dx / distance, dy / distance);
+        let mut buttons = 0;
+        if ny < -DPAD_AXIS_THRESHOLD {
+            buttons |= BUTTON_UP;
+        }
+        if ny > DPAD_AXIS_THRESHOLD {
+            buttons |= BUTTON_DOWN;
+        }
+        if nx < -DPAD_AXIS_THRESHOLD {
+            buttons |= BUTTON_LEFT;
+        }
+        if nx > DPAD_AXIS_THRESHOLD {
+            buttons |= BUTTON_RIGHT;
+        }
+        buttons
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Rect {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+}
+
+impl Rect {
+    fn point(&self, x_fraction: f64, y_fraction: f64) -> (f64, f64) {
+        (self.x + x_fraction * self.width, self.y + y_fraction * self.height)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Layout {
+    system: Option<System>,
+    game_size: FrameSize,
+    orientation: Orientation,
+    canvas_size: FrameSize,
+    game_x: u32,
+    game_y: u32,
+    dpad: Option<Dpad>,
+    buttons: Vec<OverlayButton>,
+}
+
+impl Layout {
+    fn new(system: Option<System>, game_size: FrameSize, orientation: Orientation) -> Self {
+        let game_width = f64::from(game_size.width);
+        let game_height = f64::from(game_size.height);
+
+        // The D-pad is centered in the left panel and the face buttons in the right panel
+        let (canvas_size, game_x, game_y, left_panel, right_panel) = match orientation {
+            Orientation::Portrait => {
+                let panel = Rect { x: 0.0, y: game_height, width: game_width, height: game_height };
+                let canvas_size =
+                    FrameSize { width: game_size.width, height: 2 * game_size.height };
+                let left = Rect { width: panel.width / 2.0, ..panel };
+                let right = Rect { x: panel.width / 2.0, width: panel.width / 2.0, ..panel };
+                (canvas_size, 0, 0, left, right)
+            }
+            Orientation::Landscape => {
+                let panel_width = (0.75 * game_height).round();
+                let canvas_size = FrameSize {
+                    width: game_size.width + 2 * panel_width as u32,
+                    height: game_size.height,
+                };
+                let left = Rect { x: 0.0, y: 0.0, width: panel_width, height: game_height };
+                let right = Rect { x: panel_width + game_width, ..left };
+                (canvas_size, panel_width as u32, 0, left, right)
+            }
+        };
+
+        let scale = 0.8 * f64::min(left_panel.width / 2.0, left_panel.height / 2.0);
+
+        let mut buttons = Vec::new();
+
+        let (load_x, load_y) = left_panel.point(0.12, 0.08);
+        buttons.push(OverlayButton {
+            label: "LD",
+            x: load_x,
+            y: load_y,
+            radius: 0.2 * scale,
+            kind: ButtonKind::Action(OverlayAction::OpenFile),
+        });
+
+        let dpad = system.map(|system| {
+            add_system_buttons(system, scale, left_panel, right_panel, &mut buttons);
+
+            let (dpad_x, dpad_y) = left_panel.point(0.5, 0.5);
+            Dpad { x: dpad_x, y: dpad_y, size: scale }
+        });
+
+        Self { system, game_size, orientation, canvas_size, game_x, game_y, dpad, buttons }
+    }
+}
+
+fn add_system_buttons(
+    system: System,
+    scale: f64,
+    left_panel: Rect,
+    right_panel: Rect,
+    buttons: &mut Vec<OverlayButton>,
+) {
+    let (face_x, face_y) = right_panel.point(0.5, 0.5);
+    let mut face_button = |label, dx: f64, dy: f64, radius: f64, mask| {
+        buttons.push(OverlayButton {
+            label,
+            x: face_x + dx * scale,
+            y: face_y + dy * scale,
+            radius: radius * scale,
+            kind: ButtonKind::Emulated(mask),
+        });
+    };
+
+    match system {
+        System::MasterSystem | System::GameGear => {
+            face_button("1", -0.45, 0.2, 0.3, BUTTON_A);
+            face_button("2", 0.45, -0.2, 0.3, BUTTON_B);
+        }
+        System::Genesis | System::SegaCd => {
+            face_button("A", -0.65, 0.25, 0.28, BUTTON_A);
+            face_button("B", 0.0, 0.25, 0.28, BUTTON_B);
+            face_button("C", 0.65, 0.25, 0.28, BUTTON_C);
+            face_button("X", -0.65, -0.45, 0.2, BUTTON_X);
+            face_button("Y", 0.0, -0.45, 0.2, BUTTON_Y);
+            face_button("Z", 0.65, -0.45, 0.2, BUTTON_Z);
+        }
+        System::Nes | System::GameBoy => {
+            face_button("B", -0.45, 0.2, 0.3, BUTTON_B);
+            face_button("A", 0.45, -0.2, 0.3, BUTTON_A);
+        }
+        System::Snes => {
+            face_button("X", 0.0, -0.55, 0.28, BUTTON_X);
+            face_button("A", 0.55, 0.0, 0.28, BUTTON_A);
+            face_button("B", 0.0, 0.55, 0.28, BUTTON_B);
+            face_button("Y", -0.55, 0.0, 0.28, BUTTON_Y);
+        }
+    }
+
+    let mut panel_button = |label, panel: Rect, x_fraction, y_fraction, mask| {
+        let (x, y) = panel.point(x_fraction, y_fraction);
+        buttons.push(OverlayButton {
+            label,
+            x,
+            y,
+            radius: 0.2 * scale,
+            kind: ButtonKind::Emulated(mask),
+        });
+    };
+
+    if system == System::Snes {
+        panel_button("L", left_panel, 0.5, 0.1, BUTTON_L);
+        panel_button("R", right_panel, 0.5, 0.1, BUTTON_R);
+    }
+
+    // The Master System's pause button is mapped to Start, and Genesis 3-button controllers
+    // have no Select/Mode button
+    panel_button("ST", right_panel, 0.5, 0.92, BUTTON_START);
+    if matches!(system, System::Nes | System::Snes | System::GameBoy) {
+        panel_button("SE", left_panel, 0.5, 0.92, BUTTON_SELECT);
+    }
+}
+
+pub struct TouchOverlay {
+    layout: Layout,
+    canvas: Vec<Color>,
+    // Touch ID to position in canvas coordinates
+    touches: HashMap<u64, (f64, f64)>,
+}
+
+impl TouchOverlay {
+    pub fn new() -> Self {
+        Self {
+            layout: Layout::new(None, FrameSize { width: 256, height: 224 }, Orientation::Portrait),
+            canvas: Vec::new(),
+            touches: HashMap::new(),
+        }
+    }
+
+    /// Handle a touch event, converting from window coordinates to canvas coordinates using the
+    /// renderer's current display area. Returns an action if a touch started on an action button.
+    pub fn handle_touch(
+        &mut self,
+        touch: &Touch,
+        display_info: Option<(FrameSize, DisplayArea)>,
+    ) -> Option<OverlayAction> {
+        let (frame_size, display_area) = display_info?;
+        if display_area.width == 0 || display_area.height == 0 {
+            return None;
+        }
+
+        match touch.phase {
+            TouchPhase::Started | TouchPhase::Moved => {
+                let x = (touch.location.x - f64::from(display_area.x))
+                    * f64::from(frame_size.width)
+                    / f64::from(display_area.width);
+                let y = (touch.location.y - f64::from(display_area.y))
+                    * f64::from(frame_size.height)
+                    / f64::from(display_area.height);
+                self.touches.insert(touch.id, (x, y));
+
+                if touch.phase == TouchPhase::Started {
+                    return self.layout.buttons.iter().find_map(|button| match button.kind {
+                        ButtonKind::Action(action) if button.contains(x, y) => Some(action),
+                        _ => None,
+                    });
+                }
+            }
+            TouchPhase::Ended | TouchPhase::Cancelled => {
+                self.touches.remove(&touch.id);
+            }
+        }
+
+        None
+    }
+
+    /// Release all touches, e.g. when the app loses focus and will not receive touch end events.
+    pub fn clear_touches(&mut self) {
+        self.touches.clear();
+    }
+
+    /// Currently pressed emulated buttons as a bitmask of the `BUTTON_*` constants.
+    pub fn pressed_buttons(&self) -> u32 {
+        self.touches.values().fold(0, |mut pressed, &(x, y)| {
+            if let Some(dpad) = &self.layout.dpad {
+                pressed |= dpad.buttons_at(x, y);
+            }
+            for button in &self.layout.buttons {
+                if let ButtonKind::Emulated(mask) = button.kind {
+                    if button.contains(x, y) {
+                        pressed |= mask;
+                    }
+                }
+            }
+            pressed
+        })
+    }
+
+    /// Draw the game frame and the controls into the composite frame.
+    pub fn compose(
+        &mut self,
+        system: Option<System>,
+        game_frame: &[Color],
+        game_size: FrameSize,
+        orientation: Orientation,
+    ) -> (&[Color], FrameSize) {
+        if self.layout.system != system
+            || self.layout.game_size != game_size
+            || self.layout.orientation != orientation
+        {
+            self.layout = Layout::new(system, game_size, orientation);
+            self.touches.clear();
+        }
+
+        let pressed = self.pressed_buttons();
+
+        let canvas_size = self.layout.canvas_size;
+        self.canvas.clear();
+        self.canvas.resize((canvas_size.width * canvas_size.height) as usize, BACKGROUND_COLOR);
+
+        let mut canvas = Canvas { pixels: &mut self.canvas, size: canvas_size };
+        canvas.blit(game_frame, game_size, self.layout.game_x, self.layout.game_y);
+
+        if let Some(dpad) = &self.layout.dpad {
+            canvas.draw_dpad(dpad, pressed);
+        }
+        for button in &self.layout.buttons {
+            let is_pressed = match button.kind {
+                ButtonKind::Emulated(mask) => pressed & mask != 0,
+                ButtonKind::Action(_) => false,
+            };
+            canvas.draw_button(button, is_pressed);
+        }
+
+        (&self.canvas, canvas_size)
+    }
+}
+
+struct Canvas<'a> {
+    pixels: &'a mut [Color],
+    size: FrameSize,
+}
+
+impl Canvas<'_> {
+    fn blit(&mut self, frame: &[Color], frame_size: FrameSize, x: u32, y: u32) {
+        let frame_width = frame_size.width as usize;
+        if frame_width == 0 {
+            return;
+        }
+
+        for (row, line) in frame.chunks_exact(frame_width).enumerate() {
+            let start = (y as usize + row) * self.size.width as usize + x as usize;
+            self.pixels[start..start + frame_width].copy_from_slice(line);
+        }
+    }
+
+    fn fill(&mut self, x: f64, y: f64, width: f64, height: f64, color: Color) {
+        let x_start = x.round().max(0.0) as u32;
+        let y_start = y.round().max(0.0) as u32;
+        let x_end = ((x + width).round().max(0.0) as u32).min(self.size.width);
+        let y_end = ((y + height).round().max(0.0) as u32).min(self.size.height);
+
+        for py in y_start..y_end {
+            let row = (py * self.size.width) as usize;
+            self.pixels[row + x_start as usize..row + x_end as usize].fill(color);
+        }
+    }
+
+    fn draw_dpad(&mut self, dpad: &Dpad, pressed: u32) {
+        let arm_width = 0.55 * dpad.size;
+        let half_width = arm_width / 2.0;
+        let length = 0.9 * dpad.size;
+
+        // (button, x, y, width, height)
+        let arms = [
+            (BUTTON_UP, dpad.x - half_width, dpad.y - length, arm_width, length),
+            (BUTTON_DOWN, dpad.x - half_width, dpad.y, arm_width, length),
+            (BUTTON_LEFT, dpad.x - length, dpad.y - half_width, length, arm_width),
+            (BUTTON_RIGHT, dpad.x, dpad.y - half_width, length, arm_width),
+        ];
+        for (button, x, y, width, height) in arms {
+            let color = if pressed & button != 0 { PRESSED_COLOR } else { BUTTON_COLOR };
+            self.fill(x, y, width, height, color);
+        }
+        self.fill(dpad.x - half_width, dpad.y - half_width, arm_width, arm_width, BUTTON_COLOR);
+    }
+
+    fn draw_button(&mut self, button: &OverlayButton, pressed: bool) {
+        let color = if pressed { PRESSED_COLOR } else { BUTTON_COLOR };
+
+        let y_start = (button.y - button.radius).floor().max(0.0) as u32;
+        let y_end = ((button.y + button.radius).ceil().max(0.0) as u32).min(self.size.height);
+        let x_start = (button.x - button.radius).floor().max(0.0) as u32;
+        let x_end = ((button.x + button.radius).ceil().max(0.0) as u32).min(self.size.width);
+        for py in y_start..y_end {
+            for px in x_start..x_end {
+                let (dx, dy) = (f64::from(px) + 0.5 - button.x, f64::from(py) + 0.5 - button.y);
+                if dx.hypot(dy) <= button.radius {
+                    self.pixels[(py * self.size.width + px) as usize] = color;
+                }
+            }
+        }
+
+        self.draw_label(button.label, button.x, button.y, (button.radius / 5.0).max(1.0).floor());
+    }
+
+    fn draw_label(&mut self, label: &str, center_x: f64, center_y: f64, scale: f64) {
+        let char_count = label.chars().count() as f64;
+        let width = (4.0 * char_count - 1.0) * scale;
+        let mut x = center_x - width / 2.0;
+        let y = center_y - 2.5 * scale;
+
+        for c in label.chars() {
+            let rows = glyph(c);
+            for (row, bits) in rows.into_iter().enumerate() {
+                for col in 0..3 {
+                    if bits & (0b100 >> col) != 0 {
+                        self.fill(
+                            x + f64::from(col) * scale,
+                            y + row as f64 * scale,
+                            scale,
+                            scale,
+                            LABEL_COLOR,
+                        );
+                    }
+                }
+            }
+            x += 4.0 * scale;
+        }
+    }
+}
+
+// 3x5 pixel font covering the characters used in button labels
+fn glyph(c: char) -> [u8; 5] {
+    match c {
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b110, 0b001, 0b010, 0b100, 0b111],
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        _ => [0; 5],
+    }
+}
@@ -0,0 +1,107 @@
+/*
+ * C interface to the jgenesis emulation cores.
+ *
+ * Link against the jgenesis_capi static or shared library built from frontend/jgenesis-capi.
+ * Functions that can fail return NULL or a negative value on failure; call jgenesis_last_error()
+ * for a description of the error.
+ */
+
+#ifndef JGENESIS_H
+#define JGENESIS_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define JGENESIS_SYSTEM_MASTER_SYSTEM 0
+#define JGENESIS_SYSTEM_GAME_GEAR 1
+#define JGENESIS_SYSTEM_GENESIS 2
+#define JGENESIS_SYSTEM_SEGA_CD 3
+#define JGENESIS_SYSTEM_NES 4
+#define JGENESIS_SYSTEM_SNES 5
+#define JGENESIS_SYSTEM_GAME_BOY 6
+
+/* Buttons that don't exist on a system's controller are ignored. Master System / Game Gear use A
+ * and B for buttons 1 and 2, and Start for Pause / Start. Genesis uses Select for Mode. */
+#define JGENESIS_BUTTON_UP (1u << 0)
+#define JGENESIS_BUTTON_DOWN (1u << 1)
+#define JGENESIS_BUTTON_LEFT (1u << 2)
+#define JGENESIS_BUTTON_RIGHT (1u << 3)
+#define JGENESIS_BUTTON_A (1u << 4)
+#define JGENESIS_BUTTON_B (1u << 5)
+#define JGENESIS_BUTTON_C (1u << 6)
+#define JGENESIS_BUTTON_X (1u << 7)
+#define JGENESIS_BUTTON_Y (1u << 8)
+#define JGENESIS_BUTTON_Z (1u << 9)
+#define JGENESIS_BUTTON_L (1u << 10)
+#define JGENESIS_BUTTON_R (1u << 11)
+#define JGENESIS_BUTTON_START (1u << 12)
+#define JGENESIS_BUTTON_SELECT (1u << 13)
+
+#define JGENESIS_AUDIO_SAMPLE_RATE 48000
+
+typedef struct Emulator JgenesisEmulator;
+
+/* Byte buffer allocated by the library; free with jgenesis_free_buffer(). data is NULL if empty. */
+typedef struct {
+    uint8_t *data;
+    size_t len;
+} JgenesisBuffer;
+
+/* Receives RGBA8 pixels in row-major order. The buffer is only valid during the callback. */
+typedef void (*JgenesisVideoCallback)(void *userdata, const uint8_t *pixels, uint32_t width, uint32_t height);
+
+/* Receives interleaved stereo samples at JGENESIS_AUDIO_SAMPLE_RATE. The buffer is only valid
+ * during the callback. */
+typedef void (*JgenesisAudioCallback)(void *userdata, const float *samples, size_t frames);
+
+const char *jgenesis_last_error(void);
+
+/* sram may be NULL if there is no existing save file. */
+JgenesisEmulator *jgenesis_create(uint32_t system, const uint8_t *rom, size_t rom_len,
+                                  const uint8_t *sram, size_t sram_len);
+
+/* disc_path must point to a .cue or .chd file. backup_ram may be NULL. */
+JgenesisEmulator *jgenesis_create_sega_cd(const uint8_t *bios, size_t bios_len, const char *disc_path,
+                                          const uint8_t *backup_ram, size_t backup_ram_len);
+
+void jgenesis_destroy(JgenesisEmulator *emulator);
+
+/* player is 0-3; players beyond what the system supports are ignored. */
+void jgenesis_set_buttons(JgenesisEmulator *emulator, uint32_t player, uint32_t buttons);
+
+/* Either callback may be NULL. Returns 0 on success, -1 on failure. */
+int jgenesis_run_frame(JgenesisEmulator *emulator, JgenesisVideoCallback video_callback,
+                       JgenesisAudioCallback audio_callback, void *userdata);
+
+/* Returns 0 if the frame should be stretched to fill the display. */
+double jgenesis_pixel_aspect_ratio(const JgenesisEmulator *emulator);
+
+void jgenesis_soft_reset(JgenesisEmulator *emulator);
+
+void jgenesis_hard_reset(JgenesisEmulator *emulator);
+
+/* Memory access on the main CPU's address bus; only supported for Genesis and SNES. Return 0 on
+ * success, -1 on failure. */
+int jgenesis_read_memory(JgenesisEmulator *emulator, uint32_t address, uint8_t *out, size_t len);
+
+int jgenesis_write_memory(JgenesisEmulator *emulator, uint32_t address, const uint8_t *data, size_t len);
+
+JgenesisBuffer jgenesis_save_state(const JgenesisEmulator *emulator);
+
+/* Returns 0 on success, -1 on failure. The emulator is unchanged on failure. */
+int jgenesis_load_state(JgenesisEmulator *emulator, const uint8_t *state, size_t state_len);
+
+/* Battery-backed save file (or Sega CD backup RAM) as of the last time the game wrote it. */
+JgenesisBuffer jgenesis_save_file(const JgenesisEmulator *emulator);
+
+void jgenesis_free_buffer(JgenesisBuffer buffer);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* JGENESIS_H */
@@ -0,0 +1,118 @@
+//! Default emulator configs for embedded use
+//!
+//! Embedding applications handle aspect ratio and audio sync themselves, so these configs use square
+//! pixels and disable the 60Hz audio timing hacks. Everything else matches the native frontend's
+//! defaults.
+
+use gb_core::{GameBoyEmulatorConfig, GbAspectRatio, GbPalette, GbcColorCorrection};
+use genesis_core::{
+    GenesisAspectRatio, GenesisControllerType, GenesisEmulatorConfig, GenesisMultitap,
+    GenesisRegionSpoof, GenesisSerialPort,
+};
+use jgenesis_common::audio::ResamplerQuality;
+use jgenesis_common::frontend::PixelAspectRatio;
+use nes_core::{NesAspectRatio, NesEmulatorConfig, Overscan};
+use segacd_core::SegaCdEmulatorConfig;
+use smsgg_core::psg::PsgVersion;
+use smsgg_core::{SmsControllerType, SmsGgEmulatorConfig, SmsRegion, VdpVersion};
+use snes_core::{SnesAspectRatio, SnesEmulatorConfig, SnesEnhancements};
+use std::num::NonZeroU64;
+
+pub(crate) fn smsgg(vdp_version: VdpVersion) -> SmsGgEmulatorConfig {
+    let psg_version = if vdp_version.is_master_system() {
+        PsgVersion::MasterSystem2
+    } else {
+        PsgVersion::Standard
+    };
+
+    SmsGgEmulatorConfig {
+        vdp_version,
+        psg_version,
+        pixel_aspect_ratio: Some(PixelAspectRatio::SQUARE),
+        remove_sprite_limit: false,
+        sms_region: SmsRegion::default(),
+        p1_controller_type: SmsControllerType::default(),
+        sms_crop_vertical_border: false,
+        sms_crop_left_border: false,
+        fm_sound_unit_enabled: true,
+        overclock_z80: false,
+        audio_resampler_quality: ResamplerQuality::default(),
+        initial_ram_state: None,
+        rng_seed: None,
+    }
+}
+
+pub(crate) fn genesis() -> GenesisEmulatorConfig {
+    GenesisEmulatorConfig {
+        p1_controller_type: GenesisControllerType::default(),
+        p2_controller_type: GenesisControllerType::default(),
+        multitap: GenesisMultitap::default(),
+        serial_port: GenesisSerialPort::default(),
+        forced_timing_mode: None,
+        forced_region: None,
+        region_spoof: GenesisRegionSpoof::default(),
+        report_tmss: false,
+        aspect_ratio: GenesisAspectRatio::SquarePixels,
+        adjust_aspect_ratio_in_2x_resolution: true,
+        remove_sprite_limits: false,
+        emulate_non_linear_vdp_dac: false,
+        render_vertical_border: false,
+        render_horizontal_border: false,
+        widescreen: false,
+        hi_res_output: false,
+        expanded_vram: false,
+        quantize_ym2612_output: true,
+        audio_resampler_quality: ResamplerQuality::default(),
+        initial_ram_state: None,
+        rng_seed: None,
+    }
+}
+
+pub(crate) fn sega_cd() -> SegaCdEmulatorConfig {
+    SegaCdEmulatorConfig { genesis: genesis(), enable_ram_cartridge: true, fast_boot: false }
+}
+
+pub(crate) fn nes() -> NesEmulatorConfig {
+    NesEmulatorConfig {
+        forced_timing_mode: None,
+        aspect_ratio: NesAspectRatio::SquarePixels,
+        overscan: Overscan::default(),
+        emulate_ntsc_overscan: true,
+        remove_sprite_limit: false,
+        pal_black_border: false,
+        silence_ultrasonic_triangle_output: false,
+        audio_refresh_rate_adjustment: false,
+        allow_opposing_joypad_inputs: false,
+        forced_expansion_device: None,
+        audio_resampler_quality: ResamplerQuality::default(),
+        initial_ram_state: None,
+        rng_seed: None,
+    }
+}
+
+pub(crate) fn snes() -> SnesEmulatorConfig {
+    SnesEmulatorConfig {
+        forced_timing_mode: None,
+        aspect_ratio: SnesAspectRatio::SquarePixels,
+        audio_60hz_hack: false,
+        gsu_overclock_factor: NonZeroU64::new(1).unwrap(),
+        audio_resampler_quality: ResamplerQuality::default(),
+        initial_ram_state: None,
+        rng_seed: None,
+        enhancements: SnesEnhancements::default(),
+    }
+}
+
+pub(crate) fn game_boy() -> GameBoyEmulatorConfig {
+    GameBoyEmulatorConfig {
+        force_dmg_mode: false,
+        pretend_to_be_gba: false,
+        aspect_ratio: GbAspectRatio::SquarePixels,
+        gb_palette: GbPalette::default(),
+        gbc_color_correction: GbcColorCorrection::default(),
+        audio_60hz_hack: false,
+        audio_resampler_quality: ResamplerQuality::default(),
+        initial_ram_state: None,
+        rng_seed: None,
+    }
+}
@@ -0,0 +1,569 @@
+//! Emulator handle that wraps every supported core behind one type, so that the C API does not need
+//! a separate set of functions per core
+
+use crate::config;
+use bincode::{Decode, Encode};
+use gb_core::{GameBoyEmulator, GameBoyInputs, GameBoyLoadError};
+use genesis_core::{GenesisEmulator, GenesisInputs, GenesisJoypadState};
+use jgenesis_common::frontend::{
+    AudioOutput, Color, EmulatorTrait, FrameSize, PixelAspectRatio, Renderer, SaveWriter,
+    TickEffect,
+};
+use jgenesis_common::debug::Debuggable;
+use jgenesis_common::state;
+use jgenesis_common::state::StateError;
+use nes_core::{NesEmulator, NesInitializationError, NesInputs, NesJoypadState};
+use segacd_core::{CdRomFileFormat, SegaCdEmulator, SegaCdLoadError};
+use smsgg_core::{SmsGgEmulator, SmsGgInputs, SmsGgJoypadState, VdpVersion};
+use snes_core::{
+    CoprocessorRoms, SnesEmulator, SnesInputDevice, SnesInputs, SnesJoypadState, SnesLoadError,
+};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::path::Path;
+use thiserror::Error;
+
+pub const MAX_PLAYERS: usize = 4;
+
+pub const BUTTON_UP: u32 = 1 << 0;
+pub const BUTTON_DOWN: u32 = 1 << 1;
+pub const BUTTON_LEFT: u32 = 1 << 2;
+pub const BUTTON_RIGHT: u32 = 1 << 3;
+pub const BUTTON_A: u32 = 1 << 4;
+pub const BUTTON_B: u32 = 1 << 5;
+pub const BUTTON_C: u32 = 1 << 6;
+pub const BUTTON_X: u32 = 1 << 7;
+pub const BUTTON_Y: u32 = 1 << 8;
+pub const BUTTON_Z: u32 = 1 << 9;
+pub const BUTTON_L: u32 = 1 << 10;
+pub const BUTTON_R: u32 = 1 << 11;
+pub const BUTTON_START: u32 = 1 << 12;
+pub const BUTTON_SELECT: u32 = 1 << 13;
+
+/// Audio sample rate of every core, in Hz
+pub const AUDIO_SAMPLE_RATE: u32 = jgenesis_common::audio::OUTPUT_FREQUENCY as u32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum System {
+    MasterSystem,
+    GameGear,
+    Genesis,
+    SegaCd,
+    Nes,
+    Snes,
+    GameBoy,
+}
+
+impl TryFrom<u32> for System {
+    type Error = EmulatorError;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::MasterSystem),
+            1 => Ok(Self::GameGear),
+            2 => Ok(Self::Genesis),
+            3 => Ok(Self::SegaCd),
+            4 => Ok(Self::Nes),
+            5 => Ok(Self::Snes),
+            6 => Ok(Self::GameBoy),
+            _ => Err(EmulatorError::InvalidSystem(value)),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum EmulatorError {
+    #[error("Invalid system id: {0}")]
+    InvalidSystem(u32),
+    #[error("Sega CD emulation requires a BIOS and a disc image; use the Sega CD create function")]
+    SegaCdRequiresDisc,
+    #[error("Unrecognized disc image format, expected .cue or .chd: {0}")]
+    UnknownDiscFormat(String),
+    #[error("Error loading NES ROM: {0}")]
+    NesLoad(#[from] NesInitializationError),
+    #[error("Error loading SNES ROM: {0}")]
+    SnesLoad(#[from] SnesLoadError),
+    #[error("Error loading Game Boy ROM: {0}")]
+    GameBoyLoad(#[from] GameBoyLoadError),
+    #[error("Error loading Sega CD disc: {0}")]
+    SegaCdLoad(#[from] SegaCdLoadError),
+    #[error("Emulation error: {0}")]
+    Emulation(String),
+    #[error("{0}")]
+    State(#[from] StateError),
+    #[error("Memory access is not supported for this system")]
+    MemoryAccessUnsupported,
+}
+
+/// Save writer that keeps save files in memory. Embedding applications provide the initial save file
+/// when creating the emulator and read back changes through [`Emulator::save_file`].
+#[derive(Debug, Default)]
+struct MemorySaveWriter {
+    files: HashMap<String, Vec<u8>>,
+}
+
+impl SaveWriter for MemorySaveWriter {
+    type Err = String;
+
+    fn load_bytes(&mut self, extension: &str) -> Result<Vec<u8>, Self::Err> {
+        self.files
+            .get(extension)
+            .cloned()
+            .ok_or_else(|| format!("No save file with extension {extension}"))
+    }
+
+    fn persist_bytes(&mut self, extension: &str, bytes: &[u8]) -> Result<(), Self::Err> {
+        self.files.insert(extension.into(), bytes.to_vec());
+        Ok(())
+    }
+
+    fn load_serialized<D: Decode>(&mut self, extension: &str) -> Result<D, Self::Err> {
+        let bytes = self.load_bytes(extension)?;
+        let (value, _) =
+            bincode::decode_from_slice(&bytes, bincode::config::standard()).map_err(|err| {
+                format!("Error deserializing save file with extension {extension}: {err}")
+            })?;
+        Ok(value)
+    }
+
+    fn persist_serialized<E: Encode>(&mut self, extension: &str, data: E) -> Result<(), Self::Err> {
+        let bytes = bincode::encode_to_vec(data, bincode::config::standard()).map_err(|err| {
+            format!("Error serializing save file with extension {extension}: {err}")
+        })?;
+        self.files.insert(extension.into(), bytes);
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct FrameCapture {
+    frame_buffer: Vec<Color>,
+    frame_size: FrameSize,
+    pixel_aspect_ratio: Option<PixelAspectRatio>,
+}
+
+impl Renderer for FrameCapture {
+    type Err = Infallible;
+
+    fn render_frame(
+        &mut self,
+        frame_buffer: &[Color],
+        frame_size: FrameSize,
+        pixel_aspect_ratio: Option<PixelAspectRatio>,
+    ) -> Result<(), Self::Err> {
+        let frame_len = (frame_size.width * frame_size.height) as usize;
+        self.frame_buffer.clear();
+        self.frame_buffer.extend_from_slice(&frame_buffer[..frame_len]);
+        self.frame_size = frame_size;
+        self.pixel_aspect_ratio = pixel_aspect_ratio;
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+struct AudioCapture {
+    samples: Vec<f32>,
+}
+
+impl AudioOutput for AudioCapture {
+    type Err = Infallible;
+
+    fn push_sample(&mut self, sample_l: f64, sample_r: f64) -> Result<(), Self::Err> {
+        self.samples.push(sample_l as f32);
+        self.samples.push(sample_r as f32);
+        Ok(())
+    }
+}
+
+fn pressed(buttons: u32, button: u32) -> bool {
+    buttons & button != 0
+}
+
+trait ButtonInputs: EmulatorTrait {
+    fn inputs_from_buttons(buttons: &[u32; MAX_PLAYERS]) -> Self::Inputs;
+}
+
+fn smsgg_joypad(buttons: u32) -> SmsGgJoypadState {
+    SmsGgJoypadState {
+        up: pressed(buttons, BUTTON_UP),
+        left: pressed(buttons, BUTTON_LEFT),
+        right: pressed(buttons, BUTTON_RIGHT),
+        down: pressed(buttons, BUTTON_DOWN),
+        button_1: pressed(buttons, BUTTON_A),
+        button_2: pressed(buttons, BUTTON_B),
+    }
+}
+
+impl ButtonInputs for SmsGgEmulator {
+    fn inputs_from_buttons(buttons: &[u32; MAX_PLAYERS]) -> Self::Inputs {
+        SmsGgInputs {
+            p1: smsgg_joypad(buttons[0]),
+            p2: smsgg_joypad(buttons[1]),
+            // Pause on Master System, Start on Game Gear
+            pause: pressed(buttons[0], BUTTON_START),
+            ..SmsGgInputs::default()
+        }
+    }
+}
+
+fn genesis_inputs(buttons: &[u32; MAX_PLAYERS]) -> GenesisInputs {
+    let joypad = |buttons: u32| GenesisJoypadState {
+        up: pressed(buttons, BUTTON_UP),
+        left: pressed(buttons, BUTTON_LEFT),
+        right: pressed(buttons, BUTTON_RIGHT),
+        down: pressed(buttons, BUTTON_DOWN),
+        a: pressed(buttons, BUTTON_A),
+        b: pressed(buttons, BUTTON_B),
+        c: pressed(buttons, BUTTON_C),
+        x: pressed(buttons, BUTTON_X),
+        y: pressed(buttons, BUTTON_Y),
+        z: pressed(buttons, BUTTON_Z),
+        start: pressed(buttons, BUTTON_START),
+        mode: pressed(buttons, BUTTON_SELECT),
+    };
+
+    GenesisInputs {
+        p1: joypad(buttons[0]),
+        p2: joypad(buttons[1]),
+        p3: joypad(buttons[2]),
+        p4: joypad(buttons[3]),
+    }
+}
+
+impl ButtonInputs for GenesisEmulator {
+    fn inputs_from_buttons(buttons: &[u32; MAX_PLAYERS]) -> Self::Inputs {
+        genesis_inputs(buttons)
+    }
+}
+
+impl ButtonInputs for SegaCdEmulator {
+    fn inputs_from_buttons(buttons: &[u32; MAX_PLAYERS]) -> Self::Inputs {
+        genesis_inputs(buttons)
+    }
+}
+
+impl ButtonInputs for NesEmulator {
+    fn inputs_from_buttons(buttons: &[u32; MAX_PLAYERS]) -> Self::Inputs {
+        let joypad = |buttons: u32| NesJoypadState {
+            up: pressed(buttons, BUTTON_UP),
+            down: pressed(buttons, BUTTON_DOWN),
+            left: pressed(buttons, BUTTON_LEFT),
+            right: pressed(buttons, BUTTON_RIGHT),
+            a: pressed(buttons, BUTTON_A),
+            b: pressed(buttons, BUTTON_B),
+            start: pressed(buttons, BUTTON_START),
+            select: pressed(buttons, BUTTON_SELECT),
+        };
+
+        NesInputs { p1: joypad(buttons[0]), p2: joypad(buttons[1]), ..NesInputs::default() }
+    }
+}
+
+impl ButtonInputs for SnesEmulator {
+    fn inputs_from_buttons(buttons: &[u32; MAX_PLAYERS]) -> Self::Inputs {
+        let joypad = |buttons: u32| SnesJoypadState {
+            up: pressed(buttons, BUTTON_UP),
+            left: pressed(buttons, BUTTON_LEFT),
+            right: pressed(buttons, BUTTON_RIGHT),
+            down: pressed(buttons, BUTTON_DOWN),
+            a: pressed(buttons, BUTTON_A),
+            b: pressed(buttons, BUTTON_B),
+            x: pressed(buttons, BUTTON_X),
+            y: pressed(buttons, BUTTON_Y),
+            l: pressed(buttons, BUTTON_L),
+            r: pressed(buttons, BUTTON_R),
+            start: pressed(buttons, BUTTON_START),
+            select: pressed(buttons, BUTTON_SELECT),
+        };
+
+        SnesInputs { p1: joypad(buttons[0]), p2: SnesInputDevice::Controller(joypad(buttons[1])) }
+    }
+}
+
+impl ButtonInputs for GameBoyEmulator {
+    fn inputs_from_buttons(buttons: &[u32; MAX_PLAYERS]) -> Self::Inputs {
+        let buttons = buttons[0];
+        GameBoyInputs {
+            up: pressed(buttons, BUTTON_UP),
+            left: pressed(buttons, BUTTON_LEFT),
+            right: pressed(buttons, BUTTON_RIGHT),
+            down: pressed(buttons, BUTTON_DOWN),
+            a: pressed(buttons, BUTTON_A),
+            b: pressed(buttons, BUTTON_B),
+            start: pressed(buttons, BUTTON_START),
+            select: pressed(buttons, BUTTON_SELECT),
+        }
+    }
+}
+
+struct Core<E: EmulatorTrait> {
+    emulator: E,
+    config: E::Config,
+}
+
+impl<E: ButtonInputs> Core<E> {
+    fn new(emulator: E, config: E::Config) -> Self {
+        Self { emulator, config }
+    }
+
+    fn run_frame(
+        &mut self,
+        buttons: &[u32; MAX_PLAYERS],
+        frame: &mut FrameCapture,
+        audio: &mut AudioCapture,
+        save_writer: &mut MemorySaveWriter,
+    ) -> Result<(), EmulatorError> {
+        let inputs = E::inputs_from_buttons(buttons);
+        loop {
+            match self.emulator.tick(frame, audio, &inputs) {
+                Ok(TickEffect::FrameRendered) => {
+                    // Writing to the in-memory save writer is cheap, so keep it current every frame
+                    return self
+                        .emulator
+                        .persist_save(save_writer)
+                        .map_err(EmulatorError::Emulation);
+                }
+                Ok(TickEffect::None) => {}
+                Err(err) => return Err(EmulatorError::Emulation(err.to_string())),
+            }
+        }
+    }
+
+    fn load_state(&mut self, bytes: &[u8]) -> Result<(), EmulatorError> {
+        state::load_state(&mut self.emulator, &self.config, bytes)?;
+        Ok(())
+    }
+}
+
+enum CoreKind {
+    SmsGg(Box<Core<SmsGgEmulator>>),
+    Genesis(Box<Core<GenesisEmulator>>),
+    SegaCd(Box<Core<SegaCdEmulator>>),
+    Nes(Box<Core<NesEmulator>>),
+    Snes(Box<Core<SnesEmulator>>),
+    GameBoy(Box<Core<GameBoyEmulator>>),
+}
+
+macro_rules! with_core {
+    ($core_kind:expr, $core:ident => $body:expr) => {
+        match $core_kind {
+            CoreKind::SmsGg($core) => $body,
+            CoreKind::Genesis($core) => $body,
+            CoreKind::SegaCd($core) => $body,
+            CoreKind::Nes($core) => $body,
+            CoreKind::Snes($core) => $body,
+            CoreKind::GameBoy($core) => $body,
+        }
+    };
+}
+
+/// A running emulator for any of the supported systems, plus the most recently rendered frame and
+/// the audio samples generated during the most recent frame.
+pub struct Emulator {
+    core: CoreKind,
+    buttons: [u32; MAX_PLAYERS],
+    save_writer: MemorySaveWriter,
+    frame: FrameCapture,
+    audio: AudioCapture,
+}
+
+impl Emulator {
+    fn new(core: CoreKind, save_writer: MemorySaveWriter) -> Self {
+        Self {
+            core,
+            buttons: [0; MAX_PLAYERS],
+            save_writer,
+            frame: FrameCapture {
+                frame_buffer: Vec::new(),
+                frame_size: FrameSize { width: 0, height: 0 },
+                pixel_aspect_ratio: None,
+            },
+            audio: AudioCapture::default(),
+        }
+    }
+
+    /// Create an emulator for a cartridge-based system.
+    ///
+    /// `sram` is the cartridge's battery-backed save file, if any.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the ROM cannot be loaded, or if `system` is
+    /// [`System::SegaCd`].
+    pub fn create(
+        system: System,
+        rom: Vec<u8>,
+        sram: Option<Vec<u8>>,
+    ) -> Result<Self, EmulatorError> {
+        let mut save_writer = MemorySaveWriter::default();
+        if let Some(sram) = sram {
+            save_writer.files.insert("sav".into(), sram);
+        }
+
+        let core = match system {
+            System::MasterSystem | System::GameGear => {
+                let vdp_version = if system == System::GameGear {
+                    VdpVersion::GameGear
+                } else {
+                    VdpVersion::NtscMasterSystem2
+                };
+                let config = config::smsgg(vdp_version);
+                let emulator = SmsGgEmulator::create(rom, config, &mut save_writer);
+                CoreKind::SmsGg(Box::new(Core::new(emulator, config)))
+            }
+            System::Genesis => {
+                let config = config::genesis();
+                let emulator = GenesisEmulator::create(rom, config, &mut save_writer);
+                CoreKind::Genesis(Box::new(Core::new(emulator, config)))
+            }
+            System::SegaCd => return Err(EmulatorError::SegaCdRequiresDisc),
+            System::Nes => {
+                let config = config::nes();
+                let emulator = NesEmulator::create(rom, config, &mut save_writer)?;
+                CoreKind::Nes(Box::new(Core::new(emulator, config)))
+            }
+            System::Snes => {
+                let config = config::snes();
+                let emulator =
+                    SnesEmulator::create(rom, config, CoprocessorRoms::none(), &mut save_writer)?;
+                CoreKind::Snes(Box::new(Core::new(emulator, config)))
+            }
+            System::GameBoy => {
+                let config = config::game_boy();
+                let emulator = GameBoyEmulator::create(rom, config, &mut save_writer)?;
+                CoreKind::GameBoy(Box::new(Core::new(emulator, config)))
+            }
+        };
+
+        Ok(Self::new(core, save_writer))
+    }
+
+    /// Create a Sega CD emulator from a BIOS ROM and a CUE or CHD disc image.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the BIOS is invalid or the disc image cannot be opened.
+    pub fn create_sega_cd(
+        bios: Vec<u8>,
+        disc_path: &Path,
+        backup_ram: Option<Vec<u8>>,
+    ) -> Result<Self, EmulatorError> {
+        let format = CdRomFileFormat::from_file_path(disc_path)
+            .ok_or_else(|| EmulatorError::UnknownDiscFormat(disc_path.display().to_string()))?;
+
+        let mut save_writer = MemorySaveWriter::default();
+        if let Some(backup_ram) = backup_ram {
+            save_writer.files.insert("sav".into(), backup_ram);
+        }
+
+        let config = config::sega_cd();
+        let emulator =
+            SegaCdEmulator::create(bios, disc_path, format, false, config, &mut save_writer)?;
+
+        Ok(Self::new(CoreKind::SegaCd(Box::new(Core::new(emulator, config))), save_writer))
+    }
+
+    /// Set the pressed buttons for the given player, as a bitmask of the `BUTTON_*` constants.
+    /// Players past the number supported by the current system are ignored.
+    pub fn set_buttons(&mut self, player: usize, buttons: u32) {
+        if let Some(player_buttons) = self.buttons.get_mut(player) {
+            *player_buttons = buttons;
+        }
+    }
+
+    /// Run the emulator until it renders the next frame.
+    ///
+    /// # Errors
+    ///
+    /// This method will propagate any error returned by the core.
+    pub fn run_frame(&mut self) -> Result<(), EmulatorError> {
+        self.audio.samples.clear();
+        with_core!(&mut self.core, core => core.run_frame(&self.buttons, &mut self.frame, &mut self.audio, &mut self.save_writer))
+    }
+
+    /// The most recently rendered frame as RGBA8 pixels in row-major order, along with its size.
+    #[must_use]
+    pub fn frame(&self) -> (&[u8], FrameSize) {
+        (bytemuck::cast_slice(&self.frame.frame_buffer), self.frame.frame_size)
+    }
+
+    /// Pixel aspect ratio of the most recently rendered frame, or `None` if the frame should be
+    /// stretched to fill the display.
+    #[must_use]
+    pub fn pixel_aspect_ratio(&self) -> Option<f64> {
+        self.frame.pixel_aspect_ratio.map(f64::from)
+    }
+
+    /// Interleaved stereo audio samples generated during the most recent frame, at
+    /// [`AUDIO_SAMPLE_RATE`].
+    #[must_use]
+    pub fn audio_samples(&self) -> &[f32] {
+        &self.audio.samples
+    }
+
+    pub fn soft_reset(&mut self) {
+        with_core!(&mut self.core, core => core.emulator.soft_reset());
+    }
+
+    pub fn hard_reset(&mut self) {
+        with_core!(&mut self.core, core => core.emulator.hard_reset(&mut self.save_writer));
+    }
+
+    /// # Errors
+    ///
+    /// This method will return an error if the emulator state cannot be serialized.
+    pub fn save_state(&self) -> Result<Vec<u8>, EmulatorError> {
+        Ok(with_core!(&self.core, core => state::save_state(&core.emulator))?)
+    }
+
+    /// # Errors
+    ///
+    /// This method will return an error if the state is invalid or is for a different system. The
+    /// emulator is unchanged on error.
+    pub fn load_state(&mut self, bytes: &[u8]) -> Result<(), EmulatorError> {
+        with_core!(&mut self.core, core => core.load_state(bytes))
+    }
+
+    fn debuggable(&mut self) -> Result<&mut dyn Debuggable, EmulatorError> {
+        match &mut self.core {
+            CoreKind::Genesis(core) => Ok(&mut core.emulator),
+            CoreKind::Snes(core) => Ok(&mut core.emulator),
+            _ => Err(EmulatorError::MemoryAccessUnsupported),
+        }
+    }
+
+    /// Read memory starting at the given address on the main CPU's address bus. Reads do not have
+    /// side effects.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the current system does not support memory access.
+    pub fn read_memory(&mut self, address: u32, out: &mut [u8]) -> Result<(), EmulatorError> {
+        let debuggable = self.debuggable()?;
+        for (i, byte) in out.iter_mut().enumerate() {
+            *byte = debuggable.peek_memory(address.wrapping_add(i as u32));
+        }
+        Ok(())
+    }
+
+    /// Write memory starting at the given address on the main CPU's address bus.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the current system does not support memory access.
+    pub fn write_memory(&mut self, address: u32, bytes: &[u8]) -> Result<(), EmulatorError> {
+        let debuggable = self.debuggable()?;
+        for (i, &byte) in bytes.iter().enumerate() {
+            debuggable.poke_memory(address.wrapping_add(i as u32), byte);
+        }
+        Ok(())
+    }
+
+    /// The most recently persisted save file with the given extension, e.g. `sav` for cartridge SRAM
+    /// or Sega CD backup RAM.
+    #[must_use]
+    pub fn save_file(&self, extension: &str) -> Option<&[u8]> {
+        self.save_writer.files.get(extension).map(Vec::as_slice)
+    }
+}
@@ -0,0 +1,325 @@
+//! C bindings for the emulation cores, for embedding jgenesis in non-Rust applications
+//!
+//! The matching C declarations are in `include/jgenesis.h`. Every function that can fail returns a
+//! null pointer or a negative value on failure, and [`jgenesis_last_error`] returns a description of
+//! the most recent failure on the calling thread.
+
+mod config;
+pub mod emulator;
+
+use crate::emulator::{Emulator, System};
+use std::cell::RefCell;
+use std::ffi::{c_char, c_int, c_void, CStr, CString};
+use std::path::Path;
+use std::{ptr, slice};
+
+pub type VideoCallback =
+    Option<unsafe extern "C" fn(userdata: *mut c_void, pixels: *const u8, width: u32, height: u32)>;
+
+pub type AudioCallback =
+    Option<unsafe extern "C" fn(userdata: *mut c_void, samples: *const f32, frames: usize)>;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(err: impl ToString) {
+    let message = CString::new(err.to_string().replace('\0', " "))
+        .expect("all nul bytes should have been replaced");
+    LAST_ERROR.with(|last_error| *last_error.borrow_mut() = Some(message));
+}
+
+unsafe fn bytes_from_raw(data: *const u8, len: usize) -> Vec<u8> {
+    if data.is_null() || len == 0 { Vec::new() } else { slice::from_raw_parts(data, len).to_vec() }
+}
+
+unsafe fn optional_bytes_from_raw(data: *const u8, len: usize) -> Option<Vec<u8>> {
+    (!data.is_null()).then(|| bytes_from_raw(data, len))
+}
+
+/// Returns a description of the most recent error on the calling thread, or null if no error has
+/// occurred. The string is valid until the next failing call on the same thread.
+#[no_mangle]
+pub extern "C" fn jgenesis_last_error() -> *const c_char {
+    LAST_ERROR.with(|last_error| last_error.borrow().as_ref().map_or(ptr::null(), |s| s.as_ptr()))
+}
+
+/// Create an emulator for a cartridge-based system. Returns null on failure.
+///
+/// # Safety
+///
+/// `rom` must point to `rom_len` readable bytes. `sram` must either be null or point to `sram_len`
+/// readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn jgenesis_create(
+    system: u32,
+    rom: *const u8,
+    rom_len: usize,
+    sram: *const u8,
+    sram_len: usize,
+) -> *mut Emulator {
+    let result = System::try_from(system).and_then(|system| {
+        Emulator::create(
+            system,
+            bytes_from_raw(rom, rom_len),
+            optional_bytes_from_raw(sram, sram_len),
+        )
+    });
+
+    match result {
+        Ok(emulator) => Box::into_raw(Box::new(emulator)),
+        Err(err) => {
+            set_last_error(err);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Create a Sega CD emulator from a BIOS ROM and the path to a CUE or CHD disc image. Returns null
+/// on failure.
+///
+/// # Safety
+///
+/// `bios` must point to `bios_len` readable bytes. `disc_path` must be a valid nul-terminated UTF-8
+/// string. `backup_ram` must either be null or point to `backup_ram_len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn jgenesis_create_sega_cd(
+    bios: *const u8,
+    bios_len: usize,
+    disc_path: *const c_char,
+    backup_ram: *const u8,
+    backup_ram_len: usize,
+) -> *mut Emulator {
+    if disc_path.is_null() {
+        set_last_error("Disc path is null");
+        return ptr::null_mut();
+    }
+
+    let disc_path = match CStr::from_ptr(disc_path).to_str() {
+        Ok(disc_path) => disc_path,
+        Err(err) => {
+            set_last_error(format!("Disc path is not valid UTF-8: {err}"));
+            return ptr::null_mut();
+        }
+    };
+
+    match Emulator::create_sega_cd(
+        bytes_from_raw(bios, bios_len),
+        Path::new(disc_path),
+        optional_bytes_from_raw(backup_ram, backup_ram_len),
+    ) {
+        Ok(emulator) => Box::into_raw(Box::new(emulator)),
+        Err(err) => {
+            set_last_error(err);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Destroy an emulator. Does nothing if `emulator` is null.
+///
+/// # Safety
+///
+/// `emulator` must be null or a pointer returned by one of the create functions that has not
+/// already been destroyed.
+#[no_mangle]
+pub unsafe extern "C" fn jgenesis_destroy(emulator: *mut Emulator) {
+    if !emulator.is_null() {
+        drop(Box::from_raw(emulator));
+    }
+}
+
+/// Set the pressed buttons for a player (0-3) as a bitmask of the `JGENESIS_BUTTON_*` constants.
+///
+/// # Safety
+///
+/// `emulator` must be a valid emulator pointer.
+#[no_mangle]
+pub unsafe extern "C" fn jgenesis_set_buttons(emulator: *mut Emulator, player: u32, buttons: u32) {
+    (*emulator).set_buttons(player as usize, buttons);
+}
+
+/// Run the emulator until it renders the next frame, then pass the frame and the audio generated
+/// during it to the callbacks. Either callback may be null. Returns 0 on success and -1 on failure.
+///
+/// The video callback receives RGBA8 pixels in row-major order, and the audio callback receives
+/// interleaved stereo samples at 48000 Hz. Both buffers are only valid for the duration of the
+/// callback.
+///
+/// # Safety
+///
+/// `emulator` must be a valid emulator pointer. The callbacks must be safe to call with `userdata`.
+#[no_mangle]
+pub unsafe extern "C" fn jgenesis_run_frame(
+    emulator: *mut Emulator,
+    video_callback: VideoCallback,
+    audio_callback: AudioCallback,
+    userdata: *mut c_void,
+) -> c_int {
+    let emulator = &mut *emulator;
+    if let Err(err) = emulator.run_frame() {
+        set_last_error(err);
+        return -1;
+    }
+
+    if let Some(video_callback) = video_callback {
+        let (pixels, frame_size) = emulator.frame();
+        video_callback(userdata, pixels.as_ptr(), frame_size.width, frame_size.height);
+    }
+
+    if let Some(audio_callback) = audio_callback {
+        let samples = emulator.audio_samples();
+        audio_callback(userdata, samples.as_ptr(), samples.len() / 2);
+    }
+
+    0
+}
+
+/// Pixel aspect ratio of the most recently rendered frame, or 0 if the frame should be stretched to
+/// fill the display.
+///
+/// # Safety
+///
+/// `emulator` must be a valid emulator pointer.
+#[no_mangle]
+pub unsafe extern "C" fn jgenesis_pixel_aspect_ratio(emulator: *const Emulator) -> f64 {
+    (*emulator).pixel_aspect_ratio().unwrap_or(0.0)
+}
+
+/// # Safety
+///
+/// `emulator` must be a valid emulator pointer.
+#[no_mangle]
+pub unsafe extern "C" fn jgenesis_soft_reset(emulator: *mut Emulator) {
+    (*emulator).soft_reset();
+}
+
+/// # Safety
+///
+/// `emulator` must be a valid emulator pointer.
+#[no_mangle]
+pub unsafe extern "C" fn jgenesis_hard_reset(emulator: *mut Emulator) {
+    (*emulator).hard_reset();
+}
+
+/// Read `len` bytes of memory starting at `address` on the main CPU's address bus. Only supported for
+/// Genesis and SNES. Returns 0 on success and -1 on failure.
+///
+/// # Safety
+///
+/// `emulator` must be a valid emulator pointer, and `out` must point to `len` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn jgenesis_read_memory(
+    emulator: *mut Emulator,
+    address: u32,
+    out: *mut u8,
+    len: usize,
+) -> c_int {
+    let out = if out.is_null() || len == 0 { &mut [] } else { slice::from_raw_parts_mut(out, len) };
+    match (*emulator).read_memory(address, out) {
+        Ok(()) => 0,
+        Err(err) => {
+            set_last_error(err);
+            -1
+        }
+    }
+}
+
+/// Write `len` bytes of memory starting at `address` on the main CPU's address bus. Only supported
+/// for Genesis and SNES. Returns 0 on success and -1 on failure.
+///
+/// # Safety
+///
+/// `emulator` must be a valid emulator pointer, and `data` must point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn jgenesis_write_memory(
+    emulator: *mut Emulator,
+    address: u32,
+    data: *const u8,
+    len: usize,
+) -> c_int {
+    match (*emulator).write_memory(address, &bytes_from_raw(data, len)) {
+        Ok(()) => 0,
+        Err(err) => {
+            set_last_error(err);
+            -1
+        }
+    }
+}
+
+/// A byte buffer allocated by this library; must be freed with [`jgenesis_free_buffer`].
+#[repr(C)]
+pub struct Buffer {
+    pub data: *mut u8,
+    pub len: usize,
+}
+
+impl Buffer {
+    const EMPTY: Self = Self { data: ptr::null_mut(), len: 0 };
+
+    fn from_vec(bytes: Vec<u8>) -> Self {
+        let bytes = Box::into_raw(bytes.into_boxed_slice());
+        Self { data: bytes.cast(), len: bytes.len() }
+    }
+}
+
+/// Save the emulator state. Returns an empty buffer (null data) on failure.
+///
+/// # Safety
+///
+/// `emulator` must be a valid emulator pointer.
+#[no_mangle]
+pub unsafe extern "C" fn jgenesis_save_state(emulator: *const Emulator) -> Buffer {
+    match (*emulator).save_state() {
+        Ok(state) => Buffer::from_vec(state),
+        Err(err) => {
+            set_last_error(err);
+            Buffer::EMPTY
+        }
+    }
+}
+
+/// Load a state returned by [`jgenesis_save_state`]. Returns 0 on success and -1 on failure, in
+/// which case the emulator is unchanged.
+///
+/// # Safety
+///
+/// `emulator` must be a valid emulator pointer, and `state` must point to `state_len` readable
+/// bytes.
+#[no_mangle]
+pub unsafe extern "C" fn jgenesis_load_state(
+    emulator: *mut Emulator,
+    state: *const u8,
+    state_len: usize,
+) -> c_int {
+    match (*emulator).load_state(&bytes_from_raw(state, state_len)) {
+        Ok(()) => 0,
+        Err(err) => {
+            set_last_error(err);
+            -1
+        }
+    }
+}
+
+/// Copy of the cartridge's battery-backed save file (or Sega CD backup RAM) as most recently
+/// persisted by the core. Returns an empty buffer if the game has not written a save file.
+///
+/// # Safety
+///
+/// `emulator` must be a valid emulator pointer.
+#[no_mangle]
+pub unsafe extern "C" fn jgenesis_save_file(emulator: *const Emulator) -> Buffer {
+    (*emulator).save_file("sav").map_or(Buffer::EMPTY, |sav| Buffer::from_vec(sav.to_vec()))
+}
+
+/// Free a buffer returned by this library. Does nothing if the buffer is empty.
+///
+/// # Safety
+///
+/// `buffer` must have been returned by this library and must not have already been freed.
+#[no_mangle]
+pub unsafe extern "C" fn jgenesis_free_buffer(buffer: Buffer) {
+    if !buffer.data.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(buffer.data, buffer.len)));
+    }
+}
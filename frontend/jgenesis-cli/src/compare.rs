@@ -0,0 +1,237 @@
+//! Comparison mode: run every ROM in a list headlessly for a fixed number of frames and write a
+//! report with a screenshot and stats for each, e.g. for a library-wide compatibility sweep after
+//! a core change. Frame CRCs in `report.json` can be diffed against a report from a previous build
+//! to find games whose output changed.
+
+use crate::{detect_hardware, is_spc_file, Args, Hardware};
+use anyhow::Context;
+use jgenesis_native_driver::headless::{self, HeadlessOptions, HeadlessStats};
+use jgenesis_native_driver::NativeEmulatorResult;
+use serde::Serialize;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const JSON_REPORT_FILE: &str = "report.json";
+const HTML_REPORT_FILE: &str = "report.html";
+
+#[derive(Debug, Serialize)]
+struct Report {
+    frames: u64,
+    roms: Vec<RomReport>,
+}
+
+#[derive(Debug, Serialize)]
+struct RomReport {
+    path: String,
+    hardware: String,
+    // Relative to the report directory; None if the run failed or never rendered a frame
+    screenshot: Option<String>,
+    stats: Option<RomStats>,
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct RomStats {
+    frames_rendered: u64,
+    elapsed_ms: u128,
+    fps: f64,
+    frame_width: Option<u32>,
+    frame_height: Option<u32>,
+    frame_crc32: Option<String>,
+    audio_samples: u64,
+    audio_peak: f64,
+}
+
+impl From<&HeadlessStats> for RomStats {
+    fn from(stats: &HeadlessStats) -> Self {
+        Self {
+            frames_rendered: stats.frames_rendered,
+            elapsed_ms: stats.elapsed.as_millis(),
+            fps: stats.fps(),
+            frame_width: stats.frame_size.map(|frame_size| frame_size.width),
+            frame_height: stats.frame_size.map(|frame_size| frame_size.height),
+            frame_crc32: stats.frame_crc32.map(|crc| format!("{crc:08X}")),
+            audio_samples: stats.audio_samples,
+            audio_peak: stats.audio_peak,
+        }
+    }
+}
+
+/// Run every ROM listed in the file at `list_path` and write the report to the configured output
+/// directory. Errors in individual ROMs are recorded in the report rather than returned.
+pub fn run(args: &mut Args, list_path: &Path) -> anyhow::Result<()> {
+    let list = fs::read_to_string(list_path)
+        .with_context(|| format!("Error reading ROM list '{}'", list_path.display()))?;
+    let rom_paths: Vec<&str> = list
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect();
+
+    let output_dir = PathBuf::from(&args.compare_output_dir);
+    fs::create_dir_all(&output_dir).with_context(|| {
+        format!("Error creating comparison output directory '{}'", output_dir.display())
+    })?;
+
+    let forced_hardware = args.hardware;
+    let mut roms = Vec::with_capacity(rom_paths.len());
+    for (i, &rom_path) in rom_paths.iter().enumerate() {
+        let hardware = forced_hardware.unwrap_or_else(|| detect_hardware(rom_path));
+        log::info!("[{}/{}] Running '{rom_path}' as {hardware}", i + 1, rom_paths.len());
+
+        // Prefix with the list index so that ROMs with the same file name in different
+        // directories do not overwrite each other's screenshots
+        let file_stem = Path::new(rom_path)
+            .file_stem()
+            .map_or_else(|| "rom".into(), |file_stem| file_stem.to_string_lossy().into_owned());
+        let screenshot_name = format!("{i:04}-{file_stem}.png");
+        let options = HeadlessOptions {
+            frames: args.compare_frames,
+            screenshot_path: Some(output_dir.join(&screenshot_name)),
+        };
+
+        args.file_path = Some(rom_path.into());
+        let result = run_headless(args, hardware, &options);
+
+        roms.push(match result {
+            Ok(stats) => RomReport {
+                path: rom_path.into(),
+                hardware: hardware.to_string(),
+                screenshot: stats.frame_size.map(|_| screenshot_name),
+                stats: Some(RomStats::from(&stats)),
+                error: None,
+            },
+            Err(err) => {
+                log::error!("Error running '{rom_path}': {err}");
+                RomReport {
+                    path: rom_path.into(),
+                    hardware: hardware.to_string(),
+                    screenshot: None,
+                    stats: None,
+                    error: Some(err.to_string()),
+                }
+            }
+        });
+    }
+
+    let report = Report { frames: args.compare_frames, roms };
+
+    let json_path = output_dir.join(JSON_REPORT_FILE);
+    fs::write(&json_path, serde_json::to_string_pretty(&report)?)
+        .with_context(|| format!("Error writing '{}'", json_path.display()))?;
+
+    let html_path = output_dir.join(HTML_REPORT_FILE);
+    fs::write(&html_path, html_report(&report))
+        .with_context(|| format!("Error writing '{}'", html_path.display()))?;
+
+    let num_errors = report.roms.iter().filter(|rom| rom.error.is_some()).count();
+    log::info!(
+        "Ran {} ROMs with {num_errors} errors; wrote report to '{}'",
+        report.roms.len(),
+        html_path.display()
+    );
+
+    Ok(())
+}
+
+fn run_headless(
+    args: &Args,
+    hardware: Hardware,
+    options: &HeadlessOptions,
+) -> NativeEmulatorResult<HeadlessStats> {
+    match hardware {
+        Hardware::MasterSystem => headless::run_smsgg(&args.smsgg_config(), options),
+        Hardware::Genesis => headless::run_genesis(&args.genesis_config(), options),
+        Hardware::SegaCd => headless::run_sega_cd(&args.sega_cd_config(), options),
+        Hardware::Pico => headless::run_pico(&args.genesis_config(), options),
+        Hardware::Nes => headless::run_nes(&args.nes_config(), options),
+        Hardware::Snes => {
+            let config = args.snes_config();
+            if is_spc_file(&config.common.rom_file_path) {
+                headless::run_spc(&config, options)
+            } else {
+                headless::run_snes(&config, options)
+            }
+        }
+        Hardware::GameBoy => headless::run_gb(&args.gb_config(), options),
+        Hardware::PcEngine => headless::run_pce(&args.pce_config(), options),
+        Hardware::GameBoyAdvance => headless::run_gba(&args.gba_config(), options),
+        Hardware::Atari2600 => headless::run_atari2600(&args.atari2600_config(), options),
+        Hardware::ColecoVision => headless::run_colecovision(&args.colecovision_config(), options),
+    }
+}
+
+fn html_report(report: &Report) -> String {
+    let mut html = String::from(concat!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n",
+        "<title>jgenesis comparison report</title>\n<style>\n",
+        "body { font-family: sans-serif; }\n",
+        "table { border-collapse: collapse; }\n",
+        "td, th { border: 1px solid #888; padding: 4px 8px; text-align: left; vertical-align: top; }\n",
+        "img { image-rendering: pixelated; width: 256px; }\n",
+        ".error { background: #fdd; }\n",
+        "</style>\n</head>\n<body>\n",
+    ));
+
+    let num_errors = report.roms.iter().filter(|rom| rom.error.is_some()).count();
+    let _ = writeln!(
+        html,
+        "<p>{} ROMs, {num_errors} errors, {} frames each</p>",
+        report.roms.len(),
+        report.frames
+    );
+    html.push_str(
+        "<table>\n<tr><th>Screenshot</th><th>ROM</th><th>Hardware</th><th>Frames</th><th>FPS</th><th>Frame size</th><th>Frame CRC32</th><th>Audio peak</th><th>Error</th></tr>\n",
+    );
+
+    for rom in &report.roms {
+        let class = if rom.error.is_some() { " class=\"error\"" } else { "" };
+        let _ = write!(html, "<tr{class}><td>");
+        if let Some(screenshot) = &rom.screenshot {
+            let _ = write!(html, "<img src=\"{}\">", escape_html(screenshot));
+        }
+        let _ = write!(html, "</td><td>{}</td><td>{}</td>", escape_html(&rom.path), rom.hardware);
+
+        match &rom.stats {
+            Some(stats) => {
+                let frame_size = match (stats.frame_width, stats.frame_height) {
+                    (Some(width), Some(height)) => format!("{width}x{height}"),
+                    _ => String::new(),
+                };
+                let _ = write!(
+                    html,
+                    "<td>{}</td><td>{:.1}</td><td>{frame_size}</td><td>{}</td><td>{:.3}</td>",
+                    stats.frames_rendered,
+                    stats.fps,
+                    stats.frame_crc32.as_deref().unwrap_or(""),
+                    stats.audio_peak
+                );
+            }
+            None => html.push_str("<td></td><td></td><td></td><td></td><td></td>"),
+        }
+
+        let _ = writeln!(
+            html,
+            "<td>{}</td></tr>",
+            rom.error.as_deref().map(escape_html).unwrap_or_default()
+        );
+    }
+
+    html.push_str("</table>\n</body>\n</html>\n");
+    html
+}
+
+fn escape_html(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
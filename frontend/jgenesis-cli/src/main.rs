@@ -1,27 +1,52 @@
+mod compare;
+
+use atari2600_core::api::{Atari2600AspectRatio, Atari2600Difficulty};
 use clap::Parser;
+use colecovision_core::api::ColecoVisionAspectRatio;
 use env_logger::Env;
 use gb_core::api::{GbAspectRatio, GbPalette, GbcColorCorrection};
-use genesis_core::{GenesisAspectRatio, GenesisControllerType, GenesisRegion};
+use gba_core::api::GbaAspectRatio;
+use genesis_core::{
+    GenesisAspectRatio, GenesisControllerType, GenesisMultitap, GenesisRegion, GenesisRegionSpoof,
+    GenesisSerialPort,
+};
+use jgenesis_common::audio::ResamplerQuality;
 use jgenesis_common::frontend::TimingMode;
+use jgenesis_common::logging::{LogDirective, SubsystemLogger};
+use jgenesis_common::rng::InitialRamState;
 use jgenesis_native_driver::config::input::{
-    GameBoyInputConfig, GenesisControllerConfig, GenesisInputConfig, HotkeyConfig, KeyboardInput,
-    NesInputConfig, SmsGgControllerConfig, SmsGgInputConfig, SnesControllerType, SnesInputConfig,
-    SuperScopeConfig,
+    Atari2600InputConfig, ColecoVisionInputConfig, GameBoyInputConfig, GbaInputConfig,
+    GenesisControllerConfig, GenesisInputConfig, HotkeyConfig, InputMacroConfig, KeyboardInput,
+    NesInputConfig, PceInputConfig, SmsGgControllerConfig, SmsGgInputConfig, SmsPeripheralConfig,
+    SnesControllerType, SnesInputConfig, SteamDeckInputDefaults, SuperScopeConfig,
 };
 use jgenesis_native_driver::config::{
-    CommonConfig, GameBoyConfig, GenesisConfig, GgAspectRatio, NesConfig, SegaCdConfig,
-    SmsAspectRatio, SmsGgConfig, SnesConfig, WindowSize,
+    Atari2600Config, AudioPostProcessingConfig, ColecoVisionConfig, CommonConfig, DiscordConfig,
+    GameBoyConfig, GbaConfig, GenesisConfig, GgAspectRatio, LinkTransportKind, NesConfig,
+    OsdMessages, PceConfig, RemoteControlConfig, SaveSyncConfig, SaveSyncProtocol, Secret,
+    SegaCdConfig, SmsAspectRatio, SmsGgConfig, SnesConfig, WindowSize,
 };
+use jgenesis_native_driver::msumd;
+use jgenesis_native_driver::paths::AppPaths;
+use jgenesis_native_driver::steamdeck::SteamDeckMode;
 use jgenesis_native_driver::NativeTickEffect;
 use jgenesis_proc_macros::{EnumDisplay, EnumFromStr};
 use jgenesis_renderer::config::{
-    FilterMode, PreprocessShader, PrescaleFactor, RendererConfig, Scanlines, VSyncMode, WgpuBackend,
+    ColorBlindFilter, FilterMode, MagnifierCorner, MagnifierMode, PreprocessShader, PrescaleFactor,
+    RendererConfig, Scanlines, VSyncMode, WgpuBackend,
 };
-use nes_core::api::{NesAspectRatio, Overscan};
+use jgenesis_renderer::deinterlace::DeinterlaceMode;
+use jgenesis_renderer::frameskip::DEFAULT_MAX_FRAME_SKIP;
+use log::LevelFilter;
+use nes_core::api::{NesAspectRatio, NesTimingMode, Overscan};
+use nes_core::input::NesExpansionDevice;
+use pce_core::api::{PceAspectRatio, PceRegion};
 use smsgg_core::psg::PsgVersion;
-use smsgg_core::{SmsRegion, VdpVersion};
-use snes_core::api::SnesAspectRatio;
+use smsgg_core::{SmsControllerType, SmsRegion, VdpVersion};
+use snes_core::api::{Mode7Scale, SnesAspectRatio, SnesEnhancements};
+use std::env;
 use std::ffi::OsStr;
+use std::fs;
 use std::num::NonZeroU64;
 use std::path::Path;
 use std::process;
@@ -31,9 +56,14 @@ enum Hardware {
     MasterSystem,
     Genesis,
     SegaCd,
+    Pico,
     Nes,
     Snes,
     GameBoy,
+    PcEngine,
+    GameBoyAdvance,
+    Atari2600,
+    ColecoVision,
 }
 
 const SMSGG_OPTIONS_HEADING: &str = "Master System / Game Gear Options";
@@ -42,18 +72,30 @@ const SCD_OPTIONS_HEADING: &str = "Sega CD Options";
 const NES_OPTIONS_HEADING: &str = "NES Options";
 const SNES_OPTIONS_HEADING: &str = "SNES Options";
 const GB_OPTIONS_HEADING: &str = "Game Boy Options";
+const PCE_OPTIONS_HEADING: &str = "PC Engine Options";
+const GBA_OPTIONS_HEADING: &str = "Game Boy Advance Options";
+const ATARI2600_OPTIONS_HEADING: &str = "Atari 2600 Options";
+const COLECOVISION_OPTIONS_HEADING: &str = "ColecoVision Options";
 const VIDEO_OPTIONS_HEADING: &str = "Video Options";
 const AUDIO_OPTIONS_HEADING: &str = "Audio Options";
 const INPUT_OPTIONS_HEADING: &str = "Input Options";
 const HOTKEY_OPTIONS_HEADING: &str = "Hotkey Options";
+const COMPARE_OPTIONS_HEADING: &str = "Comparison Options";
+
+const SAVE_SYNC_PASSWORD_VAR: &str = "JGENESIS_SAVE_SYNC_PASSWORD";
+const REMOTE_CONTROL_TOKEN_VAR: &str = "JGENESIS_REMOTE_CONTROL_TOKEN";
 
 #[derive(Parser)]
 struct Args {
     /// ROM file path
-    #[arg(short = 'f', long)]
-    file_path: String,
+    #[arg(short = 'f', long, required_unless_present = "compare_list")]
+    file_path: Option<String>,
 
-    /// Hardware (MasterSystem / Genesis / SegaCd / Nes / Snes), will default based on file extension if not set
+    /// IPS or BPS patch to apply to the ROM when loading it, without modifying the ROM file; can be repeated to stack patches, which are applied in order
+    #[arg(long, value_name = "PATCH_PATH")]
+    patch: Vec<String>,
+
+    /// Hardware (MasterSystem / Genesis / SegaCd / Pico / Nes / Snes / GameBoy / PcEngine / GameBoyAdvance / Atari2600 / ColecoVision), will default based on file extension if not set
     #[arg(long)]
     hardware: Option<Hardware>,
 
@@ -69,6 +111,79 @@ struct Args {
     #[arg(long, default_value_t)]
     hide_cursor_over_window: bool,
 
+    /// Steam Deck mode (Auto / Enabled / Disabled); forces fullscreen with integer scaling, maps P1 to the built-in controls, and paces frames using audio. Auto enables it when running under gamescope
+    #[arg(long, default_value_t)]
+    steam_deck_mode: SteamDeckMode,
+
+    /// Low power profile for Raspberry Pi-class hardware; uses OpenGL with WebGL2 limits, disables prescaling and shaders, lowers resampler quality, and enables auto frame skip
+    #[arg(long, default_value_t)]
+    low_power: bool,
+
+    /// Portable mode: store crash reports next to the executable instead of in the user's state directory
+    #[arg(long, default_value_t)]
+    portable: bool,
+
+    /// Save state automatically on exit and resume from it the next time this ROM is launched
+    #[arg(long, default_value_t)]
+    auto_save_state: bool,
+
+    /// Listen for GDB remote protocol connections on this localhost port (Genesis / SNES only)
+    #[arg(long)]
+    gdb_port: Option<u16>,
+
+    /// Listen for input injection connections on this localhost port, allowing external programs to press buttons on any controller with text commands like "hold p2.a" or "press p2.down, p2.right"
+    #[arg(long)]
+    input_injection_port: Option<u16>,
+
+    /// Run a remote control WebSocket/HTTP server on this localhost port, allowing external programs to send commands (load ROM, pause, save state, screenshot, read memory); clients must authenticate with the token in the JGENESIS_REMOTE_CONTROL_TOKEN environment variable
+    #[arg(long)]
+    remote_control_port: Option<u16>,
+
+    /// Symbol file (.sym / .map) to display labels from in the memory viewer; defaults to a file next to the ROM
+    #[arg(long)]
+    symbol_file: Option<String>,
+
+    /// Initial work RAM contents (AllZeroes / AllOnes / Random), defaults to Random for NES and AllZeroes otherwise
+    #[arg(long)]
+    initial_ram_state: Option<InitialRamState>,
+
+    /// Seed for all in-core randomness; set this for deterministic emulation, e.g. TAS movies or netplay
+    #[arg(long)]
+    rng_seed: Option<u64>,
+
+    /// Dump every frame and audio sample to .y4m and .wav files at this path, running as fast as possible
+    #[arg(long)]
+    av_dump: Option<String>,
+
+    /// Mirror save files to this WebDAV collection or path-style S3 bucket URL when launching and closing a game; the password or S3 secret key is read from the JGENESIS_SAVE_SYNC_PASSWORD environment variable
+    #[arg(long)]
+    save_sync_url: Option<String>,
+
+    /// Save sync protocol (WebDav / S3)
+    #[arg(long, default_value_t)]
+    save_sync_protocol: SaveSyncProtocol,
+
+    /// Save sync username (WebDAV) or access key ID (S3)
+    #[arg(long, default_value_t = String::new())]
+    save_sync_username: String,
+
+    /// Save sync signing region (S3 only)
+    #[arg(long, default_value_t = String::from("us-east-1"))]
+    save_sync_s3_region: String,
+
+    /// Show the running system and elapsed time on your Discord profile using Rich Presence, as the Discord application with this ID
+    #[arg(long, value_name = "APPLICATION_ID")]
+    discord_app_id: Option<String>,
+
+    /// Also show the running game's title in Discord Rich Presence
+    #[arg(long, default_value_t)]
+    discord_show_game_title: bool,
+
+    /// Override the log level for one subsystem (vdp / ym2612 / z80 / cdc / mapper), e.g. --log vdp=trace; can be repeated.
+    /// Debug and trace logs are only available in debug builds
+    #[arg(long = "log", value_name = "SUBSYSTEM=LEVEL")]
+    log_directives: Vec<LogDirective>,
+
     /// Force VDP version (NtscMasterSystem2 / NtscMasterSystem1 / PalMasterSystem2 / PalMasterSystem1 / GameGear)
     #[arg(long, help_heading = SMSGG_OPTIONS_HEADING)]
     vdp_version: Option<VdpVersion>,
@@ -105,6 +220,26 @@ struct Args {
     #[arg(long, default_value_t, help_heading = SMSGG_OPTIONS_HEADING)]
     smsgg_overclock_z80: bool,
 
+    /// Player 1 SMS input device (Joypad / Paddle / SportsPad / GraphicBoard)
+    #[arg(long, default_value_t, help_heading = SMSGG_OPTIONS_HEADING)]
+    sms_p1_controller_type: SmsControllerType,
+
+    /// Mouse sensitivity multiplier for the SMS Paddle, Sports Pad, and Graphic Board
+    #[arg(long, default_value_t = 1.0, help_heading = SMSGG_OPTIONS_HEADING)]
+    sms_peripheral_mouse_sensitivity: f64,
+
+    /// Analog stick sensitivity multiplier for the SMS Paddle, Sports Pad, and Graphic Board
+    #[arg(long, default_value_t = 1.0, help_heading = SMSGG_OPTIONS_HEADING)]
+    sms_peripheral_stick_sensitivity: f64,
+
+    /// Game Gear Gear-to-Gear link transport (None / TcpClient / TcpServer)
+    #[arg(long, default_value_t, help_heading = SMSGG_OPTIONS_HEADING)]
+    gg_link_transport: LinkTransportKind,
+
+    /// Address to connect to or listen on for the TCP Gear-to-Gear link transports
+    #[arg(long, default_value = "127.0.0.1:7846", help_heading = SMSGG_OPTIONS_HEADING)]
+    gg_link_address: String,
+
     /// Emulate the VDP's non-linear DAC, which tends to brighten darker colors and darken brighter colors
     #[arg(long, default_value_t, help_heading = GENESIS_OPTIONS_HEADING)]
     emulate_non_linear_vdp_dac: bool,
@@ -117,6 +252,29 @@ struct Args {
     #[arg(long, default_value_t, help_heading = GENESIS_OPTIONS_HEADING)]
     genesis_render_horizontal_border: bool,
 
+    /// Render beyond the left and right edges of the screen in H40 mode (424px wide instead of 320px)
+    #[arg(long, default_value_t, help_heading = GENESIS_OPTIONS_HEADING)]
+    genesis_widescreen: bool,
+
+    /// Widescreen patch file with per-game fixes for rendering outside of the normal screen area;
+    /// only used with --genesis-widescreen
+    #[arg(long, help_heading = GENESIS_OPTIONS_HEADING)]
+    genesis_widescreen_patches: Option<String>,
+
+    /// Output frames at 2x resolution, stretching H32 lines to match H40 lines so that the output
+    /// size does not change when games switch display modes (not accurate to hardware)
+    #[arg(long, default_value_t, help_heading = GENESIS_OPTIONS_HEADING)]
+    genesis_hi_res: bool,
+
+    /// Experimental: save each 8x8 tile to this directory as a PNG file the first time it is drawn
+    #[arg(long, help_heading = GENESIS_OPTIONS_HEADING)]
+    genesis_tile_dump_dir: Option<String>,
+
+    /// Experimental: replace tiles with PNG files from this directory, named the same as dumped
+    /// tiles
+    #[arg(long, help_heading = GENESIS_OPTIONS_HEADING)]
+    genesis_texture_pack_dir: Option<String>,
+
     /// Disable YM2612 output quantization, letting outputs cover the full 14-bit range instead of only using the highest 9 bits
     #[arg(long = "no-ym2612-quantization", default_value_t = true, action = clap::ArgAction::SetFalse, help_heading = GENESIS_OPTIONS_HEADING)]
     quantize_ym2612_output: bool,
@@ -134,6 +292,41 @@ struct Args {
     #[arg(long, help_heading = GENESIS_OPTIONS_HEADING)]
     genesis_region: Option<GenesisRegion>,
 
+    /// Region reported to the game independent of timing mode, for region-locked games (Disabled / Auto / Americas / Japan / Europe)
+    #[arg(long, default_value_t, help_heading = GENESIS_OPTIONS_HEADING)]
+    genesis_region_spoof: GenesisRegionSpoof,
+
+    /// Report a console with TMSS in the version register
+    #[arg(long, default_value_t, help_heading = GENESIS_OPTIONS_HEADING)]
+    genesis_report_tmss: bool,
+
+    /// Install 128KB of VRAM like development hardware and the Tera Drive; only needed for homebrew
+    /// and prototypes that use 128KB mode
+    #[arg(long, default_value_t, help_heading = GENESIS_OPTIONS_HEADING)]
+    genesis_128kb_vram: bool,
+
+    /// ROM to lock on to Sonic & Knuckles (e.g. Sonic 3 or Sonic 2); ignored for other games
+    #[arg(long, help_heading = GENESIS_OPTIONS_HEADING)]
+    lock_on_rom: Option<String>,
+
+    /// Sonic & Knuckles 256KB patch ROM, required for Knuckles in Sonic 2 unless it is included at
+    /// the end of the Sonic & Knuckles ROM
+    #[arg(long, help_heading = GENESIS_OPTIONS_HEADING)]
+    lock_on_patch_rom: Option<String>,
+
+    /// I/O port that the serial transport is connected to (Port1 / Port2 / Extension). The Mega
+    /// Modem plugs into the Extension port
+    #[arg(long, default_value_t, help_heading = GENESIS_OPTIONS_HEADING)]
+    genesis_serial_port: GenesisSerialPort,
+
+    /// Serial transport (None / TcpClient / TcpServer); Genesis only
+    #[arg(long, default_value_t, help_heading = GENESIS_OPTIONS_HEADING)]
+    genesis_serial_transport: LinkTransportKind,
+
+    /// Address to connect to or listen on for the TCP serial transports
+    #[arg(long, default_value = "127.0.0.1:7845", help_heading = GENESIS_OPTIONS_HEADING)]
+    genesis_serial_address: String,
+
     /// Sega CD BIOS path (required for Sega CD emulation)
     #[arg(short = 'b', long, help_heading = SCD_OPTIONS_HEADING)]
     bios_path: Option<String>,
@@ -142,10 +335,27 @@ struct Args {
     #[arg(long = "disable-ram-cartridge", default_value_t = true, action = clap::ArgAction::SetFalse, help_heading = SCD_OPTIONS_HEADING)]
     enable_ram_cartridge: bool,
 
+    /// Skip the Sega CD BIOS intro by running the boot sequence at startup without video or audio
+    #[arg(long, default_value_t, help_heading = SCD_OPTIONS_HEADING)]
+    scd_fast_boot: bool,
+
     /// Run the Sega CD emulator with no disc
     #[arg(long, default_value_t, help_heading = SCD_OPTIONS_HEADING)]
     scd_no_disc: bool,
 
+    /// Boot from this cartridge ROM with the Sega CD attached as an expansion (Mode 1); the disc
+    /// is still loaded unless --scd-no-disc is set
+    #[arg(long, help_heading = SCD_OPTIONS_HEADING)]
+    scd_mode_1_cartridge_path: Option<String>,
+
+    /// Do not run Genesis ROMs as MSU-MD games when a CD image with the same file name is present
+    #[arg(long = "disable-msu-md", default_value_t = true, action = clap::ArgAction::SetFalse, help_heading = SCD_OPTIONS_HEADING)]
+    enable_msu_md: bool,
+
+    /// Force NES timing mode (Ntsc / Pal / Dendy), overrides --forced-timing-mode if set
+    #[arg(long, help_heading = NES_OPTIONS_HEADING)]
+    nes_timing_mode: Option<NesTimingMode>,
+
     /// Aspect ratio (Ntsc / Pal / SquarePixels / Stretched)
     #[arg(long, default_value_t, help_heading = NES_OPTIONS_HEADING)]
     nes_aspect_ratio: NesAspectRatio,
@@ -166,6 +376,10 @@ struct Args {
     #[arg(long, default_value_t, help_heading = NES_OPTIONS_HEADING)]
     overscan_right: u16,
 
+    /// Show the top and bottom 8 scanlines in NTSC mode, which most NTSC TVs cut off
+    #[arg(long = "no-nes-ntsc-overscan", default_value_t = true, action = clap::ArgAction::SetFalse, help_heading = NES_OPTIONS_HEADING)]
+    nes_ntsc_overscan: bool,
+
     /// Render the PAL black border (top scanline + two columns on each side)
     #[arg(long, default_value_t, help_heading = NES_OPTIONS_HEADING)]
     nes_pal_black_border: bool,
@@ -174,6 +388,10 @@ struct Args {
     #[arg(long, default_value_t, help_heading = NES_OPTIONS_HEADING)]
     nes_allow_opposing_inputs: bool,
 
+    /// Force the connected expansion port device (None / FamilyBasicKeyboard / HyperShot / VausNes / VausFamicom), will default based on NES 2.0 header if not set
+    #[arg(long, help_heading = NES_OPTIONS_HEADING)]
+    nes_expansion_device: Option<NesExpansionDevice>,
+
     /// Silence ultrasonic triangle channel output (less accurate but reduces audio popping)
     #[arg(long, default_value_t, help_heading = NES_OPTIONS_HEADING)]
     nes_silence_ultrasonic_triangle: bool,
@@ -194,6 +412,15 @@ struct Args {
     #[arg(long, default_value_t = NonZeroU64::new(1).unwrap(), help_heading = SNES_OPTIONS_HEADING)]
     gsu_overclock_factor: NonZeroU64,
 
+    /// Render Mode 7 at a higher internal resolution (Native / X2 / X4)
+    #[arg(long, default_value_t, help_heading = SNES_OPTIONS_HEADING)]
+    snes_mode7_scale: Mode7Scale,
+
+    /// Interpolate Mode 7 parameters between scanlines to smooth out perspective effects; only
+    /// used with --snes-mode7-scale
+    #[arg(long, default_value_t, help_heading = SNES_OPTIONS_HEADING)]
+    snes_mode7_perspective_correction: bool,
+
     /// Player 2 input device (Gamepad / SuperScope)
     #[arg(long, default_value_t, help_heading = SNES_OPTIONS_HEADING)]
     snes_p2_controller_type: SnesControllerType,
@@ -246,11 +473,55 @@ struct Args {
     #[arg(long, default_value_t, help_heading = GB_OPTIONS_HEADING)]
     gb_audio_60hz_hack: bool,
 
-    /// Window width in pixels; height must also be set
+    /// Console region (Japan / Americas); Japan is the PC Engine and Americas is the TurboGrafx-16
+    #[arg(long, default_value_t, help_heading = PCE_OPTIONS_HEADING)]
+    pce_region: PceRegion,
+
+    /// Aspect ratio (Ntsc / SquarePixels / Stretched)
+    #[arg(long, default_value_t, help_heading = PCE_OPTIONS_HEADING)]
+    pce_aspect_ratio: PceAspectRatio,
+
+    /// Game Boy Advance BIOS path (required for GBA emulation)
+    #[arg(long, help_heading = GBA_OPTIONS_HEADING)]
+    gba_bios_path: Option<String>,
+
+    /// Aspect ratio (SquarePixels / Stretched)
+    #[arg(long, default_value_t, help_heading = GBA_OPTIONS_HEADING)]
+    gba_aspect_ratio: GbaAspectRatio,
+
+    /// Skip the BIOS boot animation and start executing the cartridge immediately
+    #[arg(long, default_value_t, help_heading = GBA_OPTIONS_HEADING)]
+    gba_skip_bios_intro: bool,
+
+    /// Aspect ratio (Ntsc / SquarePixels / Stretched)
+    #[arg(long, default_value_t, help_heading = ATARI2600_OPTIONS_HEADING)]
+    atari2600_aspect_ratio: Atari2600AspectRatio,
+
+    /// Left difficulty switch position (Novice / Pro); Novice is B and Pro is A
+    #[arg(long, default_value_t, help_heading = ATARI2600_OPTIONS_HEADING)]
+    atari2600_left_difficulty: Atari2600Difficulty,
+
+    /// Right difficulty switch position (Novice / Pro); Novice is B and Pro is A
+    #[arg(long, default_value_t, help_heading = ATARI2600_OPTIONS_HEADING)]
+    atari2600_right_difficulty: Atari2600Difficulty,
+
+    /// Set the TV Type switch to black and white instead of color
+    #[arg(long, default_value_t, help_heading = ATARI2600_OPTIONS_HEADING)]
+    atari2600_black_and_white: bool,
+
+    /// ColecoVision BIOS path (required for ColecoVision emulation)
+    #[arg(long, help_heading = COLECOVISION_OPTIONS_HEADING)]
+    colecovision_bios_path: Option<String>,
+
+    /// Aspect ratio (Ntsc / SquarePixels / Stretched)
+    #[arg(long, default_value_t, help_heading = COLECOVISION_OPTIONS_HEADING)]
+    colecovision_aspect_ratio: ColecoVisionAspectRatio,
+
+    /// Window width in physical pixels; height must also be set
     #[arg(long, help_heading = VIDEO_OPTIONS_HEADING)]
     window_width: Option<u32>,
 
-    /// Window height in pixels; width must also be set
+    /// Window height in physical pixels; width must also be set
     #[arg(long, help_heading = VIDEO_OPTIONS_HEADING)]
     window_height: Option<u32>,
 
@@ -278,14 +549,54 @@ struct Args {
     #[arg(long, default_value_t, help_heading = VIDEO_OPTIONS_HEADING)]
     force_integer_height_scaling: bool,
 
-    /// Filter mode (Nearest / Linear)
+    /// In fullscreen, switch to a display refresh rate that is a multiple of 50Hz when running PAL games
+    #[arg(long, default_value_t, help_heading = VIDEO_OPTIONS_HEADING)]
+    pal_50hz_fullscreen: bool,
+
+    /// Render a border around the game; uses the system's default border (e.g. Game Gear shell) if --border-image is not set
+    #[arg(long, default_value_t, help_heading = VIDEO_OPTIONS_HEADING)]
+    show_border: bool,
+
+    /// PNG image to render as a border around the game; a fully transparent region at the center of the image is used as the game screen
+    #[arg(long, help_heading = VIDEO_OPTIONS_HEADING)]
+    border_image: Option<String>,
+
+    /// Filter mode (Nearest / Linear / SharpBilinear)
     #[arg(long, default_value_t = FilterMode::Linear, help_heading = VIDEO_OPTIONS_HEADING)]
     filter_mode: FilterMode,
 
+    /// Skip rendering frames when the host is unable to keep up with emulation speed; frames are still emulated
+    #[arg(long, default_value_t, help_heading = VIDEO_OPTIONS_HEADING)]
+    auto_frame_skip: bool,
+
+    /// Maximum number of consecutive frames to skip when auto frame skip is enabled
+    #[arg(long, default_value_t = DEFAULT_MAX_FRAME_SKIP, help_heading = VIDEO_OPTIONS_HEADING)]
+    max_frame_skip: u32,
+
+    /// Deinterlacing for Genesis and SNES interlaced double resolution output (Weave / Bob / Blend / MotionAdaptive)
+    #[arg(long, default_value_t, help_heading = VIDEO_OPTIONS_HEADING)]
+    deinterlace_mode: DeinterlaceMode,
+
     /// Preprocess shader (None / HorizontalBlurTwoPixels / HorizontalBlurThreePixels / HorizontalBlurSnesAdaptive / AntiDitherWeak / AntiDitherStrong)
     #[arg(long, default_value_t, help_heading = VIDEO_OPTIONS_HEADING)]
     preprocess_shader: PreprocessShader,
 
+    /// Color blindness correction filter (None / Protanopia / Deuteranopia / Tritanopia)
+    #[arg(long, default_value_t, help_heading = VIDEO_OPTIONS_HEADING)]
+    color_blind_filter: ColorBlindFilter,
+
+    /// Limit how quickly the screen's average brightness can change to reduce the intensity of full-screen flashes
+    #[arg(long, default_value_t, help_heading = VIDEO_OPTIONS_HEADING)]
+    flash_reduction: bool,
+
+    /// Accessibility magnifier mode (None / PictureInPicture / FullScreen); the zoomed view follows the mouse cursor
+    #[arg(long, default_value_t, help_heading = VIDEO_OPTIONS_HEADING)]
+    magnifier_mode: MagnifierMode,
+
+    /// Corner to draw the picture-in-picture magnifier in (TopLeft / TopRight / BottomLeft / BottomRight)
+    #[arg(long, default_value_t, help_heading = VIDEO_OPTIONS_HEADING)]
+    magnifier_corner: MagnifierCorner,
+
     /// Disable audio sync
     #[arg(long = "no-audio-sync", default_value_t = true, action = clap::ArgAction::SetFalse, help_heading = AUDIO_OPTIONS_HEADING)]
     audio_sync: bool,
@@ -302,14 +613,74 @@ struct Args {
     #[arg(long, default_value_t = 8192, help_heading = AUDIO_OPTIONS_HEADING)]
     audio_sync_threshold: u32,
 
+    /// Target audio latency in milliseconds; overrides the three audio buffer size options above
+    #[arg(long, help_heading = AUDIO_OPTIONS_HEADING)]
+    audio_latency_ms: Option<u32>,
+
+    /// Disable briefly stretching audio to recover from audio device underruns
+    #[arg(long = "no-audio-underrun-recovery", default_value_t = true, action = clap::ArgAction::SetFalse, help_heading = AUDIO_OPTIONS_HEADING)]
+    audio_underrun_recovery: bool,
+
     /// Audio gain in decibels; can be positive or negative
     #[arg(long, default_value_t = 0.0, help_heading = AUDIO_OPTIONS_HEADING)]
     audio_gain_db: f64,
 
+    /// Audio resampler quality (High / Low); Low averages source samples instead of applying a FIR low-pass filter
+    #[arg(long, default_value_t, help_heading = AUDIO_OPTIONS_HEADING)]
+    audio_resampler_quality: ResamplerQuality,
+
+    /// Enable 3-band equalizer (low shelf / mid peak / high shelf)
+    #[arg(long, default_value_t, help_heading = AUDIO_OPTIONS_HEADING)]
+    audio_eq: bool,
+
+    /// Equalizer low shelf frequency in Hz
+    #[arg(long, default_value_t = 200.0, help_heading = AUDIO_OPTIONS_HEADING)]
+    eq_low_frequency: f64,
+
+    /// Equalizer low shelf gain in decibels
+    #[arg(long, default_value_t = 0.0, help_heading = AUDIO_OPTIONS_HEADING)]
+    eq_low_gain_db: f64,
+
+    /// Equalizer mid peak center frequency in Hz
+    #[arg(long, default_value_t = 1000.0, help_heading = AUDIO_OPTIONS_HEADING)]
+    eq_mid_frequency: f64,
+
+    /// Equalizer mid peak gain in decibels
+    #[arg(long, default_value_t = 0.0, help_heading = AUDIO_OPTIONS_HEADING)]
+    eq_mid_gain_db: f64,
+
+    /// Equalizer mid peak Q factor
+    #[arg(long, default_value_t = 0.7, help_heading = AUDIO_OPTIONS_HEADING)]
+    eq_mid_q: f64,
+
+    /// Equalizer high shelf frequency in Hz
+    #[arg(long, default_value_t = 5000.0, help_heading = AUDIO_OPTIONS_HEADING)]
+    eq_high_frequency: f64,
+
+    /// Equalizer high shelf gain in decibels
+    #[arg(long, default_value_t = 0.0, help_heading = AUDIO_OPTIONS_HEADING)]
+    eq_high_gain_db: f64,
+
+    /// Stereo widening amount from 0.0 (disabled) to 1.0; also widens mono audio
+    #[arg(long, default_value_t = 0.0, help_heading = AUDIO_OPTIONS_HEADING)]
+    stereo_widening: f64,
+
+    /// Headphone crossfeed amount from 0.0 (disabled) to 1.0
+    #[arg(long, default_value_t = 0.0, help_heading = AUDIO_OPTIONS_HEADING)]
+    crossfeed: f64,
+
+    /// Enable soft limiter to prevent clipping
+    #[arg(long, default_value_t, help_heading = AUDIO_OPTIONS_HEADING)]
+    soft_limiter: bool,
+
     /// P1 Genesis controller type (ThreeButton / SixButton)
     #[arg(long, default_value_t, help_heading = INPUT_OPTIONS_HEADING)]
     input_p1_type: GenesisControllerType,
 
+    /// Genesis multiplayer adapter (None / EaFourWayPlay / JCart)
+    #[arg(long, default_value_t, help_heading = INPUT_OPTIONS_HEADING)]
+    input_genesis_multitap: GenesisMultitap,
+
     /// P1 up key
     #[arg(long, help_heading = INPUT_OPTIONS_HEADING)]
     input_p1_up: Option<String>,
@@ -370,6 +741,14 @@ struct Args {
     #[arg(long, default_value_t = 8000, help_heading = INPUT_OPTIONS_HEADING)]
     joy_axis_deadzone: i16,
 
+    /// Pause emulation when a mapped gamepad disconnects, and resume when it reconnects
+    #[arg(long, default_value_t, help_heading = INPUT_OPTIONS_HEADING)]
+    pause_on_controller_disconnect: bool,
+
+    /// Input macro triggered by a key, as <key>=<sequence>, e.g. "H=down, down+right, right+a*2"; can be repeated
+    #[arg(long, value_parser = parse_input_macro, help_heading = INPUT_OPTIONS_HEADING)]
+    input_macro: Vec<InputMacroConfig>,
+
     /// Fast forward multiplier
     #[arg(long, default_value_t = 2, help_heading = HOTKEY_OPTIONS_HEADING)]
     fast_forward_multiplier: u64,
@@ -378,6 +757,18 @@ struct Args {
     #[arg(long, default_value_t = 10, help_heading = HOTKEY_OPTIONS_HEADING)]
     rewind_buffer_length_seconds: u64,
 
+    /// Input trace length in seconds; the trace is written next to the ROM by the dump input trace hotkey or on panic (0 to disable)
+    #[arg(long, default_value_t = 30, help_heading = HOTKEY_OPTIONS_HEADING)]
+    input_trace_length_seconds: u64,
+
+    /// Practice loop: reload the loop point after this many frames (0 to disable)
+    #[arg(long, default_value_t = 0, help_heading = HOTKEY_OPTIONS_HEADING)]
+    practice_end_frames: u64,
+
+    /// Practice loop: reload the loop point when this expression is true after a frame, e.g. "[$FF0010] == 3" (Genesis/SNES only)
+    #[arg(long, default_value_t = String::new(), help_heading = HOTKEY_OPTIONS_HEADING)]
+    practice_end_condition: String,
+
     /// Quit hotkey
     #[arg(long, default_value_t = String::from("Escape"), help_heading = HOTKEY_OPTIONS_HEADING)]
     hotkey_quit: String,
@@ -421,9 +812,66 @@ struct Args {
     /// Open memory viewer window hotkey
     #[arg(long, default_value_t = String::from("'"), help_heading = HOTKEY_OPTIONS_HEADING)]
     hotkey_open_debugger: String,
+
+    /// Music dump hotkey (SPC snapshot for SNES, start/stop VGM+GYM logging for Genesis)
+    #[arg(long, default_value_t = String::from("F12"), help_heading = HOTKEY_OPTIONS_HEADING)]
+    hotkey_music_dump: String,
+
+    /// Famicom microphone hotkey (hold to blow into the microphone)
+    #[arg(long, default_value_t = String::from("F10"), help_heading = HOTKEY_OPTIONS_HEADING)]
+    hotkey_microphone: String,
+
+    /// Dump input trace hotkey (writes recent inputs and events to a text file next to the ROM)
+    #[arg(long, default_value_t = String::from("F8"), help_heading = HOTKEY_OPTIONS_HEADING)]
+    hotkey_dump_input_trace: String,
+
+    /// Next save state slot hotkey
+    #[arg(long, default_value_t = String::from("F4"), help_heading = HOTKEY_OPTIONS_HEADING)]
+    hotkey_next_save_state_slot: String,
+
+    /// Previous save state slot hotkey
+    #[arg(long, default_value_t = String::from("F3"), help_heading = HOTKEY_OPTIONS_HEADING)]
+    hotkey_previous_save_state_slot: String,
+
+    /// Undo load state hotkey (restores the state from before the most recent load)
+    #[arg(long, default_value_t = String::from("F7"), help_heading = HOTKEY_OPTIONS_HEADING)]
+    hotkey_undo_load_state: String,
+
+    /// Record input macro hotkey (press again to stop; the recorded sequence is logged)
+    #[arg(long, default_value_t = String::from("F11"), help_heading = HOTKEY_OPTIONS_HEADING)]
+    hotkey_record_input_macro: String,
+
+    /// Set practice loop point hotkey
+    #[arg(long, default_value_t = String::from("["), help_heading = HOTKEY_OPTIONS_HEADING)]
+    hotkey_set_practice_loop_point: String,
+
+    /// Restart practice attempt hotkey (reloads the practice loop point)
+    #[arg(long, default_value_t = String::from("]"), help_heading = HOTKEY_OPTIONS_HEADING)]
+    hotkey_restart_practice_attempt: String,
+
+    /// Clear practice loop point hotkey
+    #[arg(long, default_value_t = String::from("\\"), help_heading = HOTKEY_OPTIONS_HEADING)]
+    hotkey_clear_practice_loop_point: String,
+
+    /// Instead of opening a window, run every ROM listed in this file headlessly and write a report with a screenshot and stats for each; one path per line, blank lines and lines starting with '#' are ignored
+    #[arg(long, value_name = "LIST_PATH", help_heading = COMPARE_OPTIONS_HEADING)]
+    compare_list: Option<String>,
+
+    /// Number of frames to run each ROM for in comparison mode
+    #[arg(long, default_value_t = 600, help_heading = COMPARE_OPTIONS_HEADING)]
+    compare_frames: u64,
+
+    /// Directory to write the comparison report (report.json / report.html) and screenshots to
+    #[arg(long, default_value_t = String::from("comparison"), help_heading = COMPARE_OPTIONS_HEADING)]
+    compare_output_dir: String,
 }
 
 impl Args {
+    // Clap requires -f unless running in comparison mode, which sets the path for each ROM
+    fn rom_file_path(&self) -> &str {
+        self.file_path.as_deref().expect("ROM file path should be set")
+    }
+
     fn validate(&self) {
         assert!(
             self.joy_axis_deadzone >= 0,
@@ -453,10 +901,58 @@ impl Args {
             force_integer_height_scaling: self.force_integer_height_scaling,
             filter_mode: self.filter_mode,
             preprocess_shader: self.preprocess_shader,
+            color_blind_filter: self.color_blind_filter,
+            flash_reduction: self.flash_reduction,
+            magnifier_mode: self.magnifier_mode,
+            magnifier_corner: self.magnifier_corner,
             use_webgl2_limits: false,
+            pal_50hz_fullscreen: self.pal_50hz_fullscreen,
+            show_border: self.show_border,
         }
     }
 
+    fn audio_post_processing_config(&self) -> AudioPostProcessingConfig {
+        AudioPostProcessingConfig {
+            equalizer_enabled: self.audio_eq,
+            eq_low_frequency: self.eq_low_frequency,
+            eq_low_gain_db: self.eq_low_gain_db,
+            eq_mid_frequency: self.eq_mid_frequency,
+            eq_mid_gain_db: self.eq_mid_gain_db,
+            eq_mid_q: self.eq_mid_q,
+            eq_high_frequency: self.eq_high_frequency,
+            eq_high_gain_db: self.eq_high_gain_db,
+            stereo_widening: self.stereo_widening,
+            crossfeed: self.crossfeed,
+            soft_limiter_enabled: self.soft_limiter,
+        }
+    }
+
+    fn save_sync_config(&self) -> Option<SaveSyncConfig> {
+        let url = self.save_sync_url.clone()?;
+        let password = env::var(SAVE_SYNC_PASSWORD_VAR).unwrap_or_default();
+
+        Some(SaveSyncConfig {
+            protocol: self.save_sync_protocol,
+            url,
+            username: self.save_sync_username.clone(),
+            password: Secret(password),
+            s3_region: self.save_sync_s3_region.clone(),
+        })
+    }
+
+    fn discord_config(&self) -> Option<DiscordConfig> {
+        let application_id = self.discord_app_id.clone()?;
+
+        Some(DiscordConfig { application_id, show_game_title: self.discord_show_game_title })
+    }
+
+    fn remote_control_config(&self) -> Option<RemoteControlConfig> {
+        let port = self.remote_control_port?;
+        let token = env::var(REMOTE_CONTROL_TOKEN_VAR).unwrap_or_default();
+
+        Some(RemoteControlConfig { port, token: Secret(token) })
+    }
+
     fn smsgg_keyboard_config(&self) -> SmsGgInputConfig<KeyboardInput> {
         let default = SmsGgInputConfig::default();
         SmsGgInputConfig {
@@ -499,6 +995,8 @@ impl Args {
                 mode: self.input_p1_mode.as_ref().map(keyboard_input).or(default.p1.mode),
             },
             p2: default.p2,
+            p3: default.p3,
+            p4: default.p4,
         }
     }
 
@@ -515,33 +1013,108 @@ impl Args {
             fast_forward: Some(keyboard_input(&self.hotkey_fast_forward)),
             rewind: Some(keyboard_input(&self.hotkey_rewind)),
             open_debugger: Some(keyboard_input(&self.hotkey_open_debugger)),
+            music_dump: Some(keyboard_input(&self.hotkey_music_dump)),
+            microphone: Some(keyboard_input(&self.hotkey_microphone)),
+            dump_input_trace: Some(keyboard_input(&self.hotkey_dump_input_trace)),
+            next_save_state_slot: Some(keyboard_input(&self.hotkey_next_save_state_slot)),
+            previous_save_state_slot: Some(keyboard_input(&self.hotkey_previous_save_state_slot)),
+            undo_load_state: Some(keyboard_input(&self.hotkey_undo_load_state)),
+            record_input_macro: Some(keyboard_input(&self.hotkey_record_input_macro)),
+            set_practice_loop_point: Some(keyboard_input(&self.hotkey_set_practice_loop_point)),
+            restart_practice_attempt: Some(keyboard_input(&self.hotkey_restart_practice_attempt)),
+            clear_practice_loop_point: Some(keyboard_input(&self.hotkey_clear_practice_loop_point)),
+            // The CLI has no quick menu
+            open_quick_menu: None,
+            open_quick_menu_joystick: None,
         }
     }
 
-    fn common_config<KC, JC>(
+    fn common_config<KC, JC: SteamDeckInputDefaults>(
         &self,
         keyboard_inputs: KC,
         joystick_inputs: JC,
     ) -> CommonConfig<KC, JC> {
         assert_ne!(self.fast_forward_multiplier, 0, "Fast forward multiplier must not be 0");
 
-        CommonConfig {
-            rom_file_path: self.file_path.clone(),
+        let mut config = CommonConfig {
+            rom_file_path: self.rom_file_path().into(),
+            rom_patch_paths: self.patch.clone(),
             audio_sync: self.audio_sync,
             audio_device_queue_size: self.audio_device_queue_size,
             internal_audio_buffer_size: self.internal_audio_buffer_size,
             audio_sync_threshold: self.audio_sync_threshold,
+            audio_latency_ms: self.audio_latency_ms,
+            audio_underrun_recovery: self.audio_underrun_recovery,
             audio_gain_db: self.audio_gain_db,
+            audio_post_processing: self.audio_post_processing_config(),
+            audio_resampler_quality: self.audio_resampler_quality,
             window_size: self.window_size(),
             renderer_config: self.renderer_config(),
+            border_image_path: self.border_image.clone(),
             fast_forward_multiplier: self.fast_forward_multiplier,
             rewind_buffer_length_seconds: self.rewind_buffer_length_seconds,
+            input_trace_length_seconds: self.input_trace_length_seconds,
+            auto_save_state: self.auto_save_state,
+            practice_end_frame_count: self.practice_end_frames,
+            practice_end_condition: self.practice_end_condition.clone(),
             launch_in_fullscreen: self.fullscreen,
+            auto_frame_skip: self.auto_frame_skip,
+            max_frame_skip: self.max_frame_skip,
             keyboard_inputs,
             axis_deadzone: self.joy_axis_deadzone,
+            pause_on_controller_disconnect: self.pause_on_controller_disconnect,
             joystick_inputs,
             hotkeys: self.hotkey_config(),
+            input_macros: self.input_macro.clone(),
             hide_cursor_over_window: self.hide_cursor_over_window,
+            osd_messages: OsdMessages::default(),
+            gdb_port: self.gdb_port,
+            input_injection_port: self.input_injection_port,
+            remote_control: self.remote_control_config(),
+            symbol_file_path: self.symbol_file.clone(),
+            initial_ram_state: self.initial_ram_state,
+            rng_seed: self.rng_seed,
+            av_dump_path: self.av_dump.clone(),
+            save_sync: self.save_sync_config(),
+            discord: self.discord_config(),
+            steam_deck_mode: false,
+        };
+
+        if self.steam_deck_mode.is_active() {
+            log::info!("Running in Steam Deck mode");
+            config.apply_steam_deck_mode();
+        }
+
+        if self.low_power {
+            log::info!("Using low power profile");
+            config.apply_low_power_profile();
+        }
+
+        config
+    }
+
+    fn smsgg_config(&self) -> SmsGgConfig {
+        let keyboard_inputs = self.smsgg_keyboard_config();
+        let common = self.common_config(keyboard_inputs, SmsGgInputConfig::default());
+        SmsGgConfig {
+            common,
+            p1_controller_type: self.sms_p1_controller_type,
+            peripheral_config: SmsPeripheralConfig {
+                mouse_sensitivity: self.sms_peripheral_mouse_sensitivity,
+                stick_sensitivity: self.sms_peripheral_stick_sensitivity,
+            },
+            vdp_version: self.vdp_version,
+            psg_version: self.psg_version,
+            remove_sprite_limit: self.remove_sprite_limit,
+            sms_aspect_ratio: self.sms_aspect_ratio,
+            gg_aspect_ratio: self.gg_aspect_ratio,
+            sms_region: self.sms_region,
+            sms_crop_vertical_border: self.sms_crop_vertical_border,
+            sms_crop_left_border: self.sms_crop_left_border,
+            fm_sound_unit_enabled: self.sms_fm_unit_enabled,
+            overclock_z80: self.smsgg_overclock_z80,
+            link_transport: self.gg_link_transport,
+            link_tcp_address: self.gg_link_address.clone(),
         }
     }
 
@@ -552,15 +1125,138 @@ impl Args {
             common,
             forced_timing_mode: self.forced_timing_mode,
             forced_region: self.genesis_region,
+            region_spoof: self.genesis_region_spoof,
+            report_tmss: self.genesis_report_tmss,
             p1_controller_type: self.input_p1_type,
             p2_controller_type: GenesisControllerType::default(),
+            multitap: self.input_genesis_multitap,
+            serial_port: self.genesis_serial_port,
+            serial_transport: self.genesis_serial_transport,
+            serial_tcp_address: self.genesis_serial_address.clone(),
             aspect_ratio: self.genesis_aspect_ratio,
             adjust_aspect_ratio_in_2x_resolution: self.genesis_adjust_aspect_ratio,
             remove_sprite_limits: self.remove_sprite_limit,
             emulate_non_linear_vdp_dac: self.emulate_non_linear_vdp_dac,
             render_vertical_border: self.genesis_render_vertical_border,
             render_horizontal_border: self.genesis_render_horizontal_border,
+            widescreen: self.genesis_widescreen,
+            widescreen_patches_path: self.genesis_widescreen_patches.clone(),
+            hi_res_output: self.genesis_hi_res,
+            expanded_vram: self.genesis_128kb_vram,
+            tile_dump_directory: self.genesis_tile_dump_dir.clone(),
+            texture_pack_directory: self.genesis_texture_pack_dir.clone(),
             quantize_ym2612_output: self.quantize_ym2612_output,
+            lock_on_rom_path: self.lock_on_rom.clone(),
+            lock_on_patch_rom_path: self.lock_on_patch_rom.clone(),
+            deinterlace_mode: self.deinterlace_mode,
+        }
+    }
+
+    fn sega_cd_config(&self) -> SegaCdConfig {
+        SegaCdConfig {
+            genesis: self.genesis_config(),
+            bios_file_path: self.bios_path.clone(),
+            enable_ram_cartridge: self.enable_ram_cartridge,
+            fast_boot: self.scd_fast_boot,
+            run_without_disc: self.scd_no_disc,
+            mode_1_cartridge_path: self.scd_mode_1_cartridge_path.clone(),
+        }
+    }
+
+    fn nes_config(&self) -> NesConfig {
+        NesConfig {
+            common: self.common_config(NesInputConfig::default(), NesInputConfig::default()),
+            forced_timing_mode: self.nes_timing_mode.or(self.forced_timing_mode.map(Into::into)),
+            aspect_ratio: self.nes_aspect_ratio,
+            overscan: Overscan {
+                top: self.overscan_top,
+                bottom: self.overscan_bottom,
+                left: self.overscan_left,
+                right: self.overscan_right,
+            },
+            emulate_ntsc_overscan: self.nes_ntsc_overscan,
+            remove_sprite_limit: self.remove_sprite_limit,
+            pal_black_border: self.nes_pal_black_border,
+            silence_ultrasonic_triangle_output: self.nes_silence_ultrasonic_triangle,
+            audio_refresh_rate_adjustment: self.nes_audio_60hz_hack,
+            allow_opposing_joypad_inputs: self.nes_allow_opposing_inputs,
+            forced_expansion_device: self.nes_expansion_device,
+        }
+    }
+
+    fn snes_config(&self) -> SnesConfig {
+        SnesConfig {
+            common: self.common_config(SnesInputConfig::default(), SnesInputConfig::default()),
+            p2_controller_type: self.snes_p2_controller_type,
+            super_scope_config: SuperScopeConfig::default(),
+            forced_timing_mode: self.forced_timing_mode,
+            aspect_ratio: self.snes_aspect_ratio,
+            audio_60hz_hack: self.snes_audio_60hz_hack,
+            gsu_overclock_factor: self.gsu_overclock_factor,
+            enhancements: SnesEnhancements {
+                mode_7_scale: self.snes_mode7_scale,
+                mode_7_perspective_correction: self.snes_mode7_perspective_correction,
+            },
+            dsp1_rom_path: self.dsp1_rom_path.clone(),
+            dsp2_rom_path: self.dsp2_rom_path.clone(),
+            dsp3_rom_path: self.dsp3_rom_path.clone(),
+            dsp4_rom_path: self.dsp4_rom_path.clone(),
+            st010_rom_path: self.st010_rom_path.clone(),
+            st011_rom_path: self.st011_rom_path.clone(),
+            deinterlace_mode: self.deinterlace_mode,
+        }
+    }
+
+    fn gb_config(&self) -> GameBoyConfig {
+        GameBoyConfig {
+            common: self
+                .common_config(GameBoyInputConfig::default(), GameBoyInputConfig::default()),
+            force_dmg_mode: self.force_dmg_mode,
+            pretend_to_be_gba: self.pretend_to_be_gba,
+            aspect_ratio: self.gb_aspect_ratio,
+            gb_palette: self.gb_palette,
+            gbc_color_correction: self.gbc_color_correction,
+            audio_60hz_hack: self.gb_audio_60hz_hack,
+        }
+    }
+
+    fn pce_config(&self) -> PceConfig {
+        PceConfig {
+            common: self.common_config(PceInputConfig::default(), PceInputConfig::default()),
+            region: self.pce_region,
+            aspect_ratio: self.pce_aspect_ratio,
+        }
+    }
+
+    fn gba_config(&self) -> GbaConfig {
+        GbaConfig {
+            common: self.common_config(GbaInputConfig::default(), GbaInputConfig::default()),
+            bios_file_path: self.gba_bios_path.clone(),
+            aspect_ratio: self.gba_aspect_ratio,
+            skip_bios_intro: self.gba_skip_bios_intro,
+        }
+    }
+
+    fn atari2600_config(&self) -> Atari2600Config {
+        Atari2600Config {
+            common: self
+                .common_config(Atari2600InputConfig::default(), Atari2600InputConfig::default()),
+            aspect_ratio: self.atari2600_aspect_ratio,
+            left_difficulty: self.atari2600_left_difficulty,
+            right_difficulty: self.atari2600_right_difficulty,
+            black_and_white: self.atari2600_black_and_white,
+        }
+    }
+
+    fn colecovision_config(&self) -> ColecoVisionConfig {
+        ColecoVisionConfig {
+            common: self.common_config(
+                ColecoVisionInputConfig::default(),
+                ColecoVisionInputConfig::default(),
+            ),
+            bios_file_path: self.colecovision_bios_path.clone(),
+            aspect_ratio: self.colecovision_aspect_ratio,
+            remove_sprite_limit: self.remove_sprite_limit,
         }
     }
 }
@@ -569,156 +1265,243 @@ fn keyboard_input(s: &String) -> KeyboardInput {
     KeyboardInput { keycode: s.into() }
 }
 
-fn main() -> anyhow::Result<()> {
-    env_logger::Builder::from_env(
+fn parse_input_macro(s: &str) -> Result<InputMacroConfig, String> {
+    let (key, sequence) =
+        s.split_once('=').ok_or_else(|| format!("expected <key>=<sequence>, got '{s}'"))?;
+    Ok(InputMacroConfig {
+        keyboard_trigger: Some(KeyboardInput { keycode: key.trim().into() }),
+        joystick_trigger: None,
+        sequence: sequence.trim().into(),
+    })
+}
+
+fn init_logger(directives: &[LogDirective]) {
+    let base = env_logger::Builder::from_env(
         Env::default().default_filter_or("info,wgpu_core=warn,wgpu_hal=warn"),
     )
-    .init();
+    .build();
+    let base_max_level = base.filter();
+    let unfiltered = env_logger::Builder::new().filter_level(LevelFilter::Trace).build();
+
+    SubsystemLogger::new(Box::new(base), base_max_level, Box::new(unfiltered))
+        .install()
+        .expect("init_logger should only be called once");
+
+    for &directive in directives {
+        directive.apply();
+    }
+}
 
-    let args = Args::parse();
+fn main() -> anyhow::Result<()> {
+    let mut args = Args::parse();
+    init_logger(&args.log_directives);
     args.validate();
 
-    let hardware = args.hardware.unwrap_or_else(|| {
-        let file_ext = Path::new(&args.file_path).extension().and_then(OsStr::to_str).unwrap_or("");
-        match file_ext {
-            "sms" | "gg" => Hardware::MasterSystem,
-            "md" | "bin" => Hardware::Genesis,
-            "cue" | "chd" => Hardware::SegaCd,
-            "nes" => Hardware::Nes,
-            "sfc" | "smc" => Hardware::Snes,
-            "gb" | "gbc" => Hardware::GameBoy,
-            _ => {
-                log::warn!("Unrecognized file extension: '{file_ext}' defaulting to Genesis");
-                Hardware::Genesis
+    AppPaths::resolve(args.portable).install()?;
+
+    if let Some(list_path) = args.compare_list.clone() {
+        return compare::run(&mut args, Path::new(&list_path));
+    }
+
+    loop {
+        let hardware = args.hardware.unwrap_or_else(|| detect_hardware(args.rom_file_path()));
+
+        log::info!("Running with hardware {hardware}");
+
+        let next_rom = match hardware {
+            Hardware::MasterSystem => run_sms(&args),
+            Hardware::Genesis => run_genesis(&args),
+            Hardware::SegaCd => run_sega_cd(&args),
+            Hardware::Pico => run_pico(&args),
+            Hardware::Nes => run_nes(&args),
+            Hardware::Snes => run_snes(&args),
+            Hardware::GameBoy => run_gb(&args),
+            Hardware::PcEngine => run_pce(&args),
+            Hardware::GameBoyAdvance => run_gba(&args),
+            Hardware::Atari2600 => run_atari2600(&args),
+            Hardware::ColecoVision => run_colecovision(&args),
+        }?;
+
+        let Some(file_path) = next_rom else { return Ok(()) };
+
+        log::info!("Remote control client requested loading '{file_path}'");
+
+        // Patches and symbols are specific to the previous ROM
+        args.file_path = Some(file_path);
+        args.hardware = None;
+        args.patch.clear();
+        args.symbol_file = None;
+    }
+}
+
+fn detect_hardware(file_path: &str) -> Hardware {
+    let file_ext = Path::new(file_path).extension().and_then(OsStr::to_str).unwrap_or("");
+    match file_ext {
+        "sms" | "gg" => Hardware::MasterSystem,
+        // Pico ROMs use the same file extensions as Genesis ROMs; check the header
+        "md" | "bin"
+            if fs::read(file_path).is_ok_and(|rom| genesis_core::pico::is_pico_rom(&rom)) =>
+        {
+            Hardware::Pico
+        }
+        "md" | "bin" => Hardware::Genesis,
+        "cue" | "chd" | "m3u" => Hardware::SegaCd,
+        "pco" => Hardware::Pico,
+        "nes" => Hardware::Nes,
+        "sfc" | "smc" | "spc" => Hardware::Snes,
+        "gb" | "gbc" => Hardware::GameBoy,
+        "pce" => Hardware::PcEngine,
+        "gba" => Hardware::GameBoyAdvance,
+        "a26" => Hardware::Atari2600,
+        "col" => Hardware::ColecoVision,
+        _ => {
+            log::warn!("Unrecognized file extension: '{file_ext}' defaulting to Genesis");
+            Hardware::Genesis
+        }
+    }
+}
+
+// Run the emulator until it exits, evaluating to the path of the ROM to load next if a remote
+// control client requested one
+macro_rules! run_emulator {
+    ($emulator:expr) => {{
+        let mut emulator = $emulator;
+        loop {
+            match emulator.render_frame()? {
+                // OpenQuickMenu is never returned because the CLI does not bind its hotkey
+                NativeTickEffect::None | NativeTickEffect::OpenQuickMenu => {}
+                NativeTickEffect::Exit => break None,
+                NativeTickEffect::LoadRom(file_path) => break Some(file_path),
             }
         }
-    });
+    }};
+}
+
+fn run_sms(args: &Args) -> anyhow::Result<Option<String>> {
+    let config = args.smsgg_config();
+
+    Ok(run_emulator!(jgenesis_native_driver::create_smsgg(config.into())?))
+}
 
-    log::info!("Running with hardware {hardware}");
+fn run_genesis(args: &Args) -> anyhow::Result<Option<String>> {
+    if args.enable_msu_md {
+        if let Some(disc_path) = msumd::find_msu_md_disc(Path::new(args.rom_file_path())) {
+            if args.bios_path.is_some() {
+                return run_msu_md(args, &disc_path);
+            }
 
-    match hardware {
-        Hardware::MasterSystem => run_sms(args),
-        Hardware::Genesis => run_genesis(args),
-        Hardware::SegaCd => run_sega_cd(args),
-        Hardware::Nes => run_nes(args),
-        Hardware::Snes => run_snes(args),
-        Hardware::GameBoy => run_gb(args),
+            log::warn!(
+                "Found MSU-MD track image '{}' but no Sega CD BIOS was provided (-b / --bios-file-path); running as a plain Genesis game",
+                disc_path.display()
+            );
+        }
     }
+
+    let config = args.genesis_config();
+
+    Ok(run_emulator!(jgenesis_native_driver::create_genesis(config.into())?))
 }
 
-fn run_sms(args: Args) -> anyhow::Result<()> {
-    let keyboard_inputs = args.smsgg_keyboard_config();
-    let common = args.common_config(keyboard_inputs, SmsGgInputConfig::default());
-    let config = SmsGgConfig {
-        common,
-        vdp_version: args.vdp_version,
-        psg_version: args.psg_version,
-        remove_sprite_limit: args.remove_sprite_limit,
-        sms_aspect_ratio: args.sms_aspect_ratio,
-        gg_aspect_ratio: args.gg_aspect_ratio,
-        sms_region: args.sms_region,
-        sms_crop_vertical_border: args.sms_crop_vertical_border,
-        sms_crop_left_border: args.sms_crop_left_border,
-        fm_sound_unit_enabled: args.sms_fm_unit_enabled,
-        overclock_z80: args.smsgg_overclock_z80,
-    };
+// MSU-MD games boot from the cartridge with the Sega CD attached, so they run on the Sega CD core
+// in Mode 1 with the track image loaded as the disc
+fn run_msu_md(args: &Args, disc_path: &Path) -> anyhow::Result<Option<String>> {
+    log::info!("Running as an MSU-MD game with track image '{}'", disc_path.display());
 
-    let mut emulator = jgenesis_native_driver::create_smsgg(config.into())?;
-    while emulator.render_frame()? != NativeTickEffect::Exit {}
+    let mut genesis = args.genesis_config();
+    genesis.common.rom_file_path = disc_path.to_string_lossy().into_owned();
 
-    Ok(())
+    let config = SegaCdConfig {
+        genesis,
+        bios_file_path: args.bios_path.clone(),
+        enable_ram_cartridge: args.enable_ram_cartridge,
+        fast_boot: args.scd_fast_boot,
+        run_without_disc: false,
+        mode_1_cartridge_path: Some(args.rom_file_path().into()),
+    };
+
+    Ok(run_emulator!(jgenesis_native_driver::create_sega_cd(config.into())?))
 }
 
-fn run_genesis(args: Args) -> anyhow::Result<()> {
+fn run_pico(args: &Args) -> anyhow::Result<Option<String>> {
     let config = args.genesis_config();
 
-    let mut emulator = jgenesis_native_driver::create_genesis(config.into())?;
-    while emulator.render_frame()? != NativeTickEffect::Exit {}
-
-    Ok(())
+    Ok(run_emulator!(jgenesis_native_driver::create_pico(config.into())?))
 }
 
-fn run_sega_cd(args: Args) -> anyhow::Result<()> {
-    let bios_file_path = args.bios_path.clone().unwrap_or_else(|| {
+fn run_sega_cd(args: &Args) -> anyhow::Result<Option<String>> {
+    if args.bios_path.is_none() {
         eprintln!(
             "ERROR: BIOS file path (-b / --bios-file-path) is required for Sega CD emulation"
         );
         process::exit(1);
-    });
+    }
 
-    let config = SegaCdConfig {
-        genesis: args.genesis_config(),
-        bios_file_path: Some(bios_file_path),
-        enable_ram_cartridge: args.enable_ram_cartridge,
-        run_without_disc: args.scd_no_disc,
-    };
+    let config = args.sega_cd_config();
+
+    Ok(run_emulator!(jgenesis_native_driver::create_sega_cd(config.into())?))
+}
 
-    let mut emulator = jgenesis_native_driver::create_sega_cd(config.into())?;
-    while emulator.render_frame()? != NativeTickEffect::Exit {}
+fn run_nes(args: &Args) -> anyhow::Result<Option<String>> {
+    let config = args.nes_config();
 
-    Ok(())
+    Ok(run_emulator!(jgenesis_native_driver::create_nes(config.into())?))
 }
 
-fn run_nes(args: Args) -> anyhow::Result<()> {
-    let config = NesConfig {
-        common: args.common_config(NesInputConfig::default(), NesInputConfig::default()),
-        forced_timing_mode: args.forced_timing_mode,
-        aspect_ratio: args.nes_aspect_ratio,
-        overscan: Overscan {
-            top: args.overscan_top,
-            bottom: args.overscan_bottom,
-            left: args.overscan_left,
-            right: args.overscan_right,
-        },
-        remove_sprite_limit: args.remove_sprite_limit,
-        pal_black_border: args.nes_pal_black_border,
-        silence_ultrasonic_triangle_output: args.nes_silence_ultrasonic_triangle,
-        audio_refresh_rate_adjustment: args.nes_audio_60hz_hack,
-        allow_opposing_joypad_inputs: args.nes_allow_opposing_inputs,
-    };
+// SPC files are played back using only the SNES APU
+fn is_spc_file(file_path: &str) -> bool {
+    Path::new(file_path).extension().and_then(OsStr::to_str) == Some("spc")
+}
+
+fn run_snes(args: &Args) -> anyhow::Result<Option<String>> {
+    let config = args.snes_config();
 
-    let mut emulator = jgenesis_native_driver::create_nes(config.into())?;
-    while emulator.render_frame()? != NativeTickEffect::Exit {}
+    if is_spc_file(&config.common.rom_file_path) {
+        return Ok(run_emulator!(jgenesis_native_driver::create_spc(config.into())?));
+    }
 
-    Ok(())
+    Ok(run_emulator!(jgenesis_native_driver::create_snes(config.into())?))
 }
 
-fn run_snes(args: Args) -> anyhow::Result<()> {
-    let config = SnesConfig {
-        common: args.common_config(SnesInputConfig::default(), SnesInputConfig::default()),
-        p2_controller_type: args.snes_p2_controller_type,
-        super_scope_config: SuperScopeConfig::default(),
-        forced_timing_mode: args.forced_timing_mode,
-        aspect_ratio: args.snes_aspect_ratio,
-        audio_60hz_hack: args.snes_audio_60hz_hack,
-        gsu_overclock_factor: args.gsu_overclock_factor,
-        dsp1_rom_path: args.dsp1_rom_path,
-        dsp2_rom_path: args.dsp2_rom_path,
-        dsp3_rom_path: args.dsp3_rom_path,
-        dsp4_rom_path: args.dsp4_rom_path,
-        st010_rom_path: args.st010_rom_path,
-        st011_rom_path: args.st011_rom_path,
-    };
+fn run_gb(args: &Args) -> anyhow::Result<Option<String>> {
+    let config = args.gb_config();
+
+    Ok(run_emulator!(jgenesis_native_driver::create_gb(config.into())?))
+}
 
-    let mut emulator = jgenesis_native_driver::create_snes(config.into())?;
-    while emulator.render_frame()? != NativeTickEffect::Exit {}
+fn run_pce(args: &Args) -> anyhow::Result<Option<String>> {
+    let config = args.pce_config();
 
-    Ok(())
+    Ok(run_emulator!(jgenesis_native_driver::create_pce(config.into())?))
 }
 
-fn run_gb(args: Args) -> anyhow::Result<()> {
-    let config = GameBoyConfig {
-        common: args.common_config(GameBoyInputConfig::default(), GameBoyInputConfig::default()),
-        force_dmg_mode: args.force_dmg_mode,
-        pretend_to_be_gba: args.pretend_to_be_gba,
-        aspect_ratio: args.gb_aspect_ratio,
-        gb_palette: args.gb_palette,
-        gbc_color_correction: args.gbc_color_correction,
-        audio_60hz_hack: args.gb_audio_60hz_hack,
-    };
+fn run_gba(args: &Args) -> anyhow::Result<Option<String>> {
+    if args.gba_bios_path.is_none() {
+        eprintln!(
+            "ERROR: BIOS file path (--gba-bios-path) is required for Game Boy Advance emulation"
+        );
+        process::exit(1);
+    }
+
+    let config = args.gba_config();
+
+    Ok(run_emulator!(jgenesis_native_driver::create_gba(config.into())?))
+}
+
+fn run_atari2600(args: &Args) -> anyhow::Result<Option<String>> {
+    let config = args.atari2600_config();
+
+    Ok(run_emulator!(jgenesis_native_driver::create_atari2600(config.into())?))
+}
+
+fn run_colecovision(args: &Args) -> anyhow::Result<Option<String>> {
+    if args.colecovision_bios_path.is_none() {
+        eprintln!(
+            "ERROR: BIOS file path (--colecovision-bios-path) is required for ColecoVision emulation"
+        );
+        process::exit(1);
+    }
 
-    let mut emulator = jgenesis_native_driver::create_gb(config.into())?;
-    while emulator.render_frame()? != NativeTickEffect::Exit {}
+    let config = args.colecovision_config();
 
-    Ok(())
+    Ok(run_emulator!(jgenesis_native_driver::create_colecovision(config.into())?))
 }
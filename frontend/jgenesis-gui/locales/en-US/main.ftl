@@ -0,0 +1,577 @@
+# English (US) strings for the jgenesis GUI. This is the reference translation; every message used
+# by the GUI must be defined here.
+
+## Menu bar
+
+menu-file = File
+menu-open-recent = Open Recent
+menu-open = Open
+menu-big-picture = Big Picture Mode
+menu-export-play-stats = Export Play Statistics…
+menu-export-compatibility = Export Compatibility List…
+menu-quit = Quit
+
+menu-emulation = Emulation
+menu-open-memory-viewer = Open Memory Viewer
+menu-soft-reset = Soft Reset
+menu-hard-reset = Hard Reset
+menu-screenshot = Take Screenshot
+menu-power-off = Power Off
+menu-remove-disc = Remove Disc
+menu-change-disc = Change Disc
+menu-next-disc = Next Disc (Disc { $disc } of { $count })
+menu-scan-barcode = Scan Barcode
+
+menu-settings = Settings
+menu-interface = Interface
+menu-video = Video
+menu-audio = Audio
+menu-general = General
+menu-input = Input
+menu-keyboard = Keyboard
+menu-gamepad = Gamepad
+menu-peripherals = Peripherals
+menu-hotkeys = Hotkeys
+
+menu-help = Help
+menu-about = About
+
+## ROM list
+
+romlist-configure-search-dir = Configure ROM search directory
+romlist-header-name = Name
+romlist-header-console = Console
+romlist-header-size = Size
+romlist-header-play-time = Play Time
+romlist-header-last-played = Last Played
+romlist-filter-hint = Filter by name
+romlist-filter-clear = Clear
+romlist-auto-save-state = Auto save state on exit
+romlist-manage-patches = Manage patches…
+romlist-compatibility = Compatibility
+compatibility-untested = Untested
+compatibility-works = Works
+compatibility-minor-issues = Minor issues
+compatibility-broken = Broken
+
+## Interface settings
+
+interface-window-title = UI Settings
+interface-language = Language
+interface-hide-cursor = Hide mouse cursor over emulator window
+interface-auto-save-state = Auto save state on exit
+interface-auto-save-state-tooltip = Save state when closing a game and resume from it the next time the same game is launched. Can be overridden per game by right-clicking the game in the ROM list
+interface-steam-deck-mode = Steam Deck mode
+interface-steam-deck-mode-tooltip = Fullscreen with integer scaling, player 1 mapped to the built-in controls if no gamepad inputs are set, power-efficient frame pacing, and the on-screen keyboard for text fields
+interface-steam-deck-mode-auto = Auto-detect
+interface-steam-deck-mode-enabled = Enabled
+interface-steam-deck-mode-disabled = Disabled
+interface-rom-search-dirs = ROM search directories
+interface-remove = Remove
+interface-add = Add
+interface-thumbnails = Show thumbnails in the big picture library
+interface-thumbnails-tooltip = Thumbnails are downloaded from libretro-thumbnails by console and ROM file name and cached locally. ROMs named after No-Intro and Redump sets match best
+interface-thumbnails-box-art = Box art
+interface-thumbnails-title-screen = Title screen
+interface-thumbnails-screenshot = Screenshot
+interface-thumbnails-source = Thumbnail server URL
+interface-thumbnails-offline-only = Offline only
+interface-thumbnails-offline-only-tooltip = Only show thumbnails that were already downloaded
+interface-save-sync = Sync save files to a remote server
+interface-save-sync-tooltip = Save files are synced when a game is launched and when it is closed. If both copies changed, the newer one is kept and the other is saved next to the ROM with a .sync-backup extension
+interface-save-sync-url = URL
+interface-save-sync-username = Username / access key ID
+interface-save-sync-password = Password / secret key
+interface-save-sync-s3-region = S3 region
+interface-discord = Show activity in Discord Rich Presence
+interface-discord-tooltip = Shows the running system and elapsed time on your Discord profile. Requires the Discord desktop app to be running
+interface-discord-application-id = Discord application ID
+interface-discord-show-game-title = Also show the game title
+
+## Migration from the old file layout
+
+migration-window-title = Move Settings
+migration-description = Settings from an older version were found in the current directory. Move them to the new location?
+migration-move = Move
+migration-skip = Not now
+
+## Patch manager
+
+patches-window-title = Patches
+patches-label = Translation / hack:
+patches-label-hint = e.g. English translation v1.1
+patches-order = Patches are applied from top to bottom
+patches-none = No patches
+patches-move-up = Up
+patches-move-down = Down
+patches-remove = Remove
+patches-add = Add patches
+patches-next-launch = Changes take effect the next time the game is launched
+
+## Barcode reader
+
+barcode-window-title = Datach Barcode Reader
+barcode-instructions = Enter an EAN-13 or EAN-8 barcode:
+barcode-scan = Scan
+
+## About / errors
+
+about-window-title = About
+about-version = Version: { $version }
+about-source-code = Source code:
+
+error-window-title = Emulator Error
+error-emulator-terminated = Emulator terminated with error: { $error }
+
+## Dialogs
+
+dialog-supported-rom-files = Supported ROM files
+dialog-patch-files = IPS/BPS patches
+dialog-csv-files = CSV files
+dialog-markdown-files = Markdown files
+input-config-window-title = Input Configuration
+input-config-instructions = Use the emulator window to configure input
+
+## Big picture mode
+
+bigpicture-library = Library
+bigpicture-exit = Exit Big Picture
+bigpicture-no-roms = No ROMs found in ROM search directories
+bigpicture-quick-menu = Quick Menu
+bigpicture-resume = Resume
+bigpicture-save-state = Save State
+bigpicture-load-state = Load State
+bigpicture-undo-load-state = Undo Load State
+bigpicture-quit-to-library = Quit to Library
+
+## Shared option labels
+
+option-none = None
+option-auto = Auto
+option-enabled = Enabled
+option-disabled = Disabled
+option-clear = Clear
+value-none = <None>
+timing-mode = Timing / display mode
+timing-ntsc = NTSC
+timing-pal = PAL
+region = Region
+region-americas = Americas
+region-japan = Japan
+region-europe = Europe
+link-not-connected = Not connected
+link-tcp-client = TCP client
+link-tcp-server = TCP server
+link-address = Address
+aspect-ratio = Aspect ratio
+aspect-ratio-ntsc = NTSC
+aspect-ratio-pal = PAL
+aspect-ratio-square-pixels = Square pixels
+aspect-ratio-square-pixels-tooltip = 1:1 pixel aspect ratio
+aspect-ratio-stretched = Stretched
+aspect-ratio-stretched-tooltip = Stretch image to fill the screen
+remove-sprite-limits = Remove sprite-per-scanline and sprite-pixel-per-scanline limits
+remove-sprite-limits-tooltip = Can reduce sprite flickering, but can also cause visual glitches
+render-vertical-border = Render vertical border
+render-horizontal-border = Render horizontal border
+aspect-ratio-8-7-tooltip = 8:7 pixel aspect ratio
+aspect-ratio-11-8-tooltip = 11:8 pixel aspect ratio
+aspect-ratio-stretched-window-tooltip = Stretched to fill the window
+audio-60hz-hack = Enable audio 60Hz/50Hz hack
+audio-60hz-hack-tooltip = Enabling this option will very slightly increase the audio signal frequency to time to 60Hz NTSC / 50Hz PAL
+remove-sprite-limit = Remove sprite-per-scanline limit
+
+## General video settings
+
+video-window-title = General Video Settings
+video-launch-fullscreen = Launch in fullscreen
+video-wgpu-backend = wgpu backend
+video-wgpu-vulkan = Vulkan
+video-wgpu-directx12 = DirectX 12
+video-wgpu-opengl = OpenGL
+video-vsync-mode = VSync mode
+video-vsync-fast = Fast
+video-filter-mode = Filter mode
+video-filter-nearest = Nearest neighbor
+video-filter-linear = Linear interpolation
+video-filter-sharp-bilinear = Sharp bilinear
+video-filter-sharp-bilinear-tooltip = Keeps pixels sharp while avoiding uneven pixel sizes at non-integer scales; prescaling is not needed with this mode
+video-preprocess-shader = Preprocess shader
+video-shader-blur-2px = Horizontal blur (2px)
+video-shader-blur-3px = Horizontal blur (3px)
+video-shader-blur-snes-adaptive = Horizontal blur (SNES adaptive)
+video-shader-blur-snes-adaptive-tooltip = Always maintains the effect of blurring 3px horizontally at 512px horizontal resolution
+video-shader-anti-dither-weak = Anti-dither (conservative)
+video-shader-anti-dither-strong = Anti-dither (aggressive)
+video-scanlines = Scanlines
+video-scanlines-dim = Dim
+video-scanlines-black = Black
+video-color-blind-filter = Color blindness correction
+video-color-blind-protanopia = Protanopia (red)
+video-color-blind-deuteranopia = Deuteranopia (green)
+video-color-blind-tritanopia = Tritanopia (blue)
+video-flash-reduction = Reduce flashing
+video-flash-reduction-tooltip = Limits how quickly the average brightness of the screen can change, which softens full-screen flashes. Fast brightness changes may briefly leave a faint trail
+video-magnifier = Magnifier
+video-magnifier-tooltip = Shows a zoomed view of the area under the mouse cursor
+video-magnifier-pip = Picture-in-picture
+video-magnifier-full-screen = Full screen
+video-magnifier-corner = Corner:
+video-corner-top-left = Top left
+video-corner-top-right = Top right
+video-corner-bottom-left = Bottom left
+video-corner-bottom-right = Bottom right
+video-prescale-factor = Prescale factor
+video-prescale-factor-invalid = Prescale factor must be a non-negative integer <= { $max }
+video-integer-height-scaling = Force integer height scaling
+video-integer-height-scaling-tooltip = Display area will be the largest possible integer multiple of native height that preserves aspect ratio
+video-pal-50hz-fullscreen = Use 50Hz-compatible refresh rate for PAL games in fullscreen
+video-pal-50hz-fullscreen-tooltip = Switches the display to a refresh rate that is a multiple of 50Hz (e.g. 100Hz or 200Hz) when running PAL games in fullscreen, which avoids judder when VSync is enabled
+video-show-border = Show border
+video-show-border-tooltip = Render a border image around the game. If no custom image is set, the system's default border is used (currently only Game Gear has one)
+video-custom-border-image = Custom border image
+video-custom-border-image-tooltip = PNG image; a fully transparent region at the center of the image is used as the game screen
+video-auto-frame-skip = Auto frame skip
+video-auto-frame-skip-tooltip = Skip rendering frames when the host is unable to keep up with emulation speed. Frames are still emulated, so audio is unaffected
+video-max-frame-skip = Max consecutive skipped frames
+video-low-power-profile = Low power profile
+video-low-power-profile-tooltip = For Raspberry Pi-class hardware. Overrides the settings above to use OpenGL with GLES-compatible limits, no prescaling, no scanlines or shaders, and auto frame skip, and uses low quality audio resampling
+video-scanlines-warning = Integer height scaling + even-numbered prescale factor strongly recommended when scanlines are enabled
+video-border-default = <Default>
+
+## General audio settings
+
+audio-window-title = General Audio Settings
+audio-sync = Audio sync enabled
+audio-underrun-recovery = Audio underrun recovery
+audio-underrun-recovery-tooltip = Briefly stretch audio when the audio device runs out of samples instead of letting it crackle
+audio-latency = Audio latency
+audio-latency-manual = Manual
+audio-latency-ms = { $ms } ms
+audio-latency-tooltip = Setting a latency target configures the audio device queue size, internal audio buffer size, and audio sync threshold automatically, and enables dynamic rate control when audio sync is disabled
+audio-device-queue-size = Audio device queue size (samples)
+audio-device-queue-size-invalid = Audio device queue size must be a power of 2 and must be at least { $min }
+audio-internal-buffer-size = Internal audio buffer size (samples)
+audio-internal-buffer-size-invalid = Internal audio buffer size must be a positive integer
+audio-sync-threshold = Audio sync threshold (bytes)
+audio-sync-threshold-invalid = Audio sync threshold must be at least { $min }
+audio-gain = Audio gain (dB) (+/-)
+audio-gain-invalid = Audio gain must be a finite decimal number
+audio-resampler-quality = Resampler quality
+audio-resampler-high = High
+audio-resampler-low = Low
+audio-resampler-quality-tooltip = Low quality averages source samples instead of applying a low-pass filter, which is much cheaper but lets some aliasing through
+audio-post-processing = Post-processing
+audio-equalizer = Equalizer enabled
+audio-eq-low-frequency = Low shelf frequency (Hz)
+audio-eq-low-gain = Low shelf gain (dB)
+audio-eq-mid-frequency = Mid frequency (Hz)
+audio-eq-mid-gain = Mid gain (dB)
+audio-eq-mid-q = Mid Q
+audio-eq-high-frequency = High shelf frequency (Hz)
+audio-eq-high-gain = High shelf gain (dB)
+audio-stereo-widening = Stereo widening
+audio-stereo-widening-tooltip = Also widens mono audio by mixing in a short delayed copy
+audio-crossfeed = Headphone crossfeed
+audio-crossfeed-tooltip = Mixes some of each channel into the other to reduce fatigue from hard-panned audio on headphones
+audio-soft-limiter = Soft limiter
+audio-soft-limiter-tooltip = Smoothly reduces volume when audio would otherwise clip
+
+## Deinterlacing
+
+video-deinterlacing = Deinterlacing
+video-deinterlacing-tooltip = Only applies to interlaced double resolution output
+video-deinterlace-weave = Weave
+video-deinterlace-weave-tooltip = Display both fields in every frame
+video-deinterlace-bob = Bob
+video-deinterlace-bob-tooltip = Line double one field per frame, alternating fields like a CRT
+video-deinterlace-blend = Blend
+video-deinterlace-blend-tooltip = Average the two fields together
+video-deinterlace-motion-adaptive = Motion adaptive
+video-deinterlace-motion-adaptive-tooltip = Weave where the image is still and bob where it is moving
+
+## Genesis settings
+
+genesis-general-window-title = Genesis General Settings
+genesis-region-spoof = Region reported to game
+genesis-region-spoof-tooltip = Lets region-locked games run at a different speed than their home region
+genesis-region-spoof-hardware = Hardware
+genesis-region-spoof-hardware-tooltip = Report the hardware region and the actual timing mode
+genesis-region-spoof-auto-tooltip = Report the hardware region regardless of timing mode
+genesis-report-tmss = Report a console with TMSS
+genesis-expanded-vram = 128KB VRAM
+genesis-expanded-vram-tooltip = Install 128KB of VRAM like development hardware and the Tera Drive. Only needed for homebrew and prototypes that use 128KB mode
+genesis-lock-on = Sonic & Knuckles lock-on cartridge
+genesis-lock-on-rom = Locked-on ROM
+genesis-lock-on-patch-rom = Patch ROM (Knuckles in Sonic 2)
+genesis-serial-port = Serial port
+genesis-serial-port-tooltip = For homebrew that communicates over a controller port in serial mode. The Mega Modem is not emulated
+genesis-serial-port-1 = Port 1
+genesis-serial-port-2 = Port 2
+genesis-serial-port-ext = EXT
+genesis-scd-bios-path = Sega CD BIOS path
+genesis-scd-ram-cartridge = Enable Sega CD RAM cartridge
+genesis-scd-fast-boot = Skip Sega CD BIOS intro
+genesis-scd-fast-boot-tooltip = Takes effect the next time a disc is loaded or the console is hard reset
+genesis-msu-md = Enable MSU-MD
+genesis-msu-md-tooltip = Run Genesis ROMs with the Sega CD attached when a CUE/CHD file with the same name is present
+genesis-aspect-ratio-ntsc-tooltip = 32:35 pixel aspect ratio in 320px mode, 8:7 in 256px mode
+genesis-aspect-ratio-pal-tooltip = 11:10 pixel aspect ratio in 320px mode, 11:8 in 256px mode
+genesis-adjust-aspect-ratio-2x = Automatically double pixel aspect ratio in double vertical resolution mode
+genesis-non-linear-dac = Emulate the VDP's non-linear color DAC
+genesis-non-linear-dac-tooltip = Tends to brighten darker colors and darken brighter colors
+genesis-widescreen = Widescreen (H40 mode only)
+genesis-widescreen-tooltip = Render 52 extra pixels on each side of the screen. Most games need a widescreen patch to avoid glitches at the edges. Has no effect if the horizontal border is rendered
+genesis-widescreen-patches = Widescreen patch file
+genesis-hi-res-output = 2x resolution output (enhancement)
+genesis-hi-res-output-tooltip = Output frames at double resolution, stretching 256px lines to match 320px lines. Keeps the image size stable in games that switch between display modes or into interlaced mode. Does not affect emulation
+genesis-tile-textures = Tile textures (experimental)
+genesis-tile-textures-tooltip = Tiles are saved and loaded as 8x8 PNG files named after a hash of the tile's graphics and palette. Only colors can be replaced. Takes effect the next time a game is launched
+genesis-tile-dump-directory = Dump tiles to directory
+genesis-texture-pack-directory = Load replacement tiles from directory
+genesis-quantize-ym2612 = Quantize YM2612 channel output
+genesis-quantize-ym2612-tooltip = Quantize channel outputs from 14 bits to 9 bits to emulate the YM2612's 9-bit DAC
+genesis-video-window-title = Genesis Video Settings
+genesis-audio-window-title = Genesis Audio Settings
+
+## SNES settings
+
+snes-general-window-title = SNES General Settings
+snes-video-window-title = SNES Video Settings
+snes-audio-window-title = SNES Audio Settings
+snes-gsu-overclock = Super FX GSU overclock factor
+snes-dsp1-rom-path = DSP-1 ROM path
+snes-dsp2-rom-path = DSP-2 ROM path
+snes-dsp3-rom-path = DSP-3 ROM path
+snes-dsp4-rom-path = DSP-4 ROM path
+snes-st010-rom-path = ST010 ROM path
+snes-st011-rom-path = ST011 ROM path
+snes-hd-mode7 = HD Mode 7 (enhancement, not accurate to hardware)
+snes-mode7-native = Native
+snes-mode7-perspective-correction = Perspective correction
+snes-mode7-perspective-correction-tooltip = Interpolate Mode 7 parameters between scanlines to smooth out the stairstepping in perspective effects. Can cause artifacts in games that change Mode 7 parameters mid-frame for other effects
+snes-deinterlace-unavailable = Deinterlacing is not available while HD Mode 7 is enabled
+
+## SMS / Game Gear settings
+
+smsgg-general-window-title = SMS/GG General Settings
+smsgg-video-window-title = SMS/GG Video Settings
+smsgg-audio-window-title = SMS/GG Audio Settings
+smsgg-sms-timing-mode = Sega Master System timing / display mode
+smsgg-sms-model = Sega Master System VDP version
+smsgg-sms2 = SMS2
+smsgg-sms1 = SMS1
+smsgg-sms1-tooltip = Emulates an SMS1 quirk that is required for the Japanese version of Ys
+smsgg-sms-region = Sega Master System region
+smsgg-region-international = International / Overseas
+smsgg-region-domestic = Domestic (Japan)
+smsgg-overclock-z80 = Double Z80 CPU speed
+smsgg-overclock-z80-tooltip = Can reduce slowdown in some games but can also cause major glitches
+smsgg-gg-link = Game Gear Gear-to-Gear link
+smsgg-gg-link-tooltip = Links two emulator instances over TCP. Only serial mode is supported; games that use the link port in parallel mode will not see the other Game Gear
+smsgg-sms-aspect-ratio = Sega Master System aspect ratio
+smsgg-gg-aspect-ratio = Game Gear aspect ratio
+smsgg-gg-lcd = Game Gear LCD
+smsgg-gg-lcd-tooltip = 6:5 pixel aspect ratio
+smsgg-crop-vertical-border = (SMS) Crop vertical border
+smsgg-crop-left-border = (SMS) Crop left border
+smsgg-psg-version = PSG version
+smsgg-psg-auto-tooltip = SMS games will use SMS2 PSG, Game Gear games will use SMS1/GG PSG
+smsgg-psg-sms2-tooltip = SMS2 PSG clips high volumes
+smsgg-psg-standard = SMS1 / Game Gear
+smsgg-psg-standard-tooltip = SMS1 and Game Gear PSGs correctly play high volumes
+smsgg-fm-sound-unit = Sega Master System FM sound unit enabled
+
+## NES settings
+
+nes-general-window-title = NES General Settings
+nes-video-window-title = NES Video Settings
+nes-audio-window-title = NES Audio Settings
+nes-timing-dendy = Dendy
+nes-timing-dendy-tooltip = PAL speed with NTSC-like VBlank timing, as used by many Famicom clones
+nes-allow-opposing-inputs = Allow simultaneous opposing directional inputs
+nes-allow-opposing-inputs-tooltip = Some games exhibit major glitches when opposing directions are pressed simultaneously
+nes-expansion-device = Expansion port device
+nes-expansion-auto-tooltip = Use the device specified in the NES 2.0 ROM header, if any
+nes-expansion-none = None
+nes-expansion-none-tooltip = Standard controllers only
+nes-expansion-family-basic = Family BASIC keyboard
+nes-expansion-family-basic-tooltip = Typed using the keyboard
+nes-expansion-hyper-shot = Konami Hyper Shot
+nes-expansion-hyper-shot-tooltip = Run and Jump are mapped to each controller's B and A buttons
+nes-expansion-vaus-nes = Arkanoid Vaus (NES)
+nes-expansion-mouse-tooltip = Controlled using the mouse
+nes-expansion-vaus-famicom = Arkanoid Vaus (Famicom)
+nes-remove-sprite-limit-tooltip = Eliminates most sprite flickering but can cause visual glitches
+nes-pal-black-border = Render PAL black border
+nes-pal-black-border-tooltip = Crops the image from 256x240 to 252x239
+nes-ntsc-overscan = Emulate NTSC overscan
+nes-ntsc-overscan-tooltip = Hide the top and bottom 8 scanlines in NTSC mode, which most NTSC TVs cut off. Some games use a different region from a built-in database
+nes-overscan = Overscan in pixels
+nes-overscan-top = Top
+nes-overscan-left = Left
+nes-overscan-right = Right
+nes-overscan-bottom = Bottom
+nes-overscan-invalid = { $side } value must be a non-negative integer
+nes-silence-ultrasonic-triangle = Silence ultrasonic triangle channel output
+nes-silence-ultrasonic-triangle-tooltip = Less accurate but can reduce audio popping in some games
+
+## Game Boy settings
+
+gb-general-window-title = Game Boy General Settings
+gb-video-window-title = Game Boy Video Settings
+gb-audio-window-title = Game Boy Audio Settings
+gb-force-dmg-mode = Force DMG mode in software with CGB support
+gb-force-dmg-mode-tooltip = DMG = original Game Boy, CGB = Game Boy Color
+gb-pretend-to-be-gba = Pretend to be a Game Boy Advance
+gb-pretend-to-be-gba-tooltip = For GBC software that alters behavior when run on GBA
+gb-audio-60hz-hack = Target 60 FPS instead of actual hardware speed (~59.73 FPS)
+gb-palette = GB color palette
+gb-palette-black-and-white = Black and white
+gb-palette-green-tint = Green tint
+gb-palette-lime-green = Lime green
+gb-color-correction = GBC color correction
+gb-color-correction-gbc-lcd = Game Boy Color LCD
+gb-color-correction-gba-lcd = Game Boy Advance LCD
+
+## PC Engine settings
+
+pce-general-window-title = PC Engine General Settings
+pce-video-window-title = PC Engine Video Settings
+pce-audio-window-title = PC Engine Audio Settings
+pce-region-japan = Japan (PC Engine)
+pce-region-americas = Americas (TurboGrafx-16)
+
+## Input settings
+
+input-up = Up
+input-left = Left
+input-right = Right
+input-down = Down
+input-button-1 = Button 1
+input-button-2 = Button 2
+input-a = A
+input-b = B
+input-c = C
+input-x = X
+input-y = Y
+input-z = Z
+input-l = L
+input-r = R
+input-i = I
+input-ii = II
+input-start = Start
+input-select = Select
+input-mode = Mode
+input-run = Run
+input-macros = Input macros
+input-macros-tooltip = Comma-separated frames of buttons to press, e.g. down, down+right, right+a. Use + to press multiple buttons, - for a frame with no buttons, *N to repeat a frame N times, and a p2. prefix for player 2 buttons. Recorded macros are added here
+input-macro-remove = Remove
+input-macro-add = Add macro
+input-pause-on-disconnect = Pause when a mapped gamepad disconnects
+input-pause-on-disconnect-tooltip = Emulation resumes automatically when the gamepad reconnects
+input-axis-deadzone = Joystick axis deadzone (0-32767)
+input-axis-deadzone-invalid = Axis dead zone must be an integer between 0 and 32767
+input-genesis-3-button = 3-button
+input-genesis-6-button = 6-button
+input-sms-p1-device = SMS Player 1 device
+input-sms-joypad = Joypad
+input-sms-paddle = Paddle
+input-sms-sports-pad = Sports Pad
+input-sms-graphic-board = Graphic Board
+input-mouse-sensitivity = Mouse sensitivity
+input-stick-sensitivity = Analog stick sensitivity
+input-sms-peripheral-buttons = Peripheral buttons use the Player 1 button mappings
+input-sms-graphic-board-pen = The Graphic Board pen is pressed using the left mouse button
+input-genesis-multitap = Multiplayer adapter
+input-genesis-ea-4-way-play = EA 4-Way Play
+input-genesis-j-cart = J-Cart
+input-player = Player { $player }
+input-start-pause = Start/Pause
+input-genesis-p1-controller = Player 1 controller
+input-genesis-p2-controller = Player 2 controller
+input-smsgg-keyboard-window-title = SMS/GG Keyboard Settings
+input-smsgg-gamepad-window-title = SMS/GG Gamepad Settings
+input-genesis-keyboard-window-title = Genesis Keyboard Settings
+input-genesis-gamepad-window-title = Genesis Gamepad Settings
+input-nes-keyboard-window-title = NES Keyboard Settings
+input-nes-gamepad-window-title = NES Gamepad Settings
+input-snes-keyboard-window-title = SNES Keyboard Settings
+input-snes-gamepad-window-title = SNES Gamepad Settings
+input-snes-peripheral-window-title = SNES Peripheral Settings
+input-snes-p2-device = P2 input device
+input-snes-gamepad = Gamepad
+input-snes-super-scope = Super Scope
+input-super-scope-fire = Fire
+input-super-scope-cursor = Cursor
+input-super-scope-pause = Pause
+input-super-scope-turbo = Turbo (Toggle)
+input-gb-keyboard-window-title = Game Boy Keyboard Settings
+input-gb-gamepad-window-title = Game Boy Gamepad Settings
+input-pce-keyboard-window-title = PC Engine Keyboard Settings
+input-pce-gamepad-window-title = PC Engine Gamepad Settings
+input-hotkeys-window-title = Hotkey Settings
+input-ff-multiplier = Fast forward multiplier
+input-ff-multiplier-invalid = Fast forward multiplier must be a positive integer
+input-rewind-buffer-len = Rewind buffer length in seconds
+input-rewind-buffer-len-invalid = Rewind buffer length must be a non-negative integer
+input-trace-len = Input trace length in seconds
+input-trace-len-tooltip = Recent inputs and events are written next to the ROM file when the dump input trace hotkey is pressed or if the emulator crashes. 0 disables input tracing
+input-trace-len-invalid = Input trace length must be a non-negative integer
+input-practice-loop-len = Practice loop length in frames
+input-practice-loop-len-tooltip = After this many frames, the practice loop point is reloaded and the attempt counter is incremented. 0 disables the frame limit
+input-practice-loop-len-invalid = Practice loop length must be a non-negative integer
+input-practice-end-condition = Practice loop end condition
+input-practice-end-condition-tooltip = Expression that reloads the practice loop point when it is true after a frame, e.g. [$FF0010] == 3 to check a byte of RAM. Genesis and SNES only
+
+## Hotkeys
+
+hotkey-quit = Quit
+hotkey-toggle-fullscreen = Toggle fullscreen
+hotkey-save-state = Save state
+hotkey-load-state = Load state
+hotkey-next-slot = Next save state slot
+hotkey-previous-slot = Previous save state slot
+hotkey-undo-load-state = Undo load state
+hotkey-soft-reset = Soft reset
+hotkey-hard-reset = Hard reset
+hotkey-pause = Pause/Unpause
+hotkey-step-frame = Step frame while paused
+hotkey-fast-forward = Fast forward
+hotkey-rewind = Rewind
+hotkey-open-debugger = Open memory viewer
+hotkey-music-dump = Dump music (SPC / start+stop VGM)
+hotkey-microphone = Famicom microphone (hold)
+hotkey-dump-input-trace = Dump input trace
+hotkey-set-practice-loop = Set practice loop point
+hotkey-restart-practice-attempt = Restart practice attempt
+hotkey-clear-practice-loop = Clear practice loop point
+hotkey-record-input-macro = Record input macro
+hotkey-open-quick-menu = Open quick menu
+hotkey-open-quick-menu-gamepad = Open quick menu (gamepad)
+
+## Console names in the settings menus
+
+console-smsgg = SMS / Game Gear
+console-genesis = Genesis / Sega CD
+console-nes = NES
+console-snes = SNES
+console-gb = Game Boy
+console-pce = PC Engine
+
+## On-screen messages in the emulator window
+
+osd-slot = SLOT { $slot }
+osd-slot-empty = SLOT { $slot } EMPTY
+osd-saved-slot = SAVED SLOT { $slot }
+osd-loaded-slot = LOADED SLOT { $slot }
+osd-resumed = RESUMED
+osd-undo-load = UNDO LOAD
+osd-practice-attempt = ATTEMPT { $attempt }
+osd-loop-set = LOOP SET
+osd-loop-cleared = LOOP CLEARED
+osd-controller-connected = CONTROLLER CONNECTED
+osd-controller-disconnected = CONTROLLER DISCONNECTED
+osd-recording-macro = RECORDING MACRO
+osd-macro-recorded = MACRO RECORDED
+osd-macro-empty = MACRO EMPTY
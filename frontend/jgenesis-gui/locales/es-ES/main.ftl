@@ -0,0 +1,576 @@
+# Spanish (Spain) strings for the jgenesis GUI. Messages missing from this file fall back to English.
+
+## Menu bar
+
+menu-file = Archivo
+menu-open-recent = Abrir reciente
+menu-open = Abrir
+menu-big-picture = Modo pantalla grande
+menu-export-play-stats = Exportar estadísticas de juego…
+menu-export-compatibility = Exportar lista de compatibilidad…
+menu-quit = Salir
+
+menu-emulation = Emulación
+menu-open-memory-viewer = Abrir visor de memoria
+menu-soft-reset = Reinicio suave
+menu-hard-reset = Reinicio completo
+menu-screenshot = Capturar pantalla
+menu-power-off = Apagar
+menu-remove-disc = Extraer disco
+menu-change-disc = Cambiar disco
+menu-next-disc = Siguiente disco (disco { $disc } de { $count })
+menu-scan-barcode = Escanear código de barras
+
+menu-settings = Configuración
+menu-interface = Interfaz
+menu-video = Vídeo
+menu-audio = Audio
+menu-general = General
+menu-input = Controles
+menu-keyboard = Teclado
+menu-gamepad = Mando
+menu-peripherals = Periféricos
+menu-hotkeys = Atajos
+
+menu-help = Ayuda
+menu-about = Acerca de
+
+## ROM list
+
+romlist-configure-search-dir = Configurar directorio de búsqueda de ROMs
+romlist-header-name = Nombre
+romlist-header-console = Consola
+romlist-header-size = Tamaño
+romlist-header-play-time = Tiempo de juego
+romlist-header-last-played = Última partida
+romlist-filter-hint = Filtrar por nombre
+romlist-filter-clear = Borrar
+romlist-auto-save-state = Guardar estado automáticamente al salir
+romlist-manage-patches = Administrar parches…
+romlist-compatibility = Compatibilidad
+compatibility-untested = Sin probar
+compatibility-works = Funciona
+compatibility-minor-issues = Problemas menores
+compatibility-broken = No funciona
+
+## Interface settings
+
+interface-window-title = Configuración de la interfaz
+interface-language = Idioma
+interface-hide-cursor = Ocultar el cursor sobre la ventana del emulador
+interface-auto-save-state = Guardar estado automáticamente al salir
+interface-auto-save-state-tooltip = Guarda el estado al cerrar un juego y lo reanuda la próxima vez que se inicie el mismo juego. Se puede cambiar para cada juego haciendo clic derecho en el juego en la lista de ROMs
+interface-steam-deck-mode = Modo Steam Deck
+interface-steam-deck-mode-tooltip = Pantalla completa con escalado entero, jugador 1 asignado a los controles integrados si no hay entradas de mando configuradas, sincronización de fotogramas de bajo consumo y teclado en pantalla para los campos de texto
+interface-steam-deck-mode-auto = Detectar automáticamente
+interface-steam-deck-mode-enabled = Activado
+interface-steam-deck-mode-disabled = Desactivado
+interface-rom-search-dirs = Directorios de búsqueda de ROMs
+interface-remove = Quitar
+interface-add = Añadir
+interface-thumbnails = Mostrar miniaturas en la biblioteca de pantalla grande
+interface-thumbnails-tooltip = Las miniaturas se descargan de libretro-thumbnails según la consola y el nombre del archivo de la ROM y se guardan en caché localmente. Las ROM con nombres de los sets de No-Intro y Redump coinciden mejor
+interface-thumbnails-box-art = Carátula
+interface-thumbnails-title-screen = Pantalla de título
+interface-thumbnails-screenshot = Captura de pantalla
+interface-thumbnails-source = URL del servidor de miniaturas
+interface-thumbnails-offline-only = Solo sin conexión
+interface-thumbnails-offline-only-tooltip = Mostrar solo las miniaturas que ya se descargaron
+interface-save-sync = Sincronizar partidas guardadas con un servidor remoto
+interface-save-sync-tooltip = Las partidas guardadas se sincronizan al iniciar y al cerrar un juego. Si ambas copias cambiaron, se conserva la más reciente y la otra se guarda junto a la ROM con la extensión .sync-backup
+interface-save-sync-url = URL
+interface-save-sync-username = Usuario / ID de clave de acceso
+interface-save-sync-password = Contraseña / clave secreta
+interface-save-sync-s3-region = Región de S3
+interface-discord = Mostrar actividad en Discord Rich Presence
+interface-discord-tooltip = Muestra el sistema en ejecución y el tiempo transcurrido en tu perfil de Discord. Requiere que la aplicación de escritorio de Discord esté abierta
+interface-discord-application-id = ID de aplicación de Discord
+interface-discord-show-game-title = Mostrar también el título del juego
+
+## Migration from the old file layout
+
+migration-window-title = Mover configuración
+migration-description = Se encontró configuración de una versión anterior en el directorio actual. ¿Moverla a la nueva ubicación?
+migration-move = Mover
+migration-skip = Ahora no
+
+## Patch manager
+
+patches-window-title = Parches
+patches-label = Traducción / hack:
+patches-label-hint = p. ej. traducción al inglés v1.1
+patches-order = Los parches se aplican de arriba abajo
+patches-none = No hay parches
+patches-move-up = Subir
+patches-move-down = Bajar
+patches-remove = Eliminar
+patches-add = Añadir parches
+patches-next-launch = Los cambios se aplican la próxima vez que se inicie el juego
+
+## Barcode reader
+
+barcode-window-title = Lector de códigos de barras Datach
+barcode-instructions = Introduce un código de barras EAN-13 o EAN-8:
+barcode-scan = Escanear
+
+## About / errors
+
+about-window-title = Acerca de
+about-version = Versión: { $version }
+about-source-code = Código fuente:
+
+error-window-title = Error del emulador
+error-emulator-terminated = El emulador terminó con un error: { $error }
+
+## Dialogs
+
+dialog-supported-rom-files = Archivos ROM compatibles
+dialog-patch-files = Parches IPS/BPS
+dialog-csv-files = Archivos CSV
+dialog-markdown-files = Archivos Markdown
+input-config-window-title = Configuración de controles
+input-config-instructions = Usa la ventana del emulador para configurar los controles
+
+## Big picture mode
+
+bigpicture-library = Biblioteca
+bigpicture-exit = Salir de pantalla grande
+bigpicture-no-roms = No se encontraron ROMs en los directorios de búsqueda
+bigpicture-quick-menu = Menú rápido
+bigpicture-resume = Continuar
+bigpicture-save-state = Guardar estado
+bigpicture-load-state = Cargar estado
+bigpicture-undo-load-state = Deshacer carga de estado
+bigpicture-quit-to-library = Volver a la biblioteca
+
+## Shared option labels
+
+option-none = Ninguno
+option-auto = Automático
+option-enabled = Activado
+option-disabled = Desactivado
+option-clear = Borrar
+value-none = <Ninguno>
+timing-mode = Temporización / modo de pantalla
+timing-ntsc = NTSC
+timing-pal = PAL
+region = Región
+region-americas = América
+region-japan = Japón
+region-europe = Europa
+link-not-connected = Sin conectar
+link-tcp-client = Cliente TCP
+link-tcp-server = Servidor TCP
+link-address = Dirección
+aspect-ratio = Relación de aspecto
+aspect-ratio-ntsc = NTSC
+aspect-ratio-pal = PAL
+aspect-ratio-square-pixels = Píxeles cuadrados
+aspect-ratio-square-pixels-tooltip = Relación de aspecto de píxel 1:1
+aspect-ratio-stretched = Estirada
+aspect-ratio-stretched-tooltip = Estira la imagen para llenar la pantalla
+remove-sprite-limits = Eliminar los límites de sprites y de píxeles de sprite por línea
+remove-sprite-limits-tooltip = Puede reducir el parpadeo de los sprites, pero también puede causar fallos gráficos
+render-vertical-border = Dibujar el borde vertical
+render-horizontal-border = Dibujar el borde horizontal
+aspect-ratio-8-7-tooltip = Relación de aspecto de píxel 8:7
+aspect-ratio-11-8-tooltip = Relación de aspecto de píxel 11:8
+aspect-ratio-stretched-window-tooltip = Estirada para llenar la ventana
+audio-60hz-hack = Activar el truco de audio de 60 Hz/50 Hz
+audio-60hz-hack-tooltip = Al activar esta opción, la frecuencia de la señal de audio aumenta muy ligeramente para sincronizarse a 60 Hz NTSC / 50 Hz PAL
+remove-sprite-limit = Eliminar el límite de sprites por línea
+
+## General video settings
+
+video-window-title = Configuración general de vídeo
+video-launch-fullscreen = Iniciar en pantalla completa
+video-wgpu-backend = Backend de wgpu
+video-wgpu-vulkan = Vulkan
+video-wgpu-directx12 = DirectX 12
+video-wgpu-opengl = OpenGL
+video-vsync-mode = Modo de VSync
+video-vsync-fast = Rápido
+video-filter-mode = Modo de filtrado
+video-filter-nearest = Vecino más cercano
+video-filter-linear = Interpolación lineal
+video-filter-sharp-bilinear = Bilineal nítido
+video-filter-sharp-bilinear-tooltip = Mantiene los píxeles nítidos y evita tamaños de píxel desiguales en escalas no enteras; con este modo no hace falta preescalar
+video-preprocess-shader = Shader de preprocesado
+video-shader-blur-2px = Desenfoque horizontal (2 px)
+video-shader-blur-3px = Desenfoque horizontal (3 px)
+video-shader-blur-snes-adaptive = Desenfoque horizontal (adaptativo SNES)
+video-shader-blur-snes-adaptive-tooltip = Mantiene siempre el efecto de desenfocar 3 px en horizontal a una resolución horizontal de 512 px
+video-shader-anti-dither-weak = Anti-tramado (conservador)
+video-shader-anti-dither-strong = Anti-tramado (agresivo)
+video-scanlines = Líneas de escaneo
+video-scanlines-dim = Atenuadas
+video-scanlines-black = Negras
+video-color-blind-filter = Corrección de daltonismo
+video-color-blind-protanopia = Protanopía (rojo)
+video-color-blind-deuteranopia = Deuteranopía (verde)
+video-color-blind-tritanopia = Tritanopía (azul)
+video-flash-reduction = Reducir destellos
+video-flash-reduction-tooltip = Limita la rapidez con la que puede cambiar el brillo medio de la pantalla, lo que suaviza los destellos a pantalla completa. Los cambios rápidos de brillo pueden dejar un leve rastro durante un momento
+video-magnifier = Lupa
+video-magnifier-tooltip = Muestra una vista ampliada de la zona bajo el cursor del ratón
+video-magnifier-pip = Imagen en imagen
+video-magnifier-full-screen = Pantalla completa
+video-magnifier-corner = Esquina:
+video-corner-top-left = Arriba a la izquierda
+video-corner-top-right = Arriba a la derecha
+video-corner-bottom-left = Abajo a la izquierda
+video-corner-bottom-right = Abajo a la derecha
+video-prescale-factor = Factor de preescalado
+video-prescale-factor-invalid = El factor de preescalado debe ser un entero no negativo <= { $max }
+video-integer-height-scaling = Forzar escalado vertical entero
+video-integer-height-scaling-tooltip = El área de visualización será el mayor múltiplo entero posible de la altura nativa que conserve la relación de aspecto
+video-pal-50hz-fullscreen = Usar una frecuencia de refresco compatible con 50 Hz para juegos PAL en pantalla completa
+video-pal-50hz-fullscreen-tooltip = Cambia la pantalla a una frecuencia de refresco múltiplo de 50 Hz (p. ej. 100 Hz o 200 Hz) al ejecutar juegos PAL en pantalla completa, lo que evita tirones con VSync activado
+video-show-border = Mostrar borde
+video-show-border-tooltip = Dibuja una imagen de borde alrededor del juego. Si no hay una imagen personalizada, se usa el borde predeterminado del sistema (por ahora solo Game Gear tiene uno)
+video-custom-border-image = Imagen de borde personalizada
+video-custom-border-image-tooltip = Imagen PNG; una región totalmente transparente en el centro de la imagen se usa como pantalla del juego
+video-auto-frame-skip = Salto de fotogramas automático
+video-auto-frame-skip-tooltip = Omite el dibujado de fotogramas cuando el equipo no puede mantener la velocidad de emulación. Los fotogramas se siguen emulando, así que el audio no se ve afectado
+video-max-frame-skip = Máximo de fotogramas omitidos seguidos
+video-low-power-profile = Perfil de bajo consumo
+video-low-power-profile-tooltip = Para hardware del tipo Raspberry Pi. Sustituye los ajustes anteriores para usar OpenGL con límites compatibles con GLES, sin preescalado, sin líneas de escaneo ni shaders y con salto de fotogramas automático, y usa un remuestreo de audio de baja calidad
+video-scanlines-warning = Se recomienda encarecidamente el escalado vertical entero y un factor de preescalado par cuando las líneas de escaneo están activadas
+video-border-default = <Predeterminado>
+
+## General audio settings
+
+audio-window-title = Configuración general de audio
+audio-sync = Sincronización de audio activada
+audio-underrun-recovery = Recuperación de subdesbordamiento de audio
+audio-underrun-recovery-tooltip = Estira brevemente el audio cuando el dispositivo se queda sin muestras en lugar de dejar que crepite
+audio-latency = Latencia de audio
+audio-latency-manual = Manual
+audio-latency-ms = { $ms } ms
+audio-latency-tooltip = Fijar una latencia objetivo configura automáticamente el tamaño de la cola del dispositivo de audio, el tamaño del búfer de audio interno y el umbral de sincronización de audio, y activa el control dinámico de frecuencia cuando la sincronización de audio está desactivada
+audio-device-queue-size = Tamaño de la cola del dispositivo de audio (muestras)
+audio-device-queue-size-invalid = El tamaño de la cola del dispositivo de audio debe ser una potencia de 2 y al menos { $min }
+audio-internal-buffer-size = Tamaño del búfer de audio interno (muestras)
+audio-internal-buffer-size-invalid = El tamaño del búfer de audio interno debe ser un entero positivo
+audio-sync-threshold = Umbral de sincronización de audio (bytes)
+audio-sync-threshold-invalid = El umbral de sincronización de audio debe ser al menos { $min }
+audio-gain = Ganancia de audio (dB) (+/-)
+audio-gain-invalid = La ganancia de audio debe ser un número decimal finito
+audio-resampler-quality = Calidad del remuestreador
+audio-resampler-high = Alta
+audio-resampler-low = Baja
+audio-resampler-quality-tooltip = La calidad baja promedia las muestras de origen en lugar de aplicar un filtro de paso bajo, lo que es mucho más barato pero deja pasar algo de aliasing
+audio-post-processing = Posprocesado
+audio-equalizer = Ecualizador activado
+audio-eq-low-frequency = Frecuencia del filtro de graves (Hz)
+audio-eq-low-gain = Ganancia del filtro de graves (dB)
+audio-eq-mid-frequency = Frecuencia de medios (Hz)
+audio-eq-mid-gain = Ganancia de medios (dB)
+audio-eq-mid-q = Q de medios
+audio-eq-high-frequency = Frecuencia del filtro de agudos (Hz)
+audio-eq-high-gain = Ganancia del filtro de agudos (dB)
+audio-stereo-widening = Ampliación estéreo
+audio-stereo-widening-tooltip = También amplía el audio mono mezclando una copia con un breve retardo
+audio-crossfeed = Crossfeed para auriculares
+audio-crossfeed-tooltip = Mezcla parte de cada canal en el otro para reducir la fatiga del audio muy panoramizado con auriculares
+audio-soft-limiter = Limitador suave
+audio-soft-limiter-tooltip = Reduce el volumen de forma suave cuando el audio fuera a saturar
+
+## Deinterlacing
+
+video-deinterlacing = Desentrelazado
+video-deinterlacing-tooltip = Solo se aplica a la salida entrelazada de doble resolución
+video-deinterlace-weave = Entretejido
+video-deinterlace-weave-tooltip = Muestra ambos campos en cada fotograma
+video-deinterlace-bob = Bob
+video-deinterlace-bob-tooltip = Duplica las líneas de un campo por fotograma, alternando campos como un CRT
+video-deinterlace-blend = Mezcla
+video-deinterlace-blend-tooltip = Promedia los dos campos
+video-deinterlace-motion-adaptive = Adaptativo al movimiento
+video-deinterlace-motion-adaptive-tooltip = Entreteje donde la imagen está quieta y usa bob donde se mueve
+
+## Genesis settings
+
+genesis-general-window-title = Configuración general de Genesis
+genesis-region-spoof = Región comunicada al juego
+genesis-region-spoof-tooltip = Permite que los juegos con bloqueo regional funcionen a una velocidad distinta a la de su región de origen
+genesis-region-spoof-hardware = Hardware
+genesis-region-spoof-hardware-tooltip = Comunica la región del hardware y el modo de temporización real
+genesis-region-spoof-auto-tooltip = Comunica la región del hardware sea cual sea el modo de temporización
+genesis-report-tmss = Comunicar una consola con TMSS
+genesis-expanded-vram = 128 KB de VRAM
+genesis-expanded-vram-tooltip = Instala 128 KB de VRAM como el hardware de desarrollo y el Tera Drive. Solo hace falta para homebrew y prototipos que usan el modo de 128 KB
+genesis-lock-on = Cartucho lock-on de Sonic & Knuckles
+genesis-lock-on-rom = ROM acoplada
+genesis-lock-on-patch-rom = ROM de parche (Knuckles in Sonic 2)
+genesis-serial-port = Puerto serie
+genesis-serial-port-tooltip = Para homebrew que se comunica por un puerto de mando en modo serie. El Mega Modem no está emulado
+genesis-serial-port-1 = Puerto 1
+genesis-serial-port-2 = Puerto 2
+genesis-serial-port-ext = EXT
+genesis-scd-bios-path = Ruta de la BIOS de Sega CD
+genesis-scd-ram-cartridge = Activar cartucho de RAM de Sega CD
+genesis-scd-fast-boot = Omitir la introducción de la BIOS de Sega CD
+genesis-scd-fast-boot-tooltip = Se aplica la próxima vez que se cargue un disco o se reinicie la consola por completo
+genesis-msu-md = Activar MSU-MD
+genesis-msu-md-tooltip = Ejecuta las ROM de Genesis con el Sega CD conectado cuando hay un archivo CUE/CHD con el mismo nombre
+genesis-aspect-ratio-ntsc-tooltip = Relación de aspecto de píxel 32:35 en modo de 320 px, 8:7 en modo de 256 px
+genesis-aspect-ratio-pal-tooltip = Relación de aspecto de píxel 11:10 en modo de 320 px, 11:8 en modo de 256 px
+genesis-adjust-aspect-ratio-2x = Duplicar automáticamente la relación de aspecto de píxel en el modo de doble resolución vertical
+genesis-non-linear-dac = Emular el DAC de color no lineal del VDP
+genesis-non-linear-dac-tooltip = Tiende a aclarar los colores oscuros y a oscurecer los colores claros
+genesis-widescreen = Panorámico (solo en modo H40)
+genesis-widescreen-tooltip = Dibuja 52 píxeles adicionales a cada lado de la pantalla. La mayoría de los juegos necesitan un parche panorámico para evitar fallos en los bordes. No tiene efecto si se dibuja el borde horizontal
+genesis-widescreen-patches = Archivo de parches panorámicos
+genesis-hi-res-output = Salida a resolución 2x (mejora)
+genesis-hi-res-output-tooltip = Genera los fotogramas a doble resolución, estirando las líneas de 256 px para que coincidan con las de 320 px. Mantiene estable el tamaño de la imagen en juegos que cambian de modo de pantalla o pasan a modo entrelazado. No afecta a la emulación
+genesis-tile-textures = Texturas de tiles (experimental)
+genesis-tile-textures-tooltip = Los tiles se guardan y cargan como archivos PNG de 8x8 con el nombre de un hash de los gráficos y la paleta del tile. Solo se pueden sustituir los colores. Se aplica la próxima vez que se inicie un juego
+genesis-tile-dump-directory = Volcar tiles en el directorio
+genesis-texture-pack-directory = Cargar tiles de sustitución del directorio
+genesis-quantize-ym2612 = Cuantizar la salida de los canales del YM2612
+genesis-quantize-ym2612-tooltip = Cuantiza las salidas de los canales de 14 a 9 bits para emular el DAC de 9 bits del YM2612
+genesis-video-window-title = Configuración de vídeo de Genesis
+genesis-audio-window-title = Configuración de audio de Genesis
+
+## SNES settings
+
+snes-general-window-title = Configuración general de SNES
+snes-video-window-title = Configuración de vídeo de SNES
+snes-audio-window-title = Configuración de audio de SNES
+snes-gsu-overclock = Factor de overclock del GSU de Super FX
+snes-dsp1-rom-path = Ruta de la ROM de DSP-1
+snes-dsp2-rom-path = Ruta de la ROM de DSP-2
+snes-dsp3-rom-path = Ruta de la ROM de DSP-3
+snes-dsp4-rom-path = Ruta de la ROM de DSP-4
+snes-st010-rom-path = Ruta de la ROM de ST010
+snes-st011-rom-path = Ruta de la ROM de ST011
+snes-hd-mode7 = Mode 7 en HD (mejora, no fiel al hardware)
+snes-mode7-native = Nativa
+snes-mode7-perspective-correction = Corrección de perspectiva
+snes-mode7-perspective-correction-tooltip = Interpola los parámetros de Mode 7 entre líneas para suavizar el escalonado de los efectos de perspectiva. Puede causar artefactos en juegos que cambian los parámetros de Mode 7 a mitad de fotograma para otros efectos
+snes-deinterlace-unavailable = El desentrelazado no está disponible con el Mode 7 en HD activado
+
+## SMS / Game Gear settings
+
+smsgg-general-window-title = Configuración general de SMS/GG
+smsgg-video-window-title = Configuración de vídeo de SMS/GG
+smsgg-audio-window-title = Configuración de audio de SMS/GG
+smsgg-sms-timing-mode = Temporización / modo de pantalla de Master System
+smsgg-sms-model = Versión del VDP de Master System
+smsgg-sms2 = SMS2
+smsgg-sms1 = SMS1
+smsgg-sms1-tooltip = Emula una peculiaridad del SMS1 que necesita la versión japonesa de Ys
+smsgg-sms-region = Región de Master System
+smsgg-region-international = Internacional / Extranjero
+smsgg-region-domestic = Nacional (Japón)
+smsgg-overclock-z80 = Duplicar la velocidad de la CPU Z80
+smsgg-overclock-z80-tooltip = Puede reducir las ralentizaciones en algunos juegos, pero también puede causar fallos graves
+smsgg-gg-link = Conexión Gear-to-Gear de Game Gear
+smsgg-gg-link-tooltip = Conecta dos instancias del emulador por TCP. Solo se admite el modo serie; los juegos que usan el puerto de conexión en modo paralelo no verán la otra Game Gear
+smsgg-sms-aspect-ratio = Relación de aspecto de Master System
+smsgg-gg-aspect-ratio = Relación de aspecto de Game Gear
+smsgg-gg-lcd = LCD de Game Gear
+smsgg-gg-lcd-tooltip = Relación de aspecto de píxel 6:5
+smsgg-crop-vertical-border = (SMS) Recortar el borde vertical
+smsgg-crop-left-border = (SMS) Recortar el borde izquierdo
+smsgg-psg-version = Versión del PSG
+smsgg-psg-auto-tooltip = Los juegos de SMS usarán el PSG de SMS2 y los de Game Gear el PSG de SMS1/GG
+smsgg-psg-sms2-tooltip = El PSG de SMS2 recorta los volúmenes altos
+smsgg-psg-standard = SMS1 / Game Gear
+smsgg-psg-standard-tooltip = Los PSG de SMS1 y Game Gear reproducen correctamente los volúmenes altos
+smsgg-fm-sound-unit = Unidad de sonido FM de Master System activada
+
+## NES settings
+
+nes-general-window-title = Configuración general de NES
+nes-video-window-title = Configuración de vídeo de NES
+nes-audio-window-title = Configuración de audio de NES
+nes-timing-dendy = Dendy
+nes-timing-dendy-tooltip = Velocidad PAL con una temporización de VBlank similar a NTSC, como en muchos clones de Famicom
+nes-allow-opposing-inputs = Permitir pulsar direcciones opuestas a la vez
+nes-allow-opposing-inputs-tooltip = Algunos juegos sufren fallos graves al pulsar direcciones opuestas a la vez
+nes-expansion-device = Dispositivo del puerto de expansión
+nes-expansion-auto-tooltip = Usa el dispositivo indicado en la cabecera NES 2.0 de la ROM, si lo hay
+nes-expansion-none = Ninguno
+nes-expansion-none-tooltip = Solo mandos estándar
+nes-expansion-family-basic = Teclado Family BASIC
+nes-expansion-family-basic-tooltip = Se escribe con el teclado
+nes-expansion-hyper-shot = Konami Hyper Shot
+nes-expansion-hyper-shot-tooltip = Correr y Saltar se asignan a los botones B y A de cada mando
+nes-expansion-vaus-nes = Arkanoid Vaus (NES)
+nes-expansion-mouse-tooltip = Se controla con el ratón
+nes-expansion-vaus-famicom = Arkanoid Vaus (Famicom)
+nes-remove-sprite-limit-tooltip = Elimina la mayor parte del parpadeo de los sprites, pero puede causar fallos gráficos
+nes-pal-black-border = Dibujar el borde negro de PAL
+nes-pal-black-border-tooltip = Recorta la imagen de 256x240 a 252x239
+nes-ntsc-overscan = Emular el overscan de NTSC
+nes-ntsc-overscan-tooltip = Oculta las 8 líneas superiores e inferiores en modo NTSC, que la mayoría de los televisores NTSC recortan. Algunos juegos usan otra zona según una base de datos integrada
+nes-overscan = Overscan en píxeles
+nes-overscan-top = Arriba
+nes-overscan-left = Izquierda
+nes-overscan-right = Derecha
+nes-overscan-bottom = Abajo
+nes-overscan-invalid = El valor de { $side } debe ser un entero no negativo
+nes-silence-ultrasonic-triangle = Silenciar la salida ultrasónica del canal triangular
+nes-silence-ultrasonic-triangle-tooltip = Menos preciso, pero puede reducir los chasquidos de audio en algunos juegos
+
+## Game Boy settings
+
+gb-general-window-title = Configuración general de Game Boy
+gb-video-window-title = Configuración de vídeo de Game Boy
+gb-audio-window-title = Configuración de audio de Game Boy
+gb-force-dmg-mode = Forzar el modo DMG en software compatible con CGB
+gb-force-dmg-mode-tooltip = DMG = Game Boy original, CGB = Game Boy Color
+gb-pretend-to-be-gba = Hacerse pasar por una Game Boy Advance
+gb-pretend-to-be-gba-tooltip = Para software de GBC que cambia su comportamiento al ejecutarse en GBA
+gb-audio-60hz-hack = Apuntar a 60 FPS en lugar de la velocidad real del hardware (~59,73 FPS)
+gb-palette = Paleta de colores de GB
+gb-palette-black-and-white = Blanco y negro
+gb-palette-green-tint = Tono verde
+gb-palette-lime-green = Verde lima
+gb-color-correction = Corrección de color de GBC
+gb-color-correction-gbc-lcd = LCD de Game Boy Color
+gb-color-correction-gba-lcd = LCD de Game Boy Advance
+
+## PC Engine settings
+
+pce-general-window-title = Configuración general de PC Engine
+pce-video-window-title = Configuración de vídeo de PC Engine
+pce-audio-window-title = Configuración de audio de PC Engine
+pce-region-japan = Japón (PC Engine)
+pce-region-americas = América (TurboGrafx-16)
+
+## Input settings
+
+input-up = Arriba
+input-left = Izquierda
+input-right = Derecha
+input-down = Abajo
+input-button-1 = Botón 1
+input-button-2 = Botón 2
+input-a = A
+input-b = B
+input-c = C
+input-x = X
+input-y = Y
+input-z = Z
+input-l = L
+input-r = R
+input-i = I
+input-ii = II
+input-start = Start
+input-select = Select
+input-mode = Mode
+input-run = Run
+input-macros = Macros de entrada
+input-macros-tooltip = Fotogramas de botones separados por comas, p. ej. down, down+right, right+a. Usa + para pulsar varios botones, - para un fotograma sin botones, *N para repetir un fotograma N veces y el prefijo p2. para los botones del jugador 2. Las macros grabadas se añaden aquí
+input-macro-remove = Quitar
+input-macro-add = Añadir macro
+input-pause-on-disconnect = Pausar cuando se desconecte un mando asignado
+input-pause-on-disconnect-tooltip = La emulación se reanuda automáticamente cuando el mando se vuelve a conectar
+input-axis-deadzone = Zona muerta de los ejes del joystick (0-32767)
+input-axis-deadzone-invalid = La zona muerta de los ejes debe ser un entero entre 0 y 32767
+input-genesis-3-button = 3 botones
+input-genesis-6-button = 6 botones
+input-sms-p1-device = Dispositivo del jugador 1 de SMS
+input-sms-joypad = Mando
+input-sms-paddle = Paddle
+input-sms-sports-pad = Sports Pad
+input-sms-graphic-board = Graphic Board
+input-mouse-sensitivity = Sensibilidad del ratón
+input-stick-sensitivity = Sensibilidad del stick analógico
+input-sms-peripheral-buttons = Los botones del periférico usan las asignaciones del jugador 1
+input-sms-graphic-board-pen = El lápiz de la Graphic Board se pulsa con el botón izquierdo del ratón
+input-genesis-multitap = Adaptador multijugador
+input-genesis-ea-4-way-play = EA 4-Way Play
+input-genesis-j-cart = J-Cart
+input-player = Jugador { $player }
+input-start-pause = Start/Pausa
+input-genesis-p1-controller = Mando del jugador 1
+input-genesis-p2-controller = Mando del jugador 2
+input-smsgg-keyboard-window-title = Configuración de teclado de SMS/GG
+input-smsgg-gamepad-window-title = Configuración de mando de SMS/GG
+input-genesis-keyboard-window-title = Configuración de teclado de Genesis
+input-genesis-gamepad-window-title = Configuración de mando de Genesis
+input-nes-keyboard-window-title = Configuración de teclado de NES
+input-nes-gamepad-window-title = Configuración de mando de NES
+input-snes-keyboard-window-title = Configuración de teclado de SNES
+input-snes-gamepad-window-title = Configuración de mando de SNES
+input-snes-peripheral-window-title = Configuración de periféricos de SNES
+input-snes-p2-device = Dispositivo de entrada del J2
+input-snes-gamepad = Mando
+input-snes-super-scope = Super Scope
+input-super-scope-fire = Disparo
+input-super-scope-cursor = Cursor
+input-super-scope-pause = Pausa
+input-super-scope-turbo = Turbo (alternar)
+input-gb-keyboard-window-title = Configuración de teclado de Game Boy
+input-gb-gamepad-window-title = Configuración de mando de Game Boy
+input-pce-keyboard-window-title = Configuración de teclado de PC Engine
+input-pce-gamepad-window-title = Configuración de mando de PC Engine
+input-hotkeys-window-title = Configuración de teclas rápidas
+input-ff-multiplier = Multiplicador de avance rápido
+input-ff-multiplier-invalid = El multiplicador de avance rápido debe ser un entero positivo
+input-rewind-buffer-len = Duración del búfer de rebobinado en segundos
+input-rewind-buffer-len-invalid = La duración del búfer de rebobinado debe ser un entero no negativo
+input-trace-len = Duración de la traza de entrada en segundos
+input-trace-len-tooltip = Las entradas y eventos recientes se escriben junto al archivo de la ROM al pulsar la tecla rápida de volcado de la traza de entrada o si el emulador falla. 0 desactiva la traza de entrada
+input-trace-len-invalid = La duración de la traza de entrada debe ser un entero no negativo
+input-practice-loop-len = Duración del bucle de práctica en fotogramas
+input-practice-loop-len-tooltip = Tras este número de fotogramas, se vuelve a cargar el punto de bucle de práctica y aumenta el contador de intentos. 0 desactiva el límite de fotogramas
+input-practice-loop-len-invalid = La duración del bucle de práctica debe ser un entero no negativo
+input-practice-end-condition = Condición de fin del bucle de práctica
+input-practice-end-condition-tooltip = Expresión que vuelve a cargar el punto de bucle de práctica cuando es verdadera tras un fotograma, p. ej. [$FF0010] == 3 para comprobar un byte de RAM. Solo Genesis y SNES
+
+## Hotkeys
+
+hotkey-quit = Salir
+hotkey-toggle-fullscreen = Alternar pantalla completa
+hotkey-save-state = Guardar estado
+hotkey-load-state = Cargar estado
+hotkey-next-slot = Siguiente ranura de guardado
+hotkey-previous-slot = Ranura de guardado anterior
+hotkey-undo-load-state = Deshacer carga de estado
+hotkey-soft-reset = Reinicio suave
+hotkey-hard-reset = Reinicio completo
+hotkey-pause = Pausar/Reanudar
+hotkey-step-frame = Avanzar un fotograma en pausa
+hotkey-fast-forward = Avance rápido
+hotkey-rewind = Rebobinar
+hotkey-open-debugger = Abrir el visor de memoria
+hotkey-music-dump = Volcar música (SPC / iniciar+detener VGM)
+hotkey-microphone = Micrófono de Famicom (mantener)
+hotkey-dump-input-trace = Volcar traza de entrada
+hotkey-set-practice-loop = Fijar punto de bucle de práctica
+hotkey-restart-practice-attempt = Reiniciar intento de práctica
+hotkey-clear-practice-loop = Borrar punto de bucle de práctica
+hotkey-record-input-macro = Grabar macro de entrada
+hotkey-open-quick-menu = Abrir menú rápido
+hotkey-open-quick-menu-gamepad = Abrir menú rápido (mando)
+
+## Console names in the settings menus
+
+console-smsgg = SMS / Game Gear
+console-genesis = Genesis / Sega CD
+console-nes = NES
+console-snes = SNES
+console-gb = Game Boy
+console-pce = PC Engine
+
+## On-screen messages in the emulator window
+
+osd-slot = RANURA { $slot }
+osd-slot-empty = RANURA { $slot } VACÍA
+osd-saved-slot = GUARDADO EN RANURA { $slot }
+osd-loaded-slot = CARGADA RANURA { $slot }
+osd-resumed = REANUDADO
+osd-undo-load = CARGA DESHECHA
+osd-practice-attempt = INTENTO { $attempt }
+osd-loop-set = BUCLE FIJADO
+osd-loop-cleared = BUCLE BORRADO
+osd-controller-connected = MANDO CONECTADO
+osd-controller-disconnected = MANDO DESCONECTADO
+osd-recording-macro = GRABANDO MACRO
+osd-macro-recorded = MACRO GRABADA
+osd-macro-empty = MACRO VACÍA
@@ -1,36 +1,72 @@
+// Looks up a localized message, optionally with Fluent arguments. This is a macro rather than a
+// method on App so that it only borrows the localizer, which lets labels be passed to egui widgets
+// alongside mutable references to config fields
+macro_rules! tr {
+    ($self:ident, $id:expr) => {
+        $self.state.localizer.get($id)
+    };
+    ($self:ident, $id:expr, $($name:literal => $value:expr),+ $(,)?) => {{
+        let mut args = fluent_bundle::FluentArgs::new();
+        $(args.set($name, $value);)+
+        $self.state.localizer.get_args($id, &args)
+    }};
+}
+
+mod bigpicture;
 mod common;
 mod gb;
 mod genesis;
+mod i18n;
 mod input;
 mod nes;
+mod patches;
+mod pce;
+mod playstats;
 mod romlist;
 mod smsgg;
 mod snes;
+mod thumbnails;
 
 use crate::app::common::CommonAppConfig;
 use crate::app::gb::GameBoyAppConfig;
 use crate::app::genesis::{GenesisAppConfig, SegaCdAppConfig};
+use crate::app::i18n::{Localizer, UiLanguage};
 use crate::app::input::{GenericButton, InputAppConfig};
 use crate::app::nes::{NesAppConfig, OverscanState};
+use crate::app::patches::RomPatchConfig;
+use crate::app::pce::PceAppConfig;
+use crate::app::playstats::{Compatibility, PlayStatsDatabase};
 use crate::app::romlist::{Console, RomMetadata};
 use crate::app::smsgg::SmsGgAppConfig;
 use crate::app::snes::SnesAppConfig;
+use crate::app::thumbnails::{ThumbnailConfig, ThumbnailKind, Thumbnails};
 use crate::emuthread;
 use crate::emuthread::{EmuThreadCommand, EmuThreadHandle, EmuThreadStatus};
 use eframe::Frame;
 use egui::panel::TopBottomSide;
 use egui::{
-    menu, Align, Button, CentralPanel, Color32, Context, Key, KeyboardShortcut, Layout, Modifiers,
-    Response, TextEdit, TopBottomPanel, Ui, Vec2, ViewportCommand, Widget, Window,
+    menu, Align, Button, CentralPanel, Color32, ComboBox, Context, Grid, Id, Key, KeyboardShortcut,
+    Layout, Modifiers, Response, TextEdit, TopBottomPanel, Ui, Vec2, ViewportCommand, Widget,
+    Window,
 };
 use egui_extras::{Column, TableBuilder};
+use jgenesis_common::command::EmulatorCommand;
+use jgenesis_native_driver::config::input::InputMacroConfig;
+use jgenesis_native_driver::config::SaveSyncProtocol;
+use jgenesis_native_driver::paths::{AppDir, AppPaths, LegacyFile};
+use jgenesis_native_driver::steamdeck;
+use jgenesis_native_driver::steamdeck::SteamDeckMode;
 use jgenesis_renderer::config::Scanlines;
 use rfd::FileDialog;
+use segacd_core::m3u;
 use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::ffi::OsStr;
 use std::fs;
+use std::io;
+use std::iter;
+use std::mem;
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::str::FromStr;
@@ -51,6 +87,8 @@ struct ListFilters {
     snes: bool,
     #[serde(default = "true_fn")]
     game_boy: bool,
+    #[serde(default = "true_fn")]
+    pce: bool,
     #[serde(skip)]
     title_match: String,
 }
@@ -69,6 +107,7 @@ impl Default for ListFilters {
             nes: true,
             snes: true,
             game_boy: true,
+            pce: true,
             title_match: String::new(),
         }
     }
@@ -85,6 +124,7 @@ impl ListFilters {
             self.snes.then_some(Console::Snes),
             self.game_boy.then_some(Console::GameBoy),
             self.game_boy.then_some(Console::GameBoyColor),
+            self.pce.then_some(Console::PcEngine),
         ]
         .into_iter()
         .flatten()
@@ -122,6 +162,8 @@ pub struct AppConfig {
     #[serde(default)]
     game_boy: GameBoyAppConfig,
     #[serde(default)]
+    pce: PceAppConfig,
+    #[serde(default)]
     inputs: InputAppConfig,
     #[serde(default)]
     list_filters: ListFilters,
@@ -129,6 +171,18 @@ pub struct AppConfig {
     rom_search_dirs: Vec<String>,
     #[serde(default)]
     recent_opens: Vec<String>,
+    // Per-ROM overrides of the global auto save state setting, keyed by ROM path
+    #[serde(default)]
+    auto_save_state_overrides: BTreeMap<String, bool>,
+    // Per-ROM soft patches and translation/hack labels, keyed by ROM path
+    #[serde(default)]
+    rom_patches: BTreeMap<String, RomPatchConfig>,
+    #[serde(default)]
+    big_picture_mode: bool,
+    #[serde(default)]
+    thumbnails: ThumbnailConfig,
+    #[serde(default)]
+    language: UiLanguage,
 }
 
 impl AppConfig {
@@ -155,6 +209,7 @@ enum OpenWindow {
     NesGeneral,
     SnesGeneral,
     GameBoyGeneral,
+    PceGeneral,
     Interface,
     CommonVideo,
     SmsGgVideo,
@@ -162,11 +217,14 @@ enum OpenWindow {
     NesVideo,
     SnesVideo,
     GameBoyVideo,
+    PceVideo,
     CommonAudio,
     SmsGgAudio,
     GenesisAudio,
     NesAudio,
     SnesAudio,
+    GameBoyAudio,
+    PceAudio,
     SmsGgKeyboard,
     SmsGgGamepad,
     GenesisKeyboard,
@@ -178,12 +236,20 @@ enum OpenWindow {
     SnesPeripherals,
     GameBoyKeyboard,
     GameBoyGamepad,
+    PceKeyboard,
+    PceGamepad,
     Hotkeys,
+    NesBarcode,
+    RomPatches,
     About,
+    Migration,
 }
 
 struct AppState {
     current_file_path: String,
+    // Discs from the launched M3U playlist, if any, and the index of the disc currently inserted
+    disc_playlist: Vec<PathBuf>,
+    disc_playlist_position: usize,
     open_windows: HashSet<OpenWindow>,
     error_window_open: bool,
     prescale_factor_text: String,
@@ -194,6 +260,10 @@ struct AppState {
     ff_multiplier_invalid: bool,
     rewind_buffer_len_text: String,
     rewind_buffer_len_invalid: bool,
+    input_trace_len_text: String,
+    input_trace_len_invalid: bool,
+    practice_end_frames_text: String,
+    practice_end_frames_invalid: bool,
     audio_device_queue_size_text: String,
     audio_device_queue_size_invalid: bool,
     internal_audio_buffer_size_text: String,
@@ -207,14 +277,25 @@ struct AppState {
     waiting_for_input: Option<GenericButton>,
     rom_list: Rc<RefCell<Vec<RomMetadata>>>,
     recent_open_list: Vec<RomMetadata>,
+    big_picture_active: bool,
+    big_picture_selected: usize,
+    // Set when the quick menu was opened from the emulator window, outside of big picture mode
+    quick_menu_open: bool,
+    quick_menu_selected: usize,
+    barcode_text: String,
+    patch_manager_rom: Option<RomMetadata>,
+    text_input_focused: bool,
+    localizer: Localizer,
 }
 
 impl AppState {
-    fn from_config(config: &AppConfig) -> Self {
-        let rom_list = romlist::build(&config.rom_search_dirs);
+    fn from_config(config: &AppConfig, rom_list_cache_path: &Path) -> Self {
+        let rom_list = romlist::build(&config.rom_search_dirs, rom_list_cache_path);
         let recent_open_list = romlist::from_recent_opens(&config.recent_opens);
         Self {
             current_file_path: String::new(),
+            disc_playlist: vec![],
+            disc_playlist_position: 0,
             open_windows: HashSet::new(),
             error_window_open: false,
             prescale_factor_text: config.common.prescale_factor.get().to_string(),
@@ -225,6 +306,10 @@ impl AppState {
             ff_multiplier_invalid: false,
             rewind_buffer_len_text: config.common.rewind_buffer_length_seconds.to_string(),
             rewind_buffer_len_invalid: false,
+            input_trace_len_text: config.common.input_trace_length_seconds.to_string(),
+            input_trace_len_invalid: false,
+            practice_end_frames_text: config.common.practice_end_frame_count.to_string(),
+            practice_end_frames_invalid: false,
             audio_device_queue_size_text: config.common.audio_device_queue_size.to_string(),
             audio_device_queue_size_invalid: false,
             internal_audio_buffer_size_text: config.common.internal_audio_buffer_size.to_string(),
@@ -238,16 +323,41 @@ impl AppState {
             waiting_for_input: None,
             rom_list: Rc::new(RefCell::new(rom_list)),
             recent_open_list,
+            big_picture_active: false,
+            big_picture_selected: 0,
+            quick_menu_open: false,
+            quick_menu_selected: 0,
+            barcode_text: String::new(),
+            patch_manager_rom: None,
+            text_input_focused: false,
+            localizer: Localizer::new(config.language),
         }
     }
 }
 
+fn thumbnail_kind_message_id(kind: ThumbnailKind) -> &'static str {
+    match kind {
+        ThumbnailKind::BoxArt => "interface-thumbnails-box-art",
+        ThumbnailKind::TitleScreen => "interface-thumbnails-title-screen",
+        ThumbnailKind::Screenshot => "interface-thumbnails-screenshot",
+    }
+}
+
 fn should_display_scanlines_warning(config: &AppConfig) -> bool {
     config.common.scanlines != Scanlines::None
         && (config.common.prescale_factor.get() % 2 != 0
             || !config.common.force_integer_height_scaling)
 }
 
+fn load_disc_playlist(path: &str) -> Vec<PathBuf> {
+    if Path::new(path).extension().and_then(OsStr::to_str) != Some("m3u") {
+        return vec![];
+    }
+
+    // Errors will be reported by the emulator when it fails to load the playlist
+    m3u::parse(path).unwrap_or_default()
+}
+
 struct NumericTextEdit<'a, T> {
     text: &'a mut String,
     value: &'a mut T,
@@ -296,20 +406,50 @@ impl<'a, T: Copy + FromStr> Widget for NumericTextEdit<'a, T> {
     }
 }
 
+pub const CONFIG_FILE_NAME: &str = "jgenesis-config.toml";
+
 pub struct App {
     config: AppConfig,
     state: AppState,
     config_path: PathBuf,
+    rom_list_cache_path: PathBuf,
+    thumbnails: Thumbnails,
+    play_stats: PlayStatsDatabase,
+    // Files from the old layout where everything was stored in the working directory
+    legacy_files: Vec<LegacyFile>,
     emu_thread: EmuThreadHandle,
 }
 
 impl App {
     #[must_use]
-    pub fn new(config_path: PathBuf) -> Self {
+    pub fn new(paths: &AppPaths, ctx: Context) -> Self {
+        let config_path = paths.config_dir.join(CONFIG_FILE_NAME);
+        let rom_list_cache_path = paths.cache_dir.join(romlist::CACHE_FILE_NAME);
+        let legacy_files = paths.find_legacy_files(&[
+            (CONFIG_FILE_NAME, AppDir::Config),
+            (romlist::CACHE_FILE_NAME, AppDir::Cache),
+        ]);
+
         let config = AppConfig::from_file(&config_path);
-        let state = AppState::from_config(&config);
-        let emu_thread = emuthread::spawn();
-        Self { config, state, config_path, emu_thread }
+        let mut state = AppState::from_config(&config, &rom_list_cache_path);
+        if !legacy_files.is_empty() {
+            state.open_windows.insert(OpenWindow::Migration);
+        }
+
+        let thumbnails = Thumbnails::new(paths.cache_dir.join(thumbnails::CACHE_DIR_NAME));
+        let play_stats = PlayStatsDatabase::load(paths.state_dir.join(playstats::STATS_FILE_NAME));
+
+        let emu_thread = emuthread::spawn(ctx);
+        Self {
+            config,
+            state,
+            config_path,
+            rom_list_cache_path,
+            thumbnails,
+            play_stats,
+            legacy_files,
+            emu_thread,
+        }
     }
 
     fn open_file(&mut self) {
@@ -319,8 +459,8 @@ impl App {
         }
 
         let mut file_dialog = FileDialog::new().add_filter(
-            "Supported ROM files",
-            &["sms", "gg", "md", "bin", "cue", "nes", "sfc", "smc", "gb", "gbc"],
+            &tr!(self, "dialog-supported-rom-files"),
+            &["sms", "gg", "md", "bin", "cue", "m3u", "nes", "sfc", "smc", "gb", "gbc", "pce"],
         );
         if let Some(dir) = self.config.rom_search_dirs.first() {
             file_dialog = file_dialog.set_directory(Path::new(dir));
@@ -333,6 +473,8 @@ impl App {
 
     fn launch_emulator(&mut self, path: String) {
         self.state.current_file_path = path.clone();
+        self.state.disc_playlist = load_disc_playlist(&path);
+        self.state.disc_playlist_position = 0;
 
         // Update Open Recent contents
         self.config.recent_opens.retain(|recent_open_path| recent_open_path != &path);
@@ -340,6 +482,8 @@ impl App {
         self.config.recent_opens.truncate(10);
         self.state.recent_open_list = romlist::from_recent_opens(&self.config.recent_opens);
 
+        self.play_stats.record_launch(&path);
+
         match Path::new(&path).extension().and_then(OsStr::to_str) {
             Some("sms" | "gg") => {
                 self.emu_thread.stop_emulator_if_running();
@@ -350,10 +494,15 @@ impl App {
             Some("md" | "bin") => {
                 self.emu_thread.stop_emulator_if_running();
 
+                if let Some(config) = self.config.msu_md_config(&path) {
+                    self.emu_thread.send(EmuThreadCommand::RunSegaCd(config));
+                    return;
+                }
+
                 let config = self.config.genesis_config(path);
                 self.emu_thread.send(EmuThreadCommand::RunGenesis(config));
             }
-            Some("cue") => {
+            Some("cue" | "m3u") => {
                 self.emu_thread.stop_emulator_if_running();
 
                 let config = self.config.sega_cd_config(path);
@@ -377,6 +526,12 @@ impl App {
                 let config = self.config.gb_config(path);
                 self.emu_thread.send(EmuThreadCommand::RunGameBoy(config));
             }
+            Some("pce") => {
+                self.emu_thread.stop_emulator_if_running();
+
+                let config = self.config.pce_config(path);
+                self.emu_thread.send(EmuThreadCommand::RunPce(config));
+            }
             Some(extension) => {
                 log::error!("Unsupported file extension: {extension}");
             }
@@ -384,26 +539,172 @@ impl App {
         }
     }
 
+    fn has_disc_playlist(&self) -> bool {
+        self.state.disc_playlist.len() > 1
+            && self.emu_thread.status() == EmuThreadStatus::RunningSegaCd
+    }
+
+    // Swap in the next disc from the playlist, wrapping around to the first disc after the last
+    fn next_disc(&mut self) {
+        let playlist = &self.state.disc_playlist;
+        if playlist.is_empty() {
+            return;
+        }
+
+        let position = (self.state.disc_playlist_position + 1) % playlist.len();
+        self.state.disc_playlist_position = position;
+        let path = playlist[position].to_string_lossy().to_string();
+        self.emu_thread.send(EmuThreadCommand::Emulator(EmulatorCommand::SwapDisc(Some(path))));
+    }
+
+    fn next_disc_label(&self) -> String {
+        let next_position =
+            (self.state.disc_playlist_position + 1) % self.state.disc_playlist.len();
+
+        tr!(
+            self,
+            "menu-next-disc",
+            "disc" => next_position + 1,
+            "count" => self.state.disc_playlist.len(),
+        )
+    }
+
+    fn check_play_sessions(&mut self) {
+        while let Some(session) = self.emu_thread.poll_play_session() {
+            self.play_stats.record_session(&session);
+        }
+    }
+
+    fn export_play_stats(&self) {
+        let Some(path) = FileDialog::new()
+            .add_filter(&tr!(self, "dialog-csv-files"), &["csv"])
+            .set_file_name("jgenesis-play-stats.csv")
+            .save_file()
+        else {
+            return;
+        };
+
+        let result = fs::File::create(&path)
+            .and_then(|file| self.play_stats.write_csv(io::BufWriter::new(file)));
+        match result {
+            Ok(()) => log::info!("Exported play statistics to '{}'", path.display()),
+            Err(err) => {
+                log::error!("Error exporting play statistics to '{}': {err}", path.display());
+            }
+        }
+    }
+
+    fn export_compatibility_list(&self) {
+        let Some(path) = FileDialog::new()
+            .add_filter(&tr!(self, "dialog-markdown-files"), &["md"])
+            .set_file_name("compatibility.md")
+            .save_file()
+        else {
+            return;
+        };
+
+        let result = fs::File::create(&path).and_then(|file| {
+            self.play_stats.write_compatibility_markdown(io::BufWriter::new(file))
+        });
+        match result {
+            Ok(()) => log::info!("Exported compatibility list to '{}'", path.display()),
+            Err(err) => {
+                log::error!("Error exporting compatibility list to '{}': {err}", path.display());
+            }
+        }
+    }
+
+    fn compatibility_label(&self, compatibility: Option<Compatibility>) -> String {
+        match compatibility {
+            None => tr!(self, "compatibility-untested"),
+            Some(Compatibility::Works) => tr!(self, "compatibility-works"),
+            Some(Compatibility::MinorIssues) => tr!(self, "compatibility-minor-issues"),
+            Some(Compatibility::Broken) => tr!(self, "compatibility-broken"),
+        }
+    }
+
     fn add_rom_search_directory(&mut self) {
         let Some(dir) = FileDialog::new().pick_folder() else { return };
         let Some(dir) = dir.to_str() else { return };
 
         self.config.rom_search_dirs.push(dir.into());
-        *self.state.rom_list.borrow_mut() = romlist::build(&self.config.rom_search_dirs);
+        *self.state.rom_list.borrow_mut() =
+            romlist::build(&self.config.rom_search_dirs, &self.rom_list_cache_path);
+    }
+
+    fn steam_deck_mode_label(&self, mode: SteamDeckMode) -> String {
+        match mode {
+            SteamDeckMode::Auto => tr!(self, "interface-steam-deck-mode-auto"),
+            SteamDeckMode::Enabled => tr!(self, "interface-steam-deck-mode-enabled"),
+            SteamDeckMode::Disabled => tr!(self, "interface-steam-deck-mode-disabled"),
+        }
+    }
+
+    // Gaming Mode has no physical keyboard, so bring up Steam's on-screen keyboard whenever a text
+    // field gains focus
+    fn check_text_input_focus(&mut self, ctx: &Context) {
+        let focused = ctx.wants_keyboard_input();
+        if focused
+            && !self.state.text_input_focused
+            && self.config.common.steam_deck_mode.is_active()
+        {
+            steamdeck::open_onscreen_keyboard();
+        }
+        self.state.text_input_focused = focused;
     }
 
     fn render_interface_settings(&mut self, ctx: &Context) {
         let mut open = true;
-        Window::new("UI Settings").open(&mut open).resizable(false).show(ctx, |ui| {
+        // Fixed ID so that the window does not move when the language changes
+        let window =
+            Window::new(tr!(self, "interface-window-title")).id(Id::new("interface_settings"));
+        window.open(&mut open).resizable(false).show(ctx, |ui| {
+            ComboBox::from_label(tr!(self, "interface-language"))
+                .selected_text(self.config.language.native_name())
+                .show_ui(ui, |ui| {
+                    for language in UiLanguage::ALL {
+                        ui.selectable_value(
+                            &mut self.config.language,
+                            language,
+                            language.native_name(),
+                        );
+                    }
+                });
+
+            ui.add_space(5.0);
+
             ui.checkbox(
                 &mut self.config.common.hide_cursor_over_window,
-                "Hide mouse cursor over emulator window",
+                tr!(self, "interface-hide-cursor"),
             );
 
             ui.add_space(5.0);
 
+            ui.checkbox(
+                &mut self.config.common.auto_save_state,
+                tr!(self, "interface-auto-save-state"),
+            )
+            .on_hover_text(tr!(self, "interface-auto-save-state-tooltip"));
+
+            ui.add_space(5.0);
+
+            let steam_deck_modes =
+                [SteamDeckMode::Auto, SteamDeckMode::Enabled, SteamDeckMode::Disabled]
+                    .map(|mode| (mode, self.steam_deck_mode_label(mode)));
+            ComboBox::from_label(tr!(self, "interface-steam-deck-mode"))
+                .selected_text(self.steam_deck_mode_label(self.config.common.steam_deck_mode))
+                .show_ui(ui, |ui| {
+                    for (mode, label) in steam_deck_modes {
+                        ui.selectable_value(&mut self.config.common.steam_deck_mode, mode, label);
+                    }
+                })
+                .response
+                .on_hover_text(tr!(self, "interface-steam-deck-mode-tooltip"));
+
+            ui.add_space(5.0);
+
             ui.group(|ui| {
-                ui.label("ROM search directories");
+                ui.label(tr!(self, "interface-rom-search-dirs"));
 
                 ui.add_space(5.0);
 
@@ -413,18 +714,130 @@ impl App {
                     ui.horizontal(|ui| {
                         ui.label(&rom_search_dir);
 
-                        if ui.button("Remove").clicked() {
+                        if ui.button(tr!(self, "interface-remove")).clicked() {
                             self.config.rom_search_dirs.remove(i);
-                            *self.state.rom_list.borrow_mut() =
-                                romlist::build(&self.config.rom_search_dirs);
+                            *self.state.rom_list.borrow_mut() = romlist::build(
+                                &self.config.rom_search_dirs,
+                                &self.rom_list_cache_path,
+                            );
                         }
                     });
                 }
 
-                if ui.button("Add").clicked() {
+                if ui.button(tr!(self, "interface-add")).clicked() {
                     self.add_rom_search_directory();
                 }
             });
+
+            ui.add_space(5.0);
+
+            ui.group(|ui| {
+                ui.checkbox(&mut self.config.thumbnails.enabled, tr!(self, "interface-thumbnails"))
+                    .on_hover_text(tr!(self, "interface-thumbnails-tooltip"));
+
+                ui.add_enabled_ui(self.config.thumbnails.enabled, |ui| {
+                    ui.horizontal(|ui| {
+                        for kind in ThumbnailKind::ALL {
+                            ui.radio_value(
+                                &mut self.config.thumbnails.kind,
+                                kind,
+                                tr!(self, thumbnail_kind_message_id(kind)),
+                            );
+                        }
+                    });
+
+                    ui.horizontal(|ui| {
+                        ui.label(tr!(self, "interface-thumbnails-source"));
+                        TextEdit::singleline(&mut self.config.thumbnails.source_url)
+                            .desired_width(250.0)
+                            .ui(ui);
+                    });
+
+                    ui.checkbox(
+                        &mut self.config.thumbnails.offline_only,
+                        tr!(self, "interface-thumbnails-offline-only"),
+                    )
+                    .on_hover_text(tr!(self, "interface-thumbnails-offline-only-tooltip"));
+                });
+            });
+
+            ui.add_space(5.0);
+
+            ui.group(|ui| {
+                ui.checkbox(
+                    &mut self.config.common.save_sync_enabled,
+                    tr!(self, "interface-save-sync"),
+                )
+                .on_hover_text(tr!(self, "interface-save-sync-tooltip"));
+
+                ui.add_enabled_ui(self.config.common.save_sync_enabled, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.radio_value(
+                            &mut self.config.common.save_sync_protocol,
+                            SaveSyncProtocol::WebDav,
+                            "WebDAV",
+                        );
+                        ui.radio_value(
+                            &mut self.config.common.save_sync_protocol,
+                            SaveSyncProtocol::S3,
+                            "S3",
+                        );
+                    });
+
+                    Grid::new("save_sync_grid").show(ui, |ui| {
+                        ui.label(tr!(self, "interface-save-sync-url"));
+                        TextEdit::singleline(&mut self.config.common.save_sync_url)
+                            .desired_width(250.0)
+                            .ui(ui);
+                        ui.end_row();
+
+                        ui.label(tr!(self, "interface-save-sync-username"));
+                        TextEdit::singleline(&mut self.config.common.save_sync_username)
+                            .desired_width(250.0)
+                            .ui(ui);
+                        ui.end_row();
+
+                        ui.label(tr!(self, "interface-save-sync-password"));
+                        TextEdit::singleline(&mut self.config.common.save_sync_password.0)
+                            .password(true)
+                            .desired_width(250.0)
+                            .ui(ui);
+                        ui.end_row();
+
+                        if self.config.common.save_sync_protocol == SaveSyncProtocol::S3 {
+                            ui.label(tr!(self, "interface-save-sync-s3-region"));
+                            TextEdit::singleline(&mut self.config.common.save_sync_s3_region)
+                                .desired_width(250.0)
+                                .ui(ui);
+                            ui.end_row();
+                        }
+                    });
+                });
+            });
+
+            ui.add_space(5.0);
+
+            ui.group(|ui| {
+                ui.checkbox(
+                    &mut self.config.common.discord_enabled,
+                    tr!(self, "interface-discord"),
+                )
+                .on_hover_text(tr!(self, "interface-discord-tooltip"));
+
+                ui.add_enabled_ui(self.config.common.discord_enabled, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(tr!(self, "interface-discord-application-id"));
+                        TextEdit::singleline(&mut self.config.common.discord_application_id)
+                            .desired_width(200.0)
+                            .ui(ui);
+                    });
+
+                    ui.checkbox(
+                        &mut self.config.common.discord_show_game_title,
+                        tr!(self, "interface-discord-show-game-title"),
+                    );
+                });
+            });
         });
         if !open {
             self.state.open_windows.remove(&OpenWindow::Interface);
@@ -433,18 +846,19 @@ impl App {
 
     fn render_about(&mut self, ctx: &Context) {
         let mut open = true;
-        Window::new("About").open(&mut open).resizable(false).show(ctx, |ui| {
+        let title = tr!(self, "about-window-title");
+        Window::new(title).open(&mut open).resizable(false).show(ctx, |ui| {
             ui.heading("jgenesis");
 
             ui.add_space(10.0);
-            ui.label(format!("Version: {}", env!("CARGO_PKG_VERSION")));
+            ui.label(tr!(self, "about-version", "version" => env!("CARGO_PKG_VERSION")));
 
             ui.add_space(15.0);
             ui.label("Copyright © 2023 James Groth");
 
             ui.add_space(10.0);
             ui.horizontal(|ui| {
-                ui.label("Source code:");
+                ui.label(tr!(self, "about-source-code"));
                 ui.hyperlink("https://github.com/jsgroth/jgenesis");
             });
         });
@@ -453,6 +867,75 @@ impl App {
         }
     }
 
+    fn render_migration_window(&mut self, ctx: &Context) {
+        let mut open = true;
+        let mut migrate = false;
+        let title = tr!(self, "migration-window-title");
+        Window::new(title).open(&mut open).resizable(false).show(ctx, |ui| {
+            ui.label(tr!(self, "migration-description"));
+
+            ui.add_space(5.0);
+            for file in &self.legacy_files {
+                ui.label(format!("{} → {}", file.from.display(), file.to.display()));
+            }
+
+            ui.add_space(10.0);
+            ui.horizontal(|ui| {
+                if ui.button(tr!(self, "migration-move")).clicked() {
+                    migrate = true;
+                }
+
+                if ui.button(tr!(self, "migration-skip")).clicked() {
+                    self.state.open_windows.remove(&OpenWindow::Migration);
+                }
+            });
+        });
+        if !open {
+            self.state.open_windows.remove(&OpenWindow::Migration);
+        }
+
+        if migrate {
+            for file in mem::take(&mut self.legacy_files) {
+                match file.migrate() {
+                    Ok(()) => {
+                        log::info!("Moved '{}' to '{}'", file.from.display(), file.to.display())
+                    }
+                    Err(err) => log::error!("Error moving '{}': {err}", file.from.display()),
+                }
+            }
+
+            // Reload everything from the moved config; this also closes the migration window
+            self.config = AppConfig::from_file(&self.config_path);
+            self.state = AppState::from_config(&self.config, &self.rom_list_cache_path);
+        }
+    }
+
+    fn render_barcode_window(&mut self, ctx: &Context) {
+        let mut open = true;
+        let title = tr!(self, "barcode-window-title");
+        Window::new(title).open(&mut open).resizable(false).show(ctx, |ui| {
+            ui.label(tr!(self, "barcode-instructions"));
+
+            ui.horizontal(|ui| {
+                let response =
+                    TextEdit::singleline(&mut self.state.barcode_text).desired_width(150.0).ui(ui);
+                let submitted =
+                    response.lost_focus() && ui.input(|input| input.key_pressed(Key::Enter));
+
+                let scan_enabled = self.emu_thread.status() == EmuThreadStatus::RunningNes;
+                let scan_clicked =
+                    ui.add_enabled(scan_enabled, Button::new(tr!(self, "barcode-scan"))).clicked();
+                if scan_enabled && (scan_clicked || submitted) {
+                    let barcode = self.state.barcode_text.trim().to_string();
+                    self.emu_thread.send(EmuThreadCommand::NesScanBarcode(barcode));
+                }
+            });
+        });
+        if !open {
+            self.state.open_windows.remove(&OpenWindow::NesBarcode);
+        }
+    }
+
     fn render_menu(&mut self, ctx: &Context, _frame: &mut Frame) {
         let open_shortcut = KeyboardShortcut::new(Modifiers::CTRL, Key::O);
         if ctx.input_mut(|input| input.consume_shortcut(&open_shortcut)) {
@@ -468,9 +951,9 @@ impl App {
             menu::bar(ui, |ui| {
                 ui.set_enabled(!self.state.error_window_open);
 
-                ui.menu_button("File", |ui| {
+                ui.menu_button(tr!(self, "menu-file"), |ui| {
                     ui.add_enabled_ui(!self.state.recent_open_list.is_empty(), |ui| {
-                        ui.menu_button("Open Recent", |ui| {
+                        ui.menu_button(tr!(self, "menu-open-recent"), |ui| {
                             for recent_open in self.state.recent_open_list.clone() {
                                 if ui.button(&recent_open.file_name_no_ext).clicked() {
                                     self.launch_emulator(recent_open.full_path);
@@ -482,41 +965,64 @@ impl App {
                         });
                     });
 
-                    let open_button =
-                        Button::new("Open").shortcut_text(ctx.format_shortcut(&open_shortcut));
+                    let open_button = Button::new(tr!(self, "menu-open"))
+                        .shortcut_text(ctx.format_shortcut(&open_shortcut));
                     if open_button.ui(ui).clicked() {
                         self.open_file();
                         ui.close_menu();
                     }
 
-                    let quit_button =
-                        Button::new("Quit").shortcut_text(ctx.format_shortcut(&quit_shortcut));
+                    if ui.button(tr!(self, "menu-big-picture")).clicked() {
+                        self.enter_big_picture_mode(ctx);
+                        ui.close_menu();
+                    }
+
+                    if ui.button(tr!(self, "menu-export-play-stats")).clicked() {
+                        self.export_play_stats();
+                        ui.close_menu();
+                    }
+
+                    if ui.button(tr!(self, "menu-export-compatibility")).clicked() {
+                        self.export_compatibility_list();
+                        ui.close_menu();
+                    }
+
+                    let quit_button = Button::new(tr!(self, "menu-quit"))
+                        .shortcut_text(ctx.format_shortcut(&quit_shortcut));
                     if quit_button.ui(ui).clicked() {
                         ctx.send_viewport_cmd(ViewportCommand::Close);
                     }
                 });
 
-                ui.menu_button("Emulation", |ui| {
+                ui.menu_button(tr!(self, "menu-emulation"), |ui| {
                     ui.set_enabled(self.emu_thread.status().is_running());
 
-                    if ui.button("Open Memory Viewer").clicked() {
+                    if ui.button(tr!(self, "menu-open-memory-viewer")).clicked() {
                         self.emu_thread.send(EmuThreadCommand::OpenMemoryViewer);
                         ui.close_menu();
                     }
 
                     ui.add_space(15.0);
 
-                    if ui.button("Soft Reset").clicked() {
-                        self.emu_thread.send(EmuThreadCommand::SoftReset);
+                    if ui.button(tr!(self, "menu-soft-reset")).clicked() {
+                        self.emu_thread
+                            .send(EmuThreadCommand::Emulator(EmulatorCommand::SoftReset));
+                        ui.close_menu();
+                    }
+
+                    if ui.button(tr!(self, "menu-hard-reset")).clicked() {
+                        self.emu_thread
+                            .send(EmuThreadCommand::Emulator(EmulatorCommand::HardReset));
                         ui.close_menu();
                     }
 
-                    if ui.button("Hard Reset").clicked() {
-                        self.emu_thread.send(EmuThreadCommand::HardReset);
+                    if ui.button(tr!(self, "menu-screenshot")).clicked() {
+                        self.emu_thread
+                            .send(EmuThreadCommand::Emulator(EmulatorCommand::Screenshot));
                         ui.close_menu();
                     }
 
-                    if ui.button("Power Off").clicked() {
+                    if ui.button(tr!(self, "menu-power-off")).clicked() {
                         self.emu_thread.send(EmuThreadCommand::StopEmulator);
                         ui.close_menu();
                     }
@@ -526,123 +1032,165 @@ impl App {
                     ui.add_enabled_ui(
                         self.emu_thread.status() == EmuThreadStatus::RunningSegaCd,
                         |ui| {
-                            if ui.button("Remove Disc").clicked() {
-                                self.emu_thread.send(EmuThreadCommand::SegaCdRemoveDisc);
+                            if ui.button(tr!(self, "menu-remove-disc")).clicked() {
+                                self.emu_thread.send(EmuThreadCommand::Emulator(
+                                    EmulatorCommand::SwapDisc(None),
+                                ));
                                 ui.close_menu();
                             }
 
-                            if ui.button("Change Disc").clicked() {
+                            if ui.button(tr!(self, "menu-change-disc")).clicked() {
                                 if let Some(path) =
                                     FileDialog::new().add_filter("cue", &["cue"]).pick_file()
                                 {
-                                    self.emu_thread.send(EmuThreadCommand::SegaCdChangeDisc(path));
+                                    let path = path.to_string_lossy().to_string();
+                                    self.emu_thread.send(EmuThreadCommand::Emulator(
+                                        EmulatorCommand::SwapDisc(Some(path)),
+                                    ));
                                 }
 
                                 ui.close_menu();
                             }
+
+                            if self.has_disc_playlist()
+                                && ui.button(self.next_disc_label()).clicked()
+                            {
+                                self.next_disc();
+                                ui.close_menu();
+                            }
+                        },
+                    );
+
+                    ui.add_enabled_ui(
+                        self.emu_thread.status() == EmuThreadStatus::RunningNes,
+                        |ui| {
+                            if ui.button(tr!(self, "menu-scan-barcode")).clicked() {
+                                self.state.open_windows.insert(OpenWindow::NesBarcode);
+                                ui.close_menu();
+                            }
                         },
                     );
                 });
 
-                ui.menu_button("Settings", |ui| {
-                    if ui.button("SMS / Game Gear").clicked() {
+                ui.menu_button(tr!(self, "menu-settings"), |ui| {
+                    if ui.button(tr!(self, "console-smsgg")).clicked() {
                         self.state.open_windows.insert(OpenWindow::SmsGgGeneral);
                         ui.close_menu();
                     }
 
-                    if ui.button("Genesis / Sega CD").clicked() {
+                    if ui.button(tr!(self, "console-genesis")).clicked() {
                         self.state.open_windows.insert(OpenWindow::GenesisGeneral);
                         ui.close_menu();
                     }
 
-                    if ui.button("NES").clicked() {
+                    if ui.button(tr!(self, "console-nes")).clicked() {
                         self.state.open_windows.insert(OpenWindow::NesGeneral);
                         ui.close_menu();
                     }
 
-                    if ui.button("SNES").clicked() {
+                    if ui.button(tr!(self, "console-snes")).clicked() {
                         self.state.open_windows.insert(OpenWindow::SnesGeneral);
                         ui.close_menu();
                     }
 
-                    if ui.button("Game Boy").clicked() {
+                    if ui.button(tr!(self, "console-gb")).clicked() {
                         self.state.open_windows.insert(OpenWindow::GameBoyGeneral);
                         ui.close_menu();
                     }
 
-                    if ui.button("Interface").clicked() {
+                    if ui.button(tr!(self, "console-pce")).clicked() {
+                        self.state.open_windows.insert(OpenWindow::PceGeneral);
+                        ui.close_menu();
+                    }
+
+                    if ui.button(tr!(self, "menu-interface")).clicked() {
                         self.state.open_windows.insert(OpenWindow::Interface);
                         ui.close_menu();
                     }
                 });
 
-                ui.menu_button("Video", |ui| {
-                    if ui.button("General").clicked() {
+                ui.menu_button(tr!(self, "menu-video"), |ui| {
+                    if ui.button(tr!(self, "menu-general")).clicked() {
                         self.state.open_windows.insert(OpenWindow::CommonVideo);
                         ui.close_menu();
                     }
 
-                    if ui.button("SMS / Game Gear").clicked() {
+                    if ui.button(tr!(self, "console-smsgg")).clicked() {
                         self.state.open_windows.insert(OpenWindow::SmsGgVideo);
                         ui.close_menu();
                     }
 
-                    if ui.button("Genesis / Sega CD").clicked() {
+                    if ui.button(tr!(self, "console-genesis")).clicked() {
                         self.state.open_windows.insert(OpenWindow::GenesisVideo);
                         ui.close_menu();
                     }
 
-                    if ui.button("NES").clicked() {
+                    if ui.button(tr!(self, "console-nes")).clicked() {
                         self.state.open_windows.insert(OpenWindow::NesVideo);
                         ui.close_menu();
                     }
 
-                    if ui.button("SNES").clicked() {
+                    if ui.button(tr!(self, "console-snes")).clicked() {
                         self.state.open_windows.insert(OpenWindow::SnesVideo);
                         ui.close_menu();
                     }
 
-                    if ui.button("Game Boy").clicked() {
+                    if ui.button(tr!(self, "console-gb")).clicked() {
                         self.state.open_windows.insert(OpenWindow::GameBoyVideo);
                         ui.close_menu();
                     }
+
+                    if ui.button(tr!(self, "console-pce")).clicked() {
+                        self.state.open_windows.insert(OpenWindow::PceVideo);
+                        ui.close_menu();
+                    }
                 });
 
-                ui.menu_button("Audio", |ui| {
-                    if ui.button("General").clicked() {
+                ui.menu_button(tr!(self, "menu-audio"), |ui| {
+                    if ui.button(tr!(self, "menu-general")).clicked() {
                         self.state.open_windows.insert(OpenWindow::CommonAudio);
                         ui.close_menu();
                     }
 
-                    if ui.button("SMS / Game Gear").clicked() {
+                    if ui.button(tr!(self, "console-smsgg")).clicked() {
                         self.state.open_windows.insert(OpenWindow::SmsGgAudio);
                         ui.close_menu();
                     }
 
-                    if ui.button("Genesis / Sega CD").clicked() {
+                    if ui.button(tr!(self, "console-genesis")).clicked() {
                         self.state.open_windows.insert(OpenWindow::GenesisAudio);
                         ui.close_menu();
                     }
 
-                    if ui.button("NES").clicked() {
+                    if ui.button(tr!(self, "console-nes")).clicked() {
                         self.state.open_windows.insert(OpenWindow::NesAudio);
                         ui.close_menu();
                     }
 
-                    if ui.button("SNES").clicked() {
+                    if ui.button(tr!(self, "console-snes")).clicked() {
                         self.state.open_windows.insert(OpenWindow::SnesAudio);
                         ui.close_menu();
                     }
+
+                    if ui.button(tr!(self, "console-gb")).clicked() {
+                        self.state.open_windows.insert(OpenWindow::GameBoyAudio);
+                        ui.close_menu();
+                    }
+
+                    if ui.button(tr!(self, "console-pce")).clicked() {
+                        self.state.open_windows.insert(OpenWindow::PceAudio);
+                        ui.close_menu();
+                    }
                 });
 
-                ui.menu_button("Input", |ui| {
-                    ui.menu_button("SMS / Game Gear", |ui| {
-                        if ui.button("Keyboard").clicked() {
+                ui.menu_button(tr!(self, "menu-input"), |ui| {
+                    ui.menu_button(tr!(self, "console-smsgg"), |ui| {
+                        if ui.button(tr!(self, "menu-keyboard")).clicked() {
                             self.state.open_windows.insert(OpenWindow::SmsGgKeyboard);
                             ui.close_menu();
                         }
 
-                        if ui.button("Gamepad").clicked() {
+                        if ui.button(tr!(self, "menu-gamepad")).clicked() {
                             self.state.open_windows.insert(OpenWindow::SmsGgGamepad);
                             ui.close_menu();
                         }
@@ -650,13 +1198,13 @@ impl App {
 
                     ui.add_space(5.0);
 
-                    ui.menu_button("Genesis / Sega CD", |ui| {
-                        if ui.button("Keyboard").clicked() {
+                    ui.menu_button(tr!(self, "console-genesis"), |ui| {
+                        if ui.button(tr!(self, "menu-keyboard")).clicked() {
                             self.state.open_windows.insert(OpenWindow::GenesisKeyboard);
                             ui.close_menu();
                         }
 
-                        if ui.button("Gamepad").clicked() {
+                        if ui.button(tr!(self, "menu-gamepad")).clicked() {
                             self.state.open_windows.insert(OpenWindow::GenesisGamepad);
                             ui.close_menu();
                         }
@@ -664,13 +1212,13 @@ impl App {
 
                     ui.add_space(5.0);
 
-                    ui.menu_button("NES", |ui| {
-                        if ui.button("Keyboard").clicked() {
+                    ui.menu_button(tr!(self, "console-nes"), |ui| {
+                        if ui.button(tr!(self, "menu-keyboard")).clicked() {
                             self.state.open_windows.insert(OpenWindow::NesKeyboard);
                             ui.close_menu();
                         }
 
-                        if ui.button("Gamepad").clicked() {
+                        if ui.button(tr!(self, "menu-gamepad")).clicked() {
                             self.state.open_windows.insert(OpenWindow::NesGamepad);
                             ui.close_menu();
                         }
@@ -678,18 +1226,18 @@ impl App {
 
                     ui.add_space(5.0);
 
-                    ui.menu_button("SNES", |ui| {
-                        if ui.button("Keyboard").clicked() {
+                    ui.menu_button(tr!(self, "console-snes"), |ui| {
+                        if ui.button(tr!(self, "menu-keyboard")).clicked() {
                             self.state.open_windows.insert(OpenWindow::SnesKeyboard);
                             ui.close_menu();
                         }
 
-                        if ui.button("Gamepad").clicked() {
+                        if ui.button(tr!(self, "menu-gamepad")).clicked() {
                             self.state.open_windows.insert(OpenWindow::SnesGamepad);
                             ui.close_menu();
                         }
 
-                        if ui.button("Peripherals").clicked() {
+                        if ui.button(tr!(self, "menu-peripherals")).clicked() {
                             self.state.open_windows.insert(OpenWindow::SnesPeripherals);
                             ui.close_menu();
                         }
@@ -697,13 +1245,13 @@ impl App {
 
                     ui.add_space(5.0);
 
-                    ui.menu_button("Game Boy", |ui| {
-                        if ui.button("Keyboard").clicked() {
+                    ui.menu_button(tr!(self, "console-gb"), |ui| {
+                        if ui.button(tr!(self, "menu-keyboard")).clicked() {
                             self.state.open_windows.insert(OpenWindow::GameBoyKeyboard);
                             ui.close_menu();
                         }
 
-                        if ui.button("Gamepad").clicked() {
+                        if ui.button(tr!(self, "menu-gamepad")).clicked() {
                             self.state.open_windows.insert(OpenWindow::GameBoyGamepad);
                             ui.close_menu();
                         }
@@ -711,14 +1259,28 @@ impl App {
 
                     ui.add_space(5.0);
 
-                    if ui.button("Hotkeys").clicked() {
+                    ui.menu_button(tr!(self, "console-pce"), |ui| {
+                        if ui.button(tr!(self, "menu-keyboard")).clicked() {
+                            self.state.open_windows.insert(OpenWindow::PceKeyboard);
+                            ui.close_menu();
+                        }
+
+                        if ui.button(tr!(self, "menu-gamepad")).clicked() {
+                            self.state.open_windows.insert(OpenWindow::PceGamepad);
+                            ui.close_menu();
+                        }
+                    });
+
+                    ui.add_space(5.0);
+
+                    if ui.button(tr!(self, "menu-hotkeys")).clicked() {
                         self.state.open_windows.insert(OpenWindow::Hotkeys);
                         ui.close_menu();
                     }
                 });
 
-                ui.menu_button("Help", |ui| {
-                    if ui.button("About").clicked() {
+                ui.menu_button(tr!(self, "menu-help"), |ui| {
+                    if ui.button(tr!(self, "menu-about")).clicked() {
                         self.state.open_windows.insert(OpenWindow::About);
                         ui.close_menu();
                     }
@@ -732,8 +1294,9 @@ impl App {
             ui.set_enabled(!self.state.error_window_open);
 
             if self.state.rom_list.borrow().is_empty() {
+                let label = tr!(self, "romlist-configure-search-dir");
                 ui.centered_and_justified(|ui| {
-                    if ui.selectable_label(false, "Configure ROM search directory").clicked() {
+                    if ui.selectable_label(false, label).clicked() {
                         self.add_rom_search_directory();
                     }
                 });
@@ -742,6 +1305,19 @@ impl App {
 
                 self.render_central_panel_filters(ui);
 
+                let name_header = tr!(self, "romlist-header-name");
+                let console_header = tr!(self, "romlist-header-console");
+                let size_header = tr!(self, "romlist-header-size");
+                let play_time_header = tr!(self, "romlist-header-play-time");
+                let last_played_header = tr!(self, "romlist-header-last-played");
+                let auto_save_state_label = tr!(self, "romlist-auto-save-state");
+                let manage_patches_label = tr!(self, "romlist-manage-patches");
+                let compatibility_label = tr!(self, "romlist-compatibility");
+                let compatibility_options: Vec<_> = iter::once(None)
+                    .chain(Compatibility::ALL.into_iter().map(Some))
+                    .map(|compatibility| (compatibility, self.compatibility_label(compatibility)))
+                    .collect();
+
                 ui.add_space(15.0);
 
                 TableBuilder::new(ui)
@@ -749,24 +1325,36 @@ impl App {
                     .striped(true)
                     .cell_layout(Layout::left_to_right(Align::Center))
                     .column(Column::auto().at_most(300.0))
-                    .columns(Column::auto(), 2)
+                    .columns(Column::auto(), 4)
                     .column(Column::remainder())
                     .header(30.0, |mut row| {
                         row.col(|ui| {
                             ui.vertical_centered(|ui| {
-                                ui.heading("Name");
+                                ui.heading(name_header);
+                            });
+                        });
+
+                        row.col(|ui| {
+                            ui.vertical_centered(|ui| {
+                                ui.heading(console_header);
+                            });
+                        });
+
+                        row.col(|ui| {
+                            ui.vertical_centered(|ui| {
+                                ui.heading(size_header);
                             });
                         });
 
                         row.col(|ui| {
                             ui.vertical_centered(|ui| {
-                                ui.heading("Console");
+                                ui.heading(play_time_header);
                             });
                         });
 
                         row.col(|ui| {
                             ui.vertical_centered(|ui| {
-                                ui.heading("Size");
+                                ui.heading(last_played_header);
                             });
                         });
 
@@ -778,15 +1366,65 @@ impl App {
                         for metadata in self.config.list_filters.apply(&rom_list.borrow()) {
                             body.row(40.0, |mut row| {
                                 row.col(|ui| {
-                                    if Button::new(&metadata.file_name_no_ext)
+                                    let display_name = self.config.rom_display_name(metadata);
+                                    let response = Button::new(display_name)
                                         .min_size(Vec2::new(300.0, 30.0))
                                         .wrap(true)
-                                        .ui(ui)
-                                        .clicked()
-                                    {
+                                        .ui(ui);
+                                    if response.clicked() {
                                         self.emu_thread.stop_emulator_if_running();
                                         self.launch_emulator(metadata.full_path.clone());
                                     }
+
+                                    response.context_menu(|ui| {
+                                        let mut auto_save_state = self
+                                            .config
+                                            .auto_save_state_enabled(&metadata.full_path);
+                                        if ui
+                                            .checkbox(
+                                                &mut auto_save_state,
+                                                auto_save_state_label.as_str(),
+                                            )
+                                            .changed()
+                                        {
+                                            self.config.set_auto_save_state(
+                                                &metadata.full_path,
+                                                auto_save_state,
+                                            );
+                                            ui.close_menu();
+                                        }
+
+                                        if ui.button(manage_patches_label.as_str()).clicked() {
+                                            self.open_patch_manager(metadata.clone());
+                                            ui.close_menu();
+                                        }
+
+                                        // Compatibility can only be rated after playing the game
+                                        let play_stats = self.play_stats.get(&metadata.full_path);
+                                        let mut current = play_stats
+                                            .and_then(|play_stats| play_stats.compatibility);
+                                        ui.add_enabled_ui(play_stats.is_some(), |ui| {
+                                            ui.menu_button(compatibility_label.as_str(), |ui| {
+                                                for (compatibility, label) in &compatibility_options
+                                                {
+                                                    if ui
+                                                        .radio_value(
+                                                            &mut current,
+                                                            *compatibility,
+                                                            label.as_str(),
+                                                        )
+                                                        .clicked()
+                                                    {
+                                                        self.play_stats.set_compatibility(
+                                                            &metadata.full_path,
+                                                            current,
+                                                        );
+                                                        ui.close_menu();
+                                                    }
+                                                }
+                                            });
+                                        });
+                                    });
                                 });
 
                                 row.col(|ui| {
@@ -807,6 +1445,29 @@ impl App {
                                     });
                                 });
 
+                                let play_stats = self.play_stats.get(&metadata.full_path);
+
+                                row.col(|ui| {
+                                    ui.centered_and_justified(|ui| {
+                                        if let Some(play_stats) = play_stats {
+                                            ui.label(playstats::format_play_time(
+                                                play_stats.play_time_secs,
+                                            ));
+                                        }
+                                    });
+                                });
+
+                                row.col(|ui| {
+                                    ui.centered_and_justified(|ui| {
+                                        if let Some(last_played) = play_stats
+                                            .and_then(|play_stats| play_stats.last_played)
+                                            .and_then(playstats::format_last_played)
+                                        {
+                                            ui.label(last_played);
+                                        }
+                                    });
+                                });
+
                                 // Blank column to make stripes extend to the right
                                 row.col(|_ui| {});
                             });
@@ -820,10 +1481,10 @@ impl App {
         ui.horizontal(|ui| {
             ui.add(
                 TextEdit::singleline(&mut self.config.list_filters.title_match)
-                    .hint_text("Filter by name"),
+                    .hint_text(tr!(self, "romlist-filter-hint")),
             );
 
-            if ui.button("Clear").clicked() {
+            if ui.button(tr!(self, "romlist-filter-clear")).clicked() {
                 self.config.list_filters.title_match.clear();
             }
 
@@ -836,6 +1497,7 @@ impl App {
             ui.checkbox(&mut self.config.list_filters.nes, "NES");
             ui.checkbox(&mut self.config.list_filters.snes, "SNES");
             ui.checkbox(&mut self.config.list_filters.game_boy, "GB");
+            ui.checkbox(&mut self.config.list_filters.pce, "PCE");
         });
     }
 
@@ -845,8 +1507,10 @@ impl App {
 
         if let Some(error) = error_lock.as_ref() {
             let mut open = true;
-            Window::new("Emulator Error").open(&mut open).resizable(false).show(ctx, |ui| {
-                ui.colored_label(Color32::RED, format!("Emulator terminated with error: {error}"));
+            let title = tr!(self, "error-window-title");
+            let message = tr!(self, "error-emulator-terminated", "error" => error.to_string());
+            Window::new(title).open(&mut open).resizable(false).show(ctx, |ui| {
+                ui.colored_label(Color32::RED, message);
             });
             if !open {
                 *error_lock = None;
@@ -868,13 +1532,25 @@ impl App {
                     }
                 }
             } else if self.emu_thread.status().is_running() {
-                Window::new("Input Configuration").resizable(false).show(ctx, |ui| {
-                    ui.colored_label(Color32::BLUE, "Use the emulator window to configure input");
+                let title = tr!(self, "input-config-window-title");
+                Window::new(title).resizable(false).show(ctx, |ui| {
+                    ui.colored_label(Color32::BLUE, tr!(self, "input-config-instructions"));
                 });
             }
         }
     }
 
+    fn check_recorded_macros(&mut self) {
+        while let Some(sequence) = self.emu_thread.poll_recorded_macro() {
+            // Recorded macros have no trigger until one is assigned in the hotkey settings
+            self.config.inputs.input_macros.push(InputMacroConfig {
+                keyboard_trigger: None,
+                joystick_trigger: None,
+                sequence,
+            });
+        }
+    }
+
     fn reload_config(&mut self) {
         self.emu_thread.reload_config(
             self.config.smsgg_config(self.state.current_file_path.clone()),
@@ -883,6 +1559,7 @@ impl App {
             self.config.nes_config(self.state.current_file_path.clone()),
             self.config.snes_config(self.state.current_file_path.clone()),
             self.config.gb_config(self.state.current_file_path.clone()),
+            self.config.pce_config(self.state.current_file_path.clone()),
         );
     }
 }
@@ -893,9 +1570,18 @@ impl eframe::App for App {
 
         self.check_emulator_error(ctx);
         self.check_waiting_for_input(ctx);
-
-        self.render_menu(ctx, frame);
-        self.render_central_panel(ctx);
+        self.check_recorded_macros();
+        self.check_play_sessions();
+        self.check_quick_menu_request(ctx);
+
+        if self.config.big_picture_mode {
+            self.render_big_picture(ctx);
+        } else if self.state.quick_menu_open {
+            self.render_in_game_quick_menu(ctx);
+        } else {
+            self.render_menu(ctx, frame);
+            self.render_central_panel(ctx);
+        }
 
         for open_window in self.state.open_windows.clone() {
             match open_window {
@@ -904,6 +1590,7 @@ impl eframe::App for App {
                 OpenWindow::NesGeneral => self.render_nes_general_settings(ctx),
                 OpenWindow::SnesGeneral => self.render_snes_general_settings(ctx),
                 OpenWindow::GameBoyGeneral => self.render_gb_general_settings(ctx),
+                OpenWindow::PceGeneral => self.render_pce_general_settings(ctx),
                 OpenWindow::Interface => self.render_interface_settings(ctx),
                 OpenWindow::CommonVideo => self.render_common_video_settings(ctx),
                 OpenWindow::SmsGgVideo => self.render_smsgg_video_settings(ctx),
@@ -911,11 +1598,14 @@ impl eframe::App for App {
                 OpenWindow::NesVideo => self.render_nes_video_settings(ctx),
                 OpenWindow::SnesVideo => self.render_snes_video_settings(ctx),
                 OpenWindow::GameBoyVideo => self.render_gb_video_settings(ctx),
+                OpenWindow::PceVideo => self.render_pce_video_settings(ctx),
                 OpenWindow::CommonAudio => self.render_common_audio_settings(ctx),
                 OpenWindow::SmsGgAudio => self.render_smsgg_audio_settings(ctx),
                 OpenWindow::GenesisAudio => self.render_genesis_audio_settings(ctx),
                 OpenWindow::NesAudio => self.render_nes_audio_settings(ctx),
                 OpenWindow::SnesAudio => self.render_snes_audio_settings(ctx),
+                OpenWindow::GameBoyAudio => self.render_gb_audio_settings(ctx),
+                OpenWindow::PceAudio => self.render_pce_audio_settings(ctx),
                 OpenWindow::SmsGgKeyboard => self.render_smsgg_keyboard_settings(ctx),
                 OpenWindow::SmsGgGamepad => self.render_smsgg_gamepad_settings(ctx),
                 OpenWindow::GenesisKeyboard => self.render_genesis_keyboard_settings(ctx),
@@ -927,12 +1617,23 @@ impl eframe::App for App {
                 OpenWindow::SnesPeripherals => self.render_snes_peripheral_settings(ctx),
                 OpenWindow::GameBoyKeyboard => self.render_gb_keyboard_settings(ctx),
                 OpenWindow::GameBoyGamepad => self.render_gb_joystick_settings(ctx),
+                OpenWindow::PceKeyboard => self.render_pce_keyboard_settings(ctx),
+                OpenWindow::PceGamepad => self.render_pce_joystick_settings(ctx),
                 OpenWindow::Hotkeys => self.render_hotkey_settings(ctx),
+                OpenWindow::NesBarcode => self.render_barcode_window(ctx),
+                OpenWindow::RomPatches => self.render_patch_manager(ctx),
                 OpenWindow::About => self.render_about(ctx),
+                OpenWindow::Migration => self.render_migration_window(ctx),
             }
         }
 
+        self.check_text_input_focus(ctx);
+
         if prev_config != self.config {
+            if self.config.language != self.state.localizer.language() {
+                self.state.localizer = Localizer::new(self.config.language);
+            }
+
             self.state.display_scanlines_warning = should_display_scanlines_warning(&self.config);
 
             if should_reload_config(&prev_config, &self.config) {
@@ -954,6 +1655,8 @@ fn should_reload_config(prev_config: &AppConfig, new_config: &AppConfig) -> bool
         list_filters: ListFilters::default(),
         rom_search_dirs: vec![],
         recent_opens: vec![],
+        big_picture_mode: false,
+        language: UiLanguage::default(),
         ..prev_config.clone()
     };
 
@@ -961,6 +1664,8 @@ fn should_reload_config(prev_config: &AppConfig, new_config: &AppConfig) -> bool
         list_filters: ListFilters::default(),
         rom_search_dirs: vec![],
         recent_opens: vec![],
+        big_picture_mode: false,
+        language: UiLanguage::default(),
         ..new_config.clone()
     };
 
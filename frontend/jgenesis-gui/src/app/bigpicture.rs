@@ -0,0 +1,322 @@
+use crate::app::App;
+use crate::emuthread::{EmuThreadCommand, NavigationInput};
+use egui::{
+    Align, Button, CentralPanel, Context, Image, Key, Layout, RichText, ScrollArea, Vec2,
+    ViewportCommand, Widget,
+};
+use jgenesis_common::command::EmulatorCommand;
+
+const TILE_SIZE: Vec2 = Vec2::new(260.0, 160.0);
+const TILE_SPACING: f32 = 20.0;
+const THUMBNAIL_MAX_SIZE: Vec2 = Vec2::new(120.0, 140.0);
+const QUICK_MENU_BUTTON_SIZE: Vec2 = Vec2::new(400.0, 60.0);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum QuickMenuItem {
+    Resume,
+    SaveState,
+    LoadState,
+    UndoLoadState,
+    SoftReset,
+    HardReset,
+    NextDisc,
+    QuitToLibrary,
+}
+
+impl QuickMenuItem {
+    const ALL: [Self; 8] = [
+        Self::Resume,
+        Self::SaveState,
+        Self::LoadState,
+        Self::UndoLoadState,
+        Self::SoftReset,
+        Self::HardReset,
+        Self::NextDisc,
+        Self::QuitToLibrary,
+    ];
+
+    fn message_id(self) -> &'static str {
+        match self {
+            Self::Resume => "bigpicture-resume",
+            Self::SaveState => "bigpicture-save-state",
+            Self::LoadState => "bigpicture-load-state",
+            Self::UndoLoadState => "bigpicture-undo-load-state",
+            Self::SoftReset => "menu-soft-reset",
+            Self::HardReset => "menu-hard-reset",
+            Self::NextDisc => "menu-next-disc",
+            Self::QuitToLibrary => "bigpicture-quit-to-library",
+        }
+    }
+
+    // Returns None for items that depend on app state and are handled by the app directly
+    fn command(self) -> Option<EmuThreadCommand> {
+        match self {
+            Self::Resume => Some(EmuThreadCommand::FocusEmulator),
+            Self::SaveState => Some(EmuThreadCommand::Emulator(EmulatorCommand::SaveState)),
+            Self::LoadState => Some(EmuThreadCommand::Emulator(EmulatorCommand::LoadState)),
+            Self::UndoLoadState => Some(EmuThreadCommand::UndoLoadState),
+            Self::SoftReset => Some(EmuThreadCommand::Emulator(EmulatorCommand::SoftReset)),
+            Self::HardReset => Some(EmuThreadCommand::Emulator(EmulatorCommand::HardReset)),
+            Self::NextDisc => None,
+            Self::QuitToLibrary => Some(EmuThreadCommand::StopEmulator),
+        }
+    }
+}
+
+// Move a grid selection in response to a directional input, clamping to the bounds of the grid
+fn navigate_grid(selected: usize, len: usize, columns: usize, input: NavigationInput) -> usize {
+    if len == 0 {
+        return 0;
+    }
+
+    let new_selected = match input {
+        NavigationInput::Left => selected.saturating_sub(1),
+        NavigationInput::Right => selected + 1,
+        NavigationInput::Up => selected.saturating_sub(columns),
+        NavigationInput::Down => selected + columns,
+        NavigationInput::Confirm | NavigationInput::Back => selected,
+    };
+    new_selected.min(len - 1)
+}
+
+impl App {
+    pub(super) fn enter_big_picture_mode(&mut self, ctx: &Context) {
+        self.config.big_picture_mode = true;
+        self.state.big_picture_active = true;
+
+        ctx.send_viewport_cmd(ViewportCommand::Fullscreen(true));
+        self.emu_thread.send(EmuThreadCommand::SetGamepadNavigation(Some(ctx.clone())));
+    }
+
+    pub(super) fn exit_big_picture_mode(&mut self, ctx: &Context) {
+        self.config.big_picture_mode = false;
+        self.state.big_picture_active = false;
+
+        ctx.send_viewport_cmd(ViewportCommand::Fullscreen(false));
+        self.emu_thread.send(EmuThreadCommand::SetGamepadNavigation(None));
+    }
+
+    pub(super) fn render_big_picture(&mut self, ctx: &Context) {
+        if !self.state.big_picture_active {
+            // Big picture mode was persisted in config from a previous session
+            self.enter_big_picture_mode(ctx);
+        }
+
+        let inputs = self.collect_navigation_inputs(ctx);
+
+        if self.emu_thread.status().is_running() {
+            self.render_quick_menu(ctx, &inputs);
+        } else {
+            self.render_library_grid(ctx, &inputs);
+        }
+    }
+
+    // The quick menu hotkey can be pressed in the emulator window whether or not big picture mode
+    // is enabled
+    pub(super) fn check_quick_menu_request(&mut self, ctx: &Context) {
+        if self.emu_thread.poll_quick_menu_request() {
+            self.state.quick_menu_open = true;
+            self.state.quick_menu_selected = 0;
+            ctx.send_viewport_cmd(ViewportCommand::Minimized(false));
+            ctx.send_viewport_cmd(ViewportCommand::Focus);
+        }
+
+        if !self.emu_thread.status().is_running() {
+            self.state.quick_menu_open = false;
+        }
+    }
+
+    pub(super) fn render_in_game_quick_menu(&mut self, ctx: &Context) {
+        let inputs = self.collect_navigation_inputs(ctx);
+        self.render_quick_menu(ctx, &inputs);
+    }
+
+    fn collect_navigation_inputs(&mut self, ctx: &Context) -> Vec<NavigationInput> {
+        let mut inputs = Vec::new();
+        while let Some(input) = self.emu_thread.poll_navigation_input() {
+            inputs.push(input);
+        }
+
+        ctx.input(|input| {
+            for (key, navigation_input) in [
+                (Key::ArrowUp, NavigationInput::Up),
+                (Key::ArrowDown, NavigationInput::Down),
+                (Key::ArrowLeft, NavigationInput::Left),
+                (Key::ArrowRight, NavigationInput::Right),
+                (Key::Enter, NavigationInput::Confirm),
+                (Key::Escape, NavigationInput::Back),
+            ] {
+                if input.key_pressed(key) {
+                    inputs.push(navigation_input);
+                }
+            }
+        });
+
+        inputs
+    }
+
+    fn render_library_grid(&mut self, ctx: &Context, inputs: &[NavigationInput]) {
+        let rom_list = self.state.rom_list.borrow().clone();
+        let roms: Vec<_> = self.config.list_filters.apply(&rom_list).collect();
+
+        if self.config.thumbnails.enabled {
+            self.thumbnails.update(ctx);
+        }
+
+        CentralPanel::default().show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.heading(RichText::new(tr!(self, "bigpicture-library")).size(32.0));
+
+                ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                    if ui.button(tr!(self, "bigpicture-exit")).clicked() {
+                        self.exit_big_picture_mode(ctx);
+                    }
+                });
+            });
+
+            ui.add_space(TILE_SPACING);
+
+            if roms.is_empty() {
+                ui.centered_and_justified(|ui| {
+                    ui.label(RichText::new(tr!(self, "bigpicture-no-roms")).size(24.0));
+                });
+                return;
+            }
+
+            let columns = (((ui.available_width() + TILE_SPACING) / (TILE_SIZE.x + TILE_SPACING))
+                as usize)
+                .max(1);
+
+            let prev_selected = self.state.big_picture_selected;
+            let mut selected = prev_selected.min(roms.len() - 1);
+            let mut launch = None;
+            for &input in inputs {
+                match input {
+                    NavigationInput::Confirm => launch = Some(selected),
+                    NavigationInput::Back => {
+                        self.exit_big_picture_mode(ctx);
+                        return;
+                    }
+                    _ => selected = navigate_grid(selected, roms.len(), columns, input),
+                }
+            }
+
+            ScrollArea::vertical().auto_shrink([false; 2]).show(ui, |ui| {
+                for (row_idx, row) in roms.chunks(columns).enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.spacing_mut().item_spacing.x = TILE_SPACING;
+
+                        for (col_idx, metadata) in row.iter().enumerate() {
+                            let idx = row_idx * columns + col_idx;
+                            let text = RichText::new(format!(
+                                "{}\n\n{}",
+                                self.config.rom_display_name(metadata),
+                                metadata.console.to_str()
+                            ))
+                            .size(18.0);
+
+                            let thumbnail = if self.config.thumbnails.enabled {
+                                self.thumbnails.get(ctx, &self.config.thumbnails, metadata).cloned()
+                            } else {
+                                None
+                            };
+                            let button = match &thumbnail {
+                                Some(texture) => Button::image_and_text(
+                                    Image::from_texture(texture).max_size(THUMBNAIL_MAX_SIZE),
+                                    text,
+                                ),
+                                None => Button::new(text),
+                            };
+
+                            let response = button
+                                .min_size(TILE_SIZE)
+                                .wrap(true)
+                                .selected(idx == selected)
+                                .ui(ui);
+                            if response.clicked() {
+                                launch = Some(idx);
+                            }
+
+                            if idx == selected && selected != prev_selected {
+                                response.scroll_to_me(Some(Align::Center));
+                            }
+                        }
+                    });
+
+                    ui.add_space(TILE_SPACING);
+                }
+            });
+
+            self.state.big_picture_selected = selected;
+
+            if let Some(idx) = launch {
+                self.state.big_picture_selected = idx;
+                self.launch_emulator(roms[idx].full_path.clone());
+            }
+        });
+    }
+
+    fn render_quick_menu(&mut self, ctx: &Context, inputs: &[NavigationInput]) {
+        let has_disc_playlist = self.has_disc_playlist();
+        let items: Vec<_> = QuickMenuItem::ALL
+            .into_iter()
+            .filter(|&item| item != QuickMenuItem::NextDisc || has_disc_playlist)
+            .collect();
+
+        let len = items.len();
+        let mut selected = self.state.quick_menu_selected.min(len - 1);
+        let mut chosen = None;
+        for &input in inputs {
+            match input {
+                NavigationInput::Up => selected = selected.saturating_sub(1),
+                NavigationInput::Down => selected = (selected + 1).min(len - 1),
+                NavigationInput::Confirm => chosen = Some(items[selected]),
+                NavigationInput::Back => chosen = Some(QuickMenuItem::Resume),
+                NavigationInput::Left | NavigationInput::Right => {}
+            }
+        }
+
+        CentralPanel::default().show(ctx, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.add_space(TILE_SPACING);
+                ui.heading(RichText::new(tr!(self, "bigpicture-quick-menu")).size(32.0));
+                ui.add_space(TILE_SPACING);
+
+                for (idx, &item) in items.iter().enumerate() {
+                    let label = match item {
+                        QuickMenuItem::NextDisc => self.next_disc_label(),
+                        _ => tr!(self, item.message_id()),
+                    };
+                    let label = RichText::new(label).size(24.0);
+                    let response = Button::new(label)
+                        .min_size(QUICK_MENU_BUTTON_SIZE)
+                        .selected(idx == selected)
+                        .ui(ui);
+                    if response.clicked() {
+                        selected = idx;
+                        chosen = Some(item);
+                    }
+
+                    ui.add_space(10.0);
+                }
+            });
+        });
+
+        self.state.quick_menu_selected = selected;
+
+        if let Some(item) = chosen {
+            match item.command() {
+                Some(command) => self.emu_thread.send(command),
+                None => self.next_disc(),
+            }
+
+            if matches!(item, QuickMenuItem::Resume | QuickMenuItem::QuitToLibrary) {
+                self.state.quick_menu_open = false;
+            }
+
+            if item == QuickMenuItem::QuitToLibrary {
+                self.state.quick_menu_selected = 0;
+            }
+        }
+    }
+}
@@ -1,10 +1,22 @@
+use crate::app::i18n::Localizer;
 use crate::app::{App, AppConfig, NumericTextEdit, OpenWindow};
 use eframe::epaint::Color32;
-use egui::{Context, TextEdit, Widget, Window};
-use jgenesis_native_driver::config::{CommonConfig, WindowSize};
+use egui::{Context, Slider, TextEdit, Ui, Widget, Window};
+use jgenesis_common::audio::ResamplerQuality;
+use jgenesis_common::rng::InitialRamState;
+use jgenesis_native_driver::config::input::SteamDeckInputDefaults;
+use jgenesis_native_driver::config::{
+    AudioPostProcessingConfig, CommonConfig, DiscordConfig, SaveSyncConfig, SaveSyncProtocol,
+    Secret, WindowSize,
+};
+use jgenesis_native_driver::steamdeck::SteamDeckMode;
 use jgenesis_renderer::config::{
-    FilterMode, PreprocessShader, PrescaleFactor, RendererConfig, Scanlines, VSyncMode, WgpuBackend,
+    ColorBlindFilter, FilterMode, MagnifierCorner, MagnifierMode, PreprocessShader, PrescaleFactor,
+    RendererConfig, Scanlines, VSyncMode, WgpuBackend,
 };
+use jgenesis_renderer::deinterlace::DeinterlaceMode;
+use jgenesis_renderer::frameskip::DEFAULT_MAX_FRAME_SKIP;
+use rfd::FileDialog;
 use serde::{Deserialize, Serialize};
 use std::num::NonZeroU32;
 
@@ -19,7 +31,13 @@ pub struct CommonAppConfig {
     #[serde(default = "default_audio_sync_threshold")]
     pub audio_sync_threshold: u32,
     #[serde(default)]
+    pub audio_latency_ms: Option<u32>,
+    #[serde(default = "true_fn")]
+    pub audio_underrun_recovery: bool,
+    #[serde(default)]
     pub audio_gain_db: f64,
+    #[serde(default)]
+    pub audio_resampler_quality: ResamplerQuality,
     pub window_width: Option<u32>,
     pub window_height: Option<u32>,
     #[serde(default)]
@@ -35,15 +53,67 @@ pub struct CommonAppConfig {
     #[serde(default)]
     pub force_integer_height_scaling: bool,
     #[serde(default)]
+    pub pal_50hz_fullscreen: bool,
+    #[serde(default)]
+    pub show_border: bool,
+    #[serde(default)]
+    pub border_image_path: Option<String>,
+    #[serde(default)]
     pub filter_mode: FilterMode,
     #[serde(default)]
     pub preprocess_shader: PreprocessShader,
+    #[serde(default)]
+    pub color_blind_filter: ColorBlindFilter,
+    #[serde(default)]
+    pub flash_reduction: bool,
+    #[serde(default)]
+    pub magnifier_mode: MagnifierMode,
+    #[serde(default)]
+    pub magnifier_corner: MagnifierCorner,
+    #[serde(default)]
+    pub auto_frame_skip: bool,
+    #[serde(default = "default_max_frame_skip")]
+    pub max_frame_skip: u32,
     #[serde(default = "default_fast_forward_multiplier")]
     pub fast_forward_multiplier: u64,
     #[serde(default = "default_rewind_buffer_length")]
     pub rewind_buffer_length_seconds: u64,
+    #[serde(default = "default_input_trace_length")]
+    pub input_trace_length_seconds: u64,
     #[serde(default)]
     pub hide_cursor_over_window: bool,
+    #[serde(default)]
+    pub auto_save_state: bool,
+    #[serde(default)]
+    pub practice_end_frame_count: u64,
+    #[serde(default)]
+    pub practice_end_condition: String,
+    #[serde(default)]
+    pub steam_deck_mode: SteamDeckMode,
+    #[serde(default)]
+    pub low_power_profile: bool,
+    #[serde(default)]
+    pub initial_ram_state: Option<InitialRamState>,
+    #[serde(default)]
+    pub rng_seed: Option<u64>,
+    #[serde(default)]
+    pub save_sync_enabled: bool,
+    #[serde(default)]
+    pub save_sync_protocol: SaveSyncProtocol,
+    #[serde(default)]
+    pub save_sync_url: String,
+    #[serde(default)]
+    pub save_sync_username: String,
+    #[serde(default)]
+    pub save_sync_password: Secret,
+    #[serde(default = "default_save_sync_s3_region")]
+    pub save_sync_s3_region: String,
+    #[serde(default)]
+    pub discord_enabled: bool,
+    #[serde(default)]
+    pub discord_application_id: String,
+    #[serde(default)]
+    pub discord_show_game_title: bool,
 }
 
 impl CommonAppConfig {
@@ -53,6 +123,23 @@ impl CommonAppConfig {
             _ => None,
         }
     }
+
+    fn save_sync_config(&self) -> Option<SaveSyncConfig> {
+        self.save_sync_enabled.then(|| SaveSyncConfig {
+            protocol: self.save_sync_protocol,
+            url: self.save_sync_url.clone(),
+            username: self.save_sync_username.clone(),
+            password: self.save_sync_password.clone(),
+            s3_region: self.save_sync_s3_region.clone(),
+        })
+    }
+
+    fn discord_config(&self) -> Option<DiscordConfig> {
+        self.discord_enabled.then(|| DiscordConfig {
+            application_id: self.discord_application_id.clone(),
+            show_game_title: self.discord_show_game_title,
+        })
+    }
 }
 
 impl Default for CommonAppConfig {
@@ -81,6 +168,10 @@ fn default_prescale_factor() -> PrescaleFactor {
     PrescaleFactor::from(NonZeroU32::new(3).unwrap())
 }
 
+fn default_max_frame_skip() -> u32 {
+    DEFAULT_MAX_FRAME_SKIP
+}
+
 fn default_fast_forward_multiplier() -> u64 {
     2
 }
@@ -89,20 +180,49 @@ fn default_rewind_buffer_length() -> u64 {
     10
 }
 
+fn default_input_trace_length() -> u64 {
+    30
+}
+
+fn default_save_sync_s3_region() -> String {
+    "us-east-1".into()
+}
+
 impl AppConfig {
-    pub(super) fn common_config<KC, JC>(
+    pub(super) fn auto_save_state_enabled(&self, rom_path: &str) -> bool {
+        self.auto_save_state_overrides.get(rom_path).copied().unwrap_or(self.common.auto_save_state)
+    }
+
+    pub(super) fn set_auto_save_state(&mut self, rom_path: &str, enabled: bool) {
+        if enabled == self.common.auto_save_state {
+            self.auto_save_state_overrides.remove(rom_path);
+        } else {
+            self.auto_save_state_overrides.insert(rom_path.into(), enabled);
+        }
+    }
+
+    pub(super) fn common_config<KC, JC: SteamDeckInputDefaults>(
         &self,
         path: String,
         keyboard_inputs: KC,
         joystick_inputs: JC,
+        audio_post_processing: AudioPostProcessingConfig,
     ) -> CommonConfig<KC, JC> {
-        CommonConfig {
+        let auto_save_state = self.auto_save_state_enabled(&path);
+        let rom_patch_paths = self.enabled_rom_patches(&path);
+
+        let mut config = CommonConfig {
             rom_file_path: path,
+            rom_patch_paths,
             audio_sync: self.common.audio_sync,
             audio_device_queue_size: self.common.audio_device_queue_size,
             internal_audio_buffer_size: self.common.internal_audio_buffer_size,
             audio_sync_threshold: self.common.audio_sync_threshold,
+            audio_latency_ms: self.common.audio_latency_ms,
+            audio_underrun_recovery: self.common.audio_underrun_recovery,
             audio_gain_db: self.common.audio_gain_db,
+            audio_post_processing,
+            audio_resampler_quality: self.common.audio_resampler_quality,
             window_size: self.common.window_size(),
             renderer_config: RendererConfig {
                 wgpu_backend: self.common.wgpu_backend,
@@ -112,17 +232,53 @@ impl AppConfig {
                 force_integer_height_scaling: self.common.force_integer_height_scaling,
                 filter_mode: self.common.filter_mode,
                 preprocess_shader: self.common.preprocess_shader,
+                color_blind_filter: self.common.color_blind_filter,
+                flash_reduction: self.common.flash_reduction,
+                magnifier_mode: self.common.magnifier_mode,
+                magnifier_corner: self.common.magnifier_corner,
                 use_webgl2_limits: false,
+                pal_50hz_fullscreen: self.common.pal_50hz_fullscreen,
+                show_border: self.common.show_border,
             },
+            border_image_path: self.common.border_image_path.clone(),
             fast_forward_multiplier: self.common.fast_forward_multiplier,
             rewind_buffer_length_seconds: self.common.rewind_buffer_length_seconds,
+            input_trace_length_seconds: self.common.input_trace_length_seconds,
+            auto_save_state,
+            practice_end_frame_count: self.common.practice_end_frame_count,
+            practice_end_condition: self.common.practice_end_condition.clone(),
             launch_in_fullscreen: self.common.launch_in_fullscreen,
+            auto_frame_skip: self.common.auto_frame_skip,
+            max_frame_skip: self.common.max_frame_skip,
             keyboard_inputs,
             axis_deadzone: self.inputs.axis_deadzone,
+            pause_on_controller_disconnect: self.inputs.pause_on_controller_disconnect,
             joystick_inputs,
             hotkeys: self.inputs.hotkeys.clone(),
+            input_macros: self.inputs.input_macros.clone(),
             hide_cursor_over_window: self.common.hide_cursor_over_window,
+            osd_messages: Localizer::new(self.language).osd_messages(),
+            gdb_port: None,
+            input_injection_port: None,
+            remote_control: None,
+            symbol_file_path: None,
+            initial_ram_state: self.common.initial_ram_state,
+            rng_seed: self.common.rng_seed,
+            av_dump_path: None,
+            save_sync: self.common.save_sync_config(),
+            discord: self.common.discord_config(),
+            steam_deck_mode: false,
+        };
+
+        if self.common.steam_deck_mode.is_active() {
+            config.apply_steam_deck_mode();
         }
+
+        if self.common.low_power_profile {
+            config.apply_low_power_profile();
+        }
+
+        config
     }
 }
 
@@ -131,116 +287,230 @@ const MAX_PRESCALE_FACTOR: u32 = 20;
 impl App {
     pub(super) fn render_common_video_settings(&mut self, ctx: &Context) {
         let mut open = true;
-        Window::new("General Video Settings").open(&mut open).resizable(false).show(ctx, |ui| {
-            ui.checkbox(&mut self.config.common.launch_in_fullscreen, "Launch in fullscreen");
+        let title = tr!(self, "video-window-title");
+        Window::new(title).open(&mut open).resizable(false).show(ctx, |ui| {
+            ui.checkbox(
+                &mut self.config.common.launch_in_fullscreen,
+                tr!(self, "video-launch-fullscreen"),
+            );
 
             ui.group(|ui| {
                 ui.set_enabled(!self.emu_thread.status().is_running());
 
-                ui.label("wgpu backend");
+                ui.label(tr!(self, "video-wgpu-backend"));
                 ui.horizontal(|ui| {
-                    ui.radio_value(&mut self.config.common.wgpu_backend, WgpuBackend::Auto, "Auto");
+                    ui.radio_value(
+                        &mut self.config.common.wgpu_backend,
+                        WgpuBackend::Auto,
+                        tr!(self, "option-auto"),
+                    );
                     ui.radio_value(
                         &mut self.config.common.wgpu_backend,
                         WgpuBackend::Vulkan,
-                        "Vulkan",
+                        tr!(self, "video-wgpu-vulkan"),
                     );
                     ui.radio_value(
                         &mut self.config.common.wgpu_backend,
                         WgpuBackend::DirectX12,
-                        "DirectX 12",
+                        tr!(self, "video-wgpu-directx12"),
                     );
                     ui.radio_value(
                         &mut self.config.common.wgpu_backend,
                         WgpuBackend::OpenGl,
-                        "OpenGL",
+                        tr!(self, "video-wgpu-opengl"),
                     );
                 });
             });
 
             ui.group(|ui| {
-                ui.label("VSync mode");
+                ui.label(tr!(self, "video-vsync-mode"));
 
                 ui.horizontal(|ui| {
                     ui.radio_value(
                         &mut self.config.common.vsync_mode,
                         VSyncMode::Enabled,
-                        "Enabled",
+                        tr!(self, "option-enabled"),
                     );
                     ui.radio_value(
                         &mut self.config.common.vsync_mode,
                         VSyncMode::Disabled,
-                        "Disabled",
+                        tr!(self, "option-disabled"),
+                    );
+                    ui.radio_value(
+                        &mut self.config.common.vsync_mode,
+                        VSyncMode::Fast,
+                        tr!(self, "video-vsync-fast"),
                     );
-                    ui.radio_value(&mut self.config.common.vsync_mode, VSyncMode::Fast, "Fast");
                 });
             });
 
             ui.group(|ui| {
-                ui.label("Filter mode");
+                ui.label(tr!(self, "video-filter-mode"));
                 ui.horizontal(|ui| {
                     ui.radio_value(
                         &mut self.config.common.filter_mode,
                         FilterMode::Nearest,
-                        "Nearest neighbor",
+                        tr!(self, "video-filter-nearest"),
                     );
                     ui.radio_value(
                         &mut self.config.common.filter_mode,
                         FilterMode::Linear,
-                        "Linear interpolation",
+                        tr!(self, "video-filter-linear"),
                     );
+                    ui.radio_value(
+                        &mut self.config.common.filter_mode,
+                        FilterMode::SharpBilinear,
+                        tr!(self, "video-filter-sharp-bilinear"),
+                    )
+                    .on_hover_text(tr!(self, "video-filter-sharp-bilinear-tooltip"));
                 });
             });
 
             ui.group(|ui| {
-                ui.label("Preprocess shader");
+                ui.label(tr!(self, "video-preprocess-shader"));
 
                 ui.radio_value(
                     &mut self.config.common.preprocess_shader,
                     PreprocessShader::None,
-                    "None",
+                    tr!(self, "option-none"),
                 );
 
                 ui.horizontal(|ui| {
                     ui.radio_value(
                         &mut self.config.common.preprocess_shader,
                         PreprocessShader::HorizontalBlurTwoPixels,
-                        "Horizontal blur (2px)",
+                        tr!(self, "video-shader-blur-2px"),
                     );
                     ui.radio_value(
                         &mut self.config.common.preprocess_shader,
                         PreprocessShader::HorizontalBlurThreePixels,
-                        "Horizontal blur (3px)",
+                        tr!(self, "video-shader-blur-3px"),
                     );
                     ui.radio_value(
                         &mut self.config.common.preprocess_shader,
                         PreprocessShader::HorizontalBlurSnesAdaptive,
-                        "Horizontal blur (SNES adaptive)",
+                        tr!(self, "video-shader-blur-snes-adaptive"),
                     )
-                        .on_hover_text("Always maintains the effect of blurring 3px horizontally at 512px horizontal resolution");
+                    .on_hover_text(tr!(self, "video-shader-blur-snes-adaptive-tooltip"));
                 });
 
                 ui.horizontal(|ui| {
                     ui.radio_value(
                         &mut self.config.common.preprocess_shader,
                         PreprocessShader::AntiDitherWeak,
-                        "Anti-dither (conservative)"
+                        tr!(self, "video-shader-anti-dither-weak"),
                     );
                     ui.radio_value(
                         &mut self.config.common.preprocess_shader,
                         PreprocessShader::AntiDitherStrong,
-                        "Anti-dither (aggressive)"
+                        tr!(self, "video-shader-anti-dither-strong"),
                     );
                 });
             });
 
             ui.group(|ui| {
-                ui.label("Scanlines");
+                ui.label(tr!(self, "video-scanlines"));
 
                 ui.horizontal(|ui| {
-                    ui.radio_value(&mut self.config.common.scanlines, Scanlines::None, "None");
-                    ui.radio_value(&mut self.config.common.scanlines, Scanlines::Dim, "Dim");
-                    ui.radio_value(&mut self.config.common.scanlines, Scanlines::Black, "Black");
+                    ui.radio_value(
+                        &mut self.config.common.scanlines,
+                        Scanlines::None,
+                        tr!(self, "option-none"),
+                    );
+                    ui.radio_value(
+                        &mut self.config.common.scanlines,
+                        Scanlines::Dim,
+                        tr!(self, "video-scanlines-dim"),
+                    );
+                    ui.radio_value(
+                        &mut self.config.common.scanlines,
+                        Scanlines::Black,
+                        tr!(self, "video-scanlines-black"),
+                    );
+                });
+            });
+
+            ui.group(|ui| {
+                ui.label(tr!(self, "video-color-blind-filter"));
+
+                ui.horizontal(|ui| {
+                    ui.radio_value(
+                        &mut self.config.common.color_blind_filter,
+                        ColorBlindFilter::None,
+                        tr!(self, "option-none"),
+                    );
+                    ui.radio_value(
+                        &mut self.config.common.color_blind_filter,
+                        ColorBlindFilter::Protanopia,
+                        tr!(self, "video-color-blind-protanopia"),
+                    );
+                    ui.radio_value(
+                        &mut self.config.common.color_blind_filter,
+                        ColorBlindFilter::Deuteranopia,
+                        tr!(self, "video-color-blind-deuteranopia"),
+                    );
+                    ui.radio_value(
+                        &mut self.config.common.color_blind_filter,
+                        ColorBlindFilter::Tritanopia,
+                        tr!(self, "video-color-blind-tritanopia"),
+                    );
+                });
+            });
+
+            ui.checkbox(
+                &mut self.config.common.flash_reduction,
+                tr!(self, "video-flash-reduction"),
+            )
+            .on_hover_text(tr!(self, "video-flash-reduction-tooltip"));
+
+            ui.group(|ui| {
+                ui.label(tr!(self, "video-magnifier"))
+                    .on_hover_text(tr!(self, "video-magnifier-tooltip"));
+
+                ui.horizontal(|ui| {
+                    ui.radio_value(
+                        &mut self.config.common.magnifier_mode,
+                        MagnifierMode::None,
+                        tr!(self, "option-none"),
+                    );
+                    ui.radio_value(
+                        &mut self.config.common.magnifier_mode,
+                        MagnifierMode::PictureInPicture,
+                        tr!(self, "video-magnifier-pip"),
+                    );
+                    ui.radio_value(
+                        &mut self.config.common.magnifier_mode,
+                        MagnifierMode::FullScreen,
+                        tr!(self, "video-magnifier-full-screen"),
+                    );
+                });
+
+                ui.horizontal(|ui| {
+                    ui.set_enabled(
+                        self.config.common.magnifier_mode == MagnifierMode::PictureInPicture,
+                    );
+
+                    ui.label(tr!(self, "video-magnifier-corner"));
+                    ui.radio_value(
+                        &mut self.config.common.magnifier_corner,
+                        MagnifierCorner::TopLeft,
+                        tr!(self, "video-corner-top-left"),
+                    );
+                    ui.radio_value(
+                        &mut self.config.common.magnifier_corner,
+                        MagnifierCorner::TopRight,
+                        tr!(self, "video-corner-top-right"),
+                    );
+                    ui.radio_value(
+                        &mut self.config.common.magnifier_corner,
+                        MagnifierCorner::BottomLeft,
+                        tr!(self, "video-corner-bottom-left"),
+                    );
+                    ui.radio_value(
+                        &mut self.config.common.magnifier_corner,
+                        MagnifierCorner::BottomRight,
+                        tr!(self, "video-corner-bottom-right"),
+                    );
                 });
             });
 
@@ -268,24 +538,78 @@ impl App {
                     }
                 }
 
-                ui.label("Prescale factor");
+                ui.label(tr!(self, "video-prescale-factor"));
             });
             if self.state.prescale_factor_invalid {
                 ui.colored_label(
                     Color32::RED,
-                    format!(
-                        "Prescale factor must be a non-negative integer <= {MAX_PRESCALE_FACTOR}"
-                    ),
+                    tr!(self, "video-prescale-factor-invalid", "max" => MAX_PRESCALE_FACTOR),
                 );
             }
 
             ui.checkbox(
                 &mut self.config.common.force_integer_height_scaling,
-                "Force integer height scaling",
-            ).on_hover_text("Display area will be the largest possible integer multiple of native height that preserves aspect ratio");
+                tr!(self, "video-integer-height-scaling"),
+            )
+            .on_hover_text(tr!(self, "video-integer-height-scaling-tooltip"));
+
+            ui.checkbox(
+                &mut self.config.common.pal_50hz_fullscreen,
+                tr!(self, "video-pal-50hz-fullscreen"),
+            )
+            .on_hover_text(tr!(self, "video-pal-50hz-fullscreen-tooltip"));
+
+            ui.checkbox(&mut self.config.common.show_border, tr!(self, "video-show-border"))
+                .on_hover_text(tr!(self, "video-show-border-tooltip"));
+
+            ui.horizontal(|ui| {
+                ui.set_enabled(self.config.common.show_border);
+
+                let default_border_label = tr!(self, "video-border-default");
+                let border_path_str = self
+                    .config
+                    .common
+                    .border_image_path
+                    .as_ref()
+                    .map_or(default_border_label.as_str(), String::as_str);
+                if ui.button(border_path_str).clicked() {
+                    if let Some(border_path) =
+                        FileDialog::new().add_filter("png", &["png"]).pick_file()
+                    {
+                        self.config.common.border_image_path =
+                            Some(border_path.to_string_lossy().to_string());
+                    }
+                }
+
+                if ui.button(tr!(self, "option-clear")).clicked() {
+                    self.config.common.border_image_path = None;
+                }
+
+                ui.label(tr!(self, "video-custom-border-image"));
+            })
+            .response
+            .on_hover_text(tr!(self, "video-custom-border-image-tooltip"));
+
+            ui.checkbox(
+                &mut self.config.common.auto_frame_skip,
+                tr!(self, "video-auto-frame-skip"),
+            )
+            .on_hover_text(tr!(self, "video-auto-frame-skip-tooltip"));
+
+            ui.add_enabled(
+                self.config.common.auto_frame_skip,
+                Slider::new(&mut self.config.common.max_frame_skip, 1..=10)
+                    .text(tr!(self, "video-max-frame-skip")),
+            );
+
+            ui.checkbox(
+                &mut self.config.common.low_power_profile,
+                tr!(self, "video-low-power-profile"),
+            )
+            .on_hover_text(tr!(self, "video-low-power-profile-tooltip"));
 
             if self.state.display_scanlines_warning {
-                ui.colored_label(Color32::RED, "Integer height scaling + even-numbered prescale factor strongly recommended when scanlines are enabled");
+                ui.colored_label(Color32::RED, tr!(self, "video-scanlines-warning"));
             }
         });
         if !open {
@@ -299,67 +623,92 @@ impl App {
         const MIN_AUDIO_SYNC_THRESHOLD: u32 = 64;
 
         let mut open = true;
-        Window::new("General Audio Settings").open(&mut open).resizable(false).show(ctx, |ui| {
-            ui.checkbox(&mut self.config.common.audio_sync, "Audio sync enabled");
+        let title = tr!(self, "audio-window-title");
+        Window::new(title).open(&mut open).resizable(false).show(ctx, |ui| {
+            ui.checkbox(&mut self.config.common.audio_sync, tr!(self, "audio-sync"));
+
+            ui.checkbox(&mut self.config.common.audio_underrun_recovery, tr!(self, "audio-underrun-recovery"))
+                .on_hover_text(tr!(self, "audio-underrun-recovery-tooltip"));
 
             ui.add_space(10.0);
 
-            ui.horizontal(|ui| {
-                ui.add(
-                    NumericTextEdit::new(
-                        &mut self.state.audio_device_queue_size_text,
-                        &mut self.config.common.audio_device_queue_size,
-                        &mut self.state.audio_device_queue_size_invalid,
-                    )
-                        .with_validation(|value| value.is_power_of_two() && value >= MIN_DEVICE_QUEUE_SIZE)
-                        .desired_width(TEXT_EDIT_WIDTH)
-                );
+            ui.group(|ui| {
+                ui.label(tr!(self, "audio-latency"));
 
-                ui.label("Audio device queue size (samples)");
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut self.config.common.audio_latency_ms, None, tr!(self, "audio-latency-manual"));
+                    for latency_ms in [30, 50, 80] {
+                        ui.radio_value(
+                            &mut self.config.common.audio_latency_ms,
+                            Some(latency_ms),
+                            tr!(self, "audio-latency-ms", "ms" => latency_ms),
+                        );
+                    }
+                })
+                .response
+                .on_hover_text(tr!(self, "audio-latency-tooltip"));
             });
-            if self.state.audio_device_queue_size_invalid {
-                ui.colored_label(Color32::RED, format!("Audio device queue size must be a power of 2 and must be at least {MIN_DEVICE_QUEUE_SIZE}"));
-            }
 
-            ui.horizontal(|ui| {
-                ui.add(
-                    NumericTextEdit::new(
-                        &mut self.state.internal_audio_buffer_size_text,
-                        &mut self.config.common.internal_audio_buffer_size,
-                        &mut self.state.internal_audio_buffer_size_invalid,
-                    )
-                        .with_validation(|value| value != 0)
-                        .desired_width(TEXT_EDIT_WIDTH)
-                );
+            ui.group(|ui| {
+                ui.set_enabled(self.config.common.audio_latency_ms.is_none());
 
-                ui.label("Internal audio buffer size (samples)");
-            });
-            if self.state.internal_audio_buffer_size_invalid {
-                ui.colored_label(
-                    Color32::RED,
-                    "Internal audio buffer size must be a positive integer",
-                );
-            }
+                ui.horizontal(|ui| {
+                    ui.add(
+                        NumericTextEdit::new(
+                            &mut self.state.audio_device_queue_size_text,
+                            &mut self.config.common.audio_device_queue_size,
+                            &mut self.state.audio_device_queue_size_invalid,
+                        )
+                            .with_validation(|value| value.is_power_of_two() && value >= MIN_DEVICE_QUEUE_SIZE)
+                            .desired_width(TEXT_EDIT_WIDTH)
+                    );
 
-            ui.horizontal(|ui| {
-                ui.add(
-                    NumericTextEdit::new(
-                        &mut self.state.audio_sync_threshold_text,
-                        &mut self.config.common.audio_sync_threshold,
-                        &mut self.state.audio_sync_threshold_invalid,
-                    )
-                        .with_validation(|value| value >= MIN_AUDIO_SYNC_THRESHOLD)
-                        .desired_width(TEXT_EDIT_WIDTH)
-                );
+                    ui.label(tr!(self, "audio-device-queue-size"));
+                });
+                if self.state.audio_device_queue_size_invalid {
+                    ui.colored_label(Color32::RED, tr!(self, "audio-device-queue-size-invalid", "min" => MIN_DEVICE_QUEUE_SIZE));
+                }
+
+                ui.horizontal(|ui| {
+                    ui.add(
+                        NumericTextEdit::new(
+                            &mut self.state.internal_audio_buffer_size_text,
+                            &mut self.config.common.internal_audio_buffer_size,
+                            &mut self.state.internal_audio_buffer_size_invalid,
+                        )
+                            .with_validation(|value| value != 0)
+                            .desired_width(TEXT_EDIT_WIDTH)
+                    );
 
-                ui.label("Audio sync threshold (bytes)");
+                    ui.label(tr!(self, "audio-internal-buffer-size"));
+                });
+                if self.state.internal_audio_buffer_size_invalid {
+                    ui.colored_label(
+                        Color32::RED,
+                        tr!(self, "audio-internal-buffer-size-invalid"),
+                    );
+                }
+
+                ui.horizontal(|ui| {
+                    ui.add(
+                        NumericTextEdit::new(
+                            &mut self.state.audio_sync_threshold_text,
+                            &mut self.config.common.audio_sync_threshold,
+                            &mut self.state.audio_sync_threshold_invalid,
+                        )
+                            .with_validation(|value| value >= MIN_AUDIO_SYNC_THRESHOLD)
+                            .desired_width(TEXT_EDIT_WIDTH)
+                    );
+
+                    ui.label(tr!(self, "audio-sync-threshold"));
+                });
+                if self.state.audio_sync_threshold_invalid {
+                    ui.colored_label(
+                        Color32::RED,
+                        tr!(self, "audio-sync-threshold-invalid", "min" => MIN_AUDIO_SYNC_THRESHOLD),
+                    );
+                }
             });
-            if self.state.audio_sync_threshold_invalid {
-                ui.colored_label(
-                    Color32::RED,
-                    format!("Audio sync threshold must be at least {MIN_AUDIO_SYNC_THRESHOLD}"),
-                );
-            }
 
             ui.horizontal(|ui| {
                 ui.add(
@@ -372,14 +721,128 @@ impl App {
                         .desired_width(TEXT_EDIT_WIDTH)
                 );
 
-                ui.label("Audio gain (dB) (+/-)");
+                ui.label(tr!(self, "audio-gain"));
             });
             if self.state.audio_gain_invalid {
-                ui.colored_label(Color32::RED, "Audio gain must be a finite decimal number");
+                ui.colored_label(Color32::RED, tr!(self, "audio-gain-invalid"));
             }
+
+            ui.group(|ui| {
+                ui.label(tr!(self, "audio-resampler-quality"));
+
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut self.config.common.audio_resampler_quality, ResamplerQuality::High, tr!(self, "audio-resampler-high"));
+                    ui.radio_value(&mut self.config.common.audio_resampler_quality, ResamplerQuality::Low, tr!(self, "audio-resampler-low"));
+                })
+                .response
+                .on_hover_text(tr!(self, "audio-resampler-quality-tooltip"));
+            });
         });
         if !open {
             self.state.open_windows.remove(&OpenWindow::CommonAudio);
         }
     }
 }
+
+pub(super) fn render_audio_post_processing_settings(
+    ui: &mut Ui,
+    localizer: &Localizer,
+    config: &mut AudioPostProcessingConfig,
+) {
+    ui.group(|ui| {
+        ui.label(localizer.get("audio-post-processing"));
+
+        ui.checkbox(&mut config.equalizer_enabled, localizer.get("audio-equalizer"));
+
+        ui.group(|ui| {
+            ui.set_enabled(config.equalizer_enabled);
+
+            ui.add(
+                Slider::new(&mut config.eq_low_frequency, 20.0..=1000.0)
+                    .logarithmic(true)
+                    .text(localizer.get("audio-eq-low-frequency")),
+            );
+            ui.add(
+                Slider::new(&mut config.eq_low_gain_db, -12.0..=12.0)
+                    .text(localizer.get("audio-eq-low-gain")),
+            );
+
+            ui.add(
+                Slider::new(&mut config.eq_mid_frequency, 200.0..=8000.0)
+                    .logarithmic(true)
+                    .text(localizer.get("audio-eq-mid-frequency")),
+            );
+            ui.add(
+                Slider::new(&mut config.eq_mid_gain_db, -12.0..=12.0)
+                    .text(localizer.get("audio-eq-mid-gain")),
+            );
+            ui.add(
+                Slider::new(&mut config.eq_mid_q, 0.1..=10.0)
+                    .logarithmic(true)
+                    .text(localizer.get("audio-eq-mid-q")),
+            );
+
+            ui.add(
+                Slider::new(&mut config.eq_high_frequency, 1000.0..=20000.0)
+                    .logarithmic(true)
+                    .text(localizer.get("audio-eq-high-frequency")),
+            );
+            ui.add(
+                Slider::new(&mut config.eq_high_gain_db, -12.0..=12.0)
+                    .text(localizer.get("audio-eq-high-gain")),
+            );
+        });
+
+        ui.add(
+            Slider::new(&mut config.stereo_widening, 0.0..=1.0)
+                .text(localizer.get("audio-stereo-widening")),
+        )
+        .on_hover_text(localizer.get("audio-stereo-widening-tooltip"));
+
+        ui.add(
+            Slider::new(&mut config.crossfeed, 0.0..=1.0).text(localizer.get("audio-crossfeed")),
+        )
+        .on_hover_text(localizer.get("audio-crossfeed-tooltip"));
+
+        ui.checkbox(&mut config.soft_limiter_enabled, localizer.get("audio-soft-limiter"))
+            .on_hover_text(localizer.get("audio-soft-limiter-tooltip"));
+    });
+}
+
+pub(super) fn render_deinterlace_settings(
+    ui: &mut Ui,
+    localizer: &Localizer,
+    deinterlace_mode: &mut DeinterlaceMode,
+) {
+    ui.group(|ui| {
+        ui.label(localizer.get("video-deinterlacing"))
+            .on_hover_text(localizer.get("video-deinterlacing-tooltip"));
+
+        ui.horizontal(|ui| {
+            ui.radio_value(
+                deinterlace_mode,
+                DeinterlaceMode::Weave,
+                localizer.get("video-deinterlace-weave"),
+            )
+            .on_hover_text(localizer.get("video-deinterlace-weave-tooltip"));
+            ui.radio_value(
+                deinterlace_mode,
+                DeinterlaceMode::Bob,
+                localizer.get("video-deinterlace-bob"),
+            )
+            .on_hover_text(localizer.get("video-deinterlace-bob-tooltip"));
+            ui.radio_value(
+                deinterlace_mode,
+                DeinterlaceMode::Blend,
+                localizer.get("video-deinterlace-blend"),
+            )
+            .on_hover_text(localizer.get("video-deinterlace-blend-tooltip"));
+            ui.radio_value(
+                deinterlace_mode,
+                DeinterlaceMode::MotionAdaptive,
+                localizer.get("video-deinterlace-motion-adaptive"),
+            )
+            .on_hover_text(localizer.get("video-deinterlace-motion-adaptive-tooltip"));
+        });
+    });
+}
@@ -1,11 +1,12 @@
+use crate::app::common::render_audio_post_processing_settings;
 use crate::app::{App, AppConfig, OpenWindow};
 use crate::emuthread::EmuThreadStatus;
 use egui::{Context, Window};
 use gb_core::api::{GbAspectRatio, GbPalette, GbcColorCorrection};
-use jgenesis_native_driver::config::GameBoyConfig;
+use jgenesis_native_driver::config::{AudioPostProcessingConfig, GameBoyConfig};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct GameBoyAppConfig {
     #[serde(default)]
     force_dmg_mode: bool,
@@ -19,6 +20,8 @@ pub struct GameBoyAppConfig {
     gbc_color_correction: GbcColorCorrection,
     #[serde(default)]
     audio_60hz_hack: bool,
+    #[serde(default)]
+    audio_post_processing: AudioPostProcessingConfig,
 }
 
 impl Default for GameBoyAppConfig {
@@ -34,6 +37,7 @@ impl AppConfig {
                 path,
                 self.inputs.to_gb_keyboard_config(),
                 self.inputs.gb_joystick.clone(),
+                self.game_boy.audio_post_processing,
             ),
             force_dmg_mode: self.game_boy.force_dmg_mode,
             pretend_to_be_gba: self.game_boy.pretend_to_be_gba,
@@ -48,31 +52,25 @@ impl AppConfig {
 impl App {
     pub(super) fn render_gb_general_settings(&mut self, ctx: &Context) {
         let mut open = true;
-        Window::new("Game Boy General Settings")
-            .default_width(400.0)
-            .open(&mut open)
-            .resizable(false)
-            .show(ctx, |ui| {
-                let is_running_gb = self.emu_thread.status() == EmuThreadStatus::RunningGameBoy;
-                ui.add_enabled_ui(!is_running_gb, |ui| {
-                    ui.checkbox(
-                        &mut self.config.game_boy.force_dmg_mode,
-                        "Force DMG mode in software with CGB support",
-                    )
-                    .on_hover_text("DMG = original Game Boy, CGB = Game Boy Color");
-
-                    ui.checkbox(
-                        &mut self.config.game_boy.pretend_to_be_gba,
-                        "Pretend to be a Game Boy Advance",
-                    )
-                    .on_hover_text("For GBC software that alters behavior when run on GBA");
-                });
+        let title = tr!(self, "gb-general-window-title");
+        Window::new(title).default_width(400.0).open(&mut open).resizable(false).show(ctx, |ui| {
+            let is_running_gb = self.emu_thread.status() == EmuThreadStatus::RunningGameBoy;
+            ui.add_enabled_ui(!is_running_gb, |ui| {
+                ui.checkbox(
+                    &mut self.config.game_boy.force_dmg_mode,
+                    tr!(self, "gb-force-dmg-mode"),
+                )
+                .on_hover_text(tr!(self, "gb-force-dmg-mode-tooltip"));
 
                 ui.checkbox(
-                    &mut self.config.game_boy.audio_60hz_hack,
-                    "Target 60 FPS instead of actual hardware speed (~59.73 FPS)",
-                );
+                    &mut self.config.game_boy.pretend_to_be_gba,
+                    tr!(self, "gb-pretend-to-be-gba"),
+                )
+                .on_hover_text(tr!(self, "gb-pretend-to-be-gba-tooltip"));
             });
+
+            ui.checkbox(&mut self.config.game_boy.audio_60hz_hack, tr!(self, "gb-audio-60hz-hack"));
+        });
         if !open {
             self.state.open_windows.remove(&OpenWindow::GameBoyGeneral);
         }
@@ -80,64 +78,65 @@ impl App {
 
     pub(super) fn render_gb_video_settings(&mut self, ctx: &Context) {
         let mut open = true;
-        Window::new("Game Boy Video Settings").open(&mut open).resizable(false).show(ctx, |ui| {
+        let title = tr!(self, "gb-video-window-title");
+        Window::new(title).open(&mut open).resizable(false).show(ctx, |ui| {
             ui.group(|ui| {
-                ui.label("Aspect ratio");
+                ui.label(tr!(self, "aspect-ratio"));
 
                 ui.horizontal(|ui| {
                     ui.radio_value(
                         &mut self.config.game_boy.aspect_ratio,
                         GbAspectRatio::SquarePixels,
-                        "Square pixels",
+                        tr!(self, "aspect-ratio-square-pixels"),
                     );
                     ui.radio_value(
                         &mut self.config.game_boy.aspect_ratio,
                         GbAspectRatio::Stretched,
-                        "Stretched",
+                        tr!(self, "aspect-ratio-stretched"),
                     );
                 });
             });
 
             ui.group(|ui| {
-                ui.label("GB color palette");
+                ui.label(tr!(self, "gb-palette"));
 
                 ui.horizontal(|ui| {
                     ui.radio_value(
                         &mut self.config.game_boy.gb_palette,
                         GbPalette::BlackAndWhite,
-                        "Black and white",
+                        tr!(self, "gb-palette-black-and-white"),
                     );
                     ui.radio_value(
                         &mut self.config.game_boy.gb_palette,
                         GbPalette::GreenTint,
-                        "Green tint",
+                        tr!(self, "gb-palette-green-tint"),
                     );
                     ui.radio_value(
                         &mut self.config.game_boy.gb_palette,
                         GbPalette::LimeGreen,
-                        "Lime green",
+                        tr!(self, "gb-palette-lime-green"),
                     );
                 });
             });
 
             ui.group(|ui| {
-                ui.label("GBC color correction");
+                ui.label(tr!(self, "gb-color-correction"));
 
                 ui.horizontal(|ui| {
                     ui.radio_value(
                         &mut self.config.game_boy.gbc_color_correction,
                         GbcColorCorrection::None,
-                        "None",
+                        tr!(self, "option-none"),
                     );
                     ui.radio_value(
                         &mut self.config.game_boy.gbc_color_correction,
                         GbcColorCorrection::GbcLcd,
-                        "Game Boy Color LCD",
+                        tr!(self, "gb-color-correction-gbc-lcd"),
                     );
                     ui.radio_value(
                         &mut self.config.game_boy.gbc_color_correction,
                         GbcColorCorrection::GbaLcd,
-                        "Game Boy Advance LCD",
+                        tr!(self, "gb-color-correction-gba-lcd"),
                     );
                 });
             });
@@ -146,4 +145,19 @@ impl App {
             self.state.open_windows.remove(&OpenWindow::GameBoyVideo);
         }
     }
+
+    pub(super) fn render_gb_audio_settings(&mut self, ctx: &Context) {
+        let mut open = true;
+        let title = tr!(self, "gb-audio-window-title");
+        Window::new(title).open(&mut open).resizable(false).show(ctx, |ui| {
+            render_audio_post_processing_settings(
+                ui,
+                &self.state.localizer,
+                &mut self.config.game_boy.audio_post_processing,
+            );
+        });
+        if !open {
+            self.state.open_windows.remove(&OpenWindow::GameBoyAudio);
+        }
+    }
 }
@@ -1,19 +1,30 @@
+use crate::app::common::{render_audio_post_processing_settings, render_deinterlace_settings};
+use crate::app::i18n::Localizer;
 use crate::app::{App, AppConfig, OpenWindow};
 use crate::emuthread::EmuThreadStatus;
-use egui::{Context, Window};
-use genesis_core::{GenesisAspectRatio, GenesisRegion};
+use egui::{Button, Context, TextEdit, Ui, Window};
+use genesis_core::{GenesisAspectRatio, GenesisRegion, GenesisRegionSpoof, GenesisSerialPort};
 use jgenesis_common::frontend::TimingMode;
-use jgenesis_native_driver::config::{GenesisConfig, SegaCdConfig};
+use jgenesis_native_driver::config::{
+    AudioPostProcessingConfig, GenesisConfig, LinkTransportKind, SegaCdConfig,
+};
+use jgenesis_native_driver::msumd;
+use jgenesis_renderer::deinterlace::DeinterlaceMode;
 use rfd::FileDialog;
 use serde::{Deserialize, Serialize};
+use std::path::Path;
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct GenesisAppConfig {
     #[serde(default)]
     forced_timing_mode: Option<TimingMode>,
     #[serde(default)]
     forced_region: Option<GenesisRegion>,
     #[serde(default)]
+    region_spoof: GenesisRegionSpoof,
+    #[serde(default)]
+    report_tmss: bool,
+    #[serde(default)]
     aspect_ratio: GenesisAspectRatio,
     #[serde(default = "true_fn")]
     adjust_aspect_ratio_in_2x_resolution: bool,
@@ -25,14 +36,44 @@ pub struct GenesisAppConfig {
     render_vertical_border: bool,
     #[serde(default)]
     render_horizontal_border: bool,
+    #[serde(default)]
+    widescreen: bool,
+    #[serde(default)]
+    widescreen_patches_path: Option<String>,
+    #[serde(default)]
+    hi_res_output: bool,
+    #[serde(default)]
+    expanded_vram: bool,
+    #[serde(default)]
+    tile_dump_directory: Option<String>,
+    #[serde(default)]
+    texture_pack_directory: Option<String>,
     #[serde(default = "true_fn")]
     quantize_ym2612_output: bool,
+    #[serde(default)]
+    audio_post_processing: AudioPostProcessingConfig,
+    #[serde(default)]
+    lock_on_rom_path: Option<String>,
+    #[serde(default)]
+    lock_on_patch_rom_path: Option<String>,
+    #[serde(default)]
+    deinterlace_mode: DeinterlaceMode,
+    #[serde(default)]
+    serial_port: GenesisSerialPort,
+    #[serde(default)]
+    serial_transport: LinkTransportKind,
+    #[serde(default = "default_serial_tcp_address")]
+    serial_tcp_address: String,
 }
 
 const fn true_fn() -> bool {
     true
 }
 
+fn default_serial_tcp_address() -> String {
+    "127.0.0.1:7845".into()
+}
+
 impl Default for GenesisAppConfig {
     fn default() -> Self {
         toml::from_str("").unwrap()
@@ -44,6 +85,10 @@ pub struct SegaCdAppConfig {
     bios_path: Option<String>,
     #[serde(default = "true_fn")]
     enable_ram_cartridge: bool,
+    #[serde(default)]
+    fast_boot: bool,
+    #[serde(default = "true_fn")]
+    enable_msu_md: bool,
 }
 
 impl Default for SegaCdAppConfig {
@@ -59,18 +104,34 @@ impl AppConfig {
                 path,
                 self.inputs.to_genesis_keyboard_config(),
                 self.inputs.to_genesis_joystick_config(),
+                self.genesis.audio_post_processing,
             ),
             p1_controller_type: self.inputs.genesis_p1_type,
             p2_controller_type: self.inputs.genesis_p2_type,
+            multitap: self.inputs.genesis_multitap,
+            serial_port: self.genesis.serial_port,
+            serial_transport: self.genesis.serial_transport,
+            serial_tcp_address: self.genesis.serial_tcp_address.clone(),
             forced_timing_mode: self.genesis.forced_timing_mode,
             forced_region: self.genesis.forced_region,
+            region_spoof: self.genesis.region_spoof,
+            report_tmss: self.genesis.report_tmss,
             aspect_ratio: self.genesis.aspect_ratio,
             adjust_aspect_ratio_in_2x_resolution: self.genesis.adjust_aspect_ratio_in_2x_resolution,
             remove_sprite_limits: self.genesis.remove_sprite_limits,
             emulate_non_linear_vdp_dac: self.genesis.emulate_non_linear_vdp_dac,
             render_vertical_border: self.genesis.render_vertical_border,
             render_horizontal_border: self.genesis.render_horizontal_border,
+            widescreen: self.genesis.widescreen,
+            widescreen_patches_path: self.genesis.widescreen_patches_path.clone(),
+            hi_res_output: self.genesis.hi_res_output,
+            expanded_vram: self.genesis.expanded_vram,
+            tile_dump_directory: self.genesis.tile_dump_directory.clone(),
+            texture_pack_directory: self.genesis.texture_pack_directory.clone(),
             quantize_ym2612_output: self.genesis.quantize_ym2612_output,
+            lock_on_rom_path: self.genesis.lock_on_rom_path.clone(),
+            lock_on_patch_rom_path: self.genesis.lock_on_patch_rom_path.clone(),
+            deinterlace_mode: self.genesis.deinterlace_mode,
         })
     }
 
@@ -79,15 +140,80 @@ impl AppConfig {
             genesis: *self.genesis_config(path),
             bios_file_path: self.sega_cd.bios_path.clone(),
             enable_ram_cartridge: self.sega_cd.enable_ram_cartridge,
+            fast_boot: self.sega_cd.fast_boot,
             run_without_disc: false,
+            mode_1_cartridge_path: None,
         })
     }
+
+    /// Returns a Sega CD Mode 1 config if `path` is an MSU-MD cartridge with a CD image next to it
+    /// and a Sega CD BIOS is configured.
+    pub(super) fn msu_md_config(&self, path: &str) -> Option<Box<SegaCdConfig>> {
+        if !self.sega_cd.enable_msu_md || self.sega_cd.bios_path.is_none() {
+            return None;
+        }
+
+        let disc_path = msumd::find_msu_md_disc(Path::new(path))?;
+        log::info!("Running as an MSU-MD game with track image '{}'", disc_path.display());
+
+        let mut config = self.sega_cd_config(disc_path.to_string_lossy().to_string());
+        config.mode_1_cartridge_path = Some(path.into());
+        Some(config)
+    }
+}
+
+fn render_optional_path(
+    ui: &mut Ui,
+    localizer: &Localizer,
+    path: &mut Option<String>,
+    (filter_name, extensions): (&str, &[&str]),
+    label: &str,
+) {
+    ui.horizontal(|ui| {
+        let none_label = localizer.get("value-none");
+        if ui.button(path.as_deref().unwrap_or(&none_label)).clicked() {
+            if let Some(new_path) =
+                FileDialog::new().add_filter(filter_name, extensions).pick_file()
+            {
+                *path = Some(new_path.to_string_lossy().to_string());
+            }
+        }
+
+        if ui.add_enabled(path.is_some(), Button::new(localizer.get("option-clear"))).clicked() {
+            *path = None;
+        }
+
+        ui.label(label);
+    });
+}
+
+fn render_optional_directory(
+    ui: &mut Ui,
+    localizer: &Localizer,
+    path: &mut Option<String>,
+    label: &str,
+) {
+    ui.horizontal(|ui| {
+        let none_label = localizer.get("value-none");
+        if ui.button(path.as_deref().unwrap_or(&none_label)).clicked() {
+            if let Some(new_path) = FileDialog::new().pick_folder() {
+                *path = Some(new_path.to_string_lossy().to_string());
+            }
+        }
+
+        if ui.add_enabled(path.is_some(), Button::new(localizer.get("option-clear"))).clicked() {
+            *path = None;
+        }
+
+        ui.label(label);
+    });
 }
 
 impl App {
     pub(super) fn render_genesis_general_settings(&mut self, ctx: &Context) {
         let mut open = true;
-        Window::new("Genesis General Settings").open(&mut open).resizable(true).show(ctx, |ui| {
+        let title = tr!(self, "genesis-general-window-title");
+        Window::new(title).open(&mut open).resizable(true).show(ctx, |ui| {
             let emu_thread_status = self.emu_thread.status();
             let running_genesis = emu_thread_status != EmuThreadStatus::RunningGenesis
                 && emu_thread_status != EmuThreadStatus::RunningSegaCd;
@@ -95,19 +221,23 @@ impl App {
             ui.group(|ui| {
                 ui.set_enabled(running_genesis);
 
-                ui.label("Timing / display mode");
+                ui.label(tr!(self, "timing-mode"));
 
                 ui.horizontal(|ui| {
-                    ui.radio_value(&mut self.config.genesis.forced_timing_mode, None, "Auto");
+                    ui.radio_value(
+                        &mut self.config.genesis.forced_timing_mode,
+                        None,
+                        tr!(self, "option-auto"),
+                    );
                     ui.radio_value(
                         &mut self.config.genesis.forced_timing_mode,
                         Some(TimingMode::Ntsc),
-                        "NTSC",
+                        tr!(self, "timing-ntsc"),
                     );
                     ui.radio_value(
                         &mut self.config.genesis.forced_timing_mode,
                         Some(TimingMode::Pal),
-                        "PAL",
+                        tr!(self, "timing-pal"),
                     );
                 });
             });
@@ -115,26 +245,150 @@ impl App {
             ui.group(|ui| {
                 ui.set_enabled(running_genesis);
 
-                ui.label("Region");
+                ui.label(tr!(self, "region"));
 
                 ui.horizontal(|ui| {
-                    ui.radio_value(&mut self.config.genesis.forced_region, None, "Auto");
+                    ui.radio_value(
+                        &mut self.config.genesis.forced_region,
+                        None,
+                        tr!(self, "option-auto"),
+                    );
                     ui.radio_value(
                         &mut self.config.genesis.forced_region,
                         Some(GenesisRegion::Americas),
-                        "Americas",
+                        tr!(self, "region-americas"),
                     );
                     ui.radio_value(
                         &mut self.config.genesis.forced_region,
                         Some(GenesisRegion::Japan),
-                        "Japan",
+                        tr!(self, "region-japan"),
                     );
                     ui.radio_value(
                         &mut self.config.genesis.forced_region,
                         Some(GenesisRegion::Europe),
-                        "Europe",
+                        tr!(self, "region-europe"),
+                    );
+                });
+            });
+
+            ui.group(|ui| {
+                ui.label(tr!(self, "genesis-region-spoof"))
+                    .on_hover_text(tr!(self, "genesis-region-spoof-tooltip"));
+
+                ui.horizontal(|ui| {
+                    ui.radio_value(
+                        &mut self.config.genesis.region_spoof,
+                        GenesisRegionSpoof::Disabled,
+                        tr!(self, "genesis-region-spoof-hardware"),
+                    )
+                    .on_hover_text(tr!(self, "genesis-region-spoof-hardware-tooltip"));
+                    ui.radio_value(
+                        &mut self.config.genesis.region_spoof,
+                        GenesisRegionSpoof::Auto,
+                        tr!(self, "option-auto"),
+                    )
+                    .on_hover_text(tr!(self, "genesis-region-spoof-auto-tooltip"));
+                    ui.radio_value(
+                        &mut self.config.genesis.region_spoof,
+                        GenesisRegionSpoof::Americas,
+                        tr!(self, "region-americas"),
+                    );
+                    ui.radio_value(
+                        &mut self.config.genesis.region_spoof,
+                        GenesisRegionSpoof::Japan,
+                        tr!(self, "region-japan"),
+                    );
+                    ui.radio_value(
+                        &mut self.config.genesis.region_spoof,
+                        GenesisRegionSpoof::Europe,
+                        tr!(self, "region-europe"),
                     );
                 });
+
+                ui.checkbox(&mut self.config.genesis.report_tmss, tr!(self, "genesis-report-tmss"));
+
+                ui.checkbox(
+                    &mut self.config.genesis.expanded_vram,
+                    tr!(self, "genesis-expanded-vram"),
+                )
+                .on_hover_text(tr!(self, "genesis-expanded-vram-tooltip"));
+            });
+
+            ui.group(|ui| {
+                ui.set_enabled(running_genesis);
+
+                ui.label(tr!(self, "genesis-lock-on"));
+
+                render_optional_path(
+                    ui,
+                    &self.state.localizer,
+                    &mut self.config.genesis.lock_on_rom_path,
+                    ("rom", &["md", "bin"]),
+                    &tr!(self, "genesis-lock-on-rom"),
+                );
+                render_optional_path(
+                    ui,
+                    &self.state.localizer,
+                    &mut self.config.genesis.lock_on_patch_rom_path,
+                    ("rom", &["bin"]),
+                    &tr!(self, "genesis-lock-on-patch-rom"),
+                );
+            });
+
+            ui.group(|ui| {
+                ui.set_enabled(running_genesis);
+
+                ui.label(tr!(self, "genesis-serial-port"))
+                    .on_hover_text(tr!(self, "genesis-serial-port-tooltip"));
+
+                ui.horizontal(|ui| {
+                    ui.radio_value(
+                        &mut self.config.genesis.serial_port,
+                        GenesisSerialPort::Port1,
+                        tr!(self, "genesis-serial-port-1"),
+                    );
+                    ui.radio_value(
+                        &mut self.config.genesis.serial_port,
+                        GenesisSerialPort::Port2,
+                        tr!(self, "genesis-serial-port-2"),
+                    );
+                    ui.radio_value(
+                        &mut self.config.genesis.serial_port,
+                        GenesisSerialPort::Extension,
+                        tr!(self, "genesis-serial-port-ext"),
+                    );
+                });
+
+                ui.horizontal(|ui| {
+                    ui.radio_value(
+                        &mut self.config.genesis.serial_transport,
+                        LinkTransportKind::None,
+                        tr!(self, "link-not-connected"),
+                    );
+                    ui.radio_value(
+                        &mut self.config.genesis.serial_transport,
+                        LinkTransportKind::TcpClient,
+                        tr!(self, "link-tcp-client"),
+                    );
+                    ui.radio_value(
+                        &mut self.config.genesis.serial_transport,
+                        LinkTransportKind::TcpServer,
+                        tr!(self, "link-tcp-server"),
+                    );
+                });
+
+                ui.add_enabled_ui(
+                    self.config.genesis.serial_transport != LinkTransportKind::None,
+                    |ui| {
+                        ui.horizontal(|ui| {
+                            ui.add(
+                                TextEdit::singleline(&mut self.config.genesis.serial_tcp_address)
+                                    .desired_width(150.0),
+                            );
+                            ui.label(tr!(self, "link-address"));
+                        });
+                    },
+                );
             });
 
             ui.add_space(5.0);
@@ -142,7 +396,7 @@ impl App {
                 ui.set_enabled(self.emu_thread.status() != EmuThreadStatus::RunningSegaCd);
 
                 let bios_path_str =
-                    self.config.sega_cd.bios_path.as_ref().map_or("<None>", String::as_str);
+                    self.config.sega_cd.bios_path.clone().unwrap_or_else(|| tr!(self, "value-none"));
                 if ui.button(bios_path_str).clicked() {
                     if let Some(bios_path) =
                         FileDialog::new().add_filter("bin", &["bin"]).pick_file()
@@ -152,14 +406,22 @@ impl App {
                     }
                 }
 
-                ui.label("Sega CD BIOS path");
+                ui.label(tr!(self, "genesis-scd-bios-path"));
             });
 
             ui.add_space(5.0);
             ui.checkbox(
                 &mut self.config.sega_cd.enable_ram_cartridge,
-                "Enable Sega CD RAM cartridge",
+                tr!(self, "genesis-scd-ram-cartridge"),
             );
+
+            ui.add_space(5.0);
+            ui.checkbox(&mut self.config.sega_cd.fast_boot, tr!(self, "genesis-scd-fast-boot"))
+                .on_hover_text(tr!(self, "genesis-scd-fast-boot-tooltip"));
+
+            ui.add_space(5.0);
+            ui.checkbox(&mut self.config.sega_cd.enable_msu_md, tr!(self, "genesis-msu-md"))
+                .on_hover_text(tr!(self, "genesis-msu-md-tooltip"));
         });
         if !open {
             self.state.open_windows.remove(&OpenWindow::GenesisGeneral);
@@ -168,61 +430,111 @@ impl App {
 
     pub(super) fn render_genesis_video_settings(&mut self, ctx: &Context) {
         let mut open = true;
-        Window::new("Genesis Video Settings").open(&mut open).resizable(false).show(ctx, |ui| {
+        let title = tr!(self, "genesis-video-window-title");
+        Window::new(title).open(&mut open).resizable(false).show(ctx, |ui| {
             ui.group(|ui| {
-                ui.label("Aspect ratio");
+                ui.label(tr!(self, "aspect-ratio"));
 
                 ui.horizontal(|ui| {
                     ui.radio_value(
                         &mut self.config.genesis.aspect_ratio,
                         GenesisAspectRatio::Ntsc,
-                        "NTSC",
+                        tr!(self, "aspect-ratio-ntsc"),
                     )
-                    .on_hover_text("32:35 pixel aspect ratio in 320px mode, 8:7 in 256px mode");
+                    .on_hover_text(tr!(self, "genesis-aspect-ratio-ntsc-tooltip"));
                     ui.radio_value(
                         &mut self.config.genesis.aspect_ratio,
                         GenesisAspectRatio::Pal,
-                        "PAL",
+                        tr!(self, "aspect-ratio-pal"),
                     )
-                    .on_hover_text("11:10 pixel aspect ratio in 320px mode, 11:8 in 256px mode");
+                    .on_hover_text(tr!(self, "genesis-aspect-ratio-pal-tooltip"));
                     ui.radio_value(
                         &mut self.config.genesis.aspect_ratio,
                         GenesisAspectRatio::SquarePixels,
-                        "Square pixels",
+                        tr!(self, "aspect-ratio-square-pixels"),
                     )
-                    .on_hover_text("1:1 pixel aspect ratio");
+                    .on_hover_text(tr!(self, "aspect-ratio-square-pixels-tooltip"));
                     ui.radio_value(
                         &mut self.config.genesis.aspect_ratio,
                         GenesisAspectRatio::Stretched,
-                        "Stretched",
+                        tr!(self, "aspect-ratio-stretched"),
                     )
-                    .on_hover_text("Stretch image to fill the screen");
+                    .on_hover_text(tr!(self, "aspect-ratio-stretched-tooltip"));
                 });
             });
 
             ui.checkbox(
                 &mut self.config.genesis.adjust_aspect_ratio_in_2x_resolution,
-                "Automatically double pixel aspect ratio in double vertical resolution mode",
+                tr!(self, "genesis-adjust-aspect-ratio-2x"),
             );
 
             ui.checkbox(
                 &mut self.config.genesis.remove_sprite_limits,
-                "Remove sprite-per-scanline and sprite-pixel-per-scanline limits",
+                tr!(self, "remove-sprite-limits"),
             )
-            .on_hover_text("Can reduce sprite flickering, but can also cause visual glitches");
+            .on_hover_text(tr!(self, "remove-sprite-limits-tooltip"));
 
             ui.checkbox(
                 &mut self.config.genesis.emulate_non_linear_vdp_dac,
-                "Emulate the VDP's non-linear color DAC",
+                tr!(self, "genesis-non-linear-dac"),
             )
-            .on_hover_text("Tends to brighten darker colors and darken brighter colors");
+            .on_hover_text(tr!(self, "genesis-non-linear-dac-tooltip"));
 
-            ui.checkbox(&mut self.config.genesis.render_vertical_border, "Render vertical border");
+            ui.checkbox(
+                &mut self.config.genesis.render_vertical_border,
+                tr!(self, "render-vertical-border"),
+            );
 
             ui.checkbox(
                 &mut self.config.genesis.render_horizontal_border,
-                "Render horizontal border",
+                tr!(self, "render-horizontal-border"),
+            );
+
+            ui.add_space(5.0);
+            ui.group(|ui| {
+                ui.checkbox(&mut self.config.genesis.widescreen, tr!(self, "genesis-widescreen"))
+                    .on_hover_text(tr!(self, "genesis-widescreen-tooltip"));
+
+                ui.add_enabled_ui(self.config.genesis.widescreen, |ui| {
+                    render_optional_path(
+                        ui,
+                        &self.state.localizer,
+                        &mut self.config.genesis.widescreen_patches_path,
+                        ("txt", &["txt"]),
+                        &tr!(self, "genesis-widescreen-patches"),
+                    );
+                });
+            });
+
+            ui.add_space(5.0);
+            ui.checkbox(&mut self.config.genesis.hi_res_output, tr!(self, "genesis-hi-res-output"))
+                .on_hover_text(tr!(self, "genesis-hi-res-output-tooltip"));
+
+            ui.add_space(5.0);
+            render_deinterlace_settings(
+                ui,
+                &self.state.localizer,
+                &mut self.config.genesis.deinterlace_mode,
             );
+
+            ui.add_space(5.0);
+            ui.group(|ui| {
+                ui.label(tr!(self, "genesis-tile-textures"))
+                    .on_hover_text(tr!(self, "genesis-tile-textures-tooltip"));
+
+                render_optional_directory(
+                    ui,
+                    &self.state.localizer,
+                    &mut self.config.genesis.tile_dump_directory,
+                    &tr!(self, "genesis-tile-dump-directory"),
+                );
+                render_optional_directory(
+                    ui,
+                    &self.state.localizer,
+                    &mut self.config.genesis.texture_pack_directory,
+                    &tr!(self, "genesis-texture-pack-directory"),
+                );
+            });
         });
         if !open {
             self.state.open_windows.remove(&OpenWindow::GenesisVideo);
@@ -231,13 +543,18 @@ impl App {
 
     pub(super) fn render_genesis_audio_settings(&mut self, ctx: &Context) {
         let mut open = true;
-        Window::new("Genesis Audio Settings").open(&mut open).resizable(false).show(ctx, |ui| {
+        let title = tr!(self, "genesis-audio-window-title");
+        Window::new(title).open(&mut open).resizable(false).show(ctx, |ui| {
             ui.checkbox(
                 &mut self.config.genesis.quantize_ym2612_output,
-                "Quantize YM2612 channel output",
+                tr!(self, "genesis-quantize-ym2612"),
             )
-            .on_hover_text(
-                "Quantize channel outputs from 14 bits to 9 bits to emulate the YM2612's 9-bit DAC",
+            .on_hover_text(tr!(self, "genesis-quantize-ym2612-tooltip"));
+
+            render_audio_post_processing_settings(
+                ui,
+                &self.state.localizer,
+                &mut self.config.genesis.audio_post_processing,
             );
         });
         if !open {
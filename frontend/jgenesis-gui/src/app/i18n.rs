@@ -0,0 +1,208 @@
+//! Localization support for the GUI, backed by Fluent translation files in `locales/`
+//!
+//! To add a new language, add a `locales/<language-id>/main.ftl` file, add a variant to
+//! [`UiLanguage`], and map it to the new file in [`UiLanguage::fluent_source`]. Messages that are
+//! missing from a translation fall back to English.
+
+use fluent_bundle::{FluentArgs, FluentBundle, FluentResource};
+use jgenesis_native_driver::config::OsdMessages;
+use jgenesis_proc_macros::EnumAll;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::HashSet;
+use unic_langid::LanguageIdentifier;
+
+const ENGLISH_SOURCE: &str = include_str!("../../locales/en-US/main.ftl");
+const SPANISH_SOURCE: &str = include_str!("../../locales/es-ES/main.ftl");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, EnumAll)]
+pub enum UiLanguage {
+    #[default]
+    English,
+    Spanish,
+}
+
+impl UiLanguage {
+    fn language_id(self) -> &'static str {
+        match self {
+            Self::English => "en-US",
+            Self::Spanish => "es-ES",
+        }
+    }
+
+    fn fluent_source(self) -> &'static str {
+        match self {
+            Self::English => ENGLISH_SOURCE,
+            Self::Spanish => SPANISH_SOURCE,
+        }
+    }
+
+    /// The language's name in that language, for display in the language selector.
+    pub fn native_name(self) -> &'static str {
+        match self {
+            Self::English => "English",
+            Self::Spanish => "Español",
+        }
+    }
+}
+
+fn new_bundle(language: UiLanguage) -> FluentBundle<FluentResource> {
+    let language_id: LanguageIdentifier =
+        language.language_id().parse().expect("language IDs should be valid");
+
+    let mut bundle = FluentBundle::new(vec![language_id]);
+    // Unicode isolation characters render as boxes in egui
+    bundle.set_use_isolating(false);
+
+    let resource = FluentResource::try_new(language.fluent_source().into()).unwrap_or_else(
+        |(resource, errors)| {
+            log::error!("Errors parsing {language:?} translation file: {errors:?}");
+            resource
+        },
+    );
+    if let Err(errors) = bundle.add_resource(resource) {
+        log::error!("Errors loading {language:?} translation file: {errors:?}");
+    }
+
+    bundle
+}
+
+pub struct Localizer {
+    language: UiLanguage,
+    bundle: FluentBundle<FluentResource>,
+    fallback: Option<FluentBundle<FluentResource>>,
+    // Most messages are looked up every frame, so each missing ID is only logged once
+    reported_missing: RefCell<HashSet<String>>,
+}
+
+impl Localizer {
+    pub fn new(language: UiLanguage) -> Self {
+        let bundle = new_bundle(language);
+        let fallback = (language != UiLanguage::English).then(|| new_bundle(UiLanguage::English));
+
+        Self { language, bundle, fallback, reported_missing: RefCell::new(HashSet::new()) }
+    }
+
+    pub fn language(&self) -> UiLanguage {
+        self.language
+    }
+
+    /// Look up a message by ID.
+    ///
+    /// Falls back to the English message if the current language is missing a translation, and
+    /// falls back to the message ID itself if the English file is also missing it.
+    pub fn get(&self, id: &str) -> String {
+        self.format(id, None)
+    }
+
+    /// Look up a message by ID and fill in its variables from the given arguments.
+    pub fn get_args(&self, id: &str, args: &FluentArgs<'_>) -> String {
+        self.format(id, Some(args))
+    }
+
+    fn format(&self, id: &str, args: Option<&FluentArgs<'_>>) -> String {
+        [Some(&self.bundle), self.fallback.as_ref()]
+            .into_iter()
+            .flatten()
+            .find_map(|bundle| format_message(bundle, id, args))
+            .unwrap_or_else(|| {
+                if self.reported_missing.borrow_mut().insert(id.into()) {
+                    log::warn!("Missing translation for message '{id}'");
+                }
+                id.into()
+            })
+    }
+
+    /// Text for the emulator window's on-screen messages, with the `{slot}` and `{attempt}`
+    /// placeholders that [`OsdMessages`] expects.
+    pub fn osd_messages(&self) -> OsdMessages {
+        let mut slot_args = FluentArgs::new();
+        slot_args.set("slot", "{slot}");
+        let slot = |id| self.get_args(id, &slot_args);
+
+        let mut attempt_args = FluentArgs::new();
+        attempt_args.set("attempt", "{attempt}");
+
+        OsdMessages {
+            slot: slot("osd-slot"),
+            slot_empty: slot("osd-slot-empty"),
+            saved_slot: slot("osd-saved-slot"),
+            loaded_slot: slot("osd-loaded-slot"),
+            resumed: self.get("osd-resumed"),
+            undo_load: self.get("osd-undo-load"),
+            practice_attempt: self.get_args("osd-practice-attempt", &attempt_args),
+            loop_set: self.get("osd-loop-set"),
+            loop_cleared: self.get("osd-loop-cleared"),
+            controller_connected: self.get("osd-controller-connected"),
+            controller_disconnected: self.get("osd-controller-disconnected"),
+            recording_macro: self.get("osd-recording-macro"),
+            macro_recorded: self.get("osd-macro-recorded"),
+            macro_empty: self.get("osd-macro-empty"),
+        }
+    }
+}
+
+fn format_message(
+    bundle: &FluentBundle<FluentResource>,
+    id: &str,
+    args: Option<&FluentArgs<'_>>,
+) -> Option<String> {
+    let pattern = bundle.get_message(id)?.value()?;
+
+    let mut errors = Vec::new();
+    let formatted = bundle.format_pattern(pattern, args, &mut errors);
+    if !errors.is_empty() {
+        log::error!("Errors formatting message '{id}': {errors:?}");
+    }
+
+    Some(formatted.into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn translation_files_load() {
+        for language in UiLanguage::ALL {
+            FluentResource::try_new(language.fluent_source().into())
+                .unwrap_or_else(|(_, errors)| panic!("{language:?} parse errors: {errors:?}"));
+
+            let localizer = Localizer::new(language);
+            assert_ne!(localizer.get("menu-file"), "menu-file", "{language:?}");
+        }
+    }
+
+    #[test]
+    fn translations_define_every_english_message() {
+        let english_ids: Vec<_> = ENGLISH_SOURCE
+            .lines()
+            .filter_map(|line| line.split_once(" = "))
+            .map(|(id, _)| id)
+            .filter(|id| {
+                id.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+            })
+            .collect();
+        assert!(english_ids.contains(&"menu-file"));
+
+        for language in UiLanguage::ALL {
+            let localizer = Localizer::new(language);
+            for id in &english_ids {
+                assert!(localizer.bundle.has_message(id), "{language:?} is missing '{id}'");
+            }
+        }
+    }
+
+    #[test]
+    fn missing_message_falls_back_to_id() {
+        let localizer = Localizer::new(UiLanguage::Spanish);
+        assert_eq!(localizer.get("not-a-message"), "not-a-message");
+        assert_eq!(localizer.get("not-a-message"), "not-a-message");
+        assert_eq!(localizer.reported_missing.borrow().len(), 1);
+    }
+
+    #[test]
+    fn english_osd_messages_match_defaults() {
+        assert_eq!(Localizer::new(UiLanguage::English).osd_messages(), OsdMessages::default());
+    }
+}
@@ -1,18 +1,19 @@
 use crate::app::{App, NumericTextEdit, OpenWindow};
 use crate::emuthread::{EmuThreadCommand, GenericInput, InputType};
-use egui::{Color32, Context, Grid, Ui, Window};
-use genesis_core::GenesisControllerType;
+use egui::{Color32, Context, Grid, Slider, TextEdit, Ui, Window};
+use genesis_core::{GenesisControllerType, GenesisMultitap};
 use jgenesis_native_driver::config::input::{
-    GameBoyInputConfig, GenesisControllerConfig, GenesisInputConfig, HotkeyConfig, JoystickInput,
-    KeyboardInput, KeyboardOrMouseInput, NesControllerConfig, NesInputConfig,
-    SmsGgControllerConfig, SmsGgInputConfig, SnesControllerConfig, SnesControllerType,
-    SnesInputConfig, SuperScopeConfig,
+    GameBoyInputConfig, GenesisControllerConfig, GenesisInputConfig, HotkeyConfig,
+    InputMacroConfig, JoystickInput, KeyboardInput, KeyboardOrMouseInput, NesControllerConfig,
+    NesInputConfig, PceInputConfig, SmsGgControllerConfig, SmsGgInputConfig, SmsPeripheralConfig,
+    SnesControllerConfig, SnesControllerType, SnesInputConfig, SuperScopeConfig,
 };
 use jgenesis_native_driver::input::{
-    GameBoyButton, GenesisButton, Hotkey, NesButton, Player, SmsGgButton, SnesButton,
+    GameBoyButton, GenesisButton, Hotkey, NesButton, PceButton, Player, SmsGgButton, SnesButton,
     SuperScopeButton,
 };
 use serde::{Deserialize, Serialize};
+use smsgg_core::SmsControllerType;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum GenericButton {
@@ -21,10 +22,13 @@ pub enum GenericButton {
     Nes(NesButton),
     Snes(SnesButton),
     GameBoy(GameBoyButton),
+    Pce(PceButton),
     Hotkey(Hotkey),
+    // Trigger for the input macro at the given index
+    InputMacro(usize),
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct InputAppConfig {
     #[serde(default = "default_smsgg_p1_keyboard_config")]
     pub smsgg_p1_keyboard: SmsGgControllerConfig<String>,
@@ -35,9 +39,15 @@ pub struct InputAppConfig {
     #[serde(default)]
     pub smsgg_p2_joystick: SmsGgControllerConfig<JoystickInput>,
     #[serde(default)]
+    pub smsgg_p1_type: SmsControllerType,
+    #[serde(default)]
+    pub sms_peripheral: SmsPeripheralConfig,
+    #[serde(default)]
     pub genesis_p1_type: GenesisControllerType,
     #[serde(default)]
     pub genesis_p2_type: GenesisControllerType,
+    #[serde(default)]
+    pub genesis_multitap: GenesisMultitap,
     #[serde(default = "default_genesis_p1_keyboard_config")]
     pub genesis_p1_keyboard: GenesisControllerConfig<String>,
     #[serde(default)]
@@ -46,6 +56,14 @@ pub struct InputAppConfig {
     pub genesis_p1_joystick: GenesisControllerConfig<JoystickInput>,
     #[serde(default)]
     pub genesis_p2_joystick: GenesisControllerConfig<JoystickInput>,
+    #[serde(default)]
+    pub genesis_p3_keyboard: GenesisControllerConfig<String>,
+    #[serde(default)]
+    pub genesis_p4_keyboard: GenesisControllerConfig<String>,
+    #[serde(default)]
+    pub genesis_p3_joystick: GenesisControllerConfig<JoystickInput>,
+    #[serde(default)]
+    pub genesis_p4_joystick: GenesisControllerConfig<JoystickInput>,
     #[serde(default = "default_nes_p1_keyboard_config")]
     pub nes_p1_keyboard: NesControllerConfig<String>,
     #[serde(default)]
@@ -70,10 +88,18 @@ pub struct InputAppConfig {
     pub gb_keyboard: GameBoyInputConfig<String>,
     #[serde(default)]
     pub gb_joystick: GameBoyInputConfig<JoystickInput>,
+    #[serde(default = "default_pce_keyboard_config")]
+    pub pce_keyboard: PceInputConfig<String>,
+    #[serde(default)]
+    pub pce_joystick: PceInputConfig<JoystickInput>,
     #[serde(default = "default_axis_deadzone")]
     pub axis_deadzone: i16,
     #[serde(default)]
+    pub pause_on_controller_disconnect: bool,
+    #[serde(default)]
     pub hotkeys: HotkeyConfig,
+    #[serde(default)]
+    pub input_macros: Vec<InputMacroConfig>,
 }
 
 macro_rules! set_input {
@@ -110,9 +136,25 @@ impl InputAppConfig {
             GenericButton::GameBoy(gb_button) => {
                 self.set_gb_button(input, gb_button);
             }
-            GenericButton::Hotkey(hotkey) => {
-                if let GenericInput::Keyboard(input) = input {
-                    self.set_hotkey(input, hotkey);
+            GenericButton::Pce(pce_button) => {
+                self.set_pce_button(input, pce_button);
+            }
+            GenericButton::Hotkey(hotkey) => match input {
+                GenericInput::Keyboard(input) => self.set_hotkey(input, hotkey),
+                // The quick menu is the only hotkey that can be bound to a gamepad
+                GenericInput::Joystick(input) => {
+                    if hotkey == Hotkey::OpenQuickMenu {
+                        self.hotkeys.open_quick_menu_joystick = Some(input);
+                    }
+                }
+                GenericInput::KeyboardOrMouse(_) => {}
+            },
+            GenericButton::InputMacro(idx) => {
+                let Some(input_macro) = self.input_macros.get_mut(idx) else { return };
+                match input {
+                    GenericInput::Keyboard(input) => input_macro.keyboard_trigger = Some(input),
+                    GenericInput::Joystick(input) => input_macro.joystick_trigger = Some(input),
+                    GenericInput::KeyboardOrMouse(_) => {}
                 }
             }
         }
@@ -122,6 +164,7 @@ impl InputAppConfig {
         let (keyboard, joystick) = match smsgg_button.player() {
             Player::One => (&mut self.smsgg_p1_keyboard, &mut self.smsgg_p1_joystick),
             Player::Two => (&mut self.smsgg_p2_keyboard, &mut self.smsgg_p2_joystick),
+            Player::Three | Player::Four => return,
         };
 
         match smsgg_button {
@@ -153,6 +196,8 @@ impl InputAppConfig {
         let (keyboard, joystick) = match genesis_button.player() {
             Player::One => (&mut self.genesis_p1_keyboard, &mut self.genesis_p1_joystick),
             Player::Two => (&mut self.genesis_p2_keyboard, &mut self.genesis_p2_joystick),
+            Player::Three => (&mut self.genesis_p3_keyboard, &mut self.genesis_p3_joystick),
+            Player::Four => (&mut self.genesis_p4_keyboard, &mut self.genesis_p4_joystick),
         };
 
         match genesis_button {
@@ -199,6 +244,7 @@ impl InputAppConfig {
         let (keyboard, joystick) = match nes_button.player() {
             Player::One => (&mut self.nes_p1_keyboard, &mut self.nes_p1_joystick),
             Player::Two => (&mut self.nes_p2_keyboard, &mut self.nes_p2_joystick),
+            Player::Three | Player::Four => return,
         };
 
         match nes_button {
@@ -233,6 +279,7 @@ impl InputAppConfig {
         let (keyboard, joystick) = match snes_button.player() {
             Player::One => (&mut self.snes_p1_keyboard, &mut self.snes_p1_joystick),
             Player::Two => (&mut self.snes_p2_keyboard, &mut self.snes_p2_joystick),
+            Player::Three | Player::Four => return,
         };
 
         match snes_button {
@@ -303,6 +350,22 @@ impl InputAppConfig {
         }
     }
 
+    fn set_pce_button(&mut self, input: GenericInput, pce_button: PceButton) {
+        let keyboard = &mut self.pce_keyboard;
+        let joystick = &mut self.pce_joystick;
+
+        match pce_button {
+            PceButton::Up => set_input!(input, keyboard.up, joystick.up),
+            PceButton::Left => set_input!(input, keyboard.left, joystick.left),
+            PceButton::Right => set_input!(input, keyboard.right, joystick.right),
+            PceButton::Down => set_input!(input, keyboard.down, joystick.down),
+            PceButton::I => set_input!(input, keyboard.i, joystick.i),
+            PceButton::II => set_input!(input, keyboard.ii, joystick.ii),
+            PceButton::Run => set_input!(input, keyboard.run, joystick.run),
+            PceButton::Select => set_input!(input, keyboard.select, joystick.select),
+        }
+    }
+
     fn set_hotkey(&mut self, input: KeyboardInput, hotkey: Hotkey) {
         match hotkey {
             Hotkey::Quit => {
@@ -338,6 +401,39 @@ impl InputAppConfig {
             Hotkey::OpenDebugger => {
                 self.hotkeys.open_debugger = Some(input);
             }
+            Hotkey::MusicDump => {
+                self.hotkeys.music_dump = Some(input);
+            }
+            Hotkey::Microphone => {
+                self.hotkeys.microphone = Some(input);
+            }
+            Hotkey::DumpInputTrace => {
+                self.hotkeys.dump_input_trace = Some(input);
+            }
+            Hotkey::NextSaveStateSlot => {
+                self.hotkeys.next_save_state_slot = Some(input);
+            }
+            Hotkey::PreviousSaveStateSlot => {
+                self.hotkeys.previous_save_state_slot = Some(input);
+            }
+            Hotkey::UndoLoadState => {
+                self.hotkeys.undo_load_state = Some(input);
+            }
+            Hotkey::SetPracticeLoopPoint => {
+                self.hotkeys.set_practice_loop_point = Some(input);
+            }
+            Hotkey::RestartPracticeAttempt => {
+                self.hotkeys.restart_practice_attempt = Some(input);
+            }
+            Hotkey::ClearPracticeLoopPoint => {
+                self.hotkeys.clear_practice_loop_point = Some(input);
+            }
+            Hotkey::RecordInputMacro => {
+                self.hotkeys.record_input_macro = Some(input);
+            }
+            Hotkey::OpenQuickMenu => {
+                self.hotkeys.open_quick_menu = Some(input);
+            }
         }
     }
 
@@ -356,6 +452,8 @@ impl InputAppConfig {
         GenesisInputConfig {
             p1: convert_genesis_keyboard_config(self.genesis_p1_keyboard.clone()),
             p2: convert_genesis_keyboard_config(self.genesis_p2_keyboard.clone()),
+            p3: convert_genesis_keyboard_config(self.genesis_p3_keyboard.clone()),
+            p4: convert_genesis_keyboard_config(self.genesis_p4_keyboard.clone()),
         }
     }
 
@@ -363,6 +461,8 @@ impl InputAppConfig {
         GenesisInputConfig {
             p1: self.genesis_p1_joystick.clone(),
             p2: self.genesis_p2_joystick.clone(),
+            p3: self.genesis_p3_joystick.clone(),
+            p4: self.genesis_p4_joystick.clone(),
         }
     }
 
@@ -391,6 +491,10 @@ impl InputAppConfig {
     pub fn to_gb_keyboard_config(&self) -> GameBoyInputConfig<KeyboardInput> {
         convert_gb_keyboard_config(self.gb_keyboard.clone())
     }
+
+    pub fn to_pce_keyboard_config(&self) -> PceInputConfig<KeyboardInput> {
+        convert_pce_keyboard_config(self.pce_keyboard.clone())
+    }
 }
 
 macro_rules! to_keyboard_input_config {
@@ -453,6 +557,10 @@ fn convert_gb_keyboard_config(
     )
 }
 
+fn convert_pce_keyboard_config(config: PceInputConfig<String>) -> PceInputConfig<KeyboardInput> {
+    to_keyboard_input_config!(config, PceInputConfig, [up, left, right, down, i, ii, run, select])
+}
+
 fn to_keyboard_input(s: String) -> KeyboardInput {
     KeyboardInput { keycode: s }
 }
@@ -543,14 +651,29 @@ fn default_gb_keyboard_config() -> GameBoyInputConfig<String> {
     }
 }
 
+fn default_pce_keyboard_config() -> PceInputConfig<String> {
+    let default = PceInputConfig::<KeyboardInput>::default();
+    let keycode_fn = |key: KeyboardInput| key.keycode;
+    PceInputConfig {
+        up: default.up.map(keycode_fn),
+        left: default.left.map(keycode_fn),
+        right: default.right.map(keycode_fn),
+        down: default.down.map(keycode_fn),
+        i: default.i.map(keycode_fn),
+        ii: default.ii.map(keycode_fn),
+        run: default.run.map(keycode_fn),
+        select: default.select.map(keycode_fn),
+    }
+}
+
 fn default_axis_deadzone() -> i16 {
     8000
 }
 
 macro_rules! render_buttons {
-    ($self:expr, $button_fn:ident, $config:expr, [$($field:ident: $label:literal -> $button:expr),*$(,)?], $ui:expr) => {
+    ($self:expr, $button_fn:ident, $config:expr, [$($field:ident: $label_id:literal -> $button:expr),*$(,)?], $ui:expr) => {
         $(
-            $self.$button_fn($config.$field.clone(), $label, $button, $ui);
+            $self.$button_fn($config.$field.clone(), $label_id, $button, $ui);
         )*
     }
 }
@@ -558,12 +681,12 @@ macro_rules! render_buttons {
 macro_rules! render_smsgg_input {
     ($self:expr, $button_fn:ident, $config:expr, $player:expr, $ui:expr) => {
         render_buttons!($self, $button_fn, $config, [
-            up: "Up" -> GenericButton::SmsGg(SmsGgButton::Up($player)),
-            left: "Left" -> GenericButton::SmsGg(SmsGgButton::Left($player)),
-            right: "Right" -> GenericButton::SmsGg(SmsGgButton::Right($player)),
-            down: "Down" -> GenericButton::SmsGg(SmsGgButton::Down($player)),
-            button_1: "Button 1" -> GenericButton::SmsGg(SmsGgButton::Button1($player)),
-            button_2: "Button 2" -> GenericButton::SmsGg(SmsGgButton::Button2($player)),
+            up: "input-up" -> GenericButton::SmsGg(SmsGgButton::Up($player)),
+            left: "input-left" -> GenericButton::SmsGg(SmsGgButton::Left($player)),
+            right: "input-right" -> GenericButton::SmsGg(SmsGgButton::Right($player)),
+            down: "input-down" -> GenericButton::SmsGg(SmsGgButton::Down($player)),
+            button_1: "input-button-1" -> GenericButton::SmsGg(SmsGgButton::Button1($player)),
+            button_2: "input-button-2" -> GenericButton::SmsGg(SmsGgButton::Button2($player)),
         ], $ui);
     }
 }
@@ -571,18 +694,18 @@ macro_rules! render_smsgg_input {
 macro_rules! render_genesis_input {
     ($self:expr, $button_fn:ident, $config:expr, $player:expr, $ui:expr) => {
         render_buttons!($self, $button_fn, $config, [
-            up: "Up" -> GenericButton::Genesis(GenesisButton::Up($player)),
-            left: "Left" -> GenericButton::Genesis(GenesisButton::Left($player)),
-            right: "Right" -> GenericButton::Genesis(GenesisButton::Right($player)),
-            down: "Down" -> GenericButton::Genesis(GenesisButton::Down($player)),
-            a: "A" -> GenericButton::Genesis(GenesisButton::A($player)),
-            b: "B" -> GenericButton::Genesis(GenesisButton::B($player)),
-            c: "C" -> GenericButton::Genesis(GenesisButton::C($player)),
-            x: "X" -> GenericButton::Genesis(GenesisButton::X($player)),
-            y: "Y" -> GenericButton::Genesis(GenesisButton::Y($player)),
-            z: "Z" -> GenericButton::Genesis(GenesisButton::Z($player)),
-            start: "Start" -> GenericButton::Genesis(GenesisButton::Start($player)),
-            mode: "Mode" -> GenericButton::Genesis(GenesisButton::Mode($player)),
+            up: "input-up" -> GenericButton::Genesis(GenesisButton::Up($player)),
+            left: "input-left" -> GenericButton::Genesis(GenesisButton::Left($player)),
+            right: "input-right" -> GenericButton::Genesis(GenesisButton::Right($player)),
+            down: "input-down" -> GenericButton::Genesis(GenesisButton::Down($player)),
+            a: "input-a" -> GenericButton::Genesis(GenesisButton::A($player)),
+            b: "input-b" -> GenericButton::Genesis(GenesisButton::B($player)),
+            c: "input-c" -> GenericButton::Genesis(GenesisButton::C($player)),
+            x: "input-x" -> GenericButton::Genesis(GenesisButton::X($player)),
+            y: "input-y" -> GenericButton::Genesis(GenesisButton::Y($player)),
+            z: "input-z" -> GenericButton::Genesis(GenesisButton::Z($player)),
+            start: "input-start" -> GenericButton::Genesis(GenesisButton::Start($player)),
+            mode: "input-mode" -> GenericButton::Genesis(GenesisButton::Mode($player)),
         ], $ui);
     }
 }
@@ -590,14 +713,14 @@ macro_rules! render_genesis_input {
 macro_rules! render_nes_input {
     ($self:expr, $button_fn:ident, $config:expr, $player:expr, $ui:expr) => {
         render_buttons!($self, $button_fn, $config, [
-            up: "Up" -> GenericButton::Nes(NesButton::Up($player)),
-            left: "Left" -> GenericButton::Nes(NesButton::Left($player)),
-            right: "Right" -> GenericButton::Nes(NesButton::Right($player)),
-            down: "Down" -> GenericButton::Nes(NesButton::Down($player)),
-            a: "A" -> GenericButton::Nes(NesButton::A($player)),
-            b: "B" -> GenericButton::Nes(NesButton::B($player)),
-            start: "Start" -> GenericButton::Nes(NesButton::Start($player)),
-            select: "Select" -> GenericButton::Nes(NesButton::Select($player)),
+            up: "input-up" -> GenericButton::Nes(NesButton::Up($player)),
+            left: "input-left" -> GenericButton::Nes(NesButton::Left($player)),
+            right: "input-right" -> GenericButton::Nes(NesButton::Right($player)),
+            down: "input-down" -> GenericButton::Nes(NesButton::Down($player)),
+            a: "input-a" -> GenericButton::Nes(NesButton::A($player)),
+            b: "input-b" -> GenericButton::Nes(NesButton::B($player)),
+            start: "input-start" -> GenericButton::Nes(NesButton::Start($player)),
+            select: "input-select" -> GenericButton::Nes(NesButton::Select($player)),
         ], $ui);
     }
 }
@@ -605,18 +728,18 @@ macro_rules! render_nes_input {
 macro_rules! render_snes_input {
     ($self:expr, $button_fn:ident, $config:expr, $player:expr, $ui:expr) => {
         render_buttons!($self, $button_fn, $config, [
-            up: "Up" -> GenericButton::Snes(SnesButton::Up($player)),
-            left: "Left" -> GenericButton::Snes(SnesButton::Left($player)),
-            right: "Right" -> GenericButton::Snes(SnesButton::Right($player)),
-            down: "Down" -> GenericButton::Snes(SnesButton::Down($player)),
-            a: "A" -> GenericButton::Snes(SnesButton::A($player)),
-            b: "B" -> GenericButton::Snes(SnesButton::B($player)),
-            x: "X" -> GenericButton::Snes(SnesButton::X($player)),
-            y: "Y" -> GenericButton::Snes(SnesButton::Y($player)),
-            l: "L" -> GenericButton::Snes(SnesButton::L($player)),
-            r: "R" -> GenericButton::Snes(SnesButton::R($player)),
-            start: "Start" -> GenericButton::Snes(SnesButton::Start($player)),
-            select: "Select" -> GenericButton::Snes(SnesButton::Select($player)),
+            up: "input-up" -> GenericButton::Snes(SnesButton::Up($player)),
+            left: "input-left" -> GenericButton::Snes(SnesButton::Left($player)),
+            right: "input-right" -> GenericButton::Snes(SnesButton::Right($player)),
+            down: "input-down" -> GenericButton::Snes(SnesButton::Down($player)),
+            a: "input-a" -> GenericButton::Snes(SnesButton::A($player)),
+            b: "input-b" -> GenericButton::Snes(SnesButton::B($player)),
+            x: "input-x" -> GenericButton::Snes(SnesButton::X($player)),
+            y: "input-y" -> GenericButton::Snes(SnesButton::Y($player)),
+            l: "input-l" -> GenericButton::Snes(SnesButton::L($player)),
+            r: "input-r" -> GenericButton::Snes(SnesButton::R($player)),
+            start: "input-start" -> GenericButton::Snes(SnesButton::Start($player)),
+            select: "input-select" -> GenericButton::Snes(SnesButton::Select($player)),
         ], $ui);
     }
 }
@@ -624,14 +747,29 @@ macro_rules! render_snes_input {
 macro_rules! render_gb_input {
     ($self:expr, $button_fn:ident, $config:expr, $ui:expr) => {
         render_buttons!($self, $button_fn, $config, [
-            up: "Up" -> GenericButton::GameBoy(GameBoyButton::Up),
-            left: "Left" -> GenericButton::GameBoy(GameBoyButton::Left),
-            right: "Right" -> GenericButton::GameBoy(GameBoyButton::Right),
-            down: "Down" -> GenericButton::GameBoy(GameBoyButton::Down),
-            a: "A" -> GenericButton::GameBoy(GameBoyButton::A),
-            b: "B" -> GenericButton::GameBoy(GameBoyButton::B),
-            start: "Start" -> GenericButton::GameBoy(GameBoyButton::Start),
-            select: "Select" -> GenericButton::GameBoy(GameBoyButton::Select),
+            up: "input-up" -> GenericButton::GameBoy(GameBoyButton::Up),
+            left: "input-left" -> GenericButton::GameBoy(GameBoyButton::Left),
+            right: "input-right" -> GenericButton::GameBoy(GameBoyButton::Right),
+            down: "input-down" -> GenericButton::GameBoy(GameBoyButton::Down),
+            a: "input-a" -> GenericButton::GameBoy(GameBoyButton::A),
+            b: "input-b" -> GenericButton::GameBoy(GameBoyButton::B),
+            start: "input-start" -> GenericButton::GameBoy(GameBoyButton::Start),
+            select: "input-select" -> GenericButton::GameBoy(GameBoyButton::Select),
+        ], $ui);
+    }
+}
+
+macro_rules! render_pce_input {
+    ($self:expr, $button_fn:ident, $config:expr, $ui:expr) => {
+        render_buttons!($self, $button_fn, $config, [
+            up: "input-up" -> GenericButton::Pce(PceButton::Up),
+            left: "input-left" -> GenericButton::Pce(PceButton::Left),
+            right: "input-right" -> GenericButton::Pce(PceButton::Right),
+            down: "input-down" -> GenericButton::Pce(PceButton::Down),
+            i: "input-i" -> GenericButton::Pce(PceButton::I),
+            ii: "input-ii" -> GenericButton::Pce(PceButton::II),
+            run: "input-run" -> GenericButton::Pce(PceButton::Run),
+            select: "input-select" -> GenericButton::Pce(PceButton::Select),
         ], $ui);
     }
 }
@@ -639,12 +777,13 @@ macro_rules! render_gb_input {
 impl App {
     pub(super) fn render_smsgg_keyboard_settings(&mut self, ctx: &Context) {
         let mut open = true;
-        Window::new("SMS/GG Keyboard Settings").open(&mut open).resizable(false).show(ctx, |ui| {
+        let title = tr!(self, "input-smsgg-keyboard-window-title");
+        Window::new(title).open(&mut open).resizable(false).show(ctx, |ui| {
             ui.set_enabled(self.state.waiting_for_input.is_none());
 
             Grid::new("smsgg_keyboard_grid").show(ui, |ui| {
                 Grid::new("smsgg_p1_keyboard_grid").show(ui, |ui| {
-                    ui.heading("Player 1");
+                    ui.heading(tr!(self, "input-player", "player" => 1));
                     ui.end_row();
 
                     render_smsgg_input!(
@@ -659,7 +798,7 @@ impl App {
                 ui.add_space(20.0);
 
                 Grid::new("smsgg_p2_keyboard_grid").show(ui, |ui| {
-                    ui.heading("Player 2");
+                    ui.heading(tr!(self, "input-player", "player" => 2));
                     ui.end_row();
 
                     render_smsgg_input!(
@@ -677,11 +816,14 @@ impl App {
             Grid::new("smsgg_pause_keyboard_grid").show(ui, |ui| {
                 self.keyboard_input_button(
                     self.config.inputs.smsgg_p1_keyboard.pause.clone(),
-                    "Start/Pause",
+                    "input-start-pause",
                     GenericButton::SmsGg(SmsGgButton::Pause),
                     ui,
                 );
             });
+
+            ui.add_space(20.0);
+            self.render_sms_peripheral_settings(ui);
         });
         if !open {
             self.state.open_windows.remove(&OpenWindow::SmsGgKeyboard);
@@ -690,12 +832,13 @@ impl App {
 
     pub(super) fn render_smsgg_gamepad_settings(&mut self, ctx: &Context) {
         let mut open = true;
-        Window::new("SMS/GG Gamepad Settings").open(&mut open).resizable(false).show(ctx, |ui| {
+        let title = tr!(self, "input-smsgg-gamepad-window-title");
+        Window::new(title).open(&mut open).resizable(false).show(ctx, |ui| {
             ui.set_enabled(self.state.waiting_for_input.is_none());
 
             Grid::new("smsgg_gamepad_grid").show(ui, |ui| {
                 Grid::new("smsgg_p1_gamepad_grid").show(ui, |ui| {
-                    ui.heading("Player 1");
+                    ui.heading(tr!(self, "input-player", "player" => 1));
                     ui.end_row();
 
                     render_smsgg_input!(
@@ -710,7 +853,7 @@ impl App {
                 ui.add_space(20.0);
 
                 Grid::new("smsgg_p2_gamepad_grid").show(ui, |ui| {
-                    ui.heading("Player 2");
+                    ui.heading(tr!(self, "input-player", "player" => 2));
                     ui.end_row();
 
                     render_smsgg_input!(
@@ -728,14 +871,17 @@ impl App {
             Grid::new("smsgg_pause_gamepad_grid").show(ui, |ui| {
                 self.gamepad_input_button(
                     self.config.inputs.smsgg_p1_joystick.pause.clone(),
-                    "Start/Pause",
+                    "input-start-pause",
                     GenericButton::SmsGg(SmsGgButton::Pause),
                     ui,
                 );
             });
 
             ui.add_space(20.0);
-            self.render_axis_deadzone_input(ui);
+            self.render_common_gamepad_settings(ui);
+
+            ui.add_space(20.0);
+            self.render_sms_peripheral_settings(ui);
         });
         if !open {
             self.state.open_windows.remove(&OpenWindow::SmsGgGamepad);
@@ -744,12 +890,13 @@ impl App {
 
     pub(super) fn render_genesis_keyboard_settings(&mut self, ctx: &Context) {
         let mut open = true;
-        Window::new("Genesis Keyboard Settings").open(&mut open).resizable(false).show(ctx, |ui| {
+        let title = tr!(self, "input-genesis-keyboard-window-title");
+        Window::new(title).open(&mut open).resizable(false).show(ctx, |ui| {
             ui.set_enabled(self.state.waiting_for_input.is_none());
 
             Grid::new("genesis_keyboard_grid").show(ui, |ui| {
                 Grid::new("genesis_p1_keyboard_grid").show(ui, |ui| {
-                    ui.heading("Player 1");
+                    ui.heading(tr!(self, "input-player", "player" => 1));
                     ui.end_row();
 
                     render_genesis_input!(
@@ -764,7 +911,7 @@ impl App {
                 ui.add_space(50.0);
 
                 Grid::new("genesis_p2_keyboard_grid").show(ui, |ui| {
-                    ui.heading("Player 2");
+                    ui.heading(tr!(self, "input-player", "player" => 2));
                     ui.end_row();
 
                     render_genesis_input!(
@@ -775,12 +922,44 @@ impl App {
                         ui
                     );
                 });
+                ui.end_row();
+
+                if self.config.inputs.genesis_multitap != GenesisMultitap::None {
+                    Grid::new("genesis_p3_keyboard_grid").show(ui, |ui| {
+                        ui.heading(tr!(self, "input-player", "player" => 3));
+                        ui.end_row();
+
+                        render_genesis_input!(
+                            self,
+                            keyboard_input_button,
+                            self.config.inputs.genesis_p3_keyboard,
+                            Player::Three,
+                            ui
+                        );
+                    });
+
+                    ui.add_space(50.0);
+
+                    Grid::new("genesis_p4_keyboard_grid").show(ui, |ui| {
+                        ui.heading(tr!(self, "input-player", "player" => 4));
+                        ui.end_row();
+
+                        render_genesis_input!(
+                            self,
+                            keyboard_input_button,
+                            self.config.inputs.genesis_p4_keyboard,
+                            Player::Four,
+                            ui
+                        );
+                    });
+                }
             });
 
             ui.add_space(30.0);
 
-            self.controller_type_input("Player 1 controller", Player::One, ui);
-            self.controller_type_input("Player 2 controller", Player::Two, ui);
+            self.controller_type_input("input-genesis-p1-controller", Player::One, ui);
+            self.controller_type_input("input-genesis-p2-controller", Player::Two, ui);
+            self.multitap_input(ui);
         });
         if !open {
             self.state.open_windows.remove(&OpenWindow::GenesisKeyboard);
@@ -789,12 +968,13 @@ impl App {
 
     pub(super) fn render_genesis_gamepad_settings(&mut self, ctx: &Context) {
         let mut open = true;
-        Window::new("Genesis Gamepad Settings").open(&mut open).resizable(false).show(ctx, |ui| {
+        let title = tr!(self, "input-genesis-gamepad-window-title");
+        Window::new(title).open(&mut open).resizable(false).show(ctx, |ui| {
             ui.set_enabled(self.state.waiting_for_input.is_none());
 
             Grid::new("genesis_gamepad_grid").show(ui, |ui| {
                 Grid::new("genesis_p1_gamepad_grid").show(ui, |ui| {
-                    ui.heading("Player 1");
+                    ui.heading(tr!(self, "input-player", "player" => 1));
                     ui.end_row();
 
                     render_genesis_input!(
@@ -809,7 +989,7 @@ impl App {
                 ui.add_space(50.0);
 
                 Grid::new("genesis_p2_gamepad_grid").show(ui, |ui| {
-                    ui.heading("Player 2");
+                    ui.heading(tr!(self, "input-player", "player" => 2));
                     ui.end_row();
 
                     render_genesis_input!(
@@ -820,16 +1000,48 @@ impl App {
                         ui
                     );
                 });
+                ui.end_row();
+
+                if self.config.inputs.genesis_multitap != GenesisMultitap::None {
+                    Grid::new("genesis_p3_gamepad_grid").show(ui, |ui| {
+                        ui.heading(tr!(self, "input-player", "player" => 3));
+                        ui.end_row();
+
+                        render_genesis_input!(
+                            self,
+                            gamepad_input_button,
+                            self.config.inputs.genesis_p3_joystick,
+                            Player::Three,
+                            ui
+                        );
+                    });
+
+                    ui.add_space(50.0);
+
+                    Grid::new("genesis_p4_gamepad_grid").show(ui, |ui| {
+                        ui.heading(tr!(self, "input-player", "player" => 4));
+                        ui.end_row();
+
+                        render_genesis_input!(
+                            self,
+                            gamepad_input_button,
+                            self.config.inputs.genesis_p4_joystick,
+                            Player::Four,
+                            ui
+                        );
+                    });
+                }
             });
 
             ui.add_space(30.0);
 
-            self.render_axis_deadzone_input(ui);
+            self.render_common_gamepad_settings(ui);
 
             ui.add_space(20.0);
 
-            self.controller_type_input("Player 1 controller", Player::One, ui);
-            self.controller_type_input("Player 2 controller", Player::Two, ui);
+            self.controller_type_input("input-genesis-p1-controller", Player::One, ui);
+            self.controller_type_input("input-genesis-p2-controller", Player::Two, ui);
+            self.multitap_input(ui);
         });
         if !open {
             self.state.open_windows.remove(&OpenWindow::GenesisGamepad);
@@ -838,12 +1050,13 @@ impl App {
 
     pub(super) fn render_nes_keyboard_settings(&mut self, ctx: &Context) {
         let mut open = true;
-        Window::new("NES Keyboard Settings").open(&mut open).resizable(false).show(ctx, |ui| {
+        let title = tr!(self, "input-nes-keyboard-window-title");
+        Window::new(title).open(&mut open).resizable(false).show(ctx, |ui| {
             ui.set_enabled(self.state.waiting_for_input.is_none());
 
             Grid::new("nes_keyboard_grid").show(ui, |ui| {
                 Grid::new("nes_p1_keyboard_grid").show(ui, |ui| {
-                    ui.heading("Player 1");
+                    ui.heading(tr!(self, "input-player", "player" => 1));
                     ui.end_row();
 
                     render_nes_input!(
@@ -858,7 +1071,7 @@ impl App {
                 ui.add_space(50.0);
 
                 Grid::new("nes_p2_keyboard_grid").show(ui, |ui| {
-                    ui.heading("Player 2");
+                    ui.heading(tr!(self, "input-player", "player" => 2));
                     ui.end_row();
 
                     render_nes_input!(
@@ -878,12 +1091,13 @@ impl App {
 
     pub(super) fn render_nes_joystick_settings(&mut self, ctx: &Context) {
         let mut open = true;
-        Window::new("NES Gamepad Settings").open(&mut open).resizable(false).show(ctx, |ui| {
+        let title = tr!(self, "input-nes-gamepad-window-title");
+        Window::new(title).open(&mut open).resizable(false).show(ctx, |ui| {
             ui.set_enabled(self.state.waiting_for_input.is_none());
 
             Grid::new("nes_gamepad_grid").show(ui, |ui| {
                 Grid::new("nes_p1_gamepad_grid").show(ui, |ui| {
-                    ui.heading("Player 1");
+                    ui.heading(tr!(self, "input-player", "player" => 1));
                     ui.end_row();
 
                     render_nes_input!(
@@ -898,7 +1112,7 @@ impl App {
                 ui.add_space(50.0);
 
                 Grid::new("nes_p2_joystick_grid").show(ui, |ui| {
-                    ui.heading("Player 2");
+                    ui.heading(tr!(self, "input-player", "player" => 2));
                     ui.end_row();
 
                     render_nes_input!(
@@ -913,7 +1127,7 @@ impl App {
 
             ui.add_space(30.0);
 
-            self.render_axis_deadzone_input(ui);
+            self.render_common_gamepad_settings(ui);
         });
         if !open {
             self.state.open_windows.remove(&OpenWindow::NesGamepad);
@@ -922,12 +1136,13 @@ impl App {
 
     pub(super) fn render_snes_keyboard_settings(&mut self, ctx: &Context) {
         let mut open = true;
-        Window::new("SNES Keyboard Settings").open(&mut open).resizable(false).show(ctx, |ui| {
+        let title = tr!(self, "input-snes-keyboard-window-title");
+        Window::new(title).open(&mut open).resizable(false).show(ctx, |ui| {
             ui.set_enabled(self.state.waiting_for_input.is_none());
 
             Grid::new("snes_keyboard_grid").show(ui, |ui| {
                 Grid::new("snes_p1_keyboard_grid").show(ui, |ui| {
-                    ui.heading("Player 1");
+                    ui.heading(tr!(self, "input-player", "player" => 1));
                     ui.end_row();
 
                     render_snes_input!(
@@ -942,7 +1157,7 @@ impl App {
                 ui.add_space(50.0);
 
                 Grid::new("snes_p2_keyboard_grid").show(ui, |ui| {
-                    ui.heading("Player 2");
+                    ui.heading(tr!(self, "input-player", "player" => 2));
                     ui.end_row();
 
                     render_snes_input!(
@@ -962,12 +1177,13 @@ impl App {
 
     pub(super) fn render_snes_gamepad_settings(&mut self, ctx: &Context) {
         let mut open = true;
-        Window::new("SNES Gamepad Settings").open(&mut open).resizable(false).show(ctx, |ui| {
+        let title = tr!(self, "input-snes-gamepad-window-title");
+        Window::new(title).open(&mut open).resizable(false).show(ctx, |ui| {
             ui.set_enabled(self.state.waiting_for_input.is_none());
 
             Grid::new("snes_gamepad_grid").show(ui, |ui| {
                 Grid::new("snes_p1_gamepad_grid").show(ui, |ui| {
-                    ui.heading("Player 1");
+                    ui.heading(tr!(self, "input-player", "player" => 1));
                     ui.end_row();
 
                     render_snes_input!(
@@ -982,7 +1198,7 @@ impl App {
                 ui.add_space(50.0);
 
                 Grid::new("snes_p2_gamepad_grid").show(ui, |ui| {
-                    ui.heading("Player 2");
+                    ui.heading(tr!(self, "input-player", "player" => 2));
                     ui.end_row();
 
                     render_snes_input!(
@@ -997,7 +1213,7 @@ impl App {
 
             ui.add_space(30.0);
 
-            self.render_axis_deadzone_input(ui);
+            self.render_common_gamepad_settings(ui);
         });
         if !open {
             self.state.open_windows.remove(&OpenWindow::SnesGamepad);
@@ -1006,52 +1222,53 @@ impl App {
 
     pub(super) fn render_snes_peripheral_settings(&mut self, ctx: &Context) {
         let mut open = true;
-        Window::new("SNES Peripheral Settings").open(&mut open).resizable(false).show(ctx, |ui| {
+        let title = tr!(self, "input-snes-peripheral-window-title");
+        Window::new(title).open(&mut open).resizable(false).show(ctx, |ui| {
             ui.set_enabled(self.state.waiting_for_input.is_none());
 
             ui.group(|ui| {
-                ui.label("P2 input device");
+                ui.label(tr!(self, "input-snes-p2-device"));
 
                 ui.horizontal(|ui| {
                     ui.radio_value(
                         &mut self.config.inputs.snes_p2_type,
                         SnesControllerType::Gamepad,
-                        "Gamepad",
+                        tr!(self, "input-snes-gamepad"),
                     );
                     ui.radio_value(
                         &mut self.config.inputs.snes_p2_type,
                         SnesControllerType::SuperScope,
-                        "Super Scope",
+                        tr!(self, "input-snes-super-scope"),
                     );
                 });
             });
 
             ui.add_space(10.0);
 
-            ui.heading("Super Scope");
+            ui.heading(tr!(self, "input-snes-super-scope"));
 
             Grid::new("super_scope_grid").show(ui, |ui| {
                 self.super_scope_button(
                     self.config.inputs.snes_super_scope.fire.clone(),
-                    "Fire",
+                    "input-super-scope-fire",
                     SuperScopeButton::Fire,
                     ui,
                 );
                 self.super_scope_button(
                     self.config.inputs.snes_super_scope.cursor.clone(),
-                    "Cursor",
+                    "input-super-scope-cursor",
                     SuperScopeButton::Cursor,
                     ui,
                 );
                 self.super_scope_button(
                     self.config.inputs.snes_super_scope.pause.clone(),
-                    "Pause",
+                    "input-super-scope-pause",
                     SuperScopeButton::Pause,
                     ui,
                 );
                 self.super_scope_button(
                     self.config.inputs.snes_super_scope.turbo_toggle.clone(),
-                    "Turbo (Toggle)",
+                    "input-super-scope-turbo",
                     SuperScopeButton::TurboToggle,
                     ui,
                 );
@@ -1064,21 +1281,14 @@ impl App {
 
     pub(super) fn render_gb_keyboard_settings(&mut self, ctx: &Context) {
         let mut open = true;
-        Window::new("Game Boy Keyboard Settings").open(&mut open).resizable(false).show(
-            ctx,
-            |ui| {
-                ui.set_enabled(self.state.waiting_for_input.is_none());
+        let title = tr!(self, "input-gb-keyboard-window-title");
+        Window::new(title).open(&mut open).resizable(false).show(ctx, |ui| {
+            ui.set_enabled(self.state.waiting_for_input.is_none());
 
-                Grid::new("gb_keyboard_grid").show(ui, |ui| {
-                    render_gb_input!(
-                        self,
-                        keyboard_input_button,
-                        self.config.inputs.gb_keyboard,
-                        ui
-                    );
-                });
-            },
-        );
+            Grid::new("gb_keyboard_grid").show(ui, |ui| {
+                render_gb_input!(self, keyboard_input_button, self.config.inputs.gb_keyboard, ui);
+            });
+        });
         if !open {
             self.state.open_windows.remove(&OpenWindow::GameBoyKeyboard);
         }
@@ -1086,102 +1296,197 @@ impl App {
 
     pub(super) fn render_gb_joystick_settings(&mut self, ctx: &Context) {
         let mut open = true;
-        Window::new("Game Boy Joystick Settings").open(&mut open).resizable(false).show(
-            ctx,
-            |ui| {
-                ui.set_enabled(self.state.waiting_for_input.is_none());
+        let title = tr!(self, "input-gb-gamepad-window-title");
+        Window::new(title).open(&mut open).resizable(false).show(ctx, |ui| {
+            ui.set_enabled(self.state.waiting_for_input.is_none());
 
-                Grid::new("gb_joystick_grid").show(ui, |ui| {
-                    render_gb_input!(
-                        self,
-                        gamepad_input_button,
-                        self.config.inputs.gb_joystick,
-                        ui
-                    );
-                });
+            Grid::new("gb_joystick_grid").show(ui, |ui| {
+                render_gb_input!(self, gamepad_input_button, self.config.inputs.gb_joystick, ui);
+            });
 
-                ui.add_space(30.0);
+            ui.add_space(30.0);
 
-                self.render_axis_deadzone_input(ui);
-            },
-        );
+            self.render_common_gamepad_settings(ui);
+        });
         if !open {
             self.state.open_windows.remove(&OpenWindow::GameBoyGamepad);
         }
     }
 
+    pub(super) fn render_pce_keyboard_settings(&mut self, ctx: &Context) {
+        let mut open = true;
+        let title = tr!(self, "input-pce-keyboard-window-title");
+        Window::new(title).open(&mut open).resizable(false).show(ctx, |ui| {
+            ui.set_enabled(self.state.waiting_for_input.is_none());
+
+            Grid::new("pce_keyboard_grid").show(ui, |ui| {
+                render_pce_input!(self, keyboard_input_button, self.config.inputs.pce_keyboard, ui);
+            });
+        });
+        if !open {
+            self.state.open_windows.remove(&OpenWindow::PceKeyboard);
+        }
+    }
+
+    pub(super) fn render_pce_joystick_settings(&mut self, ctx: &Context) {
+        let mut open = true;
+        let title = tr!(self, "input-pce-gamepad-window-title");
+        Window::new(title).open(&mut open).resizable(false).show(ctx, |ui| {
+            ui.set_enabled(self.state.waiting_for_input.is_none());
+
+            Grid::new("pce_joystick_grid").show(ui, |ui| {
+                render_pce_input!(self, gamepad_input_button, self.config.inputs.pce_joystick, ui);
+            });
+
+            ui.add_space(30.0);
+
+            self.render_common_gamepad_settings(ui);
+        });
+        if !open {
+            self.state.open_windows.remove(&OpenWindow::PceGamepad);
+        }
+    }
+
     pub(super) fn render_hotkey_settings(&mut self, ctx: &Context) {
         let mut open = true;
-        Window::new("Hotkey Settings").open(&mut open).resizable(false).show(ctx, |ui| {
+        let title = tr!(self, "input-hotkeys-window-title");
+        Window::new(title).open(&mut open).resizable(false).show(ctx, |ui| {
             ui.set_enabled(self.state.waiting_for_input.is_none());
 
             Grid::new("hotkeys_grid").show(ui, |ui| {
                 self.hotkey_button(
                     self.config.inputs.hotkeys.quit.clone(),
-                    "Quit",
+                    "hotkey-quit",
                     Hotkey::Quit,
                     ui,
                 );
                 self.hotkey_button(
                     self.config.inputs.hotkeys.toggle_fullscreen.clone(),
-                    "Toggle fullscreen",
+                    "hotkey-toggle-fullscreen",
                     Hotkey::ToggleFullscreen,
                     ui,
                 );
                 self.hotkey_button(
                     self.config.inputs.hotkeys.save_state.clone(),
-                    "Save state",
+                    "hotkey-save-state",
                     Hotkey::SaveState,
                     ui,
                 );
                 self.hotkey_button(
                     self.config.inputs.hotkeys.load_state.clone(),
-                    "Load state",
+                    "hotkey-load-state",
                     Hotkey::LoadState,
                     ui,
                 );
+                self.hotkey_button(
+                    self.config.inputs.hotkeys.next_save_state_slot.clone(),
+                    "hotkey-next-slot",
+                    Hotkey::NextSaveStateSlot,
+                    ui,
+                );
+                self.hotkey_button(
+                    self.config.inputs.hotkeys.previous_save_state_slot.clone(),
+                    "hotkey-previous-slot",
+                    Hotkey::PreviousSaveStateSlot,
+                    ui,
+                );
+                self.hotkey_button(
+                    self.config.inputs.hotkeys.undo_load_state.clone(),
+                    "hotkey-undo-load-state",
+                    Hotkey::UndoLoadState,
+                    ui,
+                );
                 self.hotkey_button(
                     self.config.inputs.hotkeys.soft_reset.clone(),
-                    "Soft reset",
+                    "hotkey-soft-reset",
                     Hotkey::SoftReset,
                     ui,
                 );
                 self.hotkey_button(
                     self.config.inputs.hotkeys.hard_reset.clone(),
-                    "Hard reset",
+                    "hotkey-hard-reset",
                     Hotkey::HardReset,
                     ui,
                 );
                 self.hotkey_button(
                     self.config.inputs.hotkeys.pause.clone(),
-                    "Pause/Unpause",
+                    "hotkey-pause",
                     Hotkey::Pause,
                     ui,
                 );
                 self.hotkey_button(
                     self.config.inputs.hotkeys.step_frame.clone(),
-                    "Step frame while paused",
+                    "hotkey-step-frame",
                     Hotkey::StepFrame,
                     ui,
                 );
                 self.hotkey_button(
                     self.config.inputs.hotkeys.fast_forward.clone(),
-                    "Fast forward",
+                    "hotkey-fast-forward",
                     Hotkey::FastForward,
                     ui,
                 );
                 self.hotkey_button(
                     self.config.inputs.hotkeys.rewind.clone(),
-                    "Rewind",
+                    "hotkey-rewind",
                     Hotkey::Rewind,
                     ui,
                 );
                 self.hotkey_button(
                     self.config.inputs.hotkeys.open_debugger.clone(),
-                    "Open memory viewer",
+                    "hotkey-open-debugger",
                     Hotkey::OpenDebugger,
                     ui,
                 );
+                self.hotkey_button(
+                    self.config.inputs.hotkeys.music_dump.clone(),
+                    "hotkey-music-dump",
+                    Hotkey::MusicDump,
+                    ui,
+                );
+                self.hotkey_button(
+                    self.config.inputs.hotkeys.microphone.clone(),
+                    "hotkey-microphone",
+                    Hotkey::Microphone,
+                    ui,
+                );
+                self.hotkey_button(
+                    self.config.inputs.hotkeys.dump_input_trace.clone(),
+                    "hotkey-dump-input-trace",
+                    Hotkey::DumpInputTrace,
+                    ui,
+                );
+                self.hotkey_button(
+                    self.config.inputs.hotkeys.set_practice_loop_point.clone(),
+                    "hotkey-set-practice-loop",
+                    Hotkey::SetPracticeLoopPoint,
+                    ui,
+                );
+                self.hotkey_button(
+                    self.config.inputs.hotkeys.restart_practice_attempt.clone(),
+                    "hotkey-restart-practice-attempt",
+                    Hotkey::RestartPracticeAttempt,
+                    ui,
+                );
+                self.hotkey_button(
+                    self.config.inputs.hotkeys.clear_practice_loop_point.clone(),
+                    "hotkey-clear-practice-loop",
+                    Hotkey::ClearPracticeLoopPoint,
+                    ui,
+                );
+                self.hotkey_button(
+                    self.config.inputs.hotkeys.record_input_macro.clone(),
+                    "hotkey-record-input-macro",
+                    Hotkey::RecordInputMacro,
+                    ui,
+                );
+                self.hotkey_button(
+                    self.config.inputs.hotkeys.open_quick_menu.clone(),
+                    "hotkey-open-quick-menu",
+                    Hotkey::OpenQuickMenu,
+                    ui,
+                );
+                self.quick_menu_gamepad_button(ui);
             });
 
             ui.add_space(20.0);
@@ -1197,13 +1502,10 @@ impl App {
                     .desired_width(30.0),
                 );
 
-                ui.label("Fast forward multiplier");
+                ui.label(tr!(self, "input-ff-multiplier"));
             });
             if self.state.ff_multiplier_invalid {
-                ui.colored_label(
-                    Color32::RED,
-                    "Fast forward multiplier must be a positive integer",
-                );
+                ui.colored_label(Color32::RED, tr!(self, "input-ff-multiplier-invalid"));
             }
 
             ui.horizontal(|ui| {
@@ -1216,20 +1518,139 @@ impl App {
                     .desired_width(30.0),
                 );
 
-                ui.label("Rewind buffer length in seconds");
+                ui.label(tr!(self, "input-rewind-buffer-len"));
             });
             if self.state.rewind_buffer_len_invalid {
-                ui.colored_label(
-                    Color32::RED,
-                    "Rewind buffer length must be a non-negative integer",
+                ui.colored_label(Color32::RED, tr!(self, "input-rewind-buffer-len-invalid"));
+            }
+
+            ui.horizontal(|ui| {
+                ui.add(
+                    NumericTextEdit::new(
+                        &mut self.state.input_trace_len_text,
+                        &mut self.config.common.input_trace_length_seconds,
+                        &mut self.state.input_trace_len_invalid,
+                    )
+                    .desired_width(30.0),
+                );
+
+                ui.label(tr!(self, "input-trace-len"));
+            })
+            .response
+            .on_hover_text(tr!(self, "input-trace-len-tooltip"));
+            if self.state.input_trace_len_invalid {
+                ui.colored_label(Color32::RED, tr!(self, "input-trace-len-invalid"));
+            }
+
+            ui.add_space(10.0);
+
+            ui.horizontal(|ui| {
+                ui.add(
+                    NumericTextEdit::new(
+                        &mut self.state.practice_end_frames_text,
+                        &mut self.config.common.practice_end_frame_count,
+                        &mut self.state.practice_end_frames_invalid,
+                    )
+                    .desired_width(50.0),
                 );
+
+                ui.label(tr!(self, "input-practice-loop-len"));
+            })
+            .response
+            .on_hover_text(tr!(self, "input-practice-loop-len-tooltip"));
+            if self.state.practice_end_frames_invalid {
+                ui.colored_label(Color32::RED, tr!(self, "input-practice-loop-len-invalid"));
             }
+
+            ui.horizontal(|ui| {
+                ui.add(
+                    TextEdit::singleline(&mut self.config.common.practice_end_condition)
+                        .desired_width(150.0),
+                );
+
+                ui.label(tr!(self, "input-practice-end-condition"));
+            })
+            .response
+            .on_hover_text(tr!(self, "input-practice-end-condition-tooltip"));
+
+            ui.add_space(20.0);
+
+            self.render_input_macros(ui);
         });
         if !open {
             self.state.open_windows.remove(&OpenWindow::Hotkeys);
         }
     }
 
+    fn render_input_macros(&mut self, ui: &mut Ui) {
+        ui.heading(tr!(self, "input-macros")).on_hover_text(tr!(self, "input-macros-tooltip"));
+
+        let none_label = tr!(self, "value-none");
+        let mut remove_idx = None;
+        Grid::new("input_macros_grid").show(ui, |ui| {
+            for idx in 0..self.config.inputs.input_macros.len() {
+                let input_macro = &mut self.config.inputs.input_macros[idx];
+                ui.add(TextEdit::singleline(&mut input_macro.sequence).desired_width(250.0));
+
+                let keyboard_text = input_macro
+                    .keyboard_trigger
+                    .as_ref()
+                    .map_or_else(|| none_label.clone(), |input| input.keycode.clone());
+                let joystick_text = input_macro.joystick_trigger.as_ref().map_or_else(
+                    || none_label.clone(),
+                    |input| format!("{} ({})", input.action, input.device),
+                );
+
+                for (text, input_type) in
+                    [(keyboard_text, InputType::Keyboard), (joystick_text, InputType::Joystick)]
+                {
+                    let button = GenericButton::InputMacro(idx);
+                    if ui.button(text).clicked() {
+                        log::debug!("Sending collect input command for input macro {idx}");
+                        self.emu_thread.send(EmuThreadCommand::CollectInput {
+                            input_type,
+                            axis_deadzone: self.config.inputs.axis_deadzone,
+                            ctx: ui.ctx().clone(),
+                        });
+                        self.state.waiting_for_input = Some(button);
+                    }
+
+                    if ui.button(tr!(self, "option-clear")).clicked() {
+                        self.clear_button_in_config(button, input_type);
+                    }
+                }
+
+                if ui.button(tr!(self, "input-macro-remove")).clicked() {
+                    remove_idx = Some(idx);
+                }
+
+                ui.end_row();
+            }
+        });
+
+        if let Some(idx) = remove_idx {
+            self.config.inputs.input_macros.remove(idx);
+        }
+
+        if ui.button(tr!(self, "input-macro-add")).clicked() {
+            self.config.inputs.input_macros.push(InputMacroConfig {
+                keyboard_trigger: None,
+                joystick_trigger: None,
+                sequence: String::new(),
+            });
+        }
+    }
+
+    fn render_common_gamepad_settings(&mut self, ui: &mut Ui) {
+        self.render_axis_deadzone_input(ui);
+
+        ui.checkbox(
+            &mut self.config.inputs.pause_on_controller_disconnect,
+            tr!(self, "input-pause-on-disconnect"),
+        )
+        .on_hover_text(tr!(self, "input-pause-on-disconnect-tooltip"));
+    }
+
     fn render_axis_deadzone_input(&mut self, ui: &mut Ui) {
         ui.horizontal(|ui| {
             ui.add(
@@ -1242,47 +1663,47 @@ impl App {
                 .desired_width(50.0),
             );
 
-            ui.label("Joystick axis deadzone (0-32767)");
+            ui.label(tr!(self, "input-axis-deadzone"));
         });
         if self.state.axis_deadzone_invalid {
-            ui.colored_label(Color32::RED, "Axis dead zone must be an integer between 0 and 32767");
+            ui.colored_label(Color32::RED, tr!(self, "input-axis-deadzone-invalid"));
         }
     }
 
     fn keyboard_input_button(
         &mut self,
         current_value: Option<String>,
-        label: &str,
+        label_id: &str,
         button: GenericButton,
         ui: &mut Ui,
     ) {
-        let text = current_value.unwrap_or("<None>".into());
-        self.input_button(&text, label, InputType::Keyboard, button, ui);
+        let text = current_value.unwrap_or_else(|| tr!(self, "value-none"));
+        self.input_button(&text, label_id, InputType::Keyboard, button, ui);
     }
 
     fn gamepad_input_button(
         &mut self,
         current_value: Option<JoystickInput>,
-        label: &str,
+        label_id: &str,
         button: GenericButton,
         ui: &mut Ui,
     ) {
         let text = current_value.map_or_else(
-            || "<None>".into(),
+            || tr!(self, "value-none"),
             |input| format!("{} ({})", input.action, input.device),
         );
-        self.input_button(&text, label, InputType::Joystick, button, ui);
+        self.input_button(&text, label_id, InputType::Joystick, button, ui);
     }
 
     fn input_button(
         &mut self,
         text: &str,
-        label: &str,
+        label_id: &str,
         input_type: InputType,
         button: GenericButton,
         ui: &mut Ui,
     ) {
-        ui.label(format!("{label}:"));
+        ui.label(format!("{}:", tr!(self, label_id)));
 
         if ui.button(text).clicked() {
             log::debug!("Sending collect input command for button {button:?}");
@@ -1294,7 +1715,7 @@ impl App {
             self.state.waiting_for_input = Some(button);
         }
 
-        if ui.button("Clear").clicked() {
+        if ui.button(tr!(self, "option-clear")).clicked() {
             log::debug!("Clearing button {button:?} for input_type {input_type:?}");
             self.clear_button_in_config(button, input_type);
         }
@@ -1317,6 +1738,7 @@ impl App {
                 (InputType::Joystick, Player::Two) => {
                     clear_smsgg_button(&mut self.config.inputs.smsgg_p2_joystick, button);
                 }
+                (_, Player::Three | Player::Four) => {}
                 (InputType::KeyboardOrMouse, _) => {}
             },
             GenericButton::Genesis(button) => match (input_type, button.player()) {
@@ -1332,6 +1754,18 @@ impl App {
                 (InputType::Joystick, Player::Two) => {
                     clear_genesis_button(&mut self.config.inputs.genesis_p2_joystick, button);
                 }
+                (InputType::Keyboard, Player::Three) => {
+                    clear_genesis_button(&mut self.config.inputs.genesis_p3_keyboard, button);
+                }
+                (InputType::Joystick, Player::Three) => {
+                    clear_genesis_button(&mut self.config.inputs.genesis_p3_joystick, button);
+                }
+                (InputType::Keyboard, Player::Four) => {
+                    clear_genesis_button(&mut self.config.inputs.genesis_p4_keyboard, button);
+                }
+                (InputType::Joystick, Player::Four) => {
+                    clear_genesis_button(&mut self.config.inputs.genesis_p4_joystick, button);
+                }
                 (InputType::KeyboardOrMouse, _) => {}
             },
             GenericButton::Nes(button) => match (input_type, button.player()) {
@@ -1347,6 +1781,7 @@ impl App {
                 (InputType::Joystick, Player::Two) => {
                     clear_nes_button(&mut self.config.inputs.nes_p2_joystick, button);
                 }
+                (_, Player::Three | Player::Four) => {}
                 (InputType::KeyboardOrMouse, _) => {}
             },
             GenericButton::Snes(button) => match (input_type, button.player()) {
@@ -1362,6 +1797,7 @@ impl App {
                 (InputType::Joystick, Player::Two) => {
                     clear_snes_button(&mut self.config.inputs.snes_p2_joystick, button);
                 }
+                (_, Player::Three | Player::Four) => {}
                 (InputType::KeyboardOrMouse, _) => {
                     if let SnesButton::SuperScope(super_scope_button) = button {
                         clear_super_scope_button(
@@ -1376,6 +1812,15 @@ impl App {
                 InputType::Joystick => clear_gb_button(&mut self.config.inputs.gb_joystick, button),
                 InputType::KeyboardOrMouse => {}
             },
+            GenericButton::Pce(button) => match input_type {
+                InputType::Keyboard => {
+                    clear_pce_button(&mut self.config.inputs.pce_keyboard, button);
+                }
+                InputType::Joystick => {
+                    clear_pce_button(&mut self.config.inputs.pce_joystick, button);
+                }
+                InputType::KeyboardOrMouse => {}
+            },
             GenericButton::Hotkey(hotkey) => match hotkey {
                 Hotkey::Quit => {
                     self.config.inputs.hotkeys.quit = None;
@@ -1410,26 +1855,144 @@ impl App {
                 Hotkey::OpenDebugger => {
                     self.config.inputs.hotkeys.open_debugger = None;
                 }
+                Hotkey::MusicDump => {
+                    self.config.inputs.hotkeys.music_dump = None;
+                }
+                Hotkey::Microphone => {
+                    self.config.inputs.hotkeys.microphone = None;
+                }
+                Hotkey::DumpInputTrace => {
+                    self.config.inputs.hotkeys.dump_input_trace = None;
+                }
+                Hotkey::NextSaveStateSlot => {
+                    self.config.inputs.hotkeys.next_save_state_slot = None;
+                }
+                Hotkey::PreviousSaveStateSlot => {
+                    self.config.inputs.hotkeys.previous_save_state_slot = None;
+                }
+                Hotkey::UndoLoadState => {
+                    self.config.inputs.hotkeys.undo_load_state = None;
+                }
+                Hotkey::SetPracticeLoopPoint => {
+                    self.config.inputs.hotkeys.set_practice_loop_point = None;
+                }
+                Hotkey::RestartPracticeAttempt => {
+                    self.config.inputs.hotkeys.restart_practice_attempt = None;
+                }
+                Hotkey::ClearPracticeLoopPoint => {
+                    self.config.inputs.hotkeys.clear_practice_loop_point = None;
+                }
+                Hotkey::RecordInputMacro => {
+                    self.config.inputs.hotkeys.record_input_macro = None;
+                }
+                Hotkey::OpenQuickMenu => match input_type {
+                    InputType::Keyboard => self.config.inputs.hotkeys.open_quick_menu = None,
+                    InputType::Joystick => {
+                        self.config.inputs.hotkeys.open_quick_menu_joystick = None;
+                    }
+                    InputType::KeyboardOrMouse => {}
+                },
             },
+            GenericButton::InputMacro(idx) => {
+                let Some(input_macro) = self.config.inputs.input_macros.get_mut(idx) else {
+                    return;
+                };
+                match input_type {
+                    InputType::Keyboard => input_macro.keyboard_trigger = None,
+                    InputType::Joystick => input_macro.joystick_trigger = None,
+                    InputType::KeyboardOrMouse => {}
+                }
+            }
         }
     }
 
-    fn controller_type_input(&mut self, label: &str, player: Player, ui: &mut Ui) {
+    fn controller_type_input(&mut self, label_id: &str, player: Player, ui: &mut Ui) {
         ui.group(|ui| {
-            ui.label(label);
+            ui.label(tr!(self, label_id));
 
             let controller_type_field = match player {
                 Player::One => &mut self.config.inputs.genesis_p1_type,
                 Player::Two => &mut self.config.inputs.genesis_p2_type,
+                Player::Three | Player::Four => return,
             };
 
             ui.horizontal(|ui| {
                 ui.radio_value(
                     controller_type_field,
                     GenesisControllerType::ThreeButton,
-                    "3-button",
+                    tr!(self, "input-genesis-3-button"),
+                );
+                ui.radio_value(
+                    controller_type_field,
+                    GenesisControllerType::SixButton,
+                    tr!(self, "input-genesis-6-button"),
+                );
+            });
+        });
+    }
+
+    fn render_sms_peripheral_settings(&mut self, ui: &mut Ui) {
+        ui.group(|ui| {
+            ui.label(tr!(self, "input-sms-p1-device"));
+
+            let controller_type_field = &mut self.config.inputs.smsgg_p1_type;
+            ui.horizontal(|ui| {
+                ui.radio_value(
+                    controller_type_field,
+                    SmsControllerType::Joypad,
+                    tr!(self, "input-sms-joypad"),
+                );
+                ui.radio_value(
+                    controller_type_field,
+                    SmsControllerType::Paddle,
+                    tr!(self, "input-sms-paddle"),
+                );
+                ui.radio_value(
+                    controller_type_field,
+                    SmsControllerType::SportsPad,
+                    tr!(self, "input-sms-sports-pad"),
+                );
+                ui.radio_value(
+                    controller_type_field,
+                    SmsControllerType::GraphicBoard,
+                    tr!(self, "input-sms-graphic-board"),
+                );
+            });
+
+            ui.set_enabled(self.config.inputs.smsgg_p1_type != SmsControllerType::Joypad);
+
+            let peripheral_config = &mut self.config.inputs.sms_peripheral;
+            ui.add(
+                Slider::new(&mut peripheral_config.mouse_sensitivity, 0.1..=5.0)
+                    .text(tr!(self, "input-mouse-sensitivity")),
+            );
+            ui.add(
+                Slider::new(&mut peripheral_config.stick_sensitivity, 0.1..=5.0)
+                    .text(tr!(self, "input-stick-sensitivity")),
+            );
+
+            ui.label(tr!(self, "input-sms-peripheral-buttons"));
+            ui.label(tr!(self, "input-sms-graphic-board-pen"));
+        });
+    }
+
+    fn multitap_input(&mut self, ui: &mut Ui) {
+        ui.group(|ui| {
+            ui.label(tr!(self, "input-genesis-multitap"));
+
+            let multitap_field = &mut self.config.inputs.genesis_multitap;
+            ui.horizontal(|ui| {
+                ui.radio_value(multitap_field, GenesisMultitap::None, tr!(self, "option-none"));
+                ui.radio_value(
+                    multitap_field,
+                    GenesisMultitap::EaFourWayPlay,
+                    tr!(self, "input-genesis-ea-4-way-play"),
+                );
+                ui.radio_value(
+                    multitap_field,
+                    GenesisMultitap::JCart,
+                    tr!(self, "input-genesis-j-cart"),
                 );
-                ui.radio_value(controller_type_field, GenesisControllerType::SixButton, "6-button");
             });
         });
     }
@@ -1437,15 +2000,15 @@ impl App {
     fn hotkey_button(
         &mut self,
         current_value: Option<KeyboardInput>,
-        label: &str,
+        label_id: &str,
         hotkey: Hotkey,
         ui: &mut Ui,
     ) {
-        ui.label(format!("{label}:"));
+        ui.label(format!("{}:", tr!(self, label_id)));
 
         let text = match current_value {
             Some(value) => value.keycode,
-            None => "<None>".into(),
+            None => tr!(self, "value-none"),
         };
         if ui.button(text).clicked() {
             log::debug!("Sending collect input command for hotkey {hotkey:?}");
@@ -1457,25 +2020,50 @@ impl App {
             self.state.waiting_for_input = Some(GenericButton::Hotkey(hotkey));
         }
 
-        if ui.button("Clear").clicked() {
+        if ui.button(tr!(self, "option-clear")).clicked() {
             self.clear_button_in_config(GenericButton::Hotkey(hotkey), InputType::Keyboard);
         }
 
         ui.end_row();
     }
 
+    fn quick_menu_gamepad_button(&mut self, ui: &mut Ui) {
+        ui.label(format!("{}:", tr!(self, "hotkey-open-quick-menu-gamepad")));
+
+        let button = GenericButton::Hotkey(Hotkey::OpenQuickMenu);
+        let text = match &self.config.inputs.hotkeys.open_quick_menu_joystick {
+            Some(value) => value.to_string(),
+            None => tr!(self, "value-none"),
+        };
+        if ui.button(text).clicked() {
+            log::debug!("Sending collect input command for quick menu gamepad input");
+            self.emu_thread.send(EmuThreadCommand::CollectInput {
+                input_type: InputType::Joystick,
+                axis_deadzone: self.config.inputs.axis_deadzone,
+                ctx: ui.ctx().clone(),
+            });
+            self.state.waiting_for_input = Some(button);
+        }
+
+        if ui.button(tr!(self, "option-clear")).clicked() {
+            self.clear_button_in_config(button, InputType::Joystick);
+        }
+
+        ui.end_row();
+    }
+
     fn super_scope_button(
         &mut self,
         current_value: Option<KeyboardOrMouseInput>,
-        label: &str,
+        label_id: &str,
         button: SuperScopeButton,
         ui: &mut Ui,
     ) {
-        ui.label(format!("{label}:"));
+        ui.label(format!("{}:", tr!(self, label_id)));
 
         let text = match current_value {
             Some(value) => value.to_string(),
-            None => "<None>".into(),
+            None => tr!(self, "value-none"),
         };
         if ui.button(text).clicked() {
             log::debug!("Sending collect input request for Super Scope button {button:?}");
@@ -1488,7 +2076,7 @@ impl App {
                 Some(GenericButton::Snes(SnesButton::SuperScope(button)));
         }
 
-        if ui.button("Clear").clicked() {
+        if ui.button(tr!(self, "option-clear")).clicked() {
             self.clear_button_in_config(
                 GenericButton::Snes(SnesButton::SuperScope(button)),
                 InputType::KeyboardOrMouse,
@@ -1588,3 +2176,18 @@ fn clear_gb_button<T>(config: &mut GameBoyInputConfig<T>, button: GameBoyButton)
 
     *field = None;
 }
+
+fn clear_pce_button<T>(config: &mut PceInputConfig<T>, button: PceButton) {
+    let field = match button {
+        PceButton::Up => &mut config.up,
+        PceButton::Left => &mut config.left,
+        PceButton::Right => &mut config.right,
+        PceButton::Down => &mut config.down,
+        PceButton::I => &mut config.i,
+        PceButton::II => &mut config.ii,
+        PceButton::Run => &mut config.run,
+        PceButton::Select => &mut config.select,
+    };
+
+    *field = None;
+}
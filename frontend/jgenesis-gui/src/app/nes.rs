@@ -1,20 +1,23 @@
+use crate::app::common::render_audio_post_processing_settings;
 use crate::app::{App, AppConfig, NumericTextEdit, OpenWindow};
 use crate::emuthread::EmuThreadStatus;
 use eframe::emath::Align;
 use eframe::epaint::Color32;
 use egui::{Context, Layout, Window};
-use jgenesis_common::frontend::TimingMode;
-use jgenesis_native_driver::config::NesConfig;
-use nes_core::api::{NesAspectRatio, Overscan};
+use jgenesis_native_driver::config::{AudioPostProcessingConfig, NesConfig};
+use nes_core::api::{NesAspectRatio, NesTimingMode, Overscan};
+use nes_core::input::NesExpansionDevice;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct NesAppConfig {
-    forced_timing_mode: Option<TimingMode>,
+    forced_timing_mode: Option<NesTimingMode>,
     #[serde(default)]
     aspect_ratio: NesAspectRatio,
     #[serde(default)]
     overscan: Overscan,
+    #[serde(default = "true_fn")]
+    emulate_ntsc_overscan: bool,
     #[serde(default)]
     remove_sprite_limit: bool,
     #[serde(default)]
@@ -25,6 +28,10 @@ pub struct NesAppConfig {
     audio_60hz_hack: bool,
     #[serde(default)]
     allow_opposing_joypad_inputs: bool,
+    #[serde(default)]
+    forced_expansion_device: Option<NesExpansionDevice>,
+    #[serde(default)]
+    audio_post_processing: AudioPostProcessingConfig,
 }
 
 const fn true_fn() -> bool {
@@ -76,15 +83,18 @@ impl AppConfig {
                 path,
                 self.inputs.to_nes_keyboard_config(),
                 self.inputs.to_nes_joystick_config(),
+                self.nes.audio_post_processing,
             ),
             forced_timing_mode: self.nes.forced_timing_mode,
             aspect_ratio: self.nes.aspect_ratio,
             overscan: self.nes.overscan,
+            emulate_ntsc_overscan: self.nes.emulate_ntsc_overscan,
             remove_sprite_limit: self.nes.remove_sprite_limit,
             pal_black_border: self.nes.pal_black_border,
             silence_ultrasonic_triangle_output: self.nes.silence_ultrasonic_triangle_output,
             audio_refresh_rate_adjustment: self.nes.audio_60hz_hack,
             allow_opposing_joypad_inputs: self.nes.allow_opposing_joypad_inputs,
+            forced_expansion_device: self.nes.forced_expansion_device,
         })
     }
 }
@@ -92,28 +102,83 @@ impl AppConfig {
 impl App {
     pub(super) fn render_nes_general_settings(&mut self, ctx: &Context) {
         let mut open = true;
-        Window::new("NES General Settings").open(&mut open).resizable(false).show(ctx, |ui| {
+        let title = tr!(self, "nes-general-window-title");
+        Window::new(title).open(&mut open).resizable(false).show(ctx, |ui| {
             ui.group(|ui| {
                 ui.add_enabled_ui(self.emu_thread.status() != EmuThreadStatus::RunningNes, |ui| {
-                    ui.label("Timing / display mode");
+                    ui.label(tr!(self, "timing-mode"));
 
                     ui.horizontal(|ui| {
-                        ui.radio_value(&mut self.config.nes.forced_timing_mode, None, "Auto");
                         ui.radio_value(
                             &mut self.config.nes.forced_timing_mode,
-                            Some(TimingMode::Ntsc),
-                            "NTSC",
+                            None,
+                            tr!(self, "option-auto"),
                         );
                         ui.radio_value(
                             &mut self.config.nes.forced_timing_mode,
-                            Some(TimingMode::Pal),
-                            "PAL",
+                            Some(NesTimingMode::Ntsc),
+                            tr!(self, "timing-ntsc"),
                         );
+                        ui.radio_value(
+                            &mut self.config.nes.forced_timing_mode,
+                            Some(NesTimingMode::Pal),
+                            tr!(self, "timing-pal"),
+                        );
+                        ui.radio_value(
+                            &mut self.config.nes.forced_timing_mode,
+                            Some(NesTimingMode::Dendy),
+                            tr!(self, "nes-timing-dendy"),
+                        )
+                        .on_hover_text(tr!(self, "nes-timing-dendy-tooltip"));
                     });
                 });
 
-                ui.checkbox(&mut self.config.nes.allow_opposing_joypad_inputs, "Allow simultaneous opposing directional inputs")
-                    .on_hover_text("Some games exhibit major glitches when opposing directions are pressed simultaneously");
+                ui.checkbox(
+                    &mut self.config.nes.allow_opposing_joypad_inputs,
+                    tr!(self, "nes-allow-opposing-inputs"),
+                )
+                .on_hover_text(tr!(self, "nes-allow-opposing-inputs-tooltip"));
+            });
+
+            ui.group(|ui| {
+                ui.label(tr!(self, "nes-expansion-device"));
+
+                ui.radio_value(
+                    &mut self.config.nes.forced_expansion_device,
+                    None,
+                    tr!(self, "option-auto"),
+                )
+                .on_hover_text(tr!(self, "nes-expansion-auto-tooltip"));
+                for (device, label_id, hover_text_id) in [
+                    (NesExpansionDevice::None, "nes-expansion-none", "nes-expansion-none-tooltip"),
+                    (
+                        NesExpansionDevice::FamilyBasicKeyboard,
+                        "nes-expansion-family-basic",
+                        "nes-expansion-family-basic-tooltip",
+                    ),
+                    (
+                        NesExpansionDevice::HyperShot,
+                        "nes-expansion-hyper-shot",
+                        "nes-expansion-hyper-shot-tooltip",
+                    ),
+                    (
+                        NesExpansionDevice::VausNes,
+                        "nes-expansion-vaus-nes",
+                        "nes-expansion-mouse-tooltip",
+                    ),
+                    (
+                        NesExpansionDevice::VausFamicom,
+                        "nes-expansion-vaus-famicom",
+                        "nes-expansion-mouse-tooltip",
+                    ),
+                ] {
+                    ui.radio_value(
+                        &mut self.config.nes.forced_expansion_device,
+                        Some(device),
+                        tr!(self, label_id),
+                    )
+                    .on_hover_text(tr!(self, hover_text_id));
+                }
             });
         });
         if !open {
@@ -123,44 +188,56 @@ impl App {
 
     pub(super) fn render_nes_video_settings(&mut self, ctx: &Context) {
         let mut open = true;
-        Window::new("NES Video Settings").open(&mut open).resizable(false).show(ctx, |ui| {
+        let title = tr!(self, "nes-video-window-title");
+        Window::new(title).open(&mut open).resizable(false).show(ctx, |ui| {
             ui.group(|ui| {
-                ui.label("Aspect ratio");
+                ui.label(tr!(self, "aspect-ratio"));
 
                 ui.horizontal(|ui| {
-                    ui.radio_value(&mut self.config.nes.aspect_ratio, NesAspectRatio::Ntsc, "NTSC")
-                        .on_hover_text("8:7 pixel aspect ratio");
-                    ui.radio_value(&mut self.config.nes.aspect_ratio, NesAspectRatio::Pal, "PAL")
-                        .on_hover_text("11:8 pixel aspect ratio");
+                    ui.radio_value(
+                        &mut self.config.nes.aspect_ratio,
+                        NesAspectRatio::Ntsc,
+                        tr!(self, "aspect-ratio-ntsc"),
+                    )
+                    .on_hover_text(tr!(self, "aspect-ratio-8-7-tooltip"));
+                    ui.radio_value(
+                        &mut self.config.nes.aspect_ratio,
+                        NesAspectRatio::Pal,
+                        tr!(self, "aspect-ratio-pal"),
+                    )
+                    .on_hover_text(tr!(self, "aspect-ratio-11-8-tooltip"));
                     ui.radio_value(
                         &mut self.config.nes.aspect_ratio,
                         NesAspectRatio::SquarePixels,
-                        "Square pixels",
+                        tr!(self, "aspect-ratio-square-pixels"),
                     )
-                    .on_hover_text("1:1 pixel aspect ratio");
+                    .on_hover_text(tr!(self, "aspect-ratio-square-pixels-tooltip"));
                     ui.radio_value(
                         &mut self.config.nes.aspect_ratio,
                         NesAspectRatio::Stretched,
-                        "Stretched",
+                        tr!(self, "aspect-ratio-stretched"),
                     )
-                    .on_hover_text("Stretched to fill the window");
+                    .on_hover_text(tr!(self, "aspect-ratio-stretched-window-tooltip"));
                 });
             });
 
             ui.checkbox(
                 &mut self.config.nes.remove_sprite_limit,
-                "Remove sprite-per-scanline limit",
+                tr!(self, "remove-sprite-limit"),
             )
-            .on_hover_text("Eliminates most sprite flickering but can cause visual glitches");
+            .on_hover_text(tr!(self, "nes-remove-sprite-limit-tooltip"));
+
+            ui.checkbox(&mut self.config.nes.pal_black_border, tr!(self, "nes-pal-black-border"))
+                .on_hover_text(tr!(self, "nes-pal-black-border-tooltip"));
 
-            ui.checkbox(&mut self.config.nes.pal_black_border, "Render PAL black border")
-                .on_hover_text("Crops the image from 256x240 to 252x239");
+            ui.checkbox(&mut self.config.nes.emulate_ntsc_overscan, tr!(self, "nes-ntsc-overscan"))
+                .on_hover_text(tr!(self, "nes-ntsc-overscan-tooltip"));
 
             ui.group(|ui| {
-                ui.label("Overscan in pixels");
+                ui.label(tr!(self, "nes-overscan"));
 
                 ui.vertical_centered(|ui| {
-                    ui.label("Top");
+                    ui.label(tr!(self, "nes-overscan-top"));
                     ui.add(
                         NumericTextEdit::new(
                             &mut self.state.overscan.top_text,
@@ -172,7 +249,7 @@ impl App {
                 });
 
                 ui.horizontal(|ui| {
-                    ui.label("Left");
+                    ui.label(tr!(self, "nes-overscan-left"));
                     ui.add(
                         NumericTextEdit::new(
                             &mut self.state.overscan.left_text,
@@ -183,7 +260,7 @@ impl App {
                     );
 
                     ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
-                        ui.label("Right");
+                        ui.label(tr!(self, "nes-overscan-right"));
                         ui.add(
                             NumericTextEdit::new(
                                 &mut self.state.overscan.right_text,
@@ -204,19 +281,19 @@ impl App {
                         )
                         .desired_width(30.0),
                     );
-                    ui.label("Bottom");
+                    ui.label(tr!(self, "nes-overscan-bottom"));
                 });
 
                 for (invalid, label) in [
-                    (self.state.overscan.top_invalid, "Top"),
-                    (self.state.overscan.bottom_invalid, "Bottom"),
-                    (self.state.overscan.left_invalid, "Left"),
-                    (self.state.overscan.right_invalid, "Right"),
+                    (self.state.overscan.top_invalid, tr!(self, "nes-overscan-top")),
+                    (self.state.overscan.bottom_invalid, tr!(self, "nes-overscan-bottom")),
+                    (self.state.overscan.left_invalid, tr!(self, "nes-overscan-left")),
+                    (self.state.overscan.right_invalid, tr!(self, "nes-overscan-right")),
                 ] {
                     if invalid {
                         ui.colored_label(
                             Color32::RED,
-                            format!("{label} value must be a non-negative integer"),
+                            tr!(self, "nes-overscan-invalid", "side" => label),
                         );
                     }
                 }
@@ -229,12 +306,22 @@ impl App {
 
     pub(super) fn render_nes_audio_settings(&mut self, ctx: &Context) {
         let mut open = true;
-        Window::new("NES Audio Settings").open(&mut open).resizable(false).show(ctx, |ui| {
-            ui.checkbox(&mut self.config.nes.silence_ultrasonic_triangle_output, "Silence ultrasonic triangle channel output")
-                .on_hover_text("Less accurate but can reduce audio popping in some games");
+        let title = tr!(self, "nes-audio-window-title");
+        Window::new(title).open(&mut open).resizable(false).show(ctx, |ui| {
+            ui.checkbox(
+                &mut self.config.nes.silence_ultrasonic_triangle_output,
+                tr!(self, "nes-silence-ultrasonic-triangle"),
+            )
+            .on_hover_text(tr!(self, "nes-silence-ultrasonic-triangle-tooltip"));
+
+            ui.checkbox(&mut self.config.nes.audio_60hz_hack, tr!(self, "audio-60hz-hack"))
+                .on_hover_text(tr!(self, "audio-60hz-hack-tooltip"));
 
-            ui.checkbox(&mut self.config.nes.audio_60hz_hack, "Enable audio 60Hz/50Hz hack")
-                .on_hover_text("Enabling this option will very slightly increase the audio signal frequency to time to 60Hz NTSC / 50Hz PAL");
+            render_audio_post_processing_settings(
+                ui,
+                &self.state.localizer,
+                &mut self.config.nes.audio_post_processing,
+            );
         });
         if !open {
             self.state.open_windows.remove(&OpenWindow::NesAudio);
@@ -0,0 +1,181 @@
+//! Per-ROM soft patches (fan translations, ROM hacks) and the patch manager window
+
+use crate::app::romlist::RomMetadata;
+use crate::app::{App, AppConfig, OpenWindow};
+use egui::{Button, Context, TextEdit, Widget, Window};
+use jgenesis_native_driver::patch::PatchFormat;
+use rfd::FileDialog;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+const fn true_fn() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RomPatchEntry {
+    pub path: String,
+    #[serde(default = "true_fn")]
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RomPatchConfig {
+    /// Which translation or hack the patched ROM is, e.g. "English translation v1.1"; shown next
+    /// to the ROM's name in the ROM list
+    #[serde(default)]
+    pub label: String,
+    /// Applied in order, each to the output of the previous patch
+    #[serde(default)]
+    pub patches: Vec<RomPatchEntry>,
+}
+
+impl RomPatchConfig {
+    fn is_empty(&self) -> bool {
+        self.label.trim().is_empty() && self.patches.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum PatchListAction {
+    MoveUp(usize),
+    MoveDown(usize),
+    Remove(usize),
+}
+
+impl AppConfig {
+    pub(super) fn enabled_rom_patches(&self, rom_path: &str) -> Vec<String> {
+        self.rom_patches
+            .get(rom_path)
+            .map(|config| {
+                config
+                    .patches
+                    .iter()
+                    .filter(|patch| patch.enabled)
+                    .map(|patch| patch.path.clone())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// The ROM's file name, followed by its translation/hack label if it has one.
+    pub(super) fn rom_display_name(&self, metadata: &RomMetadata) -> String {
+        let label = self
+            .rom_patches
+            .get(&metadata.full_path)
+            .map(|config| config.label.trim())
+            .filter(|label| !label.is_empty());
+        match label {
+            Some(label) => format!("{} [{label}]", metadata.file_name_no_ext),
+            None => metadata.file_name_no_ext.clone(),
+        }
+    }
+}
+
+impl App {
+    pub(super) fn open_patch_manager(&mut self, metadata: RomMetadata) {
+        self.state.patch_manager_rom = Some(metadata);
+        self.state.open_windows.insert(OpenWindow::RomPatches);
+    }
+
+    pub(super) fn render_patch_manager(&mut self, ctx: &Context) {
+        let Some(metadata) = self.state.patch_manager_rom.clone() else {
+            self.state.open_windows.remove(&OpenWindow::RomPatches);
+            return;
+        };
+
+        let mut patch_config =
+            self.config.rom_patches.get(&metadata.full_path).cloned().unwrap_or_default();
+        let mut action = None;
+
+        let mut open = true;
+        let title = tr!(self, "patches-window-title");
+        Window::new(title).open(&mut open).resizable(false).show(ctx, |ui| {
+            ui.heading(&metadata.file_name_no_ext);
+
+            ui.add_space(5.0);
+            ui.horizontal(|ui| {
+                ui.label(tr!(self, "patches-label"));
+                TextEdit::singleline(&mut patch_config.label)
+                    .hint_text(tr!(self, "patches-label-hint"))
+                    .desired_width(250.0)
+                    .ui(ui);
+            });
+
+            ui.add_space(5.0);
+            ui.group(|ui| {
+                ui.label(tr!(self, "patches-order"));
+
+                if patch_config.patches.is_empty() {
+                    ui.label(tr!(self, "patches-none"));
+                }
+
+                let len = patch_config.patches.len();
+                for (i, patch) in patch_config.patches.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        let file_name = Path::new(&patch.path)
+                            .file_name()
+                            .map_or(patch.path.clone(), |name| name.to_string_lossy().into_owned());
+                        ui.checkbox(&mut patch.enabled, file_name).on_hover_text(&patch.path);
+
+                        if ui
+                            .add_enabled(i != 0, Button::new(tr!(self, "patches-move-up")))
+                            .clicked()
+                        {
+                            action = Some(PatchListAction::MoveUp(i));
+                        }
+
+                        if ui
+                            .add_enabled(i + 1 != len, Button::new(tr!(self, "patches-move-down")))
+                            .clicked()
+                        {
+                            action = Some(PatchListAction::MoveDown(i));
+                        }
+
+                        if ui.button(tr!(self, "patches-remove")).clicked() {
+                            action = Some(PatchListAction::Remove(i));
+                        }
+                    });
+                }
+
+                if ui.button(tr!(self, "patches-add")).clicked() {
+                    let mut file_dialog = FileDialog::new()
+                        .add_filter(&tr!(self, "dialog-patch-files"), &PatchFormat::EXTENSIONS);
+                    if let Some(rom_dir) = Path::new(&metadata.full_path).parent() {
+                        file_dialog = file_dialog.set_directory(rom_dir);
+                    }
+
+                    for path in file_dialog.pick_files().unwrap_or_default() {
+                        let Some(path) = path.to_str() else { continue };
+                        patch_config
+                            .patches
+                            .push(RomPatchEntry { path: path.into(), enabled: true });
+                    }
+                }
+            });
+
+            ui.add_space(5.0);
+            ui.label(tr!(self, "patches-next-launch"));
+        });
+
+        match action {
+            Some(PatchListAction::MoveUp(i)) => patch_config.patches.swap(i - 1, i),
+            Some(PatchListAction::MoveDown(i)) => patch_config.patches.swap(i, i + 1),
+            Some(PatchListAction::Remove(i)) => {
+                patch_config.patches.remove(i);
+            }
+            None => {}
+        }
+
+        if patch_config.is_empty() {
+            self.config.rom_patches.remove(&metadata.full_path);
+        } else {
+            self.config.rom_patches.insert(metadata.full_path, patch_config);
+        }
+
+        if !open {
+            self.state.open_windows.remove(&OpenWindow::RomPatches);
+            self.state.patch_manager_rom = None;
+        }
+    }
+}
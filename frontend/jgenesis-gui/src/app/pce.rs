@@ -0,0 +1,111 @@
+use crate::app::common::render_audio_post_processing_settings;
+use crate::app::{App, AppConfig, OpenWindow};
+use egui::{Context, Window};
+use jgenesis_native_driver::config::{AudioPostProcessingConfig, PceConfig};
+use pce_core::api::{PceAspectRatio, PceRegion};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PceAppConfig {
+    #[serde(default)]
+    region: PceRegion,
+    #[serde(default)]
+    aspect_ratio: PceAspectRatio,
+    #[serde(default)]
+    audio_post_processing: AudioPostProcessingConfig,
+}
+
+impl Default for PceAppConfig {
+    fn default() -> Self {
+        toml::from_str("").unwrap()
+    }
+}
+
+impl AppConfig {
+    pub(super) fn pce_config(&self, path: String) -> Box<PceConfig> {
+        Box::new(PceConfig {
+            common: self.common_config(
+                path,
+                self.inputs.to_pce_keyboard_config(),
+                self.inputs.pce_joystick.clone(),
+                self.pce.audio_post_processing,
+            ),
+            region: self.pce.region,
+            aspect_ratio: self.pce.aspect_ratio,
+        })
+    }
+}
+
+impl App {
+    pub(super) fn render_pce_general_settings(&mut self, ctx: &Context) {
+        let mut open = true;
+        let title = tr!(self, "pce-general-window-title");
+        Window::new(title).open(&mut open).resizable(false).show(ctx, |ui| {
+            ui.group(|ui| {
+                ui.label(tr!(self, "region"));
+
+                ui.horizontal(|ui| {
+                    ui.radio_value(
+                        &mut self.config.pce.region,
+                        PceRegion::Japan,
+                        tr!(self, "pce-region-japan"),
+                    );
+                    ui.radio_value(
+                        &mut self.config.pce.region,
+                        PceRegion::Americas,
+                        tr!(self, "pce-region-americas"),
+                    );
+                });
+            });
+        });
+        if !open {
+            self.state.open_windows.remove(&OpenWindow::PceGeneral);
+        }
+    }
+
+    pub(super) fn render_pce_video_settings(&mut self, ctx: &Context) {
+        let mut open = true;
+        let title = tr!(self, "pce-video-window-title");
+        Window::new(title).open(&mut open).resizable(false).show(ctx, |ui| {
+            ui.group(|ui| {
+                ui.label(tr!(self, "aspect-ratio"));
+
+                ui.horizontal(|ui| {
+                    ui.radio_value(
+                        &mut self.config.pce.aspect_ratio,
+                        PceAspectRatio::Ntsc,
+                        tr!(self, "aspect-ratio-ntsc"),
+                    );
+                    ui.radio_value(
+                        &mut self.config.pce.aspect_ratio,
+                        PceAspectRatio::SquarePixels,
+                        tr!(self, "aspect-ratio-square-pixels"),
+                    );
+                    ui.radio_value(
+                        &mut self.config.pce.aspect_ratio,
+                        PceAspectRatio::Stretched,
+                        tr!(self, "aspect-ratio-stretched"),
+                    );
+                });
+            });
+        });
+        if !open {
+            self.state.open_windows.remove(&OpenWindow::PceVideo);
+        }
+    }
+
+    pub(super) fn render_pce_audio_settings(&mut self, ctx: &Context) {
+        let mut open = true;
+        let title = tr!(self, "pce-audio-window-title");
+        Window::new(title).open(&mut open).resizable(false).show(ctx, |ui| {
+            render_audio_post_processing_settings(
+                ui,
+                &self.state.localizer,
+                &mut self.config.pce.audio_post_processing,
+            );
+        });
+        if !open {
+            self.state.open_windows.remove(&OpenWindow::PceAudio);
+        }
+    }
+}
@@ -0,0 +1,327 @@
+//! Per-game play statistics: total play time, launch count, when the game was last played, and
+//! the compatibility status that the user recorded after playing it
+//!
+//! Statistics are keyed by ROM path and stored in the state directory rather than in the config
+//! file, since they are not settings. Play time is measured from when the emulator starts until it
+//! is stopped or its window is closed, including time spent paused.
+
+use crate::app::romlist::Console;
+use crate::emuthread::PlaySession;
+use jgenesis_proc_macros::EnumAll;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::fs;
+use std::io;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use time::OffsetDateTime;
+
+pub const STATS_FILE_NAME: &str = "jgenesis-play-stats.toml";
+
+/// How well a game runs in the core for its console
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumAll, Serialize, Deserialize)]
+pub enum Compatibility {
+    Works,
+    MinorIssues,
+    Broken,
+}
+
+impl Compatibility {
+    fn to_markdown_str(self) -> &'static str {
+        match self {
+            Self::Works => "Works",
+            Self::MinorIssues => "Minor issues",
+            Self::Broken => "Broken",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayStats {
+    #[serde(default)]
+    pub play_time_secs: u64,
+    #[serde(default)]
+    pub launch_count: u32,
+    /// Unix timestamp in seconds
+    #[serde(default)]
+    pub last_played: Option<u64>,
+    /// None if the game has not been rated
+    #[serde(default)]
+    pub compatibility: Option<Compatibility>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PlayStatsFile {
+    #[serde(default)]
+    games: BTreeMap<String, PlayStats>,
+}
+
+pub struct PlayStatsDatabase {
+    path: PathBuf,
+    file: PlayStatsFile,
+}
+
+impl PlayStatsDatabase {
+    pub fn load(path: PathBuf) -> Self {
+        let file = match fs::read_to_string(&path) {
+            Ok(stats_str) => toml::from_str(&stats_str).unwrap_or_else(|err| {
+                log::error!("Error deserializing play statistics at '{}': {err}", path.display());
+                PlayStatsFile::default()
+            }),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => PlayStatsFile::default(),
+            Err(err) => {
+                log::error!("Error reading play statistics at '{}': {err}", path.display());
+                PlayStatsFile::default()
+            }
+        };
+
+        Self { path, file }
+    }
+
+    pub fn get(&self, rom_path: &str) -> Option<&PlayStats> {
+        self.file.games.get(rom_path)
+    }
+
+    pub fn record_launch(&mut self, rom_path: &str) {
+        let stats = self.file.games.entry(rom_path.into()).or_default();
+        stats.launch_count += 1;
+        stats.last_played = Some(unix_time_now());
+
+        self.save();
+    }
+
+    pub fn record_session(&mut self, session: &PlaySession) {
+        let stats = self.file.games.entry(session.rom_path.clone()).or_default();
+        stats.play_time_secs += session.duration.as_secs();
+
+        self.save();
+    }
+
+    pub fn set_compatibility(&mut self, rom_path: &str, compatibility: Option<Compatibility>) {
+        let stats = self.file.games.entry(rom_path.into()).or_default();
+        if stats.compatibility == compatibility {
+            return;
+        }
+        stats.compatibility = compatibility;
+
+        self.save();
+    }
+
+    fn save(&self) {
+        let stats_str = match toml::to_string_pretty(&self.file) {
+            Ok(stats_str) => stats_str,
+            Err(err) => {
+                log::error!("Error serializing play statistics: {err}");
+                return;
+            }
+        };
+
+        if let Err(err) = fs::write(&self.path, stats_str) {
+            log::error!("Error writing play statistics to '{}': {err}", self.path.display());
+        }
+    }
+
+    /// Write statistics for every game that has been launched as CSV, with a header row.
+    ///
+    /// # Errors
+    ///
+    /// Propagates any errors from writing to `writer`.
+    pub fn write_csv<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writeln!(writer, "name,path,play_time_seconds,launch_count,last_played_utc")?;
+
+        for (rom_path, stats) in &self.file.games {
+            let name = Path::new(rom_path)
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let last_played = stats.last_played.and_then(format_timestamp).unwrap_or_default();
+
+            writeln!(
+                writer,
+                "{},{},{},{},{}",
+                csv_field(&name),
+                csv_field(rom_path),
+                stats.play_time_secs,
+                stats.launch_count,
+                last_played
+            )?;
+        }
+
+        writer.flush()
+    }
+
+    /// Write a Markdown compatibility list of every game that has a compatibility status, with one
+    /// table per console. Games are listed by file name, sorted case-insensitively.
+    ///
+    /// # Errors
+    ///
+    /// Propagates any errors from writing to `writer`.
+    pub fn write_compatibility_markdown<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        // Keyed by position in Console::ALL so that sections are in a stable order, with games for
+        // unrecognized file extensions last
+        let mut by_console: BTreeMap<usize, Vec<(String, Compatibility)>> = BTreeMap::new();
+        for (rom_path, stats) in &self.file.games {
+            let Some(compatibility) = stats.compatibility else { continue };
+
+            let path = Path::new(rom_path);
+            let console_idx = path
+                .extension()
+                .and_then(OsStr::to_str)
+                .and_then(Console::from_extension)
+                .and_then(|console| Console::ALL.iter().position(|&c| c == console))
+                .unwrap_or(Console::ALL.len());
+            let name = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            by_console.entry(console_idx).or_default().push((name, compatibility));
+        }
+
+        writeln!(writer, "# Compatibility")?;
+
+        for (console_idx, mut games) in by_console {
+            games.sort_by_cached_key(|(name, _)| name.to_lowercase());
+
+            let heading = Console::ALL.get(console_idx).map_or("Other", |console| console.to_str());
+            let count = |compatibility| games.iter().filter(|&&(_, c)| c == compatibility).count();
+            writeln!(writer)?;
+            writeln!(writer, "## {heading}")?;
+            writeln!(writer)?;
+            writeln!(
+                writer,
+                "{} tested: {} working, {} with minor issues, {} broken",
+                games.len(),
+                count(Compatibility::Works),
+                count(Compatibility::MinorIssues),
+                count(Compatibility::Broken)
+            )?;
+            writeln!(writer)?;
+            writeln!(writer, "| Game | Status |")?;
+            writeln!(writer, "| --- | --- |")?;
+            for (name, compatibility) in &games {
+                writeln!(
+                    writer,
+                    "| {} | {} |",
+                    markdown_table_cell(name),
+                    compatibility.to_markdown_str()
+                )?;
+            }
+        }
+
+        writer.flush()
+    }
+}
+
+fn markdown_table_cell(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('|', "\\|")
+}
+
+fn unix_time_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_secs())
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.into()
+    }
+}
+
+fn format_timestamp(timestamp: u64) -> Option<String> {
+    let date_time = OffsetDateTime::from_unix_timestamp(timestamp as i64).ok()?;
+    Some(format!(
+        "{} {:02}:{:02}:{:02}",
+        date_time.date(),
+        date_time.hour(),
+        date_time.minute(),
+        date_time.second()
+    ))
+}
+
+pub fn format_play_time(secs: u64) -> String {
+    let hours = secs / 3600;
+    let minutes = (secs / 60) % 60;
+    if hours != 0 {
+        format!("{hours}h {minutes:02}m")
+    } else {
+        format!("{minutes}m")
+    }
+}
+
+/// Returns the date (in UTC) of the given Unix timestamp, e.g. `2024-03-01`.
+pub fn format_last_played(timestamp: u64) -> Option<String> {
+    OffsetDateTime::from_unix_timestamp(timestamp as i64)
+        .ok()
+        .map(|date_time| date_time.date().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_quoting() {
+        assert_eq!(csv_field("Sonic"), "Sonic");
+        assert_eq!(csv_field("Sonic, Tails"), "\"Sonic, Tails\"");
+        assert_eq!(csv_field("\"Sonic\""), "\"\"\"Sonic\"\"\"");
+    }
+
+    #[test]
+    fn play_time_format() {
+        assert_eq!(format_play_time(59), "0m");
+        assert_eq!(format_play_time(61 * 60), "1h 01m");
+        assert_eq!(format_timestamp(86400 + 3661).as_deref(), Some("1970-01-02 01:01:01"));
+    }
+
+    #[test]
+    fn compatibility_markdown() {
+        let mut file = PlayStatsFile::default();
+        for (rom_path, compatibility) in [
+            ("roms/zelda.nes", Some(Compatibility::MinorIssues)),
+            ("roms/Contra.nes", Some(Compatibility::Works)),
+            ("roms/sonic.md", Some(Compatibility::Broken)),
+            ("roms/a|b.xyz", Some(Compatibility::Works)),
+            ("roms/unrated.md", None),
+        ] {
+            file.games.insert(rom_path.into(), PlayStats { compatibility, ..PlayStats::default() });
+        }
+        let database = PlayStatsDatabase { path: PathBuf::new(), file };
+
+        let mut markdown = Vec::new();
+        database.write_compatibility_markdown(&mut markdown).unwrap();
+
+        assert_eq!(
+            String::from_utf8(markdown).unwrap(),
+            "# Compatibility
+
+## Genesis
+
+1 tested: 0 working, 0 with minor issues, 1 broken
+
+| Game | Status |
+| --- | --- |
+| sonic | Broken |
+
+## NES
+
+2 tested: 1 working, 1 with minor issues, 0 broken
+
+| Game | Status |
+| --- | --- |
+| Contra | Works |
+| zelda | Minor issues |
+
+## Other
+
+1 tested: 1 working, 0 with minor issues, 0 broken
+
+| Game | Status |
+| --- | --- |
+| a\\|b | Works |
+"
+        );
+    }
+}
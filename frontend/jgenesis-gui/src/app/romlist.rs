@@ -1,12 +1,27 @@
+use bincode::{Decode, Encode};
+use crc::Crc;
 use jgenesis_proc_macros::EnumAll;
 use regex::Regex;
-use std::collections::HashSet;
+use segacd_core::m3u;
+use std::collections::{HashMap, HashSet};
 use std::ffi::OsStr;
-use std::path::Path;
+use std::fs::File;
+use std::io::{ErrorKind, Read};
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::OnceLock;
-use std::{fs, io};
+use std::time::UNIX_EPOCH;
+use std::{fs, io, thread};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumAll)]
+const CRC: Crc<u32> = Crc::<u32>::new(&crc::CRC_32_ISO_HDLC);
+
+// Bump whenever the cache format changes so that stale caches are discarded instead of misread
+const CACHE_VERSION: u32 = 3;
+
+pub const CACHE_FILE_NAME: &str = "jgenesis-romlist-cache.bin";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumAll, Encode, Decode)]
 pub enum Console {
     MasterSystem,
     GameGear,
@@ -16,19 +31,21 @@ pub enum Console {
     Snes,
     GameBoy,
     GameBoyColor,
+    PcEngine,
 }
 
 impl Console {
-    fn from_extension(extension: &str) -> Option<Self> {
+    pub fn from_extension(extension: &str) -> Option<Self> {
         match extension {
             "sms" => Some(Self::MasterSystem),
             "gg" => Some(Self::GameGear),
             "md" | "bin" => Some(Self::Genesis),
-            "cue" | "chd" => Some(Self::SegaCd),
+            "cue" | "chd" | "m3u" => Some(Self::SegaCd),
             "nes" => Some(Self::Nes),
             "sfc" | "smc" => Some(Self::Snes),
             "gb" => Some(Self::GameBoy),
             "gbc" => Some(Self::GameBoyColor),
+            "pce" => Some(Self::PcEngine),
             _ => None,
         }
     }
@@ -43,20 +60,106 @@ impl Console {
             Self::Snes => "SNES",
             Self::GameBoy => "Game Boy",
             Self::GameBoyColor => "Game Boy Color",
+            Self::PcEngine => "PC Engine",
         }
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Encode, Decode)]
 pub struct RomMetadata {
     pub full_path: String,
     pub file_name_no_ext: String,
     pub console: Console,
     pub file_size: u64,
+    /// CRC32 of the file contents; only populated for files found during a ROM directory scan
+    pub crc32: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+struct FileStamp {
+    modified_nanos: u128,
+    len: u64,
+}
+
+impl FileStamp {
+    fn read(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        let modified_nanos = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |duration| duration.as_nanos());
+
+        Some(Self { modified_nanos, len: metadata.len() })
+    }
+}
+
+#[derive(Debug, Clone, Encode, Decode)]
+struct CacheEntry {
+    // Stamp of the file itself followed by the stamps of any files it references (the tracks of a
+    // .cue, the discs of a .m3u), or None for referenced files that do not exist
+    stamps: Vec<Option<FileStamp>>,
+    metadata: RomMetadata,
+}
+
+#[derive(Debug, Default, Encode, Decode)]
+struct RomListCache {
+    version: u32,
+    entries: HashMap<String, CacheEntry>,
+}
+
+impl RomListCache {
+    fn load(path: &Path) -> Self {
+        let Ok(bytes) = fs::read(path) else { return Self::default() };
+
+        match bincode::decode_from_slice::<Self, _>(&bytes, bincode::config::standard()) {
+            Ok((cache, _)) if cache.version == CACHE_VERSION => cache,
+            Ok((cache, _)) => {
+                log::info!(
+                    "Discarding ROM list cache with version {}, expected {CACHE_VERSION}",
+                    cache.version
+                );
+                Self::default()
+            }
+            Err(err) => {
+                log::error!("Error decoding ROM list cache at '{}': {err}", path.display());
+                Self::default()
+            }
+        }
+    }
+
+    fn save(&self, path: &Path) {
+        let bytes = match bincode::encode_to_vec(self, bincode::config::standard()) {
+            Ok(bytes) => bytes,
+            Err(err) => {
+                log::error!("Error encoding ROM list cache: {err}");
+                return;
+            }
+        };
+
+        if let Err(err) = fs::write(path, bytes) {
+            log::error!("Error writing ROM list cache to '{}': {err}", path.display());
+        }
+    }
+
+    fn get(&self, full_path: &str, stamps: &[Option<FileStamp>]) -> Option<&RomMetadata> {
+        self.entries
+            .get(full_path)
+            .filter(|entry| entry.stamps == stamps)
+            .map(|entry| &entry.metadata)
+    }
 }
 
-pub fn build(rom_search_dirs: &[String]) -> Vec<RomMetadata> {
-    let mut metadata: Vec<_> = rom_search_dirs
+/// Scan the given directories for ROM files.
+///
+/// Files are processed across all available cores. Files that have not changed since the last scan
+/// (same path, modification time, and length, including any files referenced by a .cue or .m3u)
+/// are read from the cache at `cache_path` instead of being re-read and re-hashed, and the cache is
+/// rewritten with the results of this scan.
+pub fn build(rom_search_dirs: &[String], cache_path: &Path) -> Vec<RomMetadata> {
+    let cache = RomListCache::load(cache_path);
+
+    let file_paths: Vec<_> = rom_search_dirs
         .iter()
         .flat_map(|rom_search_dir| {
             fs::read_dir(Path::new(rom_search_dir))
@@ -64,13 +167,7 @@ pub fn build(rom_search_dirs: &[String]) -> Vec<RomMetadata> {
                     read_dir
                         .filter_map(|dir_entry| {
                             let dir_entry = dir_entry.ok()?;
-                            let metadata = dir_entry.metadata().ok()?;
-                            if !metadata.is_file() {
-                                return None;
-                            }
-
-                            let file_name = dir_entry.file_name().to_string_lossy().to_string();
-                            process_file(&file_name, &dir_entry.path(), metadata)
+                            dir_entry.file_type().ok()?.is_file().then(|| dir_entry.path())
                         })
                         .collect::<Vec<_>>()
                 })
@@ -78,6 +175,19 @@ pub fn build(rom_search_dirs: &[String]) -> Vec<RomMetadata> {
         })
         .collect();
 
+    let scanned = scan_files(&file_paths, &cache);
+
+    let new_cache = RomListCache {
+        version: CACHE_VERSION,
+        entries: scanned
+            .iter()
+            .map(|entry| (entry.metadata.full_path.clone(), entry.clone()))
+            .collect(),
+    };
+    new_cache.save(cache_path);
+
+    let mut metadata: Vec<_> = scanned.into_iter().map(|entry| entry.metadata).collect();
+
     // Remove any files that are referenced in .cue files
     let cd_bin_file_names = metadata
         .iter()
@@ -90,7 +200,7 @@ pub fn build(rom_search_dirs: &[String]) -> Vec<RomMetadata> {
             let cue_directory = path.parent()?;
             let cue_contents = fs::read_to_string(path).ok()?;
 
-            let file_names = parse_bin_file_names(&cue_contents)
+            let file_names = parse_cue_file_names(&cue_contents)
                 .filter_map(|file_name| cue_directory.join(file_name).to_str().map(String::from))
                 .collect::<Vec<_>>();
             Some(file_names)
@@ -98,12 +208,130 @@ pub fn build(rom_search_dirs: &[String]) -> Vec<RomMetadata> {
         .flatten()
         .collect::<HashSet<_>>();
 
-    metadata.retain(|metadata| !cd_bin_file_names.contains(&metadata.full_path));
+    // Remove any discs that are referenced in .m3u files so that multi-disc games only show up once
+    let playlist_disc_paths = metadata
+        .iter()
+        .filter(|metadata| {
+            Path::new(&metadata.full_path).extension().and_then(OsStr::to_str) == Some("m3u")
+        })
+        .filter_map(|metadata| m3u::parse(&metadata.full_path).ok())
+        .flatten()
+        .filter_map(|path| path.to_str().map(String::from))
+        .collect::<HashSet<_>>();
+
+    metadata.retain(|metadata| {
+        !cd_bin_file_names.contains(&metadata.full_path)
+            && !playlist_disc_paths.contains(&metadata.full_path)
+    });
 
     metadata.sort_by(|a, b| a.file_name_no_ext.cmp(&b.file_name_no_ext));
     metadata
 }
 
+fn scan_files(file_paths: &[PathBuf], cache: &RomListCache) -> Vec<CacheEntry> {
+    let num_threads =
+        thread::available_parallelism().map_or(1, NonZeroUsize::get).min(file_paths.len()).max(1);
+
+    // Workers pull files off a shared index rather than splitting the list up front, since file
+    // sizes (and thus hashing times) can vary by several orders of magnitude
+    let next_idx = AtomicUsize::new(0);
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..num_threads)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut entries = Vec::new();
+                    loop {
+                        let idx = next_idx.fetch_add(1, Ordering::Relaxed);
+                        let Some(path) = file_paths.get(idx) else { break };
+
+                        if let Some(entry) = scan_file(path, cache) {
+                            entries.push(entry);
+                        }
+                    }
+                    entries
+                })
+            })
+            .collect();
+
+        workers.into_iter().flat_map(|worker| worker.join().unwrap_or_default()).collect()
+    })
+}
+
+fn scan_file(path: &Path, cache: &RomListCache) -> Option<CacheEntry> {
+    let full_path = path.to_str()?;
+    let stamps: Vec<_> = std::iter::once(Some(FileStamp::read(path)?))
+        .chain(referenced_files(path).iter().map(|path| FileStamp::read(path)))
+        .collect();
+
+    if let Some(metadata) = cache.get(full_path, &stamps) {
+        return Some(CacheEntry { stamps, metadata: metadata.clone() });
+    }
+
+    let file_name = path.file_name()?.to_string_lossy();
+    let mut metadata = process_file(&file_name, path, fs::metadata(path).ok()?)?;
+
+    match hash_file(path) {
+        Ok(crc32) => metadata.crc32 = Some(crc32),
+        Err(err) => log::error!("Error hashing ROM file '{full_path}': {err}"),
+    }
+
+    Some(CacheEntry { stamps, metadata })
+}
+
+fn hash_file(path: &Path) -> io::Result<u32> {
+    let mut file = File::open(path)?;
+    let mut buffer = vec![0; 64 * 1024];
+    let mut digest = CRC.digest();
+    loop {
+        match file.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(bytes_read) => digest.update(&buffer[..bytes_read]),
+            Err(err) if err.kind() == ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok(digest.finalize())
+}
+
+// Files whose sizes are included in the file size of a .cue or .m3u
+fn referenced_files(path: &Path) -> Vec<PathBuf> {
+    match path.extension().and_then(OsStr::to_str) {
+        Some("cue") => cue_track_files(path),
+        Some("m3u") => {
+            let Ok(discs) = m3u::parse(path) else { return vec![] };
+
+            // Only .cue entries are expanded; a playlist that lists itself or another playlist must
+            // not recurse
+            discs
+                .into_iter()
+                .flat_map(|disc_path| {
+                    let tracks = match disc_path.extension().and_then(OsStr::to_str) {
+                        Some("cue") => cue_track_files(&disc_path),
+                        _ => vec![],
+                    };
+                    std::iter::once(disc_path).chain(tracks)
+                })
+                .collect()
+        }
+        _ => vec![],
+    }
+}
+
+fn cue_track_files(cue_path: &Path) -> Vec<PathBuf> {
+    let (Some(cue_directory), Ok(cue_contents)) = (cue_path.parent(), fs::read_to_string(cue_path))
+    else {
+        return vec![];
+    };
+
+    let mut files: Vec<_> = parse_cue_file_names(&cue_contents)
+        .map(|file_name| cue_directory.join(file_name))
+        .collect();
+    files.sort();
+    files.dedup();
+    files
+}
+
 fn process_file(file_name: &str, path: &Path, metadata: fs::Metadata) -> Option<RomMetadata> {
     let extension = Path::new(&file_name).extension().and_then(OsStr::to_str)?;
     let console = Console::from_extension(extension)?;
@@ -113,10 +341,11 @@ fn process_file(file_name: &str, path: &Path, metadata: fs::Metadata) -> Option<
 
     let file_size = match extension {
         "cue" => sega_cd_file_size(&full_path).ok()?,
+        "m3u" => playlist_file_size(&full_path).ok()?,
         _ => metadata.len(),
     };
 
-    Some(RomMetadata { full_path, file_name_no_ext, console, file_size })
+    Some(RomMetadata { full_path, file_name_no_ext, console, file_size, crc32: None })
 }
 
 fn sega_cd_file_size(cue_path: &str) -> io::Result<u64> {
@@ -124,7 +353,7 @@ fn sega_cd_file_size(cue_path: &str) -> io::Result<u64> {
     let cue_directory =
         Path::new(cue_path).parent().expect("Valid file should always have a parent dir");
 
-    let unique_file_names = parse_bin_file_names(&cue_contents).collect::<HashSet<_>>();
+    let unique_file_names = parse_cue_file_names(&cue_contents).collect::<HashSet<_>>();
 
     unique_file_names
         .iter()
@@ -135,11 +364,24 @@ fn sega_cd_file_size(cue_path: &str) -> io::Result<u64> {
         .sum()
 }
 
-fn parse_bin_file_names(cue_contents: &str) -> impl Iterator<Item = &str> {
+fn playlist_file_size(m3u_path: &str) -> io::Result<u64> {
+    let discs = m3u::parse(m3u_path).map_err(|err| io::Error::other(err.to_string()))?;
+
+    discs
+        .iter()
+        .map(|disc_path| match disc_path.extension().and_then(OsStr::to_str) {
+            Some("cue") => sega_cd_file_size(&disc_path.to_string_lossy()),
+            _ => fs::metadata(disc_path).map(|metadata| metadata.len()),
+        })
+        .sum()
+}
+
+fn parse_cue_file_names(cue_contents: &str) -> impl Iterator<Item = &str> {
     static LINE_RE: OnceLock<Regex> = OnceLock::new();
 
     cue_contents.lines().filter_map(|line| {
-        let line_re = LINE_RE.get_or_init(|| Regex::new(r#"FILE "(.*)" BINARY"#).unwrap());
+        // Match every file type (BINARY, MOTOROLA, WAVE, etc.) so that audio tracks are counted too
+        let line_re = LINE_RE.get_or_init(|| Regex::new(r#"FILE "(.*)" \S+"#).unwrap());
 
         line_re.captures(line).map(|captures| captures.get(1).unwrap().as_str())
     })
@@ -157,3 +399,49 @@ pub fn from_recent_opens(recent_opens: &[String]) -> Vec<RomMetadata> {
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cue_file_names_of_every_type() {
+        let cue_contents = "FILE \"Game (Track 1).bin\" BINARY\n\
+                            \tTRACK 01 MODE1/2352\n\
+                            FILE \"Game (Track 2).wav\" WAVE\n\
+                            \tTRACK 02 AUDIO\n\
+                            FILE \"Game (Track 3).bin\" MOTOROLA\n\
+                            \tTRACK 03 AUDIO\n";
+
+        assert_eq!(
+            parse_cue_file_names(cue_contents).collect::<Vec<_>>(),
+            vec!["Game (Track 1).bin", "Game (Track 2).wav", "Game (Track 3).bin"]
+        );
+    }
+
+    #[test]
+    fn self_referencing_playlist() {
+        let dir = std::env::temp_dir().join(format!("romlist-m3u-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let m3u_path = dir.join("Game.m3u");
+        let other_m3u_path = dir.join("Other.m3u");
+        let cue_path = dir.join("Game (Disc 1).cue");
+        fs::write(&m3u_path, "Game.m3u\nOther.m3u\nGame (Disc 1).cue\n").unwrap();
+        fs::write(&other_m3u_path, "Game.m3u\n").unwrap();
+        fs::write(&cue_path, "FILE \"Game (Disc 1).bin\" BINARY\n").unwrap();
+
+        assert_eq!(
+            referenced_files(&m3u_path),
+            vec![
+                m3u_path.clone(),
+                other_m3u_path.clone(),
+                cue_path.clone(),
+                dir.join("Game (Disc 1).bin"),
+            ]
+        );
+        assert_eq!(referenced_files(&other_m3u_path), vec![m3u_path]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,8 +1,11 @@
+use crate::app::common::render_audio_post_processing_settings;
 use crate::app::{App, AppConfig, OpenWindow};
 use crate::emuthread::EmuThreadStatus;
-use egui::{Context, Window};
+use egui::{Context, TextEdit, Window};
 use jgenesis_common::frontend::TimingMode;
-use jgenesis_native_driver::config::{GgAspectRatio, SmsAspectRatio, SmsGgConfig};
+use jgenesis_native_driver::config::{
+    AudioPostProcessingConfig, GgAspectRatio, LinkTransportKind, SmsAspectRatio, SmsGgConfig,
+};
 use serde::{Deserialize, Serialize};
 use smsgg_core::psg::PsgVersion;
 use smsgg_core::{SmsRegion, VdpVersion};
@@ -16,7 +19,7 @@ pub enum SmsModel {
     Sms2,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SmsGgAppConfig {
     psg_version: Option<PsgVersion>,
     #[serde(default)]
@@ -39,12 +42,22 @@ pub struct SmsGgAppConfig {
     fm_sound_unit_enabled: bool,
     #[serde(default)]
     overclock_z80: bool,
+    #[serde(default)]
+    audio_post_processing: AudioPostProcessingConfig,
+    #[serde(default)]
+    gg_link_transport: LinkTransportKind,
+    #[serde(default = "default_gg_link_tcp_address")]
+    gg_link_tcp_address: String,
 }
 
 const fn true_fn() -> bool {
     true
 }
 
+fn default_gg_link_tcp_address() -> String {
+    "127.0.0.1:7846".into()
+}
+
 impl Default for SmsGgAppConfig {
     fn default() -> Self {
         toml::from_str("").unwrap()
@@ -69,7 +82,10 @@ impl AppConfig {
                 path,
                 self.inputs.to_smsgg_keyboard_config(),
                 self.inputs.to_smsgg_joystick_config(),
+                self.smsgg.audio_post_processing,
             ),
+            p1_controller_type: self.inputs.smsgg_p1_type,
+            peripheral_config: self.inputs.sms_peripheral,
             vdp_version,
             psg_version: self.smsgg.psg_version,
             remove_sprite_limit: self.smsgg.remove_sprite_limit,
@@ -80,6 +96,8 @@ impl AppConfig {
             sms_crop_left_border: self.smsgg.sms_crop_left_border,
             fm_sound_unit_enabled: self.smsgg.fm_sound_unit_enabled,
             overclock_z80: self.smsgg.overclock_z80,
+            link_transport: self.smsgg.gg_link_transport,
+            link_tcp_address: self.smsgg.gg_link_tcp_address.clone(),
         })
     }
 }
@@ -87,52 +105,102 @@ impl AppConfig {
 impl App {
     pub(super) fn render_smsgg_general_settings(&mut self, ctx: &Context) {
         let mut open = true;
-        Window::new("SMS/GG General Settings").open(&mut open).resizable(false).show(ctx, |ui| {
+        let title = tr!(self, "smsgg-general-window-title");
+        Window::new(title).open(&mut open).resizable(false).show(ctx, |ui| {
             ui.group(|ui| {
-                ui.label("Sega Master System timing / display mode");
+                ui.label(tr!(self, "smsgg-sms-timing-mode"));
 
                 ui.horizontal(|ui| {
                     ui.radio_value(
                         &mut self.config.smsgg.sms_timing_mode,
                         TimingMode::Ntsc,
-                        "NTSC",
+                        tr!(self, "timing-ntsc"),
+                    );
+                    ui.radio_value(
+                        &mut self.config.smsgg.sms_timing_mode,
+                        TimingMode::Pal,
+                        tr!(self, "timing-pal"),
                     );
-                    ui.radio_value(&mut self.config.smsgg.sms_timing_mode, TimingMode::Pal, "PAL");
                 });
             });
 
             ui.group(|ui| {
-                ui.label("Sega Master System VDP version");
+                ui.label(tr!(self, "smsgg-sms-model"));
 
                 ui.horizontal(|ui| {
-                    ui.radio_value(&mut self.config.smsgg.sms_model, SmsModel::Sms2, "SMS2");
+                    ui.radio_value(
+                        &mut self.config.smsgg.sms_model,
+                        SmsModel::Sms2,
+                        tr!(self, "smsgg-sms2"),
+                    );
 
-                    ui.radio_value(&mut self.config.smsgg.sms_model, SmsModel::Sms1, "SMS1")
-                        .on_hover_text("Emulates an SMS1 quirk that is required for the Japanese version of Ys");
+                    ui.radio_value(
+                        &mut self.config.smsgg.sms_model,
+                        SmsModel::Sms1,
+                        tr!(self, "smsgg-sms1"),
+                    )
+                    .on_hover_text(tr!(self, "smsgg-sms1-tooltip"));
                 });
             });
 
             ui.group(|ui| {
-                ui.label("Sega Master System region");
+                ui.label(tr!(self, "smsgg-sms-region"));
 
                 ui.horizontal(|ui| {
                     ui.radio_value(
                         &mut self.config.smsgg.sms_region,
                         SmsRegion::International,
-                        "International / Overseas",
+                        tr!(self, "smsgg-region-international"),
                     );
                     ui.radio_value(
                         &mut self.config.smsgg.sms_region,
                         SmsRegion::Domestic,
-                        "Domestic (Japan)",
+                        tr!(self, "smsgg-region-domestic"),
                     );
                 });
             });
 
-            ui.checkbox(&mut self.config.smsgg.overclock_z80, "Double Z80 CPU speed")
-                .on_hover_text(
-                    "Can reduce slowdown in some games but can also cause major glitches",
+            ui.checkbox(&mut self.config.smsgg.overclock_z80, tr!(self, "smsgg-overclock-z80"))
+                .on_hover_text(tr!(self, "smsgg-overclock-z80-tooltip"));
+
+            ui.group(|ui| {
+                // The link is opened when the game starts
+                ui.set_enabled(self.emu_thread.status() != EmuThreadStatus::RunningSmsGg);
+
+                ui.label(tr!(self, "smsgg-gg-link"))
+                    .on_hover_text(tr!(self, "smsgg-gg-link-tooltip"));
+
+                ui.horizontal(|ui| {
+                    ui.radio_value(
+                        &mut self.config.smsgg.gg_link_transport,
+                        LinkTransportKind::None,
+                        tr!(self, "link-not-connected"),
+                    );
+                    ui.radio_value(
+                        &mut self.config.smsgg.gg_link_transport,
+                        LinkTransportKind::TcpClient,
+                        tr!(self, "link-tcp-client"),
+                    );
+                    ui.radio_value(
+                        &mut self.config.smsgg.gg_link_transport,
+                        LinkTransportKind::TcpServer,
+                        tr!(self, "link-tcp-server"),
+                    );
+                });
+
+                ui.add_enabled_ui(
+                    self.config.smsgg.gg_link_transport != LinkTransportKind::None,
+                    |ui| {
+                        ui.horizontal(|ui| {
+                            ui.add(
+                                TextEdit::singleline(&mut self.config.smsgg.gg_link_tcp_address)
+                                    .desired_width(150.0),
+                            );
+                            ui.label(tr!(self, "link-address"));
+                        });
+                    },
                 );
+            });
         });
         if !open {
             self.state.open_windows.remove(&OpenWindow::SmsGgGeneral);
@@ -141,73 +209,77 @@ impl App {
 
     pub(super) fn render_smsgg_video_settings(&mut self, ctx: &Context) {
         let mut open = true;
-        Window::new("SMS/GG Video Settings").open(&mut open).resizable(false).show(ctx, |ui| {
+        let title = tr!(self, "smsgg-video-window-title");
+        Window::new(title).open(&mut open).resizable(false).show(ctx, |ui| {
             ui.group(|ui| {
-                ui.label("Sega Master System aspect ratio");
+                ui.label(tr!(self, "smsgg-sms-aspect-ratio"));
 
                 ui.horizontal(|ui| {
                     ui.radio_value(
                         &mut self.config.smsgg.sms_aspect_ratio,
                         SmsAspectRatio::Ntsc,
-                        "NTSC",
+                        tr!(self, "aspect-ratio-ntsc"),
                     )
-                    .on_hover_text("8:7 pixel aspect ratio");
+                    .on_hover_text(tr!(self, "aspect-ratio-8-7-tooltip"));
                     ui.radio_value(
                         &mut self.config.smsgg.sms_aspect_ratio,
                         SmsAspectRatio::Pal,
-                        "PAL",
+                        tr!(self, "aspect-ratio-pal"),
                     )
-                    .on_hover_text("11:8 pixel aspect ratio");
+                    .on_hover_text(tr!(self, "aspect-ratio-11-8-tooltip"));
                     ui.radio_value(
                         &mut self.config.smsgg.sms_aspect_ratio,
                         SmsAspectRatio::SquarePixels,
-                        "Square pixels",
+                        tr!(self, "aspect-ratio-square-pixels"),
                     )
-                    .on_hover_text("1:1 pixel aspect ratio");
+                    .on_hover_text(tr!(self, "aspect-ratio-square-pixels-tooltip"));
                     ui.radio_value(
                         &mut self.config.smsgg.sms_aspect_ratio,
                         SmsAspectRatio::Stretched,
-                        "Stretched",
+                        tr!(self, "aspect-ratio-stretched"),
                     )
-                    .on_hover_text("Stretch image to fill the screen");
+                    .on_hover_text(tr!(self, "aspect-ratio-stretched-tooltip"));
                 });
             });
 
             ui.group(|ui| {
-                ui.label("Game Gear aspect ratio");
+                ui.label(tr!(self, "smsgg-gg-aspect-ratio"));
 
                 ui.horizontal(|ui| {
                     ui.radio_value(
                         &mut self.config.smsgg.gg_aspect_ratio,
                         GgAspectRatio::GgLcd,
-                        "Game Gear LCD",
+                        tr!(self, "smsgg-gg-lcd"),
                     )
-                    .on_hover_text("6:5 pixel aspect ratio");
+                    .on_hover_text(tr!(self, "smsgg-gg-lcd-tooltip"));
                     ui.radio_value(
                         &mut self.config.smsgg.gg_aspect_ratio,
                         GgAspectRatio::SquarePixels,
-                        "Square pixels",
+                        tr!(self, "aspect-ratio-square-pixels"),
                     )
-                    .on_hover_text("1:1 pixel aspect ratio");
+                    .on_hover_text(tr!(self, "aspect-ratio-square-pixels-tooltip"));
                     ui.radio_value(
                         &mut self.config.smsgg.gg_aspect_ratio,
                         GgAspectRatio::Stretched,
-                        "Stretched",
+                        tr!(self, "aspect-ratio-stretched"),
                     )
-                    .on_hover_text("Stretch image to fill the screen");
+                    .on_hover_text(tr!(self, "aspect-ratio-stretched-tooltip"));
                 });
             });
 
             ui.checkbox(
                 &mut self.config.smsgg.remove_sprite_limit,
-                "Remove sprite-per-scanline limit",
+                tr!(self, "remove-sprite-limit"),
             );
 
             ui.checkbox(
                 &mut self.config.smsgg.sms_crop_vertical_border,
-                "(SMS) Crop vertical border",
+                tr!(self, "smsgg-crop-vertical-border"),
+            );
+            ui.checkbox(
+                &mut self.config.smsgg.sms_crop_left_border,
+                tr!(self, "smsgg-crop-left-border"),
             );
-            ui.checkbox(&mut self.config.smsgg.sms_crop_left_border, "(SMS) Crop left border");
         });
         if !open {
             self.state.open_windows.remove(&OpenWindow::SmsGgVideo);
@@ -216,33 +288,43 @@ impl App {
 
     pub(super) fn render_smsgg_audio_settings(&mut self, ctx: &Context) {
         let mut open = true;
-        Window::new("SMS/GG Audio Settings").open(&mut open).resizable(false).show(ctx, |ui| {
+        let title = tr!(self, "smsgg-audio-window-title");
+        Window::new(title).open(&mut open).resizable(false).show(ctx, |ui| {
             ui.group(|ui| {
-                ui.label("PSG version");
+                ui.label(tr!(self, "smsgg-psg-version"));
 
                 ui.horizontal(|ui| {
-                    ui.radio_value(&mut self.config.smsgg.psg_version, None, "Auto").on_hover_text(
-                        "SMS games will use SMS2 PSG, Game Gear games will use SMS1/GG PSG",
-                    );
+                    ui.radio_value(
+                        &mut self.config.smsgg.psg_version,
+                        None,
+                        tr!(self, "option-auto"),
+                    )
+                    .on_hover_text(tr!(self, "smsgg-psg-auto-tooltip"));
                     ui.radio_value(
                         &mut self.config.smsgg.psg_version,
                         Some(PsgVersion::MasterSystem2),
-                        "SMS2",
+                        tr!(self, "smsgg-sms2"),
                     )
-                    .on_hover_text("SMS2 PSG clips high volumes");
+                    .on_hover_text(tr!(self, "smsgg-psg-sms2-tooltip"));
                     ui.radio_value(
                         &mut self.config.smsgg.psg_version,
                         Some(PsgVersion::Standard),
-                        "SMS1 / Game Gear",
+                        tr!(self, "smsgg-psg-standard"),
                     )
-                    .on_hover_text("SMS1 and Game Gear PSGs correctly play high volumes");
+                    .on_hover_text(tr!(self, "smsgg-psg-standard-tooltip"));
                 });
             });
 
+            render_audio_post_processing_settings(
+                ui,
+                &self.state.localizer,
+                &mut self.config.smsgg.audio_post_processing,
+            );
+
             ui.set_enabled(self.emu_thread.status() != EmuThreadStatus::RunningSmsGg);
             ui.checkbox(
                 &mut self.config.smsgg.fm_sound_unit_enabled,
-                "Sega Master System FM sound unit enabled",
+                tr!(self, "smsgg-fm-sound-unit"),
             );
         });
         if !open {
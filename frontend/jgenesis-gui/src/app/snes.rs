@@ -1,14 +1,16 @@
+use crate::app::common::{render_audio_post_processing_settings, render_deinterlace_settings};
 use crate::app::{App, AppConfig, OpenWindow};
 use crate::emuthread::EmuThreadStatus;
 use egui::{Context, Window};
 use jgenesis_common::frontend::TimingMode;
-use jgenesis_native_driver::config::SnesConfig;
+use jgenesis_native_driver::config::{AudioPostProcessingConfig, SnesConfig};
+use jgenesis_renderer::deinterlace::DeinterlaceMode;
 use rfd::FileDialog;
 use serde::{Deserialize, Serialize};
-use snes_core::api::SnesAspectRatio;
+use snes_core::api::{Mode7Scale, SnesAspectRatio, SnesEnhancements};
 use std::num::NonZeroU64;
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SnesAppConfig {
     forced_timing_mode: Option<TimingMode>,
     #[serde(default)]
@@ -23,6 +25,14 @@ pub struct SnesAppConfig {
     dsp4_rom_path: Option<String>,
     st010_rom_path: Option<String>,
     st011_rom_path: Option<String>,
+    #[serde(default)]
+    audio_post_processing: AudioPostProcessingConfig,
+    #[serde(default)]
+    mode_7_scale: Mode7Scale,
+    #[serde(default)]
+    mode_7_perspective_correction: bool,
+    #[serde(default)]
+    deinterlace_mode: DeinterlaceMode,
 }
 
 const fn true_fn() -> bool {
@@ -46,6 +56,7 @@ impl AppConfig {
                 path,
                 self.inputs.to_snes_keyboard_config(),
                 self.inputs.to_snes_joystick_config(),
+                self.snes.audio_post_processing,
             ),
             p2_controller_type: self.inputs.snes_p2_type,
             super_scope_config: self.inputs.snes_super_scope.clone(),
@@ -53,12 +64,17 @@ impl AppConfig {
             aspect_ratio: self.snes.aspect_ratio,
             audio_60hz_hack: self.snes.audio_60hz_hack,
             gsu_overclock_factor: self.snes.gsu_overclock_factor,
+            enhancements: SnesEnhancements {
+                mode_7_scale: self.snes.mode_7_scale,
+                mode_7_perspective_correction: self.snes.mode_7_perspective_correction,
+            },
             dsp1_rom_path: self.snes.dsp1_rom_path.clone(),
             dsp2_rom_path: self.snes.dsp2_rom_path.clone(),
             dsp3_rom_path: self.snes.dsp3_rom_path.clone(),
             dsp4_rom_path: self.snes.dsp4_rom_path.clone(),
             st010_rom_path: self.snes.st010_rom_path.clone(),
             st011_rom_path: self.snes.st011_rom_path.clone(),
+            deinterlace_mode: self.snes.deinterlace_mode,
         })
     }
 }
@@ -66,35 +82,41 @@ impl AppConfig {
 impl App {
     pub(super) fn render_snes_general_settings(&mut self, ctx: &Context) {
         let mut open = true;
-        Window::new("SNES General Settings").open(&mut open).resizable(false).show(ctx, |ui| {
+        let title = tr!(self, "snes-general-window-title");
+        let none_label = tr!(self, "value-none");
+        Window::new(title).open(&mut open).resizable(false).show(ctx, |ui| {
             ui.group(|ui| {
                 ui.set_enabled(self.emu_thread.status() != EmuThreadStatus::RunningSnes);
 
-                ui.label("Timing / display mode");
+                ui.label(tr!(self, "timing-mode"));
 
                 ui.horizontal(|ui| {
-                    ui.radio_value(&mut self.config.snes.forced_timing_mode, None, "Auto");
+                    ui.radio_value(
+                        &mut self.config.snes.forced_timing_mode,
+                        None,
+                        tr!(self, "option-auto"),
+                    );
                     ui.radio_value(
                         &mut self.config.snes.forced_timing_mode,
                         Some(TimingMode::Ntsc),
-                        "NTSC",
+                        tr!(self, "timing-ntsc"),
                     );
                     ui.radio_value(
                         &mut self.config.snes.forced_timing_mode,
                         Some(TimingMode::Pal),
-                        "PAL",
+                        tr!(self, "timing-pal"),
                     );
                 });
             });
 
             ui.group(|ui| {
-                ui.label("Super FX GSU overclock factor");
+                ui.label(tr!(self, "snes-gsu-overclock"));
 
                 ui.horizontal(|ui| {
                     ui.radio_value(
                         &mut self.config.snes.gsu_overclock_factor,
                         NonZeroU64::new(1).unwrap(),
-                        "None",
+                        tr!(self, "option-none"),
                     );
                     ui.radio_value(
                         &mut self.config.snes.gsu_overclock_factor,
@@ -116,56 +138,56 @@ impl App {
 
             ui.horizontal(|ui| {
                 let dsp1_rom_path = self.config.snes.dsp1_rom_path.as_deref();
-                if ui.button(dsp1_rom_path.unwrap_or("<None>")).clicked() {
+                if ui.button(dsp1_rom_path.unwrap_or(&none_label)).clicked() {
                     pick_coprocessor_rom_path(&mut self.config.snes.dsp1_rom_path);
                 }
 
-                ui.label("DSP-1 ROM path");
+                ui.label(tr!(self, "snes-dsp1-rom-path"));
             });
 
             ui.horizontal(|ui| {
                 let dsp2_rom_path = self.config.snes.dsp2_rom_path.as_deref();
-                if ui.button(dsp2_rom_path.unwrap_or("<None>")).clicked() {
+                if ui.button(dsp2_rom_path.unwrap_or(&none_label)).clicked() {
                     pick_coprocessor_rom_path(&mut self.config.snes.dsp2_rom_path);
                 }
 
-                ui.label("DSP-2 ROM path");
+                ui.label(tr!(self, "snes-dsp2-rom-path"));
             });
 
             ui.horizontal(|ui| {
                 let dsp3_rom_path = self.config.snes.dsp3_rom_path.as_deref();
-                if ui.button(dsp3_rom_path.unwrap_or("<None>")).clicked() {
+                if ui.button(dsp3_rom_path.unwrap_or(&none_label)).clicked() {
                     pick_coprocessor_rom_path(&mut self.config.snes.dsp3_rom_path);
                 }
 
-                ui.label("DSP-3 ROM path");
+                ui.label(tr!(self, "snes-dsp3-rom-path"));
             });
 
             ui.horizontal(|ui| {
                 let dsp4_rom_path = self.config.snes.dsp4_rom_path.as_deref();
-                if ui.button(dsp4_rom_path.unwrap_or("<None>")).clicked() {
+                if ui.button(dsp4_rom_path.unwrap_or(&none_label)).clicked() {
                     pick_coprocessor_rom_path(&mut self.config.snes.dsp4_rom_path);
                 }
 
-                ui.label("DSP-4 ROM path");
+                ui.label(tr!(self, "snes-dsp4-rom-path"));
             });
 
             ui.horizontal(|ui| {
                 let st010_rom_path = self.config.snes.st010_rom_path.as_deref();
-                if ui.button(st010_rom_path.unwrap_or("<None>")).clicked() {
+                if ui.button(st010_rom_path.unwrap_or(&none_label)).clicked() {
                     pick_coprocessor_rom_path(&mut self.config.snes.st010_rom_path);
                 }
 
-                ui.label("ST010 ROM path");
+                ui.label(tr!(self, "snes-st010-rom-path"));
             });
 
             ui.horizontal(|ui| {
                 let st011_rom_path = self.config.snes.st011_rom_path.as_deref();
-                if ui.button(st011_rom_path.unwrap_or("<None>")).clicked() {
+                if ui.button(st011_rom_path.unwrap_or(&none_label)).clicked() {
                     pick_coprocessor_rom_path(&mut self.config.snes.st011_rom_path);
                 }
 
-                ui.label("ST011 ROM path");
+                ui.label(tr!(self, "snes-st011-rom-path"));
             });
         });
         if !open {
@@ -175,33 +197,72 @@ impl App {
 
     pub(super) fn render_snes_video_settings(&mut self, ctx: &Context) {
         let mut open = true;
-        Window::new("SNES Video Settings").open(&mut open).resizable(false).show(ctx, |ui| {
+        let title = tr!(self, "snes-video-window-title");
+        Window::new(title).open(&mut open).resizable(false).show(ctx, |ui| {
             ui.group(|ui| {
-                ui.label("Aspect ratio");
+                ui.label(tr!(self, "aspect-ratio"));
 
                 ui.horizontal(|ui| {
                     ui.radio_value(
                         &mut self.config.snes.aspect_ratio,
                         SnesAspectRatio::Ntsc,
-                        "NTSC",
+                        tr!(self, "aspect-ratio-ntsc"),
+                    )
+                    .on_hover_text(tr!(self, "aspect-ratio-8-7-tooltip"));
+                    ui.radio_value(
+                        &mut self.config.snes.aspect_ratio,
+                        SnesAspectRatio::Pal,
+                        tr!(self, "aspect-ratio-pal"),
                     )
-                    .on_hover_text("8:7 pixel aspect ratio");
-                    ui.radio_value(&mut self.config.snes.aspect_ratio, SnesAspectRatio::Pal, "PAL")
-                        .on_hover_text("11:8 pixel aspect ratio");
+                    .on_hover_text(tr!(self, "aspect-ratio-11-8-tooltip"));
                     ui.radio_value(
                         &mut self.config.snes.aspect_ratio,
                         SnesAspectRatio::SquarePixels,
-                        "Square pixels",
+                        tr!(self, "aspect-ratio-square-pixels"),
                     )
-                    .on_hover_text("1:1 pixel aspect ratio");
+                    .on_hover_text(tr!(self, "aspect-ratio-square-pixels-tooltip"));
                     ui.radio_value(
                         &mut self.config.snes.aspect_ratio,
                         SnesAspectRatio::Stretched,
-                        "Stretched",
+                        tr!(self, "aspect-ratio-stretched"),
                     )
-                    .on_hover_text("Stretched to fill the window");
+                    .on_hover_text(tr!(self, "aspect-ratio-stretched-window-tooltip"));
                 });
             });
+
+            ui.add_space(5.0);
+            ui.group(|ui| {
+                ui.label(tr!(self, "snes-hd-mode7"));
+
+                ui.horizontal(|ui| {
+                    ui.radio_value(
+                        &mut self.config.snes.mode_7_scale,
+                        Mode7Scale::Native,
+                        tr!(self, "snes-mode7-native"),
+                    );
+                    ui.radio_value(&mut self.config.snes.mode_7_scale, Mode7Scale::X2, "2x");
+                    ui.radio_value(&mut self.config.snes.mode_7_scale, Mode7Scale::X4, "4x");
+                });
+
+                ui.add_enabled_ui(self.config.snes.mode_7_scale != Mode7Scale::Native, |ui| {
+                    ui.checkbox(
+                        &mut self.config.snes.mode_7_perspective_correction,
+                        tr!(self, "snes-mode7-perspective-correction"),
+                    )
+                    .on_hover_text(tr!(self, "snes-mode7-perspective-correction-tooltip"));
+                });
+            });
+
+            ui.add_space(5.0);
+            ui.add_enabled_ui(self.config.snes.mode_7_scale == Mode7Scale::Native, |ui| {
+                render_deinterlace_settings(
+                    ui,
+                    &self.state.localizer,
+                    &mut self.config.snes.deinterlace_mode,
+                );
+            })
+            .response
+            .on_disabled_hover_text(tr!(self, "snes-deinterlace-unavailable"));
         });
         if !open {
             self.state.open_windows.remove(&OpenWindow::SnesVideo);
@@ -210,9 +271,16 @@ impl App {
 
     pub(super) fn render_snes_audio_settings(&mut self, ctx: &Context) {
         let mut open = true;
-        Window::new("SNES Audio Settings").open(&mut open).resizable(false).show(ctx, |ui| {
-            ui.checkbox(&mut self.config.snes.audio_60hz_hack, "Enable audio 60Hz/50Hz hack")
-                .on_hover_text("Enabling this option will very slightly increase the audio signal frequency to time to 60Hz NTSC / 50Hz PAL");
+        let title = tr!(self, "snes-audio-window-title");
+        Window::new(title).open(&mut open).resizable(false).show(ctx, |ui| {
+            ui.checkbox(&mut self.config.snes.audio_60hz_hack, tr!(self, "audio-60hz-hack"))
+                .on_hover_text(tr!(self, "audio-60hz-hack-tooltip"));
+
+            render_audio_post_processing_settings(
+                ui,
+                &self.state.localizer,
+                &mut self.config.snes.audio_post_processing,
+            );
         });
         if !open {
             self.state.open_windows.remove(&OpenWindow::SnesAudio);
@@ -0,0 +1,328 @@
+//! Box art, title screen, and screenshot thumbnails for the ROM library, fetched from
+//! libretro-thumbnails or any server with the same directory layout
+//!
+//! Thumbnails are looked up by console and by the ROM's file name, which matches libretro's naming
+//! for ROM sets named after No-Intro/Redump DATs. Lookups run on a background thread and results
+//! are cached on disk under a hash of the thumbnail URL. Thumbnails that the server does not have
+//! are cached as empty files so that they are not requested again; delete the cache directory to
+//! retry them.
+
+use crate::app::romlist::{Console, RomMetadata};
+use crc::Crc;
+use egui::{ColorImage, Context, TextureHandle, TextureOptions};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::io::{ErrorKind, Read};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::sync::mpsc::{Receiver, Sender};
+use std::time::Duration;
+use std::{fs, io, thread};
+
+const CRC: Crc<u32> = Crc::<u32>::new(&crc::CRC_32_ISO_HDLC);
+
+pub const CACHE_DIR_NAME: &str = "thumbnails";
+
+const DEFAULT_SOURCE_URL: &str = "https://thumbnails.libretro.com";
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Larger responses are assumed to not be thumbnails
+const MAX_DOWNLOAD_LEN: u64 = 16 * 1024 * 1024;
+
+// Thumbnails are downscaled to fit in this size to limit texture memory usage
+const MAX_THUMBNAIL_SIZE: u32 = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ThumbnailKind {
+    #[default]
+    BoxArt,
+    TitleScreen,
+    Screenshot,
+}
+
+impl ThumbnailKind {
+    pub const ALL: [Self; 3] = [Self::BoxArt, Self::TitleScreen, Self::Screenshot];
+
+    fn directory(self) -> &'static str {
+        match self {
+            Self::BoxArt => "Named_Boxarts",
+            Self::TitleScreen => "Named_Titles",
+            Self::Screenshot => "Named_Snaps",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThumbnailConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub kind: ThumbnailKind,
+    /// Base URL of a server with the same layout as libretro-thumbnails
+    #[serde(default = "default_source_url")]
+    pub source_url: String,
+    /// Only show thumbnails that were previously downloaded; never make network requests
+    #[serde(default)]
+    pub offline_only: bool,
+}
+
+fn default_source_url() -> String {
+    DEFAULT_SOURCE_URL.into()
+}
+
+impl Default for ThumbnailConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            kind: ThumbnailKind::default(),
+            source_url: default_source_url(),
+            offline_only: false,
+        }
+    }
+}
+
+fn libretro_system_name(console: Console) -> &'static str {
+    match console {
+        Console::MasterSystem => "Sega - Master System - Mark III",
+        Console::GameGear => "Sega - Game Gear",
+        Console::Genesis => "Sega - Mega Drive - Genesis",
+        Console::SegaCd => "Sega - Mega-CD - Sega CD",
+        Console::Nes => "Nintendo - Nintendo Entertainment System",
+        Console::Snes => "Nintendo - Super Nintendo Entertainment System",
+        Console::GameBoy => "Nintendo - Game Boy",
+        Console::GameBoyColor => "Nintendo - Game Boy Color",
+        Console::PcEngine => "NEC - PC Engine - TurboGrafx 16",
+    }
+}
+
+fn uri_encode(s: &str) -> String {
+    let mut encoded = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char);
+            }
+            _ => write!(encoded, "%{byte:02X}").unwrap(),
+        }
+    }
+    encoded
+}
+
+fn thumbnail_url(config: &ThumbnailConfig, console: Console, game_name: &str) -> String {
+    // libretro-thumbnails replaces characters that are not allowed in file names on some platforms
+    let file_name: String =
+        game_name.chars().map(|c| if "&*/:`<>?\\|\"".contains(c) { '_' } else { c }).collect();
+
+    format!(
+        "{}/{}/{}/{}.png",
+        config.source_url.trim_end_matches('/'),
+        uri_encode(libretro_system_name(console)),
+        config.kind.directory(),
+        uri_encode(&file_name)
+    )
+}
+
+struct ThumbnailRequest {
+    url: String,
+    cache_path: PathBuf,
+    offline_only: bool,
+}
+
+struct ThumbnailResponse {
+    url: String,
+    image: Option<ColorImage>,
+}
+
+enum Thumbnail {
+    Pending,
+    Loaded(TextureHandle),
+    Missing,
+}
+
+struct Worker {
+    request_sender: Sender<ThumbnailRequest>,
+    response_receiver: Receiver<ThumbnailResponse>,
+}
+
+pub struct Thumbnails {
+    cache_dir: PathBuf,
+    // Keyed by URL so that changing the source or kind triggers new lookups
+    thumbnails: HashMap<String, Thumbnail>,
+    // Started on the first lookup
+    worker: Option<Worker>,
+}
+
+impl Thumbnails {
+    pub fn new(cache_dir: PathBuf) -> Self {
+        Self { cache_dir, thumbnails: HashMap::new(), worker: None }
+    }
+
+    /// Upload thumbnails that finished loading since the last call. Should be called once per frame
+    /// before calling [`get`](Self::get).
+    pub fn update(&mut self, ctx: &Context) {
+        let Some(worker) = &self.worker else { return };
+
+        while let Ok(ThumbnailResponse { url, image }) = worker.response_receiver.try_recv() {
+            let thumbnail = match image {
+                Some(image) => {
+                    Thumbnail::Loaded(ctx.load_texture(&url, image, TextureOptions::LINEAR))
+                }
+                None => Thumbnail::Missing,
+            };
+            self.thumbnails.insert(url, thumbnail);
+        }
+    }
+
+    /// Returns the thumbnail for the given ROM if it has been loaded, and starts loading it if this
+    /// is the first time it was requested.
+    pub fn get(
+        &mut self,
+        ctx: &Context,
+        config: &ThumbnailConfig,
+        metadata: &RomMetadata,
+    ) -> Option<&TextureHandle> {
+        let url = thumbnail_url(config, metadata.console, &metadata.file_name_no_ext);
+
+        if !self.thumbnails.contains_key(&url) {
+            let cache_path =
+                self.cache_dir.join(format!("{:08X}.png", CRC.checksum(url.as_bytes())));
+            let request = ThumbnailRequest {
+                url: url.clone(),
+                cache_path,
+                offline_only: config.offline_only,
+            };
+
+            let worker = self.worker.get_or_insert_with(|| spawn_worker(ctx.clone()));
+            if worker.request_sender.send(request).is_err() {
+                log::error!("Thumbnail worker thread terminated unexpectedly");
+                self.thumbnails.insert(url, Thumbnail::Missing);
+                return None;
+            }
+
+            self.thumbnails.insert(url.clone(), Thumbnail::Pending);
+        }
+
+        match self.thumbnails.get(&url) {
+            Some(Thumbnail::Loaded(texture)) => Some(texture),
+            Some(Thumbnail::Pending | Thumbnail::Missing) | None => None,
+        }
+    }
+}
+
+fn spawn_worker(ctx: Context) -> Worker {
+    let (request_sender, request_receiver) = mpsc::channel();
+    let (response_sender, response_receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        let agent = ureq::AgentBuilder::new().timeout(REQUEST_TIMEOUT).build();
+
+        // Exits when the sender is dropped
+        for request in request_receiver {
+            let image = load_thumbnail(&agent, &request);
+            if response_sender.send(ThumbnailResponse { url: request.url, image }).is_err() {
+                return;
+            }
+            ctx.request_repaint();
+        }
+    });
+
+    Worker { request_sender, response_receiver }
+}
+
+fn load_thumbnail(agent: &ureq::Agent, request: &ThumbnailRequest) -> Option<ColorImage> {
+    let bytes = match fs::read(&request.cache_path) {
+        Ok(bytes) => bytes,
+        Err(err) if err.kind() == ErrorKind::NotFound => {
+            if request.offline_only {
+                return None;
+            }
+
+            let bytes = match download(agent, &request.url) {
+                Ok(bytes) => bytes,
+                Err(err) => {
+                    // Not cached so that the thumbnail is retried next time
+                    log::error!("Error downloading thumbnail from '{}': {err}", request.url);
+                    return None;
+                }
+            };
+
+            write_cache(&request.cache_path, &bytes);
+            bytes
+        }
+        Err(err) => {
+            log::error!("Error reading cached thumbnail '{}': {err}", request.cache_path.display());
+            return None;
+        }
+    };
+
+    if bytes.is_empty() {
+        // Server does not have this thumbnail
+        return None;
+    }
+
+    let image = match image::load_from_memory(&bytes) {
+        Ok(image) => image.thumbnail(MAX_THUMBNAIL_SIZE, MAX_THUMBNAIL_SIZE).into_rgba8(),
+        Err(err) => {
+            log::error!("Error decoding thumbnail from '{}': {err}", request.url);
+            return None;
+        }
+    };
+
+    let size = [image.width() as usize, image.height() as usize];
+    Some(ColorImage::from_rgba_unmultiplied(size, image.as_raw()))
+}
+
+// Returns an empty Vec if the server does not have the thumbnail
+fn download(agent: &ureq::Agent, url: &str) -> io::Result<Vec<u8>> {
+    let response = match agent.get(url).call() {
+        Ok(response) => response,
+        Err(ureq::Error::Status(404, _)) => return Ok(vec![]),
+        Err(err) => return Err(io::Error::other(err)),
+    };
+
+    let mut bytes = Vec::new();
+    response.into_reader().take(MAX_DOWNLOAD_LEN).read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn write_cache(path: &Path, bytes: &[u8]) {
+    if let Some(parent) = path.parent() {
+        if let Err(err) = fs::create_dir_all(parent) {
+            log::error!("Error creating thumbnail cache directory '{}': {err}", parent.display());
+            return;
+        }
+    }
+
+    if let Err(err) = fs::write(path, bytes) {
+        log::error!("Error writing thumbnail cache file '{}': {err}", path.display());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn libretro_thumbnail_url() {
+        let config = ThumbnailConfig::default();
+        assert_eq!(
+            thumbnail_url(&config, Console::Genesis, "Sonic & Knuckles (World)"),
+            "https://thumbnails.libretro.com/Sega%20-%20Mega%20Drive%20-%20Genesis/Named_Boxarts/Sonic%20_%20Knuckles%20%28World%29.png"
+        );
+    }
+
+    #[test]
+    fn custom_source_trailing_slash() {
+        let config = ThumbnailConfig {
+            kind: ThumbnailKind::Screenshot,
+            source_url: "http://localhost:8080/thumbs/".into(),
+            ..ThumbnailConfig::default()
+        };
+        assert_eq!(
+            thumbnail_url(&config, Console::GameBoy, "Tetris"),
+            "http://localhost:8080/thumbs/Nintendo%20-%20Game%20Boy/Named_Snaps/Tetris.png"
+        );
+    }
+}
@@ -1,15 +1,19 @@
+mod navigation;
+
+use crate::emuthread::navigation::{GamepadNavigator, NavigationMapper};
 use anyhow::anyhow;
+use jgenesis_common::command::EmulatorCommand;
 use jgenesis_native_driver::config::input::{
     AxisDirection, HatDirection, JoystickAction, JoystickInput, KeyboardInput, KeyboardOrMouseInput,
 };
 use jgenesis_native_driver::config::{
-    GameBoyConfig, GenesisConfig, NesConfig, SegaCdConfig, SmsGgConfig, SnesConfig,
+    GameBoyConfig, GenesisConfig, NesConfig, PceConfig, SegaCdConfig, SmsGgConfig, SnesConfig,
 };
 use jgenesis_native_driver::input::Joysticks;
 use jgenesis_native_driver::{
     AudioError, NativeEmulatorResult, NativeGameBoyEmulator, NativeGenesisEmulator,
-    NativeNesEmulator, NativeSegaCdEmulator, NativeSmsGgEmulator, NativeSnesEmulator,
-    NativeTickEffect,
+    NativeNesEmulator, NativePceEmulator, NativeSegaCdEmulator, NativeSmsGgEmulator,
+    NativeSnesEmulator, NativeTickEffect,
 };
 use sdl2::event::Event;
 use sdl2::joystick::HatState;
@@ -20,10 +24,14 @@ use sdl2::{EventPump, JoystickSubsystem};
 use segacd_core::api::SegaCdLoadResult;
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicU8, Ordering};
-use std::sync::mpsc::{Receiver, Sender, TryRecvError};
+use std::sync::mpsc::{Receiver, RecvError, RecvTimeoutError, Sender, TryRecvError};
 use std::sync::{mpsc, Arc, Mutex, MutexGuard};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+
+pub use navigation::NavigationInput;
+
+const NAVIGATION_POLL_INTERVAL: Duration = Duration::from_millis(10);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum EmuThreadStatus {
@@ -34,6 +42,7 @@ pub enum EmuThreadStatus {
     RunningNes = 4,
     RunningSnes = 5,
     RunningGameBoy = 6,
+    RunningPce = 7,
 }
 
 impl EmuThreadStatus {
@@ -46,6 +55,7 @@ impl EmuThreadStatus {
             4 => Self::RunningNes,
             5 => Self::RunningSnes,
             6 => Self::RunningGameBoy,
+            7 => Self::RunningPce,
             _ => panic!("invalid status discriminant: {discriminant}"),
         }
     }
@@ -59,6 +69,7 @@ impl EmuThreadStatus {
                 | Self::RunningNes
                 | Self::RunningSnes
                 | Self::RunningGameBoy
+                | Self::RunningPce
         )
     }
 }
@@ -71,19 +82,61 @@ pub enum EmuThreadCommand {
     RunNes(Box<NesConfig>),
     RunSnes(Box<SnesConfig>),
     RunGameBoy(Box<GameBoyConfig>),
+    RunPce(Box<PceConfig>),
     ReloadSmsGgConfig(Box<SmsGgConfig>),
     ReloadGenesisConfig(Box<GenesisConfig>),
     ReloadSegaCdConfig(Box<SegaCdConfig>),
     ReloadNesConfig(Box<NesConfig>),
     ReloadSnesConfig(Box<SnesConfig>),
     ReloadGameBoyConfig(Box<GameBoyConfig>),
+    ReloadPceConfig(Box<PceConfig>),
     StopEmulator,
     CollectInput { input_type: InputType, axis_deadzone: i16, ctx: egui::Context },
-    SoftReset,
-    HardReset,
+    Emulator(EmulatorCommand),
     OpenMemoryViewer,
-    SegaCdRemoveDisc,
-    SegaCdChangeDisc(PathBuf),
+    UndoLoadState,
+    // Also closes the quick menu and unpauses the emulator if the menu was opened by its hotkey
+    FocusEmulator,
+    NesScanBarcode(String),
+    // Enable or disable polling gamepads for launcher navigation while no emulator is running;
+    // the given context is repainted whenever a navigation input is received
+    SetGamepadNavigation(Option<egui::Context>),
+}
+
+impl EmuThreadCommand {
+    fn initializes_sdl(&self) -> bool {
+        matches!(
+            self,
+            Self::RunSms(_)
+                | Self::RunGenesis(_)
+                | Self::RunSegaCd(_)
+                | Self::RunNes(_)
+                | Self::RunSnes(_)
+                | Self::RunGameBoy(_)
+                | Self::RunPce(_)
+                | Self::CollectInput { .. }
+        )
+    }
+
+    fn rom_file_path(&self) -> Option<&str> {
+        match self {
+            Self::RunSms(config) => Some(&config.common.rom_file_path),
+            Self::RunGenesis(config) => Some(&config.common.rom_file_path),
+            Self::RunSegaCd(config) => Some(&config.genesis.common.rom_file_path),
+            Self::RunNes(config) => Some(&config.common.rom_file_path),
+            Self::RunSnes(config) => Some(&config.common.rom_file_path),
+            Self::RunGameBoy(config) => Some(&config.common.rom_file_path),
+            Self::RunPce(config) => Some(&config.common.rom_file_path),
+            _ => None,
+        }
+    }
+}
+
+/// A completed run of an emulator, from launch until it was stopped or closed.
+#[derive(Debug, Clone)]
+pub struct PlaySession {
+    pub rom_path: String,
+    pub duration: Duration,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -110,6 +163,10 @@ pub struct EmuThreadHandle {
     status: Arc<AtomicU8>,
     command_sender: Sender<EmuThreadCommand>,
     input_receiver: Receiver<Option<GenericInput>>,
+    navigation_receiver: Receiver<NavigationInput>,
+    quick_menu_receiver: Receiver<()>,
+    recorded_macro_receiver: Receiver<String>,
+    play_session_receiver: Receiver<PlaySession>,
     emulator_error: Arc<Mutex<Option<anyhow::Error>>>,
 }
 
@@ -130,6 +187,23 @@ impl EmuThreadHandle {
         self.input_receiver.try_recv()
     }
 
+    pub fn poll_navigation_input(&self) -> Option<NavigationInput> {
+        self.navigation_receiver.try_recv().ok()
+    }
+
+    /// Whether the open quick menu hotkey was pressed in the emulator window since the last poll.
+    pub fn poll_quick_menu_request(&self) -> bool {
+        self.quick_menu_receiver.try_iter().count() != 0
+    }
+
+    pub fn poll_recorded_macro(&self) -> Option<String> {
+        self.recorded_macro_receiver.try_recv().ok()
+    }
+
+    pub fn poll_play_session(&self) -> Option<PlaySession> {
+        self.play_session_receiver.try_recv().ok()
+    }
+
     pub fn stop_emulator_if_running(&self) {
         if self.status().is_running() {
             self.send(EmuThreadCommand::StopEmulator);
@@ -144,6 +218,7 @@ impl EmuThreadHandle {
         nes_config: Box<NesConfig>,
         snes_config: Box<SnesConfig>,
         gb_config: Box<GameBoyConfig>,
+        pce_config: Box<PceConfig>,
     ) {
         match self.status() {
             EmuThreadStatus::RunningSmsGg => {
@@ -164,24 +239,73 @@ impl EmuThreadHandle {
             EmuThreadStatus::RunningGameBoy => {
                 self.send(EmuThreadCommand::ReloadGameBoyConfig(gb_config));
             }
+            EmuThreadStatus::RunningPce => {
+                self.send(EmuThreadCommand::ReloadPceConfig(pce_config));
+            }
             EmuThreadStatus::Idle => {}
         }
     }
 }
 
-pub fn spawn() -> EmuThreadHandle {
+/// Lets a running emulator open the GUI's quick menu, and sends gamepad inputs to the menu while
+/// it is open.
+struct QuickMenuLink {
+    ctx: egui::Context,
+    request_sender: Sender<()>,
+    navigation_sender: Sender<NavigationInput>,
+}
+
+pub fn spawn(ctx: egui::Context) -> EmuThreadHandle {
     let status_arc = Arc::new(AtomicU8::new(EmuThreadStatus::Idle as u8));
     let (command_sender, command_receiver) = mpsc::channel();
     let (input_sender, input_receiver) = mpsc::channel();
+    let (navigation_sender, navigation_receiver) = mpsc::channel();
+    let (quick_menu_sender, quick_menu_receiver) = mpsc::channel();
+    let (recorded_macro_sender, recorded_macro_receiver) = mpsc::channel();
+    let (play_session_sender, play_session_receiver) = mpsc::channel();
     let emulator_error_arc = Arc::new(Mutex::new(None));
 
     let status = Arc::clone(&status_arc);
     let emulator_error = Arc::clone(&emulator_error_arc);
     thread::spawn(move || {
+        let quick_menu =
+            QuickMenuLink { ctx, request_sender: quick_menu_sender, navigation_sender };
+        let mut navigation_ctx: Option<egui::Context> = None;
+        let mut navigator: Option<GamepadNavigator> = None;
+
         loop {
             status.store(EmuThreadStatus::Idle as u8, Ordering::Relaxed);
 
-            match command_receiver.recv() {
+            if navigator.is_none() {
+                if let Some(ctx) = &navigation_ctx {
+                    navigator = GamepadNavigator::new(ctx.clone())
+                        .map_err(|err| log::error!("Error initializing gamepad navigation: {err}"))
+                        .ok();
+                }
+            }
+
+            let command = match &mut navigator {
+                Some(navigator) => {
+                    navigator.poll(&quick_menu.navigation_sender);
+                    match command_receiver.recv_timeout(NAVIGATION_POLL_INTERVAL) {
+                        Ok(command) => Ok(command),
+                        Err(RecvTimeoutError::Timeout) => continue,
+                        Err(RecvTimeoutError::Disconnected) => Err(RecvError),
+                    }
+                }
+                None => command_receiver.recv(),
+            };
+
+            if command.as_ref().is_ok_and(EmuThreadCommand::initializes_sdl) {
+                // SDL can only be initialized once at a time
+                navigator = None;
+            }
+
+            let session_rom_path =
+                command.as_ref().ok().and_then(EmuThreadCommand::rom_file_path).map(String::from);
+            let session_start = Instant::now();
+
+            match command {
                 Ok(EmuThreadCommand::RunSms(config)) => {
                     status.store(EmuThreadStatus::RunningSmsGg as u8, Ordering::Relaxed);
 
@@ -197,7 +321,10 @@ pub fn spawn() -> EmuThreadHandle {
                         GenericEmulator::SmsGg(emulator),
                         &command_receiver,
                         &input_sender,
+                        &recorded_macro_sender,
                         &emulator_error,
+                        &quick_menu,
+                        &mut navigation_ctx,
                     );
                 }
                 Ok(EmuThreadCommand::RunGenesis(config)) => {
@@ -215,7 +342,10 @@ pub fn spawn() -> EmuThreadHandle {
                         GenericEmulator::Genesis(emulator),
                         &command_receiver,
                         &input_sender,
+                        &recorded_macro_sender,
                         &emulator_error,
+                        &quick_menu,
+                        &mut navigation_ctx,
                     );
                 }
                 Ok(EmuThreadCommand::RunSegaCd(config)) => {
@@ -233,7 +363,10 @@ pub fn spawn() -> EmuThreadHandle {
                         GenericEmulator::SegaCd(emulator),
                         &command_receiver,
                         &input_sender,
+                        &recorded_macro_sender,
                         &emulator_error,
+                        &quick_menu,
+                        &mut navigation_ctx,
                     );
                 }
                 Ok(EmuThreadCommand::RunNes(config)) => {
@@ -251,7 +384,10 @@ pub fn spawn() -> EmuThreadHandle {
                         GenericEmulator::Nes(emulator),
                         &command_receiver,
                         &input_sender,
+                        &recorded_macro_sender,
                         &emulator_error,
+                        &quick_menu,
+                        &mut navigation_ctx,
                     );
                 }
                 Ok(EmuThreadCommand::RunSnes(config)) => {
@@ -269,7 +405,10 @@ pub fn spawn() -> EmuThreadHandle {
                         GenericEmulator::Snes(emulator),
                         &command_receiver,
                         &input_sender,
+                        &recorded_macro_sender,
                         &emulator_error,
+                        &quick_menu,
+                        &mut navigation_ctx,
                     );
                 }
                 Ok(EmuThreadCommand::RunGameBoy(config)) => {
@@ -287,7 +426,31 @@ pub fn spawn() -> EmuThreadHandle {
                         GenericEmulator::GameBoy(emulator),
                         &command_receiver,
                         &input_sender,
+                        &recorded_macro_sender,
+                        &emulator_error,
+                        &quick_menu,
+                        &mut navigation_ctx,
+                    );
+                }
+                Ok(EmuThreadCommand::RunPce(config)) => {
+                    status.store(EmuThreadStatus::RunningPce as u8, Ordering::Relaxed);
+
+                    let emulator = match jgenesis_native_driver::create_pce(config) {
+                        Ok(emulator) => emulator,
+                        Err(err) => {
+                            log::error!("Error initializing PC Engine emulator: {err}");
+                            *emulator_error.lock().unwrap() = Some(err.into());
+                            continue;
+                        }
+                    };
+                    run_emulator(
+                        GenericEmulator::Pce(emulator),
+                        &command_receiver,
+                        &input_sender,
+                        &recorded_macro_sender,
                         &emulator_error,
+                        &quick_menu,
+                        &mut navigation_ctx,
                     );
                 }
                 Ok(EmuThreadCommand::CollectInput { input_type, axis_deadzone, ctx }) => {
@@ -301,6 +464,12 @@ pub fn spawn() -> EmuThreadHandle {
                         }
                     }
                 }
+                Ok(EmuThreadCommand::SetGamepadNavigation(ctx)) => {
+                    if ctx.is_none() {
+                        navigator = None;
+                    }
+                    navigation_ctx = ctx;
+                }
                 Ok(
                     EmuThreadCommand::StopEmulator
                     | EmuThreadCommand::ReloadSmsGgConfig(_)
@@ -309,11 +478,12 @@ pub fn spawn() -> EmuThreadHandle {
                     | EmuThreadCommand::ReloadNesConfig(_)
                     | EmuThreadCommand::ReloadSnesConfig(_)
                     | EmuThreadCommand::ReloadGameBoyConfig(_)
-                    | EmuThreadCommand::SoftReset
-                    | EmuThreadCommand::HardReset
+                    | EmuThreadCommand::ReloadPceConfig(_)
+                    | EmuThreadCommand::Emulator(_)
                     | EmuThreadCommand::OpenMemoryViewer
-                    | EmuThreadCommand::SegaCdRemoveDisc
-                    | EmuThreadCommand::SegaCdChangeDisc(_),
+                    | EmuThreadCommand::UndoLoadState
+                    | EmuThreadCommand::FocusEmulator
+                    | EmuThreadCommand::NesScanBarcode(_),
                 ) => {}
                 Err(err) => {
                     log::info!(
@@ -322,6 +492,14 @@ pub fn spawn() -> EmuThreadHandle {
                     break;
                 }
             }
+
+            // Emulators that fail to initialize skip this with `continue`
+            if let Some(rom_path) = session_rom_path {
+                let session = PlaySession { rom_path, duration: session_start.elapsed() };
+                if play_session_sender.send(session).is_err() {
+                    break;
+                }
+            }
         }
     });
 
@@ -329,6 +507,10 @@ pub fn spawn() -> EmuThreadHandle {
         command_sender,
         status: status_arc,
         input_receiver,
+        navigation_receiver,
+        quick_menu_receiver,
+        recorded_macro_receiver,
+        play_session_receiver,
         emulator_error: emulator_error_arc,
     }
 }
@@ -340,6 +522,7 @@ enum GenericEmulator {
     Nes(NativeNesEmulator),
     Snes(NativeSnesEmulator),
     GameBoy(NativeGameBoyEmulator),
+    Pce(NativePceEmulator),
 }
 
 macro_rules! match_each_emulator_variant {
@@ -351,6 +534,7 @@ macro_rules! match_each_emulator_variant {
             GenericEmulator::Nes($emulator) => $expr,
             GenericEmulator::Snes($emulator) => $expr,
             GenericEmulator::GameBoy($emulator) => $expr,
+            GenericEmulator::Pce($emulator) => $expr,
         }
     };
 }
@@ -404,6 +588,14 @@ impl GenericEmulator {
         Ok(())
     }
 
+    fn reload_pce_config(&mut self, config: Box<PceConfig>) -> Result<(), AudioError> {
+        if let Self::Pce(emulator) = self {
+            emulator.reload_pce_config(config)?;
+        }
+
+        Ok(())
+    }
+
     fn remove_disc(&mut self) {
         if let Self::SegaCd(emulator) = self {
             emulator.remove_disc();
@@ -418,16 +610,18 @@ impl GenericEmulator {
         Ok(())
     }
 
-    fn render_frame(&mut self) -> NativeEmulatorResult<NativeTickEffect> {
-        match_each_emulator_variant!(self, emulator => emulator.render_frame())
+    fn scan_barcode(&mut self, barcode: &str) {
+        if let Self::Nes(emulator) = self {
+            emulator.scan_barcode(barcode);
+        }
     }
 
-    fn soft_reset(&mut self) {
-        match_each_emulator_variant!(self, emulator => emulator.soft_reset());
+    fn render_frame(&mut self) -> NativeEmulatorResult<NativeTickEffect> {
+        match_each_emulator_variant!(self, emulator => emulator.render_frame())
     }
 
-    fn hard_reset(&mut self) {
-        match_each_emulator_variant!(self, emulator => emulator.hard_reset());
+    fn handle_command(&mut self, command: EmulatorCommand) -> NativeEmulatorResult<()> {
+        match_each_emulator_variant!(self, emulator => emulator.handle_command(command))
     }
 
     fn open_memory_viewer(&mut self) {
@@ -438,6 +632,26 @@ impl GenericEmulator {
         match_each_emulator_variant!(self, emulator => emulator.focus());
     }
 
+    fn set_paused(&mut self, paused: bool) {
+        match_each_emulator_variant!(self, emulator => emulator.set_paused(paused));
+    }
+
+    fn undo_load_state(&mut self) {
+        match_each_emulator_variant!(self, emulator => emulator.undo_load_state());
+    }
+
+    fn persist_save(&mut self) {
+        match_each_emulator_variant!(self, emulator => emulator.persist_save());
+    }
+
+    fn write_auto_save_state(&mut self) {
+        match_each_emulator_variant!(self, emulator => emulator.write_auto_save_state());
+    }
+
+    fn take_recorded_input_macros(&mut self) -> Vec<String> {
+        match_each_emulator_variant!(self, emulator => emulator.take_recorded_input_macros())
+    }
+
     fn event_pump_and_joysticks_mut(
         &mut self,
     ) -> (&mut EventPump, &mut Joysticks, &JoystickSubsystem) {
@@ -449,11 +663,36 @@ fn run_emulator(
     mut emulator: GenericEmulator,
     command_receiver: &Receiver<EmuThreadCommand>,
     input_sender: &Sender<Option<GenericInput>>,
+    recorded_macro_sender: &Sender<String>,
     emulator_error: &Arc<Mutex<Option<anyhow::Error>>>,
+    quick_menu: &QuickMenuLink,
+    navigation_ctx: &mut Option<egui::Context>,
 ) {
+    // Set while the quick menu is open after its hotkey was pressed. The emulator stays paused, and
+    // gamepad inputs navigate the menu instead of reaching the emulator
+    let mut quick_menu_mapper: Option<NavigationMapper> = None;
+
     loop {
-        match emulator.render_frame() {
+        let tick_result = match &mut quick_menu_mapper {
+            Some(mapper) => {
+                if !poll_quick_menu_navigation(&mut emulator, mapper, quick_menu) {
+                    // Emulator window was closed
+                    emulator.persist_save();
+                    emulator.write_auto_save_state();
+                    return;
+                }
+                thread::sleep(NAVIGATION_POLL_INTERVAL);
+                Ok(NativeTickEffect::None)
+            }
+            None => emulator.render_frame(),
+        };
+
+        match tick_result {
             Ok(NativeTickEffect::None) => {
+                for sequence in emulator.take_recorded_input_macros() {
+                    recorded_macro_sender.send(sequence).unwrap();
+                }
+
                 while let Ok(command) = command_receiver.try_recv() {
                     match command {
                         EmuThreadCommand::ReloadSmsGgConfig(config) => {
@@ -492,8 +731,16 @@ fn run_emulator(
                                 return;
                             }
                         }
+                        EmuThreadCommand::ReloadPceConfig(config) => {
+                            if let Err(err) = emulator.reload_pce_config(config) {
+                                *emulator_error.lock().unwrap() = Some(err.into());
+                                return;
+                            }
+                        }
                         EmuThreadCommand::StopEmulator => {
                             log::info!("Stopping emulator");
+                            emulator.persist_save();
+                            emulator.write_auto_save_state();
                             return;
                         }
                         EmuThreadCommand::CollectInput { input_type, axis_deadzone, ctx } => {
@@ -519,37 +766,63 @@ fn run_emulator(
 
                             if is_none {
                                 // Window was closed
+                                emulator.persist_save();
+                                emulator.write_auto_save_state();
                                 return;
                             }
                         }
-                        EmuThreadCommand::SoftReset => {
-                            emulator.soft_reset();
+                        EmuThreadCommand::Emulator(EmulatorCommand::SwapDisc(None)) => {
+                            emulator.remove_disc();
+                        }
+                        EmuThreadCommand::Emulator(EmulatorCommand::SwapDisc(Some(path))) => {
+                            if let Err(err) = emulator.change_disc(path.into()) {
+                                *emulator_error.lock().unwrap() = Some(err.into());
+                                return;
+                            }
                         }
-                        EmuThreadCommand::HardReset => {
-                            emulator.hard_reset();
+                        EmuThreadCommand::Emulator(command) => {
+                            if let Err(err) = emulator.handle_command(command) {
+                                log::error!("Error handling emulator command: {err}");
+                            }
                         }
                         EmuThreadCommand::OpenMemoryViewer => {
                             emulator.open_memory_viewer();
                         }
-                        EmuThreadCommand::SegaCdRemoveDisc => {
-                            emulator.remove_disc();
+                        EmuThreadCommand::UndoLoadState => {
+                            emulator.undo_load_state();
                         }
-                        EmuThreadCommand::SegaCdChangeDisc(path) => {
-                            if let Err(err) = emulator.change_disc(path) {
-                                *emulator_error.lock().unwrap() = Some(err.into());
-                                return;
+                        EmuThreadCommand::FocusEmulator => {
+                            if quick_menu_mapper.take().is_some() {
+                                emulator.set_paused(false);
                             }
+                            emulator.focus();
+                        }
+                        EmuThreadCommand::NesScanBarcode(barcode) => {
+                            emulator.scan_barcode(&barcode);
                         }
                         EmuThreadCommand::RunSms(_)
                         | EmuThreadCommand::RunGenesis(_)
                         | EmuThreadCommand::RunSegaCd(_)
                         | EmuThreadCommand::RunNes(_)
                         | EmuThreadCommand::RunSnes(_)
-                        | EmuThreadCommand::RunGameBoy(_) => {}
+                        | EmuThreadCommand::RunGameBoy(_)
+                        | EmuThreadCommand::RunPce(_) => {}
+                        EmuThreadCommand::SetGamepadNavigation(ctx) => {
+                            // Takes effect once the emulator stops
+                            *navigation_ctx = ctx;
+                        }
                     }
                 }
             }
-            Ok(NativeTickEffect::Exit) => {
+            Ok(NativeTickEffect::OpenQuickMenu) => {
+                log::debug!("Opening quick menu");
+                quick_menu_mapper = Some(NavigationMapper::default());
+                if quick_menu.request_sender.send(()).is_ok() {
+                    quick_menu.ctx.request_repaint();
+                }
+            }
+            // The GUI never starts the remote control server, which is the only source of LoadRom
+            Ok(NativeTickEffect::Exit | NativeTickEffect::LoadRom(_)) => {
                 return;
             }
             Err(err) => {
@@ -561,6 +834,34 @@ fn run_emulator(
     }
 }
 
+// Returns false if the emulator window was closed
+fn poll_quick_menu_navigation(
+    emulator: &mut GenericEmulator,
+    mapper: &mut NavigationMapper,
+    quick_menu: &QuickMenuLink,
+) -> bool {
+    let (event_pump, joysticks, joystick_subsystem) = emulator.event_pump_and_joysticks_mut();
+
+    let mut sent_any = false;
+    for event in event_pump.poll_iter() {
+        if let Event::Quit { .. } = event {
+            return false;
+        }
+
+        if let Some(input) = mapper.map_event(&event, joysticks, joystick_subsystem) {
+            if quick_menu.navigation_sender.send(input).is_ok() {
+                sent_any = true;
+            }
+        }
+    }
+
+    if sent_any {
+        quick_menu.ctx.request_repaint();
+    }
+
+    true
+}
+
 fn collect_input_not_running(
     input_type: InputType,
     axis_deadzone: i16,
@@ -0,0 +1,149 @@
+use anyhow::anyhow;
+use jgenesis_native_driver::input::Joysticks;
+use sdl2::event::Event;
+use sdl2::joystick::HatState;
+use sdl2::{EventPump, JoystickSubsystem, Sdl};
+use std::collections::HashMap;
+use std::sync::mpsc::Sender;
+
+// Axis values must exceed this magnitude to count as a directional press; this is intentionally
+// much higher than a typical gameplay deadzone so that resting analog sticks never drift the cursor
+const AXIS_THRESHOLD: i16 = 20000;
+
+// Raw SDL joystick button indices; these correspond to the bottom and right face buttons on most
+// XInput-style controllers (e.g. A and B on an Xbox controller)
+const CONFIRM_BUTTON_IDX: u8 = 0;
+const BACK_BUTTON_IDX: u8 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NavigationInput {
+    Up,
+    Down,
+    Left,
+    Right,
+    Confirm,
+    Back,
+}
+
+impl NavigationInput {
+    fn from_hat(state: HatState) -> Option<Self> {
+        match state {
+            HatState::Up => Some(Self::Up),
+            HatState::Down => Some(Self::Down),
+            HatState::Left => Some(Self::Left),
+            HatState::Right => Some(Self::Right),
+            _ => None,
+        }
+    }
+
+    fn from_axis(axis_idx: u8, value: i16) -> Option<Self> {
+        if value.saturating_abs() < AXIS_THRESHOLD {
+            return None;
+        }
+
+        match (axis_idx, value < 0) {
+            (0, true) => Some(Self::Left),
+            (0, false) => Some(Self::Right),
+            (1, true) => Some(Self::Up),
+            (1, false) => Some(Self::Down),
+            _ => None,
+        }
+    }
+}
+
+/// Translates SDL gamepad events into navigation inputs.
+#[derive(Default)]
+pub struct NavigationMapper {
+    // Analog sticks generate a stream of motion events; only send an input when an axis first
+    // crosses the threshold
+    axis_state: HashMap<(u32, u8), Option<NavigationInput>>,
+}
+
+impl NavigationMapper {
+    /// Handle a single event, opening and closing joysticks as they are connected and disconnected.
+    pub fn map_event(
+        &mut self,
+        event: &Event,
+        joysticks: &mut Joysticks,
+        joystick_subsystem: &JoystickSubsystem,
+    ) -> Option<NavigationInput> {
+        match *event {
+            Event::JoyDeviceAdded { which: device_id, .. } => {
+                if let Err(err) = joysticks.device_added(device_id, joystick_subsystem) {
+                    log::error!("Error adding joystick with device id {device_id}: {err}");
+                }
+                None
+            }
+            Event::JoyDeviceRemoved { which: instance_id, .. } => {
+                joysticks.device_removed(instance_id);
+                self.axis_state.retain(|&(id, _), _| id != instance_id);
+                None
+            }
+            Event::JoyButtonDown { button_idx: CONFIRM_BUTTON_IDX, .. } => {
+                Some(NavigationInput::Confirm)
+            }
+            Event::JoyButtonDown { button_idx: BACK_BUTTON_IDX, .. } => Some(NavigationInput::Back),
+            Event::JoyHatMotion { state, .. } => NavigationInput::from_hat(state),
+            Event::JoyAxisMotion { which: instance_id, axis_idx, value, .. } => {
+                let input = NavigationInput::from_axis(axis_idx, value);
+                let prev_input = self.axis_state.insert((instance_id, axis_idx), input);
+                input.filter(|&input| prev_input != Some(Some(input)))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Polls gamepads for launcher navigation while no emulator is running.
+///
+/// This owns an SDL context, so it must be dropped before anything else on the emulation thread
+/// initializes SDL.
+pub struct GamepadNavigator {
+    _sdl: Sdl,
+    joystick_subsystem: JoystickSubsystem,
+    event_pump: EventPump,
+    joysticks: Joysticks,
+    mapper: NavigationMapper,
+    ctx: egui::Context,
+}
+
+impl GamepadNavigator {
+    pub fn new(ctx: egui::Context) -> anyhow::Result<Self> {
+        // The launcher window is not an SDL window, so SDL will consider the application to be in
+        // the background at all times
+        sdl2::hint::set("SDL_JOYSTICK_ALLOW_BACKGROUND_EVENTS", "1");
+
+        let sdl = sdl2::init().map_err(|err| anyhow!("Error initializing SDL2: {err}"))?;
+        let joystick_subsystem = sdl
+            .joystick()
+            .map_err(|err| anyhow!("Error initializing SDL2 joystick subsystem: {err}"))?;
+        let event_pump =
+            sdl.event_pump().map_err(|err| anyhow!("Error initializing SDL2 event pump: {err}"))?;
+
+        Ok(Self {
+            _sdl: sdl,
+            joystick_subsystem,
+            event_pump,
+            joysticks: Joysticks::new(),
+            mapper: NavigationMapper::default(),
+            ctx,
+        })
+    }
+
+    pub fn poll(&mut self, sender: &Sender<NavigationInput>) {
+        let mut sent_any = false;
+        for event in self.event_pump.poll_iter() {
+            if let Some(input) =
+                self.mapper.map_event(&event, &mut self.joysticks, &self.joystick_subsystem)
+            {
+                if sender.send(input).is_ok() {
+                    sent_any = true;
+                }
+            }
+        }
+
+        if sent_any {
+            self.ctx.request_repaint();
+        }
+    }
+}
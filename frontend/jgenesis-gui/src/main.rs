@@ -1,8 +1,10 @@
 use eframe::NativeOptions;
 use egui::{Vec2, ViewportBuilder};
 use env_logger::Env;
+use jgenesis_common::logging::SubsystemLogger;
 use jgenesis_gui::app::App;
-use std::path::PathBuf;
+use jgenesis_native_driver::paths::AppPaths;
+use log::LevelFilter;
 
 // Attempt to detect if the application is running on a Steam Deck, and if it is then override
 // the winit scale factor to 1. It defaults to 4.5 on the Steam Deck which results in the GUI
@@ -44,21 +46,40 @@ fn steam_deck_dpi_hack() {
     }
 }
 
-fn main() -> eframe::Result<()> {
-    env_logger::Builder::from_env(
+fn init_logger() {
+    let base = env_logger::Builder::from_env(
         Env::default().default_filter_or("info,wgpu_core=warn,wgpu_hal=warn"),
     )
-    .init();
+    .build();
+    let base_max_level = base.filter();
+    let unfiltered = env_logger::Builder::new().filter_level(LevelFilter::Trace).build();
+
+    SubsystemLogger::new(Box::new(base), base_max_level, Box::new(unfiltered))
+        .install()
+        .expect("init_logger should only be called once");
+}
+
+fn main() -> eframe::Result<()> {
+    init_logger();
 
     #[cfg(target_os = "linux")]
     steam_deck_dpi_hack();
 
-    let config_path = PathBuf::from("jgenesis-config.toml");
+    // Store config and caches next to the executable instead of in the user's profile
+    let portable = std::env::args().skip(1).any(|arg| arg == "--portable");
+    let paths = AppPaths::resolve(portable);
+    if let Err(err) = paths.install() {
+        log::error!("Error creating app directories: {err}");
+    }
 
     let options = NativeOptions {
         viewport: ViewportBuilder::default().with_inner_size(Vec2::new(800.0, 600.0)),
         ..NativeOptions::default()
     };
 
-    eframe::run_native("jgenesis", options, Box::new(|_cc| Box::new(App::new(config_path))))
+    eframe::run_native(
+        "jgenesis",
+        options,
+        Box::new(|cc| Box::new(App::new(&paths, cc.egui_ctx.clone()))),
+    )
 }
@@ -1,30 +1,66 @@
 pub mod input;
 
 use crate::config::input::{
-    GameBoyInputConfig, GenesisInputConfig, HotkeyConfig, JoystickInput, KeyboardInput,
-    NesInputConfig, SmsGgInputConfig, SnesControllerType, SnesInputConfig, SuperScopeConfig,
+    Atari2600InputConfig, ColecoVisionInputConfig, GameBoyInputConfig, GbaInputConfig,
+    GenesisInputConfig, HotkeyConfig, InputMacroConfig, JoystickInput, KeyboardInput,
+    NesInputConfig, PceInputConfig, SmsGgInputConfig, SmsPeripheralConfig, SnesControllerType,
+    SnesInputConfig, SteamDeckInputDefaults, SuperScopeConfig,
 };
+use atari2600_core::api::{Atari2600AspectRatio, Atari2600Difficulty, Atari2600EmulatorConfig};
+use colecovision_core::api::{ColecoVisionAspectRatio, ColecoVisionEmulatorConfig};
 use gb_core::api::{GameBoyEmulatorConfig, GbAspectRatio, GbPalette, GbcColorCorrection};
+use gba_core::api::{GbaAspectRatio, GbaEmulatorConfig};
 use genesis_core::{
-    GenesisAspectRatio, GenesisControllerType, GenesisEmulatorConfig, GenesisRegion,
+    GenesisAspectRatio, GenesisControllerType, GenesisEmulatorConfig, GenesisMultitap,
+    GenesisRegion, GenesisRegionSpoof, GenesisSerialPort,
 };
-use jgenesis_common::frontend::{PixelAspectRatio, TimingMode};
+use jgenesis_common::audio::ResamplerQuality;
+use jgenesis_common::frontend::{FrameSize, PixelAspectRatio, TimingMode};
+use jgenesis_common::rng::InitialRamState;
 use jgenesis_proc_macros::{ConfigDisplay, EnumDisplay, EnumFromStr};
-use jgenesis_renderer::config::RendererConfig;
-use nes_core::api::{NesAspectRatio, NesEmulatorConfig, Overscan};
+use jgenesis_renderer::config::{
+    PreprocessShader, PrescaleFactor, RendererConfig, Scanlines, VSyncMode, WgpuBackend,
+};
+use jgenesis_renderer::deinterlace::DeinterlaceMode;
+use nes_core::api::{NesAspectRatio, NesEmulatorConfig, NesTimingMode, Overscan};
+use nes_core::input::NesExpansionDevice;
+use pce_core::api::{PceAspectRatio, PceEmulatorConfig, PceRegion};
 use segacd_core::api::SegaCdEmulatorConfig;
 use serde::{Deserialize, Serialize};
 use smsgg_core::psg::PsgVersion;
-use smsgg_core::{SmsGgEmulatorConfig, SmsRegion, VdpVersion};
-use snes_core::api::{CoprocessorRomFn, CoprocessorRoms, SnesAspectRatio, SnesEmulatorConfig};
-use std::fs;
+use smsgg_core::{SmsControllerType, SmsGgEmulatorConfig, SmsRegion, VdpVersion};
+use snes_core::api::{
+    CoprocessorRomFn, CoprocessorRoms, Mode7Scale, SnesAspectRatio, SnesEmulatorConfig,
+    SnesEnhancements,
+};
+use std::fmt::{Debug, Display};
 use std::num::NonZeroU64;
+use std::{fmt, fs};
 
-pub(crate) const DEFAULT_GENESIS_WINDOW_SIZE: WindowSize = WindowSize { width: 878, height: 672 };
-pub(crate) const DEFAULT_GB_WINDOW_SIZE: WindowSize =
-    WindowSize { width: 160 * 3, height: 144 * 3 };
-
+/// Frame size and pixel aspect ratio that a system's default window size is computed from.
 #[derive(Debug, Clone, Copy)]
+pub(crate) struct DefaultFrame {
+    pub(crate) frame_size: FrameSize,
+    pub(crate) pixel_aspect_ratio: f64,
+}
+
+impl DefaultFrame {
+    const fn new(width: u32, height: u32, pixel_aspect_ratio: f64) -> Self {
+        Self { frame_size: FrameSize { width, height }, pixel_aspect_ratio }
+    }
+}
+
+// 256x224 with 8:7 pixels is also the most common NES and SNES frame size, and 320x224 Genesis
+// frames have the same display size
+pub(crate) const GENESIS_DEFAULT_FRAME: DefaultFrame = DefaultFrame::new(256, 224, 8.0 / 7.0);
+pub(crate) const GB_DEFAULT_FRAME: DefaultFrame = DefaultFrame::new(160, 144, 1.0);
+pub(crate) const PCE_DEFAULT_FRAME: DefaultFrame = DefaultFrame::new(256, 242, 8.0 / 7.0);
+pub(crate) const GBA_DEFAULT_FRAME: DefaultFrame = DefaultFrame::new(240, 160, 1.0);
+pub(crate) const ATARI2600_DEFAULT_FRAME: DefaultFrame = DefaultFrame::new(160, 210, 12.0 / 7.0);
+pub(crate) const COLECOVISION_DEFAULT_FRAME: DefaultFrame = DefaultFrame::new(256, 224, 8.0 / 7.0);
+
+/// Window size in physical pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct WindowSize {
     pub width: u32,
     pub height: u32,
@@ -78,35 +114,322 @@ impl GgAspectRatio {
     }
 }
 
+/// Optional processing applied to the mixed audio signal right before it is sent to the audio
+/// device. Every stage is disabled by default.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ConfigDisplay)]
+#[serde(default)]
+pub struct AudioPostProcessingConfig {
+    pub equalizer_enabled: bool,
+    /// Low shelf corner frequency in Hz
+    pub eq_low_frequency: f64,
+    pub eq_low_gain_db: f64,
+    /// Peaking filter center frequency in Hz
+    pub eq_mid_frequency: f64,
+    pub eq_mid_gain_db: f64,
+    pub eq_mid_q: f64,
+    /// High shelf corner frequency in Hz
+    pub eq_high_frequency: f64,
+    pub eq_high_gain_db: f64,
+    /// Stereo widening amount from 0.0 (disabled) to 1.0. Mono signals are widened by mixing in a
+    /// short delayed copy with opposite polarity in each channel.
+    pub stereo_widening: f64,
+    /// Headphone crossfeed amount from 0.0 (disabled) to 1.0
+    pub crossfeed: f64,
+    /// Smoothly reduce gain when the signal would otherwise clip
+    pub soft_limiter_enabled: bool,
+}
+
+impl Default for AudioPostProcessingConfig {
+    fn default() -> Self {
+        Self {
+            equalizer_enabled: false,
+            eq_low_frequency: 200.0,
+            eq_low_gain_db: 0.0,
+            eq_mid_frequency: 1000.0,
+            eq_mid_gain_db: 0.0,
+            eq_mid_q: 0.7,
+            eq_high_frequency: 5000.0,
+            eq_high_gain_db: 0.0,
+            stereo_widening: 0.0,
+            crossfeed: 0.0,
+            soft_limiter_enabled: false,
+        }
+    }
+}
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, EnumDisplay, EnumFromStr,
+)]
+pub enum SaveSyncProtocol {
+    #[default]
+    WebDav,
+    S3,
+}
+
+/// Credential that is hidden when configs are logged or written to crash reports.
+#[derive(Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Secret(pub String);
+
+impl Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self}")
+    }
+}
+
+impl Display for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_empty() {
+            write!(f, "<empty>")
+        } else {
+            write!(f, "<hidden>")
+        }
+    }
+}
+
+/// Remote server that save files are mirrored to when a game is launched and when it is closed.
+/// If the local and remote copies of a save file differ, the more recently modified copy wins and
+/// the other copy is kept next to the ROM with a `.sync-backup` extension.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ConfigDisplay)]
+pub struct SaveSyncConfig {
+    pub protocol: SaveSyncProtocol,
+    /// WebDAV collection URL, or path-style S3 bucket URL with an optional key prefix (e.g.
+    /// `https://s3.us-east-1.amazonaws.com/my-bucket/saves`). The collection or bucket must
+    /// already exist.
+    pub url: String,
+    /// WebDAV username or S3 access key ID
+    pub username: String,
+    /// WebDAV password or S3 secret access key
+    pub password: Secret,
+    /// Signing region for S3; ignored for WebDAV
+    pub s3_region: String,
+}
+
+/// Discord Rich Presence, which shows the running system and elapsed time on the user's Discord
+/// profile through the locally running Discord client
+#[derive(Debug, Clone, PartialEq, Eq, ConfigDisplay)]
+pub struct DiscordConfig {
+    /// ID of the application to show the activity as, from the Discord developer portal
+    pub application_id: String,
+    /// Also show the title of the running game
+    pub show_game_title: bool,
+}
+
+/// Text of the messages that the emulator window shows on screen, e.g. after saving or loading a
+/// state. `{slot}` is replaced with the save state slot number and `{attempt}` with the practice
+/// attempt number.
+///
+/// The on-screen font only has uppercase letters, digits, and spaces, so messages are shown in
+/// uppercase with accents removed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OsdMessages {
+    pub slot: String,
+    pub slot_empty: String,
+    pub saved_slot: String,
+    pub loaded_slot: String,
+    pub resumed: String,
+    pub undo_load: String,
+    pub practice_attempt: String,
+    pub loop_set: String,
+    pub loop_cleared: String,
+    pub controller_connected: String,
+    pub controller_disconnected: String,
+    pub recording_macro: String,
+    pub macro_recorded: String,
+    pub macro_empty: String,
+}
+
+impl Default for OsdMessages {
+    fn default() -> Self {
+        Self {
+            slot: "SLOT {slot}".into(),
+            slot_empty: "SLOT {slot} EMPTY".into(),
+            saved_slot: "SAVED SLOT {slot}".into(),
+            loaded_slot: "LOADED SLOT {slot}".into(),
+            resumed: "RESUMED".into(),
+            undo_load: "UNDO LOAD".into(),
+            practice_attempt: "ATTEMPT {attempt}".into(),
+            loop_set: "LOOP SET".into(),
+            loop_cleared: "LOOP CLEARED".into(),
+            controller_connected: "CONTROLLER CONNECTED".into(),
+            controller_disconnected: "CONTROLLER DISCONNECTED".into(),
+            recording_macro: "RECORDING MACRO".into(),
+            macro_recorded: "MACRO RECORDED".into(),
+            macro_empty: "MACRO EMPTY".into(),
+        }
+    }
+}
+
+/// Local WebSocket/HTTP server that lets other programs (stream decks, overlays, automation
+/// scripts) send [`EmulatorCommand`](jgenesis_common::command::EmulatorCommand)s and read memory.
+#[derive(Debug, Clone, PartialEq, Eq, ConfigDisplay)]
+pub struct RemoteControlConfig {
+    /// Localhost port to listen on
+    pub port: u16,
+    /// Clients must send this token with every connection, either in the URL query string
+    /// (`?token=<token>`) or in an `Authorization: Bearer <token>` header. Must not be empty
+    pub token: Secret,
+}
+
 #[derive(Debug, Clone, ConfigDisplay)]
 pub struct CommonConfig<KeyboardConfig, JoystickConfig> {
     pub rom_file_path: String,
+    /// IPS/BPS patches to apply to the ROM when it is loaded, in order. Ignored for Sega CD discs
+    /// and SPC files.
+    #[debug_fmt]
+    pub rom_patch_paths: Vec<String>,
     pub audio_sync: bool,
     pub audio_device_queue_size: u16,
     pub internal_audio_buffer_size: u32,
     pub audio_sync_threshold: u32,
+    /// If set, derive the audio device queue size, internal audio buffer size, and audio sync
+    /// threshold from this end-to-end latency target instead of using the values above. This also
+    /// enables dynamic rate control when audio sync is disabled.
+    #[debug_fmt]
+    pub audio_latency_ms: Option<u32>,
+    /// Briefly stretch audio after the audio device runs out of samples instead of letting it
+    /// crackle
+    pub audio_underrun_recovery: bool,
     pub audio_gain_db: f64,
+    #[indent_nested]
+    pub audio_post_processing: AudioPostProcessingConfig,
+    pub audio_resampler_quality: ResamplerQuality,
+    /// Initial window size in physical pixels. If not set, the window size from the last time this
+    /// system was run is used, or a size based on the system's frame size and the display's scale
+    /// factor if there is none.
     #[debug_fmt]
     pub window_size: Option<WindowSize>,
     #[indent_nested]
     pub renderer_config: RendererConfig,
+    /// PNG image to render around the game if borders are enabled. If not set, the system's
+    /// default border is used (if it has one).
+    #[debug_fmt]
+    pub border_image_path: Option<String>,
     pub fast_forward_multiplier: u64,
     pub rewind_buffer_length_seconds: u64,
+    /// Length of the rolling input trace that can be dumped with the dump input trace hotkey and
+    /// is written automatically on panic. 0 disables input tracing.
+    pub input_trace_length_seconds: u64,
+    /// Save state automatically when the emulator exits and resume from that state the next time
+    /// the same ROM is launched
+    pub auto_save_state: bool,
+    /// Reload the practice loop point after this many frames. 0 disables the frame count trigger.
+    pub practice_end_frame_count: u64,
+    /// Reload the practice loop point when this debugger expression (e.g. `[$FF0010] == 3`) is
+    /// true after a frame. Empty disables the memory condition trigger.
+    pub practice_end_condition: String,
     pub launch_in_fullscreen: bool,
+    /// Skip rendering frames (but not emulating them) when the host is unable to keep up with
+    /// emulation speed
+    pub auto_frame_skip: bool,
+    /// Maximum number of consecutive frames that auto frame skip is allowed to skip
+    pub max_frame_skip: u32,
     #[indent_nested]
     pub keyboard_inputs: KeyboardConfig,
     pub axis_deadzone: i16,
+    /// Pause emulation when a gamepad with mapped inputs disconnects, and resume when one
+    /// reconnects
+    pub pause_on_controller_disconnect: bool,
     #[indent_nested]
     pub joystick_inputs: JoystickConfig,
     #[indent_nested]
     pub hotkeys: HotkeyConfig,
+    #[debug_fmt]
+    pub input_macros: Vec<InputMacroConfig>,
     pub hide_cursor_over_window: bool,
+    /// Text of the on-screen messages, e.g. for save state slots
+    #[debug_fmt]
+    pub osd_messages: OsdMessages,
+    /// If set, listen for GDB remote protocol connections on this localhost port. Only supported
+    /// for Genesis and SNES.
+    #[debug_fmt]
+    pub gdb_port: Option<u16>,
+    /// If set, listen for input injection connections on this localhost port, which allow external
+    /// programs to press buttons on any controller.
+    #[debug_fmt]
+    pub input_injection_port: Option<u16>,
+    /// If set, run a remote control server that accepts commands from external programs
+    #[indent_nested]
+    pub remote_control: Option<RemoteControlConfig>,
+    /// Symbol file to display labels from in the debugger. If not set, a .sym or .map file with
+    /// the same name as the ROM is loaded if one exists.
+    #[debug_fmt]
+    pub symbol_file_path: Option<String>,
+    /// Initial contents of work RAM at power on. If not set, each console's default is used.
+    #[debug_fmt]
+    pub initial_ram_state: Option<InitialRamState>,
+    /// Seed for all randomness inside the emulation core. Setting this makes emulation fully
+    /// deterministic, e.g. for TAS movies. If not set, a random seed is chosen at power on.
+    #[debug_fmt]
+    pub rng_seed: Option<u64>,
+    /// If set, dump every emulated frame and audio sample to .y4m and .wav files at this path.
+    /// Emulation runs unthrottled and audio is not played while dumping.
+    #[debug_fmt]
+    pub av_dump_path: Option<String>,
+    /// If set, mirror this game's save files to a remote server
+    #[indent_nested]
+    pub save_sync: Option<SaveSyncConfig>,
+    /// If set, publish the running game to Discord Rich Presence
+    #[indent_nested]
+    pub discord: Option<DiscordConfig>,
+    /// Set by [`CommonConfig::apply_steam_deck_mode`]; makes audio sync sleep until the audio
+    /// queue has room instead of polling it
+    pub steam_deck_mode: bool,
+}
+
+impl<KC, JC: SteamDeckInputDefaults> CommonConfig<KC, JC> {
+    /// Override settings for the Steam Deck: fullscreen with integer height scaling (e.g. 3x for
+    /// 224-line output on the 1280x800 screen), player 1 mapped to the built-in controls if no
+    /// gamepad inputs are configured, and audio-driven frame pacing.
+    ///
+    /// Vsync is disabled because gamescope may run the display at anywhere from 40Hz to 90Hz, and
+    /// waiting on it would throttle emulation to the display's refresh rate rather than 60Hz.
+    pub fn apply_steam_deck_mode(&mut self) {
+        self.steam_deck_mode = true;
+        self.launch_in_fullscreen = true;
+        self.hide_cursor_over_window = true;
+        self.audio_sync = true;
+        self.renderer_config.force_integer_height_scaling = true;
+        self.renderer_config.vsync_mode = VSyncMode::Disabled;
+        self.joystick_inputs.fill_steam_deck_defaults();
+    }
+}
+
+impl<KC, JC> CommonConfig<KC, JC> {
+    /// Override settings for low-power hosts such as the Raspberry Pi: the OpenGL backend with
+    /// WebGL2-compatible limits (for GLES 3.0 drivers), no prescaling or shader passes, the cheap
+    /// box-average audio resampler, and auto frame skip.
+    pub fn apply_low_power_profile(&mut self) {
+        self.renderer_config.wgpu_backend = WgpuBackend::OpenGl;
+        self.renderer_config.use_webgl2_limits = true;
+        self.renderer_config.prescale_factor = PrescaleFactor::ONE;
+        self.renderer_config.scanlines = Scanlines::None;
+        self.renderer_config.preprocess_shader = PreprocessShader::None;
+        self.audio_resampler_quality = ResamplerQuality::Low;
+        self.auto_frame_skip = true;
+    }
+}
+
+/// What an emulated link or serial port (Genesis serial, Gear-to-Gear) is connected to
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, EnumDisplay, EnumFromStr,
+)]
+pub enum LinkTransportKind {
+    #[default]
+    None,
+    /// Connect to a TCP server
+    TcpClient,
+    /// Accept a connection from a TCP client
+    TcpServer,
 }
 
 #[derive(Debug, Clone, ConfigDisplay)]
 pub struct SmsGgConfig {
     #[indent_nested]
     pub common: CommonConfig<SmsGgInputConfig<KeyboardInput>, SmsGgInputConfig<JoystickInput>>,
+    pub p1_controller_type: SmsControllerType,
+    #[indent_nested]
+    pub peripheral_config: SmsPeripheralConfig,
     pub vdp_version: Option<VdpVersion>,
     pub psg_version: Option<PsgVersion>,
     pub remove_sprite_limit: bool,
@@ -117,6 +440,10 @@ pub struct SmsGgConfig {
     pub sms_crop_left_border: bool,
     pub fm_sound_unit_enabled: bool,
     pub overclock_z80: bool,
+    /// What the Game Gear's Gear-to-Gear port is connected to
+    pub link_transport: LinkTransportKind,
+    // Address to connect to or listen on for the TCP link transports, e.g. `127.0.0.1:7846`
+    pub link_tcp_address: String,
 }
 
 impl SmsGgConfig {
@@ -136,10 +463,14 @@ impl SmsGgConfig {
             pixel_aspect_ratio,
             remove_sprite_limit: self.remove_sprite_limit,
             sms_region: self.sms_region,
+            p1_controller_type: self.p1_controller_type,
             sms_crop_vertical_border: self.sms_crop_vertical_border,
             sms_crop_left_border: self.sms_crop_left_border,
             fm_sound_unit_enabled: self.fm_sound_unit_enabled,
             overclock_z80: self.overclock_z80,
+            initial_ram_state: self.common.initial_ram_state,
+            rng_seed: self.common.rng_seed,
+            audio_resampler_quality: self.common.audio_resampler_quality,
         }
     }
 }
@@ -162,16 +493,16 @@ pub(crate) fn default_psg_version_for_ext(file_ext: &str) -> PsgVersion {
     }
 }
 
-pub(crate) fn default_smsgg_window_size(vdp_version: VdpVersion) -> WindowSize {
-    match vdp_version {
-        VdpVersion::NtscMasterSystem1 | VdpVersion::NtscMasterSystem2 => {
-            WindowSize { width: 940, height: 720 }
-        }
-        VdpVersion::PalMasterSystem1 | VdpVersion::PalMasterSystem2 => {
-            WindowSize { width: 1056, height: 720 }
-        }
-        VdpVersion::GameGear => WindowSize { width: 576, height: 432 },
-    }
+pub(crate) fn smsgg_default_frame(
+    vdp_version: VdpVersion,
+    pixel_aspect_ratio: Option<PixelAspectRatio>,
+) -> DefaultFrame {
+    let viewport = vdp_version.viewport_size();
+    DefaultFrame::new(
+        viewport.width.into(),
+        viewport.height.into(),
+        pixel_aspect_ratio.map_or(1.0, f64::from),
+    )
 }
 
 #[derive(Debug, Clone, ConfigDisplay)]
@@ -180,8 +511,15 @@ pub struct GenesisConfig {
     pub common: CommonConfig<GenesisInputConfig<KeyboardInput>, GenesisInputConfig<JoystickInput>>,
     pub p1_controller_type: GenesisControllerType,
     pub p2_controller_type: GenesisControllerType,
+    pub multitap: GenesisMultitap,
+    pub serial_port: GenesisSerialPort,
+    pub serial_transport: LinkTransportKind,
+    // Address to connect to or listen on for the TCP serial transports, e.g. `127.0.0.1:7845`
+    pub serial_tcp_address: String,
     pub forced_timing_mode: Option<TimingMode>,
     pub forced_region: Option<GenesisRegion>,
+    pub region_spoof: GenesisRegionSpoof,
+    pub report_tmss: bool,
     pub aspect_ratio: GenesisAspectRatio,
     // Whether or not to automatically double the pixel aspect ratio when the VDP is in interlaced
     // double resolution mode
@@ -190,7 +528,26 @@ pub struct GenesisConfig {
     pub emulate_non_linear_vdp_dac: bool,
     pub render_vertical_border: bool,
     pub render_horizontal_border: bool,
+    pub widescreen: bool,
+    // Community patches that fix games' rendering outside of the normal screen area; only applied
+    // in widescreen mode
+    pub widescreen_patches_path: Option<String>,
+    // Enhancement: output at 2x resolution with H32 lines stretched to H40 width
+    pub hi_res_output: bool,
+    // 128KB of VRAM as on development hardware and the Tera Drive; only affects games that use
+    // 128KB mode
+    pub expanded_vram: bool,
+    // Experimental: directory to save each tile to the first time it is drawn
+    pub tile_dump_directory: Option<String>,
+    // Experimental: directory of replacement tile images, in the same format as dumped tiles
+    pub texture_pack_directory: Option<String>,
     pub quantize_ym2612_output: bool,
+    // ROM to lock on to Sonic & Knuckles; ignored for other games
+    pub lock_on_rom_path: Option<String>,
+    // 256KB patch ROM from the Sonic & Knuckles cartridge, which is needed for Knuckles in Sonic 2
+    pub lock_on_patch_rom_path: Option<String>,
+    // Only affects frames in interlaced double resolution mode
+    pub deinterlace_mode: DeinterlaceMode,
 }
 
 impl GenesisConfig {
@@ -198,15 +555,25 @@ impl GenesisConfig {
         GenesisEmulatorConfig {
             forced_timing_mode: self.forced_timing_mode,
             forced_region: self.forced_region,
+            region_spoof: self.region_spoof,
+            report_tmss: self.report_tmss,
             aspect_ratio: self.aspect_ratio,
             adjust_aspect_ratio_in_2x_resolution: self.adjust_aspect_ratio_in_2x_resolution,
             remove_sprite_limits: self.remove_sprite_limits,
             emulate_non_linear_vdp_dac: self.emulate_non_linear_vdp_dac,
             render_vertical_border: self.render_vertical_border,
             render_horizontal_border: self.render_horizontal_border,
+            widescreen: self.widescreen,
+            hi_res_output: self.hi_res_output,
+            expanded_vram: self.expanded_vram,
             quantize_ym2612_output: self.quantize_ym2612_output,
             p1_controller_type: self.p1_controller_type,
             p2_controller_type: self.p2_controller_type,
+            multitap: self.multitap,
+            serial_port: self.serial_port,
+            initial_ram_state: self.common.initial_ram_state,
+            rng_seed: self.common.rng_seed,
+            audio_resampler_quality: self.common.audio_resampler_quality,
         }
     }
 }
@@ -217,7 +584,9 @@ pub struct SegaCdConfig {
     pub genesis: GenesisConfig,
     pub bios_file_path: Option<String>,
     pub enable_ram_cartridge: bool,
+    pub fast_boot: bool,
     pub run_without_disc: bool,
+    pub mode_1_cartridge_path: Option<String>,
 }
 
 impl SegaCdConfig {
@@ -225,6 +594,7 @@ impl SegaCdConfig {
         SegaCdEmulatorConfig {
             genesis: self.genesis.to_emulator_config(),
             enable_ram_cartridge: self.enable_ram_cartridge,
+            fast_boot: self.fast_boot,
         }
     }
 }
@@ -233,14 +603,16 @@ impl SegaCdConfig {
 pub struct NesConfig {
     #[indent_nested]
     pub common: CommonConfig<NesInputConfig<KeyboardInput>, NesInputConfig<JoystickInput>>,
-    pub forced_timing_mode: Option<TimingMode>,
+    pub forced_timing_mode: Option<NesTimingMode>,
     pub aspect_ratio: NesAspectRatio,
     pub overscan: Overscan,
+    pub emulate_ntsc_overscan: bool,
     pub remove_sprite_limit: bool,
     pub pal_black_border: bool,
     pub silence_ultrasonic_triangle_output: bool,
     pub audio_refresh_rate_adjustment: bool,
     pub allow_opposing_joypad_inputs: bool,
+    pub forced_expansion_device: Option<NesExpansionDevice>,
 }
 
 impl NesConfig {
@@ -249,11 +621,16 @@ impl NesConfig {
             forced_timing_mode: self.forced_timing_mode,
             aspect_ratio: self.aspect_ratio,
             overscan: self.overscan,
+            emulate_ntsc_overscan: self.emulate_ntsc_overscan,
             remove_sprite_limit: self.remove_sprite_limit,
             pal_black_border: self.pal_black_border,
             silence_ultrasonic_triangle_output: self.silence_ultrasonic_triangle_output,
             audio_refresh_rate_adjustment: self.audio_refresh_rate_adjustment,
             allow_opposing_joypad_inputs: self.allow_opposing_joypad_inputs,
+            forced_expansion_device: self.forced_expansion_device,
+            initial_ram_state: self.common.initial_ram_state,
+            rng_seed: self.common.rng_seed,
+            audio_resampler_quality: self.common.audio_resampler_quality,
         }
     }
 }
@@ -269,21 +646,38 @@ pub struct SnesConfig {
     pub aspect_ratio: SnesAspectRatio,
     pub audio_60hz_hack: bool,
     pub gsu_overclock_factor: NonZeroU64,
+    #[indent_nested]
+    pub enhancements: SnesEnhancements,
     pub dsp1_rom_path: Option<String>,
     pub dsp2_rom_path: Option<String>,
     pub dsp3_rom_path: Option<String>,
     pub dsp4_rom_path: Option<String>,
     pub st010_rom_path: Option<String>,
     pub st011_rom_path: Option<String>,
+    // Only affects frames in interlaced Mode 5/6
+    pub deinterlace_mode: DeinterlaceMode,
 }
 
 impl SnesConfig {
+    // HD Mode 7 frames are the same size as interlaced frames, so deinterlacing would blur them
+    pub(crate) fn effective_deinterlace_mode(&self) -> DeinterlaceMode {
+        if self.enhancements.mode_7_scale == Mode7Scale::Native {
+            self.deinterlace_mode
+        } else {
+            DeinterlaceMode::Weave
+        }
+    }
+
     pub(crate) fn to_emulator_config(&self) -> SnesEmulatorConfig {
         SnesEmulatorConfig {
             forced_timing_mode: self.forced_timing_mode,
             aspect_ratio: self.aspect_ratio,
             audio_60hz_hack: self.audio_60hz_hack,
             gsu_overclock_factor: self.gsu_overclock_factor,
+            enhancements: self.enhancements,
+            initial_ram_state: self.common.initial_ram_state,
+            rng_seed: self.common.rng_seed,
+            audio_resampler_quality: self.common.audio_resampler_quality,
         }
     }
 
@@ -324,6 +718,99 @@ impl GameBoyConfig {
             gb_palette: self.gb_palette,
             gbc_color_correction: self.gbc_color_correction,
             audio_60hz_hack: self.audio_60hz_hack,
+            initial_ram_state: self.common.initial_ram_state,
+            rng_seed: self.common.rng_seed,
+            audio_resampler_quality: self.common.audio_resampler_quality,
+        }
+    }
+}
+
+#[derive(Debug, Clone, ConfigDisplay)]
+pub struct PceConfig {
+    #[indent_nested]
+    pub common: CommonConfig<PceInputConfig<KeyboardInput>, PceInputConfig<JoystickInput>>,
+    pub region: PceRegion,
+    pub aspect_ratio: PceAspectRatio,
+}
+
+impl PceConfig {
+    pub(crate) fn to_emulator_config(&self) -> PceEmulatorConfig {
+        PceEmulatorConfig {
+            region: self.region,
+            aspect_ratio: self.aspect_ratio,
+            audio_resampler_quality: self.common.audio_resampler_quality,
+            initial_ram_state: self.common.initial_ram_state,
+            rng_seed: self.common.rng_seed,
+        }
+    }
+}
+
+#[derive(Debug, Clone, ConfigDisplay)]
+pub struct GbaConfig {
+    #[indent_nested]
+    pub common: CommonConfig<GbaInputConfig<KeyboardInput>, GbaInputConfig<JoystickInput>>,
+    pub bios_file_path: Option<String>,
+    pub aspect_ratio: GbaAspectRatio,
+    pub skip_bios_intro: bool,
+}
+
+impl GbaConfig {
+    pub(crate) fn to_emulator_config(&self) -> GbaEmulatorConfig {
+        GbaEmulatorConfig {
+            aspect_ratio: self.aspect_ratio,
+            skip_bios_intro: self.skip_bios_intro,
+            audio_resampler_quality: self.common.audio_resampler_quality,
+            initial_ram_state: self.common.initial_ram_state,
+            rng_seed: self.common.rng_seed,
+        }
+    }
+}
+
+#[derive(Debug, Clone, ConfigDisplay)]
+pub struct Atari2600Config {
+    #[indent_nested]
+    pub common:
+        CommonConfig<Atari2600InputConfig<KeyboardInput>, Atari2600InputConfig<JoystickInput>>,
+    pub aspect_ratio: Atari2600AspectRatio,
+    pub left_difficulty: Atari2600Difficulty,
+    pub right_difficulty: Atari2600Difficulty,
+    pub black_and_white: bool,
+}
+
+impl Atari2600Config {
+    pub(crate) fn to_emulator_config(&self) -> Atari2600EmulatorConfig {
+        Atari2600EmulatorConfig {
+            aspect_ratio: self.aspect_ratio,
+            left_difficulty: self.left_difficulty,
+            right_difficulty: self.right_difficulty,
+            black_and_white: self.black_and_white,
+            audio_resampler_quality: self.common.audio_resampler_quality,
+            initial_ram_state: self.common.initial_ram_state,
+            rng_seed: self.common.rng_seed,
+        }
+    }
+}
+
+#[derive(Debug, Clone, ConfigDisplay)]
+pub struct ColecoVisionConfig {
+    #[indent_nested]
+    pub common: CommonConfig<
+        ColecoVisionInputConfig<KeyboardInput>,
+        ColecoVisionInputConfig<JoystickInput>,
+    >,
+    pub bios_file_path: Option<String>,
+    pub aspect_ratio: ColecoVisionAspectRatio,
+    pub remove_sprite_limit: bool,
+}
+
+impl ColecoVisionConfig {
+    pub(crate) fn to_emulator_config(&self) -> ColecoVisionEmulatorConfig {
+        ColecoVisionEmulatorConfig {
+            aspect_ratio: self.aspect_ratio,
+            remove_sprite_limit: self.remove_sprite_limit,
+            audio_resampler_quality: self.common.audio_resampler_quality,
+            initial_ram_state: self.common.initial_ram_state,
+            rng_seed: self.common.rng_seed,
         }
     }
 }
@@ -1,3 +1,4 @@
+use crate::steamdeck;
 use jgenesis_proc_macros::{ConfigDisplay, EnumDisplay, EnumFromStr};
 use sdl2::keyboard::Keycode;
 use serde::{Deserialize, Serialize};
@@ -115,11 +116,39 @@ macro_rules! key_input {
     };
 }
 
+// Steam Deck built-in controls as exposed by Steam Input, which uses an Xbox-style layout:
+// A/B/X/Y are buttons 0-3, L1/R1 are 4-5, View/Menu are 6-7, and the D-pad is hat 0
+macro_rules! deck_input {
+    (Button($button_idx:literal)) => {
+        Some(JoystickInput {
+            device: JoystickDeviceId::new(steamdeck::CONTROLLER_NAME.into(), 0),
+            action: JoystickAction::Button { button_idx: $button_idx },
+        })
+    };
+    (Hat($direction:ident)) => {
+        Some(JoystickInput {
+            device: JoystickDeviceId::new(steamdeck::CONTROLLER_NAME.into(), 0),
+            action: JoystickAction::Hat { hat_idx: 0, direction: HatDirection::$direction },
+        })
+    };
+    (Unmapped(_)) => {
+        None
+    };
+}
+
+/// Default gamepad mappings for Steam Deck mode.
+pub trait SteamDeckInputDefaults {
+    /// Map player 1 to the Steam Deck's built-in controls if no gamepad inputs are configured for
+    /// player 1.
+    fn fill_steam_deck_defaults(&mut self);
+}
+
 macro_rules! define_input_config {
     (
         controller_cfg_name: $controller_cfg_name:ident,
         input_cfg_name: $input_cfg_name:ident,
-        buttons: [$($button:ident: default $keycode:ident),* $(,)?] $(,)?
+        buttons: [$($button:ident: default $keycode:ident, deck $deck:ident($deck_value:tt)),* $(,)?] $(,)?
+        $(extra_players: [$($extra_player:ident),* $(,)?] $(,)?)?
     ) => {
         #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ConfigDisplay)]
         pub struct $controller_cfg_name<Input> {
@@ -144,6 +173,11 @@ macro_rules! define_input_config {
             pub p1: $controller_cfg_name<Input>,
             #[indent_nested]
             pub p2: $controller_cfg_name<Input>,
+            $($(
+                #[serde(default)]
+                #[indent_nested]
+                pub $extra_player: $controller_cfg_name<Input>,
+            )*)?
         }
 
         impl Default for $input_cfg_name<KeyboardInput> {
@@ -155,6 +189,9 @@ macro_rules! define_input_config {
                         )*
                     },
                     p2: $controller_cfg_name::default(),
+                    $($(
+                        $extra_player: $controller_cfg_name::default(),
+                    )*)?
                 }
             }
         }
@@ -164,7 +201,24 @@ macro_rules! define_input_config {
                 Self {
                     p1: $controller_cfg_name::default(),
                     p2: $controller_cfg_name::default(),
+                    $($(
+                        $extra_player: $controller_cfg_name::default(),
+                    )*)?
+                }
+            }
+        }
+
+        impl SteamDeckInputDefaults for $input_cfg_name<JoystickInput> {
+            fn fill_steam_deck_defaults(&mut self) {
+                if self.p1 != $controller_cfg_name::default() {
+                    return;
                 }
+
+                self.p1 = $controller_cfg_name {
+                    $(
+                        $button: deck_input!($deck($deck_value)),
+                    )*
+                };
             }
         }
     }
@@ -174,13 +228,13 @@ define_input_config! {
     controller_cfg_name: SmsGgControllerConfig,
     input_cfg_name: SmsGgInputConfig,
     buttons: [
-        up: default Up,
-        left: default Left,
-        right: default Right,
-        down: default Down,
-        button_1: default S,
-        button_2: default A,
-        pause: default Return,
+        up: default Up, deck Hat(Up),
+        left: default Left, deck Hat(Left),
+        right: default Right, deck Hat(Right),
+        down: default Down, deck Hat(Down),
+        button_1: default S, deck Button(0),
+        button_2: default A, deck Button(1),
+        pause: default Return, deck Button(7),
     ],
 }
 
@@ -188,33 +242,35 @@ define_input_config! {
     controller_cfg_name: GenesisControllerConfig,
     input_cfg_name: GenesisInputConfig,
     buttons: [
-        up: default Up,
-        left: default Left,
-        right: default Right,
-        down: default Down,
-        a: default A,
-        b: default S,
-        c: default D,
-        x: default Q,
-        y: default W,
-        z: default E,
-        start: default Return,
-        mode: default RShift,
+        up: default Up, deck Hat(Up),
+        left: default Left, deck Hat(Left),
+        right: default Right, deck Hat(Right),
+        down: default Down, deck Hat(Down),
+        a: default A, deck Button(2),
+        b: default S, deck Button(0),
+        c: default D, deck Button(1),
+        x: default Q, deck Button(4),
+        y: default W, deck Button(3),
+        z: default E, deck Button(5),
+        start: default Return, deck Button(7),
+        mode: default RShift, deck Button(6),
     ],
+    // Only used with the EA 4-Way Play or J-Cart
+    extra_players: [p3, p4],
 }
 
 define_input_config! {
     controller_cfg_name: NesControllerConfig,
     input_cfg_name: NesInputConfig,
     buttons: [
-        up: default Up,
-        left: default Left,
-        right: default Right,
-        down: default Down,
-        a: default A,
-        b: default S,
-        start: default Return,
-        select: default RShift,
+        up: default Up, deck Hat(Up),
+        left: default Left, deck Hat(Left),
+        right: default Right, deck Hat(Right),
+        down: default Down, deck Hat(Down),
+        a: default A, deck Button(1),
+        b: default S, deck Button(0),
+        start: default Return, deck Button(7),
+        select: default RShift, deck Button(6),
     ],
 }
 
@@ -222,18 +278,60 @@ define_input_config! {
     controller_cfg_name: SnesControllerConfig,
     input_cfg_name: SnesInputConfig,
     buttons: [
-        up: default Up,
-        left: default Left,
-        right: default Right,
-        down: default Down,
-        a: default S,
-        b: default X,
-        x: default A,
-        y: default Z,
-        l: default D,
-        r: default C,
-        start: default Return,
-        select: default RShift,
+        up: default Up, deck Hat(Up),
+        left: default Left, deck Hat(Left),
+        right: default Right, deck Hat(Right),
+        down: default Down, deck Hat(Down),
+        a: default S, deck Button(1),
+        b: default X, deck Button(0),
+        x: default A, deck Button(3),
+        y: default Z, deck Button(2),
+        l: default D, deck Button(4),
+        r: default C, deck Button(5),
+        start: default Return, deck Button(7),
+        select: default RShift, deck Button(6),
+    ],
+}
+
+define_input_config! {
+    controller_cfg_name: Atari2600ControllerConfig,
+    input_cfg_name: Atari2600InputConfig,
+    buttons: [
+        up: default Up, deck Hat(Up),
+        left: default Left, deck Hat(Left),
+        right: default Right, deck Hat(Right),
+        down: default Down, deck Hat(Down),
+        fire: default A, deck Button(0),
+        // Console switches; only player 1's mappings are used
+        reset: default Return, deck Button(7),
+        select: default RShift, deck Button(6),
+    ],
+}
+
+define_input_config! {
+    controller_cfg_name: ColecoVisionControllerConfig,
+    input_cfg_name: ColecoVisionInputConfig,
+    buttons: [
+        up: default Up, deck Hat(Up),
+        left: default Left, deck Hat(Left),
+        right: default Right, deck Hat(Right),
+        down: default Down, deck Hat(Down),
+        left_fire: default A, deck Button(0),
+        right_fire: default S, deck Button(1),
+        // Number pad; there are not enough Steam Deck buttons for all 12 keys, so only the keys that
+        // most games use to select a game mode are mapped
+        keypad_1: default Num1, deck Button(2),
+        keypad_2: default Num2, deck Button(3),
+        keypad_3: default Num3, deck Unmapped(_),
+        keypad_4: default Num4, deck Unmapped(_),
+        keypad_5: default Num5, deck Unmapped(_),
+        keypad_6: default Num6, deck Unmapped(_),
+        keypad_7: default Num7, deck Unmapped(_),
+        keypad_8: default Num8, deck Unmapped(_),
+        keypad_9: default Num9, deck Unmapped(_),
+        keypad_0: default Num0, deck Unmapped(_),
+        keypad_star: default Minus, deck Button(6),
+        keypad_pound: default Equals, deck Button(7),
     ],
 }
 
@@ -279,6 +377,155 @@ impl Default for GameBoyInputConfig<JoystickInput> {
     }
 }
 
+impl SteamDeckInputDefaults for GameBoyInputConfig<JoystickInput> {
+    fn fill_steam_deck_defaults(&mut self) {
+        if *self != Self::default() {
+            return;
+        }
+
+        *self = Self {
+            up: deck_input!(Hat(Up)),
+            left: deck_input!(Hat(Left)),
+            right: deck_input!(Hat(Right)),
+            down: deck_input!(Hat(Down)),
+            a: deck_input!(Button(1)),
+            b: deck_input!(Button(0)),
+            start: deck_input!(Button(7)),
+            select: deck_input!(Button(6)),
+        };
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ConfigDisplay)]
+pub struct PceInputConfig<Input> {
+    pub up: Option<Input>,
+    pub left: Option<Input>,
+    pub right: Option<Input>,
+    pub down: Option<Input>,
+    pub i: Option<Input>,
+    pub ii: Option<Input>,
+    pub run: Option<Input>,
+    pub select: Option<Input>,
+}
+
+impl Default for PceInputConfig<KeyboardInput> {
+    fn default() -> Self {
+        Self {
+            up: key_input!(Up),
+            left: key_input!(Left),
+            right: key_input!(Right),
+            down: key_input!(Down),
+            i: key_input!(S),
+            ii: key_input!(A),
+            run: key_input!(Return),
+            select: key_input!(RShift),
+        }
+    }
+}
+
+impl Default for PceInputConfig<JoystickInput> {
+    fn default() -> Self {
+        Self {
+            up: None,
+            left: None,
+            right: None,
+            down: None,
+            i: None,
+            ii: None,
+            run: None,
+            select: None,
+        }
+    }
+}
+
+impl SteamDeckInputDefaults for PceInputConfig<JoystickInput> {
+    fn fill_steam_deck_defaults(&mut self) {
+        if *self != Self::default() {
+            return;
+        }
+
+        *self = Self {
+            up: deck_input!(Hat(Up)),
+            left: deck_input!(Hat(Left)),
+            right: deck_input!(Hat(Right)),
+            down: deck_input!(Hat(Down)),
+            i: deck_input!(Button(1)),
+            ii: deck_input!(Button(0)),
+            run: deck_input!(Button(7)),
+            select: deck_input!(Button(6)),
+        };
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ConfigDisplay)]
+pub struct GbaInputConfig<Input> {
+    pub up: Option<Input>,
+    pub left: Option<Input>,
+    pub right: Option<Input>,
+    pub down: Option<Input>,
+    pub a: Option<Input>,
+    pub b: Option<Input>,
+    pub l: Option<Input>,
+    pub r: Option<Input>,
+    pub start: Option<Input>,
+    pub select: Option<Input>,
+}
+
+impl Default for GbaInputConfig<KeyboardInput> {
+    fn default() -> Self {
+        Self {
+            up: key_input!(Up),
+            left: key_input!(Left),
+            right: key_input!(Right),
+            down: key_input!(Down),
+            a: key_input!(A),
+            b: key_input!(S),
+            l: key_input!(Q),
+            r: key_input!(W),
+            start: key_input!(Return),
+            select: key_input!(RShift),
+        }
+    }
+}
+
+impl Default for GbaInputConfig<JoystickInput> {
+    fn default() -> Self {
+        Self {
+            up: None,
+            left: None,
+            right: None,
+            down: None,
+            a: None,
+            b: None,
+            l: None,
+            r: None,
+            start: None,
+            select: None,
+        }
+    }
+}
+
+impl SteamDeckInputDefaults for GbaInputConfig<JoystickInput> {
+    fn fill_steam_deck_defaults(&mut self) {
+        if *self != Self::default() {
+            return;
+        }
+
+        *self = Self {
+            up: deck_input!(Hat(Up)),
+            left: deck_input!(Hat(Left)),
+            right: deck_input!(Hat(Right)),
+            down: deck_input!(Hat(Down)),
+            a: deck_input!(Button(1)),
+            b: deck_input!(Button(0)),
+            l: deck_input!(Button(4)),
+            r: deck_input!(Button(5)),
+            start: deck_input!(Button(7)),
+            select: deck_input!(Button(6)),
+        };
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ConfigDisplay)]
 pub struct SuperScopeConfig {
     pub fire: Option<KeyboardOrMouseInput>,
@@ -298,6 +545,20 @@ impl Default for SuperScopeConfig {
     }
 }
 
+/// Sensitivity settings for the SMS Paddle, Sports Pad, and Graphic Board, which are controlled
+/// using mouse movement or the first two axes of any connected joystick
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ConfigDisplay)]
+pub struct SmsPeripheralConfig {
+    pub mouse_sensitivity: f64,
+    pub stick_sensitivity: f64,
+}
+
+impl Default for SmsPeripheralConfig {
+    fn default() -> Self {
+        Self { mouse_sensitivity: 1.0, stick_sensitivity: 1.0 }
+    }
+}
+
 #[derive(
     Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, EnumDisplay, EnumFromStr,
 )]
@@ -307,6 +568,15 @@ pub enum SnesControllerType {
     SuperScope,
 }
 
+/// A button sequence that is played back when its trigger input is pressed. See
+/// [`crate::input::macros`] for the sequence syntax.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InputMacroConfig {
+    pub keyboard_trigger: Option<KeyboardInput>,
+    pub joystick_trigger: Option<JoystickInput>,
+    pub sequence: String,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, ConfigDisplay, Serialize, Deserialize)]
 pub struct HotkeyConfig {
     #[serde(default = "default_quit")]
@@ -331,6 +601,31 @@ pub struct HotkeyConfig {
     pub rewind: Option<KeyboardInput>,
     #[serde(default = "default_open_debugger")]
     pub open_debugger: Option<KeyboardInput>,
+    #[serde(default = "default_music_dump")]
+    pub music_dump: Option<KeyboardInput>,
+    #[serde(default = "default_microphone")]
+    pub microphone: Option<KeyboardInput>,
+    #[serde(default = "default_dump_input_trace")]
+    pub dump_input_trace: Option<KeyboardInput>,
+    #[serde(default = "default_next_save_state_slot")]
+    pub next_save_state_slot: Option<KeyboardInput>,
+    #[serde(default = "default_previous_save_state_slot")]
+    pub previous_save_state_slot: Option<KeyboardInput>,
+    #[serde(default = "default_undo_load_state")]
+    pub undo_load_state: Option<KeyboardInput>,
+    #[serde(default = "default_record_input_macro")]
+    pub record_input_macro: Option<KeyboardInput>,
+    #[serde(default = "default_set_practice_loop_point")]
+    pub set_practice_loop_point: Option<KeyboardInput>,
+    #[serde(default = "default_restart_practice_attempt")]
+    pub restart_practice_attempt: Option<KeyboardInput>,
+    #[serde(default = "default_clear_practice_loop_point")]
+    pub clear_practice_loop_point: Option<KeyboardInput>,
+    #[serde(default = "default_open_quick_menu")]
+    pub open_quick_menu: Option<KeyboardInput>,
+    /// Gamepad input that also opens the quick menu, so that it can be reached without a keyboard
+    #[serde(default)]
+    pub open_quick_menu_joystick: Option<JoystickInput>,
 }
 
 impl Default for HotkeyConfig {
@@ -347,6 +642,18 @@ impl Default for HotkeyConfig {
             fast_forward: default_fast_forward(),
             rewind: default_rewind(),
             open_debugger: default_open_debugger(),
+            music_dump: default_music_dump(),
+            microphone: default_microphone(),
+            dump_input_trace: default_dump_input_trace(),
+            next_save_state_slot: default_next_save_state_slot(),
+            previous_save_state_slot: default_previous_save_state_slot(),
+            undo_load_state: default_undo_load_state(),
+            record_input_macro: default_record_input_macro(),
+            set_practice_loop_point: default_set_practice_loop_point(),
+            restart_practice_attempt: default_restart_practice_attempt(),
+            clear_practice_loop_point: default_clear_practice_loop_point(),
+            open_quick_menu: default_open_quick_menu(),
+            open_quick_menu_joystick: None,
         }
     }
 }
@@ -394,3 +701,47 @@ fn default_rewind() -> Option<KeyboardInput> {
 fn default_open_debugger() -> Option<KeyboardInput> {
     key_input!(Quote)
 }
+
+fn default_music_dump() -> Option<KeyboardInput> {
+    key_input!(F12)
+}
+
+fn default_microphone() -> Option<KeyboardInput> {
+    key_input!(F10)
+}
+
+fn default_dump_input_trace() -> Option<KeyboardInput> {
+    key_input!(F8)
+}
+
+fn default_next_save_state_slot() -> Option<KeyboardInput> {
+    key_input!(F4)
+}
+
+fn default_previous_save_state_slot() -> Option<KeyboardInput> {
+    key_input!(F3)
+}
+
+fn default_undo_load_state() -> Option<KeyboardInput> {
+    key_input!(F7)
+}
+
+fn default_record_input_macro() -> Option<KeyboardInput> {
+    key_input!(F11)
+}
+
+fn default_set_practice_loop_point() -> Option<KeyboardInput> {
+    key_input!(LeftBracket)
+}
+
+fn default_restart_practice_attempt() -> Option<KeyboardInput> {
+    key_input!(RightBracket)
+}
+
+fn default_clear_practice_loop_point() -> Option<KeyboardInput> {
+    key_input!(Backslash)
+}
+
+fn default_open_quick_menu() -> Option<KeyboardInput> {
+    key_input!(Backspace)
+}
@@ -1,27 +1,46 @@
+pub mod macros;
+mod nes;
+mod pico;
+mod smsgg;
+
 use crate::config::input::{
-    AxisDirection, GameBoyInputConfig, GenesisInputConfig, HatDirection, HotkeyConfig,
+    Atari2600InputConfig, AxisDirection, ColecoVisionInputConfig, GameBoyInputConfig,
+    GbaInputConfig, GenesisInputConfig, HatDirection, HotkeyConfig, InputMacroConfig,
     JoystickAction, JoystickDeviceId, JoystickInput, KeyboardInput, KeyboardOrMouseInput,
-    NesInputConfig, SmsGgInputConfig, SnesControllerType, SnesInputConfig, SuperScopeConfig,
+    NesInputConfig, PceInputConfig, SmsGgInputConfig, SmsPeripheralConfig, SnesControllerType,
+    SnesInputConfig, SuperScopeConfig,
 };
 use crate::mainloop::{NativeEmulatorError, NativeEmulatorResult};
+use atari2600_core::input::Atari2600Inputs;
+use colecovision_core::input::ColecoVisionInputs;
 use gb_core::inputs::GameBoyInputs;
+use gba_core::input::GbaInputs;
+use genesis_core::pico::PicoInputs;
 use genesis_core::GenesisInputs;
 use jgenesis_common::frontend::FrameSize;
 use jgenesis_renderer::renderer::DisplayArea;
-use nes_core::input::NesInputs;
+use macros::{MacroButton, MacroMapper};
+use nes::NesExpansionMapper;
+use nes_core::input::{NesExpansionDevice, NesInputs};
+use pce_core::input::PceInputs;
+use pico::PicoPenMapper;
 use sdl2::event::{Event, WindowEvent};
 use sdl2::joystick::{HatState, Joystick};
 use sdl2::keyboard::Keycode;
 use sdl2::mouse::MouseButton;
 use sdl2::JoystickSubsystem;
-use smsgg_core::SmsGgInputs;
+use smsgg::SmsPeripheralMapper;
+use smsgg_core::{SmsControllerType, SmsGgInputs};
 use snes_core::input::{SnesInputDevice, SnesInputs, SnesJoypadState, SuperScopeState};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Player {
     One,
     Two,
+    // Players 3 and 4 are only used for Genesis multitaps
+    Three,
+    Four,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -114,6 +133,96 @@ impl NesButton {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Atari2600Button {
+    Up(Player),
+    Left(Player),
+    Right(Player),
+    Down(Player),
+    Fire(Player),
+    Reset,
+    Select,
+}
+
+impl Atari2600Button {
+    #[must_use]
+    pub fn player(self) -> Player {
+        match self {
+            Self::Up(player)
+            | Self::Left(player)
+            | Self::Right(player)
+            | Self::Down(player)
+            | Self::Fire(player) => player,
+            Self::Reset | Self::Select => Player::One,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColecoVisionButton {
+    Up(Player),
+    Left(Player),
+    Right(Player),
+    Down(Player),
+    LeftFire(Player),
+    RightFire(Player),
+    Keypad1(Player),
+    Keypad2(Player),
+    Keypad3(Player),
+    Keypad4(Player),
+    Keypad5(Player),
+    Keypad6(Player),
+    Keypad7(Player),
+    Keypad8(Player),
+    Keypad9(Player),
+    Keypad0(Player),
+    KeypadStar(Player),
+    KeypadPound(Player),
+}
+
+impl ColecoVisionButton {
+    #[must_use]
+    pub fn player(self) -> Player {
+        match self {
+            Self::Up(player)
+            | Self::Left(player)
+            | Self::Right(player)
+            | Self::Down(player)
+            | Self::LeftFire(player)
+            | Self::RightFire(player)
+            | Self::Keypad1(player)
+            | Self::Keypad2(player)
+            | Self::Keypad3(player)
+            | Self::Keypad4(player)
+            | Self::Keypad5(player)
+            | Self::Keypad6(player)
+            | Self::Keypad7(player)
+            | Self::Keypad8(player)
+            | Self::Keypad9(player)
+            | Self::Keypad0(player)
+            | Self::KeypadStar(player)
+            | Self::KeypadPound(player) => player,
+        }
+    }
+
+    // Returns the digit for number pad keys 0-9
+    fn keypad_digit(self) -> Option<usize> {
+        match self {
+            Self::Keypad0(..) => Some(0),
+            Self::Keypad1(..) => Some(1),
+            Self::Keypad2(..) => Some(2),
+            Self::Keypad3(..) => Some(3),
+            Self::Keypad4(..) => Some(4),
+            Self::Keypad5(..) => Some(5),
+            Self::Keypad6(..) => Some(6),
+            Self::Keypad7(..) => Some(7),
+            Self::Keypad8(..) => Some(8),
+            Self::Keypad9(..) => Some(9),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SuperScopeButton {
     Fire,
@@ -172,6 +281,32 @@ pub enum GameBoyButton {
     Select,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GbaButton {
+    Up,
+    Left,
+    Right,
+    Down,
+    A,
+    B,
+    L,
+    R,
+    Start,
+    Select,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PceButton {
+    Up,
+    Left,
+    Right,
+    Down,
+    I,
+    II,
+    Run,
+    Select,
+}
+
 pub trait MappableInputs<Button> {
     fn set_field(&mut self, button: Button, value: bool);
 
@@ -191,6 +326,7 @@ impl MappableInputs<SmsGgButton> for SmsGgInputs {
         let joypad_state = match button.player() {
             Player::One => &mut self.p1,
             Player::Two => &mut self.p2,
+            Player::Three | Player::Four => return,
         };
 
         match button {
@@ -221,6 +357,8 @@ impl MappableInputs<GenesisButton> for GenesisInputs {
         let joypad_state = match button.player() {
             Player::One => &mut self.p1,
             Player::Two => &mut self.p2,
+            Player::Three => &mut self.p3,
+            Player::Four => &mut self.p4,
         };
 
         match button {
@@ -251,11 +389,42 @@ impl MappableInputs<GenesisButton> for GenesisInputs {
     fn handle_mouse_leave(&mut self) {}
 }
 
+// The Pico's direction buttons and red button are mapped to P1's d-pad and A; the pen and
+// storyware are handled by PicoPenMapper
+impl MappableInputs<GenesisButton> for PicoInputs {
+    fn set_field(&mut self, button: GenesisButton, value: bool) {
+        if button.player() != Player::One {
+            return;
+        }
+
+        match button {
+            GenesisButton::Up(..) => self.up = value,
+            GenesisButton::Left(..) => self.left = value,
+            GenesisButton::Right(..) => self.right = value,
+            GenesisButton::Down(..) => self.down = value,
+            GenesisButton::A(..) => self.red = value,
+            _ => {}
+        }
+    }
+
+    fn handle_mouse_motion(
+        &mut self,
+        _x: i32,
+        _y: i32,
+        _frame_size: FrameSize,
+        _display_area: DisplayArea,
+    ) {
+    }
+
+    fn handle_mouse_leave(&mut self) {}
+}
+
 impl MappableInputs<NesButton> for NesInputs {
     fn set_field(&mut self, button: NesButton, value: bool) {
         let joypad_state = match button.player() {
             Player::One => &mut self.p1,
             Player::Two => &mut self.p2,
+            Player::Three | Player::Four => return,
         };
 
         match button {
@@ -307,6 +476,7 @@ impl MappableInputs<SnesButton> for SnesInputs {
                 SnesInputDevice::Controller(joypad_state) => joypad_state,
                 SnesInputDevice::SuperScope(..) => return,
             },
+            Player::Three | Player::Four => return,
         };
 
         match button {
@@ -396,6 +566,133 @@ impl MappableInputs<GameBoyButton> for GameBoyInputs {
     fn handle_mouse_leave(&mut self) {}
 }
 
+impl MappableInputs<Atari2600Button> for Atari2600Inputs {
+    fn set_field(&mut self, button: Atari2600Button, value: bool) {
+        let joystick_state = match button.player() {
+            Player::One => &mut self.p1,
+            Player::Two => &mut self.p2,
+            Player::Three | Player::Four => return,
+        };
+
+        match button {
+            Atari2600Button::Up(..) => joystick_state.up = value,
+            Atari2600Button::Left(..) => joystick_state.left = value,
+            Atari2600Button::Right(..) => joystick_state.right = value,
+            Atari2600Button::Down(..) => joystick_state.down = value,
+            Atari2600Button::Fire(..) => joystick_state.fire = value,
+            Atari2600Button::Reset => self.reset = value,
+            Atari2600Button::Select => self.select = value,
+        }
+    }
+
+    fn handle_mouse_motion(
+        &mut self,
+        _x: i32,
+        _y: i32,
+        _frame_size: FrameSize,
+        _display_area: DisplayArea,
+    ) {
+    }
+
+    fn handle_mouse_leave(&mut self) {}
+}
+
+impl MappableInputs<ColecoVisionButton> for ColecoVisionInputs {
+    fn set_field(&mut self, button: ColecoVisionButton, value: bool) {
+        let controller_state = match button.player() {
+            Player::One => &mut self.p1,
+            Player::Two => &mut self.p2,
+            Player::Three | Player::Four => return,
+        };
+
+        if let Some(digit) = button.keypad_digit() {
+            controller_state.keypad_digits[digit] = value;
+            return;
+        }
+
+        match button {
+            ColecoVisionButton::Up(..) => controller_state.up = value,
+            ColecoVisionButton::Left(..) => controller_state.left = value,
+            ColecoVisionButton::Right(..) => controller_state.right = value,
+            ColecoVisionButton::Down(..) => controller_state.down = value,
+            ColecoVisionButton::LeftFire(..) => controller_state.left_fire = value,
+            ColecoVisionButton::RightFire(..) => controller_state.right_fire = value,
+            ColecoVisionButton::KeypadStar(..) => controller_state.keypad_star = value,
+            ColecoVisionButton::KeypadPound(..) => controller_state.keypad_pound = value,
+            _ => {}
+        }
+    }
+
+    fn handle_mouse_motion(
+        &mut self,
+        _x: i32,
+        _y: i32,
+        _frame_size: FrameSize,
+        _display_area: DisplayArea,
+    ) {
+    }
+
+    fn handle_mouse_leave(&mut self) {}
+}
+
+impl MappableInputs<GbaButton> for GbaInputs {
+    fn set_field(&mut self, button: GbaButton, value: bool) {
+        use GbaButton::*;
+
+        match button {
+            Up => self.up = value,
+            Left => self.left = value,
+            Right => self.right = value,
+            Down => self.down = value,
+            A => self.a = value,
+            B => self.b = value,
+            L => self.l = value,
+            R => self.r = value,
+            Start => self.start = value,
+            Select => self.select = value,
+        }
+    }
+
+    fn handle_mouse_motion(
+        &mut self,
+        _x: i32,
+        _y: i32,
+        _frame_size: FrameSize,
+        _display_area: DisplayArea,
+    ) {
+    }
+
+    fn handle_mouse_leave(&mut self) {}
+}
+
+impl MappableInputs<PceButton> for PceInputs {
+    fn set_field(&mut self, button: PceButton, value: bool) {
+        use PceButton::*;
+
+        match button {
+            Up => self.p1.up = value,
+            Left => self.p1.left = value,
+            Right => self.p1.right = value,
+            Down => self.p1.down = value,
+            I => self.p1.i = value,
+            II => self.p1.ii = value,
+            Run => self.p1.run = value,
+            Select => self.p1.select = value,
+        }
+    }
+
+    fn handle_mouse_motion(
+        &mut self,
+        _x: i32,
+        _y: i32,
+        _frame_size: FrameSize,
+        _display_area: DisplayArea,
+    ) {
+    }
+
+    fn handle_mouse_leave(&mut self) {}
+}
+
 #[derive(Default)]
 pub struct Joysticks {
     joysticks: HashMap<u32, Joystick>,
@@ -499,8 +796,30 @@ impl TryFrom<KeyboardOrMouseInput> for KeycodeOrMouseButton {
     }
 }
 
+/// Maps raw mouse, keyboard, and joystick axis events to inputs for peripherals that cannot be
+/// expressed as button mappings
+trait PeripheralMapper<Inputs> {
+    fn handle_event(
+        &mut self,
+        event: &Event,
+        emulator_window_id: u32,
+        display_info: Option<(FrameSize, DisplayArea)>,
+    );
+
+    /// Called once per frame, before the inputs are passed to the emulator.
+    fn update_inputs(&mut self, inputs: &mut Inputs);
+}
+
+/// A change in which gamepads with mapped inputs are connected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ControllerHotplug {
+    MappedConnected,
+    MappedDisconnected,
+}
+
 pub(crate) struct InputMapper<Inputs, Button> {
     inputs: Inputs,
+    peripheral_mapper: Option<Box<dyn PeripheralMapper<Inputs>>>,
     joystick_subsystem: JoystickSubsystem,
     joysticks: Joysticks,
     axis_deadzone: i16,
@@ -508,9 +827,14 @@ pub(crate) struct InputMapper<Inputs, Button> {
     raw_joystick_mapping: HashMap<JoystickInput, Vec<Button>>,
     joystick_mapping: HashMap<(u32, JoystickAction), Vec<Button>>,
     key_or_mouse_mapping: HashMap<KeycodeOrMouseButton, Vec<Button>>,
+    macro_mapper: MacroMapper<Button>,
 }
 
 impl<Inputs, Button> InputMapper<Inputs, Button> {
+    pub(crate) fn joysticks(&self) -> &Joysticks {
+        &self.joysticks
+    }
+
     pub(crate) fn joysticks_mut(&mut self) -> (&mut Joysticks, &JoystickSubsystem) {
         (&mut self.joysticks, &self.joystick_subsystem)
     }
@@ -527,6 +851,7 @@ impl<Inputs, Button> InputMapper<Inputs, Button> {
     ) -> Self {
         Self {
             inputs,
+            peripheral_mapper: None,
             joystick_subsystem,
             joysticks: Joysticks::new(),
             axis_deadzone,
@@ -534,20 +859,21 @@ impl<Inputs, Button> InputMapper<Inputs, Button> {
             raw_joystick_mapping: joystick_mapping,
             joystick_mapping: HashMap::new(),
             key_or_mouse_mapping,
+            macro_mapper: MacroMapper::new(),
         }
     }
 }
 
 macro_rules! inputs_array {
-    ($p1_config:expr, $p2_config:expr, [$($field:ident -> $button:expr),* $(,)?] $(, extra: $extra:tt $(,)?)?) => {
+    ($p1_config:expr, $p2_config:expr, [$($field:ident -> $button:expr),* $(,)?] $(, extra: $($extra:tt),+ $(,)?)?) => {
         [
             $(
                 ($p1_config.$field, $button(Player::One)),
                 ($p2_config.$field, $button(Player::Two)),
             )*
-            $(
-                $extra
-            )?
+            $($(
+                $extra,
+            )+)?
         ]
     }
 }
@@ -581,21 +907,30 @@ macro_rules! smsgg_input_array {
 }
 
 macro_rules! genesis_input_array {
-    ($p1_config:expr, $p2_config:expr) => {
-        inputs_array!($p1_config, $p2_config, [
-            up -> GenesisButton::Up,
-            left -> GenesisButton::Left,
-            right -> GenesisButton::Right,
-            down -> GenesisButton::Down,
-            a -> GenesisButton::A,
-            b -> GenesisButton::B,
-            c -> GenesisButton::C,
-            x -> GenesisButton::X,
-            y -> GenesisButton::Y,
-            z -> GenesisButton::Z,
-            start -> GenesisButton::Start,
-            mode -> GenesisButton::Mode,
-        ])
+    ($config:expr) => {
+        [
+            ($config.p1, Player::One),
+            ($config.p2, Player::Two),
+            ($config.p3, Player::Three),
+            ($config.p4, Player::Four),
+        ]
+        .into_iter()
+        .flat_map(|(controller_config, player)| {
+            flat_inputs_array!(controller_config, [
+                up -> GenesisButton::Up(player),
+                left -> GenesisButton::Left(player),
+                right -> GenesisButton::Right(player),
+                down -> GenesisButton::Down(player),
+                a -> GenesisButton::A(player),
+                b -> GenesisButton::B(player),
+                c -> GenesisButton::C(player),
+                x -> GenesisButton::X(player),
+                y -> GenesisButton::Y(player),
+                z -> GenesisButton::Z(player),
+                start -> GenesisButton::Start(player),
+                mode -> GenesisButton::Mode(player),
+            ])
+        })
     }
 }
 
@@ -648,6 +983,81 @@ macro_rules! gb_input_array {
     }
 }
 
+macro_rules! atari2600_input_array {
+    ($p1_config:expr, $p2_config:expr) => {
+        inputs_array!(
+            $p1_config,
+            $p2_config,
+            [
+                up -> Atari2600Button::Up,
+                left -> Atari2600Button::Left,
+                right -> Atari2600Button::Right,
+                down -> Atari2600Button::Down,
+                fire -> Atari2600Button::Fire,
+            ],
+            extra: ($p1_config.reset, Atari2600Button::Reset),
+            ($p1_config.select, Atari2600Button::Select),
+        )
+    }
+}
+
+macro_rules! colecovision_input_array {
+    ($p1_config:expr, $p2_config:expr) => {
+        inputs_array!($p1_config, $p2_config, [
+            up -> ColecoVisionButton::Up,
+            left -> ColecoVisionButton::Left,
+            right -> ColecoVisionButton::Right,
+            down -> ColecoVisionButton::Down,
+            left_fire -> ColecoVisionButton::LeftFire,
+            right_fire -> ColecoVisionButton::RightFire,
+            keypad_1 -> ColecoVisionButton::Keypad1,
+            keypad_2 -> ColecoVisionButton::Keypad2,
+            keypad_3 -> ColecoVisionButton::Keypad3,
+            keypad_4 -> ColecoVisionButton::Keypad4,
+            keypad_5 -> ColecoVisionButton::Keypad5,
+            keypad_6 -> ColecoVisionButton::Keypad6,
+            keypad_7 -> ColecoVisionButton::Keypad7,
+            keypad_8 -> ColecoVisionButton::Keypad8,
+            keypad_9 -> ColecoVisionButton::Keypad9,
+            keypad_0 -> ColecoVisionButton::Keypad0,
+            keypad_star -> ColecoVisionButton::KeypadStar,
+            keypad_pound -> ColecoVisionButton::KeypadPound,
+        ])
+    }
+}
+
+macro_rules! gba_input_array {
+    ($config:expr) => {
+        flat_inputs_array!($config, [
+            up -> GbaButton::Up,
+            left -> GbaButton::Left,
+            right -> GbaButton::Right,
+            down -> GbaButton::Down,
+            a -> GbaButton::A,
+            b -> GbaButton::B,
+            l -> GbaButton::L,
+            r -> GbaButton::R,
+            start -> GbaButton::Start,
+            select -> GbaButton::Select,
+        ])
+    }
+}
+
+macro_rules! pce_input_array {
+    ($config:expr) => {
+        flat_inputs_array!($config, [
+            up -> PceButton::Up,
+            left -> PceButton::Left,
+            right -> PceButton::Right,
+            down -> PceButton::Down,
+            i -> PceButton::I,
+            ii -> PceButton::II,
+            run -> PceButton::Run,
+            select -> PceButton::Select,
+        ])
+    }
+}
+
 macro_rules! impl_generate_keyboard_mapping {
     ($name:ident, $config_t:ident, $button_t:ty, |$config:ident| $inputs_arr:expr $(,)?) => {
         fn $name(
@@ -706,7 +1116,7 @@ impl_generate_mapping_fns!(
     generate_genesis_joystick_mapping,
     GenesisInputConfig,
     GenesisButton,
-    |config| genesis_input_array!(config.p1, config.p2)
+    |config| genesis_input_array!(config)
 );
 
 impl_generate_mapping_fns!(
@@ -733,26 +1143,66 @@ impl_generate_mapping_fns!(
     |config| gb_input_array!(config)
 );
 
+impl_generate_mapping_fns!(
+    generate_atari2600_keyboard_mapping,
+    generate_atari2600_joystick_mapping,
+    Atari2600InputConfig,
+    Atari2600Button,
+    |config| atari2600_input_array!(config.p1, config.p2)
+);
+
+impl_generate_mapping_fns!(
+    generate_colecovision_keyboard_mapping,
+    generate_colecovision_joystick_mapping,
+    ColecoVisionInputConfig,
+    ColecoVisionButton,
+    |config| colecovision_input_array!(config.p1, config.p2)
+);
+
+impl_generate_mapping_fns!(
+    generate_gba_keyboard_mapping,
+    generate_gba_joystick_mapping,
+    GbaInputConfig,
+    GbaButton,
+    |config| gba_input_array!(config)
+);
+
+impl_generate_mapping_fns!(
+    generate_pce_keyboard_mapping,
+    generate_pce_joystick_mapping,
+    PceInputConfig,
+    PceButton,
+    |config| pce_input_array!(config)
+);
+
 impl InputMapper<SmsGgInputs, SmsGgButton> {
     pub(crate) fn new_smsgg(
         joystick_subsystem: JoystickSubsystem,
+        p1_controller_type: SmsControllerType,
         keyboard_inputs: SmsGgInputConfig<KeyboardInput>,
         joystick_inputs: SmsGgInputConfig<JoystickInput>,
+        peripheral_config: SmsPeripheralConfig,
         axis_deadzone: i16,
     ) -> NativeEmulatorResult<Self> {
-        Ok(Self::new_generic(
+        let mut mapper = Self::new_generic(
             joystick_subsystem,
             generate_smsgg_keyboard_mapping(keyboard_inputs)?,
             generate_smsgg_joystick_mapping(joystick_inputs),
             HashMap::new(),
             axis_deadzone,
-        ))
+        );
+        mapper.peripheral_mapper =
+            new_sms_peripheral_mapper(p1_controller_type, peripheral_config, axis_deadzone);
+
+        Ok(mapper)
     }
 
     pub(crate) fn reload_config(
         &mut self,
+        p1_controller_type: SmsControllerType,
         keyboard_inputs: SmsGgInputConfig<KeyboardInput>,
         joystick_inputs: SmsGgInputConfig<JoystickInput>,
+        peripheral_config: SmsPeripheralConfig,
         axis_deadzone: i16,
     ) -> NativeEmulatorResult<()> {
         self.reload_config_generic(
@@ -761,11 +1211,24 @@ impl InputMapper<SmsGgInputs, SmsGgButton> {
             HashMap::new(),
             axis_deadzone,
         );
+        self.peripheral_mapper =
+            new_sms_peripheral_mapper(p1_controller_type, peripheral_config, axis_deadzone);
 
         Ok(())
     }
 }
 
+fn new_sms_peripheral_mapper(
+    p1_controller_type: SmsControllerType,
+    peripheral_config: SmsPeripheralConfig,
+    axis_deadzone: i16,
+) -> Option<Box<dyn PeripheralMapper<SmsGgInputs>>> {
+    (p1_controller_type != SmsControllerType::Joypad).then(|| {
+        Box::new(SmsPeripheralMapper::new(p1_controller_type, peripheral_config, axis_deadzone))
+            as Box<dyn PeripheralMapper<SmsGgInputs>>
+    })
+}
+
 impl InputMapper<GenesisInputs, GenesisButton> {
     pub(crate) fn new_genesis(
         joystick_subsystem: JoystickSubsystem,
@@ -799,24 +1262,65 @@ impl InputMapper<GenesisInputs, GenesisButton> {
     }
 }
 
+impl InputMapper<PicoInputs, GenesisButton> {
+    pub(crate) fn new_pico(
+        joystick_subsystem: JoystickSubsystem,
+        keyboard_inputs: GenesisInputConfig<KeyboardInput>,
+        joystick_inputs: GenesisInputConfig<JoystickInput>,
+        axis_deadzone: i16,
+    ) -> NativeEmulatorResult<Self> {
+        let mut mapper = Self::new_generic(
+            joystick_subsystem,
+            generate_genesis_keyboard_mapping(keyboard_inputs)?,
+            generate_genesis_joystick_mapping(joystick_inputs),
+            HashMap::new(),
+            axis_deadzone,
+        );
+        mapper.peripheral_mapper = Some(Box::new(PicoPenMapper::new()));
+
+        Ok(mapper)
+    }
+
+    pub(crate) fn reload_config(
+        &mut self,
+        keyboard_inputs: GenesisInputConfig<KeyboardInput>,
+        joystick_inputs: GenesisInputConfig<JoystickInput>,
+        axis_deadzone: i16,
+    ) -> NativeEmulatorResult<()> {
+        self.reload_config_generic(
+            generate_genesis_keyboard_mapping(keyboard_inputs)?,
+            generate_genesis_joystick_mapping(joystick_inputs),
+            HashMap::new(),
+            axis_deadzone,
+        );
+
+        Ok(())
+    }
+}
+
 impl InputMapper<NesInputs, NesButton> {
     pub(crate) fn new_nes(
         joystick_subsystem: JoystickSubsystem,
+        expansion_device: NesExpansionDevice,
         keyboard_inputs: NesInputConfig<KeyboardInput>,
         joystick_inputs: NesInputConfig<JoystickInput>,
         axis_deadzone: i16,
     ) -> NativeEmulatorResult<Self> {
-        Ok(Self::new_generic(
+        let mut mapper = Self::new_generic(
             joystick_subsystem,
             generate_nes_keyboard_mapping(keyboard_inputs)?,
             generate_nes_joystick_mapping(joystick_inputs),
             HashMap::new(),
             axis_deadzone,
-        ))
+        );
+        mapper.peripheral_mapper = new_nes_expansion_mapper(expansion_device);
+
+        Ok(mapper)
     }
 
     pub(crate) fn reload_config(
         &mut self,
+        expansion_device: NesExpansionDevice,
         keyboard_inputs: NesInputConfig<KeyboardInput>,
         joystick_inputs: NesInputConfig<JoystickInput>,
         axis_deadzone: i16,
@@ -827,11 +1331,20 @@ impl InputMapper<NesInputs, NesButton> {
             HashMap::new(),
             axis_deadzone,
         );
+        self.peripheral_mapper = new_nes_expansion_mapper(expansion_device);
 
         Ok(())
     }
 }
 
+fn new_nes_expansion_mapper(
+    expansion_device: NesExpansionDevice,
+) -> Option<Box<dyn PeripheralMapper<NesInputs>>> {
+    (expansion_device != NesExpansionDevice::None).then(|| {
+        Box::new(NesExpansionMapper::new(expansion_device)) as Box<dyn PeripheralMapper<NesInputs>>
+    })
+}
+
 fn generate_snes_key_or_mouse_mapping(
     super_scope_config: SuperScopeConfig,
 ) -> NativeEmulatorResult<HashMap<KeycodeOrMouseButton, Vec<SnesButton>>> {
@@ -933,6 +1446,138 @@ impl InputMapper<GameBoyInputs, GameBoyButton> {
     }
 }
 
+impl InputMapper<GbaInputs, GbaButton> {
+    pub(crate) fn new_gba(
+        joystick_subsystem: JoystickSubsystem,
+        keyboard_inputs: GbaInputConfig<KeyboardInput>,
+        joystick_inputs: GbaInputConfig<JoystickInput>,
+        axis_deadzone: i16,
+    ) -> NativeEmulatorResult<Self> {
+        Ok(Self::new_generic(
+            joystick_subsystem,
+            generate_gba_keyboard_mapping(keyboard_inputs)?,
+            generate_gba_joystick_mapping(joystick_inputs),
+            HashMap::new(),
+            axis_deadzone,
+        ))
+    }
+
+    pub(crate) fn reload_config(
+        &mut self,
+        keyboard_inputs: GbaInputConfig<KeyboardInput>,
+        joystick_inputs: GbaInputConfig<JoystickInput>,
+        axis_deadzone: i16,
+    ) -> NativeEmulatorResult<()> {
+        self.reload_config_generic(
+            generate_gba_keyboard_mapping(keyboard_inputs)?,
+            generate_gba_joystick_mapping(joystick_inputs),
+            HashMap::new(),
+            axis_deadzone,
+        );
+
+        Ok(())
+    }
+}
+
+impl InputMapper<Atari2600Inputs, Atari2600Button> {
+    pub(crate) fn new_atari2600(
+        joystick_subsystem: JoystickSubsystem,
+        keyboard_inputs: Atari2600InputConfig<KeyboardInput>,
+        joystick_inputs: Atari2600InputConfig<JoystickInput>,
+        axis_deadzone: i16,
+    ) -> NativeEmulatorResult<Self> {
+        Ok(Self::new_generic(
+            joystick_subsystem,
+            generate_atari2600_keyboard_mapping(keyboard_inputs)?,
+            generate_atari2600_joystick_mapping(joystick_inputs),
+            HashMap::new(),
+            axis_deadzone,
+        ))
+    }
+
+    pub(crate) fn reload_config(
+        &mut self,
+        keyboard_inputs: Atari2600InputConfig<KeyboardInput>,
+        joystick_inputs: Atari2600InputConfig<JoystickInput>,
+        axis_deadzone: i16,
+    ) -> NativeEmulatorResult<()> {
+        self.reload_config_generic(
+            generate_atari2600_keyboard_mapping(keyboard_inputs)?,
+            generate_atari2600_joystick_mapping(joystick_inputs),
+            HashMap::new(),
+            axis_deadzone,
+        );
+
+        Ok(())
+    }
+}
+
+impl InputMapper<ColecoVisionInputs, ColecoVisionButton> {
+    pub(crate) fn new_colecovision(
+        joystick_subsystem: JoystickSubsystem,
+        keyboard_inputs: ColecoVisionInputConfig<KeyboardInput>,
+        joystick_inputs: ColecoVisionInputConfig<JoystickInput>,
+        axis_deadzone: i16,
+    ) -> NativeEmulatorResult<Self> {
+        Ok(Self::new_generic(
+            joystick_subsystem,
+            generate_colecovision_keyboard_mapping(keyboard_inputs)?,
+            generate_colecovision_joystick_mapping(joystick_inputs),
+            HashMap::new(),
+            axis_deadzone,
+        ))
+    }
+
+    pub(crate) fn reload_config(
+        &mut self,
+        keyboard_inputs: ColecoVisionInputConfig<KeyboardInput>,
+        joystick_inputs: ColecoVisionInputConfig<JoystickInput>,
+        axis_deadzone: i16,
+    ) -> NativeEmulatorResult<()> {
+        self.reload_config_generic(
+            generate_colecovision_keyboard_mapping(keyboard_inputs)?,
+            generate_colecovision_joystick_mapping(joystick_inputs),
+            HashMap::new(),
+            axis_deadzone,
+        );
+
+        Ok(())
+    }
+}
+
+impl InputMapper<PceInputs, PceButton> {
+    pub(crate) fn new_pce(
+        joystick_subsystem: JoystickSubsystem,
+        keyboard_inputs: PceInputConfig<KeyboardInput>,
+        joystick_inputs: PceInputConfig<JoystickInput>,
+        axis_deadzone: i16,
+    ) -> NativeEmulatorResult<Self> {
+        Ok(Self::new_generic(
+            joystick_subsystem,
+            generate_pce_keyboard_mapping(keyboard_inputs)?,
+            generate_pce_joystick_mapping(joystick_inputs),
+            HashMap::new(),
+            axis_deadzone,
+        ))
+    }
+
+    pub(crate) fn reload_config(
+        &mut self,
+        keyboard_inputs: PceInputConfig<KeyboardInput>,
+        joystick_inputs: PceInputConfig<JoystickInput>,
+        axis_deadzone: i16,
+    ) -> NativeEmulatorResult<()> {
+        self.reload_config_generic(
+            generate_pce_keyboard_mapping(keyboard_inputs)?,
+            generate_pce_joystick_mapping(joystick_inputs),
+            HashMap::new(),
+            axis_deadzone,
+        );
+
+        Ok(())
+    }
+}
+
 fn set_default_snes_inputs(
     inputs: &mut SnesInputs,
     p2_controller_type: SnesControllerType,
@@ -954,7 +1599,7 @@ fn set_default_snes_inputs(
 impl<Inputs, Button> InputMapper<Inputs, Button>
 where
     Inputs: Default + MappableInputs<Button>,
-    Button: Copy,
+    Button: MacroButton,
 {
     fn new_generic(
         joystick_subsystem: JoystickSubsystem,
@@ -988,21 +1633,39 @@ where
         self.update_input_mapping();
     }
 
-    pub(crate) fn device_added(&mut self, device_id: u32) -> NativeEmulatorResult<()> {
+    pub(crate) fn device_added(
+        &mut self,
+        device_id: u32,
+    ) -> NativeEmulatorResult<Option<ControllerHotplug>> {
+        let prev_mapped_count = self.mapped_joystick_count();
+
         self.joysticks.device_added(device_id, &self.joystick_subsystem)?;
         self.update_input_mapping();
 
-        Ok(())
+        Ok((self.mapped_joystick_count() > prev_mapped_count)
+            .then_some(ControllerHotplug::MappedConnected))
     }
 
-    pub(crate) fn device_removed(&mut self, instance_id: u32) {
+    pub(crate) fn device_removed(&mut self, instance_id: u32) -> Option<ControllerHotplug> {
+        let prev_mapped_count = self.mapped_joystick_count();
+
         self.joysticks.device_removed(instance_id);
         self.update_input_mapping();
+
+        (self.mapped_joystick_count() < prev_mapped_count)
+            .then_some(ControllerHotplug::MappedDisconnected)
+    }
+
+    // Number of connected joysticks that have at least one input mapped to a button
+    fn mapped_joystick_count(&self) -> usize {
+        self.joystick_mapping.keys().map(|&(device_id, _)| device_id).collect::<HashSet<_>>().len()
     }
 
     fn update_input_mapping(&mut self) {
         self.joystick_mapping.clear();
         self.inputs = Inputs::default();
+        self.macro_mapper.reset();
+        self.macro_mapper.update_joystick_triggers(&self.joysticks.name_to_device_ids);
 
         for (input, buttons) in &self.raw_joystick_mapping {
             if let Some(device_ids) = self.joysticks.name_to_device_ids.get(&input.device.name) {
@@ -1024,7 +1687,7 @@ where
     fn key(&mut self, keycode: Keycode, value: bool) {
         if let Some(buttons) = self.keyboard_mapping.get(&keycode) {
             for &button in buttons {
-                self.inputs.set_field(button, value);
+                self.macro_mapper.set_button(&mut self.inputs, button, value);
             }
         }
 
@@ -1032,7 +1695,7 @@ where
             self.key_or_mouse_mapping.get(&KeycodeOrMouseButton::Keycode(keycode))
         {
             for &button in buttons {
-                self.inputs.set_field(button, value);
+                self.macro_mapper.set_button(&mut self.inputs, button, value);
             }
         }
     }
@@ -1048,14 +1711,13 @@ where
     fn button(&mut self, instance_id: u32, button_idx: u8, value: bool) {
         let Some(device_id) = self.joysticks.device_id_for(instance_id) else { return };
 
-        let Some(buttons) =
-            self.joystick_mapping.get(&(device_id, JoystickAction::Button { button_idx }))
-        else {
-            return;
-        };
+        let action = JoystickAction::Button { button_idx };
+        self.macro_mapper.joystick_action(&mut self.inputs, device_id, action, value);
+
+        let Some(buttons) = self.joystick_mapping.get(&(device_id, action)) else { return };
 
         for &button in buttons {
-            self.inputs.set_field(button, value);
+            self.macro_mapper.set_button(&mut self.inputs, button, value);
         }
     }
 
@@ -1068,12 +1730,12 @@ where
         for (direction, value) in
             [(AxisDirection::Positive, positive_down), (AxisDirection::Negative, negative_down)]
         {
-            if let Some(buttons) = self
-                .joystick_mapping
-                .get(&(device_id, JoystickAction::Axis { axis_idx, direction }))
-            {
+            let action = JoystickAction::Axis { axis_idx, direction };
+            self.macro_mapper.joystick_action(&mut self.inputs, device_id, action, value);
+
+            if let Some(buttons) = self.joystick_mapping.get(&(device_id, action)) {
                 for &button in buttons {
-                    self.inputs.set_field(button, value);
+                    self.macro_mapper.set_button(&mut self.inputs, button, value);
                 }
             }
         }
@@ -1082,24 +1744,14 @@ where
     pub(crate) fn hat_motion(&mut self, instance_id: u32, hat_idx: u8, state: HatState) {
         let Some(device_id) = self.joysticks.device_id_for(instance_id) else { return };
 
-        let up_pressed = matches!(state, HatState::LeftUp | HatState::Up | HatState::RightUp);
-        let left_pressed = matches!(state, HatState::LeftUp | HatState::Left | HatState::LeftDown);
-        let down_pressed =
-            matches!(state, HatState::LeftDown | HatState::Down | HatState::RightDown);
-        let right_pressed =
-            matches!(state, HatState::RightUp | HatState::Right | HatState::RightDown);
-
-        for (direction, value) in [
-            (HatDirection::Up, up_pressed),
-            (HatDirection::Left, left_pressed),
-            (HatDirection::Down, down_pressed),
-            (HatDirection::Right, right_pressed),
-        ] {
-            if let Some(buttons) =
-                self.joystick_mapping.get(&(device_id, JoystickAction::Hat { hat_idx, direction }))
-            {
+        for direction in HAT_DIRECTIONS {
+            let value = hat_direction_pressed(state, direction);
+            let action = JoystickAction::Hat { hat_idx, direction };
+            self.macro_mapper.joystick_action(&mut self.inputs, device_id, action, value);
+
+            if let Some(buttons) = self.joystick_mapping.get(&(device_id, action)) {
                 for &button in buttons {
-                    self.inputs.set_field(button, value);
+                    self.macro_mapper.set_button(&mut self.inputs, button, value);
                 }
             }
         }
@@ -1110,7 +1762,7 @@ where
             self.key_or_mouse_mapping.get(&KeycodeOrMouseButton::Mouse(mouse_button))
         {
             for &button in buttons {
-                self.inputs.set_field(button, pressed);
+                self.macro_mapper.set_button(&mut self.inputs, button, pressed);
             }
         }
     }
@@ -1120,19 +1772,27 @@ where
         event: &Event,
         emulator_window_id: u32,
         display_info: Option<(FrameSize, DisplayArea)>,
-    ) -> NativeEmulatorResult<()> {
+    ) -> NativeEmulatorResult<Option<ControllerHotplug>> {
+        if let Some(peripheral_mapper) = &mut self.peripheral_mapper {
+            peripheral_mapper.handle_event(event, emulator_window_id, display_info);
+        }
+
+        let mut hotplug = None;
         match *event {
-            Event::KeyDown { keycode: Some(keycode), .. } => {
+            Event::KeyDown { keycode: Some(keycode), repeat, .. } => {
                 self.key_down(keycode);
+                if !repeat {
+                    self.macro_mapper.key_pressed(&mut self.inputs, keycode);
+                }
             }
             Event::KeyUp { keycode: Some(keycode), .. } => {
                 self.key_up(keycode);
             }
             Event::JoyDeviceAdded { which: device_id, .. } => {
-                self.device_added(device_id)?;
+                hotplug = self.device_added(device_id)?;
             }
             Event::JoyDeviceRemoved { which: instance_id, .. } => {
-                self.device_removed(instance_id);
+                hotplug = self.device_removed(instance_id);
             }
             Event::JoyButtonDown { which: instance_id, button_idx, .. } => {
                 self.button_down(instance_id, button_idx);
@@ -1169,7 +1829,51 @@ where
             _ => {}
         }
 
-        Ok(())
+        Ok(hotplug)
+    }
+
+    pub(crate) fn update_peripheral_inputs(&mut self) {
+        if let Some(peripheral_mapper) = &mut self.peripheral_mapper {
+            peripheral_mapper.update_inputs(&mut self.inputs);
+        }
+    }
+
+    /// Called once per emulated frame to advance input macro playback and recording.
+    pub(crate) fn end_macro_frame(&mut self) {
+        self.macro_mapper.end_frame(&mut self.inputs);
+    }
+
+    /// Set the buttons held through input injection, which are combined with local inputs.
+    pub(crate) fn set_injected_buttons(&mut self, buttons: &[Button]) {
+        self.macro_mapper.set_injected(&mut self.inputs, buttons);
+    }
+
+    /// Invalid macros are logged and skipped.
+    pub(crate) fn reload_macros(&mut self, macros: &[InputMacroConfig]) {
+        self.macro_mapper.reload_config(macros);
+        self.update_input_mapping();
+    }
+
+    pub(crate) fn with_macros(mut self, macros: &[InputMacroConfig]) -> Self {
+        self.reload_macros(macros);
+        self
+    }
+
+    pub(crate) fn start_macro_recording(&mut self) {
+        self.macro_mapper.start_recording();
+    }
+
+    /// Returns the recorded sequence, or None if no buttons were pressed while recording.
+    pub(crate) fn stop_macro_recording(&mut self) -> Option<&str> {
+        self.macro_mapper.stop_recording()
+    }
+
+    pub(crate) fn take_recorded_macros(&mut self) -> Vec<String> {
+        self.macro_mapper.take_recorded()
+    }
+
+    pub(crate) fn is_recording_macro(&self) -> bool {
+        self.macro_mapper.is_recording()
     }
 
     pub(crate) fn inputs(&self) -> &Inputs {
@@ -1190,6 +1894,17 @@ pub enum Hotkey {
     FastForward,
     Rewind,
     OpenDebugger,
+    MusicDump,
+    Microphone,
+    DumpInputTrace,
+    NextSaveStateSlot,
+    PreviousSaveStateSlot,
+    UndoLoadState,
+    RecordInputMacro,
+    SetPracticeLoopPoint,
+    RestartPracticeAttempt,
+    ClearPracticeLoopPoint,
+    OpenQuickMenu,
 }
 
 pub(crate) enum HotkeyMapResult<'a> {
@@ -1200,6 +1915,10 @@ pub(crate) enum HotkeyMapResult<'a> {
 
 pub(crate) struct HotkeyMapper {
     mapping: HashMap<Keycode, Vec<Hotkey>>,
+    joystick_mapping: HashMap<JoystickInput, Vec<Hotkey>>,
+    axis_deadzone: i16,
+    // Axis and hat events are sent repeatedly while held; only trigger on the initial press
+    held_joystick_inputs: HashSet<JoystickInput>,
 }
 
 const EMPTY_VEC: &Vec<Hotkey> = &Vec::new();
@@ -1210,7 +1929,7 @@ impl HotkeyMapper {
     /// # Errors
     ///
     /// This function will return an error if the given config contains any invalid keycodes.
-    pub fn from_config(config: &HotkeyConfig) -> NativeEmulatorResult<Self> {
+    pub fn from_config(config: &HotkeyConfig, axis_deadzone: i16) -> NativeEmulatorResult<Self> {
         let mut mapping: HashMap<Keycode, Vec<Hotkey>> = HashMap::new();
         for (input, hotkey) in [
             (&config.quit, Hotkey::Quit),
@@ -1224,6 +1943,17 @@ impl HotkeyMapper {
             (&config.fast_forward, Hotkey::FastForward),
             (&config.rewind, Hotkey::Rewind),
             (&config.open_debugger, Hotkey::OpenDebugger),
+            (&config.music_dump, Hotkey::MusicDump),
+            (&config.microphone, Hotkey::Microphone),
+            (&config.dump_input_trace, Hotkey::DumpInputTrace),
+            (&config.next_save_state_slot, Hotkey::NextSaveStateSlot),
+            (&config.previous_save_state_slot, Hotkey::PreviousSaveStateSlot),
+            (&config.undo_load_state, Hotkey::UndoLoadState),
+            (&config.record_input_macro, Hotkey::RecordInputMacro),
+            (&config.set_practice_loop_point, Hotkey::SetPracticeLoopPoint),
+            (&config.restart_practice_attempt, Hotkey::RestartPracticeAttempt),
+            (&config.clear_practice_loop_point, Hotkey::ClearPracticeLoopPoint),
+            (&config.open_quick_menu, Hotkey::OpenQuickMenu),
         ] {
             if let Some(input) = input {
                 let keycode = Keycode::from_name(&input.keycode)
@@ -1232,7 +1962,12 @@ impl HotkeyMapper {
             }
         }
 
-        Ok(Self { mapping })
+        let mut joystick_mapping: HashMap<JoystickInput, Vec<Hotkey>> = HashMap::new();
+        if let Some(input) = &config.open_quick_menu_joystick {
+            joystick_mapping.entry(input.clone()).or_default().push(Hotkey::OpenQuickMenu);
+        }
+
+        Ok(Self { mapping, joystick_mapping, axis_deadzone, held_joystick_inputs: HashSet::new() })
     }
 
     #[must_use]
@@ -1247,4 +1982,94 @@ impl HotkeyMapper {
             _ => HotkeyMapResult::None,
         }
     }
+
+    /// Check for hotkeys bound to gamepad inputs, returning any that were just pressed. Releases
+    /// are not reported because none of the hotkeys that can be bound to a gamepad need them.
+    #[must_use]
+    pub fn check_for_joystick_hotkeys(
+        &mut self,
+        event: &Event,
+        joysticks: &Joysticks,
+    ) -> Vec<Hotkey> {
+        if self.joystick_mapping.is_empty() {
+            return vec![];
+        }
+
+        let (instance_id, actions) = match *event {
+            Event::JoyButtonDown { which, button_idx, .. } => {
+                (which, vec![(JoystickAction::Button { button_idx }, true)])
+            }
+            Event::JoyButtonUp { which, button_idx, .. } => {
+                (which, vec![(JoystickAction::Button { button_idx }, false)])
+            }
+            Event::JoyAxisMotion { which, axis_idx, value, .. } => (
+                which,
+                vec![
+                    (
+                        JoystickAction::Axis { axis_idx, direction: AxisDirection::Positive },
+                        value > self.axis_deadzone,
+                    ),
+                    (
+                        JoystickAction::Axis { axis_idx, direction: AxisDirection::Negative },
+                        value < -self.axis_deadzone,
+                    ),
+                ],
+            ),
+            Event::JoyHatMotion { which, hat_idx, state, .. } => (
+                which,
+                HAT_DIRECTIONS
+                    .into_iter()
+                    .map(|direction| {
+                        let action = JoystickAction::Hat { hat_idx, direction };
+                        (action, hat_direction_pressed(state, direction))
+                    })
+                    .collect(),
+            ),
+            Event::JoyDeviceRemoved { .. } => {
+                // A disconnected gamepad never sends releases for its held inputs
+                self.held_joystick_inputs.clear();
+                return vec![];
+            }
+            _ => return vec![],
+        };
+
+        let Some(device) = joysticks
+            .device_id_for(instance_id)
+            .and_then(|device_id| joysticks.get_joystick_id(device_id))
+        else {
+            return vec![];
+        };
+
+        let mut pressed = vec![];
+        for (action, value) in actions {
+            let input = JoystickInput { device: device.clone(), action };
+            let Some(hotkeys) = self.joystick_mapping.get(&input) else { continue };
+
+            if !value {
+                self.held_joystick_inputs.remove(&input);
+            } else if self.held_joystick_inputs.insert(input) {
+                pressed.extend(hotkeys);
+            }
+        }
+
+        pressed
+    }
+}
+
+const HAT_DIRECTIONS: [HatDirection; 4] =
+    [HatDirection::Up, HatDirection::Left, HatDirection::Down, HatDirection::Right];
+
+fn hat_direction_pressed(state: HatState, direction: HatDirection) -> bool {
+    match direction {
+        HatDirection::Up => matches!(state, HatState::LeftUp | HatState::Up | HatState::RightUp),
+        HatDirection::Left => {
+            matches!(state, HatState::LeftUp | HatState::Left | HatState::LeftDown)
+        }
+        HatDirection::Down => {
+            matches!(state, HatState::LeftDown | HatState::Down | HatState::RightDown)
+        }
+        HatDirection::Right => {
+            matches!(state, HatState::RightUp | HatState::Right | HatState::RightDown)
+        }
+    }
 }
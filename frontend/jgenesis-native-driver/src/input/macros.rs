@@ -0,0 +1,667 @@
+//! Input macros: button sequences that are played back one frame at a time when a trigger input is
+//! pressed
+//!
+//! Sequences are written as comma-separated frames. Each frame lists the buttons held on that frame
+//! joined with `+`, or `-` for a frame with no buttons held, and a frame can be repeated with `*N`.
+//! Button names are the controller config field names (`up`, `a`, `button_1`, `start`, ...), with
+//! a `p2.` / `p3.` / `p4.` prefix for buttons on other players' controllers. For example, a
+//! quarter-circle forward followed by A held for 2 frames is `down, down+right, right+a*2`.
+
+use crate::config::input::{InputMacroConfig, JoystickAction, JoystickInput, KeyboardInput};
+use crate::input::{
+    Atari2600Button, ColecoVisionButton, GameBoyButton, GbaButton, GenesisButton, MappableInputs,
+    NesButton, PceButton, Player, SmsGgButton, SnesButton,
+};
+use sdl2::keyboard::Keycode;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use std::{iter, mem};
+use thiserror::Error;
+
+// Limit on repeat counts so that a typo can't allocate an enormous sequence; 10 minutes at 60fps
+const MAX_REPEAT: usize = 36000;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum MacroParseError {
+    #[error("Unknown button '{0}'")]
+    UnknownButton(String),
+    #[error("Invalid repeat count '{0}'")]
+    InvalidRepeatCount(String),
+}
+
+/// Emulated buttons that can be named in input macro sequences.
+pub trait MacroButton: Copy + PartialEq {
+    fn from_macro_name(name: &str, player: Player) -> Option<Self>;
+
+    /// Returns None for buttons that cannot be used in macros.
+    fn macro_name(self) -> Option<(&'static str, Player)>;
+}
+
+macro_rules! impl_macro_button {
+    ($button_t:ident, [$($name:literal -> $variant:ident),* $(,)?] $(, unnamed: $unnamed:pat)? $(,)?) => {
+        impl MacroButton for $button_t {
+            fn from_macro_name(name: &str, player: Player) -> Option<Self> {
+                match name {
+                    $($name => Some(Self::$variant(player)),)*
+                    _ => None,
+                }
+            }
+
+            fn macro_name(self) -> Option<(&'static str, Player)> {
+                match self {
+                    $(Self::$variant(player) => Some(($name, player)),)*
+                    $($unnamed => None,)?
+                }
+            }
+        }
+    };
+}
+
+impl_macro_button!(GenesisButton, [
+    "up" -> Up,
+    "left" -> Left,
+    "right" -> Right,
+    "down" -> Down,
+    "a" -> A,
+    "b" -> B,
+    "c" -> C,
+    "x" -> X,
+    "y" -> Y,
+    "z" -> Z,
+    "start" -> Start,
+    "mode" -> Mode,
+]);
+
+impl_macro_button!(NesButton, [
+    "up" -> Up,
+    "left" -> Left,
+    "right" -> Right,
+    "down" -> Down,
+    "a" -> A,
+    "b" -> B,
+    "start" -> Start,
+    "select" -> Select,
+]);
+
+impl_macro_button!(SnesButton, [
+    "up" -> Up,
+    "left" -> Left,
+    "right" -> Right,
+    "down" -> Down,
+    "a" -> A,
+    "b" -> B,
+    "x" -> X,
+    "y" -> Y,
+    "l" -> L,
+    "r" -> R,
+    "start" -> Start,
+    "select" -> Select,
+], unnamed: Self::SuperScope(_));
+
+impl MacroButton for SmsGgButton {
+    fn from_macro_name(name: &str, player: Player) -> Option<Self> {
+        match name {
+            "up" => Some(Self::Up(player)),
+            "left" => Some(Self::Left(player)),
+            "right" => Some(Self::Right(player)),
+            "down" => Some(Self::Down(player)),
+            "button_1" => Some(Self::Button1(player)),
+            "button_2" => Some(Self::Button2(player)),
+            "pause" if player == Player::One => Some(Self::Pause),
+            _ => None,
+        }
+    }
+
+    fn macro_name(self) -> Option<(&'static str, Player)> {
+        let name = match self {
+            Self::Up(_) => "up",
+            Self::Left(_) => "left",
+            Self::Right(_) => "right",
+            Self::Down(_) => "down",
+            Self::Button1(_) => "button_1",
+            Self::Button2(_) => "button_2",
+            Self::Pause => "pause",
+        };
+        Some((name, self.player()))
+    }
+}
+
+impl MacroButton for Atari2600Button {
+    fn from_macro_name(name: &str, player: Player) -> Option<Self> {
+        match name {
+            "up" => Some(Self::Up(player)),
+            "left" => Some(Self::Left(player)),
+            "right" => Some(Self::Right(player)),
+            "down" => Some(Self::Down(player)),
+            "fire" => Some(Self::Fire(player)),
+            "reset" if player == Player::One => Some(Self::Reset),
+            "select" if player == Player::One => Some(Self::Select),
+            _ => None,
+        }
+    }
+
+    fn macro_name(self) -> Option<(&'static str, Player)> {
+        let name = match self {
+            Self::Up(_) => "up",
+            Self::Left(_) => "left",
+            Self::Right(_) => "right",
+            Self::Down(_) => "down",
+            Self::Fire(_) => "fire",
+            Self::Reset => "reset",
+            Self::Select => "select",
+        };
+        Some((name, self.player()))
+    }
+}
+
+impl MacroButton for ColecoVisionButton {
+    fn from_macro_name(name: &str, player: Player) -> Option<Self> {
+        match name {
+            "up" => Some(Self::Up(player)),
+            "left" => Some(Self::Left(player)),
+            "right" => Some(Self::Right(player)),
+            "down" => Some(Self::Down(player)),
+            "left_fire" => Some(Self::LeftFire(player)),
+            "right_fire" => Some(Self::RightFire(player)),
+            "keypad_1" => Some(Self::Keypad1(player)),
+            "keypad_2" => Some(Self::Keypad2(player)),
+            "keypad_3" => Some(Self::Keypad3(player)),
+            "keypad_4" => Some(Self::Keypad4(player)),
+            "keypad_5" => Some(Self::Keypad5(player)),
+            "keypad_6" => Some(Self::Keypad6(player)),
+            "keypad_7" => Some(Self::Keypad7(player)),
+            "keypad_8" => Some(Self::Keypad8(player)),
+            "keypad_9" => Some(Self::Keypad9(player)),
+            "keypad_0" => Some(Self::Keypad0(player)),
+            "keypad_star" => Some(Self::KeypadStar(player)),
+            "keypad_pound" => Some(Self::KeypadPound(player)),
+            _ => None,
+        }
+    }
+
+    fn macro_name(self) -> Option<(&'static str, Player)> {
+        let name = match self {
+            Self::Up(_) => "up",
+            Self::Left(_) => "left",
+            Self::Right(_) => "right",
+            Self::Down(_) => "down",
+            Self::LeftFire(_) => "left_fire",
+            Self::RightFire(_) => "right_fire",
+            Self::Keypad1(_) => "keypad_1",
+            Self::Keypad2(_) => "keypad_2",
+            Self::Keypad3(_) => "keypad_3",
+            Self::Keypad4(_) => "keypad_4",
+            Self::Keypad5(_) => "keypad_5",
+            Self::Keypad6(_) => "keypad_6",
+            Self::Keypad7(_) => "keypad_7",
+            Self::Keypad8(_) => "keypad_8",
+            Self::Keypad9(_) => "keypad_9",
+            Self::Keypad0(_) => "keypad_0",
+            Self::KeypadStar(_) => "keypad_star",
+            Self::KeypadPound(_) => "keypad_pound",
+        };
+        Some((name, self.player()))
+    }
+}
+
+impl MacroButton for GameBoyButton {
+    fn from_macro_name(name: &str, player: Player) -> Option<Self> {
+        if player != Player::One {
+            return None;
+        }
+
+        match name {
+            "up" => Some(Self::Up),
+            "left" => Some(Self::Left),
+            "right" => Some(Self::Right),
+            "down" => Some(Self::Down),
+            "a" => Some(Self::A),
+            "b" => Some(Self::B),
+            "start" => Some(Self::Start),
+            "select" => Some(Self::Select),
+            _ => None,
+        }
+    }
+
+    fn macro_name(self) -> Option<(&'static str, Player)> {
+        let name = match self {
+            Self::Up => "up",
+            Self::Left => "left",
+            Self::Right => "right",
+            Self::Down => "down",
+            Self::A => "a",
+            Self::B => "b",
+            Self::Start => "start",
+            Self::Select => "select",
+        };
+        Some((name, Player::One))
+    }
+}
+
+impl MacroButton for GbaButton {
+    fn from_macro_name(name: &str, player: Player) -> Option<Self> {
+        if player != Player::One {
+            return None;
+        }
+
+        match name {
+            "up" => Some(Self::Up),
+            "left" => Some(Self::Left),
+            "right" => Some(Self::Right),
+            "down" => Some(Self::Down),
+            "a" => Some(Self::A),
+            "b" => Some(Self::B),
+            "l" => Some(Self::L),
+            "r" => Some(Self::R),
+            "start" => Some(Self::Start),
+            "select" => Some(Self::Select),
+            _ => None,
+        }
+    }
+
+    fn macro_name(self) -> Option<(&'static str, Player)> {
+        let name = match self {
+            Self::Up => "up",
+            Self::Left => "left",
+            Self::Right => "right",
+            Self::Down => "down",
+            Self::A => "a",
+            Self::B => "b",
+            Self::L => "l",
+            Self::R => "r",
+            Self::Start => "start",
+            Self::Select => "select",
+        };
+        Some((name, Player::One))
+    }
+}
+
+impl MacroButton for PceButton {
+    fn from_macro_name(name: &str, player: Player) -> Option<Self> {
+        if player != Player::One {
+            return None;
+        }
+
+        match name {
+            "up" => Some(Self::Up),
+            "left" => Some(Self::Left),
+            "right" => Some(Self::Right),
+            "down" => Some(Self::Down),
+            "i" => Some(Self::I),
+            "ii" => Some(Self::II),
+            "run" => Some(Self::Run),
+            "select" => Some(Self::Select),
+            _ => None,
+        }
+    }
+
+    fn macro_name(self) -> Option<(&'static str, Player)> {
+        let name = match self {
+            Self::Up => "up",
+            Self::Left => "left",
+            Self::Right => "right",
+            Self::Down => "down",
+            Self::I => "i",
+            Self::II => "ii",
+            Self::Run => "run",
+            Self::Select => "select",
+        };
+        Some((name, Player::One))
+    }
+}
+
+fn parse_button<Button: MacroButton>(name: &str) -> Result<Button, MacroParseError> {
+    let lowercase = name.to_ascii_lowercase();
+    let (player, button_name) = match lowercase.split_once('.') {
+        Some(("p1", button_name)) => (Player::One, button_name),
+        Some(("p2", button_name)) => (Player::Two, button_name),
+        Some(("p3", button_name)) => (Player::Three, button_name),
+        Some(("p4", button_name)) => (Player::Four, button_name),
+        Some(_) => return Err(MacroParseError::UnknownButton(name.into())),
+        None => (Player::One, lowercase.as_str()),
+    };
+
+    Button::from_macro_name(button_name, player)
+        .ok_or_else(|| MacroParseError::UnknownButton(name.into()))
+}
+
+/// Parse a single frame of `+`-separated button names, where `-` or an empty string means no
+/// buttons.
+///
+/// # Errors
+///
+/// Returns an error if a button does not exist for this system.
+pub fn parse_buttons<Button: MacroButton>(buttons: &str) -> Result<Vec<Button>, MacroParseError> {
+    match buttons.trim() {
+        "" | "-" => Ok(vec![]),
+        buttons => buttons.split('+').map(|name| parse_button(name.trim())).collect(),
+    }
+}
+
+/// Parse a macro sequence into the buttons held on each frame.
+///
+/// # Errors
+///
+/// Returns an error if the sequence names a button that does not exist for this system or contains
+/// an invalid repeat count.
+pub fn parse_sequence<Button: MacroButton>(
+    sequence: &str,
+) -> Result<Vec<Vec<Button>>, MacroParseError> {
+    let mut frames = Vec::new();
+
+    for frame in sequence.split(',') {
+        let (buttons, repeat) = match frame.rsplit_once('*') {
+            Some((buttons, repeat)) => {
+                let repeat = repeat.trim();
+                let count = repeat
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|&count| count != 0 && count <= MAX_REPEAT)
+                    .ok_or_else(|| MacroParseError::InvalidRepeatCount(repeat.into()))?;
+                (buttons, count)
+            }
+            None => (frame, 1),
+        };
+
+        let buttons = parse_buttons(buttons)?;
+        frames.extend(iter::repeat(buttons).take(repeat));
+    }
+
+    Ok(frames)
+}
+
+/// Format per-frame button states as a macro sequence, merging repeated frames.
+pub fn format_sequence<Button: MacroButton>(frames: &[Vec<Button>]) -> String {
+    let mut sequence = String::new();
+
+    for run in frames.chunk_by(|a, b| a == b) {
+        if !sequence.is_empty() {
+            sequence.push_str(", ");
+        }
+
+        let names: Vec<_> = run[0]
+            .iter()
+            .filter_map(|button| button.macro_name())
+            .map(|(name, player)| match player {
+                Player::One => name.into(),
+                Player::Two => format!("p2.{name}"),
+                Player::Three => format!("p3.{name}"),
+                Player::Four => format!("p4.{name}"),
+            })
+            .collect();
+        if names.is_empty() {
+            sequence.push('-');
+        } else {
+            sequence.push_str(&names.join("+"));
+        }
+
+        if run.len() > 1 {
+            let _ = write!(sequence, "*{}", run.len());
+        }
+    }
+
+    sequence
+}
+
+struct MacroPlayback {
+    macro_idx: usize,
+    frame: usize,
+}
+
+/// Macro triggers, playback, and recording for an [`InputMapper`](crate::input::InputMapper), along
+/// with buttons held through input injection.
+///
+/// Buttons held by a playing macro or by injection are combined with buttons held on physical
+/// inputs, so releasing a physical input does not release a button that the macro is holding and
+/// vice versa.
+pub(crate) struct MacroMapper<Button> {
+    macros: Vec<Vec<Vec<Button>>>,
+    keyboard_triggers: HashMap<Keycode, usize>,
+    raw_joystick_triggers: HashMap<JoystickInput, usize>,
+    joystick_triggers: HashMap<(u32, JoystickAction), usize>,
+    held_joystick_triggers: HashSet<(u32, JoystickAction)>,
+    playback: Option<MacroPlayback>,
+    physical_held: Vec<Button>,
+    injected_held: Vec<Button>,
+    recording: Option<Vec<Vec<Button>>>,
+    // Sequences recorded since the last call to take_recorded
+    recorded: Vec<String>,
+}
+
+impl<Button> MacroMapper<Button> {
+    pub(crate) fn new() -> Self {
+        Self {
+            macros: vec![],
+            keyboard_triggers: HashMap::new(),
+            raw_joystick_triggers: HashMap::new(),
+            joystick_triggers: HashMap::new(),
+            held_joystick_triggers: HashSet::new(),
+            playback: None,
+            physical_held: vec![],
+            injected_held: vec![],
+            recording: None,
+            recorded: vec![],
+        }
+    }
+}
+
+impl<Button: MacroButton> MacroMapper<Button> {
+    /// Invalid macros are logged and skipped.
+    pub(crate) fn reload_config(&mut self, configs: &[InputMacroConfig]) {
+        self.macros.clear();
+        self.keyboard_triggers.clear();
+        self.raw_joystick_triggers.clear();
+        self.playback = None;
+
+        for config in configs {
+            let frames = match parse_sequence(&config.sequence) {
+                Ok(frames) => frames,
+                Err(err) => {
+                    log::error!("Invalid input macro '{}': {err}", config.sequence);
+                    continue;
+                }
+            };
+
+            let idx = self.macros.len();
+            self.macros.push(frames);
+
+            if let Some(KeyboardInput { keycode }) = &config.keyboard_trigger {
+                match Keycode::from_name(keycode) {
+                    Some(keycode) => {
+                        self.keyboard_triggers.insert(keycode, idx);
+                    }
+                    None => log::error!("Invalid input macro trigger keycode '{keycode}'"),
+                }
+            }
+
+            if let Some(joystick_trigger) = &config.joystick_trigger {
+                self.raw_joystick_triggers.insert(joystick_trigger.clone(), idx);
+            }
+        }
+    }
+
+    /// Resolve joystick triggers to connected devices. Called whenever the joystick mapping changes.
+    pub(crate) fn update_joystick_triggers(
+        &mut self,
+        name_to_device_ids: &HashMap<String, Vec<u32>>,
+    ) {
+        self.joystick_triggers.clear();
+        self.held_joystick_triggers.clear();
+
+        for (input, &idx) in &self.raw_joystick_triggers {
+            if let Some(device_ids) = name_to_device_ids.get(&input.device.name) {
+                if let Some(&device_id) = device_ids.get(input.device.idx as usize) {
+                    self.joystick_triggers.insert((device_id, input.action), idx);
+                }
+            }
+        }
+    }
+
+    /// Inputs are reset to default when the input mapping changes, so forget held buttons.
+    pub(crate) fn reset(&mut self) {
+        self.playback = None;
+        self.physical_held.clear();
+        self.injected_held.clear();
+    }
+
+    fn macro_frame(&self) -> &[Button] {
+        self.playback
+            .as_ref()
+            .and_then(|playback| self.macros[playback.macro_idx].get(playback.frame))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Set a button from a physical input.
+    pub(crate) fn set_button<Inputs: MappableInputs<Button>>(
+        &mut self,
+        inputs: &mut Inputs,
+        button: Button,
+        value: bool,
+    ) {
+        if value {
+            if !self.physical_held.contains(&button) {
+                self.physical_held.push(button);
+            }
+        } else {
+            self.physical_held.retain(|&held| held != button);
+            if self.macro_frame().contains(&button) || self.injected_held.contains(&button) {
+                return;
+            }
+        }
+
+        inputs.set_field(button, value);
+    }
+
+    pub(crate) fn key_pressed<Inputs: MappableInputs<Button>>(
+        &mut self,
+        inputs: &mut Inputs,
+        keycode: Keycode,
+    ) {
+        if let Some(&idx) = self.keyboard_triggers.get(&keycode) {
+            self.start_playback(inputs, idx);
+        }
+    }
+
+    pub(crate) fn joystick_action<Inputs: MappableInputs<Button>>(
+        &mut self,
+        inputs: &mut Inputs,
+        device_id: u32,
+        action: JoystickAction,
+        value: bool,
+    ) {
+        let Some(&idx) = self.joystick_triggers.get(&(device_id, action)) else { return };
+
+        // Axis and hat events are sent repeatedly while held; only trigger on the initial press
+        if !value {
+            self.held_joystick_triggers.remove(&(device_id, action));
+        } else if self.held_joystick_triggers.insert((device_id, action)) {
+            self.start_playback(inputs, idx);
+        }
+    }
+
+    fn start_playback<Inputs: MappableInputs<Button>>(&mut self, inputs: &mut Inputs, idx: usize) {
+        self.release_macro_frame(inputs, &[]);
+        self.playback = Some(MacroPlayback { macro_idx: idx, frame: 0 });
+        self.press_macro_frame(inputs);
+    }
+
+    // Release buttons from the current macro frame that are not in the next frame or physically held
+    fn release_macro_frame<Inputs: MappableInputs<Button>>(
+        &self,
+        inputs: &mut Inputs,
+        next_frame: &[Button],
+    ) {
+        for &button in self.macro_frame() {
+            if !next_frame.contains(&button)
+                && !self.physical_held.contains(&button)
+                && !self.injected_held.contains(&button)
+            {
+                inputs.set_field(button, false);
+            }
+        }
+    }
+
+    fn press_macro_frame<Inputs: MappableInputs<Button>>(&self, inputs: &mut Inputs) {
+        for &button in self.macro_frame() {
+            inputs.set_field(button, true);
+        }
+    }
+
+    /// Called once per emulated frame, after the frame. Records the frame's inputs if recording and
+    /// advances macro playback to the next frame.
+    pub(crate) fn end_frame<Inputs: MappableInputs<Button>>(&mut self, inputs: &mut Inputs) {
+        if let Some(recording) = &mut self.recording {
+            let mut frame = self.physical_held.clone();
+            for &button in self.macro_frame() {
+                if !frame.contains(&button) {
+                    frame.push(button);
+                }
+            }
+            recording.push(frame);
+        }
+
+        let Some(playback) = &self.playback else { return };
+        let macro_idx = playback.macro_idx;
+        let next_frame_idx = playback.frame + 1;
+
+        let next_frame = self.macros[macro_idx].get(next_frame_idx).cloned().unwrap_or_default();
+        self.release_macro_frame(inputs, &next_frame);
+
+        if next_frame_idx < self.macros[macro_idx].len() {
+            self.playback = Some(MacroPlayback { macro_idx, frame: next_frame_idx });
+            self.press_macro_frame(inputs);
+        } else {
+            self.playback = None;
+        }
+    }
+
+    /// Replace the set of buttons held through input injection.
+    pub(crate) fn set_injected<Inputs: MappableInputs<Button>>(
+        &mut self,
+        inputs: &mut Inputs,
+        buttons: &[Button],
+    ) {
+        if buttons == self.injected_held {
+            return;
+        }
+
+        for &button in &self.injected_held {
+            if !buttons.contains(&button)
+                && !self.physical_held.contains(&button)
+                && !self.macro_frame().contains(&button)
+            {
+                inputs.set_field(button, false);
+            }
+        }
+
+        for &button in buttons {
+            inputs.set_field(button, true);
+        }
+
+        self.injected_held = buttons.to_vec();
+    }
+
+    pub(crate) fn start_recording(&mut self) {
+        self.recording = Some(vec![]);
+    }
+
+    /// Returns the recorded sequence with leading and trailing idle frames removed, or None if
+    /// nothing was recorded.
+    pub(crate) fn stop_recording(&mut self) -> Option<&str> {
+        let recording = self.recording.take()?;
+
+        let start = recording.iter().position(|frame| !frame.is_empty())?;
+        let end = recording.iter().rposition(|frame| !frame.is_empty())? + 1;
+        self.recorded.push(format_sequence(&recording[start..end]));
+
+        self.recorded.last().map(String::as_str)
+    }
+
+    pub(crate) fn take_recorded(&mut self) -> Vec<String> {
+        mem::take(&mut self.recorded)
+    }
+
+    pub(crate) fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+}
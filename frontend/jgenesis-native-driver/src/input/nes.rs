@@ -0,0 +1,182 @@
+//! Keyboard and mouse mapping for Famicom expansion port devices
+
+use crate::input::PeripheralMapper;
+use jgenesis_common::frontend::FrameSize;
+use jgenesis_renderer::renderer::DisplayArea;
+use nes_core::input::{FamilyBasicKey, FamilyBasicKeyboardState, NesExpansionDevice, NesInputs};
+use sdl2::event::Event;
+use sdl2::keyboard::Scancode;
+use sdl2::mouse::MouseButton;
+
+pub(crate) struct NesExpansionMapper {
+    device: NesExpansionDevice,
+    vaus_position: u8,
+    vaus_fire: bool,
+    keyboard: FamilyBasicKeyboardState,
+}
+
+impl NesExpansionMapper {
+    pub(crate) fn new(device: NesExpansionDevice) -> Self {
+        Self {
+            device,
+            vaus_position: 128,
+            vaus_fire: false,
+            keyboard: FamilyBasicKeyboardState::default(),
+        }
+    }
+}
+
+impl PeripheralMapper<NesInputs> for NesExpansionMapper {
+    fn handle_event(
+        &mut self,
+        event: &Event,
+        emulator_window_id: u32,
+        display_info: Option<(FrameSize, DisplayArea)>,
+    ) {
+        match (self.device, event) {
+            (
+                NesExpansionDevice::VausNes | NesExpansionDevice::VausFamicom,
+                &Event::MouseMotion { x, window_id, .. },
+            ) if window_id == emulator_window_id => {
+                // The knob position follows the mouse cursor's horizontal position
+                let Some((_, display_area)) = display_info else { return };
+                let x = f64::from(x - display_area.x as i32) / f64::from(display_area.width);
+                self.vaus_position = (x * 255.0).round().clamp(0.0, 255.0) as u8;
+            }
+            (
+                NesExpansionDevice::VausNes | NesExpansionDevice::VausFamicom,
+                &Event::MouseButtonDown { mouse_btn: MouseButton::Left, window_id, .. },
+            ) if window_id == emulator_window_id => {
+                self.vaus_fire = true;
+            }
+            (
+                NesExpansionDevice::VausNes | NesExpansionDevice::VausFamicom,
+                &Event::MouseButtonUp { mouse_btn: MouseButton::Left, window_id, .. },
+            ) if window_id == emulator_window_id => {
+                self.vaus_fire = false;
+            }
+            (
+                NesExpansionDevice::FamilyBasicKeyboard,
+                &Event::KeyDown { scancode: Some(scancode), .. },
+            ) => {
+                if let Some(key) = scancode_to_family_basic_key(scancode) {
+                    self.keyboard.set_pressed(key, true);
+                }
+            }
+            (
+                NesExpansionDevice::FamilyBasicKeyboard,
+                &Event::KeyUp { scancode: Some(scancode), .. },
+            ) => {
+                if let Some(key) = scancode_to_family_basic_key(scancode) {
+                    self.keyboard.set_pressed(key, false);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn update_inputs(&mut self, inputs: &mut NesInputs) {
+        let expansion = &mut inputs.expansion;
+        match self.device {
+            NesExpansionDevice::None => {}
+            NesExpansionDevice::VausNes | NesExpansionDevice::VausFamicom => {
+                expansion.vaus.position = self.vaus_position;
+                expansion.vaus.fire = self.vaus_fire;
+            }
+            NesExpansionDevice::HyperShot => {
+                // Hyper Shot buttons are mapped to the standard controllers' B (run) and A (jump)
+                expansion.hyper_shot.p1_run = inputs.p1.b;
+                expansion.hyper_shot.p1_jump = inputs.p1.a;
+                expansion.hyper_shot.p2_run = inputs.p2.b;
+                expansion.hyper_shot.p2_jump = inputs.p2.a;
+            }
+            NesExpansionDevice::FamilyBasicKeyboard => {
+                expansion.keyboard = self.keyboard;
+            }
+        }
+    }
+}
+
+// Keys are mapped by physical position on a US layout keyboard, with the Family BASIC symbol keys
+// to the right of the letters shifted to match the Japanese layout
+fn scancode_to_family_basic_key(scancode: Scancode) -> Option<FamilyBasicKey> {
+    use FamilyBasicKey as Key;
+
+    let key = match scancode {
+        Scancode::F1 => Key::F1,
+        Scancode::F2 => Key::F2,
+        Scancode::F3 => Key::F3,
+        Scancode::F4 => Key::F4,
+        Scancode::F5 => Key::F5,
+        Scancode::F6 => Key::F6,
+        Scancode::F7 => Key::F7,
+        Scancode::F8 => Key::F8,
+        Scancode::Num1 => Key::One,
+        Scancode::Num2 => Key::Two,
+        Scancode::Num3 => Key::Three,
+        Scancode::Num4 => Key::Four,
+        Scancode::Num5 => Key::Five,
+        Scancode::Num6 => Key::Six,
+        Scancode::Num7 => Key::Seven,
+        Scancode::Num8 => Key::Eight,
+        Scancode::Num9 => Key::Nine,
+        Scancode::Num0 => Key::Zero,
+        Scancode::Minus => Key::Minus,
+        Scancode::Equals => Key::Caret,
+        Scancode::Backslash => Key::Yen,
+        Scancode::End => Key::Stop,
+        Scancode::Grave => Key::Escape,
+        Scancode::Q => Key::Q,
+        Scancode::W => Key::W,
+        Scancode::E => Key::E,
+        Scancode::R => Key::R,
+        Scancode::T => Key::T,
+        Scancode::Y => Key::Y,
+        Scancode::U => Key::U,
+        Scancode::I => Key::I,
+        Scancode::O => Key::O,
+        Scancode::P => Key::P,
+        Scancode::LeftBracket => Key::At,
+        Scancode::RightBracket => Key::LeftBracket,
+        Scancode::Return => Key::Return,
+        Scancode::LCtrl => Key::Control,
+        Scancode::A => Key::A,
+        Scancode::S => Key::S,
+        Scancode::D => Key::D,
+        Scancode::F => Key::F,
+        Scancode::G => Key::G,
+        Scancode::H => Key::H,
+        Scancode::J => Key::J,
+        Scancode::K => Key::K,
+        Scancode::L => Key::L,
+        Scancode::Semicolon => Key::Semicolon,
+        Scancode::Apostrophe => Key::Colon,
+        Scancode::NonUsHash | Scancode::PageUp => Key::RightBracket,
+        Scancode::RAlt => Key::Kana,
+        Scancode::LShift => Key::LeftShift,
+        Scancode::Z => Key::Z,
+        Scancode::X => Key::X,
+        Scancode::C => Key::C,
+        Scancode::V => Key::V,
+        Scancode::B => Key::B,
+        Scancode::N => Key::N,
+        Scancode::M => Key::M,
+        Scancode::Comma => Key::Comma,
+        Scancode::Period => Key::Period,
+        Scancode::Slash => Key::Slash,
+        Scancode::PageDown => Key::Underscore,
+        Scancode::RShift => Key::RightShift,
+        Scancode::LAlt => Key::Graph,
+        Scancode::Space => Key::Space,
+        Scancode::Home => Key::ClearHome,
+        Scancode::Insert => Key::Insert,
+        Scancode::Delete | Scancode::Backspace => Key::Delete,
+        Scancode::Up => Key::Up,
+        Scancode::Down => Key::Down,
+        Scancode::Left => Key::Left,
+        Scancode::Right => Key::Right,
+        _ => return None,
+    };
+
+    Some(key)
+}
@@ -0,0 +1,113 @@
+//! Mouse and keyboard mapping for the Sega Pico pen and storyware
+//!
+//! The mouse cursor acts as the pen and the left mouse button presses it down. Right-clicking
+//! moves the pen between the drawing pad and the storyware, and Page Up / Page Down or the mouse
+//! wheel turn the storyware pages.
+
+use crate::input::PeripheralMapper;
+use genesis_core::pico::{self, PicoInputs, PicoPenTarget};
+use jgenesis_common::frontend::FrameSize;
+use jgenesis_renderer::renderer::DisplayArea;
+use sdl2::event::{Event, WindowEvent};
+use sdl2::keyboard::Scancode;
+use sdl2::mouse::MouseButton;
+
+pub(crate) struct PicoPenMapper {
+    position: Option<(u16, u16)>,
+    pressed: bool,
+    target: PicoPenTarget,
+    page: u8,
+}
+
+impl PicoPenMapper {
+    pub(crate) fn new() -> Self {
+        Self { position: None, pressed: false, target: PicoPenTarget::default(), page: 0 }
+    }
+
+    fn handle_mouse_position(
+        &mut self,
+        x: i32,
+        y: i32,
+        frame_size: FrameSize,
+        display_area: DisplayArea,
+    ) {
+        let display_x = f64::from(x - display_area.x as i32);
+        let display_y = f64::from(y - display_area.y as i32);
+        let display_width: f64 = display_area.width.into();
+        let display_height: f64 = display_area.height.into();
+
+        if !(0.0..display_width).contains(&display_x) || !(0.0..display_height).contains(&display_y)
+        {
+            self.position = None;
+            return;
+        }
+
+        let frame_x = (display_x * f64::from(frame_size.width) / display_width).round() as u16;
+        let frame_y = (display_y * f64::from(frame_size.height) / display_height).round() as u16;
+        self.position = Some((frame_x, frame_y));
+    }
+
+    fn turn_page(&mut self, forward: bool) {
+        self.page =
+            if forward { (self.page + 1).min(pico::MAX_PAGE) } else { self.page.saturating_sub(1) };
+        log::info!("Pico storyware page set to {}", self.page);
+    }
+}
+
+impl PeripheralMapper<PicoInputs> for PicoPenMapper {
+    fn handle_event(
+        &mut self,
+        event: &Event,
+        emulator_window_id: u32,
+        display_info: Option<(FrameSize, DisplayArea)>,
+    ) {
+        match *event {
+            Event::MouseMotion { x, y, window_id, .. } if window_id == emulator_window_id => {
+                let Some((frame_size, display_area)) = display_info else { return };
+                self.handle_mouse_position(x, y, frame_size, display_area);
+            }
+            Event::Window { win_event: WindowEvent::Leave, window_id, .. }
+                if window_id == emulator_window_id =>
+            {
+                self.position = None;
+            }
+            Event::MouseButtonDown { mouse_btn: MouseButton::Left, window_id, .. }
+                if window_id == emulator_window_id =>
+            {
+                self.pressed = true;
+            }
+            Event::MouseButtonUp { mouse_btn: MouseButton::Left, window_id, .. }
+                if window_id == emulator_window_id =>
+            {
+                self.pressed = false;
+            }
+            Event::MouseButtonDown { mouse_btn: MouseButton::Right, window_id, .. }
+                if window_id == emulator_window_id =>
+            {
+                self.target = match self.target {
+                    PicoPenTarget::DrawingPad => PicoPenTarget::Storyware,
+                    PicoPenTarget::Storyware => PicoPenTarget::DrawingPad,
+                };
+                log::info!("Pico pen moved to {:?}", self.target);
+            }
+            Event::MouseWheel { y, window_id, .. } if window_id == emulator_window_id && y != 0 => {
+                // Scrolling down turns to the next page
+                self.turn_page(y < 0);
+            }
+            Event::KeyDown { scancode: Some(Scancode::PageUp), repeat: false, .. } => {
+                self.turn_page(false);
+            }
+            Event::KeyDown { scancode: Some(Scancode::PageDown), repeat: false, .. } => {
+                self.turn_page(true);
+            }
+            _ => {}
+        }
+    }
+
+    fn update_inputs(&mut self, inputs: &mut PicoInputs) {
+        inputs.pen.position = self.position;
+        inputs.pen.pressed = self.pressed;
+        inputs.pen.target = self.target;
+        inputs.page = self.page;
+    }
+}
@@ -0,0 +1,164 @@
+//! Mouse and analog stick mapping for the SMS Paddle, Sports Pad, and Graphic Board
+
+use crate::config::input::SmsPeripheralConfig;
+use crate::input::PeripheralMapper;
+use jgenesis_common::frontend::FrameSize;
+use jgenesis_renderer::renderer::DisplayArea;
+use sdl2::event::Event;
+use sdl2::mouse::MouseButton;
+use smsgg_core::{SmsControllerType, SmsGgInputs};
+
+// Movement per frame with the stick fully tilted and a sensitivity of 1
+const PADDLE_STICK_SPEED: f64 = 4.0;
+const SPORTS_PAD_STICK_SPEED: f64 = 8.0;
+const GRAPHIC_BOARD_STICK_SPEED: f64 = 3.0;
+
+const GRAPHIC_BOARD_MAX_X: f64 = 255.0;
+const GRAPHIC_BOARD_MAX_Y: f64 = 191.0;
+
+pub(crate) struct SmsPeripheralMapper {
+    controller_type: SmsControllerType,
+    config: SmsPeripheralConfig,
+    axis_deadzone: i16,
+    // Positions are tracked in floating point so that low sensitivities don't drop small movements
+    paddle_position: f64,
+    sports_pad_motion: (f64, f64),
+    graphic_board_position: (f64, f64),
+    graphic_board_pen_down: bool,
+    stick: (i16, i16),
+}
+
+impl SmsPeripheralMapper {
+    pub(crate) fn new(
+        controller_type: SmsControllerType,
+        config: SmsPeripheralConfig,
+        axis_deadzone: i16,
+    ) -> Self {
+        Self {
+            controller_type,
+            config,
+            axis_deadzone,
+            paddle_position: 128.0,
+            sports_pad_motion: (0.0, 0.0),
+            graphic_board_position: (GRAPHIC_BOARD_MAX_X / 2.0, GRAPHIC_BOARD_MAX_Y / 2.0),
+            graphic_board_pen_down: false,
+            stick: (0, 0),
+        }
+    }
+
+    fn stick_deflection(&self, value: i16) -> f64 {
+        if value.unsigned_abs() <= self.axis_deadzone.unsigned_abs() {
+            return 0.0;
+        }
+
+        f64::from(value) / f64::from(i16::MAX)
+    }
+
+    fn handle_mouse_position(
+        &mut self,
+        x: i32,
+        y: i32,
+        frame_size: FrameSize,
+        display_area: DisplayArea,
+    ) {
+        let x = f64::from(x - display_area.x as i32) * f64::from(frame_size.width)
+            / f64::from(display_area.width);
+        let y = f64::from(y - display_area.y as i32) * f64::from(frame_size.height)
+            / f64::from(display_area.height);
+
+        self.graphic_board_position =
+            (x.clamp(0.0, GRAPHIC_BOARD_MAX_X), y.clamp(0.0, GRAPHIC_BOARD_MAX_Y));
+    }
+}
+
+impl PeripheralMapper<SmsGgInputs> for SmsPeripheralMapper {
+    fn handle_event(
+        &mut self,
+        event: &Event,
+        emulator_window_id: u32,
+        display_info: Option<(FrameSize, DisplayArea)>,
+    ) {
+        let mouse_sensitivity = self.config.mouse_sensitivity;
+
+        match *event {
+            Event::MouseMotion { x, y, xrel, yrel, window_id, .. }
+                if window_id == emulator_window_id =>
+            {
+                match self.controller_type {
+                    SmsControllerType::Joypad => {}
+                    SmsControllerType::Paddle => {
+                        self.paddle_position = (self.paddle_position
+                            + f64::from(xrel) * mouse_sensitivity)
+                            .clamp(0.0, 255.0);
+                    }
+                    SmsControllerType::SportsPad => {
+                        self.sports_pad_motion.0 += f64::from(xrel) * mouse_sensitivity;
+                        self.sports_pad_motion.1 += f64::from(yrel) * mouse_sensitivity;
+                    }
+                    SmsControllerType::GraphicBoard => {
+                        // The pen follows the mouse cursor directly
+                        if let Some((frame_size, display_area)) = display_info {
+                            self.handle_mouse_position(x, y, frame_size, display_area);
+                        }
+                    }
+                }
+            }
+            Event::MouseButtonDown { mouse_btn: MouseButton::Left, window_id, .. }
+                if window_id == emulator_window_id =>
+            {
+                self.graphic_board_pen_down = true;
+            }
+            Event::MouseButtonUp { mouse_btn: MouseButton::Left, window_id, .. }
+                if window_id == emulator_window_id =>
+            {
+                self.graphic_board_pen_down = false;
+            }
+            Event::JoyAxisMotion { axis_idx: 0, value, .. } => {
+                self.stick.0 = value;
+            }
+            Event::JoyAxisMotion { axis_idx: 1, value, .. } => {
+                self.stick.1 = value;
+            }
+            _ => {}
+        }
+    }
+
+    fn update_inputs(&mut self, inputs: &mut SmsGgInputs) {
+        let stick_sensitivity = self.config.stick_sensitivity;
+        let stick_x = self.stick_deflection(self.stick.0) * stick_sensitivity;
+        let stick_y = self.stick_deflection(self.stick.1) * stick_sensitivity;
+
+        let peripheral = &mut inputs.p1_peripheral;
+        match self.controller_type {
+            SmsControllerType::Joypad => {}
+            SmsControllerType::Paddle => {
+                self.paddle_position =
+                    (self.paddle_position + stick_x * PADDLE_STICK_SPEED).clamp(0.0, 255.0);
+                peripheral.paddle_position = self.paddle_position.round() as u8;
+            }
+            SmsControllerType::SportsPad => {
+                let (x, y) = self.sports_pad_motion;
+                let x = x + stick_x * SPORTS_PAD_STICK_SPEED;
+                let y = y + stick_y * SPORTS_PAD_STICK_SPEED;
+
+                let report_x = x.round().clamp(i8::MIN.into(), i8::MAX.into());
+                let report_y = y.round().clamp(i8::MIN.into(), i8::MAX.into());
+                peripheral.sports_pad_x = report_x as i8;
+                peripheral.sports_pad_y = report_y as i8;
+
+                // Carry over any movement that was too small or too large to report this frame
+                self.sports_pad_motion = (x - report_x, y - report_y);
+            }
+            SmsControllerType::GraphicBoard => {
+                let (x, y) = self.graphic_board_position;
+                self.graphic_board_position = (
+                    (x + stick_x * GRAPHIC_BOARD_STICK_SPEED).clamp(0.0, GRAPHIC_BOARD_MAX_X),
+                    (y + stick_y * GRAPHIC_BOARD_STICK_SPEED).clamp(0.0, GRAPHIC_BOARD_MAX_Y),
+                );
+                peripheral.graphic_board_x = self.graphic_board_position.0.round() as u8;
+                peripheral.graphic_board_y = self.graphic_board_position.1.round() as u8;
+                peripheral.graphic_board_pen_down = self.graphic_board_pen_down;
+            }
+        }
+    }
+}
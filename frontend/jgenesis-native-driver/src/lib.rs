@@ -1,10 +1,17 @@
 pub mod config;
 pub mod input;
 mod mainloop;
+pub mod msumd;
+pub mod patch;
+pub mod paths;
+pub mod steamdeck;
 
 pub use mainloop::{
-    create_gb, create_genesis, create_nes, create_sega_cd, create_smsgg, create_snes, AudioError,
-    NativeEmulator, NativeEmulatorResult, NativeGameBoyEmulator, NativeGenesisEmulator,
-    NativeNesEmulator, NativeSegaCdEmulator, NativeSmsGgEmulator, NativeSnesEmulator,
-    NativeTickEffect, SaveWriteError,
+    create_atari2600, create_colecovision, create_gb, create_gba, create_genesis, create_nes,
+    create_pce, create_pico, create_sega_cd, create_smsgg, create_snes, create_spc, headless,
+    AudioError, AvDumpError, NativeAtari2600Emulator, NativeColecoVisionEmulator, NativeEmulator,
+    NativeEmulatorResult, NativeGameBoyEmulator, NativeGbaEmulator, NativeGenesisEmulator,
+    NativeNesEmulator, NativePceEmulator, NativePicoEmulator, NativeSegaCdEmulator,
+    NativeSmsGgEmulator, NativeSnesEmulator, NativeSpcEmulator, NativeTickEffect, SaveWriteError,
+    CRASH_REPORT_DIR,
 };
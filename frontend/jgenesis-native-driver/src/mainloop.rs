@@ -1,57 +1,132 @@
 mod audio;
+pub(crate) mod crash;
 mod debug;
+mod discord;
+mod dump;
+mod frameskip;
+mod gdb;
+pub mod headless;
+mod injection;
+mod inputtrace;
+mod music;
+mod practice;
+mod remote;
 mod rewind;
 mod save;
+mod savestate;
+mod savesync;
+mod screenshot;
+mod textures;
+pub(crate) mod window;
 
 use crate::config;
 use crate::config::{
-    CommonConfig, GameBoyConfig, GenesisConfig, NesConfig, SegaCdConfig, SmsGgConfig, SnesConfig,
-    WindowSize,
+    Atari2600Config, ColecoVisionConfig, CommonConfig, DefaultFrame, DiscordConfig, GameBoyConfig,
+    GbaConfig, GenesisConfig, LinkTransportKind, NesConfig, PceConfig, RemoteControlConfig,
+    SegaCdConfig, SmsGgConfig, SnesConfig, WindowSize,
 };
+use crate::input::macros::MacroButton;
 use crate::input::{
-    GameBoyButton, GenesisButton, Hotkey, HotkeyMapResult, HotkeyMapper, InputMapper, Joysticks,
-    MappableInputs, NesButton, SmsGgButton, SnesButton,
+    Atari2600Button, ColecoVisionButton, ControllerHotplug, GameBoyButton, GbaButton,
+    GenesisButton, Hotkey, HotkeyMapResult, HotkeyMapper, InputMapper, Joysticks, MappableInputs,
+    NesButton, PceButton, SmsGgButton, SnesButton,
 };
 use crate::mainloop::audio::SdlAudioOutput;
 use crate::mainloop::debug::{DebugRenderFn, DebuggerWindow};
+use crate::mainloop::discord::DiscordPresence;
+use crate::mainloop::dump::AvDumpWriter;
+use crate::mainloop::frameskip::FrameSkip;
+use crate::mainloop::gdb::{DebuggableFn, GdbStub};
+use crate::mainloop::injection::InputInjectionServer;
+use crate::mainloop::inputtrace::{InputTrace, TraceEvent};
+use crate::mainloop::music::MusicDumper;
+use crate::mainloop::practice::PracticeLoop;
+use crate::mainloop::remote::{RemoteControlServer, RemoteReply, RemoteRequestKind};
 use crate::mainloop::rewind::Rewinder;
 use crate::mainloop::save::FsSaveWriter;
+use crate::mainloop::savestate::{OsdMessage, SaveStateSlots};
+use crate::mainloop::screenshot::Screenshots;
+use crate::mainloop::textures::TileDumpWriter;
+use crate::mainloop::window::WindowGeometryTracker;
+use crate::patch;
+use crate::patch::PatchError;
+use atari2600_core::api::{Atari2600Emulator, Atari2600EmulatorConfig, Atari2600LoadError};
+use atari2600_core::input::Atari2600Inputs;
 pub use audio::AudioError;
 use bincode::error::{DecodeError, EncodeError};
-use bincode::{Decode, Encode};
+use colecovision_core::api::{
+    ColecoVisionEmulator, ColecoVisionEmulatorConfig, ColecoVisionLoadError,
+};
+use colecovision_core::input::ColecoVisionInputs;
+pub use crash::CRASH_REPORT_DIR;
+pub use dump::AvDumpError;
 use gb_core::api::{GameBoyEmulator, GameBoyEmulatorConfig, GameBoyLoadError};
 use gb_core::inputs::GameBoyInputs;
-use genesis_core::{GenesisEmulator, GenesisEmulatorConfig, GenesisInputs};
-use jgenesis_common::frontend::{EmulatorTrait, PartialClone, TickEffect};
-use jgenesis_renderer::renderer::{RendererError, WgpuRenderer};
+use gba_core::api::{GbaEmulator, GbaEmulatorConfig, GbaLoadError};
+use gba_core::input::GbaInputs;
+use genesis_core::pico::{PicoEmulator, PicoInputs};
+use genesis_core::{
+    attach_lock_on_cartridge, is_sonic_and_knuckles, GenesisEmulator, GenesisEmulatorConfig,
+    GenesisInputs, LockOnError, WidescreenPatchError, WidescreenPatches,
+};
+use jgenesis_common::command::{EmulatorCommand, OpenFileKind};
+use jgenesis_common::debug::{Debuggable, FreezeList, SymbolTable};
+use jgenesis_common::frontend::filter::FrameFilterChain;
+use jgenesis_common::frontend::{
+    EmulatorTrait, FrameSize, PartialClone, SaveWriter, TickEffect, TimingMode,
+};
+use jgenesis_common::state::StateError;
+use jgenesis_link::tcp::TcpLink;
+use jgenesis_link::LinkTransport;
+use jgenesis_renderer::border::BorderImage;
+use jgenesis_renderer::config::{RendererConfig, VSyncMode};
+use jgenesis_renderer::deinterlace;
+use jgenesis_renderer::deinterlace::{DeinterlaceMode, Deinterlacer};
+use jgenesis_renderer::frameskip::SkippingRenderer;
+use jgenesis_renderer::renderer::{DisplayArea, RendererError, WgpuRenderer};
 use nes_core::api::{NesEmulator, NesEmulatorConfig, NesInitializationError};
 use nes_core::input::NesInputs;
+use pce_core::api::{PceEmulator, PceEmulatorConfig, PceLoadError};
+use pce_core::input::PceInputs;
 pub use save::SaveWriteError;
 use sdl2::event::{Event, WindowEvent};
 use sdl2::render::TextureValueError;
-use sdl2::video::{FullscreenType, Window, WindowBuildError};
+use sdl2::video::{DisplayMode, FullscreenType, Window, WindowBuildError};
 use sdl2::{AudioSubsystem, EventPump, IntegerOrSdlError, JoystickSubsystem, Sdl, VideoSubsystem};
 use segacd_core::api::{SegaCdEmulator, SegaCdEmulatorConfig, SegaCdLoadError, SegaCdLoadResult};
-use segacd_core::CdRomFileFormat;
+use segacd_core::{m3u, CdRomFileFormat};
 use smsgg_core::psg::PsgVersion;
-use smsgg_core::{SmsGgEmulator, SmsGgEmulatorConfig, SmsGgInputs};
+use smsgg_core::{SmsGgEmulator, SmsGgEmulatorConfig, SmsGgInputs, VdpVersion};
 use snes_core::api::{SnesEmulator, SnesEmulatorConfig, SnesLoadError};
 use snes_core::input::SnesInputs;
+use snes_core::spc::{SpcFile, SpcLoadError, SpcPlayer};
 use std::error::Error;
 use std::ffi::{NulError, OsStr};
-use std::fs::File;
-use std::io::{BufReader, BufWriter};
+use std::fmt::Debug;
+use std::panic::AssertUnwindSafe;
 use std::path::{Path, PathBuf};
 use std::time::Duration;
-use std::{fs, io, thread};
+use std::{fs, io, mem, panic, thread};
 use thiserror::Error;
 
+// Battery saves are written at most this often while the emulator is running (~1 second)
+const SAVE_PERSIST_INTERVAL_FRAMES: u32 = 60;
+
 trait RendererExt {
     fn focus(&mut self);
 
     fn window_id(&self) -> u32;
 
-    fn toggle_fullscreen(&mut self) -> Result<(), String>;
+    // SDL mouse coordinates are in window units while the renderer works in physical pixels
+    fn display_info_in_window_units(&self) -> Option<(FrameSize, DisplayArea)>;
+
+    fn set_magnifier_focus_from_mouse(&mut self, mouse_x: i32, mouse_y: i32);
+
+    fn toggle_fullscreen(
+        &mut self,
+        video: &VideoSubsystem,
+        timing_mode: TimingMode,
+    ) -> Result<(), String>;
 }
 
 impl RendererExt for WgpuRenderer<Window> {
@@ -66,15 +141,35 @@ impl RendererExt for WgpuRenderer<Window> {
         self.window().id()
     }
 
-    fn toggle_fullscreen(&mut self) -> Result<(), String> {
+    fn display_info_in_window_units(&self) -> Option<(FrameSize, DisplayArea)> {
+        let pixels_per_unit = window::pixels_per_window_unit(self.window());
+        self.current_display_info().map(|(frame_size, display_area)| {
+            (frame_size, window::display_area_to_window_units(display_area, pixels_per_unit))
+        })
+    }
+
+    fn set_magnifier_focus_from_mouse(&mut self, mouse_x: i32, mouse_y: i32) {
+        let pixels_per_unit = window::pixels_per_window_unit(self.window());
+        let to_pixels = |position: i32| (f64::from(position) * pixels_per_unit).round() as i32;
+        self.set_magnifier_focus(to_pixels(mouse_x), to_pixels(mouse_y));
+    }
+
+    fn toggle_fullscreen(
+        &mut self,
+        video: &VideoSubsystem,
+        timing_mode: TimingMode,
+    ) -> Result<(), String> {
+        let match_pal_refresh_rate = should_match_pal_refresh_rate(self.config(), timing_mode);
+
         // SAFETY: This is not reassigning the window
         unsafe {
             let window = self.window_mut();
-            let new_fullscreen = match window.fullscreen_state() {
-                FullscreenType::Off => FullscreenType::Desktop,
-                FullscreenType::Desktop | FullscreenType::True => FullscreenType::Off,
-            };
-            window.set_fullscreen(new_fullscreen)
+            match window.fullscreen_state() {
+                FullscreenType::Off => enter_fullscreen(window, video, match_pal_refresh_rate),
+                FullscreenType::Desktop | FullscreenType::True => {
+                    window.set_fullscreen(FullscreenType::Off)
+                }
+            }
         }
     }
 }
@@ -95,13 +190,27 @@ fn sleep(duration: Duration) {
 }
 
 struct HotkeyState<Emulator> {
-    save_state_path: PathBuf,
+    save_states: SaveStateSlots<Emulator>,
     paused: bool,
+    // Set when emulation was paused automatically because a mapped controller disconnected, in
+    // which case it resumes automatically when a mapped controller reconnects
+    paused_for_disconnect: bool,
+    pause_on_controller_disconnect: bool,
     should_step_frame: bool,
     fast_forward_multiplier: u64,
     rewinder: Rewinder<Emulator>,
+    input_trace: InputTrace,
+    practice: PracticeLoop<Emulator>,
+    // Set by the record input macro hotkey; handled by the mainloop because the input mapper is
+    // not available to hotkey handlers
+    macro_recording_toggled: bool,
     debugger_window: Option<DebuggerWindow<Emulator>>,
     debug_render_fn: fn() -> Box<DebugRenderFn<Emulator>>,
+    music_dumper: Option<MusicDumper<Emulator>>,
+    tile_dump_writer: Option<TileDumpWriter<Emulator>>,
+    microphone_fn: Option<fn(&mut Emulator, bool)>,
+    screenshots: Screenshots,
+    frames_since_save_persist: u32,
 }
 
 impl<Emulator: PartialClone> HotkeyState<Emulator> {
@@ -110,24 +219,77 @@ impl<Emulator: PartialClone> HotkeyState<Emulator> {
         save_state_path: PathBuf,
         debug_render_fn: fn() -> Box<DebugRenderFn<Emulator>>,
     ) -> Self {
+        crash::set_rom_path(Path::new(&common_config.rom_file_path));
+
         Self {
-            save_state_path,
+            save_states: SaveStateSlots::new(
+                save_state_path,
+                common_config.auto_save_state,
+                common_config.osd_messages.clone(),
+            ),
             paused: false,
+            paused_for_disconnect: false,
+            pause_on_controller_disconnect: common_config.pause_on_controller_disconnect,
             should_step_frame: false,
             fast_forward_multiplier: common_config.fast_forward_multiplier,
             rewinder: Rewinder::new(Duration::from_secs(
                 common_config.rewind_buffer_length_seconds,
             )),
+            input_trace: InputTrace::new(
+                Path::new(&common_config.rom_file_path),
+                Duration::from_secs(common_config.input_trace_length_seconds),
+            ),
+            practice: PracticeLoop::new(
+                common_config.practice_end_frame_count,
+                common_config.practice_end_condition.clone(),
+            ),
+            macro_recording_toggled: false,
             debugger_window: None,
             debug_render_fn,
+            music_dumper: None,
+            tile_dump_writer: None,
+            microphone_fn: None,
+            screenshots: Screenshots::new(Path::new(&common_config.rom_file_path)),
+            frames_since_save_persist: 0,
+        }
+    }
+
+    fn with_music_dumper(mut self, music_dumper: MusicDumper<Emulator>) -> Self {
+        self.music_dumper = Some(music_dumper);
+        self
+    }
+
+    fn with_tile_dump_writer(mut self, tile_dump_writer: Option<TileDumpWriter<Emulator>>) -> Self {
+        self.tile_dump_writer = tile_dump_writer;
+        self
+    }
+
+    fn with_microphone(mut self, microphone_fn: fn(&mut Emulator, bool)) -> Self {
+        self.microphone_fn = Some(microphone_fn);
+        self
+    }
+
+    fn restart_practice_attempt(&mut self, emulator: &mut Emulator, config: &Emulator::Config)
+    where
+        Emulator: EmulatorTrait,
+    {
+        if let Some(attempt) = self.practice.restart(emulator, config) {
+            self.save_states.show_message(OsdMessage::PracticeAttempt(attempt));
+            self.input_trace.record_event(TraceEvent::PracticeAttemptRestarted);
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum NativeTickEffect {
     None,
     Exit,
+    /// A remote control client asked to open the ROM at this path; the frontend should stop this
+    /// emulator and start a new one for the ROM
+    LoadRom(String),
+    /// The open quick menu hotkey was pressed and emulation is now paused; the frontend should show
+    /// its quick menu and unpause when the menu is closed
+    OpenQuickMenu,
 }
 
 pub struct NativeEmulator<Inputs, Button, Config, Emulator> {
@@ -142,6 +304,20 @@ pub struct NativeEmulator<Inputs, Button, Config, Emulator> {
     event_pump: EventPump,
     video: VideoSubsystem,
     hotkey_state: HotkeyState<Emulator>,
+    gdb_stub: Option<GdbStub<Emulator>>,
+    input_injection: Option<InputInjectionServer<Button>>,
+    remote_control: Option<RemoteControlServer>,
+    // Never read, only held so that the activity is cleared when the emulator is dropped
+    _discord_presence: Option<DiscordPresence>,
+    window_geometry: WindowGeometryTracker,
+    symbols: SymbolTable,
+    freeze_list: FreezeList,
+    // None for emulators that do not implement Debuggable
+    as_debuggable: Option<DebuggableFn<Emulator>>,
+    av_dump: Option<AvDumpWriter>,
+    // None if auto frame skip is disabled
+    frame_skip: Option<FrameSkip>,
+    frame_filters: FrameFilterChain,
 }
 
 impl<Inputs, Button, Config, Emulator: PartialClone>
@@ -151,9 +327,16 @@ impl<Inputs, Button, Config, Emulator: PartialClone>
         &mut self,
         config: &CommonConfig<KC, JC>,
     ) -> Result<(), AudioError> {
-        self.renderer.reload_config(config.renderer_config);
+        self.renderer.reload_config(effective_renderer_config(config));
+        self.renderer.set_custom_border_path(config.border_image_path.as_deref().map(Path::new));
         self.audio_output.reload_config(config)?;
 
+        if let Some(frame_skip) = &mut self.frame_skip {
+            // SAFETY: This is not reassigning the window
+            frame_skip.hide_indicator(unsafe { self.renderer.window_mut() });
+        }
+        self.frame_skip = FrameSkip::from_config(config);
+
         self.hotkey_state.fast_forward_multiplier = config.fast_forward_multiplier;
         // Reset speed multiplier in case the fast forward hotkey changed
         self.renderer.set_speed_multiplier(1);
@@ -162,8 +345,19 @@ impl<Inputs, Button, Config, Emulator: PartialClone>
         self.hotkey_state
             .rewinder
             .set_buffer_duration(Duration::from_secs(config.rewind_buffer_length_seconds));
+        self.hotkey_state
+            .input_trace
+            .set_buffer_duration(Duration::from_secs(config.input_trace_length_seconds));
+        self.hotkey_state.input_trace.record_event(TraceEvent::ConfigReloaded);
+        self.hotkey_state.save_states.set_auto_save(config.auto_save_state);
+        self.hotkey_state.save_states.set_osd_messages(config.osd_messages.clone());
+        self.hotkey_state.pause_on_controller_disconnect = config.pause_on_controller_disconnect;
+        self.hotkey_state.practice.set_end_trigger(
+            config.practice_end_frame_count,
+            config.practice_end_condition.clone(),
+        );
 
-        match HotkeyMapper::from_config(&config.hotkeys) {
+        match HotkeyMapper::from_config(&config.hotkeys, config.axis_deadzone) {
             Ok(hotkey_mapper) => {
                 self.hotkey_mapper = hotkey_mapper;
             }
@@ -187,6 +381,11 @@ impl<Inputs, Button, Config, Emulator: PartialClone>
         let (joysticks, joystick_subsystem) = self.input_mapper.joysticks_mut();
         (&mut self.event_pump, joysticks, joystick_subsystem)
     }
+
+    /// CPU-side filters applied to every frame before it is uploaded to the GPU.
+    pub fn frame_filters_mut(&mut self) -> &mut FrameFilterChain {
+        &mut self.frame_filters
+    }
 }
 
 pub type NativeSmsGgEmulator =
@@ -198,6 +397,7 @@ impl NativeSmsGgEmulator {
     /// This method will return an error if it is unable to reload audio config.
     pub fn reload_smsgg_config(&mut self, config: Box<SmsGgConfig>) -> Result<(), AudioError> {
         log::info!("Reloading config: {config}");
+        crash::set_config(&config);
 
         self.reload_common_config(&config.common)?;
 
@@ -212,15 +412,19 @@ impl NativeSmsGgEmulator {
 
         let emulator_config = config.to_emulator_config(vdp_version, psg_version);
         self.emulator.reload_config(&emulator_config);
+        self.renderer.set_system_default_border(system_default_border(vdp_version));
         self.config = emulator_config;
 
         if let Err(err) = self.input_mapper.reload_config(
+            config.p1_controller_type,
             config.common.keyboard_inputs,
             config.common.joystick_inputs,
+            config.peripheral_config,
             config.common.axis_deadzone,
         ) {
             log::error!("Error reloading input config: {err}");
         }
+        self.input_mapper.reload_macros(&config.common.input_macros);
 
         Ok(())
     }
@@ -235,6 +439,38 @@ impl NativeGenesisEmulator {
     /// This method will return an error if it is unable to reload audio config.
     pub fn reload_genesis_config(&mut self, config: Box<GenesisConfig>) -> Result<(), AudioError> {
         log::info!("Reloading config: {config}");
+        crash::set_config(&config);
+
+        self.reload_common_config(&config.common)?;
+
+        let emulator_config = config.to_emulator_config();
+        self.emulator.reload_config(&emulator_config);
+        self.config = emulator_config;
+
+        if let Err(err) = self.input_mapper.reload_config(
+            config.common.keyboard_inputs,
+            config.common.joystick_inputs,
+            config.common.axis_deadzone,
+        ) {
+            log::error!("Error reloading input config: {err}");
+        }
+        self.input_mapper.reload_macros(&config.common.input_macros);
+        set_deinterlace_mode(&mut self.frame_filters, config.deinterlace_mode);
+
+        Ok(())
+    }
+}
+
+pub type NativePicoEmulator =
+    NativeEmulator<PicoInputs, GenesisButton, GenesisEmulatorConfig, PicoEmulator>;
+
+impl NativePicoEmulator {
+    /// # Errors
+    ///
+    /// This method will return an error if it is unable to reload audio config.
+    pub fn reload_pico_config(&mut self, config: Box<GenesisConfig>) -> Result<(), AudioError> {
+        log::info!("Reloading config: {config}");
+        crash::set_config(&config);
 
         self.reload_common_config(&config.common)?;
 
@@ -249,6 +485,7 @@ impl NativeGenesisEmulator {
         ) {
             log::error!("Error reloading input config: {err}");
         }
+        self.input_mapper.reload_macros(&config.common.input_macros);
 
         Ok(())
     }
@@ -263,6 +500,7 @@ impl NativeSegaCdEmulator {
     /// This method will return an error if it is unable to reload audio config.
     pub fn reload_sega_cd_config(&mut self, config: Box<SegaCdConfig>) -> Result<(), AudioError> {
         log::info!("Reloading config: {config}");
+        crash::set_config(&config);
 
         self.reload_common_config(&config.genesis.common)?;
         self.emulator.reload_config(&config.to_emulator_config());
@@ -274,6 +512,8 @@ impl NativeSegaCdEmulator {
         ) {
             log::error!("Error reloading input config: {err}");
         }
+        self.input_mapper.reload_macros(&config.genesis.common.input_macros);
+        set_deinterlace_mode(&mut self.frame_filters, config.genesis.deinterlace_mode);
 
         Ok(())
     }
@@ -281,6 +521,7 @@ impl NativeSegaCdEmulator {
     #[allow(clippy::missing_panics_doc)]
     pub fn remove_disc(&mut self) {
         self.emulator.remove_disc();
+        self.hotkey_state.input_trace.record_event(TraceEvent::DiscRemoved);
 
         // SAFETY: This is not reassigning the window
         unsafe {
@@ -305,6 +546,7 @@ impl NativeSegaCdEmulator {
         });
 
         self.emulator.change_disc(rom_path, rom_format)?;
+        self.hotkey_state.input_trace.record_event(TraceEvent::DiscChanged);
 
         let title = format!("sega cd - {}", self.emulator.disc_title());
 
@@ -328,6 +570,7 @@ impl NativeNesEmulator {
     /// This method will return an error if it is unable to reload audio config.
     pub fn reload_nes_config(&mut self, config: Box<NesConfig>) -> Result<(), AudioError> {
         log::info!("Reloading config: {config}");
+        crash::set_config(&config);
 
         self.reload_common_config(&config.common)?;
 
@@ -336,15 +579,23 @@ impl NativeNesEmulator {
         self.config = emulator_config;
 
         if let Err(err) = self.input_mapper.reload_config(
+            self.emulator.expansion_device(),
             config.common.keyboard_inputs,
             config.common.joystick_inputs,
             config.common.axis_deadzone,
         ) {
             log::error!("Error reloading input config: {err}");
         }
+        self.input_mapper.reload_macros(&config.common.input_macros);
 
         Ok(())
     }
+
+    pub fn scan_barcode(&mut self, barcode: &str) {
+        if let Err(err) = self.emulator.scan_barcode(barcode) {
+            log::error!("Error scanning barcode '{barcode}': {err}");
+        }
+    }
 }
 
 pub type NativeSnesEmulator =
@@ -356,6 +607,7 @@ impl NativeSnesEmulator {
     /// This method will return an error if it is unable to reload audio config.
     pub fn reload_snes_config(&mut self, config: Box<SnesConfig>) -> Result<(), AudioError> {
         log::info!("Reloading config: {config}");
+        crash::set_config(&config);
 
         self.reload_common_config(&config.common)?;
 
@@ -372,11 +624,15 @@ impl NativeSnesEmulator {
         ) {
             log::error!("Error reloading input config: {err}");
         }
+        self.input_mapper.reload_macros(&config.common.input_macros);
+        set_deinterlace_mode(&mut self.frame_filters, config.effective_deinterlace_mode());
 
         Ok(())
     }
 }
 
+pub type NativeSpcEmulator = NativeEmulator<SnesInputs, SnesButton, SnesEmulatorConfig, SpcPlayer>;
+
 pub type NativeGameBoyEmulator =
     NativeEmulator<GameBoyInputs, GameBoyButton, GameBoyEmulatorConfig, GameBoyEmulator>;
 
@@ -386,6 +642,135 @@ impl NativeGameBoyEmulator {
     /// This method will return an error if it is unable to reload audio config.
     pub fn reload_gb_config(&mut self, config: Box<GameBoyConfig>) -> Result<(), AudioError> {
         log::info!("Reloading config: {config}");
+        crash::set_config(&config);
+
+        self.reload_common_config(&config.common)?;
+
+        let emulator_config = config.to_emulator_config();
+        self.emulator.reload_config(&emulator_config);
+        self.config = emulator_config;
+
+        if let Err(err) = self.input_mapper.reload_config(
+            config.common.keyboard_inputs,
+            config.common.joystick_inputs,
+            config.common.axis_deadzone,
+        ) {
+            log::error!("Error reloading input config: {err}");
+        }
+        self.input_mapper.reload_macros(&config.common.input_macros);
+
+        Ok(())
+    }
+}
+
+pub type NativePceEmulator = NativeEmulator<PceInputs, PceButton, PceEmulatorConfig, PceEmulator>;
+
+impl NativePceEmulator {
+    /// # Errors
+    ///
+    /// This method will return an error if it is unable to reload audio config.
+    pub fn reload_pce_config(&mut self, config: Box<PceConfig>) -> Result<(), AudioError> {
+        log::info!("Reloading config: {config}");
+        crash::set_config(&config);
+
+        self.reload_common_config(&config.common)?;
+
+        let emulator_config = config.to_emulator_config();
+        self.emulator.reload_config(&emulator_config);
+        self.config = emulator_config;
+
+        if let Err(err) = self.input_mapper.reload_config(
+            config.common.keyboard_inputs,
+            config.common.joystick_inputs,
+            config.common.axis_deadzone,
+        ) {
+            log::error!("Error reloading input config: {err}");
+        }
+        self.input_mapper.reload_macros(&config.common.input_macros);
+
+        Ok(())
+    }
+}
+
+pub type NativeGbaEmulator = NativeEmulator<GbaInputs, GbaButton, GbaEmulatorConfig, GbaEmulator>;
+
+impl NativeGbaEmulator {
+    /// # Errors
+    ///
+    /// This method will return an error if it is unable to reload audio config.
+    pub fn reload_gba_config(&mut self, config: Box<GbaConfig>) -> Result<(), AudioError> {
+        log::info!("Reloading config: {config}");
+        crash::set_config(&config);
+
+        self.reload_common_config(&config.common)?;
+
+        let emulator_config = config.to_emulator_config();
+        self.emulator.reload_config(&emulator_config);
+        self.config = emulator_config;
+
+        if let Err(err) = self.input_mapper.reload_config(
+            config.common.keyboard_inputs,
+            config.common.joystick_inputs,
+            config.common.axis_deadzone,
+        ) {
+            log::error!("Error reloading input config: {err}");
+        }
+        self.input_mapper.reload_macros(&config.common.input_macros);
+
+        Ok(())
+    }
+}
+
+pub type NativeAtari2600Emulator =
+    NativeEmulator<Atari2600Inputs, Atari2600Button, Atari2600EmulatorConfig, Atari2600Emulator>;
+
+impl NativeAtari2600Emulator {
+    /// # Errors
+    ///
+    /// This method will return an error if it is unable to reload audio config.
+    pub fn reload_atari2600_config(
+        &mut self,
+        config: Box<Atari2600Config>,
+    ) -> Result<(), AudioError> {
+        log::info!("Reloading config: {config}");
+        crash::set_config(&config);
+
+        self.reload_common_config(&config.common)?;
+
+        let emulator_config = config.to_emulator_config();
+        self.emulator.reload_config(&emulator_config);
+        self.config = emulator_config;
+
+        if let Err(err) = self.input_mapper.reload_config(
+            config.common.keyboard_inputs,
+            config.common.joystick_inputs,
+            config.common.axis_deadzone,
+        ) {
+            log::error!("Error reloading input config: {err}");
+        }
+        self.input_mapper.reload_macros(&config.common.input_macros);
+
+        Ok(())
+    }
+}
+
+pub type NativeColecoVisionEmulator = NativeEmulator<
+    ColecoVisionInputs,
+    ColecoVisionButton,
+    ColecoVisionEmulatorConfig,
+    ColecoVisionEmulator,
+>;
+
+impl NativeColecoVisionEmulator {
+    /// # Errors
+    ///
+    /// This method will return an error if it is unable to reload audio config.
+    pub fn reload_colecovision_config(
+        &mut self,
+        config: Box<ColecoVisionConfig>,
+    ) -> Result<(), AudioError> {
+        log::info!("Reloading config: {config}");
+        crash::set_config(&config);
 
         self.reload_common_config(&config.common)?;
 
@@ -400,6 +785,7 @@ impl NativeGameBoyEmulator {
         ) {
             log::error!("Error reloading input config: {err}");
         }
+        self.input_mapper.reload_macros(&config.common.input_macros);
 
         Ok(())
     }
@@ -413,6 +799,8 @@ pub enum NativeEmulatorError {
     Audio(#[from] AudioError),
     #[error("{0}")]
     SaveWrite(#[from] SaveWriteError),
+    #[error("{0}")]
+    AvDump(#[from] AvDumpError),
     #[error("Error initializing SDL2: {0}")]
     SdlInit(String),
     #[error("Error initializing SDL2 video subsystem: {0}")]
@@ -437,6 +825,8 @@ pub enum NativeEmulatorError {
     SdlCreateTexture(#[from] TextureValueError),
     #[error("Error toggling window fullscreen: {0}")]
     SdlSetFullscreen(String),
+    #[error("Error setting initial window size and position: {0}")]
+    SdlSetWindowGeometry(String),
     #[error("Error opening joystick {device_id}: {source}")]
     SdlJoystickOpen {
         device_id: u32,
@@ -459,6 +849,24 @@ pub enum NativeEmulatorError {
         #[source]
         source: io::Error,
     },
+    #[error("{0}")]
+    RomPatch(#[from] PatchError),
+    #[error("{0}")]
+    GenesisLockOn(#[from] LockOnError),
+    #[error("Failed to read widescreen patch file at '{path}': {source}")]
+    GenesisWidescreenPatchesRead {
+        path: String,
+        #[source]
+        source: io::Error,
+    },
+    #[error("Invalid widescreen patch file: {0}")]
+    GenesisWidescreenPatches(#[from] WidescreenPatchError),
+    #[error("Failed to read texture pack directory at '{path}': {source}")]
+    GenesisTexturePackRead {
+        path: String,
+        #[source]
+        source: io::Error,
+    },
     #[error("BIOS is required for Sega CD emulation")]
     SegaCdNoBios,
     #[error("Error opening BIOS file at '{path}': {source}")]
@@ -475,19 +883,105 @@ pub enum NativeEmulatorError {
     #[error("{0}")]
     SnesLoad(#[from] SnesLoadError),
     #[error("{0}")]
+    SpcLoad(#[from] SpcLoadError),
+    #[error("{0}")]
     GameBoyLoad(#[from] GameBoyLoadError),
+    #[error("{0}")]
+    PceLoad(#[from] PceLoadError),
+    #[error("BIOS is required for Game Boy Advance emulation")]
+    GbaNoBios,
+    #[error("Error opening BIOS file at '{path}': {source}")]
+    GbaBiosRead {
+        path: String,
+        #[source]
+        source: io::Error,
+    },
+    #[error("{0}")]
+    GbaLoad(#[from] GbaLoadError),
+    #[error("{0}")]
+    Atari2600Load(#[from] Atari2600LoadError),
+    #[error("BIOS is required for ColecoVision emulation")]
+    ColecoVisionNoBios,
+    #[error("Error opening BIOS file at '{path}': {source}")]
+    ColecoVisionBiosRead {
+        path: String,
+        #[source]
+        source: io::Error,
+    },
+    #[error("{0}")]
+    ColecoVisionLoad(#[from] ColecoVisionLoadError),
     #[error("I/O error opening save state file '{path}': {source}")]
     StateFileOpen {
         path: String,
         #[source]
         source: io::Error,
     },
+    #[error("I/O error writing save state file '{path}': {source}")]
+    StateFileWrite {
+        path: String,
+        #[source]
+        source: io::Error,
+    },
+    #[error("I/O error reading save state file '{path}': {source}")]
+    StateFileRead {
+        path: String,
+        #[source]
+        source: io::Error,
+    },
     #[error("Error saving state: {0}")]
     SaveState(#[from] EncodeError),
     #[error("Error loading state: {0}")]
     LoadState(#[from] DecodeError),
+    #[error("{0}")]
+    StateFormat(#[from] StateError),
+    #[error("Error creating AV dump files at '{path}': {source}")]
+    AvDumpCreate {
+        path: String,
+        #[source]
+        source: io::Error,
+    },
+    #[error("Error starting GDB server on port {port}: {source}")]
+    GdbServer {
+        port: u16,
+        #[source]
+        source: io::Error,
+    },
+    #[error("Error starting input injection server on port {port}: {source}")]
+    InputInjectionServer {
+        port: u16,
+        #[source]
+        source: io::Error,
+    },
+    #[error("Error starting remote control server on port {port}: {source}")]
+    RemoteControlServer {
+        port: u16,
+        #[source]
+        source: io::Error,
+    },
+    #[error("Remote control server requires a non-empty token")]
+    RemoteControlNoToken,
+    #[error("Error opening link connection at '{address}': {source}")]
+    LinkTransport {
+        address: String,
+        #[source]
+        source: io::Error,
+    },
+    #[error("Error writing screenshot to '{path}': {source}")]
+    HeadlessScreenshot {
+        path: String,
+        #[source]
+        source: image::ImageError,
+    },
     #[error("Error in emulation core: {0}")]
     Emulator(#[source] Box<dyn Error + Send + Sync + 'static>),
+    #[error("Emulator panicked: {0}")]
+    Panic(String),
+    #[error("{source}\n\nA crash report was written to '{bundle_path}'")]
+    CrashReport {
+        #[source]
+        source: Box<NativeEmulatorError>,
+        bundle_path: String,
+    },
 }
 
 pub type NativeEmulatorResult<T> = Result<T, NativeEmulatorError>;
@@ -495,57 +989,137 @@ pub type NativeEmulatorResult<T> = Result<T, NativeEmulatorError>;
 // TODO simplify or generalize these trait bounds
 impl<Inputs, Button, Config, Emulator> NativeEmulator<Inputs, Button, Config, Emulator>
 where
-    Inputs: Default + MappableInputs<Button>,
-    Button: Copy,
+    Inputs: Default + Debug + MappableInputs<Button>,
+    Button: MacroButton,
     Emulator: EmulatorTrait<Inputs = Inputs, Config = Config>,
-    Emulator::Err<RendererError, AudioError, SaveWriteError>: Error + Send + Sync + 'static,
+    Emulator::Err<RendererError, AudioError>: Error + Send + Sync + 'static,
+    Emulator::Err<AvDumpError, AvDumpError>: Error + Send + Sync + 'static,
 {
     /// Run the emulator until a frame is rendered.
     ///
     /// # Errors
     ///
     /// This method will propagate any errors encountered when rendering frames, pushing audio
-    /// samples, or writing save files.
+    /// samples, or writing save files. If the emulation core returns an error or panics, a crash
+    /// report is written and the returned error includes its path.
     pub fn render_frame(&mut self) -> NativeEmulatorResult<NativeTickEffect> {
+        let (err, bundle_path) =
+            match panic::catch_unwind(AssertUnwindSafe(|| self.render_frame_inner())) {
+                Ok(Ok(effect @ (NativeTickEffect::Exit | NativeTickEffect::LoadRom(_)))) => {
+                    self.persist_save();
+                    self.write_auto_save_state();
+                    return Ok(effect);
+                }
+                Ok(Err(err @ NativeEmulatorError::Emulator(_))) => {
+                    let bundle_path = crash::write_bundle(&err.to_string());
+                    (err, bundle_path)
+                }
+                Ok(result) => return result,
+                Err(payload) => {
+                    let message = crash::panic_message(payload.as_ref());
+                    (NativeEmulatorError::Panic(message), crash::take_panic_bundle())
+                }
+            };
+
+        let Some(bundle_path) = bundle_path else { return Err(err) };
+        crash::write_save_state(&bundle_path, &self.emulator);
+
+        Err(NativeEmulatorError::CrashReport {
+            source: Box::new(err),
+            bundle_path: bundle_path.display().to_string(),
+        })
+    }
+
+    fn render_frame_inner(&mut self) -> NativeEmulatorResult<NativeTickEffect> {
+        if self.hotkey_state.save_states.resume_from_auto_save(&mut self.emulator, &self.config) {
+            self.hotkey_state.input_trace.record_event(TraceEvent::LoadState);
+        }
+
         loop {
             let rewinding = self.hotkey_state.rewinder.is_rewinding();
-            let should_tick_emulator =
-                !rewinding && (!self.hotkey_state.paused || self.hotkey_state.should_step_frame);
-            let frame_rendered = should_tick_emulator
-                && self
-                    .emulator
-                    .tick(
-                        &mut self.renderer,
-                        &mut self.audio_output,
-                        self.input_mapper.inputs(),
-                        &mut self.save_writer,
-                    )
-                    .map_err(|err| NativeEmulatorError::Emulator(err.into()))?
-                    == TickEffect::FrameRendered;
+            let debugger_halted = self.gdb_stub.as_ref().is_some_and(GdbStub::is_halted);
+            let should_tick_emulator = !rewinding
+                && !debugger_halted
+                && (!self.hotkey_state.paused || self.hotkey_state.should_step_frame);
+            let frame_rendered =
+                should_tick_emulator && self.tick_emulator()? == TickEffect::FrameRendered;
+
+            if should_tick_emulator {
+                if let Some(gdb_stub) = &mut self.gdb_stub {
+                    gdb_stub.after_tick(&mut self.emulator);
+                }
+            }
+
+            if frame_rendered && !self.freeze_list.is_empty() {
+                if let Some(as_debuggable) = self.as_debuggable {
+                    self.freeze_list.apply(as_debuggable(&mut self.emulator));
+                }
+            }
+
+            if frame_rendered {
+                self.hotkey_state.input_trace.record_frame(self.input_mapper.inputs());
+
+                self.hotkey_state.frames_since_save_persist += 1;
+                if self.hotkey_state.frames_since_save_persist >= SAVE_PERSIST_INTERVAL_FRAMES {
+                    self.persist_save();
+                }
+
+                if self.hotkey_state.practice.end_reached(&mut self.emulator, self.as_debuggable) {
+                    self.hotkey_state.restart_practice_attempt(&mut self.emulator, &self.config);
+                }
+            }
 
             if !should_tick_emulator || frame_rendered {
                 self.hotkey_state.should_step_frame = false;
 
+                if let Some(gdb_stub) = &mut self.gdb_stub {
+                    gdb_stub.poll(&mut self.emulator);
+                }
+
+                if let Some(input_injection) = &mut self.input_injection {
+                    if frame_rendered {
+                        input_injection.end_frame();
+                    }
+                    input_injection.poll();
+                    self.input_mapper.set_injected_buttons(&input_injection.buttons());
+                }
+
+                if let Some(effect) = self.poll_remote_control() {
+                    return Ok(effect);
+                }
+
                 if let Some(debugger_window) = &mut self.hotkey_state.debugger_window {
-                    if let Err(err) = debugger_window.update(&mut self.emulator) {
+                    if let Err(err) = debugger_window.update(
+                        &mut self.emulator,
+                        &self.symbols,
+                        &mut self.freeze_list,
+                        &mut self.save_writer,
+                        self.audio_output.statistics(),
+                    ) {
                         log::error!("Debugger window error: {err}");
                     }
                 }
 
                 for event in self.event_pump.poll_iter() {
-                    self.input_mapper.handle_event(
+                    if let Some(hotplug) = self.input_mapper.handle_event(
                         &event,
                         self.renderer.window_id(),
-                        self.renderer.current_display_info(),
-                    )?;
+                        self.renderer.display_info_in_window_units(),
+                    )? {
+                        self.handle_controller_hotplug(hotplug);
+                    }
 
                     if let Some(debugger_window) = &mut self.hotkey_state.debugger_window {
                         debugger_window.handle_sdl_event(&event);
                     }
 
-                    if handle_hotkeys(HandleHotkeysArgs {
+                    let joystick_hotkeys = self
+                        .hotkey_mapper
+                        .check_for_joystick_hotkeys(&event, self.input_mapper.joysticks());
+                    match handle_hotkeys(HandleHotkeysArgs {
                         hotkey_mapper: &self.hotkey_mapper,
                         event: &event,
+                        joystick_hotkeys: &joystick_hotkeys,
                         emulator: &mut self.emulator,
                         config: &self.config,
                         renderer: &mut self.renderer,
@@ -553,12 +1127,18 @@ where
                         save_writer: &mut self.save_writer,
                         video: &self.video,
                         hotkey_state: &mut self.hotkey_state,
-                    })? == HotkeyResult::Quit
-                    {
-                        return Ok(NativeTickEffect::Exit);
+                        as_debuggable: self.as_debuggable,
+                    })? {
+                        HotkeyResult::None => {}
+                        HotkeyResult::Quit => return Ok(NativeTickEffect::Exit),
+                        HotkeyResult::OpenQuickMenu => return Ok(NativeTickEffect::OpenQuickMenu),
                     }
 
-                    match event {
+                    if mem::take(&mut self.hotkey_state.macro_recording_toggled) {
+                        self.toggle_macro_recording();
+                    }
+
+                    match event {
                         Event::Quit { .. } => {
                             return Ok(NativeTickEffect::Exit);
                         }
@@ -579,15 +1159,53 @@ where
                             }
 
                             if window_id == self.renderer.window_id() {
-                                handle_window_event(win_event, &mut self.renderer);
+                                handle_window_event(
+                                    win_event,
+                                    &mut self.renderer,
+                                    &mut self.window_geometry,
+                                );
                             }
                         }
+                        Event::MouseMotion { x, y, window_id, .. }
+                            if window_id == self.renderer.window_id() =>
+                        {
+                            self.renderer.set_magnifier_focus_from_mouse(x, y);
+                        }
                         _ => {}
                     }
                 }
 
+                // Frames are not rendered while the emulator is not running, so redraw the current
+                // frame if the save state OSD appeared or disappeared. Also check the current paused
+                // state in case an event above just paused emulation
+                if self.hotkey_state.save_states.take_osd_changed()
+                    && (!should_tick_emulator || self.hotkey_state.paused)
+                    && !rewinding
+                {
+                    let mut renderer =
+                        self.hotkey_state.save_states.osd_renderer(&mut self.renderer);
+                    self.emulator.force_render(&mut self.frame_filters.renderer(&mut renderer))?;
+                }
+
                 if frame_rendered {
+                    self.input_mapper.update_peripheral_inputs();
+                    self.input_mapper.end_macro_frame();
                     self.hotkey_state.rewinder.record_frame(&self.emulator);
+
+                    if let Some(music_dumper) = &mut self.hotkey_state.music_dumper {
+                        music_dumper.after_frame(&mut self.emulator);
+                    }
+
+                    if let Some(tile_dump_writer) = &mut self.hotkey_state.tile_dump_writer {
+                        tile_dump_writer.after_frame(&mut self.emulator);
+                    }
+
+                    if let Some(frame_skip) = &mut self.frame_skip {
+                        let falling_behind = self.audio_output.is_falling_behind();
+                        // SAFETY: This is not reassigning the window
+                        frame_skip
+                            .after_frame(falling_behind, unsafe { self.renderer.window_mut() });
+                    }
                 }
 
                 if rewinding {
@@ -598,7 +1216,7 @@ where
                     )?;
                 }
 
-                if rewinding || self.hotkey_state.paused {
+                if rewinding || debugger_halted || self.hotkey_state.paused {
                     // Don't spin loop when the emulator is not actively running
                     sleep(Duration::from_millis(1));
                 }
@@ -608,12 +1226,53 @@ where
         }
     }
 
+    fn tick_emulator(&mut self) -> NativeEmulatorResult<TickEffect> {
+        match &mut self.av_dump {
+            Some(av_dump) => {
+                let (mut renderer, audio_output) = av_dump.outputs(&mut self.renderer);
+                let mut renderer = self.hotkey_state.screenshots.renderer(&mut renderer);
+                self.emulator
+                    .tick_filtered(
+                        &mut self.frame_filters,
+                        &mut renderer,
+                        audio_output,
+                        self.input_mapper.inputs(),
+                    )
+                    .map_err(|err| NativeEmulatorError::Emulator(err.into()))
+            }
+            None => {
+                // Audio is still generated for skipped frames
+                let skip_frame =
+                    self.frame_skip.as_ref().is_some_and(FrameSkip::skip_current_frame);
+                let mut renderer = self.hotkey_state.save_states.osd_renderer(&mut self.renderer);
+                let mut renderer = self.hotkey_state.screenshots.renderer(&mut renderer);
+                self.emulator
+                    .tick_filtered(
+                        &mut self.frame_filters,
+                        &mut SkippingRenderer::new(&mut renderer, skip_frame),
+                        &mut self.audio_output,
+                        self.input_mapper.inputs(),
+                    )
+                    .map_err(|err| NativeEmulatorError::Emulator(err.into()))
+            }
+        }
+    }
+
     pub fn soft_reset(&mut self) {
         self.emulator.soft_reset();
+        self.hotkey_state.input_trace.record_event(TraceEvent::SoftReset);
     }
 
     pub fn hard_reset(&mut self) {
+        self.persist_save();
         self.emulator.hard_reset(&mut self.save_writer);
+        self.frame_filters.reset();
+        self.hotkey_state.input_trace.record_event(TraceEvent::HardReset);
+    }
+
+    /// Write the recent input trace to a text file next to the ROM file.
+    pub fn dump_input_trace(&self) {
+        self.hotkey_state.input_trace.dump();
     }
 
     pub fn open_memory_viewer(&mut self) {
@@ -622,6 +1281,186 @@ where
                 open_debugger_window(&self.video, self.hotkey_state.debug_render_fn);
         }
     }
+
+    /// Save state to the currently selected save state slot, the same file that the save state
+    /// hotkey writes to.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if it is unable to write the save state file.
+    pub fn save_state(&mut self) -> NativeEmulatorResult<()> {
+        self.hotkey_state.save_states.save(&mut self.emulator)?;
+        self.hotkey_state.input_trace.record_event(TraceEvent::SaveState);
+
+        Ok(())
+    }
+
+    /// Load state from the currently selected save state slot, the same file that the load state
+    /// hotkey reads from. Errors are logged and leave the current emulator state unchanged.
+    pub fn load_state(&mut self) {
+        self.hotkey_state.save_states.load(&mut self.emulator, &self.config);
+        self.hotkey_state.input_trace.record_event(TraceEvent::LoadState);
+    }
+
+    /// Write battery-backed save memory to disk if it has changed since it was last written.
+    ///
+    /// Frontends should call this before dropping the emulator; it is also called periodically
+    /// while the emulator is running.
+    pub fn persist_save(&mut self) {
+        self.hotkey_state.frames_since_save_persist = 0;
+        persist_save(&mut self.emulator, &mut self.save_writer);
+    }
+
+    /// Write the auto save state if auto save is enabled. This is done automatically when
+    /// [`render_frame`](Self::render_frame) returns [`NativeTickEffect::Exit`]; frontends that
+    /// stop the emulator in other ways should call this first.
+    pub fn write_auto_save_state(&mut self) {
+        self.hotkey_state.save_states.write_auto_save(&mut self.emulator);
+    }
+
+    /// Restore the emulator state from before the most recent load state.
+    pub fn undo_load_state(&mut self) {
+        if self.hotkey_state.save_states.undo_load(&mut self.emulator, &self.config) {
+            self.hotkey_state.input_trace.record_event(TraceEvent::LoadStateUndone);
+        }
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        set_paused(&mut self.hotkey_state, &mut self.emulator, &mut self.save_writer, paused);
+    }
+
+    /// Apply a frontend-agnostic [`EmulatorCommand`].
+    ///
+    /// Opening files and swapping discs require creating a new emulator or access to a specific
+    /// core, so those commands must be handled by the frontend and are ignored here.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if it is unable to write a save state file.
+    pub fn handle_command(&mut self, command: EmulatorCommand) -> NativeEmulatorResult<()> {
+        match command {
+            EmulatorCommand::SoftReset => self.soft_reset(),
+            EmulatorCommand::HardReset => self.hard_reset(),
+            EmulatorCommand::SaveState => self.save_state()?,
+            EmulatorCommand::LoadState => self.load_state(),
+            EmulatorCommand::SetPaused(paused) => self.set_paused(paused),
+            EmulatorCommand::SetSpeedMultiplier(speed_multiplier) => {
+                let speed_multiplier = speed_multiplier.max(1);
+                self.renderer.set_speed_multiplier(speed_multiplier);
+                self.audio_output.set_speed_multiplier(speed_multiplier);
+            }
+            EmulatorCommand::Screenshot => self.hotkey_state.screenshots.request(),
+            EmulatorCommand::OpenFile { .. } | EmulatorCommand::SwapDisc(_) => {
+                log::warn!("Ignoring command that must be handled by the frontend: {command:?}");
+            }
+        }
+
+        Ok(())
+    }
+
+    fn poll_remote_control(&mut self) -> Option<NativeTickEffect> {
+        let remote_control = self.remote_control.as_mut()?;
+
+        let mut effect = None;
+        for request in remote_control.poll() {
+            let reply = match &request.kind {
+                RemoteRequestKind::Command {
+                    command: EmulatorCommand::OpenFile { kind: OpenFileKind::Rom, path: Some(path) },
+                } => {
+                    effect = Some(NativeTickEffect::LoadRom(path.clone()));
+                    RemoteReply::Ok
+                }
+                RemoteRequestKind::Command {
+                    command:
+                        command @ (EmulatorCommand::OpenFile { .. } | EmulatorCommand::SwapDisc(_)),
+                } => RemoteReply::Error(format!("Unsupported command: {command:?}")),
+                RemoteRequestKind::Command { command } => {
+                    match self.handle_command(command.clone()) {
+                        Ok(()) => RemoteReply::Ok,
+                        Err(err) => RemoteReply::Error(err.to_string()),
+                    }
+                }
+                &RemoteRequestKind::ReadMemory { address, length } => {
+                    self.read_memory(address, length)
+                }
+            };
+
+            if let Some(remote_control) = &mut self.remote_control {
+                remote_control.reply(&request, &reply);
+            }
+        }
+
+        effect
+    }
+
+    fn read_memory(&mut self, address: u32, length: u32) -> RemoteReply {
+        let Some(as_debuggable) = self.as_debuggable else {
+            return RemoteReply::Error("Memory reads are not supported for this system".into());
+        };
+
+        if length > remote::MAX_READ_LEN {
+            return RemoteReply::Error(format!(
+                "Memory reads are limited to {} bytes",
+                remote::MAX_READ_LEN
+            ));
+        }
+
+        let debuggable = as_debuggable(&mut self.emulator);
+        let data = (0..length).map(|i| debuggable.peek_memory(address.wrapping_add(i))).collect();
+        RemoteReply::Memory(data)
+    }
+
+    fn handle_controller_hotplug(&mut self, hotplug: ControllerHotplug) {
+        match hotplug {
+            ControllerHotplug::MappedDisconnected => {
+                if !self.hotkey_state.pause_on_controller_disconnect || self.hotkey_state.paused {
+                    return;
+                }
+
+                log::info!("Pausing emulation because a mapped controller disconnected");
+                self.set_paused(true);
+                self.hotkey_state.paused_for_disconnect = true;
+                self.hotkey_state
+                    .save_states
+                    .show_persistent_message(OsdMessage::ControllerDisconnected);
+            }
+            ControllerHotplug::MappedConnected => {
+                if !self.hotkey_state.paused_for_disconnect {
+                    return;
+                }
+
+                log::info!("Resuming emulation because a mapped controller connected");
+                self.set_paused(false);
+                self.hotkey_state.save_states.show_message(OsdMessage::ControllerConnected);
+            }
+        }
+    }
+
+    fn toggle_macro_recording(&mut self) {
+        if !self.input_mapper.is_recording_macro() {
+            self.input_mapper.start_macro_recording();
+            self.hotkey_state.save_states.show_message(OsdMessage::RecordingMacro);
+            log::info!("Started recording input macro");
+            return;
+        }
+
+        match self.input_mapper.stop_macro_recording() {
+            Some(sequence) => {
+                log::info!("Recorded input macro: {sequence}");
+                self.hotkey_state.save_states.show_message(OsdMessage::MacroRecorded);
+            }
+            None => {
+                log::info!("Stopped recording input macro; no buttons were pressed");
+                self.hotkey_state.save_states.show_message(OsdMessage::MacroEmpty);
+            }
+        }
+    }
+
+    /// Returns the input macro sequences that were recorded with the record input macro hotkey
+    /// since the last call, so that the frontend can add them to its input config.
+    pub fn take_recorded_input_macros(&mut self) -> Vec<String> {
+        self.input_mapper.take_recorded_macros()
+    }
 }
 
 /// Create an emulator with the SMS/GG core with the given config.
@@ -631,19 +1470,17 @@ where
 /// This function will propagate any video, audio, or disk errors encountered.
 pub fn create_smsgg(config: Box<SmsGgConfig>) -> NativeEmulatorResult<NativeSmsGgEmulator> {
     log::info!("Running with config: {config}");
+    crash::set_config(&config);
 
     let rom_file_path = Path::new(&config.common.rom_file_path);
     let file_ext = parse_file_ext(rom_file_path)?;
 
     let save_state_path = rom_file_path.with_extension("ss0");
 
-    let rom = fs::read(rom_file_path).map_err(|source| NativeEmulatorError::RomRead {
-        path: rom_file_path.display().to_string(),
-        source,
-    })?;
+    let rom = read_patched_rom(rom_file_path, &config.common.rom_patch_paths)?;
 
     let save_path = rom_file_path.with_extension("sav");
-    let mut save_writer = FsSaveWriter::new(save_path);
+    let mut save_writer = FsSaveWriter::with_sync(save_path, config.common.save_sync.as_ref());
 
     let vdp_version =
         config.vdp_version.unwrap_or_else(|| config::default_vdp_version_for_ext(file_ext));
@@ -653,35 +1490,51 @@ pub fn create_smsgg(config: Box<SmsGgConfig>) -> NativeEmulatorResult<NativeSmsG
     log::info!("VDP version: {vdp_version:?}");
     log::info!("PSG version: {psg_version:?}");
 
+    let emulator_config = config.to_emulator_config(vdp_version, psg_version);
+    let mut emulator = SmsGgEmulator::create(rom, emulator_config, &mut save_writer);
+
+    if let Some(link_transport) =
+        open_link_transport(config.link_transport, &config.link_tcp_address)?
+    {
+        emulator.set_link_transport(link_transport);
+    }
+
     let (sdl, video, audio, joystick, event_pump) =
         init_sdl(config.common.hide_cursor_over_window)?;
 
-    let WindowSize { width: window_width, height: window_height } =
-        config.common.window_size.unwrap_or_else(|| config::default_smsgg_window_size(vdp_version));
+    let window_geometry = WindowGeometryTracker::new("smsgg");
 
     let rom_title = file_name_no_ext(rom_file_path)?;
     let window = create_window(
         &video,
         &format!("smsgg - {rom_title}"),
-        window_width,
-        window_height,
+        &window_geometry,
+        config.common.window_size,
+        config::smsgg_default_frame(vdp_version, emulator_config.pixel_aspect_ratio),
         config.common.launch_in_fullscreen,
+        should_match_pal_refresh_rate(config.common.renderer_config, emulator.timing_mode()),
     )?;
 
-    let emulator_config = config.to_emulator_config(vdp_version, psg_version);
+    let discord_presence = start_discord_presence(
+        config.common.discord.as_ref(),
+        if file_ext == "gg" { "Game Gear" } else { "Master System" },
+        &rom_title,
+    );
 
-    let renderer =
-        pollster::block_on(WgpuRenderer::new(window, Window::size, config.common.renderer_config))?;
+    let mut renderer = create_renderer(window, &config.common)?;
+    renderer.set_system_default_border(system_default_border(vdp_version));
     let audio_output = SdlAudioOutput::create_and_init(&audio, &config.common)?;
     let input_mapper = InputMapper::new_smsgg(
         joystick,
+        config.p1_controller_type,
         config.common.keyboard_inputs.clone(),
         config.common.joystick_inputs.clone(),
+        config.peripheral_config,
         config.common.axis_deadzone,
-    )?;
-    let hotkey_mapper = HotkeyMapper::from_config(&config.common.hotkeys)?;
-
-    let emulator = SmsGgEmulator::create(rom, emulator_config, &mut save_writer);
+    )?
+    .with_macros(&config.common.input_macros);
+    let hotkey_mapper =
+        HotkeyMapper::from_config(&config.common.hotkeys, config.common.axis_deadzone)?;
 
     Ok(NativeEmulator {
         emulator,
@@ -695,6 +1548,17 @@ pub fn create_smsgg(config: Box<SmsGgConfig>) -> NativeEmulatorResult<NativeSmsG
         event_pump,
         video,
         hotkey_state: HotkeyState::new(&config.common, save_state_path, debug::smsgg::render_fn),
+        gdb_stub: None,
+        input_injection: start_input_injection(config.common.input_injection_port)?,
+        remote_control: start_remote_control(config.common.remote_control.as_ref())?,
+        _discord_presence: discord_presence,
+        window_geometry,
+        symbols: SymbolTable::new(),
+        freeze_list: FreezeList::new(),
+        as_debuggable: None,
+        av_dump: start_av_dump(&config.common)?,
+        frame_skip: FrameSkip::from_config(&config.common),
+        frame_filters: FrameFilterChain::new(),
     })
 }
 
@@ -705,25 +1569,67 @@ pub fn create_smsgg(config: Box<SmsGgConfig>) -> NativeEmulatorResult<NativeSmsG
 /// This function will return an error upon encountering any video, audio, or I/O error.
 pub fn create_genesis(config: Box<GenesisConfig>) -> NativeEmulatorResult<NativeGenesisEmulator> {
     log::info!("Running with config: {config}");
+    crash::set_config(&config);
 
     let rom_file_path = Path::new(&config.common.rom_file_path);
-    let rom = fs::read(rom_file_path).map_err(|source| NativeEmulatorError::RomRead {
-        path: rom_file_path.display().to_string(),
-        source,
-    })?;
+    let mut rom = read_patched_rom(rom_file_path, &config.common.rom_patch_paths)?;
+
+    if let Some(patches_path) =
+        config.widescreen_patches_path.as_ref().filter(|_| config.widescreen)
+    {
+        let patches = fs::read_to_string(patches_path).map_err(|source| {
+            NativeEmulatorError::GenesisWidescreenPatchesRead { path: patches_path.clone(), source }
+        })?;
+        if !WidescreenPatches::parse(&patches)?.apply(&mut rom) {
+            log::info!("No widescreen patches found for ROM in '{patches_path}'");
+        }
+    }
 
-    let save_path = rom_file_path.with_extension("sav");
-    let save_state_path = rom_file_path.with_extension("ss0");
-    let mut save_writer = FsSaveWriter::new(save_path);
+    let (rom, lock_on_path) = match &config.lock_on_rom_path {
+        Some(lock_on_path) if is_sonic_and_knuckles(&rom) => {
+            let lock_on_path = Path::new(lock_on_path);
+            let lock_on_rom = read_rom(lock_on_path)?;
+            let patch_rom = config.lock_on_patch_rom_path.as_ref().map(read_rom).transpose()?;
+
+            log::info!("Locking on cartridge '{}'", lock_on_path.display());
+            let rom = attach_lock_on_cartridge(rom, &lock_on_rom, patch_rom.as_deref())?;
+            (rom, Some(lock_on_path))
+        }
+        Some(_) => {
+            log::warn!("Ignoring lock-on cartridge because the ROM is not Sonic & Knuckles");
+            (rom, None)
+        }
+        None => (rom, None),
+    };
+
+    // Cartridge RAM is on the locked-on cartridge, so share its save file. Save states are kept
+    // separate from the states for either cartridge on its own
+    let (save_path, save_state_path) = match lock_on_path {
+        Some(lock_on_path) => {
+            (lock_on_path.with_extension("sav"), lock_on_path.with_extension("lock-on.ss0"))
+        }
+        None => (rom_file_path.with_extension("sav"), rom_file_path.with_extension("ss0")),
+    };
+    let mut save_writer = FsSaveWriter::with_sync(save_path, config.common.save_sync.as_ref());
 
     let emulator_config = config.to_emulator_config();
-    let emulator = GenesisEmulator::create(rom, emulator_config, &mut save_writer);
+    let mut emulator = GenesisEmulator::create(rom, emulator_config, &mut save_writer);
+
+    let tile_replacements =
+        config.texture_pack_directory.as_deref().map(textures::load_texture_pack).transpose()?;
+    emulator.set_tile_replacements(tile_replacements);
+    emulator.set_tile_dump_enabled(config.tile_dump_directory.is_some());
+
+    if let Some(serial_transport) =
+        open_link_transport(config.serial_transport, &config.serial_tcp_address)?
+    {
+        emulator.set_serial_transport(serial_transport);
+    }
 
     let (sdl, video, audio, joystick, event_pump) =
         init_sdl(config.common.hide_cursor_over_window)?;
 
-    let WindowSize { width: window_width, height: window_height } =
-        config.common.window_size.unwrap_or(config::DEFAULT_GENESIS_WINDOW_SIZE);
+    let window_geometry = WindowGeometryTracker::new("genesis");
     let mut cartridge_title = emulator.cartridge_title();
     // Remove non-printable characters
     cartridge_title.retain(|c| {
@@ -732,21 +1638,114 @@ pub fn create_genesis(config: Box<GenesisConfig>) -> NativeEmulatorResult<Native
     let window = create_window(
         &video,
         &format!("genesis - {cartridge_title}"),
-        window_width,
-        window_height,
+        &window_geometry,
+        config.common.window_size,
+        config::GENESIS_DEFAULT_FRAME,
         config.common.launch_in_fullscreen,
+        should_match_pal_refresh_rate(config.common.renderer_config, emulator.timing_mode()),
     )?;
 
-    let renderer =
-        pollster::block_on(WgpuRenderer::new(window, Window::size, config.common.renderer_config))?;
+    let discord_presence =
+        start_discord_presence(config.common.discord.as_ref(), "Genesis", &cartridge_title);
+
+    let renderer = create_renderer(window, &config.common)?;
     let audio_output = SdlAudioOutput::create_and_init(&audio, &config.common)?;
     let input_mapper = InputMapper::new_genesis(
         joystick,
         config.common.keyboard_inputs.clone(),
         config.common.joystick_inputs.clone(),
         config.common.axis_deadzone,
+    )?
+    .with_macros(&config.common.input_macros);
+    let hotkey_mapper =
+        HotkeyMapper::from_config(&config.common.hotkeys, config.common.axis_deadzone)?;
+
+    Ok(NativeEmulator {
+        emulator,
+        config: emulator_config,
+        renderer,
+        audio_output,
+        input_mapper,
+        hotkey_mapper,
+        save_writer,
+        sdl,
+        event_pump,
+        video,
+        hotkey_state: HotkeyState::new(&config.common, save_state_path, debug::genesis::render_fn)
+            .with_music_dumper(MusicDumper::sound_log(
+                rom_file_path,
+                GenesisEmulator::sound_log_mut,
+            ))
+            .with_tile_dump_writer(config.tile_dump_directory.as_deref().map(|directory| {
+                TileDumpWriter::new(directory, GenesisEmulator::take_dumped_tiles)
+            })),
+        gdb_stub: start_gdb_stub(config.common.gdb_port)?,
+        input_injection: start_input_injection(config.common.input_injection_port)?,
+        remote_control: start_remote_control(config.common.remote_control.as_ref())?,
+        _discord_presence: discord_presence,
+        window_geometry,
+        symbols: load_symbols(&config.common),
+        freeze_list: FreezeList::new(),
+        as_debuggable: Some(as_debuggable),
+        av_dump: start_av_dump(&config.common)?,
+        frame_skip: FrameSkip::from_config(&config.common),
+        frame_filters: deinterlace_filter_chain(config.deinterlace_mode),
+    })
+}
+
+/// Create an emulator with the Sega Pico core with the given config. The Pico uses the Genesis
+/// config since the hardware is nearly identical.
+///
+/// # Errors
+///
+/// This function will return an error upon encountering any video, audio, or I/O error.
+pub fn create_pico(config: Box<GenesisConfig>) -> NativeEmulatorResult<NativePicoEmulator> {
+    log::info!("Running with config: {config}");
+    crash::set_config(&config);
+
+    let rom_file_path = Path::new(&config.common.rom_file_path);
+    let rom = read_patched_rom(rom_file_path, &config.common.rom_patch_paths)?;
+
+    // The Pico has no save files, but the save writer is still required by the common code
+    let save_writer = FsSaveWriter::new(rom_file_path.with_extension("sav"));
+    let save_state_path = rom_file_path.with_extension("ss0");
+
+    let emulator_config = config.to_emulator_config();
+    let emulator = PicoEmulator::create(rom, emulator_config);
+
+    let (sdl, video, audio, joystick, event_pump) =
+        init_sdl(config.common.hide_cursor_over_window)?;
+
+    let window_geometry = WindowGeometryTracker::new("pico");
+    let mut cartridge_title = emulator.cartridge_title();
+    // Remove non-printable characters
+    cartridge_title.retain(|c| {
+        c.is_ascii_alphanumeric() || c.is_ascii_whitespace() || c.is_ascii_punctuation()
+    });
+    let window = create_window(
+        &video,
+        &format!("pico - {cartridge_title}"),
+        &window_geometry,
+        config.common.window_size,
+        config::GENESIS_DEFAULT_FRAME,
+        config.common.launch_in_fullscreen,
+        should_match_pal_refresh_rate(config.common.renderer_config, emulator.timing_mode()),
     )?;
-    let hotkey_mapper = HotkeyMapper::from_config(&config.common.hotkeys)?;
+
+    let discord_presence =
+        start_discord_presence(config.common.discord.as_ref(), "Pico", &cartridge_title);
+
+    let renderer = create_renderer(window, &config.common)?;
+    let audio_output = SdlAudioOutput::create_and_init(&audio, &config.common)?;
+    let input_mapper = InputMapper::new_pico(
+        joystick,
+        config.common.keyboard_inputs.clone(),
+        config.common.joystick_inputs.clone(),
+        config.common.axis_deadzone,
+    )?
+    .with_macros(&config.common.input_macros);
+    let hotkey_mapper =
+        HotkeyMapper::from_config(&config.common.hotkeys, config.common.axis_deadzone)?;
 
     Ok(NativeEmulator {
         emulator,
@@ -759,10 +1758,37 @@ pub fn create_genesis(config: Box<GenesisConfig>) -> NativeEmulatorResult<Native
         sdl,
         event_pump,
         video,
-        hotkey_state: HotkeyState::new(&config.common, save_state_path, debug::genesis::render_fn),
+        hotkey_state: HotkeyState::new(&config.common, save_state_path, debug::genesis::render_fn)
+            .with_music_dumper(MusicDumper::sound_log(rom_file_path, PicoEmulator::sound_log_mut)),
+        gdb_stub: None,
+        input_injection: start_input_injection(config.common.input_injection_port)?,
+        remote_control: start_remote_control(config.common.remote_control.as_ref())?,
+        _discord_presence: discord_presence,
+        window_geometry,
+        symbols: SymbolTable::new(),
+        freeze_list: FreezeList::new(),
+        as_debuggable: None,
+        av_dump: start_av_dump(&config.common)?,
+        frame_skip: FrameSkip::from_config(&config.common),
+        frame_filters: FrameFilterChain::new(),
     })
 }
 
+fn deinterlace_filter_chain(mode: DeinterlaceMode) -> FrameFilterChain {
+    let mut frame_filters = FrameFilterChain::new();
+    set_deinterlace_mode(&mut frame_filters, mode);
+    frame_filters
+}
+
+fn set_deinterlace_mode(frame_filters: &mut FrameFilterChain, mode: DeinterlaceMode) {
+    frame_filters.remove(deinterlace::FILTER_NAME);
+
+    // Weaving is what the cores already do, so there is nothing to filter
+    if mode != DeinterlaceMode::Weave {
+        frame_filters.push_front(Deinterlacer::new(mode));
+    }
+}
+
 /// Create an emulator with the Sega CD core with the given config.
 ///
 /// # Errors
@@ -771,63 +1797,55 @@ pub fn create_genesis(config: Box<GenesisConfig>) -> NativeEmulatorResult<Native
 /// any error encountered loading the Sega CD game disc.
 pub fn create_sega_cd(config: Box<SegaCdConfig>) -> NativeEmulatorResult<NativeSegaCdEmulator> {
     log::info!("Running with config: {config}");
+    crash::set_config(&config);
 
+    // Saves for M3U playlists are named after the playlist rather than the disc so that every disc
+    // shares the same backup RAM
     let rom_path = Path::new(&config.genesis.common.rom_file_path);
-    let rom_format = CdRomFileFormat::from_file_path(rom_path).unwrap_or_else(|| {
-        log::warn!(
-            "Unrecognized CD-ROM file extension, behaving as if this is a CUE file: {}",
-            rom_path.display()
-        );
-        CdRomFileFormat::CueBin
-    });
-
     let save_path = rom_path.with_extension("sav");
     let save_state_path = rom_path.with_extension("ss0");
-    let mut save_writer = FsSaveWriter::new(save_path);
-
-    let bios_file_path = config.bios_file_path.as_ref().ok_or(NativeEmulatorError::SegaCdNoBios)?;
-    let bios = fs::read(bios_file_path).map_err(|source| NativeEmulatorError::SegaCdBiosRead {
-        path: bios_file_path.clone(),
-        source,
-    })?;
+    let mut save_writer =
+        FsSaveWriter::with_sync(save_path, config.genesis.common.save_sync.as_ref());
 
-    let emulator_config = config.to_emulator_config();
-    let emulator = SegaCdEmulator::create(
-        bios,
-        rom_path,
-        rom_format,
-        config.run_without_disc,
-        emulator_config,
-        &mut save_writer,
-    )?;
+    let emulator = load_sega_cd_emulator(&config, &mut save_writer)?;
 
     let (sdl, video, audio, joystick, event_pump) =
         init_sdl(config.genesis.common.hide_cursor_over_window)?;
 
-    let WindowSize { width: window_width, height: window_height } =
-        config.genesis.common.window_size.unwrap_or(config::DEFAULT_GENESIS_WINDOW_SIZE);
+    let window_geometry = WindowGeometryTracker::new("segacd");
 
     let window = create_window(
         &video,
         &format!("sega cd - {}", emulator.disc_title()),
-        window_width,
-        window_height,
+        &window_geometry,
+        config.genesis.common.window_size,
+        config::GENESIS_DEFAULT_FRAME,
         config.genesis.common.launch_in_fullscreen,
+        should_match_pal_refresh_rate(
+            config.genesis.common.renderer_config,
+            emulator.timing_mode(),
+        ),
     )?;
 
-    let renderer = pollster::block_on(WgpuRenderer::new(
-        window,
-        Window::size,
-        config.genesis.common.renderer_config,
-    ))?;
+    let discord_presence = start_discord_presence(
+        config.genesis.common.discord.as_ref(),
+        "Sega CD",
+        emulator.disc_title(),
+    );
+
+    let renderer = create_renderer(window, &config.genesis.common)?;
     let audio_output = SdlAudioOutput::create_and_init(&audio, &config.genesis.common)?;
     let input_mapper = InputMapper::new_genesis(
         joystick,
         config.genesis.common.keyboard_inputs.clone(),
         config.genesis.common.joystick_inputs.clone(),
         config.genesis.common.axis_deadzone,
+    )?
+    .with_macros(&config.genesis.common.input_macros);
+    let hotkey_mapper = HotkeyMapper::from_config(
+        &config.genesis.common.hotkeys,
+        config.genesis.common.axis_deadzone,
     )?;
-    let hotkey_mapper = HotkeyMapper::from_config(&config.genesis.common.hotkeys)?;
 
     Ok(NativeEmulator {
         emulator,
@@ -844,7 +1862,19 @@ pub fn create_sega_cd(config: Box<SegaCdConfig>) -> NativeEmulatorResult<NativeS
             &config.genesis.common,
             save_state_path,
             debug::genesis::render_fn,
-        ),
+        )
+        .with_music_dumper(MusicDumper::sound_log(rom_path, SegaCdEmulator::sound_log_mut)),
+        gdb_stub: None,
+        input_injection: start_input_injection(config.genesis.common.input_injection_port)?,
+        remote_control: start_remote_control(config.genesis.common.remote_control.as_ref())?,
+        _discord_presence: discord_presence,
+        window_geometry,
+        symbols: SymbolTable::new(),
+        freeze_list: FreezeList::new(),
+        as_debuggable: None,
+        av_dump: start_av_dump(&config.genesis.common)?,
+        frame_skip: FrameSkip::from_config(&config.genesis.common),
+        frame_filters: deinterlace_filter_chain(config.genesis.deinterlace_mode),
     })
 }
 
@@ -855,16 +1885,14 @@ pub fn create_sega_cd(config: Box<SegaCdConfig>) -> NativeEmulatorResult<NativeS
 /// Propagates any errors encountered during initialization.
 pub fn create_nes(config: Box<NesConfig>) -> NativeEmulatorResult<NativeNesEmulator> {
     log::info!("Running with config: {config}");
+    crash::set_config(&config);
 
     let rom_path = Path::new(&config.common.rom_file_path);
-    let rom = fs::read(rom_path).map_err(|source| NativeEmulatorError::RomRead {
-        path: config.common.rom_file_path.clone(),
-        source,
-    })?;
+    let rom = read_patched_rom(rom_path, &config.common.rom_patch_paths)?;
 
     let save_path = rom_path.with_extension("sav");
     let save_state_path = rom_path.with_extension("ss0");
-    let mut save_writer = FsSaveWriter::new(save_path);
+    let mut save_writer = FsSaveWriter::with_sync(save_path, config.common.save_sync.as_ref());
 
     let emulator_config = config.to_emulator_config();
     let emulator = NesEmulator::create(rom, emulator_config, &mut save_writer)?;
@@ -872,29 +1900,35 @@ pub fn create_nes(config: Box<NesConfig>) -> NativeEmulatorResult<NativeNesEmula
     let (sdl, video, audio, joystick, event_pump) =
         init_sdl(config.common.hide_cursor_over_window)?;
 
-    let WindowSize { width: window_width, height: window_height } =
-        config.common.window_size.unwrap_or(config::DEFAULT_GENESIS_WINDOW_SIZE);
+    let window_geometry = WindowGeometryTracker::new("nes");
 
     let rom_title = file_name_no_ext(&config.common.rom_file_path)?;
     let window = create_window(
         &video,
         &format!("nes - {rom_title}"),
-        window_width,
-        window_height,
+        &window_geometry,
+        config.common.window_size,
+        config::GENESIS_DEFAULT_FRAME,
         config.common.launch_in_fullscreen,
+        should_match_pal_refresh_rate(config.common.renderer_config, emulator.timing_mode()),
     )?;
 
-    let renderer =
-        pollster::block_on(WgpuRenderer::new(window, Window::size, config.common.renderer_config))?;
+    let discord_presence =
+        start_discord_presence(config.common.discord.as_ref(), "NES", &rom_title);
+
+    let renderer = create_renderer(window, &config.common)?;
     let audio_output = SdlAudioOutput::create_and_init(&audio, &config.common)?;
 
     let input_mapper = InputMapper::new_nes(
         joystick,
+        emulator.expansion_device(),
         config.common.keyboard_inputs.clone(),
         config.common.joystick_inputs.clone(),
         config.common.axis_deadzone,
-    )?;
-    let hotkey_mapper = HotkeyMapper::from_config(&config.common.hotkeys)?;
+    )?
+    .with_macros(&config.common.input_macros);
+    let hotkey_mapper =
+        HotkeyMapper::from_config(&config.common.hotkeys, config.common.axis_deadzone)?;
 
     Ok(NativeNesEmulator {
         emulator,
@@ -907,7 +1941,19 @@ pub fn create_nes(config: Box<NesConfig>) -> NativeEmulatorResult<NativeNesEmula
         sdl,
         event_pump,
         video,
-        hotkey_state: HotkeyState::new(&config.common, save_state_path, debug::nes::render_fn),
+        hotkey_state: HotkeyState::new(&config.common, save_state_path, debug::nes::render_fn)
+            .with_microphone(NesEmulator::set_famicom_microphone),
+        gdb_stub: None,
+        input_injection: start_input_injection(config.common.input_injection_port)?,
+        remote_control: start_remote_control(config.common.remote_control.as_ref())?,
+        _discord_presence: discord_presence,
+        window_geometry,
+        symbols: SymbolTable::new(),
+        freeze_list: FreezeList::new(),
+        as_debuggable: None,
+        av_dump: start_av_dump(&config.common)?,
+        frame_skip: FrameSkip::from_config(&config.common),
+        frame_filters: FrameFilterChain::new(),
     })
 }
 
@@ -918,16 +1964,14 @@ pub fn create_nes(config: Box<NesConfig>) -> NativeEmulatorResult<NativeNesEmula
 /// This function will return an error if unable to initialize the emulator.
 pub fn create_snes(config: Box<SnesConfig>) -> NativeEmulatorResult<NativeSnesEmulator> {
     log::info!("Running with config: {config}");
+    crash::set_config(&config);
 
     let rom_path = Path::new(&config.common.rom_file_path);
-    let rom = fs::read(rom_path).map_err(|source| NativeEmulatorError::RomRead {
-        path: config.common.rom_file_path.clone(),
-        source,
-    })?;
+    let rom = read_patched_rom(rom_path, &config.common.rom_patch_paths)?;
 
     let save_path = rom_path.with_extension("sav");
     let save_state_path = rom_path.with_extension("ss0");
-    let mut save_writer = FsSaveWriter::new(save_path);
+    let mut save_writer = FsSaveWriter::with_sync(save_path, config.common.save_sync.as_ref());
 
     let emulator_config = config.to_emulator_config();
     let coprocessor_roms = config.to_coprocessor_roms();
@@ -937,21 +1981,24 @@ pub fn create_snes(config: Box<SnesConfig>) -> NativeEmulatorResult<NativeSnesEm
     let (sdl, video, audio, joystick, event_pump) =
         init_sdl(config.common.hide_cursor_over_window)?;
 
-    // Use same default window size as Genesis / Sega CD
-    let WindowSize { width: window_width, height: window_height } =
-        config.common.window_size.unwrap_or(config::DEFAULT_GENESIS_WINDOW_SIZE);
+    let window_geometry = WindowGeometryTracker::new("snes");
 
     let cartridge_title = emulator.cartridge_title();
     let window = create_window(
         &video,
         &format!("snes - {cartridge_title}"),
-        window_width,
-        window_height,
+        &window_geometry,
+        config.common.window_size,
+        // Use same default window size as Genesis / Sega CD
+        config::GENESIS_DEFAULT_FRAME,
         config.common.launch_in_fullscreen,
+        should_match_pal_refresh_rate(config.common.renderer_config, emulator.timing_mode()),
     )?;
 
-    let renderer =
-        pollster::block_on(WgpuRenderer::new(window, Window::size, config.common.renderer_config))?;
+    let discord_presence =
+        start_discord_presence(config.common.discord.as_ref(), "SNES", &cartridge_title);
+
+    let renderer = create_renderer(window, &config.common)?;
     let audio_output = SdlAudioOutput::create_and_init(&audio, &config.common)?;
 
     let input_mapper = InputMapper::new_snes(
@@ -961,8 +2008,94 @@ pub fn create_snes(config: Box<SnesConfig>) -> NativeEmulatorResult<NativeSnesEm
         config.common.joystick_inputs.clone(),
         config.super_scope_config.clone(),
         config.common.axis_deadzone,
+    )?
+    .with_macros(&config.common.input_macros);
+    let hotkey_mapper =
+        HotkeyMapper::from_config(&config.common.hotkeys, config.common.axis_deadzone)?;
+
+    Ok(NativeEmulator {
+        emulator,
+        config: emulator_config,
+        renderer,
+        audio_output,
+        input_mapper,
+        hotkey_mapper,
+        save_writer,
+        sdl,
+        event_pump,
+        video,
+        hotkey_state: HotkeyState::new(&config.common, save_state_path, debug::snes::render_fn)
+            .with_music_dumper(MusicDumper::spc(rom_path, SnesEmulator::save_spc)),
+        gdb_stub: start_gdb_stub(config.common.gdb_port)?,
+        input_injection: start_input_injection(config.common.input_injection_port)?,
+        remote_control: start_remote_control(config.common.remote_control.as_ref())?,
+        _discord_presence: discord_presence,
+        window_geometry,
+        symbols: load_symbols(&config.common),
+        freeze_list: FreezeList::new(),
+        as_debuggable: Some(as_debuggable),
+        av_dump: start_av_dump(&config.common)?,
+        frame_skip: FrameSkip::from_config(&config.common),
+        frame_filters: deinterlace_filter_chain(config.effective_deinterlace_mode()),
+    })
+}
+
+/// Create an SPC file player using the SNES APU with the given config. The SPC file is read from
+/// the ROM file path.
+///
+/// # Errors
+///
+/// This function will return an error if unable to read or parse the SPC file.
+pub fn create_spc(config: Box<SnesConfig>) -> NativeEmulatorResult<NativeSpcEmulator> {
+    log::info!("Running with config: {config}");
+    crash::set_config(&config);
+
+    let spc_path = Path::new(&config.common.rom_file_path);
+    let spc_bytes = fs::read(spc_path).map_err(|source| NativeEmulatorError::RomRead {
+        path: config.common.rom_file_path.clone(),
+        source,
+    })?;
+    let spc = SpcFile::parse(&spc_bytes)?;
+
+    // SPC players have no save files, but the save writer is still required by the common code
+    let save_writer = FsSaveWriter::new(spc_path.with_extension("sav"));
+    let save_state_path = spc_path.with_extension("ss0");
+
+    let emulator_config = config.to_emulator_config();
+    let emulator = SpcPlayer::create(spc, emulator_config);
+
+    let (sdl, video, audio, joystick, event_pump) =
+        init_sdl(config.common.hide_cursor_over_window)?;
+
+    let window_geometry = WindowGeometryTracker::new("spc");
+
+    let window = create_window(
+        &video,
+        &format!("spc - {}", emulator.title()),
+        &window_geometry,
+        config.common.window_size,
+        config::GENESIS_DEFAULT_FRAME,
+        config.common.launch_in_fullscreen,
+        should_match_pal_refresh_rate(config.common.renderer_config, emulator.timing_mode()),
     )?;
-    let hotkey_mapper = HotkeyMapper::from_config(&config.common.hotkeys)?;
+
+    let discord_presence =
+        start_discord_presence(config.common.discord.as_ref(), "SNES", &emulator.title());
+
+    let renderer = create_renderer(window, &config.common)?;
+    let audio_output = SdlAudioOutput::create_and_init(&audio, &config.common)?;
+
+    let input_mapper = InputMapper::new_snes(
+        joystick,
+        config.p2_controller_type,
+        config.common.keyboard_inputs.clone(),
+        config.common.joystick_inputs.clone(),
+        config.super_scope_config.clone(),
+        config.common.axis_deadzone,
+    )?
+    .with_macros(&config.common.input_macros);
+    let hotkey_mapper =
+        HotkeyMapper::from_config(&config.common.hotkeys, config.common.axis_deadzone)?;
 
     Ok(NativeEmulator {
         emulator,
@@ -975,7 +2108,18 @@ pub fn create_snes(config: Box<SnesConfig>) -> NativeEmulatorResult<NativeSnesEm
         sdl,
         event_pump,
         video,
-        hotkey_state: HotkeyState::new(&config.common, save_state_path, debug::snes::render_fn),
+        hotkey_state: HotkeyState::new(&config.common, save_state_path, debug::snes::spc_render_fn),
+        gdb_stub: None,
+        input_injection: start_input_injection(config.common.input_injection_port)?,
+        remote_control: start_remote_control(config.common.remote_control.as_ref())?,
+        _discord_presence: discord_presence,
+        window_geometry,
+        symbols: SymbolTable::new(),
+        freeze_list: FreezeList::new(),
+        as_debuggable: None,
+        av_dump: start_av_dump(&config.common)?,
+        frame_skip: FrameSkip::from_config(&config.common),
+        frame_filters: FrameFilterChain::new(),
     })
 }
 
@@ -986,16 +2130,14 @@ pub fn create_snes(config: Box<SnesConfig>) -> NativeEmulatorResult<NativeSnesEm
 /// This function will return an error if unable to initialize the emulator.
 pub fn create_gb(config: Box<GameBoyConfig>) -> NativeEmulatorResult<NativeGameBoyEmulator> {
     log::info!("Running with config: {config}");
+    crash::set_config(&config);
 
     let rom_path = Path::new(&config.common.rom_file_path);
-    let rom = fs::read(rom_path).map_err(|source| NativeEmulatorError::RomRead {
-        path: config.common.rom_file_path.clone(),
-        source,
-    })?;
+    let rom = read_patched_rom(rom_path, &config.common.rom_patch_paths)?;
 
     let save_path = rom_path.with_extension("sav");
     let save_state_path = rom_path.with_extension("ss0");
-    let mut save_writer = FsSaveWriter::new(save_path);
+    let mut save_writer = FsSaveWriter::with_sync(save_path, config.common.save_sync.as_ref());
 
     let emulator_config = config.to_emulator_config();
     let emulator = GameBoyEmulator::create(rom, emulator_config, &mut save_writer)?;
@@ -1003,19 +2145,23 @@ pub fn create_gb(config: Box<GameBoyConfig>) -> NativeEmulatorResult<NativeGameB
     let (sdl, video, audio, joystick, event_pump) =
         init_sdl(config.common.hide_cursor_over_window)?;
 
-    let WindowSize { width: window_width, height: window_height } = config::DEFAULT_GB_WINDOW_SIZE;
+    let window_geometry = WindowGeometryTracker::new("gb");
 
     let rom_title = file_name_no_ext(&config.common.rom_file_path)?;
     let window = create_window(
         &video,
         &format!("gb - {rom_title}"),
-        window_width,
-        window_height,
+        &window_geometry,
+        None,
+        config::GB_DEFAULT_FRAME,
         config.common.launch_in_fullscreen,
+        should_match_pal_refresh_rate(config.common.renderer_config, emulator.timing_mode()),
     )?;
 
-    let renderer =
-        pollster::block_on(WgpuRenderer::new(window, Window::size, config.common.renderer_config))?;
+    let discord_presence =
+        start_discord_presence(config.common.discord.as_ref(), "Game Boy", &rom_title);
+
+    let renderer = create_renderer(window, &config.common)?;
     let audio_output = SdlAudioOutput::create_and_init(&audio, &config.common)?;
 
     let input_mapper = InputMapper::new_gb(
@@ -1023,8 +2169,10 @@ pub fn create_gb(config: Box<GameBoyConfig>) -> NativeEmulatorResult<NativeGameB
         config.common.keyboard_inputs.clone(),
         config.common.joystick_inputs.clone(),
         config.common.axis_deadzone,
-    )?;
-    let hotkey_mapper = HotkeyMapper::from_config(&config.common.hotkeys)?;
+    )?
+    .with_macros(&config.common.input_macros);
+    let hotkey_mapper =
+        HotkeyMapper::from_config(&config.common.hotkeys, config.common.axis_deadzone)?;
 
     Ok(NativeGameBoyEmulator {
         emulator,
@@ -1038,16 +2186,453 @@ pub fn create_gb(config: Box<GameBoyConfig>) -> NativeEmulatorResult<NativeGameB
         event_pump,
         video,
         hotkey_state: HotkeyState::new(&config.common, save_state_path, debug::gb::render_fn),
+        gdb_stub: None,
+        input_injection: start_input_injection(config.common.input_injection_port)?,
+        remote_control: start_remote_control(config.common.remote_control.as_ref())?,
+        _discord_presence: discord_presence,
+        window_geometry,
+        symbols: SymbolTable::new(),
+        freeze_list: FreezeList::new(),
+        as_debuggable: None,
+        av_dump: start_av_dump(&config.common)?,
+        frame_skip: FrameSkip::from_config(&config.common),
+        frame_filters: FrameFilterChain::new(),
     })
 }
 
-fn file_name_no_ext<P: AsRef<Path>>(path: P) -> NativeEmulatorResult<String> {
-    path.as_ref()
-        .with_extension("")
-        .file_name()
-        .map(|file_name| file_name.to_string_lossy().into_owned())
-        .ok_or_else(|| NativeEmulatorError::ParseFileName(path.as_ref().display().to_string()))
-}
+/// Create an emulator with the PC Engine core with the given config.
+///
+/// # Errors
+///
+/// This function will return an error if unable to initialize the emulator.
+pub fn create_pce(config: Box<PceConfig>) -> NativeEmulatorResult<NativePceEmulator> {
+    log::info!("Running with config: {config}");
+    crash::set_config(&config);
+
+    let rom_path = Path::new(&config.common.rom_file_path);
+    let rom = read_patched_rom(rom_path, &config.common.rom_patch_paths)?;
+
+    // HuCards have no save memory, but the save writer is still required by the common code
+    let save_path = rom_path.with_extension("sav");
+    let save_state_path = rom_path.with_extension("ss0");
+    let save_writer = FsSaveWriter::with_sync(save_path, config.common.save_sync.as_ref());
+
+    let emulator_config = config.to_emulator_config();
+    let emulator = PceEmulator::create(rom, emulator_config)?;
+
+    let (sdl, video, audio, joystick, event_pump) =
+        init_sdl(config.common.hide_cursor_over_window)?;
+
+    let window_geometry = WindowGeometryTracker::new("pce");
+
+    let rom_title = file_name_no_ext(&config.common.rom_file_path)?;
+    let window = create_window(
+        &video,
+        &format!("pce - {rom_title}"),
+        &window_geometry,
+        config.common.window_size,
+        config::PCE_DEFAULT_FRAME,
+        config.common.launch_in_fullscreen,
+        should_match_pal_refresh_rate(config.common.renderer_config, emulator.timing_mode()),
+    )?;
+
+    let discord_presence =
+        start_discord_presence(config.common.discord.as_ref(), "PC Engine", &rom_title);
+
+    let renderer = create_renderer(window, &config.common)?;
+    let audio_output = SdlAudioOutput::create_and_init(&audio, &config.common)?;
+
+    let input_mapper = InputMapper::new_pce(
+        joystick,
+        config.common.keyboard_inputs.clone(),
+        config.common.joystick_inputs.clone(),
+        config.common.axis_deadzone,
+    )?
+    .with_macros(&config.common.input_macros);
+    let hotkey_mapper =
+        HotkeyMapper::from_config(&config.common.hotkeys, config.common.axis_deadzone)?;
+
+    Ok(NativePceEmulator {
+        emulator,
+        config: emulator_config,
+        renderer,
+        audio_output,
+        input_mapper,
+        hotkey_mapper,
+        save_writer,
+        sdl,
+        event_pump,
+        video,
+        hotkey_state: HotkeyState::new(&config.common, save_state_path, debug::pce::render_fn),
+        gdb_stub: None,
+        input_injection: start_input_injection(config.common.input_injection_port)?,
+        remote_control: start_remote_control(config.common.remote_control.as_ref())?,
+        _discord_presence: discord_presence,
+        window_geometry,
+        symbols: SymbolTable::new(),
+        freeze_list: FreezeList::new(),
+        as_debuggable: None,
+        av_dump: start_av_dump(&config.common)?,
+        frame_skip: FrameSkip::from_config(&config.common),
+        frame_filters: FrameFilterChain::new(),
+    })
+}
+
+/// Create an emulator with the Game Boy Advance core with the given config.
+///
+/// # Errors
+///
+/// This function will return an error if unable to initialize the emulator.
+pub fn create_gba(config: Box<GbaConfig>) -> NativeEmulatorResult<NativeGbaEmulator> {
+    log::info!("Running with config: {config}");
+    crash::set_config(&config);
+
+    let rom_path = Path::new(&config.common.rom_file_path);
+    let rom = read_patched_rom(rom_path, &config.common.rom_patch_paths)?;
+
+    let bios_file_path = config.bios_file_path.as_ref().ok_or(NativeEmulatorError::GbaNoBios)?;
+    let bios = fs::read(bios_file_path).map_err(|source| NativeEmulatorError::GbaBiosRead {
+        path: bios_file_path.clone(),
+        source,
+    })?;
+
+    let save_path = rom_path.with_extension("sav");
+    let save_state_path = rom_path.with_extension("ss0");
+    let mut save_writer = FsSaveWriter::with_sync(save_path, config.common.save_sync.as_ref());
+
+    let emulator_config = config.to_emulator_config();
+    let emulator = GbaEmulator::create(rom, bios, emulator_config, &mut save_writer)?;
+
+    let (sdl, video, audio, joystick, event_pump) =
+        init_sdl(config.common.hide_cursor_over_window)?;
+
+    let window_geometry = WindowGeometryTracker::new("gba");
+
+    let rom_title = file_name_no_ext(&config.common.rom_file_path)?;
+    let window = create_window(
+        &video,
+        &format!("gba - {rom_title}"),
+        &window_geometry,
+        config.common.window_size,
+        config::GBA_DEFAULT_FRAME,
+        config.common.launch_in_fullscreen,
+        should_match_pal_refresh_rate(config.common.renderer_config, emulator.timing_mode()),
+    )?;
+
+    let discord_presence =
+        start_discord_presence(config.common.discord.as_ref(), "Game Boy Advance", &rom_title);
+
+    let renderer = create_renderer(window, &config.common)?;
+    let audio_output = SdlAudioOutput::create_and_init(&audio, &config.common)?;
+
+    let input_mapper = InputMapper::new_gba(
+        joystick,
+        config.common.keyboard_inputs.clone(),
+        config.common.joystick_inputs.clone(),
+        config.common.axis_deadzone,
+    )?
+    .with_macros(&config.common.input_macros);
+    let hotkey_mapper =
+        HotkeyMapper::from_config(&config.common.hotkeys, config.common.axis_deadzone)?;
+
+    Ok(NativeGbaEmulator {
+        emulator,
+        config: emulator_config,
+        renderer,
+        audio_output,
+        input_mapper,
+        hotkey_mapper,
+        save_writer,
+        sdl,
+        event_pump,
+        video,
+        hotkey_state: HotkeyState::new(&config.common, save_state_path, debug::gba::render_fn),
+        gdb_stub: None,
+        input_injection: start_input_injection(config.common.input_injection_port)?,
+        remote_control: start_remote_control(config.common.remote_control.as_ref())?,
+        _discord_presence: discord_presence,
+        window_geometry,
+        symbols: SymbolTable::new(),
+        freeze_list: FreezeList::new(),
+        as_debuggable: None,
+        av_dump: start_av_dump(&config.common)?,
+        frame_skip: FrameSkip::from_config(&config.common),
+        frame_filters: FrameFilterChain::new(),
+    })
+}
+
+/// Create an emulator with the Atari 2600 core with the given config.
+///
+/// # Errors
+///
+/// This function will return an error if unable to initialize the emulator.
+pub fn create_atari2600(
+    config: Box<Atari2600Config>,
+) -> NativeEmulatorResult<NativeAtari2600Emulator> {
+    log::info!("Running with config: {config}");
+    crash::set_config(&config);
+
+    let rom_path = Path::new(&config.common.rom_file_path);
+    let rom = read_patched_rom(rom_path, &config.common.rom_patch_paths)?;
+
+    // Atari 2600 cartridges have no save memory, but the save writer is still required by the
+    // common code
+    let save_path = rom_path.with_extension("sav");
+    let save_state_path = rom_path.with_extension("ss0");
+    let save_writer = FsSaveWriter::with_sync(save_path, config.common.save_sync.as_ref());
+
+    let emulator_config = config.to_emulator_config();
+    let emulator = Atari2600Emulator::create(rom, emulator_config)?;
+
+    let (sdl, video, audio, joystick, event_pump) =
+        init_sdl(config.common.hide_cursor_over_window)?;
+
+    let window_geometry = WindowGeometryTracker::new("atari2600");
+
+    let rom_title = file_name_no_ext(&config.common.rom_file_path)?;
+    let window = create_window(
+        &video,
+        &format!("atari2600 - {rom_title}"),
+        &window_geometry,
+        config.common.window_size,
+        config::ATARI2600_DEFAULT_FRAME,
+        config.common.launch_in_fullscreen,
+        should_match_pal_refresh_rate(config.common.renderer_config, emulator.timing_mode()),
+    )?;
+
+    let discord_presence =
+        start_discord_presence(config.common.discord.as_ref(), "Atari 2600", &rom_title);
+
+    let renderer = create_renderer(window, &config.common)?;
+    let audio_output = SdlAudioOutput::create_and_init(&audio, &config.common)?;
+
+    let input_mapper = InputMapper::new_atari2600(
+        joystick,
+        config.common.keyboard_inputs.clone(),
+        config.common.joystick_inputs.clone(),
+        config.common.axis_deadzone,
+    )?
+    .with_macros(&config.common.input_macros);
+    let hotkey_mapper =
+        HotkeyMapper::from_config(&config.common.hotkeys, config.common.axis_deadzone)?;
+
+    Ok(NativeAtari2600Emulator {
+        emulator,
+        config: emulator_config,
+        renderer,
+        audio_output,
+        input_mapper,
+        hotkey_mapper,
+        save_writer,
+        sdl,
+        event_pump,
+        video,
+        hotkey_state: HotkeyState::new(
+            &config.common,
+            save_state_path,
+            debug::atari2600::render_fn,
+        ),
+        gdb_stub: None,
+        input_injection: start_input_injection(config.common.input_injection_port)?,
+        remote_control: start_remote_control(config.common.remote_control.as_ref())?,
+        _discord_presence: discord_presence,
+        window_geometry,
+        symbols: SymbolTable::new(),
+        freeze_list: FreezeList::new(),
+        as_debuggable: None,
+        av_dump: start_av_dump(&config.common)?,
+        frame_skip: FrameSkip::from_config(&config.common),
+        frame_filters: FrameFilterChain::new(),
+    })
+}
+
+/// Create an emulator with the ColecoVision core with the given config.
+///
+/// # Errors
+///
+/// This function will return an error if unable to initialize the emulator.
+pub fn create_colecovision(
+    config: Box<ColecoVisionConfig>,
+) -> NativeEmulatorResult<NativeColecoVisionEmulator> {
+    log::info!("Running with config: {config}");
+    crash::set_config(&config);
+
+    let rom_path = Path::new(&config.common.rom_file_path);
+    let rom = read_patched_rom(rom_path, &config.common.rom_patch_paths)?;
+
+    let bios_file_path =
+        config.bios_file_path.as_ref().ok_or(NativeEmulatorError::ColecoVisionNoBios)?;
+    let bios = fs::read(bios_file_path).map_err(|source| {
+        NativeEmulatorError::ColecoVisionBiosRead { path: bios_file_path.clone(), source }
+    })?;
+
+    // ColecoVision cartridges have no save memory, but the save writer is still required by the
+    // common code
+    let save_path = rom_path.with_extension("sav");
+    let save_state_path = rom_path.with_extension("ss0");
+    let save_writer = FsSaveWriter::with_sync(save_path, config.common.save_sync.as_ref());
+
+    let emulator_config = config.to_emulator_config();
+    let emulator = ColecoVisionEmulator::create(rom, bios, emulator_config)?;
+
+    let (sdl, video, audio, joystick, event_pump) =
+        init_sdl(config.common.hide_cursor_over_window)?;
+
+    let window_geometry = WindowGeometryTracker::new("colecovision");
+
+    let rom_title = file_name_no_ext(&config.common.rom_file_path)?;
+    let window = create_window(
+        &video,
+        &format!("colecovision - {rom_title}"),
+        &window_geometry,
+        config.common.window_size,
+        config::COLECOVISION_DEFAULT_FRAME,
+        config.common.launch_in_fullscreen,
+        should_match_pal_refresh_rate(config.common.renderer_config, emulator.timing_mode()),
+    )?;
+
+    let discord_presence =
+        start_discord_presence(config.common.discord.as_ref(), "ColecoVision", &rom_title);
+
+    let renderer = create_renderer(window, &config.common)?;
+    let audio_output = SdlAudioOutput::create_and_init(&audio, &config.common)?;
+
+    let input_mapper = InputMapper::new_colecovision(
+        joystick,
+        config.common.keyboard_inputs.clone(),
+        config.common.joystick_inputs.clone(),
+        config.common.axis_deadzone,
+    )?
+    .with_macros(&config.common.input_macros);
+    let hotkey_mapper =
+        HotkeyMapper::from_config(&config.common.hotkeys, config.common.axis_deadzone)?;
+
+    Ok(NativeColecoVisionEmulator {
+        emulator,
+        config: emulator_config,
+        renderer,
+        audio_output,
+        input_mapper,
+        hotkey_mapper,
+        save_writer,
+        sdl,
+        event_pump,
+        video,
+        hotkey_state: HotkeyState::new(
+            &config.common,
+            save_state_path,
+            debug::colecovision::render_fn,
+        ),
+        gdb_stub: None,
+        input_injection: start_input_injection(config.common.input_injection_port)?,
+        remote_control: start_remote_control(config.common.remote_control.as_ref())?,
+        _discord_presence: discord_presence,
+        window_geometry,
+        symbols: SymbolTable::new(),
+        freeze_list: FreezeList::new(),
+        as_debuggable: None,
+        av_dump: start_av_dump(&config.common)?,
+        frame_skip: FrameSkip::from_config(&config.common),
+        frame_filters: FrameFilterChain::new(),
+    })
+}
+
+// Returns the first path of the form <base>_<n>.<extension> that does not exist for any of the
+// given extensions, using the first extension
+fn next_dump_path(base_path: &Path, extensions: &[&str]) -> PathBuf {
+    let file_name = base_path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+
+    (1..)
+        .map(|n| base_path.with_file_name(format!("{file_name}_{n}.{}", extensions[0])))
+        .find(|path| extensions.iter().all(|&extension| !path.with_extension(extension).exists()))
+        .expect("infinite iterator should always find a path")
+}
+
+fn load_sega_cd_emulator<S: SaveWriter>(
+    config: &SegaCdConfig,
+    save_writer: &mut S,
+) -> NativeEmulatorResult<SegaCdEmulator> {
+    let rom_path = Path::new(&config.genesis.common.rom_file_path);
+
+    // Multi-disc games boot from the first disc in the playlist
+    let disc_path = if rom_path.extension().and_then(OsStr::to_str) == Some("m3u") {
+        let mut discs = m3u::parse(rom_path).map_err(SegaCdLoadError::from)?;
+        log::info!("Loaded M3U playlist with {} discs", discs.len());
+        discs.swap_remove(0)
+    } else {
+        rom_path.to_path_buf()
+    };
+
+    let rom_format = CdRomFileFormat::from_file_path(&disc_path).unwrap_or_else(|| {
+        log::warn!(
+            "Unrecognized CD-ROM file extension, behaving as if this is a CUE file: {}",
+            disc_path.display()
+        );
+        CdRomFileFormat::CueBin
+    });
+
+    let bios_file_path = config.bios_file_path.as_ref().ok_or(NativeEmulatorError::SegaCdNoBios)?;
+    let bios = fs::read(bios_file_path).map_err(|source| NativeEmulatorError::SegaCdBiosRead {
+        path: bios_file_path.clone(),
+        source,
+    })?;
+
+    let emulator_config = config.to_emulator_config();
+    let emulator = match &config.mode_1_cartridge_path {
+        Some(cartridge_path) => {
+            let cartridge_rom = fs::read(cartridge_path).map_err(|source| {
+                NativeEmulatorError::RomRead { path: cartridge_path.clone(), source }
+            })?;
+            let disc = (!config.run_without_disc).then_some((&disc_path, rom_format));
+            SegaCdEmulator::create_mode_1(bios, cartridge_rom, disc, emulator_config, save_writer)?
+        }
+        None => SegaCdEmulator::create(
+            bios,
+            &disc_path,
+            rom_format,
+            config.run_without_disc,
+            emulator_config,
+            save_writer,
+        )?,
+    };
+
+    Ok(emulator)
+}
+
+// Returns Ok(None) if the port is configured to have nothing connected
+fn open_link_transport(
+    kind: LinkTransportKind,
+    address: &str,
+) -> NativeEmulatorResult<Option<Box<dyn LinkTransport + Send>>> {
+    let link = match kind {
+        LinkTransportKind::None => return Ok(None),
+        LinkTransportKind::TcpClient => TcpLink::connect(address),
+        LinkTransportKind::TcpServer => TcpLink::listen(address),
+    }
+    .map_err(|source| NativeEmulatorError::LinkTransport { address: address.into(), source })?;
+
+    Ok(Some(Box::new(link)))
+}
+
+fn read_rom<P: AsRef<Path>>(path: P) -> NativeEmulatorResult<Vec<u8>> {
+    let path = path.as_ref();
+    fs::read(path)
+        .map_err(|source| NativeEmulatorError::RomRead { path: path.display().to_string(), source })
+}
+
+// Read a ROM and apply the configured soft patches, if any
+fn read_patched_rom(path: &Path, patch_paths: &[String]) -> NativeEmulatorResult<Vec<u8>> {
+    let rom = read_rom(path)?;
+    Ok(patch::apply_patches(rom, patch_paths)?)
+}
+
+fn file_name_no_ext<P: AsRef<Path>>(path: P) -> NativeEmulatorResult<String> {
+    path.as_ref()
+        .with_extension("")
+        .file_name()
+        .map(|file_name| file_name.to_string_lossy().into_owned())
+        .ok_or_else(|| NativeEmulatorError::ParseFileName(path.as_ref().display().to_string()))
+}
 
 fn parse_file_ext(path: &Path) -> NativeEmulatorResult<&str> {
     path.extension()
@@ -1059,6 +2644,10 @@ fn parse_file_ext(path: &Path) -> NativeEmulatorResult<&str> {
 fn init_sdl(
     hide_cursor_over_window: bool,
 ) -> NativeEmulatorResult<(Sdl, VideoSubsystem, AudioSubsystem, JoystickSubsystem, EventPump)> {
+    // Window sizes are in physical pixels on Windows only if the process is per-monitor DPI aware;
+    // otherwise Windows bitmap-stretches the window on displays with scaling above 100%
+    sdl2::hint::set("SDL_WINDOWS_DPI_AWARENESS", "permonitorv2");
+
     let sdl = sdl2::init().map_err(NativeEmulatorError::SdlInit)?;
     let video = sdl.video().map_err(NativeEmulatorError::SdlVideoInit)?;
     let audio = sdl.audio().map_err(NativeEmulatorError::SdlAudioInit)?;
@@ -1070,33 +2659,248 @@ fn init_sdl(
     Ok((sdl, video, audio, joystick, event_pump))
 }
 
+// Use the explicitly configured symbol file if set, otherwise look for a symbol file next to the ROM
+fn load_symbols<KC, JC>(common_config: &CommonConfig<KC, JC>) -> SymbolTable {
+    let path = match &common_config.symbol_file_path {
+        Some(path) => PathBuf::from(path),
+        None => {
+            let rom_path = Path::new(&common_config.rom_file_path);
+            let Some(path) = ["sym", "map"]
+                .into_iter()
+                .map(|extension| rom_path.with_extension(extension))
+                .find(|path| path.is_file())
+            else {
+                return SymbolTable::new();
+            };
+            path
+        }
+    };
+
+    SymbolTable::load(&path).unwrap_or_else(|err| {
+        log::error!("Error loading symbol file '{}': {err}", path.display());
+        SymbolTable::new()
+    })
+}
+
+fn start_av_dump<KC, JC>(
+    common_config: &CommonConfig<KC, JC>,
+) -> NativeEmulatorResult<Option<AvDumpWriter>> {
+    common_config
+        .av_dump_path
+        .as_ref()
+        .map(|path| {
+            AvDumpWriter::create(Path::new(path))
+                .map_err(|source| NativeEmulatorError::AvDumpCreate { path: path.clone(), source })
+        })
+        .transpose()
+}
+
+fn create_renderer<KC, JC>(
+    window: Window,
+    common_config: &CommonConfig<KC, JC>,
+) -> NativeEmulatorResult<WgpuRenderer<Window>> {
+    let mut renderer = pollster::block_on(WgpuRenderer::new(
+        window,
+        Window::drawable_size,
+        effective_renderer_config(common_config),
+    ))?;
+    renderer.set_custom_border_path(common_config.border_image_path.as_deref().map(Path::new));
+
+    Ok(renderer)
+}
+
+fn system_default_border(vdp_version: VdpVersion) -> Option<BorderImage> {
+    (vdp_version == VdpVersion::GameGear).then(BorderImage::game_gear)
+}
+
+// Don't wait for vsync while dumping so that emulation runs as fast as possible
+fn effective_renderer_config<KC, JC>(common_config: &CommonConfig<KC, JC>) -> RendererConfig {
+    let mut renderer_config = common_config.renderer_config;
+    if common_config.av_dump_path.is_some() {
+        renderer_config.vsync_mode = VSyncMode::Disabled;
+    }
+    renderer_config
+}
+
+fn start_gdb_stub<Emulator: Debuggable>(
+    port: Option<u16>,
+) -> NativeEmulatorResult<Option<GdbStub<Emulator>>> {
+    port.map(|port| {
+        GdbStub::new(port, as_debuggable)
+            .map_err(|source| NativeEmulatorError::GdbServer { port, source })
+    })
+    .transpose()
+}
+
+fn start_input_injection<Button: MacroButton>(
+    port: Option<u16>,
+) -> NativeEmulatorResult<Option<InputInjectionServer<Button>>> {
+    port.map(|port| {
+        InputInjectionServer::new(port)
+            .map_err(|source| NativeEmulatorError::InputInjectionServer { port, source })
+    })
+    .transpose()
+}
+
+fn start_remote_control(
+    config: Option<&RemoteControlConfig>,
+) -> NativeEmulatorResult<Option<RemoteControlServer>> {
+    config
+        .map(|config| {
+            if config.token.0.is_empty() {
+                return Err(NativeEmulatorError::RemoteControlNoToken);
+            }
+
+            RemoteControlServer::new(config.port, config.token.0.clone()).map_err(|source| {
+                NativeEmulatorError::RemoteControlServer { port: config.port, source }
+            })
+        })
+        .transpose()
+}
+
+fn start_discord_presence(
+    config: Option<&DiscordConfig>,
+    system: &str,
+    game_title: &str,
+) -> Option<DiscordPresence> {
+    config.and_then(|config| DiscordPresence::start(config, system, game_title))
+}
+
+fn as_debuggable<Emulator: Debuggable>(emulator: &mut Emulator) -> &mut dyn Debuggable {
+    emulator
+}
+
 fn create_window(
     video: &VideoSubsystem,
     title: &str,
-    width: u32,
-    height: u32,
+    window_geometry: &WindowGeometryTracker,
+    configured_size: Option<WindowSize>,
+    default_frame: DefaultFrame,
     fullscreen: bool,
+    match_pal_refresh_rate: bool,
 ) -> NativeEmulatorResult<Window> {
-    let mut window = video.window(title, width, height).metal_view().resizable().build()?;
+    // Create hidden so that the window does not visibly jump when it is resized and repositioned
+    // after the display scale factor is known
+    let FrameSize { width, height } = default_frame.frame_size;
+    let mut window = video
+        .window(title, width, height)
+        .metal_view()
+        .resizable()
+        .allow_highdpi()
+        .hidden()
+        .build()?;
+    window::apply_initial_geometry(
+        &mut window,
+        video,
+        window_geometry,
+        configured_size,
+        default_frame,
+    )
+    .map_err(NativeEmulatorError::SdlSetWindowGeometry)?;
+    window.show();
 
     if fullscreen {
-        window
-            .set_fullscreen(FullscreenType::Desktop)
+        enter_fullscreen(&mut window, video, match_pal_refresh_rate)
             .map_err(NativeEmulatorError::SdlSetFullscreen)?;
     }
 
     Ok(window)
 }
 
+fn should_match_pal_refresh_rate(renderer_config: RendererConfig, timing_mode: TimingMode) -> bool {
+    renderer_config.pal_50hz_fullscreen && timing_mode == TimingMode::Pal
+}
+
+// Use exclusive fullscreen at a 50Hz-compatible refresh rate if requested and if the display
+// supports one, otherwise use borderless fullscreen at the desktop resolution
+fn enter_fullscreen(
+    window: &mut Window,
+    video: &VideoSubsystem,
+    match_pal_refresh_rate: bool,
+) -> Result<(), String> {
+    if match_pal_refresh_rate {
+        match find_pal_display_mode(window, video) {
+            Some(display_mode) => {
+                log::info!(
+                    "Using exclusive fullscreen at {}x{} {}Hz",
+                    display_mode.w,
+                    display_mode.h,
+                    display_mode.refresh_rate
+                );
+
+                window.set_display_mode(display_mode)?;
+                return window.set_fullscreen(FullscreenType::True);
+            }
+            None => {
+                log::warn!(
+                    "Display does not support a refresh rate that is a multiple of 50Hz at desktop resolution; using borderless fullscreen"
+                );
+            }
+        }
+    }
+
+    window.set_fullscreen(FullscreenType::Desktop)
+}
+
+// Find the display mode at desktop resolution with the highest refresh rate that is a multiple of
+// 50Hz, e.g. 200Hz is preferred over 100Hz which is preferred over 50Hz
+fn find_pal_display_mode(window: &Window, video: &VideoSubsystem) -> Option<DisplayMode> {
+    let display_idx = window.display_index().ok()?;
+    let desktop_mode = video.desktop_display_mode(display_idx).ok()?;
+    let num_modes = video.num_display_modes(display_idx).ok()?;
+
+    (0..num_modes)
+        .filter_map(|mode_idx| video.display_mode(display_idx, mode_idx).ok())
+        .filter(|mode| {
+            mode.w == desktop_mode.w
+                && mode.h == desktop_mode.h
+                && mode.refresh_rate > 0
+                && mode.refresh_rate % 50 == 0
+        })
+        .max_by_key(|mode| mode.refresh_rate)
+}
+
+fn persist_save<Emulator: EmulatorTrait>(emulator: &mut Emulator, save_writer: &mut FsSaveWriter) {
+    if let Err(err) = emulator.persist_save(save_writer) {
+        log::error!("Error writing save file: {err}");
+    }
+}
+
+fn set_paused<Emulator: EmulatorTrait>(
+    hotkey_state: &mut HotkeyState<Emulator>,
+    emulator: &mut Emulator,
+    save_writer: &mut FsSaveWriter,
+    paused: bool,
+) {
+    if paused == hotkey_state.paused {
+        return;
+    }
+
+    hotkey_state.paused = paused;
+    if paused {
+        persist_save(emulator, save_writer);
+    } else if mem::take(&mut hotkey_state.paused_for_disconnect) {
+        // Resumed manually or because a controller reconnected
+        hotkey_state.save_states.hide_message();
+    }
+    hotkey_state.input_trace.record_event(if paused {
+        TraceEvent::Paused
+    } else {
+        TraceEvent::Resumed
+    });
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum HotkeyResult {
     None,
     Quit,
+    OpenQuickMenu,
 }
 
 struct HandleHotkeysArgs<'a, Emulator: EmulatorTrait> {
     hotkey_mapper: &'a HotkeyMapper,
     event: &'a Event,
+    joystick_hotkeys: &'a [Hotkey],
     emulator: &'a mut Emulator,
     config: &'a Emulator::Config,
     renderer: &'a mut WgpuRenderer<Window>,
@@ -1104,6 +2908,7 @@ struct HandleHotkeysArgs<'a, Emulator: EmulatorTrait> {
     save_writer: &'a mut FsSaveWriter,
     video: &'a VideoSubsystem,
     hotkey_state: &'a mut HotkeyState<Emulator>,
+    as_debuggable: Option<DebuggableFn<Emulator>>,
 }
 
 fn handle_hotkeys<Emulator>(
@@ -1112,11 +2917,19 @@ fn handle_hotkeys<Emulator>(
 where
     Emulator: EmulatorTrait,
 {
+    for &hotkey in args.joystick_hotkeys {
+        let result = handle_hotkey_pressed(hotkey, &mut args)?;
+        if result != HotkeyResult::None {
+            return Ok(result);
+        }
+    }
+
     match args.hotkey_mapper.check_for_hotkeys(args.event) {
         HotkeyMapResult::Pressed(hotkeys) => {
             for &hotkey in hotkeys {
-                if handle_hotkey_pressed(hotkey, &mut args)? == HotkeyResult::Quit {
-                    return Ok(HotkeyResult::Quit);
+                let result = handle_hotkey_pressed(hotkey, &mut args)?;
+                if result != HotkeyResult::None {
+                    return Ok(result);
                 }
             }
         }
@@ -1129,6 +2942,12 @@ where
                     }
                     Hotkey::Rewind => {
                         args.hotkey_state.rewinder.stop_rewinding();
+                        args.hotkey_state.input_trace.record_event(TraceEvent::RewindStopped);
+                    }
+                    Hotkey::Microphone => {
+                        if let Some(microphone_fn) = args.hotkey_state.microphone_fn {
+                            microphone_fn(args.emulator, false);
+                        }
                     }
                     _ => {}
                 }
@@ -1147,53 +2966,59 @@ fn handle_hotkey_pressed<Emulator>(
 where
     Emulator: EmulatorTrait,
 {
-    let save_state_path = &args.hotkey_state.save_state_path;
-
     match hotkey {
         Hotkey::Quit => {
             return Ok(HotkeyResult::Quit);
         }
         Hotkey::ToggleFullscreen => {
-            args.renderer.toggle_fullscreen().map_err(NativeEmulatorError::SdlSetFullscreen)?;
+            args.renderer
+                .toggle_fullscreen(args.video, args.emulator.timing_mode())
+                .map_err(NativeEmulatorError::SdlSetFullscreen)?;
         }
         Hotkey::SaveState => {
-            save_state(args.emulator, save_state_path)?;
+            args.hotkey_state.save_states.save(args.emulator)?;
+            args.hotkey_state.input_trace.record_event(TraceEvent::SaveState);
         }
         Hotkey::LoadState => {
-            let mut loaded_emulator: Emulator = match load_state(save_state_path) {
-                Ok(emulator) => emulator,
-                Err(err) => {
-                    log::error!(
-                        "Error loading save state from {}: {err}",
-                        save_state_path.display()
-                    );
-                    return Ok(HotkeyResult::None);
-                }
-            };
-            loaded_emulator.take_rom_from(args.emulator);
-
-            // Force a config reload because the emulator will contain some config fields
-            loaded_emulator.reload_config(args.config);
-
-            *args.emulator = loaded_emulator;
+            args.hotkey_state.save_states.load(args.emulator, args.config);
+            args.hotkey_state.input_trace.record_event(TraceEvent::LoadState);
+        }
+        Hotkey::NextSaveStateSlot => {
+            args.hotkey_state.save_states.select_next_slot();
+        }
+        Hotkey::PreviousSaveStateSlot => {
+            args.hotkey_state.save_states.select_previous_slot();
+        }
+        Hotkey::UndoLoadState => {
+            if args.hotkey_state.save_states.undo_load(args.emulator, args.config) {
+                args.hotkey_state.input_trace.record_event(TraceEvent::LoadStateUndone);
+            }
         }
         Hotkey::SoftReset => {
             args.emulator.soft_reset();
+            args.hotkey_state.input_trace.record_event(TraceEvent::SoftReset);
         }
         Hotkey::HardReset => {
+            persist_save(args.emulator, args.save_writer);
             args.emulator.hard_reset(args.save_writer);
+            args.hotkey_state.input_trace.record_event(TraceEvent::HardReset);
         }
         Hotkey::Pause => {
-            args.hotkey_state.paused = !args.hotkey_state.paused;
+            let paused = !args.hotkey_state.paused;
+            set_paused(args.hotkey_state, args.emulator, args.save_writer, paused);
         }
         Hotkey::StepFrame => {
             args.hotkey_state.should_step_frame = true;
+            args.hotkey_state.input_trace.record_event(TraceEvent::StepFrame);
         }
         Hotkey::FastForward => {
             args.renderer.set_speed_multiplier(args.hotkey_state.fast_forward_multiplier);
             args.audio_output.set_speed_multiplier(args.hotkey_state.fast_forward_multiplier);
         }
         Hotkey::Rewind => {
+            if !args.hotkey_state.rewinder.is_rewinding() {
+                args.hotkey_state.input_trace.record_event(TraceEvent::RewindStarted);
+            }
             args.hotkey_state.rewinder.start_rewinding();
         }
         Hotkey::OpenDebugger => {
@@ -1209,6 +3034,39 @@ where
                 }
             }
         }
+        Hotkey::MusicDump => {
+            if let Some(music_dumper) = &mut args.hotkey_state.music_dumper {
+                music_dumper.handle_hotkey(args.emulator);
+            }
+        }
+        Hotkey::Microphone => {
+            if let Some(microphone_fn) = args.hotkey_state.microphone_fn {
+                microphone_fn(args.emulator, true);
+            }
+        }
+        Hotkey::DumpInputTrace => {
+            args.hotkey_state.input_trace.dump();
+        }
+        Hotkey::RecordInputMacro => {
+            args.hotkey_state.macro_recording_toggled = true;
+        }
+        Hotkey::SetPracticeLoopPoint => {
+            args.hotkey_state.practice.set_loop_point(args.emulator, args.as_debuggable);
+            args.hotkey_state.save_states.show_message(OsdMessage::LoopSet);
+        }
+        Hotkey::RestartPracticeAttempt => {
+            args.hotkey_state.restart_practice_attempt(args.emulator, args.config);
+        }
+        Hotkey::ClearPracticeLoopPoint => {
+            if args.hotkey_state.practice.clear_loop_point() {
+                args.hotkey_state.save_states.show_message(OsdMessage::LoopCleared);
+                log::info!("Cleared practice loop point");
+            }
+        }
+        Hotkey::OpenQuickMenu => {
+            set_paused(args.hotkey_state, args.emulator, args.save_writer, true);
+            return Ok(HotkeyResult::OpenQuickMenu);
+        }
     }
 
     Ok(HotkeyResult::None)
@@ -1228,60 +3086,28 @@ fn open_debugger_window<Emulator>(
     }
 }
 
-fn handle_window_event(win_event: WindowEvent, renderer: &mut WgpuRenderer<Window>) {
+fn handle_window_event(
+    win_event: WindowEvent,
+    renderer: &mut WgpuRenderer<Window>,
+    window_geometry: &mut WindowGeometryTracker,
+) {
     match win_event {
-        WindowEvent::Resized(..) | WindowEvent::SizeChanged(..) | WindowEvent::Maximized => {
+        // DisplayChanged can change the display scale factor, and with it the drawable size,
+        // without changing the window size in window units
+        WindowEvent::Resized(..)
+        | WindowEvent::SizeChanged(..)
+        | WindowEvent::Maximized
+        | WindowEvent::DisplayChanged(..) => {
             renderer.handle_resize();
         }
         _ => {}
     }
-}
 
-macro_rules! bincode_config {
-    () => {
-        bincode::config::standard()
-            .with_little_endian()
-            .with_fixed_int_encoding()
-            .with_limit::<{ 100 * 1024 * 1024 }>()
-    };
-}
-
-use bincode_config;
-
-fn save_state<E, P>(emulator: &E, path: P) -> NativeEmulatorResult<()>
-where
-    E: Encode,
-    P: AsRef<Path>,
-{
-    let path = path.as_ref();
-
-    let mut file = BufWriter::new(File::create(path).map_err(|source| {
-        NativeEmulatorError::StateFileOpen { path: path.display().to_string(), source }
-    })?);
-
-    let conf = bincode_config!();
-    bincode::encode_into_std_write(emulator, &mut file, conf)?;
-
-    log::info!("Saved state to {}", path.display());
-
-    Ok(())
+    if matches!(
+        win_event,
+        WindowEvent::Moved(..) | WindowEvent::SizeChanged(..) | WindowEvent::Restored
+    ) {
+        window_geometry.update(renderer.window());
+    }
 }
 
-fn load_state<D, P>(path: P) -> NativeEmulatorResult<D>
-where
-    D: Decode,
-    P: AsRef<Path>,
-{
-    let path = path.as_ref();
-
-    let mut file = BufReader::new(File::open(path).map_err(|source| {
-        NativeEmulatorError::StateFileOpen { path: path.display().to_string(), source }
-    })?);
-
-    let conf = bincode_config!();
-    let emulator = bincode::decode_from_std_read(&mut file, conf)?;
-
-    log::info!("Loaded state from {}", path.display());
-
-    Ok(emulator)
-}
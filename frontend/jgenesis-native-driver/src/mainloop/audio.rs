@@ -1,11 +1,37 @@
+mod postprocess;
+
 use crate::config::CommonConfig;
 use crate::mainloop;
+use crate::mainloop::audio::postprocess::AudioPostProcessor;
 use jgenesis_common::frontend::AudioOutput;
 use sdl2::audio::{AudioQueue, AudioSpecDesired};
 use sdl2::AudioSubsystem;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use thiserror::Error;
 
+const AUDIO_FREQUENCY: i32 = 48000;
+
+// Each sample frame is 2x f32
+const BYTES_PER_SAMPLE_FRAME: u32 = 8;
+
+// After an underrun, audio is stretched by this ratio (slightly lowering pitch) so that the queue
+// refills without a gap, and the ratio then decays back to 1 over roughly this many seconds
+const UNDERRUN_STRETCH_RATIO: f64 = 1.08;
+const UNDERRUN_STRETCH_DECAY_SECONDS: f64 = 0.5;
+
+// When a latency target is set and audio sync is disabled, the output rate is adjusted by up to
+// this fraction to keep the audio queue about half full
+const MAX_RATE_ADJUSTMENT: f64 = 0.005;
+
+// Emulation is considered to be falling behind if the audio queue is less than this fraction full
+const FALLING_BEHIND_DIVISOR: u32 = 4;
+
+// An empty queue after a gap this long is from pausing or similar, not from emulation falling behind
+const UNDERRUN_MAX_GAP: Duration = Duration::from_millis(250);
+
+// How often audio sync checks whether the audio queue has room
+const AUDIO_SYNC_POLL_INTERVAL: Duration = Duration::from_micros(250);
+
 #[derive(Debug, Error)]
 pub enum AudioError {
     #[error("Error opening SDL2 audio queue: {0}")]
@@ -14,13 +40,136 @@ pub enum AudioError {
     QueueAudio(String),
 }
 
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AudioStatistics {
+    /// Number of times the audio device ran out of samples to play
+    pub underruns: u64,
+    /// Number of times samples were dropped because the audio queue was full
+    pub overruns: u64,
+    /// Audio queue size in sample frames as of the last time samples were queued
+    pub queued_samples: u32,
+    /// Whether audio is currently being stretched to recover from an underrun
+    pub recovering: bool,
+}
+
+impl AudioStatistics {
+    pub fn queued_duration(&self) -> Duration {
+        Duration::from_secs_f64(f64::from(self.queued_samples) / f64::from(AUDIO_FREQUENCY))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct AudioBufferSettings {
+    device_queue_size: u16,
+    internal_buffer_size: u32,
+    sync_threshold: u32,
+    dynamic_rate_control: bool,
+}
+
+impl AudioBufferSettings {
+    fn from_config<KC, JC>(config: &CommonConfig<KC, JC>) -> Self {
+        match config.audio_latency_ms {
+            Some(latency_ms) => Self::from_latency(latency_ms),
+            None => Self {
+                device_queue_size: config.audio_device_queue_size,
+                internal_buffer_size: config.internal_audio_buffer_size,
+                sync_threshold: config.audio_sync_threshold,
+                dynamic_rate_control: false,
+            },
+        }
+    }
+
+    // Roughly a quarter of the latency goes to the audio device buffer (which SDL requires to be a
+    // power of 2) and the rest goes to the SDL audio queue
+    fn from_latency(latency_ms: u32) -> Self {
+        let total_samples = (f64::from(latency_ms) * f64::from(AUDIO_FREQUENCY) / 1000.0) as u32;
+
+        let device_queue_size = 1 << (total_samples / 4).clamp(64, 4096).ilog2();
+        let queue_samples = total_samples.saturating_sub(device_queue_size).max(64);
+
+        Self {
+            device_queue_size: device_queue_size as u16,
+            internal_buffer_size: (total_samples / 32).clamp(16, 64),
+            sync_threshold: queue_samples * BYTES_PER_SAMPLE_FRAME,
+            dynamic_rate_control: true,
+        }
+    }
+}
+
+// Resamples output audio when stretching to recover from an underrun or when dynamic rate control
+// is adjusting the output rate
+#[derive(Debug, Clone)]
+struct OutputResampler {
+    underrun_stretch: f64,
+    stretch_decay: f64,
+    rate_adjustment: f64,
+    position: f64,
+    last_sample: (f64, f64),
+}
+
+impl OutputResampler {
+    fn new() -> Self {
+        Self {
+            underrun_stretch: 1.0,
+            stretch_decay: (-1.0 / (UNDERRUN_STRETCH_DECAY_SECONDS * f64::from(AUDIO_FREQUENCY)))
+                .exp(),
+            rate_adjustment: 1.0,
+            position: 0.0,
+            last_sample: (0.0, 0.0),
+        }
+    }
+
+    fn is_recovering(&self) -> bool {
+        self.underrun_stretch > 1.0
+    }
+
+    fn start_underrun_recovery(&mut self) {
+        self.underrun_stretch = UNDERRUN_STRETCH_RATIO;
+    }
+
+    fn push(&mut self, sample_l: f64, sample_r: f64, buffer: &mut Vec<f32>) {
+        // Ratio of output samples to input samples
+        let ratio = self.underrun_stretch * self.rate_adjustment;
+
+        if (ratio - 1.0).abs() < 1e-6 {
+            buffer.push(sample_l as f32);
+            buffer.push(sample_r as f32);
+            self.last_sample = (sample_l, sample_r);
+            self.position = 0.0;
+            return;
+        }
+
+        // Linearly interpolate between the previous and current sample
+        let (last_l, last_r) = self.last_sample;
+        while self.position < 1.0 {
+            buffer.push((last_l + (sample_l - last_l) * self.position) as f32);
+            buffer.push((last_r + (sample_r - last_r) * self.position) as f32);
+            self.position += 1.0 / ratio;
+        }
+        self.position -= 1.0;
+        self.last_sample = (sample_l, sample_r);
+
+        if self.is_recovering() {
+            self.underrun_stretch = 1.0 + (self.underrun_stretch - 1.0) * self.stretch_decay;
+            if self.underrun_stretch < 1.0 + 1e-4 {
+                self.underrun_stretch = 1.0;
+            }
+        }
+    }
+}
+
 pub struct SdlAudioOutput {
     audio_queue: AudioQueue<f32>,
     audio_buffer: Vec<f32>,
     audio_sync: bool,
-    internal_audio_buffer_len: u32,
-    audio_sync_threshold: u32,
+    sleep_until_drained: bool,
+    buffer_settings: AudioBufferSettings,
     audio_gain_multiplier: f64,
+    post_processor: AudioPostProcessor,
+    underrun_recovery: bool,
+    resampler: OutputResampler,
+    statistics: AudioStatistics,
+    last_queue_time: Option<Instant>,
     sample_count: u64,
     speed_multiplier: u64,
 }
@@ -30,13 +179,16 @@ impl SdlAudioOutput {
         audio: &AudioSubsystem,
         config: &CommonConfig<KC, JC>,
     ) -> Result<Self, AudioError> {
+        let buffer_settings = AudioBufferSettings::from_config(config);
+        log::info!("Audio buffer settings: {buffer_settings:?}");
+
         let audio_queue = audio
             .open_queue(
                 None,
                 &AudioSpecDesired {
-                    freq: Some(48000),
+                    freq: Some(AUDIO_FREQUENCY),
                     channels: Some(2),
-                    samples: Some(config.audio_device_queue_size),
+                    samples: Some(buffer_settings.device_queue_size),
                 },
             )
             .map_err(AudioError::OpenQueue)?;
@@ -44,11 +196,19 @@ impl SdlAudioOutput {
 
         Ok(Self {
             audio_queue,
-            audio_buffer: Vec::with_capacity(config.internal_audio_buffer_size as usize),
+            audio_buffer: Vec::with_capacity(buffer_settings.internal_buffer_size as usize),
             audio_sync: config.audio_sync,
-            internal_audio_buffer_len: config.internal_audio_buffer_size,
-            audio_sync_threshold: config.audio_sync_threshold,
+            sleep_until_drained: config.steam_deck_mode,
+            buffer_settings,
             audio_gain_multiplier: decibels_to_multiplier(config.audio_gain_db),
+            post_processor: AudioPostProcessor::new(
+                config.audio_post_processing,
+                AUDIO_FREQUENCY.into(),
+            ),
+            underrun_recovery: config.audio_underrun_recovery,
+            resampler: OutputResampler::new(),
+            statistics: AudioStatistics::default(),
+            last_queue_time: None,
             sample_count: 0,
             speed_multiplier: 1,
         })
@@ -58,13 +218,23 @@ impl SdlAudioOutput {
         &mut self,
         config: &CommonConfig<KC, JC>,
     ) -> Result<(), AudioError> {
+        let buffer_settings = AudioBufferSettings::from_config(config);
+        if buffer_settings != self.buffer_settings {
+            log::info!("Audio buffer settings: {buffer_settings:?}");
+        }
+
         self.audio_sync = config.audio_sync;
-        self.internal_audio_buffer_len = config.internal_audio_buffer_size;
-        self.audio_sync_threshold = config.audio_sync_threshold;
+        self.sleep_until_drained = config.steam_deck_mode;
+        self.buffer_settings = buffer_settings;
         self.audio_gain_multiplier = decibels_to_multiplier(config.audio_gain_db);
+        self.post_processor.reload_config(config.audio_post_processing);
+        self.underrun_recovery = config.audio_underrun_recovery;
 
-        if config.audio_device_queue_size != self.audio_queue.spec().samples {
-            log::info!("Recreating SDL audio queue with size {}", config.audio_device_queue_size);
+        if buffer_settings.device_queue_size != self.audio_queue.spec().samples {
+            log::info!(
+                "Recreating SDL audio queue with size {}",
+                buffer_settings.device_queue_size
+            );
             self.audio_queue.pause();
 
             let new_audio_queue = self
@@ -73,9 +243,9 @@ impl SdlAudioOutput {
                 .open_queue(
                     None,
                     &AudioSpecDesired {
-                        freq: Some(48000),
+                        freq: Some(AUDIO_FREQUENCY),
                         channels: Some(2),
-                        samples: Some(config.audio_device_queue_size),
+                        samples: Some(buffer_settings.device_queue_size),
                     },
                 )
                 .map_err(AudioError::OpenQueue)?;
@@ -89,6 +259,70 @@ impl SdlAudioOutput {
     pub fn set_speed_multiplier(&mut self, speed_multiplier: u64) {
         self.speed_multiplier = speed_multiplier;
     }
+
+    pub fn statistics(&self) -> AudioStatistics {
+        AudioStatistics { recovering: self.resampler.is_recovering(), ..self.statistics }
+    }
+
+    /// Whether the audio queue is close to running dry, which indicates that emulation is not
+    /// keeping up with real time.
+    pub fn is_falling_behind(&self) -> bool {
+        self.last_queue_time.is_some()
+            && self.audio_queue.size()
+                < self.buffer_settings.sync_threshold / FALLING_BEHIND_DIVISOR
+    }
+
+    // In Steam Deck mode, sleep for roughly as long as it will take the audio device to drain the
+    // queue below the sync threshold instead of waking up every poll interval. This lets the CPU
+    // idle for most of each frame, which matters more than timer precision on battery power
+    fn audio_sync_sleep_duration(&self, queue_size: u32) -> Duration {
+        if !self.sleep_until_drained {
+            return AUDIO_SYNC_POLL_INTERVAL;
+        }
+
+        let excess_frames =
+            (queue_size - self.buffer_settings.sync_threshold) / BYTES_PER_SAMPLE_FRAME + 1;
+        Duration::from_secs_f64(f64::from(excess_frames) / f64::from(AUDIO_FREQUENCY))
+            .max(AUDIO_SYNC_POLL_INTERVAL)
+    }
+
+    fn check_for_underrun(&mut self, queue_size: u32) {
+        let now = Instant::now();
+        let Some(last_queue_time) = self.last_queue_time.replace(now) else { return };
+
+        if queue_size != 0 || now.duration_since(last_queue_time) > UNDERRUN_MAX_GAP {
+            return;
+        }
+
+        self.statistics.underruns += 1;
+        log::debug!("Audio underrun ({} total)", self.statistics.underruns);
+
+        if self.underrun_recovery {
+            self.resampler.start_underrun_recovery();
+        }
+    }
+
+    fn update_rate_adjustment(&mut self, queue_size: u32) {
+        if !self.buffer_settings.dynamic_rate_control || self.audio_sync {
+            // Audio sync already keeps the queue full by throttling emulation
+            self.resampler.rate_adjustment = 1.0;
+            return;
+        }
+
+        let target = f64::from(self.buffer_settings.sync_threshold) / 2.0;
+        let error = ((target - f64::from(queue_size)) / target).clamp(-1.0, 1.0);
+        self.resampler.rate_adjustment = 1.0 + MAX_RATE_ADJUSTMENT * error;
+    }
+}
+
+impl Drop for SdlAudioOutput {
+    fn drop(&mut self) {
+        log::info!(
+            "Audio statistics: {} underruns, {} overruns",
+            self.statistics.underruns,
+            self.statistics.overruns
+        );
+    }
 }
 
 fn decibels_to_multiplier(decibels: f64) -> f64 {
@@ -105,21 +339,34 @@ impl AudioOutput for SdlAudioOutput {
             return Ok(());
         }
 
-        self.audio_buffer.push((sample_l * self.audio_gain_multiplier) as f32);
-        self.audio_buffer.push((sample_r * self.audio_gain_multiplier) as f32);
+        let (sample_l, sample_r) = self
+            .post_processor
+            .process(sample_l * self.audio_gain_multiplier, sample_r * self.audio_gain_multiplier);
+        self.resampler.push(sample_l, sample_r, &mut self.audio_buffer);
+
+        if self.audio_buffer.len() >= self.buffer_settings.internal_buffer_size as usize {
+            let queue_size = self.audio_queue.size();
+            self.check_for_underrun(queue_size);
+            self.update_rate_adjustment(queue_size);
 
-        if self.audio_buffer.len() >= self.internal_audio_buffer_len as usize {
+            let sync_threshold = self.buffer_settings.sync_threshold;
             if self.audio_sync {
                 // Wait until audio queue is not full
-                while self.audio_queue.size() >= self.audio_sync_threshold {
-                    mainloop::sleep(Duration::from_micros(250));
+                loop {
+                    let queue_size = self.audio_queue.size();
+                    if queue_size < sync_threshold {
+                        break;
+                    }
+                    mainloop::sleep(self.audio_sync_sleep_duration(queue_size));
                 }
-            } else if self.audio_queue.size() >= self.audio_sync_threshold {
+            } else if self.audio_queue.size() >= sync_threshold {
                 // Audio queue is full; drop samples
+                self.statistics.overruns += 1;
                 self.audio_buffer.clear();
                 return Ok(());
             }
 
+            self.statistics.queued_samples = self.audio_queue.size() / BYTES_PER_SAMPLE_FRAME;
             self.audio_queue.queue_audio(&self.audio_buffer).map_err(AudioError::QueueAudio)?;
             self.audio_buffer.clear();
         }
@@ -0,0 +1,287 @@
+//! Optional post-processing chain applied to mixed audio before it is sent to the audio device
+//!
+//! Stages are applied in order: equalizer, stereo widening, crossfeed, soft limiter
+
+use crate::config::AudioPostProcessingConfig;
+use std::f64::consts::PI;
+
+const WIDENING_DELAY_SECONDS: f64 = 0.015;
+
+const CROSSFEED_DELAY_SECONDS: f64 = 0.0003;
+const CROSSFEED_CUTOFF_FREQUENCY: f64 = 700.0;
+
+// -1 dBFS
+const LIMITER_THRESHOLD: f64 = 0.891_250_938_133_745_5;
+const LIMITER_RELEASE_SECONDS: f64 = 0.1;
+
+#[derive(Debug, Clone, Copy)]
+enum BiquadKind {
+    LowShelf,
+    Peaking { q: f64 },
+    HighShelf,
+}
+
+#[derive(Debug, Clone)]
+struct BiquadFilter {
+    b0: f64,
+    b1: f64,
+    b2: f64,
+    a1: f64,
+    a2: f64,
+    // Direct form I history for each channel: [x1, x2, y1, y2]
+    history: [[f64; 4]; 2],
+}
+
+impl BiquadFilter {
+    // Coefficients from the Audio EQ Cookbook by Robert Bristow-Johnson; shelves use a slope of 1
+    fn new(kind: BiquadKind, frequency: f64, gain_db: f64, sample_rate: f64) -> Self {
+        let frequency = frequency.clamp(10.0, 0.45 * sample_rate);
+
+        let a = 10.0_f64.powf(gain_db / 40.0);
+        let w0 = 2.0 * PI * frequency / sample_rate;
+        let (sin_w0, cos_w0) = w0.sin_cos();
+
+        let (b0, b1, b2, a0, a1, a2) = match kind {
+            BiquadKind::LowShelf => {
+                let alpha_term = sin_w0 * a.sqrt() * 2.0_f64.sqrt();
+                (
+                    a * ((a + 1.0) - (a - 1.0) * cos_w0 + alpha_term),
+                    2.0 * a * ((a - 1.0) - (a + 1.0) * cos_w0),
+                    a * ((a + 1.0) - (a - 1.0) * cos_w0 - alpha_term),
+                    (a + 1.0) + (a - 1.0) * cos_w0 + alpha_term,
+                    -2.0 * ((a - 1.0) + (a + 1.0) * cos_w0),
+                    (a + 1.0) + (a - 1.0) * cos_w0 - alpha_term,
+                )
+            }
+            BiquadKind::Peaking { q } => {
+                let alpha = sin_w0 / (2.0 * q.max(0.1));
+                (
+                    1.0 + alpha * a,
+                    -2.0 * cos_w0,
+                    1.0 - alpha * a,
+                    1.0 + alpha / a,
+                    -2.0 * cos_w0,
+                    1.0 - alpha / a,
+                )
+            }
+            BiquadKind::HighShelf => {
+                let alpha_term = sin_w0 * a.sqrt() * 2.0_f64.sqrt();
+                (
+                    a * ((a + 1.0) + (a - 1.0) * cos_w0 + alpha_term),
+                    -2.0 * a * ((a - 1.0) + (a + 1.0) * cos_w0),
+                    a * ((a + 1.0) + (a - 1.0) * cos_w0 - alpha_term),
+                    (a + 1.0) - (a - 1.0) * cos_w0 + alpha_term,
+                    2.0 * ((a - 1.0) - (a + 1.0) * cos_w0),
+                    (a + 1.0) - (a - 1.0) * cos_w0 - alpha_term,
+                )
+            }
+        };
+
+        Self {
+            b0: b0 / a0,
+            b1: b1 / a0,
+            b2: b2 / a0,
+            a1: a1 / a0,
+            a2: a2 / a0,
+            history: [[0.0; 4]; 2],
+        }
+    }
+
+    fn filter(&mut self, channel: usize, sample: f64) -> f64 {
+        let [x1, x2, y1, y2] = self.history[channel];
+        let output = self.b0 * sample + self.b1 * x1 + self.b2 * x2 - self.a1 * y1 - self.a2 * y2;
+        self.history[channel] = [sample, x1, output, y1];
+        output
+    }
+}
+
+#[derive(Debug, Clone)]
+struct DelayLine {
+    buffer: Vec<f64>,
+    position: usize,
+}
+
+impl DelayLine {
+    fn new(delay_seconds: f64, sample_rate: f64) -> Self {
+        let len = ((delay_seconds * sample_rate).round() as usize).max(1);
+        Self { buffer: vec![0.0; len], position: 0 }
+    }
+
+    // Push a sample and return the sample from the configured delay ago
+    fn push(&mut self, sample: f64) -> f64 {
+        let delayed = std::mem::replace(&mut self.buffer[self.position], sample);
+        self.position = (self.position + 1) % self.buffer.len();
+        delayed
+    }
+}
+
+#[derive(Debug, Clone)]
+struct StereoWidener {
+    amount: f64,
+    delay: DelayLine,
+}
+
+impl StereoWidener {
+    fn process(&mut self, sample_l: f64, sample_r: f64) -> (f64, f64) {
+        let mid = 0.5 * (sample_l + sample_r);
+        let side = 0.5 * (sample_l - sample_r);
+
+        // Existing stereo separation is scaled up, and a delayed copy of the mid signal is added
+        // with opposite polarity in each channel so that mono sources are widened too. The two
+        // copies cancel out when summed back to mono.
+        let delayed_mid = self.delay.push(mid);
+        let side = side * (1.0 + self.amount) + 0.5 * self.amount * delayed_mid;
+
+        (mid + side, mid - side)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Crossfeed {
+    amount: f64,
+    lowpass_coefficient: f64,
+    lowpass_state: [f64; 2],
+    delays: [DelayLine; 2],
+}
+
+impl Crossfeed {
+    fn new(amount: f64, sample_rate: f64) -> Self {
+        Self {
+            amount,
+            lowpass_coefficient: (-2.0 * PI * CROSSFEED_CUTOFF_FREQUENCY / sample_rate).exp(),
+            lowpass_state: [0.0; 2],
+            delays: [
+                DelayLine::new(CROSSFEED_DELAY_SECONDS, sample_rate),
+                DelayLine::new(CROSSFEED_DELAY_SECONDS, sample_rate),
+            ],
+        }
+    }
+
+    fn process(&mut self, sample_l: f64, sample_r: f64) -> (f64, f64) {
+        // Each ear hears a slightly delayed and low-passed copy of the opposite channel,
+        // approximating how speakers are heard
+        let mut feed = [0.0; 2];
+        for (channel, sample) in [sample_l, sample_r].into_iter().enumerate() {
+            let state = &mut self.lowpass_state[channel];
+            *state = sample + self.lowpass_coefficient * (*state - sample);
+            feed[channel] = self.delays[channel].push(*state);
+        }
+
+        let normalization = 1.0 / (1.0 + self.amount);
+        (
+            (sample_l + self.amount * feed[1]) * normalization,
+            (sample_r + self.amount * feed[0]) * normalization,
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+struct SoftLimiter {
+    gain: f64,
+    release_coefficient: f64,
+}
+
+impl SoftLimiter {
+    fn new(sample_rate: f64) -> Self {
+        Self {
+            gain: 1.0,
+            release_coefficient: (-1.0 / (LIMITER_RELEASE_SECONDS * sample_rate)).exp(),
+        }
+    }
+
+    fn process(&mut self, sample_l: f64, sample_r: f64) -> (f64, f64) {
+        let peak = sample_l.abs().max(sample_r.abs());
+        let target_gain = if peak > LIMITER_THRESHOLD { LIMITER_THRESHOLD / peak } else { 1.0 };
+
+        // Instant attack, exponential release
+        self.gain = if target_gain < self.gain {
+            target_gain
+        } else {
+            target_gain + self.release_coefficient * (self.gain - target_gain)
+        };
+
+        (sample_l * self.gain, sample_r * self.gain)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AudioPostProcessor {
+    config: AudioPostProcessingConfig,
+    sample_rate: f64,
+    equalizer: Option<[BiquadFilter; 3]>,
+    widener: Option<StereoWidener>,
+    crossfeed: Option<Crossfeed>,
+    limiter: Option<SoftLimiter>,
+}
+
+impl AudioPostProcessor {
+    pub fn new(config: AudioPostProcessingConfig, sample_rate: f64) -> Self {
+        let equalizer = config.equalizer_enabled.then(|| {
+            [
+                BiquadFilter::new(
+                    BiquadKind::LowShelf,
+                    config.eq_low_frequency,
+                    config.eq_low_gain_db,
+                    sample_rate,
+                ),
+                BiquadFilter::new(
+                    BiquadKind::Peaking { q: config.eq_mid_q },
+                    config.eq_mid_frequency,
+                    config.eq_mid_gain_db,
+                    sample_rate,
+                ),
+                BiquadFilter::new(
+                    BiquadKind::HighShelf,
+                    config.eq_high_frequency,
+                    config.eq_high_gain_db,
+                    sample_rate,
+                ),
+            ]
+        });
+
+        let widening = config.stereo_widening.clamp(0.0, 1.0);
+        let widener = (widening > 0.0).then(|| StereoWidener {
+            amount: widening,
+            delay: DelayLine::new(WIDENING_DELAY_SECONDS, sample_rate),
+        });
+
+        let crossfeed = config.crossfeed.clamp(0.0, 1.0);
+        let crossfeed = (crossfeed > 0.0).then(|| Crossfeed::new(crossfeed, sample_rate));
+
+        let limiter = config.soft_limiter_enabled.then(|| SoftLimiter::new(sample_rate));
+
+        Self { config, sample_rate, equalizer, widener, crossfeed, limiter }
+    }
+
+    pub fn reload_config(&mut self, config: AudioPostProcessingConfig) {
+        if config != self.config {
+            *self = Self::new(config, self.sample_rate);
+        }
+    }
+
+    #[inline]
+    pub fn process(&mut self, sample_l: f64, sample_r: f64) -> (f64, f64) {
+        let (mut sample_l, mut sample_r) = (sample_l, sample_r);
+
+        if let Some(equalizer) = &mut self.equalizer {
+            for filter in equalizer {
+                sample_l = filter.filter(0, sample_l);
+                sample_r = filter.filter(1, sample_r);
+            }
+        }
+
+        if let Some(widener) = &mut self.widener {
+            (sample_l, sample_r) = widener.process(sample_l, sample_r);
+        }
+
+        if let Some(crossfeed) = &mut self.crossfeed {
+            (sample_l, sample_r) = crossfeed.process(sample_l, sample_r);
+        }
+
+        if let Some(limiter) = &mut self.limiter {
+            (sample_l, sample_r) = limiter.process(sample_l, sample_r);
+        }
+
+        (sample_l, sample_r)
+    }
+}
@@ -0,0 +1,218 @@
+//! Crash reports
+//!
+//! When the emulator panics or the emulation core returns an error, a diagnostic bundle is written
+//! to a new directory under the crash report directory (see [`crate::paths::AppPaths`]). The bundle contains the config, a CRC32 of the ROM
+//! file, recent log output, the input trace, and a gzip-compressed save state.
+//!
+//! Panics are reported from a panic hook so that a bundle is still written in builds that abort on
+//! panic. The save state is added afterwards if the panic unwinds back to the mainloop.
+
+use crate::mainloop::inputtrace;
+use bincode::Encode;
+use crc::Crc;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use jgenesis_common::logging;
+use jgenesis_common::state::bincode_config;
+use std::any::Any;
+use std::fmt::{Display, Write as _};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, Once};
+use std::thread::ThreadId;
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{fs, io, panic, thread};
+
+/// Name of the directory that crash report bundles are written to. Relative to the working
+/// directory unless [`set_report_dir`] is called.
+pub const CRASH_REPORT_DIR: &str = "crash-reports";
+
+const CRC: Crc<u32> = Crc::<u32>::new(&crc::CRC_32_ISO_HDLC);
+
+#[derive(Debug, Default)]
+struct CrashContext {
+    rom_path: PathBuf,
+    config: String,
+}
+
+// Context for the most recently created emulator
+static CONTEXT: Mutex<Option<CrashContext>> = Mutex::new(None);
+
+static REPORT_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+// Bundle written by the panic hook, so that the save state can be added to the same bundle if the
+// panic is caught
+static PANIC_BUNDLE: Mutex<Option<(ThreadId, PathBuf)>> = Mutex::new(None);
+
+pub fn set_rom_path(rom_path: &Path) {
+    install_panic_hook();
+
+    if let Ok(mut context) = CONTEXT.lock() {
+        context.get_or_insert_with(CrashContext::default).rom_path = rom_path.into();
+    }
+}
+
+pub fn set_report_dir(dir: PathBuf) {
+    if let Ok(mut report_dir) = REPORT_DIR.lock() {
+        *report_dir = Some(dir);
+    }
+}
+
+fn report_dir() -> PathBuf {
+    match REPORT_DIR.try_lock() {
+        Ok(report_dir) => report_dir.clone().unwrap_or_else(|| CRASH_REPORT_DIR.into()),
+        Err(_) => CRASH_REPORT_DIR.into(),
+    }
+}
+
+pub fn set_config(config: &impl Display) {
+    if let Ok(mut context) = CONTEXT.lock() {
+        context.get_or_insert_with(CrashContext::default).config = config.to_string();
+    }
+}
+
+fn install_panic_hook() {
+    static INSTALL: Once = Once::new();
+
+    INSTALL.call_once(|| {
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            default_hook(info);
+
+            let message = panic_message(info.payload());
+            let reason = match info.location() {
+                Some(location) => format!("Panicked at {location}: {message}"),
+                None => format!("Panicked: {message}"),
+            };
+
+            if let Some(bundle_path) = write_bundle(&reason) {
+                if let Ok(mut panic_bundle) = PANIC_BUNDLE.try_lock() {
+                    *panic_bundle = Some((thread::current().id(), bundle_path));
+                }
+            }
+        }));
+    });
+}
+
+/// Returns the path of the bundle that the panic hook wrote for a panic on the current thread.
+pub fn take_panic_bundle() -> Option<PathBuf> {
+    let mut panic_bundle = PANIC_BUNDLE.lock().ok()?;
+    match panic_bundle.take() {
+        Some((thread_id, path)) if thread_id == thread::current().id() => Some(path),
+        _ => None,
+    }
+}
+
+pub fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|&s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "<non-string panic payload>".into())
+}
+
+/// Write a crash report bundle without a save state. Errors are logged.
+///
+/// This never blocks on the emulator's locks so that it is safe to call from a panic hook.
+pub fn write_bundle(reason: &str) -> Option<PathBuf> {
+    match try_write_bundle(reason) {
+        Ok(path) => {
+            log::error!("Wrote crash report to '{}'", path.display());
+            Some(path)
+        }
+        Err(err) => {
+            log::error!("Error writing crash report: {err}");
+            None
+        }
+    }
+}
+
+fn try_write_bundle(reason: &str) -> io::Result<PathBuf> {
+    let (rom_path, config) = match CONTEXT.try_lock() {
+        Ok(context) => context
+            .as_ref()
+            .map(|context| (context.rom_path.clone(), context.config.clone()))
+            .unwrap_or_default(),
+        Err(_) => (PathBuf::new(), String::new()),
+    };
+
+    let rom_name = rom_path.file_stem().map(|stem| stem.to_string_lossy()).unwrap_or_default();
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    let bundle_path =
+        next_bundle_path(&report_dir().join(format!(
+            "{}_{timestamp}",
+            if rom_name.is_empty() { "unknown" } else { &rom_name }
+        )));
+    fs::create_dir_all(&bundle_path)?;
+
+    let mut info = String::new();
+    let _ = writeln!(info, "jgenesis {}", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(info, "Platform: {} {}", std::env::consts::OS, std::env::consts::ARCH);
+    let _ = writeln!(info, "ROM: {}", rom_path.display());
+    match rom_crc32(&rom_path) {
+        Ok(crc32) => {
+            let _ = writeln!(info, "ROM CRC32: {crc32:08X}");
+        }
+        Err(err) => {
+            let _ = writeln!(info, "ROM CRC32: <error reading ROM: {err}>");
+        }
+    }
+    let _ = writeln!(info, "Reason: {reason}");
+    fs::write(bundle_path.join("info.txt"), info)?;
+
+    fs::write(bundle_path.join("config.txt"), config)?;
+
+    let mut log = logging::recent_log_lines().join("\n");
+    log.push('\n');
+    fs::write(bundle_path.join("log.txt"), log)?;
+
+    if let Some(trace) = inputtrace::current_trace_text() {
+        fs::write(bundle_path.join("inputs.txt"), trace)?;
+    }
+
+    Ok(bundle_path)
+}
+
+/// Add a gzip-compressed save state to an existing bundle. Errors are logged.
+pub fn write_save_state<E: Encode>(bundle_path: &Path, emulator: &E) {
+    let path = bundle_path.join("state.ss0.gz");
+    if let Err(err) = try_write_save_state(&path, emulator) {
+        log::error!("Error writing crash report save state to '{}': {err}", path.display());
+    }
+}
+
+fn try_write_save_state<E: Encode>(path: &Path, emulator: &E) -> anyhow::Result<()> {
+    let file = BufWriter::new(File::create(path)?);
+    let mut encoder = GzEncoder::new(file, Compression::default());
+    bincode::encode_into_std_write(emulator, &mut encoder, bincode_config())?;
+    encoder.finish()?.flush()?;
+
+    Ok(())
+}
+
+fn rom_crc32(rom_path: &Path) -> io::Result<u32> {
+    let mut reader = BufReader::new(File::open(rom_path)?);
+    let mut digest = CRC.digest();
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        let len = reader.read(&mut buffer)?;
+        if len == 0 {
+            return Ok(digest.finalize());
+        }
+        digest.update(&buffer[..len]);
+    }
+}
+
+fn next_bundle_path(base_path: &Path) -> PathBuf {
+    let file_name = base_path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+
+    let mut path = base_path.to_path_buf();
+    let mut n = 1;
+    while path.exists() {
+        n += 1;
+        path = base_path.with_file_name(format!("{file_name}_{n}"));
+    }
+
+    path
+}
@@ -1,7 +1,14 @@
+pub mod atari2600;
+pub mod colecovision;
 mod eguisdl;
 pub mod gb;
+pub mod gba;
 pub mod genesis;
+mod images;
+mod memory;
 pub mod nes;
+pub mod pce;
+mod search;
 pub mod smsgg;
 pub mod snes;
 
@@ -9,7 +16,12 @@ use egui_wgpu_backend::ScreenDescriptor;
 
 use sdl2::event::{Event, WindowEvent};
 
-use egui::{Button, Response, Ui, Widget, WidgetText};
+use crate::mainloop::audio::AudioStatistics;
+use crate::mainloop::save::FsSaveWriter;
+use egui::{Align2, Button, Color32, ComboBox, Grid, Response, Ui, Widget, WidgetText};
+use jgenesis_common::debug::{FreezeList, SymbolTable};
+use jgenesis_common::logging::{self, LogSubsystem};
+use log::LevelFilter;
 use sdl2::video::{Window, WindowBuildError};
 use sdl2::VideoSubsystem;
 use std::iter;
@@ -35,6 +47,9 @@ pub enum DebuggerError {
 pub struct DebugRenderContext<'a, Emulator> {
     egui_ctx: &'a egui::Context,
     emulator: &'a mut Emulator,
+    symbols: &'a SymbolTable,
+    freeze_list: &'a mut FreezeList,
+    save_writer: &'a mut FsSaveWriter,
     device: &'a wgpu::Device,
     queue: &'a wgpu::Queue,
     rpass: &'a mut egui_wgpu_backend::RenderPass,
@@ -125,7 +140,14 @@ impl<Emulator> DebuggerWindow<Emulator> {
         })
     }
 
-    pub fn update(&mut self, emulator: &mut Emulator) -> Result<(), DebuggerError> {
+    pub fn update(
+        &mut self,
+        emulator: &mut Emulator,
+        symbols: &SymbolTable,
+        freeze_list: &mut FreezeList,
+        save_writer: &mut FsSaveWriter,
+        audio_statistics: AudioStatistics,
+    ) -> Result<(), DebuggerError> {
         self.platform.update_time(
             SystemTime::now().duration_since(self.start_time).unwrap_or_default().as_secs_f64(),
         );
@@ -136,11 +158,17 @@ impl<Emulator> DebuggerWindow<Emulator> {
         (self.render_fn)(DebugRenderContext {
             egui_ctx,
             emulator,
+            symbols,
+            freeze_list,
+            save_writer,
             device: &self.device,
             queue: &self.queue,
             rpass: &mut self.egui_pass,
         })?;
 
+        render_performance_window(egui_ctx, audio_statistics);
+        render_logging_window(egui_ctx);
+
         let output = match self.surface.get_current_texture() {
             Ok(output) => output,
             Err(wgpu::SurfaceError::Outdated) => {
@@ -227,6 +255,83 @@ fn determine_scale_factor(window: &Window, video: &VideoSubsystem) -> f32 {
     if (0.5..=10.0).contains(&scale_factor) { scale_factor } else { 1.0 }
 }
 
+fn render_performance_window(ctx: &egui::Context, audio_statistics: AudioStatistics) {
+    egui::Window::new("Performance")
+        .anchor(Align2::RIGHT_BOTTOM, [-10.0, -10.0])
+        .default_open(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            Grid::new("audio_statistics").num_columns(2).show(ui, |ui| {
+                ui.label("Audio queue");
+                ui.label(format!(
+                    "{} samples ({:.1} ms)",
+                    audio_statistics.queued_samples,
+                    audio_statistics.queued_duration().as_secs_f64() * 1000.0
+                ));
+                ui.end_row();
+
+                ui.label("Underruns");
+                ui.label(audio_statistics.underruns.to_string());
+                ui.end_row();
+
+                ui.label("Overruns");
+                ui.label(audio_statistics.overruns.to_string());
+                ui.end_row();
+            });
+
+            if audio_statistics.recovering {
+                ui.colored_label(Color32::YELLOW, "Recovering from underrun");
+            }
+        });
+}
+
+fn render_logging_window(ctx: &egui::Context) {
+    const LEVELS: [Option<LevelFilter>; 7] = [
+        None,
+        Some(LevelFilter::Off),
+        Some(LevelFilter::Error),
+        Some(LevelFilter::Warn),
+        Some(LevelFilter::Info),
+        Some(LevelFilter::Debug),
+        Some(LevelFilter::Trace),
+    ];
+
+    fn level_text(level: Option<LevelFilter>) -> String {
+        level.map_or_else(|| "Default".into(), |level| level.to_string())
+    }
+
+    egui::Window::new("Logging")
+        .anchor(Align2::LEFT_BOTTOM, [10.0, -10.0])
+        .default_open(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            Grid::new("log_subsystem_levels").num_columns(2).show(ui, |ui| {
+                for subsystem in LogSubsystem::ALL {
+                    let mut level = logging::subsystem_level(subsystem);
+                    let prev_level = level;
+
+                    ui.label(subsystem.to_string());
+                    ComboBox::from_id_source(("log_subsystem_level", subsystem.to_string()))
+                        .selected_text(level_text(level))
+                        .show_ui(ui, |ui| {
+                            for option in LEVELS {
+                                ui.selectable_value(&mut level, option, level_text(option));
+                            }
+                        });
+                    ui.end_row();
+
+                    if level != prev_level {
+                        logging::set_subsystem_level(subsystem, level);
+                    }
+                }
+            });
+
+            if cfg!(not(debug_assertions)) {
+                ui.colored_label(Color32::YELLOW, "Debug and trace logs require a debug build");
+            }
+        });
+}
+
 fn screen_width(ctx: &egui::Context) -> f32 {
     let window_margin = ctx.style().spacing.window_margin;
     ctx.available_rect().width() - window_margin.left - window_margin.right
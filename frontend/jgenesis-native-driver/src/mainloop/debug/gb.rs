@@ -1,9 +1,22 @@
 use crate::mainloop::debug;
-use crate::mainloop::debug::{DebugRenderContext, DebugRenderFn, DebuggerError, SelectableButton};
+use crate::mainloop::debug::images::ImageFile;
+use crate::mainloop::debug::{
+    images, DebugRenderContext, DebugRenderFn, DebuggerError, SelectableButton,
+};
 use egui::{CentralPanel, Grid, ScrollArea, Vec2};
 use gb_core::api::{BackgroundTileMap, GameBoyEmulator};
 use jgenesis_common::frontend::Color;
 
+const BACKGROUND_IMAGE: ImageFile<'static> =
+    ImageFile { extension: "bg.png", width: 256, height: 256 };
+const SPRITES_IMAGE: ImageFile<'static> =
+    ImageFile { extension: "sprites.png", width: 8 * 8, height: 5 * 8 };
+const DOUBLE_HEIGHT_SPRITES_IMAGE: ImageFile<'static> =
+    ImageFile { extension: "sprites.png", width: 8 * 8, height: 2 * 5 * 8 };
+// Background palettes on top, sprite palettes on bottom
+const PALETTES_IMAGE: ImageFile<'static> =
+    ImageFile { extension: "palettes.png", width: 4, height: 16 };
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 enum Tab {
     #[default]
@@ -23,6 +36,8 @@ struct State {
     sprites_double_height_texture: Option<(wgpu::Texture, egui::TextureId)>,
     bg_palettes_texture: Option<(wgpu::Texture, egui::TextureId)>,
     obj_palettes_texture: Option<(wgpu::Texture, egui::TextureId)>,
+    palettes_buffer: [Color; 64],
+    image_status: Option<String>,
 }
 
 impl State {
@@ -37,6 +52,8 @@ impl State {
             sprites_double_height_texture: None,
             bg_palettes_texture: None,
             obj_palettes_texture: None,
+            palettes_buffer: [Color::default(); 64],
+            image_status: None,
         }
     }
 }
@@ -55,6 +72,7 @@ fn render(
     update_palettes_texture(&mut ctx, state)?;
 
     let screen_width = debug::screen_width(ctx.egui_ctx);
+    let save_writer = &mut *ctx.save_writer;
 
     CentralPanel::default().show(ctx.egui_ctx, |ui| {
         ui.horizontal(|ui| {
@@ -82,6 +100,17 @@ fn render(
                     );
                 });
 
+                ui.horizontal(|ui| {
+                    images::render_export_button(
+                        ui,
+                        save_writer,
+                        BACKGROUND_IMAGE,
+                        &state.background_buffer,
+                        &mut state.image_status,
+                    );
+                });
+                images::render_status(ui, state.image_status.as_deref());
+
                 ui.add_space(10.0);
 
                 ScrollArea::vertical().show(ui, |ui| {
@@ -95,6 +124,25 @@ fn render(
                 });
             }
             Tab::Sprites => {
+                let sprites_image = if ctx.emulator.is_using_double_height_sprites() {
+                    DOUBLE_HEIGHT_SPRITES_IMAGE
+                } else {
+                    SPRITES_IMAGE
+                };
+                ui.horizontal(|ui| {
+                    images::render_export_button(
+                        ui,
+                        save_writer,
+                        sprites_image,
+                        &state.sprites_buffer
+                            [..(sprites_image.width * sprites_image.height) as usize],
+                        &mut state.image_status,
+                    );
+                });
+                images::render_status(ui, state.image_status.as_deref());
+
+                ui.add_space(10.0);
+
                 if ctx.emulator.is_using_double_height_sprites() {
                     ScrollArea::vertical().show(ui, |ui| {
                         ui.vertical_centered(|ui| {
@@ -122,6 +170,19 @@ fn render(
                 let bg_texture = state.bg_palettes_texture.as_ref().unwrap().1;
                 let obj_texture = state.obj_palettes_texture.as_ref().unwrap().1;
 
+                ui.horizontal(|ui| {
+                    images::render_export_button(
+                        ui,
+                        save_writer,
+                        PALETTES_IMAGE,
+                        &state.palettes_buffer,
+                        &mut state.image_status,
+                    );
+                });
+                images::render_status(ui, state.image_status.as_deref());
+
+                ui.add_space(10.0);
+
                 Grid::new("debug_gb_palettes_grid").show(ui, |ui| {
                     ui.vertical_centered(|ui| {
                         ui.heading("Background");
@@ -222,11 +283,10 @@ fn update_palettes_texture(
     ctx: &mut DebugRenderContext<'_, GameBoyEmulator>,
     state: &mut State,
 ) -> Result<(), DebuggerError> {
-    let mut palettes_buffer = [Color::default(); 64];
-
     if state.tab == Tab::Palettes {
-        ctx.emulator.copy_palettes(&mut palettes_buffer);
+        ctx.emulator.copy_palettes(&mut state.palettes_buffer);
     }
+    let palettes_buffer = &state.palettes_buffer;
 
     if state.bg_palettes_texture.is_none() {
         let (wgpu_texture, egui_texture) =
@@ -0,0 +1,87 @@
+use crate::mainloop::debug;
+use crate::mainloop::debug::images::ImageFile;
+use crate::mainloop::debug::{images, DebugRenderContext, DebugRenderFn, DebuggerError};
+use egui::{CentralPanel, Vec2};
+use gba_core::GbaEmulator;
+use jgenesis_common::frontend::Color;
+
+// Palette RAM is displayed as 32 rows of 16 colors, background palettes on top and sprite palettes
+// on bottom
+const PALETTES_IMAGE: ImageFile<'static> =
+    ImageFile { extension: "palettes.png", width: 16, height: 32 };
+
+struct State {
+    palette_texture: Option<(wgpu::Texture, egui::TextureId)>,
+    palette_buffer: Box<[Color; 512]>,
+    image_status: Option<String>,
+}
+
+impl State {
+    fn new() -> Self {
+        Self {
+            palette_texture: None,
+            palette_buffer: vec![Color::default(); 512].into_boxed_slice().try_into().unwrap(),
+            image_status: None,
+        }
+    }
+}
+
+pub fn render_fn() -> Box<DebugRenderFn<GbaEmulator>> {
+    let mut state = State::new();
+    Box::new(move |ctx| render(ctx, &mut state))
+}
+
+fn render(
+    mut ctx: DebugRenderContext<'_, GbaEmulator>,
+    state: &mut State,
+) -> Result<(), DebuggerError> {
+    update_palette_texture(&mut ctx, state)?;
+
+    let screen_width = debug::screen_width(ctx.egui_ctx);
+    let save_writer = &mut *ctx.save_writer;
+
+    CentralPanel::default().show(ctx.egui_ctx, |ui| {
+        ui.heading("Palettes");
+
+        ui.add_space(15.0);
+
+        images::render_export_button(
+            ui,
+            save_writer,
+            PALETTES_IMAGE,
+            state.palette_buffer.as_ref(),
+            &mut state.image_status,
+        );
+        images::render_status(ui, state.image_status.as_deref());
+
+        ui.add_space(15.0);
+
+        let palette_texture = state.palette_texture.as_ref().unwrap().1;
+        let image_width = 0.5 * screen_width;
+        ui.image((palette_texture, Vec2::new(image_width, 2.0 * image_width)));
+    });
+
+    Ok(())
+}
+
+fn update_palette_texture(
+    ctx: &mut DebugRenderContext<'_, GbaEmulator>,
+    state: &mut State,
+) -> Result<(), DebuggerError> {
+    ctx.emulator.copy_palettes(state.palette_buffer.as_mut());
+
+    if state.palette_texture.is_none() {
+        let (wgpu_texture, egui_texture) =
+            debug::create_texture("debug_gba_palettes", 16, 32, ctx.device, ctx.rpass);
+        state.palette_texture = Some((wgpu_texture, egui_texture));
+    }
+
+    let (wgpu_texture, egui_texture) = state.palette_texture.as_ref().unwrap();
+
+    debug::write_textures(
+        wgpu_texture,
+        *egui_texture,
+        bytemuck::cast_slice(state.palette_buffer.as_ref()),
+        ctx,
+    )
+}
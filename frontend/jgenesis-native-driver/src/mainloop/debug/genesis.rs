@@ -1,15 +1,40 @@
+mod events;
+mod soundtrace;
+
 use crate::mainloop::debug;
-use crate::mainloop::debug::{DebugRenderContext, DebugRenderFn, DebuggerError, SelectableButton};
+use crate::mainloop::debug::genesis::events::EventViewerState;
+use crate::mainloop::debug::genesis::soundtrace::SoundTraceState;
+use crate::mainloop::debug::images::ImageFile;
+use crate::mainloop::debug::memory::MemoryViewerState;
+use crate::mainloop::debug::search::RamSearchState;
+use crate::mainloop::debug::{
+    images, memory, search, DebugRenderContext, DebugRenderFn, DebuggerError, SelectableButton,
+};
 use egui::{CentralPanel, ScrollArea, Vec2};
-use genesis_core::GenesisEmulator;
-use jgenesis_common::frontend::Color;
+use genesis_core::pico::PicoEmulator;
+use genesis_core::soundlog::SoundLog;
+use genesis_core::vdp::VdpEventLog;
+use genesis_core::{soundlog, GenesisEmulator};
+use jgenesis_common::debug::Debuggable;
+use jgenesis_common::frontend::{Color, EmulatorTrait};
 use segacd_core::api::SegaCdEmulator;
 
+// VRAM is displayed as 64x32 patterns
+const VRAM_ROW_LEN: usize = 64;
+
+const VRAM_IMAGE: ImageFile<'static> =
+    ImageFile { extension: "vram.png", width: 64 * 8, height: 32 * 8 };
+const CRAM_IMAGE: ImageFile<'static> = ImageFile { extension: "cram.png", width: 16, height: 4 };
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 enum Tab {
     Cram,
     #[default]
     Vram,
+    Memory,
+    RamSearch,
+    EventViewer,
+    SoundTrace,
 }
 
 struct State {
@@ -19,6 +44,11 @@ struct State {
     vram_texture: Option<(wgpu::Texture, egui::TextureId)>,
     cram_buffer: Box<[Color; 64]>,
     vram_buffer: Box<[Color; 2048 * 64]>,
+    image_status: Option<String>,
+    memory_viewer: MemoryViewerState,
+    ram_search: RamSearchState,
+    event_viewer: EventViewerState,
+    sound_trace: SoundTraceState,
 }
 
 impl State {
@@ -30,14 +60,30 @@ impl State {
             vram_texture: None,
             cram_buffer: vec![Color::default(); 64].into_boxed_slice().try_into().unwrap(),
             vram_buffer: vec![Color::default(); 2048 * 64].into_boxed_slice().try_into().unwrap(),
+            image_status: None,
+            memory_viewer: MemoryViewerState::new(),
+            ram_search: RamSearchState::new(),
+            event_viewer: EventViewerState::new(),
+            sound_trace: SoundTraceState::new(),
         }
     }
 }
 
-pub(crate) trait GenesisBase {
+pub(crate) trait GenesisBase: EmulatorTrait {
     fn copy_cram(&self, out: &mut [Color]);
 
     fn copy_vram(&self, out: &mut [Color], palette: u8, row_len: usize);
+
+    fn import_vram(&mut self, pixels: &[Color], palette: u8, row_len: usize);
+
+    fn set_vdp_event_logging(&mut self, enabled: bool);
+
+    fn vdp_event_log(&self) -> &VdpEventLog;
+
+    fn sound_log_mut(&mut self) -> &mut SoundLog;
+
+    /// Returns None if the memory viewer and RAM search are not supported for this emulator.
+    fn debuggable(&self) -> Option<&dyn Debuggable>;
 }
 
 impl GenesisBase for GenesisEmulator {
@@ -48,6 +94,26 @@ impl GenesisBase for GenesisEmulator {
     fn copy_vram(&self, out: &mut [Color], palette: u8, row_len: usize) {
         GenesisEmulator::copy_vram(self, out, palette, row_len);
     }
+
+    fn import_vram(&mut self, pixels: &[Color], palette: u8, row_len: usize) {
+        GenesisEmulator::import_vram(self, pixels, palette, row_len);
+    }
+
+    fn set_vdp_event_logging(&mut self, enabled: bool) {
+        GenesisEmulator::set_vdp_event_logging(self, enabled);
+    }
+
+    fn vdp_event_log(&self) -> &VdpEventLog {
+        GenesisEmulator::vdp_event_log(self)
+    }
+
+    fn sound_log_mut(&mut self) -> &mut SoundLog {
+        GenesisEmulator::sound_log_mut(self)
+    }
+
+    fn debuggable(&self) -> Option<&dyn Debuggable> {
+        Some(self)
+    }
 }
 
 impl GenesisBase for SegaCdEmulator {
@@ -58,6 +124,56 @@ impl GenesisBase for SegaCdEmulator {
     fn copy_vram(&self, out: &mut [Color], palette: u8, row_len: usize) {
         SegaCdEmulator::copy_vram(self, out, palette, row_len);
     }
+
+    fn import_vram(&mut self, pixels: &[Color], palette: u8, row_len: usize) {
+        SegaCdEmulator::import_vram(self, pixels, palette, row_len);
+    }
+
+    fn set_vdp_event_logging(&mut self, enabled: bool) {
+        SegaCdEmulator::set_vdp_event_logging(self, enabled);
+    }
+
+    fn vdp_event_log(&self) -> &VdpEventLog {
+        SegaCdEmulator::vdp_event_log(self)
+    }
+
+    fn sound_log_mut(&mut self) -> &mut SoundLog {
+        SegaCdEmulator::sound_log_mut(self)
+    }
+
+    fn debuggable(&self) -> Option<&dyn Debuggable> {
+        None
+    }
+}
+
+impl GenesisBase for PicoEmulator {
+    fn copy_cram(&self, out: &mut [Color]) {
+        PicoEmulator::copy_cram(self, out);
+    }
+
+    fn copy_vram(&self, out: &mut [Color], palette: u8, row_len: usize) {
+        PicoEmulator::copy_vram(self, out, palette, row_len);
+    }
+
+    fn import_vram(&mut self, pixels: &[Color], palette: u8, row_len: usize) {
+        PicoEmulator::import_vram(self, pixels, palette, row_len);
+    }
+
+    fn set_vdp_event_logging(&mut self, enabled: bool) {
+        PicoEmulator::set_vdp_event_logging(self, enabled);
+    }
+
+    fn vdp_event_log(&self) -> &VdpEventLog {
+        PicoEmulator::vdp_event_log(self)
+    }
+
+    fn sound_log_mut(&mut self) -> &mut SoundLog {
+        PicoEmulator::sound_log_mut(self)
+    }
+
+    fn debuggable(&self) -> Option<&dyn Debuggable> {
+        None
+    }
 }
 
 pub(crate) fn render_fn<Emulator: GenesisBase>() -> Box<DebugRenderFn<Emulator>> {
@@ -72,18 +188,49 @@ fn render<Emulator: GenesisBase>(
     update_cram_texture(&mut ctx, state)?;
     update_vram_texture(&mut ctx, state)?;
 
+    // Only log VDP events while the event viewer is open
+    ctx.emulator.set_vdp_event_logging(state.tab == Tab::EventViewer);
+
+    soundtrace::update(ctx.emulator.sound_log_mut(), &mut state.sound_trace);
+    let mclk_frequency = soundlog::mclk_frequency(ctx.emulator.timing_mode());
+
     let screen_width = debug::screen_width(ctx.egui_ctx);
+    let debuggable = ctx.emulator.debuggable();
+    let event_log = ctx.emulator.vdp_event_log();
+    let symbols = ctx.symbols;
+    let freeze_list = &mut *ctx.freeze_list;
+    let save_writer = &mut *ctx.save_writer;
+    let mut imported_vram = None;
 
     CentralPanel::default().show(ctx.egui_ctx, |ui| {
         ui.horizontal(|ui| {
             ui.add(SelectableButton::new("VRAM", &mut state.tab, Tab::Vram));
             ui.add(SelectableButton::new("CRAM", &mut state.tab, Tab::Cram));
+            ui.add(SelectableButton::new("Events", &mut state.tab, Tab::EventViewer));
+            ui.add(SelectableButton::new("Sound Trace", &mut state.tab, Tab::SoundTrace));
+            if debuggable.is_some() {
+                ui.add(SelectableButton::new("Memory", &mut state.tab, Tab::Memory));
+                ui.add(SelectableButton::new("RAM Search", &mut state.tab, Tab::RamSearch));
+            }
         });
 
         ui.add_space(15.0);
 
         match state.tab {
             Tab::Cram => {
+                ui.horizontal(|ui| {
+                    images::render_export_button(
+                        ui,
+                        save_writer,
+                        CRAM_IMAGE,
+                        state.cram_buffer.as_ref(),
+                        &mut state.image_status,
+                    );
+                });
+                images::render_status(ui, state.image_status.as_deref());
+
+                ui.add_space(15.0);
+
                 let egui_texture = state.cram_texture.as_ref().unwrap().1;
                 ui.image((egui_texture, Vec2::new(screen_width, screen_width * 0.25)));
             }
@@ -96,6 +243,25 @@ fn render<Emulator: GenesisBase>(
                     }
                 });
 
+                ui.horizontal(|ui| {
+                    images::render_export_button(
+                        ui,
+                        save_writer,
+                        VRAM_IMAGE,
+                        state.vram_buffer.as_ref(),
+                        &mut state.image_status,
+                    );
+
+                    // Imported pixels are mapped back to color IDs using the selected palette
+                    imported_vram = images::render_import_button(
+                        ui,
+                        save_writer,
+                        VRAM_IMAGE,
+                        &mut state.image_status,
+                    );
+                });
+                images::render_status(ui, state.image_status.as_deref());
+
                 ui.add_space(15.0);
 
                 ScrollArea::vertical().show(ui, |ui| {
@@ -103,9 +269,29 @@ fn render<Emulator: GenesisBase>(
                     ui.image((egui_texture, Vec2::new(screen_width, screen_width * 0.5)));
                 });
             }
+            Tab::Memory => {
+                if let Some(debuggable) = debuggable {
+                    memory::render(ui, debuggable, symbols, &mut state.memory_viewer);
+                }
+            }
+            Tab::RamSearch => {
+                if let Some(debuggable) = debuggable {
+                    search::render(ui, debuggable, symbols, freeze_list, &mut state.ram_search);
+                }
+            }
+            Tab::EventViewer => {
+                events::render(ui, event_log, &mut state.event_viewer);
+            }
+            Tab::SoundTrace => {
+                soundtrace::render(ui, save_writer, mclk_frequency, &mut state.sound_trace);
+            }
         }
     });
 
+    if let Some(pixels) = imported_vram {
+        ctx.emulator.import_vram(&pixels, state.vram_palette, VRAM_ROW_LEN);
+    }
+
     Ok(())
 }
 
@@ -135,7 +321,7 @@ fn update_vram_texture<Emulator: GenesisBase>(
     ctx: &mut DebugRenderContext<'_, Emulator>,
     state: &mut State,
 ) -> Result<(), DebuggerError> {
-    ctx.emulator.copy_vram(state.vram_buffer.as_mut(), state.vram_palette, 64);
+    ctx.emulator.copy_vram(state.vram_buffer.as_mut(), state.vram_palette, VRAM_ROW_LEN);
 
     if state.vram_texture.is_none() {
         let (wgpu_texture, egui_texture) =
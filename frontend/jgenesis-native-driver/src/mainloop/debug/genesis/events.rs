@@ -0,0 +1,189 @@
+//! VDP event viewer, which plots register writes, DMA transfers, and interrupts from the last
+//! completed frame on a scanline/pixel timeline
+
+use egui::{pos2, vec2, Color32, Grid, Rect, Rounding, ScrollArea, Sense, Stroke, Ui};
+use genesis_core::vdp::{DmaMode, VdpEvent, VdpEventKind, VdpEventLog, VdpMemoryTarget};
+
+// H40 mode has 420 pixels per scanline including HBlank; H32 mode has 342
+const TIMELINE_PIXELS: f32 = 420.0;
+const TIMELINE_LINE_HEIGHT: f32 = 2.0;
+const MARKER_WIDTH: f32 = 3.0;
+
+const MAX_LISTED_EVENTS: usize = 1000;
+
+const REGISTER_WRITE_COLOR: Color32 = Color32::from_rgb(80, 160, 255);
+const DMA_COLOR: Color32 = Color32::from_rgb(255, 80, 80);
+const H_INTERRUPT_COLOR: Color32 = Color32::from_rgb(80, 220, 80);
+const V_INTERRUPT_COLOR: Color32 = Color32::from_rgb(240, 200, 40);
+
+pub(super) struct EventViewerState {
+    show_register_writes: bool,
+    show_dma: bool,
+    show_h_interrupts: bool,
+    show_v_interrupts: bool,
+    hold_frame: bool,
+    held_events: Vec<VdpEvent>,
+    held_scanlines: u16,
+}
+
+impl EventViewerState {
+    pub(super) fn new() -> Self {
+        Self {
+            show_register_writes: true,
+            show_dma: true,
+            show_h_interrupts: true,
+            show_v_interrupts: true,
+            hold_frame: false,
+            held_events: Vec::new(),
+            held_scanlines: 0,
+        }
+    }
+
+    fn is_visible(&self, event: &VdpEvent) -> bool {
+        match event.kind {
+            VdpEventKind::RegisterWrite { .. } => self.show_register_writes,
+            VdpEventKind::DmaStart { .. } => self.show_dma,
+            VdpEventKind::HInterrupt { .. } => self.show_h_interrupts,
+            VdpEventKind::VInterrupt { .. } => self.show_v_interrupts,
+        }
+    }
+}
+
+pub(super) fn render(ui: &mut Ui, event_log: &VdpEventLog, state: &mut EventViewerState) {
+    ui.horizontal(|ui| {
+        ui.checkbox(&mut state.show_register_writes, "Register writes");
+        ui.checkbox(&mut state.show_dma, "DMA");
+        ui.checkbox(&mut state.show_h_interrupts, "HINT");
+        ui.checkbox(&mut state.show_v_interrupts, "VINT");
+
+        ui.separator();
+
+        // Holding on to a single frame allows it to be inspected while the game keeps running
+        if ui.checkbox(&mut state.hold_frame, "Hold frame").changed() && state.hold_frame {
+            state.held_events = event_log.last_frame().to_vec();
+            state.held_scanlines = event_log.last_frame_scanlines();
+        }
+    });
+
+    let (events, scanlines) = if state.hold_frame {
+        (state.held_events.as_slice(), state.held_scanlines)
+    } else {
+        (event_log.last_frame(), event_log.last_frame_scanlines())
+    };
+
+    if scanlines == 0 {
+        ui.label("Waiting for a frame to complete");
+        return;
+    }
+
+    let visible_events: Vec<_> = events.iter().filter(|event| state.is_visible(event)).collect();
+
+    ui.add_space(5.0);
+    ui.label(format!("{} events in last frame ({scanlines} scanlines)", visible_events.len()));
+    ui.add_space(5.0);
+
+    ScrollArea::vertical().id_source("vdp_event_timeline").max_height(350.0).show(ui, |ui| {
+        render_timeline(ui, &visible_events, scanlines);
+    });
+
+    ui.separator();
+
+    render_event_list(ui, &visible_events);
+}
+
+fn render_timeline(ui: &mut Ui, events: &[&VdpEvent], scanlines: u16) {
+    let width = ui.available_width();
+    let height = f32::from(scanlines) * TIMELINE_LINE_HEIGHT;
+    let (response, painter) = ui.allocate_painter(vec2(width, height), Sense::hover());
+    let rect = response.rect;
+
+    painter.rect_filled(rect, Rounding::ZERO, Color32::from_gray(20));
+
+    // Faint line every 8 scanlines to make it easier to read off positions
+    for scanline in (0..scanlines).step_by(8) {
+        let y = rect.top() + f32::from(scanline) * TIMELINE_LINE_HEIGHT;
+        painter.hline(rect.x_range(), y, Stroke::new(1.0, Color32::from_gray(35)));
+    }
+
+    let x_scale = (width - MARKER_WIDTH) / TIMELINE_PIXELS;
+    for event in events {
+        let min = pos2(
+            rect.left() + f32::from(event.pixel) * x_scale,
+            rect.top() + f32::from(event.scanline) * TIMELINE_LINE_HEIGHT,
+        );
+        let marker = Rect::from_min_size(min, vec2(MARKER_WIDTH, TIMELINE_LINE_HEIGHT));
+        painter.rect_filled(marker, Rounding::ZERO, event_color(event.kind));
+    }
+
+    let Some(hover_pos) = response.hover_pos() else { return };
+    let hovered_scanline = ((hover_pos.y - rect.top()) / TIMELINE_LINE_HEIGHT) as u16;
+    response.on_hover_ui_at_pointer(|ui| {
+        ui.label(format!("Scanline {hovered_scanline}"));
+        for event in events.iter().filter(|event| event.scanline == hovered_scanline) {
+            ui.monospace(format!("{:>3}: {}", event.pixel, describe_event(event.kind)));
+        }
+    });
+}
+
+fn render_event_list(ui: &mut Ui, events: &[&VdpEvent]) {
+    if events.len() > MAX_LISTED_EVENTS {
+        ui.label(format!("Showing first {MAX_LISTED_EVENTS} events"));
+    }
+
+    ScrollArea::vertical().id_source("vdp_event_list").show(ui, |ui| {
+        Grid::new("vdp_event_list_grid").num_columns(3).striped(true).show(ui, |ui| {
+            ui.label("Line");
+            ui.label("Pixel");
+            ui.label("Event");
+            ui.end_row();
+
+            for event in events.iter().take(MAX_LISTED_EVENTS) {
+                ui.monospace(event.scanline.to_string());
+                ui.monospace(event.pixel.to_string());
+                ui.colored_label(event_color(event.kind), describe_event(event.kind));
+                ui.end_row();
+            }
+        });
+    });
+}
+
+fn event_color(kind: VdpEventKind) -> Color32 {
+    match kind {
+        VdpEventKind::RegisterWrite { .. } => REGISTER_WRITE_COLOR,
+        VdpEventKind::DmaStart { .. } => DMA_COLOR,
+        VdpEventKind::HInterrupt { .. } => H_INTERRUPT_COLOR,
+        VdpEventKind::VInterrupt { .. } => V_INTERRUPT_COLOR,
+    }
+}
+
+fn describe_event(kind: VdpEventKind) -> String {
+    match kind {
+        VdpEventKind::RegisterWrite { register, value } => {
+            format!("Register #{register} = ${value:02X}")
+        }
+        VdpEventKind::DmaStart { mode, target, source_address, destination_address, length } => {
+            let target = match target {
+                VdpMemoryTarget::Vram => "VRAM",
+                VdpMemoryTarget::Cram => "CRAM",
+                VdpMemoryTarget::Vsram => "VSRAM",
+            };
+            match mode {
+                DmaMode::MemoryToVram => format!(
+                    "DMA ${source_address:06X} -> {target} ${destination_address:04X}, {length} words"
+                ),
+                DmaMode::VramFill => {
+                    format!("DMA fill {target} ${destination_address:04X}, {length} bytes")
+                }
+                DmaMode::VramCopy => format!(
+                    "DMA copy VRAM ${source_address:04X} -> ${destination_address:04X}, {length} bytes"
+                ),
+            }
+        }
+        VdpEventKind::HInterrupt { enabled } => {
+            if enabled { "HINT".into() } else { "HINT (masked)".into() }
+        }
+        VdpEventKind::VInterrupt { enabled } => {
+            if enabled { "VINT".into() } else { "VINT (masked)".into() }
+        }
+    }
+}
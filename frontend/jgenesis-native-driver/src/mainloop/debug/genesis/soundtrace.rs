@@ -0,0 +1,229 @@
+//! Sound driver trace, which records timestamped YM2612 and PSG register writes and compares them
+//! against a reference trace from a previous run or a VGM file logged from real hardware
+//!
+//! Traces are saved next to the ROM file as `<rom>.soundtrace.txt`. The reference is loaded from
+//! `<rom>.soundtrace-ref.txt` if it exists, otherwise from `<rom>.soundtrace-ref.vgm`.
+
+use crate::mainloop::save::FsSaveWriter;
+use egui::{Button, Color32, DragValue, Grid, ScrollArea, Ui};
+use genesis_core::soundlog::trace::{SoundTraceDifference, SoundTraceEntry};
+use genesis_core::soundlog::{trace, SoundChipWrite, SoundLog, SoundWriteSource};
+use jgenesis_common::frontend::SaveWriter;
+
+const TRACE_EXTENSION: &str = "soundtrace.txt";
+const REFERENCE_TEXT_EXTENSION: &str = "soundtrace-ref.txt";
+const REFERENCE_VGM_EXTENSION: &str = "soundtrace-ref.vgm";
+
+const MAX_LISTED_ENTRIES: usize = 1000;
+const MAX_LISTED_DIFFERENCES: usize = 1000;
+
+const M68K_COLOR: Color32 = Color32::from_rgb(255, 160, 80);
+const Z80_COLOR: Color32 = Color32::from_rgb(80, 160, 255);
+const DIFFERENCE_COLOR: Color32 = Color32::from_rgb(255, 80, 80);
+
+pub(super) struct SoundTraceState {
+    recording: bool,
+    entries: Vec<SoundTraceEntry>,
+    timing_tolerance_us: u32,
+    differences: Option<Vec<SoundTraceDifference>>,
+    status: Option<String>,
+}
+
+impl SoundTraceState {
+    pub(super) fn new() -> Self {
+        Self {
+            recording: false,
+            entries: Vec::new(),
+            timing_tolerance_us: 100,
+            differences: None,
+            status: None,
+        }
+    }
+}
+
+/// Collect writes from the emulator if a trace is being recorded. This is called every frame
+/// regardless of which debugger tab is open.
+pub(super) fn update(sound_log: &mut SoundLog, state: &mut SoundTraceState) {
+    sound_log.set_trace_enabled(state.recording);
+    if state.recording {
+        state.entries.extend(sound_log.drain_trace_entries());
+    }
+}
+
+pub(super) fn render(
+    ui: &mut Ui,
+    save_writer: &mut FsSaveWriter,
+    mclk_frequency: u64,
+    state: &mut SoundTraceState,
+) {
+    ui.horizontal(|ui| {
+        let label = if state.recording { "Stop trace" } else { "Start trace" };
+        if ui.button(label).clicked() {
+            state.recording = !state.recording;
+            if state.recording {
+                state.entries.clear();
+                state.differences = None;
+            }
+        }
+
+        if ui.add_enabled(!state.entries.is_empty(), Button::new("Save trace")).clicked() {
+            let text = trace::to_text(&state.entries);
+            let result = save_writer.persist_bytes(TRACE_EXTENSION, text.as_bytes());
+            state.status = Some(match result {
+                Ok(()) => format!("Saved .{TRACE_EXTENSION}"),
+                Err(err) => {
+                    log::error!("Error saving sound trace: {err}");
+                    format!("Save failed: {err}")
+                }
+            });
+        }
+
+        ui.separator();
+
+        ui.label("Timing tolerance:");
+        ui.add(DragValue::new(&mut state.timing_tolerance_us).suffix(" µs"));
+
+        if ui.add_enabled(!state.entries.is_empty(), Button::new("Compare")).clicked() {
+            compare_with_reference(save_writer, mclk_frequency, state);
+        }
+    });
+
+    if let Some(status) = &state.status {
+        ui.label(status);
+    }
+
+    ui.add_space(5.0);
+    ui.label(format!("{} writes traced", state.entries.len()));
+    ui.add_space(5.0);
+
+    if let Some(differences) = &state.differences {
+        render_differences(ui, differences, mclk_frequency);
+        ui.separator();
+    }
+
+    render_entries(ui, &state.entries, mclk_frequency);
+}
+
+fn compare_with_reference(
+    save_writer: &mut FsSaveWriter,
+    mclk_frequency: u64,
+    state: &mut SoundTraceState,
+) {
+    let reference = match save_writer.load_bytes(REFERENCE_TEXT_EXTENSION) {
+        Ok(bytes) => trace::parse_text(&String::from_utf8_lossy(&bytes)),
+        Err(_) => match save_writer.load_bytes(REFERENCE_VGM_EXTENSION) {
+            Ok(bytes) => trace::from_vgm(&bytes),
+            Err(_) => {
+                state.status = Some(format!(
+                    "No reference trace found (.{REFERENCE_TEXT_EXTENSION} or \
+                     .{REFERENCE_VGM_EXTENSION})"
+                ));
+                return;
+            }
+        },
+    };
+
+    match reference {
+        Ok(reference) => {
+            let tolerance_mclk = u64::from(state.timing_tolerance_us) * mclk_frequency / 1_000_000;
+            let differences = trace::diff(&state.entries, &reference, tolerance_mclk);
+            state.status = Some(format!(
+                "Compared against {} reference writes: {} differences",
+                reference.len(),
+                differences.len()
+            ));
+            state.differences = Some(differences);
+        }
+        Err(err) => {
+            log::error!("Error loading reference sound trace: {err}");
+            state.status = Some(format!("Error loading reference trace: {err}"));
+        }
+    }
+}
+
+fn render_differences(ui: &mut Ui, differences: &[SoundTraceDifference], mclk_frequency: u64) {
+    if differences.is_empty() {
+        ui.label("No differences from reference trace");
+        return;
+    }
+
+    if differences.len() > MAX_LISTED_DIFFERENCES {
+        ui.label(format!("Showing first {MAX_LISTED_DIFFERENCES} differences"));
+    }
+
+    let format_time = |entry: &SoundTraceEntry| format_ms(entry.mclk, mclk_frequency);
+
+    ScrollArea::vertical().id_source("sound_trace_differences").max_height(250.0).show(ui, |ui| {
+        Grid::new("sound_trace_differences_grid").num_columns(3).striped(true).show(ui, |ui| {
+            ui.label("Trace");
+            ui.label("Reference");
+            ui.label("Difference");
+            ui.end_row();
+
+            for difference in differences.iter().take(MAX_LISTED_DIFFERENCES) {
+                let (left, right, description) = match difference {
+                    SoundTraceDifference::Timing { left, right } => {
+                        (Some(left), Some(right), "Timing".to_string())
+                    }
+                    SoundTraceDifference::Changed { left, right } => (
+                        Some(left),
+                        Some(right),
+                        format!("{} vs. {}", describe_write(left), describe_write(right)),
+                    ),
+                    SoundTraceDifference::LeftOnly(left) => {
+                        (Some(left), None, format!("Extra {}", describe_write(left)))
+                    }
+                    SoundTraceDifference::RightOnly(right) => {
+                        (None, Some(right), format!("Missing {}", describe_write(right)))
+                    }
+                };
+
+                ui.monospace(left.map(format_time).unwrap_or_default());
+                ui.monospace(right.map(format_time).unwrap_or_default());
+                ui.colored_label(DIFFERENCE_COLOR, description);
+                ui.end_row();
+            }
+        });
+    });
+}
+
+fn render_entries(ui: &mut Ui, entries: &[SoundTraceEntry], mclk_frequency: u64) {
+    if entries.len() > MAX_LISTED_ENTRIES {
+        ui.label(format!("Showing last {MAX_LISTED_ENTRIES} writes"));
+    }
+
+    let start = entries.len().saturating_sub(MAX_LISTED_ENTRIES);
+
+    ScrollArea::vertical().id_source("sound_trace_entries").stick_to_bottom(true).show(ui, |ui| {
+        Grid::new("sound_trace_entries_grid").num_columns(3).striped(true).show(ui, |ui| {
+            ui.label("Time");
+            ui.label("CPU");
+            ui.label("Write");
+            ui.end_row();
+
+            for entry in &entries[start..] {
+                ui.monospace(format_ms(entry.mclk, mclk_frequency));
+                match entry.source {
+                    Some(SoundWriteSource::M68000) => ui.colored_label(M68K_COLOR, "68000"),
+                    Some(SoundWriteSource::Z80) => ui.colored_label(Z80_COLOR, "Z80"),
+                    None => ui.label("-"),
+                };
+                ui.monospace(describe_write(entry));
+                ui.end_row();
+            }
+        });
+    });
+}
+
+fn format_ms(mclk: u64, mclk_frequency: u64) -> String {
+    format!("{:.3} ms", mclk as f64 * 1000.0 / mclk_frequency as f64)
+}
+
+fn describe_write(entry: &SoundTraceEntry) -> String {
+    match entry.write {
+        SoundChipWrite::Ym2612 { port, register, value } => {
+            format!("YM2612 port {port} ${register:02X} = ${value:02X}")
+        }
+        SoundChipWrite::Psg(value) => format!("PSG ${value:02X}"),
+    }
+}
@@ -0,0 +1,105 @@
+//! Export of debugger graphics views to PNG files, and re-import of edited PNG files
+//!
+//! Images are written next to the ROM file with an extension per view (e.g. `game.vram.png`) so
+//! that an exported image can be edited in place and then imported again.
+
+use crate::mainloop::save::FsSaveWriter;
+use egui::Ui;
+use image::{ImageFormat, ImageOutputFormat, RgbaImage};
+use jgenesis_common::frontend::{Color, SaveWriter};
+use std::io::Cursor;
+
+#[derive(Debug, Clone, Copy)]
+pub struct ImageFile<'a> {
+    pub extension: &'a str,
+    pub width: u32,
+    pub height: u32,
+}
+
+pub fn render_export_button(
+    ui: &mut Ui,
+    save_writer: &mut FsSaveWriter,
+    file: ImageFile<'_>,
+    pixels: &[Color],
+    status: &mut Option<String>,
+) {
+    if !ui.button("Export PNG").clicked() {
+        return;
+    }
+
+    let result = encode_png(file, pixels).and_then(|bytes| {
+        save_writer.persist_bytes(file.extension, &bytes).map_err(|err| err.to_string())
+    });
+    *status = Some(match result {
+        Ok(()) => format!("Exported .{}", file.extension),
+        Err(err) => {
+            log::error!("Error exporting .{}: {err}", file.extension);
+            format!("Export failed: {err}")
+        }
+    });
+}
+
+/// Returns the imported pixels if the import button was clicked and the image was loaded
+/// successfully. The image must have the same dimensions as the exported image.
+pub fn render_import_button(
+    ui: &mut Ui,
+    save_writer: &mut FsSaveWriter,
+    file: ImageFile<'_>,
+    status: &mut Option<String>,
+) -> Option<Vec<Color>> {
+    if !ui.button("Import PNG").clicked() {
+        return None;
+    }
+
+    let result = save_writer
+        .load_bytes(file.extension)
+        .map_err(|err| err.to_string())
+        .and_then(|bytes| decode_png(file, &bytes));
+    match result {
+        Ok(pixels) => {
+            *status = Some(format!("Imported .{}", file.extension));
+            Some(pixels)
+        }
+        Err(err) => {
+            log::error!("Error importing .{}: {err}", file.extension);
+            *status = Some(format!("Import failed: {err}"));
+            None
+        }
+    }
+}
+
+pub fn render_status(ui: &mut Ui, status: Option<&str>) {
+    if let Some(status) = status {
+        ui.label(status);
+    }
+}
+
+fn encode_png(file: ImageFile<'_>, pixels: &[Color]) -> Result<Vec<u8>, String> {
+    let image = RgbaImage::from_raw(file.width, file.height, bytemuck::cast_slice(pixels).to_vec())
+        .ok_or_else(|| format!("buffer is too small for a {}x{} image", file.width, file.height))?;
+
+    let mut bytes = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut bytes), ImageOutputFormat::Png)
+        .map_err(|err| err.to_string())?;
+
+    Ok(bytes)
+}
+
+fn decode_png(file: ImageFile<'_>, bytes: &[u8]) -> Result<Vec<Color>, String> {
+    let image = image::load_from_memory_with_format(bytes, ImageFormat::Png)
+        .map_err(|err| err.to_string())?
+        .into_rgba8();
+
+    if image.dimensions() != (file.width, file.height) {
+        return Err(format!(
+            "expected a {}x{} image, was {}x{}",
+            file.width,
+            file.height,
+            image.width(),
+            image.height()
+        ));
+    }
+
+    Ok(image.pixels().map(|&image::Rgba([r, g, b, a])| Color::rgba(r, g, b, a)).collect())
+}
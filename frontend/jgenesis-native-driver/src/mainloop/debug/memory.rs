@@ -0,0 +1,114 @@
+//! Memory viewer shared between all emulators that implement [`Debuggable`]
+
+use egui::{Key, ScrollArea, Ui};
+use jgenesis_common::debug::{Debuggable, SymbolTable};
+
+const ADDRESS_MASK: u32 = 0xFFFFFF;
+const BYTES_PER_ROW: u32 = 16;
+const ROWS: u32 = 32;
+const PAGE_LEN: u32 = BYTES_PER_ROW * ROWS;
+const MAX_SEARCH_RESULTS: usize = 50;
+
+pub(crate) struct MemoryViewerState {
+    address: u32,
+    goto_text: String,
+}
+
+impl MemoryViewerState {
+    pub(crate) fn new() -> Self {
+        Self { address: 0, goto_text: String::new() }
+    }
+
+    fn go_to(&mut self, address: u32) {
+        self.address = address & ADDRESS_MASK & !(BYTES_PER_ROW - 1);
+    }
+}
+
+pub(crate) fn render(
+    ui: &mut Ui,
+    debuggable: &dyn Debuggable,
+    symbols: &SymbolTable,
+    state: &mut MemoryViewerState,
+) {
+    let pc = debuggable.cpu_pc();
+    let pc_label = symbols.describe(pc).map(|label| format!(" ({label})")).unwrap_or_default();
+    ui.monospace(format!("PC: ${pc:06X}{pc_label}"));
+
+    ui.add_space(5.0);
+
+    ui.horizontal(|ui| {
+        ui.label("Go to address or symbol:");
+
+        let response = ui.text_edit_singleline(&mut state.goto_text);
+        let submitted = response.lost_focus() && ui.input(|input| input.key_pressed(Key::Enter));
+        if ui.button("Go").clicked() || submitted {
+            match resolve_address(&state.goto_text, symbols) {
+                Some(address) => state.go_to(address),
+                None => log::warn!("Unknown address or symbol: '{}'", state.goto_text),
+            }
+        }
+    });
+
+    if !symbols.is_empty() && !state.goto_text.is_empty() {
+        let matches = symbols.search(&state.goto_text);
+        if !matches.is_empty() {
+            ScrollArea::vertical().id_source("symbol_search").max_height(100.0).show(ui, |ui| {
+                for &(name, address) in matches.iter().take(MAX_SEARCH_RESULTS) {
+                    if ui.button(format!("{name} (${address:06X})")).clicked() {
+                        state.go_to(address);
+                    }
+                }
+            });
+        }
+    }
+
+    ui.add_space(5.0);
+
+    ui.horizontal(|ui| {
+        if ui.button("Previous page").clicked() {
+            state.go_to(state.address.wrapping_sub(PAGE_LEN));
+        }
+
+        if ui.button("Next page").clicked() {
+            state.go_to(state.address.wrapping_add(PAGE_LEN));
+        }
+
+        if ui.button("Go to PC").clicked() {
+            state.go_to(pc);
+        }
+    });
+
+    ui.add_space(5.0);
+
+    ScrollArea::vertical().id_source("memory_dump").show(ui, |ui| {
+        for row in 0..ROWS {
+            let row_address = state.address.wrapping_add(row * BYTES_PER_ROW) & ADDRESS_MASK;
+            let bytes: Vec<_> = (0..BYTES_PER_ROW)
+                .map(|i| debuggable.peek_memory((row_address + i) & ADDRESS_MASK))
+                .collect();
+
+            let hex: Vec<_> = bytes.iter().map(|byte| format!("{byte:02X}")).collect();
+            let ascii: String = bytes
+                .iter()
+                .map(|&byte| if byte.is_ascii_graphic() { byte as char } else { '.' })
+                .collect();
+            let labels: Vec<_> = (0..BYTES_PER_ROW)
+                .filter_map(|i| symbols.label_at((row_address + i) & ADDRESS_MASK))
+                .collect();
+
+            ui.monospace(format!(
+                "{row_address:06X}  {}  {ascii}  {}",
+                hex.join(" "),
+                labels.join(", ")
+            ));
+        }
+    });
+}
+
+fn resolve_address(text: &str, symbols: &SymbolTable) -> Option<u32> {
+    let text = text.trim();
+    symbols.address_of(text).or_else(|| {
+        let hex = text.strip_prefix('$').or_else(|| text.strip_prefix("0x")).unwrap_or(text);
+        u32::from_str_radix(hex, 16).ok()
+    })
+}
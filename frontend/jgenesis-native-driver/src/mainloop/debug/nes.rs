@@ -1,15 +1,31 @@
 use crate::mainloop::debug;
-use crate::mainloop::debug::{DebugRenderContext, DebugRenderFn, DebuggerError, SelectableButton};
-use egui::{CentralPanel, ScrollArea, Vec2};
-use jgenesis_common::frontend::Color;
+use crate::mainloop::debug::images::ImageFile;
+use crate::mainloop::debug::{
+    images, DebugRenderContext, DebugRenderFn, DebuggerError, SelectableButton,
+};
+use crate::mainloop::save::FsSaveWriter;
+use egui::{CentralPanel, Grid, ScrollArea, Ui, Vec2};
+use jgenesis_common::frontend::{Color, SaveWriter};
 use nes_core::api::{NesEmulator, PatternTable};
 
+const CDL_EXTENSION: &str = "cdl";
+
+const NAMETABLES_IMAGE: ImageFile<'static> =
+    ImageFile { extension: "nametables.png", width: 2 * 256, height: 2 * 240 };
+const OAM_IMAGE: ImageFile<'static> =
+    ImageFile { extension: "oam.png", width: 8 * 8, height: 8 * 8 };
+const DOUBLE_HEIGHT_OAM_IMAGE: ImageFile<'static> =
+    ImageFile { extension: "oam.png", width: 8 * 8, height: 2 * 8 * 8 };
+const PALETTE_RAM_IMAGE: ImageFile<'static> =
+    ImageFile { extension: "palettes.png", width: 4, height: 8 };
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 enum Tab {
     #[default]
     Nametables,
     Oam,
     PaletteRam,
+    CodeDataLog,
 }
 
 #[derive(Debug)]
@@ -23,6 +39,9 @@ struct State {
     oam_texture: Option<(wgpu::Texture, egui::TextureId)>,
     oam_double_height_texture: Option<(wgpu::Texture, egui::TextureId)>,
     palette_ram_texture: Option<(wgpu::Texture, egui::TextureId)>,
+    palette_ram_buffer: [Color; 32],
+    image_status: Option<String>,
+    cdl_status: Option<String>,
 }
 
 impl State {
@@ -37,6 +56,9 @@ impl State {
             oam_texture: None,
             oam_double_height_texture: None,
             palette_ram_texture: None,
+            palette_ram_buffer: [Color::default(); 32],
+            image_status: None,
+            cdl_status: None,
         }
     }
 }
@@ -61,6 +83,7 @@ fn render(
             ui.add(SelectableButton::new("Nametables", &mut state.tab, Tab::Nametables));
             ui.add(SelectableButton::new("OAM", &mut state.tab, Tab::Oam));
             ui.add(SelectableButton::new("Palette RAM", &mut state.tab, Tab::PaletteRam));
+            ui.add(SelectableButton::new("Code/Data Log", &mut state.tab, Tab::CodeDataLog));
         });
 
         ui.add_space(15.0);
@@ -78,6 +101,17 @@ fn render(
                     ui.radio_value(&mut state.nametables_pattern_table, PatternTable::One, "$1000");
                 });
 
+                ui.horizontal(|ui| {
+                    images::render_export_button(
+                        ui,
+                        ctx.save_writer,
+                        NAMETABLES_IMAGE,
+                        &state.nametables_buffer,
+                        &mut state.image_status,
+                    );
+                });
+                images::render_status(ui, state.image_status.as_deref());
+
                 ui.add_space(10.0);
 
                 ScrollArea::vertical().show(ui, |ui| {
@@ -100,6 +134,22 @@ fn render(
                     ui.radio_value(&mut state.oam_pattern_table, PatternTable::One, "$1000");
                 });
 
+                let oam_image = if ctx.emulator.using_double_height_sprites() {
+                    DOUBLE_HEIGHT_OAM_IMAGE
+                } else {
+                    OAM_IMAGE
+                };
+                ui.horizontal(|ui| {
+                    images::render_export_button(
+                        ui,
+                        ctx.save_writer,
+                        oam_image,
+                        &state.oam_buffer[..(oam_image.width * oam_image.height) as usize],
+                        &mut state.image_status,
+                    );
+                });
+                images::render_status(ui, state.image_status.as_deref());
+
                 ui.add_space(10.0);
 
                 ScrollArea::vertical().show(ui, |ui| {
@@ -121,17 +171,116 @@ fn render(
                 });
             }
             Tab::PaletteRam => {
+                ui.horizontal(|ui| {
+                    images::render_export_button(
+                        ui,
+                        ctx.save_writer,
+                        PALETTE_RAM_IMAGE,
+                        &state.palette_ram_buffer,
+                        &mut state.image_status,
+                    );
+                });
+                images::render_status(ui, state.image_status.as_deref());
+
+                ui.add_space(10.0);
+
                 ui.vertical_centered(|ui| {
                     let egui_texture = state.palette_ram_texture.as_ref().unwrap().1;
                     ui.image((egui_texture, Vec2::new(screen_width * 0.325, screen_width * 0.65)));
                 });
             }
+            Tab::CodeDataLog => {
+                render_code_data_log(ui, ctx.emulator, ctx.save_writer, &mut state.cdl_status);
+            }
         }
     });
 
     Ok(())
 }
 
+fn render_code_data_log(
+    ui: &mut Ui,
+    emulator: &mut NesEmulator,
+    save_writer: &mut FsSaveWriter,
+    status: &mut Option<String>,
+) {
+    let mut enabled = emulator.code_data_log().is_enabled();
+    if ui.checkbox(&mut enabled, "Log code and data accesses").changed() {
+        emulator.set_code_data_logging(enabled);
+
+        // Continue from an existing log file if there is one
+        if enabled {
+            if let Ok(bytes) = save_writer.load_bytes(CDL_EXTENSION) {
+                *status = Some(if emulator.code_data_log_mut().merge_fceux_bytes(&bytes) {
+                    "Loaded existing .cdl file".into()
+                } else {
+                    "Ignored existing .cdl file because its size does not match the ROM".into()
+                });
+            }
+        }
+    }
+
+    ui.add_space(10.0);
+
+    let coverage = emulator.code_data_log().coverage();
+
+    Grid::new("debug_nes_cdl_grid").num_columns(2).show(ui, |ui| {
+        let prg_len = coverage.prg_rom_len;
+        for (label, bytes) in [
+            ("PRG ROM code", coverage.code_bytes),
+            ("PRG ROM data", coverage.data_bytes),
+            ("DMC samples", coverage.pcm_sample_bytes),
+            ("PRG ROM unused", coverage.unused_prg_bytes),
+        ] {
+            ui.label(label);
+            ui.label(format_coverage(bytes, prg_len));
+            ui.end_row();
+        }
+
+        let chr_len = coverage.chr_rom_len;
+        if chr_len != 0 {
+            for (label, bytes) in [
+                ("CHR ROM drawn", coverage.drawn_bytes),
+                ("CHR ROM read by CPU", coverage.read_bytes),
+            ] {
+                ui.label(label);
+                ui.label(format_coverage(bytes, chr_len));
+                ui.end_row();
+            }
+        }
+    });
+
+    ui.add_space(10.0);
+
+    ui.horizontal(|ui| {
+        if ui.button("Export .cdl").clicked() {
+            let bytes = emulator.code_data_log().to_fceux_bytes();
+            *status = Some(match save_writer.persist_bytes(CDL_EXTENSION, &bytes) {
+                Ok(()) => "Exported .cdl file".into(),
+                Err(err) => {
+                    log::error!("Error exporting code/data log: {err}");
+                    format!("Export failed: {err}")
+                }
+            });
+        }
+
+        if ui.button("Reset").clicked() {
+            emulator.code_data_log_mut().clear();
+            *status = None;
+        }
+    });
+
+    if let Some(status) = status {
+        ui.add_space(5.0);
+        ui.label(status.as_str());
+    }
+}
+
+fn format_coverage(bytes: usize, len: usize) -> String {
+    let tenths_of_percent = (1000 * bytes).checked_div(len).unwrap_or(0);
+    format!("{bytes} / {len} ({}.{}%)", tenths_of_percent / 10, tenths_of_percent % 10)
+}
+
 fn update_nametables_texture(
     ctx: &mut DebugRenderContext<'_, NesEmulator>,
     state: &mut State,
@@ -204,8 +353,7 @@ fn update_palette_ram_texture(
     ctx: &mut DebugRenderContext<'_, NesEmulator>,
     state: &mut State,
 ) -> Result<(), DebuggerError> {
-    let mut colors = [Color::default(); 32];
-    ctx.emulator.copy_palette_ram(&mut colors);
+    ctx.emulator.copy_palette_ram(&mut state.palette_ram_buffer);
 
     if state.palette_ram_texture.is_none() {
         let (wgpu_texture, egui_texture) =
@@ -216,5 +364,10 @@ fn update_palette_ram_texture(
     let (wgpu_texture, egui_texture) = state.palette_ram_texture.as_ref().unwrap();
     let egui_texture = *egui_texture;
 
-    debug::write_textures(wgpu_texture, egui_texture, bytemuck::cast_slice(&colors), ctx)
+    debug::write_textures(
+        wgpu_texture,
+        egui_texture,
+        bytemuck::cast_slice(&state.palette_ram_buffer),
+        ctx,
+    )
 }
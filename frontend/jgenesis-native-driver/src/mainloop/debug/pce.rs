@@ -0,0 +1,87 @@
+use crate::mainloop::debug;
+use crate::mainloop::debug::images::ImageFile;
+use crate::mainloop::debug::{images, DebugRenderContext, DebugRenderFn, DebuggerError};
+use egui::{CentralPanel, Vec2};
+use jgenesis_common::frontend::Color;
+use pce_core::PceEmulator;
+
+// Color table is displayed as 32 rows of 16 colors, background palettes on top and sprite palettes
+// on bottom
+const COLOR_TABLE_IMAGE: ImageFile<'static> =
+    ImageFile { extension: "palettes.png", width: 16, height: 32 };
+
+struct State {
+    color_table_texture: Option<(wgpu::Texture, egui::TextureId)>,
+    color_table_buffer: Box<[Color; 512]>,
+    image_status: Option<String>,
+}
+
+impl State {
+    fn new() -> Self {
+        Self {
+            color_table_texture: None,
+            color_table_buffer: vec![Color::default(); 512].into_boxed_slice().try_into().unwrap(),
+            image_status: None,
+        }
+    }
+}
+
+pub fn render_fn() -> Box<DebugRenderFn<PceEmulator>> {
+    let mut state = State::new();
+    Box::new(move |ctx| render(ctx, &mut state))
+}
+
+fn render(
+    mut ctx: DebugRenderContext<'_, PceEmulator>,
+    state: &mut State,
+) -> Result<(), DebuggerError> {
+    update_color_table_texture(&mut ctx, state)?;
+
+    let screen_width = debug::screen_width(ctx.egui_ctx);
+    let save_writer = &mut *ctx.save_writer;
+
+    CentralPanel::default().show(ctx.egui_ctx, |ui| {
+        ui.heading("Color Table");
+
+        ui.add_space(15.0);
+
+        images::render_export_button(
+            ui,
+            save_writer,
+            COLOR_TABLE_IMAGE,
+            state.color_table_buffer.as_ref(),
+            &mut state.image_status,
+        );
+        images::render_status(ui, state.image_status.as_deref());
+
+        ui.add_space(15.0);
+
+        let color_table_texture = state.color_table_texture.as_ref().unwrap().1;
+        let image_width = 0.5 * screen_width;
+        ui.image((color_table_texture, Vec2::new(image_width, 2.0 * image_width)));
+    });
+
+    Ok(())
+}
+
+fn update_color_table_texture(
+    ctx: &mut DebugRenderContext<'_, PceEmulator>,
+    state: &mut State,
+) -> Result<(), DebuggerError> {
+    ctx.emulator.copy_color_table(state.color_table_buffer.as_mut());
+
+    if state.color_table_texture.is_none() {
+        let (wgpu_texture, egui_texture) =
+            debug::create_texture("debug_pce_color_table", 16, 32, ctx.device, ctx.rpass);
+        state.color_table_texture = Some((wgpu_texture, egui_texture));
+    }
+
+    let (wgpu_texture, egui_texture) = state.color_table_texture.as_ref().unwrap();
+
+    debug::write_textures(
+        wgpu_texture,
+        *egui_texture,
+        bytemuck::cast_slice(state.color_table_buffer.as_ref()),
+        ctx,
+    )
+}
@@ -0,0 +1,219 @@
+//! RAM search and freeze list shared between all emulators that implement [`Debuggable`]
+
+use egui::{ComboBox, DragValue, Grid, ScrollArea, Ui};
+use jgenesis_common::debug::{
+    Debuggable, FreezeList, MemorySearch, SearchComparison, SearchOperand, SearchValueSize,
+    SymbolTable,
+};
+
+const MAX_DISPLAYED_RESULTS: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OperandKind {
+    PreviousValue,
+    Value,
+}
+
+pub(crate) struct RamSearchState {
+    search: Option<MemorySearch>,
+    size: SearchValueSize,
+    comparison: SearchComparison,
+    operand_kind: OperandKind,
+    value_text: String,
+    error: Option<String>,
+}
+
+impl RamSearchState {
+    pub(crate) fn new() -> Self {
+        Self {
+            search: None,
+            size: SearchValueSize::default(),
+            comparison: SearchComparison::default(),
+            operand_kind: OperandKind::PreviousValue,
+            value_text: String::new(),
+            error: None,
+        }
+    }
+
+    fn operand(&self) -> Result<SearchOperand, String> {
+        match self.operand_kind {
+            OperandKind::PreviousValue => Ok(SearchOperand::PreviousValue),
+            OperandKind::Value => parse_value(&self.value_text)
+                .map(SearchOperand::Value)
+                .ok_or_else(|| format!("Invalid value: '{}'", self.value_text)),
+        }
+    }
+}
+
+pub(crate) fn render(
+    ui: &mut Ui,
+    debuggable: &dyn Debuggable,
+    symbols: &SymbolTable,
+    freeze_list: &mut FreezeList,
+    state: &mut RamSearchState,
+) {
+    ui.horizontal(|ui| {
+        ui.label("Value size:");
+        ui.radio_value(&mut state.size, SearchValueSize::Byte, "8-bit");
+        ui.radio_value(&mut state.size, SearchValueSize::Word, "16-bit");
+
+        if ui.button("New search").clicked() {
+            state.search =
+                Some(MemorySearch::new(debuggable.work_ram_range(), state.size, debuggable));
+            state.error = None;
+        }
+    });
+
+    ui.add_space(5.0);
+
+    ui.horizontal(|ui| {
+        ui.label("Current value");
+
+        ComboBox::from_id_source("ram_search_comparison")
+            .selected_text(state.comparison.to_string())
+            .show_ui(ui, |ui| {
+                for comparison in SearchComparison::ALL {
+                    ui.selectable_value(&mut state.comparison, comparison, comparison.to_string());
+                }
+            });
+
+        ui.radio_value(&mut state.operand_kind, OperandKind::PreviousValue, "previous value");
+        ui.radio_value(&mut state.operand_kind, OperandKind::Value, "value:");
+        ui.text_edit_singleline(&mut state.value_text);
+    });
+
+    ui.add_space(5.0);
+
+    ui.horizontal(|ui| {
+        ui.add_enabled_ui(state.search.is_some(), |ui| {
+            if ui.button("Filter").clicked() {
+                match state.operand() {
+                    Ok(operand) => {
+                        if let Some(search) = &mut state.search {
+                            search.filter(state.comparison, operand, debuggable);
+                        }
+                        state.error = None;
+                    }
+                    Err(err) => state.error = Some(err),
+                }
+            }
+
+            if ui.button("Take snapshot").clicked() {
+                if let Some(search) = &mut state.search {
+                    search.take_snapshot(debuggable);
+                }
+            }
+        });
+
+        if let Some(error) = &state.error {
+            ui.label(error.as_str());
+        }
+    });
+
+    ui.add_space(10.0);
+
+    if let Some(search) = &state.search {
+        render_results(ui, search, debuggable, symbols, freeze_list);
+    } else {
+        ui.label("Start a new search to snapshot work RAM");
+    }
+
+    ui.separator();
+
+    render_freeze_list(ui, symbols, freeze_list);
+}
+
+fn render_results(
+    ui: &mut Ui,
+    search: &MemorySearch,
+    debuggable: &dyn Debuggable,
+    symbols: &SymbolTable,
+    freeze_list: &mut FreezeList,
+) {
+    let candidates = search.candidates();
+    if candidates.len() > MAX_DISPLAYED_RESULTS {
+        ui.label(format!(
+            "{} candidates (showing first {MAX_DISPLAYED_RESULTS})",
+            candidates.len()
+        ));
+    } else {
+        ui.label(format!("{} candidates", candidates.len()));
+    }
+
+    let size = search.size();
+    ScrollArea::vertical().id_source("ram_search_results").max_height(250.0).show(ui, |ui| {
+        Grid::new("ram_search_results_grid").num_columns(4).striped(true).show(ui, |ui| {
+            ui.label("Address");
+            ui.label("Previous");
+            ui.label("Current");
+            ui.end_row();
+
+            for (idx, &address) in candidates.iter().enumerate().take(MAX_DISPLAYED_RESULTS) {
+                let current = size.read(debuggable, address);
+
+                ui.monospace(format_address(address, symbols));
+                ui.monospace(format_value(search.previous_value(idx), size));
+                ui.monospace(format_value(current, size));
+                if ui.button("Freeze").clicked() {
+                    freeze_list.add(address, current, size);
+                }
+                ui.end_row();
+            }
+        });
+    });
+}
+
+fn render_freeze_list(ui: &mut Ui, symbols: &SymbolTable, freeze_list: &mut FreezeList) {
+    ui.label("Frozen values");
+
+    if freeze_list.is_empty() {
+        ui.label("None");
+        return;
+    }
+
+    let mut remove_idx = None;
+    Grid::new("ram_search_freeze_grid").num_columns(4).show(ui, |ui| {
+        for (idx, entry) in freeze_list.entries_mut().iter_mut().enumerate() {
+            ui.checkbox(&mut entry.enabled, "");
+            ui.monospace(format_address(entry.address, symbols));
+
+            let max_value: u32 = match entry.size {
+                SearchValueSize::Byte => u8::MAX.into(),
+                SearchValueSize::Word => u16::MAX.into(),
+            };
+            ui.add(DragValue::new(&mut entry.value).clamp_range(0..=max_value));
+
+            if ui.button("Remove").clicked() {
+                remove_idx = Some(idx);
+            }
+            ui.end_row();
+        }
+    });
+
+    if let Some(idx) = remove_idx {
+        freeze_list.remove(idx);
+    }
+}
+
+fn format_address(address: u32, symbols: &SymbolTable) -> String {
+    match symbols.describe(address) {
+        Some(label) => format!("${address:06X} ({label})"),
+        None => format!("${address:06X}"),
+    }
+}
+
+fn format_value(value: u32, size: SearchValueSize) -> String {
+    match size {
+        SearchValueSize::Byte => format!("${value:02X} ({value})"),
+        SearchValueSize::Word => format!("${value:04X} ({value})"),
+    }
+}
+
+// Values are decimal unless prefixed with '$' or '0x'
+fn parse_value(text: &str) -> Option<u32> {
+    let text = text.trim();
+    match text.strip_prefix('$').or_else(|| text.strip_prefix("0x")) {
+        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+        None => text.parse().ok(),
+    }
+}
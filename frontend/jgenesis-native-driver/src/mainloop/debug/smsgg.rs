@@ -1,9 +1,19 @@
 use crate::mainloop::debug;
-use crate::mainloop::debug::{DebugRenderContext, DebugRenderFn, DebuggerError, SelectableButton};
+use crate::mainloop::debug::images::ImageFile;
+use crate::mainloop::debug::{
+    images, DebugRenderContext, DebugRenderFn, DebuggerError, SelectableButton,
+};
 use egui::{CentralPanel, ScrollArea, Vec2};
 use jgenesis_common::frontend::Color;
 use smsgg_core::SmsGgEmulator;
 
+// VRAM is displayed as 32x16 tiles
+const VRAM_ROW_LEN: usize = 32;
+
+const VRAM_IMAGE: ImageFile<'static> =
+    ImageFile { extension: "vram.png", width: 32 * 8, height: 16 * 8 };
+const CRAM_IMAGE: ImageFile<'static> = ImageFile { extension: "cram.png", width: 16, height: 2 };
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 enum Tab {
     Cram,
@@ -18,6 +28,7 @@ struct State {
     vram_texture: Option<(wgpu::Texture, egui::TextureId)>,
     cram_buffer: Box<[Color; 32]>,
     vram_buffer: Box<[Color; 512 * 64]>,
+    image_status: Option<String>,
 }
 
 impl State {
@@ -29,6 +40,7 @@ impl State {
             vram_texture: None,
             cram_buffer: vec![Color::default(); 32].into_boxed_slice().try_into().unwrap(),
             vram_buffer: vec![Color::default(); 512 * 64].into_boxed_slice().try_into().unwrap(),
+            image_status: None,
         }
     }
 }
@@ -46,6 +58,8 @@ fn render(
     update_vram_texture(&mut ctx, state)?;
 
     let screen_width = debug::screen_width(ctx.egui_ctx);
+    let save_writer = &mut *ctx.save_writer;
+    let mut imported_vram = None;
 
     CentralPanel::default().show(ctx.egui_ctx, |ui| {
         ui.horizontal(|ui| {
@@ -57,6 +71,19 @@ fn render(
 
         match state.tab {
             Tab::Cram => {
+                ui.horizontal(|ui| {
+                    images::render_export_button(
+                        ui,
+                        save_writer,
+                        CRAM_IMAGE,
+                        state.cram_buffer.as_ref(),
+                        &mut state.image_status,
+                    );
+                });
+                images::render_status(ui, state.image_status.as_deref());
+
+                ui.add_space(15.0);
+
                 let cram_texture = state.cram_texture.as_ref().unwrap().1;
                 ui.image((cram_texture, Vec2::new(screen_width, screen_width * 0.125)));
             }
@@ -68,6 +95,25 @@ fn render(
                     ui.radio_value(&mut state.vram_palette, 1, "1");
                 });
 
+                ui.horizontal(|ui| {
+                    images::render_export_button(
+                        ui,
+                        save_writer,
+                        VRAM_IMAGE,
+                        state.vram_buffer.as_ref(),
+                        &mut state.image_status,
+                    );
+
+                    // Imported pixels are mapped back to color IDs using the selected palette
+                    imported_vram = images::render_import_button(
+                        ui,
+                        save_writer,
+                        VRAM_IMAGE,
+                        &mut state.image_status,
+                    );
+                });
+                images::render_status(ui, state.image_status.as_deref());
+
                 ui.add_space(15.0);
 
                 ScrollArea::vertical().show(ui, |ui| {
@@ -78,6 +124,10 @@ fn render(
         }
     });
 
+    if let Some(pixels) = imported_vram {
+        ctx.emulator.import_vram(&pixels, state.vram_palette, VRAM_ROW_LEN);
+    }
+
     Ok(())
 }
 
@@ -107,7 +157,7 @@ fn update_vram_texture(
     ctx: &mut DebugRenderContext<'_, SmsGgEmulator>,
     state: &mut State,
 ) -> Result<(), DebuggerError> {
-    ctx.emulator.copy_vram(state.vram_buffer.as_mut(), state.vram_palette, 32);
+    ctx.emulator.copy_vram(state.vram_buffer.as_mut(), state.vram_palette, VRAM_ROW_LEN);
 
     if state.vram_texture.is_none() {
         let (wgpu_texture, egui_texture) =
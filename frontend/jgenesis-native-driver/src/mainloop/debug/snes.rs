@@ -1,14 +1,22 @@
 use crate::mainloop::debug;
-use crate::mainloop::debug::{DebugRenderContext, DebugRenderFn, DebuggerError, SelectableButton};
+use crate::mainloop::debug::images::ImageFile;
+use crate::mainloop::debug::memory::MemoryViewerState;
+use crate::mainloop::debug::search::RamSearchState;
+use crate::mainloop::debug::{
+    images, memory, search, DebugRenderContext, DebugRenderFn, DebuggerError, SelectableButton,
+};
 use egui::{CentralPanel, ScrollArea, Vec2};
 use jgenesis_common::frontend::Color;
 use snes_core::api::SnesEmulator;
+use snes_core::spc::SpcPlayer;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 enum Tab {
     Cgram,
     #[default]
     Vram,
+    Memory,
+    RamSearch,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -20,9 +28,24 @@ enum VramMode {
     Mode7,
 }
 
+impl VramMode {
+    fn image_file(self) -> ImageFile<'static> {
+        match self {
+            Self::TwoBpp => ImageFile { extension: "vram2bpp.png", width: 64 * 8, height: 64 * 8 },
+            Self::FourBpp => ImageFile { extension: "vram4bpp.png", width: 64 * 8, height: 32 * 8 },
+            Self::EightBpp => {
+                ImageFile { extension: "vram8bpp.png", width: 32 * 8, height: 32 * 8 }
+            }
+            Self::Mode7 => ImageFile { extension: "vrammode7.png", width: 16 * 8, height: 16 * 8 },
+        }
+    }
+}
+
 const CGRAM_BUFFER_LEN: usize = 256;
 const VRAM_BUFFER_LEN: usize = 256 * 1024;
 
+const CGRAM_IMAGE: ImageFile<'static> = ImageFile { extension: "cgram.png", width: 16, height: 16 };
+
 struct State {
     tab: Tab,
     vram_mode: VramMode,
@@ -34,6 +57,9 @@ struct State {
     vram_8bpp_texture: Option<(wgpu::Texture, egui::TextureId)>,
     vram_mode7_texture: Option<(wgpu::Texture, egui::TextureId)>,
     vram_buffer: Box<[Color; VRAM_BUFFER_LEN]>,
+    image_status: Option<String>,
+    memory_viewer: MemoryViewerState,
+    ram_search: RamSearchState,
 }
 
 impl State {
@@ -55,6 +81,9 @@ impl State {
                 .into_boxed_slice()
                 .try_into()
                 .unwrap(),
+            image_status: None,
+            memory_viewer: MemoryViewerState::new(),
+            ram_search: RamSearchState::new(),
         }
     }
 }
@@ -64,6 +93,17 @@ pub fn render_fn() -> Box<DebugRenderFn<SnesEmulator>> {
     Box::new(move |ctx| render(ctx, &mut state))
 }
 
+// The SPC player only runs the APU, so there is nothing to show beyond what is playing
+pub fn spc_render_fn() -> Box<DebugRenderFn<SpcPlayer>> {
+    Box::new(|ctx| {
+        CentralPanel::default().show(ctx.egui_ctx, |ui| {
+            ui.label(format!("Playing SPC: {}", ctx.emulator.title()));
+        });
+
+        Ok(())
+    })
+}
+
 fn render(
     mut ctx: DebugRenderContext<'_, SnesEmulator>,
     state: &mut State,
@@ -72,17 +112,33 @@ fn render(
     update_vram_texture(&mut ctx, state)?;
 
     let screen_width = debug::screen_width(ctx.egui_ctx);
+    let save_writer = &mut *ctx.save_writer;
 
     CentralPanel::default().show(ctx.egui_ctx, |ui| {
         ui.horizontal(|ui| {
             ui.add(SelectableButton::new("VRAM", &mut state.tab, Tab::Vram));
             ui.add(SelectableButton::new("CGRAM", &mut state.tab, Tab::Cgram));
+            ui.add(SelectableButton::new("Memory", &mut state.tab, Tab::Memory));
+            ui.add(SelectableButton::new("RAM Search", &mut state.tab, Tab::RamSearch));
         });
 
         ui.add_space(15.0);
 
         match state.tab {
             Tab::Cgram => {
+                ui.horizontal(|ui| {
+                    images::render_export_button(
+                        ui,
+                        save_writer,
+                        CGRAM_IMAGE,
+                        state.cgram_buffer.as_ref(),
+                        &mut state.image_status,
+                    );
+                });
+                images::render_status(ui, state.image_status.as_deref());
+
+                ui.add_space(15.0);
+
                 ui.vertical_centered(|ui| {
                     let egui_texture = state.cgram_texture.as_ref().unwrap().1;
                     ui.image((egui_texture, Vec2::new(screen_width * 0.65, screen_width * 0.65)));
@@ -129,6 +185,21 @@ fn render(
                     },
                 );
 
+                ui.add_space(5.0);
+
+                // The buffer contents are for the mode that was selected at the start of the frame
+                let vram_image = original_vram_mode.image_file();
+                ui.horizontal(|ui| {
+                    images::render_export_button(
+                        ui,
+                        save_writer,
+                        vram_image,
+                        &state.vram_buffer[..(vram_image.width * vram_image.height) as usize],
+                        &mut state.image_status,
+                    );
+                });
+                images::render_status(ui, state.image_status.as_deref());
+
                 ui.add_space(10.0);
 
                 ScrollArea::vertical().show(ui, |ui| match original_vram_mode {
@@ -150,6 +221,18 @@ fn render(
                     }
                 });
             }
+            Tab::Memory => {
+                memory::render(ui, &*ctx.emulator, ctx.symbols, &mut state.memory_viewer);
+            }
+            Tab::RamSearch => {
+                search::render(
+                    ui,
+                    &*ctx.emulator,
+                    ctx.symbols,
+                    ctx.freeze_list,
+                    &mut state.ram_search,
+                );
+            }
         }
     });
 
@@ -0,0 +1,184 @@
+//! Discord Rich Presence, which shows the running system, game, and elapsed time on the user's
+//! Discord profile
+//!
+//! This talks to the local Discord client over its IPC socket (a Unix domain socket, or a named pipe
+//! on Windows); nothing is sent over the network by the emulator itself. If Discord is not running,
+//! presence is skipped. Discord clears the activity when the connection is closed, which happens
+//! when the emulator is dropped.
+
+use crate::config::DiscordConfig;
+use serde_json::{json, Value};
+use std::io::{ErrorKind, Read, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{io, process};
+
+const OPCODE_HANDSHAKE: u32 = 0;
+const OPCODE_FRAME: u32 = 1;
+const OPCODE_CLOSE: u32 = 2;
+
+// Discord uses the first free index out of 0-9 in case multiple clients are running
+const MAX_IPC_INDEX: u32 = 10;
+
+const MAX_FRAME_LEN: u32 = 64 * 1024;
+
+trait IpcStream: Read + Write {}
+
+impl<T: Read + Write> IpcStream for T {}
+
+#[cfg(unix)]
+fn connect_ipc() -> Option<Box<dyn IpcStream>> {
+    use std::env;
+    use std::os::unix::net::UnixStream;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    // Avoid hanging emulator startup if Discord does not respond
+    const READ_TIMEOUT: Duration = Duration::from_secs(2);
+
+    let base_dirs: Vec<_> = ["XDG_RUNTIME_DIR", "TMPDIR", "TMP", "TEMP"]
+        .into_iter()
+        .filter_map(|var| env::var_os(var).map(PathBuf::from))
+        .chain([PathBuf::from("/tmp")])
+        .collect();
+
+    // Flatpak and Snap installs of Discord put the socket in a subdirectory
+    let subdirs = ["", "app/com.discordapp.Discord", "snap.discord"];
+
+    for base_dir in &base_dirs {
+        for subdir in subdirs {
+            for i in 0..MAX_IPC_INDEX {
+                let path = base_dir.join(subdir).join(format!("discord-ipc-{i}"));
+                let Ok(stream) = UnixStream::connect(&path) else { continue };
+
+                if let Err(err) = stream.set_read_timeout(Some(READ_TIMEOUT)) {
+                    log::error!("Error setting read timeout on Discord IPC socket: {err}");
+                    return None;
+                }
+
+                return Some(Box::new(stream));
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(windows)]
+fn connect_ipc() -> Option<Box<dyn IpcStream>> {
+    use std::fs::OpenOptions;
+
+    (0..MAX_IPC_INDEX).find_map(|i| {
+        let pipe = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(format!(r"\\?\pipe\discord-ipc-{i}"))
+            .ok()?;
+        Some(Box::new(pipe) as Box<dyn IpcStream>)
+    })
+}
+
+#[cfg(not(any(unix, windows)))]
+fn connect_ipc() -> Option<Box<dyn IpcStream>> {
+    None
+}
+
+fn write_frame(stream: &mut dyn IpcStream, opcode: u32, payload: &Value) -> io::Result<()> {
+    let payload = payload.to_string();
+
+    let mut frame = Vec::with_capacity(8 + payload.len());
+    frame.extend_from_slice(&opcode.to_le_bytes());
+    frame.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    frame.extend_from_slice(payload.as_bytes());
+
+    stream.write_all(&frame)?;
+    stream.flush()
+}
+
+fn read_frame(stream: &mut dyn IpcStream) -> io::Result<(u32, Value)> {
+    let mut header = [0; 8];
+    stream.read_exact(&mut header)?;
+
+    let opcode = u32::from_le_bytes(header[..4].try_into().unwrap());
+    let len = u32::from_le_bytes(header[4..].try_into().unwrap());
+    if len > MAX_FRAME_LEN {
+        return Err(io::Error::new(ErrorKind::InvalidData, format!("frame too large: {len}")));
+    }
+
+    let mut payload = vec![0; len as usize];
+    stream.read_exact(&mut payload)?;
+    let payload = serde_json::from_slice(&payload)
+        .map_err(|err| io::Error::new(ErrorKind::InvalidData, err))?;
+
+    Ok((opcode, payload))
+}
+
+fn set_activity(
+    stream: &mut dyn IpcStream,
+    config: &DiscordConfig,
+    system: &str,
+    game_title: &str,
+) -> io::Result<()> {
+    write_frame(stream, OPCODE_HANDSHAKE, &json!({ "v": 1, "client_id": config.application_id }))?;
+
+    let (opcode, response) = read_frame(stream)?;
+    if opcode == OPCODE_CLOSE {
+        return Err(io::Error::new(
+            ErrorKind::ConnectionRefused,
+            format!("Discord rejected handshake: {}", response["message"]),
+        ));
+    }
+
+    let start_time = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_secs());
+    let activity = if config.show_game_title {
+        json!({ "details": game_title, "state": system, "timestamps": { "start": start_time } })
+    } else {
+        json!({ "details": system, "timestamps": { "start": start_time } })
+    };
+
+    write_frame(
+        stream,
+        OPCODE_FRAME,
+        &json!({
+            "cmd": "SET_ACTIVITY",
+            "args": { "pid": process::id(), "activity": activity },
+            "nonce": "1",
+        }),
+    )?;
+
+    let (_, response) = read_frame(stream)?;
+    if response["evt"] == "ERROR" {
+        return Err(io::Error::new(
+            ErrorKind::InvalidInput,
+            format!("Discord rejected activity: {}", response["data"]["message"]),
+        ));
+    }
+
+    Ok(())
+}
+
+pub struct DiscordPresence {
+    // Never read, only held open; Discord clears the activity when the connection is closed
+    _stream: Box<dyn IpcStream>,
+}
+
+impl DiscordPresence {
+    /// Connect to the local Discord client and show the given system and game as the current
+    /// activity. Returns `None` if Discord is not running or rejects the activity.
+    pub fn start(config: &DiscordConfig, system: &str, game_title: &str) -> Option<Self> {
+        let Some(mut stream) = connect_ipc() else {
+            log::info!("Discord does not appear to be running; not setting Rich Presence");
+            return None;
+        };
+
+        match set_activity(stream.as_mut(), config, system, game_title) {
+            Ok(()) => {
+                log::info!("Set Discord Rich Presence activity");
+                Some(Self { _stream: stream })
+            }
+            Err(err) => {
+                log::error!("Error setting Discord Rich Presence activity: {err}");
+                None
+            }
+        }
+    }
+}
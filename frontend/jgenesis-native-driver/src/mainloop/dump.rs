@@ -0,0 +1,257 @@
+//! Frame-perfect audio/video dumping
+//!
+//! While a dump is active, every frame the core renders is written to a Y4M video file and every
+//! audio sample it produces is written to a WAV file, so the two stay in sync no matter how fast
+//! or slow the host is. The video frame rate is derived from the audio sample count when the dump
+//! finishes, which means video frames are buffered in a temporary file until then.
+
+use jgenesis_common::frontend::{AudioOutput, Color, FrameSize, PixelAspectRatio, Renderer};
+use jgenesis_renderer::renderer::{RendererError, WgpuRenderer};
+use sdl2::video::Window;
+use std::fs::File;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+use thiserror::Error;
+
+const AUDIO_FREQUENCY: u32 = 48000;
+const WAV_HEADER_LEN: u32 = 44;
+
+#[derive(Debug, Error)]
+pub enum AvDumpError {
+    #[error("I/O error writing AV dump: {0}")]
+    Io(#[from] io::Error),
+    #[error("{0}")]
+    Render(#[from] RendererError),
+}
+
+struct VideoDumpWriter {
+    path: PathBuf,
+    temp_path: PathBuf,
+    temp_file: BufWriter<File>,
+    // Fixed by the first frame; frames of any other size are scaled to match
+    frame_size: Option<FrameSize>,
+    frame_count: u64,
+    plane_buffer: Vec<u8>,
+}
+
+impl VideoDumpWriter {
+    fn create(path: PathBuf) -> io::Result<Self> {
+        let temp_path = path.with_extension("y4m.tmp");
+        let temp_file = BufWriter::new(File::create(&temp_path)?);
+
+        Ok(Self {
+            path,
+            temp_path,
+            temp_file,
+            frame_size: None,
+            frame_count: 0,
+            plane_buffer: Vec::new(),
+        })
+    }
+
+    fn write_frame(&mut self, frame_buffer: &[Color], frame_size: FrameSize) -> io::Result<()> {
+        let output_size = *self.frame_size.get_or_insert(frame_size);
+        let width = output_size.width as usize;
+        let height = output_size.height as usize;
+        let plane_len = width * height;
+
+        // Planar YCbCr 4:4:4, BT.601 limited range
+        self.plane_buffer.resize(3 * plane_len, 0);
+        for y in 0..height {
+            let src_y = y * frame_size.height as usize / height;
+            for x in 0..width {
+                let src_x = x * frame_size.width as usize / width;
+                let color = frame_buffer[src_y * frame_size.width as usize + src_x];
+                let [y_value, cb, cr] = rgb_to_ycbcr(color);
+
+                let i = y * width + x;
+                self.plane_buffer[i] = y_value;
+                self.plane_buffer[plane_len + i] = cb;
+                self.plane_buffer[2 * plane_len + i] = cr;
+            }
+        }
+
+        self.temp_file.write_all(b"FRAME\n")?;
+        self.temp_file.write_all(&self.plane_buffer)?;
+        self.frame_count += 1;
+
+        Ok(())
+    }
+
+    fn finish(&mut self, sample_count: u64) -> io::Result<()> {
+        self.temp_file.flush()?;
+
+        let Some(frame_size) = self.frame_size else {
+            fs::remove_file(&self.temp_path)?;
+            return Ok(());
+        };
+
+        // Each frame lasts exactly as long as the audio that was generated alongside it
+        let (rate_numerator, rate_denominator) = if sample_count != 0 {
+            let numerator = u64::from(AUDIO_FREQUENCY) * self.frame_count;
+            let divisor = gcd(numerator, sample_count);
+            (numerator / divisor, sample_count / divisor)
+        } else {
+            (60, 1)
+        };
+
+        let mut file = BufWriter::new(File::create(&self.path)?);
+        writeln!(
+            file,
+            "YUV4MPEG2 W{} H{} F{rate_numerator}:{rate_denominator} Ip C444",
+            frame_size.width, frame_size.height
+        )?;
+        io::copy(&mut File::open(&self.temp_path)?, &mut file)?;
+        file.flush()?;
+
+        fs::remove_file(&self.temp_path)?;
+
+        Ok(())
+    }
+}
+
+fn rgb_to_ycbcr(color: Color) -> [u8; 3] {
+    let r = i32::from(color.r);
+    let g = i32::from(color.g);
+    let b = i32::from(color.b);
+
+    let y = ((66 * r + 129 * g + 25 * b + 128) >> 8) + 16;
+    let cb = ((-38 * r - 74 * g + 112 * b + 128) >> 8) + 128;
+    let cr = ((112 * r - 94 * g - 18 * b + 128) >> 8) + 128;
+
+    [y as u8, cb as u8, cr as u8]
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+pub struct AudioDumpWriter {
+    file: BufWriter<File>,
+    sample_count: u64,
+}
+
+impl AudioDumpWriter {
+    fn create(path: &Path) -> io::Result<Self> {
+        let mut file = BufWriter::new(File::create(path)?);
+
+        // Chunk sizes are filled in when the dump finishes
+        file.write_all(b"RIFF")?;
+        file.write_all(&0_u32.to_le_bytes())?;
+        file.write_all(b"WAVEfmt ")?;
+        file.write_all(&16_u32.to_le_bytes())?;
+        // PCM, 2 channels, 16-bit
+        file.write_all(&1_u16.to_le_bytes())?;
+        file.write_all(&2_u16.to_le_bytes())?;
+        file.write_all(&AUDIO_FREQUENCY.to_le_bytes())?;
+        file.write_all(&(AUDIO_FREQUENCY * 4).to_le_bytes())?;
+        file.write_all(&4_u16.to_le_bytes())?;
+        file.write_all(&16_u16.to_le_bytes())?;
+        file.write_all(b"data")?;
+        file.write_all(&0_u32.to_le_bytes())?;
+
+        Ok(Self { file, sample_count: 0 })
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        let data_len = u32::try_from(4 * self.sample_count).unwrap_or_else(|_| {
+            log::error!("WAV file is larger than 4GB; header chunk sizes will be incorrect");
+            u32::MAX - WAV_HEADER_LEN
+        });
+
+        self.file.seek(SeekFrom::Start(4))?;
+        self.file.write_all(&(data_len + WAV_HEADER_LEN - 8).to_le_bytes())?;
+        self.file.seek(SeekFrom::Start(u64::from(WAV_HEADER_LEN) - 4))?;
+        self.file.write_all(&data_len.to_le_bytes())?;
+        self.file.flush()
+    }
+}
+
+impl AudioOutput for AudioDumpWriter {
+    type Err = AvDumpError;
+
+    #[inline]
+    fn push_sample(&mut self, sample_l: f64, sample_r: f64) -> Result<(), Self::Err> {
+        for sample in [sample_l, sample_r] {
+            let sample = (sample.clamp(-1.0, 1.0) * f64::from(i16::MAX)).round() as i16;
+            self.file.write_all(&sample.to_le_bytes())?;
+        }
+        self.sample_count += 1;
+
+        Ok(())
+    }
+}
+
+/// Renderer that writes every frame to the video dump before displaying it in the window.
+pub struct DumpingRenderer<'a> {
+    renderer: &'a mut WgpuRenderer<Window>,
+    video: &'a mut VideoDumpWriter,
+}
+
+impl Renderer for DumpingRenderer<'_> {
+    type Err = AvDumpError;
+
+    fn render_frame(
+        &mut self,
+        frame_buffer: &[Color],
+        frame_size: FrameSize,
+        pixel_aspect_ratio: Option<PixelAspectRatio>,
+    ) -> Result<(), Self::Err> {
+        self.video.write_frame(frame_buffer, frame_size)?;
+        self.renderer.render_frame(frame_buffer, frame_size, pixel_aspect_ratio)?;
+
+        Ok(())
+    }
+}
+
+/// Writes `<path>.y4m` and `<path>.wav`, replacing any extension that `path` already has. The
+/// dump is finished when this is dropped.
+pub struct AvDumpWriter {
+    video: VideoDumpWriter,
+    audio: AudioDumpWriter,
+}
+
+impl AvDumpWriter {
+    pub fn create(path: &Path) -> io::Result<Self> {
+        let video = VideoDumpWriter::create(path.with_extension("y4m"))?;
+        let audio = AudioDumpWriter::create(&path.with_extension("wav"))?;
+
+        log::info!("Dumping video to '{}'", video.path.display());
+
+        Ok(Self { video, audio })
+    }
+
+    /// Audio is written only to the dump and is not played, so audio sync never throttles
+    /// emulation while dumping.
+    pub fn outputs<'a>(
+        &'a mut self,
+        renderer: &'a mut WgpuRenderer<Window>,
+    ) -> (DumpingRenderer<'a>, &'a mut AudioDumpWriter) {
+        (DumpingRenderer { renderer, video: &mut self.video }, &mut self.audio)
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.audio.finish()?;
+        self.video.finish(self.audio.sample_count)?;
+
+        log::info!(
+            "Finished AV dump: {} frames, {} audio samples",
+            self.video.frame_count,
+            self.audio.sample_count
+        );
+
+        Ok(())
+    }
+}
+
+impl Drop for AvDumpWriter {
+    fn drop(&mut self) {
+        if let Err(err) = self.finish() {
+            log::error!("Error finishing AV dump: {err}");
+        }
+    }
+}
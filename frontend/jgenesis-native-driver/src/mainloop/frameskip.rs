@@ -0,0 +1,58 @@
+//! Auto frame skip, with an indicator in the window title while frames are being skipped
+
+use crate::config::CommonConfig;
+use jgenesis_renderer::frameskip::FrameSkipper;
+use sdl2::video::Window;
+
+const TITLE_INDICATOR: &str = " [frame skip]";
+
+pub struct FrameSkip {
+    skipper: FrameSkipper,
+    indicator_shown: bool,
+}
+
+impl FrameSkip {
+    pub fn from_config<KC, JC>(config: &CommonConfig<KC, JC>) -> Option<Self> {
+        // Every frame should be rendered while dumping
+        (config.auto_frame_skip && config.av_dump_path.is_none()).then(|| Self {
+            skipper: FrameSkipper::new(config.max_frame_skip),
+            indicator_shown: false,
+        })
+    }
+
+    pub fn skip_current_frame(&self) -> bool {
+        self.skipper.skip_current_frame()
+    }
+
+    pub fn after_frame(&mut self, falling_behind: bool, window: &mut Window) {
+        self.skipper.next_frame(falling_behind);
+
+        let active = self.skipper.is_active();
+        if active != self.indicator_shown {
+            if active {
+                log::debug!("Auto frame skip active");
+            }
+
+            set_title_indicator(window, active);
+            self.indicator_shown = active;
+        }
+    }
+
+    pub fn hide_indicator(&mut self, window: &mut Window) {
+        if self.indicator_shown {
+            set_title_indicator(window, false);
+            self.indicator_shown = false;
+        }
+    }
+}
+
+fn set_title_indicator(window: &mut Window, shown: bool) {
+    let title = window.title();
+    let base_title = title.strip_suffix(TITLE_INDICATOR).unwrap_or(title);
+    let new_title =
+        if shown { format!("{base_title}{TITLE_INDICATOR}") } else { base_title.into() };
+
+    if let Err(err) = window.set_title(&new_title) {
+        log::error!("Error setting window title: {err}");
+    }
+}
@@ -0,0 +1,1006 @@
+//! GDB remote serial protocol server, which allows debugging the emulated main CPU using gdb or
+//! any other tool that speaks the protocol (e.g. VS Code debug adapters)
+//!
+//! Supports reading registers, reading and writing memory, breakpoints, watchpoints, and single
+//! stepping. Only one client can be connected at a time, and the server only listens on localhost.
+//!
+//! Conditional breakpoints and watchpoints are evaluated inside the emulator and can be set using
+//! `monitor` commands; run `monitor help` from gdb for usage.
+
+use jgenesis_common::debug::{
+    BreakReason, BreakpointSet, CpuArchitecture, Debuggable, Expression, WatchKind, Watchpoint,
+};
+use std::fmt::Write as _;
+use std::io;
+use std::io::{ErrorKind, Read, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+
+const SIGINT: u8 = 2;
+const SIGTRAP: u8 = 5;
+
+const INTERRUPT_BYTE: u8 = 0x03;
+
+// Maximum packet length advertised to the client in qSupported; replies must fit within it
+const PACKET_SIZE: usize = 0x1000;
+
+// Packet size minus the framing: $<data>#<2 hex digit checksum>
+const MAX_REPLY_LEN: usize = PACKET_SIZE - 4;
+
+// Each byte is encoded as 2 hex digits
+const MAX_MEMORY_READ_LEN: u32 = (MAX_REPLY_LEN / 2) as u32;
+
+const MONITOR_HELP: &str = "\
+Commands:
+  break <address> [if <condition>]
+  watch <read|write|change> <address>[-<end address>] [if <condition>]
+  info
+  delete
+Conditions are expressions over registers (d0, a7, pc, x, dbr, ...), byte memory reads
+([$FF0000]), and the operators || && == != < <= > >= | & + - !
+";
+
+const M68K_TARGET_XML: &str = r#"<?xml version="1.0"?>
+<!DOCTYPE target SYSTEM "gdb-target.dtd">
+<target version="1.0">
+  <architecture>m68k</architecture>
+  <feature name="org.gnu.gdb.m68k.core">
+    <reg name="d0" bitsize="32"/>
+    <reg name="d1" bitsize="32"/>
+    <reg name="d2" bitsize="32"/>
+    <reg name="d3" bitsize="32"/>
+    <reg name="d4" bitsize="32"/>
+    <reg name="d5" bitsize="32"/>
+    <reg name="d6" bitsize="32"/>
+    <reg name="d7" bitsize="32"/>
+    <reg name="a0" bitsize="32" type="data_ptr"/>
+    <reg name="a1" bitsize="32" type="data_ptr"/>
+    <reg name="a2" bitsize="32" type="data_ptr"/>
+    <reg name="a3" bitsize="32" type="data_ptr"/>
+    <reg name="a4" bitsize="32" type="data_ptr"/>
+    <reg name="a5" bitsize="32" type="data_ptr"/>
+    <reg name="fp" bitsize="32" type="data_ptr"/>
+    <reg name="sp" bitsize="32" type="data_ptr"/>
+    <reg name="ps" bitsize="32"/>
+    <reg name="pc" bitsize="32" type="code_ptr"/>
+  </feature>
+</target>
+"#;
+
+// GDB does not support the 65816, so this describes the registers for clients that support custom
+// target descriptions
+const WDC65816_TARGET_XML: &str = r#"<?xml version="1.0"?>
+<!DOCTYPE target SYSTEM "gdb-target.dtd">
+<target version="1.0">
+  <feature name="org.jgenesis.wdc65816.core">
+    <reg name="a" bitsize="16"/>
+    <reg name="x" bitsize="16"/>
+    <reg name="y" bitsize="16"/>
+    <reg name="s" bitsize="16" type="data_ptr"/>
+    <reg name="d" bitsize="16"/>
+    <reg name="dbr" bitsize="8"/>
+    <reg name="p" bitsize="8"/>
+    <reg name="pc" bitsize="32" type="code_ptr"/>
+  </feature>
+</target>
+"#;
+
+trait CpuArchitectureExt {
+    fn target_xml(self) -> &'static str;
+
+    fn register_sizes(self) -> &'static [usize];
+
+    fn encode_register(self, value: u32, size: usize, out: &mut String);
+
+    fn executes_full_instruction_per_tick(self) -> bool;
+}
+
+impl CpuArchitectureExt for CpuArchitecture {
+    fn target_xml(self) -> &'static str {
+        match self {
+            Self::M68000 => M68K_TARGET_XML,
+            Self::Wdc65816 => WDC65816_TARGET_XML,
+        }
+    }
+
+    fn register_sizes(self) -> &'static [usize] {
+        match self {
+            Self::M68000 => &[4; 18],
+            Self::Wdc65816 => &[2, 2, 2, 2, 2, 1, 1, 4],
+        }
+    }
+
+    fn encode_register(self, value: u32, size: usize, out: &mut String) {
+        let bytes = match self {
+            // Big-endian
+            Self::M68000 => value.to_be_bytes()[4 - size..].to_vec(),
+            // Little-endian
+            Self::Wdc65816 => value.to_le_bytes()[..size].to_vec(),
+        };
+        for byte in bytes {
+            write!(out, "{byte:02x}").unwrap();
+        }
+    }
+
+    // The 68000 core executes exactly one instruction per emulator tick, while the 65816 core
+    // executes one CPU cycle per tick (or nothing at all while DMA is in progress)
+    fn executes_full_instruction_per_tick(self) -> bool {
+        self == Self::M68000
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RunState {
+    Running,
+    Halted,
+    Stepping { left_instruction_boundary: bool },
+}
+
+#[derive(Debug)]
+enum Packet {
+    Interrupt,
+    Command(String),
+}
+
+struct GdbConnection {
+    stream: TcpStream,
+    buffer: Vec<u8>,
+    no_ack_mode: bool,
+}
+
+impl GdbConnection {
+    fn new(stream: TcpStream) -> io::Result<Self> {
+        stream.set_nonblocking(true)?;
+        stream.set_nodelay(true)?;
+
+        Ok(Self { stream, buffer: Vec::new(), no_ack_mode: false })
+    }
+
+    // Returns Ok(false) if the client disconnected
+    fn receive(&mut self) -> io::Result<bool> {
+        let mut read_buffer = [0; 4096];
+        loop {
+            match self.stream.read(&mut read_buffer) {
+                Ok(0) => return Ok(false),
+                Ok(bytes_read) => self.buffer.extend_from_slice(&read_buffer[..bytes_read]),
+                Err(err) if err.kind() == ErrorKind::WouldBlock => return Ok(true),
+                Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    fn next_packet(&mut self) -> io::Result<Option<Packet>> {
+        loop {
+            let Some(&first) = self.buffer.first() else { return Ok(None) };
+            match first {
+                INTERRUPT_BYTE => {
+                    self.buffer.remove(0);
+                    return Ok(Some(Packet::Interrupt));
+                }
+                b'$' => {
+                    // Packet format is $<data>#<2 hex digit checksum>
+                    let Some(end) = self.buffer.iter().position(|&b| b == b'#') else {
+                        return Ok(None);
+                    };
+                    if self.buffer.len() < end + 3 {
+                        return Ok(None);
+                    }
+
+                    let data = self.buffer[1..end].to_vec();
+                    let checksum = std::str::from_utf8(&self.buffer[end + 1..end + 3])
+                        .ok()
+                        .and_then(|checksum| u8::from_str_radix(checksum, 16).ok());
+                    self.buffer.drain(..end + 3);
+
+                    if checksum != Some(compute_checksum(&data)) {
+                        log::warn!("Received GDB packet with invalid checksum, requesting resend");
+                        self.write_raw(b"-")?;
+                        continue;
+                    }
+
+                    if !self.no_ack_mode {
+                        self.write_raw(b"+")?;
+                    }
+
+                    return Ok(Some(Packet::Command(String::from_utf8_lossy(&data).into())));
+                }
+                _ => {
+                    // Acks (+/-) and any garbage between packets
+                    self.buffer.remove(0);
+                }
+            }
+        }
+    }
+
+    fn send_packet(&mut self, data: &str) -> io::Result<()> {
+        let checksum = compute_checksum(data.as_bytes());
+        self.write_raw(format!("${data}#{checksum:02x}").as_bytes())
+    }
+
+    fn write_raw(&mut self, bytes: &[u8]) -> io::Result<()> {
+        // Temporarily switch to blocking mode so that large replies are not partially written
+        self.stream.set_nonblocking(false)?;
+        let result = self.stream.write_all(bytes).and_then(|()| self.stream.flush());
+        self.stream.set_nonblocking(true)?;
+        result
+    }
+}
+
+fn compute_checksum(data: &[u8]) -> u8 {
+    data.iter().fold(0_u8, |checksum, &byte| checksum.wrapping_add(byte))
+}
+
+pub type DebuggableFn<Emulator> = fn(&mut Emulator) -> &mut dyn Debuggable;
+
+pub struct GdbStub<Emulator> {
+    listener: TcpListener,
+    connection: Option<GdbConnection>,
+    as_debuggable: DebuggableFn<Emulator>,
+    breakpoints: BreakpointSet,
+    run_state: RunState,
+    ignore_breakpoint_at: Option<u32>,
+    pending_break: Option<BreakReason>,
+    access_logging_enabled: bool,
+}
+
+impl<Emulator> GdbStub<Emulator> {
+    /// Start listening for GDB connections on the given localhost port.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if it is unable to bind to the port.
+    pub fn new(port: u16, as_debuggable: DebuggableFn<Emulator>) -> io::Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+        listener.set_nonblocking(true)?;
+
+        log::info!("GDB server listening on localhost:{port}");
+
+        Ok(Self {
+            listener,
+            connection: None,
+            as_debuggable,
+            breakpoints: BreakpointSet::new(),
+            run_state: RunState::Running,
+            ignore_breakpoint_at: None,
+            pending_break: None,
+            access_logging_enabled: false,
+        })
+    }
+
+    /// Whether the emulator should not be ticked because the debugger has halted execution.
+    #[must_use]
+    pub fn is_halted(&self) -> bool {
+        self.run_state == RunState::Halted
+    }
+
+    /// Accept new connections and process any commands received from the connected client.
+    pub fn poll(&mut self, emulator: &mut Emulator) {
+        if self.connection.is_none() && !self.accept_connection() {
+            return;
+        }
+
+        let packets = match self.receive_packets() {
+            Ok(packets) => packets,
+            Err(err) => {
+                log::error!("GDB connection error, disconnecting: {err}");
+                self.disconnect();
+                return;
+            }
+        };
+
+        for packet in packets {
+            let reply = match packet {
+                Packet::Interrupt => {
+                    self.halt(SIGINT);
+                    continue;
+                }
+                Packet::Command(command) => self.handle_command(&command, emulator),
+            };
+
+            if let Some(reply) = reply {
+                self.send(&reply);
+            }
+        }
+    }
+
+    /// Check breakpoints, watchpoints, and single step completion. Should be called after every
+    /// emulator tick.
+    #[inline]
+    pub fn after_tick(&mut self, emulator: &mut Emulator) {
+        if self.run_state == RunState::Running && self.breakpoints.is_empty() {
+            if self.access_logging_enabled {
+                (self.as_debuggable)(emulator).set_memory_access_logging(false);
+                self.access_logging_enabled = false;
+            }
+            return;
+        }
+
+        let debuggable = (self.as_debuggable)(emulator);
+
+        if self.breakpoints.has_watchpoints() {
+            if !self.access_logging_enabled {
+                debuggable.set_memory_access_logging(true);
+                self.access_logging_enabled = true;
+            }
+
+            if self.pending_break.is_none() {
+                let debuggable: &dyn Debuggable = debuggable;
+                self.pending_break =
+                    self.breakpoints.check_accesses(debuggable.memory_accesses(), debuggable);
+            }
+        }
+
+        let full_instruction_per_tick =
+            debuggable.cpu_architecture().executes_full_instruction_per_tick();
+        let at_instruction_boundary = debuggable.at_instruction_boundary();
+
+        match self.run_state {
+            RunState::Halted => {}
+            RunState::Stepping { left_instruction_boundary } => {
+                if at_instruction_boundary
+                    && (left_instruction_boundary || full_instruction_per_tick)
+                {
+                    match self.pending_break.take() {
+                        Some(reason) => self.halt_on(&reason),
+                        None => self.halt(SIGTRAP),
+                    }
+                } else if !at_instruction_boundary {
+                    self.run_state = RunState::Stepping { left_instruction_boundary: true };
+                }
+            }
+            RunState::Running => {
+                if full_instruction_per_tick || !at_instruction_boundary {
+                    self.ignore_breakpoint_at = None;
+                }
+
+                // Watchpoint hits are deferred until the end of the instruction that triggered them
+                if !at_instruction_boundary {
+                    return;
+                }
+
+                if let Some(reason) = self.pending_break.take() {
+                    self.halt_on(&reason);
+                    return;
+                }
+
+                if self.ignore_breakpoint_at != Some(debuggable.cpu_pc()) {
+                    if let Some(reason) = self.breakpoints.check_execution(debuggable) {
+                        self.halt_on(&reason);
+                    }
+                }
+            }
+        }
+    }
+
+    fn accept_connection(&mut self) -> bool {
+        match self.listener.accept() {
+            Ok((stream, addr)) => match GdbConnection::new(stream) {
+                Ok(connection) => {
+                    log::info!("GDB client connected from {addr}");
+                    self.connection = Some(connection);
+                    self.run_state = RunState::Halted;
+                    true
+                }
+                Err(err) => {
+                    log::error!("Error initializing GDB connection: {err}");
+                    false
+                }
+            },
+            Err(err) if err.kind() == ErrorKind::WouldBlock => false,
+            Err(err) => {
+                log::error!("Error accepting GDB connection: {err}");
+                false
+            }
+        }
+    }
+
+    fn receive_packets(&mut self) -> io::Result<Vec<Packet>> {
+        let Some(connection) = &mut self.connection else { return Ok(vec![]) };
+
+        if !connection.receive()? {
+            log::info!("GDB client disconnected");
+            self.disconnect();
+            return Ok(vec![]);
+        }
+
+        let mut packets = Vec::new();
+        while let Some(packet) = connection.next_packet()? {
+            packets.push(packet);
+        }
+        Ok(packets)
+    }
+
+    fn send(&mut self, data: &str) {
+        let Some(connection) = &mut self.connection else { return };
+
+        if let Err(err) = connection.send_packet(data) {
+            log::error!("Error sending GDB packet, disconnecting: {err}");
+            self.disconnect();
+        }
+    }
+
+    fn halt(&mut self, signal: u8) {
+        self.run_state = RunState::Halted;
+        self.send(&format!("S{signal:02x}"));
+    }
+
+    fn halt_on(&mut self, reason: &BreakReason) {
+        log::debug!("Hit {reason}");
+
+        match *reason {
+            BreakReason::Breakpoint { .. } => self.halt(SIGTRAP),
+            BreakReason::Watchpoint { kind, address, .. } => {
+                let watch_type = match kind {
+                    WatchKind::Read => "rwatch",
+                    WatchKind::Write | WatchKind::Change => "watch",
+                };
+
+                self.run_state = RunState::Halted;
+                self.send(&format!("T{SIGTRAP:02x}{watch_type}:{address:x};"));
+            }
+        }
+    }
+
+    fn resume(&mut self, debuggable: &dyn Debuggable) {
+        // Don't immediately re-trigger the breakpoint that execution is currently stopped on
+        self.ignore_breakpoint_at = Some(debuggable.cpu_pc());
+        self.pending_break = None;
+        self.run_state = RunState::Running;
+    }
+
+    fn disconnect(&mut self) {
+        self.connection = None;
+        self.breakpoints.clear();
+        self.pending_break = None;
+        self.run_state = RunState::Running;
+    }
+
+    // Returns None if no reply should be sent yet, e.g. for continue and step
+    fn handle_command(&mut self, command: &str, emulator: &mut Emulator) -> Option<String> {
+        log::trace!("Received GDB command: {command}");
+
+        let debuggable = (self.as_debuggable)(emulator);
+        let arch = debuggable.cpu_architecture();
+
+        let Some(first) = command.chars().next() else { return Some(String::new()) };
+        let (_, args) = command.split_at(first.len_utf8());
+        let reply = match first {
+            '?' => format!("S{SIGTRAP:02x}"),
+            'g' => {
+                let registers = debuggable.cpu_registers();
+                let mut reply = String::new();
+                for (value, &size) in registers.into_iter().zip(arch.register_sizes()) {
+                    arch.encode_register(value, size, &mut reply);
+                }
+                reply
+            }
+            'p' => {
+                let registers = debuggable.cpu_registers();
+                match parse_hex(args).and_then(|idx| {
+                    let idx = idx as usize;
+                    Some((*registers.get(idx)?, *arch.register_sizes().get(idx)?))
+                }) {
+                    Some((value, size)) => {
+                        let mut reply = String::new();
+                        arch.encode_register(value, size, &mut reply);
+                        reply
+                    }
+                    None => "E01".into(),
+                }
+            }
+            'm' => match parse_address_length(args) {
+                Some((address, length)) => {
+                    // Replies may contain fewer bytes than requested, and GDB requests the rest
+                    // in another packet
+                    let length = length.min(MAX_MEMORY_READ_LEN);
+                    let mut reply = String::with_capacity(2 * length as usize);
+                    for i in 0..length {
+                        let byte = debuggable.peek_memory(address.wrapping_add(i));
+                        write!(reply, "{byte:02x}").unwrap();
+                    }
+                    reply
+                }
+                None => "E01".into(),
+            },
+            'M' => match write_memory(debuggable, args) {
+                Some(()) => "OK".into(),
+                None => "E01".into(),
+            },
+            'c' => {
+                self.resume(debuggable);
+                return None;
+            }
+            's' => {
+                self.ignore_breakpoint_at = None;
+                self.pending_break = None;
+                self.run_state = RunState::Stepping { left_instruction_boundary: false };
+                return None;
+            }
+            'Z' | 'z' => self.handle_breakpoint_command(first == 'Z', args, debuggable),
+            'D' => {
+                log::info!("GDB client detached");
+                self.send("OK");
+                self.disconnect();
+                return None;
+            }
+            'k' => {
+                self.disconnect();
+                return None;
+            }
+            'H' => "OK".into(),
+            'q' => match command.strip_prefix("qRcmd,") {
+                Some(hex) => self.handle_monitor_command(hex, debuggable),
+                None => handle_query(command, arch),
+            },
+            'Q' if command == "QStartNoAckMode" => {
+                self.send("OK");
+                if let Some(connection) = &mut self.connection {
+                    connection.no_ack_mode = true;
+                }
+                return None;
+            }
+            // Empty reply indicates an unsupported command
+            _ => String::new(),
+        };
+
+        Some(reply)
+    }
+
+    fn handle_breakpoint_command(
+        &mut self,
+        insert: bool,
+        args: &str,
+        debuggable: &dyn Debuggable,
+    ) -> String {
+        // Format is <type>,<address>,<kind>[;<conditions>]; conditions are evaluated by the client
+        let args = args.split(';').next().unwrap_or("");
+        let mut split = args.split(',');
+        let (Some(breakpoint_type), Some(address), Some(length)) =
+            (split.next(), split.next(), split.next())
+        else {
+            return "E01".into();
+        };
+        let (Some(address), Some(length)) = (parse_hex(address), parse_hex(length)) else {
+            return "E01".into();
+        };
+
+        let watch_kinds: &[WatchKind] = match breakpoint_type {
+            // Software and hardware execution breakpoints
+            "0" | "1" => {
+                if insert {
+                    self.breakpoints.add_breakpoint(address, None);
+                } else {
+                    self.breakpoints.remove_breakpoint(address);
+                }
+                return "OK".into();
+            }
+            "2" => &[WatchKind::Write],
+            "3" => &[WatchKind::Read],
+            "4" => &[WatchKind::Read, WatchKind::Write],
+            _ => return String::new(),
+        };
+
+        let range = address..=address.wrapping_add(length.max(1) - 1);
+        for &kind in watch_kinds {
+            if insert {
+                self.breakpoints.add_watchpoint(
+                    Watchpoint { range: range.clone(), kind, condition: None },
+                    debuggable,
+                );
+            } else {
+                self.breakpoints.remove_watchpoint(&range, kind);
+            }
+        }
+
+        "OK".into()
+    }
+
+    fn handle_monitor_command(&mut self, hex: &str, debuggable: &dyn Debuggable) -> String {
+        let Some(command) = decode_hex_string(hex) else { return "E01".into() };
+
+        let output = match run_monitor_command(&command, &mut self.breakpoints, debuggable) {
+            Ok(output) => output,
+            Err(err) => format!("Error: {err}\n"),
+        };
+
+        // Console output is sent as separate O packets before the final reply
+        if !output.is_empty() {
+            self.send(&format!("O{}", encode_hex_string(&output)));
+        }
+
+        "OK".into()
+    }
+}
+
+fn run_monitor_command(
+    command: &str,
+    breakpoints: &mut BreakpointSet,
+    debuggable: &dyn Debuggable,
+) -> Result<String, String> {
+    let arch = debuggable.cpu_architecture();
+
+    // Split off an optional "if <condition>" suffix
+    let (command, condition) = match command.split_once(" if ") {
+        Some((command, condition)) => {
+            let condition = Expression::parse(condition, arch).map_err(|err| err.to_string())?;
+            (command, Some(condition))
+        }
+        None => (command, None),
+    };
+
+    let tokens: Vec<_> = command.split_whitespace().collect();
+    match tokens.as_slice() {
+        ["break", address] => {
+            let address = parse_monitor_address(address)?;
+            breakpoints.add_breakpoint(address, condition);
+            Ok(format!("Breakpoint set at ${address:06X}\n"))
+        }
+        ["watch", kind, range] => {
+            let kind = match *kind {
+                "read" => WatchKind::Read,
+                "write" => WatchKind::Write,
+                "change" => WatchKind::Change,
+                _ => return Err(format!("Invalid watchpoint type '{kind}'")),
+            };
+
+            let (start, end) = if let Some((start, end)) = range.split_once('-') {
+                (parse_monitor_address(start)?, parse_monitor_address(end)?)
+            } else {
+                let address = parse_monitor_address(range)?;
+                (address, address)
+            };
+            if end < start {
+                return Err("End address must not be less than start address".into());
+            }
+
+            breakpoints
+                .add_watchpoint(Watchpoint { range: start..=end, kind, condition }, debuggable);
+            Ok(format!("{kind} watchpoint set at ${start:06X}-${end:06X}\n"))
+        }
+        ["info"] => {
+            let mut output = String::new();
+            for breakpoint in breakpoints.breakpoints() {
+                write!(output, "break ${:06X}", breakpoint.address).unwrap();
+                if let Some(condition) = &breakpoint.condition {
+                    write!(output, " if {condition}").unwrap();
+                }
+                output.push('\n');
+            }
+            for watchpoint in breakpoints.watchpoints() {
+                write!(
+                    output,
+                    "watch {} ${:06X}-${:06X}",
+                    watchpoint.kind,
+                    watchpoint.range.start(),
+                    watchpoint.range.end()
+                )
+                .unwrap();
+                if let Some(condition) = &watchpoint.condition {
+                    write!(output, " if {condition}").unwrap();
+                }
+                output.push('\n');
+            }
+            if output.is_empty() {
+                output.push_str("No breakpoints or watchpoints\n");
+            }
+            Ok(output)
+        }
+        ["delete"] => {
+            breakpoints.clear();
+            Ok("Deleted all breakpoints and watchpoints\n".into())
+        }
+        ["help"] | [] => Ok(MONITOR_HELP.into()),
+        _ => Err(format!("Unrecognized command '{command}'; try 'monitor help'")),
+    }
+}
+
+fn parse_monitor_address(s: &str) -> Result<u32, String> {
+    let hex = s.strip_prefix('$').or_else(|| s.strip_prefix("0x")).unwrap_or(s);
+    parse_hex(hex).ok_or_else(|| format!("Invalid address '{s}'"))
+}
+
+fn decode_hex_string(hex: &str) -> Option<String> {
+    let bytes = (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect::<Option<Vec<_>>>()?;
+    String::from_utf8(bytes).ok()
+}
+
+fn encode_hex_string(s: &str) -> String {
+    s.bytes().fold(String::new(), |mut hex, byte| {
+        write!(hex, "{byte:02x}").unwrap();
+        hex
+    })
+}
+
+fn handle_query(command: &str, arch: CpuArchitecture) -> String {
+    if command.starts_with("qSupported") {
+        return format!("PacketSize={PACKET_SIZE:x};qXfer:features:read+;QStartNoAckMode+");
+    }
+
+    if let Some(args) = command.strip_prefix("qXfer:features:read:target.xml:") {
+        let Some((offset, length)) = parse_address_length(args) else { return "E01".into() };
+        let xml = arch.target_xml();
+        let start = (offset as usize).min(xml.len());
+        // Leave room for the l/m prefix
+        let end = (start + (length as usize).min(MAX_REPLY_LEN - 1)).min(xml.len());
+        let prefix = if end == xml.len() { 'l' } else { 'm' };
+        return format!("{prefix}{}", &xml[start..end]);
+    }
+
+    match command {
+        "qAttached" => "1".into(),
+        "qC" => "QC1".into(),
+        "qfThreadInfo" => "m1".into(),
+        "qsThreadInfo" => "l".into(),
+        _ => String::new(),
+    }
+}
+
+fn write_memory(debuggable: &mut dyn Debuggable, args: &str) -> Option<()> {
+    // Format is <address>,<length>:<hex bytes>
+    let (address_length, data) = args.split_once(':')?;
+    let (address, length) = parse_address_length(address_length)?;
+    if data.len() != 2 * length as usize {
+        return None;
+    }
+
+    for i in 0..length {
+        let idx = 2 * i as usize;
+        let byte = u8::from_str_radix(data.get(idx..idx + 2)?, 16).ok()?;
+        debuggable.poke_memory(address.wrapping_add(i), byte);
+    }
+
+    Some(())
+}
+
+fn parse_hex(s: &str) -> Option<u32> {
+    u32::from_str_radix(s, 16).ok()
+}
+
+fn parse_address_length(s: &str) -> Option<(u32, u32)> {
+    let (address, length) = s.split_once(',')?;
+    Some((parse_hex(address)?, parse_hex(length)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jgenesis_common::debug::MemoryAccess;
+    use std::ops::RangeInclusive;
+
+    struct TestMemory(Vec<u8>);
+
+    impl Debuggable for TestMemory {
+        fn cpu_architecture(&self) -> CpuArchitecture {
+            CpuArchitecture::M68000
+        }
+
+        fn cpu_registers(&self) -> Vec<u32> {
+            (0..18).collect()
+        }
+
+        fn cpu_pc(&self) -> u32 {
+            0
+        }
+
+        fn at_instruction_boundary(&self) -> bool {
+            true
+        }
+
+        fn peek_memory(&self, address: u32) -> u8 {
+            self.0.get(address as usize).copied().unwrap_or(0)
+        }
+
+        fn poke_memory(&mut self, address: u32, value: u8) {
+            if let Some(byte) = self.0.get_mut(address as usize) {
+                *byte = value;
+            }
+        }
+
+        fn set_memory_access_logging(&mut self, _enabled: bool) {}
+
+        fn memory_accesses(&self) -> &[MemoryAccess] {
+            &[]
+        }
+
+        fn work_ram_range(&self) -> RangeInclusive<u32> {
+            0..=(self.0.len() as u32 - 1)
+        }
+    }
+
+    fn test_memory() -> TestMemory {
+        TestMemory((0..=255).collect())
+    }
+
+    fn new_stub() -> GdbStub<TestMemory> {
+        GdbStub::new(0, |memory| memory).unwrap()
+    }
+
+    // Returns the server side of a localhost connection along with the client's stream
+    fn connection_pair() -> (GdbConnection, TcpStream) {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+        (GdbConnection::new(server).unwrap(), client)
+    }
+
+    fn read_client(client: &mut TcpStream, len: usize) -> String {
+        let mut buffer = vec![0; len];
+        client.read_exact(&mut buffer).unwrap();
+        String::from_utf8(buffer).unwrap()
+    }
+
+    fn assert_nothing_sent(client: &mut TcpStream) {
+        client.set_nonblocking(true).unwrap();
+        let err = client.read(&mut [0]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::WouldBlock);
+        client.set_nonblocking(false).unwrap();
+    }
+
+    fn command(packet: Option<Packet>) -> String {
+        match packet {
+            Some(Packet::Command(command)) => command,
+            other => panic!("expected command packet, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn packet_framing_and_ack() {
+        let (mut connection, mut client) = connection_pair();
+
+        // Acks and garbage between packets are skipped
+        connection.buffer.extend_from_slice(b"+x$qC#b4");
+        assert_eq!(command(connection.next_packet().unwrap()), "qC");
+        assert_eq!(read_client(&mut client, 1), "+");
+        assert!(connection.buffer.is_empty());
+
+        // Packets split across reads are only returned once the checksum has arrived
+        connection.buffer.extend_from_slice(b"$?#3");
+        assert!(connection.next_packet().unwrap().is_none());
+        connection.buffer.push(b'f');
+        assert_eq!(command(connection.next_packet().unwrap()), "?");
+        assert_eq!(read_client(&mut client, 1), "+");
+
+        connection.buffer.extend_from_slice(&[INTERRUPT_BYTE]);
+        assert!(matches!(connection.next_packet().unwrap(), Some(Packet::Interrupt)));
+        assert!(connection.next_packet().unwrap().is_none());
+
+        connection.send_packet("OK").unwrap();
+        assert_eq!(read_client(&mut client, 6), "$OK#9a");
+    }
+
+    #[test]
+    fn invalid_checksum_requests_resend() {
+        let (mut connection, mut client) = connection_pair();
+
+        connection.buffer.extend_from_slice(b"$qC#00$qC#zz$?#3f");
+        assert_eq!(command(connection.next_packet().unwrap()), "?");
+        assert_eq!(read_client(&mut client, 3), "--+");
+    }
+
+    #[test]
+    fn no_ack_mode() {
+        let (mut connection, mut client) = connection_pair();
+        connection.no_ack_mode = true;
+
+        connection.buffer.extend_from_slice(b"$qC#b4");
+        assert_eq!(command(connection.next_packet().unwrap()), "qC");
+        assert_nothing_sent(&mut client);
+    }
+
+    #[test]
+    fn non_utf8_packet() {
+        let (mut connection, _client) = connection_pair();
+
+        connection.buffer.extend_from_slice(b"$\xFFm#6c");
+        let command = command(connection.next_packet().unwrap());
+        assert_eq!(command, "\u{FFFD}m");
+
+        let mut stub = new_stub();
+        assert_eq!(stub.handle_command(&command, &mut test_memory()), Some(String::new()));
+    }
+
+    #[test]
+    fn non_ascii_and_empty_commands() {
+        let mut stub = new_stub();
+        let mut memory = test_memory();
+
+        for command in ["é", "éé,1", "ZÉ", "", "m\u{FFFD}", "Mé:00"] {
+            let reply = stub.handle_command(command, &mut memory);
+            assert!(matches!(reply.as_deref(), Some("" | "E01")), "{command}: {reply:?}");
+        }
+    }
+
+    #[test]
+    fn read_memory() {
+        let mut stub = new_stub();
+        let mut memory = test_memory();
+
+        assert_eq!(stub.handle_command("m10,4", &mut memory).unwrap(), "10111213");
+        assert_eq!(stub.handle_command("mFE,4", &mut memory).unwrap(), "feff0000");
+
+        for command in ["m10", "m10,", "mzz,4", "m10,4,", "m100000000,1"] {
+            assert_eq!(stub.handle_command(command, &mut memory).unwrap(), "E01", "{command}");
+        }
+    }
+
+    #[test]
+    fn memory_read_reply_is_capped() {
+        let mut stub = new_stub();
+        let mut memory = test_memory();
+
+        let reply = stub.handle_command("m0,ffffffff", &mut memory).unwrap();
+        assert_eq!(reply.len(), 2 * MAX_MEMORY_READ_LEN as usize);
+        assert!(reply.len() <= MAX_REPLY_LEN);
+        assert!(reply.starts_with("00010203"));
+
+        let reply = stub.handle_command("qXfer:features:read:target.xml:0,ffff", &mut memory);
+        assert!(reply.unwrap().len() <= MAX_REPLY_LEN);
+    }
+
+    #[test]
+    fn write_memory() {
+        let mut stub = new_stub();
+        let mut memory = test_memory();
+
+        assert_eq!(stub.handle_command("M10,2:abcd", &mut memory).unwrap(), "OK");
+        assert_eq!(&memory.0[0x10..0x12], &[0xAB, 0xCD]);
+
+        for command in
+            ["M10,2:abc", "M10,2:abcdef", "M10,2:zzzz", "M10,2abcd", "M10:abcd", "Mx,1:00"]
+        {
+            assert_eq!(stub.handle_command(command, &mut memory).unwrap(), "E01", "{command}");
+        }
+        assert_eq!(&memory.0[0x10..0x12], &[0xAB, 0xCD]);
+    }
+
+    #[test]
+    fn breakpoint_commands() {
+        let mut stub = new_stub();
+        let mut memory = test_memory();
+
+        assert_eq!(stub.handle_command("Z0,100,2", &mut memory).unwrap(), "OK");
+        assert_eq!(stub.handle_command("Z1,200,2;X1,0", &mut memory).unwrap(), "OK");
+        let addresses: Vec<_> =
+            stub.breakpoints.breakpoints().iter().map(|bp| bp.address).collect();
+        assert_eq!(addresses, vec![0x100, 0x200]);
+
+        assert_eq!(stub.handle_command("z0,100,2", &mut memory).unwrap(), "OK");
+        assert_eq!(stub.breakpoints.breakpoints().len(), 1);
+
+        // Access watchpoints add both a read and a write watchpoint
+        assert_eq!(stub.handle_command("Z4,ff0000,4", &mut memory).unwrap(), "OK");
+        let watchpoints = stub.breakpoints.watchpoints();
+        assert_eq!(watchpoints.len(), 2);
+        assert_eq!(watchpoints[0].range, 0xFF0000..=0xFF0003);
+
+        // Zero-length watchpoints still cover one byte
+        assert_eq!(stub.handle_command("Z2,10,0", &mut memory).unwrap(), "OK");
+        assert_eq!(stub.breakpoints.watchpoints()[2].range, 0x10..=0x10);
+
+        assert_eq!(stub.handle_command("z4,ff0000,4", &mut memory).unwrap(), "OK");
+        assert_eq!(stub.breakpoints.watchpoints().len(), 1);
+
+        // Unsupported breakpoint types get an empty reply; malformed arguments get an error
+        assert_eq!(stub.handle_command("Z9,0,1", &mut memory).unwrap(), "");
+        for command in ["Z0,100", "Z0", "Z0,xyz,2", "Z0,100,", "z2,10,q"] {
+            assert_eq!(stub.handle_command(command, &mut memory).unwrap(), "E01", "{command}");
+        }
+    }
+
+    #[test]
+    fn register_reads() {
+        let mut stub = new_stub();
+        let mut memory = test_memory();
+
+        let reply = stub.handle_command("g", &mut memory).unwrap();
+        assert_eq!(reply.len(), 18 * 8);
+        assert!(reply.starts_with("0000000000000001"));
+
+        assert_eq!(stub.handle_command("p11", &mut memory).unwrap(), "00000011");
+        assert_eq!(stub.handle_command("p12", &mut memory).unwrap(), "E01");
+        assert_eq!(stub.handle_command("pz", &mut memory).unwrap(), "E01");
+    }
+}
@@ -0,0 +1,431 @@
+//! Headless emulation for automated comparisons, e.g. running every ROM in a library for a fixed
+//! number of frames after a core change and comparing the final frames against a previous run.
+//!
+//! Headless runs open no window or audio device and never read or write save files, so results
+//! depend only on the ROM and the config. Frontend-only extras such as widescreen patches,
+//! lock-on cartridges, and texture packs are not applied; IPS/BPS patches are.
+
+use crate::config::{
+    self, Atari2600Config, ColecoVisionConfig, GameBoyConfig, GbaConfig, GenesisConfig, NesConfig,
+    PceConfig, SegaCdConfig, SmsGgConfig, SnesConfig,
+};
+use crate::mainloop::{
+    crash, load_sega_cd_emulator, parse_file_ext, read_patched_rom, NativeEmulatorError,
+    NativeEmulatorResult,
+};
+use atari2600_core::api::Atari2600Emulator;
+use bincode::{Decode, Encode};
+use colecovision_core::api::ColecoVisionEmulator;
+use crc::Crc;
+use gb_core::api::GameBoyEmulator;
+use gba_core::api::GbaEmulator;
+use genesis_core::pico::PicoEmulator;
+use genesis_core::GenesisEmulator;
+use image::RgbaImage;
+use jgenesis_common::frontend::{
+    AudioOutput, Color, EmulatorTrait, FrameSize, PixelAspectRatio, Renderer, SaveWriter,
+};
+use nes_core::api::NesEmulator;
+use pce_core::api::PceEmulator;
+use smsgg_core::SmsGgEmulator;
+use snes_core::api::SnesEmulator;
+use snes_core::spc::{SpcFile, SpcPlayer};
+use std::convert::Infallible;
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+const CRC: Crc<u32> = Crc::<u32>::new(&crc::CRC_32_ISO_HDLC);
+
+#[derive(Debug, Clone)]
+pub struct HeadlessOptions {
+    /// Number of frames to run before stopping
+    pub frames: u64,
+    /// If set, the last frame is saved here as a PNG at the core's native frame size
+    pub screenshot_path: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone)]
+pub struct HeadlessStats {
+    pub frames_rendered: u64,
+    /// Wall-clock time spent emulating, not including loading the ROM
+    pub elapsed: Duration,
+    /// Frame size of the last frame, or None if no frames were rendered
+    pub frame_size: Option<FrameSize>,
+    /// CRC-32 of the last frame's RGBA bytes, for detecting output changes between runs
+    pub frame_crc32: Option<u32>,
+    /// Number of stereo sample pairs
+    pub audio_samples: u64,
+    /// Peak absolute sample value, 0.0 if the game was silent
+    pub audio_peak: f64,
+}
+
+impl HeadlessStats {
+    /// Emulation speed in frames per second of wall-clock time.
+    #[must_use]
+    pub fn fps(&self) -> f64 {
+        let seconds = self.elapsed.as_secs_f64();
+        if seconds == 0.0 {
+            return 0.0;
+        }
+
+        self.frames_rendered as f64 / seconds
+    }
+}
+
+#[derive(Debug, Default)]
+struct FrameCapture {
+    frame_buffer: Vec<Color>,
+    frame_size: Option<FrameSize>,
+    frames_rendered: u64,
+}
+
+impl Renderer for FrameCapture {
+    type Err = Infallible;
+
+    fn render_frame(
+        &mut self,
+        frame_buffer: &[Color],
+        frame_size: FrameSize,
+        _pixel_aspect_ratio: Option<PixelAspectRatio>,
+    ) -> Result<(), Self::Err> {
+        let len = (frame_size.width * frame_size.height) as usize;
+        self.frame_buffer.clear();
+        self.frame_buffer.extend_from_slice(&frame_buffer[..len]);
+        self.frame_size = Some(frame_size);
+        self.frames_rendered += 1;
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+struct AudioStats {
+    samples: u64,
+    peak: f64,
+}
+
+impl AudioOutput for AudioStats {
+    type Err = Infallible;
+
+    fn push_sample(&mut self, sample_l: f64, sample_r: f64) -> Result<(), Self::Err> {
+        self.samples += 1;
+        self.peak = self.peak.max(sample_l.abs()).max(sample_r.abs());
+        Ok(())
+    }
+}
+
+// Starts every run without save files and discards any writes
+struct NullSaveWriter;
+
+impl SaveWriter for NullSaveWriter {
+    type Err = String;
+
+    fn load_bytes(&mut self, extension: &str) -> Result<Vec<u8>, Self::Err> {
+        Err(format!("Headless runs do not load save files ({extension})"))
+    }
+
+    fn persist_bytes(&mut self, _extension: &str, _bytes: &[u8]) -> Result<(), Self::Err> {
+        Ok(())
+    }
+
+    fn load_serialized<D: Decode>(&mut self, extension: &str) -> Result<D, Self::Err> {
+        Err(format!("Headless runs do not load save files ({extension})"))
+    }
+
+    fn persist_serialized<E: Encode>(
+        &mut self,
+        _extension: &str,
+        _data: E,
+    ) -> Result<(), Self::Err> {
+        Ok(())
+    }
+}
+
+fn run<Emulator>(
+    emulator: &mut Emulator,
+    options: &HeadlessOptions,
+) -> NativeEmulatorResult<HeadlessStats>
+where
+    Emulator: EmulatorTrait,
+    Emulator::Inputs: Default,
+{
+    let inputs = Emulator::Inputs::default();
+    let mut renderer = FrameCapture::default();
+    let mut audio_output = AudioStats::default();
+
+    let start = Instant::now();
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        while renderer.frames_rendered < options.frames {
+            emulator
+                .tick(&mut renderer, &mut audio_output, &inputs)
+                .map_err(|err| NativeEmulatorError::Emulator(err.into()))?;
+        }
+
+        Ok::<_, NativeEmulatorError>(())
+    }));
+    let elapsed = start.elapsed();
+
+    result.unwrap_or_else(|payload| {
+        Err(NativeEmulatorError::Panic(crash::panic_message(payload.as_ref())))
+    })?;
+
+    let frame_crc32 =
+        renderer.frame_size.map(|_| CRC.checksum(bytemuck::cast_slice(&renderer.frame_buffer)));
+
+    if let (Some(path), Some(frame_size)) = (&options.screenshot_path, renderer.frame_size) {
+        save_screenshot(path, &renderer.frame_buffer, frame_size)?;
+    }
+
+    Ok(HeadlessStats {
+        frames_rendered: renderer.frames_rendered,
+        elapsed,
+        frame_size: renderer.frame_size,
+        frame_crc32,
+        audio_samples: audio_output.samples,
+        audio_peak: audio_output.peak,
+    })
+}
+
+fn save_screenshot(
+    path: &Path,
+    frame_buffer: &[Color],
+    frame_size: FrameSize,
+) -> NativeEmulatorResult<()> {
+    let bytes = bytemuck::cast_slice(frame_buffer).to_vec();
+    let Some(image) = RgbaImage::from_raw(frame_size.width, frame_size.height, bytes) else {
+        return Ok(());
+    };
+
+    image.save(path).map_err(|source| NativeEmulatorError::HeadlessScreenshot {
+        path: path.display().to_string(),
+        source,
+    })
+}
+
+/// Run a Master System or Game Gear ROM without a window.
+///
+/// # Errors
+///
+/// Propagates any error encountered while loading the ROM or while emulating, including panics.
+pub fn run_smsgg(
+    config: &SmsGgConfig,
+    options: &HeadlessOptions,
+) -> NativeEmulatorResult<HeadlessStats> {
+    let rom_path = Path::new(&config.common.rom_file_path);
+    let file_ext = parse_file_ext(rom_path)?;
+    let rom = read_patched_rom(rom_path, &config.common.rom_patch_paths)?;
+
+    let vdp_version =
+        config.vdp_version.unwrap_or_else(|| config::default_vdp_version_for_ext(file_ext));
+    let psg_version =
+        config.psg_version.unwrap_or_else(|| config::default_psg_version_for_ext(file_ext));
+
+    let emulator_config = config.to_emulator_config(vdp_version, psg_version);
+    let mut emulator = SmsGgEmulator::create(rom, emulator_config, &mut NullSaveWriter);
+
+    run(&mut emulator, options)
+}
+
+/// Run a Genesis ROM without a window.
+///
+/// # Errors
+///
+/// Propagates any error encountered while loading the ROM or while emulating, including panics.
+pub fn run_genesis(
+    config: &GenesisConfig,
+    options: &HeadlessOptions,
+) -> NativeEmulatorResult<HeadlessStats> {
+    let rom =
+        read_patched_rom(Path::new(&config.common.rom_file_path), &config.common.rom_patch_paths)?;
+    let mut emulator =
+        GenesisEmulator::create(rom, config.to_emulator_config(), &mut NullSaveWriter);
+
+    run(&mut emulator, options)
+}
+
+/// Run a Pico ROM without a window.
+///
+/// # Errors
+///
+/// Propagates any error encountered while loading the ROM or while emulating, including panics.
+pub fn run_pico(
+    config: &GenesisConfig,
+    options: &HeadlessOptions,
+) -> NativeEmulatorResult<HeadlessStats> {
+    let rom =
+        read_patched_rom(Path::new(&config.common.rom_file_path), &config.common.rom_patch_paths)?;
+    let mut emulator = PicoEmulator::create(rom, config.to_emulator_config());
+
+    run(&mut emulator, options)
+}
+
+/// Run a Sega CD disc without a window.
+///
+/// # Errors
+///
+/// Propagates any error encountered while loading the BIOS or disc or while emulating, including
+/// panics.
+pub fn run_sega_cd(
+    config: &SegaCdConfig,
+    options: &HeadlessOptions,
+) -> NativeEmulatorResult<HeadlessStats> {
+    let mut emulator = load_sega_cd_emulator(config, &mut NullSaveWriter)?;
+
+    run(&mut emulator, options)
+}
+
+/// Run an NES ROM without a window.
+///
+/// # Errors
+///
+/// Propagates any error encountered while loading the ROM or while emulating, including panics.
+pub fn run_nes(
+    config: &NesConfig,
+    options: &HeadlessOptions,
+) -> NativeEmulatorResult<HeadlessStats> {
+    let rom =
+        read_patched_rom(Path::new(&config.common.rom_file_path), &config.common.rom_patch_paths)?;
+    let mut emulator = NesEmulator::create(rom, config.to_emulator_config(), &mut NullSaveWriter)?;
+
+    run(&mut emulator, options)
+}
+
+/// Run an SNES ROM without a window.
+///
+/// # Errors
+///
+/// Propagates any error encountered while loading the ROM or while emulating, including panics.
+pub fn run_snes(
+    config: &SnesConfig,
+    options: &HeadlessOptions,
+) -> NativeEmulatorResult<HeadlessStats> {
+    let rom =
+        read_patched_rom(Path::new(&config.common.rom_file_path), &config.common.rom_patch_paths)?;
+    let mut emulator = SnesEmulator::create(
+        rom,
+        config.to_emulator_config(),
+        config.to_coprocessor_roms(),
+        &mut NullSaveWriter,
+    )?;
+
+    run(&mut emulator, options)
+}
+
+/// Play back an SPC file without a window.
+///
+/// # Errors
+///
+/// Propagates any error encountered while loading the SPC file or while emulating, including
+/// panics.
+pub fn run_spc(
+    config: &SnesConfig,
+    options: &HeadlessOptions,
+) -> NativeEmulatorResult<HeadlessStats> {
+    let spc_bytes = fs::read(&config.common.rom_file_path).map_err(|source| {
+        NativeEmulatorError::RomRead { path: config.common.rom_file_path.clone(), source }
+    })?;
+    let spc = SpcFile::parse(&spc_bytes)?;
+    let mut emulator = SpcPlayer::create(spc, config.to_emulator_config());
+
+    run(&mut emulator, options)
+}
+
+/// Run a Game Boy or Game Boy Color ROM without a window.
+///
+/// # Errors
+///
+/// Propagates any error encountered while loading the ROM or while emulating, including panics.
+pub fn run_gb(
+    config: &GameBoyConfig,
+    options: &HeadlessOptions,
+) -> NativeEmulatorResult<HeadlessStats> {
+    let rom =
+        read_patched_rom(Path::new(&config.common.rom_file_path), &config.common.rom_patch_paths)?;
+    let mut emulator =
+        GameBoyEmulator::create(rom, config.to_emulator_config(), &mut NullSaveWriter)?;
+
+    run(&mut emulator, options)
+}
+
+/// Run a PC Engine HuCard ROM without a window.
+///
+/// # Errors
+///
+/// Propagates any error encountered while loading the ROM or while emulating, including panics.
+pub fn run_pce(
+    config: &PceConfig,
+    options: &HeadlessOptions,
+) -> NativeEmulatorResult<HeadlessStats> {
+    let rom =
+        read_patched_rom(Path::new(&config.common.rom_file_path), &config.common.rom_patch_paths)?;
+    let mut emulator = PceEmulator::create(rom, config.to_emulator_config())?;
+
+    run(&mut emulator, options)
+}
+
+/// Run a Game Boy Advance ROM without a window.
+///
+/// # Errors
+///
+/// Propagates any error encountered while loading the BIOS or ROM or while emulating, including
+/// panics.
+pub fn run_gba(
+    config: &GbaConfig,
+    options: &HeadlessOptions,
+) -> NativeEmulatorResult<HeadlessStats> {
+    let rom =
+        read_patched_rom(Path::new(&config.common.rom_file_path), &config.common.rom_patch_paths)?;
+
+    let bios_file_path = config.bios_file_path.as_ref().ok_or(NativeEmulatorError::GbaNoBios)?;
+    let bios = fs::read(bios_file_path).map_err(|source| NativeEmulatorError::GbaBiosRead {
+        path: bios_file_path.clone(),
+        source,
+    })?;
+
+    let mut emulator =
+        GbaEmulator::create(rom, bios, config.to_emulator_config(), &mut NullSaveWriter)?;
+
+    run(&mut emulator, options)
+}
+
+/// Run an Atari 2600 ROM without a window.
+///
+/// # Errors
+///
+/// Propagates any error encountered while loading the ROM or while emulating, including panics.
+pub fn run_atari2600(
+    config: &Atari2600Config,
+    options: &HeadlessOptions,
+) -> NativeEmulatorResult<HeadlessStats> {
+    let rom =
+        read_patched_rom(Path::new(&config.common.rom_file_path), &config.common.rom_patch_paths)?;
+    let mut emulator = Atari2600Emulator::create(rom, config.to_emulator_config())?;
+
+    run(&mut emulator, options)
+}
+
+/// Run a ColecoVision ROM without a window.
+///
+/// # Errors
+///
+/// Propagates any error encountered while loading the BIOS or ROM or while emulating, including
+/// panics.
+pub fn run_colecovision(
+    config: &ColecoVisionConfig,
+    options: &HeadlessOptions,
+) -> NativeEmulatorResult<HeadlessStats> {
+    let rom =
+        read_patched_rom(Path::new(&config.common.rom_file_path), &config.common.rom_patch_paths)?;
+
+    let bios_file_path =
+        config.bios_file_path.as_ref().ok_or(NativeEmulatorError::ColecoVisionNoBios)?;
+    let bios = fs::read(bios_file_path).map_err(|source| {
+        NativeEmulatorError::ColecoVisionBiosRead { path: bios_file_path.clone(), source }
+    })?;
+
+    let mut emulator = ColecoVisionEmulator::create(rom, bios, config.to_emulator_config())?;
+
+    run(&mut emulator, options)
+}
@@ -0,0 +1,214 @@
+//! Input injection server, which allows external programs (bots, Twitch-plays bridges,
+//! accessibility tools) to press buttons on any controller port
+//!
+//! Clients connect over TCP on localhost and send newline-terminated text commands, and the server
+//! replies to each command with a single line starting with `ok` or `error`:
+//!
+//! * `hold <buttons>`: Hold buttons until they are released
+//! * `release [<buttons>]`: Release held buttons, or all held buttons if none are given
+//! * `press <sequence>`: Queue a sequence to play back one frame at a time
+//! * `clear`: Release all held buttons and drop queued frames
+//! * `frame`: Reply with the number of frames emulated since the server started
+//!
+//! Buttons and sequences use the same syntax as input macros (see [`crate::input::macros`]), e.g.
+//! `hold p2.right+p2.b` or `press p2.down, p2.down+p2.right, p2.right+p2.a`. Injected buttons are
+//! combined with local keyboard and gamepad inputs, and each client's buttons are released when it
+//! disconnects.
+
+use crate::input::macros;
+use crate::input::macros::MacroButton;
+use std::collections::VecDeque;
+use std::io;
+use std::io::{ErrorKind, Read, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+
+const HELP: &str =
+    "ok Commands: hold <buttons>, release [<buttons>], press <sequence>, clear, frame";
+
+struct InjectionClient<Button> {
+    stream: TcpStream,
+    buffer: Vec<u8>,
+    held: Vec<Button>,
+    queued: VecDeque<Vec<Button>>,
+}
+
+impl<Button: MacroButton> InjectionClient<Button> {
+    fn new(stream: TcpStream) -> io::Result<Self> {
+        stream.set_nonblocking(true)?;
+        stream.set_nodelay(true)?;
+
+        Ok(Self { stream, buffer: Vec::new(), held: vec![], queued: VecDeque::new() })
+    }
+
+    // Returns Ok(false) if the client disconnected
+    fn receive(&mut self) -> io::Result<bool> {
+        let mut read_buffer = [0; 4096];
+        loop {
+            match self.stream.read(&mut read_buffer) {
+                Ok(0) => return Ok(false),
+                Ok(bytes_read) => self.buffer.extend_from_slice(&read_buffer[..bytes_read]),
+                Err(err) if err.kind() == ErrorKind::WouldBlock => return Ok(true),
+                Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    fn next_line(&mut self) -> Option<String> {
+        let end = self.buffer.iter().position(|&b| b == b'\n')?;
+        let line: Vec<_> = self.buffer.drain(..=end).collect();
+        Some(String::from_utf8_lossy(&line).trim().into())
+    }
+
+    fn handle_command(&mut self, command: &str, frame: u64) -> String {
+        let (name, args) = command.split_once(char::is_whitespace).unwrap_or((command, ""));
+        match (name, args.trim()) {
+            ("hold", buttons) => match macros::parse_buttons::<Button>(buttons) {
+                Ok(buttons) => {
+                    for button in buttons {
+                        if !self.held.contains(&button) {
+                            self.held.push(button);
+                        }
+                    }
+                    "ok".into()
+                }
+                Err(err) => format!("error {err}"),
+            },
+            ("release", "") => {
+                self.held.clear();
+                "ok".into()
+            }
+            ("release", buttons) => match macros::parse_buttons::<Button>(buttons) {
+                Ok(buttons) => {
+                    self.held.retain(|held| !buttons.contains(held));
+                    "ok".into()
+                }
+                Err(err) => format!("error {err}"),
+            },
+            ("press", sequence) => match macros::parse_sequence::<Button>(sequence) {
+                Ok(frames) => {
+                    self.queued.extend(frames);
+                    "ok".into()
+                }
+                Err(err) => format!("error {err}"),
+            },
+            ("clear", _) => {
+                self.held.clear();
+                self.queued.clear();
+                "ok".into()
+            }
+            ("frame", _) => format!("ok {frame}"),
+            ("help", _) => HELP.into(),
+            _ => format!("error Unknown command '{name}'; send 'help' for usage"),
+        }
+    }
+
+    fn send(&mut self, reply: &str) -> io::Result<()> {
+        // Temporarily switch to blocking mode so that replies are not partially written
+        self.stream.set_nonblocking(false)?;
+        let result = self
+            .stream
+            .write_all(reply.as_bytes())
+            .and_then(|()| self.stream.write_all(b"\n"))
+            .and_then(|()| self.stream.flush());
+        self.stream.set_nonblocking(true)?;
+        result
+    }
+
+    // Returns Ok(false) if the client disconnected
+    fn poll(&mut self, frame: u64) -> io::Result<bool> {
+        let connected = self.receive()?;
+
+        while let Some(line) = self.next_line() {
+            if line.is_empty() {
+                continue;
+            }
+
+            let reply = self.handle_command(&line, frame);
+            self.send(&reply)?;
+        }
+
+        Ok(connected)
+    }
+}
+
+pub struct InputInjectionServer<Button> {
+    listener: TcpListener,
+    clients: Vec<InjectionClient<Button>>,
+    frame: u64,
+}
+
+impl<Button: MacroButton> InputInjectionServer<Button> {
+    /// Start listening for input injection connections on the given localhost port.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if it is unable to bind to the port.
+    pub fn new(port: u16) -> io::Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+        listener.set_nonblocking(true)?;
+
+        log::info!("Input injection server listening on localhost:{port}");
+
+        Ok(Self { listener, clients: vec![], frame: 0 })
+    }
+
+    /// Accept new connections and process any commands received from connected clients.
+    pub fn poll(&mut self) {
+        loop {
+            match self.listener.accept() {
+                Ok((stream, addr)) => match InjectionClient::new(stream) {
+                    Ok(client) => {
+                        log::info!("Input injection client connected from {addr}");
+                        self.clients.push(client);
+                    }
+                    Err(err) => {
+                        log::error!("Error initializing input injection connection: {err}");
+                    }
+                },
+                Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                Err(err) => {
+                    log::error!("Error accepting input injection connection: {err}");
+                    break;
+                }
+            }
+        }
+
+        let frame = self.frame;
+        self.clients.retain_mut(|client| match client.poll(frame) {
+            Ok(true) => true,
+            Ok(false) => {
+                log::info!("Input injection client disconnected");
+                false
+            }
+            Err(err) => {
+                log::error!("Input injection connection error, disconnecting: {err}");
+                false
+            }
+        });
+    }
+
+    /// Advance queued sequences. Should be called after every emulated frame.
+    pub fn end_frame(&mut self) {
+        self.frame += 1;
+
+        for client in &mut self.clients {
+            client.queued.pop_front();
+        }
+    }
+
+    /// Buttons that should be held for the next frame, across all clients.
+    #[must_use]
+    pub fn buttons(&self) -> Vec<Button> {
+        let mut buttons = Vec::new();
+        for client in &self.clients {
+            let queued = client.queued.front().map_or(&[][..], Vec::as_slice);
+            for &button in client.held.iter().chain(queued) {
+                if !buttons.contains(&button) {
+                    buttons.push(button);
+                }
+            }
+        }
+        buttons
+    }
+}
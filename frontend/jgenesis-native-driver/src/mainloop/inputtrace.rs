@@ -0,0 +1,196 @@
+//! Rolling log of recent inputs and emulator events
+//!
+//! Inputs are recorded every emulated frame (but only stored when they change), along with events
+//! such as resets and save state loads that affect emulation timing. The log covers roughly the
+//! last N seconds and is written to a text file next to the ROM when the dump input trace hotkey is
+//! pressed, and is included in crash reports, so that it can be attached to bug reports.
+
+use crate::mainloop::next_dump_path;
+use std::collections::VecDeque;
+use std::fmt::{Debug, Write};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, TryLockError, Weak};
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceEvent {
+    SaveState,
+    LoadState,
+    LoadStateUndone,
+    PracticeAttemptRestarted,
+    SoftReset,
+    HardReset,
+    Paused,
+    Resumed,
+    StepFrame,
+    RewindStarted,
+    RewindStopped,
+    ConfigReloaded,
+    DiscChanged,
+    DiscRemoved,
+}
+
+#[derive(Debug, Clone)]
+enum TraceEntryKind {
+    Inputs(String),
+    Event(TraceEvent),
+}
+
+#[derive(Debug, Clone)]
+struct TraceEntry {
+    frame: u64,
+    kind: TraceEntryKind,
+}
+
+#[derive(Debug)]
+struct TraceBuffer {
+    entries: VecDeque<TraceEntry>,
+    frame: u64,
+    buffer_len: u64,
+    // Inputs that were held at the start of the window, from the most recently evicted entry
+    initial_inputs: Option<String>,
+    last_inputs: String,
+}
+
+impl TraceBuffer {
+    fn push(&mut self, kind: TraceEntryKind) {
+        self.entries.push_back(TraceEntry { frame: self.frame, kind });
+    }
+
+    fn evict_old_entries(&mut self) {
+        let window_start = self.window_start();
+        while self.entries.front().is_some_and(|entry| entry.frame < window_start) {
+            let Some(TraceEntry { kind, .. }) = self.entries.pop_front() else { break };
+            if let TraceEntryKind::Inputs(inputs) = kind {
+                self.initial_inputs = Some(inputs);
+            }
+        }
+    }
+
+    fn window_start(&self) -> u64 {
+        self.frame.saturating_sub(self.buffer_len)
+    }
+
+    fn to_text(&self) -> String {
+        let mut text = String::new();
+
+        let window_start = self.window_start();
+        let _ = writeln!(text, "# Input trace for frames {window_start}-{}", self.frame);
+        let _ = writeln!(text, "# Frame numbers count emulated frames since the emulator started");
+
+        if let Some(initial_inputs) = &self.initial_inputs {
+            let _ = writeln!(text, "{window_start}: inputs {initial_inputs}");
+        }
+
+        for TraceEntry { frame, kind } in &self.entries {
+            let _ = match kind {
+                TraceEntryKind::Inputs(inputs) => writeln!(text, "{frame}: inputs {inputs}"),
+                TraceEntryKind::Event(event) => writeln!(text, "{frame}: event {event:?}"),
+            };
+        }
+
+        text
+    }
+}
+
+// The trace to include in crash reports; only the most recently created emulator's trace is tracked
+static CURRENT_TRACE: Mutex<Option<Weak<Mutex<TraceBuffer>>>> = Mutex::new(None);
+
+pub struct InputTrace {
+    buffer: Arc<Mutex<TraceBuffer>>,
+    // Dump files are written next to the ROM file
+    base_path: PathBuf,
+}
+
+impl InputTrace {
+    pub fn new(rom_path: &Path, buffer_duration: Duration) -> Self {
+        let buffer = Arc::new(Mutex::new(TraceBuffer {
+            entries: VecDeque::new(),
+            frame: 0,
+            buffer_len: duration_to_buffer_len(buffer_duration),
+            initial_inputs: None,
+            last_inputs: String::new(),
+        }));
+        let base_path = rom_path.with_extension("");
+
+        if let Ok(mut current_trace) = CURRENT_TRACE.lock() {
+            *current_trace = Some(Arc::downgrade(&buffer));
+        }
+
+        Self { buffer, base_path }
+    }
+
+    fn with_buffer(&self, f: impl FnOnce(&mut TraceBuffer)) {
+        // Tracing is best-effort; the lock can only be poisoned by a panic inside this module
+        if let Ok(mut buffer) = self.buffer.lock() {
+            if buffer.buffer_len != 0 {
+                f(&mut buffer);
+            }
+        }
+    }
+
+    /// Record the inputs that were used for the frame that was just emulated.
+    pub fn record_frame<Inputs: Debug>(&mut self, inputs: &Inputs) {
+        self.with_buffer(|buffer| {
+            buffer.frame += 1;
+
+            let inputs = format!("{inputs:?}");
+            if inputs != buffer.last_inputs {
+                buffer.last_inputs.clone_from(&inputs);
+                buffer.push(TraceEntryKind::Inputs(inputs));
+            }
+
+            buffer.evict_old_entries();
+        });
+    }
+
+    pub fn record_event(&mut self, event: TraceEvent) {
+        self.with_buffer(|buffer| buffer.push(TraceEntryKind::Event(event)));
+    }
+
+    pub fn set_buffer_duration(&mut self, duration: Duration) {
+        if let Ok(mut buffer) = self.buffer.lock() {
+            buffer.buffer_len = duration_to_buffer_len(duration);
+            buffer.evict_old_entries();
+        }
+    }
+
+    /// Write the trace to the next available `<rom>_inputs_<n>.txt` file.
+    pub fn dump(&self) {
+        let Ok(buffer) = self.buffer.lock() else { return };
+        write_trace(&self.base_path, &buffer);
+    }
+}
+
+fn write_trace(base_path: &Path, buffer: &TraceBuffer) {
+    let path = next_dump_path(&inputs_base_path(base_path), &["txt"]);
+    match fs::write(&path, buffer.to_text()) {
+        Ok(()) => log::info!("Wrote input trace to '{}'", path.display()),
+        Err(err) => log::error!("Error writing input trace to '{}': {err}", path.display()),
+    }
+}
+
+fn inputs_base_path(base_path: &Path) -> PathBuf {
+    let file_name = base_path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+    base_path.with_file_name(format!("{file_name}_inputs"))
+}
+
+/// Returns the text of the most recently created emulator's input trace, if tracing is enabled.
+///
+/// This never blocks so that it is safe to call from a panic hook.
+pub fn current_trace_text() -> Option<String> {
+    let current_trace = CURRENT_TRACE.try_lock().ok()?;
+    let buffer = current_trace.as_ref()?.upgrade()?;
+
+    let buffer = match buffer.try_lock() {
+        Ok(buffer) => buffer,
+        Err(TryLockError::Poisoned(err)) => err.into_inner(),
+        Err(TryLockError::WouldBlock) => return None,
+    };
+    (buffer.buffer_len != 0).then(|| buffer.to_text())
+}
+
+fn duration_to_buffer_len(duration: Duration) -> u64 {
+    duration.as_secs() * 60
+}
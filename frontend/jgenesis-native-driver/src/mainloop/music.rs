@@ -0,0 +1,98 @@
+//! Music ripping via the music dump hotkey
+//!
+//! For SNES, each press of the hotkey saves an SPC snapshot of the APU. For Genesis and Sega CD,
+//! the first press starts logging YM2612 and PSG register writes and the second press stops logging
+//! and writes the log to VGM and GYM files.
+
+use crate::mainloop::next_dump_path;
+use genesis_core::soundlog::{SoundLog, SoundLogWriter};
+use jgenesis_common::frontend::EmulatorTrait;
+use snes_core::spc::SpcFile;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+enum MusicDumpKind<Emulator> {
+    Spc(fn(&mut Emulator) -> SpcFile),
+    SoundLog { sound_log_fn: fn(&mut Emulator) -> &mut SoundLog, writer: Option<SoundLogWriter> },
+}
+
+pub struct MusicDumper<Emulator> {
+    kind: MusicDumpKind<Emulator>,
+    // Dump files are written next to the ROM file
+    base_path: PathBuf,
+}
+
+impl<Emulator: EmulatorTrait> MusicDumper<Emulator> {
+    pub fn spc(rom_path: &Path, save_spc_fn: fn(&mut Emulator) -> SpcFile) -> Self {
+        Self { kind: MusicDumpKind::Spc(save_spc_fn), base_path: rom_path.with_extension("") }
+    }
+
+    pub fn sound_log(rom_path: &Path, sound_log_fn: fn(&mut Emulator) -> &mut SoundLog) -> Self {
+        Self {
+            kind: MusicDumpKind::SoundLog { sound_log_fn, writer: None },
+            base_path: rom_path.with_extension(""),
+        }
+    }
+
+    pub fn handle_hotkey(&mut self, emulator: &mut Emulator) {
+        match &mut self.kind {
+            MusicDumpKind::Spc(save_spc_fn) => {
+                let spc = save_spc_fn(emulator);
+                let path = next_dump_path(&self.base_path, &["spc"]);
+                write_dump_file(&path, &spc.to_bytes());
+            }
+            MusicDumpKind::SoundLog { sound_log_fn, writer } => match writer.take() {
+                Some(mut writer) => {
+                    let sound_log = sound_log_fn(emulator);
+                    writer.push_events(&sound_log.drain_events());
+                    sound_log.set_enabled(false);
+
+                    write_sound_log(&self.base_path, &writer);
+                }
+                None => {
+                    let timing_mode = emulator.timing_mode();
+                    let sound_log = sound_log_fn(emulator);
+                    sound_log.set_enabled(true);
+                    *writer = Some(SoundLogWriter::new(timing_mode, &sound_log.state_writes()));
+
+                    log::info!("Started logging sound chip writes");
+                }
+            },
+        }
+    }
+
+    /// Collect sound chip writes from the last frame if logging is active.
+    pub fn after_frame(&mut self, emulator: &mut Emulator) {
+        let MusicDumpKind::SoundLog { sound_log_fn, writer: Some(writer) } = &mut self.kind else {
+            return;
+        };
+
+        let sound_log = sound_log_fn(emulator);
+
+        // Loading a save state or rewinding replaces the emulator's log
+        sound_log.set_enabled(true);
+
+        writer.push_events(&sound_log.drain_events());
+    }
+}
+
+impl<Emulator> Drop for MusicDumper<Emulator> {
+    fn drop(&mut self) {
+        if let MusicDumpKind::SoundLog { writer: Some(writer), .. } = &self.kind {
+            write_sound_log(&self.base_path, writer);
+        }
+    }
+}
+
+fn write_sound_log(base_path: &Path, writer: &SoundLogWriter) {
+    let vgm_path = next_dump_path(base_path, &["vgm", "gym"]);
+    write_dump_file(&vgm_path, &writer.to_vgm_bytes());
+    write_dump_file(&vgm_path.with_extension("gym"), &writer.to_gym_bytes());
+}
+
+fn write_dump_file(path: &Path, bytes: &[u8]) {
+    match fs::write(path, bytes) {
+        Ok(()) => log::info!("Wrote music dump to '{}'", path.display()),
+        Err(err) => log::error!("Error writing music dump to '{}': {err}", path.display()),
+    }
+}
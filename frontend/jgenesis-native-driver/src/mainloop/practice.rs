@@ -0,0 +1,136 @@
+//! Practice loop for repeating a section of a game
+//!
+//! Setting a loop point keeps an in-memory copy of the current state. Once the end trigger is hit,
+//! either after a fixed number of frames or when a debugger expression over memory and registers
+//! becomes true, the loop point is reloaded and the attempt counter is incremented. Attempts can
+//! also be restarted manually.
+
+use crate::mainloop::gdb::DebuggableFn;
+use jgenesis_common::debug::{CpuArchitecture, Expression};
+use jgenesis_common::frontend::{EmulatorTrait, PartialClone};
+
+pub struct PracticeLoop<Emulator> {
+    // Does not own the ROM
+    loop_state: Option<Emulator>,
+    attempt: u32,
+    frames_since_restart: u64,
+    end_frame_count: u64,
+    end_condition_source: String,
+    end_condition: Option<Expression>,
+    // Architecture of the emulator that the loop point was set in, if it implements Debuggable
+    arch: Option<CpuArchitecture>,
+}
+
+impl<Emulator: PartialClone> PracticeLoop<Emulator> {
+    pub fn new(end_frame_count: u64, end_condition: String) -> Self {
+        Self {
+            loop_state: None,
+            attempt: 0,
+            frames_since_restart: 0,
+            end_frame_count,
+            end_condition_source: end_condition,
+            end_condition: None,
+            arch: None,
+        }
+    }
+
+    pub fn set_end_trigger(&mut self, end_frame_count: u64, end_condition: String) {
+        self.end_frame_count = end_frame_count;
+        if end_condition != self.end_condition_source {
+            self.end_condition_source = end_condition;
+            self.parse_end_condition();
+        }
+    }
+
+    fn parse_end_condition(&mut self) {
+        self.end_condition = None;
+
+        let source = self.end_condition_source.trim();
+        if source.is_empty() || self.loop_state.is_none() {
+            return;
+        }
+
+        let Some(arch) = self.arch else {
+            log::warn!("Practice end condition '{source}' is not supported for this system");
+            return;
+        };
+
+        match Expression::parse(source, arch) {
+            Ok(expression) => {
+                self.end_condition = Some(expression);
+            }
+            Err(err) => {
+                log::error!("Invalid practice end condition '{source}': {err}");
+            }
+        }
+    }
+
+    /// Set the loop point to the current state and reset the attempt counter.
+    pub fn set_loop_point(
+        &mut self,
+        emulator: &mut Emulator,
+        as_debuggable: Option<DebuggableFn<Emulator>>,
+    ) {
+        self.loop_state = Some(emulator.partial_clone());
+        self.attempt = 1;
+        self.frames_since_restart = 0;
+
+        self.arch = as_debuggable.map(|as_debuggable| as_debuggable(emulator).cpu_architecture());
+        self.parse_end_condition();
+
+        log::info!("Set practice loop point");
+    }
+
+    /// Returns false if no loop point was set.
+    pub fn clear_loop_point(&mut self) -> bool {
+        self.end_condition = None;
+        self.loop_state.take().is_some()
+    }
+
+    /// Record that a frame was emulated. Returns true if the end trigger was hit, in which case the
+    /// attempt should be restarted.
+    pub fn end_reached(
+        &mut self,
+        emulator: &mut Emulator,
+        as_debuggable: Option<DebuggableFn<Emulator>>,
+    ) -> bool {
+        if self.loop_state.is_none() {
+            return false;
+        }
+
+        self.frames_since_restart += 1;
+        if self.end_frame_count != 0 && self.frames_since_restart >= self.end_frame_count {
+            return true;
+        }
+
+        match (&self.end_condition, as_debuggable) {
+            (Some(end_condition), Some(as_debuggable)) => {
+                end_condition.is_true(as_debuggable(emulator))
+            }
+            _ => false,
+        }
+    }
+
+    /// Reload the loop point and start a new attempt.
+    ///
+    /// Returns the new attempt number, or None if no loop point was set.
+    pub fn restart(&mut self, emulator: &mut Emulator, config: &Emulator::Config) -> Option<u32>
+    where
+        Emulator: EmulatorTrait,
+    {
+        let mut loop_state = self.loop_state.as_ref()?.partial_clone();
+        loop_state.take_rom_from(emulator);
+
+        // Force a config reload because the emulator will contain some config fields
+        loop_state.reload_config(config);
+
+        *emulator = loop_state;
+
+        self.attempt += 1;
+        self.frames_since_restart = 0;
+
+        log::info!("Started practice attempt {}", self.attempt);
+
+        Some(self.attempt)
+    }
+}
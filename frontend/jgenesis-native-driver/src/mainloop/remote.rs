@@ -0,0 +1,693 @@
+//! Remote control server, which allows external programs (stream decks, overlays, automation
+//! scripts) to control the emulator
+//!
+//! The server listens on localhost and accepts JSON requests either as WebSocket text messages or
+//! as the body of an HTTP `POST` request. Every connection must authenticate with the configured
+//! token, passed either in the URL query string (e.g. `ws://localhost:<port>/?token=<token>`) or in
+//! an `Authorization: Bearer <token>` header.
+//!
+//! Requests look like the following, where `id` is optional and is copied into the response:
+//!
+//! ```text
+//! {"id": 1, "type": "command", "command": {"SetPaused": true}}
+//! {"id": 2, "type": "command", "command": {"OpenFile": {"kind": "Rom", "path": "/roms/game.md"}}}
+//! {"id": 3, "type": "read_memory", "address": 16711680, "length": 16}
+//! ```
+//!
+//! Commands use the [`EmulatorCommand`] protocol shared by all frontends. Every request gets
+//! exactly one response: `{"id": 1, "ok": true}`, `{"id": 3, "ok": true, "data": [...]}` for memory
+//! reads, or `{"id": 1, "ok": false, "error": "..."}`. HTTP connections are closed after the
+//! response.
+
+use base64::engine::general_purpose;
+use base64::Engine;
+use jgenesis_common::command::EmulatorCommand;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use sha1::{Digest, Sha1};
+use std::io::{ErrorKind, Read, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::{io, mem};
+
+// Defined by RFC 6455 for computing the Sec-WebSocket-Accept handshake header
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+// Limits to avoid buffering unbounded amounts of data from misbehaving clients
+const MAX_HEADER_LEN: usize = 16 * 1024;
+const MAX_MESSAGE_LEN: usize = 1024 * 1024;
+
+/// Maximum number of bytes returned by a single memory read.
+pub const MAX_READ_LEN: u32 = 64 * 1024;
+
+const OPCODE_CONTINUATION: u8 = 0x0;
+const OPCODE_TEXT: u8 = 0x1;
+const OPCODE_CLOSE: u8 = 0x8;
+const OPCODE_PING: u8 = 0x9;
+const OPCODE_PONG: u8 = 0xA;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RemoteRequestKind {
+    Command { command: EmulatorCommand },
+    ReadMemory { address: u32, length: u32 },
+}
+
+#[derive(Deserialize)]
+struct RequestMessage {
+    #[serde(default)]
+    id: Value,
+    #[serde(flatten)]
+    kind: RemoteRequestKind,
+}
+
+#[derive(Debug)]
+pub struct RemoteRequest {
+    client_id: u64,
+    id: Value,
+    pub kind: RemoteRequestKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RemoteReply {
+    Ok,
+    Memory(Vec<u8>),
+    Error(String),
+}
+
+impl RemoteReply {
+    fn to_json(&self, id: &Value) -> String {
+        let value = match self {
+            Self::Ok => json!({ "id": id, "ok": true }),
+            Self::Memory(data) => json!({ "id": id, "ok": true, "data": data }),
+            Self::Error(error) => json!({ "id": id, "ok": false, "error": error }),
+        };
+        value.to_string()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ClientState {
+    // Waiting for the HTTP request that opens the connection
+    Connecting,
+    WebSocket,
+    // Received an HTTP request and waiting for the emulator to handle it
+    HttpPending,
+    Closed,
+}
+
+struct HttpRequest<'a> {
+    method: &'a str,
+    target: &'a str,
+    headers: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> HttpRequest<'a> {
+    fn parse(header: &'a str) -> Option<Self> {
+        let mut lines = header.split("\r\n");
+        let mut request_line = lines.next()?.split(' ');
+        let method = request_line.next()?;
+        let target = request_line.next()?;
+        let headers = lines
+            .filter_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                Some((name.trim(), value.trim()))
+            })
+            .collect();
+
+        Some(Self { method, target, headers })
+    }
+
+    fn header(&self, name: &str) -> Option<&'a str> {
+        self.headers
+            .iter()
+            .find(|(header_name, _)| header_name.eq_ignore_ascii_case(name))
+            .map(|&(_, value)| value)
+    }
+
+    fn token(&self) -> Option<&'a str> {
+        let query_token = self
+            .target
+            .split_once('?')
+            .and_then(|(_, query)| query.split('&').find_map(|param| param.strip_prefix("token=")));
+        query_token.or_else(|| self.header("Authorization")?.strip_prefix("Bearer "))
+    }
+}
+
+// Compare every byte so that response timing does not reveal how much of the token matched
+fn token_matches(expected: &str, actual: &str) -> bool {
+    expected.len() == actual.len()
+        && expected.bytes().zip(actual.bytes()).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+fn websocket_accept(key: &str) -> String {
+    let mut hasher = Sha1::new();
+    hasher.update(key.as_bytes());
+    hasher.update(WEBSOCKET_GUID.as_bytes());
+    general_purpose::STANDARD.encode(hasher.finalize())
+}
+
+struct Frame {
+    fin: bool,
+    opcode: u8,
+    payload: Vec<u8>,
+}
+
+// Parse a frame from the start of the buffer, returning the frame and its length in bytes, or None
+// if the buffer does not contain a full frame yet
+fn parse_frame(buffer: &[u8]) -> Result<Option<(Frame, usize)>, &'static str> {
+    let [first, second, ..] = *buffer else { return Ok(None) };
+
+    if second & 0x80 == 0 {
+        return Err("client frames must be masked");
+    }
+
+    let (payload_len, mut pos) = match second & 0x7F {
+        126 => {
+            let Some(&[a, b]) = buffer.get(2..4) else { return Ok(None) };
+            (u64::from(u16::from_be_bytes([a, b])), 4)
+        }
+        127 => {
+            let Some(bytes) = buffer.get(2..10) else { return Ok(None) };
+            (u64::from_be_bytes(bytes.try_into().unwrap()), 10)
+        }
+        len => (u64::from(len), 2),
+    };
+    if payload_len > MAX_MESSAGE_LEN as u64 {
+        return Err("message is too large");
+    }
+    let payload_len = payload_len as usize;
+
+    let Some(&[m0, m1, m2, m3]) = buffer.get(pos..pos + 4) else { return Ok(None) };
+    let mask = [m0, m1, m2, m3];
+    pos += 4;
+
+    let Some(payload) = buffer.get(pos..pos + payload_len) else { return Ok(None) };
+    let payload =
+        payload.iter().zip(mask.iter().cycle()).map(|(&byte, &mask)| byte ^ mask).collect();
+
+    let frame = Frame { fin: first & 0x80 != 0, opcode: first & 0x0F, payload };
+    Ok(Some((frame, pos + payload_len)))
+}
+
+struct RemoteClient {
+    id: u64,
+    stream: TcpStream,
+    buffer: Vec<u8>,
+    state: ClientState,
+    // Payload received so far of a WebSocket message that was split across multiple frames
+    message: Vec<u8>,
+}
+
+impl RemoteClient {
+    fn new(id: u64, stream: TcpStream) -> io::Result<Self> {
+        stream.set_nonblocking(true)?;
+        stream.set_nodelay(true)?;
+
+        Ok(Self { id, stream, buffer: Vec::new(), state: ClientState::Connecting, message: vec![] })
+    }
+
+    // Returns Ok(false) if the client disconnected
+    fn receive(&mut self) -> io::Result<bool> {
+        let mut read_buffer = [0; 4096];
+        loop {
+            match self.stream.read(&mut read_buffer) {
+                Ok(0) => return Ok(false),
+                Ok(bytes_read) => self.buffer.extend_from_slice(&read_buffer[..bytes_read]),
+                Err(err) if err.kind() == ErrorKind::WouldBlock => return Ok(true),
+                Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    fn send(&mut self, bytes: &[u8]) -> io::Result<()> {
+        // Temporarily switch to blocking mode so that responses are not partially written
+        self.stream.set_nonblocking(false)?;
+        let result = self.stream.write_all(bytes).and_then(|()| self.stream.flush());
+        self.stream.set_nonblocking(true)?;
+        result
+    }
+
+    fn send_http(&mut self, status: &str, body: &str) -> io::Result<()> {
+        self.state = ClientState::Closed;
+
+        let response = format!(
+            "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        );
+        self.send(response.as_bytes())
+    }
+
+    fn send_frame(&mut self, opcode: u8, payload: &[u8]) -> io::Result<()> {
+        let mut frame = vec![0x80 | opcode];
+        match payload.len() {
+            len @ 0..=125 => frame.push(len as u8),
+            len @ 126..=0xFFFF => {
+                frame.push(126);
+                frame.extend_from_slice(&(len as u16).to_be_bytes());
+            }
+            len => {
+                frame.push(127);
+                frame.extend_from_slice(&(len as u64).to_be_bytes());
+            }
+        }
+        frame.extend_from_slice(payload);
+
+        self.send(&frame)
+    }
+
+    fn close_websocket(&mut self) -> io::Result<()> {
+        self.state = ClientState::Closed;
+        self.send_frame(OPCODE_CLOSE, &[])
+    }
+
+    // On failure, returns the error response to send back to the client
+    fn parse_request(&self, message: &[u8]) -> Result<RemoteRequest, String> {
+        match serde_json::from_slice::<RequestMessage>(message) {
+            Ok(RequestMessage { id, kind }) => Ok(RemoteRequest { client_id: self.id, id, kind }),
+            Err(err) => {
+                Err(RemoteReply::Error(format!("Invalid request: {err}")).to_json(&Value::Null))
+            }
+        }
+    }
+
+    fn handle_http_request(
+        &mut self,
+        token: &str,
+        requests: &mut Vec<RemoteRequest>,
+    ) -> io::Result<()> {
+        let Some(header_end) = self.buffer.windows(4).position(|window| window == b"\r\n\r\n")
+        else {
+            if self.buffer.len() > MAX_HEADER_LEN {
+                return self.send_http("431 Request Header Fields Too Large", "");
+            }
+            return Ok(());
+        };
+
+        let header = String::from_utf8_lossy(&self.buffer[..header_end]).into_owned();
+        let Some(request) = HttpRequest::parse(&header) else {
+            return self.send_http("400 Bad Request", "");
+        };
+
+        if !request.token().is_some_and(|request_token| token_matches(token, request_token)) {
+            log::warn!("Rejected remote control connection with missing or invalid token");
+            return self.send_http("401 Unauthorized", "");
+        }
+
+        let body_start = header_end + 4;
+
+        if request
+            .header("Upgrade")
+            .is_some_and(|upgrade| upgrade.eq_ignore_ascii_case("websocket"))
+        {
+            let Some(key) = request.header("Sec-WebSocket-Key") else {
+                return self.send_http("400 Bad Request", "");
+            };
+
+            let response = format!(
+                "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+                websocket_accept(key)
+            );
+            self.send(response.as_bytes())?;
+
+            self.buffer.drain(..body_start);
+            self.state = ClientState::WebSocket;
+            return self.handle_frames(requests);
+        }
+
+        if request.method != "POST" {
+            return self.send_http("405 Method Not Allowed", "");
+        }
+
+        let content_len =
+            request.header("Content-Length").and_then(|len| len.parse::<usize>().ok()).unwrap_or(0);
+        if content_len > MAX_MESSAGE_LEN {
+            return self.send_http("413 Payload Too Large", "");
+        }
+
+        let Some(body) = self.buffer.get(body_start..body_start + content_len) else {
+            // Wait for the rest of the body
+            return Ok(());
+        };
+
+        match self.parse_request(body) {
+            Ok(request) => {
+                self.state = ClientState::HttpPending;
+                requests.push(request);
+                Ok(())
+            }
+            Err(response) => self.send_http("400 Bad Request", &response),
+        }
+    }
+
+    fn handle_frames(&mut self, requests: &mut Vec<RemoteRequest>) -> io::Result<()> {
+        loop {
+            let (frame, frame_len) = match parse_frame(&self.buffer) {
+                Ok(Some(frame)) => frame,
+                Ok(None) => return Ok(()),
+                Err(err) => {
+                    log::error!("Invalid WebSocket frame from remote control client: {err}");
+                    return self.close_websocket();
+                }
+            };
+            self.buffer.drain(..frame_len);
+
+            match frame.opcode {
+                OPCODE_TEXT | OPCODE_CONTINUATION => {
+                    self.message.extend_from_slice(&frame.payload);
+                    if self.message.len() > MAX_MESSAGE_LEN {
+                        log::error!("WebSocket message from remote control client is too large");
+                        return self.close_websocket();
+                    }
+
+                    if !frame.fin {
+                        continue;
+                    }
+
+                    let message = mem::take(&mut self.message);
+                    match self.parse_request(&message) {
+                        Ok(request) => requests.push(request),
+                        Err(response) => self.send_frame(OPCODE_TEXT, response.as_bytes())?,
+                    }
+                }
+                OPCODE_PING => self.send_frame(OPCODE_PONG, &frame.payload)?,
+                OPCODE_PONG => {}
+                OPCODE_CLOSE => return self.close_websocket(),
+                opcode => {
+                    log::error!(
+                        "Unsupported WebSocket opcode from remote control client: {opcode:X}"
+                    );
+                    return self.close_websocket();
+                }
+            }
+        }
+    }
+
+    // Returns Ok(false) if the client disconnected or the connection was closed
+    fn poll(&mut self, token: &str, requests: &mut Vec<RemoteRequest>) -> io::Result<bool> {
+        if self.state == ClientState::Closed {
+            return Ok(false);
+        }
+
+        let connected = self.receive()?;
+
+        match self.state {
+            ClientState::Connecting => self.handle_http_request(token, requests)?,
+            ClientState::WebSocket => self.handle_frames(requests)?,
+            ClientState::HttpPending | ClientState::Closed => {}
+        }
+
+        // Keep pending HTTP clients around until they get a response even if they half-closed
+        Ok(self.state == ClientState::HttpPending
+            || (connected && self.state != ClientState::Closed))
+    }
+
+    fn reply(&mut self, id: &Value, reply: &RemoteReply) -> io::Result<()> {
+        let response = reply.to_json(id);
+        match self.state {
+            ClientState::WebSocket => self.send_frame(OPCODE_TEXT, response.as_bytes()),
+            ClientState::HttpPending => self.send_http("200 OK", &response),
+            ClientState::Connecting | ClientState::Closed => Ok(()),
+        }
+    }
+}
+
+pub struct RemoteControlServer {
+    listener: TcpListener,
+    token: String,
+    clients: Vec<RemoteClient>,
+    next_client_id: u64,
+}
+
+impl RemoteControlServer {
+    /// Start listening for remote control connections on the given localhost port.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if it is unable to bind to the port.
+    pub fn new(port: u16, token: String) -> io::Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+        listener.set_nonblocking(true)?;
+
+        log::info!("Remote control server listening on localhost:{port}");
+
+        Ok(Self { listener, token, clients: vec![], next_client_id: 0 })
+    }
+
+    /// Accept new connections and return any requests received from connected clients. Every
+    /// request should be passed back to [`reply`](Self::reply) once it has been handled.
+    #[must_use]
+    pub fn poll(&mut self) -> Vec<RemoteRequest> {
+        loop {
+            match self.listener.accept() {
+                Ok((stream, addr)) => match RemoteClient::new(self.next_client_id, stream) {
+                    Ok(client) => {
+                        log::info!("Remote control client connected from {addr}");
+                        self.next_client_id += 1;
+                        self.clients.push(client);
+                    }
+                    Err(err) => {
+                        log::error!("Error initializing remote control connection: {err}");
+                    }
+                },
+                Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                Err(err) => {
+                    log::error!("Error accepting remote control connection: {err}");
+                    break;
+                }
+            }
+        }
+
+        let mut requests = Vec::new();
+        let token = &self.token;
+        self.clients.retain_mut(|client| match client.poll(token, &mut requests) {
+            Ok(true) => true,
+            Ok(false) => {
+                log::info!("Remote control client disconnected");
+                false
+            }
+            Err(err) => {
+                log::error!("Remote control connection error, disconnecting: {err}");
+                false
+            }
+        });
+
+        requests
+    }
+
+    /// Send the response to a request returned by [`poll`](Self::poll).
+    pub fn reply(&mut self, request: &RemoteRequest, reply: &RemoteReply) {
+        let Some(client) = self.clients.iter_mut().find(|client| client.id == request.client_id)
+        else {
+            return;
+        };
+
+        if let Err(err) = client.reply(&request.id, reply) {
+            log::error!("Error sending remote control response: {err}");
+            client.state = ClientState::Closed;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MASK: [u8; 4] = [0x37, 0xFA, 0x21, 0x3D];
+
+    // Client frames are always masked
+    fn masked_frame(first: u8, payload: &[u8]) -> Vec<u8> {
+        let mut frame = vec![first];
+        match payload.len() {
+            len @ 0..=125 => frame.push(0x80 | len as u8),
+            len @ 126..=0xFFFF => {
+                frame.push(0xFE);
+                frame.extend_from_slice(&(len as u16).to_be_bytes());
+            }
+            len => {
+                frame.push(0xFF);
+                frame.extend_from_slice(&(len as u64).to_be_bytes());
+            }
+        }
+        frame.extend_from_slice(&MASK);
+        frame.extend(payload.iter().zip(MASK.iter().cycle()).map(|(&byte, &mask)| byte ^ mask));
+        frame
+    }
+
+    fn parse_complete(buffer: &[u8]) -> Frame {
+        let (frame, frame_len) = parse_frame(buffer).unwrap().unwrap();
+        assert_eq!(frame_len, buffer.len());
+        frame
+    }
+
+    #[test]
+    fn masked_frame_example() {
+        // Single-frame masked text message "Hello" from RFC 6455 section 5.7
+        let buffer = [0x81, 0x85, 0x37, 0xFA, 0x21, 0x3D, 0x7F, 0x9F, 0x4D, 0x51, 0x58];
+        let frame = parse_complete(&buffer);
+        assert!(frame.fin);
+        assert_eq!(frame.opcode, OPCODE_TEXT);
+        assert_eq!(frame.payload, b"Hello");
+
+        for len in 0..buffer.len() {
+            assert!(parse_frame(&buffer[..len]).unwrap().is_none(), "prefix of length {len}");
+        }
+    }
+
+    #[test]
+    fn unmasked_frame_rejected() {
+        assert!(parse_frame(&[0x81, 0x05, b'H', b'e', b'l', b'l', b'o']).is_err());
+    }
+
+    #[test]
+    fn extended_payload_lengths() {
+        let payload: Vec<u8> = (0..=255).cycle().take(300).collect();
+        let buffer = masked_frame(0x02, &payload);
+        assert_eq!(buffer[1], 0xFE);
+        let frame = parse_complete(&buffer);
+        assert!(!frame.fin);
+        assert_eq!(frame.opcode, 0x02);
+        assert_eq!(frame.payload, payload);
+        assert!(parse_frame(&buffer[..3]).unwrap().is_none());
+        assert!(parse_frame(&buffer[..buffer.len() - 1]).unwrap().is_none());
+
+        let payload: Vec<u8> = (0..=255).cycle().take(0x10000).collect();
+        let buffer = masked_frame(0x81, &payload);
+        assert_eq!(buffer[1], 0xFF);
+        let frame = parse_complete(&buffer);
+        assert_eq!(frame.payload, payload);
+        assert!(parse_frame(&buffer[..9]).unwrap().is_none());
+        assert!(parse_frame(&buffer[..buffer.len() - 1]).unwrap().is_none());
+
+        // Trailing bytes belong to the next frame
+        let mut buffer = masked_frame(0x81, b"a");
+        buffer.extend(masked_frame(0x81, b"b"));
+        let (frame, frame_len) = parse_frame(&buffer).unwrap().unwrap();
+        assert_eq!(frame.payload, b"a");
+        assert_eq!(frame_len, buffer.len() / 2);
+    }
+
+    #[test]
+    fn oversize_frame_rejected() {
+        // Rejected from the header alone, without waiting for the payload to arrive
+        let mut buffer = vec![0x81, 0xFF];
+        buffer.extend_from_slice(&(MAX_MESSAGE_LEN as u64 + 1).to_be_bytes());
+        assert!(parse_frame(&buffer).is_err());
+
+        let mut buffer = vec![0x81, 0xFF];
+        buffer.extend_from_slice(&u64::MAX.to_be_bytes());
+        assert!(parse_frame(&buffer).is_err());
+
+        let mut buffer = vec![0x81, 0xFF];
+        buffer.extend_from_slice(&(MAX_MESSAGE_LEN as u64).to_be_bytes());
+        assert!(parse_frame(&buffer).unwrap().is_none());
+    }
+
+    fn websocket_client() -> (RemoteClient, TcpStream) {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+
+        let mut remote_client = RemoteClient::new(0, server).unwrap();
+        remote_client.state = ClientState::WebSocket;
+        (remote_client, client)
+    }
+
+    #[test]
+    fn continuation_frames() {
+        let (mut remote_client, _client) = websocket_client();
+        remote_client.buffer.extend(masked_frame(OPCODE_TEXT, br#"{"id": 5, "type": "read_"#));
+        remote_client.buffer.extend(masked_frame(
+            0x80 | OPCODE_CONTINUATION,
+            br#"memory", "address": 16, "length": 2}"#,
+        ));
+
+        let mut requests = Vec::new();
+        remote_client.handle_frames(&mut requests).unwrap();
+
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].id, json!(5));
+        assert!(matches!(
+            requests[0].kind,
+            RemoteRequestKind::ReadMemory { address: 16, length: 2 }
+        ));
+        assert_eq!(remote_client.state, ClientState::WebSocket);
+        assert!(remote_client.buffer.is_empty());
+    }
+
+    #[test]
+    fn oversize_continuation_message_closes_connection() {
+        let (mut remote_client, mut client) = websocket_client();
+
+        // Every frame is under the limit, but the message as a whole is not
+        let payload = vec![b' '; MAX_MESSAGE_LEN / 2 + 1];
+        remote_client.buffer.extend(masked_frame(OPCODE_TEXT, &payload));
+        remote_client.buffer.extend(masked_frame(OPCODE_CONTINUATION, &payload));
+        remote_client.buffer.extend(masked_frame(0x80 | OPCODE_CONTINUATION, b"{}"));
+
+        let mut requests = Vec::new();
+        remote_client.handle_frames(&mut requests).unwrap();
+
+        assert!(requests.is_empty());
+        assert_eq!(remote_client.state, ClientState::Closed);
+
+        let mut close_frame = [0; 2];
+        client.read_exact(&mut close_frame).unwrap();
+        assert_eq!(close_frame, [0x80 | OPCODE_CLOSE, 0]);
+    }
+
+    #[test]
+    fn http_request_parsing() {
+        let request = HttpRequest::parse(
+            "GET /socket?token=abc HTTP/1.1\r\nHost: localhost:9000\r\nupgrade:  websocket \r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nmalformed line",
+        )
+        .unwrap();
+        assert_eq!(request.method, "GET");
+        assert_eq!(request.target, "/socket?token=abc");
+        assert_eq!(request.headers.len(), 3);
+        assert_eq!(request.header("Upgrade"), Some("websocket"));
+        assert_eq!(request.header("HOST"), Some("localhost:9000"));
+        assert_eq!(request.header("Content-Length"), None);
+
+        assert!(HttpRequest::parse("GET").is_none());
+        assert!(HttpRequest::parse("").is_none());
+    }
+
+    #[test]
+    fn websocket_accept_example() {
+        // Handshake example from RFC 6455 section 1.3
+        assert_eq!(websocket_accept("dGhlIHNhbXBsZSBub25jZQ=="), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+    }
+
+    fn request_token(header: &str) -> Option<String> {
+        HttpRequest::parse(header).unwrap().token().map(String::from)
+    }
+
+    #[test]
+    fn query_and_bearer_tokens() {
+        assert_eq!(request_token("GET /?token=abc HTTP/1.1"), Some("abc".into()));
+        assert_eq!(request_token("GET /?id=1&token=abc&x=2 HTTP/1.1"), Some("abc".into()));
+        assert_eq!(
+            request_token("POST / HTTP/1.1\r\nAuthorization: Bearer abc"),
+            Some("abc".into())
+        );
+        assert_eq!(
+            request_token("POST /?token=query HTTP/1.1\r\nAuthorization: Bearer header"),
+            Some("query".into())
+        );
+
+        assert_eq!(request_token("GET / HTTP/1.1"), None);
+        assert_eq!(request_token("GET /?xtoken=abc HTTP/1.1"), None);
+        assert_eq!(request_token("POST / HTTP/1.1\r\nAuthorization: Basic YWJjOmRlZg=="), None);
+    }
+
+    #[test]
+    fn token_comparison() {
+        assert!(token_matches("secret", "secret"));
+        assert!(!token_matches("secret", "secreT"));
+        assert!(!token_matches("secret", "secre"));
+        assert!(!token_matches("secret", "secrets"));
+        assert!(!token_matches("secret", ""));
+        assert!(token_matches("", ""));
+    }
+}
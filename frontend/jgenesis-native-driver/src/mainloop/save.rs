@@ -1,7 +1,9 @@
-use crate::mainloop::bincode_config;
+use crate::config::SaveSyncConfig;
+use crate::mainloop::savesync::SaveSync;
 use bincode::error::{DecodeError, EncodeError};
 use bincode::{Decode, Encode};
 use jgenesis_common::frontend::SaveWriter;
+use jgenesis_common::state::bincode_config;
 use std::collections::HashMap;
 use std::fs::File;
 use std::io::{BufReader, BufWriter, Read, Write};
@@ -58,11 +60,31 @@ struct SavePaths {
 pub struct FsSaveWriter {
     base_path: PathBuf,
     extension_to_paths: HashMap<String, SavePaths>,
+    sync: Option<SaveSync>,
 }
 
 impl FsSaveWriter {
     pub fn new(path: PathBuf) -> Self {
-        Self { base_path: path, extension_to_paths: HashMap::new() }
+        Self { base_path: path, extension_to_paths: HashMap::new(), sync: None }
+    }
+
+    /// Create a save writer that mirrors save files to a remote server if save sync is configured.
+    /// Save files are reconciled with the server immediately, before the emulation core loads
+    /// them, and again when the writer is dropped.
+    pub fn with_sync(path: PathBuf, sync_config: Option<&SaveSyncConfig>) -> Self {
+        let sync = sync_config.and_then(|config| match SaveSync::new(config.clone()) {
+            Ok(sync) => Some(sync),
+            Err(err) => {
+                log::error!("Save sync disabled: {err}");
+                None
+            }
+        });
+
+        if let Some(sync) = &sync {
+            sync.sync_all(&path);
+        }
+
+        Self { base_path: path, extension_to_paths: HashMap::new(), sync }
     }
 
     fn get_or_insert_paths(&mut self, extension: &str) -> &SavePaths {
@@ -143,7 +165,7 @@ impl SaveWriter for FsSaveWriter {
 
     fn load_serialized<D: Decode>(&mut self, extension: &str) -> Result<D, Self::Err> {
         self.read_file(extension, |mut reader, path| {
-            bincode::decode_from_std_read(&mut reader, bincode_config!()).map_err(|source| {
+            bincode::decode_from_std_read(&mut reader, bincode_config()).map_err(|source| {
                 SaveWriteError::Decode { path: path.display().to_string(), source }
             })
         })
@@ -151,7 +173,7 @@ impl SaveWriter for FsSaveWriter {
 
     fn persist_serialized<E: Encode>(&mut self, extension: &str, data: E) -> Result<(), Self::Err> {
         self.write_file(extension, |mut writer, path| {
-            bincode::encode_into_std_write(data, &mut writer, bincode_config!()).map_err(
+            bincode::encode_into_std_write(data, &mut writer, bincode_config()).map_err(
                 |source| SaveWriteError::Encode { path: path.display().to_string(), source },
             )?;
 
@@ -159,3 +181,13 @@ impl SaveWriter for FsSaveWriter {
         })
     }
 }
+
+impl Drop for FsSaveWriter {
+    fn drop(&mut self) {
+        // Frontends persist dirty save memory before dropping the emulator, so this picks up the
+        // final save writes
+        if let Some(sync) = &self.sync {
+            sync.sync_all(&self.base_path);
+        }
+    }
+}
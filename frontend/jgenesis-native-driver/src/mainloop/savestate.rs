@@ -0,0 +1,642 @@
+//! Save state slots, state file thumbnails, and the on-screen slot display
+//!
+//! Slot 0 is the `.ss0` file next to the ROM file and slots 1-9 use extensions `.ss1` through `.ss9`.
+//! Each state file stores a small thumbnail of the frame that was on screen when the state was
+//! saved, which is shown on screen when switching to that slot. The state that was replaced by the
+//! most recent load is kept in memory so that an accidental load can be undone.
+//!
+//! If auto save is enabled, a separate `.ssauto` state is written when the emulator exits and is
+//! loaded the next time the same ROM is launched.
+
+use crate::config::OsdMessages;
+use crate::mainloop::{NativeEmulatorError, NativeEmulatorResult};
+use bincode::{Decode, Encode};
+use jgenesis_common::frontend::{Color, EmulatorTrait, FrameSize, PixelAspectRatio, Renderer};
+use jgenesis_common::state;
+use std::convert::Infallible;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Seek, Write};
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+pub const SAVE_STATE_SLOTS: usize = 10;
+
+const AUTO_SAVE_EXTENSION: &str = "ssauto";
+
+// State files start with this header followed by an optional thumbnail and then the emulator state,
+// which is wrapped in the versioned container from `jgenesis_common::state`. Files from older
+// versions do not have the header, and older files may contain a raw unversioned emulator state
+const STATE_FILE_MAGIC: [u8; 8] = *b"JGSTATE\x01";
+
+// Thumbnails are downscaled by an integer factor until they are at most this wide
+const MAX_THUMBNAIL_WIDTH: u32 = 160;
+
+const OSD_DURATION: Duration = Duration::from_secs(2);
+
+const OSD_TEXT_COLOR: Color = Color::rgb(255, 255, 255);
+
+/// Messages that can be shown in the OSD. The text of each message comes from [`OsdMessages`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OsdMessage {
+    Slot(usize),
+    SlotEmpty(usize),
+    SavedSlot(usize),
+    LoadedSlot(usize),
+    Resumed,
+    UndoLoad,
+    PracticeAttempt(u32),
+    LoopSet,
+    LoopCleared,
+    ControllerConnected,
+    ControllerDisconnected,
+    RecordingMacro,
+    MacroRecorded,
+    MacroEmpty,
+}
+
+impl OsdMessage {
+    fn text(self, messages: &OsdMessages) -> String {
+        let slot_text = |template: &str, slot: usize| template.replace("{slot}", &slot.to_string());
+
+        let text = match self {
+            Self::Slot(slot) => slot_text(&messages.slot, slot),
+            Self::SlotEmpty(slot) => slot_text(&messages.slot_empty, slot),
+            Self::SavedSlot(slot) => slot_text(&messages.saved_slot, slot),
+            Self::LoadedSlot(slot) => slot_text(&messages.loaded_slot, slot),
+            Self::Resumed => messages.resumed.clone(),
+            Self::UndoLoad => messages.undo_load.clone(),
+            Self::PracticeAttempt(attempt) => {
+                messages.practice_attempt.replace("{attempt}", &attempt.to_string())
+            }
+            Self::LoopSet => messages.loop_set.clone(),
+            Self::LoopCleared => messages.loop_cleared.clone(),
+            Self::ControllerConnected => messages.controller_connected.clone(),
+            Self::ControllerDisconnected => messages.controller_disconnected.clone(),
+            Self::RecordingMacro => messages.recording_macro.clone(),
+            Self::MacroRecorded => messages.macro_recorded.clone(),
+            Self::MacroEmpty => messages.macro_empty.clone(),
+        };
+
+        // The OSD font only has uppercase glyphs
+        text.to_uppercase()
+    }
+}
+
+#[derive(Debug, Clone, Encode, Decode)]
+struct Thumbnail {
+    width: u32,
+    height: u32,
+    pixels: Vec<Color>,
+}
+
+impl Thumbnail {
+    fn from_frame(frame_buffer: &[Color], frame_size: FrameSize) -> Self {
+        let step = frame_size.width.div_ceil(MAX_THUMBNAIL_WIDTH).max(1);
+        let width = frame_size.width / step;
+        let height = frame_size.height / step;
+
+        let pixels = (0..height)
+            .flat_map(|y| {
+                (0..width)
+                    .map(move |x| frame_buffer[(y * step * frame_size.width + x * step) as usize])
+            })
+            .collect();
+
+        Self { width, height, pixels }
+    }
+}
+
+// Renderer that captures a thumbnail of the rendered frame instead of displaying it
+#[derive(Default)]
+struct ThumbnailCapture(Option<Thumbnail>);
+
+impl Renderer for ThumbnailCapture {
+    type Err = Infallible;
+
+    fn render_frame(
+        &mut self,
+        frame_buffer: &[Color],
+        frame_size: FrameSize,
+        _pixel_aspect_ratio: Option<PixelAspectRatio>,
+    ) -> Result<(), Self::Err> {
+        self.0 = Some(Thumbnail::from_frame(frame_buffer, frame_size));
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+struct Osd {
+    text: String,
+    thumbnail: Option<Thumbnail>,
+    shown_until: Option<Instant>,
+    // Persistent messages stay visible until hidden or replaced
+    persistent: bool,
+    // Whether the OSD has appeared or disappeared since the last frame was drawn
+    changed: bool,
+    frame_buffer: Vec<Color>,
+}
+
+impl Osd {
+    fn show(&mut self, text: String, thumbnail: Option<Thumbnail>) {
+        self.text = text;
+        self.thumbnail = thumbnail;
+        self.shown_until = Some(Instant::now() + OSD_DURATION);
+        self.persistent = false;
+        self.changed = true;
+    }
+
+    fn show_persistent(&mut self, text: String) {
+        self.show(text, None);
+        self.persistent = true;
+    }
+
+    fn hide(&mut self) {
+        if self.shown_until.is_some() {
+            self.shown_until = None;
+            self.thumbnail = None;
+            self.persistent = false;
+            self.changed = true;
+        }
+    }
+
+    fn expire(&mut self) {
+        if !self.persistent
+            && self.shown_until.is_some_and(|shown_until| Instant::now() >= shown_until)
+        {
+            self.hide();
+        }
+    }
+
+    fn is_visible(&self) -> bool {
+        self.shown_until.is_some()
+    }
+
+    // Draws the OSD box in the top-left corner of a copy of the frame
+    fn draw(&mut self, frame_buffer: &[Color], frame_size: FrameSize) -> &[Color] {
+        let FrameSize { width, height } = frame_size;
+        let frame_len = (width * height) as usize;
+        self.frame_buffer.clear();
+        self.frame_buffer.extend_from_slice(&frame_buffer[..frame_len]);
+
+        let mut canvas = Canvas { pixels: &mut self.frame_buffer, width, height };
+
+        // Scale up for high-resolution frames, e.g. SNES hi-res or interlaced Genesis
+        let scale = (height / 200).max(1);
+        let margin = 4 * scale;
+        let padding = 2 * scale;
+
+        let text_width = (4 * self.text.chars().count() as u32).saturating_sub(1) * scale;
+        let text_height = 5 * scale;
+
+        let (thumbnail_width, thumbnail_height) = match &self.thumbnail {
+            Some(thumbnail) if thumbnail.width != 0 => {
+                let thumbnail_width = (width / 3).max(1);
+                (thumbnail_width, thumbnail.height * thumbnail_width / thumbnail.width)
+            }
+            _ => (0, 0),
+        };
+
+        let box_width = text_width.max(thumbnail_width) + 2 * padding;
+        let mut box_height = text_height + 2 * padding;
+        if thumbnail_height != 0 {
+            box_height += thumbnail_height + padding;
+        }
+
+        for y in margin..margin + box_height {
+            for x in margin..margin + box_width {
+                canvas.map_pixel(x, y, |color| Color::rgb(color.r / 4, color.g / 4, color.b / 4));
+            }
+        }
+
+        canvas.draw_text(&self.text, margin + padding, margin + padding, scale);
+
+        if let Some(thumbnail) = &self.thumbnail {
+            let thumbnail_x = margin + padding;
+            let thumbnail_y = margin + 2 * padding + text_height;
+            for y in 0..thumbnail_height {
+                let src_y = y * thumbnail.height / thumbnail_height;
+                for x in 0..thumbnail_width {
+                    let src_x = x * thumbnail.width / thumbnail_width;
+                    let color = thumbnail.pixels[(src_y * thumbnail.width + src_x) as usize];
+                    canvas.map_pixel(thumbnail_x + x, thumbnail_y + y, |_| color);
+                }
+            }
+        }
+
+        &self.frame_buffer
+    }
+}
+
+struct Canvas<'a> {
+    pixels: &'a mut [Color],
+    width: u32,
+    height: u32,
+}
+
+impl Canvas<'_> {
+    fn map_pixel(&mut self, x: u32, y: u32, f: impl FnOnce(Color) -> Color) {
+        if x < self.width && y < self.height {
+            let pixel = &mut self.pixels[(y * self.width + x) as usize];
+            *pixel = f(*pixel);
+        }
+    }
+
+    fn draw_text(&mut self, text: &str, x: u32, y: u32, scale: u32) {
+        for (i, c) in text.chars().enumerate() {
+            let char_x = x + 4 * scale * i as u32;
+            for (row, bits) in glyph(c).into_iter().enumerate() {
+                for col in 0..3 {
+                    if bits & (0b100 >> col) == 0 {
+                        continue;
+                    }
+
+                    for dy in 0..scale {
+                        for dx in 0..scale {
+                            self.map_pixel(
+                                char_x + col * scale + dx,
+                                y + row as u32 * scale + dy,
+                                |_| OSD_TEXT_COLOR,
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+// 3x5 pixel font covering uppercase letters and digits. Accented letters are drawn without the
+// accent
+fn glyph(c: char) -> [u8; 5] {
+    match c {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b110, 0b001, 0b010, 0b100, 0b111],
+        '3' => [0b110, 0b001, 0b010, 0b001, 0b110],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b110, 0b001, 0b110],
+        '6' => [0b011, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b010, 0b010, 0b010],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b110],
+        'A' | 'Á' | 'À' | 'Â' | 'Ä' | 'Ã' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' | 'Ç' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' | 'É' | 'È' | 'Ê' | 'Ë' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' | 'Í' | 'Ì' | 'Î' | 'Ï' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' | 'Ñ' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' | 'Ó' | 'Ò' | 'Ô' | 'Ö' | 'Õ' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' | 'Ú' | 'Ù' | 'Û' | 'Ü' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        _ => [0; 5],
+    }
+}
+
+/// Renderer wrapper that draws the save state OSD over frames while it is visible.
+pub struct OsdRenderer<'a, R> {
+    renderer: &'a mut R,
+    osd: &'a mut Osd,
+}
+
+impl<R: Renderer> Renderer for OsdRenderer<'_, R> {
+    type Err = R::Err;
+
+    fn render_frame(
+        &mut self,
+        frame_buffer: &[Color],
+        frame_size: FrameSize,
+        pixel_aspect_ratio: Option<PixelAspectRatio>,
+    ) -> Result<(), Self::Err> {
+        self.osd.expire();
+        self.osd.changed = false;
+
+        if !self.osd.is_visible() {
+            return self.renderer.render_frame(frame_buffer, frame_size, pixel_aspect_ratio);
+        }
+
+        let frame_buffer = self.osd.draw(frame_buffer, frame_size);
+        self.renderer.render_frame(frame_buffer, frame_size, pixel_aspect_ratio)
+    }
+}
+
+pub struct SaveStateSlots<Emulator> {
+    // Slot 0 path; other slots differ only in the file extension
+    base_path: PathBuf,
+    slot: usize,
+    osd: Osd,
+    osd_messages: OsdMessages,
+    // State that was replaced by the most recent load state
+    undo_buffer: Option<Emulator>,
+    auto_save: bool,
+    resume_pending: bool,
+}
+
+impl<Emulator> SaveStateSlots<Emulator> {
+    pub fn new(base_path: PathBuf, auto_save: bool, osd_messages: OsdMessages) -> Self {
+        Self {
+            base_path,
+            slot: 0,
+            osd: Osd::default(),
+            osd_messages,
+            undo_buffer: None,
+            auto_save,
+            resume_pending: auto_save,
+        }
+    }
+
+    pub fn set_auto_save(&mut self, auto_save: bool) {
+        self.auto_save = auto_save;
+    }
+
+    pub fn set_osd_messages(&mut self, osd_messages: OsdMessages) {
+        self.osd_messages = osd_messages;
+    }
+
+    fn current_path(&self) -> PathBuf {
+        self.base_path.with_extension(format!("ss{}", self.slot))
+    }
+
+    pub fn select_next_slot(&mut self) {
+        self.select_slot((self.slot + 1) % SAVE_STATE_SLOTS);
+    }
+
+    pub fn select_previous_slot(&mut self) {
+        self.select_slot((self.slot + SAVE_STATE_SLOTS - 1) % SAVE_STATE_SLOTS);
+    }
+
+    fn select_slot(&mut self, slot: usize) {
+        self.slot = slot;
+        log::info!("Selected save state slot {slot}");
+
+        let path = self.current_path();
+        if !path.exists() {
+            self.show_osd(OsdMessage::SlotEmpty(slot), None);
+            return;
+        }
+
+        let thumbnail = read_thumbnail(&path).unwrap_or_else(|err| {
+            log::error!("Error reading save state thumbnail from {}: {err}", path.display());
+            None
+        });
+        self.show_osd(OsdMessage::Slot(slot), thumbnail);
+    }
+
+    /// Save state to the current slot, along with a thumbnail of the current frame.
+    pub fn save(&mut self, emulator: &mut Emulator) -> NativeEmulatorResult<()>
+    where
+        Emulator: EmulatorTrait,
+    {
+        let thumbnail = save_with_thumbnail(&self.current_path(), emulator)?;
+        self.show_osd(OsdMessage::SavedSlot(self.slot), thumbnail);
+
+        Ok(())
+    }
+
+    /// Load state from the current slot. Errors are logged and leave the current emulator state
+    /// unchanged.
+    pub fn load(&mut self, emulator: &mut Emulator, config: &Emulator::Config)
+    where
+        Emulator: EmulatorTrait,
+    {
+        let path = self.current_path();
+        match self.load_from(&path, emulator, config) {
+            Ok(thumbnail) => {
+                self.show_osd(OsdMessage::LoadedSlot(self.slot), thumbnail);
+            }
+            Err(err) => {
+                log::error!("Error loading save state from {}: {err}", path.display());
+            }
+        }
+    }
+
+    // Returns the thumbnail from the state file
+    fn load_from(
+        &mut self,
+        path: &Path,
+        emulator: &mut Emulator,
+        config: &Emulator::Config,
+    ) -> NativeEmulatorResult<Option<Thumbnail>>
+    where
+        Emulator: EmulatorTrait,
+    {
+        let (thumbnail, loaded_emulator) = read_state_file(path)?;
+        self.undo_buffer = Some(replace_state(emulator, loaded_emulator, config));
+
+        Ok(thumbnail)
+    }
+
+    /// If auto save is enabled and this is the first call since the emulator was created, load the
+    /// auto save state if one exists. The resumed state can be reverted with undo load state.
+    ///
+    /// Returns true if a state was loaded.
+    pub fn resume_from_auto_save(
+        &mut self,
+        emulator: &mut Emulator,
+        config: &Emulator::Config,
+    ) -> bool
+    where
+        Emulator: EmulatorTrait,
+    {
+        if !mem::take(&mut self.resume_pending) {
+            return false;
+        }
+
+        let path = self.base_path.with_extension(AUTO_SAVE_EXTENSION);
+        if !path.exists() {
+            return false;
+        }
+
+        match self.load_from(&path, emulator, config) {
+            Ok(thumbnail) => {
+                self.show_osd(OsdMessage::Resumed, thumbnail);
+                true
+            }
+            Err(err) => {
+                log::error!("Error loading auto save state from {}: {err}", path.display());
+                false
+            }
+        }
+    }
+
+    /// Write the auto save state if auto save is enabled. Errors are logged.
+    pub fn write_auto_save(&mut self, emulator: &mut Emulator)
+    where
+        Emulator: EmulatorTrait,
+    {
+        if !self.auto_save {
+            return;
+        }
+
+        let path = self.base_path.with_extension(AUTO_SAVE_EXTENSION);
+        if let Err(err) = save_with_thumbnail(&path, emulator) {
+            log::error!("Error writing auto save state to {}: {err}", path.display());
+        }
+    }
+
+    /// Restore the state from before the most recent load state. Undoing a second time re-applies
+    /// the loaded state.
+    ///
+    /// Returns false if there is no load to undo.
+    pub fn undo_load(&mut self, emulator: &mut Emulator, config: &Emulator::Config) -> bool
+    where
+        Emulator: EmulatorTrait,
+    {
+        let Some(previous_emulator) = self.undo_buffer.take() else {
+            log::info!("No load state to undo");
+            return false;
+        };
+
+        self.undo_buffer = Some(replace_state(emulator, previous_emulator, config));
+        self.show_osd(OsdMessage::UndoLoad, None);
+
+        log::info!("Undid load state");
+
+        true
+    }
+
+    fn show_osd(&mut self, message: OsdMessage, thumbnail: Option<Thumbnail>) {
+        self.osd.show(message.text(&self.osd_messages), thumbnail);
+    }
+
+    /// Show a message in the OSD, e.g. for other features that replace the emulator state.
+    pub fn show_message(&mut self, message: OsdMessage) {
+        self.show_osd(message, None);
+    }
+
+    /// Show a message in the OSD that stays visible until [`Self::hide_message`] is called or
+    /// another message replaces it.
+    pub fn show_persistent_message(&mut self, message: OsdMessage) {
+        self.osd.show_persistent(message.text(&self.osd_messages));
+    }
+
+    pub fn hide_message(&mut self) {
+        self.osd.hide();
+    }
+
+    pub fn osd_renderer<'a, R>(&'a mut self, renderer: &'a mut R) -> OsdRenderer<'a, R> {
+        OsdRenderer { renderer, osd: &mut self.osd }
+    }
+
+    /// Returns true if the OSD has appeared or disappeared since the last rendered frame, in which
+    /// case the frame needs to be redrawn if the emulator is not running.
+    pub fn take_osd_changed(&mut self) -> bool {
+        self.osd.expire();
+        mem::take(&mut self.osd.changed)
+    }
+}
+
+// Returns the thumbnail that was written to the state file
+fn save_with_thumbnail<Emulator: EmulatorTrait>(
+    path: &Path,
+    emulator: &mut Emulator,
+) -> NativeEmulatorResult<Option<Thumbnail>> {
+    let mut capture = ThumbnailCapture::default();
+    emulator.force_render(&mut capture).unwrap_or_else(|err| match err {});
+
+    write_state_file(path, capture.0.as_ref(), emulator)?;
+
+    Ok(capture.0)
+}
+
+// Returns the replaced state, which no longer owns the ROM
+fn replace_state<Emulator: EmulatorTrait>(
+    emulator: &mut Emulator,
+    mut new_emulator: Emulator,
+    config: &Emulator::Config,
+) -> Emulator {
+    new_emulator.take_rom_from(emulator);
+
+    // Force a config reload because the emulator will contain some config fields
+    new_emulator.reload_config(config);
+
+    mem::replace(emulator, new_emulator)
+}
+
+fn write_state_file<E: EmulatorTrait>(
+    path: &Path,
+    thumbnail: Option<&Thumbnail>,
+    emulator: &E,
+) -> NativeEmulatorResult<()> {
+    let state_bytes = state::save_state(emulator)?;
+
+    let mut file = BufWriter::new(File::create(path).map_err(|source| {
+        NativeEmulatorError::StateFileOpen { path: path.display().to_string(), source }
+    })?);
+
+    let conf = state::bincode_config();
+    bincode::encode_into_std_write(STATE_FILE_MAGIC, &mut file, conf)?;
+    bincode::encode_into_std_write(thumbnail, &mut file, conf)?;
+    file.write_all(&state_bytes).and_then(|()| file.flush()).map_err(|source| {
+        NativeEmulatorError::StateFileWrite { path: path.display().to_string(), source }
+    })?;
+
+    log::info!("Saved state to {}", path.display());
+
+    Ok(())
+}
+
+fn read_state_file<E: EmulatorTrait>(path: &Path) -> NativeEmulatorResult<(Option<Thumbnail>, E)> {
+    let mut file = open_state_file(path)?;
+    let thumbnail = read_thumbnail_from(&mut file, path)?;
+
+    let mut state_bytes = Vec::new();
+    file.read_to_end(&mut state_bytes).map_err(|source| NativeEmulatorError::StateFileRead {
+        path: path.display().to_string(),
+        source,
+    })?;
+    let emulator = state::decode_state(&state_bytes)?;
+
+    log::info!("Loaded state from {}", path.display());
+
+    Ok((thumbnail, emulator))
+}
+
+fn read_thumbnail(path: &Path) -> NativeEmulatorResult<Option<Thumbnail>> {
+    let mut file = open_state_file(path)?;
+    read_thumbnail_from(&mut file, path)
+}
+
+fn open_state_file(path: &Path) -> NativeEmulatorResult<BufReader<File>> {
+    File::open(path).map(BufReader::new).map_err(|source| NativeEmulatorError::StateFileOpen {
+        path: path.display().to_string(),
+        source,
+    })
+}
+
+// Leaves the reader positioned at the start of the emulator state
+fn read_thumbnail_from(
+    file: &mut BufReader<File>,
+    path: &Path,
+) -> NativeEmulatorResult<Option<Thumbnail>> {
+    let conf = state::bincode_config();
+    let magic: [u8; 8] = bincode::decode_from_std_read(file, conf)?;
+    if magic == STATE_FILE_MAGIC {
+        return Ok(bincode::decode_from_std_read(file, conf)?);
+    }
+
+    // No header; this file contains only the emulator state
+    file.rewind().map_err(|source| NativeEmulatorError::StateFileOpen {
+        path: path.display().to_string(),
+        source,
+    })?;
+
+    Ok(None)
+}
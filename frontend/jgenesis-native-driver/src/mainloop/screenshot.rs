@@ -0,0 +1,68 @@
+//! Screenshots of the emulator's raw frame output
+//!
+//! Screenshots are saved at the core's native frame size without aspect ratio correction or
+//! shaders, next to the ROM file as e.g. `game_1.png`.
+
+use crate::mainloop::next_dump_path;
+use image::RgbaImage;
+use jgenesis_common::frontend::{Color, FrameSize, PixelAspectRatio, Renderer};
+use std::path::{Path, PathBuf};
+
+pub struct Screenshots {
+    base_path: PathBuf,
+    requested: bool,
+}
+
+impl Screenshots {
+    pub fn new(rom_path: &Path) -> Self {
+        Self { base_path: rom_path.with_extension(""), requested: false }
+    }
+
+    /// Save the next frame that the emulator renders.
+    pub fn request(&mut self) {
+        self.requested = true;
+    }
+
+    pub fn renderer<'a, R>(&'a mut self, renderer: &'a mut R) -> ScreenshotRenderer<'a, R> {
+        ScreenshotRenderer { renderer, screenshots: self }
+    }
+
+    fn save(&mut self, frame_buffer: &[Color], frame_size: FrameSize) {
+        self.requested = false;
+
+        let len = (frame_size.width * frame_size.height) as usize;
+        let bytes = bytemuck::cast_slice(&frame_buffer[..len]).to_vec();
+        let Some(image) = RgbaImage::from_raw(frame_size.width, frame_size.height, bytes) else {
+            return;
+        };
+
+        let path = next_dump_path(&self.base_path, &["png"]);
+        match image.save(&path) {
+            Ok(()) => log::info!("Saved screenshot to '{}'", path.display()),
+            Err(err) => log::error!("Error saving screenshot to '{}': {err}", path.display()),
+        }
+    }
+}
+
+/// Renderer wrapper that saves the next frame if a screenshot was requested.
+pub struct ScreenshotRenderer<'a, R> {
+    renderer: &'a mut R,
+    screenshots: &'a mut Screenshots,
+}
+
+impl<R: Renderer> Renderer for ScreenshotRenderer<'_, R> {
+    type Err = R::Err;
+
+    fn render_frame(
+        &mut self,
+        frame_buffer: &[Color],
+        frame_size: FrameSize,
+        pixel_aspect_ratio: Option<PixelAspectRatio>,
+    ) -> Result<(), Self::Err> {
+        if self.screenshots.requested {
+            self.screenshots.save(frame_buffer, frame_size);
+        }
+
+        self.renderer.render_frame(frame_buffer, frame_size, pixel_aspect_ratio)
+    }
+}
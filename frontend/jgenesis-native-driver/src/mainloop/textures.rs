@@ -0,0 +1,116 @@
+//! Genesis tile texture packs: loading replacement tiles from a directory of PNG files, and dumping
+//! tiles to a directory in the same format
+//!
+//! Each tile is an 8x8 PNG file named after the tile's hash in hex, e.g. `0123456789ABCDEF.png`.
+//! Dumped tiles can be edited in place and the dump directory then used as a texture pack.
+
+use crate::mainloop::{NativeEmulatorError, NativeEmulatorResult};
+use genesis_core::vdp::textures::{TILE_PIXELS, TILE_SIZE};
+use genesis_core::{DumpedTile, TileImage, TileReplacements};
+use image::RgbaImage;
+use jgenesis_common::frontend::Color;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Load all tile images in the given directory. Files that are not named after a tile hash or that
+/// are not valid 8x8 images are skipped with a warning.
+///
+/// # Errors
+///
+/// Returns an error if the directory cannot be read.
+pub fn load_texture_pack(directory: &str) -> NativeEmulatorResult<TileReplacements> {
+    let entries = fs::read_dir(directory).map_err(|source| {
+        NativeEmulatorError::GenesisTexturePackRead { path: directory.into(), source }
+    })?;
+
+    let mut replacements = TileReplacements::new();
+    for entry in entries {
+        let path = match entry {
+            Ok(entry) => entry.path(),
+            Err(err) => {
+                log::warn!("Error reading entry in texture pack directory '{directory}': {err}");
+                continue;
+            }
+        };
+
+        let is_png = path
+            .extension()
+            .and_then(OsStr::to_str)
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("png"));
+        if !is_png {
+            continue;
+        }
+
+        let Some(hash) = path
+            .file_stem()
+            .and_then(OsStr::to_str)
+            .and_then(|stem| u64::from_str_radix(stem, 16).ok())
+        else {
+            log::warn!("Skipping '{}'; file name is not a tile hash", path.display());
+            continue;
+        };
+
+        match load_tile_image(&path) {
+            Ok(image) => replacements.insert(hash, image),
+            Err(err) => log::warn!("Skipping '{}': {err}", path.display()),
+        }
+    }
+
+    log::info!("Loaded {} replacement tiles from '{directory}'", replacements.len());
+
+    Ok(replacements)
+}
+
+fn load_tile_image(path: &Path) -> Result<Box<TileImage>, String> {
+    let image = image::open(path).map_err(|err| err.to_string())?.into_rgba8();
+
+    if image.dimensions() != (TILE_SIZE as u32, TILE_SIZE as u32) {
+        return Err(format!(
+            "expected a {TILE_SIZE}x{TILE_SIZE} image, was {}x{}",
+            image.width(),
+            image.height()
+        ));
+    }
+
+    let mut tile = Box::new([Color::TRANSPARENT; TILE_PIXELS]);
+    for (pixel, &image::Rgba([r, g, b, a])) in tile.iter_mut().zip(image.pixels()) {
+        *pixel = Color::rgba(r, g, b, a);
+    }
+
+    Ok(tile)
+}
+
+pub struct TileDumpWriter<Emulator> {
+    directory: PathBuf,
+    take_tiles_fn: fn(&mut Emulator) -> Vec<DumpedTile>,
+}
+
+impl<Emulator> TileDumpWriter<Emulator> {
+    pub fn new(directory: &str, take_tiles_fn: fn(&mut Emulator) -> Vec<DumpedTile>) -> Self {
+        if let Err(err) = fs::create_dir_all(directory) {
+            log::error!("Error creating tile dump directory '{directory}': {err}");
+        }
+
+        Self { directory: directory.into(), take_tiles_fn }
+    }
+
+    /// Write any tiles that were drawn for the first time during the last frame.
+    pub fn after_frame(&mut self, emulator: &mut Emulator) {
+        for tile in (self.take_tiles_fn)(emulator) {
+            let path = self.directory.join(format!("{:016X}.png", tile.hash));
+            if path.exists() {
+                // Tiles dumped in a previous session may have been edited since
+                continue;
+            }
+
+            let bytes = bytemuck::cast_slice(tile.image.as_slice()).to_vec();
+            let Some(image) = RgbaImage::from_raw(TILE_SIZE as u32, TILE_SIZE as u32, bytes) else {
+                continue;
+            };
+            if let Err(err) = image.save(&path) {
+                log::error!("Error writing dumped tile to '{}': {err}", path.display());
+            }
+        }
+    }
+}
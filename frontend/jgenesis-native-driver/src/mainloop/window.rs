@@ -0,0 +1,222 @@
+//! DPI-aware window sizing and per-system window geometry
+//!
+//! Window sizes in the config and in the saved geometry file are in physical pixels. SDL window
+//! sizes are in window units, which are physical pixels on Windows (the process is per-monitor DPI
+//! aware) and X11, but are scaled points on macOS and Wayland. On those platforms the ratio between
+//! the drawable size and the window size is the display's scale factor.
+//!
+//! The position and size of the emulator window are remembered per system so that e.g. the Game
+//! Boy window reopens where it was last closed rather than at the Genesis window's position.
+
+use crate::config::{DefaultFrame, WindowSize};
+use jgenesis_renderer::renderer::DisplayArea;
+use sdl2::sys::SDL_WindowFlags;
+use sdl2::video::{FullscreenType, Window, WindowPos};
+use sdl2::VideoSubsystem;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+pub const WINDOW_GEOMETRY_FILE: &str = "window-geometry.json";
+
+// The default window shows the default frame at 3x on a display with 100% scaling
+const DEFAULT_FRAME_SCALE: f64 = 3.0;
+
+// DPI that Windows considers 100% scaling
+const WINDOWS_BASE_DPI: f32 = 96.0;
+
+static GEOMETRY_PATH: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// Set the file that window geometry is read from and written to. Window geometry is not
+/// remembered if this is never called.
+pub fn set_geometry_path(path: PathBuf) {
+    if let Ok(mut geometry_path) = GEOMETRY_PATH.lock() {
+        *geometry_path = Some(path);
+    }
+}
+
+fn geometry_path() -> Option<PathBuf> {
+    GEOMETRY_PATH.lock().ok().and_then(|path| path.clone())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct WindowGeometry {
+    // Position in window units
+    x: i32,
+    y: i32,
+    // Size in physical pixels
+    width: u32,
+    height: u32,
+}
+
+fn read_geometry_file() -> HashMap<String, WindowGeometry> {
+    let Some(path) = geometry_path() else { return HashMap::new() };
+    let Ok(contents) = fs::read_to_string(&path) else { return HashMap::new() };
+
+    serde_json::from_str(&contents).unwrap_or_else(|err| {
+        log::warn!("Ignoring invalid window geometry file '{}': {err}", path.display());
+        HashMap::new()
+    })
+}
+
+/// Remembers the emulator window's geometry for one system, and writes it to the geometry file
+/// when dropped if it changed.
+#[derive(Debug)]
+pub struct WindowGeometryTracker {
+    system: &'static str,
+    saved: Option<WindowGeometry>,
+    current: Option<WindowGeometry>,
+}
+
+impl WindowGeometryTracker {
+    pub fn new(system: &'static str) -> Self {
+        let saved = read_geometry_file().get(system).copied();
+        Self { system, saved, current: saved }
+    }
+
+    /// Record the window's current geometry. Fullscreen and maximized windows are ignored so that
+    /// the window returns to its last windowed geometry the next time it opens.
+    pub fn update(&mut self, window: &Window) {
+        let maximized = window.window_flags() & SDL_WindowFlags::SDL_WINDOW_MAXIMIZED as u32 != 0;
+        if window.fullscreen_state() != FullscreenType::Off || maximized {
+            return;
+        }
+
+        let (x, y) = window.position();
+        let WindowSize { width, height } = physical_size(window);
+        self.current = Some(WindowGeometry { x, y, width, height });
+    }
+}
+
+impl Drop for WindowGeometryTracker {
+    fn drop(&mut self) {
+        let Some(current) = self.current else { return };
+        if self.saved == Some(current) {
+            return;
+        }
+
+        let Some(path) = geometry_path() else { return };
+
+        let mut geometry = read_geometry_file();
+        geometry.insert(self.system.into(), current);
+
+        let result = serde_json::to_string_pretty(&geometry)
+            .map_err(|err| err.to_string())
+            .and_then(|json| fs::write(&path, json).map_err(|err| err.to_string()));
+        if let Err(err) = result {
+            log::error!("Error writing window geometry to '{}': {err}", path.display());
+        }
+    }
+}
+
+/// Ratio of physical pixels to window units, e.g. 2.0 for a Retina display on macOS.
+pub fn pixels_per_window_unit(window: &Window) -> f64 {
+    let (window_width, _) = window.size();
+    let (drawable_width, _) = window.drawable_size();
+    if window_width == 0 {
+        return 1.0;
+    }
+
+    f64::from(drawable_width) / f64::from(window_width)
+}
+
+/// The display's scale factor, e.g. 1.5 for a Windows display with 150% scaling.
+pub fn display_scale_factor(window: &Window, video: &VideoSubsystem) -> f64 {
+    let pixels_per_unit = pixels_per_window_unit(window);
+    if pixels_per_unit > 1.0 || !cfg!(target_os = "windows") {
+        return pixels_per_unit;
+    }
+
+    // Window units are physical pixels on Windows, so the scale factor can only be determined
+    // from the display's DPI
+    window
+        .display_index()
+        .and_then(|display_idx| video.display_dpi(display_idx))
+        .map_or(1.0, |(_, horizontal_dpi, _)| f64::from(horizontal_dpi / WINDOWS_BASE_DPI).max(1.0))
+}
+
+fn physical_size(window: &Window) -> WindowSize {
+    let (width, height) = window.drawable_size();
+    WindowSize { width, height }
+}
+
+fn set_physical_size(window: &mut Window, size: WindowSize) -> Result<(), String> {
+    let pixels_per_unit = pixels_per_window_unit(window);
+    let to_units = |pixels: u32| (f64::from(pixels) / pixels_per_unit).round().max(1.0) as u32;
+    window.set_size(to_units(size.width), to_units(size.height)).map_err(|err| err.to_string())
+}
+
+/// Default window size for a system: the default frame scaled by an integer so that pixels scale
+/// evenly, with the integer chosen so that the window appears about the same size at any display
+/// scale factor.
+pub fn default_window_size(default_frame: DefaultFrame, scale_factor: f64) -> WindowSize {
+    let frame_scale = (DEFAULT_FRAME_SCALE * scale_factor).round().max(1.0);
+    let DefaultFrame { frame_size, pixel_aspect_ratio } = default_frame;
+
+    let width = (f64::from(frame_size.width) * pixel_aspect_ratio * frame_scale).round() as u32;
+    let height = (f64::from(frame_size.height) * frame_scale).round() as u32;
+    WindowSize { width, height }
+}
+
+/// Size and position a newly created window, in order of preference using the configured size, the
+/// size and position that this system's window had when it was last closed, or the default size
+/// for the display's scale factor.
+///
+/// # Errors
+///
+/// Propagates any errors from SDL.
+pub fn apply_initial_geometry(
+    window: &mut Window,
+    video: &VideoSubsystem,
+    tracker: &WindowGeometryTracker,
+    configured_size: Option<WindowSize>,
+    default_frame: DefaultFrame,
+) -> Result<(), String> {
+    let saved = tracker.saved;
+    let size = configured_size
+        .or_else(|| saved.map(|saved| WindowSize { width: saved.width, height: saved.height }))
+        .unwrap_or_else(|| default_window_size(default_frame, display_scale_factor(window, video)));
+    set_physical_size(window, size)?;
+
+    // Only restore the position if it is still on a connected display, e.g. not if the window was
+    // last closed on an external monitor that is no longer plugged in
+    match saved.filter(|saved| is_on_any_display(video, saved.x, saved.y)) {
+        Some(saved) => {
+            window.set_position(WindowPos::Positioned(saved.x), WindowPos::Positioned(saved.y));
+        }
+        None => window.set_position(WindowPos::Centered, WindowPos::Centered),
+    }
+
+    log::info!(
+        "Window size {}x{} physical pixels at display scale factor {}",
+        size.width,
+        size.height,
+        display_scale_factor(window, video)
+    );
+
+    Ok(())
+}
+
+fn is_on_any_display(video: &VideoSubsystem, x: i32, y: i32) -> bool {
+    let Ok(num_displays) = video.num_video_displays() else { return false };
+    (0..num_displays).any(|display_idx| {
+        video.display_bounds(display_idx).is_ok_and(|bounds| bounds.contains_point((x, y)))
+    })
+}
+
+/// Convert a display area in physical pixels to window units, which is what SDL uses for mouse
+/// coordinates.
+pub fn display_area_to_window_units(
+    display_area: DisplayArea,
+    pixels_per_unit: f64,
+) -> DisplayArea {
+    let to_units = |pixels: u32| (f64::from(pixels) / pixels_per_unit).round() as u32;
+    DisplayArea {
+        width: to_units(display_area.width),
+        height: to_units(display_area.height),
+        x: to_units(display_area.x),
+        y: to_units(display_area.y),
+    }
+}
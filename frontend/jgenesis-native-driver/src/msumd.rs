@@ -0,0 +1,21 @@
+//! MSU-MD support
+//!
+//! MSU-MD romhacks are ordinary Genesis cartridges that stream CD-quality audio by driving the
+//! Sega CD sub CPU through the expansion port while the console boots from the cartridge (Mode 1).
+//! The audio tracks are distributed as a CD image that has the same file name as the cartridge ROM,
+//! e.g. `game.md` next to `game.cue`, so launching one of these hacks only requires finding that
+//! image and running the Sega CD core in Mode 1.
+
+use std::path::{Path, PathBuf};
+
+// CHD is checked last because MSU-MD packs are almost always distributed as CUE/BIN
+const DISC_EXTENSIONS: [&str; 2] = ["cue", "chd"];
+
+/// Find the MSU-MD track image that accompanies the given cartridge ROM, if there is one.
+#[must_use]
+pub fn find_msu_md_disc(rom_path: &Path) -> Option<PathBuf> {
+    DISC_EXTENSIONS
+        .into_iter()
+        .map(|extension| rom_path.with_extension(extension))
+        .find(|disc_path| disc_path.is_file())
+}
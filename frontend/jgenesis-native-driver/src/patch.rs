@@ -0,0 +1,312 @@
+//! Soft patching: applying IPS and BPS patches (e.g. fan translations and ROM hacks) to a ROM as it
+//! is loaded, without modifying the ROM file
+//!
+//! Multiple patches are applied in order, each to the output of the previous one, so e.g. a
+//! translation can be stacked with a bug fix patch that was made against the translated ROM.
+
+use crc::Crc;
+use std::ffi::OsStr;
+use std::path::Path;
+use std::{fs, io};
+use thiserror::Error;
+
+const CRC: Crc<u32> = Crc::<u32>::new(&crc::CRC_32_ISO_HDLC);
+
+const IPS_MAGIC: &[u8] = b"PATCH";
+const IPS_EOF: &[u8] = b"EOF";
+
+const BPS_MAGIC: &[u8] = b"BPS1";
+const BPS_FOOTER_LEN: usize = 12;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatchFormat {
+    Ips,
+    Bps,
+}
+
+impl PatchFormat {
+    pub const EXTENSIONS: [&'static str; 2] = ["ips", "bps"];
+
+    #[must_use]
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension().and_then(OsStr::to_str)?;
+        match extension.to_ascii_lowercase().as_str() {
+            "ips" => Some(Self::Ips),
+            "bps" => Some(Self::Bps),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum PatchError {
+    #[error("Failed to read patch file at '{path}': {source}")]
+    Read {
+        path: String,
+        #[source]
+        source: io::Error,
+    },
+    #[error("Unsupported patch format for '{0}'; only IPS and BPS patches are supported")]
+    UnsupportedFormat(String),
+    #[error("Invalid IPS patch: {0}")]
+    InvalidIps(&'static str),
+    #[error("Invalid BPS patch: {0}")]
+    InvalidBps(&'static str),
+    #[error(
+        "BPS patch is for a ROM with CRC32 {expected:08X}, but this ROM has CRC32 {actual:08X}; it may be for a different revision or for a ROM without a copier header"
+    )]
+    BpsSourceMismatch { expected: u32, actual: u32 },
+    #[error("BPS patch produced a ROM with CRC32 {actual:08X}, expected {expected:08X}")]
+    BpsTargetMismatch { expected: u32, actual: u32 },
+}
+
+/// Apply the patch files at the given paths to the ROM, in order.
+///
+/// # Errors
+///
+/// Returns an error if any patch file cannot be read, is not a supported format, or is invalid.
+pub fn apply_patches<P: AsRef<Path>>(
+    mut rom: Vec<u8>,
+    patch_paths: &[P],
+) -> Result<Vec<u8>, PatchError> {
+    for patch_path in patch_paths {
+        let patch_path = patch_path.as_ref();
+        let format = PatchFormat::from_path(patch_path)
+            .ok_or_else(|| PatchError::UnsupportedFormat(patch_path.display().to_string()))?;
+        let patch = fs::read(patch_path).map_err(|source| PatchError::Read {
+            path: patch_path.display().to_string(),
+            source,
+        })?;
+
+        rom = apply_patch(rom, &patch, format)?;
+        log::info!("Applied patch '{}'", patch_path.display());
+    }
+
+    Ok(rom)
+}
+
+/// Apply a single patch to the ROM.
+///
+/// # Errors
+///
+/// Returns an error if the patch is invalid, or for BPS patches, if the checksums do not match.
+pub fn apply_patch(rom: Vec<u8>, patch: &[u8], format: PatchFormat) -> Result<Vec<u8>, PatchError> {
+    match format {
+        PatchFormat::Ips => apply_ips(rom, patch),
+        PatchFormat::Bps => apply_bps(&rom, patch),
+    }
+}
+
+struct PatchReader<'a> {
+    patch: &'a [u8],
+    position: usize,
+}
+
+impl<'a> PatchReader<'a> {
+    fn new(patch: &'a [u8], position: usize) -> Self {
+        Self { patch, position }
+    }
+
+    fn bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        let bytes = self.patch.get(self.position..self.position.checked_add(len)?)?;
+        self.position += len;
+        Some(bytes)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.bytes(1).map(|bytes| bytes[0])
+    }
+
+    fn u16_be(&mut self) -> Option<u16> {
+        self.bytes(2).map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u24_be(&mut self) -> Option<u32> {
+        self.bytes(3).map(|bytes| u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]))
+    }
+
+    // BPS variable-length integer: 7 bits per byte, least significant first, with the high bit
+    // marking the last byte and an implicit +1 on each continuation so encodings are unique
+    fn bps_number(&mut self) -> Option<u64> {
+        let mut value: u64 = 0;
+        let mut shift: u64 = 1;
+        loop {
+            let byte = self.u8()?;
+            value = value.checked_add(u64::from(byte & 0x7F).checked_mul(shift)?)?;
+            if byte & 0x80 != 0 {
+                return Some(value);
+            }
+            shift = shift.checked_shl(7)?;
+            value = value.checked_add(shift)?;
+        }
+    }
+}
+
+enum IpsRecord<'a> {
+    Bytes(&'a [u8]),
+    Fill(u8),
+}
+
+fn apply_ips(mut rom: Vec<u8>, patch: &[u8]) -> Result<Vec<u8>, PatchError> {
+    if !patch.starts_with(IPS_MAGIC) {
+        return Err(PatchError::InvalidIps("missing PATCH header"));
+    }
+
+    let mut reader = PatchReader::new(patch, IPS_MAGIC.len());
+    loop {
+        if patch.get(reader.position..reader.position + IPS_EOF.len()) == Some(IPS_EOF) {
+            reader.position += IPS_EOF.len();
+            break;
+        }
+
+        let offset = reader.u24_be().ok_or(PatchError::InvalidIps("missing EOF marker"))? as usize;
+        let len = reader.u16_be().ok_or(PatchError::InvalidIps("truncated record"))?;
+
+        let (len, data) = if len == 0 {
+            // Run-length encoded record
+            let run_len = reader.u16_be().ok_or(PatchError::InvalidIps("truncated RLE record"))?;
+            let value = reader.u8().ok_or(PatchError::InvalidIps("truncated RLE record"))?;
+            (usize::from(run_len), IpsRecord::Fill(value))
+        } else {
+            let data =
+                reader.bytes(len.into()).ok_or(PatchError::InvalidIps("truncated record"))?;
+            (usize::from(len), IpsRecord::Bytes(data))
+        };
+
+        // Records past the end of the ROM expand it
+        let end = offset + len;
+        if rom.len() < end {
+            rom.resize(end, 0);
+        }
+        match data {
+            IpsRecord::Bytes(data) => rom[offset..end].copy_from_slice(data),
+            IpsRecord::Fill(value) => rom[offset..end].fill(value),
+        }
+    }
+
+    // Some IPS patches specify a length to truncate the ROM to after the EOF marker
+    if let Some(truncate_len) = reader.u24_be() {
+        rom.truncate(truncate_len as usize);
+    }
+
+    Ok(rom)
+}
+
+fn apply_bps(source: &[u8], patch: &[u8]) -> Result<Vec<u8>, PatchError> {
+    if !patch.starts_with(BPS_MAGIC) || patch.len() < BPS_MAGIC.len() + BPS_FOOTER_LEN {
+        return Err(PatchError::InvalidBps("missing BPS1 header"));
+    }
+
+    let footer_start = patch.len() - BPS_FOOTER_LEN;
+    let read_crc = |offset: usize| {
+        u32::from_le_bytes(
+            patch[footer_start + offset..footer_start + offset + 4].try_into().unwrap(),
+        )
+    };
+    let expected_source_crc = read_crc(0);
+    let expected_target_crc = read_crc(4);
+    let expected_patch_crc = read_crc(8);
+
+    if CRC.checksum(&patch[..patch.len() - 4]) != expected_patch_crc {
+        return Err(PatchError::InvalidBps("patch checksum does not match; file may be corrupt"));
+    }
+
+    let source_crc = CRC.checksum(source);
+    if source_crc != expected_source_crc {
+        return Err(PatchError::BpsSourceMismatch {
+            expected: expected_source_crc,
+            actual: source_crc,
+        });
+    }
+
+    let invalid_size = PatchError::InvalidBps("invalid size");
+    let mut reader = PatchReader::new(&patch[..footer_start], BPS_MAGIC.len());
+    let source_size = reader.bps_number().ok_or(PatchError::InvalidBps("truncated header"))?;
+    let target_size = reader.bps_number().ok_or(PatchError::InvalidBps("truncated header"))?;
+    let metadata_size = reader.bps_number().ok_or(PatchError::InvalidBps("truncated header"))?;
+    if source_size != source.len() as u64 {
+        return Err(invalid_size);
+    }
+    let target_size = usize::try_from(target_size).map_err(|_| invalid_size)?;
+    reader
+        .bytes(usize::try_from(metadata_size).unwrap_or(usize::MAX))
+        .ok_or(PatchError::InvalidBps("truncated metadata"))?;
+
+    let mut target = Vec::with_capacity(target_size);
+    let mut source_relative_offset: usize = 0;
+    let mut target_relative_offset: usize = 0;
+
+    while reader.position < footer_start {
+        let command = reader.bps_number().ok_or(PatchError::InvalidBps("truncated action"))?;
+        let len = usize::try_from((command >> 2) + 1)
+            .ok()
+            .filter(|&len| target.len() + len <= target_size)
+            .ok_or(PatchError::InvalidBps("action writes past end of output"))?;
+
+        match command & 3 {
+            // SourceRead: copy from the same offset in the source
+            0 => {
+                let start = target.len();
+                let data = source
+                    .get(start..start + len)
+                    .ok_or(PatchError::InvalidBps("SourceRead past end of source"))?;
+                target.extend_from_slice(data);
+            }
+            // TargetRead: copy literal bytes from the patch
+            1 => {
+                let data =
+                    reader.bytes(len).ok_or(PatchError::InvalidBps("truncated TargetRead"))?;
+                target.extend_from_slice(data);
+            }
+            // SourceCopy: copy from anywhere in the source
+            2 => {
+                source_relative_offset =
+                    apply_relative_offset(&mut reader, source_relative_offset)?;
+                let data = source
+                    .get(source_relative_offset..source_relative_offset + len)
+                    .ok_or(PatchError::InvalidBps("SourceCopy past end of source"))?;
+                target.extend_from_slice(data);
+                source_relative_offset += len;
+            }
+            // TargetCopy: copy from earlier in the output; may overlap the bytes being written
+            _ => {
+                target_relative_offset =
+                    apply_relative_offset(&mut reader, target_relative_offset)?;
+                for _ in 0..len {
+                    let byte = *target
+                        .get(target_relative_offset)
+                        .ok_or(PatchError::InvalidBps("TargetCopy past end of output"))?;
+                    target.push(byte);
+                    target_relative_offset += 1;
+                }
+            }
+        }
+    }
+
+    if target.len() != target_size {
+        return Err(PatchError::InvalidBps("output is shorter than the expected size"));
+    }
+
+    let target_crc = CRC.checksum(&target);
+    if target_crc != expected_target_crc {
+        return Err(PatchError::BpsTargetMismatch {
+            expected: expected_target_crc,
+            actual: target_crc,
+        });
+    }
+
+    Ok(target)
+}
+
+// Relative offsets are stored as a magnitude with the sign in the lowest bit
+fn apply_relative_offset(reader: &mut PatchReader<'_>, offset: usize) -> Result<usize, PatchError> {
+    let value = reader.bps_number().ok_or(PatchError::InvalidBps("truncated action"))?;
+    let new_offset = usize::try_from(value >> 1).ok().and_then(|delta| {
+        if value & 1 != 0 {
+            offset.checked_sub(delta)
+        } else {
+            offset.checked_add(delta)
+        }
+    });
+    new_offset.ok_or(PatchError::InvalidBps("invalid relative offset"))
+}
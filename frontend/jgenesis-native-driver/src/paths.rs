@@ -0,0 +1,243 @@
+//! Locations of files that are not tied to a specific ROM: configs, caches, and crash reports.
+//! Save files and save states are always written next to the ROM.
+//!
+//! In portable mode everything is stored next to the executable. Otherwise files are stored in the
+//! platform's standard locations: XDG base directories on Linux and other Unix-likes, Known Folders
+//! on Windows, and `~/Library` on macOS.
+//!
+//! Older versions stored everything in the current working directory; [`AppPaths::find_legacy_files`]
+//! finds files from that layout so that frontends can offer to move them.
+
+use crate::mainloop::{crash, window, CRASH_REPORT_DIR};
+use std::path::{Path, PathBuf};
+use std::{env, fs, io};
+
+const APP_DIR_NAME: &str = "jgenesis";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppDir {
+    /// User settings
+    Config,
+    /// Files that can be regenerated at any time, e.g. the ROM list cache
+    Cache,
+    /// Files that should persist but are not settings, e.g. crash reports
+    State,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppPaths {
+    pub portable: bool,
+    pub config_dir: PathBuf,
+    pub cache_dir: PathBuf,
+    pub state_dir: PathBuf,
+}
+
+/// A file or directory in the legacy working directory layout that has not yet been moved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LegacyFile {
+    pub from: PathBuf,
+    pub to: PathBuf,
+}
+
+impl AppPaths {
+    #[must_use]
+    pub fn resolve(portable: bool) -> Self {
+        if portable {
+            Self::portable()
+        } else {
+            Self::platform()
+        }
+    }
+
+    /// All files next to the executable, or in the working directory if the executable path cannot
+    /// be determined.
+    #[must_use]
+    pub fn portable() -> Self {
+        let exe_dir = env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(Path::to_path_buf))
+            .unwrap_or_else(|| PathBuf::from("."));
+
+        Self {
+            portable: true,
+            config_dir: exe_dir.clone(),
+            cache_dir: exe_dir.clone(),
+            state_dir: exe_dir,
+        }
+    }
+
+    /// The platform's standard per-user locations. Falls back to portable mode if the user's home
+    /// directory cannot be determined.
+    #[must_use]
+    pub fn platform() -> Self {
+        match platform_dirs() {
+            Some((config_dir, cache_dir, state_dir)) => Self {
+                portable: false,
+                config_dir: config_dir.join(APP_DIR_NAME),
+                cache_dir: cache_dir.join(APP_DIR_NAME),
+                state_dir: state_dir.join(APP_DIR_NAME),
+            },
+            None => {
+                log::warn!("Unable to determine user directories; falling back to portable mode");
+                Self::portable()
+            }
+        }
+    }
+
+    #[must_use]
+    pub fn dir(&self, dir: AppDir) -> &Path {
+        match dir {
+            AppDir::Config => &self.config_dir,
+            AppDir::Cache => &self.cache_dir,
+            AppDir::State => &self.state_dir,
+        }
+    }
+
+    #[must_use]
+    pub fn crash_report_dir(&self) -> PathBuf {
+        self.state_dir.join(CRASH_REPORT_DIR)
+    }
+
+    /// Create the directories if they do not exist and direct process-wide output (crash reports,
+    /// window geometry) into them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any directory cannot be created.
+    pub fn install(&self) -> io::Result<()> {
+        for dir in [&self.config_dir, &self.cache_dir, &self.state_dir] {
+            fs::create_dir_all(dir)?;
+        }
+
+        crash::set_report_dir(self.crash_report_dir());
+        window::set_geometry_path(self.state_dir.join(window::WINDOW_GEOMETRY_FILE));
+
+        log::info!(
+            "Using {} paths: config '{}', cache '{}', state '{}'",
+            if self.portable { "portable" } else { "platform" },
+            self.config_dir.display(),
+            self.cache_dir.display(),
+            self.state_dir.display()
+        );
+
+        Ok(())
+    }
+
+    /// Find files with the given names in the current working directory that do not exist at their
+    /// new location yet. Crash reports are always included.
+    #[must_use]
+    pub fn find_legacy_files(&self, names: &[(&str, AppDir)]) -> Vec<LegacyFile> {
+        let Ok(working_dir) = env::current_dir() else { return vec![] };
+
+        names
+            .iter()
+            .copied()
+            .chain([(CRASH_REPORT_DIR, AppDir::State)])
+            .filter_map(|(name, dir)| {
+                let from = working_dir.join(name);
+                let to = self.dir(dir).join(name);
+                (from.exists() && !to.exists() && !same_file(&from, &to))
+                    .then_some(LegacyFile { from, to })
+            })
+            .collect()
+    }
+}
+
+impl LegacyFile {
+    /// Move the file to its new location, copying and then deleting it if it is on a different
+    /// filesystem.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be moved.
+    pub fn migrate(&self) -> io::Result<()> {
+        if let Some(parent) = self.to.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        if fs::rename(&self.from, &self.to).is_ok() {
+            return Ok(());
+        }
+
+        copy_recursive(&self.from, &self.to)?;
+        if self.from.is_dir() {
+            fs::remove_dir_all(&self.from)
+        } else {
+            fs::remove_file(&self.from)
+        }
+    }
+}
+
+fn copy_recursive(from: &Path, to: &Path) -> io::Result<()> {
+    if !from.is_dir() {
+        return fs::copy(from, to).map(|_| ());
+    }
+
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        copy_recursive(&entry.path(), &to.join(entry.file_name()))?;
+    }
+
+    Ok(())
+}
+
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+fn env_dir(var: &str) -> Option<PathBuf> {
+    env::var_os(var).map(PathBuf::from).filter(|path| path.is_absolute())
+}
+
+// Returns (config, cache, state)
+#[cfg(all(unix, not(target_os = "macos")))]
+fn platform_dirs() -> Option<(PathBuf, PathBuf, PathBuf)> {
+    let home = env_dir("HOME")?;
+    Some((
+        env_dir("XDG_CONFIG_HOME").unwrap_or_else(|| home.join(".config")),
+        env_dir("XDG_CACHE_HOME").unwrap_or_else(|| home.join(".cache")),
+        env_dir("XDG_STATE_HOME").unwrap_or_else(|| home.join(".local/state")),
+    ))
+}
+
+#[cfg(target_os = "macos")]
+fn platform_dirs() -> Option<(PathBuf, PathBuf, PathBuf)> {
+    let library = env_dir("HOME")?.join("Library");
+    let app_support = library.join("Application Support");
+    Some((app_support.clone(), library.join("Caches"), app_support))
+}
+
+#[cfg(target_os = "windows")]
+fn platform_dirs() -> Option<(PathBuf, PathBuf, PathBuf)> {
+    use windows::Win32::UI::Shell::{FOLDERID_LocalAppData, FOLDERID_RoamingAppData};
+
+    // Settings roam with the user profile; caches and crash reports stay on this machine
+    let roaming = known_folder(&FOLDERID_RoamingAppData)?;
+    let local = known_folder(&FOLDERID_LocalAppData)?;
+    Some((roaming, local.clone(), local))
+}
+
+#[cfg(target_os = "windows")]
+fn known_folder(id: &windows::core::GUID) -> Option<PathBuf> {
+    use windows::Win32::Foundation::HANDLE;
+    use windows::Win32::System::Com::CoTaskMemFree;
+    use windows::Win32::UI::Shell::{SHGetKnownFolderPath, KF_FLAG_DEFAULT};
+
+    // SAFETY: The returned string is only read before it is freed, and it is freed with
+    // CoTaskMemFree as SHGetKnownFolderPath requires
+    unsafe {
+        let path = SHGetKnownFolderPath(id, KF_FLAG_DEFAULT, HANDLE::default()).ok()?;
+        let result = path.to_string().ok().map(PathBuf::from);
+        CoTaskMemFree(Some(path.as_ptr().cast_const().cast()));
+        result
+    }
+}
+
+#[cfg(not(any(unix, target_os = "windows")))]
+fn platform_dirs() -> Option<(PathBuf, PathBuf, PathBuf)> {
+    None
+}
@@ -0,0 +1,51 @@
+//! Steam Deck / gamescope integration
+//!
+//! Steam Deck mode can be explicitly enabled or disabled, or detected automatically from the
+//! environment variables that Steam sets when running under gamescope. While active, the emulator
+//! launches fullscreen with integer scaling, maps player 1 to the built-in controls if no gamepad
+//! inputs are configured, and sleeps between audio queue checks rather than polling.
+
+use jgenesis_proc_macros::{EnumDisplay, EnumFromStr};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::process::Command;
+
+/// SDL joystick name of the Steam Deck's built-in controls when running through Steam Input.
+pub const CONTROLLER_NAME: &str = "Steam Deck";
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, EnumDisplay, EnumFromStr,
+)]
+pub enum SteamDeckMode {
+    #[default]
+    Auto,
+    Enabled,
+    Disabled,
+}
+
+impl SteamDeckMode {
+    #[must_use]
+    pub fn is_active(self) -> bool {
+        match self {
+            Self::Auto => is_steam_deck(),
+            Self::Enabled => true,
+            Self::Disabled => false,
+        }
+    }
+}
+
+/// Whether the process appears to be running on a Steam Deck or in Steam's gamescope session.
+#[must_use]
+pub fn is_steam_deck() -> bool {
+    env::var("SteamDeck").is_ok_and(|value| value == "1")
+        || env::var_os("SteamGamepadUI").is_some()
+        || env::var_os("GAMESCOPE_WAYLAND_DISPLAY").is_some()
+}
+
+/// Ask Steam to show its on-screen keyboard. There is no keyboard otherwise when running in
+/// Gaming Mode, so this should be called whenever a text field gains focus.
+pub fn open_onscreen_keyboard() {
+    if let Err(err) = Command::new("xdg-open").arg("steam://open/keyboard").spawn() {
+        log::error!("Error opening Steam on-screen keyboard: {err}");
+    }
+}
@@ -0,0 +1,169 @@
+//! Python bindings for running the emulation cores headlessly, e.g. for reinforcement learning
+//! environments or automated regression tests
+//!
+//! ```python
+//! import jgenesis
+//!
+//! emulator = jgenesis.Emulator("genesis", open("sonic.md", "rb").read())
+//! emulator.set_buttons(0, jgenesis.BUTTON_RIGHT | jgenesis.BUTTON_B)
+//! emulator.step(60)
+//! frame = emulator.frame()  # numpy uint8 array with shape (height, width, 4)
+//! ring_count = emulator.read_memory(0xFFFE20, 2)
+//! ```
+
+use jgenesis_capi::emulator::{self, Emulator, EmulatorError, System};
+use numpy::{PyArray1, PyArrayDyn, PyArrayMethods};
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use std::path::PathBuf;
+
+fn to_py_err(err: EmulatorError) -> PyErr {
+    PyRuntimeError::new_err(err.to_string())
+}
+
+fn parse_system(system: &str) -> PyResult<System> {
+    match system.to_ascii_lowercase().replace('-', "_").as_str() {
+        "master_system" | "sms" => Ok(System::MasterSystem),
+        "game_gear" | "gg" => Ok(System::GameGear),
+        "genesis" | "mega_drive" => Ok(System::Genesis),
+        "nes" => Ok(System::Nes),
+        "snes" => Ok(System::Snes),
+        "game_boy" | "gb" | "gbc" => Ok(System::GameBoy),
+        "sega_cd" => Err(PyValueError::new_err("use Emulator.sega_cd() to create a Sega CD emulator")),
+        _ => Err(PyValueError::new_err(format!(
+            "invalid system '{system}'; expected one of master_system, game_gear, genesis, nes, snes, game_boy"
+        ))),
+    }
+}
+
+/// A headless emulator. Inputs are set with `set_buttons` and persist until changed.
+#[pyclass(name = "Emulator", unsendable)]
+struct PyEmulator {
+    emulator: Emulator,
+}
+
+#[pymethods]
+impl PyEmulator {
+    /// Create an emulator for a cartridge-based system from ROM bytes, optionally with an existing
+    /// battery-backed save file.
+    #[new]
+    #[pyo3(signature = (system, rom, sram = None))]
+    fn new(system: &str, rom: &[u8], sram: Option<&[u8]>) -> PyResult<Self> {
+        let system = parse_system(system)?;
+        let emulator =
+            Emulator::create(system, rom.to_vec(), sram.map(<[u8]>::to_vec)).map_err(to_py_err)?;
+        Ok(Self { emulator })
+    }
+
+    /// Create a Sega CD emulator from BIOS bytes and the path to a CUE or CHD disc image.
+    #[staticmethod]
+    #[pyo3(signature = (bios, disc_path, backup_ram = None))]
+    fn sega_cd(bios: &[u8], disc_path: PathBuf, backup_ram: Option<&[u8]>) -> PyResult<Self> {
+        let emulator =
+            Emulator::create_sega_cd(bios.to_vec(), &disc_path, backup_ram.map(<[u8]>::to_vec))
+                .map_err(to_py_err)?;
+        Ok(Self { emulator })
+    }
+
+    /// Set the pressed buttons for a player (0-3) as a bitmask of the BUTTON_* constants.
+    fn set_buttons(&mut self, player: usize, buttons: u32) {
+        self.emulator.set_buttons(player, buttons);
+    }
+
+    /// Run the given number of frames. Only the last frame's video and audio are kept.
+    #[pyo3(signature = (frames = 1))]
+    fn step(&mut self, py: Python<'_>, frames: u32) -> PyResult<()> {
+        for _ in 0..frames {
+            self.emulator.run_frame().map_err(to_py_err)?;
+            py.check_signals()?;
+        }
+        Ok(())
+    }
+
+    /// The most recently rendered frame as an RGBA uint8 array with shape (height, width, 4).
+    fn frame<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyArrayDyn<u8>>> {
+        let (pixels, frame_size) = self.emulator.frame();
+        PyArray1::from_slice(py, pixels).reshape(vec![
+            frame_size.height as usize,
+            frame_size.width as usize,
+            4,
+        ])
+    }
+
+    /// Audio samples generated during the most recent frame as a float32 array with shape
+    /// (samples, 2), at 48000 Hz.
+    fn audio<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyArrayDyn<f32>>> {
+        let samples = self.emulator.audio_samples();
+        PyArray1::from_slice(py, samples).reshape(vec![samples.len() / 2, 2])
+    }
+
+    /// Pixel aspect ratio of the most recent frame, or None if it should be stretched to fill the
+    /// display.
+    fn pixel_aspect_ratio(&self) -> Option<f64> {
+        self.emulator.pixel_aspect_ratio()
+    }
+
+    /// Read bytes from the main CPU's address bus without side effects. Genesis and SNES only.
+    fn read_memory<'py>(
+        &mut self,
+        py: Python<'py>,
+        address: u32,
+        len: usize,
+    ) -> PyResult<Bound<'py, PyBytes>> {
+        let mut bytes = vec![0; len];
+        self.emulator.read_memory(address, &mut bytes).map_err(to_py_err)?;
+        Ok(PyBytes::new(py, &bytes))
+    }
+
+    /// Write bytes to the main CPU's address bus. Genesis and SNES only.
+    fn write_memory(&mut self, address: u32, data: &[u8]) -> PyResult<()> {
+        self.emulator.write_memory(address, data).map_err(to_py_err)
+    }
+
+    fn save_state<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        let state = self.emulator.save_state().map_err(to_py_err)?;
+        Ok(PyBytes::new(py, &state))
+    }
+
+    fn load_state(&mut self, state: &[u8]) -> PyResult<()> {
+        self.emulator.load_state(state).map_err(to_py_err)
+    }
+
+    /// The battery-backed save file (or Sega CD backup RAM) as of the last time the game wrote it.
+    fn save_file<'py>(&self, py: Python<'py>) -> Option<Bound<'py, PyBytes>> {
+        self.emulator.save_file("sav").map(|sav| PyBytes::new(py, sav))
+    }
+
+    fn soft_reset(&mut self) {
+        self.emulator.soft_reset();
+    }
+
+    fn hard_reset(&mut self) {
+        self.emulator.hard_reset();
+    }
+}
+
+#[pymodule]
+fn jgenesis(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyEmulator>()?;
+
+    m.add("AUDIO_SAMPLE_RATE", emulator::AUDIO_SAMPLE_RATE)?;
+
+    m.add("BUTTON_UP", emulator::BUTTON_UP)?;
+    m.add("BUTTON_DOWN", emulator::BUTTON_DOWN)?;
+    m.add("BUTTON_LEFT", emulator::BUTTON_LEFT)?;
+    m.add("BUTTON_RIGHT", emulator::BUTTON_RIGHT)?;
+    m.add("BUTTON_A", emulator::BUTTON_A)?;
+    m.add("BUTTON_B", emulator::BUTTON_B)?;
+    m.add("BUTTON_C", emulator::BUTTON_C)?;
+    m.add("BUTTON_X", emulator::BUTTON_X)?;
+    m.add("BUTTON_Y", emulator::BUTTON_Y)?;
+    m.add("BUTTON_Z", emulator::BUTTON_Z)?;
+    m.add("BUTTON_L", emulator::BUTTON_L)?;
+    m.add("BUTTON_R", emulator::BUTTON_R)?;
+    m.add("BUTTON_START", emulator::BUTTON_START)?;
+    m.add("BUTTON_SELECT", emulator::BUTTON_SELECT)?;
+
+    Ok(())
+}
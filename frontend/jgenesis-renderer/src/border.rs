@@ -0,0 +1,217 @@
+//! Border / bezel artwork that is drawn around the game display area
+//!
+//! If a border image contains a fully transparent region at its center, the game is displayed
+//! inside that region. Otherwise the game is displayed centered in the window on top of the border.
+
+use jgenesis_common::frontend::Color;
+use std::path::Path;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum BorderImageError {
+    #[error("Error loading border image: {0}")]
+    Load(#[from] image::ImageError),
+    #[error("Border image has invalid dimensions {width}x{height}")]
+    InvalidDimensions { width: u32, height: u32 },
+}
+
+/// A rectangular region within a border image, in image pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScreenArea {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct BorderImage {
+    width: u32,
+    height: u32,
+    pixels: Vec<Color>,
+    screen_area: Option<ScreenArea>,
+}
+
+impl BorderImage {
+    /// Create a border image from RGBA pixels in row-major order.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if either dimension is 0 or if the number of pixels does
+    /// not match the dimensions.
+    pub fn new(width: u32, height: u32, pixels: Vec<Color>) -> Result<Self, BorderImageError> {
+        if width == 0 || height == 0 || pixels.len() != (width * height) as usize {
+            return Err(BorderImageError::InvalidDimensions { width, height });
+        }
+
+        let screen_area = find_screen_area(width, height, &pixels);
+        Ok(Self { width, height, pixels, screen_area })
+    }
+
+    /// Load a border image from a PNG file.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file cannot be read or decoded.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, BorderImageError> {
+        let image = image::open(path)?.into_rgba8();
+        let (width, height) = image.dimensions();
+        let pixels =
+            image.pixels().map(|&image::Rgba([r, g, b, a])| Color::rgba(r, g, b, a)).collect();
+
+        Self::new(width, height, pixels)
+    }
+
+    /// Built-in default border for Game Gear: a simplified handheld shell with the screen cut out.
+    #[must_use]
+    pub fn game_gear() -> Self {
+        game_gear_shell()
+    }
+
+    #[must_use]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    #[must_use]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    #[must_use]
+    pub fn pixels(&self) -> &[Color] {
+        &self.pixels
+    }
+
+    /// The region of the image that the game should be displayed in, if any.
+    #[must_use]
+    pub fn screen_area(&self) -> Option<ScreenArea> {
+        self.screen_area
+    }
+}
+
+// The screen area is the largest transparent rectangle that runs through the center of the image,
+// found by scanning outwards from the center pixel along the center row and column. Scanning from
+// the center rather than taking the bounding box of all transparent pixels means that transparent
+// corners (e.g. on a rounded shell) are not mistaken for part of the screen
+fn find_screen_area(width: u32, height: u32, pixels: &[Color]) -> Option<ScreenArea> {
+    let is_transparent = |x: u32, y: u32| pixels[(y * width + x) as usize].a == 0;
+
+    let center_x = width / 2;
+    let center_y = height / 2;
+    if !is_transparent(center_x, center_y) {
+        return None;
+    }
+
+    let left =
+        (0..center_x).rev().take_while(|&x| is_transparent(x, center_y)).last().unwrap_or(center_x);
+    let right =
+        (center_x..width).take_while(|&x| is_transparent(x, center_y)).last().unwrap_or(center_x);
+    let top =
+        (0..center_y).rev().take_while(|&y| is_transparent(center_x, y)).last().unwrap_or(center_y);
+    let bottom =
+        (center_y..height).take_while(|&y| is_transparent(center_x, y)).last().unwrap_or(center_y);
+
+    Some(ScreenArea { x: left, y: top, width: right - left + 1, height: bottom - top + 1 })
+}
+
+const GG_WIDTH: u32 = 400;
+const GG_HEIGHT: u32 = 240;
+const GG_CORNER_RADIUS: u32 = 48;
+
+// Screen cutout is 4:3 to match the Game Gear LCD at its default pixel aspect ratio
+const GG_SCREEN: ScreenArea = ScreenArea { x: 104, y: 48, width: 192, height: 144 };
+const GG_BEZEL_WIDTH: u32 = 12;
+
+const GG_SHELL_COLOR: Color = Color::rgb(40, 40, 46);
+const GG_BEZEL_COLOR: Color = Color::rgb(18, 18, 22);
+const GG_DPAD_COLOR: Color = Color::rgb(24, 24, 28);
+const GG_BUTTON_COLOR: Color = Color::rgb(48, 60, 140);
+const GG_START_COLOR: Color = Color::rgb(140, 40, 60);
+
+fn game_gear_shell() -> BorderImage {
+    let mut pixels = vec![Color::TRANSPARENT; (GG_WIDTH * GG_HEIGHT) as usize];
+
+    for y in 0..GG_HEIGHT {
+        for x in 0..GG_WIDTH {
+            let color = if !in_rounded_rect(x, y, GG_WIDTH, GG_HEIGHT, GG_CORNER_RADIUS)
+                || in_rect(x, y, GG_SCREEN, 0)
+            {
+                Color::TRANSPARENT
+            } else if in_rect(x, y, GG_SCREEN, GG_BEZEL_WIDTH) {
+                GG_BEZEL_COLOR
+            } else if in_dpad(x, y, 52, 120) {
+                GG_DPAD_COLOR
+            } else if in_circle(x, y, 336, 132, 13) || in_circle(x, y, 366, 110, 13) {
+                GG_BUTTON_COLOR
+            } else if in_circle(x, y, 340, 72, 6) {
+                GG_START_COLOR
+            } else {
+                GG_SHELL_COLOR
+            };
+            pixels[(y * GG_WIDTH + x) as usize] = color;
+        }
+    }
+
+    BorderImage { width: GG_WIDTH, height: GG_HEIGHT, pixels, screen_area: Some(GG_SCREEN) }
+}
+
+fn in_rect(x: u32, y: u32, rect: ScreenArea, margin: u32) -> bool {
+    x + margin >= rect.x
+        && x < rect.x + rect.width + margin
+        && y + margin >= rect.y
+        && y < rect.y + rect.height + margin
+}
+
+fn in_circle(x: u32, y: u32, center_x: u32, center_y: u32, radius: u32) -> bool {
+    let dx = x.abs_diff(center_x);
+    let dy = y.abs_diff(center_y);
+    dx * dx + dy * dy <= radius * radius
+}
+
+fn in_dpad(x: u32, y: u32, center_x: u32, center_y: u32) -> bool {
+    const ARM_LENGTH: u32 = 24;
+    const ARM_WIDTH: u32 = 8;
+
+    let dx = x.abs_diff(center_x);
+    let dy = y.abs_diff(center_y);
+    (dx <= ARM_LENGTH && dy <= ARM_WIDTH) || (dx <= ARM_WIDTH && dy <= ARM_LENGTH)
+}
+
+fn in_rounded_rect(x: u32, y: u32, width: u32, height: u32, radius: u32) -> bool {
+    let corner_x = if x < radius {
+        radius
+    } else if x >= width - radius {
+        width - radius - 1
+    } else {
+        return true;
+    };
+    let corner_y = if y < radius {
+        radius
+    } else if y >= height - radius {
+        height - radius - 1
+    } else {
+        return true;
+    };
+
+    in_circle(x, y, corner_x, corner_y, radius)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn game_gear_screen_area_detected() {
+        let border = BorderImage::game_gear();
+        let detected = find_screen_area(border.width, border.height, &border.pixels);
+        assert_eq!(detected, Some(GG_SCREEN));
+    }
+
+    #[test]
+    fn opaque_center_has_no_screen_area() {
+        let pixels = vec![Color::rgb(255, 255, 255); 16];
+        let border = BorderImage::new(4, 4, pixels).unwrap();
+        assert_eq!(border.screen_area(), None);
+    }
+}
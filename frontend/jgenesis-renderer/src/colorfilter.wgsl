@@ -0,0 +1,35 @@
+struct ColorFilterParams {
+    // Rows of the color correction matrix; the 4th component of each row is unused padding
+    matrix_r: vec4f,
+    matrix_g: vec4f,
+    matrix_b: vec4f,
+    // Weight of the current frame when blending with the previous output; the other components
+    // are unused padding
+    blend: vec4f,
+}
+
+@group(0) @binding(0)
+var texture_in: texture_2d<f32>;
+@group(0) @binding(1)
+var previous_output: texture_2d<f32>;
+@group(0) @binding(2)
+var<uniform> params: ColorFilterParams;
+
+@fragment
+fn color_filter(@builtin(position) position: vec4f) -> @location(0) vec4f {
+    let t_position = vec2u(u32(round(position.x - 0.5)), u32(round(position.y - 0.5)));
+
+    let color = textureLoad(texture_in, t_position, 0).rgb;
+    let corrected = clamp(
+        vec3f(
+            dot(params.matrix_r.rgb, color),
+            dot(params.matrix_g.rgb, color),
+            dot(params.matrix_b.rgb, color),
+        ),
+        vec3f(0.0),
+        vec3f(1.0),
+    );
+
+    let previous = textureLoad(previous_output, t_position, 0).rgb;
+    return vec4f(mix(previous, corrected, params.blend.x), 1.0);
+}
@@ -98,13 +98,17 @@ pub enum FilterMode {
     Nearest,
     #[default]
     Linear,
+    /// Nearest neighbor within each texel, with linear interpolation only at texel edges. Avoids
+    /// both the blurriness of linear filtering and the uneven pixel sizes of nearest neighbor
+    /// filtering at non-integer scales
+    SharpBilinear,
 }
 
 impl FilterMode {
     pub(crate) fn to_wgpu_filter_mode(self) -> wgpu::FilterMode {
         match self {
             Self::Nearest => wgpu::FilterMode::Nearest,
-            Self::Linear => wgpu::FilterMode::Linear,
+            Self::Linear | Self::SharpBilinear => wgpu::FilterMode::Linear,
         }
     }
 }
@@ -122,6 +126,44 @@ pub enum PreprocessShader {
     AntiDitherStrong,
 }
 
+/// Color blindness correction. Colors are shifted so that differences which would otherwise be
+/// invisible with the given color vision deficiency are moved into colors that can be distinguished
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, EnumDisplay, EnumFromStr,
+)]
+pub enum ColorBlindFilter {
+    #[default]
+    None,
+    Protanopia,
+    Deuteranopia,
+    Tritanopia,
+}
+
+/// Accessibility magnifier that draws a zoomed view of the area around a focus point, which follows
+/// the mouse cursor in frontends that support it
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, EnumDisplay, EnumFromStr,
+)]
+pub enum MagnifierMode {
+    #[default]
+    None,
+    /// Zoomed view in a corner of the screen, drawn over the game with a high-contrast outline
+    PictureInPicture,
+    /// Zoom the entire display
+    FullScreen,
+}
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, EnumDisplay, EnumFromStr,
+)]
+pub enum MagnifierCorner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+}
+
 #[derive(Debug, Clone, Copy, ConfigDisplay)]
 pub struct RendererConfig {
     pub wgpu_backend: WgpuBackend,
@@ -131,5 +173,18 @@ pub struct RendererConfig {
     pub force_integer_height_scaling: bool,
     pub filter_mode: FilterMode,
     pub preprocess_shader: PreprocessShader,
+    pub color_blind_filter: ColorBlindFilter,
+    /// If true, limit how quickly the average brightness of the screen can change between frames
+    /// to reduce the intensity of full-screen flashes
+    pub flash_reduction: bool,
+    pub magnifier_mode: MagnifierMode,
+    /// Corner of the display that the picture-in-picture magnifier is drawn in
+    pub magnifier_corner: MagnifierCorner,
     pub use_webgl2_limits: bool,
+    /// If true, fullscreen mode for PAL games will switch the display to a refresh rate that is a
+    /// multiple of 50Hz (if available) so that frames are displayed at even intervals
+    pub pal_50hz_fullscreen: bool,
+    /// If true, render a border image around the game. A custom border image is used if one has
+    /// been set, otherwise the system's default border (if any)
+    pub show_border: bool,
 }
@@ -0,0 +1,213 @@
+//! CPU-side deinterlacing for interlaced double-resolution output
+//!
+//! Cores that support interlaced double-resolution modes (Genesis interlaced mode 2, SNES
+//! interlaced Mode 5/6) render both fields into every frame, which is equivalent to weaving the
+//! fields together. The other modes rebuild each frame from one field at a time, alternating
+//! between the even and odd fields every frame like a CRT would.
+//!
+//! Deinterlacing runs as a [`FrameFilter`] so that it is applied before the frame is scaled.
+
+use jgenesis_common::frontend::filter::{FilterFrame, FrameFilter};
+use jgenesis_common::frontend::{Color, FrameSize};
+use jgenesis_proc_macros::{EnumDisplay, EnumFromStr};
+use serde::{Deserialize, Serialize};
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, EnumDisplay, EnumFromStr,
+)]
+pub enum DeinterlaceMode {
+    /// Display both fields in every frame
+    #[default]
+    Weave,
+    /// Line double the current field, alternating fields every frame
+    Bob,
+    /// Average the two fields together
+    Blend,
+    /// Weave parts of the image that did not change since the last frame, and bob parts that did
+    MotionAdaptive,
+}
+
+pub const FILTER_NAME: &str = "deinterlace";
+
+// Interlaced double-resolution frames are always at least this many lines tall. Shorter frames are
+// progressive and are passed through unchanged
+const MIN_INTERLACED_HEIGHT: u32 = 400;
+
+#[derive(Debug, Clone)]
+pub struct Deinterlacer {
+    mode: DeinterlaceMode,
+    odd_field: bool,
+    // Unfiltered copies of the current and previous frames, used for motion detection
+    current_frame: Vec<Color>,
+    prev_frame: Vec<Color>,
+    prev_frame_size: Option<FrameSize>,
+}
+
+impl Deinterlacer {
+    #[must_use]
+    pub fn new(mode: DeinterlaceMode) -> Self {
+        Self {
+            mode,
+            odd_field: false,
+            current_frame: Vec::new(),
+            prev_frame: Vec::new(),
+            prev_frame_size: None,
+        }
+    }
+
+    fn bob(&self, frame: &mut FilterFrame) {
+        let width = frame.size.width as usize;
+        let height = frame.size.height as usize;
+
+        for line in self.other_field_lines(height) {
+            let source_line = line ^ 1;
+            frame.buffer.copy_within(source_line * width..(source_line + 1) * width, line * width);
+        }
+    }
+
+    fn motion_adaptive(&mut self, frame: &mut FilterFrame) {
+        let width = frame.size.width as usize;
+        let height = frame.size.height as usize;
+
+        self.current_frame.clone_from(&frame.buffer);
+
+        // Without a previous frame to compare against, treat every pixel as moving
+        let has_prev_frame = self.prev_frame_size == Some(frame.size);
+
+        for line in self.other_field_lines(height) {
+            let source_line = line ^ 1;
+            for x in 0..width {
+                let i = line * width + x;
+                if !has_prev_frame || self.current_frame[i] != self.prev_frame[i] {
+                    frame.buffer[i] = self.current_frame[source_line * width + x];
+                }
+            }
+        }
+
+        std::mem::swap(&mut self.current_frame, &mut self.prev_frame);
+        self.prev_frame_size = Some(frame.size);
+    }
+
+    // Lines that belong to the field that is not currently being displayed, skipping the last line
+    // if the frame has an odd number of lines
+    fn other_field_lines(&self, height: usize) -> impl Iterator<Item = usize> {
+        let first_line = usize::from(!self.odd_field);
+        (first_line..height).step_by(2).filter(move |&line| line ^ 1 < height)
+    }
+}
+
+fn blend(frame: &mut FilterFrame) {
+    let width = frame.size.width as usize;
+
+    for line_pair in frame.buffer.chunks_exact_mut(2 * width) {
+        let (even, odd) = line_pair.split_at_mut(width);
+        for (even, odd) in even.iter_mut().zip(odd) {
+            let blended = Color::rgb(
+                average(even.r, odd.r),
+                average(even.g, odd.g),
+                average(even.b, odd.b),
+            );
+            *even = blended;
+            *odd = blended;
+        }
+    }
+}
+
+fn average(a: u8, b: u8) -> u8 {
+    ((u16::from(a) + u16::from(b)) / 2) as u8
+}
+
+impl FrameFilter for Deinterlacer {
+    fn name(&self) -> &'static str {
+        FILTER_NAME
+    }
+
+    fn apply(&mut self, frame: &mut FilterFrame) {
+        if frame.size.height < MIN_INTERLACED_HEIGHT {
+            self.reset();
+            return;
+        }
+
+        match self.mode {
+            DeinterlaceMode::Weave => {}
+            DeinterlaceMode::Bob => self.bob(frame),
+            DeinterlaceMode::Blend => blend(frame),
+            DeinterlaceMode::MotionAdaptive => self.motion_adaptive(frame),
+        }
+
+        self.odd_field = !self.odd_field;
+    }
+
+    fn reset(&mut self) {
+        self.odd_field = false;
+        self.prev_frame_size = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WIDTH: u32 = 1;
+    const HEIGHT: u32 = 448;
+
+    fn new_frame(color_fn: impl Fn(usize) -> u8) -> FilterFrame {
+        FilterFrame {
+            buffer: (0..HEIGHT as usize).map(|line| Color::rgb(color_fn(line), 0, 0)).collect(),
+            size: FrameSize { width: WIDTH, height: HEIGHT },
+            pixel_aspect_ratio: None,
+        }
+    }
+
+    fn red_values(frame: &FilterFrame) -> Vec<u8> {
+        frame.buffer.iter().map(|color| color.r).take(4).collect()
+    }
+
+    #[test]
+    fn bob_alternates_fields() {
+        let mut deinterlacer = Deinterlacer::new(DeinterlaceMode::Bob);
+
+        let mut frame = new_frame(|line| line as u8);
+        deinterlacer.apply(&mut frame);
+        assert_eq!(red_values(&frame), [0, 0, 2, 2]);
+
+        let mut frame = new_frame(|line| line as u8);
+        deinterlacer.apply(&mut frame);
+        assert_eq!(red_values(&frame), [1, 1, 3, 3]);
+    }
+
+    #[test]
+    fn blend_averages_line_pairs() {
+        let mut deinterlacer = Deinterlacer::new(DeinterlaceMode::Blend);
+
+        let mut frame = new_frame(|line| if line % 2 == 0 { 100 } else { 200 });
+        deinterlacer.apply(&mut frame);
+        assert_eq!(red_values(&frame), [150, 150, 150, 150]);
+    }
+
+    #[test]
+    fn motion_adaptive_weaves_static_lines() {
+        let mut deinterlacer = Deinterlacer::new(DeinterlaceMode::MotionAdaptive);
+
+        // First frame has no history, so it is bobbed
+        let mut frame = new_frame(|line| line as u8);
+        deinterlacer.apply(&mut frame);
+        assert_eq!(red_values(&frame), [0, 0, 2, 2]);
+
+        // Line 2 changed since the last frame but lines 0 and 1 did not
+        let mut frame = new_frame(|line| if line == 2 { 50 } else { line as u8 });
+        deinterlacer.apply(&mut frame);
+        assert_eq!(red_values(&frame), [0, 1, 3, 3]);
+    }
+
+    #[test]
+    fn progressive_frames_unchanged() {
+        let mut deinterlacer = Deinterlacer::new(DeinterlaceMode::Bob);
+
+        let mut frame = new_frame(|line| line as u8);
+        frame.size.height = 224;
+        frame.buffer.truncate(224);
+        deinterlacer.apply(&mut frame);
+        assert_eq!(red_values(&frame), [0, 1, 2, 3]);
+    }
+}
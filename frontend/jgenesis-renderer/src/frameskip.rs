@@ -0,0 +1,98 @@
+//! Automatic frame skipping for hosts that are not able to render every frame at full speed
+//!
+//! Only rendering is skipped. Every frame is still fully emulated, so audio output is unaffected.
+
+use jgenesis_common::frontend::{Color, FrameSize, PixelAspectRatio, Renderer};
+
+pub const DEFAULT_MAX_FRAME_SKIP: u32 = 4;
+
+// Frame skip is reported as active for this many frames after the most recent skipped frame so
+// that the indicator doesn't flicker on and off
+const ACTIVE_INDICATOR_FRAMES: u32 = 60;
+
+#[derive(Debug, Clone)]
+pub struct FrameSkipper {
+    max_consecutive_skips: u32,
+    consecutive_skips: u32,
+    frames_since_last_skip: u32,
+    skip_current_frame: bool,
+}
+
+impl FrameSkipper {
+    #[must_use]
+    pub fn new(max_consecutive_skips: u32) -> Self {
+        Self {
+            max_consecutive_skips,
+            consecutive_skips: 0,
+            frames_since_last_skip: ACTIVE_INDICATOR_FRAMES,
+            skip_current_frame: false,
+        }
+    }
+
+    #[must_use]
+    pub fn max_consecutive_skips(&self) -> u32 {
+        self.max_consecutive_skips
+    }
+
+    /// Decide whether to skip rendering the next frame, and return the decision.
+    ///
+    /// `falling_behind` should be true if the host is currently unable to keep up with emulation
+    /// speed. A frame is always rendered after the maximum number of consecutive skipped frames,
+    /// even if the host is still falling behind.
+    pub fn next_frame(&mut self, falling_behind: bool) -> bool {
+        self.skip_current_frame =
+            falling_behind && self.consecutive_skips < self.max_consecutive_skips;
+
+        if self.skip_current_frame {
+            self.consecutive_skips += 1;
+            self.frames_since_last_skip = 0;
+        } else {
+            self.consecutive_skips = 0;
+            self.frames_since_last_skip = self.frames_since_last_skip.saturating_add(1);
+        }
+
+        self.skip_current_frame
+    }
+
+    #[must_use]
+    pub fn skip_current_frame(&self) -> bool {
+        self.skip_current_frame
+    }
+
+    /// Whether any frames have been skipped recently.
+    #[must_use]
+    pub fn is_active(&self) -> bool {
+        self.frames_since_last_skip < ACTIVE_INDICATOR_FRAMES
+    }
+}
+
+/// Renderer wrapper that drops frames instead of rendering them if `skip` is true.
+pub struct SkippingRenderer<'a, R> {
+    renderer: &'a mut R,
+    skip: bool,
+}
+
+impl<'a, R> SkippingRenderer<'a, R> {
+    #[must_use]
+    pub fn new(renderer: &'a mut R, skip: bool) -> Self {
+        Self { renderer, skip }
+    }
+}
+
+impl<R: Renderer> Renderer for SkippingRenderer<'_, R> {
+    type Err = R::Err;
+
+    #[inline]
+    fn render_frame(
+        &mut self,
+        frame_buffer: &[Color],
+        frame_size: FrameSize,
+        pixel_aspect_ratio: Option<PixelAspectRatio>,
+    ) -> Result<(), Self::Err> {
+        if self.skip {
+            return Ok(());
+        }
+
+        self.renderer.render_frame(frame_buffer, frame_size, pixel_aspect_ratio)
+    }
+}
@@ -1,2 +1,5 @@
+pub mod border;
 pub mod config;
+pub mod deinterlace;
+pub mod frameskip;
 pub mod renderer;
@@ -25,4 +25,28 @@ fn vs_main(input: VertexInput) -> VertexOutput {
 @fragment
 fn fs_main(input: VertexOutput) -> @location(0) vec4f {
     return textureSample(texture_in, sampler_in, input.texture_coords);
-}
\ No newline at end of file
+}
+
+struct OutputScale {
+    value: vec2f,
+    // Uniform structs must be padded to a 16 byte boundary for WebGL
+    _padding: vec2f,
+}
+
+@group(0) @binding(2)
+var<uniform> output_scale: OutputScale;
+
+// Sample as nearest neighbor within each texel, and interpolate linearly only across the roughly
+// one display pixel wide region at each texel edge
+@fragment
+fn fs_sharp_bilinear(input: VertexOutput) -> @location(0) vec4f {
+    let texture_size = vec2f(textureDimensions(texture_in));
+    let texel = input.texture_coords * texture_size;
+    let scale = max(output_scale.value, vec2f(1.0, 1.0));
+
+    let region_range = 0.5 - 0.5 / scale;
+    let center_dist = fract(texel) - 0.5;
+    let offset = (center_dist - clamp(center_dist, -region_range, region_range)) * scale + 0.5;
+
+    return textureSample(texture_in, sampler_in, (floor(texel) + offset) / texture_size);
+}
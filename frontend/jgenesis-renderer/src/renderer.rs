@@ -1,7 +1,15 @@
-use crate::config::{PreprocessShader, RendererConfig, Scanlines, WgpuBackend};
+use crate::border::BorderImage;
+use crate::config::{
+    ColorBlindFilter, FilterMode, MagnifierCorner, MagnifierMode, PreprocessShader, RendererConfig,
+    Scanlines, WgpuBackend,
+};
 use jgenesis_common::frontend::{Color, FrameSize, PixelAspectRatio, Renderer};
 use raw_window_handle::{HasRawDisplayHandle, HasRawWindowHandle};
-use std::{cmp, iter, mem};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::{array, cmp, iter, mem};
 use thiserror::Error;
 use wgpu::util::DeviceExt;
 use wgpu::Gles3MinorVersion;
@@ -257,128 +265,411 @@ fn padded_u32(value: u32) -> [u32; 4] {
     [value, 0, 0, 0]
 }
 
-struct RenderingPipeline {
-    frame_size: FrameSize,
-    pixel_aspect_ratio: Option<PixelAspectRatio>,
-    display_area: DisplayArea,
-    scaled_texture: wgpu::Texture,
-    vertex_buffer: wgpu::Buffer,
-    preprocess_pipeline: PreprocessPipeline,
-    prescale_bind_group: wgpu::BindGroup,
-    prescale_pipeline: wgpu::RenderPipeline,
-    render_bind_group: wgpu::BindGroup,
-    render_pipeline: wgpu::RenderPipeline,
+type ColorMatrix = [[f64; 3]; 3];
+
+const IDENTITY_MATRIX: ColorMatrix = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+
+// Daltonization constants from Fidaner, Lin, and Ozguven, "Analysis of Color Blindness"
+const RGB_TO_LMS: ColorMatrix =
+    [[17.8824, 43.5161, 4.11935], [3.45565, 27.1554, 3.86714], [0.0299566, 0.184309, 1.46709]];
+const LMS_TO_RGB: ColorMatrix = [
+    [0.0809444479, -0.130504409, 0.116721066],
+    [-0.0102485335, 0.0540193266, -0.113614708],
+    [-0.000365296938, -0.00412161469, 0.693511405],
+];
+// Moves the color information that is lost with the deficiency into the green and blue channels
+const ERROR_SHIFT: ColorMatrix = [[0.0, 0.0, 0.0], [0.7, 1.0, 0.0], [0.7, 0.0, 1.0]];
+
+trait ColorBlindFilterExt {
+    fn correction_matrix(self) -> ColorMatrix;
 }
 
-impl RenderingPipeline {
-    #[allow(clippy::too_many_arguments)]
+impl ColorBlindFilterExt for ColorBlindFilter {
+    fn correction_matrix(self) -> ColorMatrix {
+        // Simulate the deficiency in LMS space by reconstructing the missing cone response from the
+        // other two
+        let simulation = match self {
+            Self::None => return IDENTITY_MATRIX,
+            Self::Protanopia => [[0.0, 2.02344, -2.52581], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+            Self::Deuteranopia => [[1.0, 0.0, 0.0], [0.494207, 0.0, 1.24827], [0.0, 0.0, 1.0]],
+            Self::Tritanopia => [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [-0.395913, 0.801109, 0.0]],
+        };
+
+        // corrected = color + shift * (color - simulated)
+        let simulated = matrix_mul(LMS_TO_RGB, matrix_mul(simulation, RGB_TO_LMS));
+        let error = array::from_fn(|row| {
+            array::from_fn(|col| IDENTITY_MATRIX[row][col] - simulated[row][col])
+        });
+        let shifted_error = matrix_mul(ERROR_SHIFT, error);
+        array::from_fn(|row| {
+            array::from_fn(|col| IDENTITY_MATRIX[row][col] + shifted_error[row][col])
+        })
+    }
+}
+
+fn matrix_mul(a: ColorMatrix, b: ColorMatrix) -> ColorMatrix {
+    array::from_fn(|row| array::from_fn(|col| (0..3).map(|i| a[row][i] * b[i][col]).sum()))
+}
+
+// Maximum change in average luminance (0.0 to 1.0) between consecutive frames when flash reduction
+// is enabled; larger changes are spread across multiple frames
+const MAX_LUMINANCE_CHANGE: f64 = 0.04;
+
+// Rec. 709 luminance coefficients
+const LUMINANCE_WEIGHTS: [f64; 3] = [0.2126, 0.7152, 0.0722];
+
+// Tracks the average luminance of the displayed frame and determines how much of each new frame can
+// be shown without exceeding the maximum luminance change
+struct FlashLimiter {
+    // Values that the shader sees for each 8-bit color component; these are linear if the textures
+    // are sRGB
+    component_values: [f64; 256],
+    target_luminance: f64,
+    displayed_luminance: Option<f64>,
+    settled: bool,
+}
+
+impl FlashLimiter {
+    fn new(srgb: bool) -> Self {
+        let component_values = array::from_fn(|i| {
+            let value = i as f64 / 255.0;
+            if !srgb {
+                value
+            } else if value <= 0.04045 {
+                value / 12.92
+            } else {
+                ((value + 0.055) / 1.055).powf(2.4)
+            }
+        });
+
+        Self { component_values, target_luminance: 0.0, displayed_luminance: None, settled: true }
+    }
+
+    fn set_frame(&mut self, frame_buffer: &[Color], color_matrix: &ColorMatrix) {
+        let mut sums = [0.0; 3];
+        for color in frame_buffer {
+            sums[0] += self.component_values[usize::from(color.r)];
+            sums[1] += self.component_values[usize::from(color.g)];
+            sums[2] += self.component_values[usize::from(color.b)];
+        }
+        let average = sums.map(|sum| sum / cmp::max(1, frame_buffer.len()) as f64);
+
+        // Color correction is linear (ignoring clamping), so it can be applied to the average color
+        self.target_luminance = color_matrix
+            .iter()
+            .zip(LUMINANCE_WEIGHTS)
+            .map(|(row, weight)| weight * row.iter().zip(average).map(|(a, b)| a * b).sum::<f64>())
+            .sum();
+        self.settled = false;
+    }
+
+    // Returns the weight of the current frame when blending it with the previous output. The blend
+    // is linear, so the average luminance of the output moves by the same fraction of the change
+    fn next_blend_weight(&mut self) -> f64 {
+        let displayed = self.displayed_luminance.unwrap_or(self.target_luminance);
+        let change = self.target_luminance - displayed;
+
+        let weight = if change.abs() <= MAX_LUMINANCE_CHANGE {
+            self.settled = true;
+            1.0
+        } else {
+            MAX_LUMINANCE_CHANGE / change.abs()
+        };
+
+        self.displayed_luminance = Some(displayed + weight * change);
+        weight
+    }
+}
+
+// Color filter stage at native resolution. Applies color blindness correction and flash reduction,
+// and is only created if at least one of them is enabled
+struct ColorFilterPipeline {
+    output: wgpu::Texture,
+    // Copy of the last output, which new frames are blended with when flash reduction is enabled
+    previous_output: wgpu::Texture,
+    params_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    pipeline: wgpu::RenderPipeline,
+    color_matrix: ColorMatrix,
+    flash_limiter: Option<FlashLimiter>,
+}
+
+impl ColorFilterPipeline {
     fn create(
         device: &wgpu::Device,
         shaders: &Shaders,
-        window_size: (u32, u32),
-        frame_size: FrameSize,
-        pixel_aspect_ratio: Option<PixelAspectRatio>,
-        texture_format: wgpu::TextureFormat,
-        surface_config: &wgpu::SurfaceConfiguration,
+        input_texture: &wgpu::Texture,
         renderer_config: RendererConfig,
-    ) -> Self {
-        let input_texture = device.create_texture(&wgpu::TextureDescriptor {
-            label: "input_texture".into(),
-            size: wgpu::Extent3d {
-                width: frame_size.width,
-                height: frame_size.height,
-                depth_or_array_layers: 1,
-            },
+    ) -> Option<Self> {
+        if renderer_config.color_blind_filter == ColorBlindFilter::None
+            && !renderer_config.flash_reduction
+        {
+            return None;
+        }
+
+        let color_matrix = renderer_config.color_blind_filter.correction_matrix();
+        let flash_limiter = renderer_config
+            .flash_reduction
+            .then(|| FlashLimiter::new(input_texture.format().is_srgb()));
+
+        let texture_descriptor = wgpu::TextureDescriptor {
+            label: "color_filter_output_texture".into(),
+            size: input_texture.size(),
             mip_level_count: 1,
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
-            format: texture_format,
-            usage: wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::TEXTURE_BINDING,
+            format: input_texture.format(),
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC,
             view_formats: &[],
+        };
+        let output = device.create_texture(&texture_descriptor);
+        let previous_output = device.create_texture(&wgpu::TextureDescriptor {
+            label: "color_filter_previous_output_texture".into(),
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            ..texture_descriptor
         });
 
-        let prescale_factor = renderer_config.prescale_factor.get();
+        let texture_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: "color_filter_bind_group_layout".into(),
+            entries: &[
+                texture_entry(0),
+                texture_entry(1),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
 
-        let filter_mode = renderer_config.filter_mode.to_wgpu_filter_mode();
-        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-            label: "sampler".into(),
-            address_mode_u: wgpu::AddressMode::ClampToEdge,
-            address_mode_v: wgpu::AddressMode::ClampToEdge,
-            address_mode_w: wgpu::AddressMode::ClampToEdge,
-            mag_filter: filter_mode,
-            min_filter: filter_mode,
-            mipmap_filter: filter_mode,
-            ..wgpu::SamplerDescriptor::default()
+        let params_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: "color_filter_params_buffer".into(),
+            contents: bytemuck::cast_slice(&color_filter_params(&color_matrix, 1.0)),
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::UNIFORM,
         });
 
-        let display_area = determine_display_area(
-            window_size.0,
-            window_size.1,
-            frame_size,
-            pixel_aspect_ratio,
-            renderer_config.force_integer_height_scaling,
-        );
+        let input_view = input_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let previous_output_view =
+            previous_output.create_view(&wgpu::TextureViewDescriptor::default());
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: "color_filter_bind_group".into(),
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&input_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&previous_output_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                        buffer: &params_buffer,
+                        offset: 0,
+                        size: None,
+                    }),
+                },
+            ],
+        });
 
-        let vertices = match pixel_aspect_ratio {
-            Some(_) => compute_vertices(window_size.0, window_size.1, display_area),
-            None => VERTICES.into(),
-        };
-        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: "vertex_buffer".into(),
-            contents: bytemuck::cast_slice(&vertices),
-            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::VERTEX,
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: "color_filter_pipeline_layout".into(),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
         });
 
-        let preprocess_pipeline = PreprocessPipeline::create(
-            renderer_config.preprocess_shader,
-            device,
-            input_texture,
-            shaders,
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: "color_filter_pipeline".into(),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shaders.identity,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleStrip,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                unclipped_depth: false,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shaders.color_filter,
+                entry_point: "color_filter",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: output.format(),
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+        });
+
+        Some(Self {
+            output,
+            previous_output,
+            params_buffer,
+            bind_group,
+            pipeline,
+            color_matrix,
+            flash_limiter,
+        })
+    }
+
+    // Whether the output needs to be redrawn even if the frame did not change, because flash
+    // reduction is still transitioning to the current frame
+    fn needs_redraw(&self) -> bool {
+        self.flash_limiter.as_ref().is_some_and(|flash_limiter| !flash_limiter.settled)
+    }
+
+    // Update the blend weight for the next draw; frame_buffer should be Some if the frame changed
+    fn prepare(&mut self, queue: &wgpu::Queue, frame_buffer: Option<&[Color]>) {
+        let Some(flash_limiter) = &mut self.flash_limiter else { return };
+
+        if let Some(frame_buffer) = frame_buffer {
+            flash_limiter.set_frame(frame_buffer, &self.color_matrix);
+        }
+
+        let blend_weight = flash_limiter.next_blend_weight();
+        queue.write_buffer(
+            &self.params_buffer,
+            0,
+            bytemuck::cast_slice(&color_filter_params(&self.color_matrix, blend_weight)),
         );
+    }
 
-        let prescale_bind_group_layout =
-            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                label: "prescale_bind_group_layout".into(),
-                entries: &[
-                    wgpu::BindGroupLayoutEntry {
-                        binding: 0,
-                        visibility: wgpu::ShaderStages::FRAGMENT,
-                        ty: wgpu::BindingType::Texture {
-                            sample_type: wgpu::TextureSampleType::Float { filterable: false },
-                            view_dimension: wgpu::TextureViewDimension::D2,
-                            multisampled: false,
-                        },
-                        count: None,
-                    },
-                    wgpu::BindGroupLayoutEntry {
-                        binding: 1,
-                        visibility: wgpu::ShaderStages::FRAGMENT,
-                        ty: wgpu::BindingType::Buffer {
-                            ty: wgpu::BufferBindingType::Uniform,
-                            has_dynamic_offset: false,
-                            min_binding_size: None,
-                        },
-                        count: None,
+    fn draw(&self, encoder: &mut wgpu::CommandEncoder) {
+        let output_view = self.output.create_view(&wgpu::TextureViewDescriptor::default());
+
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: "color_filter_pass".into(),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &output_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: wgpu::StoreOp::Store,
                     },
-                ],
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
             });
 
-        let preprocess_output_texture = preprocess_pipeline.output_texture();
-        let preprocess_output_view =
-            preprocess_output_texture.create_view(&wgpu::TextureViewDescriptor::default());
+            render_pass.set_bind_group(0, &self.bind_group, &[]);
+            render_pass.set_pipeline(&self.pipeline);
+
+            render_pass.draw(0..VERTICES.len() as u32, 0..1);
+        }
+
+        if self.flash_limiter.is_some() {
+            encoder.copy_texture_to_texture(
+                self.output.as_image_copy(),
+                self.previous_output.as_image_copy(),
+                self.output.size(),
+            );
+        }
+    }
+}
+
+// Matches the layout of ColorFilterParams in colorfilter.wgsl
+fn color_filter_params(color_matrix: &ColorMatrix, blend_weight: f64) -> [f32; 16] {
+    let mut params = [0.0; 16];
+    for (row, values) in color_matrix.iter().enumerate() {
+        for (col, &value) in values.iter().enumerate() {
+            params[4 * row + col] = value as f32;
+        }
+    }
+    params[12] = blend_weight as f32;
+    params
+}
+
+// Integer prescale stage. Scales the preprocessed frame up by the prescale factor using nearest
+// neighbor sampling so that the output stage has more source pixels to work with, and applies
+// scanlines if enabled
+struct PrescalePipeline {
+    output: wgpu::Texture,
+    bind_group: wgpu::BindGroup,
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl PrescalePipeline {
+    fn create(
+        device: &wgpu::Device,
+        shaders: &Shaders,
+        input_texture: &wgpu::Texture,
+        renderer_config: RendererConfig,
+    ) -> Self {
+        let prescale_factor = renderer_config.prescale_factor.get();
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: "prescale_bind_group_layout".into(),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let input_view = input_texture.create_view(&wgpu::TextureViewDescriptor::default());
         let prescale_factor_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: "prescale_factor_buffer".into(),
             contents: bytemuck::cast_slice(&padded_u32(prescale_factor)),
             usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::UNIFORM,
         });
 
-        let prescale_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: "prescale_bind_group".into(),
-            layout: &prescale_bind_group_layout,
+            layout: &bind_group_layout,
             entries: &[
                 wgpu::BindGroupEntry {
                     binding: 0,
-                    resource: wgpu::BindingResource::TextureView(&preprocess_output_view),
+                    resource: wgpu::BindingResource::TextureView(&input_view),
                 },
                 wgpu::BindGroupEntry {
                     binding: 1,
@@ -391,38 +682,35 @@ impl RenderingPipeline {
             ],
         });
 
-        let prescale_pipeline_layout =
-            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                label: "prescale_pipeline_layout".into(),
-                bind_group_layouts: &[&prescale_bind_group_layout],
-                push_constant_ranges: &[],
-            });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: "prescale_pipeline_layout".into(),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
 
-        let scaled_texture = device.create_texture(&wgpu::TextureDescriptor {
+        let output = device.create_texture(&wgpu::TextureDescriptor {
             label: "scaled_texture".into(),
             size: wgpu::Extent3d {
-                width: prescale_factor * preprocess_output_texture.width(),
-                height: prescale_factor * preprocess_output_texture.height(),
+                width: prescale_factor * input_texture.width(),
+                height: prescale_factor * input_texture.height(),
                 depth_or_array_layers: 1,
             },
             mip_level_count: 1,
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
-            format: texture_format,
+            format: input_texture.format(),
             usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::RENDER_ATTACHMENT,
             view_formats: &[],
         });
-        let scaled_texture_view =
-            scaled_texture.create_view(&wgpu::TextureViewDescriptor::default());
 
-        let prescale_fs_main = match renderer_config.scanlines {
+        let fs_main = match renderer_config.scanlines {
             Scanlines::None => "basic_prescale",
             Scanlines::Dim => "dim_scanlines",
             Scanlines::Black => "black_scanlines",
         };
-        let prescale_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
             label: "prescale_pipeline".into(),
-            layout: Some(&prescale_pipeline_layout),
+            layout: Some(&pipeline_layout),
             vertex: wgpu::VertexState {
                 module: &shaders.identity,
                 entry_point: "vs_main",
@@ -445,9 +733,188 @@ impl RenderingPipeline {
             },
             fragment: Some(wgpu::FragmentState {
                 module: &shaders.prescale,
-                entry_point: prescale_fs_main,
+                entry_point: fs_main,
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: output.format(),
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+        });
+
+        Self { output, bind_group, pipeline }
+    }
+
+    fn draw(&self, encoder: &mut wgpu::CommandEncoder) {
+        let output_view = self.output.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: "prescale_pass".into(),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &output_view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.set_pipeline(&self.pipeline);
+
+        render_pass.draw(0..VERTICES.len() as u32, 0..1);
+    }
+}
+
+// Final output stage. Samples the prescaled frame into the display area of the window using the
+// configured filter mode, independent of the prescale factor
+struct OutputPipeline {
+    vertex_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl OutputPipeline {
+    fn create(
+        device: &wgpu::Device,
+        shaders: &Shaders,
+        input_texture: &wgpu::Texture,
+        vertices: &[Vertex],
+        display_area: DisplayArea,
+        surface_format: wgpu::TextureFormat,
+        filter_mode: FilterMode,
+    ) -> Self {
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: "vertex_buffer".into(),
+            contents: bytemuck::cast_slice(vertices),
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::VERTEX,
+        });
+
+        let wgpu_filter_mode = filter_mode.to_wgpu_filter_mode();
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: "sampler".into(),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu_filter_mode,
+            min_filter: wgpu_filter_mode,
+            mipmap_filter: wgpu_filter_mode,
+            ..wgpu::SamplerDescriptor::default()
+        });
+
+        // Number of display pixels per input texel in each dimension, used by the sharp bilinear
+        // shader to determine how wide the interpolated region at each texel edge should be
+        let output_scale = [
+            display_area.width as f32 / input_texture.width() as f32,
+            display_area.height as f32 / input_texture.height() as f32,
+            0.0,
+            0.0,
+        ];
+        let output_scale_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: "output_scale_buffer".into(),
+            contents: bytemuck::cast_slice(&output_scale),
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::UNIFORM,
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: "render_bind_group_layout".into(),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let input_view = input_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: "render_bind_group".into(),
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&input_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                        buffer: &output_scale_buffer,
+                        offset: 0,
+                        size: None,
+                    }),
+                },
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: "render_pipeline_layout".into(),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let fs_main = match filter_mode {
+            FilterMode::Nearest | FilterMode::Linear => "fs_main",
+            FilterMode::SharpBilinear => "fs_sharp_bilinear",
+        };
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: "render_pipeline".into(),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shaders.render,
+                entry_point: "vs_main",
+                buffers: &[Vertex::buffer_layout()],
+            },
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleStrip,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                unclipped_depth: false,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shaders.render,
+                entry_point: fs_main,
                 targets: &[Some(wgpu::ColorTargetState {
-                    format: scaled_texture.format(),
+                    format: surface_format,
                     blend: Some(wgpu::BlendState::REPLACE),
                     write_mask: wgpu::ColorWrites::ALL,
                 })],
@@ -455,179 +922,506 @@ impl RenderingPipeline {
             multiview: None,
         });
 
-        let render_bind_group_layout =
-            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                label: "render_bind_group_layout".into(),
-                entries: &[
-                    wgpu::BindGroupLayoutEntry {
-                        binding: 0,
-                        visibility: wgpu::ShaderStages::FRAGMENT,
-                        ty: wgpu::BindingType::Texture {
-                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                            view_dimension: wgpu::TextureViewDimension::D2,
-                            multisampled: false,
-                        },
-                        count: None,
-                    },
-                    wgpu::BindGroupLayoutEntry {
-                        binding: 1,
-                        visibility: wgpu::ShaderStages::FRAGMENT,
-                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
-                        count: None,
-                    },
-                ],
-            });
+        Self { vertex_buffer, bind_group, pipeline }
+    }
+
+    fn draw(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        output_view: &wgpu::TextureView,
+        load: wgpu::LoadOp<wgpu::Color>,
+    ) {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: "render_pass".into(),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: output_view,
+                resolve_target: None,
+                ops: wgpu::Operations { load, store: wgpu::StoreOp::Store },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+
+        render_pass.draw(0..VERTICES.len() as u32, 0..1);
+    }
+
+    fn write_vertices(&self, queue: &wgpu::Queue, vertices: &[Vertex]) {
+        queue.write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(vertices));
+    }
+}
+
+// Zoom level of the magnifier relative to the normal display scale
+const MAGNIFIER_ZOOM: f64 = 2.0;
+// Size of the picture-in-picture view as a fraction of the display area in each dimension
+const PIP_SIZE: f64 = 0.4;
+// Gap between the picture-in-picture view and the edges of the display area, and the width of the
+// view's outline, as fractions of the display area height
+const PIP_MARGIN: f64 = 0.03;
+const PIP_OUTLINE_WIDTH: f64 = 0.006;
+
+// (left, top) to (right, bottom), either in window pixels or in normalized texture coordinates
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Rect {
+    left: f64,
+    top: f64,
+    right: f64,
+    bottom: f64,
+}
+
+impl Rect {
+    const FULL_TEXTURE: Self = Self { left: 0.0, top: 0.0, right: 1.0, bottom: 1.0 };
+
+    fn expand(self, amount: f64) -> Self {
+        Self {
+            left: self.left - amount,
+            top: self.top - amount,
+            right: self.right + amount,
+            bottom: self.bottom + amount,
+        }
+    }
+}
+
+// Returns the window area that the magnified view should be drawn to, along with the region of the
+// frame that it shows. The region is centered on the focus point (given as a fraction of the frame
+// width and height) unless that would extend it past the edges of the frame
+fn magnifier_layout(
+    mode: MagnifierMode,
+    corner: MagnifierCorner,
+    display_area: DisplayArea,
+    focus: (f64, f64),
+) -> Option<(Rect, Rect)> {
+    let display_left = f64::from(display_area.x);
+    let display_top = f64::from(display_area.y);
+    let display_width = f64::from(display_area.width);
+    let display_height = f64::from(display_area.height);
+
+    let (view_area, view_size) = match mode {
+        MagnifierMode::None => return None,
+        MagnifierMode::FullScreen => {
+            let area = Rect {
+                left: display_left,
+                top: display_top,
+                right: display_left + display_width,
+                bottom: display_top + display_height,
+            };
+            (area, 1.0)
+        }
+        MagnifierMode::PictureInPicture => {
+            let width = display_width * PIP_SIZE;
+            let height = display_height * PIP_SIZE;
+            let margin = display_height * PIP_MARGIN;
+
+            let left = match corner {
+                MagnifierCorner::TopLeft | MagnifierCorner::BottomLeft => display_left + margin,
+                MagnifierCorner::TopRight | MagnifierCorner::BottomRight => {
+                    display_left + display_width - margin - width
+                }
+            };
+            let top = match corner {
+                MagnifierCorner::TopLeft | MagnifierCorner::TopRight => display_top + margin,
+                MagnifierCorner::BottomLeft | MagnifierCorner::BottomRight => {
+                    display_top + display_height - margin - height
+                }
+            };
+
+            (Rect { left, top, right: left + width, bottom: top + height }, PIP_SIZE)
+        }
+    };
+
+    let region_size = view_size / MAGNIFIER_ZOOM;
+    let region_start = |focus: f64| (focus - region_size / 2.0).clamp(0.0, 1.0 - region_size);
+    let left = region_start(focus.0);
+    let top = region_start(focus.1);
+
+    Some((view_area, Rect { left, top, right: left + region_size, bottom: top + region_size }))
+}
+
+// Vertices for a quad that covers the given window area and samples the given texture region
+fn quad_vertices(window_size: (u32, u32), area: Rect, texture_region: Rect) -> [Vertex; 4] {
+    let window_width = f64::from(window_size.0);
+    let window_height = f64::from(window_size.1);
+
+    VERTICES.map(|vertex| {
+        let x = if vertex.position[0] > 0.0 { area.right } else { area.left };
+        // Clip space Y increases upwards while window Y increases downwards
+        let y = if vertex.position[1] > 0.0 { area.top } else { area.bottom };
+        let u =
+            if vertex.texture_coords[0] > 0.0 { texture_region.right } else { texture_region.left };
+        let v =
+            if vertex.texture_coords[1] > 0.0 { texture_region.bottom } else { texture_region.top };
+
+        Vertex {
+            position: [
+                (x / window_width * 2.0 - 1.0) as f32,
+                (1.0 - y / window_height * 2.0) as f32,
+            ],
+            texture_coords: [u as f32, v as f32],
+        }
+    })
+}
+
+// Accessibility magnifier, drawn over the output stage. The quad vertices are rewritten before
+// every draw so that the view can follow the focus point without recreating the pipeline
+struct MagnifierPipeline {
+    mode: MagnifierMode,
+    corner: MagnifierCorner,
+    window_size: (u32, u32),
+    display_area: DisplayArea,
+    outline_pipeline: Option<OutputPipeline>,
+    view_pipeline: OutputPipeline,
+}
+
+impl MagnifierPipeline {
+    #[allow(clippy::too_many_arguments)]
+    fn create(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        shaders: &Shaders,
+        input_texture: &wgpu::Texture,
+        window_size: (u32, u32),
+        display_area: DisplayArea,
+        texture_format: wgpu::TextureFormat,
+        surface_format: wgpu::TextureFormat,
+        renderer_config: RendererConfig,
+    ) -> Option<Self> {
+        let mode = renderer_config.magnifier_mode;
+        if mode == MagnifierMode::None {
+            return None;
+        }
+
+        // The sharp bilinear shader needs to know the effective display scale of the zoomed view
+        let zoomed_area = DisplayArea {
+            width: (f64::from(display_area.width) * MAGNIFIER_ZOOM).round() as u32,
+            height: (f64::from(display_area.height) * MAGNIFIER_ZOOM).round() as u32,
+            x: 0,
+            y: 0,
+        };
+        let view_pipeline = OutputPipeline::create(
+            device,
+            shaders,
+            input_texture,
+            &VERTICES,
+            zoomed_area,
+            surface_format,
+            renderer_config.filter_mode,
+        );
+
+        // The outline is drawn by stretching a single white texel behind the view
+        let outline_pipeline = (mode == MagnifierMode::PictureInPicture).then(|| {
+            let outline_texture = device.create_texture_with_data(
+                queue,
+                &wgpu::TextureDescriptor {
+                    label: "magnifier_outline_texture".into(),
+                    size: wgpu::Extent3d { width: 1, height: 1, depth_or_array_layers: 1 },
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: texture_format,
+                    usage: wgpu::TextureUsages::TEXTURE_BINDING,
+                    view_formats: &[],
+                },
+                &[255; 4],
+            );
+
+            OutputPipeline::create(
+                device,
+                shaders,
+                &outline_texture,
+                &VERTICES,
+                display_area,
+                surface_format,
+                FilterMode::Nearest,
+            )
+        });
+
+        Some(Self {
+            mode,
+            corner: renderer_config.magnifier_corner,
+            window_size,
+            display_area,
+            outline_pipeline,
+            view_pipeline,
+        })
+    }
+
+    fn draw(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        queue: &wgpu::Queue,
+        output_view: &wgpu::TextureView,
+        focus: (f64, f64),
+    ) {
+        let Some((view_area, texture_region)) =
+            magnifier_layout(self.mode, self.corner, self.display_area, focus)
+        else {
+            return;
+        };
+
+        if let Some(outline_pipeline) = &self.outline_pipeline {
+            let outline_width =
+                (f64::from(self.display_area.height) * PIP_OUTLINE_WIDTH).round().max(1.0);
+            outline_pipeline.write_vertices(
+                queue,
+                &quad_vertices(
+                    self.window_size,
+                    view_area.expand(outline_width),
+                    Rect::FULL_TEXTURE,
+                ),
+            );
+            outline_pipeline.draw(encoder, output_view, wgpu::LoadOp::Load);
+        }
+
+        self.view_pipeline
+            .write_vertices(queue, &quad_vertices(self.window_size, view_area, texture_region));
+        self.view_pipeline.draw(encoder, output_view, wgpu::LoadOp::Load);
+    }
+}
+
+// The full rendering pipeline consists of four independent stages:
+//   1. Preprocess: optional blending/anti-dither shader at native resolution
+//   2. Color filter: optional color blindness correction and flash reduction
+//   3. Prescale: integer nearest neighbor scaling, plus scanlines
+//   4. Output: scaling to the display area using the configured filter mode
+//
+// If a border is enabled, it is drawn to the window before the output stage so that the game is
+// always drawn on top of the border. The magnifier, if enabled, is drawn after the output stage.
+// Anything that frontends draw over the rendered frame (e.g. an OSD or GUI overlay) will in turn be
+// drawn on top of all of these
+struct RenderingPipeline {
+    frame_size: FrameSize,
+    pixel_aspect_ratio: Option<PixelAspectRatio>,
+    display_area: DisplayArea,
+    preprocess_pipeline: PreprocessPipeline,
+    color_filter_pipeline: Option<ColorFilterPipeline>,
+    prescale_pipeline: PrescalePipeline,
+    border_pipeline: Option<OutputPipeline>,
+    output_pipeline: OutputPipeline,
+    magnifier_pipeline: Option<MagnifierPipeline>,
+    // Copy of the last uploaded frame, used to only upload rows that changed
+    previous_frame: Vec<Color>,
+}
+
+impl RenderingPipeline {
+    #[allow(clippy::too_many_arguments)]
+    fn create(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        shaders: &Shaders,
+        window_size: (u32, u32),
+        frame_size: FrameSize,
+        pixel_aspect_ratio: Option<PixelAspectRatio>,
+        texture_format: wgpu::TextureFormat,
+        surface_config: &wgpu::SurfaceConfiguration,
+        renderer_config: RendererConfig,
+        border: Option<&BorderImage>,
+    ) -> Self {
+        let input_texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: "input_texture".into(),
+            size: wgpu::Extent3d {
+                width: frame_size.width,
+                height: frame_size.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: texture_format,
+            usage: wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+
+        let preprocess_pipeline = PreprocessPipeline::create(
+            renderer_config.preprocess_shader,
+            device,
+            input_texture,
+            shaders,
+        );
+
+        let color_filter_pipeline = ColorFilterPipeline::create(
+            device,
+            shaders,
+            preprocess_pipeline.output_texture(),
+            renderer_config,
+        );
 
-        let render_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: "render_bind_group".into(),
-            layout: &render_bind_group_layout,
-            entries: &[
-                wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: wgpu::BindingResource::TextureView(&scaled_texture_view),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 1,
-                    resource: wgpu::BindingResource::Sampler(&sampler),
+        let prescale_pipeline = PrescalePipeline::create(
+            device,
+            shaders,
+            color_filter_pipeline
+                .as_ref()
+                .map_or(preprocess_pipeline.output_texture(), |pipeline| &pipeline.output),
+            renderer_config,
+        );
+
+        let border_pipeline = border.map(|border| {
+            let border_area = determine_border_area(window_size.0, window_size.1, border);
+            let border_texture = device.create_texture_with_data(
+                queue,
+                &wgpu::TextureDescriptor {
+                    label: "border_texture".into(),
+                    size: wgpu::Extent3d {
+                        width: border.width(),
+                        height: border.height(),
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: texture_format,
+                    usage: wgpu::TextureUsages::TEXTURE_BINDING,
+                    view_formats: &[],
                 },
-            ],
+                bytemuck::cast_slice(border.pixels()),
+            );
+
+            let pipeline = OutputPipeline::create(
+                device,
+                shaders,
+                &border_texture,
+                &compute_vertices(window_size.0, window_size.1, border_area),
+                border_area,
+                surface_config.format,
+                FilterMode::Linear,
+            );
+            (pipeline, border_screen_area(border, border_area))
         });
 
-        let render_pipeline_layout =
-            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                label: "render_pipeline_layout".into(),
-                bind_group_layouts: &[&render_bind_group_layout],
-                push_constant_ranges: &[],
-            });
+        // If the border has a screen cutout, the game is fit into the cutout instead of the window
+        let game_bounds = border_pipeline
+            .as_ref()
+            .and_then(|&(_, screen_area)| screen_area)
+            .unwrap_or(DisplayArea { width: window_size.0, height: window_size.1, x: 0, y: 0 });
 
-        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: "render_pipeline".into(),
-            layout: Some(&render_pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &shaders.render,
-                entry_point: "vs_main",
-                buffers: &[Vertex::buffer_layout()],
-            },
-            primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleStrip,
-                strip_index_format: None,
-                front_face: wgpu::FrontFace::Ccw,
-                cull_mode: None,
-                unclipped_depth: false,
-                polygon_mode: wgpu::PolygonMode::Fill,
-                conservative: false,
-            },
-            depth_stencil: None,
-            multisample: wgpu::MultisampleState {
-                count: 1,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &shaders.render,
-                entry_point: "fs_main",
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: surface_config.format,
-                    blend: Some(wgpu::BlendState::REPLACE),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-            }),
-            multiview: None,
-        });
+        let mut display_area = determine_display_area(
+            game_bounds.width,
+            game_bounds.height,
+            frame_size,
+            pixel_aspect_ratio,
+            renderer_config.force_integer_height_scaling,
+        );
+        display_area.x += game_bounds.x;
+        display_area.y += game_bounds.y;
+
+        let vertices = match (pixel_aspect_ratio, &border_pipeline) {
+            (None, None) => VERTICES.into(),
+            _ => compute_vertices(window_size.0, window_size.1, display_area),
+        };
+
+        let output_pipeline = OutputPipeline::create(
+            device,
+            shaders,
+            &prescale_pipeline.output,
+            &vertices,
+            display_area,
+            surface_config.format,
+            renderer_config.filter_mode,
+        );
+
+        let magnifier_pipeline = MagnifierPipeline::create(
+            device,
+            queue,
+            shaders,
+            &prescale_pipeline.output,
+            window_size,
+            display_area,
+            texture_format,
+            surface_config.format,
+            renderer_config,
+        );
 
         Self {
             frame_size,
             pixel_aspect_ratio,
             display_area,
-            scaled_texture,
-            vertex_buffer,
             preprocess_pipeline,
-            prescale_bind_group,
+            color_filter_pipeline,
             prescale_pipeline,
-            render_bind_group,
-            render_pipeline,
+            border_pipeline: border_pipeline.map(|(pipeline, _)| pipeline),
+            output_pipeline,
+            magnifier_pipeline,
+            previous_frame: Vec::new(),
         }
     }
 
     fn render(
-        &self,
+        &mut self,
         device: &wgpu::Device,
         queue: &wgpu::Queue,
         surface: &wgpu::Surface,
         frame_buffer: &[Color],
+        magnifier_focus: (f64, f64),
     ) -> Result<(), RendererError> {
         let output = surface.get_current_texture()?;
         let output_texture_view =
             output.texture.create_view(&wgpu::TextureViewDescriptor::default());
 
-        let input_texture = self.preprocess_pipeline.input_texture();
-        queue.write_texture(
-            wgpu::ImageCopyTexture {
-                texture: input_texture,
-                mip_level: 0,
-                origin: wgpu::Origin3d::ZERO,
-                aspect: wgpu::TextureAspect::All,
-            },
-            bytemuck::cast_slice(frame_buffer),
-            wgpu::ImageDataLayout {
-                offset: 0,
-                bytes_per_row: Some(self.frame_size.width * 4),
-                rows_per_image: Some(self.frame_size.height),
-            },
-            input_texture.size(),
-        );
+        let FrameSize { width, height } = self.frame_size;
+        let frame_buffer = &frame_buffer[..(width * height) as usize];
+
+        let dirty_rows = dirty_rows(&self.previous_frame, frame_buffer, width);
+        if let Some(dirty_rows) = dirty_rows.clone() {
+            let input_texture = self.preprocess_pipeline.input_texture();
+            let start = (dirty_rows.start * width) as usize;
+            let end = (dirty_rows.end * width) as usize;
+            queue.write_texture(
+                wgpu::ImageCopyTexture {
+                    texture: input_texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d { x: 0, y: dirty_rows.start, z: 0 },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                bytemuck::cast_slice(&frame_buffer[start..end]),
+                wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(width * 4),
+                    rows_per_image: Some(dirty_rows.len() as u32),
+                },
+                wgpu::Extent3d { width, height: dirty_rows.len() as u32, depth_or_array_layers: 1 },
+            );
+
+            if self.previous_frame.len() == frame_buffer.len() {
+                self.previous_frame[start..end].copy_from_slice(&frame_buffer[start..end]);
+            } else {
+                self.previous_frame = frame_buffer.to_vec();
+            }
+        }
 
         let mut encoder = device
             .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: "encoder".into() });
 
-        self.preprocess_pipeline.draw(&mut encoder);
-
-        let scaled_texture_view =
-            self.scaled_texture.create_view(&wgpu::TextureViewDescriptor::default());
-
-        {
-            let mut prescale_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: "prescale_pass".into(),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &scaled_texture_view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
-                        store: wgpu::StoreOp::Store,
-                    },
-                })],
-                depth_stencil_attachment: None,
-                timestamp_writes: None,
-                occlusion_query_set: None,
-            });
-
-            prescale_pass.set_bind_group(0, &self.prescale_bind_group, &[]);
-            prescale_pass.set_pipeline(&self.prescale_pipeline);
-
-            prescale_pass.draw(0..VERTICES.len() as u32, 0..1);
+        // The preprocess, color filter, and prescale outputs persist between frames, so they only
+        // need to be redrawn if the frame changed or flash reduction is still transitioning
+        let color_filter_redraw =
+            self.color_filter_pipeline.as_ref().is_some_and(ColorFilterPipeline::needs_redraw);
+        if dirty_rows.is_some() {
+            self.preprocess_pipeline.draw(&mut encoder);
+        }
+        if dirty_rows.is_some() || color_filter_redraw {
+            if let Some(color_filter_pipeline) = &mut self.color_filter_pipeline {
+                color_filter_pipeline.prepare(queue, dirty_rows.is_some().then_some(frame_buffer));
+                color_filter_pipeline.draw(&mut encoder);
+            }
+            self.prescale_pipeline.draw(&mut encoder);
         }
 
-        {
-            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: "render_pass".into(),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &output_texture_view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
-                        store: wgpu::StoreOp::Store,
-                    },
-                })],
-                depth_stencil_attachment: None,
-                timestamp_writes: None,
-                occlusion_query_set: None,
-            });
-
-            render_pass.set_bind_group(0, &self.render_bind_group, &[]);
-            render_pass.set_pipeline(&self.render_pipeline);
-            render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        let clear = wgpu::LoadOp::Clear(wgpu::Color::BLACK);
+        match &self.border_pipeline {
+            Some(border_pipeline) => {
+                border_pipeline.draw(&mut encoder, &output_texture_view, clear);
+                self.output_pipeline.draw(&mut encoder, &output_texture_view, wgpu::LoadOp::Load);
+            }
+            None => {
+                self.output_pipeline.draw(&mut encoder, &output_texture_view, clear);
+            }
+        }
 
-            render_pass.draw(0..VERTICES.len() as u32, 0..1);
+        if let Some(magnifier_pipeline) = &self.magnifier_pipeline {
+            magnifier_pipeline.draw(&mut encoder, queue, &output_texture_view, magnifier_focus);
         }
 
         queue.submit(iter::once(encoder.finish()));
@@ -637,6 +1431,25 @@ impl RenderingPipeline {
     }
 }
 
+// Returns the smallest range of rows that contains every row that differs between the two frames,
+// or None if the frames are identical. Every row is considered dirty if there is no previous frame
+fn dirty_rows(previous_frame: &[Color], frame_buffer: &[Color], width: u32) -> Option<Range<u32>> {
+    let height = (frame_buffer.len() / width as usize) as u32;
+    if previous_frame.len() != frame_buffer.len() {
+        return Some(0..height);
+    }
+
+    let row_changed = |row: &u32| {
+        let start = (row * width) as usize;
+        let end = start + width as usize;
+        previous_frame[start..end] != frame_buffer[start..end]
+    };
+
+    let first = (0..height).find(row_changed)?;
+    let last = (first..height).rev().find(row_changed).unwrap_or(first);
+    Some(first..last + 1)
+}
+
 fn compute_vertices(
     window_width: u32,
     window_height: u32,
@@ -718,6 +1531,39 @@ fn determine_display_area(
     DisplayArea { width: screen_width, height: screen_height, x, y }
 }
 
+// Scale the border image to fit the window while preserving its aspect ratio
+fn determine_border_area(
+    window_width: u32,
+    window_height: u32,
+    border: &BorderImage,
+) -> DisplayArea {
+    let border_aspect_ratio = f64::from(border.width()) / f64::from(border.height());
+
+    let width =
+        cmp::min(window_width, (f64::from(window_height) * border_aspect_ratio).round() as u32);
+    let height = cmp::min(window_height, (f64::from(width) / border_aspect_ratio).round() as u32);
+
+    let x = (window_width - width) / 2;
+    let y = (window_height - height) / 2;
+
+    DisplayArea { width, height, x, y }
+}
+
+// Map the border image's screen cutout (if any) to window coordinates
+fn border_screen_area(border: &BorderImage, border_area: DisplayArea) -> Option<DisplayArea> {
+    let screen_area = border.screen_area()?;
+
+    let scale_x = f64::from(border_area.width) / f64::from(border.width());
+    let scale_y = f64::from(border_area.height) / f64::from(border.height());
+
+    Some(DisplayArea {
+        width: (f64::from(screen_area.width) * scale_x).round() as u32,
+        height: (f64::from(screen_area.height) * scale_y).round() as u32,
+        x: border_area.x + (f64::from(screen_area.x) * scale_x).round() as u32,
+        y: border_area.y + (f64::from(screen_area.y) * scale_y).round() as u32,
+    })
+}
+
 fn scale_vertex_position(
     position: f32,
     window_dimension: u32,
@@ -746,6 +1592,8 @@ pub enum RendererError {
         "wgpu adapter does not support present mode {desired:?}; supported modes are {available:?}"
     )]
     UnsupportedPresentMode { desired: wgpu::PresentMode, available: Vec<wgpu::PresentMode> },
+    #[error("Recreating a lost wgpu device is not supported on this platform")]
+    DeviceRecoveryUnsupported,
 }
 
 struct Shaders {
@@ -753,6 +1601,7 @@ struct Shaders {
     prescale: wgpu::ShaderModule,
     identity: wgpu::ShaderModule,
     hblur: wgpu::ShaderModule,
+    color_filter: wgpu::ShaderModule,
 }
 
 impl Shaders {
@@ -761,8 +1610,9 @@ impl Shaders {
         let prescale = device.create_shader_module(wgpu::include_wgsl!("prescale.wgsl"));
         let identity = device.create_shader_module(wgpu::include_wgsl!("identity.wgsl"));
         let hblur = device.create_shader_module(wgpu::include_wgsl!("hblur.wgsl"));
+        let color_filter = device.create_shader_module(wgpu::include_wgsl!("colorfilter.wgsl"));
 
-        Self { render, prescale, identity, hblur }
+        Self { render, prescale, identity, hblur, color_filter }
     }
 }
 
@@ -775,24 +1625,38 @@ pub struct WgpuRenderer<Window> {
     queue: wgpu::Queue,
     shaders: Shaders,
     texture_format: wgpu::TextureFormat,
+    device_lost: Arc<AtomicBool>,
     renderer_config: RendererConfig,
+    custom_border: Option<BorderImage>,
+    custom_border_path: Option<PathBuf>,
+    system_default_border: Option<BorderImage>,
     pipeline: Option<RenderingPipeline>,
+    // Magnifier focus point as a fraction of the frame width and height
+    magnifier_focus: (f64, f64),
     frame_count: u64,
     speed_multiplier: u64,
+    last_recovery_attempt: Option<u64>,
     // SAFETY: The surface must not outlive the window it was created from, thus the window must be
     // declared after the surface
     window: Window,
     window_size_fn: WindowSizeFn<Window>,
 }
 
-impl<Window: HasRawDisplayHandle + HasRawWindowHandle> WgpuRenderer<Window> {
-    /// Construct a wgpu renderer from the given window and config.
-    ///
-    /// # Errors
-    ///
-    /// This function will return any errors encountered while initializing wgpu.
-    pub async fn new(
-        window: Window,
+// GPU resources that are tied to a specific wgpu device and surface, and that need to be recreated
+// if the device or surface is lost
+struct WgpuResources {
+    surface: wgpu::Surface,
+    surface_config: wgpu::SurfaceConfiguration,
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    shaders: Shaders,
+    texture_format: wgpu::TextureFormat,
+    device_lost: Arc<AtomicBool>,
+}
+
+impl WgpuResources {
+    async fn create<Window: HasRawDisplayHandle + HasRawWindowHandle>(
+        window: &Window,
         window_size_fn: WindowSizeFn<Window>,
         config: RendererConfig,
     ) -> Result<Self, RendererError> {
@@ -811,7 +1675,7 @@ impl<Window: HasRawDisplayHandle + HasRawWindowHandle> WgpuRenderer<Window> {
         });
 
         // SAFETY: The surface must not outlive the window it was created from
-        let surface = unsafe { instance.create_surface(&window) }?;
+        let surface = unsafe { instance.create_surface(window) }?;
 
         let adapter = instance
             .request_adapter(&wgpu::RequestAdapterOptions {
@@ -859,7 +1723,7 @@ impl<Window: HasRawDisplayHandle + HasRawWindowHandle> WgpuRenderer<Window> {
                 surface_capabilities.formats[0]
             });
 
-        let (window_width, window_height) = window_size_fn(&window);
+        let (window_width, window_height) = window_size_fn(window);
         let surface_config = wgpu::SurfaceConfiguration {
             usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
             format: surface_format,
@@ -879,6 +1743,54 @@ impl<Window: HasRawDisplayHandle + HasRawWindowHandle> WgpuRenderer<Window> {
 
         let shaders = Shaders::create(&device);
 
+        // wgpu reports device loss (e.g. from a driver update or a GPU reset after sleep/resume)
+        // through the uncaptured error handler. Record it so that the renderer can recreate the
+        // device instead of crashing
+        let device_lost = Arc::new(AtomicBool::new(false));
+        let handler_device_lost = Arc::clone(&device_lost);
+        device.on_uncaptured_error(Box::new(move |error| {
+            if is_device_lost_error(&error) {
+                log::error!("wgpu device lost: {error}");
+                handler_device_lost.store(true, Ordering::Relaxed);
+            } else {
+                panic!("wgpu error: {error}");
+            }
+        }));
+
+        Ok(Self { surface, surface_config, device, queue, shaders, texture_format, device_lost })
+    }
+}
+
+fn is_device_lost_error(error: &wgpu::Error) -> bool {
+    let wgpu::Error::Validation { description, .. } = error else { return false };
+    description.to_ascii_lowercase().contains("device is lost")
+}
+
+// Minimum number of frames between attempts to recreate wgpu resources after device loss, so that
+// a GPU that is still resetting isn't hammered with device requests
+const RECOVERY_RETRY_FRAMES: u64 = 60;
+
+impl<Window: HasRawDisplayHandle + HasRawWindowHandle> WgpuRenderer<Window> {
+    /// Construct a wgpu renderer from the given window and config.
+    ///
+    /// # Errors
+    ///
+    /// This function will return any errors encountered while initializing wgpu.
+    pub async fn new(
+        window: Window,
+        window_size_fn: WindowSizeFn<Window>,
+        config: RendererConfig,
+    ) -> Result<Self, RendererError> {
+        let WgpuResources {
+            surface,
+            surface_config,
+            device,
+            queue,
+            shaders,
+            texture_format,
+            device_lost,
+        } = WgpuResources::create(&window, window_size_fn, config).await?;
+
         Ok(Self {
             surface,
             surface_config,
@@ -886,17 +1798,82 @@ impl<Window: HasRawDisplayHandle + HasRawWindowHandle> WgpuRenderer<Window> {
             queue,
             shaders,
             texture_format,
+            device_lost,
             renderer_config: config,
+            custom_border: None,
+            custom_border_path: None,
+            system_default_border: None,
             pipeline: None,
+            magnifier_focus: (0.5, 0.5),
             frame_count: 0,
             speed_multiplier: 1,
+            last_recovery_attempt: None,
             window,
             window_size_fn,
         })
     }
+
+    // Returns true if the resources were successfully recreated. Failures are logged and retried
+    // on a later frame rather than returned so that a temporarily unavailable GPU doesn't end
+    // emulation
+    fn recreate_wgpu_resources(&mut self) -> bool {
+        if self.last_recovery_attempt.is_some_and(|last_attempt| {
+            self.frame_count.wrapping_sub(last_attempt) < RECOVERY_RETRY_FRAMES
+        }) {
+            return false;
+        }
+        self.last_recovery_attempt = Some(self.frame_count);
+
+        log::warn!("Recreating wgpu device and surface");
+
+        match create_resources_blocking(&self.window, self.window_size_fn, self.renderer_config) {
+            Ok(resources) => {
+                self.surface = resources.surface;
+                self.surface_config = resources.surface_config;
+                self.device = resources.device;
+                self.queue = resources.queue;
+                self.shaders = resources.shaders;
+                self.texture_format = resources.texture_format;
+                self.device_lost = resources.device_lost;
+                self.pipeline = None;
+                self.last_recovery_attempt = None;
+
+                log::info!("Successfully recreated wgpu device and surface");
+                true
+            }
+            Err(err) => {
+                log::error!("Error recreating wgpu device and surface, will retry: {err}");
+                false
+            }
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn create_resources_blocking<Window: HasRawDisplayHandle + HasRawWindowHandle>(
+    window: &Window,
+    window_size_fn: WindowSizeFn<Window>,
+    config: RendererConfig,
+) -> Result<WgpuResources, RendererError> {
+    pollster::block_on(WgpuResources::create(window, window_size_fn, config))
+}
+
+// Blocking on a future is not possible in the browser
+#[cfg(target_arch = "wasm32")]
+fn create_resources_blocking<Window: HasRawDisplayHandle + HasRawWindowHandle>(
+    _window: &Window,
+    _window_size_fn: WindowSizeFn<Window>,
+    _config: RendererConfig,
+) -> Result<WgpuResources, RendererError> {
+    Err(RendererError::DeviceRecoveryUnsupported)
 }
 
 impl<Window> WgpuRenderer<Window> {
+    #[must_use]
+    pub fn config(&self) -> RendererConfig {
+        self.renderer_config
+    }
+
     pub fn reload_config(&mut self, config: RendererConfig) {
         self.renderer_config = config;
         self.surface_config.present_mode = config.vsync_mode.to_wgpu_present_mode();
@@ -934,6 +1911,7 @@ impl<Window> WgpuRenderer<Window> {
             let window_size = (self.window_size_fn)(&self.window);
             self.pipeline = Some(RenderingPipeline::create(
                 &self.device,
+                &self.queue,
                 &self.shaders,
                 window_size,
                 frame_size,
@@ -941,10 +1919,61 @@ impl<Window> WgpuRenderer<Window> {
                 self.texture_format,
                 &self.surface_config,
                 self.renderer_config,
+                self.active_border(),
             ));
         }
     }
 
+    /// Set the user-supplied border image, which takes priority over the system default border.
+    pub fn set_custom_border(&mut self, border: Option<BorderImage>) {
+        self.custom_border = border;
+        self.custom_border_path = None;
+
+        // Force render pipeline to be recreated on the next render_frame() call
+        self.pipeline = None;
+    }
+
+    /// Load the user-supplied border image from the given PNG file. Does nothing if the path is
+    /// the same as the path of the currently loaded border image.
+    ///
+    /// If the image fails to load, the error is logged and the custom border is cleared.
+    pub fn set_custom_border_path(&mut self, path: Option<&Path>) {
+        if path.is_some() && self.custom_border_path.as_deref() == path {
+            return;
+        }
+
+        let border = path.and_then(|path| match BorderImage::load(path) {
+            Ok(border) => {
+                log::info!("Loaded border image from '{}'", path.display());
+                Some(border)
+            }
+            Err(err) => {
+                log::error!("Error loading border image from '{}': {err}", path.display());
+                None
+            }
+        });
+
+        self.set_custom_border(border);
+        self.custom_border_path = path.map(Path::to_path_buf);
+    }
+
+    /// Set the system default border image, which is used if border rendering is enabled and no
+    /// custom border image has been set.
+    pub fn set_system_default_border(&mut self, border: Option<BorderImage>) {
+        self.system_default_border = border;
+
+        // Force render pipeline to be recreated on the next render_frame() call
+        self.pipeline = None;
+    }
+
+    fn active_border(&self) -> Option<&BorderImage> {
+        if !self.renderer_config.show_border {
+            return None;
+        }
+
+        self.custom_border.as_ref().or(self.system_default_border.as_ref())
+    }
+
     /// Obtain a shared reference to the window.
     pub fn window(&self) -> &Window {
         &self.window
@@ -971,6 +2000,23 @@ impl<Window> WgpuRenderer<Window> {
         self.speed_multiplier = speed_multiplier;
     }
 
+    /// Move the magnifier's focus point to the frame position under the given window position, in
+    /// window pixels. Positions outside of the display area are clamped to its edges.
+    ///
+    /// Does nothing if a frame has not yet been rendered with the current config.
+    pub fn set_magnifier_focus(&mut self, window_x: i32, window_y: i32) {
+        let Some(pipeline) = &self.pipeline else { return };
+        let display_area = pipeline.display_area;
+
+        let to_fraction = |position: i32, offset: u32, size: u32| {
+            ((f64::from(position) - f64::from(offset)) / f64::from(size.max(1))).clamp(0.0, 1.0)
+        };
+        self.magnifier_focus = (
+            to_fraction(window_x, display_area.x, display_area.width),
+            to_fraction(window_y, display_area.y, display_area.height),
+        );
+    }
+
     /// Obtain the last rendered frame size and the current display area within the window.
     ///
     /// May return None if rendering config was just changed or initialized and a frame has not yet been rendered with
@@ -981,7 +2027,7 @@ impl<Window> WgpuRenderer<Window> {
     }
 }
 
-impl<Window> Renderer for WgpuRenderer<Window> {
+impl<Window: HasRawDisplayHandle + HasRawWindowHandle> Renderer for WgpuRenderer<Window> {
     type Err = RendererError;
 
     fn render_frame(
@@ -995,12 +2041,18 @@ impl<Window> Renderer for WgpuRenderer<Window> {
             return Ok(());
         }
 
+        if self.device_lost.load(Ordering::Relaxed) && !self.recreate_wgpu_resources() {
+            // Skip frames until the device has been recreated
+            return Ok(());
+        }
+
         self.ensure_pipeline(frame_size, pixel_aspect_ratio);
-        match self.pipeline.as_ref().unwrap().render(
+        match self.pipeline.as_mut().unwrap().render(
             &self.device,
             &self.queue,
             &self.surface,
             frame_buffer,
+            self.magnifier_focus,
         ) {
             Ok(()) => {}
             Err(RendererError::WgpuSurface(wgpu::SurfaceError::Outdated)) => {
@@ -1010,9 +2062,127 @@ impl<Window> Renderer for WgpuRenderer<Window> {
                 );
                 self.surface.configure(&self.device, &self.surface_config);
             }
+            Err(RendererError::WgpuSurface(wgpu::SurfaceError::Timeout)) => {
+                log::warn!(
+                    "Skipping frame because wgpu timed out acquiring the next surface texture"
+                );
+            }
+            Err(RendererError::WgpuSurface(wgpu::SurfaceError::Lost)) => {
+                // This can happen after sleep/resume or a display driver update; the surface and
+                // possibly the device need to be recreated
+                log::warn!("Skipping frame because wgpu surface was lost");
+                self.device_lost.store(true, Ordering::Relaxed);
+            }
             Err(err) => return Err(err),
         }
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dirty_rows_range() {
+        let previous = vec![Color::BLACK; 4 * 5];
+
+        assert_eq!(dirty_rows(&[], &previous, 4), Some(0..5));
+        assert_eq!(dirty_rows(&previous, &previous, 4), None);
+
+        let mut current = previous.clone();
+        current[4 + 2] = Color::rgb(255, 255, 255);
+        assert_eq!(dirty_rows(&previous, &current, 4), Some(1..2));
+
+        current[3 * 4] = Color::rgb(255, 0, 0);
+        assert_eq!(dirty_rows(&previous, &current, 4), Some(1..4));
+    }
+
+    #[test]
+    fn color_blind_correction_preserves_grays() {
+        for filter in [
+            ColorBlindFilter::Protanopia,
+            ColorBlindFilter::Deuteranopia,
+            ColorBlindFilter::Tritanopia,
+        ] {
+            for row in filter.correction_matrix() {
+                let sum: f64 = row.iter().sum();
+                assert!((sum - 1.0).abs() < 0.01, "{filter}: {row:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn flash_limiter_caps_luminance_change() {
+        let black = vec![Color::BLACK; 16];
+        let white = vec![Color::rgb(255, 255, 255); 16];
+
+        let mut limiter = FlashLimiter::new(false);
+        limiter.set_frame(&black, &IDENTITY_MATRIX);
+        assert!((limiter.next_blend_weight() - 1.0).abs() < 1e-9);
+        assert!(limiter.settled);
+
+        limiter.set_frame(&white, &IDENTITY_MATRIX);
+        assert!((limiter.next_blend_weight() - MAX_LUMINANCE_CHANGE).abs() < 1e-9);
+
+        let mut frames = 1;
+        while !limiter.settled {
+            limiter.next_blend_weight();
+            frames += 1;
+            assert!(frames <= 100);
+        }
+        assert!((24..=26).contains(&frames), "{frames}");
+
+        // Small changes are not limited
+        let mut gray = white.clone();
+        gray[0] = Color::rgb(200, 200, 200);
+        limiter.set_frame(&gray, &IDENTITY_MATRIX);
+        assert!((limiter.next_blend_weight() - 1.0).abs() < 1e-9);
+        assert!(limiter.settled);
+    }
+
+    #[test]
+    fn magnifier_region_stays_within_frame() {
+        let display_area = DisplayArea { width: 800, height: 600, x: 100, y: 0 };
+
+        let (view_area, region) = magnifier_layout(
+            MagnifierMode::FullScreen,
+            MagnifierCorner::default(),
+            display_area,
+            (0.5, 0.5),
+        )
+        .unwrap();
+        assert!((view_area.left - 100.0).abs() < 1e-9 && (view_area.right - 900.0).abs() < 1e-9);
+        assert!((region.left - 0.25).abs() < 1e-9 && (region.right - 0.75).abs() < 1e-9);
+
+        // Focus points near the edges clamp the region to the frame
+        let (_, region) = magnifier_layout(
+            MagnifierMode::FullScreen,
+            MagnifierCorner::default(),
+            display_area,
+            (0.0, 1.0),
+        )
+        .unwrap();
+        assert!(region.left.abs() < 1e-9 && (region.right - 0.5).abs() < 1e-9);
+        assert!((region.top - 0.5).abs() < 1e-9 && (region.bottom - 1.0).abs() < 1e-9);
+
+        let (view_area, region) = magnifier_layout(
+            MagnifierMode::PictureInPicture,
+            MagnifierCorner::TopLeft,
+            display_area,
+            (1.0, 0.0),
+        )
+        .unwrap();
+        assert!((view_area.left - 118.0).abs() < 1e-9 && (view_area.top - 18.0).abs() < 1e-9);
+        assert!((region.left - 0.8).abs() < 1e-9 && (region.right - 1.0).abs() < 1e-9);
+
+        assert!(magnifier_layout(
+            MagnifierMode::None,
+            MagnifierCorner::default(),
+            display_area,
+            (0.5, 0.5)
+        )
+        .is_none());
+    }
+}
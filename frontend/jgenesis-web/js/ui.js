@@ -65,6 +65,27 @@ export function setSaveUiEnabled(saveUiEnabled) {
     }
 }
 
+/**
+ * @param visible {boolean}
+ */
+export function setFrameSkipIndicatorVisible(visible) {
+    document.getElementById("jgenesis-frame-skip-indicator").hidden = !visible;
+}
+
+/**
+ * @param signal {string}
+ */
+export function setNetplaySignal(signal) {
+    document.getElementById("netplay-signal").value = signal;
+}
+
+/**
+ * @param status {string}
+ */
+export function setNetplayStatus(status) {
+    document.getElementById("netplay-status").innerText = status;
+}
+
 /**
  * @param key {string}
  * @return {string | null}
@@ -1,14 +1,19 @@
+use crate::netplay::NetplayCommand;
 use crate::SmsGgConsole;
-use genesis_core::input::GenesisControllerType;
-use genesis_core::{GenesisAspectRatio, GenesisEmulatorConfig};
+use genesis_core::input::serial::GenesisSerialPort;
+use genesis_core::input::{GenesisControllerType, GenesisMultitap};
+use genesis_core::{GenesisAspectRatio, GenesisEmulatorConfig, GenesisRegionSpoof};
+use jgenesis_common::audio::ResamplerQuality;
+use jgenesis_common::command::{EmulatorCommand, OpenFileKind};
 use jgenesis_common::frontend::{PixelAspectRatio, TimingMode};
 use jgenesis_proc_macros::{EnumDisplay, EnumFromStr};
 use jgenesis_renderer::config::{
-    FilterMode, PreprocessShader, PrescaleFactor, RendererConfig, Scanlines, VSyncMode, WgpuBackend,
+    ColorBlindFilter, FilterMode, MagnifierCorner, MagnifierMode, PreprocessShader, PrescaleFactor,
+    RendererConfig, Scanlines, VSyncMode, WgpuBackend,
 };
 use smsgg_core::psg::PsgVersion;
-use smsgg_core::{SmsGgEmulatorConfig, SmsRegion, VdpVersion};
-use snes_core::api::{SnesAspectRatio, SnesEmulatorConfig};
+use smsgg_core::{SmsControllerType, SmsGgEmulatorConfig, SmsRegion, VdpVersion};
+use snes_core::api::{SnesAspectRatio, SnesEmulatorConfig, SnesEnhancements};
 use std::cell::RefCell;
 use std::collections::VecDeque;
 use std::num::NonZeroU64;
@@ -57,6 +62,7 @@ pub struct CommonWebConfig {
     pub filter_mode: FilterMode,
     pub preprocess_shader: PreprocessShader,
     pub prescale_factor: PrescaleFactor,
+    pub auto_frame_skip: bool,
 }
 
 impl Default for CommonWebConfig {
@@ -65,6 +71,7 @@ impl Default for CommonWebConfig {
             filter_mode: FilterMode::default(),
             preprocess_shader: PreprocessShader::default(),
             prescale_factor: PrescaleFactor::try_from(3).unwrap(),
+            auto_frame_skip: true,
         }
     }
 }
@@ -79,7 +86,13 @@ impl CommonWebConfig {
             force_integer_height_scaling: false,
             filter_mode: self.filter_mode,
             preprocess_shader: self.preprocess_shader,
+            color_blind_filter: ColorBlindFilter::default(),
+            flash_reduction: false,
+            magnifier_mode: MagnifierMode::default(),
+            magnifier_corner: MagnifierCorner::default(),
             use_webgl2_limits: true,
+            pal_50hz_fullscreen: false,
+            show_border: false,
         }
     }
 }
@@ -133,11 +146,16 @@ impl SmsGgWebConfig {
             psg_version,
             pixel_aspect_ratio: Some(pixel_aspect_ratio),
             sms_region: self.region,
+            p1_controller_type: SmsControllerType::default(),
             remove_sprite_limit: self.remove_sprite_limit,
             sms_crop_left_border: self.sms_crop_left_border,
             sms_crop_vertical_border: self.sms_crop_vertical_border,
             fm_sound_unit_enabled: self.fm_unit_enabled,
             overclock_z80: false,
+            audio_resampler_quality: ResamplerQuality::default(),
+            initial_ram_state: None,
+            rng_seed: None,
+            enhancements: SnesEnhancements::default(),
         }
     }
 }
@@ -156,15 +174,35 @@ impl GenesisWebConfig {
         GenesisEmulatorConfig {
             p1_controller_type: GenesisControllerType::default(),
             p2_controller_type: GenesisControllerType::default(),
+            multitap: GenesisMultitap::default(),
+            serial_port: GenesisSerialPort::default(),
             forced_timing_mode: None,
             forced_region: None,
+            region_spoof: GenesisRegionSpoof::default(),
+            report_tmss: false,
             aspect_ratio: self.aspect_ratio,
             adjust_aspect_ratio_in_2x_resolution: true,
             remove_sprite_limits: self.remove_sprite_limits,
             emulate_non_linear_vdp_dac: self.emulate_non_linear_vdp_dac,
             render_vertical_border: self.render_vertical_border,
             render_horizontal_border: self.render_horizontal_border,
+            widescreen: false,
+            hi_res_output: false,
+            expanded_vram: false,
             quantize_ym2612_output: true,
+            audio_resampler_quality: ResamplerQuality::default(),
+            initial_ram_state: None,
+            rng_seed: None,
+        }
+    }
+
+    /// Emulator config for a netplay session. Settings that affect emulation rather than only
+    /// rendering must be identical for both players, so they are not taken from the web config.
+    pub fn to_netplay_emulator_config(&self, rng_seed: u64) -> GenesisEmulatorConfig {
+        GenesisEmulatorConfig {
+            remove_sprite_limits: false,
+            rng_seed: Some(rng_seed),
+            ..self.to_emulator_config()
         }
     }
 }
@@ -181,6 +219,9 @@ impl SnesWebConfig {
             aspect_ratio: self.aspect_ratio,
             audio_60hz_hack: true,
             gsu_overclock_factor: NonZeroU64::new(1).unwrap(),
+            audio_resampler_quality: ResamplerQuality::default(),
+            initial_ram_state: None,
+            rng_seed: None,
         }
     }
 }
@@ -218,6 +259,10 @@ impl WebConfigRef {
         self.borrow_mut().common.prescale_factor = prescale_factor;
     }
 
+    pub fn set_auto_frame_skip(&self, auto_frame_skip: bool) {
+        self.borrow_mut().common.auto_frame_skip = auto_frame_skip;
+    }
+
     pub fn set_sms_timing_mode(&self, timing_mode: &str) {
         let Ok(timing_mode) = timing_mode.parse() else { return };
         self.borrow_mut().smsgg.timing_mode = timing_mode;
@@ -299,18 +344,11 @@ impl Default for WebConfigRef {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum EmulatorCommand {
-    OpenFile,
-    OpenSegaCd,
-    Reset,
-    UploadSaveFile,
-}
-
 #[wasm_bindgen]
 #[derive(Clone, Default)]
 pub struct EmulatorChannel {
     commands: Rc<RefCell<VecDeque<EmulatorCommand>>>,
+    netplay_commands: Rc<RefCell<VecDeque<NetplayCommand>>>,
     current_file_name: Rc<RefCell<String>>,
 }
 
@@ -322,19 +360,49 @@ impl EmulatorChannel {
     }
 
     pub fn request_open_file(&self) {
-        self.commands.borrow_mut().push_back(EmulatorCommand::OpenFile);
+        self.commands
+            .borrow_mut()
+            .push_back(EmulatorCommand::OpenFile { kind: OpenFileKind::Rom, path: None });
     }
 
     pub fn request_open_sega_cd(&self) {
-        self.commands.borrow_mut().push_back(EmulatorCommand::OpenSegaCd);
+        self.commands
+            .borrow_mut()
+            .push_back(EmulatorCommand::OpenFile { kind: OpenFileKind::SegaCdDisc, path: None });
     }
 
     pub fn request_reset(&self) {
-        self.commands.borrow_mut().push_back(EmulatorCommand::Reset);
+        self.commands.borrow_mut().push_back(EmulatorCommand::HardReset);
     }
 
     pub fn request_upload_save_file(&self) {
-        self.commands.borrow_mut().push_back(EmulatorCommand::UploadSaveFile);
+        self.commands
+            .borrow_mut()
+            .push_back(EmulatorCommand::OpenFile { kind: OpenFileKind::SaveFile, path: None });
+    }
+
+    pub fn request_netplay_host(&self) {
+        self.netplay_commands.borrow_mut().push_back(NetplayCommand::Host);
+    }
+
+    pub fn request_netplay_join(&self, offer: String) {
+        self.netplay_commands.borrow_mut().push_back(NetplayCommand::Join { offer });
+    }
+
+    pub fn request_netplay_add_spectator(&self) {
+        self.netplay_commands.borrow_mut().push_back(NetplayCommand::AddSpectator);
+    }
+
+    pub fn request_netplay_spectate(&self, offer: String) {
+        self.netplay_commands.borrow_mut().push_back(NetplayCommand::Spectate { offer });
+    }
+
+    pub fn request_netplay_accept_answer(&self, answer: String) {
+        self.netplay_commands.borrow_mut().push_back(NetplayCommand::AcceptAnswer { answer });
+    }
+
+    pub fn request_netplay_disconnect(&self) {
+        self.netplay_commands.borrow_mut().push_back(NetplayCommand::Disconnect);
     }
 
     pub fn current_file_name(&self) -> String {
@@ -351,6 +419,10 @@ impl EmulatorChannel {
         self.commands.borrow_mut().pop_front()
     }
 
+    pub fn pop_netplay_command(&self) -> Option<NetplayCommand> {
+        self.netplay_commands.borrow_mut().pop_front()
+    }
+
     pub fn set_current_file_name(&self, current_file_name: String) {
         *self.current_file_name.borrow_mut() = current_file_name;
     }
@@ -23,6 +23,12 @@ extern "C" {
 
     pub fn setSaveUiEnabled(save_ui_enabled: bool);
 
+    pub fn setFrameSkipIndicatorVisible(visible: bool);
+
+    pub fn setNetplaySignal(signal: &str);
+
+    pub fn setNetplayStatus(status: &str);
+
     pub fn localStorageGet(key: &str) -> Option<String>;
 
     pub fn localStorageSet(key: &str, value: &str);
@@ -3,22 +3,28 @@
 mod audio;
 mod config;
 mod js;
+mod netplay;
 
 use crate::audio::AudioQueue;
-use crate::config::{EmulatorChannel, EmulatorCommand, WebConfig, WebConfigRef};
+use crate::config::{EmulatorChannel, WebConfig, WebConfigRef};
+use crate::netplay::{NetplayCommand, NetplaySaveWriter, WebNetplay};
 use base64::engine::general_purpose;
 use base64::Engine;
 use bincode::{Decode, Encode};
 use genesis_core::{GenesisEmulator, GenesisInputs};
+use jgenesis_common::command::{EmulatorCommand, OpenFileKind};
 use jgenesis_common::frontend::{
     AudioOutput, Color, EmulatorTrait, FrameSize, Renderer, SaveWriter, TickEffect, TimingMode,
 };
+use jgenesis_common::state::bincode_config;
+use jgenesis_renderer::frameskip::{FrameSkipper, SkippingRenderer, DEFAULT_MAX_FRAME_SKIP};
 use jgenesis_renderer::renderer::WgpuRenderer;
 use rfd::AsyncFileDialog;
 use segacd_core::api::{SegaCdEmulator, SegaCdEmulatorConfig};
 use smsgg_core::{SmsGgEmulator, SmsGgInputs};
 use snes_core::api::{CoprocessorRoms, SnesEmulator};
 use snes_core::input::SnesInputs;
+use std::cmp;
 use std::collections::HashMap;
 use std::error::Error;
 use std::fmt::{Debug, Display};
@@ -110,15 +116,6 @@ impl LocalStorageSaveWriter {
     }
 }
 
-macro_rules! bincode_config {
-    () => {
-        bincode::config::standard()
-            .with_little_endian()
-            .with_fixed_int_encoding()
-            .with_limit::<{ 10 * 1024 * 1024 }>()
-    };
-}
-
 impl SaveWriter for LocalStorageSaveWriter {
     type Err = String;
 
@@ -140,7 +137,7 @@ impl SaveWriter for LocalStorageSaveWriter {
     fn load_serialized<D: Decode>(&mut self, extension: &str) -> Result<D, Self::Err> {
         let file_name = self.get_file_name(extension);
         let bytes = read_save_file(&file_name)?;
-        let (value, _) = bincode::decode_from_slice(&bytes, bincode_config!())
+        let (value, _) = bincode::decode_from_slice(&bytes, bincode_config())
             .map_err(|err| format!("Error serializing value into {file_name}: {err}"))?;
 
         Ok(value)
@@ -148,7 +145,7 @@ impl SaveWriter for LocalStorageSaveWriter {
 
     fn persist_serialized<E: Encode>(&mut self, extension: &str, data: E) -> Result<(), Self::Err> {
         let bytes_len =
-            bincode::encode_into_slice(data, &mut self.serialization_buffer, bincode_config!())
+            bincode::encode_into_slice(data, &mut self.serialization_buffer, bincode_config())
                 .map_err(|err| format!("Error serializing value: {err}"))?;
         let bytes_b64 = general_purpose::STANDARD.encode(&self.serialization_buffer[..bytes_len]);
 
@@ -234,36 +231,34 @@ impl Emulator {
                 noise_generator.render(renderer).expect("Failed to render random noise");
             }
             Self::SmsGg(emulator, inputs, _) => {
-                while emulator
-                    .tick(renderer, audio_output, inputs, save_writer)
-                    .expect("Emulator error")
+                while emulator.tick(renderer, audio_output, inputs).expect("Emulator error")
                     != TickEffect::FrameRendered
                 {}
+                emulator.persist_save(save_writer).expect("Failed to write save file");
             }
             Self::Genesis(emulator, inputs) => {
-                while emulator
-                    .tick(renderer, audio_output, inputs, save_writer)
-                    .expect("Emulator error")
+                while emulator.tick(renderer, audio_output, inputs).expect("Emulator error")
                     != TickEffect::FrameRendered
                 {}
+                emulator.persist_save(save_writer).expect("Failed to write save file");
             }
             Self::SegaCd(emulator, inputs) => {
-                while emulator
-                    .tick(renderer, audio_output, inputs, save_writer)
-                    .expect("Emulator error")
+                while emulator.tick(renderer, audio_output, inputs).expect("Emulator error")
                     != TickEffect::FrameRendered
                 {}
+                emulator.persist_save(save_writer).expect("Failed to write save file");
             }
             Self::Snes(emulator, inputs) => {
-                while emulator
-                    .tick(renderer, audio_output, inputs, save_writer)
-                    .expect("Emulator error")
+                while emulator.tick(renderer, audio_output, inputs).expect("Emulator error")
                     != TickEffect::FrameRendered
                 {}
+                emulator.persist_save(save_writer).expect("Failed to write save file");
             }
         }
     }
 
+    // Saves are persisted after every frame, so there is nothing left to write before resetting.
+    // Persisting here would also clobber an uploaded save file
     fn reset(&mut self, save_writer: &mut LocalStorageSaveWriter) {
         match self {
             Self::None(..) => {}
@@ -337,6 +332,7 @@ impl Emulator {
                 emulator.reload_config(&SegaCdEmulatorConfig {
                     genesis: config.genesis.to_emulator_config(),
                     enable_ram_cartridge: true,
+                    fast_boot: false,
                 });
             }
             Self::Snes(emulator, ..) => {
@@ -508,6 +504,16 @@ fn run_event_loop(
     let mut emulator = Emulator::None(RandomNoiseGenerator::new());
     let mut current_config = config_ref.borrow().clone();
 
+    let mut frame_skipper = FrameSkipper::new(DEFAULT_MAX_FRAME_SKIP);
+    let mut frame_skip_indicator_visible = false;
+
+    // Netplay is Genesis-only and powers on a fresh emulator when a session starts, so the ROM of
+    // the currently loaded cartridge is kept around. While netplay is active, keyboard input goes
+    // to a separate state because the emulator's inputs are overwritten by the session every frame
+    let mut current_rom: Option<Vec<u8>> = None;
+    let mut netplay: Option<WebNetplay> = None;
+    let mut netplay_inputs = GenesisInputs::default();
+
     let event_loop_proxy = event_loop.create_proxy();
     event_loop.run(move |event, _, control_flow| match event {
         Event::UserEvent(user_event) => match user_event {
@@ -516,6 +522,7 @@ fn run_event_loop(
 
                 let prev_file_name = Rc::clone(&save_writer.file_name);
                 save_writer.update_file_name(rom_file_name.clone());
+                let cartridge_rom = bios.is_none().then(|| rom.clone());
                 emulator =
                     match open_emulator(rom, bios, &rom_file_name, &config_ref, &mut save_writer) {
                         Ok(emulator) => emulator,
@@ -526,6 +533,12 @@ fn run_event_loop(
                         }
                     };
 
+                current_rom = cartridge_rom.filter(|_| matches!(emulator, Emulator::Genesis(..)));
+                // The new emulator is not part of the session
+                if netplay.take().is_some() {
+                    js::setNetplayStatus("Not connected");
+                }
+
                 emulator_channel.set_current_file_name(rom_file_name.clone());
 
                 js::setRomTitle(&emulator.rom_title(&rom_file_name));
@@ -538,6 +551,11 @@ fn run_event_loop(
                     return;
                 }
 
+                if netplay.is_some() {
+                    js::alert("Save files cannot be uploaded during netplay");
+                    return;
+                }
+
                 audio_output.suspend();
 
                 // Immediately persist save file because it won't get written again until the game writes to SRAM
@@ -557,46 +575,164 @@ fn run_event_loop(
             }
 
             let fps = emulator.target_fps();
+            let mut frames_due = 0;
             while now >= next_frame_time {
                 next_frame_time += 1000.0 / fps;
+                frames_due += 1;
+            }
+
+            if let Some(active_netplay) = &mut netplay {
+                // Frames run in lockstep with the other player, so there is no catching up on
+                // dropped frames
+                if let Err(err) = run_netplay_frame(
+                    active_netplay,
+                    &mut emulator,
+                    &netplay_inputs,
+                    &current_config,
+                    &mut renderer,
+                    &mut audio_output,
+                ) {
+                    js::alert(&format!("Netplay error: {err}"));
+                    end_netplay(&mut netplay, &mut emulator, &mut save_writer, &current_config);
+                }
+            } else if current_config.common.auto_frame_skip {
+                // Catch up by emulating frames without rendering them, up to the skip limit.
+                // Any frames past the limit are dropped
+                let frames_to_run = cmp::min(frames_due, frame_skipper.max_consecutive_skips() + 1);
+                for remaining in (0..frames_to_run).rev() {
+                    let skip = frame_skipper.next_frame(remaining != 0);
+                    emulator.render_frame(
+                        &mut SkippingRenderer::new(&mut renderer, skip),
+                        &mut audio_output,
+                        &mut save_writer,
+                    );
+                }
+            } else {
+                // Without frame skip, frames that could not be run in time are dropped
+                emulator.render_frame(&mut renderer, &mut audio_output, &mut save_writer);
             }
 
-            emulator.render_frame(&mut renderer, &mut audio_output, &mut save_writer);
+            let frame_skip_active =
+                current_config.common.auto_frame_skip && frame_skipper.is_active();
+            if frame_skip_active != frame_skip_indicator_visible {
+                js::setFrameSkipIndicatorVisible(frame_skip_active);
+                frame_skip_indicator_visible = frame_skip_active;
+            }
 
             let config = config_ref.borrow().clone();
             if config != current_config {
                 renderer.reload_config(config.common.to_renderer_config());
-                emulator.reload_config(&config);
+                match (netplay.as_ref().and_then(WebNetplay::session_info), &mut emulator) {
+                    (Some(session), Emulator::Genesis(genesis, _)) => {
+                        genesis.reload_config(
+                            &config.genesis.to_netplay_emulator_config(session.rng_seed),
+                        );
+                    }
+                    _ => emulator.reload_config(&config),
+                }
                 current_config = config;
             }
 
             while let Some(command) = emulator_channel.pop_command() {
                 match command {
-                    EmulatorCommand::OpenFile => {
+                    // Files can only be opened through the browser's file picker
+                    EmulatorCommand::OpenFile { kind: OpenFileKind::Rom, .. } => {
                         wasm_bindgen_futures::spawn_local(open_file(event_loop_proxy.clone()));
                     }
-                    EmulatorCommand::OpenSegaCd => {
+                    EmulatorCommand::OpenFile { kind: OpenFileKind::SegaCdDisc, .. } => {
                         wasm_bindgen_futures::spawn_local(open_sega_cd(event_loop_proxy.clone()));
                     }
-                    EmulatorCommand::UploadSaveFile => {
+                    EmulatorCommand::OpenFile { kind: OpenFileKind::SaveFile, .. } => {
                         wasm_bindgen_futures::spawn_local(upload_save_file(
                             event_loop_proxy.clone(),
                         ));
                     }
-                    EmulatorCommand::Reset => {
+                    EmulatorCommand::HardReset => {
+                        if netplay.is_some() {
+                            js::alert("Reset is not available during netplay");
+                            continue;
+                        }
+
                         audio_output.suspend();
 
                         emulator.reset(&mut save_writer);
 
                         js::focusCanvas();
                     }
+                    EmulatorCommand::SoftReset
+                    | EmulatorCommand::SaveState
+                    | EmulatorCommand::LoadState
+                    | EmulatorCommand::SwapDisc(_)
+                    | EmulatorCommand::SetPaused(_)
+                    | EmulatorCommand::SetSpeedMultiplier(_)
+                    | EmulatorCommand::Screenshot => {
+                        log::warn!("Command not supported in web frontend: {command:?}");
+                    }
+                }
+            }
+
+            while let Some(command) = emulator_channel.pop_netplay_command() {
+                match command {
+                    NetplayCommand::Host
+                    | NetplayCommand::Join { .. }
+                    | NetplayCommand::Spectate { .. } => {
+                        let Some(rom) = current_rom.clone() else {
+                            js::alert("Load a Genesis ROM before starting netplay");
+                            continue;
+                        };
+
+                        end_netplay(&mut netplay, &mut emulator, &mut save_writer, &current_config);
+
+                        let result = match command {
+                            NetplayCommand::Host => WebNetplay::host(rom),
+                            NetplayCommand::Join { offer } => WebNetplay::join(rom, &offer),
+                            NetplayCommand::Spectate { offer } => WebNetplay::spectate(rom, &offer),
+                            NetplayCommand::AddSpectator
+                            | NetplayCommand::AcceptAnswer { .. }
+                            | NetplayCommand::Disconnect => {
+                                unreachable!("nested match expressions")
+                            }
+                        };
+                        match result {
+                            Ok(new_netplay) => {
+                                netplay = Some(new_netplay);
+                                netplay_inputs = GenesisInputs::default();
+                            }
+                            Err(err) => js::alert(&format!("Unable to start netplay: {err}")),
+                        }
+                    }
+                    NetplayCommand::AddSpectator => {
+                        let result = match &mut netplay {
+                            Some(netplay) => netplay.add_spectator(),
+                            None => Err("Click Host to start a session first".into()),
+                        };
+                        if let Err(err) = result {
+                            js::alert(&format!("Netplay error: {err}"));
+                        }
+                    }
+                    NetplayCommand::AcceptAnswer { answer } => {
+                        let result = match &mut netplay {
+                            Some(netplay) => netplay.accept_answer(&answer),
+                            None => Err("Click Host to create an offer first".into()),
+                        };
+                        if let Err(err) = result {
+                            js::alert(&format!("Netplay error: {err}"));
+                        }
+                    }
+                    NetplayCommand::Disconnect => {
+                        end_netplay(&mut netplay, &mut emulator, &mut save_writer, &current_config);
+                    }
                 }
             }
         }
         Event::WindowEvent { event: window_event, window_id }
             if window_id == renderer.window().id() =>
         {
-            emulator.handle_window_event(&window_event);
+            if netplay.is_some() {
+                handle_genesis_input(&mut netplay_inputs, &window_event);
+            } else {
+                emulator.handle_window_event(&window_event);
+            }
 
             match window_event {
                 WindowEvent::CloseRequested => {
@@ -634,6 +770,76 @@ fn run_event_loop(
     });
 }
 
+fn run_netplay_frame(
+    netplay: &mut WebNetplay,
+    emulator: &mut Emulator,
+    local_inputs: &GenesisInputs,
+    config: &WebConfig,
+    renderer: &mut WgpuRenderer<Window>,
+    audio_output: &mut WebAudioOutput,
+) -> Result<(), Box<dyn Error>> {
+    let (inputs, state) = match netplay {
+        WebNetplay::Player(player) => (player.advance(local_inputs.p1)?, None),
+        WebNetplay::Spectator(spectator) => match spectator.advance()? {
+            Some(frame) => (Some(frame.inputs), frame.state),
+            None => (None, None),
+        },
+    };
+
+    if let Some(session) = netplay.take_started_session() {
+        // Both players power on from the same state; spectators load the host's state over this
+        audio_output.suspend();
+        let genesis = GenesisEmulator::create(
+            netplay.rom().to_vec(),
+            config.genesis.to_netplay_emulator_config(session.rng_seed),
+            &mut NetplaySaveWriter,
+        );
+        *emulator = Emulator::Genesis(genesis, GenesisInputs::default());
+    }
+
+    let Some([p1, p2]) = inputs else { return Ok(()) };
+    let Emulator::Genesis(genesis, genesis_inputs) = emulator else { return Ok(()) };
+
+    if let (Some(state), Some(session)) = (&state, netplay.session_info()) {
+        let emulator_config = config.genesis.to_netplay_emulator_config(session.rng_seed);
+        jgenesis_common::state::load_state(genesis, &emulator_config, state)
+            .map_err(|err| format!("Unable to load state from host: {err}"))?;
+    }
+
+    if let WebNetplay::Player(player) = netplay {
+        // Spectators are sent the state from before the frame runs along with its inputs
+        let state = if player.spectators_need_state() {
+            Some(jgenesis_common::state::save_state(&*genesis)?)
+        } else {
+            None
+        };
+        player.broadcast_frame(&[p1, p2], state.as_deref());
+    }
+
+    genesis_inputs.p1 = p1;
+    genesis_inputs.p2 = p2;
+    emulator.render_frame(renderer, audio_output, &mut NetplaySaveWriter);
+
+    Ok(())
+}
+
+// The netplay emulator was created without loading the local save file, so after a session it is
+// hard reset to load the save before anything can be persisted over it
+fn end_netplay(
+    netplay: &mut Option<WebNetplay>,
+    emulator: &mut Emulator,
+    save_writer: &mut LocalStorageSaveWriter,
+    config: &WebConfig,
+) {
+    let Some(netplay) = netplay.take() else { return };
+    js::setNetplayStatus("Not connected");
+
+    if netplay.session_info().is_some() {
+        emulator.reset(save_writer);
+        emulator.reload_config(config);
+    }
+}
+
 async fn open_file(event_loop_proxy: EventLoopProxy<JgenesisUserEvent>) {
     let file = AsyncFileDialog::new()
         .add_filter("sms/gg/md", &["sms", "gg", "md", "bin", "sfc", "smc"])
@@ -742,6 +948,7 @@ fn open_emulator(
                 SegaCdEmulatorConfig {
                     genesis: config_ref.borrow().genesis.to_emulator_config(),
                     enable_ram_cartridge: true,
+                    fast_boot: false,
                 },
                 save_writer,
             )?;
@@ -0,0 +1,605 @@
+//! Genesis netplay over WebRTC
+//!
+//! There is no signaling server: the host copies an offer string to the other player through any
+//! chat program, and the guest copies back an answer string. Each string is a base64-encoded SDP
+//! that already contains every ICE candidate, so the connection can be established from those two
+//! strings alone.
+//!
+//! Spectators connect to the host the same way, each with their own offer and answer. The host
+//! streams both players' inputs and periodic save states to them.
+
+use crate::js;
+use base64::engine::general_purpose;
+use base64::Engine;
+use bincode::{Decode, Encode};
+use genesis_core::input::GenesisJoypadState;
+use jgenesis_common::frontend::SaveWriter;
+use jgenesis_link::channel::ChannelLink;
+use jgenesis_link::webrtc::WebRtcBridge;
+use jgenesis_netplay::{
+    NetplayRole, NetplaySession, SessionInfo, SpectatorFeed, SpectatorFrame, SpectatorSession,
+    DEFAULT_INPUT_DELAY,
+};
+use std::cell::RefCell;
+use std::error::Error;
+use std::future::Future;
+use std::rc::Rc;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    RtcConfiguration, RtcDataChannelEvent, RtcDataChannelState, RtcIceGatheringState, RtcIceServer,
+    RtcPeerConnection, RtcSdpType, RtcSessionDescriptionInit,
+};
+
+const STUN_SERVER: &str = "stun:stun.l.google.com:19302";
+const DATA_CHANNEL_LABEL: &str = "jgenesis-netplay";
+
+#[derive(Debug, Clone)]
+pub enum NetplayCommand {
+    Host,
+    Join { offer: String },
+    AddSpectator,
+    Spectate { offer: String },
+    AcceptAnswer { answer: String },
+    Disconnect,
+}
+
+type Session = NetplaySession<GenesisJoypadState, ChannelLink>;
+type Feed = SpectatorFeed<GenesisJoypadState, ChannelLink>;
+type Spectator = SpectatorSession<GenesisJoypadState, ChannelLink>;
+
+// Set by async signaling tasks and checked every frame
+type SignalingError = Rc<RefCell<Option<String>>>;
+
+// The bridge is created as soon as the data channel exists so that no message can arrive before
+// its onmessage handler is installed
+type PendingChannel = Rc<RefCell<Option<(WebRtcBridge, ChannelLink)>>>;
+
+/// A WebRTC peer connection with a single data channel, set up through copy/paste signaling.
+struct PeerLink {
+    peer_connection: RtcPeerConnection,
+    pending_channel: PendingChannel,
+    signaling_error: SignalingError,
+    signal_published: bool,
+    awaiting_answer: bool,
+    _on_data_channel: Option<Closure<dyn FnMut(RtcDataChannelEvent)>>,
+}
+
+impl PeerLink {
+    /// Create an offer. [`poll_signal`](Self::poll_signal) shows it in the UI once ICE gathering
+    /// completes.
+    fn offer() -> Result<Self, Box<dyn Error>> {
+        let peer_connection = new_peer_connection()?;
+        let data_channel = peer_connection.create_data_channel(DATA_CHANNEL_LABEL);
+        let pending_channel = Rc::new(RefCell::new(Some(WebRtcBridge::new(data_channel))));
+
+        let signaling_error = SignalingError::default();
+        spawn_signaling(&signaling_error, create_offer(peer_connection.clone()));
+
+        Ok(Self {
+            peer_connection,
+            pending_channel,
+            signaling_error,
+            signal_published: false,
+            awaiting_answer: true,
+            _on_data_channel: None,
+        })
+    }
+
+    /// Create an answer to `offer`. [`poll_signal`](Self::poll_signal) shows it in the UI once ICE
+    /// gathering completes.
+    fn answer(offer: &str) -> Result<Self, Box<dyn Error>> {
+        let offer_sdp = decode_signal(offer)?;
+        let peer_connection = new_peer_connection()?;
+
+        let pending_channel = PendingChannel::default();
+        let on_data_channel = {
+            let pending_channel = Rc::clone(&pending_channel);
+            Closure::<dyn FnMut(RtcDataChannelEvent)>::new(move |event: RtcDataChannelEvent| {
+                *pending_channel.borrow_mut() = Some(WebRtcBridge::new(event.channel()));
+            })
+        };
+        peer_connection.set_ondatachannel(Some(on_data_channel.as_ref().unchecked_ref()));
+
+        let signaling_error = SignalingError::default();
+        spawn_signaling(&signaling_error, create_answer(peer_connection.clone(), offer_sdp));
+
+        Ok(Self {
+            peer_connection,
+            pending_channel,
+            signaling_error,
+            signal_published: false,
+            awaiting_answer: false,
+            _on_data_channel: Some(on_data_channel),
+        })
+    }
+
+    fn accept_answer(&mut self, answer: &str) -> Result<(), Box<dyn Error>> {
+        let answer_sdp = decode_signal(answer)?;
+        spawn_signaling(
+            &self.signaling_error,
+            set_remote_description(self.peer_connection.clone(), RtcSdpType::Answer, answer_sdp),
+        );
+        self.awaiting_answer = false;
+
+        Ok(())
+    }
+
+    /// Check for signaling errors, and show the local connection string once it is complete.
+    /// Returns true on the call that shows it.
+    fn poll_signal(&mut self) -> Result<bool, Box<dyn Error>> {
+        if let Some(err) = self.signaling_error.borrow_mut().take() {
+            return Err(err.into());
+        }
+
+        if self.signal_published
+            || self.peer_connection.ice_gathering_state() != RtcIceGatheringState::Complete
+        {
+            return Ok(false);
+        }
+        let Some(description) = self.peer_connection.local_description() else { return Ok(false) };
+
+        js::setNetplaySignal(&general_purpose::STANDARD.encode(description.sdp()));
+        self.signal_published = true;
+
+        Ok(true)
+    }
+
+    /// Returns the data channel the first time this is called after it opens.
+    fn take_open_channel(&self) -> Option<(WebRtcBridge, ChannelLink)> {
+        let is_open = self.pending_channel.borrow().as_ref().is_some_and(|(bridge, _)| {
+            bridge.data_channel().ready_state() == RtcDataChannelState::Open
+        });
+        if !is_open {
+            return None;
+        }
+
+        self.pending_channel.borrow_mut().take()
+    }
+}
+
+impl Drop for PeerLink {
+    fn drop(&mut self) {
+        self.peer_connection.set_ondatachannel(None);
+        self.peer_connection.close();
+    }
+}
+
+fn check_open(bridge: &WebRtcBridge) -> Result<(), Box<dyn Error>> {
+    if bridge.data_channel().ready_state() == RtcDataChannelState::Closed {
+        return Err("Disconnected".into());
+    }
+
+    Ok(())
+}
+
+/// Host side of one spectator's connection.
+struct SpectatorConnection {
+    link: PeerLink,
+    feed: Option<(WebRtcBridge, Feed)>,
+}
+
+/// An active netplay connection, either as one of the two players or as a spectator.
+pub enum WebNetplay {
+    Player(WebPlayer),
+    Spectator(WebSpectator),
+}
+
+impl WebNetplay {
+    pub fn host(rom: Vec<u8>) -> Result<Self, Box<dyn Error>> {
+        WebPlayer::host(rom).map(Self::Player)
+    }
+
+    pub fn join(rom: Vec<u8>, offer: &str) -> Result<Self, Box<dyn Error>> {
+        WebPlayer::join(rom, offer).map(Self::Player)
+    }
+
+    pub fn spectate(rom: Vec<u8>, offer: &str) -> Result<Self, Box<dyn Error>> {
+        WebSpectator::join(rom, offer).map(Self::Spectator)
+    }
+
+    pub fn add_spectator(&mut self) -> Result<(), Box<dyn Error>> {
+        match self {
+            Self::Player(player) => player.add_spectator(),
+            Self::Spectator(_) => Err("Only the host can add spectators".into()),
+        }
+    }
+
+    pub fn accept_answer(&mut self, answer: &str) -> Result<(), Box<dyn Error>> {
+        match self {
+            Self::Player(player) => player.accept_answer(answer),
+            Self::Spectator(_) => Err("Only the host can accept an answer".into()),
+        }
+    }
+
+    /// Returns the session settings once, on the frame where the session starts. The caller must
+    /// power on a fresh emulator with these settings before running that frame.
+    pub fn take_started_session(&mut self) -> Option<SessionInfo> {
+        match self {
+            Self::Player(player) => player.started_session.take(),
+            Self::Spectator(spectator) => spectator.started_session.take(),
+        }
+    }
+
+    /// Settings of the current session, or `None` if the session has not started.
+    pub fn session_info(&self) -> Option<&SessionInfo> {
+        match self {
+            Self::Player(player) => player.session_info(),
+            Self::Spectator(spectator) => spectator.session_info(),
+        }
+    }
+
+    pub fn rom(&self) -> &[u8] {
+        match self {
+            Self::Player(player) => &player.rom,
+            Self::Spectator(spectator) => &spectator.rom,
+        }
+    }
+}
+
+pub struct WebPlayer {
+    role: NetplayRole,
+    rom: Vec<u8>,
+    link: PeerLink,
+    connection: Option<(WebRtcBridge, Session)>,
+    started_session: Option<SessionInfo>,
+    spectators: Vec<SpectatorConnection>,
+}
+
+impl WebPlayer {
+    /// Start hosting a session for `rom`. The offer string is shown in the UI once ICE gathering
+    /// completes.
+    pub fn host(rom: Vec<u8>) -> Result<Self, Box<dyn Error>> {
+        let link = PeerLink::offer()?;
+        js::setNetplayStatus("Creating offer...");
+
+        Ok(Self::new(NetplayRole::Host, rom, link))
+    }
+
+    /// Join the session that produced `offer`, which must be running the same ROM. The answer
+    /// string is shown in the UI once ICE gathering completes.
+    pub fn join(rom: Vec<u8>, offer: &str) -> Result<Self, Box<dyn Error>> {
+        let link = PeerLink::answer(offer)?;
+        js::setNetplayStatus("Creating answer...");
+
+        Ok(Self::new(NetplayRole::Guest, rom, link))
+    }
+
+    fn new(role: NetplayRole, rom: Vec<u8>, link: PeerLink) -> Self {
+        Self { role, rom, link, connection: None, started_session: None, spectators: Vec::new() }
+    }
+
+    /// Complete signaling with an answer string. The answer is for the most recent offer that has
+    /// not been answered yet, which is either the newest spectator's or the other player's.
+    pub fn accept_answer(&mut self, answer: &str) -> Result<(), Box<dyn Error>> {
+        if self.role != NetplayRole::Host {
+            return Err("Only the host can accept an answer".into());
+        }
+
+        let spectator_link = self
+            .spectators
+            .iter_mut()
+            .rev()
+            .map(|spectator| &mut spectator.link)
+            .find(|link| link.awaiting_answer);
+        let link = match spectator_link {
+            Some(link) => link,
+            None if self.link.awaiting_answer => &mut self.link,
+            None => return Err("There is no offer waiting for an answer".into()),
+        };
+        link.accept_answer(answer)?;
+
+        js::setNetplayStatus("Connecting...");
+
+        Ok(())
+    }
+
+    /// Create an offer for a new spectator. Only the host can add spectators.
+    pub fn add_spectator(&mut self) -> Result<(), Box<dyn Error>> {
+        if self.role != NetplayRole::Host {
+            return Err("Only the host can add spectators".into());
+        }
+
+        let link = PeerLink::offer()?;
+        self.spectators.push(SpectatorConnection { link, feed: None });
+
+        js::setNetplayStatus("Creating spectator offer...");
+
+        Ok(())
+    }
+
+    /// Exchange inputs for the next frame. Returns both players' inputs if the frame should run,
+    /// or `None` if still connecting or waiting on the other player.
+    pub fn advance(
+        &mut self,
+        local_input: GenesisJoypadState,
+    ) -> Result<Option<[GenesisJoypadState; 2]>, Box<dyn Error>> {
+        if self.link.poll_signal()? {
+            js::setNetplayStatus(match self.role {
+                NetplayRole::Host => {
+                    "Send this offer to the other player and paste their answer here"
+                }
+                NetplayRole::Guest => "Send this answer to the host",
+            });
+        }
+
+        self.poll_spectators();
+
+        if self.connection.is_none() {
+            self.open_session()?;
+        }
+        let Some((bridge, session)) = &mut self.connection else { return Ok(None) };
+
+        check_open(bridge).map_err(|_| "The other player disconnected")?;
+
+        let was_running = session.is_running();
+        let inputs = session.advance(local_input)?;
+        bridge.flush();
+
+        if !was_running && session.is_running() {
+            self.started_session = session.info().cloned();
+            js::setNetplayStatus(match self.role {
+                NetplayRole::Host => "Connected as player 1",
+                NetplayRole::Guest => "Connected as player 2",
+            });
+        }
+
+        Ok(inputs)
+    }
+
+    /// Settings of the current session, or `None` if the handshake has not completed.
+    pub fn session_info(&self) -> Option<&SessionInfo> {
+        let (_, session) = self.connection.as_ref()?;
+        session.info().filter(|_| session.is_running())
+    }
+
+    /// Whether any spectator needs a save state along with the next frame's inputs.
+    pub fn spectators_need_state(&self) -> bool {
+        self.spectators.iter().any(|spectator| {
+            spectator.feed.as_ref().is_some_and(|(_, feed)| feed.needs_state_sync())
+        })
+    }
+
+    /// Send the inputs for the frame that is about to run to all spectators. `state` should be the
+    /// current emulator state if [`spectators_need_state`](Self::spectators_need_state) returned
+    /// true.
+    pub fn broadcast_frame(&mut self, inputs: &[GenesisJoypadState; 2], state: Option<&[u8]>) {
+        self.spectators.retain_mut(|spectator| {
+            let Some((bridge, feed)) = &mut spectator.feed else { return true };
+
+            let result = feed.send_frame(inputs, state);
+            bridge.flush();
+
+            result.map_err(|err| log::error!("Error sending frame to spectator: {err}")).is_ok()
+        });
+    }
+
+    fn poll_spectators(&mut self) {
+        let info = self.session_info().cloned();
+        let mut status_changed = false;
+
+        self.spectators.retain_mut(|spectator| match poll_spectator(spectator, info.as_ref()) {
+            Ok(connected) => {
+                status_changed |= connected;
+                true
+            }
+            Err(err) => {
+                log::warn!("Dropping spectator: {err}");
+                status_changed = true;
+                false
+            }
+        });
+
+        if status_changed {
+            let watching = self
+                .spectators
+                .iter()
+                .filter(|spectator| {
+                    spectator.feed.as_ref().is_some_and(|(_, feed)| feed.is_ready())
+                })
+                .count();
+            js::setNetplayStatus(&format!(
+                "Connected as player 1, {watching} spectator(s) watching"
+            ));
+        }
+    }
+
+    fn open_session(&mut self) -> Result<(), Box<dyn Error>> {
+        let Some((bridge, link)) = self.link.take_open_channel() else { return Ok(()) };
+
+        let rom_crc32 = jgenesis_netplay::rom_checksum(&self.rom);
+        let session = match self.role {
+            NetplayRole::Host => Session::host(
+                link,
+                SessionInfo {
+                    rom_crc32,
+                    rng_seed: rand::random(),
+                    input_delay: DEFAULT_INPUT_DELAY,
+                },
+            )?,
+            NetplayRole::Guest => Session::join(link, rom_crc32),
+        };
+        self.connection = Some((bridge, session));
+
+        js::setNetplayStatus("Connected, waiting for the other player...");
+
+        Ok(())
+    }
+}
+
+// Returns true if the spectator finished connecting
+fn poll_spectator(
+    spectator: &mut SpectatorConnection,
+    info: Option<&SessionInfo>,
+) -> Result<bool, Box<dyn Error>> {
+    if spectator.link.poll_signal()? {
+        js::setNetplayStatus("Send this offer to the spectator and paste their answer here");
+    }
+
+    if let Some((bridge, feed)) = &mut spectator.feed {
+        check_open(bridge)?;
+
+        let was_ready = feed.is_ready();
+        feed.poll()?;
+        bridge.flush();
+
+        return Ok(!was_ready && feed.is_ready());
+    }
+
+    // Spectators can only be sent the session settings once the players have agreed on them
+    let Some(info) = info else { return Ok(false) };
+    let Some((bridge, link)) = spectator.link.take_open_channel() else { return Ok(false) };
+
+    let feed = Feed::new(link, info)?;
+    bridge.flush();
+    spectator.feed = Some((bridge, feed));
+
+    Ok(false)
+}
+
+pub struct WebSpectator {
+    rom: Vec<u8>,
+    link: PeerLink,
+    connection: Option<(WebRtcBridge, Spectator)>,
+    started_session: Option<SessionInfo>,
+}
+
+impl WebSpectator {
+    /// Spectate the session that produced `offer`, which must be running the same ROM.
+    pub fn join(rom: Vec<u8>, offer: &str) -> Result<Self, Box<dyn Error>> {
+        let link = PeerLink::answer(offer)?;
+        js::setNetplayStatus("Creating answer...");
+
+        Ok(Self { rom, link, connection: None, started_session: None })
+    }
+
+    /// Returns the next frame to run, if it has arrived. A frame that includes a state must be run
+    /// from that state.
+    pub fn advance(
+        &mut self,
+    ) -> Result<Option<SpectatorFrame<GenesisJoypadState>>, Box<dyn Error>> {
+        if self.link.poll_signal()? {
+            js::setNetplayStatus("Send this answer to the host");
+        }
+
+        if self.connection.is_none() {
+            if let Some((bridge, link)) = self.link.take_open_channel() {
+                let session = Spectator::join(link, jgenesis_netplay::rom_checksum(&self.rom));
+                self.connection = Some((bridge, session));
+                js::setNetplayStatus("Connected, waiting for the host...");
+            }
+        }
+        let Some((bridge, session)) = &mut self.connection else { return Ok(None) };
+
+        check_open(bridge).map_err(|_| "The host disconnected")?;
+
+        let was_running = session.is_running();
+        let frame = session.advance()?;
+        bridge.flush();
+
+        if !was_running && session.is_running() {
+            self.started_session = session.info().cloned();
+            js::setNetplayStatus("Spectating");
+        }
+
+        Ok(frame)
+    }
+
+    /// Settings of the session, or `None` if the first state has not been received.
+    pub fn session_info(&self) -> Option<&SessionInfo> {
+        let (_, session) = self.connection.as_ref()?;
+        session.info().filter(|_| session.is_running())
+    }
+}
+
+fn new_peer_connection() -> Result<RtcPeerConnection, Box<dyn Error>> {
+    let mut ice_server = RtcIceServer::new();
+    ice_server.urls(&JsValue::from_str(STUN_SERVER));
+
+    let mut config = RtcConfiguration::new();
+    config.ice_servers(&js_sys::Array::of1(&ice_server));
+
+    let peer_connection = RtcPeerConnection::new_with_configuration(&config)
+        .map_err(|err| format!("Unable to create WebRTC peer connection: {err:?}"))?;
+    Ok(peer_connection)
+}
+
+fn decode_signal(signal: &str) -> Result<String, Box<dyn Error>> {
+    let bytes = general_purpose::STANDARD
+        .decode(signal.trim())
+        .map_err(|err| format!("Invalid netplay connection string: {err}"))?;
+    Ok(String::from_utf8(bytes)?)
+}
+
+fn spawn_signaling<F>(signaling_error: &SignalingError, future: F)
+where
+    F: Future<Output = Result<(), JsValue>> + 'static,
+{
+    let signaling_error = Rc::clone(signaling_error);
+    wasm_bindgen_futures::spawn_local(async move {
+        if let Err(err) = future.await {
+            *signaling_error.borrow_mut() = Some(format!("WebRTC signaling failed: {err:?}"));
+        }
+    });
+}
+
+async fn create_offer(peer_connection: RtcPeerConnection) -> Result<(), JsValue> {
+    let offer = JsFuture::from(peer_connection.create_offer()).await?;
+    JsFuture::from(peer_connection.set_local_description(offer.unchecked_ref())).await?;
+
+    Ok(())
+}
+
+async fn create_answer(
+    peer_connection: RtcPeerConnection,
+    offer_sdp: String,
+) -> Result<(), JsValue> {
+    set_remote_description(peer_connection.clone(), RtcSdpType::Offer, offer_sdp).await?;
+
+    let answer = JsFuture::from(peer_connection.create_answer()).await?;
+    JsFuture::from(peer_connection.set_local_description(answer.unchecked_ref())).await?;
+
+    Ok(())
+}
+
+async fn set_remote_description(
+    peer_connection: RtcPeerConnection,
+    sdp_type: RtcSdpType,
+    sdp: String,
+) -> Result<(), JsValue> {
+    let mut description = RtcSessionDescriptionInit::new(sdp_type);
+    description.sdp(&sdp);
+    JsFuture::from(peer_connection.set_remote_description(&description)).await?;
+
+    Ok(())
+}
+
+/// Save writer used while netplay is active. Save files are never loaded because the two players'
+/// saves would almost certainly differ, and nothing is persisted so that a netplay session cannot
+/// overwrite the local save.
+pub struct NetplaySaveWriter;
+
+impl SaveWriter for NetplaySaveWriter {
+    type Err = String;
+
+    fn load_bytes(&mut self, _extension: &str) -> Result<Vec<u8>, Self::Err> {
+        Err("Save files are not loaded during netplay".into())
+    }
+
+    fn persist_bytes(&mut self, _extension: &str, _bytes: &[u8]) -> Result<(), Self::Err> {
+        Ok(())
+    }
+
+    fn load_serialized<D: Decode>(&mut self, _extension: &str) -> Result<D, Self::Err> {
+        Err("Save files are not loaded during netplay".into())
+    }
+
+    fn persist_serialized<E: Encode>(
+        &mut self,
+        _extension: &str,
+        _data: E,
+    ) -> Result<(), Self::Err> {
+        Ok(())
+    }
+}
@@ -0,0 +1,13 @@
+FILE "Track 01.bin" BINARY
+  TRACK 01 MODE1/2352
+    INDEX 01 00:00:00
+  TRACK 02 AUDIO
+    INDEX 00 03:00:00
+FILE "Track 02.wav" WAVE
+    INDEX 01 00:00:00
+    POSTGAP 00:02:00
+FILE "Track 03.bin" BINARY
+  TRACK 03 AUDIO
+    FLAGS DCP PRE
+    PREGAP 00:02:00
+    INDEX 01 00:00:00
@@ -0,0 +1,6 @@
+FILE "Game.bin" BINARY
+  TRACK 01 MODE1/2352
+    INDEX 01 00:00:00
+  TRACK 02 AUDIO
+    INDEX 00 05:00:00
+    INDEX 01 05:02:00
@@ -0,0 +1,40 @@
+//! Frontend-agnostic commands for controlling an emulator
+//!
+//! Every frontend (the web UI, the native GUI, and any remote control interface) sends these same
+//! commands to whatever is driving the emulator. A frontend that cannot support a command (e.g. the
+//! web frontend has no save state slots) should ignore it rather than fail.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OpenFileKind {
+    /// A ROM or disc image; the console is determined from the file
+    Rom,
+    /// A Sega CD disc image
+    SegaCdDisc,
+    /// Battery-backed save memory for the currently running game
+    SaveFile,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EmulatorCommand {
+    /// Open a file. If no path is given, the frontend prompts the user to choose one
+    OpenFile {
+        kind: OpenFileKind,
+        path: Option<String>,
+    },
+    SoftReset,
+    HardReset,
+    /// Save state to the currently selected slot
+    SaveState,
+    /// Load state from the currently selected slot
+    LoadState,
+    /// Replace the current disc with the disc image at the given path, or remove it if no path is
+    /// given. Only applicable to disc-based consoles
+    SwapDisc(Option<String>),
+    SetPaused(bool),
+    /// Set the emulation speed as a multiple of normal speed
+    SetSpeedMultiplier(u64),
+    /// Save the next frame to an image file
+    Screenshot,
+}
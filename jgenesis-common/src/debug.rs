@@ -0,0 +1,59 @@
+//! Interfaces for external debuggers, such as the GDB remote protocol stub in the native driver
+
+mod access;
+mod breakpoints;
+mod expression;
+pub mod graphics;
+mod search;
+mod symbols;
+
+pub use access::{MemoryAccess, MemoryAccessKind, MemoryAccessLog};
+pub use breakpoints::{BreakReason, Breakpoint, BreakpointSet, WatchKind, Watchpoint};
+pub use expression::{Expression, ExpressionError};
+pub use search::{
+    FreezeList, FrozenValue, MemorySearch, SearchComparison, SearchOperand, SearchValueSize,
+};
+pub use symbols::SymbolTable;
+
+use std::ops::RangeInclusive;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CpuArchitecture {
+    M68000,
+    Wdc65816,
+}
+
+/// Debugger access to an emulator's main CPU and address space.
+///
+/// Memory accesses through this trait must not have side effects. Reads from I/O registers or other
+/// addresses that cannot be read without side effects should return 0, and writes to them should
+/// be ignored.
+pub trait Debuggable {
+    fn cpu_architecture(&self) -> CpuArchitecture;
+
+    /// Current main CPU register values. The order depends on the CPU architecture:
+    /// * 68000: D0-D7, A0-A7 (A7 is the active stack pointer), SR, PC
+    /// * 65816: A, X, Y, S, D, DBR, P, PC (bits 16-23 of PC are the program bank register)
+    fn cpu_registers(&self) -> Vec<u32>;
+
+    /// Current main CPU program counter, as a full address on the CPU's address bus.
+    fn cpu_pc(&self) -> u32;
+
+    /// Whether the main CPU is between instructions. Breakpoints are only checked at instruction
+    /// boundaries.
+    fn at_instruction_boundary(&self) -> bool;
+
+    fn peek_memory(&self, address: u32) -> u8;
+
+    fn poke_memory(&mut self, address: u32, value: u8);
+
+    /// Enable or disable recording of main CPU memory accesses, which is required for watchpoints.
+    fn set_memory_access_logging(&mut self, enabled: bool);
+
+    /// Memory accesses made by the main CPU during the most recent tick. Always empty if memory
+    /// access logging is disabled.
+    fn memory_accesses(&self) -> &[MemoryAccess];
+
+    /// Address range of the main CPU's work RAM, which is the range covered by RAM search.
+    fn work_ram_range(&self) -> RangeInclusive<u32>;
+}
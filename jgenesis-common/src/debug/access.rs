@@ -0,0 +1,62 @@
+use jgenesis_proc_macros::{FakeDecode, FakeEncode};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryAccessKind {
+    Read,
+    Write,
+}
+
+/// A single byte-sized memory access made by a CPU. Word-sized accesses are recorded as one access
+/// per byte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryAccess {
+    pub address: u32,
+    pub value: u8,
+    pub kind: MemoryAccessKind,
+}
+
+/// Records the memory accesses made by a CPU, for use by watchpoints.
+///
+/// CPU cores record into this through their bus interception wrappers. Recording is disabled by
+/// default so that there is no overhead when no watchpoints are set. This is never persisted in
+/// save states.
+#[derive(Debug, Clone, Default, FakeEncode, FakeDecode)]
+pub struct MemoryAccessLog {
+    enabled: bool,
+    accesses: Vec<MemoryAccess>,
+}
+
+impl MemoryAccessLog {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.accesses.clear();
+        }
+    }
+
+    #[inline]
+    pub fn record(&mut self, address: u32, value: u8, kind: MemoryAccessKind) {
+        self.accesses.push(MemoryAccess { address, value, kind });
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.accesses.clear();
+    }
+
+    #[must_use]
+    pub fn accesses(&self) -> &[MemoryAccess] {
+        &self.accesses
+    }
+}
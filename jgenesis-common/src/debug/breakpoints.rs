@@ -0,0 +1,206 @@
+use crate::debug::{Debuggable, Expression, MemoryAccess, MemoryAccessKind};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::ops::RangeInclusive;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchKind {
+    Read,
+    Write,
+    /// Writes that change the value in memory
+    Change,
+}
+
+impl Display for WatchKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Read => write!(f, "read"),
+            Self::Write => write!(f, "write"),
+            Self::Change => write!(f, "change"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Breakpoint {
+    pub address: u32,
+    pub condition: Option<Expression>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Watchpoint {
+    pub range: RangeInclusive<u32>,
+    pub kind: WatchKind,
+    pub condition: Option<Expression>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BreakReason {
+    Breakpoint { address: u32 },
+    Watchpoint { kind: WatchKind, address: u32, value: u8 },
+}
+
+impl Display for BreakReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Breakpoint { address } => write!(f, "breakpoint at ${address:06X}"),
+            Self::Watchpoint { kind, address, value } => {
+                write!(f, "{kind} watchpoint at ${address:06X} (value ${value:02X})")
+            }
+        }
+    }
+}
+
+/// Execution breakpoints and memory watchpoints, either of which can have a condition that must
+/// evaluate to true for execution to stop.
+///
+/// Watchpoints are checked against the memory accesses that the CPU core reports through its bus
+/// interception wrapper, so they see every access the CPU makes regardless of which component
+/// handles it.
+#[derive(Debug, Clone, Default)]
+pub struct BreakpointSet {
+    breakpoints: Vec<Breakpoint>,
+    watchpoints: Vec<Watchpoint>,
+    // Last known value for addresses covered by change watchpoints
+    watched_values: HashMap<u32, u8>,
+}
+
+impl BreakpointSet {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn breakpoints(&self) -> &[Breakpoint] {
+        &self.breakpoints
+    }
+
+    #[must_use]
+    pub fn watchpoints(&self) -> &[Watchpoint] {
+        &self.watchpoints
+    }
+
+    #[must_use]
+    pub fn has_watchpoints(&self) -> bool {
+        !self.watchpoints.is_empty()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.breakpoints.is_empty() && self.watchpoints.is_empty()
+    }
+
+    pub fn add_breakpoint(&mut self, address: u32, condition: Option<Expression>) {
+        self.breakpoints.push(Breakpoint { address, condition });
+    }
+
+    /// Remove all breakpoints at the given address.
+    pub fn remove_breakpoint(&mut self, address: u32) {
+        self.breakpoints.retain(|breakpoint| breakpoint.address != address);
+    }
+
+    pub fn remove_breakpoint_at_index(&mut self, idx: usize) {
+        if idx < self.breakpoints.len() {
+            self.breakpoints.remove(idx);
+        }
+    }
+
+    /// Add a watchpoint. The debuggable is used to read the initial values for change watchpoints.
+    pub fn add_watchpoint(&mut self, watchpoint: Watchpoint, debuggable: &dyn Debuggable) {
+        if watchpoint.kind == WatchKind::Change {
+            for address in watchpoint.range.clone() {
+                self.watched_values.insert(address, debuggable.peek_memory(address));
+            }
+        }
+
+        self.watchpoints.push(watchpoint);
+    }
+
+    pub fn remove_watchpoint_at_index(&mut self, idx: usize) {
+        if idx < self.watchpoints.len() {
+            self.watchpoints.remove(idx);
+        }
+    }
+
+    /// Remove all watchpoints of the given kind that exactly match the given range.
+    pub fn remove_watchpoint(&mut self, range: &RangeInclusive<u32>, kind: WatchKind) {
+        self.watchpoints.retain(|watchpoint| watchpoint.range != *range || watchpoint.kind != kind);
+    }
+
+    pub fn clear(&mut self) {
+        self.breakpoints.clear();
+        self.watchpoints.clear();
+        self.watched_values.clear();
+    }
+
+    /// Check whether the CPU has reached an execution breakpoint. Should only be called at
+    /// instruction boundaries.
+    #[must_use]
+    pub fn check_execution(&self, debuggable: &dyn Debuggable) -> Option<BreakReason> {
+        if self.breakpoints.is_empty() {
+            return None;
+        }
+
+        let pc = debuggable.cpu_pc();
+        self.breakpoints
+            .iter()
+            .filter(|breakpoint| breakpoint.address == pc)
+            .any(|breakpoint| condition_met(breakpoint.condition.as_ref(), debuggable))
+            .then_some(BreakReason::Breakpoint { address: pc })
+    }
+
+    /// Check the given memory accesses against all watchpoints.
+    pub fn check_accesses(
+        &mut self,
+        accesses: &[MemoryAccess],
+        debuggable: &dyn Debuggable,
+    ) -> Option<BreakReason> {
+        let mut reason = None;
+
+        for access in accesses {
+            for watchpoint in &self.watchpoints {
+                if !watchpoint.range.contains(&access.address) {
+                    continue;
+                }
+
+                let matches = match (watchpoint.kind, access.kind) {
+                    (WatchKind::Read, MemoryAccessKind::Read)
+                    | (WatchKind::Write, MemoryAccessKind::Write) => true,
+                    (WatchKind::Change, MemoryAccessKind::Write) => {
+                        self.watched_values.get(&access.address) != Some(&access.value)
+                    }
+                    _ => false,
+                };
+
+                if reason.is_none()
+                    && matches
+                    && condition_met(watchpoint.condition.as_ref(), debuggable)
+                {
+                    reason = Some(BreakReason::Watchpoint {
+                        kind: watchpoint.kind,
+                        address: access.address,
+                        value: access.value,
+                    });
+                }
+            }
+
+            // Keep tracking values even after a watchpoint triggers so that later writes in the
+            // same batch don't re-trigger spuriously
+            if access.kind == MemoryAccessKind::Write {
+                if let Some(value) = self.watched_values.get_mut(&access.address) {
+                    *value = access.value;
+                }
+            }
+        }
+
+        reason
+    }
+}
+
+fn condition_met(condition: Option<&Expression>, debuggable: &dyn Debuggable) -> bool {
+    match condition {
+        Some(condition) => condition.is_true(debuggable),
+        None => true,
+    }
+}
@@ -0,0 +1,402 @@
+//! Expressions for conditional breakpoints and watchpoints
+//!
+//! Expressions operate on unsigned 32-bit integers and support:
+//! * Hex (`$1F`, `0x1F`) and decimal (`31`) literals
+//! * Register names (`d0`-`d7`, `a0`-`a7`, `sp`, `sr`, `pc` for the 68000; `a`, `x`, `y`, `s`, `d`,
+//!   `dbr`, `p`, `pc` for the 65816)
+//! * Byte memory reads (`[$FF0000]`)
+//! * Operators, from lowest to highest precedence: `||`, `&&`, comparisons (`==`, `!=`, `<`,
+//!   `<=`, `>`, `>=`), `|`, `&`, `+` / `-`, and unary `!` / `-`
+//!
+//! Comparison and logical operators evaluate to 1 for true and 0 for false, and any non-zero value
+//! is considered true.
+
+use crate::debug::{CpuArchitecture, Debuggable};
+use std::fmt::{Display, Formatter};
+use thiserror::Error;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ExpressionError {
+    #[error("Unexpected character '{0}'")]
+    UnexpectedChar(char),
+    #[error("Invalid number '{0}'")]
+    InvalidNumber(String),
+    #[error("Unknown register '{0}'")]
+    UnknownRegister(String),
+    #[error("Unexpected end of expression")]
+    UnexpectedEnd,
+    #[error("Unexpected token '{0}'")]
+    UnexpectedToken(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BinaryOp {
+    Or,
+    And,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    BitOr,
+    BitAnd,
+    Add,
+    Subtract,
+}
+
+impl BinaryOp {
+    fn apply(self, l: u32, r: u32) -> u32 {
+        match self {
+            Self::Or => u32::from(l != 0 || r != 0),
+            Self::And => u32::from(l != 0 && r != 0),
+            Self::Equal => u32::from(l == r),
+            Self::NotEqual => u32::from(l != r),
+            Self::Less => u32::from(l < r),
+            Self::LessEqual => u32::from(l <= r),
+            Self::Greater => u32::from(l > r),
+            Self::GreaterEqual => u32::from(l >= r),
+            Self::BitOr => l | r,
+            Self::BitAnd => l & r,
+            Self::Add => l.wrapping_add(r),
+            Self::Subtract => l.wrapping_sub(r),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Node {
+    Literal(u32),
+    Register(usize),
+    Memory(Box<Node>),
+    Not(Box<Node>),
+    Negate(Box<Node>),
+    Binary(BinaryOp, Box<Node>, Box<Node>),
+}
+
+impl Node {
+    fn evaluate(&self, debuggable: &dyn Debuggable, registers: &[u32]) -> u32 {
+        match self {
+            &Self::Literal(value) => value,
+            &Self::Register(idx) => registers.get(idx).copied().unwrap_or(0),
+            Self::Memory(address) => {
+                debuggable.peek_memory(address.evaluate(debuggable, registers)).into()
+            }
+            Self::Not(operand) => u32::from(operand.evaluate(debuggable, registers) == 0),
+            Self::Negate(operand) => operand.evaluate(debuggable, registers).wrapping_neg(),
+            Self::Binary(op, l, r) => {
+                op.apply(l.evaluate(debuggable, registers), r.evaluate(debuggable, registers))
+            }
+        }
+    }
+}
+
+/// A parsed expression that can be evaluated against the current state of an emulator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expression {
+    source: String,
+    root: Node,
+}
+
+impl Expression {
+    /// Parse an expression. Register names are resolved using the given CPU architecture.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the expression is not syntactically valid or if it
+    /// references an unknown register.
+    pub fn parse(source: &str, arch: CpuArchitecture) -> Result<Self, ExpressionError> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser { tokens: &tokens, position: 0, arch };
+        let root = parser.parse_or()?;
+        if let Some(token) = parser.peek() {
+            return Err(ExpressionError::UnexpectedToken(token.to_string()));
+        }
+
+        Ok(Self { source: source.trim().into(), root })
+    }
+
+    #[must_use]
+    pub fn evaluate(&self, debuggable: &dyn Debuggable) -> u32 {
+        let registers = debuggable.cpu_registers();
+        self.root.evaluate(debuggable, &registers)
+    }
+
+    #[must_use]
+    pub fn is_true(&self, debuggable: &dyn Debuggable) -> bool {
+        self.evaluate(debuggable) != 0
+    }
+}
+
+impl Display for Expression {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+fn register_index(name: &str, arch: CpuArchitecture) -> Option<usize> {
+    let name = name.to_ascii_lowercase();
+    match arch {
+        CpuArchitecture::M68000 => match name.as_str() {
+            "sp" => Some(15),
+            "sr" => Some(16),
+            "pc" => Some(17),
+            _ => {
+                let (prefix, n) = name.split_at(1);
+                let n: usize = n.parse().ok().filter(|&n| n < 8)?;
+                match prefix {
+                    "d" => Some(n),
+                    "a" => Some(8 + n),
+                    _ => None,
+                }
+            }
+        },
+        CpuArchitecture::Wdc65816 => ["a", "x", "y", "s", "d", "dbr", "p", "pc"]
+            .into_iter()
+            .position(|register| register == name),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Number(u32),
+    Identifier(String),
+    Symbol(&'static str),
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Number(n) => write!(f, "{n}"),
+            Self::Identifier(identifier) => write!(f, "{identifier}"),
+            Self::Symbol(symbol) => write!(f, "{symbol}"),
+        }
+    }
+}
+
+const SYMBOLS: &[&str] =
+    &["||", "&&", "==", "!=", "<=", ">=", "<", ">", "|", "&", "+", "-", "!", "(", ")", "[", "]"];
+
+fn tokenize(source: &str) -> Result<Vec<Token>, ExpressionError> {
+    let mut tokens = Vec::new();
+    let mut remaining = source.trim_start();
+    while let Some(c) = remaining.chars().next() {
+        if let Some(&symbol) = SYMBOLS.iter().find(|&&symbol| remaining.starts_with(symbol)) {
+            tokens.push(Token::Symbol(symbol));
+            remaining = &remaining[symbol.len()..];
+        } else if c == '$' || c.is_ascii_alphanumeric() || c == '_' {
+            let len = remaining[1..]
+                .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                .map_or(remaining.len(), |len| len + 1);
+            let word = &remaining[..len];
+            remaining = &remaining[len..];
+
+            tokens.push(if c == '$' || c.is_ascii_digit() {
+                Token::Number(parse_number(word)?)
+            } else {
+                Token::Identifier(word.into())
+            });
+        } else {
+            return Err(ExpressionError::UnexpectedChar(c));
+        }
+
+        remaining = remaining.trim_start();
+    }
+
+    Ok(tokens)
+}
+
+fn parse_number(word: &str) -> Result<u32, ExpressionError> {
+    let result = if let Some(hex) = word.strip_prefix('$') {
+        u32::from_str_radix(hex, 16)
+    } else if let Some(hex) = word.strip_prefix("0x").or_else(|| word.strip_prefix("0X")) {
+        u32::from_str_radix(hex, 16)
+    } else {
+        word.parse()
+    };
+
+    result.map_err(|_| ExpressionError::InvalidNumber(word.into()))
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    position: usize,
+    arch: CpuArchitecture,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&'a Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Result<&'a Token, ExpressionError> {
+        let token = self.peek().ok_or(ExpressionError::UnexpectedEnd)?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn expect(&mut self, symbol: &'static str) -> Result<(), ExpressionError> {
+        match self.next()? {
+            Token::Symbol(s) if *s == symbol => Ok(()),
+            token => Err(ExpressionError::UnexpectedToken(token.to_string())),
+        }
+    }
+
+    fn parse_binary(
+        &mut self,
+        ops: &[(&str, BinaryOp)],
+        next: fn(&mut Self) -> Result<Node, ExpressionError>,
+    ) -> Result<Node, ExpressionError> {
+        let mut node = next(self)?;
+        while let Some(Token::Symbol(symbol)) = self.peek() {
+            let Some(&(_, op)) = ops.iter().find(|(s, _)| s == symbol) else { break };
+            self.position += 1;
+            node = Node::Binary(op, Box::new(node), Box::new(next(self)?));
+        }
+        Ok(node)
+    }
+
+    fn parse_or(&mut self) -> Result<Node, ExpressionError> {
+        self.parse_binary(&[("||", BinaryOp::Or)], Self::parse_and)
+    }
+
+    fn parse_and(&mut self) -> Result<Node, ExpressionError> {
+        self.parse_binary(&[("&&", BinaryOp::And)], Self::parse_comparison)
+    }
+
+    fn parse_comparison(&mut self) -> Result<Node, ExpressionError> {
+        self.parse_binary(
+            &[
+                ("==", BinaryOp::Equal),
+                ("!=", BinaryOp::NotEqual),
+                ("<", BinaryOp::Less),
+                ("<=", BinaryOp::LessEqual),
+                (">", BinaryOp::Greater),
+                (">=", BinaryOp::GreaterEqual),
+            ],
+            Self::parse_bit_or,
+        )
+    }
+
+    fn parse_bit_or(&mut self) -> Result<Node, ExpressionError> {
+        self.parse_binary(&[("|", BinaryOp::BitOr)], Self::parse_bit_and)
+    }
+
+    fn parse_bit_and(&mut self) -> Result<Node, ExpressionError> {
+        self.parse_binary(&[("&", BinaryOp::BitAnd)], Self::parse_additive)
+    }
+
+    fn parse_additive(&mut self) -> Result<Node, ExpressionError> {
+        self.parse_binary(&[("+", BinaryOp::Add), ("-", BinaryOp::Subtract)], Self::parse_unary)
+    }
+
+    fn parse_unary(&mut self) -> Result<Node, ExpressionError> {
+        match self.peek() {
+            Some(Token::Symbol("!")) => {
+                self.position += 1;
+                Ok(Node::Not(Box::new(self.parse_unary()?)))
+            }
+            Some(Token::Symbol("-")) => {
+                self.position += 1;
+                Ok(Node::Negate(Box::new(self.parse_unary()?)))
+            }
+            _ => self.parse_primary(),
+        }
+    }
+
+    fn parse_primary(&mut self) -> Result<Node, ExpressionError> {
+        match self.next()? {
+            &Token::Number(n) => Ok(Node::Literal(n)),
+            Token::Identifier(name) => register_index(name, self.arch)
+                .map(Node::Register)
+                .ok_or_else(|| ExpressionError::UnknownRegister(name.clone())),
+            Token::Symbol("(") => {
+                let node = self.parse_or()?;
+                self.expect(")")?;
+                Ok(node)
+            }
+            Token::Symbol("[") => {
+                let node = self.parse_or()?;
+                self.expect("]")?;
+                Ok(Node::Memory(Box::new(node)))
+            }
+            token @ Token::Symbol(_) => Err(ExpressionError::UnexpectedToken(token.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::debug::MemoryAccess;
+    use std::ops::RangeInclusive;
+
+    struct TestDebuggable;
+
+    impl Debuggable for TestDebuggable {
+        fn cpu_architecture(&self) -> CpuArchitecture {
+            CpuArchitecture::M68000
+        }
+
+        fn cpu_registers(&self) -> Vec<u32> {
+            (0..18).collect()
+        }
+
+        fn cpu_pc(&self) -> u32 {
+            0x200
+        }
+
+        fn at_instruction_boundary(&self) -> bool {
+            true
+        }
+
+        fn peek_memory(&self, address: u32) -> u8 {
+            address as u8
+        }
+
+        fn poke_memory(&mut self, _address: u32, _value: u8) {}
+
+        fn set_memory_access_logging(&mut self, _enabled: bool) {}
+
+        fn memory_accesses(&self) -> &[MemoryAccess] {
+            &[]
+        }
+
+        fn work_ram_range(&self) -> RangeInclusive<u32> {
+            0..=0xFFFF
+        }
+    }
+
+    fn eval(source: &str) -> u32 {
+        Expression::parse(source, CpuArchitecture::M68000).unwrap().evaluate(&TestDebuggable)
+    }
+
+    #[test]
+    fn arithmetic_and_precedence() {
+        assert_eq!(eval("1 + 2 - 4"), u32::MAX);
+        assert_eq!(eval("$F0 | $0F & $3"), 0xF3);
+        assert_eq!(eval("(1 + 2) == 3 && 0x10 >= 16"), 1);
+        assert_eq!(eval("!0 || 0"), 1);
+        assert_eq!(eval("-1"), u32::MAX);
+    }
+
+    #[test]
+    fn registers_and_memory() {
+        assert_eq!(eval("d3"), 3);
+        assert_eq!(eval("A2"), 10);
+        assert_eq!(eval("sp"), 15);
+        assert_eq!(eval("[$FF0012]"), 0x12);
+        assert_eq!(eval("[d5 + 1] == 6"), 1);
+    }
+
+    #[test]
+    fn errors() {
+        let parse = |source| Expression::parse(source, CpuArchitecture::M68000);
+        assert_eq!(parse("d8"), Err(ExpressionError::UnknownRegister("d8".into())));
+        assert_eq!(parse("1 +"), Err(ExpressionError::UnexpectedEnd));
+        assert_eq!(parse("(1"), Err(ExpressionError::UnexpectedEnd));
+        assert_eq!(parse("1 2"), Err(ExpressionError::UnexpectedToken("2".into())));
+        assert_eq!(parse("1 # 2"), Err(ExpressionError::UnexpectedChar('#')));
+        assert!(Expression::parse("dbr == 0", CpuArchitecture::Wdc65816).is_ok());
+    }
+}
@@ -0,0 +1,46 @@
+//! Helpers for converting edited debugger images back into emulated graphics data
+
+use crate::frontend::Color;
+
+/// Return the index of the palette color that is closest to `color`, or 0 if the palette is empty.
+///
+/// Distance is measured as squared Euclidean distance in RGB space, and ties are broken in favor of
+/// the lowest index. Alpha is ignored.
+#[must_use]
+pub fn closest_color_index(palette: &[Color], color: Color) -> usize {
+    palette
+        .iter()
+        .enumerate()
+        .min_by_key(|&(_, &palette_color)| color_distance(palette_color, color))
+        .map_or(0, |(i, _)| i)
+}
+
+fn color_distance(a: Color, b: Color) -> u32 {
+    let dr = u32::from(a.r.abs_diff(b.r));
+    let dg = u32::from(a.g.abs_diff(b.g));
+    let db = u32::from(a.b.abs_diff(b.b));
+    dr * dr + dg * dg + db * db
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn closest_color() {
+        let palette = [Color::rgb(0, 0, 0), Color::rgb(255, 0, 0), Color::rgb(0, 0, 255)];
+
+        assert_eq!(closest_color_index(&palette, Color::rgb(0, 0, 0)), 0);
+        assert_eq!(closest_color_index(&palette, Color::rgb(200, 30, 10)), 1);
+        assert_eq!(closest_color_index(&palette, Color::rgb(10, 20, 180)), 2);
+        assert_eq!(closest_color_index(&palette, Color::rgba(255, 0, 0, 0)), 1);
+    }
+
+    #[test]
+    fn ties_prefer_lowest_index() {
+        let palette = [Color::rgb(10, 10, 10), Color::rgb(0, 0, 0), Color::rgb(0, 0, 0)];
+
+        assert_eq!(closest_color_index(&palette, Color::rgb(0, 0, 0)), 1);
+        assert_eq!(closest_color_index(&[], Color::rgb(0, 0, 0)), 0);
+    }
+}
@@ -0,0 +1,339 @@
+//! RAM search and value freezing, i.e. the classic cheat-finding workflow
+//!
+//! A search starts with every address in work RAM as a candidate. Each filter step compares the
+//! current value at every remaining candidate against either a fixed value or the value from the
+//! previous snapshot, and then takes a new snapshot.
+
+use crate::debug::{CpuArchitecture, Debuggable};
+use std::fmt::{Display, Formatter};
+use std::ops::RangeInclusive;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SearchValueSize {
+    #[default]
+    Byte,
+    Word,
+}
+
+impl SearchValueSize {
+    #[must_use]
+    pub fn byte_len(self) -> u32 {
+        match self {
+            Self::Byte => 1,
+            Self::Word => 2,
+        }
+    }
+
+    /// Read a value of this size using the CPU's byte order.
+    #[must_use]
+    pub fn read(self, debuggable: &dyn Debuggable, address: u32) -> u32 {
+        match self {
+            Self::Byte => debuggable.peek_memory(address).into(),
+            Self::Word => {
+                let first = debuggable.peek_memory(address);
+                let second = debuggable.peek_memory(address.wrapping_add(1));
+                let bytes = [first, second];
+                match debuggable.cpu_architecture() {
+                    CpuArchitecture::M68000 => u16::from_be_bytes(bytes).into(),
+                    CpuArchitecture::Wdc65816 => u16::from_le_bytes(bytes).into(),
+                }
+            }
+        }
+    }
+
+    /// Write a value of this size using the CPU's byte order. Excess high bits are ignored.
+    pub fn write(self, debuggable: &mut dyn Debuggable, address: u32, value: u32) {
+        match self {
+            Self::Byte => debuggable.poke_memory(address, value as u8),
+            Self::Word => {
+                let bytes = match debuggable.cpu_architecture() {
+                    CpuArchitecture::M68000 => (value as u16).to_be_bytes(),
+                    CpuArchitecture::Wdc65816 => (value as u16).to_le_bytes(),
+                };
+                debuggable.poke_memory(address, bytes[0]);
+                debuggable.poke_memory(address.wrapping_add(1), bytes[1]);
+            }
+        }
+    }
+}
+
+impl Display for SearchValueSize {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Byte => write!(f, "8-bit"),
+            Self::Word => write!(f, "16-bit"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SearchComparison {
+    #[default]
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl SearchComparison {
+    pub const ALL: [Self; 6] = [
+        Self::Equal,
+        Self::NotEqual,
+        Self::Less,
+        Self::LessOrEqual,
+        Self::Greater,
+        Self::GreaterOrEqual,
+    ];
+
+    fn compare(self, lhs: u32, rhs: u32) -> bool {
+        match self {
+            Self::Equal => lhs == rhs,
+            Self::NotEqual => lhs != rhs,
+            Self::Less => lhs < rhs,
+            Self::LessOrEqual => lhs <= rhs,
+            Self::Greater => lhs > rhs,
+            Self::GreaterOrEqual => lhs >= rhs,
+        }
+    }
+}
+
+impl Display for SearchComparison {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Equal => write!(f, "=="),
+            Self::NotEqual => write!(f, "!="),
+            Self::Less => write!(f, "<"),
+            Self::LessOrEqual => write!(f, "<="),
+            Self::Greater => write!(f, ">"),
+            Self::GreaterOrEqual => write!(f, ">="),
+        }
+    }
+}
+
+/// What to compare the current value against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchOperand {
+    Value(u32),
+    /// The value at the same address in the previous snapshot; e.g. comparing with
+    /// [`SearchComparison::NotEqual`] finds values that changed
+    PreviousValue,
+}
+
+#[derive(Debug, Clone)]
+pub struct MemorySearch {
+    range: RangeInclusive<u32>,
+    size: SearchValueSize,
+    snapshot: Vec<u32>,
+    candidates: Vec<u32>,
+}
+
+impl MemorySearch {
+    /// Start a new search over the given address range, with every address as a candidate. 16-bit
+    /// searches on the 68000 only consider even addresses.
+    #[must_use]
+    pub fn new(
+        range: RangeInclusive<u32>,
+        size: SearchValueSize,
+        debuggable: &dyn Debuggable,
+    ) -> Self {
+        let step = match (size, debuggable.cpu_architecture()) {
+            (SearchValueSize::Word, CpuArchitecture::M68000) => 2,
+            _ => 1,
+        };
+        let last_address = range.end().saturating_sub(size.byte_len() - 1);
+        let candidates: Vec<_> = (*range.start()..=last_address).step_by(step).collect();
+
+        let mut search = Self { range, size, snapshot: Vec::new(), candidates };
+        search.take_snapshot(debuggable);
+        search
+    }
+
+    #[must_use]
+    pub fn range(&self) -> &RangeInclusive<u32> {
+        &self.range
+    }
+
+    #[must_use]
+    pub fn size(&self) -> SearchValueSize {
+        self.size
+    }
+
+    /// Remaining candidate addresses, in ascending order.
+    #[must_use]
+    pub fn candidates(&self) -> &[u32] {
+        &self.candidates
+    }
+
+    /// The value at the given candidate's address when the last snapshot was taken.
+    #[must_use]
+    pub fn previous_value(&self, candidate_idx: usize) -> u32 {
+        self.snapshot[candidate_idx]
+    }
+
+    /// Remove candidates whose current value does not satisfy the given comparison, then take a
+    /// new snapshot of the remaining candidates.
+    pub fn filter(
+        &mut self,
+        comparison: SearchComparison,
+        operand: SearchOperand,
+        debuggable: &dyn Debuggable,
+    ) {
+        let mut retained_idx = 0;
+        for idx in 0..self.candidates.len() {
+            let address = self.candidates[idx];
+            let current = self.size.read(debuggable, address);
+            let rhs = match operand {
+                SearchOperand::Value(value) => value,
+                SearchOperand::PreviousValue => self.snapshot[idx],
+            };
+
+            if comparison.compare(current, rhs) {
+                self.candidates[retained_idx] = address;
+                self.snapshot[retained_idx] = current;
+                retained_idx += 1;
+            }
+        }
+
+        self.candidates.truncate(retained_idx);
+        self.snapshot.truncate(retained_idx);
+    }
+
+    /// Take a new snapshot without removing any candidates.
+    pub fn take_snapshot(&mut self, debuggable: &dyn Debuggable) {
+        self.snapshot =
+            self.candidates.iter().map(|&address| self.size.read(debuggable, address)).collect();
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrozenValue {
+    pub address: u32,
+    pub value: u32,
+    pub size: SearchValueSize,
+    pub enabled: bool,
+}
+
+/// A list of addresses whose values are repeatedly overwritten with a fixed value, i.e. "freeze"
+/// cheats.
+#[derive(Debug, Clone, Default)]
+pub struct FreezeList {
+    entries: Vec<FrozenValue>,
+}
+
+impl FreezeList {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    #[must_use]
+    pub fn entries(&self) -> &[FrozenValue] {
+        &self.entries
+    }
+
+    #[must_use]
+    pub fn entries_mut(&mut self) -> &mut [FrozenValue] {
+        &mut self.entries
+    }
+
+    /// Freeze the given address, replacing any existing entry for that address.
+    pub fn add(&mut self, address: u32, value: u32, size: SearchValueSize) {
+        self.entries.retain(|entry| entry.address != address);
+        self.entries.push(FrozenValue { address, value, size, enabled: true });
+    }
+
+    pub fn remove(&mut self, idx: usize) {
+        if idx < self.entries.len() {
+            self.entries.remove(idx);
+        }
+    }
+
+    /// Write all enabled frozen values to memory. Should be called once per frame.
+    pub fn apply(&self, debuggable: &mut dyn Debuggable) {
+        for entry in self.entries.iter().filter(|entry| entry.enabled) {
+            entry.size.write(debuggable, entry.address, entry.value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::debug::MemoryAccess;
+
+    struct TestRam(Vec<u8>);
+
+    impl Debuggable for TestRam {
+        fn cpu_architecture(&self) -> CpuArchitecture {
+            CpuArchitecture::M68000
+        }
+
+        fn cpu_registers(&self) -> Vec<u32> {
+            vec![0; 18]
+        }
+
+        fn cpu_pc(&self) -> u32 {
+            0
+        }
+
+        fn at_instruction_boundary(&self) -> bool {
+            true
+        }
+
+        fn peek_memory(&self, address: u32) -> u8 {
+            self.0.get(address as usize).copied().unwrap_or(0)
+        }
+
+        fn poke_memory(&mut self, address: u32, value: u8) {
+            if let Some(byte) = self.0.get_mut(address as usize) {
+                *byte = value;
+            }
+        }
+
+        fn set_memory_access_logging(&mut self, _enabled: bool) {}
+
+        fn memory_accesses(&self) -> &[MemoryAccess] {
+            &[]
+        }
+
+        fn work_ram_range(&self) -> RangeInclusive<u32> {
+            0..=(self.0.len() as u32 - 1)
+        }
+    }
+
+    #[test]
+    fn search_and_freeze() {
+        let mut ram = TestRam(vec![3; 16]);
+        let mut search = MemorySearch::new(ram.work_ram_range(), SearchValueSize::Byte, &ram);
+        assert_eq!(search.candidates().len(), 16);
+
+        search.filter(SearchComparison::Equal, SearchOperand::Value(3), &ram);
+        assert_eq!(search.candidates().len(), 16);
+
+        ram.0[5] = 2;
+        ram.0[9] = 2;
+        ram.0[12] = 4;
+        search.filter(SearchComparison::Less, SearchOperand::PreviousValue, &ram);
+        assert_eq!(search.candidates(), &[5, 9]);
+
+        ram.0[9] = 1;
+        search.filter(SearchComparison::NotEqual, SearchOperand::PreviousValue, &ram);
+        assert_eq!(search.candidates(), &[9]);
+        assert_eq!(search.previous_value(0), 1);
+
+        let mut freeze_list = FreezeList::new();
+        freeze_list.add(8, 0x1234, SearchValueSize::Word);
+        freeze_list.apply(&mut ram);
+        assert_eq!(&ram.0[8..10], &[0x12, 0x34]);
+
+        let word_search = MemorySearch::new(ram.work_ram_range(), SearchValueSize::Word, &ram);
+        assert_eq!(word_search.candidates().len(), 8);
+    }
+}
@@ -0,0 +1,223 @@
+//! Symbol file parsing, so that debuggers can display labels instead of raw addresses
+//!
+//! Supported formats:
+//! * WLA-DX / bsnes-style `.sym` files (`[labels]` sections with `BB:AAAA name` lines)
+//! * ca65 / ld65 VICE label files (`al 00C000 .name`)
+//! * `nm`-style symbol listings such as SGDK's `symbol.txt` (`00000200 T name`)
+//! * GNU ld `.map` files (`0x00000200    name`)
+//! * Plain `address name` listings
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::{fs, io};
+
+#[derive(Debug, Clone, Default)]
+pub struct SymbolTable {
+    by_address: BTreeMap<u32, String>,
+    by_name: HashMap<String, u32>,
+}
+
+impl SymbolTable {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load symbols from the given file, auto-detecting the file format.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if it is unable to read the file.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let bytes = fs::read(path.as_ref())?;
+        let symbols = Self::parse(&String::from_utf8_lossy(&bytes));
+
+        log::info!("Loaded {} symbols from '{}'", symbols.len(), path.as_ref().display());
+
+        Ok(symbols)
+    }
+
+    /// Parse symbols from the contents of a symbol file. Lines that are not recognized are ignored.
+    #[must_use]
+    pub fn parse(contents: &str) -> Self {
+        let mut symbols = Self::new();
+
+        let mut in_ignored_section = false;
+        for line in contents.lines() {
+            let line = line.split(';').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if line.starts_with('[') {
+                // WLA-DX [definitions] sections contain constants rather than addresses
+                in_ignored_section = line.eq_ignore_ascii_case("[definitions]");
+                continue;
+            }
+
+            if in_ignored_section {
+                continue;
+            }
+
+            if let Some((address, name)) = parse_line(line) {
+                symbols.insert(address, name);
+            }
+        }
+
+        symbols
+    }
+
+    /// Add a symbol. If multiple symbols share an address, the first one added is displayed at
+    /// that address, but all of them can be looked up by name.
+    pub fn insert(&mut self, address: u32, name: &str) {
+        self.by_address.entry(address).or_insert_with(|| name.into());
+        self.by_name.insert(name.into(), address);
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.by_name.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.by_name.is_empty()
+    }
+
+    /// The label at exactly the given address, if any.
+    #[must_use]
+    pub fn label_at(&self, address: u32) -> Option<&str> {
+        self.by_address.get(&address).map(String::as_str)
+    }
+
+    /// The closest label at or before the given address, along with the offset from that label.
+    #[must_use]
+    pub fn nearest_label(&self, address: u32) -> Option<(&str, u32)> {
+        self.by_address
+            .range(..=address)
+            .next_back()
+            .map(|(&label_address, name)| (name.as_str(), address - label_address))
+    }
+
+    /// Format an address as `label` or `label+offset` if there is a label at or before it.
+    #[must_use]
+    pub fn describe(&self, address: u32) -> Option<String> {
+        self.nearest_label(address).map(|(name, offset)| match offset {
+            0 => name.into(),
+            _ => format!("{name}+${offset:X}"),
+        })
+    }
+
+    /// Look up a symbol's address by name. Exact matches are preferred over case-insensitive
+    /// matches.
+    #[must_use]
+    pub fn address_of(&self, name: &str) -> Option<u32> {
+        self.by_name.get(name).copied().or_else(|| {
+            self.by_name
+                .iter()
+                .find_map(|(symbol, &address)| symbol.eq_ignore_ascii_case(name).then_some(address))
+        })
+    }
+
+    /// All symbols whose names contain the given string (case-insensitive), sorted by name.
+    #[must_use]
+    pub fn search(&self, query: &str) -> Vec<(&str, u32)> {
+        let query = query.to_ascii_lowercase();
+        let mut matches: Vec<_> = self
+            .by_name
+            .iter()
+            .filter(|(name, _)| name.to_ascii_lowercase().contains(&query))
+            .map(|(name, &address)| (name.as_str(), address))
+            .collect();
+        matches.sort_unstable();
+        matches
+    }
+}
+
+fn parse_line(line: &str) -> Option<(u32, &str)> {
+    let tokens: Vec<_> = line.split_whitespace().collect();
+    match tokens.as_slice() {
+        // ca65 / ld65 VICE label file
+        ["al", address, name] => {
+            Some((parse_hex(address)?, valid_name(name.strip_prefix('.').unwrap_or(name))?))
+        }
+        // nm output, e.g. SGDK's symbol.txt
+        [address, symbol_type, name] if symbol_type.len() == 1 => {
+            Some((parse_hex(address)?, valid_name(name)?))
+        }
+        // GNU ld map file, WLA-DX, or plain listing
+        [address, name] => Some((parse_address(address)?, valid_name(name)?)),
+        _ => None,
+    }
+}
+
+fn parse_address(s: &str) -> Option<u32> {
+    match s.split_once(':') {
+        Some((bank, address)) => {
+            let bank = parse_hex(bank)?;
+            let address = parse_hex(address)?;
+            (bank <= 0xFF && address <= 0xFFFF).then_some((bank << 16) | address)
+        }
+        None => parse_hex(s),
+    }
+}
+
+fn parse_hex(s: &str) -> Option<u32> {
+    let s = s.strip_prefix("0x").or_else(|| s.strip_prefix('$')).unwrap_or(s);
+    u32::from_str_radix(s, 16).ok()
+}
+
+fn valid_name(name: &str) -> Option<&str> {
+    let mut chars = name.chars();
+    let first = chars.next()?;
+    let valid = (first.is_ascii_alphabetic() || "_.@".contains(first))
+        && chars.all(|c| c.is_ascii_alphanumeric() || "_.@$?".contains(c));
+    valid.then_some(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wla_dx() {
+        let symbols = SymbolTable::parse(
+            "; WLA symbolic information\n[labels]\n00:8000 Reset\n7e:0010 player_x\n\n[definitions]\n00000010 SPRITE_COUNT\n",
+        );
+
+        assert_eq!(symbols.len(), 2);
+        assert_eq!(symbols.address_of("Reset"), Some(0x008000));
+        assert_eq!(symbols.address_of("player_x"), Some(0x7E0010));
+        assert_eq!(symbols.address_of("SPRITE_COUNT"), None);
+    }
+
+    #[test]
+    fn ca65_vice_labels() {
+        let symbols = SymbolTable::parse("al 00C000 .Reset\nal 000200 .frame_counter\n");
+
+        assert_eq!(symbols.address_of("Reset"), Some(0xC000));
+        assert_eq!(symbols.label_at(0x0200), Some("frame_counter"));
+    }
+
+    #[test]
+    fn nm_and_ld_map() {
+        let symbols = SymbolTable::parse(
+            "00000200 T main\n00ff0000 B vblank_count\n .text  0x00000000  0x200 out/sega.o\n                0x00000300                VDP_init\n",
+        );
+
+        assert_eq!(symbols.len(), 3);
+        assert_eq!(symbols.address_of("main"), Some(0x200));
+        assert_eq!(symbols.address_of("vblank_count"), Some(0xFF0000));
+        assert_eq!(symbols.address_of("vdp_init"), Some(0x300));
+    }
+
+    #[test]
+    fn describe() {
+        let mut symbols = SymbolTable::new();
+        symbols.insert(0x1000, "loop");
+
+        assert_eq!(symbols.describe(0x0FFF), None);
+        assert_eq!(symbols.describe(0x1000).as_deref(), Some("loop"));
+        assert_eq!(symbols.describe(0x1012).as_deref(), Some("loop+$12"));
+    }
+}
@@ -1,3 +1,7 @@
+pub mod filter;
+
+use crate::frontend::filter::FrameFilterChain;
+use crate::state::StateFormat;
 use bincode::{Decode, Encode};
 use jgenesis_proc_macros::{EnumDisplay, EnumFromStr};
 use std::error::Error;
@@ -155,6 +159,12 @@ pub trait PartialClone {
     fn partial_clone(&self) -> Self;
 }
 
+impl<T: PartialClone> PartialClone for Option<T> {
+    fn partial_clone(&self) -> Self {
+        self.as_ref().map(T::partial_clone)
+    }
+}
+
 pub use jgenesis_proc_macros::PartialClone;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, EnumDisplay, EnumFromStr, Encode, Decode)]
@@ -177,29 +187,70 @@ pub trait EmulatorTrait: Encode + Decode + PartialClone {
     type Inputs;
     type Config;
 
-    type Err<RErr: Debug + Display + Send + Sync + 'static, AErr: Debug + Display + Send + Sync + 'static, SErr: Debug + Display + Send + Sync + 'static>: Error + Send + Sync + 'static;
+    /// Identifies this core's save states. The version should be incremented whenever the state
+    /// format changes; see [`StateFormat`].
+    const STATE_FORMAT: StateFormat;
+
+    type Err<RErr: Debug + Display + Send + Sync + 'static, AErr: Debug + Display + Send + Sync + 'static>: Error + Send + Sync + 'static;
 
     /// Tick the emulator for a small amount of time, e.g. a single CPU instruction.
     ///
+    /// Cores never write save files during `tick`; see [`EmulatorTrait::persist_save`].
+    ///
     /// # Errors
     ///
-    /// This method should propagate any errors encountered while rendering frames, pushing audio
-    /// samples, or persisting save files.
+    /// This method should propagate any errors encountered while rendering frames or pushing audio
+    /// samples.
     #[allow(clippy::type_complexity)]
-    fn tick<R, A, S>(
+    fn tick<R, A>(
         &mut self,
         renderer: &mut R,
         audio_output: &mut A,
         inputs: &Self::Inputs,
-        save_writer: &mut S,
-    ) -> TickResult<Self::Err<R::Err, A::Err, S::Err>>
+    ) -> TickResult<Self::Err<R::Err, A::Err>>
+    where
+        R: Renderer,
+        R::Err: Debug + Display + Send + Sync + 'static,
+        A: AudioOutput,
+        A::Err: Debug + Display + Send + Sync + 'static;
+
+    /// Tick the emulator, passing every frame rendered during the tick through `frame_filters`
+    /// before it reaches the renderer.
+    ///
+    /// # Errors
+    ///
+    /// This method should propagate any errors encountered while rendering frames or pushing audio
+    /// samples.
+    #[allow(clippy::type_complexity)]
+    fn tick_filtered<R, A>(
+        &mut self,
+        frame_filters: &mut FrameFilterChain,
+        renderer: &mut R,
+        audio_output: &mut A,
+        inputs: &Self::Inputs,
+    ) -> TickResult<Self::Err<R::Err, A::Err>>
     where
         R: Renderer,
         R::Err: Debug + Display + Send + Sync + 'static,
         A: AudioOutput,
         A::Err: Debug + Display + Send + Sync + 'static,
-        S: SaveWriter,
-        S::Err: Debug + Display + Send + Sync + 'static;
+    {
+        self.tick(&mut frame_filters.renderer(renderer), audio_output, inputs)
+    }
+
+    /// Returns true if battery-backed save memory has changed since it was last persisted.
+    fn save_dirty(&self) -> bool;
+
+    /// Write battery-backed save memory (and any auxiliary save files, e.g. RTC state) if it has
+    /// changed since it was last persisted. Does nothing if there are no unsaved changes.
+    ///
+    /// The frontend decides when to persist saves, e.g. periodically during emulation, when
+    /// pausing, before a hard reset, and before exiting.
+    ///
+    /// # Errors
+    ///
+    /// This method should propagate any errors returned by the save writer.
+    fn persist_save<S: SaveWriter>(&mut self, save_writer: &mut S) -> Result<(), S::Err>;
 
     /// Forcibly render the current frame buffer.
     ///
@@ -216,6 +267,10 @@ pub trait EmulatorTrait: Encode + Decode + PartialClone {
 
     fn soft_reset(&mut self);
 
+    /// Recreate the emulator from its ROM, reloading save files with the given save writer.
+    ///
+    /// Unsaved changes to save memory are lost, so frontends should call
+    /// [`EmulatorTrait::persist_save`] first.
     fn hard_reset<S: SaveWriter>(&mut self, save_writer: &mut S);
 
     fn timing_mode(&self) -> TimingMode;
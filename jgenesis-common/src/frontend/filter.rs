@@ -0,0 +1,242 @@
+//! CPU-side frame filters
+//!
+//! Filters run on every frame after the core renders it and before the frontend uploads it to the
+//! GPU. This is the place for filters that need the raw frame rather than a texture, e.g. NTSC
+//! composite filters, deinterlacers, and LCD grid effects. Filters may keep state between frames,
+//! which lets them look at two consecutive frames.
+
+use crate::frontend::{Color, FrameSize, PixelAspectRatio, Renderer};
+
+/// A frame as it passes through the filter chain.
+///
+/// `buffer` always contains exactly `size.width * size.height` pixels.
+#[derive(Debug, Clone)]
+pub struct FilterFrame {
+    pub buffer: Vec<Color>,
+    pub size: FrameSize,
+    pub pixel_aspect_ratio: Option<PixelAspectRatio>,
+}
+
+impl Default for FilterFrame {
+    fn default() -> Self {
+        Self {
+            buffer: Vec::new(),
+            size: FrameSize { width: 0, height: 0 },
+            pixel_aspect_ratio: None,
+        }
+    }
+}
+
+pub trait FrameFilter: Send {
+    /// Short name that identifies the filter within a chain.
+    fn name(&self) -> &'static str;
+
+    /// Filter a frame in place. Filters may change the frame size and pixel aspect ratio, but they
+    /// must keep the buffer length equal to the frame's pixel count.
+    fn apply(&mut self, frame: &mut FilterFrame);
+
+    /// Discard any state carried between frames, e.g. after a reset or after loading a save state.
+    fn reset(&mut self) {}
+}
+
+/// An ordered list of frame filters that are applied one after another.
+#[derive(Default)]
+pub struct FrameFilterChain {
+    filters: Vec<Box<dyn FrameFilter>>,
+    frame: FilterFrame,
+}
+
+impl FrameFilterChain {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a filter to the end of the chain.
+    pub fn push<F: FrameFilter + 'static>(&mut self, filter: F) {
+        self.filters.push(Box::new(filter));
+    }
+
+    /// Add a filter to the start of the chain, e.g. for filters that should see the frame exactly
+    /// as the core rendered it.
+    pub fn push_front<F: FrameFilter + 'static>(&mut self, filter: F) {
+        self.filters.insert(0, Box::new(filter));
+    }
+
+    /// Remove every filter with the given name.
+    pub fn remove(&mut self, name: &str) {
+        self.filters.retain(|filter| filter.name() != name);
+    }
+
+    pub fn clear(&mut self) {
+        self.filters.clear();
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.filters.is_empty()
+    }
+
+    /// Reset the state of every filter in the chain.
+    pub fn reset(&mut self) {
+        for filter in &mut self.filters {
+            filter.reset();
+        }
+    }
+
+    /// Wrap a renderer so that every frame is passed through the filter chain before it reaches
+    /// the renderer.
+    pub fn renderer<'a, R>(&'a mut self, renderer: &'a mut R) -> FilteringRenderer<'a, R> {
+        FilteringRenderer { chain: self, renderer }
+    }
+
+    fn apply(
+        &mut self,
+        frame_buffer: &[Color],
+        frame_size: FrameSize,
+        pixel_aspect_ratio: Option<PixelAspectRatio>,
+    ) -> &FilterFrame {
+        let len = (frame_size.width * frame_size.height) as usize;
+        self.frame.buffer.clear();
+        self.frame.buffer.extend_from_slice(&frame_buffer[..len]);
+        self.frame.size = frame_size;
+        self.frame.pixel_aspect_ratio = pixel_aspect_ratio;
+
+        for filter in &mut self.filters {
+            filter.apply(&mut self.frame);
+        }
+
+        &self.frame
+    }
+}
+
+/// Renderer wrapper that applies a [`FrameFilterChain`] to every frame.
+pub struct FilteringRenderer<'a, R> {
+    chain: &'a mut FrameFilterChain,
+    renderer: &'a mut R,
+}
+
+impl<R: Renderer> Renderer for FilteringRenderer<'_, R> {
+    type Err = R::Err;
+
+    fn render_frame(
+        &mut self,
+        frame_buffer: &[Color],
+        frame_size: FrameSize,
+        pixel_aspect_ratio: Option<PixelAspectRatio>,
+    ) -> Result<(), Self::Err> {
+        if self.chain.is_empty() {
+            return self.renderer.render_frame(frame_buffer, frame_size, pixel_aspect_ratio);
+        }
+
+        let frame = self.chain.apply(frame_buffer, frame_size, pixel_aspect_ratio);
+        self.renderer.render_frame(&frame.buffer, frame.size, frame.pixel_aspect_ratio)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct CaptureRenderer(Vec<Color>, FrameSize);
+
+    impl Renderer for CaptureRenderer {
+        type Err = ();
+
+        fn render_frame(
+            &mut self,
+            frame_buffer: &[Color],
+            frame_size: FrameSize,
+            _pixel_aspect_ratio: Option<PixelAspectRatio>,
+        ) -> Result<(), Self::Err> {
+            let len = (frame_size.width * frame_size.height) as usize;
+            self.0 = frame_buffer[..len].to_vec();
+            self.1 = frame_size;
+            Ok(())
+        }
+    }
+
+    // Doubles every line and blends each frame with the previous frame
+    #[derive(Default)]
+    struct TestFilter {
+        prev: Vec<Color>,
+    }
+
+    impl FrameFilter for TestFilter {
+        fn name(&self) -> &'static str {
+            "test"
+        }
+
+        fn apply(&mut self, frame: &mut FilterFrame) {
+            let width = frame.size.width as usize;
+            frame.buffer = frame
+                .buffer
+                .chunks(width)
+                .flat_map(|line| [line, line])
+                .flatten()
+                .copied()
+                .collect();
+            frame.size.height *= 2;
+
+            if self.prev.len() == frame.buffer.len() {
+                for (color, prev) in frame.buffer.iter_mut().zip(&self.prev) {
+                    color.r = color.r / 2 + prev.r / 2;
+                }
+            }
+            self.prev.clone_from(&frame.buffer);
+        }
+
+        fn reset(&mut self) {
+            self.prev.clear();
+        }
+    }
+
+    #[test]
+    fn filters_applied_in_order() {
+        let mut chain = FrameFilterChain::new();
+        chain.push(TestFilter::default());
+
+        let mut renderer = CaptureRenderer(Vec::new(), FrameSize { width: 0, height: 0 });
+        let size = FrameSize { width: 2, height: 1 };
+        let frame = [Color::rgb(100, 0, 0), Color::rgb(200, 0, 0), Color::rgb(255, 255, 255)];
+
+        chain.renderer(&mut renderer).render_frame(&frame, size, None).unwrap();
+        assert_eq!(renderer.1, FrameSize { width: 2, height: 2 });
+        assert_eq!(
+            renderer.0.iter().map(|color| color.r).collect::<Vec<_>>(),
+            [100, 200, 100, 200]
+        );
+
+        let frame = [Color::rgb(0, 0, 0); 2];
+        chain.renderer(&mut renderer).render_frame(&frame, size, None).unwrap();
+        assert_eq!(renderer.0.iter().map(|color| color.r).collect::<Vec<_>>(), [50, 100, 50, 100]);
+
+        chain.reset();
+        chain.renderer(&mut renderer).render_frame(&frame, size, None).unwrap();
+        assert!(renderer.0.iter().all(|&color| color == Color::BLACK));
+    }
+
+    #[test]
+    fn remove_by_name() {
+        let mut chain = FrameFilterChain::new();
+        chain.push(TestFilter::default());
+        chain.push_front(TestFilter::default());
+        assert!(!chain.is_empty());
+
+        chain.remove("test");
+        assert!(chain.is_empty());
+    }
+
+    #[test]
+    fn empty_chain_passes_through() {
+        let mut chain = FrameFilterChain::new();
+        let mut renderer = CaptureRenderer(Vec::new(), FrameSize { width: 0, height: 0 });
+        let frame = [Color::rgb(1, 2, 3); 4];
+
+        chain
+            .renderer(&mut renderer)
+            .render_frame(&frame, FrameSize { width: 2, height: 2 }, None)
+            .unwrap();
+        assert_eq!(renderer.0, frame);
+    }
+}
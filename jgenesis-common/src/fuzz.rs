@@ -0,0 +1,41 @@
+//! Support for replaying the cargo-fuzz corpus in regular tests
+//!
+//! The fuzz targets in the repository's `fuzz` directory call entry points exposed by each crate
+//! under its `fuzzing` feature. Inputs in `fuzz/corpus/<target>` include a few hand-written seeds
+//! plus every input that has ever crashed a target, so replaying them in `cargo test` catches
+//! regressions without needing a nightly toolchain or cargo-fuzz.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Directory containing the corpus for the given fuzz target.
+#[must_use]
+pub fn corpus_dir(target: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../fuzz/corpus").join(target)
+}
+
+/// Call `f` with every input in the given fuzz target's corpus.
+///
+/// The path of each input is printed before running it so that a failing test shows which input
+/// caused the failure.
+///
+/// # Panics
+///
+/// Panics if the corpus directory exists but cannot be read.
+pub fn replay_corpus(target: &str, mut f: impl FnMut(&[u8])) {
+    let dir = corpus_dir(target);
+    let Ok(entries) = fs::read_dir(&dir) else {
+        // The corpus is not included in published crates
+        eprintln!("No fuzz corpus found at '{}'", dir.display());
+        return;
+    };
+
+    let mut paths: Vec<_> = entries.map(|entry| entry.unwrap().path()).collect();
+    paths.sort();
+
+    for path in paths {
+        let input = fs::read(&path).unwrap();
+        eprintln!("Replaying '{}'", path.display());
+        f(&input);
+    }
+}
@@ -1,4 +1,12 @@
 pub mod audio;
+pub mod command;
+pub mod debug;
 pub mod frontend;
+#[cfg(any(test, feature = "fuzzing"))]
+pub mod fuzz;
+pub mod logging;
 pub mod num;
+pub mod rng;
+pub mod simd;
+pub mod state;
 pub mod timeutils;
@@ -0,0 +1,307 @@
+//! Per-subsystem log level overrides
+//!
+//! A subsystem groups the log targets for one kind of component across all of the emulation cores,
+//! e.g. [`LogSubsystem::Vdp`] covers the Genesis/SMS VDPs as well as the NES/SNES/Game Boy PPUs.
+//! Overriding a subsystem's level makes it possible to trace one component without enabling trace
+//! logging everywhere else. Overrides can be changed at any time, including while a game is running.
+//!
+//! [`SubsystemLogger`] also keeps the most recent log messages in memory so that they can be
+//! included in crash reports.
+
+use jgenesis_proc_macros::{EnumAll, EnumDisplay, EnumFromStr};
+use log::{LevelFilter, Log, Metadata, Record, SetLoggerError};
+use std::collections::VecDeque;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{Mutex, TryLockError};
+use thiserror::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumDisplay, EnumFromStr, EnumAll)]
+pub enum LogSubsystem {
+    /// Video chips: VDPs and PPUs
+    Vdp,
+    Ym2612,
+    Z80,
+    /// Sega CD CD-ROM decoder
+    Cdc,
+    /// Cartridge mappers and coprocessors
+    Mapper,
+}
+
+impl LogSubsystem {
+    fn target_prefixes(self) -> &'static [&'static str] {
+        match self {
+            Self::Vdp => &[
+                "genesis_core::vdp",
+                "smsgg_core::vdp",
+                "nes_core::ppu",
+                "snes_core::ppu",
+                "gb_core::ppu",
+            ],
+            Self::Ym2612 => &["genesis_core::ym2612"],
+            Self::Z80 => &["z80_emu"],
+            Self::Cdc => &["segacd_core::cddrive::cdc"],
+            Self::Mapper => &[
+                "genesis_core::memory::external",
+                "genesis_core::memory::eeprom",
+                "smsgg_core::memory",
+                "nes_core::bus::cartridge",
+                "snes_core::memory::cartridge",
+                "snes_coprocessors",
+                "gb_core::cartridge",
+            ],
+        }
+    }
+
+    /// Returns the subsystem that the given log target belongs to, if any.
+    #[must_use]
+    pub fn from_target(target: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|subsystem| {
+            subsystem.target_prefixes().iter().any(|&prefix| {
+                target
+                    .strip_prefix(prefix)
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+            })
+        })
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+const LEVEL_FILTERS: [LevelFilter; 6] = [
+    LevelFilter::Off,
+    LevelFilter::Error,
+    LevelFilter::Warn,
+    LevelFilter::Info,
+    LevelFilter::Debug,
+    LevelFilter::Trace,
+];
+
+// 0 means no override; otherwise the value is 1 + the LevelFilter discriminant
+const NO_OVERRIDE: u8 = 0;
+
+#[allow(clippy::declare_interior_mutable_const)]
+const NO_OVERRIDE_ATOMIC: AtomicU8 = AtomicU8::new(NO_OVERRIDE);
+
+static SUBSYSTEM_LEVELS: [AtomicU8; LogSubsystem::ALL.len()] =
+    [NO_OVERRIDE_ATOMIC; LogSubsystem::ALL.len()];
+
+static BASE_MAX_LEVEL: AtomicU8 = AtomicU8::new(LevelFilter::Trace as u8);
+
+fn encode_level(level: Option<LevelFilter>) -> u8 {
+    level.map_or(NO_OVERRIDE, |level| level as u8 + 1)
+}
+
+fn decode_level(value: u8) -> Option<LevelFilter> {
+    (value != NO_OVERRIDE).then(|| LEVEL_FILTERS[(value - 1) as usize])
+}
+
+/// Returns the override level for the given subsystem, or `None` if the subsystem uses the
+/// default log filter.
+#[must_use]
+pub fn subsystem_level(subsystem: LogSubsystem) -> Option<LevelFilter> {
+    decode_level(SUBSYSTEM_LEVELS[subsystem.index()].load(Ordering::Relaxed))
+}
+
+/// Override the log level for the given subsystem. `None` removes the override so that the
+/// subsystem goes back to using the default log filter.
+pub fn set_subsystem_level(subsystem: LogSubsystem, level: Option<LevelFilter>) {
+    let prev = SUBSYSTEM_LEVELS[subsystem.index()].swap(encode_level(level), Ordering::Relaxed);
+    if prev != encode_level(level) {
+        log::info!(
+            "Log level for subsystem {subsystem} set to {}",
+            level.map_or_else(|| "default".into(), |level| level.to_string())
+        );
+    }
+
+    update_max_level();
+}
+
+// The log macros check the global max level before doing anything else, so keep it as low as
+// possible to avoid slowing down hot paths when trace logging is not enabled
+fn update_max_level() {
+    let base_max_level = LEVEL_FILTERS[BASE_MAX_LEVEL.load(Ordering::Relaxed) as usize];
+    let max_level =
+        LogSubsystem::ALL.into_iter().filter_map(subsystem_level).fold(base_max_level, Ord::max);
+    log::set_max_level(max_level);
+}
+
+const RECENT_LOG_CAPACITY: usize = 500;
+
+static RECENT_LOG: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+fn push_recent_log(line: String) {
+    let Ok(mut recent_log) = RECENT_LOG.lock() else { return };
+
+    if recent_log.len() == RECENT_LOG_CAPACITY {
+        recent_log.pop_front();
+    }
+    recent_log.push_back(line);
+}
+
+/// Returns the most recent messages that were logged through [`SubsystemLogger`], oldest first.
+///
+/// This never blocks so that it is safe to call from a panic hook; if the log is currently locked
+/// then no messages are returned.
+#[must_use]
+pub fn recent_log_lines() -> Vec<String> {
+    match RECENT_LOG.try_lock() {
+        Ok(recent_log) => recent_log.iter().cloned().collect(),
+        Err(TryLockError::Poisoned(err)) => err.into_inner().iter().cloned().collect(),
+        Err(TryLockError::WouldBlock) => vec![],
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum LogDirectiveError {
+    #[error("log directive '{0}' is not in the format SUBSYSTEM=LEVEL")]
+    InvalidFormat(String),
+    #[error("invalid log subsystem '{0}'; expected one of vdp, ym2612, z80, cdc, mapper")]
+    InvalidSubsystem(String),
+    #[error("invalid log level '{0}'; expected one of off, error, warn, info, debug, trace")]
+    InvalidLevel(String),
+}
+
+/// A subsystem level override in the format `SUBSYSTEM=LEVEL`, e.g. `vdp=trace`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogDirective {
+    pub subsystem: LogSubsystem,
+    pub level: LevelFilter,
+}
+
+impl LogDirective {
+    pub fn apply(self) {
+        set_subsystem_level(self.subsystem, Some(self.level));
+    }
+}
+
+impl FromStr for LogDirective {
+    type Err = LogDirectiveError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((subsystem, level)) = s.split_once('=') else {
+            return Err(LogDirectiveError::InvalidFormat(s.into()));
+        };
+
+        let subsystem = LogSubsystem::from_str(subsystem.trim())
+            .map_err(|_| LogDirectiveError::InvalidSubsystem(subsystem.into()))?;
+        let level = LevelFilter::from_str(level.trim())
+            .map_err(|_| LogDirectiveError::InvalidLevel(level.into()))?;
+
+        Ok(Self { subsystem, level })
+    }
+}
+
+/// Logger that applies subsystem level overrides on top of a base logger.
+///
+/// Records from subsystems without an override are passed to the base logger, which applies its
+/// own filtering. Records from subsystems with an override are filtered by the override level and
+/// then passed to the unfiltered logger, which should accept records of every level.
+pub struct SubsystemLogger {
+    base: Box<dyn Log>,
+    unfiltered: Box<dyn Log>,
+}
+
+impl SubsystemLogger {
+    #[must_use]
+    pub fn new(base: Box<dyn Log>, base_max_level: LevelFilter, unfiltered: Box<dyn Log>) -> Self {
+        BASE_MAX_LEVEL.store(base_max_level as u8, Ordering::Relaxed);
+        Self { base, unfiltered }
+    }
+
+    /// Install this as the global logger.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a global logger has already been installed.
+    pub fn install(self) -> Result<(), SetLoggerError> {
+        log::set_boxed_logger(Box::new(self))?;
+        update_max_level();
+        Ok(())
+    }
+}
+
+impl Log for SubsystemLogger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        match LogSubsystem::from_target(metadata.target()).and_then(subsystem_level) {
+            Some(level) => metadata.level() <= level,
+            None => self.base.enabled(metadata),
+        }
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        match LogSubsystem::from_target(record.target()).and_then(subsystem_level) {
+            Some(level) => {
+                if record.level() <= level {
+                    self.unfiltered.log(record);
+                    push_recent_log(format_record(record));
+                }
+            }
+            None => {
+                if self.base.enabled(record.metadata()) {
+                    push_recent_log(format_record(record));
+                }
+                self.base.log(record);
+            }
+        }
+    }
+
+    fn flush(&self) {
+        self.base.flush();
+        self.unfiltered.flush();
+    }
+}
+
+fn format_record(record: &Record<'_>) -> String {
+    format!("[{} {}] {}", record.level(), record.target(), record.args())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_directive() {
+        assert_eq!(
+            "vdp=trace".parse::<LogDirective>().unwrap(),
+            LogDirective { subsystem: LogSubsystem::Vdp, level: LevelFilter::Trace }
+        );
+        assert_eq!(
+            "YM2612=Debug".parse::<LogDirective>().unwrap(),
+            LogDirective { subsystem: LogSubsystem::Ym2612, level: LevelFilter::Debug }
+        );
+
+        assert!(matches!("vdp".parse::<LogDirective>(), Err(LogDirectiveError::InvalidFormat(_))));
+        assert!(matches!(
+            "psg=trace".parse::<LogDirective>(),
+            Err(LogDirectiveError::InvalidSubsystem(_))
+        ));
+        assert!(matches!(
+            "cdc=verbose".parse::<LogDirective>(),
+            Err(LogDirectiveError::InvalidLevel(_))
+        ));
+    }
+
+    #[test]
+    fn subsystem_from_target() {
+        assert_eq!(LogSubsystem::from_target("genesis_core::vdp"), Some(LogSubsystem::Vdp));
+        assert_eq!(LogSubsystem::from_target("genesis_core::vdp::render"), Some(LogSubsystem::Vdp));
+        assert_eq!(LogSubsystem::from_target("z80_emu::core"), Some(LogSubsystem::Z80));
+        assert_eq!(LogSubsystem::from_target("genesis_core::vdpx"), None);
+        assert_eq!(LogSubsystem::from_target("genesis_core::memory"), None);
+    }
+
+    #[test]
+    fn recent_log_drops_oldest_lines() {
+        for i in 0..RECENT_LOG_CAPACITY + 10 {
+            push_recent_log(i.to_string());
+        }
+
+        let lines = recent_log_lines();
+        assert_eq!(lines.len(), RECENT_LOG_CAPACITY);
+        assert_eq!(lines[0], "10");
+        assert_eq!(lines[RECENT_LOG_CAPACITY - 1], (RECENT_LOG_CAPACITY + 9).to_string());
+    }
+}
@@ -0,0 +1,94 @@
+//! Seedable randomness for emulation cores
+//!
+//! All randomness inside an emulation core should come from [`Rng`] so that a core created with a
+//! fixed seed behaves identically every time it is run, which TAS movies and netplay depend on.
+
+use bincode::{Decode, Encode};
+use jgenesis_proc_macros::{EnumDisplay, EnumFromStr};
+
+/// Small pseudo-random number generator (`SplitMix64`). The algorithm is fixed so that a given
+/// seed produces the same sequence across platforms and versions.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    #[must_use]
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Create an RNG from the given seed, or from a randomly chosen seed if `seed` is None.
+    #[must_use]
+    pub fn from_optional_seed(seed: Option<u64>) -> Self {
+        let seed = seed.unwrap_or_else(|| {
+            let seed = rand::random();
+            log::info!("Using randomly generated RNG seed {seed}");
+            seed
+        });
+        Self::new(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+/// Contents of work RAM at power on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumDisplay, EnumFromStr, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InitialRamState {
+    /// Every byte is $00
+    AllZeroes,
+    /// Every byte is $FF
+    AllOnes,
+    /// Mostly $00 and $FF bytes with some fully random bytes mixed in, which is similar to how
+    /// SRAM chips tend to power on
+    Random,
+}
+
+impl InitialRamState {
+    pub fn fill(self, ram: &mut [u8], rng: &mut Rng) {
+        match self {
+            Self::AllZeroes => ram.fill(0x00),
+            Self::AllOnes => ram.fill(0xFF),
+            Self::Random => {
+                for byte in ram {
+                    let random = rng.next_u64();
+                    *byte = match random & 0x07 {
+                        0 => (random >> 8) as u8,
+                        1..=3 => 0x00,
+                        _ => 0xFF,
+                    };
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_same_ram() {
+        let mut ram1 = [0; 256];
+        let mut ram2 = [0; 256];
+
+        InitialRamState::Random.fill(&mut ram1, &mut Rng::new(12345));
+        InitialRamState::Random.fill(&mut ram2, &mut Rng::new(12345));
+        assert_eq!(ram1, ram2);
+
+        InitialRamState::Random.fill(&mut ram2, &mut Rng::new(54321));
+        assert_ne!(ram1, ram2);
+
+        InitialRamState::AllOnes.fill(&mut ram1, &mut Rng::new(12345));
+        assert!(ram1.iter().all(|&byte| byte == 0xFF));
+    }
+}
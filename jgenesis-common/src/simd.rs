@@ -0,0 +1,229 @@
+//! Runtime host CPU feature detection and SIMD kernel dispatch
+//!
+//! Kernels are compiled for every instruction set that the target architecture might support, and
+//! the best one is selected at runtime based on what the host CPU reports. This avoids needing
+//! separate builds for e.g. AVX2-capable CPUs while keeping a portable scalar fallback for every
+//! other target, including WASM.
+
+use jgenesis_proc_macros::EnumDisplay;
+use std::sync::OnceLock;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumDisplay)]
+pub enum SimdLevel {
+    /// No SIMD; plain Rust that the compiler may or may not auto-vectorize
+    Scalar,
+    /// x86 SSE2 (always available on x86-64)
+    Sse2,
+    /// x86 AVX2
+    Avx2,
+    /// ARM NEON (always available on 64-bit ARM)
+    Neon,
+}
+
+impl SimdLevel {
+    /// Detect the best SIMD instruction set supported by the host CPU.
+    #[must_use]
+    pub fn detect() -> Self {
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        {
+            if is_x86_feature_detected!("avx2") {
+                return Self::Avx2;
+            }
+
+            if is_x86_feature_detected!("sse2") {
+                return Self::Sse2;
+            }
+        }
+
+        #[cfg(target_arch = "aarch64")]
+        {
+            if std::arch::is_aarch64_feature_detected!("neon") {
+                return Self::Neon;
+            }
+        }
+
+        Self::Scalar
+    }
+
+    /// Every level that is usable on the host CPU, from least to most capable.
+    #[must_use]
+    pub fn supported() -> Vec<Self> {
+        let mut levels = vec![Self::Scalar];
+
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        {
+            if is_x86_feature_detected!("sse2") {
+                levels.push(Self::Sse2);
+            }
+
+            if is_x86_feature_detected!("avx2") {
+                levels.push(Self::Avx2);
+            }
+        }
+
+        #[cfg(target_arch = "aarch64")]
+        {
+            if std::arch::is_aarch64_feature_detected!("neon") {
+                levels.push(Self::Neon);
+            }
+        }
+
+        levels
+    }
+}
+
+static HOST_SIMD_LEVEL: OnceLock<SimdLevel> = OnceLock::new();
+
+/// The SIMD level used by the dispatching kernels in this module. Detected the first time it is
+/// needed and cached for the rest of the process.
+#[must_use]
+pub fn host_simd_level() -> SimdLevel {
+    *HOST_SIMD_LEVEL.get_or_init(|| {
+        let level = SimdLevel::detect();
+        log::info!("Using {level} kernels for SIMD-accelerated code paths");
+        level
+    })
+}
+
+/// Sum of the element-wise products of `a` and `b`, ignoring elements past the end of the shorter
+/// slice.
+///
+/// The SIMD versions add in a different order than the scalar version, so results may differ in
+/// the last few bits.
+#[must_use]
+pub fn dot_product(a: &[f64], b: &[f64]) -> f64 {
+    dot_product_with(host_simd_level(), a, b)
+}
+
+fn dot_product_with(level: SimdLevel, a: &[f64], b: &[f64]) -> f64 {
+    let len = a.len().min(b.len());
+    let (a, b) = (&a[..len], &b[..len]);
+
+    match level {
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        // SAFETY: Only selected if the CPU reports AVX2 support
+        SimdLevel::Avx2 => unsafe { x86::dot_product_avx2(a, b) },
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        // SAFETY: Only selected if the CPU reports SSE2 support
+        SimdLevel::Sse2 => unsafe { x86::dot_product_sse2(a, b) },
+        #[cfg(target_arch = "aarch64")]
+        SimdLevel::Neon => aarch64::dot_product_neon(a, b),
+        _ => dot_product_scalar(a, b),
+    }
+}
+
+fn dot_product_scalar(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+mod x86 {
+    #[cfg(target_arch = "x86")]
+    use std::arch::x86::{
+        __m128d, _mm_add_pd, _mm_add_sd, _mm_cvtsd_f64, _mm_loadu_pd, _mm_mul_pd, _mm_setzero_pd,
+        _mm_unpackhi_pd, _mm256_add_pd, _mm256_castpd256_pd128, _mm256_extractf128_pd,
+        _mm256_loadu_pd, _mm256_mul_pd, _mm256_setzero_pd,
+    };
+    #[cfg(target_arch = "x86_64")]
+    use std::arch::x86_64::{
+        __m128d, _mm_add_pd, _mm_add_sd, _mm_cvtsd_f64, _mm_loadu_pd, _mm_mul_pd, _mm_setzero_pd,
+        _mm_unpackhi_pd, _mm256_add_pd, _mm256_castpd256_pd128, _mm256_extractf128_pd,
+        _mm256_loadu_pd, _mm256_mul_pd, _mm256_setzero_pd,
+    };
+
+    // Both slices must be the same length
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn dot_product_avx2(a: &[f64], b: &[f64]) -> f64 {
+        let simd_len = a.len() & !3;
+
+        let mut acc = _mm256_setzero_pd();
+        for i in (0..simd_len).step_by(4) {
+            // SAFETY: i + 4 <= simd_len <= the length of both slices
+            let (a_vec, b_vec) =
+                unsafe { (_mm256_loadu_pd(a.as_ptr().add(i)), _mm256_loadu_pd(b.as_ptr().add(i))) };
+            acc = _mm256_add_pd(acc, _mm256_mul_pd(a_vec, b_vec));
+        }
+
+        let acc = _mm_add_pd(_mm256_castpd256_pd128(acc), _mm256_extractf128_pd::<1>(acc));
+        // SAFETY: AVX2 implies SSE2
+        let simd_sum = unsafe { horizontal_sum(acc) };
+
+        simd_sum + super::dot_product_scalar(&a[simd_len..], &b[simd_len..])
+    }
+
+    // Both slices must be the same length
+    #[target_feature(enable = "sse2")]
+    pub(super) unsafe fn dot_product_sse2(a: &[f64], b: &[f64]) -> f64 {
+        let simd_len = a.len() & !1;
+
+        let mut acc = _mm_setzero_pd();
+        for i in (0..simd_len).step_by(2) {
+            // SAFETY: i + 2 <= simd_len <= the length of both slices
+            let (a_vec, b_vec) =
+                unsafe { (_mm_loadu_pd(a.as_ptr().add(i)), _mm_loadu_pd(b.as_ptr().add(i))) };
+            acc = _mm_add_pd(acc, _mm_mul_pd(a_vec, b_vec));
+        }
+
+        // SAFETY: This function requires SSE2
+        let simd_sum = unsafe { horizontal_sum(acc) };
+
+        simd_sum + super::dot_product_scalar(&a[simd_len..], &b[simd_len..])
+    }
+
+    #[target_feature(enable = "sse2")]
+    unsafe fn horizontal_sum(v: __m128d) -> f64 {
+        _mm_cvtsd_f64(_mm_add_sd(v, _mm_unpackhi_pd(v, v)))
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+mod aarch64 {
+    use std::arch::aarch64::{vaddvq_f64, vdupq_n_f64, vfmaq_f64, vld1q_f64};
+
+    // Both slices must be the same length
+    pub(super) fn dot_product_neon(a: &[f64], b: &[f64]) -> f64 {
+        let simd_len = a.len() & !1;
+
+        // SAFETY: NEON is always available on 64-bit ARM, and all loads are of 2 elements starting at
+        // an index less than simd_len, which is at most the length of both slices
+        let simd_sum = unsafe {
+            let mut acc = vdupq_n_f64(0.0);
+            for i in (0..simd_len).step_by(2) {
+                acc = vfmaq_f64(acc, vld1q_f64(a.as_ptr().add(i)), vld1q_f64(b.as_ptr().add(i)));
+            }
+            vaddvq_f64(acc)
+        };
+
+        simd_sum + super::dot_product_scalar(&a[simd_len..], &b[simd_len..])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detected_level_is_supported() {
+        assert!(SimdLevel::supported().contains(&SimdLevel::detect()));
+    }
+
+    #[test]
+    fn dot_product_matches_scalar() {
+        let a: Vec<f64> = (0..37).map(|i| f64::from(i) * 0.25 - 3.0).collect();
+        let b: Vec<f64> = (0..40).map(|i| 1.0 / f64::from(i + 1)).collect();
+        let expected = dot_product_with(SimdLevel::Scalar, &a, &b);
+
+        for level in SimdLevel::supported() {
+            for len in [0, 1, 2, 3, 5, 8, 37] {
+                let expected = dot_product_with(SimdLevel::Scalar, &a[..len], &b);
+                let actual = dot_product_with(level, &a[..len], &b);
+                assert!(
+                    (actual - expected).abs() < 1e-9,
+                    "{level}, len {len}: {actual} != {expected}"
+                );
+            }
+
+            assert!((dot_product_with(level, &a, &b) - expected).abs() < 1e-9, "{level}");
+        }
+    }
+}
@@ -0,0 +1,59 @@
+//! Save state serialization shared by all emulation cores
+//!
+//! Save states are the bincode encoding of the emulator struct wrapped in the versioned container
+//! from [`jgenesis_state`], which records the core and state format version
+//! ([`EmulatorTrait::STATE_FORMAT`]) that produced them. The native frontend's save state files use
+//! the same encoding, so states produced here can be loaded there and vice versa. ROM contents and
+//! other read-only data are not included in states; [`load_state`] takes them from the emulator that
+//! the state is loaded into.
+
+use crate::frontend::EmulatorTrait;
+
+pub use jgenesis_state::{bincode_config, StateError, StateFormat};
+
+/// Serialize the current state of the given emulator.
+///
+/// # Errors
+///
+/// This function will return an error if encoding fails, which should only happen if the state
+/// exceeds the maximum state size (100MB).
+pub fn save_state<E: EmulatorTrait>(emulator: &E) -> Result<Vec<u8>, StateError> {
+    jgenesis_state::encode_state(emulator, E::STATE_FORMAT)
+}
+
+/// Deserialize a state previously returned by [`save_state`]. The returned emulator does not own a
+/// ROM; see [`load_state`].
+///
+/// States saved before states were versioned, which contain only the bincode-encoded emulator, are
+/// also accepted.
+///
+/// # Errors
+///
+/// This function will return an error if the state is invalid or was saved by a different core or
+/// an incompatible version of the same core.
+pub fn decode_state<E: EmulatorTrait>(state: &[u8]) -> Result<E, StateError> {
+    jgenesis_state::decode_state_or_legacy(state, E::STATE_FORMAT)
+}
+
+/// Replace the state of `emulator` with a state previously returned by [`save_state`].
+///
+/// The ROM is carried over from `emulator` and the given config is re-applied, since states contain
+/// the config that was active when the state was saved. On error, `emulator` is left unchanged.
+///
+/// # Errors
+///
+/// This function will return an error if the state is invalid or was saved by a different core or
+/// an incompatible version of the same core.
+pub fn load_state<E: EmulatorTrait>(
+    emulator: &mut E,
+    config: &E::Config,
+    state: &[u8],
+) -> Result<(), StateError> {
+    let mut loaded_emulator: E = decode_state(state)?;
+    loaded_emulator.take_rom_from(emulator);
+    loaded_emulator.reload_config(config);
+
+    *emulator = loaded_emulator;
+
+    Ok(())
+}
@@ -0,0 +1,85 @@
+//! In-process link between two transports, e.g. two emulator instances running in the same process
+//! or an emulator and a bridge to another transport
+
+use crate::LinkTransport;
+use std::sync::mpsc;
+use std::sync::mpsc::{Receiver, Sender, TryRecvError};
+
+/// One end of an in-process link. Bytes sent on one end are received on the other in order.
+#[derive(Debug)]
+pub struct ChannelLink {
+    pub(crate) sender: Sender<u8>,
+    pub(crate) receiver: Receiver<u8>,
+    connected: bool,
+}
+
+impl ChannelLink {
+    /// Whether the other end still exists.
+    #[must_use]
+    pub fn is_connected(&self) -> bool {
+        self.connected
+    }
+}
+
+/// Create both ends of an in-process link.
+#[must_use]
+pub fn pair() -> (ChannelLink, ChannelLink) {
+    let (a_sender, b_receiver) = mpsc::channel();
+    let (b_sender, a_receiver) = mpsc::channel();
+
+    (
+        ChannelLink { sender: a_sender, receiver: a_receiver, connected: true },
+        ChannelLink { sender: b_sender, receiver: b_receiver, connected: true },
+    )
+}
+
+impl LinkTransport for ChannelLink {
+    fn send(&mut self, byte: u8) {
+        // Bytes sent after the other end is dropped are lost, same as with the cable unplugged
+        if self.sender.send(byte).is_err() {
+            self.connected = false;
+        }
+    }
+
+    fn receive(&mut self) -> Option<u8> {
+        match self.receiver.try_recv() {
+            Ok(byte) => Some(byte),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => {
+                self.connected = false;
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bytes_arrive_in_order() {
+        let (mut a, mut b) = pair();
+
+        a.send(0x01);
+        a.send(0x02);
+        b.send(0x03);
+
+        assert_eq!(b.receive(), Some(0x01));
+        assert_eq!(b.receive(), Some(0x02));
+        assert_eq!(b.receive(), None);
+        assert_eq!(a.receive(), Some(0x03));
+        assert_eq!(a.receive(), None);
+    }
+
+    #[test]
+    fn disconnect() {
+        let (mut a, b) = pair();
+        assert!(a.is_connected());
+
+        drop(b);
+        a.send(0x01);
+        assert_eq!(a.receive(), None);
+        assert!(!a.is_connected());
+    }
+}
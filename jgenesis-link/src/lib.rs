@@ -0,0 +1,93 @@
+//! Byte-stream links between emulated link/serial ports and the outside world
+//!
+//! Cores that emulate a networked peripheral (Genesis controller port serial mode, the Game Gear
+//! Gear-to-Gear port, and eventually the Game Boy link cable) exchange raw bytes through a
+//! [`LinkTransport`] supplied by the frontend and never deal with sockets or framing themselves.
+//! Any transport works with any core:
+//!
+//! - [`NullLink`]: nothing plugged in
+//! - [`channel::ChannelLink`]: two emulator instances in the same process
+//! - [`tcp::TcpLink`]: a raw TCP socket, for linking two native instances or talking to an external
+//!   program (not available on wasm)
+//! - [`webrtc::WebRtcBridge`]: a WebRTC data channel, for the web frontend (wasm only, requires the
+//!   `webrtc` feature)
+//!
+//! [`uart::Uart`] implements the serial port shared by Sega's consoles on top of a transport.
+
+use jgenesis_proc_macros::{FakeDecode, FakeEncode};
+use std::fmt::{Debug, Formatter};
+
+pub mod channel;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod tcp;
+pub mod uart;
+#[cfg(all(target_arch = "wasm32", feature = "webrtc"))]
+pub mod webrtc;
+
+/// A bidirectional byte stream that an emulated link port is connected to.
+///
+/// Transports must never block: emulation runs on the same thread, and a peer that stops
+/// responding should look like an unplugged cable rather than freezing the emulator.
+pub trait LinkTransport {
+    /// Called each time the emulated port finishes transmitting a byte.
+    fn send(&mut self, byte: u8);
+
+    /// Returns the next received byte, or `None` if nothing has arrived.
+    fn receive(&mut self) -> Option<u8>;
+}
+
+impl<T: LinkTransport + ?Sized> LinkTransport for Box<T> {
+    fn send(&mut self, byte: u8) {
+        (**self).send(byte);
+    }
+
+    fn receive(&mut self) -> Option<u8> {
+        (**self).receive()
+    }
+}
+
+/// Transport with nothing on the other end. Sent bytes are discarded and nothing is ever received.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NullLink;
+
+impl LinkTransport for NullLink {
+    fn send(&mut self, _byte: u8) {}
+
+    fn receive(&mut self) -> Option<u8> {
+        None
+    }
+}
+
+/// Frontend-supplied transport owned by a core. Transports are never persisted in save states: this
+/// encodes to nothing and decodes to [`NullLink`].
+#[derive(FakeEncode, FakeDecode)]
+pub struct BoxedLink(Box<dyn LinkTransport + Send>);
+
+impl BoxedLink {
+    #[must_use]
+    pub fn new(transport: Box<dyn LinkTransport + Send>) -> Self {
+        Self(transport)
+    }
+}
+
+impl Default for BoxedLink {
+    fn default() -> Self {
+        Self(Box::new(NullLink))
+    }
+}
+
+impl Debug for BoxedLink {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("BoxedLink")
+    }
+}
+
+impl LinkTransport for BoxedLink {
+    fn send(&mut self, byte: u8) {
+        self.0.send(byte);
+    }
+
+    fn receive(&mut self) -> Option<u8> {
+        self.0.receive()
+    }
+}
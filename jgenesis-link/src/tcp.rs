@@ -0,0 +1,185 @@
+//! TCP transport
+//!
+//! Bytes are sent and received raw with no framing, so two emulator instances can be linked by
+//! having one listen and the other connect, and homebrew can talk to any program that opens a TCP
+//! socket. The transport never blocks emulation: sends are buffered while the socket is not
+//! writable, and in listen mode a new client can connect after the previous one disconnects.
+
+use crate::LinkTransport;
+use std::collections::VecDeque;
+use std::io;
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+
+#[derive(Debug)]
+enum Connection {
+    Client,
+    Server(TcpListener),
+}
+
+#[derive(Debug)]
+pub struct TcpLink {
+    connection: Connection,
+    stream: Option<TcpStream>,
+    outgoing: Vec<u8>,
+    incoming: VecDeque<u8>,
+}
+
+impl TcpLink {
+    /// Connect to a peer that is listening on `address`.
+    ///
+    /// # Errors
+    ///
+    /// Propagates any errors from connecting or configuring the socket.
+    pub fn connect(address: &str) -> io::Result<Self> {
+        let stream = TcpStream::connect(address)?;
+        init_stream(&stream)?;
+        log::info!("Link connected to {address}");
+
+        Ok(Self::new(Connection::Client, Some(stream)))
+    }
+
+    /// Listen on `address` and accept a peer whenever none is connected.
+    ///
+    /// # Errors
+    ///
+    /// Propagates any errors from binding or configuring the socket.
+    pub fn listen(address: &str) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        log::info!("Link listening on {address}");
+
+        Ok(Self::new(Connection::Server(listener), None))
+    }
+
+    fn new(connection: Connection, stream: Option<TcpStream>) -> Self {
+        Self { connection, stream, outgoing: Vec::new(), incoming: VecDeque::new() }
+    }
+
+    /// Whether a peer is currently connected.
+    #[must_use]
+    pub fn is_connected(&self) -> bool {
+        self.stream.is_some()
+    }
+
+    fn accept(&mut self) {
+        let Connection::Server(listener) = &self.connection else { return };
+        if self.stream.is_some() {
+            return;
+        }
+
+        match listener.accept() {
+            Ok((stream, peer)) => {
+                if let Err(err) = init_stream(&stream) {
+                    log::error!("Error configuring link connection from {peer}: {err}");
+                    return;
+                }
+                log::info!("Link accepted connection from {peer}");
+                self.stream = Some(stream);
+            }
+            Err(err) if err.kind() == ErrorKind::WouldBlock => {}
+            Err(err) => log::error!("Error accepting link connection: {err}"),
+        }
+    }
+
+    // Returns Ok(false) if the peer disconnected
+    fn flush_and_read(&mut self) -> io::Result<bool> {
+        let Some(stream) = &mut self.stream else { return Ok(true) };
+
+        while !self.outgoing.is_empty() {
+            match stream.write(&self.outgoing) {
+                Ok(0) => return Ok(false),
+                Ok(bytes_written) => {
+                    self.outgoing.drain(..bytes_written);
+                }
+                Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+
+        let mut read_buffer = [0; 256];
+        loop {
+            match stream.read(&mut read_buffer) {
+                Ok(0) => return Ok(false),
+                Ok(bytes_read) => self.incoming.extend(&read_buffer[..bytes_read]),
+                Err(err) if err.kind() == ErrorKind::WouldBlock => return Ok(true),
+                Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    fn poll(&mut self) {
+        self.accept();
+
+        let connected = self.flush_and_read().unwrap_or_else(|err| {
+            log::error!("Link connection error: {err}");
+            false
+        });
+        if !connected {
+            log::info!("Link peer disconnected");
+            self.stream = None;
+            self.outgoing.clear();
+        }
+    }
+}
+
+fn init_stream(stream: &TcpStream) -> io::Result<()> {
+    stream.set_nonblocking(true)?;
+    stream.set_nodelay(true)
+}
+
+impl LinkTransport for TcpLink {
+    fn send(&mut self, byte: u8) {
+        self.accept();
+
+        // Bytes sent while nothing is connected are lost, same as with no cable plugged in
+        if self.stream.is_none() {
+            return;
+        }
+
+        self.outgoing.push(byte);
+        self.poll();
+    }
+
+    fn receive(&mut self) -> Option<u8> {
+        if self.incoming.is_empty() {
+            self.poll();
+        }
+        self.incoming.pop_front()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    fn receive_with_timeout(link: &mut TcpLink) -> Option<u8> {
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_secs(5) {
+            if let Some(byte) = link.receive() {
+                return Some(byte);
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+        None
+    }
+
+    #[test]
+    fn loopback() {
+        let mut server = TcpLink::listen("127.0.0.1:0").unwrap();
+        let Connection::Server(listener) = &server.connection else { unreachable!() };
+        let address = listener.local_addr().unwrap().to_string();
+
+        let mut client = TcpLink::connect(&address).unwrap();
+        client.send(0x5A);
+        assert_eq!(receive_with_timeout(&mut server), Some(0x5A));
+        assert!(server.is_connected());
+
+        server.send(0xA5);
+        assert_eq!(receive_with_timeout(&mut client), Some(0xA5));
+    }
+}
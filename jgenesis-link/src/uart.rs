@@ -0,0 +1,267 @@
+//! Serial port shared by Sega's consoles
+//!
+//! The Game Gear's Gear-to-Gear port and each of the Genesis I/O ports contain the same UART: a
+//! transmit data register, a receive data register, and a control register laid out as:
+//!
+//! - Bits 7-6: Baud rate (0=4800, 1=2400, 2=1200, 3=300)
+//! - Bit 5: Serial input enabled
+//! - Bit 4: Serial output enabled
+//! - Bit 3: Interrupt when a byte is received (NMI on Game Gear, level 2 interrupt on Genesis)
+//! - Bit 2 (read-only): Receive error, set if a byte arrived before the previous one was read
+//! - Bit 1 (read-only): Receive data ready
+//! - Bit 0 (read-only): Transmit data full, set while a byte is being transmitted
+//!
+//! The baud rate generator is driven by the CPU clock, so each core supplies its own clock rate.
+
+use crate::LinkTransport;
+use bincode::{Decode, Encode};
+use jgenesis_common::num::GetBit;
+
+// Start bit + 8 data bits + stop bit
+const BITS_PER_BYTE: u32 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode)]
+enum BaudRate {
+    #[default]
+    B4800,
+    B2400,
+    B1200,
+    B300,
+}
+
+impl BaudRate {
+    fn from_ctrl_bits(bits: u8) -> Self {
+        match bits & 0x03 {
+            0x00 => Self::B4800,
+            0x01 => Self::B2400,
+            0x02 => Self::B1200,
+            0x03 => Self::B300,
+            _ => unreachable!("value & 0x03 is always <= 0x03"),
+        }
+    }
+
+    fn to_ctrl_bits(self) -> u8 {
+        match self {
+            Self::B4800 => 0x00,
+            Self::B2400 => 0x01,
+            Self::B1200 => 0x02,
+            Self::B300 => 0x03,
+        }
+    }
+
+    fn bits_per_second(self) -> u32 {
+        match self {
+            Self::B4800 => 4800,
+            Self::B2400 => 2400,
+            Self::B1200 => 1200,
+            Self::B300 => 300,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Encode, Decode)]
+pub struct Uart {
+    clock_hz: u32,
+    baud_rate: BaudRate,
+    serial_in: bool,
+    serial_out: bool,
+    rx_interrupt_enabled: bool,
+    tx_data: u8,
+    // Non-zero while a byte is being transmitted, which is when the transmit full bit reads 1
+    tx_cycles_remaining: u32,
+    // Byte that finished transmitting and has not yet been handed to the transport
+    transmitted: Option<u8>,
+    rx_data: u8,
+    rx_ready: bool,
+    rx_error: bool,
+    rx_poll_cycles_remaining: u32,
+}
+
+impl Uart {
+    /// Create a UART whose baud rate generator is driven by a `clock_hz` clock. `tick` should be
+    /// called with cycles of this clock.
+    #[must_use]
+    pub fn new(clock_hz: u32) -> Self {
+        Self {
+            clock_hz,
+            baud_rate: BaudRate::default(),
+            serial_in: false,
+            serial_out: false,
+            rx_interrupt_enabled: false,
+            // Transmit data registers read 0xFF at power-on
+            tx_data: 0xFF,
+            tx_cycles_remaining: 0,
+            transmitted: None,
+            rx_data: 0x00,
+            rx_ready: false,
+            rx_error: false,
+            rx_poll_cycles_remaining: 0,
+        }
+    }
+
+    /// Number of clock cycles to transmit or receive one byte at the current baud rate.
+    #[must_use]
+    pub fn cycles_per_byte(&self) -> u32 {
+        self.clock_hz * BITS_PER_BYTE / self.baud_rate.bits_per_second()
+    }
+
+    #[must_use]
+    pub fn read_tx_data(&self) -> u8 {
+        self.tx_data
+    }
+
+    pub fn write_tx_data(&mut self, value: u8) {
+        self.tx_data = value;
+
+        if !self.serial_out {
+            return;
+        }
+
+        if self.tx_cycles_remaining != 0 {
+            log::debug!("Serial transmit data written while a transmission was in progress");
+        }
+        self.tx_cycles_remaining = self.cycles_per_byte();
+    }
+
+    /// Read the received byte, which clears the ready and error bits.
+    pub fn read_rx_data(&mut self) -> u8 {
+        self.rx_ready = false;
+        self.rx_error = false;
+        self.rx_data
+    }
+
+    #[must_use]
+    pub fn read_ctrl(&self) -> u8 {
+        (self.baud_rate.to_ctrl_bits() << 6)
+            | (u8::from(self.serial_in) << 5)
+            | (u8::from(self.serial_out) << 4)
+            | (u8::from(self.rx_interrupt_enabled) << 3)
+            | (u8::from(self.rx_error) << 2)
+            | (u8::from(self.rx_ready) << 1)
+            | u8::from(self.tx_cycles_remaining != 0)
+    }
+
+    pub fn write_ctrl(&mut self, value: u8) {
+        // Bits 0-2 are read-only status bits
+        self.baud_rate = BaudRate::from_ctrl_bits(value >> 6);
+        self.serial_in = value.bit(5);
+        self.serial_out = value.bit(4);
+        self.rx_interrupt_enabled = value.bit(3);
+
+        if !self.serial_out {
+            self.tx_cycles_remaining = 0;
+        }
+    }
+
+    pub fn tick(&mut self, cycles: u32) {
+        if self.tx_cycles_remaining != 0 {
+            self.tx_cycles_remaining = self.tx_cycles_remaining.saturating_sub(cycles);
+            if self.tx_cycles_remaining == 0 {
+                self.transmitted = Some(self.tx_data);
+            }
+        }
+
+        self.rx_poll_cycles_remaining = self.rx_poll_cycles_remaining.saturating_sub(cycles);
+    }
+
+    /// Hand a transmitted byte to the transport if one finished since the last call, and poll the
+    /// transport for a received byte at most once per byte period while serial input is enabled.
+    pub fn exchange<T: LinkTransport + ?Sized>(&mut self, transport: &mut T) {
+        if let Some(byte) = self.transmitted.take() {
+            transport.send(byte);
+        }
+
+        if !self.serial_in || self.rx_poll_cycles_remaining != 0 {
+            return;
+        }
+        self.rx_poll_cycles_remaining = self.cycles_per_byte();
+
+        let Some(byte) = transport.receive() else { return };
+
+        // Overrun: the previous byte was never read
+        if self.rx_ready {
+            self.rx_error = true;
+        }
+        self.rx_data = byte;
+        self.rx_ready = true;
+    }
+
+    /// Whether receive interrupts are enabled and a byte is ready.
+    #[must_use]
+    pub fn interrupt_pending(&self) -> bool {
+        self.rx_interrupt_enabled && self.rx_ready
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::channel;
+
+    const CLOCK_HZ: u32 = 3_579_545;
+
+    #[test]
+    fn transmit_completes_at_baud_rate() {
+        let (mut link, mut peer) = channel::pair();
+        let mut uart = Uart::new(CLOCK_HZ);
+
+        // 1200 baud, serial out
+        uart.write_ctrl(0x90);
+        uart.write_tx_data(0x41);
+        assert_eq!(uart.read_ctrl(), 0x91);
+
+        let cycles_per_byte = uart.cycles_per_byte();
+        assert_eq!(cycles_per_byte, CLOCK_HZ * 10 / 1200);
+
+        uart.tick(cycles_per_byte - 1);
+        uart.exchange(&mut link);
+        assert_eq!(uart.read_ctrl() & 0x01, 0x01);
+        assert_eq!(peer.receive(), None);
+
+        uart.tick(1);
+        uart.exchange(&mut link);
+        assert_eq!(uart.read_ctrl() & 0x01, 0x00);
+        assert_eq!(peer.receive(), Some(0x41));
+    }
+
+    #[test]
+    fn disabling_serial_out_cancels_transmit() {
+        let (mut link, mut peer) = channel::pair();
+        let mut uart = Uart::new(CLOCK_HZ);
+
+        uart.write_ctrl(0x10);
+        uart.write_tx_data(0x41);
+        uart.write_ctrl(0x00);
+        assert_eq!(uart.read_ctrl(), 0x00);
+
+        uart.tick(uart.cycles_per_byte());
+        uart.exchange(&mut link);
+        assert_eq!(peer.receive(), None);
+    }
+
+    #[test]
+    fn receive_overrun() {
+        let (mut link, mut peer) = channel::pair();
+        let mut uart = Uart::new(CLOCK_HZ);
+        peer.send(0x12);
+        peer.send(0x34);
+
+        // Serial in with receive interrupts
+        uart.write_ctrl(0x28);
+        uart.exchange(&mut link);
+        assert_eq!(uart.read_ctrl(), 0x2A);
+        assert!(uart.interrupt_pending());
+
+        // Not polled again until a full byte period has elapsed
+        uart.exchange(&mut link);
+        assert_eq!(uart.read_ctrl(), 0x2A);
+
+        uart.tick(uart.cycles_per_byte());
+        uart.exchange(&mut link);
+        assert_eq!(uart.read_ctrl(), 0x2E);
+
+        assert_eq!(uart.read_rx_data(), 0x34);
+        assert_eq!(uart.read_ctrl(), 0x28);
+        assert!(!uart.interrupt_pending());
+    }
+}
@@ -0,0 +1,84 @@
+//! WebRTC data channel transport for the web frontend
+//!
+//! Browser objects cannot be moved between threads, so the transport handed to a core is an
+//! ordinary [`ChannelLink`] and [`WebRtcBridge`] moves bytes between the other end of that channel
+//! and an `RTCDataChannel`. Received messages are forwarded as soon as the browser delivers them;
+//! sent bytes are batched until the frontend calls [`WebRtcBridge::flush`], typically once per
+//! frame.
+//!
+//! Establishing the peer connection (signaling and ICE) is up to the frontend. The data channel
+//! should be reliable and ordered, which is the default for `createDataChannel`.
+
+use crate::channel::{self, ChannelLink};
+use std::sync::mpsc::{Receiver, Sender};
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{MessageEvent, RtcDataChannel, RtcDataChannelState, RtcDataChannelType};
+
+// Largest message size that every browser can send on a data channel
+const MAX_MESSAGE_LEN: usize = 16 * 1024;
+
+pub struct WebRtcBridge {
+    data_channel: RtcDataChannel,
+    outgoing: Receiver<u8>,
+    _on_message: Closure<dyn FnMut(MessageEvent)>,
+}
+
+impl WebRtcBridge {
+    /// Bridge `data_channel` to a new in-process link, returning the bridge and the end of the link
+    /// to connect to the core.
+    #[must_use]
+    pub fn new(data_channel: RtcDataChannel) -> (Self, ChannelLink) {
+        let (bridge_end, core_end) = channel::pair();
+        let ChannelLink { sender, receiver: outgoing, .. } = bridge_end;
+
+        data_channel.set_binary_type(RtcDataChannelType::Arraybuffer);
+        let on_message = Closure::<dyn FnMut(MessageEvent)>::new(move |event: MessageEvent| {
+            forward_message(&sender, &event.data());
+        });
+        data_channel.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+
+        (Self { data_channel, outgoing, _on_message: on_message }, core_end)
+    }
+
+    /// Send all bytes that the core has transmitted since the last flush. Bytes sent while the data
+    /// channel is not open are discarded, same as with no cable plugged in.
+    pub fn flush(&self) {
+        let bytes: Vec<u8> = self.outgoing.try_iter().collect();
+        if bytes.is_empty() || self.data_channel.ready_state() != RtcDataChannelState::Open {
+            return;
+        }
+
+        for chunk in bytes.chunks(MAX_MESSAGE_LEN) {
+            if let Err(err) = self.data_channel.send_with_u8_array(chunk) {
+                log::error!("Error sending on WebRTC data channel: {err:?}");
+                return;
+            }
+        }
+    }
+
+    #[must_use]
+    pub fn data_channel(&self) -> &RtcDataChannel {
+        &self.data_channel
+    }
+}
+
+fn forward_message(sender: &Sender<u8>, data: &JsValue) {
+    let Some(buffer) = data.dyn_ref::<js_sys::ArrayBuffer>() else {
+        log::warn!("Ignoring non-binary WebRTC data channel message");
+        return;
+    };
+
+    for byte in js_sys::Uint8Array::new(buffer).to_vec() {
+        // Fails only if the core end of the link was dropped
+        if sender.send(byte).is_err() {
+            return;
+        }
+    }
+}
+
+impl Drop for WebRtcBridge {
+    fn drop(&mut self) {
+        self.data_channel.set_onmessage(None);
+    }
+}
@@ -0,0 +1,60 @@
+//! Delay-based lockstep netplay
+//!
+//! Both players run the same ROM from power-on with the same RNG seed, and every frame each side
+//! sends its local controller state to the other. A frame only runs once both players' inputs for
+//! it are known, and local inputs are scheduled a few frames in the future (the input delay) so
+//! that the peer's inputs usually arrive before they are needed instead of stalling emulation.
+//!
+//! Sessions exchange length-prefixed messages over any [`LinkTransport`](jgenesis_link::LinkTransport)
+//! byte stream, e.g. a WebRTC data channel in the web frontend or TCP in native builds. The session
+//! is agnostic to the console: the input type only needs to be bincode-encodable.
+//!
+//! The host can also stream the session to any number of spectators, see [`SpectatorFeed`] and
+//! [`SpectatorSession`].
+
+mod protocol;
+mod session;
+mod spectator;
+
+pub use protocol::PROTOCOL_VERSION;
+pub use session::{NetplayRole, NetplaySession, SessionInfo};
+pub use spectator::{SpectatorFeed, SpectatorFrame, SpectatorSession, STATE_SYNC_INTERVAL};
+
+use crc::Crc;
+use thiserror::Error;
+
+const CRC: Crc<u32> = Crc::<u32>::new(&crc::CRC_32_ISO_HDLC);
+
+/// Input delay used if the host does not choose one; enough to hide about 50ms of round trip
+/// latency at 60fps
+pub const DEFAULT_INPUT_DELAY: u32 = 3;
+
+#[derive(Debug, Error)]
+pub enum NetplayError {
+    #[error("Error encoding netplay message: {0}")]
+    Encode(#[from] bincode::error::EncodeError),
+    #[error("Error decoding netplay message: {0}")]
+    Decode(#[from] bincode::error::DecodeError),
+    #[error("Netplay message of {len} bytes exceeds maximum length")]
+    MessageTooLarge { len: usize },
+    #[error("Peer uses netplay protocol version {peer}, expected {PROTOCOL_VERSION}")]
+    ProtocolVersion { peer: u32 },
+    #[error("Peer is running a different ROM (CRC32 {peer:08X}, expected {local:08X})")]
+    RomMismatch { local: u32, peer: u32 },
+    #[error("Connection is for a {offered}, expected a {expected}")]
+    ConnectionKind { offered: &'static str, expected: &'static str },
+    #[error("Peer rejected the session: {reason}")]
+    Rejected { reason: String },
+    #[error("Unexpected netplay message from peer: {0}")]
+    UnexpectedMessage(&'static str),
+    #[error("Received input for frame {received} from peer, expected frame {expected}")]
+    InputOutOfOrder { expected: u64, received: u64 },
+}
+
+pub type NetplayResult<T> = Result<T, NetplayError>;
+
+/// Checksum used to verify that both players loaded the same ROM.
+#[must_use]
+pub fn rom_checksum(rom: &[u8]) -> u32 {
+    CRC.checksum(rom)
+}
@@ -0,0 +1,176 @@
+//! Netplay wire protocol
+//!
+//! Each message is bincode-encoded and prefixed with its length as a little-endian u32.
+
+use crate::session::SessionInfo;
+use crate::{NetplayError, NetplayResult};
+use bincode::{Decode, Encode};
+use jgenesis_link::LinkTransport;
+
+/// Incremented whenever the message format changes; peers with different versions refuse to connect
+pub const PROTOCOL_VERSION: u32 = 2;
+
+// Larger than any message the protocol sends; guards against allocating a huge buffer if the
+// stream is corrupted or the peer is not a netplay client
+const MAX_MESSAGE_LEN: usize = 16 * 1024 * 1024;
+
+const LEN_PREFIX_LEN: usize = 4;
+
+macro_rules! bincode_config {
+    () => {
+        bincode::config::standard()
+    };
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub(crate) enum Message<I> {
+    /// Sent by the host as soon as the connection opens
+    Hello { protocol_version: u32, spectator: bool, info: SessionInfo },
+    /// Sent by the guest or a spectator to accept the host's session
+    Ready,
+    /// Sent by either side before giving up on the session
+    Reject { reason: String },
+    /// Controller state of the sender's player for one frame
+    Input { frame: u64, input: I },
+    /// Sent by the host to spectators: serialized emulator state at the start of a frame
+    StateSync { frame: u64, state: Vec<u8> },
+    /// Sent by the host to spectators: both players' inputs for one frame
+    Frame { frame: u64, p1: I, p2: I },
+}
+
+impl<I> Message<I> {
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Self::Hello { .. } => "Hello",
+            Self::Ready => "Ready",
+            Self::Reject { .. } => "Reject",
+            Self::Input { .. } => "Input",
+            Self::StateSync { .. } => "StateSync",
+            Self::Frame { .. } => "Frame",
+        }
+    }
+}
+
+pub(crate) fn write_message<I, T>(transport: &mut T, message: &Message<I>) -> NetplayResult<()>
+where
+    I: Encode,
+    T: LinkTransport + ?Sized,
+{
+    let payload = bincode::encode_to_vec(message, bincode_config!())?;
+    let len = payload.len() as u32;
+    for byte in len.to_le_bytes().into_iter().chain(payload) {
+        transport.send(byte);
+    }
+
+    Ok(())
+}
+
+/// Reassembles messages from a byte stream.
+#[derive(Debug, Default)]
+pub(crate) struct MessageReader {
+    buffer: Vec<u8>,
+}
+
+impl MessageReader {
+    /// Read all bytes currently available from the transport and decode the next complete message,
+    /// if there is one.
+    pub(crate) fn read<I, T>(&mut self, transport: &mut T) -> NetplayResult<Option<Message<I>>>
+    where
+        I: Decode,
+        T: LinkTransport + ?Sized,
+    {
+        while let Some(byte) = transport.receive() {
+            self.buffer.push(byte);
+        }
+
+        if self.buffer.len() < LEN_PREFIX_LEN {
+            return Ok(None);
+        }
+
+        let len = u32::from_le_bytes(self.buffer[..LEN_PREFIX_LEN].try_into().unwrap()) as usize;
+        if len > MAX_MESSAGE_LEN {
+            return Err(NetplayError::MessageTooLarge { len });
+        }
+
+        let end = LEN_PREFIX_LEN + len;
+        if self.buffer.len() < end {
+            return Ok(None);
+        }
+
+        let (message, _) =
+            bincode::decode_from_slice(&self.buffer[LEN_PREFIX_LEN..end], bincode_config!())?;
+        self.buffer.drain(..end);
+
+        Ok(Some(message))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+
+    // Receives whatever was sent to it
+    #[derive(Default)]
+    struct Loopback(VecDeque<u8>);
+
+    impl LinkTransport for Loopback {
+        fn send(&mut self, byte: u8) {
+            self.0.push_back(byte);
+        }
+
+        fn receive(&mut self) -> Option<u8> {
+            self.0.pop_front()
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut transport = Loopback::default();
+        let mut reader = MessageReader::default();
+
+        let messages = [
+            Message::Ready,
+            Message::Input { frame: 5, input: 0x1234_u16 },
+            Message::Reject { reason: "test".into() },
+            Message::StateSync { frame: 6, state: vec![1, 2, 3] },
+            Message::Frame { frame: 6, p1: 0x5678, p2: 0x9ABC },
+        ];
+        for message in &messages {
+            write_message(&mut transport, message).unwrap();
+        }
+
+        for message in messages {
+            assert_eq!(reader.read::<u16, _>(&mut transport).unwrap(), Some(message));
+        }
+        assert_eq!(reader.read::<u16, _>(&mut transport).unwrap(), None);
+    }
+
+    #[test]
+    fn partial_message() {
+        let mut encoded = Loopback::default();
+        write_message(&mut encoded, &Message::Input { frame: 1, input: 7_u8 }).unwrap();
+        let last_byte = encoded.0.pop_back().unwrap();
+
+        let mut reader = MessageReader::default();
+        assert_eq!(reader.read::<u8, _>(&mut encoded).unwrap(), None);
+
+        encoded.send(last_byte);
+        assert_eq!(
+            reader.read::<u8, _>(&mut encoded).unwrap(),
+            Some(Message::Input { frame: 1, input: 7 })
+        );
+    }
+
+    #[test]
+    fn oversized_length_prefix() {
+        let mut transport = Loopback::default();
+        transport.0.extend(u32::MAX.to_le_bytes());
+
+        let mut reader = MessageReader::default();
+        assert!(matches!(
+            reader.read::<u8, _>(&mut transport),
+            Err(NetplayError::MessageTooLarge { .. })
+        ));
+    }
+}
@@ -0,0 +1,341 @@
+//! Lockstep session state machine
+
+use crate::protocol::{self, Message, MessageReader};
+use crate::{NetplayError, NetplayResult, PROTOCOL_VERSION};
+use bincode::{Decode, Encode};
+use jgenesis_link::LinkTransport;
+use std::collections::VecDeque;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NetplayRole {
+    /// Chooses the session settings and controls player 1
+    Host,
+    /// Joins the host's session and controls player 2
+    Guest,
+}
+
+/// Session settings chosen by the host and sent to the guest during the handshake.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct SessionInfo {
+    /// CRC32 of the host's ROM, see [`rom_checksum`](crate::rom_checksum)
+    pub rom_crc32: u32,
+    /// Both sides must create their emulator with this RNG seed for emulation to stay in sync
+    pub rng_seed: u64,
+    /// Number of frames between a local input being read and that input taking effect
+    pub input_delay: u32,
+}
+
+/// One side of a two-player lockstep session.
+///
+/// The frontend calls [`advance`](Self::advance) once per frame with its local input and runs a
+/// frame only when it returns both players' inputs.
+#[derive(Debug)]
+pub struct NetplaySession<I, T> {
+    role: NetplayRole,
+    transport: T,
+    reader: MessageReader,
+    local_rom_crc32: u32,
+    info: Option<SessionInfo>,
+    running: bool,
+    frame: u64,
+    local_inputs: VecDeque<I>,
+    remote_inputs: VecDeque<I>,
+}
+
+impl<I, T> NetplaySession<I, T>
+where
+    I: Clone + Default + Encode + Decode,
+    T: LinkTransport,
+{
+    /// Host a session over `transport`, which should already be connected to the guest.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the handshake message cannot be encoded.
+    pub fn host(mut transport: T, info: SessionInfo) -> NetplayResult<Self> {
+        protocol::write_message::<I, _>(
+            &mut transport,
+            &Message::Hello {
+                protocol_version: PROTOCOL_VERSION,
+                spectator: false,
+                info: info.clone(),
+            },
+        )?;
+
+        Ok(Self::new(NetplayRole::Host, transport, info.rom_crc32, Some(info)))
+    }
+
+    /// Join the session hosted on the other end of `transport`. The session does not start until
+    /// the host's settings arrive and `rom_crc32` matches the host's ROM.
+    #[must_use]
+    pub fn join(transport: T, rom_crc32: u32) -> Self {
+        Self::new(NetplayRole::Guest, transport, rom_crc32, None)
+    }
+
+    fn new(role: NetplayRole, transport: T, rom_crc32: u32, info: Option<SessionInfo>) -> Self {
+        Self {
+            role,
+            transport,
+            reader: MessageReader::default(),
+            local_rom_crc32: rom_crc32,
+            info,
+            running: false,
+            frame: 0,
+            local_inputs: VecDeque::new(),
+            remote_inputs: VecDeque::new(),
+        }
+    }
+
+    /// Process messages from the peer, schedule `local_input`, and return the inputs for the next
+    /// frame as `[player 1, player 2]` if both players' inputs are known.
+    ///
+    /// Returns `None` while the handshake is in progress or while waiting on the peer. The local
+    /// input is dropped if the input queue is already full, so the caller should keep polling
+    /// every frame with its current input rather than buffering inputs itself.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the peer rejects the session, sends an invalid message, or is running a
+    /// different ROM or protocol version. The session cannot continue after an error.
+    pub fn advance(&mut self, local_input: I) -> NetplayResult<Option<[I; 2]>> {
+        self.poll()?;
+
+        if !self.running {
+            return Ok(None);
+        }
+
+        if self.local_inputs.len() <= self.input_delay() as usize {
+            let frame = self.frame + self.local_inputs.len() as u64;
+            protocol::write_message(
+                &mut self.transport,
+                &Message::Input { frame, input: local_input.clone() },
+            )?;
+            self.local_inputs.push_back(local_input);
+        }
+
+        if self.remote_inputs.is_empty() {
+            return Ok(None);
+        }
+
+        // A local input was just queued if there was room, so only the remote queue can be empty
+        let (Some(local), Some(remote)) =
+            (self.local_inputs.pop_front(), self.remote_inputs.pop_front())
+        else {
+            return Ok(None);
+        };
+        self.frame += 1;
+
+        Ok(Some(match self.role {
+            NetplayRole::Host => [local, remote],
+            NetplayRole::Guest => [remote, local],
+        }))
+    }
+
+    fn poll(&mut self) -> NetplayResult<()> {
+        while let Some(message) = self.reader.read(&mut self.transport)? {
+            match message {
+                Message::Hello { protocol_version, spectator, info } => {
+                    if self.role != NetplayRole::Guest || self.info.is_some() {
+                        return Err(NetplayError::UnexpectedMessage("Hello"));
+                    }
+
+                    answer_hello::<I, _>(
+                        &mut self.transport,
+                        protocol_version,
+                        spectator,
+                        &info,
+                        self.local_rom_crc32,
+                        false,
+                    )?;
+                    self.info = Some(info);
+                    self.start();
+                }
+                Message::Ready => {
+                    if self.role != NetplayRole::Host || self.running {
+                        return Err(NetplayError::UnexpectedMessage("Ready"));
+                    }
+                    self.start();
+                }
+                Message::Reject { reason } => return Err(NetplayError::Rejected { reason }),
+                Message::Input { frame, input } => {
+                    if !self.running {
+                        return Err(NetplayError::UnexpectedMessage("Input"));
+                    }
+
+                    let expected = self.frame + self.remote_inputs.len() as u64;
+                    if frame != expected {
+                        return Err(NetplayError::InputOutOfOrder { expected, received: frame });
+                    }
+                    self.remote_inputs.push_back(input);
+                }
+                message @ (Message::StateSync { .. } | Message::Frame { .. }) => {
+                    return Err(NetplayError::UnexpectedMessage(message.name()));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn start(&mut self) {
+        // Both sides fill their queues with neutral inputs for the first frames so that neither
+        // has to wait on the other before the first delayed inputs arrive
+        let delay = self.input_delay() as usize;
+        self.local_inputs = std::iter::repeat_with(I::default).take(delay).collect();
+        self.remote_inputs = std::iter::repeat_with(I::default).take(delay).collect();
+        self.running = true;
+    }
+
+    fn input_delay(&self) -> u32 {
+        self.info.as_ref().map_or(0, |info| info.input_delay)
+    }
+}
+
+impl<I, T> NetplaySession<I, T> {
+    #[must_use]
+    pub fn role(&self) -> NetplayRole {
+        self.role
+    }
+
+    /// Session settings, or `None` if this is a guest that has not yet received them from the host.
+    #[must_use]
+    pub fn info(&self) -> Option<&SessionInfo> {
+        self.info.as_ref()
+    }
+
+    /// Whether the handshake has completed and inputs are being exchanged.
+    #[must_use]
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    /// Number of frames that have run since the session started.
+    #[must_use]
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    #[must_use]
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    pub fn transport_mut(&mut self) -> &mut T {
+        &mut self.transport
+    }
+}
+
+/// Check that the session described by the host's `Hello` is compatible, then reply with `Ready`
+/// to accept it or with `Reject` to refuse it.
+pub(crate) fn answer_hello<I, T>(
+    transport: &mut T,
+    protocol_version: u32,
+    offered_spectator: bool,
+    info: &SessionInfo,
+    local_rom_crc32: u32,
+    spectator: bool,
+) -> NetplayResult<()>
+where
+    I: Encode,
+    T: LinkTransport + ?Sized,
+{
+    let error = if protocol_version != PROTOCOL_VERSION {
+        Some(NetplayError::ProtocolVersion { peer: protocol_version })
+    } else if offered_spectator != spectator {
+        Some(NetplayError::ConnectionKind {
+            offered: connection_kind(offered_spectator),
+            expected: connection_kind(spectator),
+        })
+    } else if info.rom_crc32 != local_rom_crc32 {
+        Some(NetplayError::RomMismatch { local: local_rom_crc32, peer: info.rom_crc32 })
+    } else {
+        None
+    };
+
+    if let Some(error) = error {
+        protocol::write_message::<I, _>(transport, &Message::Reject { reason: error.to_string() })?;
+        return Err(error);
+    }
+
+    protocol::write_message::<I, _>(transport, &Message::Ready)
+}
+
+fn connection_kind(spectator: bool) -> &'static str {
+    if spectator { "spectator" } else { "player" }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jgenesis_link::channel::{self, ChannelLink};
+
+    type Session = NetplaySession<u8, ChannelLink>;
+
+    fn info(input_delay: u32) -> SessionInfo {
+        SessionInfo { rom_crc32: 0x1234_5678, rng_seed: 42, input_delay }
+    }
+
+    fn connect(input_delay: u32) -> (Session, Session) {
+        let (host_link, guest_link) = channel::pair();
+        let host = Session::host(host_link, info(input_delay)).unwrap();
+        let guest = Session::join(guest_link, 0x1234_5678);
+        (host, guest)
+    }
+
+    #[test]
+    fn handshake() {
+        let (mut host, mut guest) = connect(2);
+        assert!(!host.is_running());
+        assert_eq!(guest.info(), None);
+
+        guest.advance(0).unwrap();
+        assert!(guest.is_running());
+        assert_eq!(guest.info(), Some(&info(2)));
+
+        host.advance(0).unwrap();
+        assert!(host.is_running());
+    }
+
+    #[test]
+    fn rom_mismatch() {
+        let (host_link, guest_link) = channel::pair();
+        let mut host = Session::host(host_link, info(0)).unwrap();
+        let mut guest = Session::join(guest_link, 0xDEAD_BEEF);
+
+        assert!(matches!(
+            guest.advance(0),
+            Err(NetplayError::RomMismatch { local: 0xDEAD_BEEF, peer: 0x1234_5678 })
+        ));
+        assert!(matches!(host.advance(0), Err(NetplayError::Rejected { .. })));
+    }
+
+    #[test]
+    fn lockstep_with_input_delay() {
+        let (mut host, mut guest) = connect(2);
+
+        // The first two frames use the neutral prefilled inputs
+        assert_eq!(guest.advance(10).unwrap(), Some([0, 0]));
+        assert_eq!(host.advance(1).unwrap(), Some([0, 0]));
+        assert_eq!(guest.advance(20).unwrap(), Some([0, 0]));
+        assert_eq!(host.advance(2).unwrap(), Some([0, 0]));
+
+        assert_eq!(guest.advance(30).unwrap(), Some([1, 10]));
+        assert_eq!(host.advance(3).unwrap(), Some([1, 10]));
+    }
+
+    #[test]
+    fn stalls_until_peer_input_arrives() {
+        let (mut host, mut guest) = connect(0);
+
+        assert_eq!(guest.advance(5).unwrap(), None);
+        assert_eq!(host.advance(1).unwrap(), Some([1, 5]));
+        assert_eq!(host.advance(2).unwrap(), None);
+        // The input for the stalled frame was already sent, so this one is dropped
+        assert_eq!(host.advance(3).unwrap(), None);
+
+        assert_eq!(guest.advance(6).unwrap(), Some([1, 5]));
+        assert_eq!(guest.advance(7).unwrap(), Some([2, 7]));
+        assert_eq!(host.advance(4).unwrap(), Some([2, 7]));
+        assert_eq!(host.frame(), 2);
+    }
+}
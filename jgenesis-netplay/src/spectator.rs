@@ -0,0 +1,376 @@
+//! Spectating a netplay session
+//!
+//! Spectators connect to the host, never send inputs, and receive both players' inputs for every
+//! frame once the host has run it. Since a spectator can join at any point, the stream starts with
+//! a serialized emulator state, and the host sends a new state every [`STATE_SYNC_INTERVAL`] frames
+//! so that a spectator that falls behind can skip ahead instead of lagging further and further.
+//!
+//! The state is opaque to this crate; the frontend serializes and loads it.
+
+use crate::protocol::{self, Message, MessageReader};
+use crate::session::{self, SessionInfo};
+use crate::{NetplayError, NetplayResult, PROTOCOL_VERSION};
+use bincode::{Decode, Encode};
+use jgenesis_link::LinkTransport;
+use std::collections::VecDeque;
+use std::marker::PhantomData;
+
+/// Number of frames between state syncs sent to each spectator, about 10 seconds at 60fps
+pub const STATE_SYNC_INTERVAL: u64 = 600;
+
+// A spectator with more frames than this buffered when a state sync arrives discards them and
+// continues from the new state
+const MAX_SPECTATOR_LAG: usize = 60;
+
+/// Host side of the connection to one spectator.
+#[derive(Debug)]
+pub struct SpectatorFeed<I, T> {
+    transport: T,
+    reader: MessageReader,
+    ready: bool,
+    frame: u64,
+    last_sync_frame: Option<u64>,
+    _input: PhantomData<I>,
+}
+
+impl<I, T> SpectatorFeed<I, T>
+where
+    I: Clone + Encode + Decode,
+    T: LinkTransport,
+{
+    /// Offer the session to a spectator on the other end of `transport`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the handshake message cannot be encoded.
+    pub fn new(mut transport: T, info: &SessionInfo) -> NetplayResult<Self> {
+        protocol::write_message::<I, _>(
+            &mut transport,
+            &Message::Hello {
+                protocol_version: PROTOCOL_VERSION,
+                spectator: true,
+                info: info.clone(),
+            },
+        )?;
+
+        Ok(Self {
+            transport,
+            reader: MessageReader::default(),
+            ready: false,
+            frame: 0,
+            last_sync_frame: None,
+            _input: PhantomData,
+        })
+    }
+
+    /// Process messages from the spectator. Should be called every frame.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the spectator rejects the session or sends an invalid message.
+    pub fn poll(&mut self) -> NetplayResult<()> {
+        while let Some(message) = self.reader.read::<I, _>(&mut self.transport)? {
+            match message {
+                Message::Ready if !self.ready => self.ready = true,
+                Message::Reject { reason } => return Err(NetplayError::Rejected { reason }),
+                _ => return Err(NetplayError::UnexpectedMessage(message.name())),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Whether the next [`send_frame`](Self::send_frame) call should include the emulator state.
+    #[must_use]
+    pub fn needs_state_sync(&self) -> bool {
+        self.ready
+            && self.last_sync_frame.is_none_or(|last| self.frame >= last + STATE_SYNC_INTERVAL)
+    }
+
+    /// Send both players' inputs for the frame that is about to run. `state` is the emulator state
+    /// before the frame runs, and only needs to be provided when
+    /// [`needs_state_sync`](Self::needs_state_sync) returns true.
+    ///
+    /// Nothing is sent until the spectator has accepted the session and a first state has been
+    /// provided.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a message cannot be encoded.
+    pub fn send_frame(&mut self, inputs: &[I; 2], state: Option<&[u8]>) -> NetplayResult<()> {
+        if let Some(state) = state.filter(|_| self.needs_state_sync()) {
+            protocol::write_message::<I, _>(
+                &mut self.transport,
+                &Message::StateSync { frame: self.frame, state: state.to_vec() },
+            )?;
+            self.last_sync_frame = Some(self.frame);
+        }
+
+        if !self.ready || self.last_sync_frame.is_none() {
+            return Ok(());
+        }
+
+        let [p1, p2] = inputs.clone();
+        protocol::write_message(
+            &mut self.transport,
+            &Message::Frame { frame: self.frame, p1, p2 },
+        )?;
+        self.frame += 1;
+
+        Ok(())
+    }
+
+    /// Whether the spectator has accepted the session.
+    #[must_use]
+    pub fn is_ready(&self) -> bool {
+        self.ready
+    }
+
+    #[must_use]
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    pub fn transport_mut(&mut self) -> &mut T {
+        &mut self.transport
+    }
+}
+
+/// One frame of a spectated session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpectatorFrame<I> {
+    /// Emulator state to load before running this frame, if the host sent one
+    pub state: Option<Vec<u8>>,
+    /// `[player 1, player 2]` inputs for this frame
+    pub inputs: [I; 2],
+}
+
+/// Spectator side of a session.
+#[derive(Debug)]
+pub struct SpectatorSession<I, T> {
+    transport: T,
+    reader: MessageReader,
+    local_rom_crc32: u32,
+    info: Option<SessionInfo>,
+    next_frame: Option<u64>,
+    pending_state: Option<Vec<u8>>,
+    frames: VecDeque<SpectatorFrame<I>>,
+}
+
+impl<I, T> SpectatorSession<I, T>
+where
+    I: Clone + Encode + Decode,
+    T: LinkTransport,
+{
+    /// Spectate the session hosted on the other end of `transport`, which must be running the ROM
+    /// with checksum `rom_crc32`.
+    #[must_use]
+    pub fn join(transport: T, rom_crc32: u32) -> Self {
+        Self {
+            transport,
+            reader: MessageReader::default(),
+            local_rom_crc32: rom_crc32,
+            info: None,
+            next_frame: None,
+            pending_state: None,
+            frames: VecDeque::new(),
+        }
+    }
+
+    /// Process messages from the host and return the next frame to run, if one has arrived. The
+    /// first frame returned always includes a state.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the host sends an invalid message or is running a different ROM or
+    /// protocol version.
+    pub fn advance(&mut self) -> NetplayResult<Option<SpectatorFrame<I>>> {
+        self.poll()?;
+
+        Ok(self.frames.pop_front())
+    }
+
+    fn poll(&mut self) -> NetplayResult<()> {
+        while let Some(message) = self.reader.read(&mut self.transport)? {
+            match message {
+                Message::Hello { protocol_version, spectator, info } if self.info.is_none() => {
+                    session::answer_hello::<I, _>(
+                        &mut self.transport,
+                        protocol_version,
+                        spectator,
+                        &info,
+                        self.local_rom_crc32,
+                        true,
+                    )?;
+                    self.info = Some(info);
+                }
+                Message::Reject { reason } => return Err(NetplayError::Rejected { reason }),
+                Message::StateSync { frame, state } if self.info.is_some() => {
+                    if let Some(expected) = self.next_frame {
+                        if frame != expected {
+                            return Err(NetplayError::InputOutOfOrder {
+                                expected,
+                                received: frame,
+                            });
+                        }
+                    }
+
+                    if self.frames.len() > MAX_SPECTATOR_LAG {
+                        log::info!(
+                            "Spectator is {} frames behind, skipping ahead to state sync",
+                            self.frames.len()
+                        );
+                        self.frames.clear();
+                    }
+                    self.pending_state = Some(state);
+                    self.next_frame = Some(frame);
+                }
+                Message::Frame { frame, p1, p2 } => {
+                    let Some(expected) = self.next_frame else {
+                        return Err(NetplayError::UnexpectedMessage("Frame"));
+                    };
+                    if frame != expected {
+                        return Err(NetplayError::InputOutOfOrder { expected, received: frame });
+                    }
+
+                    self.frames.push_back(SpectatorFrame {
+                        state: self.pending_state.take(),
+                        inputs: [p1, p2],
+                    });
+                    self.next_frame = Some(frame + 1);
+                }
+                _ => return Err(NetplayError::UnexpectedMessage(message.name())),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<I, T> SpectatorSession<I, T> {
+    /// Session settings, or `None` if they have not yet been received from the host.
+    #[must_use]
+    pub fn info(&self) -> Option<&SessionInfo> {
+        self.info.as_ref()
+    }
+
+    /// Whether the first state has been received.
+    #[must_use]
+    pub fn is_running(&self) -> bool {
+        self.next_frame.is_some()
+    }
+
+    /// Number of frames that have been received but not yet returned by
+    /// [`advance`](Self::advance).
+    #[must_use]
+    pub fn buffered_frames(&self) -> usize {
+        self.frames.len()
+    }
+
+    #[must_use]
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    pub fn transport_mut(&mut self) -> &mut T {
+        &mut self.transport
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NetplaySession;
+    use jgenesis_link::channel::{self, ChannelLink};
+
+    const ROM_CRC32: u32 = 0x1234_5678;
+
+    fn info() -> SessionInfo {
+        SessionInfo { rom_crc32: ROM_CRC32, rng_seed: 42, input_delay: 2 }
+    }
+
+    fn connect() -> (SpectatorFeed<u8, ChannelLink>, SpectatorSession<u8, ChannelLink>) {
+        let (host_link, spectator_link) = channel::pair();
+        let mut feed = SpectatorFeed::new(host_link, &info()).unwrap();
+        let mut spectator = SpectatorSession::join(spectator_link, ROM_CRC32);
+
+        assert_eq!(spectator.advance().unwrap(), None);
+        assert_eq!(spectator.info(), Some(&info()));
+        feed.poll().unwrap();
+        assert!(feed.is_ready());
+
+        (feed, spectator)
+    }
+
+    #[test]
+    fn stream_starts_with_state() {
+        let (mut feed, mut spectator) = connect();
+
+        // Frames are not sent until a state has been provided
+        assert!(feed.needs_state_sync());
+        feed.send_frame(&[1, 2], None).unwrap();
+        assert_eq!(spectator.advance().unwrap(), None);
+        assert!(!spectator.is_running());
+
+        feed.send_frame(&[3, 4], Some(&[0xAA])).unwrap();
+        assert!(!feed.needs_state_sync());
+        feed.send_frame(&[5, 6], Some(&[0xBB])).unwrap();
+
+        assert_eq!(
+            spectator.advance().unwrap(),
+            Some(SpectatorFrame { state: Some(vec![0xAA]), inputs: [3, 4] })
+        );
+        assert_eq!(
+            spectator.advance().unwrap(),
+            Some(SpectatorFrame { state: None, inputs: [5, 6] })
+        );
+        assert_eq!(spectator.advance().unwrap(), None);
+    }
+
+    #[test]
+    fn periodic_state_sync() {
+        let (mut feed, mut spectator) = connect();
+
+        feed.send_frame(&[0, 0], Some(&[0])).unwrap();
+        spectator.advance().unwrap();
+        for frame in 1..STATE_SYNC_INTERVAL {
+            assert!(!feed.needs_state_sync());
+            feed.send_frame(&[0, 0], None).unwrap();
+            assert_eq!(spectator.advance().unwrap().unwrap().state, None, "frame {frame}");
+        }
+
+        assert!(feed.needs_state_sync());
+        feed.send_frame(&[0, 0], Some(&[1])).unwrap();
+        assert_eq!(spectator.advance().unwrap().unwrap().state, Some(vec![1]));
+    }
+
+    #[test]
+    fn lagging_spectator_skips_to_state_sync() {
+        let (mut feed, mut spectator) = connect();
+
+        feed.send_frame(&[0, 0], Some(&[0])).unwrap();
+        for _ in 1..STATE_SYNC_INTERVAL {
+            feed.send_frame(&[1, 1], None).unwrap();
+        }
+        feed.send_frame(&[2, 2], Some(&[1])).unwrap();
+
+        assert_eq!(
+            spectator.advance().unwrap(),
+            Some(SpectatorFrame { state: Some(vec![1]), inputs: [2, 2] })
+        );
+        assert_eq!(spectator.buffered_frames(), 0);
+    }
+
+    #[test]
+    fn player_connection_rejected_by_spectator() {
+        let (host_link, spectator_link) = channel::pair();
+        let mut host = NetplaySession::<u8, _>::host(host_link, info()).unwrap();
+        let mut spectator = SpectatorSession::<u8, _>::join(spectator_link, ROM_CRC32);
+
+        assert!(matches!(
+            spectator.advance(),
+            Err(NetplayError::ConnectionKind { offered: "player", expected: "spectator" })
+        ));
+        assert!(matches!(host.advance(0), Err(NetplayError::Rejected { .. })));
+    }
+}
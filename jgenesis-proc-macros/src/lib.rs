@@ -2,6 +2,7 @@ mod config;
 mod encode;
 mod enums;
 mod partialclone;
+mod savestate;
 
 use proc_macro::TokenStream;
 
@@ -236,6 +237,63 @@ pub fn partial_clone(input: TokenStream) -> TokenStream {
     partialclone::partial_clone(input)
 }
 
+/// Implement the `bincode::Encode`, `bincode::Decode`, and `bincode::BorrowDecode` traits for a
+/// struct that is part of an emulation core's save state.
+///
+/// This macro should be imported through `jgenesis_state` instead of directly from this crate, since
+/// the generated code depends on helpers and the `bincode` re-export in that crate.
+///
+/// Fields are encoded in declaration order, identically to the `bincode` derives. The following
+/// field attributes are supported:
+///
+/// * `#[save_state(skip)]`: The field is not encoded, and decoding sets it to its default value.
+///   Intended for ROM contents and caches that are restored after the state is loaded.
+/// * `#[save_state(big_array)]`: The field is a `Box<[T; N]>` that is decoded directly onto the
+///   heap instead of through a temporary array on the stack. The encoding is unchanged.
+/// * `#[save_state(since = N)]`: The field was added in version N of the core's state format. When
+///   loading a state from an older version through `jgenesis_state::decode_state`, the field is not
+///   read and is set to its default value instead.
+/// * `#[save_state(default = "path::to::fn")]`: Use the given function instead of the `Default`
+///   trait when a `skip` or `since` field is not decoded.
+///
+/// If the struct has any generic type parameters, the traits will only be implemented where all of
+/// the generic types implement the corresponding `bincode` trait.
+///
+/// Example:
+/// ```
+/// use jgenesis_state::{SaveState, StateFormat};
+///
+/// #[derive(Debug, PartialEq, SaveState)]
+/// struct Example {
+///     #[save_state(skip)]
+///     rom: Vec<u8>,
+///     #[save_state(big_array)]
+///     ram: Box<[u8; 8192]>,
+///     #[save_state(since = 2, default = "default_volume")]
+///     volume: u8,
+/// }
+///
+/// fn default_volume() -> u8 {
+///     100
+/// }
+///
+/// let example = Example { rom: vec![1, 2, 3], ram: Box::new([5; 8192]), volume: 50 };
+/// let format = StateFormat { core: "example", version: 2 };
+/// let state = jgenesis_state::encode_state(&example, format).unwrap();
+///
+/// let decoded: Example = jgenesis_state::decode_state(&state, format).unwrap();
+/// assert_eq!(decoded, Example { rom: vec![], ram: Box::new([5; 8192]), volume: 50 });
+/// ```
+///
+/// # Panics
+///
+/// This macro only supports structs with named fields and it will panic if applied to any other
+/// data type.
+#[proc_macro_derive(SaveState, attributes(save_state))]
+pub fn save_state(input: TokenStream) -> TokenStream {
+    savestate::save_state(input)
+}
+
 /// This macro is fairly specific to the NES Mapper enum, although it could theoretically
 /// be more generalized if needed.
 ///
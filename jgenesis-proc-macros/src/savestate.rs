@@ -0,0 +1,159 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_quote, Data, DeriveInput, Expr, ExprPath, Field, Fields, LitInt, LitStr};
+
+pub fn save_state(input: TokenStream) -> TokenStream {
+    let input: DeriveInput = syn::parse(input).expect("Unable to parse input");
+
+    let type_ident = &input.ident;
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => panic!("SaveState only supports structs with named fields; {type_ident} does not have named fields"),
+        },
+        _ => panic!("SaveState only supports structs; {type_ident} is not a struct"),
+    };
+
+    let field_attrs: Vec<_> =
+        fields.iter().map(|field| (field, parse_save_state_attr(field))).collect();
+
+    let encode_fields: Vec<_> = field_attrs
+        .iter()
+        .filter(|(_, attr)| !attr.skip)
+        .map(|(field, _)| {
+            let field_ident = &field.ident;
+            quote! {
+                ::jgenesis_state::bincode::Encode::encode(&self.#field_ident, encoder)?;
+            }
+        })
+        .collect();
+
+    let decode_fields: Vec<_> = field_attrs
+        .iter()
+        .map(|(field, attr)| {
+            let field_ident = &field.ident;
+            let default = match &attr.default {
+                Some(path) => quote! { #path() },
+                None => quote! { ::std::default::Default::default() },
+            };
+
+            if attr.skip {
+                return quote! { #field_ident: #default };
+            }
+
+            let decode = if attr.big_array {
+                quote! { ::jgenesis_state::big_array::decode(decoder)? }
+            } else {
+                quote! { ::jgenesis_state::bincode::Decode::decode(decoder)? }
+            };
+
+            match &attr.since {
+                Some(version) => quote! {
+                    #field_ident: if ::jgenesis_state::field_present(#version) {
+                        #decode
+                    } else {
+                        #default
+                    }
+                },
+                None => quote! { #field_ident: #decode },
+            }
+        })
+        .collect();
+
+    let mut encode_generics = input.generics.clone();
+    for type_param in encode_generics.type_params_mut() {
+        type_param.bounds.push(parse_quote!(::jgenesis_state::bincode::Encode));
+    }
+    let (encode_impl_generics, type_generics, where_clause) = encode_generics.split_for_impl();
+
+    let mut decode_generics = input.generics.clone();
+    for type_param in decode_generics.type_params_mut() {
+        type_param.bounds.push(parse_quote!(::jgenesis_state::bincode::Decode));
+    }
+    let (decode_impl_generics, _, _) = decode_generics.split_for_impl();
+
+    let mut borrow_decode_generics = decode_generics.clone();
+    borrow_decode_generics.params.insert(0, parse_quote!('__de));
+    let (borrow_decode_impl_generics, _, _) = borrow_decode_generics.split_for_impl();
+
+    let gen = quote! {
+        impl #encode_impl_generics ::jgenesis_state::bincode::Encode for #type_ident #type_generics #where_clause {
+            fn encode<__E: ::jgenesis_state::bincode::enc::Encoder>(
+                &self,
+                encoder: &mut __E,
+            ) -> ::std::result::Result<(), ::jgenesis_state::bincode::error::EncodeError> {
+                #(#encode_fields)*
+                ::std::result::Result::Ok(())
+            }
+        }
+
+        impl #decode_impl_generics ::jgenesis_state::bincode::Decode for #type_ident #type_generics #where_clause {
+            fn decode<__D: ::jgenesis_state::bincode::de::Decoder>(
+                decoder: &mut __D,
+            ) -> ::std::result::Result<Self, ::jgenesis_state::bincode::error::DecodeError> {
+                ::std::result::Result::Ok(Self {
+                    #(#decode_fields,)*
+                })
+            }
+        }
+
+        impl #borrow_decode_impl_generics ::jgenesis_state::bincode::BorrowDecode<'__de> for #type_ident #type_generics #where_clause {
+            fn borrow_decode<__D: ::jgenesis_state::bincode::de::BorrowDecoder<'__de>>(
+                decoder: &mut __D,
+            ) -> ::std::result::Result<Self, ::jgenesis_state::bincode::error::DecodeError> {
+                <Self as ::jgenesis_state::bincode::Decode>::decode(decoder)
+            }
+        }
+    };
+
+    gen.into()
+}
+
+#[derive(Default)]
+struct SaveStateAttr {
+    skip: bool,
+    big_array: bool,
+    since: Option<LitInt>,
+    default: Option<ExprPath>,
+}
+
+fn parse_save_state_attr(field: &Field) -> SaveStateAttr {
+    let mut attr = SaveStateAttr::default();
+
+    for field_attr in field.attrs.iter().filter(|attr| attr.path().is_ident("save_state")) {
+        field_attr
+            .parse_nested_meta(|meta| {
+                if meta.path.is_ident("skip") {
+                    attr.skip = true;
+                    Ok(())
+                } else if meta.path.is_ident("big_array") {
+                    attr.big_array = true;
+                    Ok(())
+                } else if meta.path.is_ident("since") {
+                    attr.since = Some(meta.value()?.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("default") {
+                    let path: LitStr = meta.value()?.parse()?;
+                    match path.parse()? {
+                        Expr::Path(path) => {
+                            attr.default = Some(path);
+                            Ok(())
+                        }
+                        _ => Err(meta.error("save_state default must be a function path")),
+                    }
+                } else {
+                    Err(meta.error(
+                        "nested save_state attribute must be 'skip', 'big_array', 'since', or 'default'",
+                    ))
+                }
+            })
+            .expect("Unable to parse save_state attribute");
+    }
+
+    assert!(
+        !attr.skip || !(attr.big_array || attr.since.is_some()),
+        "save_state 'skip' cannot be combined with 'big_array' or 'since'"
+    );
+
+    attr
+}
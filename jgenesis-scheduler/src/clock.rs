@@ -0,0 +1,135 @@
+use bincode::{Decode, Encode};
+
+/// Clock for a component that runs at a fixed ratio of the master clock.
+///
+/// The ratio is `numerator / denominator` component cycles per master clock cycle, e.g. 1/15 for a
+/// component with a master clock divider of 15. Elapsed time that does not add up to a full
+/// component cycle is carried over to the next call rather than rounded away.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct ClockRatio {
+    numerator: u64,
+    denominator: u64,
+    // Elapsed master clock cycles multiplied by the numerator that have not yet been converted
+    // into component cycles; always less than the denominator after a catch-up
+    remainder: u64,
+}
+
+impl ClockRatio {
+    /// # Panics
+    ///
+    /// Panics if `denominator` is 0.
+    #[must_use]
+    pub fn new(numerator: u64, denominator: u64) -> Self {
+        assert_ne!(denominator, 0, "clock ratio denominator must be non-zero");
+
+        Self { numerator, denominator, remainder: 0 }
+    }
+
+    /// Create a clock for a component that runs once every `divider` master clock cycles.
+    #[must_use]
+    pub fn divider(divider: u64) -> Self {
+        Self::new(1, divider)
+    }
+
+    /// Change the ratio's numerator, e.g. to speed up or slow down a component relative to the
+    /// master clock. Time that has already elapsed is not affected.
+    pub fn set_numerator(&mut self, numerator: u64) {
+        self.numerator = numerator;
+    }
+
+    /// Add elapsed master clock cycles without running any component cycles.
+    pub fn advance(&mut self, master_cycles: u64) {
+        self.remainder += master_cycles * self.numerator;
+    }
+
+    /// Consume a single component cycle if enough time has elapsed, returning whether one was
+    /// consumed. Useful for catching up a component that may need to stop partway through.
+    #[must_use]
+    pub fn next_cycle(&mut self) -> bool {
+        if self.remainder < self.denominator {
+            return false;
+        }
+
+        self.remainder -= self.denominator;
+        true
+    }
+
+    /// Consume and return every full component cycle that has elapsed.
+    #[must_use]
+    pub fn take_cycles(&mut self) -> u64 {
+        let cycles = self.remainder / self.denominator;
+        self.remainder %= self.denominator;
+        cycles
+    }
+
+    /// Add elapsed master clock cycles and return the number of component cycles to run.
+    #[must_use]
+    pub fn tick(&mut self, master_cycles: u64) -> u64 {
+        self.advance(master_cycles);
+        self.take_cycles()
+    }
+
+    /// Discard up to `master_cycles` of elapsed time that has not yet been converted into
+    /// component cycles, e.g. while the component is stalled waiting on a bus.
+    ///
+    /// Returns how many master clock cycles of the stall are left over because not enough time had
+    /// elapsed to cover it.
+    #[must_use]
+    pub fn stall(&mut self, master_cycles: u64) -> u64 {
+        if self.numerator == 0 {
+            return master_cycles;
+        }
+
+        let stalled = self.remainder.min(master_cycles * self.numerator);
+        self.remainder -= stalled;
+        master_cycles - stalled.div_ceil(self.numerator)
+    }
+
+    /// Discard any partial component cycle.
+    pub fn reset(&mut self) {
+        self.remainder = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn divider_carries_remainder() {
+        let mut clock = ClockRatio::divider(15);
+
+        assert_eq!(clock.tick(7), 0);
+        assert_eq!(clock.tick(7), 0);
+        assert_eq!(clock.tick(7), 1);
+        assert_eq!(clock.tick(30), 2);
+        assert_eq!(clock.tick(9), 1);
+    }
+
+    #[test]
+    fn fractional_ratio() {
+        // 3 component cycles for every 2 master cycles
+        let mut clock = ClockRatio::new(3, 2);
+
+        let cycles: u64 = (0..100).map(|_| clock.tick(1)).sum();
+        assert_eq!(cycles, 150);
+
+        clock.advance(1);
+        assert!(clock.next_cycle());
+        assert!(!clock.next_cycle());
+        clock.advance(1);
+        assert!(clock.next_cycle());
+        assert!(clock.next_cycle());
+        assert!(!clock.next_cycle());
+    }
+
+    #[test]
+    fn stall() {
+        let mut clock = ClockRatio::divider(15);
+
+        clock.advance(10);
+        assert_eq!(clock.stall(4), 0);
+        assert_eq!(clock.stall(20), 14);
+        assert_eq!(clock.tick(15), 1);
+    }
+}
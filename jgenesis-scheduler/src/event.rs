@@ -0,0 +1,127 @@
+use bincode::{BorrowDecode, Decode, Encode};
+
+#[derive(Debug, Clone, Encode, Decode)]
+struct ScheduledEvent<Event> {
+    timestamp: u64,
+    event: Event,
+}
+
+/// Queue of events that are due at master clock timestamps.
+///
+/// Events that are due at the same timestamp are returned in the order they were scheduled. Cores
+/// only ever have a handful of pending events, so they are kept in a sorted list rather than a heap.
+#[derive(Debug, Clone, Encode, Decode)]
+#[bincode(
+    encode_bounds = "Event: Encode + 'static",
+    decode_bounds = "Event: Decode + 'static",
+    borrow_decode_bounds = "Event: BorrowDecode<'__de> + 'static"
+)]
+pub struct EventQueue<Event> {
+    // Sorted from latest to earliest so that the next event can be popped off the end
+    events: Vec<ScheduledEvent<Event>>,
+}
+
+impl<Event> EventQueue<Event> {
+    #[must_use]
+    pub fn new() -> Self {
+        Self { events: Vec::new() }
+    }
+
+    /// Schedule an event to occur at the given master clock timestamp.
+    pub fn schedule(&mut self, timestamp: u64, event: Event) {
+        let idx = self.events.partition_point(|scheduled| scheduled.timestamp > timestamp);
+        self.events.insert(idx, ScheduledEvent { timestamp, event });
+    }
+
+    /// Timestamp of the earliest pending event, if any. Cores can use this to limit how far
+    /// components are run ahead before the event needs to be processed.
+    #[must_use]
+    pub fn next_timestamp(&self) -> Option<u64> {
+        self.events.last().map(|scheduled| scheduled.timestamp)
+    }
+
+    /// Remove and return the earliest pending event along with its timestamp if it is due at or
+    /// before `now`.
+    pub fn pop_due(&mut self, now: u64) -> Option<(u64, Event)> {
+        if self.next_timestamp()? > now {
+            return None;
+        }
+
+        self.events.pop().map(|ScheduledEvent { timestamp, event }| (timestamp, event))
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.events.clear();
+    }
+}
+
+impl<Event: PartialEq> EventQueue<Event> {
+    /// Remove every pending occurrence of the given event.
+    pub fn cancel(&mut self, event: &Event) {
+        self.events.retain(|scheduled| &scheduled.event != event);
+    }
+
+    #[must_use]
+    pub fn is_scheduled(&self, event: &Event) -> bool {
+        self.events.iter().any(|scheduled| &scheduled.event == event)
+    }
+}
+
+impl<Event> Default for EventQueue<Event> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum TestEvent {
+        A,
+        B,
+        C,
+    }
+
+    #[test]
+    fn events_pop_in_timestamp_order() {
+        let mut queue = EventQueue::new();
+        queue.schedule(100, TestEvent::A);
+        queue.schedule(50, TestEvent::B);
+        queue.schedule(100, TestEvent::C);
+
+        assert_eq!(queue.next_timestamp(), Some(50));
+        assert_eq!(queue.pop_due(49), None);
+        assert_eq!(queue.pop_due(75), Some((50, TestEvent::B)));
+        assert_eq!(queue.pop_due(75), None);
+
+        // Events due at the same time come out in the order they were scheduled
+        assert_eq!(queue.pop_due(200), Some((100, TestEvent::A)));
+        assert_eq!(queue.pop_due(200), Some((100, TestEvent::C)));
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn cancel() {
+        let mut queue = EventQueue::new();
+        queue.schedule(10, TestEvent::A);
+        queue.schedule(20, TestEvent::B);
+        queue.schedule(30, TestEvent::A);
+
+        queue.cancel(&TestEvent::A);
+        assert!(!queue.is_scheduled(&TestEvent::A));
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue.pop_due(u64::MAX), Some((20, TestEvent::B)));
+    }
+}
@@ -0,0 +1,13 @@
+//! Timing primitives shared by the emulation cores
+//!
+//! Cores are driven by a master clock: the CPU executes an instruction or DMA step, and every other
+//! component is then caught up by the number of master clock cycles that elapsed. [`ClockRatio`]
+//! converts elapsed master clock cycles into cycles of a component that runs at a fixed ratio of
+//! the master clock, and [`EventQueue`] holds events that are due at specific master clock
+//! timestamps.
+
+mod clock;
+mod event;
+
+pub use clock::ClockRatio;
+pub use event::EventQueue;
@@ -0,0 +1,56 @@
+//! Decoding for large boxed arrays
+//!
+//! bincode decodes `Box<[T; N]>` by decoding a `[T; N]` on the stack and then moving it to the heap,
+//! which can overflow the stack for arrays the size of a console's RAM. The encoding of an array is
+//! the same as a slice without a length prefix, so it can instead be decoded through a `Vec`.
+
+use bincode::de::Decoder;
+use bincode::error::DecodeError;
+use bincode::Decode;
+use std::mem;
+
+/// Decode a `Box<[T; N]>` without constructing the array on the stack.
+///
+/// # Errors
+///
+/// This function will return an error if decoding any element fails or if the array would exceed the
+/// decoder's size limit.
+pub fn decode<T: Decode, D: Decoder, const N: usize>(
+    decoder: &mut D,
+) -> Result<Box<[T; N]>, DecodeError> {
+    decoder.claim_container_read::<T>(N)?;
+
+    let mut values = Vec::with_capacity(N);
+    for _ in 0..N {
+        // Matches bincode's Vec implementation; each element claims its own size while decoding
+        decoder.unclaim_bytes_read(mem::size_of::<T>());
+        values.push(T::decode(decoder)?);
+    }
+
+    Ok(values.into_boxed_slice().try_into().unwrap_or_else(|_| unreachable!("Vec has length N")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bincode::config;
+
+    #[test]
+    fn same_encoding_as_array() {
+        let array: Box<[u16; 100_000]> = (0..=u16::MAX)
+            .cycle()
+            .take(100_000)
+            .collect::<Vec<_>>()
+            .into_boxed_slice()
+            .try_into()
+            .unwrap();
+        let bytes = bincode::encode_to_vec(&array, config::standard()).unwrap();
+
+        let mut decoder = bincode::de::DecoderImpl::new(
+            bincode::de::read::SliceReader::new(&bytes),
+            config::standard(),
+        );
+        let decoded: Box<[u16; 100_000]> = decode(&mut decoder).unwrap();
+        assert_eq!(decoded, array);
+    }
+}
@@ -0,0 +1,233 @@
+//! Save state serialization shared by the emulation cores
+//!
+//! Core state structs implement the bincode traits through [`SaveState`] (or the regular bincode
+//! derives when no customization is needed), and [`encode_state`] / [`decode_state`] wrap the
+//! encoded state in a container that identifies the core and the version of its state format.
+
+pub mod big_array;
+
+use bincode::config::{Configuration, Fixint, Limit, LittleEndian};
+use bincode::error::{DecodeError, EncodeError};
+use bincode::{Decode, Encode};
+use std::cell::Cell;
+use thiserror::Error;
+
+pub use bincode;
+pub use jgenesis_proc_macros::SaveState;
+
+// Allow the derive's generated `::jgenesis_state` paths to resolve inside this crate's tests
+#[cfg(test)]
+extern crate self as jgenesis_state;
+
+const MAGIC: &[u8; 8] = b"JGSTATE\0";
+
+/// Maximum size of an encoded state. Decoding fails rather than allocating more than this.
+pub const MAX_STATE_LEN: usize = 100 * 1024 * 1024;
+
+/// The bincode configuration used for save states, and by frontends for other emulator data that
+/// they persist (save files, crash dumps, etc.).
+#[must_use]
+pub fn bincode_config() -> Configuration<LittleEndian, Fixint, Limit<MAX_STATE_LEN>> {
+    bincode::config::standard()
+        .with_little_endian()
+        .with_fixed_int_encoding()
+        .with_limit::<MAX_STATE_LEN>()
+}
+
+/// Identifies the state format of a core.
+///
+/// `version` should be incremented whenever a field is added to the core's state, and the new field
+/// should be marked with `#[save_state(since = <new version>)]` so that older states still load.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StateFormat {
+    pub core: &'static str,
+    pub version: u32,
+}
+
+#[derive(Debug, Error)]
+pub enum StateError {
+    #[error("Data is not a save state")]
+    NotAState,
+    #[error("Save state is for core '{actual}', expected '{expected}'")]
+    WrongCore { expected: &'static str, actual: String },
+    #[error("Save state version {actual} is newer than the latest supported version {supported}")]
+    UnsupportedVersion { supported: u32, actual: u32 },
+    #[error("Error encoding save state: {0}")]
+    Encode(#[from] EncodeError),
+    #[error("Error decoding save state: {0}")]
+    Decode(#[from] DecodeError),
+}
+
+thread_local! {
+    // Version of the state currently being decoded by decode_state(), if any
+    static DECODING_VERSION: Cell<Option<u32>> = const { Cell::new(None) };
+}
+
+struct DecodingVersionGuard {
+    previous: Option<u32>,
+}
+
+impl DecodingVersionGuard {
+    fn new(version: u32) -> Self {
+        let previous = DECODING_VERSION.replace(Some(version));
+        Self { previous }
+    }
+}
+
+impl Drop for DecodingVersionGuard {
+    fn drop(&mut self) {
+        DECODING_VERSION.set(self.previous);
+    }
+}
+
+/// Returns whether a field that was added in version `since` is present in the state currently
+/// being decoded. Used by code generated by [`SaveState`].
+///
+/// Always returns true when decoding outside of [`decode_state`], e.g. for in-memory rewind
+/// snapshots, since those are always encoded by the current version.
+#[must_use]
+pub fn field_present(since: u32) -> bool {
+    DECODING_VERSION.get().is_none_or(|version| version >= since)
+}
+
+/// Encode a core's state into a versioned state container.
+///
+/// # Errors
+///
+/// This function will return an error if encoding fails, which should only happen if the state
+/// exceeds the maximum state size (100MB).
+pub fn encode_state<T: Encode>(value: &T, format: StateFormat) -> Result<Vec<u8>, StateError> {
+    let mut bytes = MAGIC.to_vec();
+    bincode::encode_into_std_write((format.core, format.version), &mut bytes, bincode_config())?;
+    bincode::encode_into_std_write(value, &mut bytes, bincode_config())?;
+
+    Ok(bytes)
+}
+
+/// Decode a core's state from a container previously returned by [`encode_state`].
+///
+/// States from older versions of the same core are accepted; fields marked with
+/// `#[save_state(since = N)]` that are not present in the state are set to their defaults.
+///
+/// # Errors
+///
+/// This function will return an error if the data is not a save state, if the state was saved by a
+/// different core or a newer version of the same core, or if decoding fails.
+pub fn decode_state<T: Decode>(bytes: &[u8], format: StateFormat) -> Result<T, StateError> {
+    let bytes = bytes.strip_prefix(MAGIC).ok_or(StateError::NotAState)?;
+
+    let ((core, version), header_len): ((String, u32), _) =
+        bincode::decode_from_slice(bytes, bincode_config())?;
+    if core != format.core {
+        return Err(StateError::WrongCore { expected: format.core, actual: core });
+    }
+    if version > format.version {
+        return Err(StateError::UnsupportedVersion { supported: format.version, actual: version });
+    }
+
+    let _guard = DecodingVersionGuard::new(version);
+    let (value, _) = bincode::decode_from_slice(&bytes[header_len..], bincode_config())?;
+
+    Ok(value)
+}
+
+/// Like [`decode_state`], but also accepts states saved before states were versioned, which contain
+/// only the bincode-encoded state without a container.
+///
+/// # Errors
+///
+/// This function will return an error if the state was saved by a different core or a newer
+/// version of the same core, or if decoding fails.
+pub fn decode_state_or_legacy<T: Decode>(
+    bytes: &[u8],
+    format: StateFormat,
+) -> Result<T, StateError> {
+    match decode_state(bytes, format) {
+        Err(StateError::NotAState) => {
+            let (value, _) = bincode::decode_from_slice(bytes, bincode_config())?;
+            Ok(value)
+        }
+        result => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq, SaveState)]
+    struct V1 {
+        a: u32,
+        #[save_state(skip)]
+        rom: Vec<u8>,
+        b: u16,
+    }
+
+    #[derive(Debug, PartialEq, SaveState)]
+    struct V2 {
+        a: u32,
+        #[save_state(skip)]
+        rom: Vec<u8>,
+        b: u16,
+        #[save_state(since = 2, default = "default_c")]
+        c: u8,
+    }
+
+    fn default_c() -> u8 {
+        7
+    }
+
+    const V1_FORMAT: StateFormat = StateFormat { core: "test", version: 1 };
+    const V2_FORMAT: StateFormat = StateFormat { core: "test", version: 2 };
+
+    #[test]
+    fn skipped_fields_not_encoded() {
+        let state = encode_state(&V1 { a: 1, rom: vec![1, 2, 3], b: 2 }, V1_FORMAT).unwrap();
+        let decoded: V1 = decode_state(&state, V1_FORMAT).unwrap();
+        assert_eq!(decoded, V1 { a: 1, rom: vec![], b: 2 });
+    }
+
+    #[test]
+    fn older_version_uses_defaults() {
+        let state = encode_state(&V1 { a: 1, rom: vec![], b: 2 }, V1_FORMAT).unwrap();
+        let decoded: V2 = decode_state(&state, V2_FORMAT).unwrap();
+        assert_eq!(decoded, V2 { a: 1, rom: vec![], b: 2, c: 7 });
+
+        let state = encode_state(&V2 { a: 1, rom: vec![], b: 2, c: 3 }, V2_FORMAT).unwrap();
+        let decoded: V2 = decode_state(&state, V2_FORMAT).unwrap();
+        assert_eq!(decoded, V2 { a: 1, rom: vec![], b: 2, c: 3 });
+    }
+
+    #[test]
+    fn rejects_mismatched_states() {
+        let state = encode_state(&V2 { a: 1, rom: vec![], b: 2, c: 3 }, V2_FORMAT).unwrap();
+
+        assert!(matches!(
+            decode_state::<V1>(&state, V1_FORMAT),
+            Err(StateError::UnsupportedVersion { supported: 1, actual: 2 })
+        ));
+        assert!(matches!(
+            decode_state::<V2>(&state, StateFormat { core: "other", version: 2 }),
+            Err(StateError::WrongCore { .. })
+        ));
+        assert!(matches!(decode_state::<V2>(&state[1..], V2_FORMAT), Err(StateError::NotAState)));
+    }
+
+    #[test]
+    fn legacy_states_without_container() {
+        let value = V2 { a: 1, rom: vec![], b: 2, c: 3 };
+        let legacy_state = bincode::encode_to_vec(&value, bincode_config()).unwrap();
+
+        assert!(matches!(decode_state::<V2>(&legacy_state, V2_FORMAT), Err(StateError::NotAState)));
+        let decoded: V2 = decode_state_or_legacy(&legacy_state, V2_FORMAT).unwrap();
+        assert_eq!(decoded, value);
+
+        // Versioned states still go through the container checks
+        let state = encode_state(&value, V2_FORMAT).unwrap();
+        assert_eq!(decode_state_or_legacy::<V2>(&state, V2_FORMAT).unwrap(), value);
+        assert!(matches!(
+            decode_state_or_legacy::<V1>(&state, V1_FORMAT),
+            Err(StateError::UnsupportedVersion { supported: 1, actual: 2 })
+        ));
+    }
+}